@@ -60,6 +60,11 @@ impl<T: Clone + Serialize + for<'de> Deserialize<'de>> DiskCache<T> {
         })
     }
 
+    /// Get the total size of cached files in bytes
+    pub fn total_bytes(&self) -> usize {
+        self.index.read().total_size_bytes
+    }
+
     /// Load existing index or create new one
     fn load_or_create_index(config: &DiskConfig) -> CacheResult<DiskCacheIndex> {
         let index_path = config.cache_dir.join("cache_index.bin");
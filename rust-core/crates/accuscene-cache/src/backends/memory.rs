@@ -1,6 +1,6 @@
 //! In-memory LRU cache backend
 
-use crate::backends::CacheBackend;
+use crate::backends::{CacheBackend, Weigher};
 use crate::config::CacheConfig;
 use crate::error::{CacheError, CacheResult};
 use crate::key::CacheKey;
@@ -11,10 +11,18 @@ use std::sync::Arc;
 use tracing::{debug, trace};
 
 /// In-memory LRU cache backend
-#[derive(Debug, Clone)]
+///
+/// Besides the entry limit, the cache can be bounded by total weight, which
+/// requires a weigher. The weigher sets each entry's `size_bytes` metadata on
+/// insert. Least recently used entries are evicted until a new entry fits, and
+/// inserting an entry heavier than the whole cache fails with
+/// [`CacheError::CacheFull`], leaving the cache unchanged.
+#[derive(Clone)]
 pub struct MemoryCache<T: Clone> {
     inner: Arc<RwLock<MemoryCacheInner<T>>>,
     max_entries: usize,
+    max_weight: Option<usize>,
+    weigher: Option<Weigher<T>>,
 }
 
 #[derive(Debug)]
@@ -27,65 +35,113 @@ struct MemoryCacheInner<T: Clone> {
     access_order: HashMap<String, usize>,
     /// Current access generation
     generation: usize,
+    /// Total weight of stored entries
+    total_weight: usize,
+}
+
+impl<T: Clone> MemoryCacheInner<T> {
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            data: HashMap::with_capacity(capacity),
+            lru_queue: VecDeque::with_capacity(capacity),
+            access_order: HashMap::with_capacity(capacity),
+            generation: 0,
+            total_weight: 0,
+        }
+    }
+
+    /// Remove an entry along with its LRU tracking
+    fn remove_entry(&mut self, key: &str) -> Option<CacheValue<T>> {
+        let value = self.data.remove(key)?;
+        if let Some(pos) = self.lru_queue.iter().position(|k| k == key) {
+            self.lru_queue.remove(pos);
+        }
+        self.access_order.remove(key);
+        self.total_weight = self.total_weight.saturating_sub(value.size_estimate());
+        Some(value)
+    }
+
+    /// Evict least recently used entry
+    fn evict_lru(&mut self) -> CacheResult<()> {
+        match self.lru_queue.front().cloned() {
+            Some(key) => {
+                self.remove_entry(&key);
+                debug!("Evicted LRU entry: {}", key);
+                Ok(())
+            }
+            None => Err(CacheError::Unknown("No entries to evict".to_string())),
+        }
+    }
+
+    /// Update LRU tracking
+    fn update_lru(&mut self, key: &str) {
+        // Remove from current position
+        if let Some(pos) = self.lru_queue.iter().position(|k| k == key) {
+            self.lru_queue.remove(pos);
+        }
+
+        // Add to back (most recent)
+        self.lru_queue.push_back(key.to_string());
+
+        // Update access generation
+        self.generation += 1;
+        let gen = self.generation;
+        self.access_order.insert(key.to_string(), gen);
+    }
 }
 
 impl<T: Clone> MemoryCache<T> {
     /// Create a new memory cache
     pub fn new(config: &CacheConfig) -> Self {
         Self {
-            inner: Arc::new(RwLock::new(MemoryCacheInner {
-                data: HashMap::with_capacity(config.max_entries),
-                lru_queue: VecDeque::with_capacity(config.max_entries),
-                access_order: HashMap::with_capacity(config.max_entries),
-                generation: 0,
-            })),
+            inner: Arc::new(RwLock::new(MemoryCacheInner::with_capacity(config.max_entries))),
             max_entries: config.max_entries,
+            max_weight: config.max_weight,
+            weigher: None,
         }
     }
 
     /// Create with specific capacity
     pub fn with_capacity(capacity: usize) -> Self {
         Self {
-            inner: Arc::new(RwLock::new(MemoryCacheInner {
-                data: HashMap::with_capacity(capacity),
-                lru_queue: VecDeque::with_capacity(capacity),
-                access_order: HashMap::with_capacity(capacity),
-                generation: 0,
-            })),
+            inner: Arc::new(RwLock::new(MemoryCacheInner::with_capacity(capacity))),
             max_entries: capacity,
+            max_weight: None,
+            weigher: None,
         }
     }
 
-    /// Evict least recently used entry
-    fn evict_lru(&self) -> CacheResult<()> {
-        let mut inner = self.inner.write();
-
-        if let Some(key) = inner.lru_queue.pop_front() {
-            inner.data.remove(&key);
-            inner.access_order.remove(&key);
-            debug!("Evicted LRU entry: {}", key);
-            Ok(())
-        } else {
-            Err(CacheError::Unknown("No entries to evict".to_string()))
+    /// Bound the total weight of entries in addition to their number
+    pub fn with_max_weight(
+        self,
+        max_weight: usize,
+        weigher: impl Fn(&CacheKey, &T) -> usize + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            max_weight: Some(max_weight),
+            ..self.with_weigher(weigher)
         }
     }
 
-    /// Update LRU tracking
-    fn update_lru(&self, key: &str) {
-        let mut inner = self.inner.write();
-
-        // Remove from current position
-        if let Some(pos) = inner.lru_queue.iter().position(|k| k == key) {
-            inner.lru_queue.remove(pos);
-        }
+    /// Weigh entries on insert, typically by their approximate size in bytes
+    ///
+    /// Required when the cache is created from a config with `max_weight` set.
+    pub fn with_weigher(
+        mut self,
+        weigher: impl Fn(&CacheKey, &T) -> usize + Send + Sync + 'static,
+    ) -> Self {
+        self.weigher = Some(Arc::new(weigher));
+        self
+    }
 
-        // Add to back (most recent)
-        inner.lru_queue.push_back(key.to_string());
+    /// Get the total weight of stored entries
+    pub fn total_weight(&self) -> usize {
+        self.inner.read().total_weight
+    }
 
-        // Update access generation
-        inner.generation += 1;
-        let gen = inner.generation;
-        inner.access_order.insert(key.to_string(), gen);
+    /// Get the weight capacity, if bounded
+    pub fn max_weight(&self) -> Option<usize> {
+        self.max_weight
     }
 }
 
@@ -95,60 +151,75 @@ impl<T: Clone + Send + Sync + std::fmt::Debug> CacheBackend for MemoryCache<T> {
     fn get(&self, key: &CacheKey) -> CacheResult<Option<CacheValue<Self::Value>>> {
         let key_str = key.as_string();
 
-        let result = {
-            let inner = self.inner.read();
-            inner.data.get(&key_str).cloned()
-        };
+        let mut inner = self.inner.write();
 
-        if let Some(mut value) = result {
-            // Check expiration
-            if value.is_expired() {
-                trace!("Cache entry expired: {}", key_str);
-                let mut inner = self.inner.write();
-                inner.data.remove(&key_str);
-                if let Some(pos) = inner.lru_queue.iter().position(|k| k == &key_str) {
-                    inner.lru_queue.remove(pos);
-                }
-                inner.access_order.remove(&key_str);
+        let expired = match inner.data.get(&key_str) {
+            Some(value) => value.is_expired(),
+            None => {
+                trace!("Cache miss: {}", key_str);
                 return Ok(None);
             }
+        };
+
+        // Check expiration
+        if expired {
+            trace!("Cache entry expired: {}", key_str);
+            inner.remove_entry(&key_str);
+            return Ok(None);
+        }
 
-            // Update access tracking
+        // Update access tracking on the stored value
+        let value = inner.data.get_mut(&key_str).map(|value| {
             value.record_access();
-            self.update_lru(&key_str);
+            value.clone()
+        });
+        inner.update_lru(&key_str);
 
-            // Update stored value with new access info
-            let mut inner = self.inner.write();
-            inner.data.insert(key_str.clone(), value.clone());
+        trace!("Cache hit: {}", key_str);
+        Ok(value)
+    }
 
-            trace!("Cache hit: {}", key_str);
-            Ok(Some(value))
-        } else {
-            trace!("Cache miss: {}", key_str);
-            Ok(None)
+    fn insert(&self, key: CacheKey, mut value: CacheValue<Self::Value>) -> CacheResult<()> {
+        if let Some(ref weigher) = self.weigher {
+            value.metadata.size_bytes = weigher(&key, &value.data);
+        } else if self.max_weight.is_some() {
+            return Err(CacheError::InvalidConfig(
+                "A weigher is required when max_weight is set".to_string(),
+            ));
         }
-    }
 
-    fn insert(&self, key: CacheKey, value: CacheValue<Self::Value>) -> CacheResult<()> {
         let key_str = key.as_string();
+        let weight = value.size_estimate();
+
+        // Storing an entry heavier than the cache would only flush everything else
+        if let Some(max_weight) = self.max_weight.filter(|max_weight| weight > *max_weight) {
+            debug!(
+                "Not caching {}: weight {} exceeds capacity {}",
+                key_str, weight, max_weight
+            );
+            return Err(CacheError::CacheFull);
+        }
+
+        let mut inner = self.inner.write();
+
+        // The previous value for this key no longer counts against capacity
+        inner.remove_entry(&key_str);
 
-        // Evict if at capacity and key doesn't exist
+        // Evict until the entry fits
+        while inner.data.len() >= self.max_entries
+            || self
+                .max_weight
+                .is_some_and(|max_weight| inner.total_weight + weight > max_weight)
         {
-            let inner = self.inner.read();
-            if inner.data.len() >= self.max_entries && !inner.data.contains_key(&key_str) {
-                drop(inner);
-                self.evict_lru()?;
-            }
+            inner.evict_lru()?;
         }
 
         // Insert value
-        {
-            let mut inner = self.inner.write();
-            inner.data.insert(key_str.clone(), value);
-        }
+        inner.total_weight += weight;
+        inner.data.insert(key_str.clone(), value);
 
         // Update LRU
-        self.update_lru(&key_str);
+        inner.update_lru(&key_str);
 
         debug!("Inserted cache entry: {}", key_str);
         Ok(())
@@ -158,13 +229,9 @@ impl<T: Clone + Send + Sync + std::fmt::Debug> CacheBackend for MemoryCache<T> {
         let key_str = key.as_string();
         let mut inner = self.inner.write();
 
-        let value = inner.data.remove(&key_str);
+        let value = inner.remove_entry(&key_str);
 
         if value.is_some() {
-            if let Some(pos) = inner.lru_queue.iter().position(|k| k == &key_str) {
-                inner.lru_queue.remove(pos);
-            }
-            inner.access_order.remove(&key_str);
             debug!("Removed cache entry: {}", key_str);
         }
 
@@ -182,6 +249,7 @@ impl<T: Clone + Send + Sync + std::fmt::Debug> CacheBackend for MemoryCache<T> {
         inner.lru_queue.clear();
         inner.access_order.clear();
         inner.generation = 0;
+        inner.total_weight = 0;
         debug!("Cleared all cache entries");
         Ok(())
     }
@@ -212,11 +280,7 @@ impl<T: Clone + Send + Sync + std::fmt::Debug> CacheBackend for MemoryCache<T> {
 
         let count = expired_keys.len();
         for key in expired_keys {
-            inner.data.remove(&key);
-            if let Some(pos) = inner.lru_queue.iter().position(|k| k == &key) {
-                inner.lru_queue.remove(pos);
-            }
-            inner.access_order.remove(&key);
+            inner.remove_entry(&key);
         }
 
         if count > 0 {
@@ -231,6 +295,17 @@ impl<T: Clone + Send + Sync + std::fmt::Debug> CacheBackend for MemoryCache<T> {
     }
 }
 
+impl<T: Clone + std::fmt::Debug> std::fmt::Debug for MemoryCache<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoryCache")
+            .field("inner", &self.inner)
+            .field("max_entries", &self.max_entries)
+            .field("max_weight", &self.max_weight)
+            .field("weigher", &self.weigher.as_ref().map(|_| "<function>"))
+            .finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -273,4 +348,53 @@ mod tests {
         cache.clear().unwrap();
         assert_eq!(cache.len(), 0);
     }
+
+    #[test]
+    fn test_memory_cache_weight_eviction() {
+        let cache =
+            MemoryCache::with_capacity(10).with_max_weight(100, |_key, value: &String| value.len());
+
+        cache.insert(CacheKey::new("test", "key1"), CacheValue::new("a".repeat(40))).unwrap();
+        cache.insert(CacheKey::new("test", "key2"), CacheValue::new("b".repeat(40))).unwrap();
+        assert_eq!(cache.total_weight(), 80);
+
+        // A 50 byte entry evicts the least recently used entry only
+        cache.insert(CacheKey::new("test", "key3"), CacheValue::new("c".repeat(50))).unwrap();
+        assert!(!cache.contains_key(&CacheKey::new("test", "key1")));
+        assert!(cache.contains_key(&CacheKey::new("test", "key2")));
+        assert_eq!(cache.total_weight(), 90);
+
+        // Replacing an entry releases its previous weight
+        cache.insert(CacheKey::new("test", "key2"), CacheValue::new("b".repeat(10))).unwrap();
+        assert_eq!(cache.total_weight(), 60);
+        assert_eq!(cache.len(), 2);
+
+        // Entries heavier than the cache are rejected and keep the previous value
+        let key2 = CacheKey::new("test", "key2");
+        let result = cache.insert(key2.clone(), CacheValue::new("d".repeat(200)));
+        assert!(matches!(result, Err(CacheError::CacheFull)));
+        assert_eq!(cache.get(&key2).unwrap().unwrap().data, "b".repeat(10));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.total_weight(), 60);
+
+        cache.remove(&CacheKey::new("test", "key3")).unwrap();
+        assert_eq!(cache.total_weight(), 10);
+        cache.clear().unwrap();
+        assert_eq!(cache.total_weight(), 0);
+    }
+
+    #[test]
+    fn test_memory_cache_max_weight_requires_weigher() {
+        let config = CacheConfig {
+            max_weight: Some(100),
+            ..CacheConfig::default()
+        };
+        let cache = MemoryCache::new(&config);
+        let result = cache.insert(CacheKey::new("test", "key1"), CacheValue::new("data1"));
+        assert!(matches!(result, Err(CacheError::InvalidConfig(_))));
+
+        let cache = cache.with_weigher(|_key, value: &&str| value.len());
+        cache.insert(CacheKey::new("test", "key1"), CacheValue::new("data1")).unwrap();
+        assert_eq!(cache.total_weight(), 5);
+    }
 }
@@ -4,15 +4,27 @@ pub mod memory;
 pub mod moka;
 pub mod disk;
 pub mod tiered;
+#[cfg(feature = "redis")]
+pub mod redis;
 
 use crate::error::CacheResult;
 use crate::key::CacheKey;
 use crate::value::CacheValue;
+use serde::Serialize;
 use std::fmt::Debug;
+use std::sync::Arc;
+
+/// Computes the weight of a cache entry, typically its approximate size in bytes
+pub type Weigher<T> = Arc<dyn Fn(&CacheKey, &T) -> usize + Send + Sync>;
+
+/// Weigher using the bincode-encoded size of the value
+pub fn serialized_size<T: Serialize>(_key: &CacheKey, value: &T) -> usize {
+    bincode::serialized_size(value).map_or(0, |size| size as usize)
+}
 
 /// Trait for cache backends
 pub trait CacheBackend: Send + Sync + Debug {
-    type Value: Clone + Debug;
+    type Value: Clone;
 
     /// Get a value from the cache
     fn get(&self, key: &CacheKey) -> CacheResult<Option<CacheValue<Self::Value>>>;
@@ -10,15 +10,23 @@ use std::sync::Arc;
 use tracing::{debug, trace};
 
 /// Moka-based concurrent cache backend
+///
+/// When bounded by weight, entries weigh their `size_bytes` metadata and the
+/// number of entries is not limited.
 #[derive(Debug, Clone)]
 pub struct MokaCacheBackend<T: Clone + Send + Sync + 'static> {
     cache: Arc<MokaCache<String, CacheValue<T>>>,
     max_entries: usize,
+    max_weight: Option<usize>,
 }
 
 impl<T: Clone + Send + Sync + 'static> MokaCacheBackend<T> {
     /// Create a new Moka cache backend
     pub fn new(config: &CacheConfig) -> Self {
+        if let Some(max_weight) = config.max_weight {
+            return Self::with_max_weight(max_weight);
+        }
+
         let cache = MokaCache::builder()
             .max_capacity(config.max_entries as u64)
             .build();
@@ -26,6 +34,23 @@ impl<T: Clone + Send + Sync + 'static> MokaCacheBackend<T> {
         Self {
             cache: Arc::new(cache),
             max_entries: config.max_entries,
+            max_weight: None,
+        }
+    }
+
+    /// Create a cache bounded by the total weight of its entries
+    pub fn with_max_weight(max_weight: usize) -> Self {
+        let cache = MokaCache::builder()
+            .max_capacity(max_weight as u64)
+            .weigher(|_key: &String, value: &CacheValue<T>| {
+                u32::try_from(value.size_estimate()).unwrap_or(u32::MAX)
+            })
+            .build();
+
+        Self {
+            cache: Arc::new(cache),
+            max_entries: usize::MAX,
+            max_weight: Some(max_weight),
         }
     }
 
@@ -38,6 +63,7 @@ impl<T: Clone + Send + Sync + 'static> MokaCacheBackend<T> {
         Self {
             cache: Arc::new(cache),
             max_entries: capacity,
+            max_weight: None,
         }
     }
 
@@ -51,6 +77,7 @@ impl<T: Clone + Send + Sync + 'static> MokaCacheBackend<T> {
         Self {
             cache: Arc::new(cache),
             max_entries: capacity,
+            max_weight: None,
         }
     }
 
@@ -64,6 +91,7 @@ impl<T: Clone + Send + Sync + 'static> MokaCacheBackend<T> {
         Self {
             cache: Arc::new(cache),
             max_entries,
+            max_weight: None,
         }
     }
 
@@ -88,6 +116,20 @@ impl<T: Clone + Send + Sync + 'static> MokaCacheBackend<T> {
         }
     }
 
+    /// Get the total weight of stored entries
+    pub fn total_weight(&self) -> usize {
+        if self.max_weight.is_some() {
+            self.cache.run_pending_tasks();
+            return self.cache.weighted_size() as usize;
+        }
+        self.cache.iter().map(|(_, value)| value.size_estimate()).sum()
+    }
+
+    /// Get the weight capacity, if bounded
+    pub fn max_weight(&self) -> Option<usize> {
+        self.max_weight
+    }
+
     /// Run cache maintenance (eviction, cleanup)
     pub fn run_pending_tasks(&self) {
         self.cache.run_pending_tasks();
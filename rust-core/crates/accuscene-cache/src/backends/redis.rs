@@ -0,0 +1,277 @@
+//! Redis distributed cache backend
+
+use crate::backends::CacheBackend;
+use crate::config::RedisConfig;
+use crate::error::{CacheError, CacheResult};
+use crate::key::CacheKey;
+use crate::serialization::{CacheSerializer, SerializationFormat};
+use crate::value::CacheValue;
+use parking_lot::Mutex;
+use redis::Commands;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use tracing::{debug, trace, warn};
+
+/// Redis-based cache backend shared between processes
+///
+/// Each entry is the serialized `CacheValue` under `<prefix>:<key>`, with a
+/// Redis expiry matching the value's TTL. Commands share one connection that
+/// is re-established after connection errors. Removal uses `GETDEL`
+/// (Redis 6.2 or later).
+pub struct RedisCache<T, S: CacheSerializer = SerializationFormat> {
+    client: redis::Client,
+    connection: Mutex<Option<redis::Connection>>,
+    key_prefix: String,
+    serializer: S,
+    max_entries: usize,
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T> RedisCache<T, SerializationFormat>
+where
+    T: Clone + Send + Sync + Serialize + for<'de> Deserialize<'de>,
+{
+    /// Connect to Redis using the configured serialization format
+    pub fn new(config: &RedisConfig) -> CacheResult<Self> {
+        let client = redis::Client::open(config.url.as_str()).map_err(|e| {
+            CacheError::InvalidConfig(format!("Invalid Redis URL: {}", e))
+        })?;
+        let connection = client.get_connection().map_err(|e| {
+            CacheError::DistributedError(format!("Failed to connect to Redis: {}", e))
+        })?;
+
+        debug!("Connected Redis cache with prefix {}", config.key_prefix);
+
+        Ok(Self {
+            client,
+            connection: Mutex::new(Some(connection)),
+            key_prefix: config.key_prefix.clone(),
+            serializer: config.serialization,
+            max_entries: config.max_entries,
+            _phantom: std::marker::PhantomData,
+        })
+    }
+}
+
+impl<T, S> RedisCache<T, S>
+where
+    T: Clone + Send + Sync + Serialize + for<'de> Deserialize<'de>,
+    S: CacheSerializer,
+{
+    /// Use a different serializer for cached values
+    pub fn with_serializer<S2: CacheSerializer>(self, serializer: S2) -> RedisCache<T, S2> {
+        RedisCache {
+            client: self.client,
+            connection: self.connection,
+            key_prefix: self.key_prefix,
+            serializer,
+            max_entries: self.max_entries,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Redis key for a cache key
+    fn redis_key(&self, key: &CacheKey) -> String {
+        format!("{}:{}", self.key_prefix, key.as_string())
+    }
+
+    /// Cache key for a Redis key under this cache's prefix
+    fn cache_key(&self, redis_key: &str) -> Option<CacheKey> {
+        redis_key
+            .strip_prefix(self.key_prefix.as_str())
+            .and_then(|rest| rest.strip_prefix(':'))
+            .map(|key| CacheKey::from(key.to_string()))
+    }
+
+    /// Run a command, reconnecting first if the last command lost the connection
+    fn with_connection<R>(
+        &self,
+        command: impl FnOnce(&mut redis::Connection) -> redis::RedisResult<R>,
+    ) -> CacheResult<R> {
+        let mut guard = self.connection.lock();
+        let connection = match &mut *guard {
+            Some(connection) => connection,
+            slot @ None => slot.insert(self.client.get_connection().map_err(|e| {
+                CacheError::DistributedError(format!("Failed to connect to Redis: {}", e))
+            })?),
+        };
+
+        command(connection).map_err(|e| {
+            if e.is_io_error() || e.is_connection_dropped() {
+                *guard = None;
+            }
+            CacheError::DistributedError(format!("Redis command failed: {}", e))
+        })
+    }
+
+    /// All Redis keys under this cache's prefix
+    fn scan_keys(&self) -> CacheResult<Vec<String>> {
+        let pattern = format!("{}:*", self.key_prefix);
+        self.with_connection(|connection| {
+            Ok(connection.scan_match::<_, String>(&pattern)?.collect())
+        })
+    }
+
+    /// Decode a stored entry, treating expired values as missing
+    fn decode(&self, payload: Option<Vec<u8>>) -> CacheResult<Option<CacheValue<T>>> {
+        match payload {
+            Some(bytes) => {
+                let value: CacheValue<T> = self.serializer.deserialize(&bytes)?;
+                Ok(Some(value).filter(|value| !value.is_expired()))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl<T, S> CacheBackend for RedisCache<T, S>
+where
+    T: Clone + Send + Sync + Serialize + for<'de> Deserialize<'de> + fmt::Debug,
+    S: CacheSerializer,
+{
+    type Value = T;
+
+    fn get(&self, key: &CacheKey) -> CacheResult<Option<CacheValue<Self::Value>>> {
+        let redis_key = self.redis_key(key);
+        let payload: Option<Vec<u8>> =
+            self.with_connection(|connection| connection.get(&redis_key))?;
+
+        let value = self.decode(payload)?;
+        if value.is_some() {
+            trace!("Redis cache hit: {}", key);
+        } else {
+            trace!("Redis cache miss: {}", key);
+        }
+        Ok(value)
+    }
+
+    fn insert(&self, key: CacheKey, value: CacheValue<Self::Value>) -> CacheResult<()> {
+        let redis_key = self.redis_key(&key);
+
+        // Propagate the value's TTL as the Redis expiry
+        let ttl_millis = value
+            .time_until_expiry()
+            .map(|ttl| ttl.num_milliseconds());
+        if ttl_millis.is_some_and(|millis| millis <= 0) {
+            self.with_connection(|connection| connection.del::<_, ()>(&redis_key))?;
+            return Ok(());
+        }
+
+        let payload = self.serializer.serialize(&value)?;
+        let mut command = redis::cmd("SET");
+        command.arg(&redis_key).arg(payload);
+        if let Some(millis) = ttl_millis {
+            command.arg("PX").arg(millis);
+        }
+        self.with_connection(|connection| command.query::<()>(connection))?;
+
+        debug!("Inserted Redis cache entry: {}", key);
+        Ok(())
+    }
+
+    fn remove(&self, key: &CacheKey) -> CacheResult<Option<CacheValue<Self::Value>>> {
+        let redis_key = self.redis_key(key);
+        let payload: Option<Vec<u8>> = self.with_connection(|connection| {
+            redis::cmd("GETDEL").arg(&redis_key).query(connection)
+        })?;
+
+        self.decode(payload)
+    }
+
+    fn contains_key(&self, key: &CacheKey) -> bool {
+        let redis_key = self.redis_key(key);
+        self.with_connection(|connection| connection.exists(&redis_key))
+            .unwrap_or_else(|e| {
+                warn!("Failed to check Redis key {}: {}", redis_key, e);
+                false
+            })
+    }
+
+    fn clear(&self) -> CacheResult<()> {
+        let keys = self.scan_keys()?;
+        for chunk in keys.chunks(500) {
+            self.with_connection(|connection| connection.unlink::<_, ()>(chunk))?;
+        }
+
+        debug!("Cleared {} Redis cache entries", keys.len());
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.scan_keys().map(|keys| keys.len()).unwrap_or_else(|e| {
+            warn!("Failed to count Redis cache entries: {}", e);
+            0
+        })
+    }
+
+    fn keys(&self) -> Vec<CacheKey> {
+        match self.scan_keys() {
+            Ok(keys) => keys.iter().filter_map(|key| self.cache_key(key)).collect(),
+            Err(e) => {
+                warn!("Failed to list Redis cache keys: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    fn evict_expired(&self) -> CacheResult<usize> {
+        // Redis expires entries itself
+        Ok(0)
+    }
+
+    fn capacity(&self) -> usize {
+        self.max_entries
+    }
+}
+
+impl<T, S: CacheSerializer> fmt::Debug for RedisCache<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedisCache")
+            .field("key_prefix", &self.key_prefix)
+            .field("serializer", &self.serializer.name())
+            .field("max_entries", &self.max_entries)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache() -> RedisCache<String> {
+        // Clients connect lazily, so this needs no running server
+        RedisCache {
+            client: redis::Client::open("redis://127.0.0.1:6379").unwrap(),
+            connection: Mutex::new(None),
+            key_prefix: "test-prefix".to_string(),
+            serializer: SerializationFormat::Json,
+            max_entries: 100,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    #[test]
+    fn test_key_mapping() {
+        let cache = cache();
+        let key = CacheKey::with_version("physics", "simulation_1", 3);
+
+        let redis_key = cache.redis_key(&key);
+        assert_eq!(redis_key, "test-prefix:physics:simulation_1:v3");
+        assert_eq!(cache.cache_key(&redis_key), Some(key));
+        assert_eq!(cache.cache_key("other-prefix:physics:simulation_1"), None);
+    }
+
+    #[test]
+    fn test_expired_entries_decode_as_missing() {
+        let cache = cache();
+        let live = CacheValue::with_ttl("live".to_string(), chrono::Duration::seconds(60));
+        let expired = CacheValue::with_ttl("expired".to_string(), chrono::Duration::seconds(-1));
+
+        let payload = CacheSerializer::serialize(&cache.serializer, &live).unwrap();
+        assert_eq!(cache.decode(Some(payload)).unwrap().unwrap().data, "live");
+
+        let payload = CacheSerializer::serialize(&cache.serializer, &expired).unwrap();
+        assert!(cache.decode(Some(payload)).unwrap().is_none());
+        assert!(cache.decode(None).unwrap().is_none());
+    }
+}
@@ -1,38 +1,150 @@
-//! Multi-tier cache (L1/L2/L3) backend
-
-use crate::backends::{CacheBackend, disk::DiskCache, memory::MemoryCache, moka::MokaCacheBackend};
-use crate::config::{CacheConfig, TierConfig};
-use crate::error::CacheResult;
+//! Multi-tier cache (L1/L2/L3/L4) backend
+
+use crate::backends::{
+    CacheBackend, Weigher, disk::DiskCache, memory::MemoryCache, moka::MokaCacheBackend,
+    serialized_size,
+};
+use crate::config::{CacheConfig, TierConfig, WriteMode};
+use crate::error::{CacheError, CacheResult};
 use crate::key::CacheKey;
 use crate::value::CacheValue;
 use serde::{Deserialize, Serialize};
-use tracing::{debug, trace};
-
-/// Multi-tier cache with L1 (memory), L2 (moka), L3 (disk)
-#[derive(Debug)]
+use std::fmt::Debug;
+use std::sync::{mpsc, Arc};
+use std::thread::JoinHandle;
+use tracing::{debug, trace, warn};
+
+/// Multi-tier cache with L1 (memory), L2 (moka), L3 (disk) and an optional L4 (distributed)
+///
+/// With a weigher, values are weighed once on insert and carry their weight in
+/// `size_bytes` to every tier, so weight-bounded L1 and L2 tiers evict by size.
 pub struct TieredCache<T>
 where
-    T: Clone + Send + Sync + Serialize + for<'de> Deserialize<'de> + 'static,
+    T: Clone + Send + Sync + Serialize + for<'de> Deserialize<'de> + Debug + 'static,
 {
     l1: Option<MemoryCache<T>>,
     l2: Option<MokaCacheBackend<T>>,
     l3: Option<DiskCache<T>>,
+    l4: Option<DistributedTier<T>>,
+    weigher: Option<Weigher<T>>,
     config: TierConfig,
 }
 
+/// Write queued for the distributed tier
+enum DistributedOp<T> {
+    Insert(CacheKey, CacheValue<T>),
+    Remove(CacheKey),
+    Clear,
+    Flush(mpsc::SyncSender<()>),
+}
+
+/// Distributed cache tier, written through or behind
+#[derive(Debug)]
+struct DistributedTier<T: Clone + Debug> {
+    backend: Arc<dyn CacheBackend<Value = T>>,
+    queue: Option<mpsc::SyncSender<DistributedOp<T>>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl<T: Clone + Send + Debug + 'static> DistributedTier<T> {
+    fn new(backend: Arc<dyn CacheBackend<Value = T>>, mode: WriteMode) -> CacheResult<Self> {
+        let WriteMode::WriteBehind { queue_capacity } = mode else {
+            return Ok(Self {
+                backend,
+                queue: None,
+                worker: None,
+            });
+        };
+
+        let (queue, receiver) = mpsc::sync_channel(queue_capacity.max(1));
+        let writer = Arc::clone(&backend);
+        let worker = std::thread::Builder::new()
+            .name("cache-l4-writer".to_string())
+            .spawn(move || {
+                for op in receiver {
+                    if let Err(e) = Self::apply(writer.as_ref(), op) {
+                        warn!("Write-behind to L4 cache failed: {}", e);
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            backend,
+            queue: Some(queue),
+            worker: Some(worker),
+        })
+    }
+
+    fn apply(backend: &dyn CacheBackend<Value = T>, op: DistributedOp<T>) -> CacheResult<()> {
+        match op {
+            DistributedOp::Insert(key, value) => backend.insert(key, value),
+            DistributedOp::Remove(key) => backend.remove(&key).map(|_| ()),
+            DistributedOp::Clear => backend.clear(),
+            DistributedOp::Flush(done) => {
+                let _ = done.send(());
+                Ok(())
+            }
+        }
+    }
+
+    /// Apply a write now, or queue it in write-behind mode
+    ///
+    /// Queueing blocks while the queue is full so writes stay in order.
+    fn write(&self, op: DistributedOp<T>) -> CacheResult<()> {
+        match self.queue {
+            Some(ref queue) => queue.send(op).map_err(|_| {
+                CacheError::DistributedError("L4 write-behind worker stopped".to_string())
+            }),
+            None => Self::apply(self.backend.as_ref(), op),
+        }
+    }
+
+    fn is_write_behind(&self) -> bool {
+        self.queue.is_some()
+    }
+
+    /// Wait until all queued writes have been applied
+    fn flush(&self) -> CacheResult<()> {
+        if self.queue.is_none() {
+            return Ok(());
+        }
+
+        let (done, wait) = mpsc::sync_channel(1);
+        self.write(DistributedOp::Flush(done))?;
+        wait.recv().map_err(|_| {
+            CacheError::DistributedError("L4 write-behind worker stopped".to_string())
+        })
+    }
+}
+
+impl<T: Clone + Debug> Drop for DistributedTier<T> {
+    fn drop(&mut self) {
+        // Closing the queue lets the worker drain pending writes and exit
+        self.queue.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
 impl<T> TieredCache<T>
 where
-    T: Clone + Send + Sync + Serialize + for<'de> Deserialize<'de> + 'static,
+    T: Clone + Send + Sync + Serialize + for<'de> Deserialize<'de> + Debug + 'static,
 {
     /// Create a new tiered cache
     pub fn new(cache_config: &CacheConfig) -> CacheResult<Self> {
         let tier_config = &cache_config.tier_config;
 
+        // Weight-bounded tiers need weights; default to the serialized size
+        let weigher = (tier_config.l1_max_weight.is_some() || tier_config.l2_max_weight.is_some())
+            .then(|| Arc::new(serialized_size::<T>) as Weigher<T>);
+
         // Create L1 (fast memory cache)
         let l1 = if tier_config.enable_l1 {
             let mut l1_config = cache_config.clone();
             l1_config.max_entries = tier_config.l1_max_entries;
-            Some(MemoryCache::new(&l1_config))
+            l1_config.max_weight = tier_config.l1_max_weight;
+            Some(Self::weighted_l1(MemoryCache::new(&l1_config), weigher.clone()))
         } else {
             None
         };
@@ -41,6 +153,7 @@ where
         let l2 = if tier_config.enable_l2 {
             let mut l2_config = cache_config.clone();
             l2_config.max_entries = tier_config.l2_max_entries;
+            l2_config.max_weight = tier_config.l2_max_weight;
             Some(MokaCacheBackend::new(&l2_config))
         } else {
             None
@@ -57,14 +170,80 @@ where
             None
         };
 
+        // Create L4 (distributed cache)
+        let l4 = if tier_config.enable_l4 {
+            Some(Self::distributed_tier(cache_config)?)
+        } else {
+            None
+        };
+
         Ok(Self {
             l1,
             l2,
             l3,
+            l4,
+            weigher,
             config: tier_config.clone(),
         })
     }
 
+    /// Let L1 weigh its entries the same way as the tiered cache
+    fn weighted_l1(l1: MemoryCache<T>, weigher: Option<Weigher<T>>) -> MemoryCache<T> {
+        match weigher {
+            Some(weigher) => l1.with_weigher(move |key, value| weigher(key, value)),
+            None => l1,
+        }
+    }
+
+    #[cfg(feature = "redis")]
+    fn distributed_tier(cache_config: &CacheConfig) -> CacheResult<DistributedTier<T>> {
+        let redis_config = cache_config.redis_config.as_ref().ok_or_else(|| {
+            CacheError::InvalidConfig("L4 cache enabled without a Redis configuration".to_string())
+        })?;
+        let backend = crate::backends::redis::RedisCache::<T>::new(redis_config)?;
+        DistributedTier::new(Arc::new(backend), cache_config.tier_config.l4_write_mode)
+    }
+
+    #[cfg(not(feature = "redis"))]
+    fn distributed_tier(_cache_config: &CacheConfig) -> CacheResult<DistributedTier<T>> {
+        Err(CacheError::InvalidConfig(
+            "L4 cache requires the `redis` feature".to_string(),
+        ))
+    }
+
+    /// Use a backend as the L4 (distributed) tier
+    pub fn with_distributed_tier(
+        mut self,
+        backend: impl CacheBackend<Value = T> + 'static,
+        mode: WriteMode,
+    ) -> CacheResult<Self> {
+        self.l4 = Some(DistributedTier::new(Arc::new(backend), mode)?);
+        self.config.enable_l4 = true;
+        self.config.l4_write_mode = mode;
+        Ok(self)
+    }
+
+    /// Weigh values on insert, typically by their approximate size in bytes
+    ///
+    /// Weight-bounded tiers weigh values by their serialized size by default.
+    pub fn with_weigher(
+        mut self,
+        weigher: impl Fn(&CacheKey, &T) -> usize + Send + Sync + 'static,
+    ) -> Self {
+        let weigher: Weigher<T> = Arc::new(weigher);
+        self.l1 = self.l1.take().map(|l1| Self::weighted_l1(l1, Some(weigher.clone())));
+        self.weigher = Some(weigher);
+        self
+    }
+
+    /// Wait until queued write-behind writes have reached the L4 tier
+    pub fn flush_distributed(&self) -> CacheResult<()> {
+        match self.l4 {
+            Some(ref l4) => l4.flush(),
+            None => Ok(()),
+        }
+    }
+
     /// Promote value to upper tiers
     fn promote(&self, key: &CacheKey, value: &CacheValue<T>) -> CacheResult<()> {
         // Promote to L2 if exists and value came from L3
@@ -84,7 +263,13 @@ where
     fn write_through(&self, key: &CacheKey, value: &CacheValue<T>) -> CacheResult<()> {
         // Write to all enabled tiers
         if let Some(ref l1) = self.l1 {
-            l1.insert(key.clone(), value.clone())?;
+            match l1.insert(key.clone(), value.clone()) {
+                // Too heavy for L1; drop any stale copy and serve it from lower tiers
+                Err(CacheError::CacheFull) => {
+                    l1.remove(key)?;
+                }
+                result => result?,
+            }
         }
 
         if let Some(ref l2) = self.l2 {
@@ -95,6 +280,10 @@ where
             l3.insert(key.clone(), value.clone())?;
         }
 
+        if let Some(ref l4) = self.l4 {
+            l4.write(DistributedOp::Insert(key.clone(), value.clone()))?;
+        }
+
         Ok(())
     }
 
@@ -104,13 +293,17 @@ where
             l1_entries: self.l1.as_ref().map(|c| c.len()).unwrap_or(0),
             l2_entries: self.l2.as_ref().map(|c| c.len()).unwrap_or(0),
             l3_entries: self.l3.as_ref().map(|c| c.len()).unwrap_or(0),
+            l4_entries: self.l4.as_ref().map(|c| c.backend.len()).unwrap_or(0),
+            l1_bytes: self.l1.as_ref().map(|c| c.total_weight()).unwrap_or(0),
+            l2_bytes: self.l2.as_ref().map(|c| c.total_weight()).unwrap_or(0),
+            l3_bytes: self.l3.as_ref().map(|c| c.total_bytes()).unwrap_or(0),
         }
     }
 }
 
 impl<T> CacheBackend for TieredCache<T>
 where
-    T: Clone + Send + Sync + Serialize + for<'de> Deserialize<'de> + std::fmt::Debug + 'static,
+    T: Clone + Send + Sync + Serialize + for<'de> Deserialize<'de> + Debug + 'static,
 {
     type Value = T;
 
@@ -145,11 +338,28 @@ where
             }
         }
 
+        // Try L4 (distributed)
+        if let Some(ref l4) = self.l4 {
+            if let Some(value) = l4.backend.get(key)? {
+                trace!("L4 cache hit: {}", key);
+                // Promote to local tiers, keeping the value's expiry
+                if let Some(ref l3) = self.l3 {
+                    let _ = l3.insert(key.clone(), value.clone());
+                }
+                self.promote(key, &value)?;
+                return Ok(Some(value));
+            }
+        }
+
         trace!("Cache miss on all tiers: {}", key);
         Ok(None)
     }
 
-    fn insert(&self, key: CacheKey, value: CacheValue<Self::Value>) -> CacheResult<()> {
+    fn insert(&self, key: CacheKey, mut value: CacheValue<Self::Value>) -> CacheResult<()> {
+        if let Some(ref weigher) = self.weigher {
+            value.metadata.size_bytes = weigher(&key, &value.data);
+        }
+
         // Write through all tiers
         self.write_through(&key, &value)?;
         debug!("Inserted to tiered cache: {}", key);
@@ -180,6 +390,14 @@ where
             }
         }
 
+        if let Some(ref l4) = self.l4 {
+            if result.is_none() && !l4.is_write_behind() {
+                result = l4.backend.remove(key)?;
+            } else {
+                l4.write(DistributedOp::Remove(key.clone()))?;
+            }
+        }
+
         if result.is_some() {
             debug!("Removed from tiered cache: {}", key);
         }
@@ -207,6 +425,12 @@ where
             }
         }
 
+        if let Some(ref l4) = self.l4 {
+            if l4.backend.contains_key(key) {
+                return true;
+            }
+        }
+
         false
     }
 
@@ -224,6 +448,10 @@ where
             l3.clear()?;
         }
 
+        if let Some(ref l4) = self.l4 {
+            l4.write(DistributedOp::Clear)?;
+        }
+
         debug!("Cleared all tiers");
         Ok(())
     }
@@ -244,6 +472,10 @@ where
             max_len = max_len.max(l3.len());
         }
 
+        if let Some(ref l4) = self.l4 {
+            max_len = max_len.max(l4.backend.len());
+        }
+
         max_len
     }
 
@@ -263,6 +495,10 @@ where
             keys.extend(l3.keys());
         }
 
+        if let Some(ref l4) = self.l4 {
+            keys.extend(l4.backend.keys());
+        }
+
         keys.into_iter().collect()
     }
 
@@ -281,6 +517,10 @@ where
             total += l3.evict_expired()?;
         }
 
+        if let Some(ref l4) = self.l4 {
+            total += l4.backend.evict_expired()?;
+        }
+
         if total > 0 {
             debug!("Evicted {} expired entries across all tiers", total);
         }
@@ -303,22 +543,50 @@ where
             total += l3.capacity();
         }
 
+        if let Some(ref l4) = self.l4 {
+            total += l4.backend.capacity();
+        }
+
         total
     }
 }
 
+impl<T> Debug for TieredCache<T>
+where
+    T: Clone + Send + Sync + Serialize + for<'de> Deserialize<'de> + Debug + 'static,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TieredCache")
+            .field("l1", &self.l1)
+            .field("l2", &self.l2)
+            .field("l3", &self.l3)
+            .field("l4", &self.l4)
+            .field("weigher", &self.weigher.as_ref().map(|_| "<function>"))
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
 /// Statistics for tiered cache
 #[derive(Debug, Clone)]
 pub struct TierStats {
     pub l1_entries: usize,
     pub l2_entries: usize,
     pub l3_entries: usize,
+    pub l4_entries: usize,
+    /// Total weight of L1 entries (approximate bytes)
+    pub l1_bytes: usize,
+    /// Total weight of L2 entries (approximate bytes)
+    pub l2_bytes: usize,
+    /// Size of L3 files on disk
+    pub l3_bytes: usize,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::config::{CacheConfig, DiskConfig};
+    use chrono::Duration;
 
     #[test]
     fn test_tiered_cache_l1_l2() {
@@ -355,4 +623,115 @@ mod tests {
         assert!(cache.get(&CacheKey::new("test", "key1")).unwrap().is_some());
         assert!(cache.get(&CacheKey::new("test", "key2")).unwrap().is_some());
     }
+
+    fn local_only_config() -> CacheConfig {
+        let mut config = CacheConfig::default();
+        config.tier_config.enable_l1 = true;
+        config.tier_config.enable_l2 = false;
+        config.tier_config.enable_l3 = false;
+        config
+    }
+
+    #[test]
+    fn test_distributed_tier_write_through() {
+        let remote = MemoryCache::<String>::with_capacity(100);
+        let cache: TieredCache<String> = TieredCache::new(&local_only_config())
+            .unwrap()
+            .with_distributed_tier(remote.clone(), WriteMode::WriteThrough)
+            .unwrap();
+
+        let key = CacheKey::new("test", "shared");
+        cache
+            .insert(key.clone(), CacheValue::with_ttl("data".to_string(), Duration::seconds(60)))
+            .unwrap();
+        assert!(remote.contains_key(&key));
+
+        // Another node with a cold local tier reads through L4, keeping the TTL
+        let other: TieredCache<String> = TieredCache::new(&local_only_config())
+            .unwrap()
+            .with_distributed_tier(remote.clone(), WriteMode::WriteThrough)
+            .unwrap();
+        let value = other.get(&key).unwrap().unwrap();
+        assert_eq!(value.data, "data");
+        assert!(value.time_until_expiry().unwrap() > Duration::seconds(50));
+        assert_eq!(other.tier_stats().l1_entries, 1);
+
+        other.remove(&key).unwrap();
+        assert!(!remote.contains_key(&key));
+    }
+
+    #[test]
+    fn test_distributed_tier_write_behind() {
+        let remote = MemoryCache::<String>::with_capacity(100);
+        let cache: TieredCache<String> = TieredCache::new(&local_only_config())
+            .unwrap()
+            .with_distributed_tier(remote.clone(), WriteMode::WriteBehind { queue_capacity: 8 })
+            .unwrap();
+
+        for i in 0..20 {
+            let key = CacheKey::new("test", format!("key{}", i));
+            cache.insert(key, CacheValue::new(format!("data{}", i))).unwrap();
+        }
+        cache.remove(&CacheKey::new("test", "key0")).unwrap();
+        cache.flush_distributed().unwrap();
+
+        assert_eq!(remote.len(), 19);
+        assert!(!remote.contains_key(&CacheKey::new("test", "key0")));
+
+        // Dropping the cache drains queued writes
+        cache.clear().unwrap();
+        drop(cache);
+        assert!(remote.is_empty());
+    }
+
+    #[cfg(not(feature = "redis"))]
+    #[test]
+    fn test_l4_requires_redis_feature() {
+        let mut config = local_only_config();
+        config.tier_config.enable_l4 = true;
+        assert!(TieredCache::<String>::new(&config).is_err());
+    }
+
+    #[test]
+    fn test_weighted_l1() {
+        let mut config = local_only_config();
+        config.tier_config.l1_max_weight = Some(100);
+        let cache: TieredCache<String> = TieredCache::new(&config)
+            .unwrap()
+            .with_weigher(|_key, value: &String| value.len());
+
+        let scene = CacheKey::new("scene", "blob");
+        cache.insert(scene.clone(), CacheValue::new("s".repeat(50))).unwrap();
+        cache.insert(CacheKey::new("query", "small"), CacheValue::new("q".repeat(10))).unwrap();
+        assert_eq!(cache.tier_stats().l1_bytes, 60);
+
+        // The weight is carried in the value's metadata
+        assert_eq!(cache.get(&scene).unwrap().unwrap().size_estimate(), 50);
+
+        // The least recently used entry makes room for a heavy insert
+        cache.insert(CacheKey::new("scene", "other"), CacheValue::new("o".repeat(45))).unwrap();
+        let stats = cache.tier_stats();
+        assert_eq!(stats.l1_entries, 2);
+        assert_eq!(stats.l1_bytes, 95);
+        assert!(cache.contains_key(&scene));
+    }
+
+    #[test]
+    fn test_oversized_entries_skip_l1() {
+        let mut config = local_only_config();
+        config.tier_config.enable_l2 = true;
+        config.tier_config.l1_max_weight = Some(100);
+        let cache: TieredCache<String> = TieredCache::new(&config).unwrap();
+
+        // Without a custom weigher values weigh their serialized size
+        let key = CacheKey::new("scene", "blob");
+        cache.insert(key.clone(), CacheValue::new("s".repeat(50))).unwrap();
+        assert_eq!(cache.tier_stats().l1_bytes, serialized_size(&key, &"s".repeat(50)));
+
+        // A value too heavy for L1 replaces the L1 copy with the L2 one
+        cache.insert(key.clone(), CacheValue::new("S".repeat(200))).unwrap();
+        let stats = cache.tier_stats();
+        assert_eq!((stats.l1_entries, stats.l2_entries), (0, 1));
+        assert_eq!(cache.get(&key).unwrap().unwrap().data, "S".repeat(200));
+    }
 }
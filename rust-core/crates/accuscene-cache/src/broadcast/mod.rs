@@ -0,0 +1,485 @@
+//! Invalidation broadcast between cache instances
+//!
+//! Every process keeps its own in-memory caches, so a write handled by one
+//! process leaves stale entries in the others. An [`InvalidationBus`] applies
+//! key, tag, namespace and full invalidations to the caches registered with
+//! it and publishes them through an [`InvalidationTransport`], so that the
+//! buses of other instances apply them as well.
+
+#[cfg(feature = "redis")]
+pub mod redis;
+#[cfg(feature = "streaming")]
+pub mod streaming;
+
+use crate::backends::CacheBackend;
+use crate::error::{CacheError, CacheResult};
+use crate::key::CacheKey;
+use chrono::{DateTime, Utc};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use tracing::{debug, trace, warn};
+use uuid::Uuid;
+
+/// What to invalidate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Invalidation {
+    /// A single key
+    Key(CacheKey),
+    /// Every entry carrying a tag
+    Tag(String),
+    /// Every entry in a namespace
+    Namespace(String),
+    /// Every entry
+    All,
+}
+
+/// Invalidation sent between cache instances
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InvalidationEvent {
+    pub id: Uuid,
+    /// Node ID of the publishing bus
+    pub origin: String,
+    pub timestamp: DateTime<Utc>,
+    pub invalidation: Invalidation,
+}
+
+impl InvalidationEvent {
+    pub fn new(origin: impl Into<String>, invalidation: Invalidation) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            origin: origin.into(),
+            timestamp: Utc::now(),
+            invalidation,
+        }
+    }
+
+    /// Encode for transports carrying text payloads
+    pub fn to_json(&self) -> CacheResult<String> {
+        serde_json::to_string(self).map_err(|e| CacheError::SerializationError(e.to_string()))
+    }
+
+    /// Decode a payload produced by [`InvalidationEvent::to_json`]
+    pub fn from_json(payload: &str) -> CacheResult<Self> {
+        serde_json::from_str(payload).map_err(|e| CacheError::DeserializationError(e.to_string()))
+    }
+}
+
+/// Callback receiving events delivered by a transport
+pub type InvalidationHandler = Arc<dyn Fn(InvalidationEvent) + Send + Sync>;
+
+/// Active transport subscription, cancelled when dropped
+pub struct Subscription {
+    active: Arc<AtomicBool>,
+    on_cancel: Mutex<Option<Box<dyn FnOnce() + Send>>>,
+}
+
+impl Subscription {
+    pub fn new() -> Self {
+        Self {
+            active: Arc::new(AtomicBool::new(true)),
+            on_cancel: Mutex::new(None),
+        }
+    }
+
+    /// Run a callback when the subscription is cancelled, e.g. to stop a listener task
+    pub fn with_cancel(self, on_cancel: impl FnOnce() + Send + 'static) -> Self {
+        *self.on_cancel.lock() = Some(Box::new(on_cancel));
+        self
+    }
+
+    /// Flag that transports check before delivering to the subscriber
+    pub fn flag(&self) -> Arc<AtomicBool> {
+        self.active.clone()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Acquire)
+    }
+
+    pub fn cancel(&self) {
+        self.active.store(false, Ordering::Release);
+        if let Some(on_cancel) = self.on_cancel.lock().take() {
+            on_cancel();
+        }
+    }
+}
+
+impl Default for Subscription {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Subscription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscription")
+            .field("active", &self.is_active())
+            .finish()
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// Transport carrying invalidations between cache instances
+///
+/// Subscribers receive every published event, including those published
+/// through the same transport instance.
+pub trait InvalidationTransport: Send + Sync + fmt::Debug {
+    /// Publish an event to all subscribers
+    fn publish(&self, event: &InvalidationEvent) -> CacheResult<()>;
+
+    /// Deliver published events to `handler` until the subscription is dropped
+    fn subscribe(&self, handler: InvalidationHandler) -> CacheResult<Subscription>;
+
+    /// Transport name
+    fn name(&self) -> &str;
+}
+
+/// Cache that invalidations can be applied to
+pub trait InvalidationTarget: Send + Sync {
+    /// Apply an invalidation, returning the number of removed entries
+    fn apply(&self, invalidation: &Invalidation) -> CacheResult<usize>;
+}
+
+impl<B: CacheBackend> InvalidationTarget for B {
+    fn apply(&self, invalidation: &Invalidation) -> CacheResult<usize> {
+        match invalidation {
+            Invalidation::Key(key) => Ok(usize::from(self.remove(key)?.is_some())),
+            Invalidation::Tag(tag) => {
+                let mut removed = 0;
+                for key in self.keys() {
+                    let tagged = self.get(&key)?.is_some_and(|value| value.has_tag(tag));
+                    if tagged && self.remove(&key)?.is_some() {
+                        removed += 1;
+                    }
+                }
+                Ok(removed)
+            }
+            Invalidation::Namespace(namespace) => {
+                let mut removed = 0;
+                for key in self.keys() {
+                    if &key.namespace == namespace && self.remove(&key)?.is_some() {
+                        removed += 1;
+                    }
+                }
+                Ok(removed)
+            }
+            Invalidation::All => {
+                let removed = self.len();
+                self.clear()?;
+                Ok(removed)
+            }
+        }
+    }
+}
+
+type Targets = Arc<RwLock<Vec<Weak<dyn InvalidationTarget>>>>;
+
+/// Apply an invalidation to every live target, dropping targets that are gone
+fn apply_to_targets(targets: &Targets, invalidation: &Invalidation) -> usize {
+    let mut removed = 0;
+    let mut stale = false;
+
+    for target in targets.read().iter() {
+        match target.upgrade() {
+            Some(target) => match target.apply(invalidation) {
+                Ok(count) => removed += count,
+                Err(e) => warn!("Failed to apply invalidation {:?}: {}", invalidation, e),
+            },
+            None => stale = true,
+        }
+    }
+
+    if stale {
+        targets.write().retain(|target| target.strong_count() > 0);
+    }
+    removed
+}
+
+/// Applies invalidations locally and propagates them to other cache instances
+///
+/// Caches are held weakly, so dropping a cache unregisters it. Events received
+/// from the transport that this bus published itself are ignored, since they
+/// were already applied when published.
+pub struct InvalidationBus {
+    node_id: String,
+    transport: Arc<dyn InvalidationTransport>,
+    targets: Targets,
+    subscription: Mutex<Option<Subscription>>,
+}
+
+impl InvalidationBus {
+    pub fn new(transport: impl InvalidationTransport + 'static) -> Self {
+        Self::with_transport(Arc::new(transport))
+    }
+
+    /// Create a bus on a transport shared with other buses
+    pub fn with_transport(transport: Arc<dyn InvalidationTransport>) -> Self {
+        Self {
+            node_id: Uuid::new_v4().to_string(),
+            transport,
+            targets: Arc::new(RwLock::new(Vec::new())),
+            subscription: Mutex::new(None),
+        }
+    }
+
+    /// Set the node ID identifying this instance (random by default)
+    pub fn with_node_id(mut self, node_id: impl Into<String>) -> Self {
+        self.node_id = node_id.into();
+        self
+    }
+
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    /// Register a cache to apply invalidations to
+    pub fn register<C: InvalidationTarget + 'static>(&self, cache: &Arc<C>) {
+        let cache: Arc<dyn InvalidationTarget> = cache.clone();
+        self.targets.write().push(Arc::downgrade(&cache));
+    }
+
+    /// Number of registered caches that are still alive
+    pub fn target_count(&self) -> usize {
+        self.targets
+            .read()
+            .iter()
+            .filter(|target| target.strong_count() > 0)
+            .count()
+    }
+
+    /// Start applying invalidations published by other instances
+    pub fn start(&self) -> CacheResult<()> {
+        let mut subscription = self.subscription.lock();
+        if subscription.is_some() {
+            return Ok(());
+        }
+
+        let node_id = self.node_id.clone();
+        let targets = self.targets.clone();
+        let handler: InvalidationHandler = Arc::new(move |event: InvalidationEvent| {
+            if event.origin == node_id {
+                return;
+            }
+            let removed = apply_to_targets(&targets, &event.invalidation);
+            trace!(
+                "Applied invalidation {:?} from {}, removed {} entries",
+                event.invalidation,
+                event.origin,
+                removed
+            );
+        });
+
+        *subscription = Some(self.transport.subscribe(handler)?);
+        debug!(
+            "Invalidation bus {} subscribed via {} transport",
+            self.node_id,
+            self.transport.name()
+        );
+        Ok(())
+    }
+
+    /// Stop applying remote invalidations
+    pub fn stop(&self) {
+        if self.subscription.lock().take().is_some() {
+            debug!("Invalidation bus {} unsubscribed", self.node_id);
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.subscription.lock().is_some()
+    }
+
+    /// Invalidate locally and broadcast, returning the number of entries removed locally
+    ///
+    /// Local caches are invalidated even if publishing fails.
+    pub fn invalidate(&self, invalidation: Invalidation) -> CacheResult<usize> {
+        let removed = apply_to_targets(&self.targets, &invalidation);
+        let event = InvalidationEvent::new(self.node_id.clone(), invalidation);
+        self.transport.publish(&event)?;
+
+        debug!(
+            "Broadcast invalidation {:?}, removed {} local entries",
+            event.invalidation,
+            removed
+        );
+        Ok(removed)
+    }
+
+    pub fn invalidate_key(&self, key: &CacheKey) -> CacheResult<usize> {
+        self.invalidate(Invalidation::Key(key.clone()))
+    }
+
+    pub fn invalidate_tag(&self, tag: &str) -> CacheResult<usize> {
+        self.invalidate(Invalidation::Tag(tag.to_string()))
+    }
+
+    pub fn invalidate_namespace(&self, namespace: &str) -> CacheResult<usize> {
+        self.invalidate(Invalidation::Namespace(namespace.to_string()))
+    }
+
+    pub fn invalidate_all(&self) -> CacheResult<usize> {
+        self.invalidate(Invalidation::All)
+    }
+}
+
+impl fmt::Debug for InvalidationBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InvalidationBus")
+            .field("node_id", &self.node_id)
+            .field("transport", &self.transport)
+            .field("targets", &self.target_count())
+            .field("running", &self.is_running())
+            .finish()
+    }
+}
+
+type Subscriber = (Arc<AtomicBool>, InvalidationHandler);
+
+/// In-process transport delivering events synchronously to every subscriber
+///
+/// Clones share subscribers, so buses created from clones of one transport
+/// see each other's invalidations.
+#[derive(Clone, Default)]
+pub struct ChannelTransport {
+    subscribers: Arc<RwLock<Vec<Subscriber>>>,
+}
+
+impl ChannelTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of active subscribers
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers
+            .read()
+            .iter()
+            .filter(|(active, _)| active.load(Ordering::Acquire))
+            .count()
+    }
+}
+
+impl InvalidationTransport for ChannelTransport {
+    fn publish(&self, event: &InvalidationEvent) -> CacheResult<()> {
+        let handlers: Vec<InvalidationHandler> = {
+            let mut subscribers = self.subscribers.write();
+            subscribers.retain(|(active, _)| active.load(Ordering::Acquire));
+            subscribers.iter().map(|(_, handler)| handler.clone()).collect()
+        };
+
+        for handler in handlers {
+            handler(event.clone());
+        }
+        Ok(())
+    }
+
+    fn subscribe(&self, handler: InvalidationHandler) -> CacheResult<Subscription> {
+        let subscription = Subscription::new();
+        self.subscribers.write().push((subscription.flag(), handler));
+        Ok(subscription)
+    }
+
+    fn name(&self) -> &str {
+        "channel"
+    }
+}
+
+impl fmt::Debug for ChannelTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChannelTransport")
+            .field("subscribers", &self.subscriber_count())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::memory::MemoryCache;
+    use crate::value::CacheValue;
+
+    fn cache_with_entries() -> Arc<MemoryCache<String>> {
+        let cache = Arc::new(MemoryCache::with_capacity(10));
+        cache
+            .insert(CacheKey::new("physics", "sim_1"), CacheValue::new("a".to_string()))
+            .unwrap();
+        cache
+            .insert(
+                CacheKey::new("physics", "sim_2"),
+                CacheValue::with_tags("b".to_string(), vec!["case_7".to_string()]),
+            )
+            .unwrap();
+        cache
+            .insert(
+                CacheKey::new("query", "list"),
+                CacheValue::with_tags("c".to_string(), vec!["case_7".to_string()]),
+            )
+            .unwrap();
+        cache
+    }
+
+    #[test]
+    fn test_invalidations_reach_other_instances() {
+        let transport = ChannelTransport::new();
+        let desktop = InvalidationBus::new(transport.clone()).with_node_id("desktop");
+        let server = InvalidationBus::new(transport.clone()).with_node_id("server");
+
+        let desktop_cache = cache_with_entries();
+        let server_cache = cache_with_entries();
+        desktop.register(&desktop_cache);
+        server.register(&server_cache);
+        desktop.start().unwrap();
+        server.start().unwrap();
+        assert_eq!(transport.subscriber_count(), 2);
+
+        let key = CacheKey::new("physics", "sim_1");
+        assert_eq!(server.invalidate_key(&key).unwrap(), 1);
+        assert!(!server_cache.contains_key(&key));
+        assert!(!desktop_cache.contains_key(&key));
+
+        assert_eq!(desktop.invalidate_tag("case_7").unwrap(), 2);
+        assert_eq!(desktop_cache.len(), 0);
+        assert_eq!(server_cache.len(), 0);
+
+        // Stopped buses no longer apply remote invalidations
+        server.stop();
+        server_cache
+            .insert(key.clone(), CacheValue::new("a".to_string()))
+            .unwrap();
+        desktop.invalidate_all().unwrap();
+        assert!(server_cache.contains_key(&key));
+        assert_eq!(transport.subscriber_count(), 1);
+    }
+
+    #[test]
+    fn test_namespace_invalidation_and_dropped_targets() {
+        let bus = InvalidationBus::new(ChannelTransport::new());
+        let cache = cache_with_entries();
+        bus.register(&cache);
+
+        assert_eq!(bus.invalidate_namespace("physics").unwrap(), 2);
+        assert!(cache.contains_key(&CacheKey::new("query", "list")));
+
+        drop(cache);
+        assert_eq!(bus.target_count(), 0);
+        assert_eq!(bus.invalidate_all().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_event_json_roundtrip() {
+        let event = InvalidationEvent::new(
+            "node",
+            Invalidation::Key(CacheKey::with_version("physics", "sim_1", 2)),
+        );
+        let decoded = InvalidationEvent::from_json(&event.to_json().unwrap()).unwrap();
+        assert_eq!(decoded, event);
+    }
+}
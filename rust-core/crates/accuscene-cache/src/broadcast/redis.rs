@@ -0,0 +1,163 @@
+//! Redis pub/sub invalidation transport
+
+use super::{InvalidationEvent, InvalidationHandler, InvalidationTransport, Subscription};
+use crate::config::RedisConfig;
+use crate::error::{CacheError, CacheResult};
+use parking_lot::Mutex;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tracing::{debug, trace, warn};
+
+/// How often subscriber threads check whether they were cancelled
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Delay before a subscriber reconnects after losing its connection
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Transport publishing invalidations on a Redis pub/sub channel
+///
+/// Each subscription reads on its own connection in a background thread and
+/// reconnects after connection errors. Redis does not buffer pub/sub messages,
+/// so invalidations published while a subscriber is reconnecting are lost.
+pub struct RedisTransport {
+    client: redis::Client,
+    connection: Mutex<Option<redis::Connection>>,
+    channel: String,
+}
+
+impl RedisTransport {
+    /// Create a transport for a channel; connections are opened on first use
+    pub fn new(url: &str, channel: impl Into<String>) -> CacheResult<Self> {
+        let client = redis::Client::open(url).map_err(|e| {
+            CacheError::InvalidConfig(format!("Invalid Redis URL: {}", e))
+        })?;
+
+        Ok(Self {
+            client,
+            connection: Mutex::new(None),
+            channel: channel.into(),
+        })
+    }
+
+    /// Create a transport on the `<key_prefix>:invalidations` channel of a Redis cache
+    pub fn from_config(config: &RedisConfig) -> CacheResult<Self> {
+        Self::new(&config.url, format!("{}:invalidations", config.key_prefix))
+    }
+
+    pub fn channel(&self) -> &str {
+        &self.channel
+    }
+
+    /// Read messages until the subscription is cancelled or the connection fails
+    fn listen(
+        client: &redis::Client,
+        channel: &str,
+        active: &AtomicBool,
+        handler: &InvalidationHandler,
+    ) -> redis::RedisResult<()> {
+        let mut connection = client.get_connection()?;
+        let mut pubsub = connection.as_pubsub();
+        pubsub.set_read_timeout(Some(POLL_INTERVAL))?;
+        pubsub.subscribe(channel)?;
+
+        while active.load(Ordering::Acquire) {
+            let message = match pubsub.get_message() {
+                Ok(message) => message,
+                Err(e) if e.is_timeout() => continue,
+                Err(e) => return Err(e),
+            };
+
+            let payload: String = message.get_payload()?;
+            match InvalidationEvent::from_json(&payload) {
+                Ok(event) => handler(event),
+                Err(e) => warn!("Ignoring malformed invalidation on {}: {}", channel, e),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl InvalidationTransport for RedisTransport {
+    fn publish(&self, event: &InvalidationEvent) -> CacheResult<()> {
+        let payload = event.to_json()?;
+
+        let mut guard = self.connection.lock();
+        let connection = match &mut *guard {
+            Some(connection) => connection,
+            slot @ None => slot.insert(self.client.get_connection().map_err(|e| {
+                CacheError::DistributedError(format!("Failed to connect to Redis: {}", e))
+            })?),
+        };
+
+        let receivers: usize = redis::cmd("PUBLISH")
+            .arg(&self.channel)
+            .arg(payload)
+            .query(connection)
+            .map_err(|e| {
+                if e.is_io_error() || e.is_connection_dropped() {
+                    *guard = None;
+                }
+                CacheError::DistributedError(format!("Redis publish failed: {}", e))
+            })?;
+
+        trace!("Published invalidation to {} Redis subscribers", receivers);
+        Ok(())
+    }
+
+    fn subscribe(&self, handler: InvalidationHandler) -> CacheResult<Subscription> {
+        let subscription = Subscription::new();
+        let active = subscription.flag();
+        let client = self.client.clone();
+        let channel = self.channel.clone();
+
+        std::thread::Builder::new()
+            .name("cache-invalidation-redis".to_string())
+            .spawn(move || {
+                while active.load(Ordering::Acquire) {
+                    match Self::listen(&client, &channel, &active, &handler) {
+                        Ok(()) => break,
+                        Err(e) => {
+                            warn!("Redis invalidation subscriber on {} failed: {}", channel, e);
+                            std::thread::sleep(RECONNECT_DELAY);
+                        }
+                    }
+                }
+                debug!("Redis invalidation subscriber on {} stopped", channel);
+            })?;
+
+        Ok(subscription)
+    }
+
+    fn name(&self) -> &str {
+        "redis"
+    }
+}
+
+impl fmt::Debug for RedisTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedisTransport")
+            .field("channel", &self.channel)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_from_config() {
+        let config = RedisConfig {
+            key_prefix: "accuscene-test".to_string(),
+            ..RedisConfig::default()
+        };
+        let transport = RedisTransport::from_config(&config).unwrap();
+        assert_eq!(transport.channel(), "accuscene-test:invalidations");
+
+        assert!(matches!(
+            RedisTransport::new("not a url", "channel"),
+            Err(CacheError::InvalidConfig(_))
+        ));
+    }
+}
@@ -0,0 +1,165 @@
+//! Invalidation transport over an accuscene-streaming event bus
+
+use super::{InvalidationEvent, InvalidationHandler, InvalidationTransport, Subscription};
+use crate::error::{CacheError, CacheResult};
+use accuscene_streaming::bus::EventBus;
+use accuscene_streaming::{Event, EventFilter, EventPayload, EventType};
+use futures::StreamExt;
+use std::fmt;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use tokio::runtime::Handle;
+use tokio::sync::mpsc;
+use tracing::{trace, warn};
+
+/// Custom event type carrying cache invalidations
+pub const INVALIDATION_EVENT_TYPE: &str = "cache.invalidation";
+
+/// Transport publishing invalidations as custom events on a streaming bus
+///
+/// Events are published in order by a task on the given runtime. Subscribing
+/// also happens on the runtime, so events published before a new subscription
+/// is established are not delivered to it.
+pub struct StreamingTransport {
+    bus: Arc<dyn EventBus>,
+    runtime: Handle,
+    publisher: mpsc::UnboundedSender<Event>,
+}
+
+impl StreamingTransport {
+    /// Create a transport on the current Tokio runtime
+    ///
+    /// # Panics
+    ///
+    /// Panics when called outside a Tokio runtime.
+    pub fn new(bus: Arc<dyn EventBus>) -> Self {
+        Self::with_runtime(bus, Handle::current())
+    }
+
+    /// Create a transport running its tasks on a specific runtime
+    pub fn with_runtime(bus: Arc<dyn EventBus>, runtime: Handle) -> Self {
+        let (publisher, mut events) = mpsc::unbounded_channel::<Event>();
+        let publishing_bus = bus.clone();
+        runtime.spawn(async move {
+            while let Some(event) = events.recv().await {
+                if let Err(e) = publishing_bus.publish(event).await {
+                    warn!("Failed to publish cache invalidation: {}", e);
+                }
+            }
+        });
+
+        Self {
+            bus,
+            runtime,
+            publisher,
+        }
+    }
+
+    fn encode(event: &InvalidationEvent) -> CacheResult<Event> {
+        let data = serde_json::to_value(event)
+            .map_err(|e| CacheError::SerializationError(e.to_string()))?;
+        Ok(Event::new(
+            EventType::Custom(INVALIDATION_EVENT_TYPE.to_string()),
+            EventPayload::Json(data),
+        ))
+    }
+
+    fn decode(event: Event) -> CacheResult<InvalidationEvent> {
+        match event.payload {
+            EventPayload::Json(data) => serde_json::from_value(data)
+                .map_err(|e| CacheError::DeserializationError(e.to_string())),
+            _ => Err(CacheError::DeserializationError(
+                "Invalidation event without JSON payload".to_string(),
+            )),
+        }
+    }
+}
+
+impl InvalidationTransport for StreamingTransport {
+    fn publish(&self, event: &InvalidationEvent) -> CacheResult<()> {
+        self.publisher.send(Self::encode(event)?).map_err(|_| {
+            CacheError::DistributedError("Invalidation publisher task stopped".to_string())
+        })?;
+
+        trace!("Queued invalidation {} for the streaming bus", event.id);
+        Ok(())
+    }
+
+    fn subscribe(&self, handler: InvalidationHandler) -> CacheResult<Subscription> {
+        let subscription = Subscription::new();
+        let active = subscription.flag();
+        let bus = self.bus.clone();
+        let filter = EventFilter::new()
+            .with_types(vec![EventType::Custom(INVALIDATION_EVENT_TYPE.to_string())]);
+
+        let task = self.runtime.spawn(async move {
+            let mut events = match bus.subscribe(filter).await {
+                Ok(events) => events,
+                Err(e) => {
+                    warn!("Failed to subscribe to cache invalidations: {}", e);
+                    return;
+                }
+            };
+
+            while let Some(event) = events.next().await {
+                if !active.load(Ordering::Acquire) {
+                    break;
+                }
+                match Self::decode(event) {
+                    Ok(event) => handler(event),
+                    Err(e) => warn!("Ignoring malformed cache invalidation: {}", e),
+                }
+            }
+        });
+
+        Ok(subscription.with_cancel(move || task.abort()))
+    }
+
+    fn name(&self) -> &str {
+        "streaming"
+    }
+}
+
+impl fmt::Debug for StreamingTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamingTransport").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::memory::MemoryCache;
+    use crate::backends::CacheBackend;
+    use crate::broadcast::InvalidationBus;
+    use crate::key::CacheKey;
+    use crate::value::CacheValue;
+    use accuscene_streaming::bus::memory::MemoryEventBus;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_invalidations_over_event_bus() {
+        let bus: Arc<dyn EventBus> = Arc::new(MemoryEventBus::new());
+        let desktop = InvalidationBus::new(StreamingTransport::new(bus.clone()));
+        let server = InvalidationBus::new(StreamingTransport::new(bus));
+
+        let key = CacheKey::new("physics", "sim_1");
+        let cache = Arc::new(MemoryCache::with_capacity(10));
+        cache.insert(key.clone(), CacheValue::new("stale".to_string())).unwrap();
+        desktop.register(&cache);
+        desktop.start().unwrap();
+        server.start().unwrap();
+
+        // Let the subscriptions be established
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        server.invalidate_key(&key).unwrap();
+
+        for _ in 0..50 {
+            if !cache.contains_key(&key) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(!cache.contains_key(&key));
+    }
+}
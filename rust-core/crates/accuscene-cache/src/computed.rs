@@ -1,11 +1,14 @@
 //! Computed and memoized cache values
 
 use crate::backends::CacheBackend;
-use crate::error::CacheResult;
+use crate::error::{CacheError, CacheResult};
 use crate::key::CacheKey;
 use crate::value::CacheValue;
+use parking_lot::{Condvar, Mutex};
+use std::collections::HashMap;
 use std::sync::Arc;
-use tracing::{debug, trace};
+use std::time::{Duration, Instant};
+use tracing::{debug, trace, warn};
 
 /// Trait for computing cache values
 pub trait ComputeFn<T>: Send + Sync {
@@ -23,17 +26,79 @@ where
     }
 }
 
+/// Outcome of an in-flight computation shared with waiting callers
+struct Flight<T> {
+    result: Mutex<Option<Result<T, String>>>,
+    done: Condvar,
+}
+
+impl<T: Clone> Flight<T> {
+    fn new() -> Self {
+        Self {
+            result: Mutex::new(None),
+            done: Condvar::new(),
+        }
+    }
+
+    /// Publish the result unless one was already published
+    fn complete(&self, result: Result<T, String>) {
+        let mut slot = self.result.lock();
+        if slot.is_none() {
+            *slot = Some(result);
+            self.done.notify_all();
+        }
+    }
+
+    /// Block until the leading caller publishes its result
+    fn wait(&self) -> Result<T, String> {
+        let mut slot = self.result.lock();
+        loop {
+            if let Some(result) = slot.as_ref() {
+                return result.clone();
+            }
+            self.done.wait(&mut slot);
+        }
+    }
+}
+
+type InFlight<T> = Mutex<HashMap<CacheKey, Arc<Flight<T>>>>;
+
+/// Unregisters a flight when the leading caller finishes, failing waiters if it panicked
+struct FlightGuard<'a, T: Clone> {
+    in_flight: &'a InFlight<T>,
+    key: CacheKey,
+    flight: Arc<Flight<T>>,
+}
+
+impl<T: Clone> Drop for FlightGuard<'_, T> {
+    fn drop(&mut self) {
+        self.in_flight.lock().remove(&self.key);
+        self.flight.complete(Err("computation panicked".to_string()));
+    }
+}
+
 /// Cache with automatic computation of missing values
+///
+/// Concurrent misses for the same key are coalesced: the first caller
+/// computes while the others wait for its result. With early expiration
+/// enabled, entries with a TTL are refreshed probabilistically before they
+/// expire (XFetch), so recomputation of hot keys is spread out instead of
+/// happening at the moment of expiry. Callers that do not win the draw, or
+/// find a refresh already running, keep getting the cached value.
 pub struct ComputedCache<T: Clone + Send + Sync + 'static> {
     cache: Box<dyn CacheBackend<Value = T>>,
     compute_fn: Arc<dyn ComputeFn<T>>,
+    in_flight: InFlight<T>,
+    early_expiration: Option<f64>,
 }
 
-impl<T: Clone + Send + Sync + std::fmt::Debug + 'static> std::fmt::Debug for ComputedCache<T> {
+impl<T: Clone + Send + Sync + 'static> std::fmt::Debug for ComputedCache<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ComputedCache")
             .field("cache", &self.cache)
             .field("compute_fn", &"<function>")
+            .field("in_flight", &self.in_flight.lock().len())
+            .field("early_expiration", &self.early_expiration)
             .finish()
     }
 }
@@ -46,24 +111,24 @@ impl<T: Clone + Send + Sync + 'static> ComputedCache<T> {
         Self {
             cache,
             compute_fn: Arc::new(compute_fn),
+            in_flight: Mutex::new(HashMap::new()),
+            early_expiration: None,
         }
     }
 
+    /// Refresh entries with a TTL early using XFetch
+    ///
+    /// `beta` scales how far ahead of expiry refreshes start relative to how
+    /// long the value took to compute; 1.0 is the usual choice and larger
+    /// values refresh earlier. Zero or negative values disable early refresh.
+    pub fn with_early_expiration(mut self, beta: f64) -> Self {
+        self.early_expiration = Some(beta).filter(|beta| *beta > 0.0);
+        self
+    }
+
     /// Get value, computing if not in cache
     pub fn get_or_compute(&self, key: &CacheKey) -> CacheResult<T> {
-        // Try cache first
-        if let Some(cached) = self.cache.get(key)? {
-            trace!("Cache hit for computed value: {}", key);
-            return Ok(cached.data);
-        }
-
-        // Compute and cache
-        debug!("Computing value for: {}", key);
-        let value = self.compute_fn.compute(key)?;
-        let cache_value = CacheValue::new(value.clone());
-        self.cache.insert(key.clone(), cache_value)?;
-
-        Ok(value)
+        self.get_or_load(key, None)
     }
 
     /// Get value with custom TTL if computed
@@ -72,19 +137,7 @@ impl<T: Clone + Send + Sync + 'static> ComputedCache<T> {
         key: &CacheKey,
         ttl: chrono::Duration,
     ) -> CacheResult<T> {
-        // Try cache first
-        if let Some(cached) = self.cache.get(key)? {
-            trace!("Cache hit for computed value: {}", key);
-            return Ok(cached.data);
-        }
-
-        // Compute and cache with TTL
-        debug!("Computing value with TTL for: {}", key);
-        let value = self.compute_fn.compute(key)?;
-        let cache_value = CacheValue::with_ttl(value.clone(), ttl);
-        self.cache.insert(key.clone(), cache_value)?;
-
-        Ok(value)
+        self.get_or_load(key, Some(ttl))
     }
 
     /// Invalidate cached value (will be recomputed on next access)
@@ -104,6 +157,133 @@ impl<T: Clone + Send + Sync + 'static> ComputedCache<T> {
     pub fn cache(&self) -> &dyn CacheBackend<Value = T> {
         &*self.cache
     }
+
+    /// Number of keys currently being computed
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.lock().len()
+    }
+
+    fn get_or_load(&self, key: &CacheKey, ttl: Option<chrono::Duration>) -> CacheResult<T> {
+        // Try cache first
+        if let Some(cached) = self.cache.get(key)? {
+            if !self.should_refresh_early(&cached) {
+                trace!("Cache hit for computed value: {}", key);
+                return Ok(cached.data);
+            }
+            debug!("Refreshing computed value before expiry: {}", key);
+            return self.load(key, ttl, Some(cached.data));
+        }
+
+        self.load(key, ttl, None)
+    }
+
+    /// Compute a value once per key, sharing the result with concurrent callers
+    ///
+    /// `current` is the still valid cached value during an early refresh. It is
+    /// returned instead of waiting when another caller is already refreshing,
+    /// and instead of the error when the refresh fails.
+    fn load(
+        &self,
+        key: &CacheKey,
+        ttl: Option<chrono::Duration>,
+        current: Option<T>,
+    ) -> CacheResult<T> {
+        let (flight, leader) = {
+            let mut in_flight = self.in_flight.lock();
+            match in_flight.get(key) {
+                Some(flight) => (flight.clone(), false),
+                None => {
+                    let flight = Arc::new(Flight::new());
+                    in_flight.insert(key.clone(), flight.clone());
+                    (flight, true)
+                }
+            }
+        };
+
+        if !leader {
+            if let Some(value) = current {
+                trace!("Refresh already in flight, using cached value: {}", key);
+                return Ok(value);
+            }
+            trace!("Waiting for in-flight computation: {}", key);
+            return flight.wait().map_err(|e| {
+                CacheError::BackendError(format!("Computation for {} failed: {}", key, e))
+            });
+        }
+
+        let guard = FlightGuard {
+            in_flight: &self.in_flight,
+            key: key.clone(),
+            flight,
+        };
+
+        // The previous flight may have finished between our cache miss and
+        // taking the lock
+        if current.is_none() {
+            if let Some(cached) = self.cache.get(key)? {
+                guard.flight.complete(Ok(cached.data.clone()));
+                return Ok(cached.data);
+            }
+        }
+
+        let result = self.compute_and_store(key, ttl);
+        guard.flight.complete(match &result {
+            Ok(value) => Ok(value.clone()),
+            Err(e) => Err(e.to_string()),
+        });
+        drop(guard);
+
+        match (result, current) {
+            (Err(e), Some(value)) => {
+                warn!("Early refresh of {} failed, keeping cached value: {}", key, e);
+                Ok(value)
+            }
+            (result, _) => result,
+        }
+    }
+
+    fn compute_and_store(&self, key: &CacheKey, ttl: Option<chrono::Duration>) -> CacheResult<T> {
+        debug!("Computing value for: {}", key);
+        let started = Instant::now();
+        let value = self.compute_fn.compute(key)?;
+        let elapsed = started.elapsed();
+
+        let mut cache_value = match ttl {
+            Some(ttl) => CacheValue::with_ttl(value.clone(), ttl),
+            None => CacheValue::new(value.clone()),
+        };
+        cache_value.metadata.compute_time = Some(elapsed);
+        self.cache.insert(key.clone(), cache_value)?;
+
+        Ok(value)
+    }
+
+    fn should_refresh_early(&self, cached: &CacheValue<T>) -> bool {
+        let Some(beta) = self.early_expiration else {
+            return false;
+        };
+        let Some(remaining) = cached.time_until_expiry() else {
+            return false;
+        };
+        let Some(compute_time) = cached.metadata.compute_time else {
+            return false;
+        };
+
+        xfetch(
+            compute_time,
+            beta,
+            remaining.to_std().unwrap_or_default(),
+            rand::random(),
+        )
+    }
+}
+
+/// XFetch decision: refresh when `-delta * beta * ln(u)` reaches the remaining TTL
+///
+/// `draw` is uniform in `[0, 1)`; it is mapped to `(0, 1]` so the logarithm stays finite.
+fn xfetch(compute_time: Duration, beta: f64, remaining: Duration, draw: f64) -> bool {
+    let gap = -compute_time.as_secs_f64() * beta * (1.0 - draw).ln();
+    gap >= remaining.as_secs_f64()
 }
 
 /// Memoization helper for expensive functions
@@ -168,6 +348,7 @@ where
     cache: Option<Box<dyn CacheBackend<Value = V>>>,
     ttl: Option<chrono::Duration>,
     namespace: String,
+    _phantom: std::marker::PhantomData<K>,
 }
 
 impl<K, V> MemoizerBuilder<K, V>
@@ -180,6 +361,7 @@ where
             cache: None,
             ttl: None,
             namespace: namespace.into(),
+            _phantom: std::marker::PhantomData,
         }
     }
 
@@ -235,6 +417,73 @@ mod tests {
         assert_eq!(compute_count.load(Ordering::SeqCst), 1); // Not incremented
     }
 
+    #[test]
+    fn test_concurrent_misses_compute_once() {
+        let cache = Box::new(MemoryCache::with_capacity(10));
+        let compute_count = Arc::new(AtomicU64::new(0));
+        let compute_count_clone = compute_count.clone();
+
+        let compute_fn = move |key: &CacheKey| -> CacheResult<String> {
+            compute_count_clone.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(50));
+            Ok(format!("computed_{}", key.identifier))
+        };
+
+        let computed_cache = Arc::new(ComputedCache::new(cache, compute_fn));
+        let barrier = Arc::new(std::sync::Barrier::new(16));
+
+        let handles: Vec<_> = (0..16)
+            .map(|_| {
+                let computed_cache = computed_cache.clone();
+                let barrier = barrier.clone();
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    computed_cache.get_or_compute(&CacheKey::new("physics", "hot"))
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap().unwrap(), "computed_hot");
+        }
+        assert_eq!(compute_count.load(Ordering::SeqCst), 1);
+        assert_eq!(computed_cache.in_flight(), 0);
+    }
+
+    #[test]
+    fn test_early_expiration() {
+        let compute_time = Duration::from_millis(100);
+        let remaining = Duration::from_secs(1);
+
+        // A refresh needs -ln(1 - draw) * 0.1s >= 1s, i.e. draw >= 1 - e^-10
+        assert!(!xfetch(compute_time, 1.0, remaining, 0.5));
+        assert!(xfetch(compute_time, 1.0, remaining, 0.99999));
+        assert!(xfetch(compute_time, 100.0, remaining, 0.5));
+        assert!(!xfetch(compute_time, 100.0, remaining, 0.0));
+
+        let cache = Box::new(MemoryCache::with_capacity(10));
+        let compute_count = Arc::new(AtomicU64::new(0));
+        let compute_count_clone = compute_count.clone();
+
+        let compute_fn = move |_key: &CacheKey| -> CacheResult<u64> {
+            std::thread::sleep(Duration::from_millis(1));
+            Ok(compute_count_clone.fetch_add(1, Ordering::SeqCst) + 1)
+        };
+
+        // A huge beta refreshes on practically every hit while the entry is valid
+        let computed_cache = ComputedCache::new(cache, compute_fn).with_early_expiration(1e12);
+        let key = CacheKey::new("physics", "simulation_1");
+        let ttl = chrono::Duration::seconds(60);
+
+        assert_eq!(computed_cache.get_or_compute_with_ttl(&key, ttl).unwrap(), 1);
+        assert_eq!(computed_cache.get_or_compute_with_ttl(&key, ttl).unwrap(), 2);
+
+        // Entries without a TTL never refresh early
+        let other = CacheKey::new("physics", "simulation_2");
+        assert_eq!(computed_cache.get_or_compute(&other).unwrap(), 3);
+        assert_eq!(computed_cache.get_or_compute(&other).unwrap(), 3);
+    }
+
     #[test]
     fn test_memoizer() {
         let cache = Box::new(MemoryCache::with_capacity(10));
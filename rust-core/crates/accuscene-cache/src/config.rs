@@ -1,5 +1,6 @@
 //! Cache configuration and settings
 
+use crate::serialization::SerializationFormat;
 use chrono::Duration;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -13,6 +14,13 @@ pub struct CacheConfig {
     /// Maximum memory size in bytes
     pub max_memory_bytes: usize,
 
+    /// Maximum total weight of entries, unbounded when unset
+    ///
+    /// Entries weigh their `size_bytes` metadata, which weighers set on insert.
+    /// Memory caches need a weigher; tiered caches default to the serialized size.
+    #[serde(default)]
+    pub max_weight: Option<usize>,
+
     /// Default time-to-live for entries
     pub default_ttl: Option<Duration>,
 
@@ -30,6 +38,10 @@ pub struct CacheConfig {
 
     /// Disk cache configuration
     pub disk_config: Option<DiskConfig>,
+
+    /// Redis cache configuration
+    #[serde(default)]
+    pub redis_config: Option<RedisConfig>,
 }
 
 impl Default for CacheConfig {
@@ -37,12 +49,14 @@ impl Default for CacheConfig {
         Self {
             max_entries: 10_000,
             max_memory_bytes: 100 * 1024 * 1024, // 100MB
+            max_weight: None,
             default_ttl: Some(Duration::hours(1)),
             enable_stats: true,
             enable_tracing: true,
             eviction_policy: EvictionPolicy::Lru,
             tier_config: TierConfig::default(),
             disk_config: None,
+            redis_config: None,
         }
     }
 }
@@ -71,17 +85,33 @@ pub struct TierConfig {
     /// L1 cache size
     pub l1_max_entries: usize,
 
+    /// L1 cache weight capacity
+    #[serde(default)]
+    pub l1_max_weight: Option<usize>,
+
     /// Enable L2 (moka concurrent) cache
     pub enable_l2: bool,
 
     /// L2 cache size
     pub l2_max_entries: usize,
 
+    /// L2 cache weight capacity, replacing the entry limit when set
+    #[serde(default)]
+    pub l2_max_weight: Option<usize>,
+
     /// Enable L3 (disk) cache
     pub enable_l3: bool,
 
     /// L3 cache size
     pub l3_max_entries: usize,
+
+    /// Enable L4 (distributed Redis) cache
+    #[serde(default)]
+    pub enable_l4: bool,
+
+    /// How writes reach the L4 cache
+    #[serde(default)]
+    pub l4_write_mode: WriteMode,
 }
 
 impl Default for TierConfig {
@@ -89,10 +119,14 @@ impl Default for TierConfig {
         Self {
             enable_l1: true,
             l1_max_entries: 1_000,
+            l1_max_weight: None,
             enable_l2: true,
             l2_max_entries: 10_000,
+            l2_max_weight: None,
             enable_l3: false,
             l3_max_entries: 100_000,
+            enable_l4: false,
+            l4_write_mode: WriteMode::WriteThrough,
         }
     }
 }
@@ -124,6 +158,46 @@ impl Default for DiskConfig {
     }
 }
 
+/// Write mode for the distributed cache tier
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum WriteMode {
+    /// Write to the distributed tier before an insert returns
+    #[default]
+    WriteThrough,
+    /// Queue writes to the distributed tier on a background thread
+    WriteBehind {
+        /// Maximum number of queued writes before inserts block
+        queue_capacity: usize,
+    },
+}
+
+/// Redis cache configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedisConfig {
+    /// Redis connection URL
+    pub url: String,
+
+    /// Prefix for all keys written by this cache
+    pub key_prefix: String,
+
+    /// Serialization format for cached values
+    pub serialization: SerializationFormat,
+
+    /// Advisory capacity; Redis enforces its own memory limits
+    pub max_entries: usize,
+}
+
+impl Default for RedisConfig {
+    fn default() -> Self {
+        Self {
+            url: "redis://127.0.0.1:6379".to_string(),
+            key_prefix: "accuscene-cache".to_string(),
+            serialization: SerializationFormat::Bincode,
+            max_entries: 1_000_000,
+        }
+    }
+}
+
 /// Cache type classification
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum CacheType {
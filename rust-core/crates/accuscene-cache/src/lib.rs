@@ -4,10 +4,12 @@
 //!
 //! # Features
 //!
-//! - **Multi-tier caching**: L1 (memory), L2 (concurrent), L3 (disk)
-//! - **Multiple eviction policies**: LRU, LFU, TTL, Adaptive
+//! - **Multi-tier caching**: L1 (memory), L2 (concurrent), L3 (disk), L4 (Redis, `redis` feature)
+//! - **Multiple eviction policies**: LRU, LFU, TTL, Adaptive, bounded by entry count or weight
 //! - **Tag-based invalidation**: Group-based cache invalidation
-//! - **Computed values**: Automatic computation and memoization
+//! - **Invalidation broadcast**: Propagate invalidations across processes (in-process, Redis
+//!   pub/sub, or the streaming event bus with the `streaming` feature)
+//! - **Computed values**: Automatic computation and memoization with stampede protection
 //! - **Statistics tracking**: Hit rates, eviction rates, utilization
 //! - **Partitioning**: Separate caches by data type
 //! - **Middleware**: Logging, metrics, validation
@@ -72,6 +74,7 @@
 //! ```
 
 pub mod backends;
+pub mod broadcast;
 pub mod computed;
 pub mod config;
 pub mod distributed;
@@ -96,10 +99,17 @@ pub use value::CacheValue;
 pub mod prelude {
     pub use crate::backends::memory::MemoryCache;
     pub use crate::backends::moka::MokaCacheBackend;
+    #[cfg(feature = "redis")]
+    pub use crate::backends::redis::RedisCache;
     pub use crate::backends::tiered::TieredCache;
     pub use crate::backends::CacheBackend;
+    #[cfg(feature = "redis")]
+    pub use crate::broadcast::redis::RedisTransport;
+    #[cfg(feature = "streaming")]
+    pub use crate::broadcast::streaming::StreamingTransport;
+    pub use crate::broadcast::{ChannelTransport, Invalidation, InvalidationBus};
     pub use crate::computed::ComputedCache;
-    pub use crate::config::{CacheConfig, CacheType, EvictionPolicy};
+    pub use crate::config::{CacheConfig, CacheType, EvictionPolicy, RedisConfig, WriteMode};
     pub use crate::error::{CacheError, CacheResult};
     pub use crate::key::{CacheKey, CacheKeyBuilder};
     pub use crate::partitioning::PartitionedCache;
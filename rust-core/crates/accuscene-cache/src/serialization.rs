@@ -55,6 +55,39 @@ impl CacheSerializer for JsonSerializer {
     }
 }
 
+/// Serialization format selectable from configuration
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SerializationFormat {
+    /// Compact binary format
+    #[default]
+    Bincode,
+    /// Human-readable JSON
+    Json,
+}
+
+impl CacheSerializer for SerializationFormat {
+    fn serialize<T: Serialize>(&self, value: &T) -> CacheResult<Vec<u8>> {
+        match self {
+            SerializationFormat::Bincode => BincodeSerializer.serialize(value),
+            SerializationFormat::Json => JsonSerializer.serialize(value),
+        }
+    }
+
+    fn deserialize<T: for<'de> Deserialize<'de>>(&self, bytes: &[u8]) -> CacheResult<T> {
+        match self {
+            SerializationFormat::Bincode => BincodeSerializer.deserialize(bytes),
+            SerializationFormat::Json => JsonSerializer.deserialize(bytes),
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            SerializationFormat::Bincode => BincodeSerializer.name(),
+            SerializationFormat::Json => JsonSerializer.name(),
+        }
+    }
+}
+
 /// Compressed serializer wrapper
 #[derive(Debug)]
 pub struct CompressedSerializer<S: CacheSerializer> {
@@ -144,4 +177,21 @@ mod tests {
 
         assert_eq!(data, deserialized);
     }
+
+    #[test]
+    fn test_serialization_format() {
+        let data = TestData {
+            id: 7,
+            name: "format".to_string(),
+            values: vec![0.5],
+        };
+
+        for format in [SerializationFormat::Bincode, SerializationFormat::Json] {
+            // Disambiguated from the serde traits the format also implements
+            let bytes = CacheSerializer::serialize(&format, &data).unwrap();
+            let deserialized: TestData = CacheSerializer::deserialize(&format, &bytes).unwrap();
+            assert_eq!(data, deserialized);
+        }
+        assert_eq!(SerializationFormat::Json.name(), "json");
+    }
 }
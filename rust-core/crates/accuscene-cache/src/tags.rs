@@ -1,6 +1,7 @@
 //! Tag-based cache invalidation
 
 use crate::backends::CacheBackend;
+use crate::broadcast::{Invalidation, InvalidationTarget};
 use crate::error::CacheResult;
 use crate::key::CacheKey;
 use crate::value::CacheValue;
@@ -188,6 +189,29 @@ impl<T: Clone + Send + Sync + 'static> TaggedCache<T> {
     }
 }
 
+impl<T: Clone + Send + Sync + 'static> InvalidationTarget for TaggedCache<T> {
+    fn apply(&self, invalidation: &Invalidation) -> CacheResult<usize> {
+        match invalidation {
+            Invalidation::Key(key) => Ok(usize::from(self.remove(key)?.is_some())),
+            Invalidation::Tag(tag) => self.invalidate_tag(tag),
+            Invalidation::Namespace(namespace) => {
+                let mut removed = 0;
+                for key in self.cache.keys() {
+                    if &key.namespace == namespace && self.remove(&key)?.is_some() {
+                        removed += 1;
+                    }
+                }
+                Ok(removed)
+            }
+            Invalidation::All => {
+                let removed = self.cache.len();
+                self.clear()?;
+                Ok(removed)
+            }
+        }
+    }
+}
+
 /// Tag cache statistics
 #[derive(Debug, Clone)]
 pub struct TagCacheStats {
@@ -313,4 +337,37 @@ mod tests {
         // key2 should still exist (only has tag1)
         assert!(tagged_cache.get(&key2).unwrap().is_some());
     }
+
+    #[test]
+    fn test_broadcast_invalidation() {
+        let cache = Box::new(MemoryCache::with_capacity(10));
+        let tagged_cache: TaggedCache<String> = TaggedCache::new(cache);
+
+        let key1 = CacheKey::new("physics", "key1");
+        let key2 = CacheKey::new("query", "key2");
+
+        tagged_cache
+            .insert_with_tags(
+                key1.clone(),
+                CacheValue::new("value1".to_string()),
+                vec!["case_7".to_string()],
+            )
+            .unwrap();
+
+        tagged_cache
+            .insert_with_tags(key2.clone(), CacheValue::new("value2".to_string()), vec![])
+            .unwrap();
+
+        // Untagged entries are still found by namespace
+        let count = tagged_cache
+            .apply(&Invalidation::Namespace("query".to_string()))
+            .unwrap();
+        assert_eq!(count, 1);
+
+        let count = tagged_cache
+            .apply(&Invalidation::Tag("case_7".to_string()))
+            .unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(tagged_cache.stats().total_entries, 0);
+    }
 }
@@ -93,6 +93,10 @@ pub struct CacheMetadata {
 
     /// Whether this value is pinned (never evict)
     pub pinned: bool,
+
+    /// How long the value took to compute, used for early expiration
+    #[serde(default)]
+    pub compute_time: Option<std::time::Duration>,
 }
 
 impl CacheMetadata {
@@ -108,6 +112,7 @@ impl CacheMetadata {
             tags: Vec::new(),
             version: 1,
             pinned: false,
+            compute_time: None,
         }
     }
 
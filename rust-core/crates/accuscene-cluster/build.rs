@@ -0,0 +1,19 @@
+//! Build script for the optional gRPC transport.
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto/cluster.proto");
+
+        if std::env::var_os("PROTOC").is_none() {
+            let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc");
+            std::env::set_var("PROTOC", protoc);
+        }
+
+        tonic_build::configure()
+            .compile_protos(&["proto/cluster.proto"], &["proto"])
+            .expect("failed to compile cluster.proto");
+    }
+}
@@ -2,6 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::time::Duration;
 
 /// Comprehensive cluster configuration.
@@ -118,6 +119,16 @@ pub struct ConsensusConfig {
 
     /// Maximum log entries per append
     pub max_log_entries: usize,
+
+    /// Directory for the durable log and snapshots (in memory when unset)
+    pub log_dir: Option<PathBuf>,
+
+    /// Committed entries since the last snapshot before a new one is due
+    pub snapshot_threshold: u64,
+
+    /// Entries kept behind a snapshot so followers that are only slightly
+    /// behind catch up from the log instead of installing the snapshot
+    pub snapshot_trailing_entries: u64,
 }
 
 impl Default for ConsensusConfig {
@@ -128,6 +139,9 @@ impl Default for ConsensusConfig {
             leader_lease: Duration::from_millis(500),
             enable_prevote: true,
             max_log_entries: 1000,
+            log_dir: None,
+            snapshot_threshold: 10_000,
+            snapshot_trailing_entries: 1_000,
         }
     }
 }
@@ -150,6 +164,15 @@ pub struct ReplicationConfig {
     /// Anti-entropy interval
     pub anti_entropy_interval: Duration,
 
+    /// Key ranges compared per anti-entropy digest
+    pub anti_entropy_ranges: usize,
+
+    /// Keys sent per batch when rebalancing partitions
+    pub rebalance_batch_size: usize,
+
+    /// Bytes per second rebalancing may send, zero for no limit
+    pub rebalance_rate_limit: u64,
+
     /// Conflict resolution strategy
     pub conflict_resolution: ConflictResolutionStrategy,
 }
@@ -162,6 +185,9 @@ impl Default for ReplicationConfig {
             read_consistency: ConsistencyLevel::Quorum,
             enable_anti_entropy: true,
             anti_entropy_interval: Duration::from_secs(60),
+            anti_entropy_ranges: 256,
+            rebalance_batch_size: 500,
+            rebalance_rate_limit: 8 * 1024 * 1024,
             conflict_resolution: ConflictResolutionStrategy::LastWriteWins,
         }
     }
@@ -127,6 +127,20 @@ impl LeaderElection {
         self.leader_info.read().term
     }
 
+    /// Get the candidate voted for in the current term.
+    pub fn voted_for(&self) -> Option<Uuid> {
+        *self.voted_for.read()
+    }
+
+    /// Restore the term and vote saved before a restart.
+    pub fn restore(&self, term: u64, voted_for: Option<Uuid>) {
+        let mut leader_info = self.leader_info.write();
+        if term >= leader_info.term {
+            leader_info.term = term;
+            *self.voted_for.write() = voted_for;
+        }
+    }
+
     /// Start election as candidate.
     pub fn start_election(&self) -> u64 {
         let mut state = self.state.write();
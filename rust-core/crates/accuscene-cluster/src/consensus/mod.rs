@@ -2,16 +2,29 @@
 
 pub mod leader;
 pub mod raft_lite;
+pub mod storage;
 
 pub use leader::{LeaderElection, LeaderInfo, LeaderState};
 pub use raft_lite::{LogEntry, NextIndexTracker, RaftMessage, ReplicatedLog};
+pub use storage::{HardState, RaftStorage, RecoveredState, Snapshot};
 
 use crate::config::ConsensusConfig;
 use crate::error::{ClusterError, Result};
-use parking_lot::RwLock;
+use crate::messaging::{RpcRequest, RpcResponse, RpcService};
+use parking_lot::{Mutex, RwLock};
 use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+/// Queue of Raft messages waiting for the transport.
+pub type OutboundRaft = mpsc::UnboundedReceiver<(Uuid, RaftMessage)>;
+
+/// RPC method Raft messages are sent with.
+pub const RAFT_RPC_METHOD: &str = "raft";
+
 /// Consensus service.
 pub struct ConsensusService {
     /// Local node ID
@@ -23,6 +36,22 @@ pub struct ConsensusService {
     /// Replicated log
     log: Arc<RwLock<ReplicatedLog>>,
 
+    /// Durable storage, once recovered
+    storage: Mutex<Option<RaftStorage>>,
+
+    /// Latest snapshot
+    snapshot: RwLock<Option<Snapshot>>,
+
+    /// Replication progress of the other voting nodes
+    followers: RwLock<NextIndexTracker>,
+
+    /// Outgoing Raft messages
+    outbound_tx: mpsc::UnboundedSender<(Uuid, RaftMessage)>,
+    outbound_rx: Mutex<Option<OutboundRaft>>,
+
+    /// Cancelled on stop
+    shutdown: CancellationToken,
+
     /// Configuration
     config: ConsensusConfig,
 }
@@ -39,15 +68,162 @@ impl ConsensusService {
         let log = Arc::new(RwLock::new(ReplicatedLog::new(
             config.max_log_entries,
         )));
+        let (outbound_tx, outbound_rx) = mpsc::unbounded_channel();
 
         Self {
             local_id,
             leader_election,
             log,
+            storage: Mutex::new(None),
+            snapshot: RwLock::new(None),
+            followers: RwLock::new(NextIndexTracker::new()),
+            outbound_tx,
+            outbound_rx: Mutex::new(Some(outbound_rx)),
+            shutdown: CancellationToken::new(),
             config,
         }
     }
 
+    /// Take the queue of outgoing Raft messages.
+    ///
+    /// The transport sends each message to its node, hands it to that node's
+    /// [`handle_message`](Self::handle_message) and returns the reply the
+    /// same way. Only the first call gets the queue; nothing is queued
+    /// before it is taken.
+    pub fn take_outbound(&self) -> Option<OutboundRaft> {
+        self.outbound_rx.lock().take()
+    }
+
+    /// Carry Raft messages over RPC.
+    ///
+    /// Registers the handler for messages sent by other nodes and spawns a
+    /// task that calls each queued message's node and applies its reply,
+    /// until [`stop`](Self::stop) is called. Returns `None` if the outbound
+    /// queue was already taken.
+    pub fn serve_rpc(self: &Arc<Self>, rpc: &Arc<RpcService>) -> Option<JoinHandle<()>> {
+        let mut outbound = self.take_outbound()?;
+
+        let service = Arc::clone(self);
+        rpc.register(RAFT_RPC_METHOD, move |request: RpcRequest| {
+            let (from, message): (Uuid, RaftMessage) = request.decode_params()?;
+            RpcResponse::success(&service.handle_message(from, message))
+        });
+
+        let service = Arc::clone(self);
+        let rpc = Arc::clone(rpc);
+        Some(tokio::spawn(async move {
+            loop {
+                let (to, message) = tokio::select! {
+                    next = outbound.recv() => match next {
+                        Some(next) => next,
+                        None => break,
+                    },
+                    _ = service.shutdown.cancelled() => break,
+                };
+
+                // A slow or unreachable follower must not hold up the others
+                let (service, rpc) = (Arc::clone(&service), Arc::clone(&rpc));
+                tokio::spawn(async move {
+                    let params = (service.local_id, message);
+                    match rpc.call::<_, Option<RaftMessage>>(to, RAFT_RPC_METHOD, &params).await {
+                        Ok(Some(reply)) => {
+                            service.handle_message(to, reply);
+                        }
+                        Ok(None) => {}
+                        Err(e) => debug!("Raft message to {} failed: {}", to, e),
+                    }
+                });
+            }
+        }))
+    }
+
+    /// Send AppendEntries to the followers every heartbeat interval while
+    /// this node leads, until [`stop`](Self::stop) is called.
+    pub fn start_replication(self: &Arc<Self>) -> JoinHandle<()> {
+        let service = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(service.config.heartbeat_interval);
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    _ = service.shutdown.cancelled() => break,
+                }
+                service.replicate();
+            }
+        })
+    }
+
+    /// Stop the replication task.
+    pub fn stop(&self) {
+        self.shutdown.cancel();
+    }
+
+    /// Queue the message that brings each follower up to date; with nothing
+    /// new to send these are heartbeats carrying the commit index.
+    pub fn replicate(&self) {
+        if !self.is_leader() {
+            return;
+        }
+        if self.outbound_rx.lock().is_some() {
+            debug!("No transport for Raft messages");
+            return;
+        }
+
+        let targets: Vec<(Uuid, u64)> = self.followers.read().next_indices().collect();
+        for (follower_id, next_index) in targets {
+            match self.replication_message(next_index) {
+                Ok(message) => {
+                    if self.outbound_tx.send((follower_id, message)).is_err() {
+                        debug!("Raft transport stopped");
+                        return;
+                    }
+                }
+                Err(e) => warn!("Cannot replicate to {}: {}", follower_id, e),
+            }
+        }
+    }
+
+    /// Open durable storage in the configured log directory and restore
+    /// the term, vote, log and snapshot saved before the last shutdown.
+    ///
+    /// Does nothing when no log directory is configured. The state machine
+    /// should be restored from [`latest_snapshot`](Self::latest_snapshot)
+    /// before applying committed entries.
+    pub fn recover(&self) -> Result<()> {
+        let Some(dir) = &self.config.log_dir else {
+            return Ok(());
+        };
+
+        let mut log = self.log.write();
+        let mut storage = self.storage.lock();
+        if storage.is_some() {
+            return Ok(());
+        }
+
+        let (opened, recovered) = RaftStorage::open(dir)?;
+        let RecoveredState { hard_state, snapshot, compacted, entries } = recovered;
+
+        self.leader_election.restore(hard_state.term, hard_state.voted_for);
+        log.restore(compacted.0, compacted.1, entries);
+        if let Some(snapshot) = &snapshot {
+            let last_applied = snapshot.last_included_index.max(log.last_applied());
+            log.set_commit_index(snapshot.last_included_index);
+            log.set_last_applied(last_applied);
+        }
+
+        info!(
+            "Recovered Raft log at term {} with {} entries after index {}",
+            hard_state.term,
+            log.len(),
+            compacted.0
+        );
+
+        *self.snapshot.write() = snapshot;
+        *storage = Some(opened);
+
+        Ok(())
+    }
+
     /// Check if this node is the leader.
     pub fn is_leader(&self) -> bool {
         self.leader_election.is_leader()
@@ -65,17 +241,35 @@ impl ConsensusService {
 
     /// Propose a value for consensus.
     pub async fn propose(&self, data: Vec<u8>) -> Result<u64> {
+        self.append_proposal(data)
+    }
+
+    /// Append a proposed value to the leader's log and send it to the
+    /// followers, returning its index.
+    pub(crate) fn append_proposal(&self, data: Vec<u8>) -> Result<u64> {
         if !self.is_leader() {
             return Err(ClusterError::NotLeader(self.current_leader()));
         }
 
-        let mut log = self.log.write();
-        let term = self.leader_election.current_term();
-        let (last_index, _) = log.last_log_info();
-        let new_index = last_index + 1;
+        let index = {
+            let mut log = self.log.write();
+            let term = self.leader_election.current_term();
+            let (last_index, _) = log.last_log_info();
+            let new_index = last_index + 1;
 
-        let entry = LogEntry::new(term, new_index, data);
-        let index = log.append(entry);
+            let entry = LogEntry::new(term, new_index, data);
+            self.persist(|storage| storage.append(std::slice::from_ref(&entry)))?;
+            let index = log.append(entry);
+
+            // Without other voters the leader's log is the majority
+            if self.followers.read().follower_count() == 0 {
+                log.set_commit_index(index);
+            }
+            index
+        };
+
+        // Send the entry now rather than with the next heartbeat
+        self.replicate();
 
         Ok(index)
     }
@@ -86,8 +280,180 @@ impl ConsensusService {
         log.unapplied_entries()
     }
 
-    /// Handle Raft message.
-    pub fn handle_message(&self, message: RaftMessage) -> Option<RaftMessage> {
+    /// Get the commit index.
+    pub fn commit_index(&self) -> u64 {
+        self.log.read().commit_index()
+    }
+
+    /// Get committed entries after an index that are still in the log.
+    pub fn committed_since(&self, index: u64) -> Vec<LogEntry> {
+        let log = self.log.read();
+        let commit_index = log.commit_index();
+        log.get_from(index + 1, usize::MAX)
+            .into_iter()
+            .take_while(|entry| entry.index <= commit_index)
+            .collect()
+    }
+
+    /// Add a voting node.
+    pub fn add_peer(&self, peer_id: Uuid) {
+        if peer_id == self.local_id {
+            return;
+        }
+
+        let (last_index, _) = self.log.read().last_log_info();
+        let mut followers = self.followers.write();
+        if !followers.has_follower(&peer_id) {
+            followers.init_follower(peer_id, last_index);
+        }
+    }
+
+    /// Remove a voting node.
+    pub fn remove_peer(&self, peer_id: &Uuid) {
+        self.followers.write().remove_follower(peer_id);
+        if self.is_leader() {
+            self.advance_commit_index();
+        }
+    }
+
+    /// Handle a follower's reply to AppendEntries.
+    pub fn handle_append_reply(&self, follower_id: Uuid, term: u64, success: bool, match_index: u64) {
+        if term > self.leader_election.current_term() {
+            self.leader_election.step_down(None, term);
+            if let Err(e) = self.persist_hard_state() {
+                error!("Failed to persist term: {}", e);
+            }
+            return;
+        }
+        if !self.is_leader() {
+            return;
+        }
+
+        if success {
+            self.followers.write().update_success(follower_id, match_index);
+            self.advance_commit_index();
+        } else {
+            self.followers.write().update_failure(follower_id);
+        }
+    }
+
+    /// Get the next index to replicate to a follower from.
+    pub fn next_index(&self, follower_id: &Uuid) -> u64 {
+        self.followers.read().get_next_index(follower_id)
+    }
+
+    /// Get the latest snapshot, taken locally or installed from the leader.
+    pub fn latest_snapshot(&self) -> Option<Snapshot> {
+        self.snapshot.read().clone()
+    }
+
+    /// Check whether enough entries were committed since the last snapshot
+    /// that a new one is due.
+    pub fn should_snapshot(&self) -> bool {
+        let snapshot_index = self
+            .snapshot
+            .read()
+            .as_ref()
+            .map(|s| s.last_included_index)
+            .unwrap_or(0);
+
+        self.log.read().commit_index().saturating_sub(snapshot_index) >= self.config.snapshot_threshold
+    }
+
+    /// Save a snapshot of the state machine as of a committed index and
+    /// compact the log behind it.
+    ///
+    /// `snapshot_trailing_entries` entries before the snapshot stay in the
+    /// log for followers that are only slightly behind.
+    pub fn create_snapshot(&self, last_included_index: u64, data: Vec<u8>) -> Result<()> {
+        let mut log = self.log.write();
+
+        if last_included_index > log.commit_index() {
+            return Err(ClusterError::InvalidMessage(format!(
+                "Cannot snapshot uncommitted index {}",
+                last_included_index
+            )));
+        }
+        if self
+            .snapshot
+            .read()
+            .as_ref()
+            .is_some_and(|s| s.last_included_index >= last_included_index)
+        {
+            return Ok(());
+        }
+
+        let term = log.term_at(last_included_index).ok_or_else(|| {
+            ClusterError::InvalidMessage(format!("Index {} is not in the log", last_included_index))
+        })?;
+        let snapshot = Snapshot::new(last_included_index, term, data);
+
+        let compact_to = last_included_index.saturating_sub(self.config.snapshot_trailing_entries);
+        let (compacted_index, _) = log.snapshot_info();
+        let retained = log.get_from(compact_to + 1, usize::MAX);
+
+        let mut storage = self.storage.lock();
+        if let Some(storage) = storage.as_mut() {
+            storage.save_snapshot(&snapshot)?;
+            if compact_to > compacted_index {
+                if let Some(compact_term) = log.term_at(compact_to) {
+                    storage.compact(compact_to, compact_term, &retained)?;
+                }
+            }
+        }
+
+        let last_applied = last_included_index.max(log.last_applied());
+        log.compact(compact_to);
+        log.set_last_applied(last_applied);
+        *self.snapshot.write() = Some(snapshot);
+
+        Ok(())
+    }
+
+    /// Build the message that brings a follower up to date from its next
+    /// index: entries when the log still holds them, the snapshot otherwise.
+    pub fn replication_message(&self, next_index: u64) -> Result<RaftMessage> {
+        if !self.is_leader() {
+            return Err(ClusterError::NotLeader(self.current_leader()));
+        }
+
+        let term = self.leader_election.current_term();
+        let log = self.log.read();
+        let (compacted_index, _) = log.snapshot_info();
+
+        if next_index <= compacted_index {
+            let snapshot = self.snapshot.read().clone().ok_or_else(|| {
+                ClusterError::ReplicationFailed(format!(
+                    "Entries up to {} were compacted without a snapshot",
+                    compacted_index
+                ))
+            })?;
+
+            return Ok(RaftMessage::InstallSnapshot {
+                term,
+                leader_id: self.local_id,
+                last_included_index: snapshot.last_included_index,
+                last_included_term: snapshot.last_included_term,
+                data: snapshot.data,
+            });
+        }
+
+        let prev_log_index = next_index - 1;
+        Ok(RaftMessage::AppendEntries {
+            term,
+            leader_id: self.local_id,
+            prev_log_index,
+            prev_log_term: log.term_at(prev_log_index).unwrap_or(0),
+            entries: log.get_from(next_index, self.config.max_log_entries),
+            leader_commit: log.commit_index(),
+        })
+    }
+
+    /// Handle a Raft message from another node.
+    ///
+    /// Replies carry no sender, so the transport passes the node it got
+    /// the message from.
+    pub fn handle_message(&self, from: Uuid, message: RaftMessage) -> Option<RaftMessage> {
         match message {
             RaftMessage::RequestVote {
                 term,
@@ -144,7 +510,32 @@ impl ConsensusService {
                 success,
                 match_index,
             } => {
-                // Handle response in replication logic
+                self.handle_append_reply(from, term, success, match_index);
+                None
+            }
+
+            RaftMessage::InstallSnapshot {
+                term,
+                leader_id,
+                last_included_index,
+                last_included_term,
+                data,
+            } => {
+                let match_index = self.handle_install_snapshot(
+                    term,
+                    leader_id,
+                    Snapshot::new(last_included_index, last_included_term, data),
+                );
+
+                Some(RaftMessage::InstallSnapshotReply {
+                    term: self.leader_election.current_term(),
+                    match_index,
+                })
+            }
+
+            RaftMessage::InstallSnapshotReply { term, match_index } => {
+                // A rejected snapshot reports index 0
+                self.handle_append_reply(from, term, match_index > 0, match_index);
                 None
             }
         }
@@ -176,8 +567,14 @@ impl ConsensusService {
             return false;
         }
 
-        // Vote for candidate
-        self.leader_election.vote(candidate_id, term)
+        // Vote for candidate; the vote only counts once it is durable
+        let granted = self.leader_election.vote(candidate_id, term);
+        if let Err(e) = self.persist_hard_state() {
+            error!("Failed to persist vote: {}", e);
+            return false;
+        }
+
+        granted
     }
 
     /// Handle append entries RPC.
@@ -199,44 +596,151 @@ impl ConsensusService {
 
         // Update leader
         self.leader_election.handle_heartbeat(leader_id, term);
+        if let Err(e) = self.persist_hard_state() {
+            error!("Failed to persist term: {}", e);
+            return (false, 0);
+        }
 
         let mut log = self.log.write();
+        let (compacted_index, _) = log.snapshot_info();
 
-        // Check previous log entry
-        if prev_log_index > 0 {
-            if let Some(entry) = log.get(prev_log_index) {
-                if entry.term != prev_log_term {
-                    return (false, 0);
-                }
-            } else {
-                return (false, 0);
+        // Check previous log entry; compacted entries are committed and match
+        if prev_log_index > compacted_index {
+            match log.term_at(prev_log_index) {
+                Some(term) if term == prev_log_term => {}
+                _ => return (false, 0),
             }
         }
 
-        // Append new entries
-        if !entries.is_empty() {
-            // Truncate conflicting entries
-            if let Some(first) = entries.first() {
-                log.truncate_from(first.index);
+        // Index of the last entry this request covers; a stale or reordered
+        // request must not report or commit past it
+        let last_new_index = entries.last().map_or(prev_log_index, |e| e.index);
+
+        // Skip entries the log already holds and only truncate from the
+        // first one whose term conflicts, so an old request cannot delete
+        // entries appended after it
+        let entries: Vec<LogEntry> = entries
+            .into_iter()
+            .filter(|e| e.index > compacted_index)
+            .skip_while(|e| log.term_at(e.index) == Some(e.term))
+            .collect();
+        if let Some(first) = entries.first() {
+            let first_index = first.index;
+            let conflict = log.term_at(first_index).is_some();
+
+            // Persist before acknowledging
+            let persisted = self.persist(|storage| {
+                if conflict {
+                    storage.truncate_from(first_index)?;
+                }
+                storage.append(&entries)
+            });
+            if let Err(e) = persisted {
+                error!("Failed to persist log entries: {}", e);
+                return (false, 0);
             }
 
+            log.truncate_from(first_index);
             log.append_entries(entries);
         }
 
         // Update commit index
         if leader_commit > log.commit_index() {
-            let (last_index, _) = log.last_log_info();
-            let new_commit = leader_commit.min(last_index);
-            log.set_commit_index(new_commit);
+            let new_commit = leader_commit.min(last_new_index);
+            if new_commit > log.commit_index() {
+                log.set_commit_index(new_commit);
+            }
         }
 
-        let (match_index, _) = log.last_log_info();
-        (true, match_index)
+        (true, last_new_index)
+    }
+
+    /// Handle install snapshot RPC, returning the index the log now
+    /// matches the leader's up to.
+    fn handle_install_snapshot(&self, term: u64, leader_id: Uuid, snapshot: Snapshot) -> u64 {
+        if term < self.leader_election.current_term() {
+            return 0;
+        }
+
+        self.leader_election.handle_heartbeat(leader_id, term);
+        if let Err(e) = self.persist_hard_state() {
+            error!("Failed to persist term: {}", e);
+            return 0;
+        }
+
+        let mut log = self.log.write();
+        let index = snapshot.last_included_index;
+
+        // Already covered by committed entries
+        if index <= log.commit_index() {
+            return index;
+        }
+
+        let term = snapshot.last_included_term;
+        let retained = if log.term_at(index) == Some(term) {
+            log.get_from(index + 1, usize::MAX)
+        } else {
+            Vec::new()
+        };
+
+        let persisted = self.persist(|storage| {
+            storage.save_snapshot(&snapshot)?;
+            storage.compact(index, term, &retained)
+        });
+        if let Err(e) = persisted {
+            error!("Failed to persist snapshot: {}", e);
+            return 0;
+        }
+
+        log.install_snapshot(index, term);
+        *self.snapshot.write() = Some(snapshot);
+
+        info!("Installed snapshot through index {} from {}", index, leader_id);
+        index
+    }
+
+    /// Run a write against durable storage, if recovered.
+    fn persist<F>(&self, write: F) -> Result<()>
+    where
+        F: FnOnce(&mut RaftStorage) -> Result<()>,
+    {
+        match self.storage.lock().as_mut() {
+            Some(storage) => write(storage),
+            None => Ok(()),
+        }
+    }
+
+    /// Commit entries of the current term stored on a majority.
+    fn advance_commit_index(&self) {
+        let mut log = self.log.write();
+        let followers = self.followers.read();
+
+        let candidate = if followers.follower_count() == 0 {
+            log.last_log_info().0
+        } else {
+            followers.calculate_commit_index(log.commit_index(), followers.follower_count() + 1)
+        };
+
+        // Earlier terms' entries only commit along with one of this term
+        if log.term_at(candidate) == Some(self.leader_election.current_term()) {
+            log.set_commit_index(candidate);
+        }
+    }
+
+    /// Save the current term and vote.
+    fn persist_hard_state(&self) -> Result<()> {
+        let hard_state = HardState {
+            term: self.leader_election.current_term(),
+            voted_for: self.leader_election.voted_for(),
+        };
+        self.persist(|storage| storage.save_hard_state(hard_state))
     }
 
     /// Start election.
-    pub fn start_election(&self) -> u64 {
-        self.leader_election.start_election()
+    pub fn start_election(&self) -> Result<u64> {
+        let term = self.leader_election.start_election();
+        self.persist_hard_state()?;
+        Ok(term)
     }
 
     /// Become leader.
@@ -249,3 +753,135 @@ impl ConsensusService {
         &self.leader_election
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn config(dir: &TempDir) -> ConsensusConfig {
+        ConsensusConfig {
+            log_dir: Some(dir.path().to_path_buf()),
+            snapshot_threshold: 4,
+            snapshot_trailing_entries: 1,
+            ..ConsensusConfig::default()
+        }
+    }
+
+    fn leader(id: Uuid, config: ConsensusConfig) -> ConsensusService {
+        let service = ConsensusService::new(id, config);
+        service.recover().unwrap();
+        service.start_election().unwrap();
+        service.become_leader();
+        service
+    }
+
+    #[tokio::test]
+    async fn test_log_recovered_after_restart() {
+        let dir = TempDir::new().unwrap();
+        let id = Uuid::new_v4();
+
+        {
+            let service = leader(id, config(&dir));
+            for i in 0..3u8 {
+                service.propose(vec![i]).await.unwrap();
+            }
+        }
+
+        let service = ConsensusService::new(id, config(&dir));
+        service.recover().unwrap();
+        assert_eq!(service.leader_election().current_term(), 1);
+        assert_eq!(service.leader_election().voted_for(), Some(id));
+        assert_eq!(service.log.read().last_log_info(), (3, 1));
+    }
+
+    #[tokio::test]
+    async fn test_lagging_follower_installs_snapshot() {
+        let leader_dir = TempDir::new().unwrap();
+        let follower_dir = TempDir::new().unwrap();
+        let leader = leader(Uuid::new_v4(), config(&leader_dir));
+
+        for i in 0..5u8 {
+            leader.propose(vec![i]).await.unwrap();
+        }
+        leader.log.write().set_commit_index(5);
+        assert!(leader.should_snapshot());
+
+        leader.create_snapshot(5, b"state@5".to_vec()).unwrap();
+        assert!(!leader.should_snapshot());
+        assert_eq!(leader.log.read().snapshot_info(), (4, 1));
+
+        // Index 5 is still in the log; anything earlier needs the snapshot
+        assert!(matches!(
+            leader.replication_message(5).unwrap(),
+            RaftMessage::AppendEntries { prev_log_index: 4, prev_log_term: 1, .. }
+        ));
+        let message = leader.replication_message(1).unwrap();
+        assert!(matches!(message, RaftMessage::InstallSnapshot { last_included_index: 5, .. }));
+
+        let follower_id = Uuid::new_v4();
+        let follower = ConsensusService::new(follower_id, config(&follower_dir));
+        follower.recover().unwrap();
+        assert!(matches!(
+            follower.handle_message(leader.local_id, message),
+            Some(RaftMessage::InstallSnapshotReply { match_index: 5, .. })
+        ));
+        assert_eq!(follower.log.read().last_log_info(), (5, 1));
+
+        // The installed snapshot survives a restart
+        drop(follower);
+        let follower = ConsensusService::new(follower_id, config(&follower_dir));
+        follower.recover().unwrap();
+        assert_eq!(follower.latest_snapshot().unwrap().data, b"state@5");
+        assert_eq!(follower.log.read().last_log_info(), (5, 1));
+    }
+    #[test]
+    fn test_stale_append_keeps_later_entries() {
+        let dir = TempDir::new().unwrap();
+        let follower_id = Uuid::new_v4();
+        let follower = ConsensusService::new(follower_id, config(&dir));
+        follower.recover().unwrap();
+
+        let leader_id = Uuid::new_v4();
+        let append = |prev_log_index: u64, range: std::ops::RangeInclusive<u64>| {
+            RaftMessage::AppendEntries {
+                term: 1,
+                leader_id,
+                prev_log_index,
+                prev_log_term: if prev_log_index == 0 { 0 } else { 1 },
+                entries: range.map(|i| LogEntry::new(1, i, vec![i as u8])).collect(),
+                leader_commit: 0,
+            }
+        };
+
+        assert!(matches!(
+            follower.handle_message(leader_id, append(0, 1..=3)),
+            Some(RaftMessage::AppendEntriesReply { success: true, match_index: 3, .. })
+        ));
+
+        // A delayed copy of an earlier request only covers its own entries
+        assert!(matches!(
+            follower.handle_message(leader_id, append(0, 1..=1)),
+            Some(RaftMessage::AppendEntriesReply { success: true, match_index: 1, .. })
+        ));
+        assert_eq!(follower.log.read().last_log_info(), (3, 1));
+
+        // A conflicting term still replaces the tail
+        let conflict = RaftMessage::AppendEntries {
+            term: 2,
+            leader_id,
+            prev_log_index: 1,
+            prev_log_term: 1,
+            entries: vec![LogEntry::new(2, 2, vec![9])],
+            leader_commit: 0,
+        };
+        follower.handle_message(leader_id, conflict);
+        assert_eq!(follower.log.read().last_log_info(), (2, 2));
+
+        drop(follower);
+        let follower = ConsensusService::new(follower_id, config(&dir));
+        follower.recover().unwrap();
+        assert_eq!(follower.log.read().last_log_info(), (2, 2));
+        assert_eq!(follower.log.read().term_at(1), Some(1));
+    }
+}
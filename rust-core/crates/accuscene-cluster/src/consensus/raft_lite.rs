@@ -65,6 +65,21 @@ pub enum RaftMessage {
         success: bool,
         match_index: u64,
     },
+
+    /// Install snapshot, for followers behind the compacted log
+    InstallSnapshot {
+        term: u64,
+        leader_id: Uuid,
+        last_included_index: u64,
+        last_included_term: u64,
+        data: Vec<u8>,
+    },
+
+    /// Install snapshot response
+    InstallSnapshotReply {
+        term: u64,
+        match_index: u64,
+    },
 }
 
 /// Replicated log.
@@ -80,6 +95,12 @@ pub struct ReplicatedLog {
 
     /// Maximum log size
     max_size: usize,
+
+    /// Index of the last compacted entry
+    snapshot_index: u64,
+
+    /// Term of the last compacted entry
+    snapshot_term: u64,
 }
 
 impl ReplicatedLog {
@@ -90,9 +111,20 @@ impl ReplicatedLog {
             commit_index: 0,
             last_applied: 0,
             max_size,
+            snapshot_index: 0,
+            snapshot_term: 0,
         }
     }
 
+    /// Restore a log read back from storage.
+    pub fn restore(&mut self, snapshot_index: u64, snapshot_term: u64, entries: Vec<LogEntry>) {
+        self.entries = entries.into_iter().filter(|e| e.index > snapshot_index).collect();
+        self.snapshot_index = snapshot_index;
+        self.snapshot_term = snapshot_term;
+        self.commit_index = self.commit_index.max(snapshot_index);
+        self.last_applied = self.last_applied.max(snapshot_index);
+    }
+
     /// Append a new entry.
     pub fn append(&mut self, entry: LogEntry) -> u64 {
         let index = entry.index;
@@ -102,6 +134,8 @@ impl ReplicatedLog {
         while self.entries.len() > self.max_size {
             if let Some(first) = self.entries.front() {
                 if first.index < self.last_applied {
+                    self.snapshot_index = first.index;
+                    self.snapshot_term = first.term;
                     self.entries.pop_front();
                 } else {
                     break;
@@ -143,10 +177,54 @@ impl ReplicatedLog {
         if let Some(last) = self.entries.back() {
             (last.index, last.term)
         } else {
-            (0, 0)
+            (self.snapshot_index, self.snapshot_term)
+        }
+    }
+
+    /// Get the term of the entry at index, including the last compacted one.
+    pub fn term_at(&self, index: u64) -> Option<u64> {
+        if index == self.snapshot_index {
+            Some(self.snapshot_term)
+        } else {
+            self.get(index).map(|e| e.term)
         }
     }
 
+    /// Get index and term of the last compacted entry.
+    pub fn snapshot_info(&self) -> (u64, u64) {
+        (self.snapshot_index, self.snapshot_term)
+    }
+
+    /// Drop committed entries up to index, returning the term of the last
+    /// one dropped.
+    pub fn compact(&mut self, index: u64) -> Option<u64> {
+        if index <= self.snapshot_index || index > self.commit_index {
+            return None;
+        }
+
+        let term = self.term_at(index)?;
+        self.entries.retain(|e| e.index > index);
+        self.snapshot_index = index;
+        self.snapshot_term = term;
+        Some(term)
+    }
+
+    /// Replace the log up to a snapshot received from the leader.
+    ///
+    /// Entries after the snapshot are kept if the log agrees with it.
+    pub fn install_snapshot(&mut self, index: u64, term: u64) {
+        if self.term_at(index) == Some(term) {
+            self.entries.retain(|e| e.index > index);
+        } else {
+            self.entries.clear();
+        }
+
+        self.snapshot_index = index;
+        self.snapshot_term = term;
+        self.commit_index = self.commit_index.max(index);
+        self.last_applied = self.last_applied.max(index);
+    }
+
     /// Set commit index.
     pub fn set_commit_index(&mut self, index: u64) {
         if index > self.commit_index {
@@ -164,6 +242,11 @@ impl ReplicatedLog {
         self.last_applied = index;
     }
 
+    /// Get last applied index.
+    pub fn last_applied(&self) -> u64 {
+        self.last_applied
+    }
+
     /// Get unapplied entries.
     pub fn unapplied_entries(&self) -> Vec<LogEntry> {
         self.entries
@@ -254,6 +337,21 @@ impl NextIndexTracker {
         }
     }
 
+    /// Check if a follower is tracked.
+    pub fn has_follower(&self, follower_id: &Uuid) -> bool {
+        self.match_index.contains_key(follower_id)
+    }
+
+    /// Get each follower with the next index to send it.
+    pub fn next_indices(&self) -> impl Iterator<Item = (Uuid, u64)> + '_ {
+        self.next_index.iter().map(|(id, next)| (*id, *next))
+    }
+
+    /// Get the number of followers.
+    pub fn follower_count(&self) -> usize {
+        self.match_index.len()
+    }
+
     /// Remove follower.
     pub fn remove_follower(&mut self, follower_id: &Uuid) {
         self.next_index.remove(follower_id);
@@ -291,6 +389,30 @@ mod tests {
         assert_eq!(log.commit_index(), 2);
     }
 
+    #[test]
+    fn test_log_compaction() {
+        let mut log = ReplicatedLog::new(100);
+        for index in 1..=5 {
+            log.append(LogEntry::new(1, index, vec![]));
+        }
+
+        // Only committed entries can be compacted
+        assert_eq!(log.compact(3), None);
+        log.set_commit_index(4);
+        assert_eq!(log.compact(3), Some(1));
+
+        assert_eq!(log.len(), 2);
+        assert_eq!(log.snapshot_info(), (3, 1));
+        assert_eq!(log.term_at(3), Some(1));
+        assert_eq!(log.term_at(2), None);
+
+        // A snapshot the log disagrees with replaces it entirely
+        log.install_snapshot(6, 2);
+        assert!(log.is_empty());
+        assert_eq!(log.last_log_info(), (6, 2));
+        assert_eq!(log.commit_index(), 6);
+    }
+
     #[test]
     fn test_next_index_tracker() {
         let mut tracker = NextIndexTracker::new();
@@ -0,0 +1,405 @@
+//! Durable Raft log and snapshot storage.
+//!
+//! Log entries, truncations and the current term and vote are appended to
+//! a record log (`raft.wal`) and synced before the node acts on them. Each
+//! record is framed as `[len: u32][crc32: u32][bincode]`; on startup the log
+//! is replayed up to the first torn or corrupt record, and that tail is cut
+//! off. Snapshots are written to `snapshot.bin` with a checksum. Compaction
+//! rewrites the log with only the entries after the compaction point and
+//! swaps it in with a rename, so a crash leaves either the old or the new
+//! log in place.
+
+use crate::consensus::raft_lite::LogEntry;
+use crate::error::{ClusterError, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Record log file name.
+const WAL_FILE: &str = "raft.wal";
+
+/// Snapshot file name.
+const SNAPSHOT_FILE: &str = "snapshot.bin";
+
+/// Length and checksum prefix of each record.
+const FRAME_HEADER_LEN: usize = 8;
+
+/// Term and vote that must survive restarts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HardState {
+    /// Current term
+    pub term: u64,
+
+    /// Candidate voted for in the current term
+    pub voted_for: Option<Uuid>,
+}
+
+/// Snapshot of the state machine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Index of the last entry the snapshot covers
+    pub last_included_index: u64,
+
+    /// Term of the last entry the snapshot covers
+    pub last_included_term: u64,
+
+    /// Serialized state machine
+    pub data: Vec<u8>,
+
+    /// Creation timestamp
+    pub created_at: i64,
+}
+
+impl Snapshot {
+    /// Create a new snapshot.
+    pub fn new(last_included_index: u64, last_included_term: u64, data: Vec<u8>) -> Self {
+        Self {
+            last_included_index,
+            last_included_term,
+            data,
+            created_at: chrono::Utc::now().timestamp(),
+        }
+    }
+}
+
+/// State read back from storage on startup.
+#[derive(Debug, Default)]
+pub struct RecoveredState {
+    /// Saved term and vote
+    pub hard_state: HardState,
+
+    /// Latest snapshot
+    pub snapshot: Option<Snapshot>,
+
+    /// Index and term of the last compacted entry
+    pub compacted: (u64, u64),
+
+    /// Entries after the compaction point
+    pub entries: Vec<LogEntry>,
+}
+
+/// Record appended to the log.
+#[derive(Debug, Serialize, Deserialize)]
+enum Record {
+    Entry(LogEntry),
+    TruncateFrom(u64),
+    HardState(HardState),
+    Compacted { index: u64, term: u64 },
+}
+
+/// Durable storage for one node's Raft state.
+pub struct RaftStorage {
+    /// Storage directory
+    dir: PathBuf,
+
+    /// Record log, positioned at its end
+    wal: BufWriter<File>,
+
+    /// Last saved term and vote
+    hard_state: HardState,
+}
+
+impl RaftStorage {
+    /// Open storage in a directory, reading back what it holds.
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<(Self, RecoveredState)> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        let mut recovered = RecoveredState {
+            snapshot: read_snapshot(&dir.join(SNAPSHOT_FILE))?,
+            ..RecoveredState::default()
+        };
+
+        let path = dir.join(WAL_FILE);
+        for record in replay(&path)? {
+            match record {
+                Record::Entry(entry) => {
+                    if entry.index > recovered.compacted.0 {
+                        recovered.entries.retain(|e| e.index < entry.index);
+                        recovered.entries.push(entry);
+                    }
+                }
+                Record::TruncateFrom(index) => recovered.entries.retain(|e| e.index < index),
+                Record::HardState(hard_state) => recovered.hard_state = hard_state,
+                Record::Compacted { index, term } => {
+                    recovered.entries.retain(|e| e.index > index);
+                    recovered.compacted = (index, term);
+                }
+            }
+        }
+
+        let storage = Self {
+            dir,
+            wal: open_log(&path)?,
+            hard_state: recovered.hard_state,
+        };
+
+        Ok((storage, recovered))
+    }
+
+    /// Storage directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Durably append entries.
+    pub fn append(&mut self, entries: &[LogEntry]) -> Result<()> {
+        for entry in entries {
+            write_record(&mut self.wal, &Record::Entry(entry.clone()))?;
+        }
+        sync(&mut self.wal)?;
+        Ok(())
+    }
+
+    /// Durably drop entries from `index` on.
+    pub fn truncate_from(&mut self, index: u64) -> Result<()> {
+        write_record(&mut self.wal, &Record::TruncateFrom(index))?;
+        sync(&mut self.wal)?;
+        Ok(())
+    }
+
+    /// Durably save the term and vote, if they changed.
+    pub fn save_hard_state(&mut self, hard_state: HardState) -> Result<()> {
+        if hard_state == self.hard_state {
+            return Ok(());
+        }
+
+        write_record(&mut self.wal, &Record::HardState(hard_state))?;
+        sync(&mut self.wal)?;
+        self.hard_state = hard_state;
+        Ok(())
+    }
+
+    /// Durably replace the snapshot.
+    pub fn save_snapshot(&mut self, snapshot: &Snapshot) -> Result<()> {
+        let data = bincode::serialize(snapshot)?;
+        let path = self.dir.join(SNAPSHOT_FILE);
+        let tmp = path.with_extension("tmp");
+
+        let mut file = File::create(&tmp)?;
+        file.write_all(&crc32fast::hash(&data).to_le_bytes())?;
+        file.write_all(&data)?;
+        file.sync_all()?;
+        fs::rename(&tmp, &path)?;
+
+        Ok(())
+    }
+
+    /// Rewrite the log without the entries up to `index`.
+    ///
+    /// `retained` are the entries after `index` that stay in the log.
+    pub fn compact(&mut self, index: u64, term: u64, retained: &[LogEntry]) -> Result<()> {
+        let path = self.dir.join(WAL_FILE);
+        let tmp = path.with_extension("compact");
+        if tmp.exists() {
+            fs::remove_file(&tmp)?;
+        }
+
+        let mut compacted = BufWriter::new(File::create(&tmp)?);
+        write_record(&mut compacted, &Record::Compacted { index, term })?;
+        write_record(&mut compacted, &Record::HardState(self.hard_state))?;
+        for entry in retained {
+            write_record(&mut compacted, &Record::Entry(entry.clone()))?;
+        }
+        sync(&mut compacted)?;
+        drop(compacted);
+
+        fs::rename(&tmp, &path)?;
+        self.wal = open_log(&path)?;
+
+        Ok(())
+    }
+
+    /// Size of the record log in bytes.
+    pub fn wal_size(&self) -> Result<u64> {
+        Ok(self.wal.get_ref().metadata()?.len())
+    }
+}
+
+/// Open the record log for appending.
+fn open_log(path: &Path) -> Result<BufWriter<File>> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    Ok(BufWriter::new(file))
+}
+
+fn write_record(log: &mut BufWriter<File>, record: &Record) -> Result<()> {
+    let data = bincode::serialize(record)?;
+    let len = u32::try_from(data.len())
+        .map_err(|_| ClusterError::Storage("Raft record too large".to_string()))?;
+
+    log.write_all(&len.to_le_bytes())?;
+    log.write_all(&crc32fast::hash(&data).to_le_bytes())?;
+    log.write_all(&data)?;
+    Ok(())
+}
+
+/// Flush buffered records and sync them to disk.
+fn sync(log: &mut BufWriter<File>) -> Result<()> {
+    log.flush()?;
+    log.get_ref().sync_data()?;
+    Ok(())
+}
+
+/// Read every intact record, cutting off a torn or corrupt tail.
+fn replay(path: &Path) -> Result<Vec<Record>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut bytes = Vec::new();
+    File::open(path)?.read_to_end(&mut bytes)?;
+
+    let mut records = Vec::new();
+    let mut offset = 0;
+    while let Some(header) = bytes.get(offset..offset + FRAME_HEADER_LEN) {
+        let len = u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let checksum = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+
+        let start = offset + FRAME_HEADER_LEN;
+        let Some(data) = bytes.get(start..start + len) else {
+            break;
+        };
+        if crc32fast::hash(data) != checksum {
+            break;
+        }
+        let Ok(record) = bincode::deserialize(data) else {
+            break;
+        };
+
+        records.push(record);
+        offset = start + len;
+    }
+
+    if offset < bytes.len() {
+        tracing::warn!(
+            "Discarding {} bytes of torn Raft log tail in {}",
+            bytes.len() - offset,
+            path.display()
+        );
+        OpenOptions::new().write(true).open(path)?.set_len(offset as u64)?;
+    }
+
+    Ok(records)
+}
+
+fn read_snapshot(path: &Path) -> Result<Option<Snapshot>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let bytes = fs::read(path)?;
+    if bytes.len() < 4 {
+        return Err(ClusterError::ChecksumMismatch);
+    }
+
+    let (checksum, data) = bytes.split_at(4);
+    if crc32fast::hash(data).to_le_bytes() != checksum {
+        return Err(ClusterError::ChecksumMismatch);
+    }
+
+    Ok(Some(bincode::deserialize(data)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entries(range: std::ops::RangeInclusive<u64>, term: u64) -> Vec<LogEntry> {
+        range.map(|i| LogEntry::new(term, i, vec![i as u8])).collect()
+    }
+
+    #[test]
+    fn test_log_survives_reopen() {
+        let dir = TempDir::new().unwrap();
+        let vote = HardState {
+            term: 2,
+            voted_for: Some(Uuid::new_v4()),
+        };
+
+        {
+            let (mut storage, recovered) = RaftStorage::open(dir.path()).unwrap();
+            assert!(recovered.entries.is_empty());
+
+            storage.append(&entries(1..=5, 1)).unwrap();
+            storage.truncate_from(4).unwrap();
+            storage.append(&entries(4..=4, 2)).unwrap();
+            storage.save_hard_state(vote).unwrap();
+        }
+
+        let (_, recovered) = RaftStorage::open(dir.path()).unwrap();
+        assert_eq!(recovered.hard_state, vote);
+        let indexes: Vec<_> = recovered.entries.iter().map(|e| (e.index, e.term)).collect();
+        assert_eq!(indexes, vec![(1, 1), (2, 1), (3, 1), (4, 2)]);
+    }
+
+    #[test]
+    fn test_snapshot_and_compaction() {
+        let dir = TempDir::new().unwrap();
+
+        {
+            let (mut storage, _) = RaftStorage::open(dir.path()).unwrap();
+            storage.append(&entries(1..=10, 1)).unwrap();
+            storage.save_hard_state(HardState { term: 1, voted_for: None }).unwrap();
+
+            let size = storage.wal_size().unwrap();
+            storage.save_snapshot(&Snapshot::new(8, 1, b"state".to_vec())).unwrap();
+            storage.compact(8, 1, &entries(9..=10, 1)).unwrap();
+            assert!(storage.wal_size().unwrap() < size);
+
+            storage.append(&entries(11..=11, 1)).unwrap();
+        }
+
+        let (_, recovered) = RaftStorage::open(dir.path()).unwrap();
+        assert_eq!(recovered.compacted, (8, 1));
+        assert_eq!(recovered.hard_state.term, 1);
+        assert_eq!(recovered.entries.iter().map(|e| e.index).collect::<Vec<_>>(), vec![9, 10, 11]);
+
+        let snapshot = recovered.snapshot.unwrap();
+        assert_eq!(snapshot.last_included_index, 8);
+        assert_eq!(snapshot.data, b"state");
+    }
+
+    #[test]
+    fn test_torn_tail_discarded() {
+        let dir = TempDir::new().unwrap();
+        {
+            let (mut storage, _) = RaftStorage::open(dir.path()).unwrap();
+            storage.append(&entries(1..=3, 1)).unwrap();
+        }
+
+        let path = dir.path().join(WAL_FILE);
+        let mut bytes = fs::read(&path).unwrap();
+        bytes.truncate(bytes.len() - 2);
+        fs::write(&path, bytes).unwrap();
+
+        {
+            let (mut storage, recovered) = RaftStorage::open(dir.path()).unwrap();
+            assert_eq!(recovered.entries.iter().map(|e| e.index).collect::<Vec<_>>(), vec![1, 2]);
+            storage.append(&entries(3..=3, 2)).unwrap();
+        }
+
+        let (_, recovered) = RaftStorage::open(dir.path()).unwrap();
+        let indexes: Vec<_> = recovered.entries.iter().map(|e| (e.index, e.term)).collect();
+        assert_eq!(indexes, vec![(1, 1), (2, 1), (3, 2)]);
+    }
+
+    #[test]
+    fn test_corrupt_snapshot_rejected() {
+        let dir = TempDir::new().unwrap();
+        {
+            let (mut storage, _) = RaftStorage::open(dir.path()).unwrap();
+            storage.save_snapshot(&Snapshot::new(3, 1, b"state".to_vec())).unwrap();
+        }
+
+        let path = dir.path().join(SNAPSHOT_FILE);
+        let mut bytes = fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        fs::write(&path, bytes).unwrap();
+
+        assert!(matches!(RaftStorage::open(dir.path()), Err(ClusterError::ChecksumMismatch)));
+    }
+}
@@ -6,16 +6,39 @@ use crate::discovery::DiscoveryService;
 use crate::error::Result;
 use crate::failover::{FailoverConfig, FailoverManager};
 use crate::load_balancing::{LoadBalancer, LoadBalancingStrategy};
+use crate::lock::{LockManager, LOCK_RPC_METHOD};
 use crate::membership::MembershipService;
+#[cfg(feature = "tls")]
+use crate::messaging::ClusterTls;
 use crate::messaging::RpcService;
 use crate::node::{Node, NodeId, NodeRegistry, NodeState};
-use crate::partitioning::PartitioningService;
+use crate::partitioning::{PartitioningService, RebalancePlan, RebalanceProgress, Rebalancer};
 use crate::replication::ReplicationService;
+use serde::Serialize;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::info;
+use tracing::{info, warn};
 use uuid::Uuid;
 
+/// Cluster metrics.
+#[derive(Debug, Clone, Serialize)]
+pub struct ClusterMetrics {
+    /// Members in the membership view
+    pub cluster_size: usize,
+
+    /// Active members
+    pub active_nodes: usize,
+
+    /// Nodes on the partition ring
+    pub partition_nodes: usize,
+
+    /// Whether this node leads the cluster
+    pub is_leader: bool,
+
+    /// Progress of the current or last partition rebalance
+    pub rebalance: RebalanceProgress,
+}
+
 /// Cluster coordinator managing all distributed components.
 pub struct ClusterCoordinator {
     /// Local node identity
@@ -39,12 +62,22 @@ pub struct ClusterCoordinator {
     /// Replication service
     replication: Arc<ReplicationService>,
 
+    /// Named locks and leases
+    locks: Arc<LockManager>,
+
     /// Partitioning service
     partitioning: Arc<RwLock<PartitioningService>>,
 
+    /// Moves data when partition ownership changes
+    rebalancer: Arc<Rebalancer>,
+
     /// RPC service
     rpc: Arc<RpcService>,
 
+    /// Mutual TLS for connections between nodes
+    #[cfg(feature = "tls")]
+    tls: Option<Arc<ClusterTls>>,
+
     /// Load balancer
     load_balancer: Arc<LoadBalancer>,
 
@@ -76,6 +109,7 @@ impl ClusterCoordinator {
 
         let consensus = Arc::new(ConsensusService::new(local_id, config.consensus.clone()));
 
+
         let replication = Arc::new(ReplicationService::new(
             local_id,
             config.replication.clone(),
@@ -86,8 +120,18 @@ impl ClusterCoordinator {
             config.replication.replication_factor,
         )));
 
+        let rebalancer = Arc::new(Rebalancer::new(
+            local_id,
+            Arc::clone(&replication),
+            &config.replication,
+        ));
+
         let rpc = Arc::new(RpcService::new(local_id));
 
+        let locks = Arc::new(
+            LockManager::new(local_id, Arc::clone(&consensus)).with_rpc(Arc::clone(&rpc)),
+        );
+
         let load_balancer = Arc::new(LoadBalancer::new(LoadBalancingStrategy::LeastConnections));
 
         let failover_config = FailoverConfig::default();
@@ -105,14 +149,27 @@ impl ClusterCoordinator {
             membership,
             consensus,
             replication,
+            locks,
             partitioning,
+            rebalancer,
             rpc,
+            #[cfg(feature = "tls")]
+            tls: None,
             load_balancer,
             failover,
             running: Arc::new(RwLock::new(false)),
         }
     }
 
+    /// Secure connections between nodes with mutual TLS.
+    ///
+    /// Only nodes that have joined the cluster may connect.
+    #[cfg(feature = "tls")]
+    pub fn with_tls(mut self, tls: Arc<ClusterTls>) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
     /// Start the cluster coordinator.
     pub async fn start(&self) -> Result<()> {
         let mut running = self.running.write().await;
@@ -124,6 +181,11 @@ impl ClusterCoordinator {
 
         info!("Starting cluster coordinator for node {}", self.local_node.id);
 
+        // Restore the durable consensus log before taking part in elections
+        self.consensus.recover()?;
+        self.consensus.serve_rpc(&self.rpc);
+        self.consensus.start_replication();
+
         // Register local node
         let local = Node::new(self.local_node.clone());
         self.registry.upsert(local.clone());
@@ -145,6 +207,10 @@ impl ClusterCoordinator {
         self.failover.read().await.start().await?;
         info!("Failover manager started");
 
+        // Start anti-entropy
+        self.replication.start().await?;
+        info!("Replication service started");
+
         // Add local node to partitioning
         self.partitioning.write().await.add_node(self.local_node.id);
         info!("Partitioning service initialized");
@@ -178,6 +244,10 @@ impl ClusterCoordinator {
         // Stop failover
         self.failover.read().await.stop().await;
 
+        // Stop anti-entropy and log replication
+        self.replication.stop().await;
+        self.consensus.stop();
+
         info!("Cluster coordinator stopped");
 
         Ok(())
@@ -277,6 +347,39 @@ impl ClusterCoordinator {
         &self.load_balancer
     }
 
+    /// Get cluster TLS, if enabled.
+    #[cfg(feature = "tls")]
+    pub fn tls(&self) -> Option<&ClusterTls> {
+        self.tls.as_deref()
+    }
+
+    /// Get lock manager.
+    pub fn locks(&self) -> &LockManager {
+        &self.locks
+    }
+
+    /// Get partition rebalancer.
+    pub fn rebalancer(&self) -> &Rebalancer {
+        &self.rebalancer
+    }
+
+    /// Get cluster metrics.
+    pub async fn metrics(&self) -> ClusterMetrics {
+        let (cluster_size, active_nodes) = {
+            let membership = self.membership.read().await;
+            let view = membership.view();
+            (view.count(), view.count_active())
+        };
+
+        ClusterMetrics {
+            cluster_size,
+            active_nodes,
+            partition_nodes: self.partitioning.read().await.node_count(),
+            is_leader: self.consensus.is_leader(),
+            rebalance: self.rebalancer.progress(),
+        }
+    }
+
     /// Register RPC handlers.
     fn register_rpc_handlers(&self) {
         use crate::messaging::{RpcRequest, RpcResponse};
@@ -291,6 +394,12 @@ impl ClusterCoordinator {
             RpcResponse::success(&"pong")
         });
 
+        // Lock commands forwarded by followers
+        let locks = Arc::clone(&self.locks);
+        self.rpc.register(LOCK_RPC_METHOD, move |req: RpcRequest| {
+            locks.handle_forwarded(req)
+        });
+
         // Add more handlers as needed
     }
 
@@ -301,12 +410,30 @@ impl ClusterCoordinator {
         // Add to registry
         self.registry.upsert(node.clone());
 
-        // Add to partitioning
-        self.partitioning.write().await.add_node(node.id.id);
+        // Accept its connections
+        #[cfg(feature = "tls")]
+        if let Some(tls) = &self.tls {
+            tls.allow_peer(node.id.id);
+        }
+
+        // Add to partitioning and move the keys it now owns
+        let plan = {
+            let mut partitioning = self.partitioning.write().await;
+            let before = partitioning.clone();
+            partitioning.add_node(node.id.id);
+            self.rebalancer.plan(&before, &partitioning)
+        };
+        self.spawn_rebalance(plan);
 
         // Add to load balancer
         self.load_balancer.add_node(node.id.id, 1);
 
+        // Exchange anti-entropy digests with it
+        self.replication.add_peer(node.id.id);
+
+        // Replicate the consensus log to it
+        self.consensus.add_peer(node.id.id);
+
         Ok(())
     }
 
@@ -320,12 +447,38 @@ impl ClusterCoordinator {
             self.registry.upsert(node);
         }
 
-        // Remove from partitioning
-        self.partitioning.write().await.remove_node(&node_id);
+        // Remove from partitioning and re-replicate its keys
+        let plan = {
+            let mut partitioning = self.partitioning.write().await;
+            let before = partitioning.clone();
+            partitioning.remove_node(&node_id);
+            self.rebalancer.plan(&before, &partitioning)
+        };
+        self.spawn_rebalance(plan);
 
         // Remove from load balancer
         self.load_balancer.remove_node(&node_id);
 
+        // Stop anti-entropy with it
+        self.replication.remove_peer(&node_id);
+
+        // Stop waiting on it for commits and free the locks it held
+        self.consensus.remove_peer(&node_id);
+        if self.consensus.is_leader() {
+            let locks = Arc::clone(&self.locks);
+            tokio::spawn(async move {
+                if let Err(e) = locks.release_node(node_id).await {
+                    warn!("Failed to release locks of {}: {}", node_id, e);
+                }
+            });
+        }
+
+        // Refuse new connections from it
+        #[cfg(feature = "tls")]
+        if let Some(tls) = &self.tls {
+            tls.revoke_peer(&node_id);
+        }
+
         // Eventually remove from registry
         self.registry.remove(&node_id);
 
@@ -339,12 +492,33 @@ impl ClusterCoordinator {
         let active_nodes = self.active_nodes().await;
 
         // Rebalance partitions if using range partitioning
-        if let Some(range_map) = self.partitioning.write().await.range_map_mut() {
-            range_map.rebalance(&active_nodes);
-        }
+        let plan = {
+            let mut partitioning = self.partitioning.write().await;
+            let before = partitioning.clone();
+            match partitioning.range_map_mut() {
+                Some(range_map) => range_map.rebalance(&active_nodes),
+                None => return Ok(()),
+            }
+            self.rebalancer.plan(&before, &partitioning)
+        };
+        self.spawn_rebalance(plan);
 
         Ok(())
     }
+
+    /// Move data for a partition change in the background.
+    fn spawn_rebalance(&self, plan: RebalancePlan) {
+        if plan.is_empty() {
+            return;
+        }
+
+        let rebalancer = Arc::clone(&self.rebalancer);
+        tokio::spawn(async move {
+            if let Err(e) = rebalancer.run(plan).await {
+                warn!("Partition rebalance failed: {}", e);
+            }
+        });
+    }
 }
 
 #[cfg(test)]
@@ -367,4 +541,28 @@ mod tests {
         coordinator.stop().await.unwrap();
         assert!(!coordinator.is_running().await);
     }
+
+    #[tokio::test]
+    async fn test_join_rebalances_partitions() {
+        let local_node = NodeId::new("127.0.0.1:7946".parse::<SocketAddr>().unwrap());
+        let coordinator = ClusterCoordinator::new(local_node, ClusterConfig::default());
+        let mut outbound = coordinator.rebalancer().take_outbound().unwrap();
+
+        coordinator.partitioning.write().await.add_node(coordinator.local_id());
+        for i in 0..20 {
+            coordinator.write(format!("case-{}", i), vec![i]).await.unwrap();
+        }
+
+        let joining = Node::new(NodeId::new("127.0.0.1:7947".parse::<SocketAddr>().unwrap()));
+        let joining_id = joining.id.id;
+        coordinator.handle_node_join(joining).await.unwrap();
+
+        // With three replicas and two nodes the new node owns every key
+        let (target, _) = outbound.recv().await.unwrap();
+        assert_eq!(target, joining_id);
+
+        let rebalance = coordinator.metrics().await.rebalance;
+        assert_eq!(rebalance.keys_total, 20);
+        assert_eq!(coordinator.metrics().await.partition_nodes, 2);
+    }
 }
@@ -2,13 +2,16 @@
 
 use crate::error::Result;
 use crate::node::NodeId;
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::net::UdpSocket;
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
 use tokio::time;
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 
 /// Broadcast discovery message.
@@ -46,6 +49,12 @@ pub struct BroadcastDiscovery {
 
     /// Running flag
     running: Arc<RwLock<bool>>,
+
+    /// Cancelled on stop or by an enclosing shutdown scope
+    shutdown: CancellationToken,
+
+    /// Spawned background tasks
+    tasks: Arc<Mutex<Vec<(String, JoinHandle<()>)>>>,
 }
 
 impl BroadcastDiscovery {
@@ -63,9 +72,22 @@ impl BroadcastDiscovery {
             interval,
             discovered: Arc::new(RwLock::new(Vec::new())),
             running: Arc::new(RwLock::new(false)),
+            shutdown: CancellationToken::new(),
+            tasks: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
+    /// Stop background tasks when the given token is cancelled
+    pub fn with_shutdown_token(mut self, token: CancellationToken) -> Self {
+        self.shutdown = token;
+        self
+    }
+
+    /// Hand over the spawned task handles so a shutdown scope can await them.
+    pub fn take_task_handles(&self) -> Vec<(String, JoinHandle<()>)> {
+        std::mem::take(&mut *self.tasks.lock())
+    }
+
     /// Start discovery service.
     pub async fn start(&self) -> Result<()> {
         let mut running = self.running.write().await;
@@ -79,7 +101,7 @@ impl BroadcastDiscovery {
 
         // Start broadcaster
         let broadcaster = self.clone_for_task();
-        tokio::spawn(async move {
+        let broadcast = tokio::spawn(async move {
             if let Err(e) = broadcaster.broadcast_loop().await {
                 error!("Broadcast loop error: {}", e);
             }
@@ -87,19 +109,27 @@ impl BroadcastDiscovery {
 
         // Start listener
         let listener = self.clone_for_task();
-        tokio::spawn(async move {
+        let listen = tokio::spawn(async move {
             if let Err(e) = listener.listen_loop().await {
                 error!("Listen loop error: {}", e);
             }
         });
 
+        self.tasks.lock().extend([
+            ("broadcast".to_string(), broadcast),
+            ("listen".to_string(), listen),
+        ]);
+
         Ok(())
     }
 
     /// Stop discovery service.
+    ///
+    /// Also interrupts a pending receive; the service cannot be restarted.
     pub async fn stop(&self) {
         let mut running = self.running.write().await;
         *running = false;
+        self.shutdown.cancel();
         info!("Stopped broadcast discovery");
     }
 
@@ -128,7 +158,10 @@ impl BroadcastDiscovery {
         let mut interval = time::interval(self.interval);
 
         loop {
-            interval.tick().await;
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = self.shutdown.cancelled() => break,
+            }
 
             let running = self.running.read().await;
             if !*running {
@@ -172,7 +205,12 @@ impl BroadcastDiscovery {
             }
             drop(running);
 
-            match socket.recv_from(&mut buf).await {
+            let received = tokio::select! {
+                received = socket.recv_from(&mut buf) => received,
+                _ = self.shutdown.cancelled() => break,
+            };
+
+            match received {
                 Ok((len, addr)) => {
                     if let Err(e) = self.handle_message(&buf[..len], addr).await {
                         debug!("Failed to handle discovery message: {}", e);
@@ -233,6 +271,8 @@ impl BroadcastDiscovery {
             interval: self.interval,
             discovered: Arc::clone(&self.discovered),
             running: Arc::clone(&self.running),
+            shutdown: self.shutdown.clone(),
+            tasks: Arc::clone(&self.tasks),
         }
     }
 }
@@ -68,6 +68,18 @@ pub enum ClusterError {
 
     #[error("Timeout: {0}")]
     Timeout(String),
+
+    #[error("Storage error: {0}")]
+    Storage(String),
+
+    #[error("TLS error: {0}")]
+    Tls(String),
+
+    #[error("Lock {name} is held by {owner}")]
+    LockHeld { name: String, owner: Uuid },
+
+    #[error("Lock not held: {0}")]
+    LockNotHeld(String),
 }
 
 impl From<bincode::Error> for ClusterError {
@@ -81,3 +93,31 @@ impl From<serde_json::Error> for ClusterError {
         ClusterError::Serialization(err.to_string())
     }
 }
+
+#[cfg(feature = "tls")]
+impl From<accuscene_crypto::CryptoError> for ClusterError {
+    fn from(err: accuscene_crypto::CryptoError) -> Self {
+        ClusterError::Tls(err.to_string())
+    }
+}
+
+#[cfg(feature = "tls")]
+impl From<rustls::Error> for ClusterError {
+    fn from(err: rustls::Error) -> Self {
+        ClusterError::Tls(err.to_string())
+    }
+}
+
+#[cfg(feature = "grpc")]
+impl From<tonic::Status> for ClusterError {
+    fn from(status: tonic::Status) -> Self {
+        ClusterError::Network(status.to_string())
+    }
+}
+
+#[cfg(feature = "grpc")]
+impl From<tonic::transport::Error> for ClusterError {
+    fn from(err: tonic::transport::Error) -> Self {
+        ClusterError::Network(err.to_string())
+    }
+}
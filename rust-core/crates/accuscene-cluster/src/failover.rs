@@ -3,11 +3,13 @@
 use crate::consensus::LeaderElection;
 use crate::error::Result;
 use chrono::{DateTime, Utc};
-use parking_lot::RwLock as ParkingRwLock;
+use parking_lot::{Mutex, RwLock as ParkingRwLock};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{mpsc, RwLock};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
 /// Failover event types.
@@ -112,6 +114,12 @@ pub struct FailoverManager {
 
     /// Running flag
     running: Arc<RwLock<bool>>,
+
+    /// Cancelled on stop or by an enclosing shutdown scope
+    shutdown: CancellationToken,
+
+    /// Spawned background tasks
+    tasks: Arc<Mutex<Vec<(String, JoinHandle<()>)>>>,
 }
 
 impl FailoverManager {
@@ -131,9 +139,22 @@ impl FailoverManager {
             event_tx,
             event_rx: Arc::new(RwLock::new(event_rx)),
             running: Arc::new(RwLock::new(false)),
+            shutdown: CancellationToken::new(),
+            tasks: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
+    /// Stop background tasks when the given token is cancelled
+    pub fn with_shutdown_token(mut self, token: CancellationToken) -> Self {
+        self.shutdown = token;
+        self
+    }
+
+    /// Hand over the spawned task handles so a shutdown scope can await them.
+    pub fn take_task_handles(&self) -> Vec<(String, JoinHandle<()>)> {
+        std::mem::take(&mut *self.tasks.lock())
+    }
+
     /// Start failover manager.
     pub async fn start(&self) -> Result<()> {
         let mut running = self.running.write().await;
@@ -147,17 +168,21 @@ impl FailoverManager {
 
         // Start monitoring task
         let manager = self.clone_for_task();
-        tokio::spawn(async move {
+        let monitor = tokio::spawn(async move {
             manager.monitor_task().await;
         });
+        self.tasks.lock().push(("monitor".to_string(), monitor));
 
         Ok(())
     }
 
     /// Stop failover manager.
+    ///
+    /// The monitor task exits at its next tick; the manager cannot be restarted.
     pub async fn stop(&self) {
         let mut running = self.running.write().await;
         *running = false;
+        self.shutdown.cancel();
         tracing::info!("Stopped failover manager");
     }
 
@@ -265,7 +290,10 @@ impl FailoverManager {
         let mut interval = tokio::time::interval(Duration::from_secs(5));
 
         loop {
-            interval.tick().await;
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = self.shutdown.cancelled() => break,
+            }
 
             let running = self.running.read().await;
             if !*running {
@@ -312,6 +340,8 @@ impl FailoverManager {
             event_tx: self.event_tx.clone(),
             event_rx: Arc::clone(&self.event_rx),
             running: Arc::clone(&self.running),
+            shutdown: self.shutdown.clone(),
+            tasks: Arc::clone(&self.tasks),
         }
     }
 }
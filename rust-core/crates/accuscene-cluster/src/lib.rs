@@ -7,12 +7,14 @@
 //!
 //! - **Node Discovery**: Static configuration and UDP broadcast-based discovery
 //! - **Membership Management**: SWIM-like gossip protocol for failure detection
-//! - **Consensus**: Raft-lite consensus protocol with leader election
-//! - **Data Replication**: Multi-strategy replication with conflict resolution
-//! - **Partitioning**: Consistent hashing and range-based data partitioning
+//! - **Consensus**: Raft-lite consensus protocol with leader election, a durable log and snapshots
+//! - **Data Replication**: Multi-strategy replication with conflict resolution, anti-entropy and read repair
+//! - **Partitioning**: Consistent hashing and range-based data partitioning with throttled rebalancing
+//! - **Locks**: Leader-coordinated named leases with fencing tokens, backed by the consensus log
 //! - **Failover**: Automatic failover with configurable strategies
 //! - **Load Balancing**: Multiple strategies including round-robin and least connections
-//! - **RPC**: Binary messaging protocol for inter-node communication
+//! - **RPC**: Binary messaging protocol for inter-node communication, with mutually
+//!   authenticated TLS behind the `tls` feature and a gRPC transport behind the `grpc` feature
 //!
 //! # Example
 //!
@@ -51,6 +53,7 @@ pub mod discovery;
 pub mod error;
 pub mod failover;
 pub mod load_balancing;
+pub mod lock;
 pub mod membership;
 pub mod messaging;
 pub mod node;
@@ -60,21 +63,26 @@ pub mod replication;
 // Re-export primary types
 pub use config::{ClusterConfig, ConsistencyLevel, ConflictResolutionStrategy};
 pub use consensus::{ConsensusService, LeaderElection, LeaderInfo, LeaderState};
-pub use coordinator::ClusterCoordinator;
+pub use coordinator::{ClusterCoordinator, ClusterMetrics};
 pub use discovery::{BroadcastDiscovery, DiscoveryService, StaticDiscovery};
 pub use error::{ClusterError, Result};
 pub use failover::{FailoverConfig, FailoverEvent, FailoverManager, FailoverStrategy};
 pub use load_balancing::{LoadBalancer, LoadBalancingStrategy, NodeLoadMetrics};
+pub use lock::{Lease, LockManager};
 pub use membership::{GossipMessage, MembershipService, MembershipView};
-pub use messaging::{Message, MessageType, RpcService};
+pub use messaging::{Message, MessageType, RpcService, WireProtocol};
+#[cfg(feature = "tls")]
+pub use messaging::{ClusterTls, NodeIdentity};
 pub use node::{
     HealthStatus, Node, NodeCapabilities, NodeId, NodeRegistry, NodeState, NodeVersion,
 };
 pub use partitioning::{
     ConsistentHashRing, PartitioningService, PartitioningStrategy, RangePartition,
+    RebalanceProgress, Rebalancer,
 };
 pub use replication::{
-    ConflictResolution, ConflictResolver, ReplicationService, StateSnapshot, VectorClock,
+    ConflictResolution, ConflictResolver, MerkleTree, QuorumRead, ReplicaRead, ReplicationService,
+    StateSnapshot, VectorClock,
 };
 
 /// Cluster version information.
@@ -94,6 +102,6 @@ mod tests {
 
     #[test]
     fn test_protocol_version() {
-        assert_eq!(PROTOCOL_VERSION, 1);
+        assert_eq!(PROTOCOL_VERSION, 2);
     }
 }
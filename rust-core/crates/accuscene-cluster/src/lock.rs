@@ -0,0 +1,619 @@
+//! Distributed locks and leases.
+//!
+//! Leases are granted through the consensus log: the leader proposes each
+//! acquire, renew and release, and every node applies the committed
+//! commands in log order to the same lease table. Commands made on a
+//! follower are forwarded to the leader over RPC. A lease's fencing token
+//! is the log index of the command that granted it, so tokens only grow;
+//! resources guarded by a lock should refuse writes carrying a token lower
+//! than the last one they accepted.
+//!
+//! Leases expire after their TTL unless renewed. Expiry inside the lease
+//! table uses the leader's clock stamped on each command, so all replicas
+//! agree on which commands found a lease expired. Leases held by a node
+//! that leaves the cluster are released straight away.
+//!
+//! Each caller acquires under its own owner token, so two tasks on the
+//! same node exclude each other. The lease table is saved in the consensus
+//! snapshot, and a node whose log was compacted past what it had applied
+//! restores the table from it.
+
+use crate::consensus::{ConsensusService, Snapshot};
+use crate::error::{ClusterError, Result};
+use crate::messaging::{RpcRequest, RpcResponse, RpcService};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Prefix marking lock commands in the consensus log.
+const LOCK_ENTRY_TAG: &[u8] = b"lock\0";
+
+/// Outcomes kept for callers still waiting on their command.
+const MAX_OUTCOMES: usize = 1024;
+
+/// RPC method followers forward lock commands to the leader with.
+pub const LOCK_RPC_METHOD: &str = "lock";
+
+/// A lease on a named lock.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lease {
+    /// Lock name
+    pub name: String,
+
+    /// Owner token of the caller holding the lease
+    pub owner: Uuid,
+
+    /// Node the lease was acquired on
+    pub node_id: Uuid,
+
+    /// Fencing token, increasing with every new grant of the lock
+    pub fencing_token: u64,
+
+    /// Expiry, in milliseconds since the Unix epoch
+    pub expires_at: i64,
+}
+
+impl Lease {
+    /// Check if the lease has expired.
+    pub fn is_expired(&self) -> bool {
+        now_millis() >= self.expires_at
+    }
+
+    /// Get the time left on the lease.
+    pub fn remaining(&self) -> Duration {
+        Duration::from_millis(self.expires_at.saturating_sub(now_millis()).max(0) as u64)
+    }
+}
+
+/// Lock command replicated through the consensus log.
+#[derive(Debug, Serialize, Deserialize)]
+struct LockRequest {
+    id: Uuid,
+    command: LockCommand,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum LockCommand {
+    Acquire { name: String, owner: Uuid, node_id: Uuid, ttl_ms: u64, now: i64 },
+    Renew { name: String, token: u64, ttl_ms: u64, now: i64 },
+    Release { name: String, token: u64 },
+    ReleaseNode { node_id: Uuid },
+}
+
+impl LockCommand {
+    /// Stamp the command with the leader's clock.
+    fn stamp(&mut self, at: i64) {
+        match self {
+            LockCommand::Acquire { now, .. } | LockCommand::Renew { now, .. } => *now = at,
+            LockCommand::Release { .. } | LockCommand::ReleaseNode { .. } => {}
+        }
+    }
+}
+
+/// Result of applying a command.
+#[derive(Debug, Clone)]
+enum LockOutcome {
+    Granted(Lease),
+    Held(Lease),
+    NotHeld,
+    Released,
+}
+
+/// Leases as of the last applied log entry.
+#[derive(Default)]
+struct LeaseTable {
+    leases: HashMap<String, Lease>,
+    applied_index: u64,
+    outcomes: HashMap<Uuid, LockOutcome>,
+    outcome_order: VecDeque<Uuid>,
+}
+
+impl LeaseTable {
+    fn apply(&mut self, index: u64, command: LockCommand) -> LockOutcome {
+        match command {
+            LockCommand::Acquire { name, owner, node_id, ttl_ms, now } => {
+                let expires_at = now + ttl_ms as i64;
+                match self.leases.get_mut(&name) {
+                    Some(lease) if lease.expires_at > now && lease.owner != owner => {
+                        LockOutcome::Held(lease.clone())
+                    }
+                    // Acquiring a lease the same owner already holds extends it
+                    Some(lease) if lease.expires_at > now => {
+                        lease.expires_at = expires_at;
+                        LockOutcome::Granted(lease.clone())
+                    }
+                    _ => {
+                        let lease = Lease {
+                            name: name.clone(),
+                            owner,
+                            node_id,
+                            fencing_token: index,
+                            expires_at,
+                        };
+                        self.leases.insert(name, lease.clone());
+                        LockOutcome::Granted(lease)
+                    }
+                }
+            }
+            LockCommand::Renew { name, token, ttl_ms, now } => match self.leases.get_mut(&name) {
+                Some(lease) if lease.fencing_token == token && lease.expires_at > now => {
+                    lease.expires_at = now + ttl_ms as i64;
+                    LockOutcome::Granted(lease.clone())
+                }
+                _ => LockOutcome::NotHeld,
+            },
+            LockCommand::Release { name, token } => match self.leases.get(&name) {
+                Some(lease) if lease.fencing_token == token => {
+                    self.leases.remove(&name);
+                    LockOutcome::Released
+                }
+                _ => LockOutcome::NotHeld,
+            },
+            LockCommand::ReleaseNode { node_id } => {
+                self.leases.retain(|_, lease| lease.node_id != node_id);
+                LockOutcome::Released
+            }
+        }
+    }
+
+    fn record(&mut self, request_id: Uuid, outcome: LockOutcome) {
+        if self.outcome_order.len() >= MAX_OUTCOMES {
+            if let Some(oldest) = self.outcome_order.pop_front() {
+                self.outcomes.remove(&oldest);
+            }
+        }
+        self.outcome_order.push_back(request_id);
+        self.outcomes.insert(request_id, outcome);
+    }
+
+    /// Replace the leases with those saved in a snapshot.
+    fn restore(&mut self, snapshot: &Snapshot) -> Result<()> {
+        let leases: Vec<Lease> = bincode::deserialize(&snapshot.data)?;
+        self.leases = leases.into_iter().map(|lease| (lease.name.clone(), lease)).collect();
+        self.applied_index = snapshot.last_included_index;
+        Ok(())
+    }
+}
+
+/// Named locks with leases, coordinated by the cluster leader.
+pub struct LockManager {
+    /// Local node ID, recorded on leases acquired here
+    local_id: Uuid,
+
+    /// Consensus log the commands go through
+    consensus: Arc<ConsensusService>,
+
+    /// RPC used to forward commands to the leader
+    rpc: Option<Arc<RpcService>>,
+
+    /// Applied lease state
+    table: Mutex<LeaseTable>,
+
+    /// How long to wait for a command to commit
+    commit_timeout: Duration,
+}
+
+impl LockManager {
+    /// Create a new lock manager.
+    pub fn new(local_id: Uuid, consensus: Arc<ConsensusService>) -> Self {
+        Self {
+            local_id,
+            consensus,
+            rpc: None,
+            table: Mutex::new(LeaseTable::default()),
+            commit_timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Set how long to wait for a command to commit.
+    pub fn with_commit_timeout(mut self, timeout: Duration) -> Self {
+        self.commit_timeout = timeout;
+        self
+    }
+
+    /// Forward commands made while this node is a follower to the leader.
+    pub fn with_rpc(mut self, rpc: Arc<RpcService>) -> Self {
+        self.rpc = Some(rpc);
+        self
+    }
+
+    /// Acquire a lease on a lock for an owner.
+    ///
+    /// `owner` identifies the caller; give each task that takes locks its
+    /// own token. Acquiring a lock the same owner already holds extends the
+    /// lease and keeps its fencing token.
+    pub async fn acquire(&self, name: &str, owner: Uuid, ttl: Duration) -> Result<Lease> {
+        let command = LockCommand::Acquire {
+            name: name.to_string(),
+            owner,
+            node_id: self.local_id,
+            ttl_ms: ttl.as_millis() as u64,
+            now: now_millis(),
+        };
+
+        match self.submit(command).await? {
+            LockOutcome::Granted(lease) => Ok(lease),
+            LockOutcome::Held(lease) => Err(ClusterError::LockHeld {
+                name: lease.name,
+                owner: lease.owner,
+            }),
+            _ => Err(ClusterError::LockNotHeld(name.to_string())),
+        }
+    }
+
+    /// Extend a lease before it expires.
+    pub async fn renew(&self, lease: &Lease, ttl: Duration) -> Result<Lease> {
+        let command = LockCommand::Renew {
+            name: lease.name.clone(),
+            token: lease.fencing_token,
+            ttl_ms: ttl.as_millis() as u64,
+            now: now_millis(),
+        };
+
+        match self.submit(command).await? {
+            LockOutcome::Granted(lease) => Ok(lease),
+            _ => Err(ClusterError::LockNotHeld(lease.name.clone())),
+        }
+    }
+
+    /// Release a lease.
+    pub async fn release(&self, lease: &Lease) -> Result<()> {
+        let command = LockCommand::Release {
+            name: lease.name.clone(),
+            token: lease.fencing_token,
+        };
+
+        match self.submit(command).await? {
+            LockOutcome::Released => Ok(()),
+            _ => Err(ClusterError::LockNotHeld(lease.name.clone())),
+        }
+    }
+
+    /// Release every lease held by a node, as when it fails or leaves.
+    pub async fn release_node(&self, node_id: Uuid) -> Result<()> {
+        self.submit(LockCommand::ReleaseNode { node_id }).await?;
+        Ok(())
+    }
+
+    /// Get the current lease on a lock.
+    pub fn lease(&self, name: &str) -> Option<Lease> {
+        self.apply_committed();
+        self.table
+            .lock()
+            .leases
+            .get(name)
+            .filter(|lease| !lease.is_expired())
+            .cloned()
+    }
+
+    /// Get all current leases.
+    pub fn leases(&self) -> Vec<Lease> {
+        self.apply_committed();
+        self.table
+            .lock()
+            .leases
+            .values()
+            .filter(|lease| !lease.is_expired())
+            .cloned()
+            .collect()
+    }
+
+    /// Apply lock commands committed since the last call.
+    pub fn apply_committed(&self) {
+        let mut table = self.table.lock();
+
+        let mut entries = self.consensus.committed_since(table.applied_index);
+        if entries.first().map(|e| e.index) != Some(table.applied_index + 1) {
+            // The entries after the applied index were compacted away
+            if let Some(snapshot) = self
+                .consensus
+                .latest_snapshot()
+                .filter(|s| s.last_included_index > table.applied_index)
+            {
+                match table.restore(&snapshot) {
+                    Ok(()) => entries = self.consensus.committed_since(table.applied_index),
+                    Err(e) => {
+                        tracing::error!("Cannot restore leases from snapshot: {}", e);
+                        return;
+                    }
+                }
+            }
+        }
+
+        for entry in entries {
+            table.applied_index = entry.index;
+
+            let Some(data) = entry.data.strip_prefix(LOCK_ENTRY_TAG) else {
+                continue;
+            };
+            match bincode::deserialize::<LockRequest>(data) {
+                Ok(request) => {
+                    let outcome = table.apply(entry.index, request.command);
+                    table.record(request.id, outcome);
+                }
+                Err(e) => tracing::warn!("Skipping unreadable lock command at {}: {}", entry.index, e),
+            }
+        }
+
+        if self.consensus.should_snapshot() {
+            if let Err(e) = self.snapshot(&table) {
+                tracing::warn!("Cannot snapshot leases: {}", e);
+            }
+        }
+    }
+
+    /// Handle a command forwarded by a follower, proposing it on this node.
+    pub fn handle_forwarded(&self, request: RpcRequest) -> Result<RpcResponse> {
+        let mut request: LockRequest = request.decode_params()?;
+        request.command.stamp(now_millis());
+
+        let index = self.consensus.append_proposal(encode(&request)?)?;
+        RpcResponse::success(&index)
+    }
+
+    /// Save the lease table as the consensus snapshot and compact the log.
+    fn snapshot(&self, table: &LeaseTable) -> Result<()> {
+        let leases: Vec<&Lease> = table.leases.values().collect();
+        self.consensus
+            .create_snapshot(table.applied_index, bincode::serialize(&leases)?)
+    }
+
+    /// Propose a command, through the leader if this node follows, and wait
+    /// for its outcome.
+    async fn submit(&self, command: LockCommand) -> Result<LockOutcome> {
+        let request = LockRequest {
+            id: Uuid::new_v4(),
+            command,
+        };
+
+        if self.consensus.is_leader() {
+            self.consensus.propose(encode(&request)?).await?;
+        } else {
+            let (Some(rpc), Some(leader)) = (&self.rpc, self.consensus.current_leader()) else {
+                return Err(ClusterError::NotLeader(self.consensus.current_leader()));
+            };
+            rpc.call::<_, u64>(leader, LOCK_RPC_METHOD, &request).await?;
+        }
+
+        let deadline = tokio::time::Instant::now() + self.commit_timeout;
+        loop {
+            self.apply_committed();
+            if let Some(outcome) = self.table.lock().outcomes.remove(&request.id) {
+                return Ok(outcome);
+            }
+
+            if tokio::time::Instant::now() >= deadline {
+                return Err(ClusterError::Timeout("Lock command was not committed".to_string()));
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+}
+
+/// Encode a request as a consensus log entry.
+fn encode(request: &LockRequest) -> Result<Vec<u8>> {
+    let mut data = LOCK_ENTRY_TAG.to_vec();
+    data.extend_from_slice(&bincode::serialize(request)?);
+    Ok(data)
+}
+
+fn now_millis() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConsensusConfig;
+
+    fn leader(config: ConsensusConfig) -> Arc<ConsensusService> {
+        let consensus = ConsensusService::new(Uuid::new_v4(), config);
+        consensus.start_election().unwrap();
+        consensus.become_leader();
+        Arc::new(consensus)
+    }
+
+    /// A node's consensus log, RPC endpoint and lock manager.
+    struct TestNode {
+        id: Uuid,
+        consensus: Arc<ConsensusService>,
+        locks: Arc<LockManager>,
+    }
+
+    /// Nodes whose RPC messages are delivered to each other in memory, with
+    /// the first one leading.
+    fn cluster(size: usize) -> Vec<TestNode> {
+        let mut nodes = Vec::new();
+        let mut endpoints = HashMap::new();
+        for _ in 0..size {
+            let id = Uuid::new_v4();
+            let consensus = Arc::new(ConsensusService::new(id, ConsensusConfig::default()));
+            let rpc = Arc::new(RpcService::new(id));
+            let locks = Arc::new(
+                LockManager::new(id, Arc::clone(&consensus))
+                    .with_rpc(Arc::clone(&rpc))
+                    .with_commit_timeout(Duration::from_secs(2)),
+            );
+
+            let handler = Arc::clone(&locks);
+            rpc.register(LOCK_RPC_METHOD, move |request| handler.handle_forwarded(request));
+            consensus.serve_rpc(&rpc).unwrap();
+
+            endpoints.insert(id, rpc);
+            nodes.push(TestNode { id, consensus, locks });
+        }
+
+        for node in &nodes {
+            for peer in &nodes {
+                node.consensus.add_peer(peer.id);
+            }
+
+            let mut outbound = endpoints[&node.id].take_outbound().unwrap();
+            let endpoints = endpoints.clone();
+            let local = Arc::clone(&endpoints[&node.id]);
+            tokio::spawn(async move {
+                while let Some((to, message)) = outbound.recv().await {
+                    if let Ok(Some(reply)) = endpoints[&to].handle_message(message) {
+                        let _ = local.handle_message(reply);
+                    }
+                }
+            });
+        }
+
+        nodes[0].consensus.start_election().unwrap();
+        nodes[0].consensus.become_leader();
+        nodes[0].consensus.start_replication();
+        nodes
+    }
+
+    async fn wait_for(what: &str, mut done: impl FnMut() -> bool) {
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(2);
+        while !done() {
+            assert!(tokio::time::Instant::now() < deadline, "timed out waiting for {}", what);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_acquire_renew_release() {
+        let consensus = leader(ConsensusConfig::default());
+        let locks = LockManager::new(Uuid::new_v4(), Arc::clone(&consensus));
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let ttl = Duration::from_secs(30);
+
+        let lease = locks.acquire("scheduler", a, ttl).await.unwrap();
+        assert_eq!(lease.owner, a);
+        assert_eq!(locks.lease("scheduler"), Some(lease.clone()));
+
+        // Another caller on the same node is refused
+        assert!(matches!(
+            locks.acquire("scheduler", b, ttl).await,
+            Err(ClusterError::LockHeld { owner, .. }) if owner == a
+        ));
+
+        // Re-acquiring under the same owner keeps the token
+        let again = locks.acquire("scheduler", a, ttl).await.unwrap();
+        assert_eq!(again.fencing_token, lease.fencing_token);
+        let renewed = locks.renew(&lease, ttl).await.unwrap();
+        assert_eq!(renewed.fencing_token, lease.fencing_token);
+
+        locks.release(&lease).await.unwrap();
+        assert!(locks.release(&lease).await.is_err());
+
+        // A new grant gets a higher token; the old lease is fenced off
+        let next = locks.acquire("scheduler", b, ttl).await.unwrap();
+        assert!(next.fencing_token > lease.fencing_token);
+        assert!(matches!(locks.renew(&lease, ttl).await, Err(ClusterError::LockNotHeld(_))));
+    }
+
+    #[tokio::test]
+    async fn test_expiry_and_node_failure() {
+        let consensus = leader(ConsensusConfig::default());
+        let a = LockManager::new(Uuid::new_v4(), Arc::clone(&consensus));
+        let b = LockManager::new(Uuid::new_v4(), Arc::clone(&consensus));
+        let owner = Uuid::new_v4();
+
+        let short = a.acquire("notifications", owner, Duration::ZERO).await.unwrap();
+        assert!(a.lease("notifications").is_none());
+        let taken = b
+            .acquire("notifications", Uuid::new_v4(), Duration::from_secs(30))
+            .await
+            .unwrap();
+        assert!(taken.fencing_token > short.fencing_token);
+
+        a.acquire("reports", owner, Duration::from_secs(30)).await.unwrap();
+        b.release_node(taken.node_id).await.unwrap();
+        assert!(b.lease("notifications").is_none());
+        assert!(b.lease("reports").is_some());
+        assert_eq!(a.leases().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_commands_commit_on_follower_ack() {
+        let nodes = cluster(2);
+        let (leader, follower) = (&nodes[0], &nodes[1]);
+
+        let owner = Uuid::new_v4();
+        let lease = leader
+            .locks
+            .acquire("scheduler", owner, Duration::from_secs(30))
+            .await
+            .unwrap();
+        assert_eq!(leader.consensus.commit_index(), 1);
+
+        // The next heartbeat carries the commit index to the follower
+        wait_for("the follower to apply the grant", || {
+            follower.locks.lease("scheduler").is_some()
+        })
+        .await;
+        assert_eq!(follower.locks.lease("scheduler"), Some(lease));
+
+        leader.consensus.stop();
+    }
+
+    #[tokio::test]
+    async fn test_follower_forwards_to_leader() {
+        let nodes = cluster(2);
+        let (leader, follower) = (&nodes[0], &nodes[1]);
+        wait_for("the first heartbeat", || {
+            follower.consensus.current_leader() == Some(leader.id)
+        })
+        .await;
+
+        let owner = Uuid::new_v4();
+        let ttl = Duration::from_secs(30);
+        let lease = follower.locks.acquire("scheduler", owner, ttl).await.unwrap();
+        assert_eq!(lease.node_id, follower.id);
+        assert_eq!(leader.locks.lease("scheduler"), Some(lease.clone()));
+
+        assert!(matches!(
+            leader.locks.acquire("scheduler", Uuid::new_v4(), ttl).await,
+            Err(ClusterError::LockHeld { owner: holder, .. }) if holder == owner
+        ));
+
+        follower.locks.renew(&lease, ttl).await.unwrap();
+        follower.locks.release(&lease).await.unwrap();
+        assert!(leader.locks.lease("scheduler").is_none());
+
+        leader.consensus.stop();
+    }
+
+    #[tokio::test]
+    async fn test_commands_need_leader() {
+        let follower = Arc::new(ConsensusService::new(Uuid::new_v4(), ConsensusConfig::default()));
+        let locks = LockManager::new(Uuid::new_v4(), follower);
+
+        // Without RPC there is no way to reach a leader
+        assert!(matches!(
+            locks.acquire("scheduler", Uuid::new_v4(), Duration::from_secs(1)).await,
+            Err(ClusterError::NotLeader(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_leases_survive_compaction() {
+        let consensus = leader(ConsensusConfig {
+            snapshot_threshold: 4,
+            snapshot_trailing_entries: 1,
+            ..ConsensusConfig::default()
+        });
+        let locks = LockManager::new(Uuid::new_v4(), Arc::clone(&consensus));
+
+        let owner = Uuid::new_v4();
+        for i in 0..5 {
+            let name = format!("partition-{}", i);
+            locks.acquire(&name, owner, Duration::from_secs(30)).await.unwrap();
+        }
+        assert_eq!(locks.leases().len(), 5);
+        assert_eq!(consensus.latest_snapshot().unwrap().last_included_index, 4);
+
+        // A replica starting from scratch only finds the snapshot
+        let replica = LockManager::new(Uuid::new_v4(), Arc::clone(&consensus));
+        assert!(consensus.committed_since(0).first().is_some_and(|e| e.index > 1));
+        assert_eq!(replica.leases().len(), 5);
+        assert_eq!(replica.lease("partition-0"), locks.lease("partition-0"));
+    }
+}
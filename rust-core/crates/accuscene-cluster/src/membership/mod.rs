@@ -9,8 +9,11 @@ pub use view::{MemberInfo, MembershipView};
 use crate::config::MembershipConfig;
 use crate::error::Result;
 use crate::node::{Node, NodeState};
+use parking_lot::Mutex;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 
@@ -30,6 +33,12 @@ pub struct MembershipService {
 
     /// Running flag
     running: Arc<RwLock<bool>>,
+
+    /// Cancelled on stop or by an enclosing shutdown scope
+    shutdown: CancellationToken,
+
+    /// Spawned background tasks
+    tasks: Arc<Mutex<Vec<(String, JoinHandle<()>)>>>,
 }
 
 impl MembershipService {
@@ -43,9 +52,22 @@ impl MembershipService {
             gossip: Arc::new(RwLock::new(gossip)),
             config,
             running: Arc::new(RwLock::new(false)),
+            shutdown: CancellationToken::new(),
+            tasks: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
+    /// Stop background tasks when the given token is cancelled
+    pub fn with_shutdown_token(mut self, token: CancellationToken) -> Self {
+        self.shutdown = token;
+        self
+    }
+
+    /// Hand over the spawned task handles so a shutdown scope can await them.
+    pub fn take_task_handles(&self) -> Vec<(String, JoinHandle<()>)> {
+        std::mem::take(&mut *self.tasks.lock())
+    }
+
     /// Start membership service.
     pub async fn start(&self) -> Result<()> {
         let mut running = self.running.write().await;
@@ -59,23 +81,31 @@ impl MembershipService {
 
         // Start gossip task
         let service = self.clone_for_task();
-        tokio::spawn(async move {
+        let gossip = tokio::spawn(async move {
             service.gossip_task().await;
         });
 
         // Start failure detection task
         let service = self.clone_for_task();
-        tokio::spawn(async move {
+        let failure_detection = tokio::spawn(async move {
             service.failure_detection_task().await;
         });
 
+        self.tasks.lock().extend([
+            ("gossip".to_string(), gossip),
+            ("failure-detection".to_string(), failure_detection),
+        ]);
+
         Ok(())
     }
 
     /// Stop membership service.
+    ///
+    /// Background tasks exit at their next tick; the service cannot be restarted.
     pub async fn stop(&self) {
         let mut running = self.running.write().await;
         *running = false;
+        self.shutdown.cancel();
         info!("Stopped membership service");
     }
 
@@ -161,7 +191,10 @@ impl MembershipService {
         let mut interval = tokio::time::interval(self.config.gossip_interval);
 
         loop {
-            interval.tick().await;
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = self.shutdown.cancelled() => break,
+            }
 
             let running = self.running.read().await;
             if !*running {
@@ -189,7 +222,10 @@ impl MembershipService {
         let mut interval = tokio::time::interval(self.config.gossip_interval);
 
         loop {
-            interval.tick().await;
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = self.shutdown.cancelled() => break,
+            }
 
             let running = self.running.read().await;
             if !*running {
@@ -220,6 +256,8 @@ impl MembershipService {
             gossip: Arc::clone(&self.gossip),
             config: self.config.clone(),
             running: Arc::clone(&self.running),
+            shutdown: self.shutdown.clone(),
+            tasks: Arc::clone(&self.tasks),
         }
     }
 }
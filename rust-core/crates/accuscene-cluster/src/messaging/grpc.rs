@@ -0,0 +1,493 @@
+//! gRPC transport for cluster messaging.
+//!
+//! Carries the same messages as the binary protocol, described by the
+//! protobuf schemas in `proto/cluster.proto`. RPC requests and responses are
+//! mapped field by field so they can be inspected with standard gRPC
+//! tooling; other message types keep their bincode payload. Nodes agree on
+//! the transport with a `Handshake` call before exchanging messages.
+
+use crate::error::{ClusterError, Result};
+use crate::messaging::protocol::{
+    negotiate, Message, MessageHeader, MessageType, Negotiated, ProtocolHello, WireProtocol,
+    MIN_PROTOCOL_VERSION, PROTOCOL_VERSION,
+};
+use crate::messaging::rpc::{RpcRequest, RpcResponse, RpcService};
+use bytes::Bytes;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::{Channel, Server};
+use tonic::{Request, Response, Status};
+use uuid::Uuid;
+
+/// Types generated from `proto/cluster.proto`.
+pub mod proto {
+    tonic::include_proto!("accuscene.cluster.v1");
+}
+
+use proto::cluster_messaging_client::ClusterMessagingClient;
+use proto::cluster_messaging_server::{ClusterMessaging, ClusterMessagingServer};
+
+impl From<MessageType> for proto::MessageType {
+    fn from(message_type: MessageType) -> Self {
+        match message_type {
+            MessageType::Ping => proto::MessageType::Ping,
+            MessageType::Pong => proto::MessageType::Pong,
+            MessageType::Hello => proto::MessageType::Hello,
+            MessageType::Request => proto::MessageType::Request,
+            MessageType::Response => proto::MessageType::Response,
+            MessageType::Error => proto::MessageType::Error,
+            MessageType::Gossip => proto::MessageType::Gossip,
+            MessageType::Consensus => proto::MessageType::Consensus,
+            MessageType::Replication => proto::MessageType::Replication,
+        }
+    }
+}
+
+impl TryFrom<proto::MessageType> for MessageType {
+    type Error = ClusterError;
+
+    fn try_from(message_type: proto::MessageType) -> Result<Self> {
+        match message_type {
+            proto::MessageType::Ping => Ok(MessageType::Ping),
+            proto::MessageType::Pong => Ok(MessageType::Pong),
+            proto::MessageType::Hello => Ok(MessageType::Hello),
+            proto::MessageType::Request => Ok(MessageType::Request),
+            proto::MessageType::Response => Ok(MessageType::Response),
+            proto::MessageType::Error => Ok(MessageType::Error),
+            proto::MessageType::Gossip => Ok(MessageType::Gossip),
+            proto::MessageType::Consensus => Ok(MessageType::Consensus),
+            proto::MessageType::Replication => Ok(MessageType::Replication),
+            proto::MessageType::Unspecified => Err(ClusterError::InvalidMessage(
+                "Unspecified message type".to_string(),
+            )),
+        }
+    }
+}
+
+impl From<WireProtocol> for proto::WireProtocol {
+    fn from(protocol: WireProtocol) -> Self {
+        match protocol {
+            WireProtocol::Binary => proto::WireProtocol::Binary,
+            WireProtocol::Grpc => proto::WireProtocol::Grpc,
+        }
+    }
+}
+
+impl From<&ProtocolHello> for proto::Hello {
+    fn from(hello: &ProtocolHello) -> Self {
+        Self {
+            node_id: hello.node_id.to_string(),
+            version: hello.version as u32,
+            protocols: hello
+                .protocols
+                .iter()
+                .map(|protocol| proto::WireProtocol::from(*protocol) as i32)
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<proto::Hello> for ProtocolHello {
+    type Error = ClusterError;
+
+    fn try_from(hello: proto::Hello) -> Result<Self> {
+        let protocols = hello
+            .protocols()
+            .map(|protocol| match protocol {
+                proto::WireProtocol::Binary => WireProtocol::Binary,
+                proto::WireProtocol::Grpc => WireProtocol::Grpc,
+            })
+            .collect();
+
+        Ok(Self {
+            node_id: parse_uuid(&hello.node_id)?,
+            version: u8::try_from(hello.version).unwrap_or(u8::MAX),
+            protocols,
+        })
+    }
+}
+
+impl From<RpcRequest> for proto::RpcRequest {
+    fn from(request: RpcRequest) -> Self {
+        Self {
+            method: request.method,
+            params: request.params,
+            timeout_ms: request.timeout_ms,
+        }
+    }
+}
+
+impl From<proto::RpcRequest> for RpcRequest {
+    fn from(request: proto::RpcRequest) -> Self {
+        Self {
+            method: request.method,
+            params: request.params,
+            timeout_ms: request.timeout_ms,
+        }
+    }
+}
+
+impl From<RpcResponse> for proto::RpcResponse {
+    fn from(response: RpcResponse) -> Self {
+        Self {
+            success: response.success,
+            data: response.data,
+            error: response.error,
+        }
+    }
+}
+
+impl From<proto::RpcResponse> for RpcResponse {
+    fn from(response: proto::RpcResponse) -> Self {
+        Self {
+            success: response.success,
+            data: response.data,
+            error: response.error,
+        }
+    }
+}
+
+impl TryFrom<&Message> for proto::Envelope {
+    type Error = ClusterError;
+
+    fn try_from(message: &Message) -> Result<Self> {
+        let header = &message.header;
+        let body = match header.message_type {
+            MessageType::Request => {
+                proto::envelope::Body::Request(message.decode_payload::<RpcRequest>()?.into())
+            }
+            MessageType::Response => {
+                proto::envelope::Body::Response(message.decode_payload::<RpcResponse>()?.into())
+            }
+            _ => {
+                if crc32fast::hash(&message.payload) != header.checksum {
+                    return Err(ClusterError::ChecksumMismatch);
+                }
+                proto::envelope::Body::Payload(message.payload.to_vec())
+            }
+        };
+
+        Ok(Self {
+            version: header.version as u32,
+            message_type: proto::MessageType::from(header.message_type) as i32,
+            message_id: header.message_id.to_string(),
+            source: header.source.to_string(),
+            destination: header.destination.map(|id| id.to_string()),
+            body: Some(body),
+        })
+    }
+}
+
+impl TryFrom<proto::Envelope> for Message {
+    type Error = ClusterError;
+
+    fn try_from(envelope: proto::Envelope) -> Result<Self> {
+        let version = u8::try_from(envelope.version)
+            .ok()
+            .filter(|version| (MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION).contains(version))
+            .ok_or_else(|| {
+                ClusterError::InvalidMessage(format!(
+                    "Unsupported protocol version: {}",
+                    envelope.version
+                ))
+            })?;
+        let message_type = MessageType::try_from(envelope.message_type())?;
+
+        let payload = match envelope.body {
+            Some(proto::envelope::Body::Request(request)) => {
+                bincode::serialize(&RpcRequest::from(request))?
+            }
+            Some(proto::envelope::Body::Response(response)) => {
+                bincode::serialize(&RpcResponse::from(response))?
+            }
+            Some(proto::envelope::Body::Payload(payload)) => payload,
+            None => {
+                return Err(ClusterError::InvalidMessage(
+                    "Envelope has no body".to_string(),
+                ))
+            }
+        };
+
+        let mut header = MessageHeader::new(message_type, parse_uuid(&envelope.source)?);
+        header.version = version;
+        header.message_id = parse_uuid(&envelope.message_id)?;
+        header.destination = envelope.destination.as_deref().map(parse_uuid).transpose()?;
+        header.payload_length = payload.len() as u32;
+        header.checksum = crc32fast::hash(&payload);
+
+        Ok(Self {
+            header,
+            payload: Bytes::from(payload),
+        })
+    }
+}
+
+/// Serves cluster messages over gRPC.
+///
+/// RPC requests go to the node's `RpcService`; gossip, consensus and
+/// replication messages are queued for the owning service to drain.
+pub struct GrpcMessaging {
+    /// Local node ID
+    local_id: Uuid,
+
+    /// Handles RPC requests and responses
+    rpc: Arc<RpcService>,
+
+    /// Queue of other incoming messages
+    inbound_tx: mpsc::UnboundedSender<Message>,
+
+    /// Receiving end of the inbound queue, until taken
+    inbound_rx: Mutex<Option<mpsc::UnboundedReceiver<Message>>>,
+}
+
+impl GrpcMessaging {
+    /// Create a new gRPC messaging service.
+    pub fn new(local_id: Uuid, rpc: Arc<RpcService>) -> Self {
+        let (inbound_tx, inbound_rx) = mpsc::unbounded_channel();
+
+        Self {
+            local_id,
+            rpc,
+            inbound_tx,
+            inbound_rx: Mutex::new(Some(inbound_rx)),
+        }
+    }
+
+    /// Take the queue of incoming messages not handled here.
+    pub fn take_inbound(&self) -> Option<mpsc::UnboundedReceiver<Message>> {
+        self.inbound_rx.lock().take()
+    }
+
+    /// Handle an incoming message, returning the reply if it has one.
+    pub fn handle_message(&self, message: Message) -> Result<Option<Message>> {
+        match message.header.message_type {
+            MessageType::Ping => Ok(Some(Message::new(MessageType::Pong, self.local_id, &())?)),
+            MessageType::Hello => Ok(Some(ProtocolHello::local(self.local_id).to_message()?)),
+            MessageType::Request | MessageType::Response => self.rpc.handle_message(message),
+            _ => {
+                let _ = self.inbound_tx.send(message);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Serve connections accepted on a listener until shutdown.
+    pub async fn serve(self: Arc<Self>, listener: TcpListener, shutdown: CancellationToken) -> Result<()> {
+        let incoming = TcpIncoming::from_listener(listener, true, None)
+            .map_err(|e| ClusterError::Network(e.to_string()))?;
+
+        Server::builder()
+            .add_service(ClusterMessagingServer::from_arc(self))
+            .serve_with_incoming_shutdown(incoming, shutdown.cancelled_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[tonic::async_trait]
+impl ClusterMessaging for GrpcMessaging {
+    async fn handshake(&self, request: Request<proto::Hello>) -> std::result::Result<Response<proto::Hello>, Status> {
+        let remote = ProtocolHello::try_from(request.into_inner())?;
+        let local = ProtocolHello::local(self.local_id);
+        negotiate(&local, &remote)?;
+
+        Ok(Response::new(proto::Hello::from(&local)))
+    }
+
+    async fn exchange(&self, request: Request<proto::Envelope>) -> std::result::Result<Response<proto::Reply>, Status> {
+        let message = Message::try_from(request.into_inner())?;
+        let reply = self
+            .handle_message(message)?
+            .map(|reply| proto::Envelope::try_from(&reply))
+            .transpose()?;
+
+        Ok(Response::new(proto::Reply { message: reply }))
+    }
+}
+
+/// Client side of the gRPC transport to one peer.
+pub struct GrpcClient {
+    /// Local node ID
+    local_id: Uuid,
+
+    /// Version and transport agreed in the handshake
+    negotiated: Negotiated,
+
+    /// Generated client
+    inner: ClusterMessagingClient<Channel>,
+
+    /// Timeout for each call
+    timeout: Duration,
+}
+
+impl GrpcClient {
+    /// Connect to a peer and negotiate the protocol.
+    ///
+    /// Fails if the peer does not accept gRPC, in which case the caller
+    /// should use the binary protocol.
+    pub async fn connect(local_id: Uuid, addr: SocketAddr) -> Result<Self> {
+        let channel = Channel::from_shared(format!("http://{}", addr))
+            .map_err(|e| ClusterError::InvalidConfiguration(e.to_string()))?
+            .connect()
+            .await?;
+        let mut inner = ClusterMessagingClient::new(channel);
+
+        let local = ProtocolHello::local(local_id);
+        let remote = inner
+            .handshake(proto::Hello::from(&local))
+            .await?
+            .into_inner();
+        let negotiated = negotiate(&local, &ProtocolHello::try_from(remote)?)?;
+
+        if negotiated.protocol != WireProtocol::Grpc {
+            return Err(ClusterError::Network(format!(
+                "Peer at {} does not accept gRPC",
+                addr
+            )));
+        }
+
+        Ok(Self {
+            local_id,
+            negotiated,
+            inner,
+            timeout: Duration::from_secs(5),
+        })
+    }
+
+    /// Set the timeout for each call.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Get the negotiated version and transport.
+    pub fn negotiated(&self) -> Negotiated {
+        self.negotiated
+    }
+
+    /// Send a message, returning the reply if it has one.
+    pub async fn send(&mut self, message: &Message) -> Result<Option<Message>> {
+        let mut envelope = proto::Envelope::try_from(message)?;
+        envelope.version = self.negotiated.version as u32;
+
+        let mut request = Request::new(envelope);
+        request.set_timeout(self.timeout);
+
+        self.inner
+            .exchange(request)
+            .await?
+            .into_inner()
+            .message
+            .map(Message::try_from)
+            .transpose()
+    }
+
+    /// Make an RPC call.
+    pub async fn call<T, R>(&mut self, method: impl Into<String>, params: &T) -> Result<R>
+    where
+        T: Serialize,
+        R: for<'de> Deserialize<'de>,
+    {
+        let request = RpcRequest::new(method, params)?.with_timeout(self.timeout);
+        let message = Message::new(MessageType::Request, self.local_id, &request)?;
+
+        match self.send(&message).await? {
+            Some(reply) if reply.header.message_type == MessageType::Response => {
+                reply.decode_payload::<RpcResponse>()?.decode_data()
+            }
+            _ => Err(ClusterError::InvalidMessage(
+                "Expected response message".to_string(),
+            )),
+        }
+    }
+}
+
+impl From<ClusterError> for Status {
+    fn from(err: ClusterError) -> Self {
+        match err {
+            ClusterError::InvalidMessage(_) | ClusterError::ChecksumMismatch => {
+                Status::invalid_argument(err.to_string())
+            }
+            err => Status::internal(err.to_string()),
+        }
+    }
+}
+
+fn parse_uuid(value: &str) -> Result<Uuid> {
+    Uuid::parse_str(value)
+        .map_err(|e| ClusterError::InvalidMessage(format!("Invalid node or message ID: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messaging::rpc::RpcRequest;
+
+    #[test]
+    fn test_envelope_roundtrip() {
+        let source = Uuid::new_v4();
+        let request = RpcRequest::new("health", &42u32).unwrap();
+        let mut message = Message::new(MessageType::Request, source, &request).unwrap();
+        message.header.destination = Some(Uuid::new_v4());
+
+        let envelope = proto::Envelope::try_from(&message).unwrap();
+        assert!(matches!(
+            &envelope.body,
+            Some(proto::envelope::Body::Request(request)) if request.method == "health"
+        ));
+
+        let decoded = Message::try_from(envelope).unwrap();
+        assert_eq!(decoded.header.message_id, message.header.message_id);
+        assert_eq!(decoded.header.destination, message.header.destination);
+        let decoded_request: RpcRequest = decoded.decode_payload().unwrap();
+        assert_eq!(decoded_request.decode_params::<u32>().unwrap(), 42);
+    }
+
+    #[tokio::test]
+    async fn test_grpc_exchange() {
+        let server_id = Uuid::new_v4();
+        let rpc = Arc::new(RpcService::new(server_id));
+        rpc.register("double", |req: RpcRequest| {
+            let value: i32 = req.decode_params()?;
+            RpcResponse::success(&(value * 2))
+        });
+
+        let messaging = Arc::new(GrpcMessaging::new(server_id, rpc));
+        let mut inbound = messaging.take_inbound().unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let shutdown = CancellationToken::new();
+        let server = tokio::spawn(Arc::clone(&messaging).serve(listener, shutdown.clone()));
+
+        let client_id = Uuid::new_v4();
+        let mut client = GrpcClient::connect(client_id, addr).await.unwrap();
+        assert_eq!(client.negotiated().protocol, WireProtocol::Grpc);
+        assert_eq!(client.negotiated().version, PROTOCOL_VERSION);
+
+        let doubled: i32 = client.call("double", &21).await.unwrap();
+        assert_eq!(doubled, 42);
+        assert!(client.call::<_, i32>("missing", &1).await.is_err());
+
+        let ping = Message::new(MessageType::Ping, client_id, &()).unwrap();
+        let pong = client.send(&ping).await.unwrap().unwrap();
+        assert_eq!(pong.header.message_type, MessageType::Pong);
+        assert_eq!(pong.header.source, server_id);
+
+        let gossip = Message::new(MessageType::Gossip, client_id, &"alive".to_string()).unwrap();
+        assert!(client.send(&gossip).await.unwrap().is_none());
+        let received = inbound.recv().await.unwrap();
+        assert_eq!(received.header.source, client_id);
+        assert_eq!(received.decode_payload::<String>().unwrap(), "alive");
+
+        shutdown.cancel();
+        server.await.unwrap().unwrap();
+    }
+}
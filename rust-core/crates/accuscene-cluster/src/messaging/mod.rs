@@ -1,7 +1,18 @@
 //! Inter-node messaging infrastructure.
 
+#[cfg(feature = "grpc")]
+pub mod grpc;
 pub mod protocol;
 pub mod rpc;
+#[cfg(feature = "tls")]
+pub mod tls;
 
-pub use protocol::{Message, MessageHeader, MessageType, PROTOCOL_VERSION};
-pub use rpc::{RpcClient, RpcRequest, RpcResponse, RpcServer, RpcService};
+#[cfg(feature = "grpc")]
+pub use grpc::{GrpcClient, GrpcMessaging};
+pub use protocol::{
+    negotiate, Message, MessageHeader, MessageType, Negotiated, ProtocolHello, WireProtocol,
+    MIN_PROTOCOL_VERSION, PROTOCOL_VERSION,
+};
+pub use rpc::{OutboundRpc, RpcClient, RpcRequest, RpcResponse, RpcServer, RpcService};
+#[cfg(feature = "tls")]
+pub use tls::{ClusterTls, NodeIdentity, PeerConnection};
@@ -6,7 +6,13 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 /// Protocol version.
-pub const PROTOCOL_VERSION: u8 = 1;
+pub const PROTOCOL_VERSION: u8 = 2;
+
+/// Oldest protocol version still accepted.
+pub const MIN_PROTOCOL_VERSION: u8 = 1;
+
+/// First protocol version that can negotiate the gRPC transport.
+pub const GRPC_PROTOCOL_VERSION: u8 = 2;
 
 /// Message type codes.
 #[repr(u8)]
@@ -14,6 +20,7 @@ pub const PROTOCOL_VERSION: u8 = 1;
 pub enum MessageType {
     Ping = 0x01,
     Pong = 0x02,
+    Hello = 0x03,
     Request = 0x10,
     Response = 0x11,
     Error = 0x12,
@@ -28,6 +35,7 @@ impl MessageType {
         match value {
             0x01 => Some(MessageType::Ping),
             0x02 => Some(MessageType::Pong),
+            0x03 => Some(MessageType::Hello),
             0x10 => Some(MessageType::Request),
             0x11 => Some(MessageType::Response),
             0x12 => Some(MessageType::Error),
@@ -44,6 +52,106 @@ impl MessageType {
     }
 }
 
+/// Transport used for messages between two nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WireProtocol {
+    /// Length-prefixed binary frames
+    Binary,
+
+    /// gRPC with protobuf schemas
+    Grpc,
+}
+
+/// Protocol versions and transports a node supports.
+///
+/// Sent in a `Hello` message with a `MIN_PROTOCOL_VERSION` header so that
+/// peers on any supported version can read it. A peer on version 1 does not
+/// know `Hello` and rejects it; the sender then stays on version 1 binary.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolHello {
+    /// Sending node
+    pub node_id: Uuid,
+
+    /// Highest protocol version supported
+    pub version: u8,
+
+    /// Transports supported
+    pub protocols: Vec<WireProtocol>,
+}
+
+impl ProtocolHello {
+    /// Describe what this build supports.
+    pub fn local(node_id: Uuid) -> Self {
+        let mut protocols = Vec::new();
+        if cfg!(feature = "grpc") {
+            protocols.push(WireProtocol::Grpc);
+        }
+        protocols.push(WireProtocol::Binary);
+
+        Self {
+            node_id,
+            version: PROTOCOL_VERSION,
+            protocols,
+        }
+    }
+
+    /// Restrict the transports offered.
+    pub fn with_protocols(mut self, protocols: Vec<WireProtocol>) -> Self {
+        self.protocols = protocols;
+        self
+    }
+
+    /// Check if a transport is supported.
+    pub fn supports(&self, protocol: WireProtocol) -> bool {
+        self.protocols.contains(&protocol)
+    }
+
+    /// Create the `Hello` message announcing this.
+    pub fn to_message(&self) -> Result<Message> {
+        let mut message = Message::new(MessageType::Hello, self.node_id, self)?;
+        message.header.version = MIN_PROTOCOL_VERSION;
+        Ok(message)
+    }
+}
+
+/// Version and transport agreed with a peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Negotiated {
+    /// Protocol version both sides speak
+    pub version: u8,
+
+    /// Transport to use
+    pub protocol: WireProtocol,
+}
+
+/// Agree on a protocol version and transport with a peer.
+///
+/// Both sides run this on the exchanged hellos and reach the same result:
+/// the lower of the two versions, and gRPC when both offer it and that
+/// version allows it, otherwise binary.
+pub fn negotiate(local: &ProtocolHello, remote: &ProtocolHello) -> Result<Negotiated> {
+    let version = local.version.min(remote.version);
+    if version < MIN_PROTOCOL_VERSION {
+        return Err(ClusterError::InvalidMessage(format!(
+            "Unsupported protocol version: {}",
+            remote.version
+        )));
+    }
+
+    let common = |protocol| local.supports(protocol) && remote.supports(protocol);
+    let protocol = if version >= GRPC_PROTOCOL_VERSION && common(WireProtocol::Grpc) {
+        WireProtocol::Grpc
+    } else if common(WireProtocol::Binary) {
+        WireProtocol::Binary
+    } else {
+        return Err(ClusterError::InvalidMessage(
+            "No common wire protocol".to_string(),
+        ));
+    };
+
+    Ok(Negotiated { version, protocol })
+}
+
 /// Message header.
 #[derive(Debug, Clone)]
 pub struct MessageHeader {
@@ -132,7 +240,7 @@ impl MessageHeader {
 
         // Version
         let version = buf.get_u8();
-        if version != PROTOCOL_VERSION {
+        if !(MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION).contains(&version) {
             return Err(ClusterError::InvalidMessage(format!(
                 "Unsupported protocol version: {}",
                 version
@@ -280,6 +388,33 @@ mod tests {
         assert_eq!(decoded.source, source);
     }
 
+    #[test]
+    fn test_negotiate() {
+        let grpc = ProtocolHello::local(Uuid::new_v4())
+            .with_protocols(vec![WireProtocol::Grpc, WireProtocol::Binary]);
+        let binary = ProtocolHello::local(Uuid::new_v4()).with_protocols(vec![WireProtocol::Binary]);
+        let mut old = grpc.clone();
+        old.version = 1;
+
+        let agreed = negotiate(&grpc, &grpc).unwrap();
+        assert_eq!(agreed.protocol, WireProtocol::Grpc);
+        assert_eq!(agreed.version, PROTOCOL_VERSION);
+
+        assert_eq!(negotiate(&grpc, &binary).unwrap().protocol, WireProtocol::Binary);
+        assert_eq!(negotiate(&binary, &grpc).unwrap().protocol, WireProtocol::Binary);
+
+        let agreed = negotiate(&grpc, &old).unwrap();
+        assert_eq!(agreed, negotiate(&old, &grpc).unwrap());
+        assert_eq!(agreed.version, 1);
+        assert_eq!(agreed.protocol, WireProtocol::Binary);
+
+        // Hellos are readable by version 1 peers
+        let message = grpc.to_message().unwrap();
+        let decoded = Message::decode(&message.encode()[..]).unwrap();
+        assert_eq!(decoded.header.version, MIN_PROTOCOL_VERSION);
+        assert_eq!(decoded.decode_payload::<ProtocolHello>().unwrap(), grpc);
+    }
+
     #[test]
     fn test_message() {
         let source = Uuid::new_v4();
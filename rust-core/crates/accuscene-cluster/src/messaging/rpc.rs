@@ -2,14 +2,17 @@
 
 use crate::error::{ClusterError, Result};
 use crate::messaging::protocol::{Message, MessageType};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::oneshot;
+use tokio::sync::{mpsc, oneshot};
 use uuid::Uuid;
 
+/// Queue of RPC messages waiting for the transport.
+pub type OutboundRpc = mpsc::UnboundedReceiver<(Uuid, Message)>;
+
 /// RPC request.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RpcRequest {
@@ -140,6 +143,9 @@ impl RpcServer {
     }
 
     /// Handle incoming message.
+    ///
+    /// The response carries the request's message ID so the caller can
+    /// match it to the pending call.
     pub fn handle_message(&self, message: Message) -> Result<Message> {
         if message.header.message_type != MessageType::Request {
             return Err(ClusterError::InvalidMessage(
@@ -150,7 +156,10 @@ impl RpcServer {
         let request: RpcRequest = message.decode_payload()?;
         let response = self.handle_request(request);
 
-        Message::new(MessageType::Response, self.local_id, &response)
+        let mut reply = Message::new(MessageType::Response, self.local_id, &response)?;
+        reply.header.message_id = message.header.message_id;
+        reply.header.destination = Some(message.header.source);
+        Ok(reply)
     }
 }
 
@@ -169,15 +178,23 @@ pub struct RpcClient {
 
     /// Default timeout
     default_timeout: Duration,
+
+    /// Outgoing requests
+    outbound_tx: mpsc::UnboundedSender<(Uuid, Message)>,
+    outbound_rx: Mutex<Option<OutboundRpc>>,
 }
 
 impl RpcClient {
     /// Create a new RPC client.
     pub fn new(local_id: Uuid) -> Self {
+        let (outbound_tx, outbound_rx) = mpsc::unbounded_channel();
+
         Self {
             local_id,
             pending: Arc::new(RwLock::new(HashMap::new())),
             default_timeout: Duration::from_secs(5),
+            outbound_tx,
+            outbound_rx: Mutex::new(Some(outbound_rx)),
         }
     }
 
@@ -187,6 +204,16 @@ impl RpcClient {
         self
     }
 
+    /// Take the queue of outgoing requests.
+    ///
+    /// The transport sends each request to its node, hands it to that
+    /// node's [`RpcService::handle_message`] and passes the response back to
+    /// [`handle_response`](Self::handle_response). Only the first call gets
+    /// the queue.
+    pub fn take_outbound(&self) -> Option<OutboundRpc> {
+        self.outbound_rx.lock().take()
+    }
+
     /// Make an RPC call.
     pub async fn call<T, R>(
         &self,
@@ -200,7 +227,8 @@ impl RpcClient {
     {
         let request = RpcRequest::new(method, params)?.with_timeout(self.default_timeout);
 
-        let message = Message::new(MessageType::Request, self.local_id, &request)?;
+        let mut message = Message::new(MessageType::Request, self.local_id, &request)?;
+        message.header.destination = Some(target);
         let message_id = message.header.message_id;
 
         // Create pending call
@@ -210,12 +238,17 @@ impl RpcClient {
             PendingCall { sender: tx },
         );
 
-        // In a real implementation, send the message to the target node
-        // For now, we'll simulate an error
-        drop(message); // Prevent unused variable warning
+        if self.outbound_tx.send((target, message)).is_err() {
+            self.pending.write().remove(&message_id);
+            return Err(ClusterError::Network("RPC transport stopped".to_string()));
+        }
 
         // Wait for response with timeout
-        match tokio::time::timeout(self.default_timeout, rx).await {
+        let response = tokio::time::timeout(self.default_timeout, rx).await;
+        if response.is_err() {
+            self.pending.write().remove(&message_id);
+        }
+        match response {
             Ok(Ok(Ok(response))) => response.decode_data(),
             Ok(Ok(Err(e))) => Err(e),
             Ok(Err(_)) => Err(ClusterError::Network("Channel closed".to_string())),
@@ -279,6 +312,11 @@ impl RpcService {
         self.server.register(method, handler);
     }
 
+    /// Take the queue of outgoing requests.
+    pub fn take_outbound(&self) -> Option<OutboundRpc> {
+        self.client.take_outbound()
+    }
+
     /// Make an RPC call.
     pub async fn call<T, R>(
         &self,
@@ -365,4 +403,33 @@ mod tests {
         let result: TestResult = response.decode_data().unwrap();
         assert_eq!(result.result, 42);
     }
+    #[tokio::test]
+    async fn test_call_round_trip() {
+        let (a_id, b_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let a = RpcService::new(a_id);
+        let b = Arc::new(RpcService::new(b_id));
+        b.register("double", |req: RpcRequest| {
+            let params: TestParams = req.decode_params()?;
+            RpcResponse::success(&TestResult { result: params.value * 2 })
+        });
+
+        let mut outbound = a.take_outbound().unwrap();
+        assert!(a.take_outbound().is_none());
+        let client = Arc::clone(&a.client);
+        let transport = tokio::spawn(async move {
+            while let Some((to, message)) = outbound.recv().await {
+                assert_eq!(to, b_id);
+                if let Some(reply) = b.handle_message(message).unwrap() {
+                    client.handle_response(reply).unwrap();
+                }
+            }
+        });
+
+        let result: TestResult = a.call(b_id, "double", &TestParams { value: 21 }).await.unwrap();
+        assert_eq!(result.result, 42);
+        assert!(a.call::<_, TestResult>(b_id, "missing", &()).await.is_err());
+        assert_eq!(a.client.pending_count(), 0);
+
+        transport.abort();
+    }
 }
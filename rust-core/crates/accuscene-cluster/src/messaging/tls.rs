@@ -0,0 +1,612 @@
+//! TLS with mutual authentication for inter-node connections.
+//!
+//! Each node holds an X.509 certificate issued by the cluster's
+//! [`CertificateAuthority`] with its node ID as the subject. Both ends of a
+//! connection present their certificate and check the other's chain against
+//! the trusted cluster roots, and the peer must be a known cluster member;
+//! anything else fails the handshake. Connections are TLS 1.3 only.
+//!
+//! Certificates rotate without dropping connections: a new identity is used
+//! for handshakes from then on while established connections keep running.
+//! To move to a new CA, trust its root on every node first, then issue new
+//! identities, then stop trusting the old root.
+
+use crate::error::{ClusterError, Result};
+use crate::messaging::Message;
+use accuscene_crypto::asymmetric::{verify_signature, Ed25519KeyPair, Signature};
+use accuscene_crypto::certificate::{CertificateAuthority, CertificateBuilder, X509Certificate};
+use accuscene_crypto::CryptoError;
+use parking_lot::RwLock;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::ResolvesClientCert;
+use rustls::crypto::ring::sign::any_eddsa_type;
+use rustls::pki_types::{CertificateDer, PrivatePkcs8KeyDer, ServerName, UnixTime};
+use rustls::server::danger::{ClientCertVerified, ClientCertVerifier};
+use rustls::server::{ClientHello, ResolvesServerCert};
+use rustls::sign::CertifiedKey;
+use rustls::{
+    CertificateError, ClientConfig, DigitallySignedStruct, DistinguishedName, ServerConfig,
+    SignatureScheme,
+};
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_rustls::{TlsAcceptor, TlsConnector, TlsStream};
+use uuid::Uuid;
+
+/// Purpose recorded on node certificates.
+const NODE_CERT_PURPOSE: &str = "cluster-node";
+
+/// PKCS#8 v1 prefix of an Ed25519 private key (RFC 8410).
+const ED25519_PKCS8_PREFIX: [u8; 16] = [
+    0x30, 0x2e, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x04, 0x22, 0x04, 0x20,
+];
+
+/// Largest frame accepted from a peer.
+const MAX_FRAME_SIZE: usize = 64 * 1024 * 1024;
+
+/// A node's certificate and key.
+#[derive(Clone)]
+pub struct NodeIdentity {
+    /// Node the certificate names
+    node_id: Uuid,
+
+    /// Node certificate followed by any intermediate CA certificates
+    chain: Vec<X509Certificate>,
+
+    /// Key pair of the node certificate
+    keypair: Ed25519KeyPair,
+}
+
+impl NodeIdentity {
+    /// Issue a node certificate with a fresh key pair.
+    pub fn issue(ca: &CertificateAuthority, node_id: Uuid, validity: Duration) -> Result<Self> {
+        let keypair = Ed25519KeyPair::generate()?;
+        let certificate = CertificateBuilder::new(node_id.to_string(), keypair.public_key().clone())
+            .validity(validity)
+            .purpose(NODE_CERT_PURPOSE.to_string())
+            .sign(ca)?;
+
+        Ok(Self {
+            node_id,
+            chain: vec![ca.export_x509(&certificate)?],
+            keypair,
+        })
+    }
+
+    /// Send an intermediate CA certificate along with the node certificate.
+    pub fn with_intermediate(mut self, certificate: X509Certificate) -> Self {
+        self.chain.push(certificate);
+        self
+    }
+
+    /// Get the node ID.
+    pub fn node_id(&self) -> Uuid {
+        self.node_id
+    }
+
+    /// Get the node certificate.
+    pub fn certificate(&self) -> &X509Certificate {
+        &self.chain[0]
+    }
+
+    /// Build the key rustls signs handshakes with.
+    fn certified_key(&self) -> Result<Arc<CertifiedKey>> {
+        let mut pkcs8 = ED25519_PKCS8_PREFIX.to_vec();
+        pkcs8.extend_from_slice(self.keypair.secret_key().as_bytes());
+        let key = any_eddsa_type(&PrivatePkcs8KeyDer::from(pkcs8))?;
+
+        let chain = self
+            .chain
+            .iter()
+            .map(|cert| CertificateDer::from(cert.to_der().to_vec()))
+            .collect();
+
+        Ok(Arc::new(CertifiedKey::new(chain, key)))
+    }
+}
+
+/// Serves the current identity to new handshakes.
+#[derive(Debug)]
+struct IdentityResolver {
+    current: RwLock<Arc<CertifiedKey>>,
+}
+
+impl ResolvesClientCert for IdentityResolver {
+    fn resolve(&self, _hints: &[&[u8]], _schemes: &[SignatureScheme]) -> Option<Arc<CertifiedKey>> {
+        Some(self.current.read().clone())
+    }
+
+    fn has_certs(&self) -> bool {
+        true
+    }
+}
+
+impl ResolvesServerCert for IdentityResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.current.read().clone())
+    }
+}
+
+/// Verifies peer certificates against the cluster roots and membership.
+#[derive(Debug)]
+struct PeerVerifier {
+    /// Trusted CA roots
+    roots: RwLock<Vec<X509Certificate>>,
+
+    /// Nodes allowed to connect
+    peers: RwLock<HashSet<Uuid>>,
+}
+
+impl PeerVerifier {
+    /// Check a peer's chain and membership, returning its node ID.
+    fn verify_peer(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        now: UnixTime,
+    ) -> std::result::Result<Uuid, rustls::Error> {
+        let leaf = parse(end_entity)?;
+        let intermediates = intermediates
+            .iter()
+            .map(parse)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        if leaf.is_ca() {
+            return Err(CertificateError::InvalidPurpose.into());
+        }
+
+        let mut result = Err(CertificateError::UnknownIssuer);
+        for root in self.roots.read().iter() {
+            match leaf.verify_chain(&intermediates, root, now.as_secs()) {
+                Ok(()) => {
+                    result = Ok(());
+                    break;
+                }
+                Err(CryptoError::CertificateExpired) => result = Err(CertificateError::Expired),
+                Err(_) => {}
+            }
+        }
+        result?;
+
+        let node_id = Uuid::parse_str(leaf.subject()).map_err(|_| CertificateError::NotValidForName)?;
+        if !self.peers.read().contains(&node_id) {
+            tracing::warn!("Rejected connection from unknown node {}", node_id);
+            return Err(CertificateError::ApplicationVerificationFailure.into());
+        }
+
+        Ok(node_id)
+    }
+
+    /// Check a handshake signature made with a peer's certificate key.
+    fn verify_handshake(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        if dss.scheme != SignatureScheme::ED25519 {
+            return Err(CertificateError::BadSignature.into());
+        }
+
+        let signature: [u8; 64] = dss
+            .signature()
+            .try_into()
+            .map_err(|_| CertificateError::BadSignature)?;
+
+        match verify_signature(parse(cert)?.public_key(), message, &Signature::from_bytes(signature)) {
+            Ok(true) => Ok(HandshakeSignatureValid::assertion()),
+            _ => Err(CertificateError::BadSignature.into()),
+        }
+    }
+}
+
+impl ServerCertVerifier for PeerVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        let node_id = self.verify_peer(end_entity, intermediates, now)?;
+
+        // The server must be the node that was dialled
+        match server_name {
+            ServerName::DnsName(name) if name.as_ref() == node_id.to_string() => {
+                Ok(ServerCertVerified::assertion())
+            }
+            _ => Err(CertificateError::NotValidForName.into()),
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        Err(rustls::Error::General("TLS 1.2 is not supported".to_string()))
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        self.verify_handshake(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        vec![SignatureScheme::ED25519]
+    }
+}
+
+impl ClientCertVerifier for PeerVerifier {
+    fn root_hint_subjects(&self) -> &[DistinguishedName] {
+        &[]
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        now: UnixTime,
+    ) -> std::result::Result<ClientCertVerified, rustls::Error> {
+        self.verify_peer(end_entity, intermediates, now)?;
+        Ok(ClientCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        Err(rustls::Error::General("TLS 1.2 is not supported".to_string()))
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        self.verify_handshake(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        vec![SignatureScheme::ED25519]
+    }
+}
+
+fn parse(cert: &CertificateDer<'_>) -> std::result::Result<X509Certificate, rustls::Error> {
+    X509Certificate::from_der(cert.as_ref()).map_err(|_| CertificateError::BadEncoding.into())
+}
+
+/// Mutually authenticated TLS for cluster connections.
+pub struct ClusterTls {
+    /// Local node ID
+    local_id: Uuid,
+
+    /// Current identity
+    identity: Arc<IdentityResolver>,
+
+    /// Trusted roots and known peers
+    verifier: Arc<PeerVerifier>,
+
+    /// Configuration for outgoing connections
+    connector: TlsConnector,
+
+    /// Configuration for incoming connections
+    acceptor: TlsAcceptor,
+}
+
+impl ClusterTls {
+    /// Create TLS for a node trusting certificates under `root`.
+    pub fn new(identity: NodeIdentity, root: X509Certificate) -> Result<Self> {
+        if !root.is_ca() {
+            return Err(ClusterError::InvalidConfiguration(
+                "Trust root is not a CA certificate".to_string(),
+            ));
+        }
+
+        let local_id = identity.node_id();
+        let resolver = Arc::new(IdentityResolver {
+            current: RwLock::new(identity.certified_key()?),
+        });
+        let verifier = Arc::new(PeerVerifier {
+            roots: RwLock::new(vec![root]),
+            peers: RwLock::new(HashSet::new()),
+        });
+
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+
+        let client = ClientConfig::builder_with_provider(provider.clone())
+            .with_protocol_versions(&[&rustls::version::TLS13])?
+            .dangerous()
+            .with_custom_certificate_verifier(verifier.clone())
+            .with_client_cert_resolver(resolver.clone());
+
+        let server = ServerConfig::builder_with_provider(provider)
+            .with_protocol_versions(&[&rustls::version::TLS13])?
+            .with_client_cert_verifier(verifier.clone())
+            .with_cert_resolver(resolver.clone());
+
+        Ok(Self {
+            local_id,
+            identity: resolver,
+            verifier,
+            connector: TlsConnector::from(Arc::new(client)),
+            acceptor: TlsAcceptor::from(Arc::new(server)),
+        })
+    }
+
+    /// Get the local node ID.
+    pub fn local_id(&self) -> Uuid {
+        self.local_id
+    }
+
+    /// Allow a node to connect.
+    pub fn allow_peer(&self, node_id: Uuid) {
+        self.verifier.peers.write().insert(node_id);
+    }
+
+    /// Stop accepting new connections from a node.
+    pub fn revoke_peer(&self, node_id: &Uuid) {
+        self.verifier.peers.write().remove(node_id);
+    }
+
+    /// Check if a node may connect.
+    pub fn is_allowed(&self, node_id: &Uuid) -> bool {
+        self.verifier.peers.read().contains(node_id)
+    }
+
+    /// Use a new certificate for handshakes from now on.
+    pub fn rotate_identity(&self, identity: NodeIdentity) -> Result<()> {
+        if identity.node_id() != self.local_id {
+            return Err(ClusterError::InvalidConfiguration(format!(
+                "Certificate is for node {}, not {}",
+                identity.node_id(),
+                self.local_id
+            )));
+        }
+
+        *self.identity.current.write() = identity.certified_key()?;
+        tracing::info!("Rotated TLS certificate for node {}", self.local_id);
+        Ok(())
+    }
+
+    /// Trust certificates issued under another CA root.
+    pub fn add_trust_root(&self, root: X509Certificate) -> Result<()> {
+        if !root.is_ca() {
+            return Err(ClusterError::InvalidConfiguration(
+                "Trust root is not a CA certificate".to_string(),
+            ));
+        }
+
+        let mut roots = self.verifier.roots.write();
+        if !roots.iter().any(|r| r.to_der() == root.to_der()) {
+            roots.push(root);
+        }
+        Ok(())
+    }
+
+    /// Stop trusting the CA root with the given subject.
+    pub fn remove_trust_root(&self, subject: &str) {
+        self.verifier.roots.write().retain(|root| root.subject() != subject);
+    }
+
+    /// Connect to a node.
+    pub async fn connect(&self, addr: SocketAddr, node_id: Uuid) -> Result<PeerConnection> {
+        let server_name = ServerName::try_from(node_id.to_string())
+            .map_err(|e| ClusterError::Tls(e.to_string()))?;
+
+        let stream = TcpStream::connect(addr).await?;
+        let stream = self.connector.connect(server_name, stream).await?;
+
+        Ok(PeerConnection {
+            peer_id: node_id,
+            stream: TlsStream::Client(stream),
+        })
+    }
+
+    /// Complete the handshake of an incoming connection.
+    pub async fn accept(&self, stream: TcpStream) -> Result<PeerConnection> {
+        let stream = self.acceptor.accept(stream).await?;
+
+        let peer_id = stream
+            .get_ref()
+            .1
+            .peer_certificates()
+            .and_then(|certs| certs.first())
+            .and_then(|cert| X509Certificate::from_der(cert.as_ref()).ok())
+            .and_then(|cert| Uuid::parse_str(cert.subject()).ok())
+            .ok_or_else(|| ClusterError::Tls("Peer presented no node certificate".to_string()))?;
+
+        Ok(PeerConnection {
+            peer_id,
+            stream: TlsStream::Server(stream),
+        })
+    }
+}
+
+/// Authenticated connection to another node.
+pub struct PeerConnection {
+    /// Node on the other end, as named by its certificate
+    peer_id: Uuid,
+
+    /// TLS stream
+    stream: TlsStream<TcpStream>,
+}
+
+impl PeerConnection {
+    /// Get the node on the other end.
+    pub fn peer_id(&self) -> Uuid {
+        self.peer_id
+    }
+
+    /// Send a message.
+    pub async fn send(&mut self, message: &Message) -> Result<()> {
+        let frame = message.encode();
+        self.stream.write_u32(frame.len() as u32).await?;
+        self.stream.write_all(&frame).await?;
+        self.stream.flush().await?;
+        Ok(())
+    }
+
+    /// Receive a message.
+    ///
+    /// Messages must come from the authenticated node.
+    pub async fn recv(&mut self) -> Result<Message> {
+        let len = self.stream.read_u32().await? as usize;
+        if len > MAX_FRAME_SIZE {
+            return Err(ClusterError::InvalidMessage(format!("Frame of {} bytes is too large", len)));
+        }
+
+        let mut frame = vec![0u8; len];
+        self.stream.read_exact(&mut frame).await?;
+
+        let message = Message::decode(frame.as_slice())?;
+        if message.header.source != self.peer_id {
+            return Err(ClusterError::InvalidMessage(format!(
+                "Message from {} on connection with {}",
+                message.header.source, self.peer_id
+            )));
+        }
+
+        Ok(message)
+    }
+
+    /// Close the connection.
+    pub async fn close(mut self) -> Result<()> {
+        self.stream.shutdown().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messaging::MessageType;
+    use tokio::net::TcpListener;
+
+    const VALIDITY: Duration = Duration::from_secs(3600);
+
+    struct Cluster {
+        ca: CertificateAuthority,
+        root: X509Certificate,
+    }
+
+    impl Cluster {
+        fn new(name: &str) -> Self {
+            let ca = CertificateAuthority::generate(name.to_string()).unwrap();
+            let root = ca.x509_root(VALIDITY).unwrap();
+            Self { ca, root }
+        }
+
+        fn node(&self) -> ClusterTls {
+            let identity = NodeIdentity::issue(&self.ca, Uuid::new_v4(), VALIDITY).unwrap();
+            ClusterTls::new(identity, self.root.clone()).unwrap()
+        }
+    }
+
+    /// Accept one connection on `server` while `client` dials it.
+    async fn handshake(
+        client: &ClusterTls,
+        server: &ClusterTls,
+    ) -> (Result<PeerConnection>, Result<PeerConnection>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let accept = async {
+            let (stream, _) = listener.accept().await.unwrap();
+            server.accept(stream).await
+        };
+        tokio::join!(client.connect(addr, server.local_id()), accept)
+    }
+
+    #[tokio::test]
+    async fn test_mutual_authentication() {
+        let cluster = Cluster::new("AccuScene Cluster CA");
+        let a = cluster.node();
+        let b = cluster.node();
+        a.allow_peer(b.local_id());
+        b.allow_peer(a.local_id());
+
+        let (client, server) = handshake(&a, &b).await;
+        let (mut client, mut server) = (client.unwrap(), server.unwrap());
+        assert_eq!(client.peer_id(), b.local_id());
+        assert_eq!(server.peer_id(), a.local_id());
+
+        let ping = Message::new(MessageType::Ping, a.local_id(), &"ping").unwrap();
+        client.send(&ping).await.unwrap();
+        let received = server.recv().await.unwrap();
+        assert_eq!(received.decode_payload::<String>().unwrap(), "ping");
+
+        // A message claiming another source is rejected
+        let forged = Message::new(MessageType::Ping, Uuid::new_v4(), &"ping").unwrap();
+        client.send(&forged).await.unwrap();
+        assert!(server.recv().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_peers_rejected() {
+        let cluster = Cluster::new("AccuScene Cluster CA");
+        let a = cluster.node();
+        let b = cluster.node();
+
+        // b does not know a
+        a.allow_peer(b.local_id());
+        let (client, server) = handshake(&a, &b).await;
+        assert!(server.is_err());
+        drop(client);
+
+        // A node with a certificate from another CA
+        let outsider = Cluster::new("Other CA").node();
+        outsider.allow_peer(b.local_id());
+        b.allow_peer(outsider.local_id());
+        let (client, server) = handshake(&outsider, &b).await;
+        assert!(client.is_err());
+        assert!(server.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rotation() {
+        let old = Cluster::new("Cluster CA 2025");
+        let new = Cluster::new("Cluster CA 2026");
+        let a = old.node();
+        let b = old.node();
+        a.allow_peer(b.local_id());
+        b.allow_peer(a.local_id());
+
+        let (client, server) = handshake(&a, &b).await;
+        let (mut established, mut server) = (client.unwrap(), server.unwrap());
+
+        // Trust the new root everywhere, then move both nodes to it
+        a.add_trust_root(new.root.clone()).unwrap();
+        b.add_trust_root(new.root.clone()).unwrap();
+        a.rotate_identity(NodeIdentity::issue(&new.ca, a.local_id(), VALIDITY).unwrap()).unwrap();
+        b.rotate_identity(NodeIdentity::issue(&new.ca, b.local_id(), VALIDITY).unwrap()).unwrap();
+        a.remove_trust_root("Cluster CA 2025");
+        b.remove_trust_root("Cluster CA 2025");
+
+        // The established connection keeps working
+        let ping = Message::new(MessageType::Ping, a.local_id(), &1u32).unwrap();
+        established.send(&ping).await.unwrap();
+        assert_eq!(server.recv().await.unwrap().decode_payload::<u32>().unwrap(), 1);
+
+        // New connections use the new certificates
+        let (client, server) = handshake(&a, &b).await;
+        assert!(client.is_ok() && server.is_ok());
+
+        // A certificate for another node cannot be installed
+        let other = NodeIdentity::issue(&new.ca, Uuid::new_v4(), VALIDITY).unwrap();
+        assert!(a.rotate_identity(other).is_err());
+    }
+}
@@ -4,6 +4,7 @@ use std::collections::BTreeMap;
 use uuid::Uuid;
 
 /// Consistent hash ring.
+#[derive(Clone)]
 pub struct ConsistentHashRing {
     /// Virtual nodes on the ring
     ring: BTreeMap<u64, Uuid>,
@@ -2,9 +2,13 @@
 
 pub mod consistent_hash;
 pub mod range;
+pub mod rebalance;
 
 pub use consistent_hash::{ConsistentHashRing, RingStats};
 pub use range::{RangePartition, RangePartitionMap};
+pub use rebalance::{
+    OwnershipChange, RebalancePlan, RebalanceProgress, RebalanceState, Rebalancer, Transfer,
+};
 
 use uuid::Uuid;
 
@@ -18,6 +22,7 @@ pub enum PartitioningStrategy {
 }
 
 /// Unified partitioning service.
+#[derive(Clone)]
 pub struct PartitioningService {
     /// Partitioning strategy
     strategy: PartitioningStrategy,
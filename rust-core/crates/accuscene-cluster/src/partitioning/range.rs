@@ -61,6 +61,7 @@ impl RangePartition {
 }
 
 /// Range partition map.
+#[derive(Clone)]
 pub struct RangePartitionMap {
     /// Partitions sorted by start key
     partitions: BTreeMap<String, RangePartition>,
@@ -0,0 +1,421 @@
+//! Partition rebalancing after membership changes.
+//!
+//! When nodes join or leave, each node compares who owned its keys before
+//! and after the change. A key is only sent to the replicas that newly own
+//! it, and only by one node: the first previous owner still in the ring, or
+//! the local node when none of them is left. Keys are sent in batches as
+//! anti-entropy data, so receivers merge them by version, and batches are
+//! paced to the configured byte rate.
+
+use crate::config::ReplicationConfig;
+use crate::error::{ClusterError, Result};
+use crate::partitioning::PartitioningService;
+use crate::replication::{ReplicationService, SyncResponse};
+use chrono::{DateTime, Utc};
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::{debug, info};
+use uuid::Uuid;
+
+/// Queue of rebalance batches waiting for the transport.
+pub type OutboundTransfers = mpsc::UnboundedReceiver<(Uuid, SyncResponse)>;
+
+/// Change in the replicas owning a key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnershipChange {
+    /// Key
+    pub key: String,
+
+    /// Owners before the change
+    pub before: Vec<Uuid>,
+
+    /// Owners that gained the key
+    pub gained: Vec<Uuid>,
+
+    /// Owners that lost the key
+    pub lost: Vec<Uuid>,
+}
+
+impl OwnershipChange {
+    /// Get the node that sends the key to its new owners.
+    pub fn sender(&self, after: &PartitioningService, local_id: Uuid) -> Uuid {
+        let remaining = after.all_nodes();
+        self.before
+            .iter()
+            .find(|id| remaining.contains(id))
+            .copied()
+            .unwrap_or(local_id)
+    }
+}
+
+/// Compare the owners of each key before and after a membership change.
+pub fn ownership_diff<'a>(
+    keys: impl IntoIterator<Item = &'a String>,
+    before: &PartitioningService,
+    after: &PartitioningService,
+) -> Vec<OwnershipChange> {
+    keys.into_iter()
+        .filter_map(|key| {
+            let old = before.get_nodes(key);
+            let new = after.get_nodes(key);
+
+            let gained: Vec<_> = new.iter().filter(|id| !old.contains(id)).copied().collect();
+            let lost: Vec<_> = old.iter().filter(|id| !new.contains(id)).copied().collect();
+            if gained.is_empty() && lost.is_empty() {
+                return None;
+            }
+
+            Some(OwnershipChange {
+                key: key.clone(),
+                before: old,
+                gained,
+                lost,
+            })
+        })
+        .collect()
+}
+
+/// Keys to send to one node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transfer {
+    /// Receiving node
+    pub target: Uuid,
+
+    /// Keys to send
+    pub keys: Vec<String>,
+}
+
+/// Transfers the local node owes after a membership change.
+#[derive(Debug, Clone, Default)]
+pub struct RebalancePlan {
+    /// Transfers by target node
+    pub transfers: Vec<Transfer>,
+}
+
+impl RebalancePlan {
+    /// Plan the transfers of the local node's keys.
+    pub fn compute<'a>(
+        local_id: Uuid,
+        keys: impl IntoIterator<Item = &'a String>,
+        before: &PartitioningService,
+        after: &PartitioningService,
+    ) -> Self {
+        let mut by_target: BTreeMap<Uuid, Vec<String>> = BTreeMap::new();
+
+        for change in ownership_diff(keys, before, after) {
+            if change.sender(after, local_id) != local_id {
+                continue;
+            }
+
+            for target in change.gained.iter().filter(|&&id| id != local_id) {
+                by_target.entry(*target).or_default().push(change.key.clone());
+            }
+        }
+
+        Self {
+            transfers: by_target
+                .into_iter()
+                .map(|(target, keys)| Transfer { target, keys })
+                .collect(),
+        }
+    }
+
+    /// Get the number of keys to send, counting each target.
+    pub fn key_count(&self) -> usize {
+        self.transfers.iter().map(|t| t.keys.len()).sum()
+    }
+
+    /// Check if there is nothing to send.
+    pub fn is_empty(&self) -> bool {
+        self.transfers.is_empty()
+    }
+}
+
+/// Rebalance state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum RebalanceState {
+    /// No rebalance has run
+    Idle,
+    /// Sending keys
+    Running,
+    /// The last rebalance finished
+    Completed,
+    /// The last rebalance stopped on an error
+    Failed,
+}
+
+/// Progress of the current or last rebalance.
+#[derive(Debug, Clone, Serialize)]
+pub struct RebalanceProgress {
+    /// Rebalance state
+    pub state: RebalanceState,
+
+    /// Rebalances finished since startup
+    pub completed: u64,
+
+    /// Keys to send
+    pub keys_total: usize,
+
+    /// Keys sent
+    pub keys_moved: usize,
+
+    /// Bytes sent
+    pub bytes_moved: u64,
+
+    /// Target nodes not yet done
+    pub pending_transfers: usize,
+
+    /// Start time
+    pub started_at: Option<DateTime<Utc>>,
+
+    /// Finish time
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl Default for RebalanceProgress {
+    fn default() -> Self {
+        Self {
+            state: RebalanceState::Idle,
+            completed: 0,
+            keys_total: 0,
+            keys_moved: 0,
+            bytes_moved: 0,
+            pending_transfers: 0,
+            started_at: None,
+            finished_at: None,
+        }
+    }
+}
+
+/// Moves data between nodes after membership changes.
+pub struct Rebalancer {
+    /// Local node ID
+    local_id: Uuid,
+
+    /// Local replica data
+    replication: Arc<ReplicationService>,
+
+    /// Keys per batch
+    batch_size: usize,
+
+    /// Bytes per second, zero for no limit
+    rate_limit: u64,
+
+    /// Progress of the current or last run
+    progress: Arc<RwLock<RebalanceProgress>>,
+
+    /// Runs one rebalance at a time
+    run_lock: tokio::sync::Mutex<()>,
+
+    /// Outgoing batches
+    outbound_tx: mpsc::UnboundedSender<(Uuid, SyncResponse)>,
+    outbound_rx: Mutex<Option<OutboundTransfers>>,
+}
+
+impl Rebalancer {
+    /// Create a new rebalancer.
+    pub fn new(local_id: Uuid, replication: Arc<ReplicationService>, config: &ReplicationConfig) -> Self {
+        let (outbound_tx, outbound_rx) = mpsc::unbounded_channel();
+
+        Self {
+            local_id,
+            replication,
+            batch_size: config.rebalance_batch_size.max(1),
+            rate_limit: config.rebalance_rate_limit,
+            progress: Arc::new(RwLock::new(RebalanceProgress::default())),
+            run_lock: tokio::sync::Mutex::new(()),
+            outbound_tx,
+            outbound_rx: Mutex::new(Some(outbound_rx)),
+        }
+    }
+
+    /// Take the queue of outgoing batches.
+    ///
+    /// The transport sends each batch to its node, which applies it with
+    /// [`ReplicationService::apply_sync_response`]. Only the first call gets
+    /// the queue.
+    pub fn take_outbound(&self) -> Option<OutboundTransfers> {
+        self.outbound_rx.lock().take()
+    }
+
+    /// Get rebalance progress.
+    pub fn progress(&self) -> RebalanceProgress {
+        self.progress.read().clone()
+    }
+
+    /// Plan the transfers of the local data for a membership change.
+    pub fn plan(&self, before: &PartitioningService, after: &PartitioningService) -> RebalancePlan {
+        let snapshot = self.replication.get_snapshot();
+        RebalancePlan::compute(self.local_id, snapshot.data.keys(), before, after)
+    }
+
+    /// Send the planned keys, waiting for earlier runs to finish first.
+    pub async fn run(&self, plan: RebalancePlan) -> Result<()> {
+        let _running = self.run_lock.lock().await;
+        if plan.is_empty() {
+            return Ok(());
+        }
+
+        {
+            let mut progress = self.progress.write();
+            progress.state = RebalanceState::Running;
+            progress.keys_total = plan.key_count();
+            progress.keys_moved = 0;
+            progress.bytes_moved = 0;
+            progress.pending_transfers = plan.transfers.len();
+            progress.started_at = Some(Utc::now());
+            progress.finished_at = None;
+        }
+
+        info!(
+            "Rebalancing {} keys to {} nodes",
+            plan.key_count(),
+            plan.transfers.len()
+        );
+
+        let result = self.send(plan).await;
+
+        let mut progress = self.progress.write();
+        progress.finished_at = Some(Utc::now());
+        match result {
+            Ok(()) => {
+                progress.state = RebalanceState::Completed;
+                progress.completed += 1;
+                info!("Rebalance moved {} keys", progress.keys_moved);
+            }
+            Err(_) => progress.state = RebalanceState::Failed,
+        }
+
+        result
+    }
+
+    /// Send the plan's keys in paced batches.
+    async fn send(&self, plan: RebalancePlan) -> Result<()> {
+        let data = self.replication.get_snapshot().data;
+        let started = Instant::now();
+        let mut bytes_sent = 0u64;
+
+        for transfer in plan.transfers {
+            for keys in transfer.keys.chunks(self.batch_size) {
+                let batch: HashMap<String, Vec<u8>> = keys
+                    .iter()
+                    .filter_map(|key| data.get(key).map(|value| (key.clone(), value.clone())))
+                    .collect();
+                let bytes: u64 = batch.iter().map(|(k, v)| (k.len() + v.len()) as u64).sum();
+
+                self.outbound_tx
+                    .send((transfer.target, SyncResponse::AntiEntropyData { data: batch }))
+                    .map_err(|_| ClusterError::Network("Rebalance transport closed".to_string()))?;
+
+                bytes_sent += bytes;
+                {
+                    let mut progress = self.progress.write();
+                    progress.keys_moved += keys.len();
+                    progress.bytes_moved += bytes;
+                }
+
+                if let Some(delay) = throttle_delay(bytes_sent, self.rate_limit, started.elapsed()) {
+                    tokio::time::sleep(delay).await;
+                }
+            }
+
+            debug!("Rebalance transfer to {} done", transfer.target);
+            self.progress.write().pending_transfers -= 1;
+        }
+
+        Ok(())
+    }
+}
+
+/// Time to wait so that `bytes` sent over `elapsed` stays within `rate` bytes per second.
+fn throttle_delay(bytes: u64, rate: u64, elapsed: Duration) -> Option<Duration> {
+    if rate == 0 {
+        return None;
+    }
+
+    let due = Duration::from_secs_f64(bytes as f64 / rate as f64);
+    due.checked_sub(elapsed).filter(|delay| !delay.is_zero())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(nodes: &[Uuid]) -> PartitioningService {
+        let mut partitioning = PartitioningService::with_consistent_hash(50, 2);
+        for node in nodes {
+            partitioning.add_node(*node);
+        }
+        partitioning
+    }
+
+    #[test]
+    fn test_only_gained_keys_move() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let keys: Vec<String> = (0..500).map(|i| format!("case-{}", i)).collect();
+
+        let before = ring(&[a, b]);
+        let after = ring(&[a, b, c]);
+
+        let changes = ownership_diff(&keys, &before, &after);
+        assert!(!changes.is_empty() && changes.len() < keys.len());
+        assert!(changes.iter().all(|change| change.gained == vec![c] && change.lost.len() == 1));
+
+        // Each moved key is sent once, by its previous primary
+        let from_a = RebalancePlan::compute(a, &keys, &before, &after);
+        let from_b = RebalancePlan::compute(b, &keys, &before, &after);
+        assert_eq!(from_a.key_count() + from_b.key_count(), changes.len());
+        assert!(from_a.transfers.iter().chain(&from_b.transfers).all(|t| t.target == c));
+    }
+
+    #[tokio::test]
+    async fn test_rebalance_streams_keys() {
+        let (a, c) = (Uuid::new_v4(), Uuid::new_v4());
+        let config = ReplicationConfig {
+            rebalance_batch_size: 7,
+            ..ReplicationConfig::default()
+        };
+
+        let source = Arc::new(ReplicationService::new(a, config.clone()));
+        for i in 0..50 {
+            source.write(format!("case-{}", i), vec![i as u8]).await.unwrap();
+        }
+
+        let rebalancer = Rebalancer::new(a, Arc::clone(&source), &config);
+        let mut outbound = rebalancer.take_outbound().unwrap();
+        assert!(rebalancer.take_outbound().is_none());
+
+        let plan = rebalancer.plan(&ring(&[a]), &ring(&[a, c]));
+        let moved = plan.key_count();
+        assert_eq!(moved, 50);
+
+        rebalancer.run(plan).await.unwrap();
+
+        let target = ReplicationService::new(c, config);
+        let mut batches = 0;
+        while let Ok((node, batch)) = outbound.try_recv() {
+            assert_eq!(node, c);
+            target.apply_sync_response(batch).await.unwrap();
+            batches += 1;
+        }
+        assert_eq!(batches, 8);
+        assert_eq!(target.read("case-42").await.unwrap(), Some(vec![42]));
+
+        let progress = rebalancer.progress();
+        assert_eq!(progress.state, RebalanceState::Completed);
+        assert_eq!(progress.keys_moved, moved);
+        assert_eq!(progress.pending_transfers, 0);
+        assert!(progress.bytes_moved > 0);
+    }
+
+    #[test]
+    fn test_throttle_delay() {
+        assert_eq!(throttle_delay(1000, 0, Duration::ZERO), None);
+        assert_eq!(throttle_delay(1000, 1000, Duration::from_millis(400)), Some(Duration::from_millis(600)));
+        assert_eq!(throttle_delay(1000, 1000, Duration::from_secs(2)), None);
+    }
+}
@@ -2,7 +2,7 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
 /// Versioned value with metadata.
@@ -45,17 +45,20 @@ impl VersionedValue {
 }
 
 /// Vector clock for causal ordering.
+///
+/// Clocks are kept ordered by node so equal clocks serialize identically,
+/// which replica digests rely on.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct VectorClock {
     /// Clock values for each node
-    clocks: HashMap<Uuid, u64>,
+    clocks: BTreeMap<Uuid, u64>,
 }
 
 impl VectorClock {
     /// Create a new vector clock.
     pub fn new() -> Self {
         Self {
-            clocks: HashMap::new(),
+            clocks: BTreeMap::new(),
         }
     }
 
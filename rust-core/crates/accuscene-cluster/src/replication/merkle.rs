@@ -0,0 +1,153 @@
+//! Merkle trees for anti-entropy.
+//!
+//! Keys are hashed into a fixed number of ranges. Each leaf hashes the keys
+//! and values in one range and each inner node hashes its children, so two
+//! replicas find the ranges they disagree on by comparing trees top-down
+//! and only exchange the keys in those ranges.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use xxhash_rust::xxh3::{xxh3_64, Xxh3};
+
+/// Merkle tree over a replica's state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleTree {
+    /// Number of key ranges (leaves), a power of two
+    ranges: usize,
+
+    /// Node hashes in heap order: the root first, leaves last
+    nodes: Vec<u64>,
+}
+
+impl MerkleTree {
+    /// Build a tree over state data, rounding `ranges` up to a power of two.
+    pub fn build(data: &HashMap<String, Vec<u8>>, ranges: usize) -> Self {
+        let ranges = ranges.max(1).next_power_of_two();
+
+        let mut buckets: Vec<Vec<(&str, &[u8])>> = vec![Vec::new(); ranges];
+        for (key, value) in data {
+            buckets[Self::range_of(key, ranges)].push((key.as_str(), value.as_slice()));
+        }
+
+        let mut nodes = vec![0u64; 2 * ranges - 1];
+        for (range, mut bucket) in buckets.into_iter().enumerate() {
+            nodes[ranges - 1 + range] = Self::hash_range(&mut bucket);
+        }
+        for index in (0..ranges - 1).rev() {
+            nodes[index] = Self::hash_children(nodes[2 * index + 1], nodes[2 * index + 2]);
+        }
+
+        Self { ranges, nodes }
+    }
+
+    /// Get the range a key falls in.
+    pub fn range_of(key: &str, ranges: usize) -> usize {
+        (xxh3_64(key.as_bytes()) % ranges as u64) as usize
+    }
+
+    /// Get the number of key ranges.
+    pub fn ranges(&self) -> usize {
+        self.ranges
+    }
+
+    /// Get the root hash.
+    pub fn root(&self) -> u64 {
+        self.nodes[0]
+    }
+
+    /// Find the key ranges where this tree and another differ.
+    pub fn diff(&self, other: &MerkleTree) -> Vec<usize> {
+        if self.ranges != other.ranges {
+            return (0..self.ranges).collect();
+        }
+
+        let mut divergent = Vec::new();
+        let mut pending = vec![0];
+
+        while let Some(index) = pending.pop() {
+            if self.nodes[index] == other.nodes[index] {
+                continue;
+            }
+
+            if index >= self.ranges - 1 {
+                divergent.push(index - (self.ranges - 1));
+            } else {
+                pending.push(2 * index + 2);
+                pending.push(2 * index + 1);
+            }
+        }
+
+        divergent.sort_unstable();
+        divergent
+    }
+
+    /// Hash the entries of one range; empty ranges hash to zero.
+    fn hash_range(entries: &mut [(&str, &[u8])]) -> u64 {
+        if entries.is_empty() {
+            return 0;
+        }
+
+        entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+        let mut hasher = Xxh3::new();
+        for (key, value) in entries.iter() {
+            hasher.update(&(key.len() as u64).to_le_bytes());
+            hasher.update(key.as_bytes());
+            hasher.update(&(value.len() as u64).to_le_bytes());
+            hasher.update(value);
+        }
+        hasher.digest()
+    }
+
+    fn hash_children(left: u64, right: u64) -> u64 {
+        if left == 0 && right == 0 {
+            return 0;
+        }
+
+        let mut bytes = [0u8; 16];
+        bytes[..8].copy_from_slice(&left.to_le_bytes());
+        bytes[8..].copy_from_slice(&right.to_le_bytes());
+        xxh3_64(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(entries: &[(&str, &[u8])]) -> HashMap<String, Vec<u8>> {
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn test_divergent_ranges() {
+        let a = state(&[("case-1", b"a"), ("case-2", b"b"), ("case-3", b"c")]);
+        let mut b = a.clone();
+
+        let tree_a = MerkleTree::build(&a, 16);
+        assert_eq!(tree_a, MerkleTree::build(&b, 16));
+        assert!(tree_a.diff(&MerkleTree::build(&b, 16)).is_empty());
+
+        b.insert("case-2".to_string(), b"changed".to_vec());
+        b.insert("case-4".to_string(), b"d".to_vec());
+        let tree_b = MerkleTree::build(&b, 16);
+
+        let mut expected = vec![MerkleTree::range_of("case-2", 16), MerkleTree::range_of("case-4", 16)];
+        expected.sort_unstable();
+        expected.dedup();
+
+        assert_ne!(tree_a.root(), tree_b.root());
+        assert_eq!(tree_a.diff(&tree_b), expected);
+    }
+
+    #[test]
+    fn test_ranges_rounded_to_power_of_two() {
+        let tree = MerkleTree::build(&HashMap::new(), 100);
+        assert_eq!(tree.ranges(), 128);
+        assert_eq!(tree.root(), 0);
+        assert_eq!(tree.diff(&MerkleTree::build(&HashMap::new(), 8)).len(), 128);
+    }
+}
@@ -1,19 +1,75 @@
 //! Data replication across cluster nodes.
+//!
+//! Replicas that drift apart, for example across a partition, reconcile in
+//! two ways. Anti-entropy periodically sends a Merkle digest of the local
+//! state to a peer, which answers with its values in the key ranges that
+//! differ; both sides merge what they receive. Quorum reads repair replicas
+//! that returned stale values. Merges follow the vector clocks: a causally
+//! newer value wins, and concurrent values fall back to last-write-wins on
+//! a merged clock so every replica settles on the same value.
 
 pub mod conflict;
+pub mod merkle;
 pub mod sync;
 
 pub use conflict::{
     ConflictResolution, ConflictResolver, VectorClock, VectorClockOrdering, VersionedValue,
 };
+pub use merkle::MerkleTree;
 pub use sync::{StateDelta, StateSnapshot, StateSynchronizer, SyncRequest, SyncResponse};
 
 use crate::config::ReplicationConfig;
 use crate::error::{ClusterError, Result};
+use parking_lot::Mutex;
+use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{mpsc, RwLock};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
 use uuid::Uuid;
 
+/// Queue of anti-entropy requests waiting for the transport.
+pub type OutboundSync = mpsc::UnboundedReceiver<(Uuid, SyncRequest)>;
+
+/// A replica's answer to a quorum read.
+#[derive(Debug, Clone)]
+pub struct ReplicaRead {
+    /// Replica node
+    pub node_id: Uuid,
+
+    /// Value held by the replica
+    pub value: Option<VersionedValue>,
+}
+
+impl ReplicaRead {
+    /// Read a key's value out of a replica's anti-entropy response.
+    pub fn from_response(node_id: Uuid, key: &str, response: &SyncResponse) -> Result<Self> {
+        let data = match response {
+            SyncResponse::AntiEntropyData { data } => data,
+            SyncResponse::Error(msg) => return Err(ClusterError::ReplicationFailed(msg.clone())),
+            _ => return Err(ClusterError::InvalidMessage("Expected anti-entropy data".to_string())),
+        };
+
+        let value = match data.get(key) {
+            Some(bytes) => Some(bincode::deserialize(bytes)?),
+            None => None,
+        };
+
+        Ok(Self { node_id, value })
+    }
+}
+
+/// Result of a quorum read.
+#[derive(Debug, Clone)]
+pub struct QuorumRead {
+    /// Resolved value
+    pub value: Option<Vec<u8>>,
+
+    /// Repairs to send to replicas that returned stale values
+    pub repairs: Vec<(Uuid, SyncResponse)>,
+}
+
 /// Replication service.
 pub struct ReplicationService {
     /// Local node ID
@@ -27,6 +83,22 @@ pub struct ReplicationService {
 
     /// Vector clock for this node
     vector_clock: Arc<RwLock<VectorClock>>,
+
+    /// Peers to run anti-entropy with
+    peers: Arc<parking_lot::RwLock<Vec<Uuid>>>,
+
+    /// Outgoing anti-entropy digests
+    outbound_tx: mpsc::UnboundedSender<(Uuid, SyncRequest)>,
+    outbound_rx: Arc<Mutex<Option<OutboundSync>>>,
+
+    /// Running flag
+    running: Arc<RwLock<bool>>,
+
+    /// Cancelled on stop or by an enclosing shutdown scope
+    shutdown: CancellationToken,
+
+    /// Spawned background tasks
+    tasks: Arc<Mutex<Vec<(String, JoinHandle<()>)>>>,
 }
 
 impl ReplicationService {
@@ -34,15 +106,156 @@ impl ReplicationService {
     pub fn new(local_id: Uuid, config: ReplicationConfig) -> Self {
         let synchronizer = Arc::new(StateSynchronizer::new(local_id));
         let vector_clock = Arc::new(RwLock::new(VectorClock::new()));
+        let (outbound_tx, outbound_rx) = mpsc::unbounded_channel();
 
         Self {
             local_id,
             synchronizer,
             config,
             vector_clock,
+            peers: Arc::new(parking_lot::RwLock::new(Vec::new())),
+            outbound_tx,
+            outbound_rx: Arc::new(Mutex::new(Some(outbound_rx))),
+            running: Arc::new(RwLock::new(false)),
+            shutdown: CancellationToken::new(),
+            tasks: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Stop background tasks when the given token is cancelled
+    pub fn with_shutdown_token(mut self, token: CancellationToken) -> Self {
+        self.shutdown = token;
+        self
+    }
+
+    /// Hand over the spawned task handles so a shutdown scope can await them.
+    pub fn take_task_handles(&self) -> Vec<(String, JoinHandle<()>)> {
+        std::mem::take(&mut *self.tasks.lock())
+    }
+
+    /// Take the queue of outgoing anti-entropy digests.
+    ///
+    /// The transport sends each request to its peer and hands the reply to
+    /// [`apply_sync_response`](Self::apply_sync_response). Only the first
+    /// call gets the queue.
+    pub fn take_outbound(&self) -> Option<OutboundSync> {
+        self.outbound_rx.lock().take()
+    }
+
+    /// Start the anti-entropy task, if enabled.
+    pub async fn start(&self) -> Result<()> {
+        let mut running = self.running.write().await;
+        if *running || !self.config.enable_anti_entropy {
+            return Ok(());
+        }
+        *running = true;
+        drop(running);
+
+        tracing::info!("Starting replication anti-entropy");
+
+        let service = self.clone_for_task();
+        let anti_entropy = tokio::spawn(async move {
+            service.anti_entropy_task().await;
+        });
+        self.tasks.lock().push(("anti-entropy".to_string(), anti_entropy));
+
+        Ok(())
+    }
+
+    /// Stop replication.
+    ///
+    /// The anti-entropy task exits at its next tick; the service cannot be restarted.
+    pub async fn stop(&self) {
+        let mut running = self.running.write().await;
+        *running = false;
+        self.shutdown.cancel();
+        tracing::info!("Stopped replication anti-entropy");
+    }
+
+    /// Add a peer to run anti-entropy with.
+    pub fn add_peer(&self, peer_id: Uuid) {
+        let mut peers = self.peers.write();
+        if peer_id != self.local_id && !peers.contains(&peer_id) {
+            peers.push(peer_id);
+        }
+    }
+
+    /// Remove a peer.
+    pub fn remove_peer(&self, peer_id: &Uuid) {
+        self.peers.write().retain(|id| id != peer_id);
+    }
+
+    /// Build an anti-entropy digest of the local state.
+    pub fn digest_request(&self) -> SyncRequest {
+        SyncRequest::Digest {
+            tree: self.synchronizer.merkle_tree(self.config.anti_entropy_ranges),
+        }
+    }
+
+    /// Build the repair a peer needs after a digest exchange: the local
+    /// values in the ranges that differed.
+    pub fn repair_for(&self, ranges: &[usize]) -> SyncResponse {
+        let range_count = self.config.anti_entropy_ranges.max(1).next_power_of_two();
+        SyncResponse::AntiEntropyData {
+            data: self.synchronizer.range_data(ranges, range_count),
         }
     }
 
+    /// Resolve a quorum read from the local value and the replicas' answers.
+    ///
+    /// `total_replicas` counts the local node. The local copy is repaired
+    /// right away; repairs for stale replicas are returned for sending.
+    pub async fn quorum_read(
+        &self,
+        key: &str,
+        replies: Vec<ReplicaRead>,
+        total_replicas: usize,
+    ) -> Result<QuorumRead> {
+        let required = self.config.read_consistency.required_nodes(total_replicas);
+        if replies.len() + 1 < required {
+            return Err(ClusterError::QuorumNotReached {
+                current: replies.len() + 1,
+                required,
+            });
+        }
+
+        let mut winner = self.local_value(key)?;
+        for reply in &replies {
+            if let Some(value) = &reply.value {
+                if !value.verify() {
+                    warn!("Replica {} returned a corrupt value for {}", reply.node_id, key);
+                    continue;
+                }
+                winner = Some(match winner {
+                    Some(current) => Self::merge_versions(current, value.clone()),
+                    None => value.clone(),
+                });
+            }
+        }
+
+        let Some(winner) = winner else {
+            return Ok(QuorumRead { value: None, repairs: Vec::new() });
+        };
+
+        let bytes = bincode::serialize(&winner)?;
+        self.merge_remote(key, &bytes).await?;
+
+        let repairs = replies
+            .iter()
+            .filter(|reply| !reply.value.as_ref().is_some_and(|v| Self::same_version(v, &winner)))
+            .map(|reply| {
+                let mut data = HashMap::new();
+                data.insert(key.to_string(), bytes.clone());
+                (reply.node_id, SyncResponse::AntiEntropyData { data })
+            })
+            .collect();
+
+        Ok(QuorumRead {
+            value: Some(winner.value),
+            repairs,
+        })
+    }
+
     /// Write a value with replication.
     pub async fn write(&self, key: String, value: Vec<u8>) -> Result<()> {
         // Increment vector clock
@@ -139,15 +352,17 @@ impl ReplicationService {
                 Ok(())
             }
 
-            SyncResponse::AntiEntropyData { data } => {
-                // Apply anti-entropy data
+            SyncResponse::AntiEntropyData { data } | SyncResponse::RangeData { data, .. } => {
+                // Merge anti-entropy data by version
+                let mut repaired = 0;
                 for (key, value) in data {
-                    let mut delta = StateDelta::new(
-                        self.synchronizer.current_version(),
-                        self.synchronizer.current_version() + 1,
-                    );
-                    delta.add_update(key, value);
-                    self.synchronizer.apply_delta(delta)?;
+                    if self.merge_remote(&key, &value).await? {
+                        repaired += 1;
+                    }
+                }
+
+                if repaired > 0 {
+                    debug!("Anti-entropy repaired {} keys", repaired);
                 }
 
                 Ok(())
@@ -212,4 +427,209 @@ impl ReplicationService {
     pub fn current_version(&self) -> u64 {
         self.synchronizer.current_version()
     }
+
+    /// Merge a value received from a replica into the local state,
+    /// returning whether the local state changed.
+    async fn merge_remote(&self, key: &str, bytes: &[u8]) -> Result<bool> {
+        let remote: VersionedValue = bincode::deserialize(bytes)?;
+        if !remote.verify() {
+            warn!("Discarding corrupt replica value for {}", key);
+            return Ok(false);
+        }
+
+        self.vector_clock.write().await.merge(&remote.version);
+
+        let merged = match self.local_value(key)? {
+            Some(local) => {
+                let merged = Self::merge_versions(local.clone(), remote);
+                if Self::same_version(&merged, &local) {
+                    return Ok(false);
+                }
+                merged
+            }
+            None => remote,
+        };
+
+        let mut delta = StateDelta::new(
+            self.synchronizer.current_version(),
+            self.synchronizer.current_version() + 1,
+        );
+        delta.add_update(key.to_string(), bincode::serialize(&merged)?);
+        self.synchronizer.apply_delta(delta)?;
+
+        Ok(true)
+    }
+
+    /// Decode the local value of a key.
+    fn local_value(&self, key: &str) -> Result<Option<VersionedValue>> {
+        match self.synchronizer.get_snapshot().data.get(key) {
+            Some(bytes) => Ok(Some(bincode::deserialize(bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Pick the newer of two versions of a value.
+    ///
+    /// Concurrent versions keep the later write, ties broken by node, under
+    /// the merged clock, so all replicas pick the same value.
+    fn merge_versions(a: VersionedValue, b: VersionedValue) -> VersionedValue {
+        match a.version.compare(&b.version) {
+            VectorClockOrdering::After => a,
+            VectorClockOrdering::Before => b,
+            VectorClockOrdering::Equal | VectorClockOrdering::Concurrent => {
+                let mut version = a.version.clone();
+                version.merge(&b.version);
+
+                let winner = if (b.timestamp, b.node_id) > (a.timestamp, a.node_id) { b } else { a };
+                VersionedValue { version, ..winner }
+            }
+        }
+    }
+
+    fn same_version(a: &VersionedValue, b: &VersionedValue) -> bool {
+        a.version == b.version
+            && a.checksum == b.checksum
+            && a.node_id == b.node_id
+            && a.timestamp == b.timestamp
+    }
+
+    /// Anti-entropy task.
+    async fn anti_entropy_task(&self) {
+        let mut interval = tokio::time::interval(self.config.anti_entropy_interval);
+        let mut next_peer = 0usize;
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = self.shutdown.cancelled() => break,
+            }
+
+            let running = self.running.read().await;
+            if !*running {
+                break;
+            }
+            drop(running);
+
+            // Exchange digests with one peer per round, in turn
+            let peer = {
+                let peers = self.peers.read();
+                if peers.is_empty() {
+                    continue;
+                }
+                next_peer = (next_peer + 1) % peers.len();
+                peers[next_peer]
+            };
+
+            if self.outbound_tx.send((peer, self.digest_request())).is_err() {
+                debug!("No transport for anti-entropy digests");
+            }
+        }
+    }
+
+    /// Clone for async task.
+    fn clone_for_task(&self) -> Self {
+        Self {
+            local_id: self.local_id,
+            synchronizer: Arc::clone(&self.synchronizer),
+            config: self.config.clone(),
+            vector_clock: Arc::clone(&self.vector_clock),
+            peers: Arc::clone(&self.peers),
+            outbound_tx: self.outbound_tx.clone(),
+            outbound_rx: Arc::clone(&self.outbound_rx),
+            running: Arc::clone(&self.running),
+            shutdown: self.shutdown.clone(),
+            tasks: Arc::clone(&self.tasks),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConsistencyLevel;
+
+    fn replica() -> ReplicationService {
+        ReplicationService::new(Uuid::new_v4(), ReplicationConfig::default())
+    }
+
+    /// Run one digest exchange from `a` to `b` and back.
+    async fn anti_entropy(a: &ReplicationService, b: &ReplicationService) {
+        let response = b.handle_sync_request(a.digest_request());
+        let SyncResponse::RangeData { ranges, .. } = &response else {
+            panic!("expected range data");
+        };
+        let repair = a.repair_for(ranges);
+
+        a.apply_sync_response(response).await.unwrap();
+        b.apply_sync_response(repair).await.unwrap();
+    }
+
+    fn root(service: &ReplicationService) -> u64 {
+        service.synchronizer.merkle_tree(256).root()
+    }
+
+    #[tokio::test]
+    async fn test_anti_entropy_converges() {
+        let a = replica();
+        let b = replica();
+
+        a.write("case-1".to_string(), b"a1".to_vec()).await.unwrap();
+        b.write("case-2".to_string(), b"b2".to_vec()).await.unwrap();
+
+        // Concurrent writes to the same key during a partition
+        a.write("shared".to_string(), b"from-a".to_vec()).await.unwrap();
+        b.write("shared".to_string(), b"from-b".to_vec()).await.unwrap();
+        assert_ne!(root(&a), root(&b));
+
+        anti_entropy(&a, &b).await;
+
+        assert_eq!(root(&a), root(&b));
+        assert_eq!(b.read("case-1").await.unwrap(), Some(b"a1".to_vec()));
+        assert_eq!(a.read("case-2").await.unwrap(), Some(b"b2".to_vec()));
+        assert_eq!(a.read("shared").await.unwrap(), b.read("shared").await.unwrap());
+
+        // A write after the merge supersedes both concurrent versions
+        a.write("shared".to_string(), b"resolved".to_vec()).await.unwrap();
+        anti_entropy(&b, &a).await;
+        assert_eq!(b.read("shared").await.unwrap(), Some(b"resolved".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn test_quorum_read_repairs_stale_replicas() {
+        let local = replica();
+        let stale = replica();
+        let missing = Uuid::new_v4();
+
+        stale.write("case".to_string(), b"old".to_vec()).await.unwrap();
+        local.apply_sync_response(stale.handle_sync_request(SyncRequest::AntiEntropy {
+            keys: vec!["case".to_string()],
+        })).await.unwrap();
+        local.write("case".to_string(), b"new".to_vec()).await.unwrap();
+
+        let reply = stale.handle_sync_request(SyncRequest::AntiEntropy { keys: vec!["case".to_string()] });
+        let replies = vec![
+            ReplicaRead::from_response(stale.local_id, "case", &reply).unwrap(),
+            ReplicaRead { node_id: missing, value: None },
+        ];
+
+        let read = local.quorum_read("case", replies, 3).await.unwrap();
+        assert_eq!(read.value, Some(b"new".to_vec()));
+        assert_eq!(read.repairs.len(), 2);
+
+        let (_, repair) = read.repairs.into_iter().find(|(id, _)| *id == stale.local_id).unwrap();
+        stale.apply_sync_response(repair).await.unwrap();
+        assert_eq!(stale.read("case").await.unwrap(), Some(b"new".to_vec()));
+
+        let strict = ReplicationService::new(
+            Uuid::new_v4(),
+            ReplicationConfig {
+                read_consistency: ConsistencyLevel::All,
+                ..ReplicationConfig::default()
+            },
+        );
+        assert!(matches!(
+            strict.quorum_read("case", Vec::new(), 3).await,
+            Err(ClusterError::QuorumNotReached { current: 1, required: 3 })
+        ));
+    }
 }
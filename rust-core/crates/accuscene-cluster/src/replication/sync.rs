@@ -1,6 +1,7 @@
 //! State synchronization between nodes.
 
 use crate::error::Result;
+use crate::replication::merkle::MerkleTree;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -162,6 +163,11 @@ pub enum SyncRequest {
     AntiEntropy {
         keys: Vec<String>,
     },
+
+    /// Merkle digest of the requester's state
+    Digest {
+        tree: MerkleTree,
+    },
 }
 
 /// Synchronization response.
@@ -178,6 +184,12 @@ pub enum SyncResponse {
         data: HashMap<String, Vec<u8>>,
     },
 
+    /// State in the key ranges where the digests differ
+    RangeData {
+        ranges: Vec<usize>,
+        data: HashMap<String, Vec<u8>>,
+    },
+
     /// Error response
     Error(String),
 }
@@ -216,6 +228,22 @@ impl StateSynchronizer {
         self.current_snapshot.read().clone()
     }
 
+    /// Build a Merkle tree over the current state.
+    pub fn merkle_tree(&self, ranges: usize) -> MerkleTree {
+        MerkleTree::build(&self.current_snapshot.read().data, ranges)
+    }
+
+    /// Get the state in the given key ranges.
+    pub fn range_data(&self, ranges: &[usize], range_count: usize) -> HashMap<String, Vec<u8>> {
+        self.current_snapshot
+            .read()
+            .data
+            .iter()
+            .filter(|(key, _)| ranges.contains(&MerkleTree::range_of(key, range_count)))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+
     /// Update state with delta.
     pub fn apply_delta(&self, delta: StateDelta) -> Result<()> {
         let mut snapshot = self.current_snapshot.write();
@@ -284,6 +312,13 @@ impl StateSynchronizer {
 
                 SyncResponse::AntiEntropyData { data }
             }
+
+            SyncRequest::Digest { tree } => {
+                let ranges = self.merkle_tree(tree.ranges()).diff(&tree);
+                let data = self.range_data(&ranges, tree.ranges());
+
+                SyncResponse::RangeData { ranges, data }
+            }
         }
     }
 }
@@ -131,39 +131,33 @@ impl AdaptiveCompressor {
         let algorithm = self.select_algorithm(data, level)?;
         trace!("Compressing {} bytes with {:?}", data.len(), algorithm);
 
-        let mut compressed = crate::algorithms::compress(data, algorithm, level)?;
-
-        // Prepend algorithm identifier (1 byte)
-        let mut result = Vec::with_capacity(compressed.len() + 1);
-        result.push(algorithm as u8);
-        result.append(&mut compressed);
-
-        Ok(result)
+        crate::detect::compress_with_header(data, algorithm, level)
     }
 
     /// Decompress data (algorithm is embedded in the data)
+    ///
+    /// Accepts anything [`decompress_auto`](crate::detect::decompress_auto)
+    /// recognises, plus the one-byte algorithm prefix written by earlier
+    /// versions.
     pub fn decompress(data: &[u8]) -> Result<Vec<u8>> {
+        if crate::detect::is_compressed(data) || crate::detect::has_header(data) {
+            return crate::detect::decompress_auto(data);
+        }
+
         if data.is_empty() {
             return Err(crate::error::CompressionError::CorruptedData(
                 "Empty data".to_string(),
             ));
         }
 
-        let algorithm = match data[0] {
-            0 => Algorithm::Lz4,
-            1 => Algorithm::Zstd,
-            2 => Algorithm::Brotli,
-            3 => Algorithm::Deflate,
-            4 => Algorithm::Snappy,
-            _ => {
-                return Err(crate::error::CompressionError::InvalidAlgorithm(format!(
-                    "Unknown algorithm ID: {}",
-                    data[0]
-                )))
-            }
-        };
+        let algorithm = Algorithm::from_id(data[0]).ok_or_else(|| {
+            crate::error::CompressionError::InvalidAlgorithm(format!(
+                "Unknown algorithm ID: {}",
+                data[0]
+            ))
+        })?;
 
-        trace!("Decompressing with {:?}", algorithm);
+        trace!("Decompressing legacy prefix with {:?}", algorithm);
         crate::algorithms::decompress(&data[1..], algorithm)
     }
 
@@ -294,6 +288,16 @@ mod tests {
         assert_eq!(data.to_vec(), decompressed);
     }
 
+    #[test]
+    fn test_legacy_prefix_still_decompresses() {
+        let data = b"Written before the self-describing header.".repeat(20);
+
+        let mut legacy = vec![Algorithm::Snappy.id()];
+        legacy.extend(crate::algorithms::compress(&data, Algorithm::Snappy, CompressionLevel::Default).unwrap());
+
+        assert_eq!(decompress_auto(&legacy).unwrap(), data);
+    }
+
     #[test]
     fn test_entropy_calculation() {
         // Low entropy (all zeros)
@@ -3,7 +3,7 @@
 use crate::error::{CompressionError, Result};
 use crate::traits::{Algorithm, CompressionLevel, Compressor as CompressorTrait};
 use tracing::{debug, trace};
-use zstd::bulk::{compress, decompress, Compressor, Decompressor};
+use zstd::bulk::{compress, Compressor, Decompressor};
 
 /// Zstandard compression implementation
 #[derive(Debug, Clone)]
@@ -92,8 +92,8 @@ impl CompressorTrait for ZstdCompressor {
     fn decompress(&self, data: &[u8]) -> Result<Vec<u8>> {
         trace!("Zstandard decompressing {} bytes", data.len());
 
-        // Use a reasonable upper bound for decompressed size
-        decompress(data, usize::MAX)
+        // Stream decode: the frame may not record its content size
+        zstd::stream::decode_all(data)
             .map_err(|e| CompressionError::Zstd(e.to_string()))
     }
 
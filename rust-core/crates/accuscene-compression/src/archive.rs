@@ -1,7 +1,20 @@
 //! Multi-file archive format (.accuscene)
+//!
+//! Entries are either plaintext (compressed with the archive algorithm) or
+//! sealed: encrypted with their own data encryption key (DEK), which is
+//! wrapped by a case key encryption key (KEK) using `accuscene-crypto`
+//! envelopes. Access to selected sealed entries can be granted to another
+//! key holder with an [`ArchiveKeyGrant`] without re-packaging the archive.
+//! Creating and opening sealed entries requires the `encryption` feature.
 
 use crate::error::{CompressionError, Result};
 use crate::traits::{Algorithm, CompressionLevel};
+#[cfg(feature = "encryption")]
+use accuscene_crypto::envelope::{EnvelopeEncrypted, EnvelopeEncryptor};
+#[cfg(feature = "encryption")]
+use accuscene_crypto::symmetric::{aes::EncryptedData, SymmetricKey};
+#[cfg(feature = "encryption")]
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tracing::{debug, trace};
 
@@ -9,7 +22,16 @@ use tracing::{debug, trace};
 const ARCHIVE_MAGIC: u32 = 0x41435343;
 
 /// Archive format version
-const ARCHIVE_VERSION: u32 = 1;
+const ARCHIVE_VERSION: u32 = 2;
+
+/// Oldest readable format version (no per-entry flags)
+const ARCHIVE_VERSION_V1: u32 = 1;
+
+/// Entry flag: plaintext entry
+const ENTRY_PLAIN: u8 = 0;
+
+/// Entry flag: sealed entry
+const ENTRY_SEALED: u8 = 1;
 
 /// Entry in the archive
 #[derive(Debug, Clone)]
@@ -45,11 +67,106 @@ impl ArchiveEntry {
     }
 }
 
+/// Entry encrypted with its own data encryption key
+///
+/// Sealed entries stay encrypted in memory until extracted, so archives can
+/// be listed and re-serialized without any key.
+#[derive(Debug, Clone)]
+pub struct SealedEntry {
+    /// File name/path within archive
+    pub name: String,
+    /// Metadata (stored unencrypted)
+    pub metadata: HashMap<String, String>,
+    /// Identifier of the case KEK wrapping the entry DEK
+    pub key_id: String,
+    /// Uncompressed size in bytes
+    pub size: u64,
+    /// Serialized envelope of the compressed data
+    envelope: Vec<u8>,
+}
+
+#[cfg(feature = "encryption")]
+impl SealedEntry {
+    /// Deserialize the envelope
+    fn envelope(&self) -> Result<EnvelopeEncrypted> {
+        bincode::deserialize(&self.envelope)
+            .map_err(|e| CompressionError::CorruptedData(format!("Sealed entry '{}': {}", self.name, e)))
+    }
+
+    /// Decrypt the envelope with the given KEK and decompress
+    fn open(&self, envelope: &EnvelopeEncrypted, kek: &SymmetricKey) -> Result<ArchiveEntry> {
+        // The entry name is bound as associated data so entries cannot be swapped
+        let compressed = EnvelopeEncryptor::new(kek.clone())
+            .decrypt_with_metadata(envelope, self.name.as_bytes())
+            .map_err(|e| CompressionError::Decryption(e.to_string()))?;
+
+        let data = crate::detect::decompress_auto(compressed.as_bytes())?;
+        if data.len() as u64 != self.size {
+            return Err(CompressionError::CorruptedData(format!(
+                "Sealed entry '{}' has {} bytes, expected {}",
+                self.name,
+                data.len(),
+                self.size
+            )));
+        }
+
+        Ok(ArchiveEntry {
+            name: self.name.clone(),
+            data,
+            metadata: self.metadata.clone(),
+        })
+    }
+}
+
+/// Entry keys granting a recipient access to selected sealed entries
+///
+/// Each entry DEK is re-wrapped under the recipient's KEK. The archive
+/// itself is unchanged, so the grant is shipped alongside it.
+#[cfg(feature = "encryption")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveKeyGrant {
+    /// Identifier of the recipient KEK
+    pub recipient_key_id: String,
+    /// Wrapped DEKs by entry name
+    keys: HashMap<String, EncryptedData>,
+}
+
+#[cfg(feature = "encryption")]
+impl ArchiveKeyGrant {
+    /// Names of the entries covered by the grant
+    pub fn entries(&self) -> Vec<&str> {
+        self.keys.keys().map(|s| s.as_str()).collect()
+    }
+
+    /// Check whether the grant covers an entry
+    pub fn covers(&self, name: &str) -> bool {
+        self.keys.contains_key(name)
+    }
+
+    /// Serialize grant to bytes
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    /// Deserialize grant from bytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        bincode::deserialize(bytes).map_err(|e| CompressionError::Deserialization(e.to_string()))
+    }
+}
+
+/// Entry as read from bytes
+enum StoredEntry {
+    Plain(ArchiveEntry),
+    Sealed(SealedEntry),
+}
+
 /// AccuScene archive (.accuscene file format)
 #[derive(Debug)]
 pub struct Archive {
     /// Archive entries
     entries: HashMap<String, ArchiveEntry>,
+    /// Sealed entries
+    sealed: HashMap<String, SealedEntry>,
     /// Global metadata
     metadata: HashMap<String, String>,
     /// Compression algorithm
@@ -63,6 +180,7 @@ impl Archive {
     pub fn new(algorithm: Algorithm, level: CompressionLevel) -> Self {
         Self {
             entries: HashMap::new(),
+            sealed: HashMap::new(),
             metadata: HashMap::new(),
             algorithm,
             level,
@@ -77,6 +195,7 @@ impl Archive {
     /// Add an entry to the archive
     pub fn add_entry(&mut self, entry: ArchiveEntry) {
         trace!("Adding entry '{}' ({} bytes)", entry.name, entry.data.len());
+        self.sealed.remove(&entry.name);
         self.entries.insert(entry.name.clone(), entry);
     }
 
@@ -85,29 +204,145 @@ impl Archive {
         self.add_entry(ArchiveEntry::new(name, data));
     }
 
-    /// Get an entry by name
+    /// Get a plaintext entry by name
     pub fn get_entry(&self, name: &str) -> Option<&ArchiveEntry> {
         self.entries.get(name)
     }
 
-    /// Remove an entry
+    /// Get a sealed entry by name
+    pub fn get_sealed(&self, name: &str) -> Option<&SealedEntry> {
+        self.sealed.get(name)
+    }
+
+    /// Check whether an entry is sealed
+    pub fn is_sealed(&self, name: &str) -> bool {
+        self.sealed.contains_key(name)
+    }
+
+    /// Remove a plaintext entry
     pub fn remove_entry(&mut self, name: &str) -> Option<ArchiveEntry> {
         self.entries.remove(name)
     }
 
-    /// List all entry names
+    /// Remove a sealed entry
+    pub fn remove_sealed(&mut self, name: &str) -> Option<SealedEntry> {
+        self.sealed.remove(name)
+    }
+
+    /// List all entry names, plaintext and sealed
     pub fn list_entries(&self) -> Vec<&str> {
-        self.entries.keys().map(|s| s.as_str()).collect()
+        self.entries
+            .keys()
+            .chain(self.sealed.keys())
+            .map(|s| s.as_str())
+            .collect()
     }
 
     /// Get number of entries
     pub fn len(&self) -> usize {
-        self.entries.len()
+        self.entries.len() + self.sealed.len()
     }
 
     /// Check if archive is empty
     pub fn is_empty(&self) -> bool {
-        self.entries.is_empty()
+        self.entries.is_empty() && self.sealed.is_empty()
+    }
+
+    /// Add an entry sealed with its own DEK wrapped by the case KEK
+    ///
+    /// `key_id` identifies the KEK so recipients know which key to use.
+    #[cfg(feature = "encryption")]
+    pub fn add_sealed_entry(
+        &mut self,
+        entry: ArchiveEntry,
+        key_id: &str,
+        kek: &SymmetricKey,
+    ) -> Result<()> {
+        trace!("Sealing entry '{}' ({} bytes)", entry.name, entry.data.len());
+
+        let compressed = crate::detect::compress_with_header(&entry.data, self.algorithm, self.level)?;
+        let envelope = EnvelopeEncryptor::new(kek.clone())
+            .encrypt_with_metadata(&compressed, entry.name.as_bytes())
+            .map_err(|e| CompressionError::Encryption(e.to_string()))?;
+
+        self.entries.remove(&entry.name);
+        self.sealed.insert(
+            entry.name.clone(),
+            SealedEntry {
+                name: entry.name,
+                metadata: entry.metadata,
+                key_id: key_id.to_string(),
+                size: entry.data.len() as u64,
+                envelope: bincode::serialize(&envelope)?,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Extract a single entry, decrypting it with the case KEK if sealed
+    #[cfg(feature = "encryption")]
+    pub fn extract(&self, name: &str, kek: &SymmetricKey) -> Result<ArchiveEntry> {
+        if let Some(entry) = self.entries.get(name) {
+            return Ok(entry.clone());
+        }
+
+        let sealed = self.sealed_entry(name)?;
+        sealed.open(&sealed.envelope()?, kek)
+    }
+
+    /// Grant the holder of `recipient_kek` access to selected sealed entries
+    #[cfg(feature = "encryption")]
+    pub fn grant_access(
+        &self,
+        names: &[&str],
+        kek: &SymmetricKey,
+        recipient_key_id: &str,
+        recipient_kek: &SymmetricKey,
+    ) -> Result<ArchiveKeyGrant> {
+        let encryptor = EnvelopeEncryptor::new(kek.clone());
+        let mut keys = HashMap::new();
+
+        for &name in names {
+            let envelope = self.sealed_entry(name)?.envelope()?;
+            let wrapped = encryptor
+                .rewrap_dek(&envelope, recipient_kek)
+                .map_err(|e| CompressionError::Decryption(e.to_string()))?;
+            keys.insert(name.to_string(), wrapped);
+        }
+
+        debug!("Granted '{}' access to {} entries", recipient_key_id, keys.len());
+
+        Ok(ArchiveKeyGrant {
+            recipient_key_id: recipient_key_id.to_string(),
+            keys,
+        })
+    }
+
+    /// Extract a sealed entry using a grant and the recipient's KEK
+    #[cfg(feature = "encryption")]
+    pub fn extract_with_grant(
+        &self,
+        name: &str,
+        grant: &ArchiveKeyGrant,
+        recipient_kek: &SymmetricKey,
+    ) -> Result<ArchiveEntry> {
+        let sealed = self.sealed_entry(name)?;
+        let wrapped = grant.keys.get(name).ok_or_else(|| {
+            CompressionError::Decryption(format!("Grant does not cover entry '{}'", name))
+        })?;
+
+        let mut envelope = sealed.envelope()?;
+        envelope.encrypted_dek = wrapped.clone();
+        sealed.open(&envelope, recipient_kek)
+    }
+
+    /// Look up a sealed entry
+    #[cfg(feature = "encryption")]
+    fn sealed_entry(&self, name: &str) -> Result<&SealedEntry> {
+        self.sealed
+            .get(name)
+            .ok_or_else(|| CompressionError::Archive(format!("No sealed entry '{}'", name)))
     }
 
     /// Add global metadata
@@ -140,12 +375,15 @@ impl Archive {
         self.write_metadata(&mut bytes, &self.metadata);
 
         // Write number of entries
-        bytes.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.len() as u32).to_le_bytes());
 
         // Write each entry
         for entry in self.entries.values() {
             self.write_entry(&mut bytes, entry)?;
         }
+        for entry in self.sealed.values() {
+            self.write_sealed_entry(&mut bytes, entry);
+        }
 
         debug!("Archive serialized: {} bytes", bytes.len());
         Ok(bytes)
@@ -155,6 +393,12 @@ impl Archive {
     pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
         debug!("Deserializing archive from {} bytes", bytes.len());
 
+        // Archives compressed as a whole (e.g. `.accuscene.zst`) are unwrapped first
+        if !bytes.starts_with(&ARCHIVE_MAGIC.to_le_bytes()) && crate::detect::is_compressed(bytes) {
+            let inner = crate::detect::decompress_auto(bytes)?;
+            return Self::from_bytes(&inner);
+        }
+
         if bytes.len() < 16 {
             return Err(CompressionError::Archive(
                 "Archive too small".to_string(),
@@ -177,7 +421,7 @@ impl Archive {
         let version = u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
         offset += 4;
 
-        if version != ARCHIVE_VERSION {
+        if version != ARCHIVE_VERSION && version != ARCHIVE_VERSION_V1 {
             return Err(CompressionError::UnsupportedVersion(version));
         }
 
@@ -218,16 +462,29 @@ impl Archive {
 
         // Read entries
         let mut entries = HashMap::new();
+        let mut sealed = HashMap::new();
         for _ in 0..entry_count {
-            let (entry, new_offset) = Self::read_entry(bytes, offset, algorithm)?;
+            let (entry, new_offset) = Self::read_entry(bytes, offset, algorithm, version)?;
             offset = new_offset;
-            entries.insert(entry.name.clone(), entry);
+            match entry {
+                StoredEntry::Plain(entry) => {
+                    entries.insert(entry.name.clone(), entry);
+                }
+                StoredEntry::Sealed(entry) => {
+                    sealed.insert(entry.name.clone(), entry);
+                }
+            }
         }
 
-        debug!("Archive deserialized: {} entries", entries.len());
+        debug!(
+            "Archive deserialized: {} entries ({} sealed)",
+            entries.len() + sealed.len(),
+            sealed.len()
+        );
 
         Ok(Self {
             entries,
+            sealed,
             metadata,
             algorithm,
             level,
@@ -293,6 +550,7 @@ impl Archive {
 
         // Write metadata
         self.write_metadata(bytes, &entry.metadata);
+        bytes.push(ENTRY_PLAIN);
 
         // Compress data
         let compressed = crate::algorithms::compress(&entry.data, self.algorithm, self.level)?;
@@ -307,8 +565,32 @@ impl Archive {
         Ok(())
     }
 
+    /// Write sealed entry to bytes
+    fn write_sealed_entry(&self, bytes: &mut Vec<u8>, entry: &SealedEntry) {
+        let name_bytes = entry.name.as_bytes();
+        bytes.extend_from_slice(&(name_bytes.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(name_bytes);
+
+        self.write_metadata(bytes, &entry.metadata);
+        bytes.push(ENTRY_SEALED);
+
+        let key_id_bytes = entry.key_id.as_bytes();
+        bytes.extend_from_slice(&(key_id_bytes.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(key_id_bytes);
+
+        bytes.extend_from_slice(&entry.size.to_le_bytes());
+
+        bytes.extend_from_slice(&(entry.envelope.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&entry.envelope);
+    }
+
     /// Read entry from bytes
-    fn read_entry(bytes: &[u8], mut offset: usize, algorithm: Algorithm) -> Result<(ArchiveEntry, usize)> {
+    fn read_entry(
+        bytes: &[u8],
+        mut offset: usize,
+        algorithm: Algorithm,
+        version: u32,
+    ) -> Result<(StoredEntry, usize)> {
         // Read name
         let name_len = u32::from_le_bytes(
             bytes[offset..offset + 4].try_into().unwrap()
@@ -323,6 +605,21 @@ impl Archive {
         let (metadata, new_offset) = Self::read_metadata(bytes, offset)?;
         offset = new_offset;
 
+        // Version 1 archives have no entry flags
+        let flag = if version == ARCHIVE_VERSION_V1 {
+            ENTRY_PLAIN
+        } else {
+            offset += 1;
+            bytes[offset - 1]
+        };
+
+        if flag == ENTRY_SEALED {
+            return Self::read_sealed_entry(bytes, offset, name, metadata);
+        }
+        if flag != ENTRY_PLAIN {
+            return Err(CompressionError::Archive(format!("Invalid entry flag: {}", flag)));
+        }
+
         // Read uncompressed size
         let _uncompressed_size = u64::from_le_bytes(
             bytes[offset..offset + 8].try_into().unwrap()
@@ -347,7 +644,47 @@ impl Archive {
             metadata,
         };
 
-        Ok((entry, offset))
+        Ok((StoredEntry::Plain(entry), offset))
+    }
+
+    /// Read the sealed part of an entry from bytes
+    fn read_sealed_entry(
+        bytes: &[u8],
+        mut offset: usize,
+        name: String,
+        metadata: HashMap<String, String>,
+    ) -> Result<(StoredEntry, usize)> {
+        let key_id_len = u32::from_le_bytes(
+            bytes[offset..offset + 4].try_into().unwrap()
+        ) as usize;
+        offset += 4;
+
+        let key_id = String::from_utf8(bytes[offset..offset + key_id_len].to_vec())
+            .map_err(|e| CompressionError::Archive(e.to_string()))?;
+        offset += key_id_len;
+
+        let size = u64::from_le_bytes(
+            bytes[offset..offset + 8].try_into().unwrap()
+        );
+        offset += 8;
+
+        let envelope_len = u32::from_le_bytes(
+            bytes[offset..offset + 4].try_into().unwrap()
+        ) as usize;
+        offset += 4;
+
+        let envelope = bytes[offset..offset + envelope_len].to_vec();
+        offset += envelope_len;
+
+        let entry = SealedEntry {
+            name,
+            metadata,
+            key_id,
+            size,
+            envelope,
+        };
+
+        Ok((StoredEntry::Sealed(entry), offset))
     }
 
     /// Write archive to file
@@ -411,4 +748,86 @@ mod tests {
             assert_eq!(archive.len(), restored.len());
         }
     }
+
+    #[test]
+    fn test_archive_compressed_as_whole() {
+        let mut archive = Archive::new(Algorithm::Lz4, CompressionLevel::Default);
+        archive.add_file("scene.json".to_string(), b"{}".to_vec());
+
+        let bytes = archive.to_bytes().unwrap();
+        let gzipped = crate::compress(&bytes, Algorithm::Deflate, CompressionLevel::Default).unwrap();
+
+        let restored = Archive::from_bytes(&gzipped).unwrap();
+        assert_eq!(restored.get_entry("scene.json").unwrap().data, b"{}".to_vec());
+    }
+
+    #[cfg(feature = "encryption")]
+    fn sealed_archive(kek: &SymmetricKey) -> Archive {
+        let mut archive = Archive::new_default();
+        archive.add_file("scene.json".to_string(), b"{\"vehicles\": 2}".to_vec());
+        archive
+            .add_sealed_entry(
+                ArchiveEntry::new("exhibit-a.jpg".to_string(), b"photo A".repeat(20))
+                    .add_metadata("exhibit".to_string(), "A".to_string()),
+                "case-42",
+                kek,
+            )
+            .unwrap();
+        archive
+            .add_sealed_entry(
+                ArchiveEntry::new("exhibit-b.jpg".to_string(), b"photo B".repeat(20)),
+                "case-42",
+                kek,
+            )
+            .unwrap();
+        archive
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_sealed_entries_round_trip() {
+        let kek = SymmetricKey::generate().unwrap();
+        let archive = sealed_archive(&kek);
+
+        let restored = Archive::from_bytes(&archive.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored.len(), 3);
+        assert!(restored.is_sealed("exhibit-a.jpg"));
+        assert!(restored.get_entry("exhibit-a.jpg").is_none());
+
+        let sealed = restored.get_sealed("exhibit-a.jpg").unwrap();
+        assert_eq!(sealed.key_id, "case-42");
+        assert_eq!(sealed.metadata.get("exhibit"), Some(&"A".to_string()));
+
+        let entry = restored.extract("exhibit-a.jpg", &kek).unwrap();
+        assert_eq!(entry.data, b"photo A".repeat(20));
+        assert_eq!(restored.extract("scene.json", &kek).unwrap().data, b"{\"vehicles\": 2}".to_vec());
+
+        let wrong = SymmetricKey::generate().unwrap();
+        assert!(matches!(
+            restored.extract("exhibit-a.jpg", &wrong),
+            Err(CompressionError::Decryption(_))
+        ));
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_grant_selected_entries() {
+        let kek = SymmetricKey::generate().unwrap();
+        let recipient = SymmetricKey::generate().unwrap();
+        let archive = sealed_archive(&kek);
+
+        let grant = archive
+            .grant_access(&["exhibit-a.jpg"], &kek, "insurer", &recipient)
+            .unwrap();
+        let grant = ArchiveKeyGrant::from_bytes(&grant.to_bytes().unwrap()).unwrap();
+        assert_eq!(grant.recipient_key_id, "insurer");
+        assert!(grant.covers("exhibit-a.jpg"));
+        assert!(!grant.covers("exhibit-b.jpg"));
+
+        let entry = archive.extract_with_grant("exhibit-a.jpg", &grant, &recipient).unwrap();
+        assert_eq!(entry.data, b"photo A".repeat(20));
+
+        assert!(archive.extract_with_grant("exhibit-b.jpg", &grant, &recipient).is_err());
+        assert!(archive.extract("exhibit-a.jpg", &recipient).is_err());
+    }
 }
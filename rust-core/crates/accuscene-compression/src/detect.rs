@@ -0,0 +1,289 @@
+//! Compressed format detection and transparent decompression
+//!
+//! [`decompress`](crate::decompress) requires the caller to know the
+//! algorithm, which data arriving from outside (imports, uploads, files
+//! written by other tools) rarely states. Compressed data is recognised here
+//! in two ways:
+//!
+//! - **Self-describing header** written by [`compress_with_header`]: magic
+//!   number, version, algorithm ID and original size. Works for every
+//!   algorithm.
+//! - **Magic bytes** of the standard container formats: Zstandard frames,
+//!   gzip members, LZ4 frames and the Snappy framing format.
+//!
+//! Raw Brotli streams and the LZ4/Snappy block formats produced by
+//! [`compress`](crate::compress) carry no magic number and are only
+//! recognised when written with the header.
+
+use crate::algorithms::snappy::SnappyCompressor;
+use crate::error::{CompressionError, Result};
+use crate::traits::{Algorithm, CompressionLevel};
+use std::io::Read;
+use tracing::{debug, trace};
+
+/// Self-describing header magic ("ACSZ")
+const HEADER_MAGIC: u32 = 0x5A534341;
+
+/// Self-describing header version
+const HEADER_VERSION: u8 = 1;
+
+/// Header length: magic (4) + version (1) + algorithm (1) + original size (8)
+pub const HEADER_LEN: usize = 14;
+
+/// Zstandard frame magic
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// gzip member magic
+const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+
+/// LZ4 frame magic
+const LZ4_FRAME_MAGIC: [u8; 4] = [0x04, 0x22, 0x4D, 0x18];
+
+/// Snappy framing format stream identifier chunk
+const SNAPPY_FRAME_MAGIC: [u8; 10] = [0xFF, 0x06, 0x00, 0x00, 0x73, 0x4E, 0x61, 0x50, 0x70, 0x59];
+
+/// How compressed data is wrapped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Container {
+    /// Self-describing header written by [`compress_with_header`]
+    Header {
+        /// Uncompressed size recorded in the header
+        original_size: u64,
+    },
+    /// Standard container format of the algorithm, recognised by magic bytes
+    Frame,
+}
+
+/// Result of format detection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DetectedFormat {
+    /// Compression algorithm
+    pub algorithm: Algorithm,
+    /// How the compressed data is wrapped
+    pub container: Container,
+}
+
+/// Detect the compression format of the data
+///
+/// Returns `None` for uncompressed data and for formats without a magic
+/// number.
+pub fn detect_format(data: &[u8]) -> Option<DetectedFormat> {
+    if let Some(format) = read_header(data) {
+        return Some(format);
+    }
+
+    let algorithm = if data.starts_with(&ZSTD_MAGIC) {
+        Algorithm::Zstd
+    } else if data.starts_with(&GZIP_MAGIC) {
+        Algorithm::Deflate
+    } else if data.starts_with(&LZ4_FRAME_MAGIC) {
+        Algorithm::Lz4
+    } else if data.starts_with(&SNAPPY_FRAME_MAGIC) {
+        Algorithm::Snappy
+    } else {
+        return None;
+    };
+
+    Some(DetectedFormat {
+        algorithm,
+        container: Container::Frame,
+    })
+}
+
+/// Check whether the data is recognisably compressed
+pub fn is_compressed(data: &[u8]) -> bool {
+    detect_format(data).is_some()
+}
+
+/// Check whether the data starts with the self-describing header
+pub fn has_header(data: &[u8]) -> bool {
+    data.len() >= HEADER_LEN && data[..4] == HEADER_MAGIC.to_le_bytes()
+}
+
+/// Read the self-describing header, if present and valid
+fn read_header(data: &[u8]) -> Option<DetectedFormat> {
+    if !has_header(data) || data[4] != HEADER_VERSION {
+        return None;
+    }
+
+    let algorithm = Algorithm::from_id(data[5])?;
+    let original_size = u64::from_le_bytes(data[6..HEADER_LEN].try_into().unwrap());
+
+    Some(DetectedFormat {
+        algorithm,
+        container: Container::Header { original_size },
+    })
+}
+
+/// Compress data and prepend the self-describing header
+///
+/// The output can be decompressed with [`decompress_auto`] without knowing
+/// the algorithm.
+pub fn compress_with_header(
+    data: &[u8],
+    algorithm: Algorithm,
+    level: CompressionLevel,
+) -> Result<Vec<u8>> {
+    let compressed = crate::algorithms::compress(data, algorithm, level)?;
+
+    let mut output = Vec::with_capacity(HEADER_LEN + compressed.len());
+    output.extend_from_slice(&HEADER_MAGIC.to_le_bytes());
+    output.push(HEADER_VERSION);
+    output.push(algorithm.id());
+    output.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    output.extend_from_slice(&compressed);
+
+    Ok(output)
+}
+
+/// Decompress data without knowing the algorithm
+///
+/// Accepts output of [`compress_with_header`] and the standard container
+/// formats listed in the [module docs](self).
+pub fn decompress_auto(data: &[u8]) -> Result<Vec<u8>> {
+    if has_header(data) && read_header(data).is_none() {
+        return Err(header_error(data));
+    }
+
+    let format = detect_format(data).ok_or(CompressionError::UnknownFormat)?;
+    debug!("Detected {:?} in {:?}", format.algorithm, format.container);
+
+    match format.container {
+        Container::Header { original_size } => {
+            let payload = &data[HEADER_LEN..];
+            trace!("Decompressing {} byte payload", payload.len());
+
+            let output = crate::algorithms::decompress(payload, format.algorithm)?;
+            if output.len() as u64 != original_size {
+                return Err(CompressionError::CorruptedData(format!(
+                    "Header records {} bytes, decompressed {}",
+                    original_size,
+                    output.len()
+                )));
+            }
+
+            Ok(output)
+        }
+        Container::Frame => decompress_frame(data, format.algorithm),
+    }
+}
+
+/// Decompress a standard container format
+fn decompress_frame(data: &[u8], algorithm: Algorithm) -> Result<Vec<u8>> {
+    let mut output = Vec::new();
+
+    match algorithm {
+        Algorithm::Zstd => {
+            output = crate::algorithms::decompress(data, Algorithm::Zstd)?;
+        }
+        Algorithm::Deflate => {
+            // Other tools may write several concatenated gzip members
+            flate2::read::MultiGzDecoder::new(data)
+                .read_to_end(&mut output)
+                .map_err(|e| CompressionError::Deflate(e.to_string()))?;
+        }
+        Algorithm::Lz4 => {
+            lz4_flex::frame::FrameDecoder::new(data)
+                .read_to_end(&mut output)
+                .map_err(|e| CompressionError::Lz4(e.to_string()))?;
+        }
+        Algorithm::Snappy => {
+            output = SnappyCompressor::new().decompress_framed(data)?;
+        }
+        Algorithm::Brotli => {
+            return Err(CompressionError::UnknownFormat);
+        }
+    }
+
+    Ok(output)
+}
+
+/// Explain why a header with the right magic was rejected
+fn header_error(data: &[u8]) -> CompressionError {
+    if data[4] != HEADER_VERSION {
+        CompressionError::UnsupportedVersion(u32::from(data[4]))
+    } else {
+        CompressionError::InvalidAlgorithm(format!("Unknown algorithm ID: {}", data[5]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const ALGORITHMS: [Algorithm; 5] = [
+        Algorithm::Lz4,
+        Algorithm::Zstd,
+        Algorithm::Brotli,
+        Algorithm::Deflate,
+        Algorithm::Snappy,
+    ];
+
+    #[test]
+    fn test_header_round_trip_all_algorithms() {
+        let data = b"Self-describing compression for AccuScene.".repeat(50);
+
+        for algorithm in ALGORITHMS {
+            let compressed = compress_with_header(&data, algorithm, CompressionLevel::Default).unwrap();
+
+            let format = detect_format(&compressed).unwrap();
+            assert_eq!(format.algorithm, algorithm);
+            assert_eq!(
+                format.container,
+                Container::Header {
+                    original_size: data.len() as u64
+                }
+            );
+            assert_eq!(decompress_auto(&compressed).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_detects_standard_frames() {
+        let data = b"Frames written by other tools.".repeat(50);
+
+        let zstd = crate::compress(&data, Algorithm::Zstd, CompressionLevel::Default).unwrap();
+        let gzip = crate::compress(&data, Algorithm::Deflate, CompressionLevel::Default).unwrap();
+        let snappy = SnappyCompressor::new().compress_framed(&data).unwrap();
+        let mut lz4 = lz4_flex::frame::FrameEncoder::new(Vec::new());
+        lz4.write_all(&data).unwrap();
+        let lz4 = lz4.finish().unwrap();
+
+        for (compressed, algorithm) in [
+            (zstd, Algorithm::Zstd),
+            (gzip, Algorithm::Deflate),
+            (snappy, Algorithm::Snappy),
+            (lz4, Algorithm::Lz4),
+        ] {
+            let format = detect_format(&compressed).unwrap();
+            assert_eq!(format.algorithm, algorithm);
+            assert_eq!(format.container, Container::Frame);
+            assert_eq!(decompress_auto(&compressed).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_rejects_unknown_and_corrupt_data() {
+        assert!(detect_format(b"plain text").is_none());
+        assert!(matches!(
+            decompress_auto(b"plain text"),
+            Err(CompressionError::UnknownFormat)
+        ));
+
+        let mut compressed =
+            compress_with_header(b"payload", Algorithm::Zstd, CompressionLevel::Default).unwrap();
+        compressed[5] = 99;
+        assert!(matches!(
+            decompress_auto(&compressed),
+            Err(CompressionError::InvalidAlgorithm(_))
+        ));
+
+        compressed[5] = Algorithm::Zstd.id();
+        compressed[6] = 0xFF;
+        assert!(matches!(
+            decompress_auto(&compressed),
+            Err(CompressionError::CorruptedData(_))
+        ));
+    }
+}
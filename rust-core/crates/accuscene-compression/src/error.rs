@@ -52,6 +52,10 @@ pub enum CompressionError {
     #[error("Invalid magic number: expected {expected:x}, got {actual:x}")]
     InvalidMagic { expected: u32, actual: u32 },
 
+    /// Data is not in a recognised compressed format
+    #[error("Unrecognized compression format")]
+    UnknownFormat,
+
     /// Corrupted data detected
     #[error("Corrupted data: {0}")]
     CorruptedData(String),
@@ -6,11 +6,13 @@
 //!
 //! - **Multiple Algorithms**: LZ4, Zstandard, Brotli, Deflate, Snappy
 //! - **Adaptive Compression**: Automatically selects the best algorithm
+//! - **Format Detection**: Decompress data without knowing the algorithm
 //! - **Streaming Support**: Compress large files efficiently
 //! - **Dictionary Compression**: Improved ratios for similar data
 //! - **Delta Compression**: Efficient incremental saves
 //! - **Archive Format**: Bundle multiple files into .accuscene archives
-//! - **Encryption**: Optional AES-256-GCM encryption layer
+//! - **Encryption**: Optional AES-256-GCM encryption layer and per-entry
+//!   archive encryption with shareable entry keys
 //! - **Serialization**: Binary, MessagePack, and compact formats
 //! - **Benchmarking**: Built-in performance testing
 //!
@@ -38,6 +40,18 @@
 //! let decompressed = decompress_auto(&compressed).unwrap();
 //! ```
 //!
+//! ## Format Detection
+//!
+//! ```rust,no_run
+//! use accuscene_compression::{compress_with_header, decompress_auto, Algorithm, CompressionLevel};
+//!
+//! let data = b"Data from anywhere";
+//! let compressed = compress_with_header(data, Algorithm::Brotli, CompressionLevel::Default).unwrap();
+//!
+//! // The header records the algorithm; standard zstd/gzip/LZ4/Snappy frames are detected by magic bytes
+//! let decompressed = decompress_auto(&compressed).unwrap();
+//! ```
+//!
 //! ## Archive Format
 //!
 //! ```rust,no_run
@@ -59,6 +73,7 @@ pub mod algorithms;
 pub mod archive;
 pub mod benchmark;
 pub mod delta;
+pub mod detect;
 pub mod dictionary;
 pub mod encryption;
 pub mod error;
@@ -67,6 +82,7 @@ pub mod streaming;
 pub mod traits;
 
 // Re-export commonly used types
+pub use detect::{compress_with_header, decompress_auto, detect_format, DetectedFormat};
 pub use error::{CompressionError, Result};
 pub use traits::{
     Algorithm, CompressionLevel, CompressionStats, Compressible, Compressor,
@@ -110,6 +126,7 @@ pub fn decompress(data: &[u8], algorithm: Algorithm) -> Result<Vec<u8>> {
 pub struct CompressionFacade {
     default_algorithm: Algorithm,
     default_level: CompressionLevel,
+    header: bool,
 }
 
 impl CompressionFacade {
@@ -118,6 +135,7 @@ impl CompressionFacade {
         Self {
             default_algorithm: Algorithm::Zstd,
             default_level: CompressionLevel::Default,
+            header: false,
         }
     }
 
@@ -126,17 +144,32 @@ impl CompressionFacade {
         Self {
             default_algorithm: algorithm,
             default_level: level,
+            header: false,
         }
     }
 
+    /// Write the self-describing header in compressed output
+    pub fn with_header(mut self, enabled: bool) -> Self {
+        self.header = enabled;
+        self
+    }
+
     /// Compress with default settings
     pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>> {
-        algorithms::compress(data, self.default_algorithm, self.default_level)
+        if self.header {
+            detect::compress_with_header(data, self.default_algorithm, self.default_level)
+        } else {
+            algorithms::compress(data, self.default_algorithm, self.default_level)
+        }
     }
 
-    /// Decompress with default algorithm
+    /// Decompress with default algorithm, or the one named in a self-describing header
     pub fn decompress(&self, data: &[u8]) -> Result<Vec<u8>> {
-        algorithms::decompress(data, self.default_algorithm)
+        if detect::has_header(data) {
+            detect::decompress_auto(data)
+        } else {
+            algorithms::decompress(data, self.default_algorithm)
+        }
     }
 
     /// Compress with specific algorithm
@@ -201,9 +234,10 @@ impl Default for CompressionFacade {
 pub mod prelude {
     pub use crate::adaptive::{compress_auto, decompress_auto, AdaptiveCompressor};
     pub use crate::algorithms;
-    pub use crate::archive::{Archive, ArchiveEntry};
+    pub use crate::archive::{Archive, ArchiveEntry, SealedEntry};
     pub use crate::benchmark::{benchmark, BenchmarkConfig, BenchmarkResult};
     pub use crate::delta::{compress_delta, decompress_delta, DeltaPatch};
+    pub use crate::detect::{compress_with_header, detect_format, DetectedFormat};
     pub use crate::dictionary::{CompressionDictionary, DictionaryManager};
     pub use crate::error::{CompressionError, Result};
     pub use crate::serialization::{self, SerializationFormat};
@@ -215,6 +249,8 @@ pub mod prelude {
 
     #[cfg(feature = "encryption")]
     pub use crate::encryption::{compress_encrypt, decrypt_decompress};
+    #[cfg(feature = "encryption")]
+    pub use crate::archive::ArchiveKeyGrant;
 }
 
 #[cfg(test)]
@@ -262,6 +298,19 @@ mod tests {
         assert!(stats.ratio < 1.0);
     }
 
+    #[test]
+    fn test_facade_with_header() {
+        let data = b"Self-describing facade output".repeat(20);
+        let writer = CompressionFacade::with_defaults(Algorithm::Brotli, CompressionLevel::Fast)
+            .with_header(true);
+
+        let compressed = writer.compress(&data).unwrap();
+
+        // A reader with a different default still decompresses it
+        assert_eq!(CompressionFacade::new().decompress(&compressed).unwrap(), data);
+        assert_eq!(decompress_auto(&compressed).unwrap(), data);
+    }
+
     #[test]
     fn test_large_data() {
         let data = vec![0u8; 1_000_000]; // 1MB of zeros
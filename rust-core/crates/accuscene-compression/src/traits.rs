@@ -42,6 +42,41 @@ impl Algorithm {
         }
     }
 
+    /// Get the one-byte identifier used in headers
+    pub fn id(&self) -> u8 {
+        match self {
+            Algorithm::Lz4 => 0,
+            Algorithm::Zstd => 1,
+            Algorithm::Brotli => 2,
+            Algorithm::Deflate => 3,
+            Algorithm::Snappy => 4,
+        }
+    }
+
+    /// Get algorithm from its one-byte header identifier
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Algorithm::Lz4),
+            1 => Some(Algorithm::Zstd),
+            2 => Some(Algorithm::Brotli),
+            3 => Some(Algorithm::Deflate),
+            4 => Some(Algorithm::Snappy),
+            _ => None,
+        }
+    }
+
+    /// Get algorithm from a file extension (e.g. `zst`, `gz`)
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "lz4" => Some(Algorithm::Lz4),
+            "zst" | "zstd" => Some(Algorithm::Zstd),
+            "br" => Some(Algorithm::Brotli),
+            "gz" | "gzip" => Some(Algorithm::Deflate),
+            "snap" | "sz" => Some(Algorithm::Snappy),
+            _ => None,
+        }
+    }
+
     /// Get file extension for algorithm
     pub fn extension(&self) -> &'static str {
         match self {
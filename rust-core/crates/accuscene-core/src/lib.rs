@@ -11,6 +11,7 @@
 //! - **Error Handling**: Robust error types with detailed categorization
 //! - **Configuration**: Type-safe configuration management
 //! - **Traits**: Common traits for serialization, validation, and identification
+//! - **Measurements**: Annotated scene measurements with uncertainty budgets
 //!
 //! # Example
 //!
@@ -44,6 +45,7 @@
 
 pub mod config;
 pub mod error;
+pub mod measurements;
 pub mod traits;
 pub mod types;
 pub mod utils;
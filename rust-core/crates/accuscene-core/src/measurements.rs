@@ -0,0 +1,524 @@
+//! Scene measurements with uncertainty budgets
+//!
+//! Every physical measurement taken at a scene (skid length, crush depth,
+//! gouge position, ...) carries the uncertainty of the instrument and method
+//! used to take it. A [`Measurement`] keeps that uncertainty as a budget of
+//! independent components combined by root-sum-square, so it can be
+//! propagated into derived quantities such as speed estimates, or sampled as
+//! an input distribution for Monte Carlo runs.
+
+use crate::error::{AccuSceneError, Result};
+use crate::traits::{Identifiable, Serializable, Validatable};
+use crate::types::vector::Vector2D;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Default coverage factor (k = 2, roughly 95% for a normal distribution)
+pub const DEFAULT_COVERAGE_FACTOR: f64 = 2.0;
+
+/// Name of the budget component seeded from the measurement method
+pub const INSTRUMENT_COMPONENT: &str = "instrument";
+
+/// What was measured
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MeasurementKind {
+    /// Length of a straight skid mark (m)
+    SkidLength,
+    /// Radius of a yaw mark (m)
+    YawRadius,
+    /// Residual crush depth (m)
+    CrushDepth,
+    /// Position of a gouge or scrape along the reference line (m)
+    GougePosition,
+    /// Pedestrian or object throw distance (m)
+    ThrowDistance,
+    /// Horizontal distance travelled while airborne (m)
+    AirborneDistance,
+    /// Vertical drop while airborne (m)
+    FallHeight,
+    /// Road grade (percent)
+    RoadGrade,
+    /// Anything else, with a free-form name
+    Other(String),
+}
+
+impl MeasurementKind {
+    /// Unit the value is expressed in
+    pub fn unit(&self) -> &str {
+        match self {
+            MeasurementKind::RoadGrade => "%",
+            _ => "m",
+        }
+    }
+
+    /// Whether the quantity can never be negative
+    pub fn is_non_negative(&self) -> bool {
+        !matches!(
+            self,
+            MeasurementKind::GougePosition | MeasurementKind::RoadGrade | MeasurementKind::Other(_)
+        )
+    }
+}
+
+/// How a measurement was taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MeasurementMethod {
+    /// Steel or fibreglass tape
+    Tape,
+    /// Measuring wheel
+    MeasuringWheel,
+    /// Total station survey
+    TotalStation,
+    /// 3D laser scanner
+    LaserScanner,
+    /// Photogrammetry from scene photographs
+    Photogrammetry,
+    /// Aerial survey by drone
+    Drone,
+    /// Crush profile jig
+    CrushJig,
+    /// Visual estimate
+    Estimated,
+}
+
+impl MeasurementMethod {
+    /// Typical standard uncertainty of the method for a value of this size
+    ///
+    /// These are conservative defaults; record the instrument's calibrated
+    /// uncertainty with [`Measurement::with_instrument`] when it is known.
+    pub fn typical_uncertainty(&self, value: f64) -> f64 {
+        let magnitude = value.abs();
+        match self {
+            MeasurementMethod::Tape => 0.005 + 0.001 * magnitude,
+            MeasurementMethod::MeasuringWheel => 0.02 + 0.01 * magnitude,
+            MeasurementMethod::TotalStation => 0.005,
+            MeasurementMethod::LaserScanner => 0.003,
+            MeasurementMethod::Photogrammetry => 0.02 * magnitude,
+            MeasurementMethod::Drone => 0.01 + 0.005 * magnitude,
+            MeasurementMethod::CrushJig => 0.01,
+            MeasurementMethod::Estimated => 0.1 * magnitude,
+        }
+    }
+}
+
+/// Independent source of uncertainty
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UncertaintyComponent {
+    /// Source of the uncertainty (e.g. "instrument", "end point definition")
+    pub source: String,
+    /// Standard uncertainty (one standard deviation), in the unit of the measurement
+    pub standard_uncertainty: f64,
+}
+
+/// Uncertainty budget of a measurement
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UncertaintyBudget {
+    /// Independent components
+    pub components: Vec<UncertaintyComponent>,
+}
+
+impl UncertaintyBudget {
+    /// Create an empty budget
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a component, replacing an existing one from the same source
+    pub fn set(&mut self, source: &str, standard_uncertainty: f64) {
+        match self.components.iter_mut().find(|c| c.source == source) {
+            Some(component) => component.standard_uncertainty = standard_uncertainty,
+            None => self.components.push(UncertaintyComponent {
+                source: source.to_string(),
+                standard_uncertainty,
+            }),
+        }
+    }
+
+    /// Combined standard uncertainty (root-sum-square of the components)
+    pub fn combined(&self) -> f64 {
+        self.components
+            .iter()
+            .map(|c| c.standard_uncertainty * c.standard_uncertainty)
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    /// Component contributing the most uncertainty
+    pub fn dominant(&self) -> Option<&UncertaintyComponent> {
+        self.components
+            .iter()
+            .max_by(|a, b| a.standard_uncertainty.total_cmp(&b.standard_uncertainty))
+    }
+}
+
+/// Value derived from measurements, with its propagated standard uncertainty
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct UncertainValue {
+    /// Nominal value
+    pub value: f64,
+    /// Standard uncertainty
+    pub standard_uncertainty: f64,
+}
+
+impl UncertainValue {
+    /// Expanded uncertainty at a coverage factor
+    pub fn expanded(&self, coverage_factor: f64) -> f64 {
+        coverage_factor * self.standard_uncertainty
+    }
+}
+
+/// Annotated scene measurement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Measurement {
+    /// Unique identifier
+    pub id: String,
+    /// What was measured
+    pub kind: MeasurementKind,
+    /// Measured value, in the unit of the kind
+    pub value: f64,
+    /// How it was measured
+    pub method: MeasurementMethod,
+    /// Instrument make/model or serial number
+    pub instrument: Option<String>,
+    /// Uncertainty budget
+    pub budget: UncertaintyBudget,
+    /// Coverage factor used when reporting expanded uncertainty
+    pub coverage_factor: f64,
+    /// Position of the annotation in scene coordinates
+    pub position: Option<Vector2D>,
+    /// Evidence item the measurement documents
+    pub evidence_id: Option<String>,
+    /// Free-form notes
+    pub notes: Option<String>,
+    /// When the measurement was taken
+    pub measured_at: DateTime<Utc>,
+}
+
+impl Measurement {
+    /// Create a measurement with the method's typical uncertainty
+    pub fn new(kind: MeasurementKind, value: f64, method: MeasurementMethod) -> Self {
+        let mut budget = UncertaintyBudget::new();
+        budget.set(INSTRUMENT_COMPONENT, method.typical_uncertainty(value));
+
+        Self {
+            id: Uuid::new_v4().to_string(),
+            kind,
+            value,
+            method,
+            instrument: None,
+            budget,
+            coverage_factor: DEFAULT_COVERAGE_FACTOR,
+            position: None,
+            evidence_id: None,
+            notes: None,
+            measured_at: Utc::now(),
+        }
+    }
+
+    /// Record the instrument and its calibrated standard uncertainty
+    pub fn with_instrument(mut self, instrument: String, standard_uncertainty: f64) -> Self {
+        self.instrument = Some(instrument);
+        self.budget.set(INSTRUMENT_COMPONENT, standard_uncertainty);
+        self
+    }
+
+    /// Add an uncertainty component (e.g. end point definition of a skid mark)
+    pub fn with_uncertainty(mut self, source: &str, standard_uncertainty: f64) -> Self {
+        self.budget.set(source, standard_uncertainty);
+        self
+    }
+
+    /// Set the coverage factor
+    pub fn with_coverage_factor(mut self, coverage_factor: f64) -> Self {
+        self.coverage_factor = coverage_factor;
+        self
+    }
+
+    /// Set the scene position of the annotation
+    pub fn with_position(mut self, position: Vector2D) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    /// Link the measurement to an evidence item
+    pub fn with_evidence(mut self, evidence_id: String) -> Self {
+        self.evidence_id = Some(evidence_id);
+        self
+    }
+
+    /// Combined standard uncertainty
+    pub fn standard_uncertainty(&self) -> f64 {
+        self.budget.combined()
+    }
+
+    /// Expanded uncertainty (± at the coverage factor)
+    pub fn expanded_uncertainty(&self) -> f64 {
+        self.coverage_factor * self.standard_uncertainty()
+    }
+
+    /// Relative standard uncertainty
+    pub fn relative_uncertainty(&self) -> f64 {
+        if self.value == 0.0 {
+            return f64::INFINITY;
+        }
+        self.standard_uncertainty() / self.value.abs()
+    }
+
+    /// Coverage interval `(low, high)` at the coverage factor
+    pub fn interval(&self) -> (f64, f64) {
+        let u = self.expanded_uncertainty();
+        let low = self.value - u;
+        let low = if self.kind.is_non_negative() { low.max(0.0) } else { low };
+        (low, self.value + u)
+    }
+
+    /// Value `z` standard deviations away from the nominal value
+    pub fn sample(&self, z: f64) -> f64 {
+        let value = self.value + z * self.standard_uncertainty();
+        if self.kind.is_non_negative() {
+            value.max(0.0)
+        } else {
+            value
+        }
+    }
+
+    /// As an uncertain value
+    pub fn as_uncertain(&self) -> UncertainValue {
+        UncertainValue {
+            value: self.value,
+            standard_uncertainty: self.standard_uncertainty(),
+        }
+    }
+
+    /// Propagate the uncertainty through a function of this measurement
+    pub fn propagate<F>(&self, f: F) -> UncertainValue
+    where
+        F: Fn(f64) -> f64,
+    {
+        propagate(&[self], |values| f(values[0]))
+    }
+}
+
+/// Propagate the uncertainty of independent measurements through a function
+///
+/// Uses first-order (linear) propagation with numerical partial derivatives:
+/// `u(f)² = Σ (∂f/∂xᵢ · u(xᵢ))²`. Adequate when the uncertainties are small
+/// relative to the curvature of `f`; use Monte Carlo sampling otherwise.
+pub fn propagate<F>(inputs: &[&Measurement], f: F) -> UncertainValue
+where
+    F: Fn(&[f64]) -> f64,
+{
+    let nominal: Vec<f64> = inputs.iter().map(|m| m.value).collect();
+    let value = f(&nominal);
+
+    let variance: f64 = inputs
+        .iter()
+        .enumerate()
+        .map(|(i, measurement)| {
+            let u = measurement.standard_uncertainty();
+            if u == 0.0 {
+                return 0.0;
+            }
+
+            // Central difference with a step well inside the uncertainty
+            let h = (u * 1e-3).max(f64::EPSILON.sqrt() * nominal[i].abs().max(1.0));
+            let mut plus = nominal.clone();
+            let mut minus = nominal.clone();
+            plus[i] += h;
+            minus[i] -= h;
+            let derivative = (f(&plus) - f(&minus)) / (2.0 * h);
+
+            (derivative * u).powi(2)
+        })
+        .sum();
+
+    UncertainValue {
+        value,
+        standard_uncertainty: variance.sqrt(),
+    }
+}
+
+impl Identifiable for Measurement {
+    type Id = String;
+
+    fn id(&self) -> &Self::Id {
+        &self.id
+    }
+
+    fn set_id(&mut self, id: Self::Id) {
+        self.id = id;
+    }
+
+    fn with_new_id(mut self) -> Self {
+        self.id = Uuid::new_v4().to_string();
+        self
+    }
+}
+
+impl Validatable for Measurement {
+    fn validate(&self) -> Result<()> {
+        if !self.value.is_finite() {
+            return Err(AccuSceneError::validation_field(
+                "Measurement value must be finite",
+                "value",
+            ));
+        }
+
+        if self.kind.is_non_negative() && self.value < 0.0 {
+            return Err(AccuSceneError::validation_field(
+                format!("{:?} cannot be negative", self.kind),
+                "value".to_string(),
+            ));
+        }
+
+        if let Some(component) = self
+            .budget
+            .components
+            .iter()
+            .find(|c| !c.standard_uncertainty.is_finite() || c.standard_uncertainty < 0.0)
+        {
+            return Err(AccuSceneError::validation_field(
+                format!("Invalid uncertainty for {}", component.source),
+                "budget".to_string(),
+            ));
+        }
+
+        if !(self.coverage_factor.is_finite() && self.coverage_factor > 0.0) {
+            return Err(AccuSceneError::validation_field(
+                "Coverage factor must be positive",
+                "coverage_factor",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Serializable for Measurement {}
+
+/// Deterministic sampler of measurement distributions for Monte Carlo runs
+///
+/// Measurements are sampled as normal distributions around their nominal
+/// value. The generator is seeded so runs are reproducible.
+#[derive(Debug, Clone)]
+pub struct MeasurementSampler {
+    state: u64,
+    spare: Option<f64>,
+}
+
+impl MeasurementSampler {
+    /// Create a sampler from a seed
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: seed,
+            spare: None,
+        }
+    }
+
+    /// Next uniform value in `(0, 1)`
+    fn next_uniform(&mut self) -> f64 {
+        // SplitMix64
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        ((z >> 11) as f64 + 0.5) / (1u64 << 53) as f64
+    }
+
+    /// Next standard normal value
+    pub fn next_standard_normal(&mut self) -> f64 {
+        if let Some(spare) = self.spare.take() {
+            return spare;
+        }
+
+        // Box-Muller transform
+        let u1 = self.next_uniform();
+        let u2 = self.next_uniform();
+        let radius = (-2.0 * u1.ln()).sqrt();
+        let angle = 2.0 * std::f64::consts::PI * u2;
+        self.spare = Some(radius * angle.sin());
+        radius * angle.cos()
+    }
+
+    /// Draw a value for a measurement
+    pub fn sample(&mut self, measurement: &Measurement) -> f64 {
+        let z = self.next_standard_normal();
+        measurement.sample(z)
+    }
+
+    /// Draw one value for each measurement
+    pub fn sample_all(&mut self, measurements: &[Measurement]) -> Vec<f64> {
+        measurements.iter().map(|m| self.sample(m)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_combines_root_sum_square() {
+        let measurement = Measurement::new(MeasurementKind::SkidLength, 30.0, MeasurementMethod::Tape)
+            .with_instrument("Tape T-100".to_string(), 0.03)
+            .with_uncertainty("end point definition", 0.04);
+
+        assert!((measurement.standard_uncertainty() - 0.05).abs() < 1e-12);
+        assert!((measurement.expanded_uncertainty() - 0.10).abs() < 1e-12);
+        assert_eq!(measurement.budget.dominant().unwrap().source, "end point definition");
+        assert_eq!(measurement.budget.components.len(), 2);
+    }
+
+    #[test]
+    fn test_interval_respects_non_negative_kinds() {
+        let crush = Measurement::new(MeasurementKind::CrushDepth, 0.01, MeasurementMethod::Estimated)
+            .with_uncertainty("deformation", 0.02);
+        assert_eq!(crush.interval().0, 0.0);
+        assert_eq!(crush.sample(-3.0), 0.0);
+
+        let grade = Measurement::new(MeasurementKind::RoadGrade, 0.5, MeasurementMethod::TotalStation);
+        assert!(grade.sample(-1000.0) < 0.0);
+    }
+
+    #[test]
+    fn test_propagate_square_root() {
+        // v = sqrt(k * d): relative uncertainty halves
+        let skid = Measurement::new(MeasurementKind::SkidLength, 40.0, MeasurementMethod::Tape)
+            .with_instrument("tape".to_string(), 0.4);
+        let speed = skid.propagate(|d| (2.0 * 0.7 * 9.81 * d).sqrt());
+
+        let relative = speed.standard_uncertainty / speed.value;
+        assert!((relative - 0.005).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_sampler_is_reproducible_and_centered() {
+        let measurement = Measurement::new(MeasurementKind::ThrowDistance, 20.0, MeasurementMethod::Tape)
+            .with_instrument("tape".to_string(), 0.5);
+
+        let mut a = MeasurementSampler::new(42);
+        let mut b = MeasurementSampler::new(42);
+        let samples: Vec<f64> = (0..5000).map(|_| a.sample(&measurement)).collect();
+        assert_eq!(samples[..10], (0..10).map(|_| b.sample(&measurement)).collect::<Vec<_>>()[..]);
+
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let std_dev = (samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / samples.len() as f64).sqrt();
+        assert!((mean - 20.0).abs() < 0.05);
+        assert!((std_dev - 0.5).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_validation() {
+        let valid = Measurement::new(MeasurementKind::SkidLength, 12.0, MeasurementMethod::LaserScanner);
+        assert!(valid.validate().is_ok());
+
+        let negative = Measurement::new(MeasurementKind::SkidLength, -1.0, MeasurementMethod::Tape);
+        assert!(negative.validate().is_err());
+
+        let bad_budget = valid.with_uncertainty("placement", f64::NAN);
+        assert!(bad_budget.validate().is_err());
+    }
+}
@@ -271,18 +271,12 @@ impl AccidentScene {
 
     /// Get all stationary vehicles
     pub fn stationary_vehicles(&self) -> Vec<&Vehicle> {
-        self.vehicles
-            .iter()
-            .filter(|v| v.is_stationary(0.1))
-            .collect()
+        self.vehicles.iter().filter(|v| v.is_stationary(0.1)).collect()
     }
 
     /// Get all moving vehicles
     pub fn moving_vehicles(&self) -> Vec<&Vehicle> {
-        self.vehicles
-            .iter()
-            .filter(|v| !v.is_stationary(0.1))
-            .collect()
+        self.vehicles.iter().filter(|v| !v.is_stationary(0.1)).collect()
     }
 
     /// Calculate total kinetic energy in the scene
@@ -292,10 +286,7 @@ impl AccidentScene {
 
     /// Find vehicles near a point
     pub fn vehicles_near(&self, point: Vector2D, radius: f64) -> Vec<&Vehicle> {
-        self.vehicles
-            .iter()
-            .filter(|v| v.position.distance(&point) <= radius)
-            .collect()
+        self.vehicles.iter().filter(|v| v.position.distance(&point) <= radius).collect()
     }
 
     /// Check if scene is within bounds
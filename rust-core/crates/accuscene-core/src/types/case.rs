@@ -37,10 +37,7 @@ impl CaseStatus {
 
     /// Check if case is finalized
     pub fn is_finalized(&self) -> bool {
-        matches!(
-            self,
-            Self::Completed | Self::Archived | Self::Cancelled
-        )
+        matches!(self, Self::Completed | Self::Archived | Self::Cancelled)
     }
 
     /// Get display name
@@ -231,12 +228,15 @@ impl Case {
     pub fn set_status(&mut self, status: CaseStatus) -> Result<()> {
         // Validate status transitions
         match (&self.status, &status) {
-            (CaseStatus::Completed | CaseStatus::Archived | CaseStatus::Cancelled, CaseStatus::Draft) => {
+            (
+                CaseStatus::Completed | CaseStatus::Archived | CaseStatus::Cancelled,
+                CaseStatus::Draft,
+            ) => {
                 return Err(AccuSceneError::InvalidState(
                     "Cannot revert finalized case to draft".to_string(),
                 ));
-            }
-            _ => {}
+            },
+            _ => {},
         }
 
         self.status = status;
@@ -283,9 +283,7 @@ impl Case {
     /// Set deadline
     pub fn set_deadline(&mut self, deadline: DateTime<Utc>) -> Result<()> {
         if deadline < Utc::now() {
-            return Err(AccuSceneError::validation(
-                "Deadline cannot be in the past",
-            ));
+            return Err(AccuSceneError::validation("Deadline cannot be in the past"));
         }
         self.deadline = Some(deadline);
         self.touch();
@@ -455,12 +453,7 @@ impl MemoryFootprint for Case {
             + self.description.as_ref().map(|s| s.capacity()).unwrap_or(0)
             + self.scene.memory_footprint()
             + self.investigators.len() * std::mem::size_of::<Investigator>()
-            + self
-                .metadata
-                .tags
-                .iter()
-                .map(|t| t.capacity())
-                .sum::<usize>()
+            + self.metadata.tags.iter().map(|t| t.capacity()).sum::<usize>()
     }
 }
 
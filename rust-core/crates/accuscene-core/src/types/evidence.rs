@@ -264,9 +264,7 @@ impl Evidence {
 
     /// Get current custodian
     pub fn current_custodian(&self) -> Option<&str> {
-        self.chain_of_custody
-            .last()
-            .map(|entry| entry.custodian.as_str())
+        self.chain_of_custody.last().map(|entry| entry.custodian.as_str())
     }
 
     /// Add a tag
@@ -317,11 +315,7 @@ impl Evidence {
 
     /// Verify file checksum
     pub fn verify_checksum(&self, provided_checksum: &str) -> bool {
-        self.metadata
-            .checksum
-            .as_ref()
-            .map(|c| c == provided_checksum)
-            .unwrap_or(false)
+        self.metadata.checksum.as_ref().map(|c| c == provided_checksum).unwrap_or(false)
     }
 
     /// Get human-readable file size
@@ -515,7 +509,11 @@ mod tests {
     fn test_file_attachment() {
         let mut evidence = Evidence::new("Photo".to_string(), EvidenceType::Photo);
 
-        evidence.attach_file("/path/to/photo.jpg".to_string(), 1024 * 500, "jpg".to_string());
+        evidence.attach_file(
+            "/path/to/photo.jpg".to_string(),
+            1024 * 500,
+            "jpg".to_string(),
+        );
         assert!(evidence.has_file());
 
         let size_str = evidence.file_size_string().unwrap();
@@ -2,11 +2,13 @@
 //!
 //! This module contains all the core types used throughout the
 //! AccuScene platform, including physics types, vehicle models,
-//! accident scenes, cases, and evidence tracking.
+//! accident scenes, cases, evidence tracking, organizations, and parties.
 
 pub mod accident;
 pub mod case;
 pub mod evidence;
+pub mod organization;
+pub mod party;
 pub mod vector;
 pub mod vehicle;
 
@@ -14,5 +16,12 @@ pub mod vehicle;
 pub use accident::{Accident, AccidentScene, RoadCondition, WeatherCondition};
 pub use case::{Case, CaseMetadata, CaseStatus};
 pub use evidence::{Evidence, EvidenceMetadata, EvidenceType};
+pub use organization::{
+    MemberRole, Membership, OrgHierarchy, OrgScope, Organization, OwnedResourceKind,
+    ResourceOwnership, Team, Workspace,
+};
+pub use party::{
+    MatchScore, Party, PartyLink, PartyMatcher, PartyMerge, PartyRole, PiiField, PrivacyClass,
+};
 pub use vector::{Vector2D, Vector3D};
 pub use vehicle::{Vehicle, VehicleCategory, VehicleMetadata};
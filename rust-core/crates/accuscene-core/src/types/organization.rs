@@ -0,0 +1,731 @@
+//! Organization hierarchy types
+//!
+//! This module defines the tenancy model: organizations contain teams,
+//! and workspaces belong to an organization and optionally a team.
+//! Cases, dashboards, saved searches and report templates are owned by a
+//! workspace, and memberships at any level of the hierarchy grant access to
+//! everything beneath it.
+
+use crate::error::{AccuSceneError, Result};
+use crate::traits::{Identifiable, Serializable, Timestamped, Validatable};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Top-level tenant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Organization {
+    /// Unique identifier
+    pub id: String,
+    /// Display name
+    pub name: String,
+    /// URL-safe slug
+    pub slug: String,
+    /// Creation timestamp
+    pub created_at: DateTime<Utc>,
+    /// Last update timestamp
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Organization {
+    /// Create a new organization
+    pub fn new(name: String, slug: String) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            slug,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+/// Team within an organization
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Team {
+    /// Unique identifier
+    pub id: String,
+    /// Owning organization
+    pub organization_id: String,
+    /// Display name
+    pub name: String,
+    /// Creation timestamp
+    pub created_at: DateTime<Utc>,
+    /// Last update timestamp
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Team {
+    /// Create a new team
+    pub fn new(organization_id: String, name: String) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            organization_id,
+            name,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+/// Workspace that owns cases and related resources
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    /// Unique identifier
+    pub id: String,
+    /// Owning organization
+    pub organization_id: String,
+    /// Owning team, if any
+    pub team_id: Option<String>,
+    /// Display name
+    pub name: String,
+    /// Creation timestamp
+    pub created_at: DateTime<Utc>,
+    /// Last update timestamp
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Workspace {
+    /// Create a new workspace directly under an organization
+    pub fn new(organization_id: String, name: String) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            organization_id,
+            team_id: None,
+            name,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Create a new workspace owned by a team
+    pub fn for_team(organization_id: String, team_id: String, name: String) -> Self {
+        let mut workspace = Self::new(organization_id, name);
+        workspace.team_id = Some(team_id);
+        workspace
+    }
+}
+
+/// A level in the organization hierarchy
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", content = "id", rename_all = "snake_case")]
+pub enum OrgScope {
+    /// Organization scope
+    Organization(String),
+    /// Team scope
+    Team(String),
+    /// Workspace scope
+    Workspace(String),
+}
+
+impl OrgScope {
+    /// Get the scope ID
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Organization(id) | Self::Team(id) | Self::Workspace(id) => id,
+        }
+    }
+
+    /// Get the scope kind name
+    pub fn kind(&self) -> &str {
+        match self {
+            Self::Organization(_) => "organization",
+            Self::Team(_) => "team",
+            Self::Workspace(_) => "workspace",
+        }
+    }
+
+    /// Build a scope from its kind name and ID
+    pub fn from_parts(kind: &str, id: String) -> Option<Self> {
+        match kind {
+            "organization" => Some(Self::Organization(id)),
+            "team" => Some(Self::Team(id)),
+            "workspace" => Some(Self::Workspace(id)),
+            _ => None,
+        }
+    }
+
+    /// Get the authorization scope string (e.g. `org:<id>`)
+    pub fn to_scope_string(&self) -> String {
+        match self {
+            Self::Organization(id) => format!("org:{}", id),
+            Self::Team(id) => format!("team:{}", id),
+            Self::Workspace(id) => format!("workspace:{}", id),
+        }
+    }
+
+    /// Parse an authorization scope string
+    pub fn parse(scope: &str) -> Option<Self> {
+        let (kind, id) = scope.split_once(':')?;
+        if id.is_empty() {
+            return None;
+        }
+
+        match kind {
+            "org" => Some(Self::Organization(id.to_string())),
+            "team" => Some(Self::Team(id.to_string())),
+            "workspace" => Some(Self::Workspace(id.to_string())),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for OrgScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_scope_string())
+    }
+}
+
+/// Role held by a member at a given scope
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum MemberRole {
+    /// Read-only access
+    Viewer,
+    /// Read and write access
+    Member,
+    /// Can manage members and settings
+    Admin,
+    /// Full control including deletion
+    Owner,
+}
+
+impl MemberRole {
+    /// Check if the role can modify resources
+    pub fn can_write(&self) -> bool {
+        *self >= Self::Member
+    }
+
+    /// Check if the role can manage memberships
+    pub fn can_manage_members(&self) -> bool {
+        *self >= Self::Admin
+    }
+
+    /// Get the storage name for this role
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Viewer => "viewer",
+            Self::Member => "member",
+            Self::Admin => "admin",
+            Self::Owner => "owner",
+        }
+    }
+
+    /// Parse a storage name
+    pub fn parse(role: &str) -> Option<Self> {
+        match role {
+            "viewer" => Some(Self::Viewer),
+            "member" => Some(Self::Member),
+            "admin" => Some(Self::Admin),
+            "owner" => Some(Self::Owner),
+            _ => None,
+        }
+    }
+
+    /// Get display name
+    pub fn display_name(&self) -> &str {
+        match self {
+            Self::Viewer => "Viewer",
+            Self::Member => "Member",
+            Self::Admin => "Admin",
+            Self::Owner => "Owner",
+        }
+    }
+}
+
+/// Membership of a user at a scope
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Membership {
+    /// User ID
+    pub user_id: String,
+    /// Scope the membership applies to
+    pub scope: OrgScope,
+    /// Role at the scope
+    pub role: MemberRole,
+    /// When the user joined
+    pub joined_at: DateTime<Utc>,
+}
+
+impl Membership {
+    /// Create a new membership
+    pub fn new(user_id: String, scope: OrgScope, role: MemberRole) -> Self {
+        Self {
+            user_id,
+            scope,
+            role,
+            joined_at: Utc::now(),
+        }
+    }
+}
+
+/// Kinds of resources that can be owned by a workspace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OwnedResourceKind {
+    /// Investigation case
+    Case,
+    /// Dashboard
+    Dashboard,
+    /// Saved search
+    SavedSearch,
+    /// Report template
+    ReportTemplate,
+}
+
+impl OwnedResourceKind {
+    /// Get the storage name for this kind
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Case => "case",
+            Self::Dashboard => "dashboard",
+            Self::SavedSearch => "saved_search",
+            Self::ReportTemplate => "report_template",
+        }
+    }
+
+    /// Parse a storage name
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "case" => Some(Self::Case),
+            "dashboard" => Some(Self::Dashboard),
+            "saved_search" => Some(Self::SavedSearch),
+            "report_template" => Some(Self::ReportTemplate),
+            _ => None,
+        }
+    }
+}
+
+/// Ownership link from a resource to its workspace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceOwnership {
+    /// Kind of resource
+    pub resource_kind: OwnedResourceKind,
+    /// Resource ID
+    pub resource_id: String,
+    /// Owning workspace
+    pub workspace_id: String,
+    /// User who owns the resource
+    pub owner_id: String,
+    /// When ownership was recorded
+    pub created_at: DateTime<Utc>,
+}
+
+impl ResourceOwnership {
+    /// Create a new ownership link
+    pub fn new(
+        resource_kind: OwnedResourceKind,
+        resource_id: String,
+        workspace_id: String,
+        owner_id: String,
+    ) -> Self {
+        Self {
+            resource_kind,
+            resource_id,
+            workspace_id,
+            owner_id,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// In-memory view of the organization hierarchy
+///
+/// Resolves effective roles and authorization scopes by walking from a
+/// workspace up through its team to the organization.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OrgHierarchy {
+    organizations: HashMap<String, Organization>,
+    teams: HashMap<String, Team>,
+    workspaces: HashMap<String, Workspace>,
+    memberships: Vec<Membership>,
+}
+
+impl OrgHierarchy {
+    /// Create an empty hierarchy
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an organization
+    pub fn add_organization(&mut self, organization: Organization) -> Result<()> {
+        organization.validate()?;
+        self.organizations.insert(organization.id.clone(), organization);
+        Ok(())
+    }
+
+    /// Add a team to an existing organization
+    pub fn add_team(&mut self, team: Team) -> Result<()> {
+        if !self.organizations.contains_key(&team.organization_id) {
+            return Err(AccuSceneError::not_found(
+                "Organization",
+                team.organization_id.as_str(),
+            ));
+        }
+        self.teams.insert(team.id.clone(), team);
+        Ok(())
+    }
+
+    /// Add a workspace to an existing organization (and team, if set)
+    pub fn add_workspace(&mut self, workspace: Workspace) -> Result<()> {
+        if !self.organizations.contains_key(&workspace.organization_id) {
+            return Err(AccuSceneError::not_found(
+                "Organization",
+                workspace.organization_id.as_str(),
+            ));
+        }
+
+        if let Some(team_id) = &workspace.team_id {
+            let team = self
+                .teams
+                .get(team_id)
+                .ok_or_else(|| AccuSceneError::not_found("Team", team_id.as_str()))?;
+            if team.organization_id != workspace.organization_id {
+                return Err(AccuSceneError::validation_field(
+                    "Workspace team belongs to a different organization",
+                    "team_id",
+                ));
+            }
+        }
+
+        self.workspaces.insert(workspace.id.clone(), workspace);
+        Ok(())
+    }
+
+    /// Get an organization
+    pub fn organization(&self, id: &str) -> Option<&Organization> {
+        self.organizations.get(id)
+    }
+
+    /// Get a team
+    pub fn team(&self, id: &str) -> Option<&Team> {
+        self.teams.get(id)
+    }
+
+    /// Get a workspace
+    pub fn workspace(&self, id: &str) -> Option<&Workspace> {
+        self.workspaces.get(id)
+    }
+
+    /// List teams of an organization
+    pub fn teams_of(&self, organization_id: &str) -> Vec<&Team> {
+        self.teams.values().filter(|t| t.organization_id == organization_id).collect()
+    }
+
+    /// List workspaces under a scope
+    pub fn workspaces_under(&self, scope: &OrgScope) -> Vec<&Workspace> {
+        self.workspaces
+            .values()
+            .filter(|w| match scope {
+                OrgScope::Organization(id) => &w.organization_id == id,
+                OrgScope::Team(id) => w.team_id.as_ref() == Some(id),
+                OrgScope::Workspace(id) => &w.id == id,
+            })
+            .collect()
+    }
+
+    /// Check whether a scope exists
+    pub fn contains_scope(&self, scope: &OrgScope) -> bool {
+        match scope {
+            OrgScope::Organization(id) => self.organizations.contains_key(id),
+            OrgScope::Team(id) => self.teams.contains_key(id),
+            OrgScope::Workspace(id) => self.workspaces.contains_key(id),
+        }
+    }
+
+    /// Add or replace a membership
+    pub fn add_member(&mut self, membership: Membership) -> Result<()> {
+        if !self.contains_scope(&membership.scope) {
+            return Err(AccuSceneError::not_found(
+                "Scope".to_string(),
+                membership.scope.to_scope_string(),
+            ));
+        }
+
+        self.memberships
+            .retain(|m| !(m.user_id == membership.user_id && m.scope == membership.scope));
+        self.memberships.push(membership);
+        Ok(())
+    }
+
+    /// Remove a membership
+    pub fn remove_member(&mut self, user_id: &str, scope: &OrgScope) -> Result<()> {
+        let initial_len = self.memberships.len();
+        self.memberships.retain(|m| !(m.user_id == user_id && &m.scope == scope));
+
+        if self.memberships.len() == initial_len {
+            return Err(AccuSceneError::not_found("Membership", user_id));
+        }
+        Ok(())
+    }
+
+    /// List direct members of a scope
+    pub fn members_of(&self, scope: &OrgScope) -> Vec<&Membership> {
+        self.memberships.iter().filter(|m| &m.scope == scope).collect()
+    }
+
+    /// List all memberships
+    pub fn memberships(&self) -> &[Membership] {
+        &self.memberships
+    }
+
+    /// List a user's direct memberships
+    pub fn memberships_of(&self, user_id: &str) -> Vec<&Membership> {
+        self.memberships.iter().filter(|m| m.user_id == user_id).collect()
+    }
+
+    /// Get the scope chain from a scope up to its organization (inclusive)
+    pub fn ancestors(&self, scope: &OrgScope) -> Vec<OrgScope> {
+        let mut chain = vec![scope.clone()];
+
+        match scope {
+            OrgScope::Workspace(id) => {
+                if let Some(workspace) = self.workspaces.get(id) {
+                    if let Some(team_id) = &workspace.team_id {
+                        chain.push(OrgScope::Team(team_id.clone()));
+                    }
+                    chain.push(OrgScope::Organization(workspace.organization_id.clone()));
+                }
+            },
+            OrgScope::Team(id) => {
+                if let Some(team) = self.teams.get(id) {
+                    chain.push(OrgScope::Organization(team.organization_id.clone()));
+                }
+            },
+            OrgScope::Organization(_) => {},
+        }
+
+        chain
+    }
+
+    /// Get the organization a scope belongs to
+    pub fn organization_of(&self, scope: &OrgScope) -> Option<&str> {
+        match scope {
+            OrgScope::Organization(id) => self.organizations.get(id).map(|o| o.id.as_str()),
+            OrgScope::Team(id) => self.teams.get(id).map(|t| t.organization_id.as_str()),
+            OrgScope::Workspace(id) => self.workspaces.get(id).map(|w| w.organization_id.as_str()),
+        }
+    }
+
+    /// Resolve a user's effective role at a scope, inherited from ancestors
+    pub fn effective_role(&self, user_id: &str, scope: &OrgScope) -> Option<MemberRole> {
+        let chain = self.ancestors(scope);
+        self.memberships
+            .iter()
+            .filter(|m| m.user_id == user_id && chain.contains(&m.scope))
+            .map(|m| m.role)
+            .max()
+    }
+
+    /// Authorization scopes granted to a user by their memberships
+    pub fn authorization_scopes(&self, user_id: &str) -> Vec<String> {
+        self.memberships_of(user_id)
+            .into_iter()
+            .map(|m| m.scope.to_scope_string())
+            .collect()
+    }
+
+    /// Authorization scopes a resource is visible in
+    pub fn resource_scopes(&self, ownership: &ResourceOwnership) -> Vec<String> {
+        self.ancestors(&OrgScope::Workspace(ownership.workspace_id.clone()))
+            .iter()
+            .map(OrgScope::to_scope_string)
+            .collect()
+    }
+
+    /// Check whether a user can access a resource
+    pub fn can_access(&self, user_id: &str, ownership: &ResourceOwnership) -> bool {
+        ownership.owner_id == user_id
+            || self
+                .effective_role(
+                    user_id,
+                    &OrgScope::Workspace(ownership.workspace_id.clone()),
+                )
+                .is_some()
+    }
+}
+
+impl Identifiable for Organization {
+    type Id = String;
+
+    fn id(&self) -> &Self::Id {
+        &self.id
+    }
+
+    fn set_id(&mut self, id: Self::Id) {
+        self.id = id;
+        self.touch();
+    }
+
+    fn with_new_id(mut self) -> Self {
+        self.id = Uuid::new_v4().to_string();
+        self
+    }
+}
+
+impl Timestamped for Organization {
+    fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+
+    fn touch(&mut self) {
+        self.updated_at = Utc::now();
+    }
+}
+
+impl Validatable for Organization {
+    fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(AccuSceneError::validation_field(
+                "Organization name cannot be empty",
+                "name",
+            ));
+        }
+
+        if self.slug.is_empty()
+            || !self
+                .slug
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        {
+            return Err(AccuSceneError::validation_field(
+                "Organization slug must be lowercase alphanumeric with dashes",
+                "slug",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Serializable for Organization {}
+impl Serializable for Team {}
+impl Serializable for Workspace {}
+impl Serializable for Membership {}
+impl Serializable for ResourceOwnership {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_hierarchy() -> (OrgHierarchy, Organization, Team, Workspace) {
+        let mut hierarchy = OrgHierarchy::new();
+        let org = Organization::new("Acme Forensics".to_string(), "acme".to_string());
+        let team = Team::new(org.id.clone(), "Reconstruction".to_string());
+        let workspace =
+            Workspace::for_team(org.id.clone(), team.id.clone(), "Highway 9".to_string());
+
+        hierarchy.add_organization(org.clone()).unwrap();
+        hierarchy.add_team(team.clone()).unwrap();
+        hierarchy.add_workspace(workspace.clone()).unwrap();
+
+        (hierarchy, org, team, workspace)
+    }
+
+    #[test]
+    fn test_scope_string_roundtrip() {
+        let scope = OrgScope::Team("t1".to_string());
+        assert_eq!(scope.to_scope_string(), "team:t1");
+        assert_eq!(OrgScope::parse("team:t1"), Some(scope));
+        assert_eq!(OrgScope::parse("team:"), None);
+        assert_eq!(OrgScope::parse("tenant:t1"), None);
+    }
+
+    #[test]
+    fn test_organization_validation() {
+        let org = Organization::new("Acme".to_string(), "Acme Inc".to_string());
+        assert!(org.validate().is_err());
+    }
+
+    #[test]
+    fn test_workspace_requires_matching_team() {
+        let (mut hierarchy, _, team, _) = sample_hierarchy();
+        let other = Organization::new("Other".to_string(), "other".to_string());
+        hierarchy.add_organization(other.clone()).unwrap();
+
+        let workspace = Workspace::for_team(other.id, team.id, "Mismatch".to_string());
+        assert!(hierarchy.add_workspace(workspace).is_err());
+    }
+
+    #[test]
+    fn test_effective_role_inherits_from_ancestors() {
+        let (mut hierarchy, org, team, workspace) = sample_hierarchy();
+        let ws_scope = OrgScope::Workspace(workspace.id.clone());
+
+        hierarchy
+            .add_member(Membership::new(
+                "alice".to_string(),
+                OrgScope::Organization(org.id.clone()),
+                MemberRole::Viewer,
+            ))
+            .unwrap();
+        hierarchy
+            .add_member(Membership::new(
+                "alice".to_string(),
+                OrgScope::Team(team.id.clone()),
+                MemberRole::Admin,
+            ))
+            .unwrap();
+
+        assert_eq!(
+            hierarchy.effective_role("alice", &ws_scope),
+            Some(MemberRole::Admin)
+        );
+        assert_eq!(hierarchy.effective_role("bob", &ws_scope), None);
+    }
+
+    #[test]
+    fn test_resource_access_through_scopes() {
+        let (mut hierarchy, org, _, workspace) = sample_hierarchy();
+        let ownership = ResourceOwnership::new(
+            OwnedResourceKind::Case,
+            "case-1".to_string(),
+            workspace.id.clone(),
+            "carol".to_string(),
+        );
+
+        hierarchy
+            .add_member(Membership::new(
+                "dave".to_string(),
+                OrgScope::Organization(org.id.clone()),
+                MemberRole::Member,
+            ))
+            .unwrap();
+
+        let resource_scopes = hierarchy.resource_scopes(&ownership);
+        assert_eq!(resource_scopes.len(), 3);
+        assert!(hierarchy
+            .authorization_scopes("dave")
+            .iter()
+            .any(|s| resource_scopes.contains(s)));
+
+        assert!(hierarchy.can_access("carol", &ownership));
+        assert!(hierarchy.can_access("dave", &ownership));
+        assert!(!hierarchy.can_access("eve", &ownership));
+    }
+
+    #[test]
+    fn test_remove_member() {
+        let (mut hierarchy, org, _, _) = sample_hierarchy();
+        let scope = OrgScope::Organization(org.id);
+
+        hierarchy
+            .add_member(Membership::new(
+                "alice".to_string(),
+                scope.clone(),
+                MemberRole::Owner,
+            ))
+            .unwrap();
+        assert_eq!(hierarchy.members_of(&scope).len(), 1);
+
+        hierarchy.remove_member("alice", &scope).unwrap();
+        assert!(hierarchy.members_of(&scope).is_empty());
+        assert!(hierarchy.remove_member("alice", &scope).is_err());
+    }
+}
@@ -0,0 +1,710 @@
+//! Party registry types
+//!
+//! A party is a person involved in one or more cases: a driver, passenger,
+//! witness, pedestrian or vehicle owner. Names are re-entered by hand and
+//! often carry typos, so parties are deduplicated with fuzzy matching that
+//! combines normalized names, phonetic codes and edit distance, corroborated
+//! by date of birth and licence number. Personal fields carry a privacy
+//! classification and are redacted for readers without sufficient clearance.
+
+use crate::error::{AccuSceneError, Result};
+use crate::traits::{Identifiable, Mergeable, Serializable, Timestamped, Validatable};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Mask used for redacted fields
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Privacy classification of personal data, in increasing sensitivity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PrivacyClass {
+    /// Can be shown to anyone with access to the case
+    Public,
+    /// Internal to the investigating organization
+    Internal,
+    /// Personal data; need-to-know access
+    Confidential,
+    /// Sensitive identifiers (date of birth, licence); strictly limited
+    Restricted,
+}
+
+impl PrivacyClass {
+    /// Get the class name
+    pub fn as_str(&self) -> &str {
+        match self {
+            PrivacyClass::Public => "public",
+            PrivacyClass::Internal => "internal",
+            PrivacyClass::Confidential => "confidential",
+            PrivacyClass::Restricted => "restricted",
+        }
+    }
+
+    /// Parse a class name
+    pub fn parse(class: &str) -> Option<Self> {
+        match class {
+            "public" => Some(PrivacyClass::Public),
+            "internal" => Some(PrivacyClass::Internal),
+            "confidential" => Some(PrivacyClass::Confidential),
+            "restricted" => Some(PrivacyClass::Restricted),
+            _ => None,
+        }
+    }
+}
+
+/// Personal field of a party
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiField {
+    /// Full name
+    FullName,
+    /// Date of birth
+    DateOfBirth,
+    /// Driving licence number
+    LicenceNumber,
+    /// Phone number
+    Phone,
+    /// Postal address
+    Address,
+}
+
+impl PiiField {
+    /// Baseline classification of the field
+    pub fn classification(&self) -> PrivacyClass {
+        match self {
+            PiiField::FullName => PrivacyClass::Internal,
+            PiiField::Phone | PiiField::Address => PrivacyClass::Confidential,
+            PiiField::DateOfBirth | PiiField::LicenceNumber => PrivacyClass::Restricted,
+        }
+    }
+}
+
+/// Role a party plays in a case
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PartyRole {
+    /// Vehicle driver
+    Driver,
+    /// Vehicle passenger
+    Passenger,
+    /// Witness
+    Witness,
+    /// Pedestrian or cyclist
+    Pedestrian,
+    /// Registered vehicle owner
+    Owner,
+    /// Any other role
+    Other,
+}
+
+impl PartyRole {
+    /// Get the role name
+    pub fn as_str(&self) -> &str {
+        match self {
+            PartyRole::Driver => "driver",
+            PartyRole::Passenger => "passenger",
+            PartyRole::Witness => "witness",
+            PartyRole::Pedestrian => "pedestrian",
+            PartyRole::Owner => "owner",
+            PartyRole::Other => "other",
+        }
+    }
+
+    /// Parse a role name
+    pub fn parse(role: &str) -> Option<Self> {
+        match role {
+            "driver" => Some(PartyRole::Driver),
+            "passenger" => Some(PartyRole::Passenger),
+            "witness" => Some(PartyRole::Witness),
+            "pedestrian" => Some(PartyRole::Pedestrian),
+            "owner" => Some(PartyRole::Owner),
+            "other" => Some(PartyRole::Other),
+            _ => None,
+        }
+    }
+}
+
+/// A person in the registry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Party {
+    /// Unique identifier
+    pub id: String,
+    /// Full name as entered
+    pub full_name: String,
+    /// Normalized name used for matching
+    pub normalized_name: String,
+    /// Phonetic key used for candidate lookup
+    pub phonetic_key: String,
+    /// Date of birth
+    pub date_of_birth: Option<NaiveDate>,
+    /// Driving licence number (normalized)
+    pub licence_number: Option<String>,
+    /// Phone number
+    pub phone: Option<String>,
+    /// Postal address
+    pub address: Option<String>,
+    /// Minimum classification applied to all of this party's personal data
+    pub privacy_class: PrivacyClass,
+    /// Surviving party if this record was merged into another
+    pub merged_into: Option<String>,
+    /// Creation timestamp
+    pub created_at: DateTime<Utc>,
+    /// Last update timestamp
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Party {
+    /// Create a new party
+    pub fn new(full_name: String) -> Self {
+        let now = Utc::now();
+        let normalized_name = normalize_name(&full_name);
+        Self {
+            id: Uuid::new_v4().to_string(),
+            phonetic_key: phonetic_key(&normalized_name),
+            normalized_name,
+            full_name,
+            date_of_birth: None,
+            licence_number: None,
+            phone: None,
+            address: None,
+            privacy_class: PrivacyClass::Internal,
+            merged_into: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Set the date of birth
+    pub fn with_date_of_birth(mut self, date_of_birth: NaiveDate) -> Self {
+        self.date_of_birth = Some(date_of_birth);
+        self
+    }
+
+    /// Set the licence number
+    pub fn with_licence_number(mut self, licence_number: &str) -> Self {
+        self.licence_number = Some(normalize_licence(licence_number));
+        self
+    }
+
+    /// Set the phone number
+    pub fn with_phone(mut self, phone: String) -> Self {
+        self.phone = Some(phone);
+        self
+    }
+
+    /// Set the address
+    pub fn with_address(mut self, address: String) -> Self {
+        self.address = Some(address);
+        self
+    }
+
+    /// Raise the classification of this party's data (e.g. for minors)
+    pub fn with_privacy_class(mut self, privacy_class: PrivacyClass) -> Self {
+        self.privacy_class = privacy_class;
+        self
+    }
+
+    /// Change the name and recompute the matching keys
+    pub fn rename(&mut self, full_name: String) {
+        self.normalized_name = normalize_name(&full_name);
+        self.phonetic_key = phonetic_key(&self.normalized_name);
+        self.full_name = full_name;
+        self.touch();
+    }
+
+    /// Check if this record was merged into another
+    pub fn is_merged(&self) -> bool {
+        self.merged_into.is_some()
+    }
+
+    /// Effective classification of a field for this party
+    pub fn field_class(&self, field: PiiField) -> PrivacyClass {
+        field.classification().max(self.privacy_class)
+    }
+
+    /// Copy with every field above `clearance` masked
+    pub fn redacted(&self, clearance: PrivacyClass) -> Self {
+        let visible = |field: PiiField| self.field_class(field) <= clearance;
+        let mut party = self.clone();
+
+        if !visible(PiiField::FullName) {
+            party.full_name = REDACTED.to_string();
+            party.normalized_name = String::new();
+            party.phonetic_key = String::new();
+        }
+        if !visible(PiiField::DateOfBirth) {
+            party.date_of_birth = None;
+        }
+        if !visible(PiiField::LicenceNumber) {
+            party.licence_number = party.licence_number.as_ref().map(|_| REDACTED.to_string());
+        }
+        if !visible(PiiField::Phone) {
+            party.phone = party.phone.as_ref().map(|_| REDACTED.to_string());
+        }
+        if !visible(PiiField::Address) {
+            party.address = party.address.as_ref().map(|_| REDACTED.to_string());
+        }
+
+        party
+    }
+}
+
+impl Identifiable for Party {
+    type Id = String;
+
+    fn id(&self) -> &Self::Id {
+        &self.id
+    }
+
+    fn set_id(&mut self, id: Self::Id) {
+        self.id = id;
+        self.touch();
+    }
+
+    fn with_new_id(mut self) -> Self {
+        self.id = Uuid::new_v4().to_string();
+        self
+    }
+}
+
+impl Timestamped for Party {
+    fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+
+    fn touch(&mut self) {
+        self.updated_at = Utc::now();
+    }
+}
+
+impl Validatable for Party {
+    fn validate(&self) -> Result<()> {
+        if self.normalized_name.is_empty() {
+            return Err(AccuSceneError::validation_field(
+                "Party name cannot be empty",
+                "full_name",
+            ));
+        }
+
+        if let Some(dob) = self.date_of_birth {
+            if dob > Utc::now().date_naive() {
+                return Err(AccuSceneError::validation_field(
+                    "Date of birth cannot be in the future",
+                    "date_of_birth",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Mergeable for Party {
+    /// Fill missing details from a duplicate; conflicting details are kept as-is
+    fn merge(&mut self, other: &Self) -> Result<()> {
+        if !self.can_merge(other) {
+            return Err(AccuSceneError::validation(format!(
+                "Party {} cannot be merged into {}",
+                other.id, self.id
+            )));
+        }
+
+        self.date_of_birth = self.date_of_birth.or(other.date_of_birth);
+        self.licence_number = self.licence_number.take().or_else(|| other.licence_number.clone());
+        self.phone = self.phone.take().or_else(|| other.phone.clone());
+        self.address = self.address.take().or_else(|| other.address.clone());
+        self.privacy_class = self.privacy_class.max(other.privacy_class);
+        self.touch();
+        Ok(())
+    }
+
+    fn can_merge(&self, other: &Self) -> bool {
+        self.id != other.id && !self.is_merged() && !other.is_merged()
+    }
+}
+
+impl Serializable for Party {}
+
+/// Link from a party to a case, and optionally to a piece of evidence
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PartyLink {
+    /// Link identifier
+    pub id: String,
+    /// Party ID
+    pub party_id: String,
+    /// Case ID
+    pub case_id: String,
+    /// Evidence ID (e.g. a witness statement)
+    pub evidence_id: Option<String>,
+    /// Role in the case
+    pub role: PartyRole,
+    /// Creation timestamp
+    pub created_at: DateTime<Utc>,
+}
+
+impl PartyLink {
+    /// Link a party to a case
+    pub fn new(party_id: String, case_id: String, role: PartyRole) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            party_id,
+            case_id,
+            evidence_id: None,
+            role,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Attach the link to a piece of evidence
+    pub fn with_evidence(mut self, evidence_id: String) -> Self {
+        self.evidence_id = Some(evidence_id);
+        self
+    }
+}
+
+/// Record of a merge, preserving the duplicate as it was before merging
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartyMerge {
+    /// Merge identifier
+    pub id: String,
+    /// Surviving party
+    pub survivor_id: String,
+    /// Party merged into the survivor
+    pub merged_id: String,
+    /// User who performed the merge
+    pub merged_by: String,
+    /// Reason given for the merge
+    pub reason: Option<String>,
+    /// Snapshot of the merged party before the merge
+    pub snapshot: Party,
+    /// Merge timestamp
+    pub merged_at: DateTime<Utc>,
+}
+
+impl PartyMerge {
+    /// Record merging `merged` into `survivor_id`
+    pub fn new(survivor_id: String, merged: &Party, merged_by: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            survivor_id,
+            merged_id: merged.id.clone(),
+            merged_by,
+            reason: None,
+            snapshot: merged.clone(),
+            merged_at: Utc::now(),
+        }
+    }
+
+    /// Set the reason
+    pub fn with_reason(mut self, reason: String) -> Self {
+        self.reason = Some(reason);
+        self
+    }
+}
+
+/// Score of a candidate match
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchScore {
+    /// Overall score in `[0, 1]`
+    pub score: f64,
+    /// Name similarity from edit distance in `[0, 1]`
+    pub name_similarity: f64,
+    /// Whether the phonetic keys agree
+    pub phonetic_match: bool,
+    /// Date of birth agreement, if both are known
+    pub dob_match: Option<bool>,
+    /// Licence agreement, if both are known
+    pub licence_match: Option<bool>,
+}
+
+/// Fuzzy matcher for party deduplication
+#[derive(Debug, Clone)]
+pub struct PartyMatcher {
+    /// Minimum score to report a candidate
+    pub threshold: f64,
+}
+
+impl PartyMatcher {
+    /// Create a matcher with a custom threshold
+    pub fn new(threshold: f64) -> Self {
+        Self { threshold }
+    }
+
+    /// Score how likely two parties are the same person
+    ///
+    /// Names contribute edit-distance similarity with a bonus for phonetic
+    /// agreement. A matching licence is near-conclusive; a conflicting date
+    /// of birth or licence rules the match out regardless of the name.
+    pub fn score(&self, a: &Party, b: &Party) -> MatchScore {
+        let name_similarity = similarity(&a.normalized_name, &b.normalized_name);
+        let phonetic_match = !a.phonetic_key.is_empty() && a.phonetic_key == b.phonetic_key;
+
+        let dob_match = match (a.date_of_birth, b.date_of_birth) {
+            (Some(x), Some(y)) => Some(x == y),
+            _ => None,
+        };
+        let licence_match = match (&a.licence_number, &b.licence_number) {
+            (Some(x), Some(y)) => Some(x == y),
+            _ => None,
+        };
+
+        let mut score = 0.8 * name_similarity + if phonetic_match { 0.2 } else { 0.0 };
+        match dob_match {
+            Some(true) => score = (score + 0.15).min(1.0),
+            Some(false) => score *= 0.3,
+            None => {},
+        }
+        match licence_match {
+            Some(true) => score = score.max(0.95),
+            Some(false) => score *= 0.3,
+            None => {},
+        }
+
+        MatchScore {
+            score,
+            name_similarity,
+            phonetic_match,
+            dob_match,
+            licence_match,
+        }
+    }
+
+    /// Rank existing parties against a candidate, best first
+    pub fn find_matches<'a>(
+        &self,
+        candidate: &Party,
+        parties: &'a [Party],
+    ) -> Vec<(&'a Party, MatchScore)> {
+        let mut matches: Vec<_> = parties
+            .iter()
+            .filter(|p| p.id != candidate.id && !p.is_merged())
+            .map(|p| (p, self.score(candidate, p)))
+            .filter(|(_, s)| s.score >= self.threshold)
+            .collect();
+
+        matches
+            .sort_by(|a, b| b.1.score.partial_cmp(&a.1.score).unwrap_or(std::cmp::Ordering::Equal));
+        matches
+    }
+}
+
+impl Default for PartyMatcher {
+    fn default() -> Self {
+        Self::new(0.75)
+    }
+}
+
+/// Normalize a name: lowercase ASCII letters, no punctuation, tokens sorted
+///
+/// Sorting the tokens makes "Doe, Jane" and "Jane Doe" compare equal.
+pub fn normalize_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'À' | 'Á' | 'Â' | 'Ã' | 'Ä' | 'Å' => {
+                'a'
+            },
+            'è' | 'é' | 'ê' | 'ë' | 'È' | 'É' | 'Ê' | 'Ë' => 'e',
+            'ì' | 'í' | 'î' | 'ï' | 'Ì' | 'Í' | 'Î' | 'Ï' => 'i',
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ö' => 'o',
+            'ù' | 'ú' | 'û' | 'ü' | 'Ù' | 'Ú' | 'Û' | 'Ü' => 'u',
+            'ñ' | 'Ñ' => 'n',
+            'ç' | 'Ç' => 'c',
+            c if c.is_ascii_alphabetic() => c.to_ascii_lowercase(),
+            '\'' => '\0',
+            _ => ' ',
+        })
+        .filter(|c| *c != '\0')
+        .collect();
+
+    let mut tokens: Vec<&str> = cleaned.split_whitespace().collect();
+    tokens.sort_unstable();
+    tokens.join(" ")
+}
+
+/// Normalize a licence number: uppercase alphanumerics only
+pub fn normalize_licence(licence: &str) -> String {
+    licence
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Phonetic key of a normalized name: the Soundex code of each token
+pub fn phonetic_key(normalized_name: &str) -> String {
+    normalized_name.split_whitespace().map(soundex).collect::<Vec<_>>().join(" ")
+}
+
+/// American Soundex code of a single word
+pub fn soundex(word: &str) -> String {
+    fn code(c: char) -> Option<char> {
+        match c {
+            'b' | 'f' | 'p' | 'v' => Some('1'),
+            'c' | 'g' | 'j' | 'k' | 'q' | 's' | 'x' | 'z' => Some('2'),
+            'd' | 't' => Some('3'),
+            'l' => Some('4'),
+            'm' | 'n' => Some('5'),
+            'r' => Some('6'),
+            _ => None,
+        }
+    }
+
+    let letters: Vec<char> = word
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    let Some(&first) = letters.first() else {
+        return String::new();
+    };
+
+    let mut result = String::from(first.to_ascii_uppercase());
+    let mut last = code(first);
+    for &c in &letters[1..] {
+        let current = code(c);
+        if let Some(digit) = current.filter(|_| current != last) {
+            result.push(digit);
+            if result.len() == 4 {
+                break;
+            }
+        }
+        // 'h' and 'w' do not separate equal codes; vowels do
+        if c != 'h' && c != 'w' {
+            last = current;
+        }
+    }
+
+    while result.len() < 4 {
+        result.push('0');
+    }
+    result
+}
+
+/// Levenshtein edit distance between two strings
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+/// Edit-distance similarity in `[0, 1]`
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 0.0;
+    }
+    1.0 - edit_distance(a, b) as f64 / longest as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("Doe, Jane"), "doe jane");
+        assert_eq!(normalize_name("  JANE   doe "), "doe jane");
+        assert_eq!(normalize_name("Zoë O'Brien"), "obrien zoe");
+    }
+
+    #[test]
+    fn test_soundex() {
+        assert_eq!(soundex("robert"), "R163");
+        assert_eq!(soundex("rupert"), "R163");
+        assert_eq!(soundex("ashcraft"), "A261");
+        assert_eq!(soundex("tymczak"), "T522");
+        assert_eq!(soundex("smith"), soundex("smyth"));
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+
+    #[test]
+    fn test_matcher_tolerates_typos() {
+        let matcher = PartyMatcher::default();
+        let dob = NaiveDate::from_ymd_opt(1985, 4, 12).unwrap();
+        let a = Party::new("John Smith".to_string()).with_date_of_birth(dob);
+        let b = Party::new("Jon Smyth".to_string()).with_date_of_birth(dob);
+
+        let score = matcher.score(&a, &b);
+        assert!(score.phonetic_match);
+        assert_eq!(score.dob_match, Some(true));
+        assert!(score.score >= matcher.threshold);
+    }
+
+    #[test]
+    fn test_conflicting_dob_rules_out_match() {
+        let matcher = PartyMatcher::default();
+        let a = Party::new("John Smith".to_string())
+            .with_date_of_birth(NaiveDate::from_ymd_opt(1985, 4, 12).unwrap());
+        let b = Party::new("John Smith".to_string())
+            .with_date_of_birth(NaiveDate::from_ymd_opt(1962, 1, 3).unwrap());
+
+        assert!(matcher.score(&a, &b).score < matcher.threshold);
+        assert!(matcher.find_matches(&a, &[b]).is_empty());
+    }
+
+    #[test]
+    fn test_licence_corroborates_different_spelling() {
+        let matcher = PartyMatcher::default();
+        let a = Party::new("Katherine Nguyen".to_string()).with_licence_number("d123-456-789");
+        let b = Party::new("Kathy Nguyen".to_string()).with_licence_number("D123456789");
+
+        assert_eq!(matcher.score(&a, &b).licence_match, Some(true));
+        assert!(matcher.score(&a, &b).score >= 0.95);
+    }
+
+    #[test]
+    fn test_redaction_by_clearance() {
+        let party = Party::new("Jane Doe".to_string())
+            .with_date_of_birth(NaiveDate::from_ymd_opt(1990, 1, 1).unwrap())
+            .with_phone("555-0100".to_string());
+
+        let internal = party.redacted(PrivacyClass::Internal);
+        assert_eq!(internal.full_name, "Jane Doe");
+        assert_eq!(internal.phone.as_deref(), Some(REDACTED));
+        assert!(internal.date_of_birth.is_none());
+
+        let minor = party.clone().with_privacy_class(PrivacyClass::Restricted);
+        assert_eq!(
+            minor.redacted(PrivacyClass::Confidential).full_name,
+            REDACTED
+        );
+        assert_eq!(minor.redacted(PrivacyClass::Restricted), minor);
+    }
+
+    #[test]
+    fn test_merge_fills_missing_details() {
+        let mut survivor = Party::new("Jane Doe".to_string());
+        let duplicate = Party::new("Jane Do".to_string())
+            .with_licence_number("X1")
+            .with_privacy_class(PrivacyClass::Confidential);
+
+        survivor.merge(&duplicate).unwrap();
+        assert_eq!(survivor.licence_number.as_deref(), Some("X1"));
+        assert_eq!(survivor.privacy_class, PrivacyClass::Confidential);
+        assert!(survivor.merge(&survivor.clone()).is_err());
+    }
+}
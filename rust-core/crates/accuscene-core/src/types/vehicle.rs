@@ -207,8 +207,8 @@ impl Vehicle {
     /// Calculate rotational kinetic energy
     pub fn rotational_energy(&self) -> f64 {
         // Using simplified moment of inertia for a rectangular box
-        let moment = (self.mass_kg / 12.0)
-            * (self.length_m * self.length_m + self.width_m * self.width_m);
+        let moment =
+            (self.mass_kg / 12.0) * (self.length_m * self.length_m + self.width_m * self.width_m);
         0.5 * moment * self.angular_velocity * self.angular_velocity
     }
 
@@ -399,25 +399,10 @@ impl MemoryFootprint for Vehicle {
         std::mem::size_of::<Self>()
             + self.metadata.make.capacity()
             + self.metadata.model.capacity()
-            + self
-                .metadata
-                .color
-                .as_ref()
-                .map(|s| s.capacity())
-                .unwrap_or(0)
-            + self
-                .metadata
-                .license_plate
-                .as_ref()
-                .map(|s| s.capacity())
-                .unwrap_or(0)
+            + self.metadata.color.as_ref().map(|s| s.capacity()).unwrap_or(0)
+            + self.metadata.license_plate.as_ref().map(|s| s.capacity()).unwrap_or(0)
             + self.metadata.vin.as_ref().map(|s| s.capacity()).unwrap_or(0)
-            + self
-                .metadata
-                .notes
-                .as_ref()
-                .map(|s| s.capacity())
-                .unwrap_or(0)
+            + self.metadata.notes.as_ref().map(|s| s.capacity()).unwrap_or(0)
     }
 }
 
@@ -1,6 +1,11 @@
 //! Simple certificate handling
 //!
 //! Provides a lightweight certificate system for public key distribution and verification.
+//! Certificates can be exported as X.509 for external tooling (see [`x509`]).
+
+pub mod x509;
+
+pub use self::x509::{KeyUsage, X509Certificate};
 
 use crate::asymmetric::signing::{verify_signature, Ed25519Signer, Signature};
 use crate::asymmetric::{Ed25519KeyPair, Ed25519PublicKey};
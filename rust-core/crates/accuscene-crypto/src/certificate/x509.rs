@@ -0,0 +1,1030 @@
+//! X.509 encoding for certificates
+//!
+//! Encodes and parses DER/PEM X.509 v3 certificates with Ed25519 keys
+//! (RFC 5280, RFC 8410) so certificates issued by a
+//! [`CertificateAuthority`](super::CertificateAuthority) can be consumed by
+//! browsers and OpenSSL-based verifiers.
+//!
+//! Supported extensions:
+//! - Basic constraints (CA flag and path length)
+//! - Key usage (digital signature, certificate signing, CRL signing)
+//! - Subject and authority key identifiers
+//! - Subject alternative name (DNS name or email address)
+//!
+//! Unknown non-critical extensions are ignored when parsing; unknown
+//! critical extensions are rejected.
+
+use super::{Certificate, CertificateAuthority};
+use crate::asymmetric::signing::{sign_message, verify_signature, Signature};
+use crate::asymmetric::{Ed25519KeyPair, Ed25519PublicKey};
+use crate::error::{CryptoError, CryptoResult};
+use crate::hash::sha256;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// PEM label for certificates
+const PEM_LABEL: &str = "CERTIFICATE";
+
+/// Maximum number of certificates in a chain
+const MAX_CHAIN_DEPTH: usize = 8;
+
+// DER tags
+const TAG_BOOLEAN: u8 = 0x01;
+const TAG_INTEGER: u8 = 0x02;
+const TAG_BIT_STRING: u8 = 0x03;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_OID: u8 = 0x06;
+const TAG_UTF8_STRING: u8 = 0x0c;
+const TAG_PRINTABLE_STRING: u8 = 0x13;
+const TAG_UTC_TIME: u8 = 0x17;
+const TAG_GENERALIZED_TIME: u8 = 0x18;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_SET: u8 = 0x31;
+const TAG_VERSION: u8 = 0xa0;
+const TAG_EXTENSIONS: u8 = 0xa3;
+const TAG_KEY_IDENTIFIER: u8 = 0x80;
+const TAG_RFC822_NAME: u8 = 0x81;
+const TAG_DNS_NAME: u8 = 0x82;
+
+// Object identifiers (encoded content)
+const OID_ED25519: &[u8] = &[0x2b, 0x65, 0x70];
+const OID_COMMON_NAME: &[u8] = &[0x55, 0x04, 0x03];
+const OID_SUBJECT_KEY_ID: &[u8] = &[0x55, 0x1d, 0x0e];
+const OID_KEY_USAGE: &[u8] = &[0x55, 0x1d, 0x0f];
+const OID_SUBJECT_ALT_NAME: &[u8] = &[0x55, 0x1d, 0x11];
+const OID_BASIC_CONSTRAINTS: &[u8] = &[0x55, 0x1d, 0x13];
+const OID_AUTHORITY_KEY_ID: &[u8] = &[0x55, 0x1d, 0x23];
+
+/// Latest time representable in a certificate (9999-12-31 23:59:59 UTC)
+const MAX_TIME: u64 = 253_402_300_799;
+
+/// Key usage flags of a certificate
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct KeyUsage {
+    /// Key may verify signatures other than on certificates and CRLs
+    pub digital_signature: bool,
+    /// Key may verify signatures on certificates
+    pub key_cert_sign: bool,
+    /// Key may verify signatures on CRLs
+    pub crl_sign: bool,
+}
+
+impl KeyUsage {
+    /// Key usage of an end-entity certificate
+    pub fn end_entity() -> Self {
+        Self {
+            digital_signature: true,
+            ..Default::default()
+        }
+    }
+
+    /// Key usage of a CA certificate
+    pub fn certificate_authority() -> Self {
+        Self {
+            key_cert_sign: true,
+            crl_sign: true,
+            ..Default::default()
+        }
+    }
+
+    fn to_bits(self) -> u8 {
+        (u8::from(self.digital_signature) << 7)
+            | (u8::from(self.key_cert_sign) << 2)
+            | (u8::from(self.crl_sign) << 1)
+    }
+
+    fn from_bits(bits: u8) -> Self {
+        Self {
+            digital_signature: bits & 0x80 != 0,
+            key_cert_sign: bits & 0x04 != 0,
+            crl_sign: bits & 0x02 != 0,
+        }
+    }
+}
+
+impl CertificateAuthority {
+    /// Create a self-signed X.509 root certificate for this CA
+    pub fn x509_root(&self, validity: Duration) -> CryptoResult<X509Certificate> {
+        let now = unix_now();
+        TbsCertificate {
+            serial: random_serial(),
+            issuer: self.name.clone(),
+            subject: self.name.clone(),
+            not_before: now,
+            not_after: now.saturating_add(validity.as_secs()),
+            public_key: self.keypair.public_key().clone(),
+            ca: Some(None),
+            key_usage: KeyUsage::certificate_authority(),
+        }
+        .sign(&self.keypair)
+    }
+
+    /// Export a certificate issued by this CA as an X.509 end-entity
+    /// certificate
+    ///
+    /// The certificate keeps its subject, key, validity period and serial
+    /// number.
+    pub fn export_x509(&self, cert: &Certificate) -> CryptoResult<X509Certificate> {
+        if cert.issuer != self.name {
+            return Err(CryptoError::InvalidCertificate(
+                "Certificate not issued by this CA".to_string(),
+            ));
+        }
+        if !cert.verify_signature(self.keypair.public_key())? {
+            return Err(CryptoError::InvalidSignature);
+        }
+
+        TbsCertificate {
+            serial: serial_from_str(&cert.metadata.serial_number),
+            issuer: self.name.clone(),
+            subject: cert.subject.clone(),
+            not_before: cert.issued_at,
+            not_after: cert.expires_at,
+            public_key: cert.public_key.clone(),
+            ca: None,
+            key_usage: KeyUsage::end_entity(),
+        }
+        .sign(&self.keypair)
+    }
+
+    /// Issue an X.509 certificate for a subordinate (intermediate) CA
+    ///
+    /// `path_len` limits the number of further intermediates below the
+    /// subordinate.
+    pub fn issue_x509_ca(
+        &self,
+        subordinate: &CertificateAuthority,
+        validity: Duration,
+        path_len: Option<u8>,
+    ) -> CryptoResult<X509Certificate> {
+        let now = unix_now();
+        TbsCertificate {
+            serial: random_serial(),
+            issuer: self.name.clone(),
+            subject: subordinate.name.clone(),
+            not_before: now,
+            not_after: now.saturating_add(validity.as_secs()),
+            public_key: subordinate.keypair.public_key().clone(),
+            ca: Some(path_len),
+            key_usage: KeyUsage::certificate_authority(),
+        }
+        .sign(&self.keypair)
+    }
+}
+
+/// Fields of a certificate to be signed
+struct TbsCertificate {
+    serial: Vec<u8>,
+    issuer: String,
+    subject: String,
+    not_before: u64,
+    not_after: u64,
+    public_key: Ed25519PublicKey,
+    /// CA path length constraint, or `None` for end-entity certificates
+    ca: Option<Option<u8>>,
+    key_usage: KeyUsage,
+}
+
+impl TbsCertificate {
+    /// Sign the certificate with the issuer's key pair
+    fn sign(self, issuer: &Ed25519KeyPair) -> CryptoResult<X509Certificate> {
+        let tbs = self.encode(issuer.public_key());
+        let signature = sign_message(issuer, &tbs)?;
+        let der = sequence(&[
+            tbs,
+            algorithm_identifier(),
+            bit_string(signature.as_bytes()),
+        ]);
+        X509Certificate::from_der(&der)
+    }
+
+    fn encode(&self, issuer_key: &Ed25519PublicKey) -> Vec<u8> {
+        let subject_key_id = key_identifier(&self.public_key);
+        let mut extensions = vec![
+            extension(OID_BASIC_CONSTRAINTS, true, &basic_constraints(self.ca)),
+            extension(OID_KEY_USAGE, true, &key_usage(self.key_usage)),
+            extension(
+                OID_SUBJECT_KEY_ID,
+                false,
+                &tlv(TAG_OCTET_STRING, &subject_key_id),
+            ),
+            extension(
+                OID_AUTHORITY_KEY_ID,
+                false,
+                &sequence(&[tlv(TAG_KEY_IDENTIFIER, &key_identifier(issuer_key))]),
+            ),
+        ];
+        if let Some(name) = alternative_name(&self.subject) {
+            extensions.push(extension(OID_SUBJECT_ALT_NAME, false, &sequence(&[name])));
+        }
+
+        sequence(&[
+            tlv(TAG_VERSION, &integer(&[2])),
+            integer(&self.serial),
+            algorithm_identifier(),
+            name(&self.issuer),
+            sequence(&[time(self.not_before), time(self.not_after)]),
+            name(&self.subject),
+            sequence(&[
+                algorithm_identifier(),
+                bit_string(self.public_key.as_bytes()),
+            ]),
+            tlv(TAG_EXTENSIONS, &sequence(&extensions)),
+        ])
+    }
+}
+
+/// A DER-encoded X.509 v3 certificate with an Ed25519 key
+#[derive(Clone, Debug)]
+pub struct X509Certificate {
+    der: Vec<u8>,
+    tbs_range: std::ops::Range<usize>,
+    serial: Vec<u8>,
+    issuer: String,
+    subject: String,
+    not_before: u64,
+    not_after: u64,
+    public_key: Ed25519PublicKey,
+    ca: Option<Option<u8>>,
+    key_usage: Option<KeyUsage>,
+    subject_key_id: Option<Vec<u8>>,
+    authority_key_id: Option<Vec<u8>>,
+    signature: Signature,
+}
+
+impl X509Certificate {
+    /// Parse a DER-encoded certificate
+    pub fn from_der(der: &[u8]) -> CryptoResult<Self> {
+        let mut outer = DerReader::new(der);
+        let certificate = outer.read(TAG_SEQUENCE)?;
+        outer.finish()?;
+
+        let mut reader = DerReader::new(certificate);
+        let tbs_start = der.len() - certificate.len();
+        let tbs_raw = reader.read_raw(TAG_SEQUENCE)?;
+        let tbs_range = tbs_start..tbs_start + tbs_raw.len();
+        expect_ed25519(reader.read(TAG_SEQUENCE)?)?;
+        let signature = Signature::from_slice(bit_string_content(reader.read(TAG_BIT_STRING)?)?)?;
+        reader.finish()?;
+
+        let mut tbs = DerReader::new(DerReader::new(tbs_raw).read(TAG_SEQUENCE)?);
+        let version = DerReader::new(tbs.read(TAG_VERSION)?).read(TAG_INTEGER)?;
+        if version != [2] {
+            return Err(invalid("only X.509 v3 certificates are supported"));
+        }
+        let serial = tbs.read(TAG_INTEGER)?.to_vec();
+        expect_ed25519(tbs.read(TAG_SEQUENCE)?)?;
+        let issuer = parse_name(tbs.read(TAG_SEQUENCE)?)?;
+
+        let mut validity = DerReader::new(tbs.read(TAG_SEQUENCE)?);
+        let not_before = parse_time(&mut validity)?;
+        let not_after = parse_time(&mut validity)?;
+        validity.finish()?;
+
+        let subject = parse_name(tbs.read(TAG_SEQUENCE)?)?;
+        let mut spki = DerReader::new(tbs.read(TAG_SEQUENCE)?);
+        expect_ed25519(spki.read(TAG_SEQUENCE)?)?;
+        let public_key =
+            Ed25519PublicKey::from_slice(bit_string_content(spki.read(TAG_BIT_STRING)?)?)?;
+        spki.finish()?;
+
+        let mut parsed = Self {
+            der: der.to_vec(),
+            tbs_range,
+            serial,
+            issuer,
+            subject,
+            not_before,
+            not_after,
+            public_key,
+            ca: None,
+            key_usage: None,
+            subject_key_id: None,
+            authority_key_id: None,
+            signature,
+        };
+
+        if !tbs.is_empty() {
+            let extensions = DerReader::new(tbs.read(TAG_EXTENSIONS)?).read(TAG_SEQUENCE)?;
+            parsed.parse_extensions(extensions)?;
+        }
+        tbs.finish()?;
+
+        Ok(parsed)
+    }
+
+    /// Parse a PEM-encoded certificate
+    pub fn from_pem(pem: &str) -> CryptoResult<Self> {
+        let begin = format!("-----BEGIN {}-----", PEM_LABEL);
+        let end = format!("-----END {}-----", PEM_LABEL);
+
+        let start = pem
+            .find(&begin)
+            .ok_or_else(|| CryptoError::DecodingError("missing PEM header".to_string()))?
+            + begin.len();
+        let stop = pem[start..]
+            .find(&end)
+            .ok_or_else(|| CryptoError::DecodingError("missing PEM footer".to_string()))?
+            + start;
+
+        let body: String = pem[start..stop].split_whitespace().collect();
+        let der = STANDARD.decode(body)?;
+        Self::from_der(&der)
+    }
+
+    /// DER encoding
+    pub fn to_der(&self) -> &[u8] {
+        &self.der
+    }
+
+    /// PEM encoding
+    pub fn to_pem(&self) -> String {
+        let encoded = STANDARD.encode(&self.der);
+        let mut pem = format!("-----BEGIN {}-----\n", PEM_LABEL);
+        for line in encoded.as_bytes().chunks(64) {
+            pem.push_str(std::str::from_utf8(line).unwrap_or_default());
+            pem.push('\n');
+        }
+        pem.push_str(&format!("-----END {}-----\n", PEM_LABEL));
+        pem
+    }
+
+    /// Serial number as lowercase hex
+    pub fn serial_number(&self) -> String {
+        hex::encode(&self.serial)
+    }
+
+    /// Issuer common name
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    /// Subject common name
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// Start of validity (Unix timestamp)
+    pub fn not_before(&self) -> u64 {
+        self.not_before
+    }
+
+    /// End of validity (Unix timestamp)
+    pub fn not_after(&self) -> u64 {
+        self.not_after
+    }
+
+    /// Subject public key
+    pub fn public_key(&self) -> &Ed25519PublicKey {
+        &self.public_key
+    }
+
+    /// Whether the certificate belongs to a CA
+    pub fn is_ca(&self) -> bool {
+        self.ca.is_some()
+    }
+
+    /// Maximum number of intermediate CAs below this CA
+    pub fn path_len(&self) -> Option<u8> {
+        self.ca.flatten()
+    }
+
+    /// Key usage, if the extension is present
+    pub fn key_usage(&self) -> Option<KeyUsage> {
+        self.key_usage
+    }
+
+    /// Subject key identifier, if the extension is present
+    pub fn subject_key_id(&self) -> Option<&[u8]> {
+        self.subject_key_id.as_deref()
+    }
+
+    /// Authority key identifier, if the extension is present
+    pub fn authority_key_id(&self) -> Option<&[u8]> {
+        self.authority_key_id.as_deref()
+    }
+
+    /// Whether the certificate is valid at the given Unix timestamp
+    pub fn is_valid_at(&self, timestamp: u64) -> bool {
+        self.not_before <= timestamp && timestamp <= self.not_after
+    }
+
+    /// Whether the certificate names itself as issuer
+    pub fn is_self_issued(&self) -> bool {
+        self.issuer == self.subject
+    }
+
+    /// Verify the certificate signature with the issuer's public key
+    pub fn verify_signature(&self, issuer_public_key: &Ed25519PublicKey) -> CryptoResult<bool> {
+        verify_signature(
+            issuer_public_key,
+            &self.der[self.tbs_range.clone()],
+            &self.signature,
+        )
+    }
+
+    /// Verify that `issuer` issued this certificate and may issue certificates
+    pub fn verify_issued_by(&self, issuer: &X509Certificate) -> CryptoResult<()> {
+        if self.issuer != issuer.subject {
+            return Err(CryptoError::InvalidCertificate(format!(
+                "certificate for '{}' was not issued by '{}'",
+                self.subject, issuer.subject
+            )));
+        }
+        if let (Some(authority), Some(subject)) = (&self.authority_key_id, &issuer.subject_key_id) {
+            if authority != subject {
+                return Err(CryptoError::InvalidCertificate(
+                    "authority key identifier does not match issuer".to_string(),
+                ));
+            }
+        }
+        if !issuer.is_ca() || issuer.key_usage.is_some_and(|usage| !usage.key_cert_sign) {
+            return Err(CryptoError::InvalidCertificate(format!(
+                "'{}' is not allowed to issue certificates",
+                issuer.subject
+            )));
+        }
+        if !self.verify_signature(&issuer.public_key)? {
+            return Err(CryptoError::InvalidSignature);
+        }
+        Ok(())
+    }
+
+    /// Validate the chain from this certificate to a trusted root at the
+    /// given Unix timestamp
+    ///
+    /// `intermediates` may be in any order. Checks names, key identifiers,
+    /// signatures, validity periods, CA flags, key usage and path length
+    /// constraints.
+    pub fn verify_chain(
+        &self,
+        intermediates: &[X509Certificate],
+        root: &X509Certificate,
+        timestamp: u64,
+    ) -> CryptoResult<()> {
+        if !root.is_valid_at(timestamp) || !self.is_valid_at(timestamp) {
+            return Err(CryptoError::CertificateExpired);
+        }
+
+        let mut current = self;
+        for intermediate_count in 0..MAX_CHAIN_DEPTH {
+            if current.issuer == root.subject {
+                current.verify_issued_by(root)?;
+                check_path_len(root, intermediate_count)?;
+                return Ok(());
+            }
+
+            let issuer = intermediates
+                .iter()
+                .find(|candidate| {
+                    candidate.subject == current.issuer && !std::ptr::eq(*candidate, current)
+                })
+                .ok_or_else(|| {
+                    CryptoError::InvalidCertificate(format!(
+                        "no issuer found for '{}'",
+                        current.subject
+                    ))
+                })?;
+            if !issuer.is_valid_at(timestamp) {
+                return Err(CryptoError::CertificateExpired);
+            }
+            current.verify_issued_by(issuer)?;
+            check_path_len(issuer, intermediate_count)?;
+            current = issuer;
+        }
+
+        Err(CryptoError::InvalidCertificate(
+            "certificate chain too long".to_string(),
+        ))
+    }
+
+    fn parse_extensions(&mut self, extensions: &[u8]) -> CryptoResult<()> {
+        let mut reader = DerReader::new(extensions);
+        while !reader.is_empty() {
+            let mut extension = DerReader::new(reader.read(TAG_SEQUENCE)?);
+            let oid = extension.read(TAG_OID)?;
+            let critical = if extension.peek() == Some(TAG_BOOLEAN) {
+                extension.read(TAG_BOOLEAN)? == [0xff]
+            } else {
+                false
+            };
+            let value = extension.read(TAG_OCTET_STRING)?;
+            extension.finish()?;
+
+            match oid {
+                OID_BASIC_CONSTRAINTS => {
+                    let mut constraints = DerReader::new(DerReader::new(value).read(TAG_SEQUENCE)?);
+                    let is_ca = constraints.peek() == Some(TAG_BOOLEAN)
+                        && constraints.read(TAG_BOOLEAN)? == [0xff];
+                    let path_len = if constraints.peek() == Some(TAG_INTEGER) {
+                        match constraints.read(TAG_INTEGER)? {
+                            [len] if *len < 0x80 => Some(*len),
+                            _ => return Err(invalid("unsupported path length constraint")),
+                        }
+                    } else {
+                        None
+                    };
+                    constraints.finish()?;
+                    self.ca = is_ca.then_some(path_len);
+                },
+                OID_KEY_USAGE => {
+                    let bits = bit_string_bytes(DerReader::new(value).read(TAG_BIT_STRING)?)?;
+                    self.key_usage = Some(KeyUsage::from_bits(bits.first().copied().unwrap_or(0)));
+                },
+                OID_SUBJECT_KEY_ID => {
+                    self.subject_key_id =
+                        Some(DerReader::new(value).read(TAG_OCTET_STRING)?.to_vec());
+                },
+                OID_AUTHORITY_KEY_ID => {
+                    let mut identifier = DerReader::new(DerReader::new(value).read(TAG_SEQUENCE)?);
+                    if identifier.peek() == Some(TAG_KEY_IDENTIFIER) {
+                        self.authority_key_id = Some(identifier.read(TAG_KEY_IDENTIFIER)?.to_vec());
+                    }
+                },
+                OID_SUBJECT_ALT_NAME => {},
+                _ if critical => {
+                    return Err(invalid("unsupported critical extension"));
+                },
+                _ => {},
+            }
+        }
+        Ok(())
+    }
+}
+
+fn check_path_len(issuer: &X509Certificate, intermediates_below: usize) -> CryptoResult<()> {
+    match issuer.path_len() {
+        Some(max) if intermediates_below > max as usize => Err(CryptoError::InvalidCertificate(
+            format!("path length constraint of '{}' exceeded", issuer.subject),
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn invalid(message: &str) -> CryptoError {
+    CryptoError::InvalidCertificate(message.to_string())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+fn random_serial() -> Vec<u8> {
+    uuid::Uuid::new_v4().as_bytes().to_vec()
+}
+
+/// Serial number bytes of a UUID, or of a hash of any other serial string
+fn serial_from_str(serial: &str) -> Vec<u8> {
+    match uuid::Uuid::parse_str(serial) {
+        Ok(uuid) => uuid.as_bytes().to_vec(),
+        Err(_) => sha256(serial.as_bytes())[..16].to_vec(),
+    }
+}
+
+/// Key identifier: the first 160 bits of the SHA-256 of the public key
+/// (RFC 7093, method 1)
+fn key_identifier(public_key: &Ed25519PublicKey) -> Vec<u8> {
+    sha256(public_key.as_bytes())[..20].to_vec()
+}
+
+// ---------------------------------------------------------------------------
+// DER encoding
+// ---------------------------------------------------------------------------
+
+fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    let len = content.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let skip = bytes.iter().take_while(|&&b| b == 0).count();
+        out.push(0x80 | (bytes.len() - skip) as u8);
+        out.extend_from_slice(&bytes[skip..]);
+    }
+    out.extend_from_slice(content);
+    out
+}
+
+fn sequence(parts: &[Vec<u8>]) -> Vec<u8> {
+    tlv(TAG_SEQUENCE, &parts.concat())
+}
+
+/// Unsigned big-endian integer in minimal two's complement form
+fn integer(value: &[u8]) -> Vec<u8> {
+    let skip = value.iter().take_while(|&&b| b == 0).count().min(value.len().saturating_sub(1));
+    let trimmed = &value[skip..];
+    if trimmed.first().is_some_and(|&b| b & 0x80 != 0) {
+        tlv(TAG_INTEGER, &[&[0u8][..], trimmed].concat())
+    } else {
+        tlv(TAG_INTEGER, trimmed)
+    }
+}
+
+fn bit_string(bytes: &[u8]) -> Vec<u8> {
+    tlv(TAG_BIT_STRING, &[&[0u8][..], bytes].concat())
+}
+
+fn algorithm_identifier() -> Vec<u8> {
+    sequence(&[tlv(TAG_OID, OID_ED25519)])
+}
+
+fn name(common_name: &str) -> Vec<u8> {
+    let attribute = sequence(&[
+        tlv(TAG_OID, OID_COMMON_NAME),
+        tlv(TAG_UTF8_STRING, common_name.as_bytes()),
+    ]);
+    sequence(&[tlv(TAG_SET, &attribute)])
+}
+
+fn extension(oid: &[u8], critical: bool, value: &[u8]) -> Vec<u8> {
+    let mut parts = vec![tlv(TAG_OID, oid)];
+    if critical {
+        parts.push(tlv(TAG_BOOLEAN, &[0xff]));
+    }
+    parts.push(tlv(TAG_OCTET_STRING, value));
+    sequence(&parts)
+}
+
+fn basic_constraints(ca: Option<Option<u8>>) -> Vec<u8> {
+    match ca {
+        Some(path_len) => {
+            let mut parts = vec![tlv(TAG_BOOLEAN, &[0xff])];
+            if let Some(len) = path_len {
+                parts.push(integer(&[len]));
+            }
+            sequence(&parts)
+        },
+        None => sequence(&[]),
+    }
+}
+
+fn key_usage(usage: KeyUsage) -> Vec<u8> {
+    let bits = usage.to_bits();
+    let unused = if bits == 0 {
+        0
+    } else {
+        bits.trailing_zeros() as u8
+    };
+    tlv(TAG_BIT_STRING, &[unused, bits])
+}
+
+/// Subject alternative name for host names and email addresses
+fn alternative_name(subject: &str) -> Option<Vec<u8>> {
+    let is_host = |s: &str| {
+        !s.is_empty()
+            && s.split('.').all(|label| {
+                !label.is_empty()
+                    && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '*')
+            })
+    };
+
+    match subject.split_once('@') {
+        Some((local, domain)) if !local.is_empty() && is_host(domain) => {
+            Some(tlv(TAG_RFC822_NAME, subject.as_bytes()))
+        },
+        None if subject.contains('.') && is_host(subject) => {
+            Some(tlv(TAG_DNS_NAME, subject.as_bytes()))
+        },
+        _ => None,
+    }
+}
+
+/// UTCTime before 2050, GeneralizedTime from 2050 (RFC 5280 4.1.2.5)
+fn time(timestamp: u64) -> Vec<u8> {
+    let timestamp = timestamp.min(MAX_TIME);
+    let (year, month, day) = civil_from_days((timestamp / 86_400) as i64);
+    let seconds = timestamp % 86_400;
+    let clock = format!(
+        "{:02}{:02}{:02}{:02}{:02}Z",
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+
+    if (1950..2050).contains(&year) {
+        tlv(
+            TAG_UTC_TIME,
+            format!("{:02}{}", year % 100, clock).as_bytes(),
+        )
+    } else {
+        tlv(
+            TAG_GENERALIZED_TIME,
+            format!("{:04}{}", year, clock).as_bytes(),
+        )
+    }
+}
+
+// ---------------------------------------------------------------------------
+// DER decoding
+// ---------------------------------------------------------------------------
+
+struct DerReader<'a> {
+    input: &'a [u8],
+}
+
+impl<'a> DerReader<'a> {
+    fn new(input: &'a [u8]) -> Self {
+        Self { input }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.input.is_empty()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.first().copied()
+    }
+
+    fn finish(&self) -> CryptoResult<()> {
+        if self.input.is_empty() {
+            Ok(())
+        } else {
+            Err(CryptoError::DecodingError(
+                "trailing data in DER value".to_string(),
+            ))
+        }
+    }
+
+    /// Read a value with the expected tag, returning its content
+    fn read(&mut self, tag: u8) -> CryptoResult<&'a [u8]> {
+        let (header, content) = self.split(tag)?;
+        let value = &self.input[header..header + content];
+        self.input = &self.input[header + content..];
+        Ok(value)
+    }
+
+    /// Read a value with the expected tag, returning its full encoding
+    fn read_raw(&mut self, tag: u8) -> CryptoResult<&'a [u8]> {
+        let (header, content) = self.split(tag)?;
+        let raw = &self.input[..header + content];
+        self.input = &self.input[header + content..];
+        Ok(raw)
+    }
+
+    /// Header and content lengths of the next value
+    fn split(&self, tag: u8) -> CryptoResult<(usize, usize)> {
+        let error = |message: &str| CryptoError::DecodingError(message.to_string());
+
+        match self.input.first() {
+            Some(&actual) if actual == tag => {},
+            Some(&actual) => {
+                return Err(CryptoError::DecodingError(format!(
+                    "expected DER tag {:#04x}, found {:#04x}",
+                    tag, actual
+                )))
+            },
+            None => return Err(error("unexpected end of DER data")),
+        }
+
+        let first = *self.input.get(1).ok_or_else(|| error("missing DER length"))?;
+        let (header, content) = if first < 0x80 {
+            (2, first as usize)
+        } else {
+            let count = (first & 0x7f) as usize;
+            if count == 0 || count > 4 {
+                return Err(error("unsupported DER length"));
+            }
+            let bytes =
+                self.input.get(2..2 + count).ok_or_else(|| error("truncated DER length"))?;
+            if bytes[0] == 0 {
+                return Err(error("non-minimal DER length"));
+            }
+            let content = bytes.iter().fold(0usize, |acc, &b| (acc << 8) | b as usize);
+            if content < 0x80 {
+                return Err(error("non-minimal DER length"));
+            }
+            (2 + count, content)
+        };
+
+        if self.input.len() < header + content {
+            return Err(error("truncated DER value"));
+        }
+        Ok((header, content))
+    }
+}
+
+fn expect_ed25519(algorithm: &[u8]) -> CryptoResult<()> {
+    let mut reader = DerReader::new(algorithm);
+    if reader.read(TAG_OID)? != OID_ED25519 || !reader.is_empty() {
+        return Err(invalid("only Ed25519 certificates are supported"));
+    }
+    Ok(())
+}
+
+/// Content of a BIT STRING, allowing unused trailing bits
+fn bit_string_bytes(content: &[u8]) -> CryptoResult<&[u8]> {
+    match content.split_first() {
+        Some((&unused, bytes)) if unused < 8 && (unused == 0 || !bytes.is_empty()) => Ok(bytes),
+        _ => Err(CryptoError::DecodingError(
+            "invalid DER bit string".to_string(),
+        )),
+    }
+}
+
+/// Content of a BIT STRING holding whole bytes
+fn bit_string_content(content: &[u8]) -> CryptoResult<&[u8]> {
+    match content.split_first() {
+        Some((0, bytes)) => Ok(bytes),
+        _ => Err(CryptoError::DecodingError(
+            "invalid DER bit string".to_string(),
+        )),
+    }
+}
+
+/// Common name of a distinguished name
+fn parse_name(name: &[u8]) -> CryptoResult<String> {
+    let mut rdns = DerReader::new(name);
+    while !rdns.is_empty() {
+        let mut set = DerReader::new(rdns.read(TAG_SET)?);
+        while !set.is_empty() {
+            let mut attribute = DerReader::new(set.read(TAG_SEQUENCE)?);
+            let oid = attribute.read(TAG_OID)?;
+            let value = match attribute.peek() {
+                Some(TAG_PRINTABLE_STRING) => attribute.read(TAG_PRINTABLE_STRING)?,
+                _ => attribute.read(TAG_UTF8_STRING)?,
+            };
+            if oid == OID_COMMON_NAME {
+                return String::from_utf8(value.to_vec())
+                    .map_err(|e| CryptoError::DecodingError(e.to_string()));
+            }
+        }
+    }
+    Err(invalid("name has no common name"))
+}
+
+fn parse_time(reader: &mut DerReader<'_>) -> CryptoResult<u64> {
+    let error = || CryptoError::DecodingError("invalid certificate time".to_string());
+
+    let (text, year) = match reader.peek() {
+        Some(TAG_UTC_TIME) => {
+            let text = std::str::from_utf8(reader.read(TAG_UTC_TIME)?).map_err(|_| error())?;
+            let short: i64 = text.get(..2).and_then(|s| s.parse().ok()).ok_or_else(error)?;
+            (
+                &text[2.min(text.len())..],
+                if short < 50 {
+                    2000 + short
+                } else {
+                    1900 + short
+                },
+            )
+        },
+        _ => {
+            let text =
+                std::str::from_utf8(reader.read(TAG_GENERALIZED_TIME)?).map_err(|_| error())?;
+            let year: i64 = text.get(..4).and_then(|s| s.parse().ok()).ok_or_else(error)?;
+            (&text[4.min(text.len())..], year)
+        },
+    };
+
+    if text.len() != 11 || !text.ends_with('Z') || !text[..10].bytes().all(|b| b.is_ascii_digit()) {
+        return Err(error());
+    }
+    let field = |i: usize| -> i64 { text[i..i + 2].parse().unwrap_or(0) };
+    let (month, day) = (field(0), field(2));
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return Err(error());
+    }
+
+    let days = days_from_civil(year, month, day);
+    let seconds = days * 86_400 + field(4) * 3600 + field(6) * 60 + field(8);
+    u64::try_from(seconds).map_err(|_| error())
+}
+
+/// Civil date from days since the Unix epoch
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Days since the Unix epoch from a civil date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::certificate::CertificateMetadata;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn issue_leaf(ca: &CertificateAuthority) -> Certificate {
+        let keypair = Ed25519KeyPair::generate().unwrap();
+        ca.issue_certificate(
+            "user@example.com".to_string(),
+            keypair.public_key().clone(),
+            DAY,
+            CertificateMetadata::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_x509_export_roundtrip() {
+        let ca = CertificateAuthority::generate("Test CA".to_string()).unwrap();
+        let cert = issue_leaf(&ca);
+        let x509 = ca.export_x509(&cert).unwrap();
+
+        let parsed = X509Certificate::from_pem(&x509.to_pem()).unwrap();
+        assert_eq!(parsed.to_der(), x509.to_der());
+        assert_eq!(parsed.subject(), "user@example.com");
+        assert_eq!(parsed.issuer(), "Test CA");
+        assert_eq!(parsed.not_before(), cert.issued_at);
+        assert_eq!(parsed.not_after(), cert.expires_at);
+        assert_eq!(parsed.public_key().as_bytes(), cert.public_key.as_bytes());
+        assert_eq!(
+            parsed.serial_number(),
+            cert.metadata.serial_number.replace('-', "")
+        );
+        assert_eq!(parsed.key_usage(), Some(KeyUsage::end_entity()));
+        assert!(!parsed.is_ca());
+        assert!(parsed.verify_signature(ca.public_key()).unwrap());
+    }
+
+    #[test]
+    fn test_x509_chain_validation() {
+        let root_ca = CertificateAuthority::generate("Root CA".to_string()).unwrap();
+        let intermediate_ca = CertificateAuthority::generate("Issuing CA".to_string()).unwrap();
+        let root = root_ca.x509_root(DAY * 365).unwrap();
+        let intermediate = root_ca.issue_x509_ca(&intermediate_ca, DAY * 30, Some(0)).unwrap();
+        let leaf = intermediate_ca.export_x509(&issue_leaf(&intermediate_ca)).unwrap();
+        let now = unix_now();
+
+        assert!(root.is_self_issued());
+        assert_eq!(intermediate.path_len(), Some(0));
+        leaf.verify_chain(std::slice::from_ref(&intermediate), &root, now).unwrap();
+
+        // Missing intermediate
+        assert!(leaf.verify_chain(&[], &root, now).is_err());
+        // Outside the validity period
+        assert!(matches!(
+            leaf.verify_chain(
+                std::slice::from_ref(&intermediate),
+                &root,
+                now + 2 * DAY.as_secs()
+            ),
+            Err(CryptoError::CertificateExpired)
+        ));
+        // End-entity certificates cannot issue
+        let rogue_ca = CertificateAuthority::new(
+            "user@example.com".to_string(),
+            Ed25519KeyPair::generate().unwrap(),
+        );
+        let rogue = rogue_ca.export_x509(&issue_leaf(&rogue_ca)).unwrap();
+        assert!(rogue.verify_issued_by(&leaf).is_err());
+        // Path length constraint of the intermediate
+        let sub_ca = CertificateAuthority::generate("Sub CA".to_string()).unwrap();
+        let sub = intermediate_ca.issue_x509_ca(&sub_ca, DAY, None).unwrap();
+        let sub_leaf = sub_ca.export_x509(&issue_leaf(&sub_ca)).unwrap();
+        assert!(sub_leaf.verify_chain(&[sub, intermediate], &root, now).is_err());
+    }
+
+    #[test]
+    fn test_x509_rejects_tampering() {
+        let ca = CertificateAuthority::generate("Test CA".to_string()).unwrap();
+        let other_ca = CertificateAuthority::generate("Test CA".to_string()).unwrap();
+        let root = ca.x509_root(DAY).unwrap();
+        let leaf = ca.export_x509(&issue_leaf(&ca)).unwrap();
+
+        let mut der = leaf.to_der().to_vec();
+        let position = der.windows(4).position(|w| w == b"user").unwrap();
+        der[position] = b'x';
+        let tampered = X509Certificate::from_der(&der).unwrap();
+        assert!(tampered.verify_chain(&[], &root, unix_now()).is_err());
+
+        let forged = other_ca.x509_root(DAY).unwrap();
+        assert!(leaf.verify_chain(&[], &forged, unix_now()).is_err());
+
+        assert!(X509Certificate::from_der(&der[..der.len() - 1]).is_err());
+        assert!(ca.export_x509(&issue_leaf(&other_ca)).is_err());
+    }
+
+    #[test]
+    fn test_x509_time_encoding() {
+        for timestamp in [0, 1_700_000_000, 2_524_608_000, MAX_TIME] {
+            let encoded = time(timestamp);
+            let mut reader = DerReader::new(&encoded);
+            assert_eq!(parse_time(&mut reader).unwrap(), timestamp);
+        }
+        assert_eq!(time(2_524_608_000)[0], TAG_GENERALIZED_TIME);
+        assert_eq!(time(1_700_000_000)[0], TAG_UTC_TIME);
+    }
+}
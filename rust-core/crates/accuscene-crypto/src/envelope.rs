@@ -138,6 +138,20 @@ impl EnvelopeEncryptor {
 
         Ok(data)
     }
+
+    /// Re-wrap the DEK of an envelope under another KEK
+    ///
+    /// The data ciphertext is untouched: pairing the returned DEK with the
+    /// envelope's `encrypted_data` gives the holder of `new_kek` access
+    /// without re-encrypting the data.
+    pub fn rewrap_dek(
+        &self,
+        envelope: &EnvelopeEncrypted,
+        new_kek: &SymmetricKey,
+    ) -> CryptoResult<EncryptedData> {
+        let dek_bytes = decrypt_aes256gcm(&self.kek, &envelope.encrypted_dek, None)?;
+        encrypt_aes256gcm(new_kek, dek_bytes.as_bytes(), None)
+    }
 }
 
 /// Encrypt data using envelope encryption
@@ -217,6 +231,20 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_envelope_rewrap_dek() {
+        let kek = SymmetricKey::generate().unwrap();
+        let recipient_kek = SymmetricKey::generate().unwrap();
+
+        let mut envelope = envelope_encrypt(&kek, b"Exhibit 7").unwrap();
+        envelope.encrypted_dek = EnvelopeEncryptor::new(kek)
+            .rewrap_dek(&envelope, &recipient_kek)
+            .unwrap();
+
+        let decrypted = envelope_decrypt(&recipient_kek, &envelope).unwrap();
+        assert_eq!(b"Exhibit 7", decrypted.as_bytes());
+    }
+
     #[test]
     fn test_envelope_base64_roundtrip() {
         let kek = SymmetricKey::generate().unwrap();
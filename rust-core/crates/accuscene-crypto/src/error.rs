@@ -114,10 +114,22 @@ pub enum CryptoError {
     #[error("Vault error: {0}")]
     VaultError(String),
 
+    /// Secret sharing split or reconstruction failed
+    #[error("Secret sharing failed: {0}")]
+    SecretSharingFailed(String),
+
+    /// Key provider failed to wrap or unwrap a key
+    #[error("Key provider failed: {0}")]
+    KeyProviderFailed(String),
+
     /// Key not found in vault
     #[error("Key not found in vault: {0}")]
     KeyNotFound(String),
 
+    /// Trusted timestamp issuance or verification failed
+    #[error("Timestamp verification failed: {0}")]
+    TimestampFailed(String),
+
     /// Integrity check failed
     #[error("Integrity check failed: {0}")]
     IntegrityCheckFailed(String),
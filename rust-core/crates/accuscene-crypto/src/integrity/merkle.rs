@@ -0,0 +1,434 @@
+//! Merkle-tree hashing of evidence bundles
+//!
+//! Hashes a whole bundle of evidence files into a single BLAKE3 root that can
+//! be signed or timestamped like any other [`IntegrityProof`]. An
+//! [`InclusionProof`] shows that one file belongs to a bundle without
+//! rehashing the other files.
+//!
+//! Leaves are sorted by relative path and commit to both the path and the
+//! file hash. Leaf and node hashes are domain separated (RFC 6962) and an
+//! unpaired node is promoted to the next level unchanged.
+
+use super::{IntegrityAlgorithm, IntegrityProof, IntegrityVerifier};
+use crate::asymmetric::signing::{verify_signature, Ed25519Signer};
+use crate::error::{CryptoError, CryptoResult};
+use crate::hash::blake3::{blake3_hash, blake3_hash_file, Blake3Hasher};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Domain separation prefix of leaf hashes
+const LEAF_PREFIX: u8 = 0x00;
+
+/// Domain separation prefix of interior node hashes
+const NODE_PREFIX: u8 = 0x01;
+
+/// A file in an evidence bundle
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MerkleLeaf {
+    /// Path relative to the bundle root, using `/` separators
+    pub path: String,
+    /// Hex-encoded BLAKE3 hash of the file contents
+    pub hash: String,
+}
+
+/// Builder collecting the files of an evidence bundle
+#[derive(Debug, Default)]
+pub struct MerkleTreeBuilder {
+    leaves: BTreeMap<String, [u8; 32]>,
+}
+
+impl MerkleTreeBuilder {
+    /// Create an empty builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a file under a bundle-relative path
+    pub fn add_file<P: AsRef<Path>>(mut self, relative_path: &str, file: P) -> CryptoResult<Self> {
+        let hash = blake3_hash_file(file)?;
+        self.insert(relative_path, hash)?;
+        Ok(self)
+    }
+
+    /// Add in-memory contents under a bundle-relative path
+    pub fn add_bytes(mut self, relative_path: &str, contents: &[u8]) -> CryptoResult<Self> {
+        self.insert(relative_path, blake3_hash(contents))?;
+        Ok(self)
+    }
+
+    /// Add every file below a directory, with paths relative to it
+    pub fn add_directory<P: AsRef<Path>>(mut self, dir: P) -> CryptoResult<Self> {
+        let root = dir.as_ref();
+        let mut pending = vec![root.to_path_buf()];
+
+        while let Some(current) = pending.pop() {
+            for entry in std::fs::read_dir(&current)? {
+                let entry = entry?;
+                let path = entry.path();
+                // Symlinked directories are not followed, avoiding cycles
+                if entry.file_type()?.is_dir() {
+                    pending.push(path);
+                    continue;
+                }
+
+                let relative = path
+                    .strip_prefix(root)
+                    .map_err(|e| CryptoError::IntegrityCheckFailed(e.to_string()))?
+                    .components()
+                    .map(|part| part.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                let hash = blake3_hash_file(&path)?;
+                self.insert(&relative, hash)?;
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Build the tree
+    pub fn build(self) -> CryptoResult<MerkleTree> {
+        if self.leaves.is_empty() {
+            return Err(CryptoError::IntegrityCheckFailed(
+                "evidence bundle is empty".to_string(),
+            ));
+        }
+
+        let mut levels =
+            vec![self.leaves.iter().map(|(path, hash)| leaf_hash(path, hash)).collect::<Vec<_>>()];
+        let leaves: Vec<MerkleLeaf> = self
+            .leaves
+            .into_iter()
+            .map(|(path, hash)| MerkleLeaf {
+                path,
+                hash: hex::encode(hash),
+            })
+            .collect();
+
+        while levels.last().is_some_and(|level| level.len() > 1) {
+            let next = levels[levels.len() - 1]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+
+        Ok(MerkleTree { leaves, levels })
+    }
+
+    fn insert(&mut self, relative_path: &str, hash: [u8; 32]) -> CryptoResult<()> {
+        let path = relative_path.trim_start_matches('/').replace('\\', "/");
+        if path.is_empty() || self.leaves.insert(path.clone(), hash).is_some() {
+            return Err(CryptoError::IntegrityCheckFailed(format!(
+                "invalid or duplicate bundle path '{}'",
+                path
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Merkle tree over the files of an evidence bundle
+#[derive(Debug, Clone)]
+pub struct MerkleTree {
+    leaves: Vec<MerkleLeaf>,
+    /// Hashes per level, from the leaves up to the root
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    /// Hash every file below a directory
+    pub fn from_directory<P: AsRef<Path>>(dir: P) -> CryptoResult<Self> {
+        MerkleTreeBuilder::new().add_directory(dir)?.build()
+    }
+
+    /// Root hash
+    pub fn root(&self) -> [u8; 32] {
+        self.levels[self.levels.len() - 1][0]
+    }
+
+    /// Hex-encoded root hash
+    pub fn root_hex(&self) -> String {
+        hex::encode(self.root())
+    }
+
+    /// Files in the bundle, sorted by path
+    pub fn leaves(&self) -> &[MerkleLeaf] {
+        &self.leaves
+    }
+
+    /// Number of files in the bundle
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    /// Check if the bundle has no files (never true for a built tree)
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    /// Unsigned proof over the root hash
+    pub fn root_proof(&self) -> IntegrityProof {
+        IntegrityProof {
+            hash: self.root_hex(),
+            algorithm: IntegrityAlgorithm::Blake3,
+            hmac: None,
+            signature: None,
+            public_key: None,
+            timestamp: None,
+        }
+    }
+
+    /// Proof over the root hash signed with Ed25519
+    pub fn sign_root(&self, signer: &Ed25519Signer) -> CryptoResult<IntegrityProof> {
+        let signature = signer.sign(&self.root())?;
+        Ok(IntegrityProof {
+            hash: self.root_hex(),
+            algorithm: IntegrityAlgorithm::Ed25519,
+            hmac: None,
+            signature: Some(signature),
+            public_key: Some(signer.public_key().clone()),
+            timestamp: None,
+        })
+    }
+
+    /// Verify a root proof against this tree
+    pub fn verify_root_proof(&self, proof: &IntegrityProof) -> CryptoResult<bool> {
+        if !proof.hash.eq_ignore_ascii_case(&self.root_hex()) {
+            return Ok(false);
+        }
+
+        match proof.algorithm {
+            IntegrityAlgorithm::Blake3 => Ok(true),
+            IntegrityAlgorithm::Ed25519 => {
+                let signature = proof
+                    .signature
+                    .as_ref()
+                    .ok_or_else(|| CryptoError::IntegrityCheckFailed("No signature".to_string()))?;
+                let public_key = proof.public_key.as_ref().ok_or_else(|| {
+                    CryptoError::IntegrityCheckFailed("No public key".to_string())
+                })?;
+                verify_signature(public_key, &self.root(), signature)
+            },
+            _ => Err(CryptoError::IntegrityCheckFailed(
+                "Proof is not a bundle root proof".to_string(),
+            )),
+        }
+    }
+
+    /// Inclusion proof for a file in the bundle
+    pub fn inclusion_proof(&self, relative_path: &str) -> Option<InclusionProof> {
+        let index = self
+            .leaves
+            .binary_search_by(|leaf| leaf.path.as_str().cmp(relative_path))
+            .ok()?;
+
+        let mut siblings = Vec::new();
+        let mut position = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = position ^ 1;
+            if let Some(hash) = level.get(sibling) {
+                siblings.push(hex::encode(hash));
+            }
+            position /= 2;
+        }
+
+        Some(InclusionProof {
+            leaf: self.leaves[index].clone(),
+            index,
+            leaf_count: self.leaves.len(),
+            siblings,
+        })
+    }
+}
+
+/// Proof that a file is part of an evidence bundle
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct InclusionProof {
+    /// The file being proven
+    pub leaf: MerkleLeaf,
+    /// Position of the file in the sorted bundle
+    pub index: usize,
+    /// Number of files in the bundle
+    pub leaf_count: usize,
+    /// Hex-encoded sibling hashes from the leaf level upwards
+    pub siblings: Vec<String>,
+}
+
+impl InclusionProof {
+    /// Verify the proof against a hex-encoded bundle root
+    pub fn verify(&self, root: &str) -> CryptoResult<bool> {
+        if self.index >= self.leaf_count {
+            return Ok(false);
+        }
+
+        let mut hash = leaf_hash(&self.leaf.path, &decode_hash(&self.leaf.hash)?);
+        let mut siblings = self.siblings.iter();
+        let mut position = self.index;
+        let mut width = self.leaf_count;
+
+        while width > 1 {
+            if position % 2 == 1 {
+                let Some(sibling) = siblings.next() else {
+                    return Ok(false);
+                };
+                hash = node_hash(&decode_hash(sibling)?, &hash);
+            } else if position + 1 < width {
+                let Some(sibling) = siblings.next() else {
+                    return Ok(false);
+                };
+                hash = node_hash(&hash, &decode_hash(sibling)?);
+            }
+            position /= 2;
+            width = width.div_ceil(2);
+        }
+
+        Ok(siblings.next().is_none() && hex::encode(hash).eq_ignore_ascii_case(root))
+    }
+
+    /// Verify a file on disk against the proof and a bundle root
+    ///
+    /// Only the given file is hashed.
+    pub fn verify_file<P: AsRef<Path>>(&self, file: P, root: &str) -> CryptoResult<bool> {
+        let hash = blake3_hash_file(file)?;
+        Ok(hex::encode(hash).eq_ignore_ascii_case(&self.leaf.hash) && self.verify(root)?)
+    }
+}
+
+impl IntegrityVerifier {
+    /// Compute the Merkle root proof of an evidence directory
+    pub fn hash_bundle<P: AsRef<Path>>(dir: P) -> CryptoResult<IntegrityProof> {
+        Ok(MerkleTree::from_directory(dir)?.root_proof())
+    }
+
+    /// Sign the Merkle root of an evidence directory with Ed25519
+    pub fn sign_bundle<P: AsRef<Path>>(
+        dir: P,
+        signer: &Ed25519Signer,
+    ) -> CryptoResult<IntegrityProof> {
+        MerkleTree::from_directory(dir)?.sign_root(signer)
+    }
+
+    /// Verify an evidence directory against a bundle root proof
+    pub fn verify_bundle<P: AsRef<Path>>(dir: P, proof: &IntegrityProof) -> CryptoResult<bool> {
+        MerkleTree::from_directory(dir)?.verify_root_proof(proof)
+    }
+}
+
+fn leaf_hash(path: &str, file_hash: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Blake3Hasher::new();
+    hasher.update(&[LEAF_PREFIX]);
+    hasher.update(&(path.len() as u64).to_le_bytes());
+    hasher.update(path.as_bytes());
+    hasher.update(file_hash);
+    hasher.finalize()
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Blake3Hasher::new();
+    hasher.update(&[NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize()
+}
+
+fn decode_hash(encoded: &str) -> CryptoResult<[u8; 32]> {
+    hex::decode(encoded)
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| CryptoError::DecodingError(format!("invalid hash '{}'", encoded)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle(count: usize) -> MerkleTree {
+        (0..count)
+            .fold(MerkleTreeBuilder::new(), |builder, i| {
+                builder
+                    .add_bytes(
+                        &format!("photos/{:02}.jpg", i),
+                        format!("photo {}", i).as_bytes(),
+                    )
+                    .unwrap()
+            })
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_inclusion_proofs() {
+        for count in 1..=9 {
+            let tree = bundle(count);
+            let root = tree.root_hex();
+
+            for leaf in tree.leaves() {
+                let proof = tree.inclusion_proof(&leaf.path).unwrap();
+                assert!(proof.verify(&root).unwrap(), "{} of {}", leaf.path, count);
+
+                let mut forged = proof.clone();
+                forged.leaf.hash = hex::encode(blake3_hash(b"forged"));
+                assert!(!forged.verify(&root).unwrap());
+            }
+        }
+
+        let tree = bundle(5);
+        assert!(tree.inclusion_proof("photos/99.jpg").is_none());
+        let proof = tree.inclusion_proof("photos/01.jpg").unwrap();
+        assert!(!proof.verify(&bundle(6).root_hex()).unwrap());
+    }
+
+    #[test]
+    fn test_root_commits_to_paths_and_contents() {
+        let tree = MerkleTreeBuilder::new()
+            .add_bytes("a.txt", b"one")
+            .unwrap()
+            .add_bytes("b.txt", b"two")
+            .unwrap()
+            .build()
+            .unwrap();
+        let renamed = MerkleTreeBuilder::new()
+            .add_bytes("a.txt", b"one")
+            .unwrap()
+            .add_bytes("c.txt", b"two")
+            .unwrap()
+            .build()
+            .unwrap();
+
+        assert_ne!(tree.root(), renamed.root());
+        assert!(MerkleTreeBuilder::new()
+            .add_bytes("a.txt", b"one")
+            .unwrap()
+            .add_bytes("a.txt", b"two")
+            .is_err());
+        assert!(MerkleTreeBuilder::new().build().is_err());
+    }
+
+    #[test]
+    fn test_sign_and_verify_bundle() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("photos")).unwrap();
+        std::fs::write(dir.path().join("report.pdf"), b"report").unwrap();
+        std::fs::write(dir.path().join("photos/front.jpg"), b"front").unwrap();
+        std::fs::write(dir.path().join("photos/rear.jpg"), b"rear").unwrap();
+
+        let signer = Ed25519Signer::generate().unwrap();
+        let proof = IntegrityVerifier::sign_bundle(dir.path(), &signer).unwrap();
+        assert!(IntegrityVerifier::verify_bundle(dir.path(), &proof).unwrap());
+
+        let tree = MerkleTree::from_directory(dir.path()).unwrap();
+        assert_eq!(tree.len(), 3);
+        let inclusion = tree.inclusion_proof("photos/rear.jpg").unwrap();
+        assert!(inclusion.verify_file(dir.path().join("photos/rear.jpg"), &proof.hash).unwrap());
+
+        std::fs::write(dir.path().join("photos/rear.jpg"), b"edited").unwrap();
+        assert!(!IntegrityVerifier::verify_bundle(dir.path(), &proof).unwrap());
+        assert!(!inclusion.verify_file(dir.path().join("photos/rear.jpg"), &proof.hash).unwrap());
+    }
+}
@@ -1,6 +1,17 @@
 //! File integrity verification
 //!
 //! Provides HMAC and digital signature-based file integrity verification.
+//! Proofs can carry a trusted timestamp proving when the content existed
+//! (see [`timestamp`]), and whole evidence bundles hash to a single Merkle
+//! root (see [`merkle`]).
+
+pub mod merkle;
+pub mod timestamp;
+
+pub use self::merkle::{InclusionProof, MerkleLeaf, MerkleTree, MerkleTreeBuilder};
+pub use self::timestamp::{
+    verify_custody_chain, TimestampAuthority, TimestampRequest, TimestampToken,
+};
 
 use crate::asymmetric::signing::{verify_signature, Ed25519Signer, Signature};
 use crate::asymmetric::Ed25519PublicKey;
@@ -27,6 +38,9 @@ pub struct IntegrityProof {
     pub signature: Option<Signature>,
     /// Public key used for signature (if signed)
     pub public_key: Option<Ed25519PublicKey>,
+    /// Trusted timestamp over the hash (if timestamped)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<TimestampToken>,
 }
 
 /// Integrity verification algorithm
@@ -74,6 +88,7 @@ impl IntegrityVerifier {
             hmac: None,
             signature: None,
             public_key: None,
+            timestamp: None,
         })
     }
 
@@ -86,6 +101,7 @@ impl IntegrityVerifier {
             hmac: None,
             signature: None,
             public_key: None,
+            timestamp: None,
         })
     }
 
@@ -98,6 +114,7 @@ impl IntegrityVerifier {
             hmac: None,
             signature: None,
             public_key: None,
+            timestamp: None,
         })
     }
 
@@ -127,6 +144,7 @@ impl IntegrityVerifier {
             hmac: Some(hash),
             signature: None,
             public_key: None,
+            timestamp: None,
         })
     }
 
@@ -148,6 +166,7 @@ impl IntegrityVerifier {
             hmac: None,
             signature: Some(signature),
             public_key: Some(signer.public_key().clone()),
+            timestamp: None,
         })
     }
 
@@ -362,6 +381,7 @@ mod tests {
             hmac: None,
             signature: None,
             public_key: None,
+            timestamp: None,
         };
 
         let encoded = proof.to_base64().unwrap();
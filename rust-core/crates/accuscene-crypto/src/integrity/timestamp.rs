@@ -0,0 +1,480 @@
+//! Trusted timestamps for integrity proofs
+//!
+//! An RFC 3161-style workflow: the holder of an [`IntegrityProof`] sends a
+//! [`TimestampRequest`] over its hash to a [`TimestampAuthority`], which
+//! returns a [`TimestampToken`] binding the hash to a time with its signature.
+//! Tokens verify offline against the authority's public key or X.509
+//! certificate.
+//!
+//! For chain of custody, each token may link to the previous token for the
+//! same evidence so the custody history cannot be reordered or truncated
+//! without detection (see [`verify_custody_chain`]).
+
+use super::{IntegrityAlgorithm, IntegrityProof};
+use crate::asymmetric::signing::{sign_message, verify_signature, Signature};
+use crate::asymmetric::{Ed25519KeyPair, Ed25519PublicKey};
+use crate::certificate::X509Certificate;
+use crate::error::{CryptoError, CryptoResult};
+use crate::hash::sha256;
+use crate::random::SecureRng;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Current token format version
+const TOKEN_VERSION: u8 = 1;
+
+/// Default timestamp policy identifier
+const DEFAULT_POLICY: &str = "accuscene.tsa.v1";
+
+/// Request for a trusted timestamp over a hash
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TimestampRequest {
+    /// Hex-encoded hash being timestamped
+    pub message_imprint: String,
+    /// Algorithm that produced the hash
+    pub algorithm: IntegrityAlgorithm,
+    /// Nonce echoed in the token to prevent replay
+    pub nonce: Option<u64>,
+    /// Digest of the previous token in the custody chain
+    pub previous: Option<String>,
+}
+
+impl TimestampRequest {
+    /// Create a request for a hex-encoded hash
+    pub fn new(message_imprint: String, algorithm: IntegrityAlgorithm) -> Self {
+        Self {
+            message_imprint,
+            algorithm,
+            nonce: None,
+            previous: None,
+        }
+    }
+
+    /// Add a random nonce
+    pub fn with_random_nonce(mut self) -> CryptoResult<Self> {
+        self.nonce = Some(SecureRng::new()?.generate_u64());
+        Ok(self)
+    }
+
+    /// Set the nonce
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    /// Link the request to the previous token in a custody chain
+    pub fn with_previous(mut self, previous: &TimestampToken) -> Self {
+        self.previous = Some(previous.digest());
+        self
+    }
+}
+
+/// Signed statement that a hash existed at a point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimestampToken {
+    /// Token format version
+    pub version: u8,
+    /// Name of the issuing authority
+    pub tsa_name: String,
+    /// Policy under which the token was issued
+    pub policy: String,
+    /// Unique serial number of the token
+    pub serial_number: String,
+    /// Hex-encoded hash that was timestamped
+    pub message_imprint: String,
+    /// Algorithm that produced the hash
+    pub algorithm: IntegrityAlgorithm,
+    /// Generation time (Unix timestamp)
+    pub gen_time: u64,
+    /// Accuracy of the generation time (seconds)
+    pub accuracy: u64,
+    /// Nonce from the request
+    pub nonce: Option<u64>,
+    /// Digest of the previous token in the custody chain
+    pub previous: Option<String>,
+    /// Public key of the issuing authority
+    pub tsa_public_key: Ed25519PublicKey,
+    /// Signature by the issuing authority
+    pub signature: Signature,
+}
+
+impl TimestampToken {
+    /// Verify the token signature against a trusted authority key
+    pub fn verify(&self, tsa_public_key: &Ed25519PublicKey) -> CryptoResult<bool> {
+        if self.tsa_public_key.as_bytes() != tsa_public_key.as_bytes() {
+            return Ok(false);
+        }
+        let signed = self.serialize_for_signing()?;
+        verify_signature(tsa_public_key, &signed, &self.signature)
+    }
+
+    /// Verify the token against the authority's X.509 certificate
+    ///
+    /// The certificate must be valid at the generation time of the token.
+    pub fn verify_with_certificate(&self, certificate: &X509Certificate) -> CryptoResult<bool> {
+        if certificate.subject() != self.tsa_name {
+            return Ok(false);
+        }
+        if !certificate.is_valid_at(self.gen_time) {
+            return Err(CryptoError::CertificateExpired);
+        }
+        self.verify(certificate.public_key())
+    }
+
+    /// Check that the token covers a hash
+    pub fn covers(&self, message_imprint: &str, algorithm: IntegrityAlgorithm) -> bool {
+        self.algorithm == algorithm && self.message_imprint.eq_ignore_ascii_case(message_imprint)
+    }
+
+    /// Hex-encoded SHA-256 digest of the signed token, used for chain links
+    pub fn digest(&self) -> String {
+        let mut bytes = self.serialize_for_signing().unwrap_or_default();
+        bytes.extend_from_slice(self.signature.as_bytes());
+        hex::encode(sha256(&bytes))
+    }
+
+    /// Encode the token to base64
+    pub fn to_base64(&self) -> CryptoResult<String> {
+        let json = serde_json::to_string(self)?;
+        Ok(STANDARD.encode(json.as_bytes()))
+    }
+
+    /// Decode a token from base64
+    pub fn from_base64(encoded: &str) -> CryptoResult<Self> {
+        let json_bytes = STANDARD.decode(encoded)?;
+        let json_str = std::str::from_utf8(&json_bytes)
+            .map_err(|e| CryptoError::DecodingError(e.to_string()))?;
+        let token = serde_json::from_str(json_str)?;
+        Ok(token)
+    }
+
+    /// Serialize token data for signing (excludes signature field)
+    fn serialize_for_signing(&self) -> CryptoResult<Vec<u8>> {
+        #[derive(Serialize)]
+        struct TokenDataForSigning<'a> {
+            version: u8,
+            tsa_name: &'a str,
+            policy: &'a str,
+            serial_number: &'a str,
+            message_imprint: &'a str,
+            algorithm: IntegrityAlgorithm,
+            gen_time: u64,
+            accuracy: u64,
+            nonce: Option<u64>,
+            previous: Option<&'a str>,
+            tsa_public_key: &'a Ed25519PublicKey,
+        }
+
+        let signed = TokenDataForSigning {
+            version: self.version,
+            tsa_name: &self.tsa_name,
+            policy: &self.policy,
+            serial_number: &self.serial_number,
+            message_imprint: &self.message_imprint,
+            algorithm: self.algorithm,
+            gen_time: self.gen_time,
+            accuracy: self.accuracy,
+            nonce: self.nonce,
+            previous: self.previous.as_deref(),
+            tsa_public_key: &self.tsa_public_key,
+        };
+
+        serde_json::to_vec(&signed).map_err(|e| CryptoError::SerializationError(e.to_string()))
+    }
+}
+
+/// Timestamping authority issuing signed timestamp tokens
+pub struct TimestampAuthority {
+    /// The authority's signing key pair
+    keypair: Ed25519KeyPair,
+    /// The authority's name
+    name: String,
+    /// Policy identifier stamped into tokens
+    policy: String,
+    /// Clock accuracy (seconds)
+    accuracy: u64,
+}
+
+impl TimestampAuthority {
+    /// Create a new timestamping authority
+    pub fn new(name: String, keypair: Ed25519KeyPair) -> Self {
+        Self {
+            keypair,
+            name,
+            policy: DEFAULT_POLICY.to_string(),
+            accuracy: 1,
+        }
+    }
+
+    /// Generate a new authority with a random key pair
+    pub fn generate(name: String) -> CryptoResult<Self> {
+        let keypair = Ed25519KeyPair::generate()?;
+        Ok(Self::new(name, keypair))
+    }
+
+    /// Set the policy identifier
+    pub fn with_policy(mut self, policy: String) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Set the clock accuracy (seconds)
+    pub fn with_accuracy(mut self, accuracy: u64) -> Self {
+        self.accuracy = accuracy;
+        self
+    }
+
+    /// Issue a token for a request at the current time
+    pub fn timestamp(&self, request: &TimestampRequest) -> CryptoResult<TimestampToken> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        self.timestamp_at(request, now)
+    }
+
+    /// Issue a token for a request at a given time
+    fn timestamp_at(
+        &self,
+        request: &TimestampRequest,
+        gen_time: u64,
+    ) -> CryptoResult<TimestampToken> {
+        let imprint = hex::decode(&request.message_imprint)
+            .map_err(|e| CryptoError::TimestampFailed(format!("invalid message imprint: {}", e)))?;
+        if imprint.is_empty() {
+            return Err(CryptoError::TimestampFailed(
+                "empty message imprint".to_string(),
+            ));
+        }
+
+        let mut token = TimestampToken {
+            version: TOKEN_VERSION,
+            tsa_name: self.name.clone(),
+            policy: self.policy.clone(),
+            serial_number: uuid::Uuid::new_v4().to_string(),
+            message_imprint: hex::encode(imprint),
+            algorithm: request.algorithm,
+            gen_time,
+            accuracy: self.accuracy,
+            nonce: request.nonce,
+            previous: request.previous.clone(),
+            tsa_public_key: self.keypair.public_key().clone(),
+            signature: Signature::from_bytes([0u8; 64]), // Placeholder
+        };
+
+        let signed = token.serialize_for_signing()?;
+        token.signature = sign_message(&self.keypair, &signed)?;
+        Ok(token)
+    }
+
+    /// Get the authority's public key
+    pub fn public_key(&self) -> &Ed25519PublicKey {
+        self.keypair.public_key()
+    }
+
+    /// Get the authority's name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl IntegrityProof {
+    /// Create a timestamp request over the proof's hash
+    pub fn timestamp_request(&self) -> TimestampRequest {
+        TimestampRequest::new(self.hash.clone(), self.algorithm)
+    }
+
+    /// Embed a timestamp token, checking that it covers the proof's hash
+    pub fn with_timestamp(mut self, token: TimestampToken) -> CryptoResult<Self> {
+        if !token.covers(&self.hash, self.algorithm) {
+            return Err(CryptoError::TimestampFailed(
+                "token does not cover the proof hash".to_string(),
+            ));
+        }
+        self.timestamp = Some(token);
+        Ok(self)
+    }
+
+    /// Verify the embedded timestamp and return its generation time
+    pub fn verify_timestamp(&self, tsa_public_key: &Ed25519PublicKey) -> CryptoResult<u64> {
+        let token = self
+            .timestamp
+            .as_ref()
+            .ok_or_else(|| CryptoError::TimestampFailed("proof is not timestamped".to_string()))?;
+        if !token.covers(&self.hash, self.algorithm) {
+            return Err(CryptoError::TimestampFailed(
+                "token does not cover the proof hash".to_string(),
+            ));
+        }
+        if !token.verify(tsa_public_key)? {
+            return Err(CryptoError::InvalidSignature);
+        }
+        Ok(token.gen_time)
+    }
+}
+
+/// Verify a chain-of-custody sequence of timestamp tokens offline
+///
+/// Each token must be signed by one of the trusted authorities, link to its
+/// predecessor and not predate it beyond the combined clock accuracy. The
+/// first token must not link to anything.
+pub fn verify_custody_chain(
+    tokens: &[TimestampToken],
+    trusted: &[Ed25519PublicKey],
+) -> CryptoResult<()> {
+    let mut previous: Option<&TimestampToken> = None;
+
+    for (position, token) in tokens.iter().enumerate() {
+        let signed = trusted
+            .iter()
+            .map(|key| token.verify(key))
+            .collect::<CryptoResult<Vec<_>>>()?
+            .into_iter()
+            .any(|valid| valid);
+        if !signed {
+            return Err(CryptoError::TimestampFailed(format!(
+                "token {} is not signed by a trusted authority",
+                position
+            )));
+        }
+
+        match previous {
+            None if token.previous.is_some() => {
+                return Err(CryptoError::TimestampFailed(
+                    "custody chain does not start at its first token".to_string(),
+                ));
+            },
+            Some(prev) if token.previous.as_deref() != Some(prev.digest().as_str()) => {
+                return Err(CryptoError::TimestampFailed(format!(
+                    "token {} does not link to its predecessor",
+                    position
+                )));
+            },
+            Some(prev) if token.gen_time + token.accuracy + prev.accuracy < prev.gen_time => {
+                return Err(CryptoError::TimestampFailed(format!(
+                    "token {} predates its predecessor",
+                    position
+                )));
+            },
+            _ => {},
+        }
+
+        previous = Some(token);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrity::checksum_blake3;
+
+    fn proof(content: &[u8]) -> IntegrityProof {
+        IntegrityProof {
+            hash: checksum_blake3(content),
+            algorithm: IntegrityAlgorithm::Blake3,
+            hmac: None,
+            signature: None,
+            public_key: None,
+            timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_timestamp_proof() {
+        let tsa = TimestampAuthority::generate("Test TSA".to_string()).unwrap();
+        let proof = proof(b"scene diagram");
+
+        let request = proof.timestamp_request().with_random_nonce().unwrap();
+        let token = tsa.timestamp(&request).unwrap();
+        assert_eq!(token.nonce, request.nonce);
+
+        let proof = proof.with_timestamp(token.clone()).unwrap();
+        assert_eq!(
+            proof.verify_timestamp(tsa.public_key()).unwrap(),
+            token.gen_time
+        );
+
+        // Survives the proof's storage encoding
+        let decoded = IntegrityProof::from_base64(&proof.to_base64().unwrap()).unwrap();
+        assert_eq!(
+            decoded.verify_timestamp(tsa.public_key()).unwrap(),
+            token.gen_time
+        );
+
+        let other = TimestampAuthority::generate("Test TSA".to_string()).unwrap();
+        assert!(proof.verify_timestamp(other.public_key()).is_err());
+    }
+
+    #[test]
+    fn test_timestamp_rejects_tampering() {
+        let tsa = TimestampAuthority::generate("Test TSA".to_string()).unwrap();
+        let original = proof(b"scene diagram");
+        let token = tsa.timestamp(&original.timestamp_request()).unwrap();
+
+        assert!(proof(b"edited diagram").with_timestamp(token.clone()).is_err());
+
+        let mut backdated = token.clone();
+        backdated.gen_time -= 3600;
+        assert!(!backdated.verify(tsa.public_key()).unwrap());
+        assert!(original
+            .with_timestamp(backdated)
+            .unwrap()
+            .verify_timestamp(tsa.public_key())
+            .is_err());
+
+        let invalid = TimestampRequest::new("not hex".to_string(), IntegrityAlgorithm::Blake3);
+        assert!(tsa.timestamp(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_timestamp_with_certificate() {
+        use crate::certificate::CertificateAuthority;
+        use std::time::Duration;
+
+        let ca = CertificateAuthority::generate("Root CA".to_string()).unwrap();
+        let tsa = TimestampAuthority::generate("Test TSA".to_string()).unwrap();
+        let cert = ca
+            .issue_certificate(
+                tsa.name().to_string(),
+                tsa.public_key().clone(),
+                Duration::from_secs(3600),
+                Default::default(),
+            )
+            .unwrap();
+        let x509 = ca.export_x509(&cert).unwrap();
+
+        let token = tsa.timestamp(&proof(b"scene").timestamp_request()).unwrap();
+        assert!(token.verify_with_certificate(&x509).unwrap());
+
+        let late = tsa
+            .timestamp_at(&proof(b"scene").timestamp_request(), cert.expires_at + 1)
+            .unwrap();
+        assert!(late.verify_with_certificate(&x509).is_err());
+    }
+
+    #[test]
+    fn test_custody_chain() {
+        let tsa = TimestampAuthority::generate("Test TSA".to_string()).unwrap();
+        let request = proof(b"evidence photo").timestamp_request();
+
+        let seized = tsa.timestamp_at(&request, 1_000).unwrap();
+        let transferred = tsa.timestamp_at(&request.clone().with_previous(&seized), 2_000).unwrap();
+        let analysed =
+            tsa.timestamp_at(&request.clone().with_previous(&transferred), 3_000).unwrap();
+        let trusted = [tsa.public_key().clone()];
+
+        let chain = [seized.clone(), transferred.clone(), analysed.clone()];
+        verify_custody_chain(&chain, &trusted).unwrap();
+
+        // Reordered, truncated at the start, or from an untrusted authority
+        assert!(verify_custody_chain(&[seized.clone(), analysed.clone()], &trusted).is_err());
+        assert!(verify_custody_chain(&[transferred.clone(), analysed], &trusted).is_err());
+        let other = TimestampAuthority::generate("Other TSA".to_string()).unwrap();
+        assert!(verify_custody_chain(&chain, &[other.public_key().clone()]).is_err());
+
+        // Linked but going back in time
+        let backdated = tsa.timestamp_at(&request.with_previous(&transferred), 500).unwrap();
+        assert!(verify_custody_chain(&[seized, transferred, backdated], &trusted).is_err());
+    }
+}
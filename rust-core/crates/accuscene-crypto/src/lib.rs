@@ -10,10 +10,13 @@
 //! - **Asymmetric Cryptography**: Ed25519 signatures and X25519 key exchange
 //! - **Key Derivation**: HKDF and PBKDF2 for deriving keys from passwords
 //! - **Envelope Encryption**: Secure encryption for large data with DEK/KEK pattern
-//! - **Secure Vault**: In-memory key storage with automatic zeroization
+//! - **Secure Vault**: Key storage with automatic zeroization and encrypted persistence
+//! - **Key Providers**: Vault KEKs in the OS keychain, PKCS#11/TPM or a cloud KMS
+//! - **Secret Sharing**: Shamir M-of-N escrow of the vault master key
 //! - **Token Management**: Secure token generation and validation with expiration
-//! - **Integrity Verification**: File integrity checking with HMAC and signatures
-//! - **Certificate System**: Simple PKI for public key distribution
+//! - **JWT/JWS**: EdDSA and HS256 JSON Web Tokens with claims validation
+//! - **Integrity Verification**: File integrity checking with HMAC, signatures and timestamps
+//! - **Certificate System**: Simple PKI for public key distribution, with X.509 export
 //!
 //! ## Security Properties
 //!
@@ -64,6 +67,7 @@
 //! - [`kdf`] - Key derivation functions
 //! - [`envelope`] - Envelope encryption for large data
 //! - [`vault`] - Secure in-memory key vault
+//! - [`secret_sharing`] - Shamir secret sharing for key escrow
 //! - [`token`] - Token generation and validation
 //! - [`integrity`] - File integrity verification
 //! - [`certificate`] - Simple certificate system
@@ -88,6 +92,7 @@ pub mod symmetric;
 pub mod certificate;
 pub mod envelope;
 pub mod integrity;
+pub mod secret_sharing;
 pub mod token;
 pub mod vault;
 
@@ -135,19 +140,26 @@ pub mod prelude {
     pub use crate::envelope::{envelope_decrypt, envelope_encrypt, EnvelopeEncryptor};
 
     // Vault
-    pub use crate::vault::{Vault, VaultEntryBuilder};
+    pub use crate::vault::{KeyProvider, Vault, VaultEntryBuilder, WrappedKey};
+
+    // Secret sharing
+    pub use crate::secret_sharing::{combine_shares, split_secret, Share};
 
     // Tokens
-    pub use crate::token::{generate_token_string, hash_token, Token, TokenGenerator};
+    pub use crate::token::{
+        generate_token_string, hash_token, JwtSigner, JwtVerifier, Token, TokenGenerator,
+    };
 
     // Integrity
     pub use crate::integrity::{
         checksum_blake3, checksum_sha256, hmac_sha256, IntegrityAlgorithm, IntegrityProof,
-        IntegrityVerifier,
+        IntegrityVerifier, MerkleTree, MerkleTreeBuilder, TimestampAuthority, TimestampToken,
     };
 
     // Certificates
-    pub use crate::certificate::{Certificate, CertificateAuthority, CertificateBuilder};
+    pub use crate::certificate::{
+        Certificate, CertificateAuthority, CertificateBuilder, X509Certificate,
+    };
 }
 
 #[cfg(test)]
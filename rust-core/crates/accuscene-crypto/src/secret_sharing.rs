@@ -0,0 +1,361 @@
+//! Shamir secret sharing over GF(256)
+//!
+//! Splits a secret (typically the vault KEK) into N shares so that any M of
+//! them reconstruct it and fewer than M reveal nothing. Each secret byte is
+//! the constant term of a random polynomial of degree M-1; share `x` holds
+//! the polynomial values at `x`.
+//!
+//! Field arithmetic uses the AES polynomial (x^8 + x^4 + x^3 + x + 1) and
+//! is branch-free and table-free, so reconstruction time does not depend on
+//! share or secret values.
+
+use crate::error::{CryptoError, CryptoResult};
+use crate::random::SecureRng;
+use crate::secure_memory::SecureBytes;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Version byte of the binary share encoding
+const SHARE_FORMAT_VERSION: u8 = 1;
+
+/// Length of the share set identifier
+const SET_ID_LEN: usize = 16;
+
+/// Length of the binary share header (version, threshold, index, set ID)
+const HEADER_LEN: usize = 3 + SET_ID_LEN;
+
+/// One share of a split secret
+#[derive(Clone, Zeroize, ZeroizeOnDrop, Serialize, Deserialize)]
+pub struct Share {
+    /// Number of shares needed to reconstruct the secret
+    threshold: u8,
+    /// Evaluation point of this share (1-255)
+    index: u8,
+    /// Identifier shared by all shares of one split
+    set_id: [u8; SET_ID_LEN],
+    /// Polynomial values, one per secret byte
+    value: Vec<u8>,
+}
+
+impl Share {
+    /// Number of shares needed to reconstruct the secret
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// Evaluation point of this share
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// Identifier shared by all shares of one split
+    pub fn set_id(&self) -> &[u8; SET_ID_LEN] {
+        &self.set_id
+    }
+
+    /// Encode the share as bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + self.value.len());
+        bytes.push(SHARE_FORMAT_VERSION);
+        bytes.push(self.threshold);
+        bytes.push(self.index);
+        bytes.extend_from_slice(&self.set_id);
+        bytes.extend_from_slice(&self.value);
+        bytes
+    }
+
+    /// Decode a share from bytes
+    pub fn from_bytes(bytes: &[u8]) -> CryptoResult<Self> {
+        if bytes.len() <= HEADER_LEN {
+            return Err(CryptoError::DecodingError(format!(
+                "share too short: {} bytes",
+                bytes.len()
+            )));
+        }
+        if bytes[0] != SHARE_FORMAT_VERSION {
+            return Err(CryptoError::DecodingError(format!(
+                "unsupported share version: {}",
+                bytes[0]
+            )));
+        }
+
+        let mut set_id = [0u8; SET_ID_LEN];
+        set_id.copy_from_slice(&bytes[3..HEADER_LEN]);
+        let share = Self {
+            threshold: bytes[1],
+            index: bytes[2],
+            set_id,
+            value: bytes[HEADER_LEN..].to_vec(),
+        };
+
+        if share.threshold == 0 || share.index == 0 {
+            return Err(CryptoError::DecodingError(
+                "share threshold and index must be non-zero".to_string(),
+            ));
+        }
+        Ok(share)
+    }
+
+    /// Encode the share as hex, e.g. for printing on paper
+    pub fn to_hex(&self) -> String {
+        let mut bytes = self.to_bytes();
+        let encoded = hex::encode(&bytes);
+        bytes.zeroize();
+        encoded
+    }
+
+    /// Decode a share from hex
+    pub fn from_hex(encoded: &str) -> CryptoResult<Self> {
+        let mut bytes =
+            hex::decode(encoded.trim()).map_err(|e| CryptoError::DecodingError(e.to_string()))?;
+        let share = Self::from_bytes(&bytes);
+        bytes.zeroize();
+        share
+    }
+}
+
+// Don't print share values
+impl fmt::Debug for Share {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Share")
+            .field("threshold", &self.threshold)
+            .field("index", &self.index)
+            .field("set_id", &hex::encode(self.set_id))
+            .field("value", &"[REDACTED]")
+            .finish()
+    }
+}
+
+/// Split a secret into `shares` shares, any `threshold` of which reconstruct it
+pub fn split_secret(secret: &[u8], threshold: u8, shares: u8) -> CryptoResult<Vec<Share>> {
+    let mut rng = SecureRng::new()?;
+    split_secret_with_rng(secret, threshold, shares, &mut rng)
+}
+
+/// Split a secret using the given random number generator
+pub fn split_secret_with_rng(
+    secret: &[u8],
+    threshold: u8,
+    shares: u8,
+    rng: &mut SecureRng,
+) -> CryptoResult<Vec<Share>> {
+    if secret.is_empty() {
+        return Err(CryptoError::InvalidInput("secret must not be empty".to_string()));
+    }
+    if threshold == 0 || threshold > shares {
+        return Err(CryptoError::SecretSharingFailed(format!(
+            "threshold must be between 1 and the number of shares, got {} of {}",
+            threshold, shares
+        )));
+    }
+
+    let mut set_id = [0u8; SET_ID_LEN];
+    rng.fill_bytes(&mut set_id);
+
+    let mut result: Vec<Share> = (1..=shares)
+        .map(|index| Share {
+            threshold,
+            index,
+            set_id,
+            value: Vec::with_capacity(secret.len()),
+        })
+        .collect();
+
+    // coefficients[0] is the secret byte, the rest are random
+    let mut coefficients = vec![0u8; threshold as usize];
+    for &byte in secret {
+        coefficients[0] = byte;
+        rng.fill_bytes(&mut coefficients[1..]);
+
+        for share in &mut result {
+            share.value.push(evaluate(&coefficients, share.index));
+        }
+    }
+    coefficients.zeroize();
+
+    Ok(result)
+}
+
+/// Reconstruct a secret from at least `threshold` shares of the same split
+///
+/// Extra shares beyond the threshold are ignored.
+pub fn combine_shares(shares: &[Share]) -> CryptoResult<SecureBytes> {
+    let first = shares
+        .first()
+        .ok_or_else(|| CryptoError::SecretSharingFailed("no shares provided".to_string()))?;
+    let threshold = first.threshold as usize;
+
+    if shares.len() < threshold {
+        return Err(CryptoError::SecretSharingFailed(format!(
+            "need {} shares, got {}",
+            threshold,
+            shares.len()
+        )));
+    }
+
+    let used = &shares[..threshold];
+    for (i, share) in used.iter().enumerate() {
+        if share.set_id != first.set_id
+            || share.threshold != first.threshold
+            || share.value.len() != first.value.len()
+        {
+            return Err(CryptoError::SecretSharingFailed(
+                "shares belong to different splits".to_string(),
+            ));
+        }
+        if share.index == 0 || used[..i].iter().any(|other| other.index == share.index) {
+            return Err(CryptoError::SecretSharingFailed(format!(
+                "duplicate or invalid share index {}",
+                share.index
+            )));
+        }
+    }
+
+    // Lagrange basis polynomials at x = 0 depend only on the public indices
+    let basis: Vec<u8> = used
+        .iter()
+        .map(|share| {
+            let (numerator, denominator) = used
+                .iter()
+                .filter(|other| other.index != share.index)
+                .fold((1u8, 1u8), |(num, den), other| {
+                    (gf_mul(num, other.index), gf_mul(den, other.index ^ share.index))
+                });
+            gf_mul(numerator, gf_inv(denominator))
+        })
+        .collect();
+
+    let mut secret = vec![0u8; first.value.len()];
+    for (byte, out) in secret.iter_mut().enumerate() {
+        *out = used
+            .iter()
+            .zip(&basis)
+            .fold(0u8, |acc, (share, &weight)| acc ^ gf_mul(share.value[byte], weight));
+    }
+
+    Ok(SecureBytes::new(secret))
+}
+
+/// Evaluate a polynomial at `x` using Horner's method
+fn evaluate(coefficients: &[u8], x: u8) -> u8 {
+    coefficients
+        .iter()
+        .rev()
+        .fold(0u8, |acc, &coefficient| gf_mul(acc, x) ^ coefficient)
+}
+
+/// Multiply in GF(256) without branches or table lookups
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        product ^= a & (b & 1).wrapping_neg();
+        let carry = (a >> 7).wrapping_neg();
+        a = (a << 1) ^ (0x1b & carry);
+        b >>= 1;
+    }
+    product
+}
+
+/// Multiplicative inverse in GF(256) as a^254 (0 maps to 0)
+fn gf_inv(a: u8) -> u8 {
+    let a2 = gf_mul(a, a);
+    let a4 = gf_mul(a2, a2);
+    let a8 = gf_mul(a4, a4);
+    let a16 = gf_mul(a8, a8);
+    let a32 = gf_mul(a16, a16);
+    let a64 = gf_mul(a32, a32);
+    let a128 = gf_mul(a64, a64);
+
+    // 254 = 128 + 64 + 32 + 16 + 8 + 4 + 2
+    [a4, a8, a16, a32, a64, a128]
+        .iter()
+        .fold(a2, |acc, &power| gf_mul(acc, power))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gf_arithmetic() {
+        // Known AES field product: 0x57 * 0x83 = 0xc1
+        assert_eq!(gf_mul(0x57, 0x83), 0xc1);
+        assert_eq!(gf_inv(0), 0);
+        for a in 1..=255u8 {
+            assert_eq!(gf_mul(a, gf_inv(a)), 1);
+        }
+    }
+
+    #[test]
+    fn test_split_and_combine() {
+        let secret = b"key-encryption-key-material-32b!";
+        let shares = split_secret(secret, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        // Any three shares reconstruct the secret
+        for combination in [[0, 1, 2], [0, 2, 4], [1, 3, 4], [4, 3, 2]] {
+            let subset: Vec<Share> = combination.iter().map(|&i| shares[i].clone()).collect();
+            let recovered = combine_shares(&subset).unwrap();
+            assert_eq!(recovered.as_bytes(), secret);
+        }
+
+        // Two shares are not enough
+        assert!(combine_shares(&shares[..2]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        assert!(split_secret(b"secret", 0, 3).is_err());
+        assert!(split_secret(b"secret", 4, 3).is_err());
+        assert!(split_secret(b"", 2, 3).is_err());
+
+        // Threshold of one: every share is the secret
+        let shares = split_secret(b"secret", 1, 2).unwrap();
+        assert_eq!(combine_shares(&shares[1..]).unwrap().as_bytes(), b"secret");
+    }
+
+    #[test]
+    fn test_rejects_mixed_and_duplicate_shares() {
+        let first = split_secret(b"secret", 2, 3).unwrap();
+        let second = split_secret(b"secret", 2, 3).unwrap();
+
+        let mixed = vec![first[0].clone(), second[1].clone()];
+        assert!(combine_shares(&mixed).is_err());
+
+        let duplicate = vec![first[0].clone(), first[0].clone()];
+        assert!(combine_shares(&duplicate).is_err());
+    }
+
+    #[test]
+    fn test_share_encoding() {
+        let shares = split_secret(b"secret", 2, 3).unwrap();
+
+        let decoded: Vec<Share> = shares
+            .iter()
+            .map(|share| Share::from_hex(&share.to_hex()).unwrap())
+            .collect();
+        assert_eq!(decoded[1].index(), 2);
+        assert_eq!(decoded[1].threshold(), 2);
+        assert_eq!(combine_shares(&decoded[1..]).unwrap().as_bytes(), b"secret");
+
+        let json = serde_json::to_string(&shares[0]).unwrap();
+        let from_json: Share = serde_json::from_str(&json).unwrap();
+        assert_eq!(from_json.to_bytes(), shares[0].to_bytes());
+
+        assert!(Share::from_bytes(&[SHARE_FORMAT_VERSION, 2, 1]).is_err());
+        let mut bytes = shares[0].to_bytes();
+        bytes[0] = 9;
+        assert!(Share::from_bytes(&bytes).is_err());
+        assert!(!format!("{:?}", shares[0]).contains(&hex::encode(&shares[0].value)));
+    }
+
+    #[test]
+    fn test_deterministic_split() {
+        let mut rng = SecureRng::from_seed([7u8; 32]);
+        let a = split_secret_with_rng(b"secret", 2, 3, &mut rng).unwrap();
+        let mut rng = SecureRng::from_seed([7u8; 32]);
+        let b = split_secret_with_rng(b"secret", 2, 3, &mut rng).unwrap();
+        assert_eq!(a[2].to_bytes(), b[2].to_bytes());
+    }
+}
@@ -0,0 +1,614 @@
+//! JSON Web Tokens
+//!
+//! Issues and verifies compact JWS/JWT tokens (RFC 7515, RFC 7519) signed
+//! with EdDSA (Ed25519, RFC 8037) or HS256.
+//!
+//! Verification keys are looked up by the `kid` header. Keys certified by
+//! the certificate system are registered under the certificate serial
+//! number, so a token signed with a certified key verifies only while its
+//! certificate is valid.
+
+use crate::asymmetric::signing::{sign_message, verify_signature, Signature};
+use crate::asymmetric::{Ed25519KeyPair, Ed25519PublicKey};
+use crate::certificate::{Certificate, X509Certificate};
+use crate::error::{CryptoError, CryptoResult};
+use crate::symmetric::key::SymmetricKey;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine as _;
+use ring::hmac;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Default clock skew tolerance (seconds)
+const DEFAULT_LEEWAY: u64 = 60;
+
+/// JWS signing algorithm
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum JwtAlgorithm {
+    /// Ed25519 signature
+    EdDSA,
+    /// HMAC-SHA256
+    HS256,
+}
+
+/// JOSE header
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct JwtHeader {
+    /// Signing algorithm
+    pub alg: JwtAlgorithm,
+    /// Token type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub typ: Option<String>,
+    /// Key identifier
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kid: Option<String>,
+}
+
+/// Audience claim, a single value or a list
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum Audience {
+    /// Single audience
+    Single(String),
+    /// Multiple audiences
+    Multiple(Vec<String>),
+}
+
+impl Audience {
+    /// Check if the audience includes a value
+    pub fn contains(&self, audience: &str) -> bool {
+        match self {
+            Audience::Single(value) => value == audience,
+            Audience::Multiple(values) => values.iter().any(|value| value == audience),
+        }
+    }
+}
+
+/// Registered and custom JWT claims
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct Claims {
+    /// Issuer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    /// Subject
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    /// Audience
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<Audience>,
+    /// Expiration time (Unix timestamp)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<u64>,
+    /// Not-before time (Unix timestamp)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<u64>,
+    /// Issued-at time (Unix timestamp)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iat: Option<u64>,
+    /// Token identifier
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+    /// Custom claims
+    #[serde(flatten)]
+    pub custom: HashMap<String, serde_json::Value>,
+}
+
+impl Claims {
+    /// Create claims issued now with a random token identifier
+    pub fn new() -> Self {
+        Self {
+            iat: Some(unix_now()),
+            jti: Some(uuid::Uuid::new_v4().to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Set the issuer
+    pub fn with_issuer(mut self, issuer: String) -> Self {
+        self.iss = Some(issuer);
+        self
+    }
+
+    /// Set the subject
+    pub fn with_subject(mut self, subject: String) -> Self {
+        self.sub = Some(subject);
+        self
+    }
+
+    /// Set a single audience
+    pub fn with_audience(mut self, audience: String) -> Self {
+        self.aud = Some(Audience::Single(audience));
+        self
+    }
+
+    /// Set the lifetime, counted from the issued-at time
+    pub fn with_lifetime(mut self, lifetime: Duration) -> Self {
+        let issued_at = *self.iat.get_or_insert_with(unix_now);
+        self.exp = Some(issued_at + lifetime.as_secs());
+        self
+    }
+
+    /// Set the not-before time (Unix timestamp)
+    pub fn with_not_before(mut self, not_before: u64) -> Self {
+        self.nbf = Some(not_before);
+        self
+    }
+
+    /// Add a custom claim
+    pub fn with_claim(mut self, name: String, value: serde_json::Value) -> Self {
+        self.custom.insert(name, value);
+        self
+    }
+}
+
+/// Key used to sign tokens
+enum SigningKey {
+    Ed25519(Ed25519KeyPair),
+    Hmac(SymmetricKey),
+}
+
+/// Issuer of signed JWTs
+pub struct JwtSigner {
+    key: SigningKey,
+    kid: Option<String>,
+}
+
+impl JwtSigner {
+    /// Create a signer using EdDSA
+    pub fn ed25519(keypair: Ed25519KeyPair) -> Self {
+        Self {
+            key: SigningKey::Ed25519(keypair),
+            kid: None,
+        }
+    }
+
+    /// Create a signer using HS256
+    pub fn hs256(key: SymmetricKey) -> Self {
+        Self {
+            key: SigningKey::Hmac(key),
+            kid: None,
+        }
+    }
+
+    /// Set the key identifier placed in the header
+    pub fn with_key_id(mut self, kid: String) -> Self {
+        self.kid = Some(kid);
+        self
+    }
+
+    /// Use the serial number of a certificate for the signing key as the
+    /// key identifier
+    pub fn with_certificate(self, certificate: &Certificate) -> CryptoResult<Self> {
+        match &self.key {
+            SigningKey::Ed25519(keypair)
+                if keypair.public_key().as_bytes() == certificate.public_key.as_bytes() =>
+            {
+                let kid = certificate.metadata.serial_number.clone();
+                Ok(self.with_key_id(kid))
+            },
+            _ => Err(CryptoError::InvalidCertificate(
+                "certificate does not certify the signing key".to_string(),
+            )),
+        }
+    }
+
+    /// Signing algorithm
+    pub fn algorithm(&self) -> JwtAlgorithm {
+        match self.key {
+            SigningKey::Ed25519(_) => JwtAlgorithm::EdDSA,
+            SigningKey::Hmac(_) => JwtAlgorithm::HS256,
+        }
+    }
+
+    /// Sign claims into a compact JWT
+    pub fn sign(&self, claims: &Claims) -> CryptoResult<String> {
+        let header = JwtHeader {
+            alg: self.algorithm(),
+            typ: Some("JWT".to_string()),
+            kid: self.kid.clone(),
+        };
+        let signing_input = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(serde_json::to_vec(&header)?),
+            URL_SAFE_NO_PAD.encode(serde_json::to_vec(claims)?)
+        );
+
+        let signature = match &self.key {
+            SigningKey::Ed25519(keypair) => {
+                sign_message(keypair, signing_input.as_bytes())?.to_bytes().to_vec()
+            },
+            SigningKey::Hmac(key) => {
+                let key = hmac::Key::new(hmac::HMAC_SHA256, key.as_bytes());
+                hmac::sign(&key, signing_input.as_bytes()).as_ref().to_vec()
+            },
+        };
+
+        Ok(format!(
+            "{}.{}",
+            signing_input,
+            URL_SAFE_NO_PAD.encode(signature)
+        ))
+    }
+}
+
+/// Claims validation rules
+#[derive(Clone, Debug)]
+pub struct JwtValidation {
+    /// Required issuer
+    pub issuer: Option<String>,
+    /// Required audience
+    pub audience: Option<String>,
+    /// Clock skew tolerance (seconds)
+    pub leeway: u64,
+    /// Reject tokens without an expiration time
+    pub require_exp: bool,
+}
+
+impl Default for JwtValidation {
+    fn default() -> Self {
+        Self {
+            issuer: None,
+            audience: None,
+            leeway: DEFAULT_LEEWAY,
+            require_exp: true,
+        }
+    }
+}
+
+impl JwtValidation {
+    /// Require an issuer
+    pub fn with_issuer(mut self, issuer: String) -> Self {
+        self.issuer = Some(issuer);
+        self
+    }
+
+    /// Require an audience
+    pub fn with_audience(mut self, audience: String) -> Self {
+        self.audience = Some(audience);
+        self
+    }
+
+    /// Set the clock skew tolerance
+    pub fn with_leeway(mut self, leeway: Duration) -> Self {
+        self.leeway = leeway.as_secs();
+        self
+    }
+
+    /// Validate claims at a Unix timestamp
+    pub fn validate(&self, claims: &Claims, now: u64) -> CryptoResult<()> {
+        match claims.exp {
+            Some(exp) if now >= exp.saturating_add(self.leeway) => {
+                return Err(CryptoError::TokenExpired)
+            },
+            None if self.require_exp => {
+                return Err(CryptoError::TokenValidationFailed(
+                    "missing exp claim".to_string(),
+                ))
+            },
+            _ => {},
+        }
+
+        if claims.nbf.is_some_and(|nbf| now.saturating_add(self.leeway) < nbf) {
+            return Err(CryptoError::TokenValidationFailed(
+                "token is not valid yet".to_string(),
+            ));
+        }
+
+        if let Some(issuer) = &self.issuer {
+            if claims.iss.as_ref() != Some(issuer) {
+                return Err(CryptoError::TokenValidationFailed(
+                    "issuer mismatch".to_string(),
+                ));
+            }
+        }
+
+        if let Some(audience) = &self.audience {
+            if !claims.aud.as_ref().is_some_and(|aud| aud.contains(audience)) {
+                return Err(CryptoError::TokenValidationFailed(
+                    "audience mismatch".to_string(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Key used to verify tokens
+#[derive(Clone)]
+enum VerificationKey {
+    Ed25519 {
+        public_key: Ed25519PublicKey,
+        /// End of the certificate validity (Unix timestamp)
+        not_after: Option<u64>,
+    },
+    Hmac(SymmetricKey),
+}
+
+/// Verifier of JWTs against a set of keys identified by `kid`
+pub struct JwtVerifier {
+    keys: HashMap<String, VerificationKey>,
+    validation: JwtValidation,
+}
+
+impl JwtVerifier {
+    /// Create a verifier with no keys
+    pub fn new(validation: JwtValidation) -> Self {
+        Self {
+            keys: HashMap::new(),
+            validation,
+        }
+    }
+
+    /// Register an Ed25519 public key
+    pub fn add_ed25519_key(&mut self, kid: String, public_key: Ed25519PublicKey) {
+        let key = VerificationKey::Ed25519 {
+            public_key,
+            not_after: None,
+        };
+        self.keys.insert(kid, key);
+    }
+
+    /// Register an HS256 secret
+    pub fn add_hs256_key(&mut self, kid: String, key: SymmetricKey) {
+        self.keys.insert(kid, VerificationKey::Hmac(key));
+    }
+
+    /// Register the key of a certificate issued by a trusted CA
+    ///
+    /// The key is registered under the certificate serial number and
+    /// accepted only until the certificate expires.
+    pub fn add_certificate(
+        &mut self,
+        certificate: &Certificate,
+        ca_public_key: &Ed25519PublicKey,
+    ) -> CryptoResult<()> {
+        if certificate.is_expired() {
+            return Err(CryptoError::CertificateExpired);
+        }
+        if !certificate.verify_signature(ca_public_key)? {
+            return Err(CryptoError::InvalidSignature);
+        }
+
+        let key = VerificationKey::Ed25519 {
+            public_key: certificate.public_key.clone(),
+            not_after: Some(certificate.expires_at),
+        };
+        self.keys.insert(certificate.metadata.serial_number.clone(), key);
+        Ok(())
+    }
+
+    /// Register the key of an X.509 certificate whose chain was validated
+    ///
+    /// The key is registered under the certificate serial number (hex) and
+    /// accepted only until the certificate expires.
+    pub fn add_x509_certificate(&mut self, certificate: &X509Certificate) {
+        let key = VerificationKey::Ed25519 {
+            public_key: certificate.public_key().clone(),
+            not_after: Some(certificate.not_after()),
+        };
+        self.keys.insert(certificate.serial_number(), key);
+    }
+
+    /// Verify a token and validate its claims at the current time
+    pub fn verify(&self, token: &str) -> CryptoResult<Claims> {
+        self.verify_at(token, unix_now())
+    }
+
+    /// Verify a token and validate its claims at a Unix timestamp
+    pub fn verify_at(&self, token: &str, now: u64) -> CryptoResult<Claims> {
+        let (header, claims, signing_input, signature) = decode_parts(token)?;
+        let key = self.find_key(header.kid.as_deref())?;
+
+        let valid = match (header.alg, key) {
+            (
+                JwtAlgorithm::EdDSA,
+                VerificationKey::Ed25519 {
+                    public_key,
+                    not_after,
+                },
+            ) => {
+                if not_after.is_some_and(|not_after| now >= not_after) {
+                    return Err(CryptoError::CertificateExpired);
+                }
+                let signature = Signature::from_slice(&signature)
+                    .map_err(|_| CryptoError::InvalidTokenFormat("bad signature".to_string()))?;
+                verify_signature(public_key, signing_input.as_bytes(), &signature)?
+            },
+            (JwtAlgorithm::HS256, VerificationKey::Hmac(key)) => {
+                let key = hmac::Key::new(hmac::HMAC_SHA256, key.as_bytes());
+                hmac::verify(&key, signing_input.as_bytes(), &signature).is_ok()
+            },
+            // Never let the header pick a different key type (algorithm confusion)
+            _ => {
+                return Err(CryptoError::TokenValidationFailed(
+                    "algorithm does not match key".to_string(),
+                ))
+            },
+        };
+        if !valid {
+            return Err(CryptoError::InvalidSignature);
+        }
+
+        let claims: Claims = serde_json::from_slice(&claims)
+            .map_err(|e| CryptoError::InvalidTokenFormat(e.to_string()))?;
+        self.validation.validate(&claims, now)?;
+        Ok(claims)
+    }
+
+    fn find_key(&self, kid: Option<&str>) -> CryptoResult<&VerificationKey> {
+        match kid {
+            Some(kid) => self.keys.get(kid).ok_or_else(|| {
+                CryptoError::TokenValidationFailed(format!("unknown key '{}'", kid))
+            }),
+            None if self.keys.len() == 1 => Ok(self.keys.values().next().unwrap()),
+            None => Err(CryptoError::TokenValidationFailed(
+                "token has no key identifier".to_string(),
+            )),
+        }
+    }
+}
+
+/// Decode the header of a token without verifying it
+pub fn decode_header(token: &str) -> CryptoResult<JwtHeader> {
+    decode_parts(token).map(|(header, ..)| header)
+}
+
+/// Split a token into header, claims bytes, signing input and signature
+fn decode_parts(token: &str) -> CryptoResult<(JwtHeader, Vec<u8>, &str, Vec<u8>)> {
+    let format_error = |message: &str| CryptoError::InvalidTokenFormat(message.to_string());
+
+    let (signing_input, signature) =
+        token.rsplit_once('.').ok_or_else(|| format_error("expected three segments"))?;
+    let (header, claims) = signing_input
+        .split_once('.')
+        .filter(|(_, claims)| !claims.contains('.'))
+        .ok_or_else(|| format_error("expected three segments"))?;
+
+    let header: JwtHeader = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(header)?)
+        .map_err(|e| CryptoError::InvalidTokenFormat(e.to_string()))?;
+    let claims = URL_SAFE_NO_PAD.decode(claims)?;
+    let signature = URL_SAFE_NO_PAD.decode(signature)?;
+    Ok((header, claims, signing_input, signature))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::certificate::{CertificateAuthority, CertificateMetadata};
+
+    fn claims() -> Claims {
+        Claims::new()
+            .with_issuer("accuscene-auth".to_string())
+            .with_subject("investigator-7".to_string())
+            .with_audience("accuscene-api".to_string())
+            .with_lifetime(Duration::from_secs(3600))
+            .with_claim("role".to_string(), serde_json::json!("reviewer"))
+    }
+
+    fn validation() -> JwtValidation {
+        JwtValidation::default()
+            .with_issuer("accuscene-auth".to_string())
+            .with_audience("accuscene-api".to_string())
+    }
+
+    #[test]
+    fn test_eddsa_roundtrip() {
+        let keypair = Ed25519KeyPair::generate().unwrap();
+        let public_key = keypair.public_key().clone();
+        let signer = JwtSigner::ed25519(keypair).with_key_id("k1".to_string());
+        let claims = claims();
+        let token = signer.sign(&claims).unwrap();
+
+        let header = decode_header(&token).unwrap();
+        assert_eq!(header.alg, JwtAlgorithm::EdDSA);
+        assert_eq!(header.kid.as_deref(), Some("k1"));
+
+        let mut verifier = JwtVerifier::new(validation());
+        verifier.add_ed25519_key("k1".to_string(), public_key);
+        let verified = verifier.verify(&token).unwrap();
+        assert_eq!(verified, claims);
+        assert_eq!(verified.custom["role"], "reviewer");
+    }
+
+    #[test]
+    fn test_hs256_and_algorithm_confusion() {
+        let key = SymmetricKey::generate().unwrap();
+        let token = JwtSigner::hs256(key.clone()).sign(&claims()).unwrap();
+
+        let mut verifier = JwtVerifier::new(validation());
+        verifier.add_hs256_key("shared".to_string(), key);
+        assert!(verifier.verify(&token).is_ok());
+
+        // An HS256 token must not verify against an Ed25519 key
+        let keypair = Ed25519KeyPair::generate().unwrap();
+        let mut eddsa_verifier = JwtVerifier::new(validation());
+        eddsa_verifier.add_ed25519_key("k1".to_string(), keypair.public_key().clone());
+        assert!(eddsa_verifier.verify(&token).is_err());
+
+        // Tampered payload
+        let (head, rest) = token.split_once('.').unwrap();
+        let (_, signature) = rest.split_once('.').unwrap();
+        let forged_claims = claims().with_subject("admin".to_string());
+        let forged = format!(
+            "{}.{}.{}",
+            head,
+            URL_SAFE_NO_PAD.encode(serde_json::to_vec(&forged_claims).unwrap()),
+            signature
+        );
+        assert!(matches!(
+            verifier.verify(&forged),
+            Err(CryptoError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_claims_validation() {
+        let key = SymmetricKey::generate().unwrap();
+        let signer = JwtSigner::hs256(key.clone());
+        let mut verifier = JwtVerifier::new(validation());
+        verifier.add_hs256_key("shared".to_string(), key);
+
+        let token = signer.sign(&claims()).unwrap();
+        let issued = claims().iat.unwrap();
+        assert!(matches!(
+            verifier.verify_at(&token, issued + 3600 + DEFAULT_LEEWAY),
+            Err(CryptoError::TokenExpired)
+        ));
+
+        let early = signer.sign(&claims().with_not_before(issued + 600)).unwrap();
+        assert!(verifier.verify_at(&early, issued).is_err());
+        assert!(verifier.verify_at(&early, issued + 600).is_ok());
+
+        let wrong_audience = signer.sign(&claims().with_audience("other".to_string())).unwrap();
+        assert!(verifier.verify(&wrong_audience).is_err());
+
+        let wrong_issuer = signer.sign(&claims().with_issuer("other".to_string())).unwrap();
+        assert!(verifier.verify(&wrong_issuer).is_err());
+
+        let no_expiry = signer
+            .sign(&Claims {
+                exp: None,
+                ..claims()
+            })
+            .unwrap();
+        assert!(verifier.verify(&no_expiry).is_err());
+    }
+
+    #[test]
+    fn test_certificate_key_ids() {
+        let ca = CertificateAuthority::generate("Test CA".to_string()).unwrap();
+        let keypair = Ed25519KeyPair::generate().unwrap();
+        let certificate = ca
+            .issue_certificate(
+                "auth-service".to_string(),
+                keypair.public_key().clone(),
+                Duration::from_secs(600),
+                CertificateMetadata::default(),
+            )
+            .unwrap();
+
+        let signer = JwtSigner::ed25519(keypair).with_certificate(&certificate).unwrap();
+        let token = signer.sign(&claims()).unwrap();
+
+        let mut verifier = JwtVerifier::new(validation());
+        verifier.add_certificate(&certificate, ca.public_key()).unwrap();
+        assert!(verifier.verify(&token).is_ok());
+        assert!(matches!(
+            verifier.verify_at(&token, certificate.expires_at),
+            Err(CryptoError::CertificateExpired)
+        ));
+
+        let other_ca = CertificateAuthority::generate("Test CA".to_string()).unwrap();
+        let mut untrusted = JwtVerifier::new(validation());
+        assert!(untrusted.add_certificate(&certificate, other_ca.public_key()).is_err());
+
+        let other_key = JwtSigner::ed25519(Ed25519KeyPair::generate().unwrap());
+        assert!(other_key.with_certificate(&certificate).is_err());
+    }
+}
@@ -1,6 +1,11 @@
 //! Secure token generation and validation
 //!
 //! Provides cryptographic token generation and validation with expiration.
+//! Standard JWTs are issued and verified by the [`jwt`] submodule.
+
+pub mod jwt;
+
+pub use self::jwt::{Claims, JwtAlgorithm, JwtSigner, JwtValidation, JwtVerifier};
 
 use crate::error::{CryptoError, CryptoResult};
 use crate::hash::sha::sha256;
@@ -1,8 +1,20 @@
 //! Secure key vault with memory protection
 //!
 //! Provides a secure in-memory vault for storing cryptographic keys.
+//! The vault master key can be wrapped by a [`KeyProvider`] so the key
+//! encryption key stays in the OS keychain, a hardware token or a KMS, and
+//! the vault can be persisted to a passphrase-protected file.
+
+pub mod persistence;
+pub mod provider;
+
+pub use self::provider::{
+    HardwareKeyProvider, HardwareToken, KeyProvider, KeychainKeyProvider, KmsKeyProvider,
+    KmsTransport, MemoryKeyProvider, WrappedKey,
+};
 
 use crate::error::{CryptoError, CryptoResult};
+use crate::secret_sharing::{combine_shares, split_secret, Share};
 use crate::secure_memory::SecureBytes;
 use crate::symmetric::aes::{decrypt_aes256gcm, encrypt_aes256gcm, EncryptedData};
 use crate::symmetric::key::SymmetricKey;
@@ -15,6 +27,8 @@ pub struct Vault {
     master_key: SymmetricKey,
     /// Encrypted entries in the vault
     entries: HashMap<String, VaultEntry>,
+    /// Master key wrapped by a key provider
+    wrapped_master_key: Option<WrappedKey>,
 }
 
 /// An entry in the vault
@@ -58,9 +72,34 @@ impl Vault {
         Self {
             master_key,
             entries: HashMap::new(),
+            wrapped_master_key: None,
         }
     }
 
+    /// Create a new vault whose random master key is wrapped by a key provider
+    ///
+    /// Persist [`Vault::wrapped_master_key`] to reopen the vault later.
+    pub fn with_key_provider(provider: &dyn KeyProvider) -> CryptoResult<Self> {
+        let master_key = SymmetricKey::generate()?;
+        let wrapped = provider.wrap_key(&master_key)?;
+        let mut vault = Self::new(master_key);
+        vault.wrapped_master_key = Some(wrapped);
+        Ok(vault)
+    }
+
+    /// Open an empty vault by unwrapping its master key with a key provider
+    ///
+    /// Use [`Vault::import`] afterwards to restore exported entries.
+    pub fn open_with_key_provider(
+        provider: &dyn KeyProvider,
+        wrapped: &WrappedKey,
+    ) -> CryptoResult<Self> {
+        let master_key = provider.unwrap_key(wrapped)?;
+        let mut vault = Self::new(master_key);
+        vault.wrapped_master_key = Some(wrapped.clone());
+        Ok(vault)
+    }
+
     /// Create a new vault with a randomly generated master key
     pub fn generate() -> CryptoResult<Self> {
         let master_key = SymmetricKey::generate()?;
@@ -173,10 +212,43 @@ impl Vault {
         Ok(())
     }
 
+    /// Get the master key wrapped by the vault's key provider, if any
+    pub fn wrapped_master_key(&self) -> Option<&WrappedKey> {
+        self.wrapped_master_key.as_ref()
+    }
+
+    /// Wrap the master key with another key provider, e.g. to rotate the
+    /// key encryption key or migrate between backends
+    pub fn rewrap_master_key(&mut self, provider: &dyn KeyProvider) -> CryptoResult<&WrappedKey> {
+        let wrapped = provider.wrap_key(&self.master_key)?;
+        Ok(self.wrapped_master_key.insert(wrapped))
+    }
+
     /// Get the master key (use with caution!)
     pub fn master_key(&self) -> &SymmetricKey {
         &self.master_key
     }
+
+    /// Export the master key (KEK) as `shares` escrow shares, any
+    /// `threshold` of which recover it
+    pub fn export_master_key_shares(&self, threshold: u8, shares: u8) -> CryptoResult<Vec<Share>> {
+        split_secret(self.master_key.as_bytes(), threshold, shares)
+    }
+
+    /// Create an empty vault with a master key recovered from escrow shares
+    ///
+    /// Use [`Vault::import`] afterwards to restore exported entries.
+    pub fn from_master_key_shares(shares: &[Share]) -> CryptoResult<Self> {
+        let master_key = Self::recover_master_key(shares)?;
+        Ok(Self::new(master_key))
+    }
+
+    /// Recover a master key from escrow shares
+    pub fn recover_master_key(shares: &[Share]) -> CryptoResult<SymmetricKey> {
+        let secret = combine_shares(shares)?;
+        SymmetricKey::from_bytes(secret.as_bytes())
+            .map_err(|e| CryptoError::VaultError(format!("recovered master key is invalid: {}", e)))
+    }
 }
 
 impl Drop for Vault {
@@ -340,6 +412,42 @@ mod tests {
         assert!(vault2.contains("key2"));
     }
 
+    #[test]
+    fn test_vault_master_key_escrow() {
+        let mut vault1 = Vault::generate().unwrap();
+        vault1.store("key1".to_string(), b"secret1").unwrap();
+        let exported = vault1.export().unwrap();
+
+        let shares = vault1.export_master_key_shares(2, 3).unwrap();
+        assert_eq!(shares.len(), 3);
+
+        let mut vault2 = Vault::from_master_key_shares(&shares[1..]).unwrap();
+        assert!(vault2.master_key().as_bytes() == vault1.master_key().as_bytes());
+        vault2.import(&exported).unwrap();
+        assert_eq!(vault2.retrieve("key1").unwrap().as_bytes(), b"secret1");
+
+        assert!(Vault::from_master_key_shares(&shares[..1]).is_err());
+    }
+
+    #[test]
+    fn test_vault_key_provider() {
+        let provider = MemoryKeyProvider::generate("vault-kek".to_string()).unwrap();
+        let mut vault = Vault::with_key_provider(&provider).unwrap();
+        vault.store("api_key".to_string(), b"secret123").unwrap();
+        let exported = vault.export().unwrap();
+        let wrapped = vault.wrapped_master_key().unwrap().clone();
+
+        let mut reopened = Vault::open_with_key_provider(&provider, &wrapped).unwrap();
+        reopened.import(&exported).unwrap();
+        assert_eq!(reopened.retrieve("api_key").unwrap().as_bytes(), b"secret123");
+
+        // Rotate to a new KEK; the old wrapped key no longer opens with it
+        let rotated = MemoryKeyProvider::generate("vault-kek-2".to_string()).unwrap();
+        let rewrapped = reopened.rewrap_master_key(&rotated).unwrap().clone();
+        assert!(Vault::open_with_key_provider(&rotated, &wrapped).is_err());
+        assert!(Vault::open_with_key_provider(&rotated, &rewrapped).is_ok());
+    }
+
     #[test]
     fn test_vault_last_accessed_updated() {
         let mut vault = Vault::generate().unwrap();
@@ -0,0 +1,312 @@
+//! Passphrase-protected vault files
+//!
+//! File layout:
+//! - Magic `ACSVAULT` (8 bytes)
+//! - Format version (u16, big-endian)
+//! - Header length (u32, big-endian)
+//! - JSON header: Argon2id parameters, salt and AES-GCM nonce
+//! - AES-256-GCM ciphertext of the vault contents
+//!
+//! The encryption key is derived from the passphrase with Argon2id. The
+//! magic, version and header are authenticated as associated data, so any
+//! modification of the file is detected on load.
+
+use super::{Vault, VaultEntry, WrappedKey};
+use crate::error::{CryptoError, CryptoResult};
+use crate::hash::password::Argon2Params;
+use crate::random::generate_random_bytes;
+use crate::secure_memory::SecureBytes;
+use crate::symmetric::aes::{decrypt_aes256gcm, EncryptedData};
+use crate::symmetric::key::SymmetricKey;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// File signature
+const MAGIC: &[u8; 8] = b"ACSVAULT";
+
+/// Current file format version
+const FORMAT_VERSION: u16 = 1;
+
+/// Length of the fixed prefix (magic, version, header length)
+const PREFIX_LEN: usize = 14;
+
+/// Maximum accepted header length
+const MAX_HEADER_LEN: usize = 4096;
+
+/// Salt length for key derivation
+const SALT_LEN: usize = 16;
+
+/// Upper bounds on KDF parameters accepted from a file (memory in KiB)
+const MAX_MEMORY_COST: u32 = 2 * 1024 * 1024;
+const MAX_TIME_COST: u32 = 64;
+const MAX_PARALLELISM: u32 = 16;
+
+/// Unencrypted file header
+#[derive(Serialize, Deserialize)]
+struct FileHeader {
+    kdf: String,
+    memory_cost: u32,
+    time_cost: u32,
+    parallelism: u32,
+    salt: String,
+    cipher: String,
+    nonce: String,
+}
+
+/// Encrypted vault contents
+#[derive(Serialize, Deserialize)]
+struct FileContents {
+    master_key: String,
+    entries: HashMap<String, VaultEntry>,
+    wrapped_master_key: Option<WrappedKey>,
+}
+
+impl Vault {
+    /// Save the vault to a file encrypted with a passphrase
+    ///
+    /// The file is written atomically: a crash leaves either the previous
+    /// file or the new one.
+    pub fn save_encrypted<P: AsRef<Path>>(&self, path: P, passphrase: &str) -> CryptoResult<()> {
+        self.save_encrypted_with_params(path, passphrase, &Argon2Params::default())
+    }
+
+    /// Save the vault with custom Argon2id parameters
+    pub fn save_encrypted_with_params<P: AsRef<Path>>(
+        &self,
+        path: P,
+        passphrase: &str,
+        params: &Argon2Params,
+    ) -> CryptoResult<()> {
+        let salt = generate_random_bytes(SALT_LEN)?;
+        let key = derive_file_key(passphrase, salt.as_bytes(), params)?;
+
+        let contents = FileContents {
+            master_key: self.master_key.to_base64(),
+            entries: self.entries.clone(),
+            wrapped_master_key: self.wrapped_master_key.clone(),
+        };
+        let plaintext = SecureBytes::new(serde_json::to_vec(&contents)?);
+        drop(contents);
+
+        // The nonce is chosen up front so the header carrying it can be
+        // authenticated as associated data
+        let nonce = generate_random_bytes(12)?;
+        let header = FileHeader {
+            kdf: "argon2id".to_string(),
+            memory_cost: params.memory_cost,
+            time_cost: params.time_cost,
+            parallelism: params.parallelism,
+            salt: hex::encode(salt.as_bytes()),
+            cipher: "aes-256-gcm".to_string(),
+            nonce: hex::encode(nonce.as_bytes()),
+        };
+        let header = serde_json::to_vec(&header)?;
+        let prefix = file_prefix(&header)?;
+
+        let encrypted = encrypt_with_nonce(&key, nonce.as_bytes(), plaintext.as_bytes(), &prefix)?;
+        let mut file = prefix;
+        file.extend_from_slice(&encrypted);
+
+        write_atomically(path.as_ref(), &file)
+    }
+
+    /// Load a vault from a file encrypted with a passphrase
+    pub fn load_encrypted<P: AsRef<Path>>(path: P, passphrase: &str) -> CryptoResult<Self> {
+        let file = std::fs::read(path)?;
+        let corrupt =
+            |message: &str| CryptoError::VaultError(format!("invalid vault file: {}", message));
+
+        if file.len() < PREFIX_LEN || &file[..8] != MAGIC {
+            return Err(corrupt("not a vault file"));
+        }
+        let version = u16::from_be_bytes([file[8], file[9]]);
+        if version != FORMAT_VERSION {
+            return Err(corrupt(&format!("unsupported format version {}", version)));
+        }
+        let header_len = u32::from_be_bytes([file[10], file[11], file[12], file[13]]) as usize;
+        if header_len > MAX_HEADER_LEN || file.len() < PREFIX_LEN + header_len {
+            return Err(corrupt("truncated header"));
+        }
+
+        let (prefix, ciphertext) = file.split_at(PREFIX_LEN + header_len);
+        let header: FileHeader =
+            serde_json::from_slice(&prefix[PREFIX_LEN..]).map_err(|e| corrupt(&e.to_string()))?;
+        if header.kdf != "argon2id" || header.cipher != "aes-256-gcm" {
+            return Err(corrupt("unsupported algorithms"));
+        }
+        if header.memory_cost > MAX_MEMORY_COST
+            || header.time_cost > MAX_TIME_COST
+            || header.parallelism > MAX_PARALLELISM
+        {
+            return Err(corrupt("key derivation parameters out of range"));
+        }
+
+        let params = Argon2Params {
+            memory_cost: header.memory_cost,
+            time_cost: header.time_cost,
+            parallelism: header.parallelism,
+        };
+        let salt = hex::decode(&header.salt).map_err(|e| corrupt(&e.to_string()))?;
+        let key = derive_file_key(passphrase, &salt, &params)?;
+
+        let encrypted = EncryptedData {
+            nonce: hex::decode(&header.nonce).map_err(|e| corrupt(&e.to_string()))?,
+            ciphertext: ciphertext.to_vec(),
+            algorithm: header.cipher,
+        };
+        let plaintext = decrypt_aes256gcm(&key, &encrypted, Some(prefix)).map_err(|_| {
+            CryptoError::VaultError("wrong passphrase or corrupted vault file".to_string())
+        })?;
+
+        let contents: FileContents = serde_json::from_slice(plaintext.as_bytes())?;
+        let master_key = SymmetricKey::from_base64(&contents.master_key)?;
+        let mut vault = Vault::new(master_key);
+        vault.entries = contents.entries;
+        vault.wrapped_master_key = contents.wrapped_master_key;
+        Ok(vault)
+    }
+}
+
+/// Derive the file encryption key from a passphrase with Argon2id
+fn derive_file_key(
+    passphrase: &str,
+    salt: &[u8],
+    params: &Argon2Params,
+) -> CryptoResult<SymmetricKey> {
+    let params = argon2::Params::new(
+        params.memory_cost,
+        params.time_cost,
+        params.parallelism,
+        Some(32),
+    )?;
+    let argon2 = argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+
+    let mut key = SecureBytes::zeros(32);
+    argon2.hash_password_into(passphrase.as_bytes(), salt, key.as_bytes_mut())?;
+    SymmetricKey::from_bytes(key.as_bytes())
+}
+
+/// Magic, version, header length and header
+fn file_prefix(header: &[u8]) -> CryptoResult<Vec<u8>> {
+    if header.len() > MAX_HEADER_LEN {
+        return Err(CryptoError::VaultError(
+            "vault file header too large".to_string(),
+        ));
+    }
+    let mut prefix = Vec::with_capacity(PREFIX_LEN + header.len());
+    prefix.extend_from_slice(MAGIC);
+    prefix.extend_from_slice(&FORMAT_VERSION.to_be_bytes());
+    prefix.extend_from_slice(&(header.len() as u32).to_be_bytes());
+    prefix.extend_from_slice(header);
+    Ok(prefix)
+}
+
+/// AES-256-GCM with a caller-chosen nonce, returning ciphertext and tag
+fn encrypt_with_nonce(
+    key: &SymmetricKey,
+    nonce: &[u8],
+    plaintext: &[u8],
+    associated_data: &[u8],
+) -> CryptoResult<Vec<u8>> {
+    use aes_gcm::aead::{Aead, KeyInit, Payload};
+
+    let cipher = aes_gcm::Aes256Gcm::new_from_slice(key.as_bytes())
+        .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
+    let payload = Payload {
+        msg: plaintext,
+        aad: associated_data,
+    };
+    cipher
+        .encrypt(aes_gcm::Nonce::from_slice(nonce), payload)
+        .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))
+}
+
+/// Write a file through a temporary file and rename
+fn write_atomically(path: &Path, contents: &[u8]) -> CryptoResult<()> {
+    use std::io::Write;
+
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let mut file = std::fs::File::create(&temp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+
+    std::fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fast parameters for tests
+    fn params() -> Argon2Params {
+        Argon2Params {
+            memory_cost: 256,
+            time_cost: 1,
+            parallelism: 1,
+        }
+    }
+
+    #[test]
+    fn test_save_and_load_encrypted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keys.vault");
+
+        let mut vault = Vault::generate().unwrap();
+        vault.store("api_key".to_string(), b"secret123").unwrap();
+        vault.save_encrypted_with_params(&path, "correct horse", &params()).unwrap();
+
+        let file = std::fs::read(&path).unwrap();
+        assert_eq!(&file[..8], MAGIC);
+        assert!(!file.windows(9).any(|w| w == b"secret123"));
+
+        let mut loaded = Vault::load_encrypted(&path, "correct horse").unwrap();
+        assert_eq!(loaded.retrieve("api_key").unwrap().as_bytes(), b"secret123");
+        assert_eq!(
+            loaded.master_key().as_bytes(),
+            vault.master_key().as_bytes()
+        );
+
+        assert!(Vault::load_encrypted(&path, "wrong horse").is_err());
+    }
+
+    #[test]
+    fn test_load_detects_tampering() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keys.vault");
+
+        let mut vault = Vault::generate().unwrap();
+        vault.store("api_key".to_string(), b"secret123").unwrap();
+        vault.save_encrypted_with_params(&path, "passphrase", &params()).unwrap();
+        let original = std::fs::read(&path).unwrap();
+
+        // Flip a ciphertext byte
+        let mut tampered = original.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        std::fs::write(&path, &tampered).unwrap();
+        assert!(Vault::load_encrypted(&path, "passphrase").is_err());
+
+        // Weaken the KDF parameters in the header
+        let mut tampered = original.clone();
+        let field = b"\"time_cost\":1";
+        let position = tampered.windows(field.len()).position(|w| w == field).unwrap();
+        tampered[position + field.len() - 1] = b'2';
+        std::fs::write(&path, &tampered).unwrap();
+        assert!(Vault::load_encrypted(&path, "passphrase").is_err());
+
+        // Unknown version
+        let mut tampered = original.clone();
+        tampered[9] = 2;
+        std::fs::write(&path, &tampered).unwrap();
+        assert!(Vault::load_encrypted(&path, "passphrase").is_err());
+
+        std::fs::write(&path, &original[..20]).unwrap();
+        assert!(Vault::load_encrypted(&path, "passphrase").is_err());
+    }
+}
@@ -0,0 +1,580 @@
+//! Key providers for vault master keys
+//!
+//! A [`KeyProvider`] holds the key encryption key (KEK) and wraps the vault
+//! master key with it, so the KEK itself never has to live in process memory.
+//! Only the wrapped master key is persisted alongside the vault.
+//!
+//! Backends:
+//! - [`MemoryKeyProvider`] - KEK in process memory (development and tests)
+//! - [`KeychainKeyProvider`] - KEK in the OS keychain, loaded per operation
+//! - [`HardwareKeyProvider`] - KEK inside a PKCS#11 token or TPM
+//! - [`KmsKeyProvider`] - KEK in a cloud KMS reached over HTTP
+
+use crate::error::{CryptoError, CryptoResult};
+use crate::secure_memory::SecureBytes;
+use crate::symmetric::aes::{decrypt_aes256gcm, encrypt_aes256gcm, EncryptedData};
+use crate::symmetric::key::SymmetricKey;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+
+/// AES-GCM nonce length used by locally wrapped keys
+const NONCE_LEN: usize = 12;
+
+/// Source of a key encryption key that wraps and unwraps vault master keys
+pub trait KeyProvider: Send + Sync {
+    /// Backend name recorded in wrapped keys
+    fn name(&self) -> &str;
+
+    /// Identifier of the key encryption key
+    fn key_id(&self) -> &str;
+
+    /// Wrap a master key with the key encryption key
+    fn wrap_key(&self, key: &SymmetricKey) -> CryptoResult<WrappedKey>;
+
+    /// Unwrap a master key wrapped by this provider
+    fn unwrap_key(&self, wrapped: &WrappedKey) -> CryptoResult<SymmetricKey>;
+}
+
+/// A master key wrapped by a key provider
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WrappedKey {
+    /// Name of the provider backend
+    pub provider: String,
+    /// Identifier of the key encryption key
+    pub key_id: String,
+    /// Wrapped key material
+    pub ciphertext: Vec<u8>,
+}
+
+impl WrappedKey {
+    fn new(provider: &dyn KeyProvider, ciphertext: Vec<u8>) -> Self {
+        Self {
+            provider: provider.name().to_string(),
+            key_id: provider.key_id().to_string(),
+            ciphertext,
+        }
+    }
+
+    /// Check that the key was wrapped by the given provider
+    fn check(&self, provider: &dyn KeyProvider) -> CryptoResult<()> {
+        if self.provider != provider.name() || self.key_id != provider.key_id() {
+            return Err(CryptoError::KeyProviderFailed(format!(
+                "key wrapped by {}:{} cannot be unwrapped by {}:{}",
+                self.provider,
+                self.key_id,
+                provider.name(),
+                provider.key_id()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Associated data binding a wrapped key to its KEK
+fn wrap_context(key_id: &str) -> Vec<u8> {
+    format!("accuscene.vault.kek:{}", key_id).into_bytes()
+}
+
+/// Wrap a key with a locally available KEK (AES-256-GCM)
+fn seal(kek: &SymmetricKey, key_id: &str, key: &SymmetricKey) -> CryptoResult<Vec<u8>> {
+    let encrypted = encrypt_aes256gcm(kek, key.as_bytes(), Some(&wrap_context(key_id)))?;
+    let mut sealed = encrypted.nonce;
+    sealed.extend_from_slice(&encrypted.ciphertext);
+    Ok(sealed)
+}
+
+/// Unwrap a key sealed with a locally available KEK
+fn open(kek: &SymmetricKey, key_id: &str, sealed: &[u8]) -> CryptoResult<SymmetricKey> {
+    if sealed.len() <= NONCE_LEN {
+        return Err(CryptoError::KeyProviderFailed(
+            "wrapped key is truncated".to_string(),
+        ));
+    }
+    let encrypted = EncryptedData {
+        nonce: sealed[..NONCE_LEN].to_vec(),
+        ciphertext: sealed[NONCE_LEN..].to_vec(),
+        algorithm: "AES-256-GCM".to_string(),
+    };
+    let key = decrypt_aes256gcm(kek, &encrypted, Some(&wrap_context(key_id)))?;
+    SymmetricKey::from_bytes(key.as_bytes())
+}
+
+/// Key provider holding the KEK in process memory
+///
+/// Matches the behavior of a vault created with [`Vault::new`](super::Vault::new)
+/// and is intended for development and tests.
+pub struct MemoryKeyProvider {
+    key_id: String,
+    kek: SymmetricKey,
+}
+
+impl MemoryKeyProvider {
+    /// Create a provider from an existing KEK
+    pub fn new(key_id: String, kek: SymmetricKey) -> Self {
+        Self { key_id, kek }
+    }
+
+    /// Create a provider with a randomly generated KEK
+    pub fn generate(key_id: String) -> CryptoResult<Self> {
+        Ok(Self::new(key_id, SymmetricKey::generate()?))
+    }
+}
+
+impl KeyProvider for MemoryKeyProvider {
+    fn name(&self) -> &str {
+        "memory"
+    }
+
+    fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    fn wrap_key(&self, key: &SymmetricKey) -> CryptoResult<WrappedKey> {
+        Ok(WrappedKey::new(self, seal(&self.kek, &self.key_id, key)?))
+    }
+
+    fn unwrap_key(&self, wrapped: &WrappedKey) -> CryptoResult<SymmetricKey> {
+        wrapped.check(self)?;
+        open(&self.kek, &self.key_id, &wrapped.ciphertext)
+    }
+}
+
+impl std::fmt::Debug for MemoryKeyProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoryKeyProvider")
+            .field("key_id", &self.key_id)
+            .field("kek", &"[REDACTED]")
+            .finish()
+    }
+}
+
+/// Key provider storing the KEK in the OS keychain
+///
+/// The KEK is read from the keychain for each wrap or unwrap and zeroized
+/// immediately afterwards. Uses the `security` tool on macOS and
+/// `secret-tool` (Secret Service) on Linux.
+#[derive(Clone, Debug)]
+pub struct KeychainKeyProvider {
+    service: String,
+    account: String,
+    key_id: String,
+}
+
+impl KeychainKeyProvider {
+    /// Create a provider for a keychain item
+    pub fn new(service: String, account: String) -> Self {
+        let key_id = format!("{}/{}", service, account);
+        Self {
+            service,
+            account,
+            key_id,
+        }
+    }
+
+    /// Create a random KEK in the keychain unless one exists
+    pub fn ensure_key(&self) -> CryptoResult<()> {
+        if self.load_kek().is_ok() {
+            return Ok(());
+        }
+        let kek = SymmetricKey::generate()?;
+        self.store_secret(&SecureBytes::new(
+            STANDARD.encode(kek.as_bytes()).into_bytes(),
+        ))
+    }
+
+    fn load_kek(&self) -> CryptoResult<SymmetricKey> {
+        let secret = self.read_secret()?;
+        let encoded = std::str::from_utf8(secret.as_bytes())
+            .map_err(|e| CryptoError::KeyProviderFailed(e.to_string()))?;
+        let bytes = SecureBytes::new(STANDARD.decode(encoded.trim())?);
+        SymmetricKey::from_bytes(bytes.as_bytes())
+    }
+
+    #[cfg(target_os = "macos")]
+    fn read_secret(&self) -> CryptoResult<SecureBytes> {
+        let mut command = std::process::Command::new("security");
+        command.args([
+            "find-generic-password",
+            "-s",
+            &self.service,
+            "-a",
+            &self.account,
+            "-w",
+        ]);
+        run_keychain_command(command, None)
+    }
+
+    #[cfg(target_os = "macos")]
+    fn store_secret(&self, secret: &SecureBytes) -> CryptoResult<()> {
+        let encoded = std::str::from_utf8(secret.as_bytes())
+            .map_err(|e| CryptoError::KeyProviderFailed(e.to_string()))?;
+        let mut command = std::process::Command::new("security");
+        command.args([
+            "add-generic-password",
+            "-U",
+            "-s",
+            &self.service,
+            "-a",
+            &self.account,
+        ]);
+        command.args(["-w", encoded]);
+        run_keychain_command(command, None).map(|_| ())
+    }
+
+    #[cfg(target_os = "linux")]
+    fn read_secret(&self) -> CryptoResult<SecureBytes> {
+        let mut command = std::process::Command::new("secret-tool");
+        command.args(["lookup", "service", &self.service, "account", &self.account]);
+        run_keychain_command(command, None)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn store_secret(&self, secret: &SecureBytes) -> CryptoResult<()> {
+        let mut command = std::process::Command::new("secret-tool");
+        command.args(["store", "--label", "AccuScene vault KEK"]);
+        command.args(["service", &self.service, "account", &self.account]);
+        run_keychain_command(command, Some(secret)).map(|_| ())
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    fn read_secret(&self) -> CryptoResult<SecureBytes> {
+        Err(CryptoError::KeyProviderFailed(
+            "OS keychain is not supported on this platform".to_string(),
+        ))
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    fn store_secret(&self, _secret: &SecureBytes) -> CryptoResult<()> {
+        Err(CryptoError::KeyProviderFailed(
+            "OS keychain is not supported on this platform".to_string(),
+        ))
+    }
+}
+
+/// Run a keychain tool, optionally writing a secret to its stdin
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn run_keychain_command(
+    mut command: std::process::Command,
+    stdin: Option<&SecureBytes>,
+) -> CryptoResult<SecureBytes> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let failed = |e: std::io::Error| CryptoError::KeyProviderFailed(e.to_string());
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(failed)?;
+    if let (Some(secret), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        pipe.write_all(secret.as_bytes()).map_err(failed)?;
+    }
+    let output = child.wait_with_output().map_err(failed)?;
+    let stdout = SecureBytes::new(output.stdout);
+
+    if !output.status.success() {
+        return Err(CryptoError::KeyProviderFailed(format!(
+            "keychain command failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(stdout)
+}
+
+impl KeyProvider for KeychainKeyProvider {
+    fn name(&self) -> &str {
+        "keychain"
+    }
+
+    fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    fn wrap_key(&self, key: &SymmetricKey) -> CryptoResult<WrappedKey> {
+        let kek = self.load_kek()?;
+        Ok(WrappedKey::new(self, seal(&kek, &self.key_id, key)?))
+    }
+
+    fn unwrap_key(&self, wrapped: &WrappedKey) -> CryptoResult<SymmetricKey> {
+        wrapped.check(self)?;
+        let kek = self.load_kek()?;
+        open(&kek, &self.key_id, &wrapped.ciphertext)
+    }
+}
+
+/// Session with a hardware token that wraps keys without exporting its KEK
+///
+/// Implement this over a PKCS#11 session (e.g. `CKM_AES_KEY_WRAP_PAD` with
+/// `cryptoki`) or a TPM context (e.g. sealing with `tss-esapi`).
+pub trait HardwareToken: Send + Sync {
+    /// Wrap key material with the token key identified by `key_label`
+    fn wrap(&self, key_label: &str, plaintext: &[u8]) -> CryptoResult<Vec<u8>>;
+
+    /// Unwrap key material with the token key identified by `key_label`
+    fn unwrap(&self, key_label: &str, ciphertext: &[u8]) -> CryptoResult<SecureBytes>;
+}
+
+/// Key provider backed by a PKCS#11 token or TPM
+pub struct HardwareKeyProvider<T: HardwareToken> {
+    token: T,
+    key_label: String,
+    backend: &'static str,
+}
+
+impl<T: HardwareToken> HardwareKeyProvider<T> {
+    /// Create a provider for a key on a PKCS#11 token
+    pub fn pkcs11(token: T, key_label: String) -> Self {
+        Self {
+            token,
+            key_label,
+            backend: "pkcs11",
+        }
+    }
+
+    /// Create a provider for a key in a TPM
+    pub fn tpm(token: T, key_label: String) -> Self {
+        Self {
+            token,
+            key_label,
+            backend: "tpm",
+        }
+    }
+
+    /// Get the underlying token
+    pub fn token(&self) -> &T {
+        &self.token
+    }
+}
+
+impl<T: HardwareToken> KeyProvider for HardwareKeyProvider<T> {
+    fn name(&self) -> &str {
+        self.backend
+    }
+
+    fn key_id(&self) -> &str {
+        &self.key_label
+    }
+
+    fn wrap_key(&self, key: &SymmetricKey) -> CryptoResult<WrappedKey> {
+        let ciphertext = self.token.wrap(&self.key_label, key.as_bytes())?;
+        Ok(WrappedKey::new(self, ciphertext))
+    }
+
+    fn unwrap_key(&self, wrapped: &WrappedKey) -> CryptoResult<SymmetricKey> {
+        wrapped.check(self)?;
+        let key = self.token.unwrap(&self.key_label, &wrapped.ciphertext)?;
+        SymmetricKey::from_bytes(key.as_bytes())
+    }
+}
+
+/// HTTP client used by [`KmsKeyProvider`]
+///
+/// Implementations must use TLS; the master key travels in the request body.
+pub trait KmsTransport: Send + Sync {
+    /// POST a JSON body and return the JSON response body
+    fn post_json(&self, url: &str, bearer_token: Option<&str>, body: &str) -> CryptoResult<String>;
+}
+
+#[derive(Serialize)]
+struct KmsWrapRequest<'a> {
+    plaintext: String,
+    context: &'a str,
+}
+
+#[derive(Deserialize)]
+struct KmsWrapResponse {
+    ciphertext: String,
+}
+
+#[derive(Serialize)]
+struct KmsUnwrapRequest<'a> {
+    ciphertext: String,
+    context: &'a str,
+}
+
+#[derive(Deserialize)]
+struct KmsUnwrapResponse {
+    plaintext: String,
+}
+
+/// Key provider backed by a cloud KMS
+///
+/// Calls `POST {endpoint}/v1/keys/{key_id}/wrap` and `.../unwrap` with
+/// base64-encoded key material and an encryption context.
+pub struct KmsKeyProvider<T: KmsTransport> {
+    transport: T,
+    endpoint: String,
+    key_id: String,
+    bearer_token: Option<String>,
+    context: String,
+}
+
+impl<T: KmsTransport> KmsKeyProvider<T> {
+    /// Create a provider for a KMS key
+    pub fn new(transport: T, endpoint: String, key_id: String) -> Self {
+        let context = String::from_utf8_lossy(&wrap_context(&key_id)).into_owned();
+        Self {
+            transport,
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            key_id,
+            bearer_token: None,
+            context,
+        }
+    }
+
+    /// Set the bearer token for authentication
+    pub fn with_bearer_token(mut self, token: String) -> Self {
+        self.bearer_token = Some(token);
+        self
+    }
+
+    /// Set the encryption context bound to wrapped keys
+    pub fn with_context(mut self, context: String) -> Self {
+        self.context = context;
+        self
+    }
+
+    fn call(&self, operation: &str, body: &str) -> CryptoResult<String> {
+        let url = format!("{}/v1/keys/{}/{}", self.endpoint, self.key_id, operation);
+        self.transport.post_json(&url, self.bearer_token.as_deref(), body)
+    }
+}
+
+impl<T: KmsTransport> KeyProvider for KmsKeyProvider<T> {
+    fn name(&self) -> &str {
+        "kms"
+    }
+
+    fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    fn wrap_key(&self, key: &SymmetricKey) -> CryptoResult<WrappedKey> {
+        let request = KmsWrapRequest {
+            plaintext: STANDARD.encode(key.as_bytes()),
+            context: &self.context,
+        };
+        let body = SecureBytes::new(serde_json::to_vec(&request)?);
+        let body = std::str::from_utf8(body.as_bytes())
+            .map_err(|e| CryptoError::KeyProviderFailed(e.to_string()))?;
+
+        let response: KmsWrapResponse = serde_json::from_str(&self.call("wrap", body)?)?;
+        Ok(WrappedKey::new(self, STANDARD.decode(response.ciphertext)?))
+    }
+
+    fn unwrap_key(&self, wrapped: &WrappedKey) -> CryptoResult<SymmetricKey> {
+        wrapped.check(self)?;
+        let request = KmsUnwrapRequest {
+            ciphertext: STANDARD.encode(&wrapped.ciphertext),
+            context: &self.context,
+        };
+        let body = serde_json::to_string(&request)?;
+
+        let response = SecureBytes::new(self.call("unwrap", &body)?.into_bytes());
+        let response: KmsUnwrapResponse = serde_json::from_slice(response.as_bytes())?;
+        let key = SecureBytes::new(STANDARD.decode(&response.plaintext)?);
+        SymmetricKey::from_bytes(key.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Token that XORs with a fixed pad, standing in for a PKCS#11 session
+    struct FakeToken;
+
+    impl HardwareToken for FakeToken {
+        fn wrap(&self, key_label: &str, plaintext: &[u8]) -> CryptoResult<Vec<u8>> {
+            let pad = crate::hash::sha256(key_label.as_bytes());
+            Ok(plaintext.iter().zip(pad.iter().cycle()).map(|(a, b)| a ^ b).collect())
+        }
+
+        fn unwrap(&self, key_label: &str, ciphertext: &[u8]) -> CryptoResult<SecureBytes> {
+            Ok(SecureBytes::new(self.wrap(key_label, ciphertext)?))
+        }
+    }
+
+    /// KMS server that keeps its KEK to itself
+    struct FakeKms {
+        kek: SymmetricKey,
+    }
+
+    impl KmsTransport for FakeKms {
+        fn post_json(
+            &self,
+            url: &str,
+            bearer_token: Option<&str>,
+            body: &str,
+        ) -> CryptoResult<String> {
+            assert_eq!(bearer_token, Some("secret-token"));
+            let request: serde_json::Value = serde_json::from_str(body)?;
+            let context = request["context"].as_str().unwrap();
+
+            if url == "https://kms.example.com/v1/keys/vault-kek/wrap" {
+                let plaintext = STANDARD.decode(request["plaintext"].as_str().unwrap())?;
+                let key = SymmetricKey::from_bytes(&plaintext)?;
+                let sealed = seal(&self.kek, context, &key)?;
+                Ok(serde_json::json!({ "ciphertext": STANDARD.encode(sealed) }).to_string())
+            } else {
+                let sealed = STANDARD.decode(request["ciphertext"].as_str().unwrap())?;
+                let key = open(&self.kek, context, &sealed)?;
+                Ok(serde_json::json!({ "plaintext": STANDARD.encode(key.as_bytes()) }).to_string())
+            }
+        }
+    }
+
+    fn roundtrip(provider: &dyn KeyProvider) {
+        let key = SymmetricKey::generate().unwrap();
+        let wrapped = provider.wrap_key(&key).unwrap();
+        assert_ne!(wrapped.ciphertext.as_slice(), key.as_bytes().as_slice());
+        assert_eq!(wrapped.provider, provider.name());
+
+        let unwrapped = provider.unwrap_key(&wrapped).unwrap();
+        assert_eq!(unwrapped.as_bytes(), key.as_bytes());
+    }
+
+    #[test]
+    fn test_memory_provider() {
+        let provider = MemoryKeyProvider::generate("dev".to_string()).unwrap();
+        roundtrip(&provider);
+
+        let key = SymmetricKey::generate().unwrap();
+        let mut wrapped = provider.wrap_key(&key).unwrap();
+        let other = MemoryKeyProvider::generate("dev".to_string()).unwrap();
+        assert!(other.unwrap_key(&wrapped).is_err());
+
+        let last = wrapped.ciphertext.len() - 1;
+        wrapped.ciphertext[last] ^= 1;
+        assert!(provider.unwrap_key(&wrapped).is_err());
+    }
+
+    #[test]
+    fn test_hardware_provider() {
+        roundtrip(&HardwareKeyProvider::pkcs11(
+            FakeToken,
+            "vault-kek".to_string(),
+        ));
+
+        let pkcs11 = HardwareKeyProvider::pkcs11(FakeToken, "vault-kek".to_string());
+        let tpm = HardwareKeyProvider::tpm(FakeToken, "vault-kek".to_string());
+        let wrapped = pkcs11.wrap_key(&SymmetricKey::generate().unwrap()).unwrap();
+        assert!(tpm.unwrap_key(&wrapped).is_err());
+    }
+
+    #[test]
+    fn test_kms_provider() {
+        let kms = FakeKms {
+            kek: SymmetricKey::generate().unwrap(),
+        };
+        let provider = KmsKeyProvider::new(
+            kms,
+            "https://kms.example.com/".to_string(),
+            "vault-kek".to_string(),
+        )
+        .with_bearer_token("secret-token".to_string());
+        roundtrip(&provider);
+    }
+}
@@ -0,0 +1,197 @@
+//! Async database backend on sqlx and tokio
+//!
+//! An alternative to the blocking r2d2/rusqlite pool for tokio services.
+//! [`AsyncDatabasePool`] opens the same SQLite database through sqlx, applies
+//! the shared [`MigrationRegistry`](crate::migrations::MigrationRegistry)
+//! and runs [`QueryBuilder`] queries. Repositories expose async versions of
+//! their operations through [`AsyncRepository`].
+//!
+//! Enabled by the `async` feature.
+
+pub mod repositories;
+
+pub use self::repositories::{AsyncRepository, FromSqliteRow};
+
+use crate::config::{AutoVacuum, DatabaseConfig, JournalMode, SynchronousMode};
+use crate::encryption::{self, DatabaseKey};
+use crate::error::{DatabaseError, DbResult};
+use crate::migrations::runner::MigrationRunner;
+use crate::query::QueryBuilder;
+use sqlx::sqlite::{
+    SqliteAutoVacuum, SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions,
+    SqliteSynchronous,
+};
+use std::str::FromStr;
+use tracing::info;
+
+/// Async connection pool for SQLite database
+#[derive(Clone)]
+pub struct AsyncDatabasePool {
+    pool: SqlitePool,
+}
+
+impl AsyncDatabasePool {
+    /// Create a new async database pool
+    pub async fn new(config: &DatabaseConfig) -> DbResult<Self> {
+        config.validate()?;
+
+        info!(
+            "Creating async database pool for '{}' with max_size={}",
+            config.url, config.pool.max_size
+        );
+
+        let key = if config.encryption.enabled {
+            Some(encryption::prepare_database(config)?)
+        } else {
+            None
+        };
+
+        // Every connection to ":memory:" is a separate database, keep exactly one alive
+        let in_memory = config.url == ":memory:";
+        let options = connect_options(config, key.as_ref())?;
+        let pool = SqlitePoolOptions::new()
+            .max_connections(if in_memory { 1 } else { config.pool.max_size })
+            .min_connections(if in_memory {
+                1
+            } else {
+                config.pool.min_idle.unwrap_or(0)
+            })
+            .acquire_timeout(config.connection_timeout())
+            .max_lifetime(if in_memory {
+                None
+            } else {
+                config.max_lifetime()
+            })
+            .idle_timeout(if in_memory {
+                None
+            } else {
+                config.idle_timeout()
+            })
+            .connect_with(options)
+            .await?;
+
+        info!("Async database pool created successfully");
+
+        Ok(Self { pool })
+    }
+
+    /// Get the underlying sqlx pool
+    pub fn inner(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    /// Apply pending migrations from the shared migration registry
+    ///
+    /// Migrations are written against rusqlite, so they run on the raw
+    /// SQLite handle of a pooled connection and block the calling task
+    /// while they do. Run this once at startup.
+    pub async fn migrate(&self, runner: &MigrationRunner) -> DbResult<()> {
+        let mut conn = self.pool.acquire().await?;
+        let mut handle = conn.lock_handle().await?;
+
+        // SAFETY: the handle stays locked, and the pooled connection checked
+        // out, until the rusqlite connection is dropped at the end of this
+        // scope. `from_handle` never closes the handle.
+        let mut migration_conn =
+            unsafe { rusqlite::Connection::from_handle(handle.as_raw_handle().as_ptr()) }?;
+        runner.migrate(&mut migration_conn)
+    }
+
+    /// Run a query built with the query builder
+    pub async fn fetch<E>(&self, query: &QueryBuilder) -> DbResult<Vec<E>>
+    where
+        E: FromSqliteRow,
+    {
+        let rows = sqlx::query(&query.build()).fetch_all(&self.pool).await?;
+        let entities = rows.iter().map(E::from_sqlite_row).collect::<Result<Vec<_>, _>>()?;
+
+        Ok(entities)
+    }
+
+    /// Count the rows matched by a query built with the query builder
+    pub async fn fetch_count(&self, query: &QueryBuilder) -> DbResult<i64> {
+        let count = sqlx::query_scalar(&query.build_count()).fetch_one(&self.pool).await?;
+        Ok(count)
+    }
+
+    /// Perform a health check on the pool
+    pub async fn health_check(&self) -> DbResult<()> {
+        sqlx::query("SELECT 1")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DatabaseError::ConnectionError(format!("Health check failed: {}", e)))?;
+        Ok(())
+    }
+
+    /// Close the pool gracefully
+    pub async fn close(&self) {
+        info!("Closing async database pool");
+        self.pool.close().await;
+    }
+}
+
+/// Connection options mirroring the blocking pool's connection setup
+fn connect_options(
+    config: &DatabaseConfig,
+    key: Option<&DatabaseKey>,
+) -> DbResult<SqliteConnectOptions> {
+    let options = if config.url == ":memory:" {
+        SqliteConnectOptions::from_str("sqlite::memory:")?
+    } else {
+        SqliteConnectOptions::new().filename(&config.url).create_if_missing(true)
+    };
+
+    let performance = &config.performance;
+    let mut options = options
+        .journal_mode(match performance.journal_mode {
+            JournalMode::Delete => SqliteJournalMode::Delete,
+            JournalMode::Truncate => SqliteJournalMode::Truncate,
+            JournalMode::Persist => SqliteJournalMode::Persist,
+            JournalMode::Memory => SqliteJournalMode::Memory,
+            JournalMode::Wal => SqliteJournalMode::Wal,
+            JournalMode::Off => SqliteJournalMode::Off,
+        })
+        .synchronous(match performance.synchronous {
+            SynchronousMode::Off => SqliteSynchronous::Off,
+            SynchronousMode::Normal => SqliteSynchronous::Normal,
+            SynchronousMode::Full => SqliteSynchronous::Full,
+            SynchronousMode::Extra => SqliteSynchronous::Extra,
+        })
+        .auto_vacuum(match performance.auto_vacuum {
+            AutoVacuum::None => SqliteAutoVacuum::None,
+            AutoVacuum::Full => SqliteAutoVacuum::Full,
+            AutoVacuum::Incremental => SqliteAutoVacuum::Incremental,
+        })
+        .page_size(performance.page_size)
+        .busy_timeout(config.busy_timeout())
+        .foreign_keys(true)
+        .pragma("cache_size", performance.cache_size.to_string());
+
+    if performance.mmap_size > 0 {
+        options = options.pragma("mmap_size", performance.mmap_size.to_string());
+    }
+
+    if performance.wal_mode {
+        options = options.pragma("wal_autocheckpoint", "1000");
+    }
+
+    // sqlx issues `key` before any other pragma, as SQLCipher requires
+    if let Some(key) = key {
+        options = options.pragma("key", quote_pragma(&key.pragma_value()));
+
+        if let Some(page_size) = config.encryption.cipher_page_size {
+            options = options.pragma("cipher_page_size", page_size.to_string());
+        }
+
+        if let Some(kdf_iter) = config.encryption.kdf_iter {
+            options = options.pragma("kdf_iter", kdf_iter.to_string());
+        }
+    }
+
+    Ok(options)
+}
+
+/// Quote a pragma value, sqlx inserts pragma values into the statement verbatim
+fn quote_pragma(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
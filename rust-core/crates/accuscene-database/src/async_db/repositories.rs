@@ -0,0 +1,840 @@
+//! Async repository implementations
+//!
+//! Mirrors [`Repository`](crate::repositories::Repository) with async
+//! methods running on an [`AsyncDatabasePool`]. Implemented by the case data
+//! repositories; the queries match their blocking counterparts, including
+//! soft delete filtering and version checks.
+
+use super::AsyncDatabasePool;
+use crate::error::{DatabaseError, DbResult};
+use crate::repositories::{
+    AccidentRepository, CaseRepository, EvidenceRepository, TrashEntry, VehicleRepository,
+};
+use crate::{Accident, Case, Evidence, Vehicle};
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlx::sqlite::SqliteRow;
+use sqlx::Row;
+
+const CASE_COLUMNS: &str = "id, case_number, title, description, status, priority, assigned_to,
+    created_by, organization, tags, metadata, closed_at, created_at, updated_at, version";
+
+const ACCIDENT_COLUMNS: &str = "id, case_id, accident_date, location, location_lat, location_lng,
+    weather_conditions, road_conditions, light_conditions, traffic_control,
+    description, severity, fatalities, injuries, property_damage_estimate,
+    police_report_number, police_department, reconstruction_data, created_at, updated_at";
+
+const VEHICLE_COLUMNS: &str = "id, accident_id, vehicle_number, make, model, year, color, vin,
+    license_plate, vehicle_type, damage_description, occupants,
+    speed_estimate, direction_of_travel, final_position_lat, final_position_lng,
+    airbag_deployment, driver_info, insurance_info, metadata, created_at, updated_at, version";
+
+const EVIDENCE_COLUMNS: &str = "id, case_id, accident_id, evidence_type, title, description,
+    file_path, file_name, file_size, file_mime_type, file_hash,
+    collected_by, collected_at, location, chain_of_custody, tags, metadata,
+    is_verified, created_at, updated_at, version";
+
+/// Entity that can be read from a sqlx row
+pub trait FromSqliteRow: Sized {
+    /// Map a row selected with the entity's columns
+    fn from_sqlite_row(row: &SqliteRow) -> Result<Self, sqlx::Error>;
+}
+
+/// Base async repository trait
+#[async_trait]
+pub trait AsyncRepository: Send + Sync {
+    type Entity: FromSqliteRow + Send + Sync;
+    type Id: ToString + Send + Sync;
+
+    /// Find an entity by ID
+    async fn find_by_id(
+        &self,
+        pool: &AsyncDatabasePool,
+        id: &Self::Id,
+    ) -> DbResult<Option<Self::Entity>>;
+
+    /// Find all entities
+    async fn find_all(&self, pool: &AsyncDatabasePool) -> DbResult<Vec<Self::Entity>>;
+
+    /// Create a new entity
+    async fn create(&self, pool: &AsyncDatabasePool, entity: &Self::Entity) -> DbResult<()>;
+
+    /// Update an existing entity
+    async fn update(&self, pool: &AsyncDatabasePool, entity: &Self::Entity) -> DbResult<()>;
+
+    /// Delete an entity by ID
+    async fn delete(&self, pool: &AsyncDatabasePool, id: &Self::Id) -> DbResult<()>;
+
+    /// Check if an entity exists by ID
+    async fn exists(&self, pool: &AsyncDatabasePool, id: &Self::Id) -> DbResult<bool> {
+        Ok(self.find_by_id(pool, id).await?.is_some())
+    }
+
+    /// Count all entities
+    async fn count(&self, pool: &AsyncDatabasePool) -> DbResult<i64>;
+
+    /// Table backing the repository, for repositories with soft delete support
+    fn trash_table(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Move an entity to the trash
+    async fn soft_delete(&self, pool: &AsyncDatabasePool, id: &Self::Id) -> DbResult<()> {
+        let table = require_trash_table(self.trash_table())?;
+        let id = id.to_string();
+        let result = sqlx::query(&format!(
+            "UPDATE {} SET deleted_at = datetime('now') WHERE id = ? AND deleted_at IS NULL",
+            table
+        ))
+        .bind(&id)
+        .execute(pool.inner())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            Err(DatabaseError::not_found(table, "id", id))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Restore an entity from the trash
+    async fn restore(&self, pool: &AsyncDatabasePool, id: &Self::Id) -> DbResult<()> {
+        let table = require_trash_table(self.trash_table())?;
+        let id = id.to_string();
+        let result = sqlx::query(&format!(
+            "UPDATE {} SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL",
+            table
+        ))
+        .bind(&id)
+        .execute(pool.inner())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            Err(DatabaseError::not_found(table, "id", id))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// List trashed entities, most recently deleted first
+    async fn list_trash(&self, pool: &AsyncDatabasePool) -> DbResult<Vec<TrashEntry>> {
+        let table = require_trash_table(self.trash_table())?;
+        let rows = sqlx::query(&format!(
+            "SELECT id, deleted_at FROM {} WHERE deleted_at IS NOT NULL
+             ORDER BY deleted_at DESC",
+            table
+        ))
+        .fetch_all(pool.inner())
+        .await?;
+
+        let entries = rows
+            .iter()
+            .map(|row| {
+                Ok(TrashEntry {
+                    id: row.try_get("id")?,
+                    deleted_at: row.try_get("deleted_at")?,
+                })
+            })
+            .collect::<Result<Vec<_>, sqlx::Error>>()?;
+
+        Ok(entries)
+    }
+
+    /// Permanently delete entities that have been in the trash longer than `age`
+    async fn purge_older_than(
+        &self,
+        pool: &AsyncDatabasePool,
+        age: chrono::Duration,
+    ) -> DbResult<u64> {
+        let table = require_trash_table(self.trash_table())?;
+        let cutoff = (chrono::Utc::now() - age).format("%Y-%m-%d %H:%M:%S").to_string();
+        let result = sqlx::query(&format!(
+            "DELETE FROM {} WHERE deleted_at IS NOT NULL AND deleted_at <= ?",
+            table
+        ))
+        .bind(cutoff)
+        .execute(pool.inner())
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+fn require_trash_table(table: Option<&'static str>) -> DbResult<&'static str> {
+    table.ok_or_else(|| {
+        DatabaseError::QueryError("Repository does not support soft delete".to_string())
+    })
+}
+
+/// Error for an update that matched no row at the expected version
+async fn version_conflict(
+    pool: &AsyncDatabasePool,
+    table: &str,
+    entity: &str,
+    id: &str,
+    expected_version: i64,
+) -> DatabaseError {
+    let current_version =
+        sqlx::query_scalar::<_, i64>(&format!("SELECT version FROM {} WHERE id = ?", table))
+            .bind(id)
+            .fetch_optional(pool.inner())
+            .await;
+
+    match current_version {
+        Ok(Some(current_version)) => DatabaseError::Conflict {
+            entity: entity.to_string(),
+            id: id.to_string(),
+            expected_version,
+            current_version,
+        },
+        Ok(None) => DatabaseError::not_found(entity, "id", id),
+        Err(err) => err.into(),
+    }
+}
+
+async fn fetch_by_id<E: FromSqliteRow>(
+    pool: &AsyncDatabasePool,
+    columns: &str,
+    table: &str,
+    id: &str,
+) -> DbResult<Option<E>> {
+    let row = sqlx::query(&format!(
+        "SELECT {} FROM {} WHERE id = ? AND deleted_at IS NULL",
+        columns, table
+    ))
+    .bind(id)
+    .fetch_optional(pool.inner())
+    .await?;
+
+    Ok(row.as_ref().map(E::from_sqlite_row).transpose()?)
+}
+
+async fn fetch_all<E: FromSqliteRow>(
+    pool: &AsyncDatabasePool,
+    columns: &str,
+    table: &str,
+    order_by: &str,
+) -> DbResult<Vec<E>> {
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM {} WHERE deleted_at IS NULL ORDER BY {}",
+        columns, table, order_by
+    ))
+    .fetch_all(pool.inner())
+    .await?;
+
+    Ok(rows.iter().map(E::from_sqlite_row).collect::<Result<Vec<_>, _>>()?)
+}
+
+async fn delete_by_id(
+    pool: &AsyncDatabasePool,
+    table: &str,
+    entity: &str,
+    id: &str,
+) -> DbResult<()> {
+    let result = sqlx::query(&format!("DELETE FROM {} WHERE id = ?", table))
+        .bind(id)
+        .execute(pool.inner())
+        .await?;
+
+    if result.rows_affected() == 0 {
+        Err(DatabaseError::not_found(entity, "id", id))
+    } else {
+        Ok(())
+    }
+}
+
+async fn count_live(pool: &AsyncDatabasePool, table: &str) -> DbResult<i64> {
+    let count = sqlx::query_scalar(&format!(
+        "SELECT COUNT(*) FROM {} WHERE deleted_at IS NULL",
+        table
+    ))
+    .fetch_one(pool.inner())
+    .await?;
+    Ok(count)
+}
+
+/// Decode a JSON text column, ignoring malformed values like the blocking repositories
+fn json_column<T: DeserializeOwned>(
+    row: &SqliteRow,
+    column: &str,
+) -> Result<Option<T>, sqlx::Error> {
+    let value: Option<String> = row.try_get(column)?;
+    Ok(value.and_then(|s| serde_json::from_str(&s).ok()))
+}
+
+fn to_json<T: Serialize>(value: &Option<T>) -> Option<String> {
+    value.as_ref().and_then(|v| serde_json::to_string(v).ok())
+}
+
+impl FromSqliteRow for Case {
+    fn from_sqlite_row(row: &SqliteRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            case_number: row.try_get("case_number")?,
+            title: row.try_get("title")?,
+            description: row.try_get("description")?,
+            status: row.try_get("status")?,
+            priority: row.try_get("priority")?,
+            assigned_to: row.try_get("assigned_to")?,
+            created_by: row.try_get("created_by")?,
+            organization: row.try_get("organization")?,
+            tags: json_column(row, "tags")?,
+            metadata: json_column(row, "metadata")?,
+            closed_at: row.try_get("closed_at")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+            version: row.try_get("version")?,
+        })
+    }
+}
+
+#[async_trait]
+impl AsyncRepository for CaseRepository {
+    type Entity = Case;
+    type Id = String;
+
+    async fn find_by_id(&self, pool: &AsyncDatabasePool, id: &String) -> DbResult<Option<Case>> {
+        fetch_by_id(pool, CASE_COLUMNS, "cases", id).await
+    }
+
+    async fn find_all(&self, pool: &AsyncDatabasePool) -> DbResult<Vec<Case>> {
+        fetch_all(pool, CASE_COLUMNS, "cases", "created_at DESC").await
+    }
+
+    async fn create(&self, pool: &AsyncDatabasePool, entity: &Case) -> DbResult<()> {
+        sqlx::query(
+            "INSERT INTO cases (id, case_number, title, description, status, priority,
+                               assigned_to, created_by, organization, tags, metadata)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&entity.id)
+        .bind(&entity.case_number)
+        .bind(&entity.title)
+        .bind(&entity.description)
+        .bind(&entity.status)
+        .bind(&entity.priority)
+        .bind(&entity.assigned_to)
+        .bind(&entity.created_by)
+        .bind(&entity.organization)
+        .bind(to_json(&entity.tags))
+        .bind(to_json(&entity.metadata))
+        .execute(pool.inner())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn update(&self, pool: &AsyncDatabasePool, entity: &Case) -> DbResult<()> {
+        let result = sqlx::query(
+            "UPDATE cases SET case_number = ?, title = ?, description = ?, status = ?,
+                             priority = ?, assigned_to = ?, organization = ?, tags = ?, metadata = ?,
+                             version = version + 1
+             WHERE id = ? AND version = ?",
+        )
+        .bind(&entity.case_number)
+        .bind(&entity.title)
+        .bind(&entity.description)
+        .bind(&entity.status)
+        .bind(&entity.priority)
+        .bind(&entity.assigned_to)
+        .bind(&entity.organization)
+        .bind(to_json(&entity.tags))
+        .bind(to_json(&entity.metadata))
+        .bind(&entity.id)
+        .bind(entity.version)
+        .execute(pool.inner())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            Err(version_conflict(pool, "cases", "Case", &entity.id, entity.version).await)
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn delete(&self, pool: &AsyncDatabasePool, id: &String) -> DbResult<()> {
+        delete_by_id(pool, "cases", "Case", id).await
+    }
+
+    async fn count(&self, pool: &AsyncDatabasePool) -> DbResult<i64> {
+        count_live(pool, "cases").await
+    }
+
+    fn trash_table(&self) -> Option<&'static str> {
+        Some("cases")
+    }
+}
+
+impl FromSqliteRow for Accident {
+    fn from_sqlite_row(row: &SqliteRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            case_id: row.try_get("case_id")?,
+            accident_date: row.try_get("accident_date")?,
+            location: row.try_get("location")?,
+            location_lat: row.try_get("location_lat")?,
+            location_lng: row.try_get("location_lng")?,
+            weather_conditions: row.try_get("weather_conditions")?,
+            road_conditions: row.try_get("road_conditions")?,
+            light_conditions: row.try_get("light_conditions")?,
+            traffic_control: row.try_get("traffic_control")?,
+            description: row.try_get("description")?,
+            severity: row.try_get("severity")?,
+            fatalities: row.try_get("fatalities")?,
+            injuries: row.try_get("injuries")?,
+            property_damage_estimate: row.try_get("property_damage_estimate")?,
+            police_report_number: row.try_get("police_report_number")?,
+            police_department: row.try_get("police_department")?,
+            reconstruction_data: json_column(row, "reconstruction_data")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+#[async_trait]
+impl AsyncRepository for AccidentRepository {
+    type Entity = Accident;
+    type Id = String;
+
+    async fn find_by_id(
+        &self,
+        pool: &AsyncDatabasePool,
+        id: &String,
+    ) -> DbResult<Option<Accident>> {
+        fetch_by_id(pool, ACCIDENT_COLUMNS, "accidents", id).await
+    }
+
+    async fn find_all(&self, pool: &AsyncDatabasePool) -> DbResult<Vec<Accident>> {
+        fetch_all(pool, ACCIDENT_COLUMNS, "accidents", "accident_date DESC").await
+    }
+
+    async fn create(&self, pool: &AsyncDatabasePool, entity: &Accident) -> DbResult<()> {
+        sqlx::query(
+            "INSERT INTO accidents (id, case_id, accident_date, location, location_lat, location_lng,
+                                   weather_conditions, road_conditions, light_conditions, traffic_control,
+                                   description, severity, fatalities, injuries, property_damage_estimate,
+                                   police_report_number, police_department, reconstruction_data)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&entity.id)
+        .bind(&entity.case_id)
+        .bind(&entity.accident_date)
+        .bind(&entity.location)
+        .bind(entity.location_lat)
+        .bind(entity.location_lng)
+        .bind(&entity.weather_conditions)
+        .bind(&entity.road_conditions)
+        .bind(&entity.light_conditions)
+        .bind(&entity.traffic_control)
+        .bind(&entity.description)
+        .bind(&entity.severity)
+        .bind(entity.fatalities)
+        .bind(entity.injuries)
+        .bind(entity.property_damage_estimate)
+        .bind(&entity.police_report_number)
+        .bind(&entity.police_department)
+        .bind(to_json(&entity.reconstruction_data))
+        .execute(pool.inner())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn update(&self, pool: &AsyncDatabasePool, entity: &Accident) -> DbResult<()> {
+        let result = sqlx::query(
+            "UPDATE accidents SET case_id = ?, accident_date = ?, location = ?, location_lat = ?,
+                                 location_lng = ?, weather_conditions = ?, road_conditions = ?,
+                                 light_conditions = ?, traffic_control = ?, description = ?,
+                                 severity = ?, fatalities = ?, injuries = ?, property_damage_estimate = ?,
+                                 police_report_number = ?, police_department = ?, reconstruction_data = ?
+             WHERE id = ?",
+        )
+        .bind(&entity.case_id)
+        .bind(&entity.accident_date)
+        .bind(&entity.location)
+        .bind(entity.location_lat)
+        .bind(entity.location_lng)
+        .bind(&entity.weather_conditions)
+        .bind(&entity.road_conditions)
+        .bind(&entity.light_conditions)
+        .bind(&entity.traffic_control)
+        .bind(&entity.description)
+        .bind(&entity.severity)
+        .bind(entity.fatalities)
+        .bind(entity.injuries)
+        .bind(entity.property_damage_estimate)
+        .bind(&entity.police_report_number)
+        .bind(&entity.police_department)
+        .bind(to_json(&entity.reconstruction_data))
+        .bind(&entity.id)
+        .execute(pool.inner())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            Err(DatabaseError::not_found("Accident", "id", &entity.id))
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn delete(&self, pool: &AsyncDatabasePool, id: &String) -> DbResult<()> {
+        delete_by_id(pool, "accidents", "Accident", id).await
+    }
+
+    async fn count(&self, pool: &AsyncDatabasePool) -> DbResult<i64> {
+        count_live(pool, "accidents").await
+    }
+
+    fn trash_table(&self) -> Option<&'static str> {
+        Some("accidents")
+    }
+}
+
+impl FromSqliteRow for Vehicle {
+    fn from_sqlite_row(row: &SqliteRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            accident_id: row.try_get("accident_id")?,
+            vehicle_number: row.try_get("vehicle_number")?,
+            make: row.try_get("make")?,
+            model: row.try_get("model")?,
+            year: row.try_get("year")?,
+            color: row.try_get("color")?,
+            vin: row.try_get("vin")?,
+            license_plate: row.try_get("license_plate")?,
+            vehicle_type: row.try_get("vehicle_type")?,
+            damage_description: row.try_get("damage_description")?,
+            occupants: row.try_get("occupants")?,
+            speed_estimate: row.try_get("speed_estimate")?,
+            direction_of_travel: row.try_get("direction_of_travel")?,
+            final_position_lat: row.try_get("final_position_lat")?,
+            final_position_lng: row.try_get("final_position_lng")?,
+            airbag_deployment: row.try_get("airbag_deployment")?,
+            driver_info: json_column(row, "driver_info")?,
+            insurance_info: json_column(row, "insurance_info")?,
+            metadata: json_column(row, "metadata")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+            version: row.try_get("version")?,
+        })
+    }
+}
+
+#[async_trait]
+impl AsyncRepository for VehicleRepository {
+    type Entity = Vehicle;
+    type Id = String;
+
+    async fn find_by_id(&self, pool: &AsyncDatabasePool, id: &String) -> DbResult<Option<Vehicle>> {
+        fetch_by_id(pool, VEHICLE_COLUMNS, "vehicles", id).await
+    }
+
+    async fn find_all(&self, pool: &AsyncDatabasePool) -> DbResult<Vec<Vehicle>> {
+        fetch_all(pool, VEHICLE_COLUMNS, "vehicles", "created_at DESC").await
+    }
+
+    async fn create(&self, pool: &AsyncDatabasePool, entity: &Vehicle) -> DbResult<()> {
+        sqlx::query(
+            "INSERT INTO vehicles (id, accident_id, vehicle_number, make, model, year, color, vin,
+                                  license_plate, vehicle_type, damage_description, occupants,
+                                  speed_estimate, direction_of_travel, final_position_lat, final_position_lng,
+                                  airbag_deployment, driver_info, insurance_info, metadata)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&entity.id)
+        .bind(&entity.accident_id)
+        .bind(entity.vehicle_number)
+        .bind(&entity.make)
+        .bind(&entity.model)
+        .bind(entity.year)
+        .bind(&entity.color)
+        .bind(&entity.vin)
+        .bind(&entity.license_plate)
+        .bind(&entity.vehicle_type)
+        .bind(&entity.damage_description)
+        .bind(entity.occupants)
+        .bind(entity.speed_estimate)
+        .bind(entity.direction_of_travel)
+        .bind(entity.final_position_lat)
+        .bind(entity.final_position_lng)
+        .bind(entity.airbag_deployment)
+        .bind(to_json(&entity.driver_info))
+        .bind(to_json(&entity.insurance_info))
+        .bind(to_json(&entity.metadata))
+        .execute(pool.inner())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn update(&self, pool: &AsyncDatabasePool, entity: &Vehicle) -> DbResult<()> {
+        let result = sqlx::query(
+            "UPDATE vehicles SET accident_id = ?, vehicle_number = ?, make = ?, model = ?, year = ?,
+                                color = ?, vin = ?, license_plate = ?, vehicle_type = ?,
+                                damage_description = ?, occupants = ?, speed_estimate = ?,
+                                direction_of_travel = ?, final_position_lat = ?, final_position_lng = ?,
+                                airbag_deployment = ?, driver_info = ?, insurance_info = ?, metadata = ?,
+                                version = version + 1
+             WHERE id = ? AND version = ?",
+        )
+        .bind(&entity.accident_id)
+        .bind(entity.vehicle_number)
+        .bind(&entity.make)
+        .bind(&entity.model)
+        .bind(entity.year)
+        .bind(&entity.color)
+        .bind(&entity.vin)
+        .bind(&entity.license_plate)
+        .bind(&entity.vehicle_type)
+        .bind(&entity.damage_description)
+        .bind(entity.occupants)
+        .bind(entity.speed_estimate)
+        .bind(entity.direction_of_travel)
+        .bind(entity.final_position_lat)
+        .bind(entity.final_position_lng)
+        .bind(entity.airbag_deployment)
+        .bind(to_json(&entity.driver_info))
+        .bind(to_json(&entity.insurance_info))
+        .bind(to_json(&entity.metadata))
+        .bind(&entity.id)
+        .bind(entity.version)
+        .execute(pool.inner())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            Err(version_conflict(pool, "vehicles", "Vehicle", &entity.id, entity.version).await)
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn delete(&self, pool: &AsyncDatabasePool, id: &String) -> DbResult<()> {
+        delete_by_id(pool, "vehicles", "Vehicle", id).await
+    }
+
+    async fn count(&self, pool: &AsyncDatabasePool) -> DbResult<i64> {
+        count_live(pool, "vehicles").await
+    }
+
+    fn trash_table(&self) -> Option<&'static str> {
+        Some("vehicles")
+    }
+}
+
+impl FromSqliteRow for Evidence {
+    fn from_sqlite_row(row: &SqliteRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            case_id: row.try_get("case_id")?,
+            accident_id: row.try_get("accident_id")?,
+            evidence_type: row.try_get("evidence_type")?,
+            title: row.try_get("title")?,
+            description: row.try_get("description")?,
+            file_path: row.try_get("file_path")?,
+            file_name: row.try_get("file_name")?,
+            file_size: row.try_get("file_size")?,
+            file_mime_type: row.try_get("file_mime_type")?,
+            file_hash: row.try_get("file_hash")?,
+            collected_by: row.try_get("collected_by")?,
+            collected_at: row.try_get("collected_at")?,
+            location: row.try_get("location")?,
+            chain_of_custody: json_column(row, "chain_of_custody")?,
+            tags: json_column(row, "tags")?,
+            metadata: json_column(row, "metadata")?,
+            is_verified: row.try_get("is_verified")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+            version: row.try_get("version")?,
+        })
+    }
+}
+
+#[async_trait]
+impl AsyncRepository for EvidenceRepository {
+    type Entity = Evidence;
+    type Id = String;
+
+    async fn find_by_id(
+        &self,
+        pool: &AsyncDatabasePool,
+        id: &String,
+    ) -> DbResult<Option<Evidence>> {
+        fetch_by_id(pool, EVIDENCE_COLUMNS, "evidence", id).await
+    }
+
+    async fn find_all(&self, pool: &AsyncDatabasePool) -> DbResult<Vec<Evidence>> {
+        fetch_all(pool, EVIDENCE_COLUMNS, "evidence", "collected_at DESC").await
+    }
+
+    async fn create(&self, pool: &AsyncDatabasePool, entity: &Evidence) -> DbResult<()> {
+        sqlx::query(
+            "INSERT INTO evidence (id, case_id, accident_id, evidence_type, title, description,
+                                  file_path, file_name, file_size, file_mime_type, file_hash,
+                                  collected_by, collected_at, location, chain_of_custody, tags,
+                                  metadata, is_verified)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&entity.id)
+        .bind(&entity.case_id)
+        .bind(&entity.accident_id)
+        .bind(&entity.evidence_type)
+        .bind(&entity.title)
+        .bind(&entity.description)
+        .bind(&entity.file_path)
+        .bind(&entity.file_name)
+        .bind(entity.file_size)
+        .bind(&entity.file_mime_type)
+        .bind(&entity.file_hash)
+        .bind(&entity.collected_by)
+        .bind(&entity.collected_at)
+        .bind(&entity.location)
+        .bind(to_json(&entity.chain_of_custody))
+        .bind(to_json(&entity.tags))
+        .bind(to_json(&entity.metadata))
+        .bind(entity.is_verified)
+        .execute(pool.inner())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn update(&self, pool: &AsyncDatabasePool, entity: &Evidence) -> DbResult<()> {
+        let result = sqlx::query(
+            "UPDATE evidence SET case_id = ?, accident_id = ?, evidence_type = ?, title = ?,
+                               description = ?, file_path = ?, file_name = ?, file_size = ?,
+                               file_mime_type = ?, file_hash = ?, collected_by = ?, collected_at = ?,
+                               location = ?, chain_of_custody = ?, tags = ?, metadata = ?,
+                               is_verified = ?, version = version + 1
+             WHERE id = ? AND version = ?",
+        )
+        .bind(&entity.case_id)
+        .bind(&entity.accident_id)
+        .bind(&entity.evidence_type)
+        .bind(&entity.title)
+        .bind(&entity.description)
+        .bind(&entity.file_path)
+        .bind(&entity.file_name)
+        .bind(entity.file_size)
+        .bind(&entity.file_mime_type)
+        .bind(&entity.file_hash)
+        .bind(&entity.collected_by)
+        .bind(&entity.collected_at)
+        .bind(&entity.location)
+        .bind(to_json(&entity.chain_of_custody))
+        .bind(to_json(&entity.tags))
+        .bind(to_json(&entity.metadata))
+        .bind(entity.is_verified)
+        .bind(&entity.id)
+        .bind(entity.version)
+        .execute(pool.inner())
+        .await?;
+
+        if result.rows_affected() == 0 {
+            Err(version_conflict(pool, "evidence", "Evidence", &entity.id, entity.version).await)
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn delete(&self, pool: &AsyncDatabasePool, id: &String) -> DbResult<()> {
+        delete_by_id(pool, "evidence", "Evidence", id).await
+    }
+
+    async fn count(&self, pool: &AsyncDatabasePool) -> DbResult<i64> {
+        count_live(pool, "evidence").await
+    }
+
+    fn trash_table(&self) -> Option<&'static str> {
+        Some("evidence")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::runner::MigrationRunner;
+    use crate::query::QueryBuilder;
+    use crate::DatabaseConfig;
+
+    async fn setup() -> AsyncDatabasePool {
+        let pool = AsyncDatabasePool::new(&DatabaseConfig::in_memory()).await.unwrap();
+        pool.migrate(&MigrationRunner::new()).await.unwrap();
+        sqlx::query(
+            "INSERT INTO users (id, email, username, full_name, password_hash)
+             VALUES ('user-1', 'analyst@example.com', 'analyst', 'Analyst', 'hash')",
+        )
+        .execute(pool.inner())
+        .await
+        .unwrap();
+        pool
+    }
+
+    fn test_case(id: &str) -> Case {
+        Case {
+            id: id.to_string(),
+            case_number: format!("CASE-{}", id),
+            title: "Intersection collision".to_string(),
+            description: None,
+            status: "open".to_string(),
+            priority: "high".to_string(),
+            assigned_to: None,
+            created_by: "user-1".to_string(),
+            organization: None,
+            tags: Some(vec!["intersection".to_string()]),
+            metadata: None,
+            closed_at: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+            version: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_async_case_crud_and_conflict() {
+        let pool = setup().await;
+        let repo = CaseRepository::new();
+
+        repo.create(&pool, &test_case("c1")).await.unwrap();
+        let mut case = repo.find_by_id(&pool, &"c1".to_string()).await.unwrap().unwrap();
+        assert_eq!(case.tags, Some(vec!["intersection".to_string()]));
+        assert_eq!(case.version, 1);
+
+        let stale = case.clone();
+        case.title = "Rear-end collision".to_string();
+        repo.update(&pool, &case).await.unwrap();
+
+        let err = repo.update(&pool, &stale).await.unwrap_err();
+        assert!(err.is_conflict());
+
+        repo.soft_delete(&pool, &"c1".to_string()).await.unwrap();
+        assert_eq!(repo.count(&pool).await.unwrap(), 0);
+        assert_eq!(repo.list_trash(&pool).await.unwrap().len(), 1);
+
+        repo.restore(&pool, &"c1".to_string()).await.unwrap();
+        assert!(repo.exists(&pool, &"c1".to_string()).await.unwrap());
+
+        repo.delete(&pool, &"c1".to_string()).await.unwrap();
+        assert!(repo.delete(&pool, &"c1".to_string()).await.unwrap_err().is_not_found());
+    }
+
+    #[tokio::test]
+    async fn test_async_query_builder() {
+        let pool = setup().await;
+        let repo = CaseRepository::new();
+        repo.create(&pool, &test_case("c1")).await.unwrap();
+        repo.create(&pool, &test_case("c2")).await.unwrap();
+        repo.soft_delete(&pool, &"c2".to_string()).await.unwrap();
+
+        let query = QueryBuilder::new("cases")
+            .select(&CASE_COLUMNS.split(',').map(str::trim).collect::<Vec<_>>())
+            .where_clause("status = 'open'")
+            .exclude_trashed();
+
+        let cases: Vec<Case> = pool.fetch(&query).await.unwrap();
+        assert_eq!(cases.len(), 1);
+        assert_eq!(cases[0].id, "c1");
+        assert_eq!(pool.fetch_count(&query).await.unwrap(), 1);
+    }
+}
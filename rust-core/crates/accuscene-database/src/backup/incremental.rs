@@ -0,0 +1,541 @@
+//! Incremental backups with point-in-time restore
+//!
+//! The database file is split into fixed-size chunks and every backup
+//! records a manifest with the hash of each chunk. A full backup stores all
+//! chunks; an incremental backup stores only the chunks whose hash differs
+//! from its parent. A full backup and the incrementals on top of it form a
+//! chain, and whole chains are retired by the retention policy.
+//!
+//! Files live in the `chains` directory of the backup directory:
+//! - `<id>.manifest.json`: the backup manifest
+//! - `<id>.chunks`: the stored chunks, concatenated in manifest order
+
+use super::BackupManager;
+use crate::error::{DatabaseError, DbResult};
+use chrono::{DateTime, Utc};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+/// Subdirectory of the backup directory holding backup chains
+const CHAINS_DIR: &str = "chains";
+
+const MANIFEST_SUFFIX: &str = ".manifest.json";
+const CHUNKS_SUFFIX: &str = ".chunks";
+
+/// Kind of backup in a chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupKind {
+    /// All chunks of the database
+    Full,
+    /// Chunks changed since the parent backup
+    Incremental,
+}
+
+/// Manifest of a full or incremental backup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// Backup identifier
+    pub id: String,
+    /// Position of the backup across all chains
+    pub sequence: u64,
+    /// Backup kind
+    pub kind: BackupKind,
+    /// Parent backup for incrementals
+    pub parent: Option<String>,
+    /// Identifier of the full backup the chain starts with
+    pub chain: String,
+    /// When the backup was taken
+    pub created_at: DateTime<Utc>,
+    /// Size of the database file in bytes
+    pub database_size: u64,
+    /// Chunk size in bytes
+    pub chunk_size: u64,
+    /// SHA-256 of every chunk of the database, hex encoded
+    pub chunk_hashes: Vec<String>,
+    /// Indices of the chunks stored in this backup, in file order
+    pub stored_chunks: Vec<u64>,
+}
+
+impl BackupManifest {
+    /// Length of a chunk, the last one may be short
+    fn chunk_len(&self, index: u64) -> u64 {
+        self.chunk_size.min(self.database_size.saturating_sub(index * self.chunk_size))
+    }
+
+    /// Bytes stored by this backup
+    pub fn stored_size(&self) -> u64 {
+        self.stored_chunks.iter().map(|&i| self.chunk_len(i)).sum()
+    }
+}
+
+impl BackupManager {
+    /// Create a full backup starting a new chain
+    pub fn backup_full(&self, conn: &Connection) -> DbResult<BackupManifest> {
+        self.backup_chained(conn, None)
+    }
+
+    /// Create an incremental backup on top of the latest backup
+    ///
+    /// Falls back to a full backup when there is no chain yet, the chain has
+    /// reached its maximum length or the chunk size changed.
+    pub fn backup_incremental(&self, conn: &Connection) -> DbResult<BackupManifest> {
+        let manifests = self.list_manifests()?;
+        let parent = manifests.last().filter(|latest| {
+            let chain_length = manifests.iter().filter(|m| m.chain == latest.chain).count();
+            latest.chunk_size == self.incremental.chunk_size
+                && chain_length <= self.incremental.max_chain_length as usize
+        });
+
+        self.backup_chained(conn, parent)
+    }
+
+    /// List backup manifests, oldest first
+    pub fn list_manifests(&self) -> DbResult<Vec<BackupManifest>> {
+        let dir = self.chains_dir();
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut manifests = Vec::new();
+        for entry in fs::read_dir(&dir).map_err(|e| {
+            DatabaseError::BackupError(format!("Failed to read chains directory: {}", e))
+        })? {
+            let path = entry?.path();
+            let is_manifest = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.ends_with(MANIFEST_SUFFIX));
+            if is_manifest {
+                manifests.push(read_manifest(&path)?);
+            }
+        }
+
+        manifests.sort_by_key(|m| m.sequence);
+        Ok(manifests)
+    }
+
+    /// Restore the database to the latest backup taken at or before a point in time
+    ///
+    /// Replays the chain's full backup and incrementals, checks every chunk
+    /// against the target manifest and copies the result into `conn`.
+    pub fn restore_to_point(
+        &self,
+        conn: &mut Connection,
+        timestamp: DateTime<Utc>,
+    ) -> DbResult<BackupManifest> {
+        let manifests = self.list_manifests()?;
+        let target =
+            manifests.iter().rev().find(|m| m.created_at <= timestamp).ok_or_else(|| {
+                DatabaseError::BackupError(format!("No backup taken at or before {}", timestamp))
+            })?;
+
+        let chain = resolve_chain(&manifests, target)?;
+        info!(
+            "Restoring backup {} ({} backups in chain) for {}",
+            target.id,
+            chain.len(),
+            timestamp
+        );
+
+        let staging = self.chains_dir().join(format!("{}.restore", target.id));
+        let result = self.replay_chain(&chain, &staging).and_then(|_| {
+            let backup_conn = Connection::open(&staging).map_err(|e| {
+                DatabaseError::BackupError(format!("Failed to open restored backup: {}", e))
+            })?;
+            let backup = rusqlite::backup::Backup::new(&backup_conn, conn).map_err(|e| {
+                DatabaseError::BackupError(format!("Failed to create restore: {}", e))
+            })?;
+            backup
+                .run_to_completion(5, std::time::Duration::from_millis(250), None)
+                .map_err(|e| DatabaseError::BackupError(format!("Restore failed: {}", e)))
+        });
+
+        if let Err(e) = fs::remove_file(&staging) {
+            warn!(
+                "Failed to remove restore staging file {}: {}",
+                staging.display(),
+                e
+            );
+        }
+        result?;
+
+        info!("Database restored to backup {}", target.id);
+        Ok(target.clone())
+    }
+
+    /// Delete the oldest chains beyond the retention count
+    ///
+    /// Returns the number of deleted backups.
+    pub fn apply_chain_retention(&self) -> DbResult<usize> {
+        let manifests = self.list_manifests()?;
+        let mut chains: Vec<&str> = Vec::new();
+        for manifest in manifests.iter().rev() {
+            if !chains.contains(&manifest.chain.as_str()) {
+                chains.push(&manifest.chain);
+            }
+        }
+
+        let keep = (self.incremental.retention_chains as usize).max(1);
+        let expired = chains.get(keep..).unwrap_or_default();
+
+        let mut deleted = 0;
+        for manifest in manifests.iter().filter(|m| expired.contains(&m.chain.as_str())) {
+            let dir = self.chains_dir();
+            for path in [
+                dir.join(format!("{}{}", manifest.id, CHUNKS_SUFFIX)),
+                dir.join(format!("{}{}", manifest.id, MANIFEST_SUFFIX)),
+            ] {
+                if let Err(e) = fs::remove_file(&path) {
+                    warn!("Failed to delete backup file {}: {}", path.display(), e);
+                }
+            }
+            debug!(
+                "Deleted backup {} of expired chain {}",
+                manifest.id, manifest.chain
+            );
+            deleted += 1;
+        }
+
+        if deleted > 0 {
+            info!(
+                "Deleted {} backups from {} expired chains",
+                deleted,
+                expired.len()
+            );
+        }
+
+        Ok(deleted)
+    }
+
+    fn chains_dir(&self) -> PathBuf {
+        self.backup_dir.join(CHAINS_DIR)
+    }
+
+    fn backup_chained(
+        &self,
+        conn: &Connection,
+        parent: Option<&BackupManifest>,
+    ) -> DbResult<BackupManifest> {
+        let dir = self.chains_dir();
+        fs::create_dir_all(&dir).map_err(|e| {
+            DatabaseError::BackupError(format!("Failed to create chains directory: {}", e))
+        })?;
+
+        let id = uuid::Uuid::new_v4().to_string();
+        let kind = if parent.is_some() {
+            BackupKind::Incremental
+        } else {
+            BackupKind::Full
+        };
+        let sequence = self.list_manifests()?.last().map_or(1, |latest| latest.sequence + 1);
+
+        // Take a consistent snapshot first, the live file may change underneath
+        let snapshot = dir.join(format!("{}.snapshot", id));
+        let result = snapshot_database(conn, &snapshot).and_then(|_| {
+            let chunks_path = dir.join(format!("{}{}", id, CHUNKS_SUFFIX));
+            self.write_chunks(&snapshot, &chunks_path, parent)
+        });
+        if let Err(e) = fs::remove_file(&snapshot) {
+            warn!(
+                "Failed to remove backup snapshot {}: {}",
+                snapshot.display(),
+                e
+            );
+        }
+        let (database_size, chunk_hashes, stored_chunks) = result?;
+
+        let manifest = BackupManifest {
+            chain: parent.map_or_else(|| id.clone(), |p| p.chain.clone()),
+            id,
+            sequence,
+            kind,
+            parent: parent.map(|p| p.id.clone()),
+            created_at: Utc::now(),
+            database_size,
+            chunk_size: self.incremental.chunk_size,
+            chunk_hashes,
+            stored_chunks,
+        };
+        write_manifest(
+            &dir.join(format!("{}{}", manifest.id, MANIFEST_SUFFIX)),
+            &manifest,
+        )?;
+
+        info!(
+            "Created {:?} backup {}: {} of {} chunks ({} bytes)",
+            manifest.kind,
+            manifest.id,
+            manifest.stored_chunks.len(),
+            manifest.chunk_hashes.len(),
+            manifest.stored_size()
+        );
+
+        self.apply_chain_retention()?;
+
+        Ok(manifest)
+    }
+
+    /// Hash the snapshot and store the chunks that differ from the parent
+    fn write_chunks(
+        &self,
+        snapshot: &Path,
+        chunks_path: &Path,
+        parent: Option<&BackupManifest>,
+    ) -> DbResult<(u64, Vec<String>, Vec<u64>)> {
+        let chunk_size = self.incremental.chunk_size;
+        let mut reader = BufReader::new(File::open(snapshot)?);
+        let mut writer = BufWriter::new(File::create(chunks_path)?);
+
+        let mut buffer = vec![0u8; chunk_size as usize];
+        let mut database_size = 0;
+        let mut chunk_hashes = Vec::new();
+        let mut stored_chunks = Vec::new();
+
+        loop {
+            let len = read_chunk(&mut reader, &mut buffer)?;
+            if len == 0 {
+                break;
+            }
+
+            let chunk = &buffer[..len];
+            let index = chunk_hashes.len();
+            let hash = format!("{:x}", Sha256::digest(chunk));
+            let unchanged = parent
+                .and_then(|p| p.chunk_hashes.get(index))
+                .is_some_and(|previous| *previous == hash);
+
+            if !unchanged {
+                writer.write_all(chunk)?;
+                stored_chunks.push(index as u64);
+            }
+
+            chunk_hashes.push(hash);
+            database_size += len as u64;
+        }
+
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+
+        Ok((database_size, chunk_hashes, stored_chunks))
+    }
+
+    /// Rebuild the database file of the last backup in a chain
+    fn replay_chain(&self, chain: &[&BackupManifest], staging: &Path) -> DbResult<()> {
+        let target = chain
+            .last()
+            .ok_or_else(|| DatabaseError::BackupError("Empty backup chain".to_string()))?;
+        let mut output = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(staging)?;
+
+        let mut buffer = vec![0u8; target.chunk_size as usize];
+        for manifest in chain {
+            let chunks_path = self.chains_dir().join(format!("{}{}", manifest.id, CHUNKS_SUFFIX));
+            let mut reader = BufReader::new(File::open(&chunks_path).map_err(|e| {
+                DatabaseError::BackupError(format!(
+                    "Missing chunks for backup {}: {}",
+                    manifest.id, e
+                ))
+            })?);
+
+            for &index in &manifest.stored_chunks {
+                let len = manifest.chunk_len(index) as usize;
+                reader.read_exact(&mut buffer[..len])?;
+                output.seek(SeekFrom::Start(index * manifest.chunk_size))?;
+                output.write_all(&buffer[..len])?;
+            }
+        }
+        output.set_len(target.database_size)?;
+
+        // Every chunk must match the target before the restore is applied
+        output.seek(SeekFrom::Start(0))?;
+        let mut reader = BufReader::new(output);
+        for (index, expected) in target.chunk_hashes.iter().enumerate() {
+            let len = read_chunk(&mut reader, &mut buffer)?;
+            if format!("{:x}", Sha256::digest(&buffer[..len])) != *expected {
+                return Err(DatabaseError::BackupError(format!(
+                    "Chunk {} of backup {} failed verification",
+                    index, target.id
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Copy the database into a standalone file with the SQLite backup API
+fn snapshot_database(conn: &Connection, path: &Path) -> DbResult<()> {
+    let mut snapshot_conn = Connection::open(path).map_err(|e| {
+        DatabaseError::BackupError(format!("Failed to create backup connection: {}", e))
+    })?;
+
+    let backup = rusqlite::backup::Backup::new(conn, &mut snapshot_conn)
+        .map_err(|e| DatabaseError::BackupError(format!("Failed to create backup: {}", e)))?;
+    backup
+        .run_to_completion(5, std::time::Duration::from_millis(250), None)
+        .map_err(|e| DatabaseError::BackupError(format!("Backup failed: {}", e)))?;
+
+    Ok(())
+}
+
+/// Fill the buffer as far as the reader allows, returning the bytes read
+fn read_chunk(reader: &mut impl Read, buffer: &mut [u8]) -> DbResult<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+/// Backups from the chain's full backup up to the target, oldest first
+fn resolve_chain<'a>(
+    manifests: &'a [BackupManifest],
+    target: &'a BackupManifest,
+) -> DbResult<Vec<&'a BackupManifest>> {
+    let mut chain = vec![target];
+    let mut current = target;
+
+    while let Some(parent_id) = &current.parent {
+        current = manifests.iter().find(|m| &m.id == parent_id).ok_or_else(|| {
+            DatabaseError::BackupError(format!(
+                "Backup {} is missing its parent {}",
+                current.id, parent_id
+            ))
+        })?;
+        chain.push(current);
+    }
+
+    if current.kind != BackupKind::Full {
+        return Err(DatabaseError::BackupError(format!(
+            "Backup chain of {} does not start with a full backup",
+            target.id
+        )));
+    }
+
+    chain.reverse();
+    Ok(chain)
+}
+
+fn read_manifest(path: &Path) -> DbResult<BackupManifest> {
+    let file = File::open(path)?;
+    serde_json::from_reader(BufReader::new(file)).map_err(|e| {
+        DatabaseError::BackupError(format!("Invalid backup manifest {}: {}", path.display(), e))
+    })
+}
+
+fn write_manifest(path: &Path, manifest: &BackupManifest) -> DbResult<()> {
+    // Write then rename so a crash never leaves a partial manifest behind
+    let partial = path.with_extension("partial");
+    let mut writer = BufWriter::new(File::create(&partial)?);
+    serde_json::to_writer_pretty(&mut writer, manifest)?;
+    writer.flush()?;
+    writer.get_ref().sync_all()?;
+    fs::rename(&partial, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::IncrementalBackupConfig;
+    use tempfile::TempDir;
+
+    fn manager(dir: &TempDir, max_chain_length: u32, retention_chains: u32) -> BackupManager {
+        BackupManager::new(dir.path().join("backups")).with_incremental(IncrementalBackupConfig {
+            chunk_size: 4096,
+            max_chain_length,
+            retention_chains,
+        })
+    }
+
+    fn database(dir: &TempDir) -> Connection {
+        let conn = Connection::open(dir.path().join("cases.db")).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT);
+             WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 2000)
+             INSERT INTO notes (body) SELECT printf('note %d measured at scene', i) FROM n;",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn note(conn: &Connection, id: i64) -> String {
+        conn.query_row("SELECT body FROM notes WHERE id = ?", [id], |row| {
+            row.get(0)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_incremental_stores_changed_chunks_only() {
+        let dir = TempDir::new().unwrap();
+        let manager = manager(&dir, 10, 3);
+        let conn = database(&dir);
+
+        let full = manager.backup_incremental(&conn).unwrap();
+        assert_eq!(full.kind, BackupKind::Full);
+        assert_eq!(full.stored_chunks.len(), full.chunk_hashes.len());
+
+        conn.execute("UPDATE notes SET body = 'revised' WHERE id = 1", []).unwrap();
+        let incremental = manager.backup_incremental(&conn).unwrap();
+        assert_eq!(incremental.kind, BackupKind::Incremental);
+        assert_eq!(incremental.parent.as_deref(), Some(full.id.as_str()));
+        assert_eq!(incremental.chain, full.id);
+        assert!(!incremental.stored_chunks.is_empty());
+        assert!(incremental.stored_chunks.len() < incremental.chunk_hashes.len());
+    }
+
+    #[test]
+    fn test_restore_to_point() {
+        let dir = TempDir::new().unwrap();
+        let manager = manager(&dir, 10, 3);
+        let conn = database(&dir);
+
+        manager.backup_full(&conn).unwrap();
+        conn.execute("UPDATE notes SET body = 'second' WHERE id = 1", []).unwrap();
+        let second = manager.backup_incremental(&conn).unwrap();
+        conn.execute("UPDATE notes SET body = 'third' WHERE id = 1", []).unwrap();
+        conn.execute("DELETE FROM notes WHERE id > 1000", []).unwrap();
+        manager.backup_incremental(&conn).unwrap();
+
+        let mut restored = Connection::open_in_memory().unwrap();
+        let manifest = manager.restore_to_point(&mut restored, second.created_at).unwrap();
+        assert_eq!(manifest.id, second.id);
+        assert_eq!(note(&restored, 1), "second");
+        let count: i64 =
+            restored.query_row("SELECT COUNT(*) FROM notes", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 2000);
+
+        let before_first = second.created_at - chrono::Duration::days(1);
+        assert!(manager.restore_to_point(&mut restored, before_first).is_err());
+    }
+
+    #[test]
+    fn test_chain_length_and_retention() {
+        let dir = TempDir::new().unwrap();
+        let manager = manager(&dir, 1, 1);
+        let conn = database(&dir);
+
+        let first = manager.backup_incremental(&conn).unwrap();
+        manager.backup_incremental(&conn).unwrap();
+        let second_chain = manager.backup_incremental(&conn).unwrap();
+        assert_eq!(second_chain.kind, BackupKind::Full);
+
+        let manifests = manager.list_manifests().unwrap();
+        assert_eq!(manifests.len(), 1);
+        assert!(manifests.iter().all(|m| m.chain != first.id));
+    }
+}
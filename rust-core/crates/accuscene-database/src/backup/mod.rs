@@ -1,8 +1,14 @@
 //! Database backup and restore functionality
 //!
 //! Provides utilities for backing up and restoring SQLite databases,
-//! with support for compression and automatic backups.
+//! with support for compression, automatic backups and incremental
+//! backup chains with point-in-time restore.
 
+pub mod incremental;
+
+pub use self::incremental::{BackupKind, BackupManifest};
+
+use crate::config::IncrementalBackupConfig;
 use crate::error::{DatabaseError, DbResult};
 use rusqlite::Connection;
 use std::fs;
@@ -14,6 +20,7 @@ use tracing::{debug, info, warn};
 pub struct BackupManager {
     backup_dir: PathBuf,
     compress: bool,
+    incremental: IncrementalBackupConfig,
 }
 
 impl BackupManager {
@@ -22,6 +29,7 @@ impl BackupManager {
         Self {
             backup_dir: backup_dir.into(),
             compress: true,
+            incremental: IncrementalBackupConfig::default(),
         }
     }
 
@@ -31,6 +39,12 @@ impl BackupManager {
         self
     }
 
+    /// Set incremental backup chunking and chain retention
+    pub fn with_incremental(mut self, incremental: IncrementalBackupConfig) -> Self {
+        self.incremental = incremental;
+        self
+    }
+
     /// Ensure backup directory exists
     fn ensure_backup_dir(&self) -> DbResult<()> {
         if !self.backup_dir.exists() {
@@ -45,23 +59,25 @@ impl BackupManager {
         self.ensure_backup_dir()?;
 
         let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
-        let backup_name = name.unwrap_or(&format!("backup_{}", timestamp));
+        let default_name = format!("backup_{}", timestamp);
+        let backup_name = name.unwrap_or(&default_name);
         let extension = if self.compress { "db.gz" } else { "db" };
-        let backup_path = self.backup_dir.join(format!("{}. {}", backup_name, extension));
+        let backup_path = self.backup_dir.join(format!("{}.{}", backup_name, extension));
 
         info!("Creating backup: {}", backup_path.display());
 
         // Use SQLite backup API
-        let backup_conn = Connection::open(&backup_path)
+        let mut backup_conn = Connection::open(&backup_path)
             .map_err(|e| DatabaseError::BackupError(format!("Failed to create backup connection: {}", e)))?;
 
-        let backup = rusqlite::backup::Backup::new(conn, &backup_conn)
+        let backup = rusqlite::backup::Backup::new(conn, &mut backup_conn)
             .map_err(|e| DatabaseError::BackupError(format!("Failed to create backup: {}", e)))?;
 
         backup
             .run_to_completion(5, std::time::Duration::from_millis(250), None)
             .map_err(|e| DatabaseError::BackupError(format!("Backup failed: {}", e)))?;
 
+        drop(backup);
         drop(backup_conn);
 
         // Compress if enabled
@@ -133,7 +149,7 @@ impl BackupManager {
                             created_at: metadata
                                 .created()
                                 .ok()
-                                .and_then(|t| chrono::DateTime::from(t).format("%Y-%m-%d %H:%M:%S").to_string().into()),
+                                .and_then(|t| chrono::DateTime::<chrono::Utc>::from(t).format("%Y-%m-%d %H:%M:%S").to_string().into()),
                         });
                     }
                 }
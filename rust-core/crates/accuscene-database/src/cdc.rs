@@ -0,0 +1,439 @@
+//! Change data capture
+//!
+//! Triggers on the captured tables write every insert, update and delete into
+//! the `_changelog` table together with a JSON image of the row before and
+//! after the change. Consumers read the log through a [`ChangeFeed`], which
+//! keeps a per-consumer cursor in `_changelog_cursors` so that each change is
+//! delivered at least once even across restarts. The integration layer turns
+//! the feed into domain events on the event bus.
+//!
+//! Triggers list the table columns explicitly, so a migration that adds
+//! columns to a captured table should call [`ChangeCapture::install`] again.
+
+use crate::error::{DatabaseError, DbResult};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+/// Tables captured by default
+pub const DEFAULT_CAPTURED_TABLES: &[&str] = &["cases", "accidents", "vehicles", "evidence"];
+
+/// Default number of changes returned per poll
+pub const DEFAULT_BATCH_SIZE: usize = 500;
+
+/// Columns ignored when deciding whether an update changed a row
+const IGNORED_COLUMNS: &[&str] = &["updated_at"];
+
+/// Kind of row change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeOperation {
+    /// Row inserted
+    Insert,
+    /// Row updated
+    Update,
+    /// Row deleted
+    Delete,
+}
+
+impl ChangeOperation {
+    /// Get the operation name
+    pub fn as_str(&self) -> &str {
+        match self {
+            ChangeOperation::Insert => "insert",
+            ChangeOperation::Update => "update",
+            ChangeOperation::Delete => "delete",
+        }
+    }
+
+    /// Parse an operation name
+    pub fn parse(operation: &str) -> Option<Self> {
+        match operation {
+            "insert" => Some(ChangeOperation::Insert),
+            "update" => Some(ChangeOperation::Update),
+            "delete" => Some(ChangeOperation::Delete),
+            _ => None,
+        }
+    }
+}
+
+/// Captured row change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeRecord {
+    /// Position in the changelog
+    pub sequence: i64,
+    /// Table the row belongs to
+    pub table_name: String,
+    /// ID of the changed row
+    pub row_id: String,
+    /// Kind of change
+    pub operation: ChangeOperation,
+    /// Row before the change (updates and deletes)
+    pub old_values: Option<serde_json::Value>,
+    /// Row after the change (inserts and updates)
+    pub new_values: Option<serde_json::Value>,
+    /// When the change was captured
+    pub changed_at: String,
+}
+
+impl ChangeRecord {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let operation: String = row.get(3)?;
+        let old_json: Option<String> = row.get(4)?;
+        let new_json: Option<String> = row.get(5)?;
+
+        Ok(Self {
+            sequence: row.get(0)?,
+            table_name: row.get(1)?,
+            row_id: row.get(2)?,
+            operation: ChangeOperation::parse(&operation).unwrap_or(ChangeOperation::Update),
+            old_values: old_json.and_then(|s| serde_json::from_str(&s).ok()),
+            new_values: new_json.and_then(|s| serde_json::from_str(&s).ok()),
+            changed_at: row.get(6)?,
+        })
+    }
+
+    /// Columns whose value differs between the old and new row
+    ///
+    /// Inserts and deletes report every column of the row.
+    pub fn changed_columns(&self) -> Vec<String> {
+        let empty = serde_json::Map::new();
+        let old = self.old_values.as_ref().and_then(|v| v.as_object()).unwrap_or(&empty);
+        let new = self.new_values.as_ref().and_then(|v| v.as_object()).unwrap_or(&empty);
+
+        let mut columns: Vec<String> = old
+            .keys()
+            .chain(new.keys())
+            .filter(|column| old.get(*column) != new.get(*column))
+            .cloned()
+            .collect();
+        columns.sort();
+        columns.dedup();
+        columns
+    }
+
+    /// Get a column value from the new row, falling back to the old row
+    pub fn value(&self, column: &str) -> Option<&serde_json::Value> {
+        self.new_values
+            .as_ref()
+            .and_then(|v| v.get(column))
+            .or_else(|| self.old_values.as_ref().and_then(|v| v.get(column)))
+    }
+}
+
+/// Installs and removes change capture triggers
+pub struct ChangeCapture;
+
+impl ChangeCapture {
+    /// Create a new change capture manager
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Install capture triggers on a table, replacing existing ones
+    pub fn install(&self, conn: &Connection, table: &str) -> DbResult<()> {
+        validate_identifier(table)?;
+        let columns = table_columns(conn, table)?;
+        if !columns.iter().any(|column| column == "id") {
+            return Err(DatabaseError::SchemaError(format!(
+                "Cannot capture changes on {}: table has no id column",
+                table
+            )));
+        }
+
+        let row_json = |alias: &str| {
+            let pairs: Vec<String> = columns
+                .iter()
+                .map(|column| format!("'{}', {}.\"{}\"", column, alias, column))
+                .collect();
+            format!("json_object({})", pairs.join(", "))
+        };
+        let changed = columns
+            .iter()
+            .filter(|column| !IGNORED_COLUMNS.contains(&column.as_str()))
+            .map(|column| format!("OLD.\"{}\" IS NOT NEW.\"{}\"", column, column))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+
+        self.uninstall(conn, table)?;
+        conn.execute_batch(&format!(
+            "CREATE TRIGGER _cdc_{table}_insert AFTER INSERT ON {table}
+             BEGIN
+                 INSERT INTO _changelog (table_name, row_id, operation, new_values)
+                 VALUES ('{table}', NEW.id, 'insert', {new});
+             END;
+
+             CREATE TRIGGER _cdc_{table}_update AFTER UPDATE ON {table}
+             WHEN {changed}
+             BEGIN
+                 INSERT INTO _changelog (table_name, row_id, operation, old_values, new_values)
+                 VALUES ('{table}', NEW.id, 'update', {old}, {new});
+             END;
+
+             CREATE TRIGGER _cdc_{table}_delete AFTER DELETE ON {table}
+             BEGIN
+                 INSERT INTO _changelog (table_name, row_id, operation, old_values)
+                 VALUES ('{table}', OLD.id, 'delete', {old});
+             END;",
+            table = table,
+            new = row_json("NEW"),
+            old = row_json("OLD"),
+            changed = changed,
+        ))?;
+
+        Ok(())
+    }
+
+    /// Install capture triggers on the default tables
+    pub fn install_defaults(&self, conn: &Connection) -> DbResult<()> {
+        for table in DEFAULT_CAPTURED_TABLES {
+            self.install(conn, table)?;
+        }
+        Ok(())
+    }
+
+    /// Remove capture triggers from a table
+    pub fn uninstall(&self, conn: &Connection, table: &str) -> DbResult<()> {
+        validate_identifier(table)?;
+        conn.execute_batch(&format!(
+            "DROP TRIGGER IF EXISTS _cdc_{table}_insert;
+             DROP TRIGGER IF EXISTS _cdc_{table}_update;
+             DROP TRIGGER IF EXISTS _cdc_{table}_delete;",
+            table = table
+        ))?;
+        Ok(())
+    }
+
+    /// Tables with capture triggers installed
+    pub fn captured_tables(&self, conn: &Connection) -> DbResult<Vec<String>> {
+        let mut stmt = conn.prepare(
+            "SELECT tbl_name FROM sqlite_master
+             WHERE type = 'trigger' AND name LIKE '\\_cdc\\_%\\_insert' ESCAPE '\\'
+             ORDER BY tbl_name",
+        )?;
+
+        let tables = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(tables)
+    }
+}
+
+impl Default for ChangeCapture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Cursor-based reader of the changelog for a named consumer
+#[derive(Debug, Clone)]
+pub struct ChangeFeed {
+    consumer: String,
+    batch_size: usize,
+}
+
+impl ChangeFeed {
+    /// Create a feed for a consumer
+    pub fn new(consumer: impl Into<String>) -> Self {
+        Self {
+            consumer: consumer.into(),
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    /// Set the maximum number of changes returned per poll
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Consumer name
+    pub fn consumer(&self) -> &str {
+        &self.consumer
+    }
+
+    /// Sequence of the last acknowledged change
+    pub fn cursor(&self, conn: &Connection) -> DbResult<i64> {
+        let cursor = conn
+            .query_row(
+                "SELECT last_sequence FROM _changelog_cursors WHERE consumer = ?",
+                [&self.consumer],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        Ok(cursor.unwrap_or(0))
+    }
+
+    /// Read the next batch of unacknowledged changes
+    pub fn poll(&self, conn: &Connection) -> DbResult<Vec<ChangeRecord>> {
+        let cursor = self.cursor(conn)?;
+        let mut stmt = conn.prepare(
+            "SELECT sequence, table_name, row_id, operation, old_values, new_values, changed_at
+             FROM _changelog WHERE sequence > ? ORDER BY sequence LIMIT ?",
+        )?;
+
+        let changes = stmt
+            .query_map(params![cursor, self.batch_size as i64], ChangeRecord::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(changes)
+    }
+
+    /// Acknowledge all changes up to and including `sequence`
+    pub fn acknowledge(&self, conn: &Connection, sequence: i64) -> DbResult<()> {
+        conn.execute(
+            "INSERT INTO _changelog_cursors (consumer, last_sequence) VALUES (?, ?)
+             ON CONFLICT(consumer) DO UPDATE SET
+                 last_sequence = MAX(last_sequence, excluded.last_sequence),
+                 updated_at = datetime('now')",
+            params![self.consumer, sequence],
+        )?;
+        Ok(())
+    }
+
+    /// Number of changes not yet acknowledged by this consumer
+    pub fn lag(&self, conn: &Connection) -> DbResult<i64> {
+        let cursor = self.cursor(conn)?;
+        let lag = conn.query_row(
+            "SELECT COUNT(*) FROM _changelog WHERE sequence > ?",
+            [cursor],
+            |row| row.get(0),
+        )?;
+        Ok(lag)
+    }
+}
+
+/// Delete changes acknowledged by every registered consumer
+///
+/// Returns the number of deleted changes. Nothing is deleted while no
+/// consumer is registered.
+pub fn prune_changelog(conn: &Connection) -> DbResult<usize> {
+    let pruned = conn.execute(
+        "DELETE FROM _changelog
+         WHERE EXISTS (SELECT 1 FROM _changelog_cursors)
+           AND sequence <= (SELECT MIN(last_sequence) FROM _changelog_cursors)",
+        [],
+    )?;
+    Ok(pruned)
+}
+
+fn validate_identifier(name: &str) -> DbResult<()> {
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !name.starts_with(|c: char| c.is_ascii_digit());
+    if valid {
+        Ok(())
+    } else {
+        Err(DatabaseError::InvalidData(format!("Invalid table name: {}", name)))
+    }
+}
+
+fn table_columns(conn: &Connection, table: &str) -> DbResult<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT name FROM pragma_table_info(?) ORDER BY cid")?;
+    let columns = stmt
+        .query_map([table], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+
+    if columns.is_empty() {
+        return Err(DatabaseError::SchemaError(format!("Table {} does not exist", table)));
+    }
+    Ok(columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::v001_initial::InitialMigration;
+    use crate::migrations::v009_change_log::ChangeLogMigration;
+    use crate::migrations::Migration;
+
+    fn setup_db() -> Connection {
+        let mut conn = Connection::open_in_memory().unwrap();
+        InitialMigration.up(&mut conn).unwrap();
+        ChangeLogMigration.up(&mut conn).unwrap();
+        conn.execute(
+            "INSERT INTO users (id, email, username, full_name, password_hash)
+             VALUES ('u1', 'analyst@example.com', 'analyst', 'Analyst', 'hash')",
+            [],
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_capture_insert_update_delete() {
+        let conn = setup_db();
+        conn.execute_batch(
+            "INSERT INTO cases (id, case_number, title, created_by)
+             VALUES ('c1', 'CASE-1', 'Intersection collision', 'u1');
+             UPDATE cases SET status = 'closed' WHERE id = 'c1';
+             UPDATE cases SET status = 'closed' WHERE id = 'c1';
+             DELETE FROM cases WHERE id = 'c1';",
+        )
+        .unwrap();
+
+        let feed = ChangeFeed::new("test");
+        let changes = feed.poll(&conn).unwrap();
+        let operations: Vec<ChangeOperation> = changes.iter().map(|c| c.operation).collect();
+        assert_eq!(
+            operations,
+            vec![ChangeOperation::Insert, ChangeOperation::Update, ChangeOperation::Delete]
+        );
+
+        assert_eq!(changes[0].row_id, "c1");
+        assert_eq!(changes[0].value("title").unwrap(), "Intersection collision");
+        assert_eq!(changes[1].changed_columns(), vec!["status".to_string()]);
+        assert_eq!(changes[1].old_values.as_ref().unwrap()["status"], "open");
+        assert!(changes[2].new_values.is_none());
+    }
+
+    #[test]
+    fn test_feed_cursor_and_prune() {
+        let conn = setup_db();
+        conn.execute_batch(
+            "INSERT INTO cases (id, case_number, title, created_by) VALUES ('c1', 'CASE-1', 'A', 'u1');
+             INSERT INTO cases (id, case_number, title, created_by) VALUES ('c2', 'CASE-2', 'B', 'u1');
+             INSERT INTO cases (id, case_number, title, created_by) VALUES ('c3', 'CASE-3', 'C', 'u1');",
+        )
+        .unwrap();
+
+        let projections = ChangeFeed::new("projections").with_batch_size(2);
+        let notifications = ChangeFeed::new("notifications");
+
+        let batch = projections.poll(&conn).unwrap();
+        assert_eq!(batch.len(), 2);
+        projections.acknowledge(&conn, batch[1].sequence).unwrap();
+        assert_eq!(projections.lag(&conn).unwrap(), 1);
+        assert_eq!(projections.poll(&conn).unwrap()[0].row_id, "c3");
+
+        // Acknowledging an older sequence never moves the cursor back
+        projections.acknowledge(&conn, batch[0].sequence).unwrap();
+        assert_eq!(projections.cursor(&conn).unwrap(), batch[1].sequence);
+
+        notifications.acknowledge(&conn, batch[0].sequence).unwrap();
+        assert_eq!(prune_changelog(&conn).unwrap(), 1);
+        assert_eq!(notifications.poll(&conn).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_install_and_uninstall() {
+        let conn = setup_db();
+        let capture = ChangeCapture::new();
+        assert_eq!(
+            capture.captured_tables(&conn).unwrap(),
+            vec!["accidents", "cases", "evidence", "vehicles"]
+        );
+
+        capture.uninstall(&conn, "cases").unwrap();
+        conn.execute(
+            "INSERT INTO cases (id, case_number, title, created_by) VALUES ('c1', 'CASE-1', 'A', 'u1')",
+            [],
+        )
+        .unwrap();
+        assert!(ChangeFeed::new("test").poll(&conn).unwrap().is_empty());
+
+        assert!(capture.install(&conn, "cases; DROP TABLE users").is_err());
+        assert!(capture.install(&conn, "missing_table").is_err());
+    }
+}
@@ -25,6 +25,14 @@ pub struct DatabaseConfig {
 
     /// Backup configuration
     pub backup: BackupConfig,
+
+    /// Encryption at rest configuration
+    #[serde(default)]
+    pub encryption: EncryptionConfig,
+
+    /// Read replica and attached database configuration
+    #[serde(default)]
+    pub routing: RoutingConfig,
 }
 
 /// Connection pool configuration
@@ -178,6 +186,150 @@ pub struct BackupConfig {
 
     /// Backup on shutdown
     pub backup_on_shutdown: bool,
+
+    /// Incremental backup configuration
+    #[serde(default)]
+    pub incremental: IncrementalBackupConfig,
+}
+
+/// Incremental backup configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncrementalBackupConfig {
+    /// Chunk size in bytes used to detect changed regions of the database file
+    pub chunk_size: u64,
+
+    /// Maximum number of incremental backups on top of a full backup
+    pub max_chain_length: u32,
+
+    /// Number of backup chains (a full backup and its incrementals) to retain
+    pub retention_chains: u32,
+}
+
+/// Encryption at rest configuration (SQLCipher)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptionConfig {
+    /// Encrypt the database file
+    pub enabled: bool,
+
+    /// Where the key is read from
+    pub key_source: KeySource,
+
+    /// How the key is interpreted
+    pub key_format: KeyFormat,
+
+    /// Cipher page size in bytes (None for the SQLCipher default)
+    pub cipher_page_size: Option<u32>,
+
+    /// Key derivation iterations for passphrase keys (None for the SQLCipher default)
+    pub kdf_iter: Option<u32>,
+
+    /// Encrypt an existing plaintext database when the pool opens it
+    pub migrate_plaintext: bool,
+}
+
+/// Source of the database encryption key
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum KeySource {
+    /// Read the key from an environment variable
+    Env {
+        /// Variable name
+        var: String,
+    },
+
+    /// Read the key from a file (surrounding whitespace is trimmed)
+    File {
+        /// Key file path
+        path: PathBuf,
+    },
+
+    /// Key given inline in the configuration
+    Inline {
+        /// Key value
+        key: String,
+    },
+}
+
+impl std::fmt::Debug for KeySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeySource::Env { var } => f.debug_struct("Env").field("var", var).finish(),
+            KeySource::File { path } => f.debug_struct("File").field("path", path).finish(),
+            KeySource::Inline { .. } => f.debug_struct("Inline").field("key", &"<redacted>").finish(),
+        }
+    }
+}
+
+/// Encryption key format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyFormat {
+    /// Passphrase run through the SQLCipher key derivation
+    Passphrase,
+
+    /// Raw 256-bit key as 64 hex characters, used without key derivation
+    RawHex,
+}
+
+/// Read routing configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingConfig {
+    /// Read replica database paths, opened read-only
+    pub replicas: Vec<String>,
+
+    /// Maximum number of connections per replica pool
+    pub replica_pool_size: u32,
+
+    /// Replica connection timeout in milliseconds before falling back to the primary
+    pub replica_timeout: u64,
+
+    /// Secondary databases attached to every connection
+    pub attached: Vec<AttachedDatabase>,
+}
+
+/// Secondary database attached under a schema alias
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttachedDatabase {
+    /// Schema alias used to qualify tables, e.g. `archive.cases`
+    pub alias: String,
+
+    /// Database file path
+    pub path: String,
+}
+
+impl AttachedDatabase {
+    /// Create an attached database entry
+    pub fn new(alias: impl Into<String>, path: impl Into<String>) -> Self {
+        Self {
+            alias: alias.into(),
+            path: path.into(),
+        }
+    }
+
+    /// Validate the schema alias
+    pub fn validate(&self) -> DbResult<()> {
+        let valid_identifier = self
+            .alias
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && self.alias.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+        if !valid_identifier {
+            return Err(DatabaseError::ConfigError(format!(
+                "Invalid attached database alias '{}'",
+                self.alias
+            )));
+        }
+
+        if self.alias.eq_ignore_ascii_case("main") || self.alias.eq_ignore_ascii_case("temp") {
+            return Err(DatabaseError::ConfigError(format!(
+                "Attached database alias '{}' is reserved",
+                self.alias
+            )));
+        }
+
+        Ok(())
+    }
 }
 
 impl Default for DatabaseConfig {
@@ -188,6 +340,8 @@ impl Default for DatabaseConfig {
             performance: PerformanceConfig::default(),
             features: FeatureConfig::default(),
             backup: BackupConfig::default(),
+            encryption: EncryptionConfig::default(),
+            routing: RoutingConfig::default(),
         }
     }
 }
@@ -241,6 +395,54 @@ impl Default for BackupConfig {
             retention_count: 24, // Keep 24 backups (1 day if hourly)
             compress: true,
             backup_on_shutdown: true,
+            incremental: IncrementalBackupConfig::default(),
+        }
+    }
+}
+
+impl Default for IncrementalBackupConfig {
+    fn default() -> Self {
+        Self {
+            chunk_size: 1024 * 1024, // 1MB
+            max_chain_length: 23,    // Daily full backup when hourly
+            retention_chains: 7,
+        }
+    }
+}
+
+impl Default for RoutingConfig {
+    fn default() -> Self {
+        Self {
+            replicas: Vec::new(),
+            replica_pool_size: 8,
+            replica_timeout: 250,
+            attached: Vec::new(),
+        }
+    }
+}
+
+impl Default for EncryptionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            key_source: KeySource::Env {
+                var: "ACCUSCENE_DB_KEY".to_string(),
+            },
+            key_format: KeyFormat::Passphrase,
+            cipher_page_size: None,
+            kdf_iter: None,
+            migrate_plaintext: false,
+        }
+    }
+}
+
+impl EncryptionConfig {
+    /// Create an enabled encryption configuration for a key source
+    pub fn new(key_source: KeySource) -> Self {
+        Self {
+            enabled: true,
+            key_source,
+            ..Default::default()
         }
     }
 }
@@ -260,6 +462,7 @@ impl DatabaseConfig {
             url: ":memory:".to_string(),
             pool: PoolConfig {
                 max_size: 1, // In-memory databases should use a single connection
+                min_idle: Some(1),
                 ..Default::default()
             },
             performance: PerformanceConfig {
@@ -346,6 +549,49 @@ impl DatabaseConfig {
             ));
         }
 
+        if self.backup.incremental.chunk_size == 0 {
+            return Err(DatabaseError::ConfigError(
+                "Backup chunk size must be greater than 0".to_string(),
+            ));
+        }
+
+        if !self.routing.replicas.is_empty() && self.routing.replica_pool_size == 0 {
+            return Err(DatabaseError::ConfigError(
+                "Replica pool size must be greater than 0".to_string(),
+            ));
+        }
+
+        for (index, attached) in self.routing.attached.iter().enumerate() {
+            attached.validate()?;
+
+            if self.routing.attached[..index]
+                .iter()
+                .any(|other| other.alias.eq_ignore_ascii_case(&attached.alias))
+            {
+                return Err(DatabaseError::ConfigError(format!(
+                    "Duplicate attached database alias '{}'",
+                    attached.alias
+                )));
+            }
+        }
+
+        if self.encryption.enabled {
+            if let Some(page_size) = self.encryption.cipher_page_size {
+                if !(512..=65536).contains(&page_size) || !page_size.is_power_of_two() {
+                    return Err(DatabaseError::ConfigError(
+                        "Cipher page size must be a power of 2 between 512 and 65536 bytes"
+                            .to_string(),
+                    ));
+                }
+            }
+
+            if self.encryption.kdf_iter == Some(0) {
+                return Err(DatabaseError::ConfigError(
+                    "Key derivation iterations must be greater than 0".to_string(),
+                ));
+            }
+        }
+
         Ok(())
     }
 
@@ -368,6 +614,11 @@ impl DatabaseConfig {
     pub fn busy_timeout(&self) -> Duration {
         Duration::from_millis(self.performance.busy_timeout)
     }
+
+    /// Get replica connection timeout as Duration
+    pub fn replica_timeout(&self) -> Duration {
+        Duration::from_millis(self.routing.replica_timeout)
+    }
 }
 
 #[cfg(test)]
@@ -410,6 +661,22 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_validation_attached_aliases() {
+        let mut config = DatabaseConfig::default();
+        config.routing.attached = vec![AttachedDatabase::new("archive", "archive.db")];
+        assert!(config.validate().is_ok());
+
+        config.routing.attached.push(AttachedDatabase::new("ARCHIVE", "other.db"));
+        assert!(config.validate().is_err());
+
+        config.routing.attached = vec![AttachedDatabase::new("main", "archive.db")];
+        assert!(config.validate().is_err());
+
+        config.routing.attached = vec![AttachedDatabase::new("archive; DROP", "archive.db")];
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_validation_invalid_page_size() {
         let mut config = DatabaseConfig::default();
@@ -417,6 +684,29 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_validation_invalid_cipher_page_size() {
+        let mut config = DatabaseConfig {
+            encryption: EncryptionConfig::new(KeySource::Inline {
+                key: "secret".to_string(),
+            }),
+            ..Default::default()
+        };
+        config.encryption.cipher_page_size = Some(1000);
+        assert!(config.validate().is_err());
+
+        config.encryption.cipher_page_size = Some(4096);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_key_source_debug_redacts_inline_key() {
+        let source = KeySource::Inline {
+            key: "hunter2".to_string(),
+        };
+        assert!(!format!("{:?}", source).contains("hunter2"));
+    }
+
     #[test]
     fn test_high_performance_config() {
         let config = DatabaseConfig::high_performance("test.db");
@@ -0,0 +1,442 @@
+//! Encryption at rest using SQLCipher
+//!
+//! Keys pool connections with `PRAGMA key`, rotates keys with `PRAGMA rekey`
+//! and encrypts existing plaintext databases with `sqlcipher_export`.
+//!
+//! SQLCipher is linked by the `sqlcipher` feature. Without it, plain SQLite
+//! ignores the key pragmas, so enabling encryption fails instead of silently
+//! writing plaintext.
+
+use crate::config::{DatabaseConfig, EncryptionConfig, KeyFormat, KeySource};
+use crate::error::{DatabaseError, DbResult};
+use rusqlite::Connection;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Header of a plaintext SQLite database file
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+/// Length of a raw key in bytes
+const RAW_KEY_LEN: usize = 32;
+
+/// Resolved database encryption key
+#[derive(Clone)]
+pub struct DatabaseKey {
+    secret: String,
+    format: KeyFormat,
+}
+
+impl DatabaseKey {
+    /// Create a passphrase key
+    pub fn passphrase(passphrase: impl Into<String>) -> DbResult<Self> {
+        let secret = passphrase.into();
+        if secret.is_empty() {
+            return Err(DatabaseError::EncryptionError(
+                "Encryption key is empty".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            secret,
+            format: KeyFormat::Passphrase,
+        })
+    }
+
+    /// Create a raw 256-bit key
+    pub fn raw(key: &[u8]) -> DbResult<Self> {
+        if key.len() != RAW_KEY_LEN {
+            return Err(DatabaseError::EncryptionError(format!(
+                "Raw key must be {} bytes, got {}",
+                RAW_KEY_LEN,
+                key.len()
+            )));
+        }
+
+        Ok(Self {
+            secret: key.iter().map(|b| format!("{:02X}", b)).collect(),
+            format: KeyFormat::RawHex,
+        })
+    }
+
+    /// Create a raw 256-bit key from 64 hex characters
+    pub fn from_hex(hex: &str) -> DbResult<Self> {
+        let hex = hex.trim();
+        if hex.len() != RAW_KEY_LEN * 2 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(DatabaseError::EncryptionError(format!(
+                "Raw key must be {} hex characters",
+                RAW_KEY_LEN * 2
+            )));
+        }
+
+        Ok(Self {
+            secret: hex.to_ascii_uppercase(),
+            format: KeyFormat::RawHex,
+        })
+    }
+
+    /// Key format
+    pub fn format(&self) -> KeyFormat {
+        self.format
+    }
+
+    /// Value passed to `PRAGMA key`, `PRAGMA rekey` and `ATTACH ... KEY`
+    pub(crate) fn pragma_value(&self) -> String {
+        match self.format {
+            KeyFormat::Passphrase => self.secret.clone(),
+            KeyFormat::RawHex => format!("x'{}'", self.secret),
+        }
+    }
+}
+
+impl std::fmt::Debug for DatabaseKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DatabaseKey")
+            .field("format", &self.format)
+            .field("secret", &"<redacted>")
+            .finish()
+    }
+}
+
+impl EncryptionConfig {
+    /// Read the key from the configured source
+    pub fn resolve_key(&self) -> DbResult<DatabaseKey> {
+        let value = match &self.key_source {
+            KeySource::Env { var } => std::env::var(var).map_err(|_| {
+                DatabaseError::EncryptionError(format!(
+                    "Encryption key variable '{}' is not set",
+                    var
+                ))
+            })?,
+            KeySource::File { path } => fs::read_to_string(path)
+                .map_err(|e| {
+                    DatabaseError::EncryptionError(format!(
+                        "Failed to read key file {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?
+                .trim()
+                .to_string(),
+            KeySource::Inline { key } => key.clone(),
+        };
+
+        match self.key_format {
+            KeyFormat::Passphrase => DatabaseKey::passphrase(value),
+            KeyFormat::RawHex => DatabaseKey::from_hex(&value),
+        }
+    }
+}
+
+/// SQLCipher version, or None when the linked SQLite has no encryption support
+pub fn cipher_version(conn: &Connection) -> Option<String> {
+    conn.query_row("PRAGMA cipher_version", [], |row| row.get(0)).ok()
+}
+
+/// Fail unless the linked SQLite is SQLCipher
+pub fn ensure_sqlcipher(conn: &Connection) -> DbResult<String> {
+    cipher_version(conn).ok_or_else(|| {
+        DatabaseError::EncryptionError(
+            "SQLCipher is not available; build with the `sqlcipher` feature".to_string(),
+        )
+    })
+}
+
+/// Check whether a database file exists and is stored as plaintext
+pub fn is_plaintext_database(path: &Path) -> DbResult<bool> {
+    if !path.exists() {
+        return Ok(false);
+    }
+
+    let mut header = [0u8; 16];
+    let mut file = fs::File::open(path)?;
+    match file.read_exact(&mut header) {
+        Ok(()) => Ok(&header == SQLITE_HEADER),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Key a freshly opened connection
+///
+/// Must run before any other statement on the connection. Fails with
+/// `SQLITE_NOTADB` when the key does not match the database.
+pub fn apply_key(
+    conn: &Connection,
+    key: &DatabaseKey,
+    config: &EncryptionConfig,
+) -> Result<(), rusqlite::Error> {
+    conn.pragma_update(None, "key", key.pragma_value())?;
+
+    if let Some(page_size) = config.cipher_page_size {
+        conn.pragma_update(None, "cipher_page_size", page_size)?;
+    }
+
+    if let Some(kdf_iter) = config.kdf_iter {
+        conn.pragma_update(None, "kdf_iter", kdf_iter)?;
+    }
+
+    // The key is only checked when the first page is read
+    conn.query_row("SELECT count(*) FROM sqlite_master", [], |_| Ok(()))
+}
+
+/// Re-encrypt the database behind a keyed connection with a new key
+///
+/// No other connection may have the database open while it is rekeyed.
+pub fn rekey(conn: &Connection, new_key: &DatabaseKey) -> DbResult<()> {
+    ensure_sqlcipher(conn)?;
+    conn.pragma_update(None, "rekey", new_key.pragma_value())
+        .map_err(|e| DatabaseError::EncryptionError(format!("Rekey failed: {}", e)))?;
+
+    info!("Database rekeyed");
+    Ok(())
+}
+
+/// Rotate the key of a database file that is not open elsewhere
+pub fn rotate_key(
+    path: &Path,
+    current: &DatabaseKey,
+    new_key: &DatabaseKey,
+    config: &EncryptionConfig,
+) -> DbResult<()> {
+    let conn = Connection::open(path)?;
+    ensure_sqlcipher(&conn)?;
+    apply_key(&conn, current, config).map_err(|_| invalid_key(path))?;
+    rekey(&conn, new_key)?;
+    drop(conn);
+
+    verify_key(path, new_key, config)
+}
+
+/// Encrypt a plaintext database file in place
+///
+/// The database is exported to a staging file next to it, which replaces the
+/// original once it opens with the new key.
+pub fn encrypt_plaintext_database(
+    path: &Path,
+    key: &DatabaseKey,
+    config: &EncryptionConfig,
+) -> DbResult<()> {
+    if !is_plaintext_database(path)? {
+        return Err(DatabaseError::EncryptionError(format!(
+            "{} is not a plaintext database",
+            path.display()
+        )));
+    }
+
+    let staging = sibling(path, "encrypting");
+    if staging.exists() {
+        fs::remove_file(&staging)?;
+    }
+
+    info!("Encrypting plaintext database {}", path.display());
+
+    let conn = Connection::open(path)?;
+    ensure_sqlcipher(&conn)?;
+
+    // Fold the WAL into the main file so the export sees every page
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+    conn.pragma_update(None, "journal_mode", "DELETE")?;
+
+    let user_version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+    conn.execute(
+        "ATTACH DATABASE ?1 AS encrypted KEY ?2",
+        rusqlite::params![staging.to_string_lossy(), key.pragma_value()],
+    )?;
+    if let Some(page_size) = config.cipher_page_size {
+        conn.execute_batch(&format!(
+            "PRAGMA encrypted.cipher_page_size = {}",
+            page_size
+        ))?;
+    }
+    if let Some(kdf_iter) = config.kdf_iter {
+        conn.execute_batch(&format!("PRAGMA encrypted.kdf_iter = {}", kdf_iter))?;
+    }
+
+    let exported =
+        conn.query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()))
+            .and_then(|_| {
+                conn.execute_batch(&format!("PRAGMA encrypted.user_version = {}", user_version))
+            });
+    conn.execute_batch("DETACH DATABASE encrypted")?;
+    drop(conn);
+
+    if let Err(e) = exported
+        .map_err(DatabaseError::from)
+        .and_then(|_| verify_key(&staging, key, config))
+    {
+        let _ = fs::remove_file(&staging);
+        return Err(DatabaseError::EncryptionError(format!(
+            "Failed to encrypt {}: {}",
+            path.display(),
+            e
+        )));
+    }
+
+    fs::rename(&staging, path)?;
+    for suffix in ["wal", "shm"] {
+        let stale = sibling(path, suffix);
+        if stale.exists() {
+            if let Err(e) = fs::remove_file(&stale) {
+                warn!("Failed to remove {}: {}", stale.display(), e);
+            }
+        }
+    }
+
+    info!("Database {} encrypted", path.display());
+    Ok(())
+}
+
+/// Prepare the database file for an encrypted pool and return its key
+///
+/// Plaintext databases are encrypted in place when `migrate_plaintext` is
+/// set and rejected otherwise.
+pub fn prepare_database(config: &DatabaseConfig) -> DbResult<DatabaseKey> {
+    let key = config.encryption.resolve_key()?;
+    let version = ensure_sqlcipher(&Connection::open_in_memory()?)?;
+    info!("Database encryption enabled (SQLCipher {})", version);
+
+    if config.url == ":memory:" {
+        return Ok(key);
+    }
+
+    let path = Path::new(&config.url);
+    if is_plaintext_database(path)? {
+        if !config.encryption.migrate_plaintext {
+            return Err(DatabaseError::EncryptionError(format!(
+                "{} is not encrypted; set encryption.migrate_plaintext to encrypt it",
+                path.display()
+            )));
+        }
+        encrypt_plaintext_database(path, &key, &config.encryption)?;
+    } else if path.exists() {
+        verify_key(path, &key, &config.encryption)?;
+    }
+
+    Ok(key)
+}
+
+fn verify_key(path: &Path, key: &DatabaseKey, config: &EncryptionConfig) -> DbResult<()> {
+    let conn = Connection::open(path)?;
+    apply_key(&conn, key, config).map_err(|_| invalid_key(path))
+}
+
+fn invalid_key(path: &Path) -> DatabaseError {
+    DatabaseError::EncryptionError(format!(
+        "Invalid key for {} or file is not a database",
+        path.display()
+    ))
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!("-{}", suffix));
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn plaintext_database(dir: &TempDir) -> PathBuf {
+        let path = dir.path().join("plain.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT);
+             INSERT INTO notes (body) VALUES ('skid marks 14m');
+             PRAGMA user_version = 3;",
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn test_resolve_key_sources() {
+        let dir = TempDir::new().unwrap();
+        let key_file = dir.path().join("db.key");
+        fs::write(&key_file, format!("{}\n", "ab".repeat(32))).unwrap();
+
+        let mut config = EncryptionConfig::new(KeySource::File { path: key_file });
+        config.key_format = KeyFormat::RawHex;
+        let key = config.resolve_key().unwrap();
+        assert_eq!(key.pragma_value(), format!("x'{}'", "AB".repeat(32)));
+
+        std::env::set_var("ACCUSCENE_TEST_DB_KEY", "correct horse");
+        let config = EncryptionConfig::new(KeySource::Env {
+            var: "ACCUSCENE_TEST_DB_KEY".to_string(),
+        });
+        assert_eq!(
+            config.resolve_key().unwrap().pragma_value(),
+            "correct horse"
+        );
+
+        let config = EncryptionConfig::new(KeySource::Env {
+            var: "ACCUSCENE_TEST_DB_KEY_MISSING".to_string(),
+        });
+        assert!(config.resolve_key().is_err());
+    }
+
+    #[test]
+    fn test_key_validation_and_redaction() {
+        assert!(DatabaseKey::passphrase("").is_err());
+        assert!(DatabaseKey::raw(&[0u8; 16]).is_err());
+        assert!(DatabaseKey::from_hex("not hex").is_err());
+
+        let key = DatabaseKey::passphrase("hunter2").unwrap();
+        assert!(!format!("{:?}", key).contains("hunter2"));
+    }
+
+    #[test]
+    fn test_is_plaintext_database() {
+        let dir = TempDir::new().unwrap();
+        assert!(is_plaintext_database(&plaintext_database(&dir)).unwrap());
+        assert!(!is_plaintext_database(&dir.path().join("missing.db")).unwrap());
+
+        let garbage = dir.path().join("garbage.db");
+        fs::write(&garbage, [0x5Au8; 64]).unwrap();
+        assert!(!is_plaintext_database(&garbage).unwrap());
+    }
+
+    #[cfg(not(feature = "sqlcipher"))]
+    #[test]
+    fn test_encryption_requires_sqlcipher() {
+        let config = DatabaseConfig {
+            encryption: EncryptionConfig::new(KeySource::Inline {
+                key: "secret".to_string(),
+            }),
+            ..DatabaseConfig::in_memory()
+        };
+
+        let err = prepare_database(&config).unwrap_err();
+        assert!(matches!(err, DatabaseError::EncryptionError(_)));
+    }
+
+    #[cfg(feature = "sqlcipher")]
+    #[test]
+    fn test_encrypt_plaintext_and_rotate_key() {
+        let dir = TempDir::new().unwrap();
+        let path = plaintext_database(&dir);
+        let config = EncryptionConfig::default();
+        let key = DatabaseKey::passphrase("first").unwrap();
+
+        encrypt_plaintext_database(&path, &key, &config).unwrap();
+        assert!(!is_plaintext_database(&path).unwrap());
+
+        let conn = Connection::open(&path).unwrap();
+        apply_key(&conn, &key, &config).unwrap();
+        let body: String = conn.query_row("SELECT body FROM notes", [], |row| row.get(0)).unwrap();
+        let user_version: i64 =
+            conn.query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap();
+        assert_eq!(body, "skid marks 14m");
+        assert_eq!(user_version, 3);
+        drop(conn);
+
+        let new_key = DatabaseKey::raw(&[7u8; 32]).unwrap();
+        rotate_key(&path, &key, &new_key, &config).unwrap();
+        assert!(verify_key(&path, &key, &config).is_err());
+        assert!(verify_key(&path, &new_key, &config).is_ok());
+    }
+}
@@ -51,6 +51,15 @@ pub enum DatabaseError {
         value: String,
     },
 
+    /// Optimistic concurrency conflict
+    #[error("Version conflict: {entity} {id} is at version {current_version}, expected {expected_version}")]
+    Conflict {
+        entity: String,
+        id: String,
+        expected_version: i64,
+        current_version: i64,
+    },
+
     /// Constraint violation
     #[error("Constraint violation: {constraint} on {table}")]
     ConstraintViolation { constraint: String, table: String },
@@ -71,6 +80,10 @@ pub enum DatabaseError {
     #[error("Backup error: {0}")]
     BackupError(String),
 
+    /// Encryption at rest errors
+    #[error("Encryption error: {0}")]
+    EncryptionError(String),
+
     /// Audit logging errors
     #[error("Audit logging error: {0}")]
     AuditError(String),
@@ -83,6 +96,10 @@ pub enum DatabaseError {
     #[error("Lock timeout: {0}")]
     LockTimeout(String),
 
+    /// Row-level security rejected the operation
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
     /// Deadlock detected
     #[error("Deadlock detected: {0}")]
     Deadlock(String),
@@ -131,7 +148,7 @@ impl From<serde_json::Error> for DatabaseError {
     }
 }
 
-#[cfg(feature = "postgres")]
+#[cfg(any(feature = "postgres", feature = "async"))]
 impl From<sqlx::Error> for DatabaseError {
     fn from(err: sqlx::Error) -> Self {
         match err {
@@ -164,6 +181,16 @@ impl DatabaseError {
         matches!(self, DatabaseError::ConstraintViolation { .. })
     }
 
+    /// Check if error is an optimistic concurrency conflict
+    pub fn is_conflict(&self) -> bool {
+        matches!(self, DatabaseError::Conflict { .. })
+    }
+
+    /// Check if error is a row-level security denial
+    pub fn is_permission_denied(&self) -> bool {
+        matches!(self, DatabaseError::PermissionDenied(_))
+    }
+
     /// Check if error is transient and can be retried
     pub fn is_transient(&self) -> bool {
         matches!(
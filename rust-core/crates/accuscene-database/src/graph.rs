@@ -0,0 +1,827 @@
+//! Case relationship graph
+//!
+//! Cases, vehicles, people, locations and evidence form a graph: the same
+//! driver shows up in several cases, the same intersection recurs. This
+//! module keeps a lightweight typed graph alongside the relational tables and
+//! answers traversal queries (neighbors, shortest path, common entities) and
+//! pattern queries such as "vehicles appearing in more than two cases this
+//! year". Queries can be submitted as serializable [`GraphQuery`] values so
+//! that analytics and search can run them without knowing the schema.
+
+use crate::error::{DatabaseError, DbResult};
+use crate::repositories::{
+    AccidentRepository, CaseRepository, EvidenceRepository, Repository, VehicleRepository,
+};
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Default depth limit for path searches
+pub const DEFAULT_MAX_DEPTH: usize = 6;
+
+/// Kind of graph node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+    /// Case
+    Case,
+    /// Vehicle, identified by VIN or plate
+    Vehicle,
+    /// Driver, witness or other party
+    Person,
+    /// Accident location
+    Location,
+    /// Evidence item
+    Evidence,
+}
+
+impl NodeKind {
+    /// Get the kind name
+    pub fn as_str(&self) -> &str {
+        match self {
+            NodeKind::Case => "case",
+            NodeKind::Vehicle => "vehicle",
+            NodeKind::Person => "person",
+            NodeKind::Location => "location",
+            NodeKind::Evidence => "evidence",
+        }
+    }
+
+    /// Parse a kind name
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "case" => Some(NodeKind::Case),
+            "vehicle" => Some(NodeKind::Vehicle),
+            "person" => Some(NodeKind::Person),
+            "location" => Some(NodeKind::Location),
+            "evidence" => Some(NodeKind::Evidence),
+            _ => None,
+        }
+    }
+}
+
+/// Type of graph edge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeType {
+    /// Case involves a vehicle
+    Involves,
+    /// Person drove a vehicle
+    Drove,
+    /// Person witnessed a case
+    Witnessed,
+    /// Case occurred at a location
+    OccurredAt,
+    /// Case has a piece of evidence
+    HasEvidence,
+    /// Generic relationship
+    RelatedTo,
+}
+
+impl EdgeType {
+    /// Get the edge type name
+    pub fn as_str(&self) -> &str {
+        match self {
+            EdgeType::Involves => "involves",
+            EdgeType::Drove => "drove",
+            EdgeType::Witnessed => "witnessed",
+            EdgeType::OccurredAt => "occurred_at",
+            EdgeType::HasEvidence => "has_evidence",
+            EdgeType::RelatedTo => "related_to",
+        }
+    }
+
+    /// Parse an edge type name
+    pub fn parse(edge_type: &str) -> Option<Self> {
+        match edge_type {
+            "involves" => Some(EdgeType::Involves),
+            "drove" => Some(EdgeType::Drove),
+            "witnessed" => Some(EdgeType::Witnessed),
+            "occurred_at" => Some(EdgeType::OccurredAt),
+            "has_evidence" => Some(EdgeType::HasEvidence),
+            "related_to" => Some(EdgeType::RelatedTo),
+            _ => None,
+        }
+    }
+}
+
+/// Edge direction for traversals
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    /// Edges leaving the node
+    Outgoing,
+    /// Edges entering the node
+    Incoming,
+    /// Edges in either direction
+    #[default]
+    Both,
+}
+
+/// Graph node
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphNode {
+    /// Node ID (`<kind>:<key>`)
+    pub id: String,
+    /// Node kind
+    pub kind: NodeKind,
+    /// Normalized identity key within the kind
+    pub entity_key: String,
+    /// Display label
+    pub label: String,
+    /// Additional properties
+    pub properties: Option<serde_json::Value>,
+}
+
+impl GraphNode {
+    /// Create a node; the ID is derived from kind and key
+    pub fn new(kind: NodeKind, entity_key: impl Into<String>, label: impl Into<String>) -> Self {
+        let entity_key = entity_key.into();
+        Self {
+            id: Self::node_id(kind, &entity_key),
+            kind,
+            entity_key,
+            label: label.into(),
+            properties: None,
+        }
+    }
+
+    /// Set node properties
+    pub fn with_properties(mut self, properties: serde_json::Value) -> Self {
+        self.properties = Some(properties);
+        self
+    }
+
+    /// Build the node ID for a kind and key
+    pub fn node_id(kind: NodeKind, entity_key: &str) -> String {
+        format!("{}:{}", kind.as_str(), entity_key)
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let kind: String = row.get(1)?;
+        let properties: Option<String> = row.get(4)?;
+
+        Ok(Self {
+            id: row.get(0)?,
+            kind: NodeKind::parse(&kind).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    1,
+                    rusqlite::types::Type::Text,
+                    format!("unknown node kind: {}", kind).into(),
+                )
+            })?,
+            entity_key: row.get(2)?,
+            label: row.get(3)?,
+            properties: properties.and_then(|s| serde_json::from_str(&s).ok()),
+        })
+    }
+}
+
+/// Graph edge
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphEdge {
+    /// Edge ID
+    pub id: String,
+    /// Source node ID
+    pub source_id: String,
+    /// Target node ID
+    pub target_id: String,
+    /// Edge type
+    pub edge_type: EdgeType,
+    /// Case in which the relationship was observed
+    pub case_id: Option<String>,
+    /// When the relationship was observed
+    pub observed_at: String,
+    /// Additional properties
+    pub properties: Option<serde_json::Value>,
+}
+
+impl GraphEdge {
+    /// Create an edge observed now
+    pub fn new(source_id: impl Into<String>, target_id: impl Into<String>, edge_type: EdgeType) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            source_id: source_id.into(),
+            target_id: target_id.into(),
+            edge_type,
+            case_id: None,
+            observed_at: chrono::Utc::now().to_rfc3339(),
+            properties: None,
+        }
+    }
+
+    /// Scope the edge to a case
+    pub fn in_case(mut self, case_id: impl Into<String>) -> Self {
+        self.case_id = Some(case_id.into());
+        self
+    }
+
+    /// Set the observation time
+    pub fn observed_at(mut self, observed_at: impl Into<String>) -> Self {
+        self.observed_at = observed_at.into();
+        self
+    }
+
+    /// Set edge properties
+    pub fn with_properties(mut self, properties: serde_json::Value) -> Self {
+        self.properties = Some(properties);
+        self
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let edge_type: String = row.get(3)?;
+        let properties: Option<String> = row.get(6)?;
+
+        Ok(Self {
+            id: row.get(0)?,
+            source_id: row.get(1)?,
+            target_id: row.get(2)?,
+            edge_type: EdgeType::parse(&edge_type).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    3,
+                    rusqlite::types::Type::Text,
+                    format!("unknown edge type: {}", edge_type).into(),
+                )
+            })?,
+            case_id: row.get(4)?,
+            observed_at: row.get(5)?,
+            properties: properties.and_then(|s| serde_json::from_str(&s).ok()),
+        })
+    }
+
+    /// The node on the other side of this edge
+    pub fn other_end(&self, node_id: &str) -> &str {
+        if self.source_id == node_id {
+            &self.target_id
+        } else {
+            &self.source_id
+        }
+    }
+}
+
+/// Entity with the number of distinct cases it appears in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityCaseCount {
+    /// Entity node
+    pub node: GraphNode,
+    /// Number of distinct cases
+    pub case_count: i64,
+}
+
+/// Case related to another through shared entities
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelatedCase {
+    /// Related case ID
+    pub case_id: String,
+    /// Number of shared entities
+    pub shared_entities: i64,
+}
+
+/// Result of synchronizing a case into the graph
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GraphSyncStats {
+    /// Nodes written
+    pub nodes: usize,
+    /// Edges created
+    pub edges: usize,
+}
+
+/// Serializable graph query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "query", rename_all = "snake_case")]
+pub enum GraphQuery {
+    /// Nodes adjacent to a node
+    Neighbors {
+        /// Start node
+        node_id: String,
+        /// Edge direction
+        #[serde(default)]
+        direction: Direction,
+        /// Restrict to one edge type
+        edge_type: Option<EdgeType>,
+    },
+    /// Shortest undirected path between two nodes
+    ShortestPath {
+        /// Start node
+        from: String,
+        /// End node
+        to: String,
+        /// Depth limit
+        max_depth: Option<usize>,
+    },
+    /// Entities shared by two cases
+    CommonEntities {
+        /// First case
+        case_a: String,
+        /// Second case
+        case_b: String,
+        /// Restrict to one entity kind
+        kind: Option<NodeKind>,
+    },
+    /// Entities of a kind appearing in at least `min_cases` cases
+    RecurringEntities {
+        /// Entity kind
+        kind: NodeKind,
+        /// Minimum number of distinct cases
+        min_cases: i64,
+        /// Only count observations at or after this timestamp
+        since: Option<String>,
+    },
+    /// Cases sharing entities with a case
+    RelatedCases {
+        /// Case ID
+        case_id: String,
+    },
+}
+
+/// Graph store over the database
+pub struct CaseGraph;
+
+impl CaseGraph {
+    /// Create a new graph store
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Insert a node or update its label and properties
+    pub fn upsert_node(&self, conn: &Connection, node: &GraphNode) -> DbResult<()> {
+        let properties = node.properties.as_ref().map(|p| p.to_string());
+        conn.execute(
+            "INSERT INTO graph_nodes (id, kind, entity_key, label, properties)
+             VALUES (?, ?, ?, ?, ?)
+             ON CONFLICT(id) DO UPDATE SET label = excluded.label,
+                 properties = COALESCE(excluded.properties, graph_nodes.properties)",
+            params![node.id, node.kind.as_str(), node.entity_key, node.label, properties],
+        )?;
+        Ok(())
+    }
+
+    /// Find a node by ID
+    pub fn find_node(&self, conn: &Connection, id: &str) -> DbResult<Option<GraphNode>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, kind, entity_key, label, properties FROM graph_nodes WHERE id = ?",
+        )?;
+
+        let mut rows = stmt.query_map([id], GraphNode::from_row)?;
+        Ok(rows.next().transpose()?)
+    }
+
+    /// Add an edge; returns `false` if an identical edge already exists
+    pub fn add_edge(&self, conn: &Connection, edge: &GraphEdge) -> DbResult<bool> {
+        let properties = edge.properties.as_ref().map(|p| p.to_string());
+        let affected = conn.execute(
+            "INSERT OR IGNORE INTO graph_edges
+                 (id, source_id, target_id, edge_type, case_id, observed_at, properties)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+            params![
+                edge.id, edge.source_id, edge.target_id, edge.edge_type.as_str(),
+                edge.case_id, edge.observed_at, properties,
+            ],
+        )?;
+        Ok(affected > 0)
+    }
+
+    /// Remove all edges recorded for a case
+    pub fn remove_case_edges(&self, conn: &Connection, case_id: &str) -> DbResult<usize> {
+        let affected = conn.execute("DELETE FROM graph_edges WHERE case_id = ?", [case_id])?;
+        Ok(affected)
+    }
+
+    /// Remove nodes that no longer have any edges
+    pub fn prune_orphans(&self, conn: &Connection) -> DbResult<usize> {
+        let affected = conn.execute(
+            "DELETE FROM graph_nodes WHERE id NOT IN
+                 (SELECT source_id FROM graph_edges UNION SELECT target_id FROM graph_edges)",
+            [],
+        )?;
+        Ok(affected)
+    }
+
+    /// Edges touching a node
+    pub fn edges_of(
+        &self,
+        conn: &Connection,
+        node_id: &str,
+        direction: Direction,
+        edge_type: Option<EdgeType>,
+    ) -> DbResult<Vec<GraphEdge>> {
+        let endpoint = match direction {
+            Direction::Outgoing => "source_id = ?1",
+            Direction::Incoming => "target_id = ?1",
+            Direction::Both => "(source_id = ?1 OR target_id = ?1)",
+        };
+        let sql = format!(
+            "SELECT id, source_id, target_id, edge_type, case_id, observed_at, properties
+             FROM graph_edges WHERE {} AND (?2 IS NULL OR edge_type = ?2)
+             ORDER BY observed_at",
+            endpoint
+        );
+
+        let mut stmt = conn.prepare(&sql)?;
+        let edges = stmt
+            .query_map(
+                params![node_id, edge_type.map(|t| t.as_str().to_string())],
+                GraphEdge::from_row,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(edges)
+    }
+
+    /// Nodes adjacent to a node
+    pub fn neighbors(
+        &self,
+        conn: &Connection,
+        node_id: &str,
+        direction: Direction,
+        edge_type: Option<EdgeType>,
+    ) -> DbResult<Vec<GraphNode>> {
+        let mut seen = HashSet::new();
+        let mut nodes = Vec::new();
+
+        for edge in self.edges_of(conn, node_id, direction, edge_type)? {
+            let other = edge.other_end(node_id).to_string();
+            if seen.insert(other.clone()) {
+                if let Some(node) = self.find_node(conn, &other)? {
+                    nodes.push(node);
+                }
+            }
+        }
+
+        Ok(nodes)
+    }
+
+    /// Shortest undirected path between two nodes, as a list of node IDs
+    pub fn shortest_path(
+        &self,
+        conn: &Connection,
+        from: &str,
+        to: &str,
+        max_depth: usize,
+    ) -> DbResult<Option<Vec<String>>> {
+        if from == to {
+            return Ok(Some(vec![from.to_string()]));
+        }
+
+        let mut previous: HashMap<String, String> = HashMap::new();
+        let mut visited: HashSet<String> = HashSet::from([from.to_string()]);
+        let mut queue = VecDeque::from([(from.to_string(), 0usize)]);
+
+        while let Some((node, depth)) = queue.pop_front() {
+            if depth >= max_depth {
+                continue;
+            }
+
+            for edge in self.edges_of(conn, &node, Direction::Both, None)? {
+                let next = edge.other_end(&node).to_string();
+                if !visited.insert(next.clone()) {
+                    continue;
+                }
+                previous.insert(next.clone(), node.clone());
+
+                if next == to {
+                    let mut path = vec![next];
+                    while let Some(prev) = previous.get(path.last().unwrap()) {
+                        path.push(prev.clone());
+                    }
+                    path.reverse();
+                    return Ok(Some(path));
+                }
+
+                queue.push_back((next, depth + 1));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Entities that appear in both cases
+    pub fn common_entities(
+        &self,
+        conn: &Connection,
+        case_a: &str,
+        case_b: &str,
+        kind: Option<NodeKind>,
+    ) -> DbResult<Vec<GraphNode>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, kind, entity_key, label, properties FROM graph_nodes
+             WHERE kind != 'case' AND (?3 IS NULL OR kind = ?3)
+               AND id IN (SELECT source_id FROM graph_edges WHERE case_id = ?1
+                          UNION SELECT target_id FROM graph_edges WHERE case_id = ?1)
+               AND id IN (SELECT source_id FROM graph_edges WHERE case_id = ?2
+                          UNION SELECT target_id FROM graph_edges WHERE case_id = ?2)
+             ORDER BY kind, label",
+        )?;
+
+        let nodes = stmt
+            .query_map(
+                params![case_a, case_b, kind.map(|k| k.as_str().to_string())],
+                GraphNode::from_row,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(nodes)
+    }
+
+    /// Entities of a kind that appear in at least `min_cases` distinct cases,
+    /// optionally only counting observations since a timestamp
+    pub fn recurring_entities(
+        &self,
+        conn: &Connection,
+        kind: NodeKind,
+        min_cases: i64,
+        since: Option<&str>,
+    ) -> DbResult<Vec<EntityCaseCount>> {
+        let mut stmt = conn.prepare(
+            "SELECT n.id, n.kind, n.entity_key, n.label, n.properties,
+                    COUNT(DISTINCT e.case_id) AS case_count
+             FROM graph_nodes n
+             JOIN graph_edges e ON e.source_id = n.id OR e.target_id = n.id
+             WHERE n.kind = ?1 AND e.case_id IS NOT NULL
+               AND (?3 IS NULL OR e.observed_at >= ?3)
+             GROUP BY n.id
+             HAVING case_count >= ?2
+             ORDER BY case_count DESC, n.label",
+        )?;
+
+        let entities = stmt
+            .query_map(params![kind.as_str(), min_cases, since], |row| {
+                Ok(EntityCaseCount {
+                    node: GraphNode::from_row(row)?,
+                    case_count: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(entities)
+    }
+
+    /// Cases that share at least one entity with a case
+    pub fn related_cases(&self, conn: &Connection, case_id: &str) -> DbResult<Vec<RelatedCase>> {
+        let mut stmt = conn.prepare(
+            "WITH entities AS (
+                 SELECT n.id FROM graph_nodes n
+                 JOIN graph_edges e ON e.source_id = n.id OR e.target_id = n.id
+                 WHERE e.case_id = ?1 AND n.kind != 'case'
+             )
+             SELECT e.case_id, COUNT(DISTINCT n.id) AS shared
+             FROM graph_edges e
+             JOIN graph_nodes n ON e.source_id = n.id OR e.target_id = n.id
+             WHERE n.id IN (SELECT id FROM entities)
+               AND e.case_id IS NOT NULL AND e.case_id != ?1
+             GROUP BY e.case_id
+             ORDER BY shared DESC",
+        )?;
+
+        let cases = stmt
+            .query_map([case_id], |row| {
+                Ok(RelatedCase {
+                    case_id: row.get(0)?,
+                    shared_entities: row.get(1)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(cases)
+    }
+
+    /// Rebuild the graph neighborhood of a case from the relational tables
+    pub fn sync_case(&self, conn: &Connection, case_id: &str) -> DbResult<GraphSyncStats> {
+        let case = CaseRepository::new()
+            .find_by_id(conn, &case_id.to_string())?
+            .ok_or_else(|| DatabaseError::not_found("Case", "id", case_id))?;
+
+        self.remove_case_edges(conn, case_id)?;
+        let mut stats = GraphSyncStats::default();
+
+        let case_node = GraphNode::new(NodeKind::Case, &case.id, &case.title)
+            .with_properties(serde_json::json!({ "case_number": case.case_number }));
+        self.upsert_node(conn, &case_node)?;
+        stats.nodes += 1;
+
+        let link = |conn: &Connection, stats: &mut GraphSyncStats, node: &GraphNode, edge: GraphEdge| {
+            self.upsert_node(conn, node)?;
+            stats.nodes += 1;
+            if self.add_edge(conn, &edge.in_case(case_id))? {
+                stats.edges += 1;
+            }
+            DbResult::Ok(())
+        };
+
+        let vehicles = VehicleRepository::new();
+        for accident in AccidentRepository::new().find_by_case_id(conn, case_id)? {
+            let observed_at = accident.accident_date.clone();
+
+            let location = location_node(
+                &accident.location,
+                accident.location_lat,
+                accident.location_lng,
+            );
+            let edge = GraphEdge::new(&case_node.id, &location.id, EdgeType::OccurredAt)
+                .observed_at(observed_at.clone());
+            link(conn, &mut stats, &location, edge)?;
+
+            for vehicle in vehicles.find_by_accident_id(conn, &accident.id)? {
+                let vehicle_node = vehicle_node(&vehicle);
+                let edge = GraphEdge::new(&case_node.id, &vehicle_node.id, EdgeType::Involves)
+                    .observed_at(observed_at.clone());
+                link(conn, &mut stats, &vehicle_node, edge)?;
+
+                if let Some(driver) = vehicle.driver_info.as_ref().and_then(person_node) {
+                    let edge = GraphEdge::new(&driver.id, &vehicle_node.id, EdgeType::Drove)
+                        .observed_at(observed_at.clone());
+                    link(conn, &mut stats, &driver, edge)?;
+                }
+            }
+        }
+
+        for evidence in EvidenceRepository::new().find_by_case_id(conn, case_id)? {
+            let node = GraphNode::new(NodeKind::Evidence, &evidence.id, &evidence.title)
+                .with_properties(serde_json::json!({ "evidence_type": evidence.evidence_type }));
+            let edge = GraphEdge::new(&case_node.id, &node.id, EdgeType::HasEvidence);
+            link(conn, &mut stats, &node, edge)?;
+        }
+
+        Ok(stats)
+    }
+
+    /// Execute a serializable query and return the result as JSON
+    pub fn execute(&self, conn: &Connection, query: &GraphQuery) -> DbResult<serde_json::Value> {
+        let value = match query {
+            GraphQuery::Neighbors { node_id, direction, edge_type } => {
+                serde_json::to_value(self.neighbors(conn, node_id, *direction, *edge_type)?)?
+            }
+            GraphQuery::ShortestPath { from, to, max_depth } => serde_json::to_value(
+                self.shortest_path(conn, from, to, max_depth.unwrap_or(DEFAULT_MAX_DEPTH))?,
+            )?,
+            GraphQuery::CommonEntities { case_a, case_b, kind } => {
+                serde_json::to_value(self.common_entities(conn, case_a, case_b, *kind)?)?
+            }
+            GraphQuery::RecurringEntities { kind, min_cases, since } => serde_json::to_value(
+                self.recurring_entities(conn, *kind, *min_cases, since.as_deref())?,
+            )?,
+            GraphQuery::RelatedCases { case_id } => {
+                serde_json::to_value(self.related_cases(conn, case_id)?)?
+            }
+        };
+
+        Ok(value)
+    }
+}
+
+impl Default for CaseGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Normalize free text for use as a node key
+fn normalize_key(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Location node, keyed by rounded coordinates when available (about 10 m)
+fn location_node(location: &str, lat: Option<f64>, lng: Option<f64>) -> GraphNode {
+    let key = match (lat, lng) {
+        (Some(lat), Some(lng)) => format!("geo:{:.4},{:.4}", lat, lng),
+        _ => normalize_key(location),
+    };
+    GraphNode::new(NodeKind::Location, key, location)
+}
+
+/// Vehicle node, keyed by VIN, then plate, then the row ID
+fn vehicle_node(vehicle: &crate::repositories::vehicle::Vehicle) -> GraphNode {
+    let key = if let Some(vin) = vehicle.vin.as_deref().filter(|v| !v.trim().is_empty()) {
+        format!("vin:{}", vin.trim().to_uppercase())
+    } else if let Some(plate) = vehicle.license_plate.as_deref().filter(|p| !p.trim().is_empty()) {
+        format!("plate:{}", plate.replace(char::is_whitespace, "").to_uppercase())
+    } else {
+        format!("row:{}", vehicle.id)
+    };
+
+    let label = [vehicle.make.as_deref(), vehicle.model.as_deref()]
+        .iter()
+        .flatten()
+        .copied()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let label = if label.is_empty() { key.clone() } else { label };
+
+    GraphNode::new(NodeKind::Vehicle, key, label)
+}
+
+/// Person node from a vehicle's driver info, keyed by licence number or name
+fn person_node(driver_info: &serde_json::Value) -> Option<GraphNode> {
+    let name = driver_info.get("name").and_then(|v| v.as_str())?;
+    let licence = driver_info
+        .get("license_number")
+        .or_else(|| driver_info.get("licence_number"))
+        .and_then(|v| v.as_str())
+        .filter(|l| !l.trim().is_empty());
+
+    let key = match licence {
+        Some(licence) => format!("licence:{}", licence.trim().to_uppercase()),
+        None => format!("name:{}", normalize_key(name)),
+    };
+    Some(GraphNode::new(NodeKind::Person, key, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::v001_initial::InitialMigration;
+    use crate::migrations::v004_entity_graph::EntityGraphMigration;
+    use crate::migrations::v007_soft_delete::SoftDeleteMigration;
+    use crate::migrations::v008_row_versioning::RowVersioningMigration;
+    use crate::migrations::Migration;
+
+    fn setup() -> Connection {
+        let mut conn = Connection::open_in_memory().unwrap();
+        InitialMigration.up(&mut conn).unwrap();
+        EntityGraphMigration.up(&mut conn).unwrap();
+        SoftDeleteMigration.up(&mut conn).unwrap();
+        RowVersioningMigration.up(&mut conn).unwrap();
+        conn.execute_batch(
+            r#"
+            INSERT INTO users (id, email, username, full_name, password_hash)
+                VALUES ('u1', 'u1@example.com', 'u1', 'User One', 'hash');
+            INSERT INTO cases (id, case_number, title, created_by) VALUES ('c1', 'C-1', 'First', 'u1');
+            INSERT INTO cases (id, case_number, title, created_by) VALUES ('c2', 'C-2', 'Second', 'u1');
+            INSERT INTO accidents (id, case_id, accident_date, location)
+                VALUES ('a1', 'c1', '2026-03-01T10:00:00Z', 'Main St & 5th Ave');
+            INSERT INTO accidents (id, case_id, accident_date, location)
+                VALUES ('a2', 'c2', '2026-06-01T10:00:00Z', 'main st  &  5th ave');
+            INSERT INTO vehicles (id, accident_id, vehicle_number, make, model, vin, driver_info)
+                VALUES ('v1', 'a1', 1, 'Ford', 'Focus', '1fadp3f2xel', '{"name": "Jane Doe"}');
+            INSERT INTO vehicles (id, accident_id, vehicle_number, make, model, vin, driver_info)
+                VALUES ('v2', 'a2', 1, 'Ford', 'Focus', '1FADP3F2XEL', '{"name": "jane  doe"}');
+            "#,
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_sync_links_shared_entities() {
+        let conn = setup();
+        let graph = CaseGraph::new();
+
+        graph.sync_case(&conn, "c1").unwrap();
+        graph.sync_case(&conn, "c2").unwrap();
+
+        let common = graph.common_entities(&conn, "c1", "c2", None).unwrap();
+        let kinds: HashSet<NodeKind> = common.iter().map(|n| n.kind).collect();
+        assert_eq!(common.len(), 3);
+        assert!(kinds.contains(&NodeKind::Vehicle));
+        assert!(kinds.contains(&NodeKind::Location));
+        assert!(kinds.contains(&NodeKind::Person));
+
+        let related = graph.related_cases(&conn, "c1").unwrap();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].case_id, "c2");
+
+        // Re-syncing is idempotent
+        graph.sync_case(&conn, "c1").unwrap();
+        let edges: i64 = conn
+            .query_row("SELECT COUNT(*) FROM graph_edges WHERE case_id = 'c1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(edges, 3);
+    }
+
+    #[test]
+    fn test_traversal_queries() {
+        let conn = setup();
+        let graph = CaseGraph::new();
+        graph.sync_case(&conn, "c1").unwrap();
+        graph.sync_case(&conn, "c2").unwrap();
+
+        let path = graph
+            .shortest_path(&conn, "case:c1", "case:c2", DEFAULT_MAX_DEPTH)
+            .unwrap()
+            .unwrap();
+        assert_eq!(path.len(), 3);
+
+        let recurring = graph
+            .recurring_entities(&conn, NodeKind::Vehicle, 2, Some("2026-01-01"))
+            .unwrap();
+        assert_eq!(recurring.len(), 1);
+        assert_eq!(recurring[0].case_count, 2);
+
+        let none = graph
+            .recurring_entities(&conn, NodeKind::Vehicle, 2, Some("2026-04-01"))
+            .unwrap();
+        assert!(none.is_empty());
+
+        let query: GraphQuery = serde_json::from_value(serde_json::json!({
+            "query": "neighbors",
+            "node_id": "case:c1",
+            "edge_type": "involves"
+        }))
+        .unwrap();
+        let result = graph.execute(&conn, &query).unwrap();
+        assert_eq!(result.as_array().unwrap().len(), 1);
+    }
+}
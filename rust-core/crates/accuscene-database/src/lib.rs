@@ -6,13 +6,20 @@
 //! # Features
 //!
 //! - **Connection Pooling**: Efficient connection management with r2d2
+//! - **Read Routing**: Read replicas with primary fallback and attached secondary databases
 //! - **Migrations**: Automatic schema migrations with version tracking
-//! - **Repositories**: Repository pattern for type-safe data access
+//! - **Repositories**: Repository pattern for type-safe data access with soft delete and restore
 //! - **Query Builder**: Type-safe query construction with filtering and pagination
 //! - **Transactions**: Transaction support with automatic rollback and retry logic
 //! - **Audit Logging**: Comprehensive audit trail for compliance
-//! - **Full-Text Search**: FTS5-powered search with ranking and snippets
-//! - **Backup/Restore**: Database backup and restore functionality
+//! - **Full-Text Search**: FTS5-powered search with ranking and snippets, including attachment contents
+//! - **Relationship Graph**: Typed entity graph with traversal and pattern queries
+//! - **Party Registry**: Deduplicated person registry with fuzzy matching and merge history
+//! - **Row-Level Security**: Auth contexts on queries with pluggable row policies
+//! - **Change Data Capture**: Trigger-based changelog with per-consumer change feeds
+//! - **Encryption at Rest**: SQLCipher keying, key rotation and plaintext migration
+//! - **Async Backend**: sqlx-based pool and async repositories for tokio services (`async` feature)
+//! - **Backup/Restore**: Full and incremental backups with point-in-time restore
 //!
 //! # Example
 //!
@@ -42,6 +49,7 @@ pub mod error;
 pub mod config;
 pub mod pool;
 pub mod connection;
+pub mod encryption;
 
 // Database schema and migrations
 pub mod migrations;
@@ -50,31 +58,43 @@ pub mod schema;
 // Data access layer
 pub mod repositories;
 pub mod query;
+pub mod row_security;
 
 // Enterprise features
 pub mod transaction;
 pub mod backup;
 pub mod audit;
 pub mod search;
+pub mod graph;
+pub mod cdc;
+
+// Async backend
+#[cfg(feature = "async")]
+pub mod async_db;
 
 // Re-export commonly used types
 pub use error::{DatabaseError, DbResult};
 pub use config::{
     DatabaseConfig, PoolConfig, PerformanceConfig, FeatureConfig, BackupConfig,
+    IncrementalBackupConfig, EncryptionConfig, KeySource, KeyFormat, RoutingConfig,
+    AttachedDatabase,
     SynchronousMode, AutoVacuum, JournalMode,
 };
 pub use pool::{DatabasePool, PoolState, PoolStats};
 pub use connection::{DbConnection, DbTransaction, IsolationLevel};
+pub use encryption::DatabaseKey;
 
 // Re-export migration types
 pub use migrations::{Migration, MigrationRegistry, MigrationHistory};
 pub use migrations::runner::MigrationRunner;
+pub use migrations::diff::{SchemaDiff, SchemaSnapshot};
 
 // Re-export repository types
 pub use repositories::{
     Repository,
     CaseRepository, AccidentRepository, VehicleRepository,
-    EvidenceRepository, UserRepository,
+    EvidenceRepository, UserRepository, OrganizationRepository,
+    PartyRepository, TrashEntry, SecuredRepository, EntityId,
 };
 
 // Re-export repository entity types
@@ -84,9 +104,12 @@ pub use repositories::vehicle::Vehicle;
 pub use repositories::evidence::Evidence;
 pub use repositories::user::User;
 
+// Re-export row-level security types
+pub use row_security::{AuthContext, RowAction, RowFilter, RowPolicy, ScopedRowPolicy, UnrestrictedPolicy};
+
 // Re-export query types
 pub use query::{
-    QueryBuilder, Filter, FilterOperator, FilterCondition, FilterValue,
+    QueryBuilder, TrashFilter, Filter, FilterOperator, FilterCondition, FilterValue,
     Pagination, PaginationResult, CursorPagination, CursorPaginationResult,
 };
 
@@ -94,13 +117,26 @@ pub use query::{
 pub use transaction::{TransactionOptions, TransactionManager, execute_transaction, with_transaction};
 
 // Re-export backup types
-pub use backup::{BackupManager, BackupInfo};
+pub use backup::{BackupManager, BackupInfo, BackupKind, BackupManifest};
+
+// Re-export graph types
+pub use graph::{CaseGraph, EdgeType, GraphEdge, GraphNode, GraphQuery, NodeKind};
+
+// Re-export async backend types
+#[cfg(feature = "async")]
+pub use async_db::{AsyncDatabasePool, AsyncRepository, FromSqliteRow};
+
+// Re-export change data capture types
+pub use cdc::{ChangeCapture, ChangeFeed, ChangeOperation, ChangeRecord};
 
 // Re-export audit types
 pub use audit::{AuditLogger, AuditEntry, AuditAction, ChangeValue};
 
 // Re-export search types
-pub use search::{SearchManager, SearchResult, SearchOptions, SearchQuery};
+pub use search::{
+    SearchManager, SearchResult, SearchOptions, SearchQuery,
+    AttachmentMatch, AttachmentText, ExtractionSource,
+};
 
 /// Database version
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -177,6 +213,7 @@ impl DatabaseManager {
     /// Create a new database manager
     pub fn new(config: DatabaseConfig) -> DbResult<Self> {
         let backup_dir = config.backup.directory.clone();
+        let incremental = config.backup.incremental.clone();
         let pool = initialize_database_with_config(config.clone())?;
 
         Ok(Self {
@@ -187,7 +224,7 @@ impl DatabaseManager {
                 AuditLogger::disabled()
             },
             search_manager: SearchManager::new(),
-            backup_manager: BackupManager::new(backup_dir),
+            backup_manager: BackupManager::new(backup_dir).with_incremental(incremental),
         })
     }
 
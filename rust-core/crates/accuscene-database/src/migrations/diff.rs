@@ -0,0 +1,1194 @@
+//! Schema introspection, diffing and migration generation
+//!
+//! Compares the live schema of a database with a declared schema and
+//! generates candidate up/down SQL for a new migration. The declared schema
+//! is either the one produced by the registered migrations
+//! ([`SchemaSnapshot::declared`]) or hand-written DDL describing the desired
+//! schema ([`SchemaSnapshot::from_sql`]).
+//!
+//! Columns are added and dropped with `ALTER TABLE` where SQLite allows it;
+//! any other table change (column types, constraints, keys) rebuilds the
+//! table by copying the shared columns into a new table. Generated SQL is a
+//! starting point for review: run [`SchemaDiff::dry_run`] to apply it inside
+//! a rolled back transaction and check the outcome before committing it as a
+//! migration.
+
+use super::runner::MigrationRunner;
+use super::Migration;
+use crate::error::{DatabaseError, DbResult};
+use rusqlite::Connection;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use tracing::{debug, info};
+
+/// Tables managed by the migration runner, never part of a diff
+const RUNNER_TABLES: &[&str] = &["_migrations"];
+
+/// Suffixes of the shadow tables virtual table modules create
+const SHADOW_TABLE_SUFFIXES: &[&str] = &[
+    "_data",
+    "_idx",
+    "_content",
+    "_docsize",
+    "_config",
+    "_segments",
+    "_segdir",
+    "_stat",
+    "_node",
+    "_rowid",
+    "_parent",
+];
+
+/// Prefix for the temporary table used when rebuilding a table
+const REBUILD_PREFIX: &str = "_new_";
+
+/// Column as reported by `table_info`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnSchema {
+    /// Column name
+    pub name: String,
+    /// Declared type
+    pub sql_type: String,
+    /// NOT NULL constraint
+    pub not_null: bool,
+    /// Default value expression
+    pub default: Option<String>,
+    /// Position in the primary key (0 when not part of it)
+    pub primary_key: u32,
+}
+
+impl ColumnSchema {
+    fn matches(&self, other: &ColumnSchema) -> bool {
+        self.sql_type.eq_ignore_ascii_case(&other.sql_type)
+            && self.not_null == other.not_null
+            && self.default.as_deref().map(normalize_sql)
+                == other.default.as_deref().map(normalize_sql)
+            && self.primary_key == other.primary_key
+    }
+}
+
+/// Foreign key constraint of a table
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ForeignKeySchema {
+    /// Referencing columns
+    pub columns: Vec<String>,
+    /// Referenced table
+    pub table: String,
+    /// Referenced columns (empty for the primary key)
+    pub references: Vec<String>,
+    /// ON UPDATE action
+    pub on_update: String,
+    /// ON DELETE action
+    pub on_delete: String,
+}
+
+/// Table definition
+#[derive(Debug, Clone)]
+pub struct TableSchema {
+    /// Table name
+    pub name: String,
+    /// CREATE statement
+    pub sql: String,
+    /// Virtual table (e.g. FTS5)
+    pub is_virtual: bool,
+    /// Columns in declaration order
+    pub columns: Vec<ColumnSchema>,
+    /// Foreign key constraints
+    pub foreign_keys: Vec<ForeignKeySchema>,
+    /// Column sets of UNIQUE constraints
+    pub unique_constraints: Vec<Vec<String>>,
+}
+
+impl TableSchema {
+    /// Find a column by name
+    pub fn column(&self, name: &str) -> Option<&ColumnSchema> {
+        self.columns.iter().find(|c| c.name.eq_ignore_ascii_case(name))
+    }
+
+    fn is_constrained(&self, column: &str) -> bool {
+        let eq = |c: &String| c.eq_ignore_ascii_case(column);
+        self.column(column).is_some_and(|c| c.primary_key > 0)
+            || self.unique_constraints.iter().any(|u| u.iter().any(eq))
+            || self.foreign_keys.iter().any(|fk| fk.columns.iter().any(eq))
+    }
+}
+
+/// Index, trigger or view definition
+#[derive(Debug, Clone)]
+pub struct SchemaObject {
+    /// Object name
+    pub name: String,
+    /// Table the object belongs to
+    pub table: String,
+    /// CREATE statement
+    pub sql: String,
+}
+
+impl SchemaObject {
+    fn matches(&self, other: &SchemaObject) -> bool {
+        normalize_sql(&self.sql) == normalize_sql(&other.sql)
+    }
+}
+
+/// Kind of schema object
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectKind {
+    Table,
+    Index,
+    Trigger,
+    View,
+}
+
+impl ObjectKind {
+    /// SQL keyword for the object kind
+    pub fn as_str(&self) -> &str {
+        match self {
+            ObjectKind::Table => "TABLE",
+            ObjectKind::Index => "INDEX",
+            ObjectKind::Trigger => "TRIGGER",
+            ObjectKind::View => "VIEW",
+        }
+    }
+}
+
+/// A single difference between two schemas
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaChange {
+    /// Object only in the target schema
+    Create { kind: ObjectKind, name: String },
+    /// Object only in the current schema
+    Drop { kind: ObjectKind, name: String },
+    /// Object whose definition changed
+    Replace { kind: ObjectKind, name: String },
+    /// Column only in the target table
+    AddColumn { table: String, column: String },
+    /// Column only in the current table
+    DropColumn { table: String, column: String },
+    /// Column whose type, nullability, default or key changed
+    AlterColumn { table: String, column: String },
+    /// Foreign key or UNIQUE constraints of a table changed
+    AlterConstraints { table: String },
+}
+
+impl SchemaChange {
+    /// Check whether applying the change can lose data
+    pub fn is_destructive(&self) -> bool {
+        matches!(
+            self,
+            SchemaChange::Drop {
+                kind: ObjectKind::Table,
+                ..
+            } | SchemaChange::Replace {
+                kind: ObjectKind::Table,
+                ..
+            } | SchemaChange::DropColumn { .. }
+        )
+    }
+}
+
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaChange::Create { kind, name } => {
+                write!(f, "create {} {}", kind.as_str().to_lowercase(), name)
+            },
+            SchemaChange::Drop { kind, name } => {
+                write!(f, "drop {} {}", kind.as_str().to_lowercase(), name)
+            },
+            SchemaChange::Replace { kind, name } => {
+                write!(f, "replace {} {}", kind.as_str().to_lowercase(), name)
+            },
+            SchemaChange::AddColumn { table, column } => {
+                write!(f, "add column {}.{}", table, column)
+            },
+            SchemaChange::DropColumn { table, column } => {
+                write!(f, "drop column {}.{}", table, column)
+            },
+            SchemaChange::AlterColumn { table, column } => {
+                write!(f, "alter column {}.{}", table, column)
+            },
+            SchemaChange::AlterConstraints { table } => write!(f, "alter constraints of {}", table),
+        }
+    }
+}
+
+/// Introspected database schema
+#[derive(Debug, Clone, Default)]
+pub struct SchemaSnapshot {
+    /// Tables by name
+    pub tables: BTreeMap<String, TableSchema>,
+    /// Indexes by name
+    pub indexes: BTreeMap<String, SchemaObject>,
+    /// Triggers by name
+    pub triggers: BTreeMap<String, SchemaObject>,
+    /// Views by name
+    pub views: BTreeMap<String, SchemaObject>,
+}
+
+impl SchemaSnapshot {
+    /// Introspect the schema of a connection
+    pub fn introspect(conn: &Connection) -> DbResult<Self> {
+        let objects = conn
+            .prepare(
+                "SELECT type, name, tbl_name, sql FROM sqlite_master
+                 WHERE sql IS NOT NULL AND name NOT LIKE 'sqlite_%'
+                 ORDER BY name",
+            )?
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    SchemaObject {
+                        name: row.get(1)?,
+                        table: row.get(2)?,
+                        sql: row.get(3)?,
+                    },
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let virtual_tables: Vec<&str> = objects
+            .iter()
+            .filter(|(kind, object)| kind == "table" && is_virtual_table(&object.sql))
+            .map(|(_, object)| object.name.as_str())
+            .collect();
+        let is_shadow_table = |name: &str| {
+            virtual_tables.iter().any(|v| {
+                name.strip_prefix(v)
+                    .is_some_and(|suffix| SHADOW_TABLE_SUFFIXES.contains(&suffix))
+            })
+        };
+
+        let mut snapshot = Self::default();
+        for (kind, object) in &objects {
+            if RUNNER_TABLES.contains(&object.table.as_str()) {
+                continue;
+            }
+
+            match kind.as_str() {
+                "table" if !is_shadow_table(&object.name) => {
+                    let table = introspect_table(conn, object)?;
+                    snapshot.tables.insert(object.name.clone(), table);
+                },
+                "index" => {
+                    snapshot.indexes.insert(object.name.clone(), object.clone());
+                },
+                "trigger" => {
+                    snapshot.triggers.insert(object.name.clone(), object.clone());
+                },
+                "view" => {
+                    snapshot.views.insert(object.name.clone(), object.clone());
+                },
+                _ => {},
+            }
+        }
+
+        debug!(
+            "Introspected schema: {} tables, {} indexes, {} triggers, {} views",
+            snapshot.tables.len(),
+            snapshot.indexes.len(),
+            snapshot.triggers.len(),
+            snapshot.views.len()
+        );
+
+        Ok(snapshot)
+    }
+
+    /// Schema declared by the registered migrations
+    pub fn declared() -> DbResult<Self> {
+        Self::from_migrations(&MigrationRunner::new())
+    }
+
+    /// Schema produced by applying all migrations of a runner to an empty database
+    pub fn from_migrations(runner: &MigrationRunner) -> DbResult<Self> {
+        let mut conn = Connection::open_in_memory()?;
+        runner.migrate(&mut conn)?;
+        Self::introspect(&conn)
+    }
+
+    /// Schema described by DDL statements
+    pub fn from_sql(sql: &str) -> DbResult<Self> {
+        let conn = Connection::open_in_memory()?;
+        conn.execute_batch(sql)
+            .map_err(|e| DatabaseError::SchemaError(format!("Invalid schema definition: {}", e)))?;
+        Self::introspect(&conn)
+    }
+}
+
+/// Difference between a current and a target schema
+#[derive(Debug, Clone)]
+pub struct SchemaDiff {
+    from: SchemaSnapshot,
+    to: SchemaSnapshot,
+}
+
+impl SchemaDiff {
+    /// Diff two schemas
+    pub fn between(from: SchemaSnapshot, to: SchemaSnapshot) -> Self {
+        Self { from, to }
+    }
+
+    /// Diff the live schema of a connection with a target schema
+    pub fn live(conn: &Connection, to: SchemaSnapshot) -> DbResult<Self> {
+        Ok(Self::between(SchemaSnapshot::introspect(conn)?, to))
+    }
+
+    /// Current schema
+    pub fn from_schema(&self) -> &SchemaSnapshot {
+        &self.from
+    }
+
+    /// Target schema
+    pub fn to_schema(&self) -> &SchemaSnapshot {
+        &self.to
+    }
+
+    /// List the changes from the current to the target schema
+    pub fn changes(&self) -> Vec<SchemaChange> {
+        schema_changes(&self.from, &self.to)
+    }
+
+    /// Check whether the schemas are equivalent
+    pub fn is_empty(&self) -> bool {
+        self.changes().is_empty()
+    }
+
+    /// Check whether any change can lose data
+    pub fn is_destructive(&self) -> bool {
+        self.changes().iter().any(SchemaChange::is_destructive)
+    }
+
+    /// Candidate SQL migrating the current schema to the target schema
+    pub fn up_sql(&self) -> String {
+        render_statements(&migration_statements(&self.from, &self.to))
+    }
+
+    /// Candidate SQL migrating the target schema back to the current schema
+    pub fn down_sql(&self) -> String {
+        render_statements(&migration_statements(&self.to, &self.from))
+    }
+
+    /// Generate a migration from the candidate SQL
+    pub fn to_migration(&self, version: u32, name: impl Into<String>) -> GeneratedMigration {
+        GeneratedMigration {
+            version,
+            name: name.into(),
+            description: String::new(),
+            up_sql: self.up_sql(),
+            down_sql: self.down_sql(),
+        }
+    }
+
+    /// Apply the candidate SQL to a database and roll it back
+    ///
+    /// Runs the up SQL, checks the result matches the target schema and that
+    /// no surviving table lost rows, then runs the down SQL and checks the
+    /// original schema is restored. The transaction is always rolled back.
+    pub fn dry_run(&self, conn: &mut Connection) -> DbResult<DryRunReport> {
+        info!(
+            "Dry-running schema migration ({} changes)",
+            self.changes().len()
+        );
+
+        let tx = conn.transaction()?;
+        let mut report = DryRunReport::default();
+
+        let before = row_counts(&tx, &self.from)?;
+
+        if let Err(e) = tx.execute_batch(&self.up_sql()) {
+            report.up_error = Some(e.to_string());
+            return Ok(report);
+        }
+
+        let applied = SchemaSnapshot::introspect(&tx)?;
+        report.remaining_changes = schema_changes(&applied, &self.to);
+
+        let after = row_counts(&tx, &applied)?;
+        report.row_counts = before
+            .into_iter()
+            .filter_map(|(table, before)| {
+                after.get(&table).map(|&after| RowCountChange {
+                    table,
+                    before,
+                    after,
+                })
+            })
+            .filter(|change| change.before != change.after)
+            .collect();
+
+        if let Err(e) = tx.execute_batch(&self.down_sql()) {
+            report.down_error = Some(e.to_string());
+            return Ok(report);
+        }
+
+        let restored = SchemaSnapshot::introspect(&tx)?;
+        report.down_remaining_changes = schema_changes(&restored, &self.from);
+
+        // Dropping the transaction rolls it back
+        drop(tx);
+
+        Ok(report)
+    }
+}
+
+/// Row count of a table before and after a dry run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowCountChange {
+    /// Table name
+    pub table: String,
+    /// Rows before the up SQL ran
+    pub before: i64,
+    /// Rows after the up SQL ran
+    pub after: i64,
+}
+
+/// Outcome of a dry run
+#[derive(Debug, Clone, Default)]
+pub struct DryRunReport {
+    /// Error raised by the up SQL
+    pub up_error: Option<String>,
+    /// Differences left between the migrated and the target schema
+    pub remaining_changes: Vec<SchemaChange>,
+    /// Tables whose row count changed
+    pub row_counts: Vec<RowCountChange>,
+    /// Error raised by the down SQL
+    pub down_error: Option<String>,
+    /// Differences left between the rolled back and the original schema
+    pub down_remaining_changes: Vec<SchemaChange>,
+}
+
+impl DryRunReport {
+    /// Check whether the migration applied and rolled back cleanly without losing rows
+    pub fn is_valid(&self) -> bool {
+        self.up_error.is_none()
+            && self.down_error.is_none()
+            && self.remaining_changes.is_empty()
+            && self.down_remaining_changes.is_empty()
+            && self.row_counts.iter().all(|c| c.after >= c.before)
+    }
+}
+
+/// Migration generated from a schema diff
+#[derive(Debug, Clone)]
+pub struct GeneratedMigration {
+    /// Migration version
+    pub version: u32,
+    /// Migration name
+    pub name: String,
+    /// Migration description
+    pub description: String,
+    /// Upgrade SQL
+    pub up_sql: String,
+    /// Downgrade SQL
+    pub down_sql: String,
+}
+
+impl GeneratedMigration {
+    /// Set the migration description
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Render the migration as a Rust source file for the migrations module
+    pub fn to_source(&self) -> String {
+        let struct_name = format!(
+            "{}Migration",
+            self.name
+                .split('_')
+                .filter(|part| !part.is_empty())
+                .map(|part| {
+                    let mut chars = part.chars();
+                    chars
+                        .next()
+                        .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                        .unwrap_or_default()
+                })
+                .collect::<String>()
+        );
+        let description = if self.description.is_empty() {
+            "Generated from a schema diff"
+        } else {
+            &self.description
+        };
+
+        format!(
+            r#"//! {description}
+
+use super::Migration;
+use crate::error::DbResult;
+use rusqlite::Connection;
+
+pub struct {struct_name};
+
+impl Migration for {struct_name} {{
+    fn version(&self) -> u32 {{
+        {version}
+    }}
+
+    fn name(&self) -> &str {{
+        "{name}"
+    }}
+
+    fn description(&self) -> &str {{
+        "{description}"
+    }}
+
+    fn up(&self, conn: &mut Connection) -> DbResult<()> {{
+        conn.execute_batch(
+            {up}
+        )?;
+
+        Ok(())
+    }}
+
+    fn down(&self, conn: &mut Connection) -> DbResult<()> {{
+        conn.execute_batch(
+            {down}
+        )?;
+
+        Ok(())
+    }}
+}}
+"#,
+            description = description.replace('"', "'"),
+            struct_name = struct_name,
+            version = self.version,
+            name = self.name,
+            up = raw_string_literal(&self.up_sql),
+            down = raw_string_literal(&self.down_sql),
+        )
+    }
+}
+
+impl Migration for GeneratedMigration {
+    fn version(&self) -> u32 {
+        self.version
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn up(&self, conn: &mut Connection) -> DbResult<()> {
+        conn.execute_batch(&self.up_sql)?;
+        Ok(())
+    }
+
+    fn down(&self, conn: &mut Connection) -> DbResult<()> {
+        conn.execute_batch(&self.down_sql)?;
+        Ok(())
+    }
+}
+
+/// Introspect the columns and constraints of a table
+fn introspect_table(conn: &Connection, object: &SchemaObject) -> DbResult<TableSchema> {
+    let columns = conn
+        .prepare("SELECT name, type, \"notnull\", dflt_value, pk FROM pragma_table_info(?1) ORDER BY cid")?
+        .query_map([&object.name], |row| {
+            Ok(ColumnSchema {
+                name: row.get(0)?,
+                sql_type: row.get(1)?,
+                not_null: row.get(2)?,
+                default: row.get(3)?,
+                primary_key: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut foreign_keys: BTreeMap<i64, ForeignKeySchema> = BTreeMap::new();
+    let rows = conn
+        .prepare(
+            "SELECT id, \"table\", \"from\", \"to\", on_update, on_delete
+             FROM pragma_foreign_key_list(?1) ORDER BY id, seq",
+        )?
+        .query_map([&object.name], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    for (id, table, from, to, on_update, on_delete) in rows {
+        let fk = foreign_keys.entry(id).or_insert_with(|| ForeignKeySchema {
+            columns: Vec::new(),
+            table,
+            references: Vec::new(),
+            on_update,
+            on_delete,
+        });
+        fk.columns.push(from);
+        fk.references.extend(to);
+    }
+    let mut foreign_keys: Vec<_> = foreign_keys.into_values().collect();
+    foreign_keys.sort();
+
+    let unique_indexes = conn
+        .prepare("SELECT name FROM pragma_index_list(?1) WHERE origin = 'u'")?
+        .query_map([&object.name], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut unique_constraints = Vec::new();
+    for index in unique_indexes {
+        let mut columns = conn
+            .prepare("SELECT name FROM pragma_index_info(?1) ORDER BY seqno")?
+            .query_map([&index], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        columns.iter_mut().for_each(|c| *c = c.to_lowercase());
+        unique_constraints.push(columns);
+    }
+    unique_constraints.sort();
+
+    Ok(TableSchema {
+        name: object.name.clone(),
+        sql: object.sql.clone(),
+        is_virtual: is_virtual_table(&object.sql),
+        columns,
+        foreign_keys,
+        unique_constraints,
+    })
+}
+
+/// How a table present in both schemas has to change
+enum TableChange {
+    None,
+    Alter {
+        added: Vec<ColumnSchema>,
+        dropped: Vec<ColumnSchema>,
+    },
+    Rebuild,
+}
+
+fn table_change(from: &TableSchema, to: &TableSchema) -> TableChange {
+    if from.is_virtual || to.is_virtual {
+        return if from.is_virtual == to.is_virtual
+            && normalize_sql(&from.sql) == normalize_sql(&to.sql)
+        {
+            TableChange::None
+        } else {
+            TableChange::Rebuild
+        };
+    }
+
+    let added: Vec<_> =
+        to.columns.iter().filter(|c| from.column(&c.name).is_none()).cloned().collect();
+    let dropped: Vec<_> =
+        from.columns.iter().filter(|c| to.column(&c.name).is_none()).cloned().collect();
+    let altered = to.columns.iter().any(|c| from.column(&c.name).is_some_and(|f| !f.matches(c)));
+    let constraints_changed =
+        from.foreign_keys != to.foreign_keys || from.unique_constraints != to.unique_constraints;
+
+    if !altered && !constraints_changed && added.is_empty() && dropped.is_empty() {
+        return TableChange::None;
+    }
+
+    let can_alter = !altered
+        && !constraints_changed
+        && added.iter().all(|c| can_add_column(c, to))
+        && dropped.iter().all(|c| !from.is_constrained(&c.name));
+
+    if can_alter {
+        TableChange::Alter { added, dropped }
+    } else {
+        TableChange::Rebuild
+    }
+}
+
+/// Check whether `ALTER TABLE ADD COLUMN` can add a column
+fn can_add_column(column: &ColumnSchema, table: &TableSchema) -> bool {
+    let constant_default = column.default.as_deref().is_none_or(|d| {
+        let d = d.trim().to_uppercase();
+        !d.starts_with('(') && !d.starts_with("CURRENT_")
+    });
+    let has_default = column.default.as_deref().is_some_and(|d| !d.eq_ignore_ascii_case("NULL"));
+
+    !table.is_constrained(&column.name) && constant_default && (!column.not_null || has_default)
+}
+
+fn schema_changes(from: &SchemaSnapshot, to: &SchemaSnapshot) -> Vec<SchemaChange> {
+    let mut changes = Vec::new();
+
+    for (name, table) in &to.tables {
+        let Some(current) = from.tables.get(name) else {
+            changes.push(SchemaChange::Create {
+                kind: ObjectKind::Table,
+                name: name.clone(),
+            });
+            continue;
+        };
+
+        match table_change(current, table) {
+            TableChange::None => {},
+            TableChange::Rebuild if table.is_virtual || current.is_virtual => {
+                changes.push(SchemaChange::Replace {
+                    kind: ObjectKind::Table,
+                    name: name.clone(),
+                });
+            },
+            _ => {
+                for column in &table.columns {
+                    match current.column(&column.name) {
+                        None => changes.push(SchemaChange::AddColumn {
+                            table: name.clone(),
+                            column: column.name.clone(),
+                        }),
+                        Some(c) if !c.matches(column) => changes.push(SchemaChange::AlterColumn {
+                            table: name.clone(),
+                            column: column.name.clone(),
+                        }),
+                        Some(_) => {},
+                    }
+                }
+                for column in &current.columns {
+                    if table.column(&column.name).is_none() {
+                        changes.push(SchemaChange::DropColumn {
+                            table: name.clone(),
+                            column: column.name.clone(),
+                        });
+                    }
+                }
+                if current.foreign_keys != table.foreign_keys
+                    || current.unique_constraints != table.unique_constraints
+                {
+                    changes.push(SchemaChange::AlterConstraints {
+                        table: name.clone(),
+                    });
+                }
+            },
+        }
+    }
+
+    for name in from.tables.keys().filter(|name| !to.tables.contains_key(*name)) {
+        changes.push(SchemaChange::Drop {
+            kind: ObjectKind::Table,
+            name: name.clone(),
+        });
+    }
+
+    for (kind, from_objects, to_objects) in [
+        (ObjectKind::Index, &from.indexes, &to.indexes),
+        (ObjectKind::Trigger, &from.triggers, &to.triggers),
+        (ObjectKind::View, &from.views, &to.views),
+    ] {
+        for (name, object) in to_objects {
+            match from_objects.get(name) {
+                None => changes.push(SchemaChange::Create {
+                    kind,
+                    name: name.clone(),
+                }),
+                Some(current) if !current.matches(object) => changes.push(SchemaChange::Replace {
+                    kind,
+                    name: name.clone(),
+                }),
+                Some(_) => {},
+            }
+        }
+        for name in from_objects.keys().filter(|name| !to_objects.contains_key(*name)) {
+            changes.push(SchemaChange::Drop {
+                kind,
+                name: name.clone(),
+            });
+        }
+    }
+
+    changes
+}
+
+/// Statements migrating one schema to another
+fn migration_statements(from: &SchemaSnapshot, to: &SchemaSnapshot) -> Vec<String> {
+    let mut statements = Vec::new();
+
+    let mut rebuilt = BTreeSet::new();
+    let mut altered = Vec::new();
+    for (name, table) in &to.tables {
+        if let Some(current) = from.tables.get(name) {
+            match table_change(current, table) {
+                TableChange::None => {},
+                TableChange::Alter { added, dropped } => altered.push((name, added, dropped)),
+                TableChange::Rebuild => {
+                    rebuilt.insert(name.clone());
+                },
+            }
+        }
+    }
+
+    // Tables whose indexes, triggers and dependent views disappear with them
+    let replaced_tables: BTreeSet<&String> = from
+        .tables
+        .keys()
+        .filter(|name| !to.tables.contains_key(*name) || rebuilt.contains(*name))
+        .collect();
+    let is_recreated = |object: &SchemaObject| rebuilt.contains(&object.table);
+
+    // Drop changed or removed triggers, views and indexes first
+    for (kind, from_objects, to_objects) in [
+        (ObjectKind::Trigger, &from.triggers, &to.triggers),
+        (ObjectKind::View, &from.views, &to.views),
+        (ObjectKind::Index, &from.indexes, &to.indexes),
+    ] {
+        for (name, object) in from_objects {
+            let unchanged = to_objects.get(name).is_some_and(|target| target.matches(object));
+            let dependent_view = kind == ObjectKind::View
+                && replaced_tables.iter().any(|table| references_table(&object.sql, table));
+            if !unchanged || dependent_view {
+                statements.push(format!("DROP {} IF EXISTS {}", kind.as_str(), ident(name)));
+            }
+        }
+    }
+
+    for name in from.tables.keys().filter(|name| !to.tables.contains_key(*name)) {
+        statements.push(format!("DROP TABLE {}", ident(name)));
+    }
+
+    // Virtual tables cannot be rebuilt by copying, replace them
+    for name in &rebuilt {
+        if from.tables[name].is_virtual || to.tables[name].is_virtual {
+            statements.push(format!("DROP TABLE {}", ident(name)));
+        }
+    }
+
+    for (name, table) in &to.tables {
+        let replaced_virtual =
+            rebuilt.contains(name) && (table.is_virtual || from.tables[name].is_virtual);
+        if !from.tables.contains_key(name) || replaced_virtual {
+            statements.push(table.sql.clone());
+        }
+    }
+
+    for (name, added, dropped) in altered {
+        for column in added {
+            statements.push(format!(
+                "ALTER TABLE {} ADD COLUMN {}",
+                ident(name),
+                column_definition(&column, &to.tables[name])
+            ));
+        }
+        for column in dropped {
+            statements.push(format!(
+                "ALTER TABLE {} DROP COLUMN {}",
+                ident(name),
+                ident(&column.name)
+            ));
+        }
+    }
+
+    for name in &rebuilt {
+        let (current, table) = (&from.tables[name], &to.tables[name]);
+        if current.is_virtual || table.is_virtual {
+            continue;
+        }
+
+        let temp = format!("{}{}", REBUILD_PREFIX, name);
+        let shared: Vec<String> = table
+            .columns
+            .iter()
+            .filter(|c| current.column(&c.name).is_some())
+            .map(|c| ident(&c.name))
+            .collect();
+
+        statements.push(rename_create_table(&table.sql, &temp));
+        if !shared.is_empty() {
+            statements.push(format!(
+                "INSERT INTO {temp} ({columns}) SELECT {columns} FROM {table}",
+                temp = ident(&temp),
+                columns = shared.join(", "),
+                table = ident(name)
+            ));
+        }
+        statements.push(format!("DROP TABLE {}", ident(name)));
+        statements.push(format!(
+            "ALTER TABLE {} RENAME TO {}",
+            ident(&temp),
+            ident(name)
+        ));
+    }
+
+    // Create new or changed indexes, triggers and views, and the ones dropped with rebuilt tables
+    for (kind, from_objects, to_objects) in [
+        (ObjectKind::Index, &from.indexes, &to.indexes),
+        (ObjectKind::Trigger, &from.triggers, &to.triggers),
+        (ObjectKind::View, &from.views, &to.views),
+    ] {
+        for (name, object) in to_objects {
+            let unchanged = from_objects.get(name).is_some_and(|current| current.matches(object));
+            let dependent_view = kind == ObjectKind::View
+                && replaced_tables.iter().any(|table| references_table(&object.sql, table));
+            let dropped_with_table = kind != ObjectKind::View && is_recreated(object);
+            if !unchanged || dependent_view || dropped_with_table {
+                statements.push(object.sql.clone());
+            }
+        }
+    }
+
+    statements
+}
+
+/// Column definition for `ALTER TABLE ADD COLUMN`
+fn column_definition(column: &ColumnSchema, table: &TableSchema) -> String {
+    let mut definition = ident(&column.name);
+
+    if !column.sql_type.is_empty() {
+        definition.push(' ');
+        definition.push_str(&column.sql_type);
+    }
+    if column.not_null {
+        definition.push_str(" NOT NULL");
+    }
+    if let Some(default) = &column.default {
+        definition.push_str(" DEFAULT ");
+        definition.push_str(default);
+    }
+
+    let foreign_key = table
+        .foreign_keys
+        .iter()
+        .find(|fk| fk.columns.len() == 1 && fk.columns[0].eq_ignore_ascii_case(&column.name));
+    if let Some(fk) = foreign_key {
+        definition.push_str(&format!(" REFERENCES {}", ident(&fk.table)));
+        if !fk.references.is_empty() {
+            definition.push_str(&format!("({})", fk.references.join(", ")));
+        }
+        if fk.on_delete != "NO ACTION" {
+            definition.push_str(&format!(" ON DELETE {}", fk.on_delete));
+        }
+        if fk.on_update != "NO ACTION" {
+            definition.push_str(&format!(" ON UPDATE {}", fk.on_update));
+        }
+    }
+
+    definition
+}
+
+/// Rewrite a CREATE TABLE statement to create a table under another name
+fn rename_create_table(sql: &str, name: &str) -> String {
+    match sql.find('(') {
+        Some(index) => format!("CREATE TABLE {} {}", ident(name), &sql[index..]),
+        None => sql.to_string(),
+    }
+}
+
+fn render_statements(statements: &[String]) -> String {
+    statements
+        .iter()
+        .map(|statement| format!("{};", statement.trim().trim_end_matches(';')))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn row_counts(conn: &Connection, schema: &SchemaSnapshot) -> DbResult<BTreeMap<String, i64>> {
+    let mut counts = BTreeMap::new();
+    for table in schema.tables.values().filter(|t| !t.is_virtual) {
+        let count = conn.query_row(
+            &format!("SELECT COUNT(*) FROM {}", ident(&table.name)),
+            [],
+            |row| row.get(0),
+        )?;
+        counts.insert(table.name.clone(), count);
+    }
+    Ok(counts)
+}
+
+fn is_virtual_table(sql: &str) -> bool {
+    normalize_sql(sql).starts_with("create virtual table")
+}
+
+/// Check whether SQL mentions a table as a whole word
+fn references_table(sql: &str, table: &str) -> bool {
+    let table = table.to_lowercase();
+    sql.to_lowercase()
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .any(|word| word == table)
+}
+
+/// Normalize SQL for comparison
+fn normalize_sql(sql: &str) -> String {
+    sql.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+        .replace(" if not exists", "")
+        .trim_end_matches(';')
+        .to_string()
+}
+
+/// Quote an identifier when it is not a plain name
+fn ident(name: &str) -> String {
+    let plain = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if plain {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    }
+}
+
+/// Raw string literal holding SQL, indented like the hand-written migrations
+fn raw_string_literal(sql: &str) -> String {
+    let mut hashes = String::from("#");
+    while sql.contains(&format!("\"{}", hashes)) {
+        hashes.push('#');
+    }
+
+    let body = sql
+        .lines()
+        .map(|line| format!("            {}", line))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!(
+        "r{hashes}\"\n{body}\n            \"{hashes}",
+        hashes = hashes,
+        body = body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURRENT: &str = "
+        CREATE TABLE cases (
+            id TEXT PRIMARY KEY,
+            title TEXT NOT NULL,
+            legacy_code TEXT
+        );
+        CREATE INDEX idx_cases_legacy_code ON cases(legacy_code);
+        CREATE TABLE notes (id TEXT PRIMARY KEY, body TEXT);
+    ";
+
+    const TARGET: &str = "
+        CREATE TABLE cases (
+            id TEXT PRIMARY KEY,
+            title TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'open'
+        );
+        CREATE INDEX idx_cases_status ON cases(status);
+        CREATE TABLE notes (id TEXT PRIMARY KEY, body TEXT NOT NULL, case_id TEXT REFERENCES cases(id));
+        CREATE TABLE tags (id TEXT PRIMARY KEY, name TEXT UNIQUE NOT NULL);
+    ";
+
+    #[test]
+    fn test_schema_changes() {
+        let diff = SchemaDiff::between(
+            SchemaSnapshot::from_sql(CURRENT).unwrap(),
+            SchemaSnapshot::from_sql(TARGET).unwrap(),
+        );
+        let changes = diff.changes();
+
+        assert!(changes.contains(&SchemaChange::AddColumn {
+            table: "cases".to_string(),
+            column: "status".to_string()
+        }));
+        assert!(changes.contains(&SchemaChange::DropColumn {
+            table: "cases".to_string(),
+            column: "legacy_code".to_string()
+        }));
+        assert!(changes.contains(&SchemaChange::AlterColumn {
+            table: "notes".to_string(),
+            column: "body".to_string()
+        }));
+        assert!(changes.contains(&SchemaChange::Create {
+            kind: ObjectKind::Table,
+            name: "tags".to_string()
+        }));
+        assert!(changes.contains(&SchemaChange::Drop {
+            kind: ObjectKind::Index,
+            name: "idx_cases_legacy_code".to_string()
+        }));
+        assert!(diff.is_destructive());
+
+        let up = diff.up_sql();
+        assert!(up.contains("ALTER TABLE cases ADD COLUMN status TEXT NOT NULL DEFAULT 'open';"));
+        assert!(up.contains("ALTER TABLE cases DROP COLUMN legacy_code;"));
+        assert!(up.contains("ALTER TABLE _new_notes RENAME TO notes;"));
+    }
+
+    #[test]
+    fn test_identical_schemas() {
+        let diff = SchemaDiff::between(
+            SchemaSnapshot::from_sql(TARGET).unwrap(),
+            SchemaSnapshot::from_sql(TARGET).unwrap(),
+        );
+        assert!(diff.is_empty());
+        assert!(diff.up_sql().is_empty());
+    }
+
+    #[test]
+    fn test_dry_run() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(CURRENT).unwrap();
+        conn.execute(
+            "INSERT INTO cases (id, title) VALUES ('c1', 'Collision')",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO notes (id, body) VALUES ('n1', 'Skid marks')",
+            [],
+        )
+        .unwrap();
+
+        let diff = SchemaDiff::live(&conn, SchemaSnapshot::from_sql(TARGET).unwrap()).unwrap();
+        let report = diff.dry_run(&mut conn).unwrap();
+        assert!(report.is_valid(), "{:?}", report);
+
+        // The database is left untouched
+        assert!(
+            SchemaDiff::live(&conn, SchemaSnapshot::from_sql(CURRENT).unwrap())
+                .unwrap()
+                .is_empty()
+        );
+
+        // A rebuild adding a NOT NULL column without a default fails on existing rows
+        let target = SchemaSnapshot::from_sql(
+            "CREATE TABLE cases (id TEXT PRIMARY KEY, title TEXT NOT NULL, legacy_code TEXT, owner TEXT NOT NULL);
+             CREATE INDEX idx_cases_legacy_code ON cases(legacy_code);
+             CREATE TABLE notes (id TEXT PRIMARY KEY, body TEXT);",
+        )
+        .unwrap();
+        let report = SchemaDiff::live(&conn, target).unwrap().dry_run(&mut conn).unwrap();
+        assert!(report.up_error.is_some());
+        assert!(!report.is_valid());
+    }
+
+    #[test]
+    fn test_generated_migration() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(CURRENT).unwrap();
+
+        let diff = SchemaDiff::live(&conn, SchemaSnapshot::from_sql(TARGET).unwrap()).unwrap();
+        let migration = diff.to_migration(10, "case_status").with_description("Add case status");
+
+        migration.up(&mut conn).unwrap();
+        assert!(
+            SchemaDiff::live(&conn, SchemaSnapshot::from_sql(TARGET).unwrap())
+                .unwrap()
+                .is_empty()
+        );
+
+        migration.down(&mut conn).unwrap();
+        assert!(
+            SchemaDiff::live(&conn, SchemaSnapshot::from_sql(CURRENT).unwrap())
+                .unwrap()
+                .is_empty()
+        );
+
+        let source = migration.to_source();
+        assert!(source.contains("pub struct CaseStatusMigration;"));
+        assert!(source.contains("        10\n"));
+    }
+
+    #[test]
+    fn test_declared_schema_matches_migrated_database() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        MigrationRunner::new().migrate(&mut conn).unwrap();
+
+        let diff = SchemaDiff::live(&conn, SchemaSnapshot::declared().unwrap()).unwrap();
+        assert!(diff.is_empty(), "{:?}", diff.changes());
+    }
+}
@@ -1,10 +1,20 @@
 //! Database migration system for AccuScene Enterprise
 //!
 //! Provides automatic schema migrations with version tracking,
-//! rollback support, and migration history.
+//! rollback support, and migration history. The [`diff`] module compares
+//! a live schema with the declared one and generates candidate migrations.
 
+pub mod diff;
 pub mod runner;
 pub mod v001_initial;
+pub mod v002_user_admin;
+pub mod v003_organizations;
+pub mod v004_entity_graph;
+pub mod v005_party_registry;
+pub mod v006_attachment_text;
+pub mod v007_soft_delete;
+pub mod v008_row_versioning;
+pub mod v009_change_log;
 
 use crate::error::{DatabaseError, DbResult};
 use rusqlite::Connection;
@@ -91,6 +101,14 @@ impl Default for MigrationRegistry {
 
         // Register all migrations
         registry.register(Box::new(v001_initial::InitialMigration));
+        registry.register(Box::new(v002_user_admin::UserAdminMigration));
+        registry.register(Box::new(v003_organizations::OrganizationsMigration));
+        registry.register(Box::new(v004_entity_graph::EntityGraphMigration));
+        registry.register(Box::new(v005_party_registry::PartyRegistryMigration));
+        registry.register(Box::new(v006_attachment_text::AttachmentTextMigration));
+        registry.register(Box::new(v007_soft_delete::SoftDeleteMigration));
+        registry.register(Box::new(v008_row_versioning::RowVersioningMigration));
+        registry.register(Box::new(v009_change_log::ChangeLogMigration));
 
         info!(
             "Registered {} migrations, latest version: {}",
@@ -74,6 +74,28 @@ impl MigrationRunner {
         Ok(())
     }
 
+    /// Run a function inside a transaction, rolling back on error
+    ///
+    /// Migrations take `&mut Connection`, which a `Transaction` cannot hand
+    /// out, so the transaction is managed with explicit statements.
+    fn in_transaction<F>(conn: &mut Connection, f: F) -> DbResult<()>
+    where
+        F: FnOnce(&mut Connection) -> DbResult<()>,
+    {
+        conn.execute_batch("BEGIN")
+            .map_err(|e| DatabaseError::TransactionError(e.to_string()))?;
+
+        if let Err(e) = f(conn) {
+            if let Err(rollback_error) = conn.execute_batch("ROLLBACK") {
+                error!("Failed to roll back migration transaction: {}", rollback_error);
+            }
+            return Err(e);
+        }
+
+        conn.execute_batch("COMMIT")
+            .map_err(|e| DatabaseError::TransactionError(e.to_string()))
+    }
+
     /// Run all pending migrations
     pub fn migrate(&self, conn: &mut Connection) -> DbResult<()> {
         info!("Starting database migration");
@@ -119,26 +141,21 @@ impl MigrationRunner {
         let start = Instant::now();
 
         // Use a transaction for safety
-        let tx = conn
-            .transaction()
-            .map_err(|e| DatabaseError::TransactionError(e.to_string()))?;
-
-        // Execute the migration
-        if let Err(e) = migration.up(&mut tx.into()) {
-            error!("Migration v{} failed: {}", version, e);
-            return Err(DatabaseError::MigrationError(format!(
-                "Migration v{} failed: {}",
-                version, e
-            )));
-        }
-
-        // Record the migration
-        let execution_time = start.elapsed().as_millis() as u64;
-        Self::record_migration(&mut tx.into(), version, name, execution_time)?;
+        let mut execution_time = 0;
+        Self::in_transaction(conn, |conn| {
+            // Execute the migration
+            if let Err(e) = migration.up(conn) {
+                error!("Migration v{} failed: {}", version, e);
+                return Err(DatabaseError::MigrationError(format!(
+                    "Migration v{} failed: {}",
+                    version, e
+                )));
+            }
 
-        // Commit the transaction
-        tx.commit()
-            .map_err(|e| DatabaseError::TransactionError(e.to_string()))?;
+            // Record the migration
+            execution_time = start.elapsed().as_millis() as u64;
+            Self::record_migration(conn, version, name, execution_time)
+        })?;
 
         info!(
             "Migration v{} applied successfully in {}ms",
@@ -193,25 +210,19 @@ impl MigrationRunner {
         let start = Instant::now();
 
         // Use a transaction for safety
-        let tx = conn
-            .transaction()
-            .map_err(|e| DatabaseError::TransactionError(e.to_string()))?;
-
-        // Execute the rollback
-        if let Err(e) = migration.down(&mut tx.into()) {
-            error!("Rollback of v{} failed: {}", version, e);
-            return Err(DatabaseError::MigrationError(format!(
-                "Rollback of v{} failed: {}",
-                version, e
-            )));
-        }
-
-        // Remove from migration history
-        Self::remove_migration(&mut tx.into(), version)?;
+        Self::in_transaction(conn, |conn| {
+            // Execute the rollback
+            if let Err(e) = migration.down(conn) {
+                error!("Rollback of v{} failed: {}", version, e);
+                return Err(DatabaseError::MigrationError(format!(
+                    "Rollback of v{} failed: {}",
+                    version, e
+                )));
+            }
 
-        // Commit the transaction
-        tx.commit()
-            .map_err(|e| DatabaseError::TransactionError(e.to_string()))?;
+            // Remove from migration history
+            Self::remove_migration(conn, version)
+        })?;
 
         let execution_time = start.elapsed().as_millis();
         info!(
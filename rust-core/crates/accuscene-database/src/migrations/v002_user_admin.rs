@@ -0,0 +1,90 @@
+//! User administration migration
+//!
+//! Adds the columns needed by the admin surface:
+//! - MFA enrollment flag and opaque secret
+//! - Forced password reset flag
+
+use super::Migration;
+use crate::error::DbResult;
+use rusqlite::Connection;
+
+pub struct UserAdminMigration;
+
+impl Migration for UserAdminMigration {
+    fn version(&self) -> u32 {
+        2
+    }
+
+    fn name(&self) -> &str {
+        "user_admin"
+    }
+
+    fn description(&self) -> &str {
+        "Add MFA and forced password reset columns to users"
+    }
+
+    fn up(&self, conn: &mut Connection) -> DbResult<()> {
+        conn.execute_batch(
+            r#"
+            ALTER TABLE users ADD COLUMN mfa_enabled INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE users ADD COLUMN mfa_secret TEXT;
+            ALTER TABLE users ADD COLUMN password_reset_required INTEGER NOT NULL DEFAULT 0;
+
+            CREATE INDEX idx_users_role ON users(role);
+            CREATE INDEX idx_users_is_active ON users(is_active);
+            "#,
+        )?;
+
+        Ok(())
+    }
+
+    fn down(&self, conn: &mut Connection) -> DbResult<()> {
+        conn.execute_batch(
+            r#"
+            DROP INDEX IF EXISTS idx_users_is_active;
+            DROP INDEX IF EXISTS idx_users_role;
+
+            ALTER TABLE users DROP COLUMN password_reset_required;
+            ALTER TABLE users DROP COLUMN mfa_secret;
+            ALTER TABLE users DROP COLUMN mfa_enabled;
+            "#,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::v001_initial::InitialMigration;
+
+    #[test]
+    fn test_user_admin_migration_up_down() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        InitialMigration.up(&mut conn).unwrap();
+
+        let migration = UserAdminMigration;
+        migration.up(&mut conn).unwrap();
+
+        let reset_required: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('users') WHERE name = 'password_reset_required'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(reset_required, 1);
+
+        migration.down(&mut conn).unwrap();
+
+        let mfa_columns: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('users') WHERE name LIKE 'mfa_%'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(mfa_columns, 0);
+    }
+}
@@ -0,0 +1,155 @@
+//! Organization hierarchy migration
+//!
+//! Creates the tenancy tables:
+//! - Organizations, teams and workspaces
+//! - Memberships at any level of the hierarchy
+//! - Ownership links from cases, dashboards, saved searches and report templates
+
+use super::Migration;
+use crate::error::DbResult;
+use rusqlite::Connection;
+
+pub struct OrganizationsMigration;
+
+impl Migration for OrganizationsMigration {
+    fn version(&self) -> u32 {
+        3
+    }
+
+    fn name(&self) -> &str {
+        "organizations"
+    }
+
+    fn description(&self) -> &str {
+        "Create organization, team, workspace, membership and ownership tables"
+    }
+
+    fn up(&self, conn: &mut Connection) -> DbResult<()> {
+        conn.execute_batch(
+            r#"
+            -- Organizations table
+            CREATE TABLE organizations (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                slug TEXT UNIQUE NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            -- Teams table
+            CREATE TABLE teams (
+                id TEXT PRIMARY KEY,
+                organization_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+                FOREIGN KEY (organization_id) REFERENCES organizations(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX idx_teams_organization_id ON teams(organization_id);
+
+            -- Workspaces table
+            CREATE TABLE workspaces (
+                id TEXT PRIMARY KEY,
+                organization_id TEXT NOT NULL,
+                team_id TEXT,
+                name TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+                FOREIGN KEY (organization_id) REFERENCES organizations(id) ON DELETE CASCADE,
+                FOREIGN KEY (team_id) REFERENCES teams(id) ON DELETE SET NULL
+            );
+
+            CREATE INDEX idx_workspaces_organization_id ON workspaces(organization_id);
+            CREATE INDEX idx_workspaces_team_id ON workspaces(team_id);
+
+            -- Memberships table
+            CREATE TABLE memberships (
+                user_id TEXT NOT NULL,
+                scope_type TEXT NOT NULL,
+                scope_id TEXT NOT NULL,
+                role TEXT NOT NULL DEFAULT 'member',
+                joined_at TEXT NOT NULL DEFAULT (datetime('now')),
+                PRIMARY KEY (user_id, scope_type, scope_id),
+                FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX idx_memberships_scope ON memberships(scope_type, scope_id);
+
+            -- Resource ownership table
+            CREATE TABLE resource_ownership (
+                resource_type TEXT NOT NULL,
+                resource_id TEXT NOT NULL,
+                workspace_id TEXT NOT NULL,
+                owner_id TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                PRIMARY KEY (resource_type, resource_id),
+                FOREIGN KEY (workspace_id) REFERENCES workspaces(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX idx_resource_ownership_workspace ON resource_ownership(workspace_id);
+            CREATE INDEX idx_resource_ownership_owner ON resource_ownership(owner_id);
+
+            -- Update timestamps trigger for organizations
+            CREATE TRIGGER update_organizations_timestamp AFTER UPDATE ON organizations
+            FOR EACH ROW BEGIN
+                UPDATE organizations SET updated_at = datetime('now') WHERE id = NEW.id;
+            END;
+            "#,
+        )?;
+
+        Ok(())
+    }
+
+    fn down(&self, conn: &mut Connection) -> DbResult<()> {
+        conn.execute_batch(
+            r#"
+            DROP TRIGGER IF EXISTS update_organizations_timestamp;
+
+            DROP TABLE IF EXISTS resource_ownership;
+            DROP TABLE IF EXISTS memberships;
+            DROP TABLE IF EXISTS workspaces;
+            DROP TABLE IF EXISTS teams;
+            DROP TABLE IF EXISTS organizations;
+            "#,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::v001_initial::InitialMigration;
+
+    #[test]
+    fn test_organizations_migration_up_down() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        InitialMigration.up(&mut conn).unwrap();
+
+        let migration = OrganizationsMigration;
+        migration.up(&mut conn).unwrap();
+
+        let tables: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table'
+                 AND name IN ('organizations', 'teams', 'workspaces', 'memberships', 'resource_ownership')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(tables, 5);
+
+        migration.down(&mut conn).unwrap();
+
+        let tables: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'organizations'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(tables, 0);
+    }
+}
@@ -0,0 +1,85 @@
+//! Entity graph migration
+//!
+//! Creates the tables backing the case relationship graph:
+//! - Nodes for cases, vehicles, people, locations and evidence
+//! - Typed, case-scoped edges between nodes
+
+use super::Migration;
+use crate::error::DbResult;
+use rusqlite::Connection;
+
+pub struct EntityGraphMigration;
+
+impl Migration for EntityGraphMigration {
+    fn version(&self) -> u32 {
+        4
+    }
+
+    fn name(&self) -> &str {
+        "entity_graph"
+    }
+
+    fn description(&self) -> &str {
+        "Create graph node and edge tables for case relationships"
+    }
+
+    fn up(&self, conn: &mut Connection) -> DbResult<()> {
+        conn.execute_batch(
+            r#"
+            -- Graph nodes table
+            CREATE TABLE graph_nodes (
+                id TEXT PRIMARY KEY, -- "<kind>:<key>"
+                kind TEXT NOT NULL,
+                entity_key TEXT NOT NULL,
+                label TEXT NOT NULL,
+                properties TEXT, -- JSON object
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE INDEX idx_graph_nodes_kind ON graph_nodes(kind);
+
+            -- Graph edges table
+            CREATE TABLE graph_edges (
+                id TEXT PRIMARY KEY,
+                source_id TEXT NOT NULL,
+                target_id TEXT NOT NULL,
+                edge_type TEXT NOT NULL,
+                case_id TEXT,
+                observed_at TEXT NOT NULL DEFAULT (datetime('now')),
+                properties TEXT, -- JSON object
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                FOREIGN KEY (source_id) REFERENCES graph_nodes(id) ON DELETE CASCADE,
+                FOREIGN KEY (target_id) REFERENCES graph_nodes(id) ON DELETE CASCADE,
+                UNIQUE (source_id, target_id, edge_type, case_id)
+            );
+
+            CREATE INDEX idx_graph_edges_source ON graph_edges(source_id, edge_type);
+            CREATE INDEX idx_graph_edges_target ON graph_edges(target_id, edge_type);
+            CREATE INDEX idx_graph_edges_case_id ON graph_edges(case_id);
+            CREATE INDEX idx_graph_edges_observed_at ON graph_edges(observed_at);
+
+            -- Update timestamps trigger for graph nodes
+            CREATE TRIGGER update_graph_nodes_timestamp AFTER UPDATE ON graph_nodes
+            FOR EACH ROW BEGIN
+                UPDATE graph_nodes SET updated_at = datetime('now') WHERE id = NEW.id;
+            END;
+            "#,
+        )?;
+
+        Ok(())
+    }
+
+    fn down(&self, conn: &mut Connection) -> DbResult<()> {
+        conn.execute_batch(
+            r#"
+            DROP TRIGGER IF EXISTS update_graph_nodes_timestamp;
+
+            DROP TABLE IF EXISTS graph_edges;
+            DROP TABLE IF EXISTS graph_nodes;
+            "#,
+        )?;
+
+        Ok(())
+    }
+}
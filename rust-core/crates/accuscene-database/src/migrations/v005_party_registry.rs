@@ -0,0 +1,144 @@
+//! Party registry migration
+//!
+//! Creates the party registry tables:
+//! - Deduplicated parties with matching keys and privacy classification
+//! - Links from parties to cases and evidence
+//! - Merge history with a snapshot of each merged record
+
+use super::Migration;
+use crate::error::DbResult;
+use rusqlite::Connection;
+
+pub struct PartyRegistryMigration;
+
+impl Migration for PartyRegistryMigration {
+    fn version(&self) -> u32 {
+        5
+    }
+
+    fn name(&self) -> &str {
+        "party_registry"
+    }
+
+    fn description(&self) -> &str {
+        "Create party, party link and party merge tables"
+    }
+
+    fn up(&self, conn: &mut Connection) -> DbResult<()> {
+        conn.execute_batch(
+            r#"
+            -- Parties table
+            CREATE TABLE parties (
+                id TEXT PRIMARY KEY,
+                full_name TEXT NOT NULL,
+                normalized_name TEXT NOT NULL,
+                phonetic_key TEXT NOT NULL,
+                date_of_birth TEXT,
+                licence_number TEXT,
+                phone TEXT,
+                address TEXT,
+                privacy_class TEXT NOT NULL DEFAULT 'internal',
+                merged_into TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+                FOREIGN KEY (merged_into) REFERENCES parties(id) ON DELETE SET NULL
+            );
+
+            CREATE INDEX idx_parties_phonetic_key ON parties(phonetic_key);
+            CREATE INDEX idx_parties_licence_number ON parties(licence_number);
+            CREATE INDEX idx_parties_date_of_birth ON parties(date_of_birth);
+            CREATE INDEX idx_parties_merged_into ON parties(merged_into);
+
+            -- Party links table
+            CREATE TABLE party_links (
+                id TEXT PRIMARY KEY,
+                party_id TEXT NOT NULL,
+                case_id TEXT NOT NULL,
+                evidence_id TEXT,
+                role TEXT NOT NULL DEFAULT 'other',
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                FOREIGN KEY (party_id) REFERENCES parties(id) ON DELETE CASCADE,
+                FOREIGN KEY (case_id) REFERENCES cases(id) ON DELETE CASCADE,
+                FOREIGN KEY (evidence_id) REFERENCES evidence(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX idx_party_links_party_id ON party_links(party_id);
+            CREATE INDEX idx_party_links_case_id ON party_links(case_id);
+            CREATE INDEX idx_party_links_evidence_id ON party_links(evidence_id);
+
+            -- Party merge history table
+            CREATE TABLE party_merges (
+                id TEXT PRIMARY KEY,
+                survivor_id TEXT NOT NULL,
+                merged_id TEXT NOT NULL,
+                merged_by TEXT NOT NULL,
+                reason TEXT,
+                snapshot TEXT NOT NULL, -- JSON object
+                merged_at TEXT NOT NULL DEFAULT (datetime('now')),
+                FOREIGN KEY (survivor_id) REFERENCES parties(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX idx_party_merges_survivor_id ON party_merges(survivor_id);
+            CREATE INDEX idx_party_merges_merged_id ON party_merges(merged_id);
+
+            -- Update timestamps trigger for parties
+            CREATE TRIGGER update_parties_timestamp AFTER UPDATE ON parties
+            FOR EACH ROW BEGIN
+                UPDATE parties SET updated_at = datetime('now') WHERE id = NEW.id;
+            END;
+            "#,
+        )?;
+
+        Ok(())
+    }
+
+    fn down(&self, conn: &mut Connection) -> DbResult<()> {
+        conn.execute_batch(
+            r#"
+            DROP TRIGGER IF EXISTS update_parties_timestamp;
+
+            DROP TABLE IF EXISTS party_merges;
+            DROP TABLE IF EXISTS party_links;
+            DROP TABLE IF EXISTS parties;
+            "#,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::v001_initial::InitialMigration;
+
+    #[test]
+    fn test_party_registry_migration_up_down() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        InitialMigration.up(&mut conn).unwrap();
+
+        let migration = PartyRegistryMigration;
+        migration.up(&mut conn).unwrap();
+
+        let tables: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table'
+                 AND name IN ('parties', 'party_links', 'party_merges')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(tables, 3);
+
+        migration.down(&mut conn).unwrap();
+
+        let tables: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'parties'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(tables, 0);
+    }
+}
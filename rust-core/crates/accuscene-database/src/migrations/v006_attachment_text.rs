@@ -0,0 +1,132 @@
+//! Attachment text migration
+//!
+//! Creates the attachment content index:
+//! - Extracted text for evidence attachments (PDF text layers, OCR output)
+//! - FTS5 table over attachment text kept in sync by triggers
+
+use super::Migration;
+use crate::error::DbResult;
+use rusqlite::Connection;
+
+pub struct AttachmentTextMigration;
+
+impl Migration for AttachmentTextMigration {
+    fn version(&self) -> u32 {
+        6
+    }
+
+    fn name(&self) -> &str {
+        "attachment_text"
+    }
+
+    fn description(&self) -> &str {
+        "Create evidence attachment text table and full-text index"
+    }
+
+    fn up(&self, conn: &mut Connection) -> DbResult<()> {
+        conn.execute_batch(
+            r#"
+            -- Extracted attachment text table
+            CREATE TABLE evidence_attachment_text (
+                id TEXT PRIMARY KEY,
+                evidence_id TEXT NOT NULL,
+                file_name TEXT NOT NULL,
+                mime_type TEXT,
+                page INTEGER,
+                source TEXT NOT NULL DEFAULT 'text',
+                content TEXT NOT NULL,
+                indexed_at TEXT NOT NULL DEFAULT (datetime('now')),
+                FOREIGN KEY (evidence_id) REFERENCES evidence(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX idx_evidence_attachment_text_evidence_file
+                ON evidence_attachment_text(evidence_id, file_name);
+
+            -- Full-text search virtual table for attachment text
+            CREATE VIRTUAL TABLE evidence_attachment_fts USING fts5(
+                file_name,
+                content,
+                content=evidence_attachment_text,
+                content_rowid=rowid
+            );
+
+            -- Triggers to keep FTS in sync with attachment text table
+            CREATE TRIGGER evidence_attachment_fts_insert AFTER INSERT ON evidence_attachment_text
+            BEGIN
+                INSERT INTO evidence_attachment_fts(rowid, file_name, content)
+                VALUES (new.rowid, new.file_name, new.content);
+            END;
+
+            CREATE TRIGGER evidence_attachment_fts_delete AFTER DELETE ON evidence_attachment_text
+            BEGIN
+                INSERT INTO evidence_attachment_fts(
+                    evidence_attachment_fts, rowid, file_name, content
+                ) VALUES ('delete', old.rowid, old.file_name, old.content);
+            END;
+
+            CREATE TRIGGER evidence_attachment_fts_update AFTER UPDATE ON evidence_attachment_text
+            BEGIN
+                INSERT INTO evidence_attachment_fts(
+                    evidence_attachment_fts, rowid, file_name, content
+                ) VALUES ('delete', old.rowid, old.file_name, old.content);
+                INSERT INTO evidence_attachment_fts(rowid, file_name, content)
+                VALUES (new.rowid, new.file_name, new.content);
+            END;
+            "#,
+        )?;
+
+        Ok(())
+    }
+
+    fn down(&self, conn: &mut Connection) -> DbResult<()> {
+        conn.execute_batch(
+            r#"
+            DROP TRIGGER IF EXISTS evidence_attachment_fts_update;
+            DROP TRIGGER IF EXISTS evidence_attachment_fts_delete;
+            DROP TRIGGER IF EXISTS evidence_attachment_fts_insert;
+
+            DROP TABLE IF EXISTS evidence_attachment_fts;
+            DROP TABLE IF EXISTS evidence_attachment_text;
+            "#,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::v001_initial::InitialMigration;
+
+    #[test]
+    fn test_attachment_text_migration_up_down() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        InitialMigration.up(&mut conn).unwrap();
+
+        let migration = AttachmentTextMigration;
+        migration.up(&mut conn).unwrap();
+
+        let tables: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table'
+                 AND name IN ('evidence_attachment_text', 'evidence_attachment_fts')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(tables, 2);
+
+        migration.down(&mut conn).unwrap();
+
+        let tables: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table'
+                 AND name = 'evidence_attachment_text'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(tables, 0);
+    }
+}
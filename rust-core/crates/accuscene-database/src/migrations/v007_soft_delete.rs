@@ -0,0 +1,85 @@
+//! Soft delete migration
+//!
+//! Adds a `deleted_at` column to the case data tables so that deleted
+//! records are moved to the trash and can be restored until purged:
+//! - cases
+//! - accidents
+//! - vehicles
+//! - evidence
+
+use super::Migration;
+use crate::error::DbResult;
+use rusqlite::Connection;
+
+/// Tables with soft delete support
+pub const SOFT_DELETE_TABLES: &[&str] = &["cases", "accidents", "vehicles", "evidence"];
+
+pub struct SoftDeleteMigration;
+
+impl Migration for SoftDeleteMigration {
+    fn version(&self) -> u32 {
+        7
+    }
+
+    fn name(&self) -> &str {
+        "soft_delete"
+    }
+
+    fn description(&self) -> &str {
+        "Add deleted_at columns for trash and restore"
+    }
+
+    fn up(&self, conn: &mut Connection) -> DbResult<()> {
+        for table in SOFT_DELETE_TABLES {
+            conn.execute_batch(&format!(
+                "ALTER TABLE {table} ADD COLUMN deleted_at TEXT;
+                 CREATE INDEX idx_{table}_deleted_at ON {table}(deleted_at);",
+                table = table
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    fn down(&self, conn: &mut Connection) -> DbResult<()> {
+        for table in SOFT_DELETE_TABLES {
+            conn.execute_batch(&format!(
+                "DROP INDEX IF EXISTS idx_{table}_deleted_at;
+                 ALTER TABLE {table} DROP COLUMN deleted_at;",
+                table = table
+            ))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::v001_initial::InitialMigration;
+
+    fn has_deleted_at(conn: &Connection, table: &str) -> bool {
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info(?) WHERE name = 'deleted_at'",
+                [table],
+                |row| row.get(0),
+            )
+            .unwrap();
+        count == 1
+    }
+
+    #[test]
+    fn test_soft_delete_migration_up_down() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        InitialMigration.up(&mut conn).unwrap();
+
+        let migration = SoftDeleteMigration;
+        migration.up(&mut conn).unwrap();
+        assert!(SOFT_DELETE_TABLES.iter().all(|t| has_deleted_at(&conn, t)));
+
+        migration.down(&mut conn).unwrap();
+        assert!(!SOFT_DELETE_TABLES.iter().any(|t| has_deleted_at(&conn, t)));
+    }
+}
@@ -0,0 +1,89 @@
+//! Row versioning migration
+//!
+//! Adds a `version` column used for optimistic concurrency control. Updates
+//! through the repositories compare the version read by the client with the
+//! stored one and increment it, so concurrent edits are detected instead of
+//! silently overwriting each other:
+//! - cases
+//! - evidence
+//! - vehicles
+
+use super::Migration;
+use crate::error::DbResult;
+use rusqlite::Connection;
+
+/// Tables with row versioning
+pub const VERSIONED_TABLES: &[&str] = &["cases", "evidence", "vehicles"];
+
+pub struct RowVersioningMigration;
+
+impl Migration for RowVersioningMigration {
+    fn version(&self) -> u32 {
+        8
+    }
+
+    fn name(&self) -> &str {
+        "row_versioning"
+    }
+
+    fn description(&self) -> &str {
+        "Add version columns for optimistic concurrency control"
+    }
+
+    fn up(&self, conn: &mut Connection) -> DbResult<()> {
+        for table in VERSIONED_TABLES {
+            conn.execute_batch(&format!(
+                "ALTER TABLE {} ADD COLUMN version INTEGER NOT NULL DEFAULT 1;",
+                table
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    fn down(&self, conn: &mut Connection) -> DbResult<()> {
+        for table in VERSIONED_TABLES {
+            conn.execute_batch(&format!("ALTER TABLE {} DROP COLUMN version;", table))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::v001_initial::InitialMigration;
+
+    #[test]
+    fn test_row_versioning_migration_up_down() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        InitialMigration.up(&mut conn).unwrap();
+
+        let migration = RowVersioningMigration;
+        migration.up(&mut conn).unwrap();
+
+        conn.execute_batch(
+            "INSERT INTO users (id, email, username, full_name, password_hash)
+             VALUES ('u1', 'analyst@example.com', 'analyst', 'Analyst', 'hash');
+             INSERT INTO cases (id, case_number, title, created_by)
+             VALUES ('c1', 'CASE-1', 'Intersection collision', 'u1');",
+        )
+        .unwrap();
+        let version: i64 = conn
+            .query_row("SELECT version FROM cases WHERE id = 'c1'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, 1);
+
+        migration.down(&mut conn).unwrap();
+
+        let columns: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('cases') WHERE name = 'version'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(columns, 0);
+    }
+}
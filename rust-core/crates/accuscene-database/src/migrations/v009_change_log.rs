@@ -0,0 +1,109 @@
+//! Change log migration
+//!
+//! Creates the change data capture tables and installs capture triggers on
+//! the default tables:
+//! - Changelog with before and after images of each row change
+//! - Per-consumer cursors into the changelog
+
+use super::Migration;
+use crate::cdc::ChangeCapture;
+use crate::error::DbResult;
+use rusqlite::Connection;
+
+pub struct ChangeLogMigration;
+
+impl Migration for ChangeLogMigration {
+    fn version(&self) -> u32 {
+        9
+    }
+
+    fn name(&self) -> &str {
+        "change_log"
+    }
+
+    fn description(&self) -> &str {
+        "Create changelog and consumer cursor tables with capture triggers"
+    }
+
+    fn up(&self, conn: &mut Connection) -> DbResult<()> {
+        conn.execute_batch(
+            r#"
+            -- Changelog table
+            CREATE TABLE _changelog (
+                sequence INTEGER PRIMARY KEY AUTOINCREMENT,
+                table_name TEXT NOT NULL,
+                row_id TEXT NOT NULL,
+                operation TEXT NOT NULL,
+                old_values TEXT, -- JSON object
+                new_values TEXT, -- JSON object
+                changed_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+
+            CREATE INDEX idx_changelog_entity ON _changelog(table_name, row_id);
+
+            -- Consumer cursors table
+            CREATE TABLE _changelog_cursors (
+                consumer TEXT PRIMARY KEY,
+                last_sequence INTEGER NOT NULL DEFAULT 0,
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            "#,
+        )?;
+
+        ChangeCapture::new().install_defaults(conn)?;
+
+        Ok(())
+    }
+
+    fn down(&self, conn: &mut Connection) -> DbResult<()> {
+        let capture = ChangeCapture::new();
+        for table in capture.captured_tables(conn)? {
+            capture.uninstall(conn, &table)?;
+        }
+
+        conn.execute_batch(
+            r#"
+            DROP TABLE IF EXISTS _changelog_cursors;
+            DROP TABLE IF EXISTS _changelog;
+            "#,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::v001_initial::InitialMigration;
+
+    #[test]
+    fn test_change_log_migration_up_down() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        InitialMigration.up(&mut conn).unwrap();
+
+        let migration = ChangeLogMigration;
+        migration.up(&mut conn).unwrap();
+
+        let triggers: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'trigger' AND name LIKE '_cdc_%'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(triggers, 12);
+
+        migration.down(&mut conn).unwrap();
+
+        let objects: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master
+                 WHERE name LIKE '_cdc_%' OR name LIKE '_changelog%'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(objects, 0);
+    }
+}
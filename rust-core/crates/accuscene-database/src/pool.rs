@@ -2,13 +2,21 @@
 //!
 //! Provides a high-performance connection pool using r2d2 with automatic
 //! health checks, connection lifecycle management, and performance tuning.
+//!
+//! Secondary database files (e.g. archived cases) can be attached to every
+//! connection under a schema alias, and read-only queries can be routed to
+//! read replicas with automatic fallback to the primary.
 
-use crate::config::{DatabaseConfig, PerformanceConfig};
+use crate::config::{AttachedDatabase, DatabaseConfig, PerformanceConfig};
+use crate::encryption;
 use crate::error::{DatabaseError, DbResult};
+use crate::query::QueryBuilder;
 use parking_lot::RwLock;
 use r2d2::{Pool, PooledConnection};
 use r2d2_sqlite::SqliteConnectionManager;
 use rusqlite::{Connection, OpenFlags};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
@@ -16,6 +24,10 @@ use tracing::{debug, error, info, warn};
 /// Connection pool for SQLite database
 pub struct DatabasePool {
     pool: Pool<SqliteConnectionManager>,
+    replicas: Vec<Pool<SqliteConnectionManager>>,
+    next_replica: AtomicUsize,
+    attached: RwLock<Vec<AttachedDatabase>>,
+    manages_attachments: AtomicBool,
     config: Arc<DatabaseConfig>,
     stats: Arc<RwLock<PoolStats>>,
 }
@@ -35,6 +47,10 @@ pub struct PoolStats {
     pub last_health_check: Option<Instant>,
     /// Health check failures
     pub health_check_failures: u64,
+    /// Connections handed out from read replicas
+    pub replica_reads: u64,
+    /// Reads that fell back to the primary because no replica was available
+    pub replica_fallbacks: u64,
 }
 
 impl DatabasePool {
@@ -47,13 +63,34 @@ impl DatabasePool {
             config.url, config.pool.max_size
         );
 
+        // Keying must happen before anything else touches the file
+        let key = if config.encryption.enabled {
+            Some(encryption::prepare_database(&config)?)
+        } else {
+            None
+        };
+
+        let replicas = config
+            .routing
+            .replicas
+            .iter()
+            .map(|path| build_replica_pool(path, &config, key.clone()))
+            .collect::<DbResult<Vec<_>>>()?;
+
+        let performance = config.performance.clone();
+        let encryption_config = config.encryption.clone();
         let manager = SqliteConnectionManager::file(&config.url)
             .with_flags(
                 OpenFlags::SQLITE_OPEN_READ_WRITE
                     | OpenFlags::SQLITE_OPEN_CREATE
                     | OpenFlags::SQLITE_OPEN_NO_MUTEX,
             )
-            .with_init(move |conn| configure_connection(conn, &config.performance));
+            .with_init(move |conn| {
+                if let Some(key) = &key {
+                    encryption::apply_key(conn, key, &encryption_config)?;
+                }
+                configure_connection(conn, &performance)
+            });
 
         let pool = Pool::builder()
             .max_size(config.pool.max_size)
@@ -67,8 +104,13 @@ impl DatabasePool {
             .build(manager)
             .map_err(|e| DatabaseError::PoolError(e.to_string()))?;
 
+        let attached = config.routing.attached.clone();
         let pool_instance = Self {
             pool,
+            replicas,
+            next_replica: AtomicUsize::new(0),
+            manages_attachments: AtomicBool::new(!attached.is_empty()),
+            attached: RwLock::new(attached),
             config: Arc::new(config),
             stats: Arc::new(RwLock::new(PoolStats::default())),
         };
@@ -97,15 +139,134 @@ impl DatabasePool {
 
         if elapsed > Duration::from_secs(1) {
             warn!(
-                "Slow connection acquisition: {:?} (pool state: {})",
+                "Slow connection acquisition: {:?} (pool state: {:?})",
                 elapsed,
                 self.pool.state()
             );
         }
 
+        self.sync_attached(&conn)?;
+
         Ok(conn)
     }
 
+    /// Get a connection for read-only queries
+    ///
+    /// Replicas are tried in round-robin order; when none of them can hand
+    /// out a connection within the replica timeout, the primary is used.
+    pub fn get_read(&self) -> DbResult<PooledConnection<SqliteConnectionManager>> {
+        if self.replicas.is_empty() {
+            return self.get();
+        }
+
+        let start = self.next_replica.fetch_add(1, Ordering::Relaxed);
+        for offset in 0..self.replicas.len() {
+            let index = (start + offset) % self.replicas.len();
+            let replica = &self.config.routing.replicas[index];
+
+            let conn = match self.replicas[index].get() {
+                Ok(conn) => conn,
+                Err(e) => {
+                    warn!("Read replica '{}' unavailable: {}", replica, e);
+                    continue;
+                },
+            };
+
+            if let Err(e) = self.sync_attached(&conn) {
+                warn!("Failed to attach databases on read replica '{}': {}", replica, e);
+                continue;
+            }
+
+            debug!("Routing read to replica '{}'", replica);
+            self.stats.write().replica_reads += 1;
+            return Ok(conn);
+        }
+
+        warn!("No read replica available, falling back to the primary");
+        self.stats.write().replica_fallbacks += 1;
+        self.get()
+    }
+
+    /// Get a connection for a query, honouring its read-only hint
+    pub fn get_for(&self, query: &QueryBuilder) -> DbResult<PooledConnection<SqliteConnectionManager>> {
+        if query.is_read_only() {
+            self.get_read()
+        } else {
+            self.get()
+        }
+    }
+
+    /// Attach a secondary database to every connection under a schema alias
+    ///
+    /// Connections pick up the attachment the next time they are checked out.
+    pub fn attach(&self, alias: impl Into<String>, path: impl Into<String>) -> DbResult<()> {
+        let attachment = AttachedDatabase::new(alias, path);
+        attachment.validate()?;
+
+        let mut attached = self.attached.write();
+        if let Some(existing) = attached
+            .iter()
+            .find(|a| a.alias.eq_ignore_ascii_case(&attachment.alias))
+        {
+            if *existing == attachment {
+                return Ok(());
+            }
+            return Err(DatabaseError::DuplicateRecord {
+                entity: "AttachedDatabase".to_string(),
+                field: "alias".to_string(),
+                value: attachment.alias,
+            });
+        }
+
+        info!("Attaching database '{}' as '{}'", attachment.path, attachment.alias);
+        attached.push(attachment);
+        self.manages_attachments.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    /// Detach a secondary database from every connection
+    ///
+    /// Connections drop the attachment the next time they are checked out.
+    pub fn detach(&self, alias: &str) -> DbResult<()> {
+        let mut attached = self.attached.write();
+        let len = attached.len();
+        attached.retain(|a| !a.alias.eq_ignore_ascii_case(alias));
+
+        if attached.len() == len {
+            return Err(DatabaseError::NotFound {
+                entity: "AttachedDatabase".to_string(),
+                field: "alias".to_string(),
+                value: alias.to_string(),
+            });
+        }
+
+        info!("Detaching database '{}'", alias);
+        Ok(())
+    }
+
+    /// Get the attached secondary databases
+    pub fn attached(&self) -> Vec<AttachedDatabase> {
+        self.attached.read().clone()
+    }
+
+    /// Get the number of read replicas
+    pub fn replica_count(&self) -> usize {
+        self.replicas.len()
+    }
+
+    /// Bring a connection's attached databases in line with the pool
+    fn sync_attached(&self, conn: &Connection) -> DbResult<()> {
+        if !self.manages_attachments.load(Ordering::Acquire) {
+            return Ok(());
+        }
+
+        sync_attached(conn, &self.attached.read()).map_err(|e| {
+            error!("Failed to sync attached databases: {}", e);
+            self.stats.write().errors += 1;
+            DatabaseError::ConnectionError(format!("Failed to attach databases: {}", e))
+        })
+    }
+
     /// Perform a health check on the pool
     pub fn health_check(&self) -> DbResult<()> {
         debug!("Performing pool health check");
@@ -175,7 +336,17 @@ impl DatabasePool {
         F: FnOnce(&Connection) -> DbResult<T>,
     {
         let conn = self.get()?;
-        let result = f(&*conn)?;
+        let result = f(&conn)?;
+        Ok(result)
+    }
+
+    /// Execute a function with a read connection, from a replica when available
+    pub fn with_read_connection<F, T>(&self, f: F) -> DbResult<T>
+    where
+        F: FnOnce(&Connection) -> DbResult<T>,
+    {
+        let conn = self.get_read()?;
+        let result = f(&conn)?;
         Ok(result)
     }
 
@@ -223,6 +394,7 @@ impl PoolState {
 }
 
 /// Connection customizer for r2d2
+#[derive(Debug)]
 struct ConnectionCustomizer {
     config: DatabaseConfig,
 }
@@ -276,6 +448,108 @@ fn configure_connection(conn: &Connection, config: &PerformanceConfig) -> Result
     Ok(())
 }
 
+/// Build a lazily connecting pool for a read replica
+///
+/// Replica connections are opened read-only and never change the journal
+/// mode or other persistent settings of the replica file.
+fn build_replica_pool(
+    path: &str,
+    config: &DatabaseConfig,
+    key: Option<encryption::DatabaseKey>,
+) -> DbResult<Pool<SqliteConnectionManager>> {
+    info!("Adding read replica '{}'", path);
+
+    let performance = config.performance.clone();
+    let encryption_config = config.encryption.clone();
+    let manager = SqliteConnectionManager::file(path)
+        .with_flags(OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+        .with_init(move |conn| {
+            if let Some(key) = &key {
+                encryption::apply_key(conn, key, &encryption_config)?;
+            }
+            configure_read_connection(conn, &performance)
+        });
+
+    // An unreachable replica must not keep the pool from starting
+    Ok(Pool::builder()
+        .max_size(config.routing.replica_pool_size)
+        .min_idle(Some(0))
+        .connection_timeout(config.replica_timeout())
+        .max_lifetime(config.max_lifetime())
+        .idle_timeout(config.idle_timeout())
+        .build_unchecked(manager))
+}
+
+/// Configure a read replica connection
+fn configure_read_connection(conn: &Connection, config: &PerformanceConfig) -> Result<(), rusqlite::Error> {
+    conn.pragma_update(None, "cache_size", config.cache_size)?;
+
+    if config.mmap_size > 0 {
+        conn.pragma_update(None, "mmap_size", config.mmap_size)?;
+    }
+
+    conn.busy_timeout(Duration::from_millis(config.busy_timeout))?;
+    conn.pragma_update(None, "foreign_keys", "ON")?;
+
+    // Reject writes, including to attached databases
+    conn.pragma_update(None, "query_only", "ON")?;
+
+    debug!("Read replica connection configured");
+
+    Ok(())
+}
+
+/// Attach and detach databases so a connection matches the wanted set
+fn sync_attached(conn: &Connection, attached: &[AttachedDatabase]) -> Result<(), rusqlite::Error> {
+    let current = conn
+        .prepare("SELECT name, file FROM pragma_database_list")?
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (name, file) in &current {
+        if name == "main" || name == "temp" {
+            continue;
+        }
+
+        let wanted = attached
+            .iter()
+            .any(|a| a.alias.eq_ignore_ascii_case(name) && is_same_file(file, &a.path));
+        if !wanted {
+            debug!("Detaching database '{}'", name);
+            conn.execute_batch(&format!("DETACH DATABASE \"{}\"", name))?;
+        }
+    }
+
+    for attachment in attached {
+        let present = current.iter().any(|(name, file)| {
+            name.eq_ignore_ascii_case(&attachment.alias) && is_same_file(file, &attachment.path)
+        });
+        if !present {
+            debug!("Attaching database '{}' as '{}'", attachment.path, attachment.alias);
+            // Aliases are validated identifiers, the path is bound
+            conn.execute(
+                &format!("ATTACH DATABASE ?1 AS \"{}\"", attachment.alias),
+                [&attachment.path],
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Compare a file reported by `database_list` with a configured path
+fn is_same_file(file: &str, path: &str) -> bool {
+    // In-memory and temporary databases report an empty file name
+    if file.is_empty() {
+        return path.is_empty() || path == ":memory:";
+    }
+
+    match (Path::new(file).canonicalize(), Path::new(path).canonicalize()) {
+        (Ok(file), Ok(path)) => file == path,
+        _ => file == path,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -329,4 +603,86 @@ mod tests {
 
         assert_eq!(result, 42);
     }
+
+    #[test]
+    fn test_attached_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive_path = dir.path().join("archive.db").to_string_lossy().to_string();
+        let archive = Connection::open(&archive_path).unwrap();
+        archive
+            .execute_batch("CREATE TABLE cases (id TEXT PRIMARY KEY); INSERT INTO cases VALUES ('c1');")
+            .unwrap();
+        drop(archive);
+
+        let mut config = DatabaseConfig::test();
+        config.routing.attached = vec![AttachedDatabase::new("archive", archive_path.clone())];
+        let pool = DatabasePool::new(config).unwrap();
+
+        let count: i64 = pool
+            .with_connection(|conn| Ok(conn.query_row("SELECT COUNT(*) FROM archive.cases", [], |row| row.get(0))?))
+            .unwrap();
+        assert_eq!(count, 1);
+
+        assert!(pool.attach("archive", "other.db").is_err());
+        pool.attach("archive", archive_path).unwrap();
+
+        pool.detach("archive").unwrap();
+        assert!(pool.attached().is_empty());
+        assert!(pool.detach("archive").is_err());
+
+        let result = pool.with_connection(|conn| {
+            Ok(conn.query_row("SELECT COUNT(*) FROM archive.cases", [], |row| row.get::<_, i64>(0))?)
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_read_replica_routing() {
+        let dir = tempfile::tempdir().unwrap();
+        let primary_path = dir.path().join("primary.db").to_string_lossy().to_string();
+        let replica_path = dir.path().join("replica.db").to_string_lossy().to_string();
+
+        for (path, source) in [(&primary_path, "primary"), (&replica_path, "replica")] {
+            let conn = Connection::open(path).unwrap();
+            conn.execute("CREATE TABLE marker (source TEXT)", []).unwrap();
+            conn.execute("INSERT INTO marker VALUES (?1)", [source]).unwrap();
+        }
+
+        let mut config = DatabaseConfig::new(primary_path);
+        config.routing.replicas = vec![replica_path];
+        let pool = DatabasePool::new(config).unwrap();
+        assert_eq!(pool.replica_count(), 1);
+
+        let source = |conn: &Connection| -> String {
+            conn.query_row("SELECT source FROM marker", [], |row| row.get(0)).unwrap()
+        };
+
+        let query = QueryBuilder::new("marker");
+        assert_eq!(source(&pool.get_for(&query).unwrap()), "primary");
+
+        let replica = pool.get_for(&query.read_only()).unwrap();
+        assert_eq!(source(&replica), "replica");
+        assert!(replica.execute("INSERT INTO marker VALUES ('write')", []).is_err());
+
+        let stats = pool.stats();
+        assert_eq!(stats.replica_reads, 1);
+        assert_eq!(stats.replica_fallbacks, 0);
+    }
+
+    #[test]
+    fn test_read_replica_fallback() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = DatabaseConfig::test();
+        config.routing.replicas = vec![dir.path().join("missing.db").to_string_lossy().to_string()];
+        config.routing.replica_timeout = 50;
+        let pool = DatabasePool::new(config).unwrap();
+
+        let conn = pool.get_read().unwrap();
+        conn.execute_batch("SELECT 1").unwrap();
+        drop(conn);
+
+        let stats = pool.stats();
+        assert_eq!(stats.replica_reads, 0);
+        assert_eq!(stats.replica_fallbacks, 1);
+    }
 }
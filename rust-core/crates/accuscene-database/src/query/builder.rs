@@ -1,7 +1,9 @@
 //! Type-safe query builder for AccuScene database
 
+use crate::error::{DatabaseError, DbResult};
 use crate::query::filter::FilterCondition;
 use crate::query::pagination::Pagination;
+use crate::row_security::{AuthContext, RowAction, RowFilter, RowPolicy};
 
 /// Query builder for constructing SQL queries
 pub struct QueryBuilder {
@@ -12,6 +14,21 @@ pub struct QueryBuilder {
     limit: Option<usize>,
     offset: Option<usize>,
     joins: Vec<String>,
+    trash: TrashFilter,
+    read_only: bool,
+    auth: Option<AuthContext>,
+}
+
+/// Selection of soft-deleted rows for tables with a `deleted_at` column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrashFilter {
+    /// No filtering on `deleted_at`
+    #[default]
+    Include,
+    /// Only rows that are not in the trash
+    Exclude,
+    /// Only rows in the trash
+    Only,
 }
 
 impl QueryBuilder {
@@ -25,9 +42,17 @@ impl QueryBuilder {
             limit: None,
             offset: None,
             joins: Vec::new(),
+            trash: TrashFilter::Include,
+            read_only: false,
+            auth: None,
         }
     }
 
+    /// Create a query builder for a table in an attached database
+    pub fn attached(alias: &str, table: &str) -> Self {
+        Self::new(format!("{}.{}", alias, table))
+    }
+
     /// Specify columns to select
     pub fn select(mut self, columns: &[&str]) -> Self {
         self.select = columns.iter().map(|s| s.to_string()).collect();
@@ -97,6 +122,74 @@ impl QueryBuilder {
         self
     }
 
+    /// Exclude rows in the trash
+    pub fn exclude_trashed(mut self) -> Self {
+        self.trash = TrashFilter::Exclude;
+        self
+    }
+
+    /// Select only rows in the trash
+    pub fn only_trashed(mut self) -> Self {
+        self.trash = TrashFilter::Only;
+        self
+    }
+
+    /// Include rows in the trash (default)
+    pub fn with_trashed(mut self) -> Self {
+        self.trash = TrashFilter::Include;
+        self
+    }
+
+    /// Mark the query as read-only so it can be routed to a read replica
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Check whether the query may run on a read replica
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Attach the caller's auth context for row-level security
+    pub fn with_auth(mut self, context: AuthContext) -> Self {
+        self.auth = Some(context);
+        self
+    }
+
+    /// Get the attached auth context
+    pub fn auth_context(&self) -> Option<&AuthContext> {
+        self.auth.as_ref()
+    }
+
+    /// Append the row filter of a policy for the attached auth context
+    ///
+    /// Fails when no auth context is attached, so unauthenticated queries
+    /// cannot bypass the policy by accident.
+    pub fn apply_row_policy(mut self, policy: &dyn RowPolicy, action: RowAction) -> DbResult<Self> {
+        let context = self.auth.as_ref().ok_or_else(|| {
+            DatabaseError::PermissionDenied(format!("Query on {} has no auth context", self.table))
+        })?;
+
+        match policy.row_filter(context, &self.table, action)? {
+            RowFilter::Unrestricted => {},
+            filter => self.where_clause.push(filter.to_sql()),
+        }
+
+        Ok(self)
+    }
+
+    /// WHERE conditions including the trash filter
+    fn conditions(&self) -> Vec<String> {
+        let mut conditions = self.where_clause.clone();
+        match self.trash {
+            TrashFilter::Include => {}
+            TrashFilter::Exclude => conditions.push(format!("{}.deleted_at IS NULL", self.table)),
+            TrashFilter::Only => conditions.push(format!("{}.deleted_at IS NOT NULL", self.table)),
+        }
+        conditions
+    }
+
     /// Build the SQL query
     pub fn build(&self) -> String {
         let mut query = format!("SELECT {} FROM {}", self.select.join(", "), self.table);
@@ -107,8 +200,9 @@ impl QueryBuilder {
         }
 
         // Add WHERE clause
-        if !self.where_clause.is_empty() {
-            query.push_str(&format!(" WHERE {}", self.where_clause.join(" AND ")));
+        let conditions = self.conditions();
+        if !conditions.is_empty() {
+            query.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
         }
 
         // Add ORDER BY
@@ -139,8 +233,9 @@ impl QueryBuilder {
         }
 
         // Add WHERE clause
-        if !self.where_clause.is_empty() {
-            query.push_str(&format!(" WHERE {}", self.where_clause.join(" AND ")));
+        let conditions = self.conditions();
+        if !conditions.is_empty() {
+            query.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
         }
 
         query
@@ -195,6 +290,58 @@ mod tests {
         assert_eq!(query, "SELECT * FROM cases ORDER BY created_at DESC LIMIT 10");
     }
 
+    #[test]
+    fn test_query_with_trash_filter() {
+        let query = QueryBuilder::new("cases")
+            .where_clause("status = 'open'")
+            .exclude_trashed()
+            .build();
+        assert_eq!(
+            query,
+            "SELECT * FROM cases WHERE status = 'open' AND cases.deleted_at IS NULL"
+        );
+
+        let query = QueryBuilder::new("cases").only_trashed().build_count();
+        assert_eq!(query, "SELECT COUNT(*) FROM cases WHERE cases.deleted_at IS NOT NULL");
+
+        let query = QueryBuilder::new("cases").only_trashed().with_trashed().build();
+        assert_eq!(query, "SELECT * FROM cases");
+    }
+
+    #[test]
+    fn test_read_only_attached_query() {
+        let query = QueryBuilder::attached("archive", "cases").exclude_trashed();
+        assert!(!query.is_read_only());
+        assert_eq!(
+            query.build(),
+            "SELECT * FROM archive.cases WHERE archive.cases.deleted_at IS NULL"
+        );
+
+        let query = query.read_only();
+        assert!(query.is_read_only());
+    }
+
+    #[test]
+    fn test_query_with_row_policy() {
+        use crate::row_security::ScopedRowPolicy;
+
+        let policy = ScopedRowPolicy::new().with_owner_columns("cases", &["created_by"]);
+
+        let result = QueryBuilder::new("cases").apply_row_policy(&policy, RowAction::Read);
+        assert!(result.is_err_and(|e| e.is_permission_denied()));
+
+        let query = QueryBuilder::new("cases")
+            .where_clause("status = 'open'")
+            .with_auth(AuthContext::new("u1"))
+            .apply_row_policy(&policy, RowAction::Read)
+            .unwrap()
+            .build();
+        assert_eq!(
+            query,
+            "SELECT * FROM cases WHERE status = 'open' AND cases.created_by = 'u1'"
+        );
+    }
+
     #[test]
     fn test_query_with_join() {
         let query = QueryBuilder::new("cases")
@@ -7,6 +7,6 @@ pub mod builder;
 pub mod filter;
 pub mod pagination;
 
-pub use builder::QueryBuilder;
+pub use builder::{QueryBuilder, TrashFilter};
 pub use filter::{Filter, FilterOperator, FilterCondition};
 pub use pagination::{Pagination, PaginationResult, CursorPagination};
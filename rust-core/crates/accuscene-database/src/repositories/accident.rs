@@ -73,7 +73,7 @@ impl AccidentRepository {
                     description, severity, fatalities, injuries, property_damage_estimate,
                     police_report_number, police_department, reconstruction_data,
                     created_at, updated_at
-             FROM accidents WHERE case_id = ? ORDER BY accident_date DESC",
+             FROM accidents WHERE case_id = ? AND deleted_at IS NULL ORDER BY accident_date DESC",
         )?;
 
         let accidents = stmt
@@ -90,7 +90,7 @@ impl AccidentRepository {
                     description, severity, fatalities, injuries, property_damage_estimate,
                     police_report_number, police_department, reconstruction_data,
                     created_at, updated_at
-             FROM accidents WHERE severity = ? ORDER BY accident_date DESC",
+             FROM accidents WHERE severity = ? AND deleted_at IS NULL ORDER BY accident_date DESC",
         )?;
 
         let accidents = stmt
@@ -118,7 +118,7 @@ impl Repository for AccidentRepository {
                     description, severity, fatalities, injuries, property_damage_estimate,
                     police_report_number, police_department, reconstruction_data,
                     created_at, updated_at
-             FROM accidents WHERE id = ?",
+             FROM accidents WHERE id = ? AND deleted_at IS NULL",
         )?;
 
         let mut rows = stmt.query_map([id], Accident::from_row)?;
@@ -132,7 +132,7 @@ impl Repository for AccidentRepository {
                     description, severity, fatalities, injuries, property_damage_estimate,
                     police_report_number, police_department, reconstruction_data,
                     created_at, updated_at
-             FROM accidents ORDER BY accident_date DESC",
+             FROM accidents WHERE deleted_at IS NULL ORDER BY accident_date DESC",
         )?;
 
         let accidents = stmt
@@ -201,7 +201,15 @@ impl Repository for AccidentRepository {
     }
 
     fn count(&self, conn: &Connection) -> DbResult<i64> {
-        let count = conn.query_row("SELECT COUNT(*) FROM accidents", [], |row| row.get(0))?;
+        let count = conn.query_row(
+            "SELECT COUNT(*) FROM accidents WHERE deleted_at IS NULL",
+            [],
+            |row| row.get(0),
+        )?;
         Ok(count)
     }
+
+    fn trash_table(&self) -> Option<&'static str> {
+        Some("accidents")
+    }
 }
@@ -3,7 +3,7 @@
 //! Provides CRUD operations and specialized queries for cases.
 
 use crate::error::{DatabaseError, DbResult};
-use crate::repositories::Repository;
+use crate::repositories::{version_conflict, Repository};
 use rusqlite::{params, Connection, Row};
 use serde::{Deserialize, Serialize};
 
@@ -24,6 +24,8 @@ pub struct Case {
     pub closed_at: Option<String>,
     pub created_at: String,
     pub updated_at: String,
+    #[serde(default)]
+    pub version: i64,
 }
 
 impl Case {
@@ -49,6 +51,7 @@ impl Case {
             closed_at: row.get(11)?,
             created_at: row.get(12)?,
             updated_at: row.get(13)?,
+            version: row.get(14)?,
         })
     }
 }
@@ -65,8 +68,8 @@ impl CaseRepository {
     pub fn find_by_status(&self, conn: &Connection, status: &str) -> DbResult<Vec<Case>> {
         let mut stmt = conn.prepare(
             "SELECT id, case_number, title, description, status, priority, assigned_to,
-                    created_by, organization, tags, metadata, closed_at, created_at, updated_at
-             FROM cases WHERE status = ? ORDER BY created_at DESC",
+                    created_by, organization, tags, metadata, closed_at, created_at, updated_at, version
+             FROM cases WHERE status = ? AND deleted_at IS NULL ORDER BY created_at DESC",
         )?;
 
         let cases = stmt
@@ -80,8 +83,8 @@ impl CaseRepository {
     pub fn find_by_assigned_to(&self, conn: &Connection, user_id: &str) -> DbResult<Vec<Case>> {
         let mut stmt = conn.prepare(
             "SELECT id, case_number, title, description, status, priority, assigned_to,
-                    created_by, organization, tags, metadata, closed_at, created_at, updated_at
-             FROM cases WHERE assigned_to = ? ORDER BY created_at DESC",
+                    created_by, organization, tags, metadata, closed_at, created_at, updated_at, version
+             FROM cases WHERE assigned_to = ? AND deleted_at IS NULL ORDER BY created_at DESC",
         )?;
 
         let cases = stmt
@@ -95,8 +98,8 @@ impl CaseRepository {
     pub fn find_by_organization(&self, conn: &Connection, organization: &str) -> DbResult<Vec<Case>> {
         let mut stmt = conn.prepare(
             "SELECT id, case_number, title, description, status, priority, assigned_to,
-                    created_by, organization, tags, metadata, closed_at, created_at, updated_at
-             FROM cases WHERE organization = ? ORDER BY created_at DESC",
+                    created_by, organization, tags, metadata, closed_at, created_at, updated_at, version
+             FROM cases WHERE organization = ? AND deleted_at IS NULL ORDER BY created_at DESC",
         )?;
 
         let cases = stmt
@@ -110,8 +113,8 @@ impl CaseRepository {
     pub fn find_by_case_number(&self, conn: &Connection, case_number: &str) -> DbResult<Option<Case>> {
         let mut stmt = conn.prepare(
             "SELECT id, case_number, title, description, status, priority, assigned_to,
-                    created_by, organization, tags, metadata, closed_at, created_at, updated_at
-             FROM cases WHERE case_number = ?",
+                    created_by, organization, tags, metadata, closed_at, created_at, updated_at, version
+             FROM cases WHERE case_number = ? AND deleted_at IS NULL",
         )?;
 
         let mut rows = stmt.query_map([case_number], Case::from_row)?;
@@ -126,7 +129,8 @@ impl CaseRepository {
     /// Close a case
     pub fn close_case(&self, conn: &Connection, id: &str) -> DbResult<()> {
         let affected = conn.execute(
-            "UPDATE cases SET status = 'closed', closed_at = datetime('now') WHERE id = ?",
+            "UPDATE cases SET status = 'closed', closed_at = datetime('now'), version = version + 1
+             WHERE id = ?",
             [id],
         )?;
 
@@ -140,7 +144,7 @@ impl CaseRepository {
     /// Reopen a case
     pub fn reopen_case(&self, conn: &Connection, id: &str) -> DbResult<()> {
         let affected = conn.execute(
-            "UPDATE cases SET status = 'open', closed_at = NULL WHERE id = ?",
+            "UPDATE cases SET status = 'open', closed_at = NULL, version = version + 1 WHERE id = ?",
             [id],
         )?;
 
@@ -154,7 +158,7 @@ impl CaseRepository {
     /// Update case status
     pub fn update_status(&self, conn: &Connection, id: &str, status: &str) -> DbResult<()> {
         let affected = conn.execute(
-            "UPDATE cases SET status = ? WHERE id = ?",
+            "UPDATE cases SET status = ?, version = version + 1 WHERE id = ?",
             params![status, id],
         )?;
 
@@ -168,7 +172,7 @@ impl CaseRepository {
     /// Assign case to user
     pub fn assign_to(&self, conn: &Connection, id: &str, user_id: &str) -> DbResult<()> {
         let affected = conn.execute(
-            "UPDATE cases SET assigned_to = ? WHERE id = ?",
+            "UPDATE cases SET assigned_to = ?, version = version + 1 WHERE id = ?",
             params![user_id, id],
         )?;
 
@@ -193,8 +197,8 @@ impl Repository for CaseRepository {
     fn find_by_id(&self, conn: &Connection, id: &String) -> DbResult<Option<Case>> {
         let mut stmt = conn.prepare(
             "SELECT id, case_number, title, description, status, priority, assigned_to,
-                    created_by, organization, tags, metadata, closed_at, created_at, updated_at
-             FROM cases WHERE id = ?",
+                    created_by, organization, tags, metadata, closed_at, created_at, updated_at, version
+             FROM cases WHERE id = ? AND deleted_at IS NULL",
         )?;
 
         let mut rows = stmt.query_map([id], Case::from_row)?;
@@ -209,8 +213,8 @@ impl Repository for CaseRepository {
     fn find_all(&self, conn: &Connection) -> DbResult<Vec<Case>> {
         let mut stmt = conn.prepare(
             "SELECT id, case_number, title, description, status, priority, assigned_to,
-                    created_by, organization, tags, metadata, closed_at, created_at, updated_at
-             FROM cases ORDER BY created_at DESC",
+                    created_by, organization, tags, metadata, closed_at, created_at, updated_at, version
+             FROM cases WHERE deleted_at IS NULL ORDER BY created_at DESC",
         )?;
 
         let cases = stmt
@@ -252,8 +256,9 @@ impl Repository for CaseRepository {
 
         let affected = conn.execute(
             "UPDATE cases SET case_number = ?, title = ?, description = ?, status = ?,
-                             priority = ?, assigned_to = ?, organization = ?, tags = ?, metadata = ?
-             WHERE id = ?",
+                             priority = ?, assigned_to = ?, organization = ?, tags = ?, metadata = ?,
+                             version = version + 1
+             WHERE id = ? AND version = ?",
             params![
                 entity.case_number,
                 entity.title,
@@ -265,11 +270,12 @@ impl Repository for CaseRepository {
                 tags_json,
                 metadata_json,
                 entity.id,
+                entity.version,
             ],
         )?;
 
         if affected == 0 {
-            Err(DatabaseError::not_found("Case", "id", &entity.id))
+            Err(version_conflict(conn, "cases", "Case", &entity.id, entity.version))
         } else {
             Ok(())
         }
@@ -286,9 +292,17 @@ impl Repository for CaseRepository {
     }
 
     fn count(&self, conn: &Connection) -> DbResult<i64> {
-        let count = conn.query_row("SELECT COUNT(*) FROM cases", [], |row| row.get(0))?;
+        let count = conn.query_row(
+            "SELECT COUNT(*) FROM cases WHERE deleted_at IS NULL",
+            [],
+            |row| row.get(0),
+        )?;
         Ok(count)
     }
+
+    fn trash_table(&self) -> Option<&'static str> {
+        Some("cases")
+    }
 }
 
 #[cfg(test)]
@@ -312,6 +326,7 @@ mod tests {
             closed_at: None,
             created_at: chrono::Utc::now().to_rfc3339(),
             updated_at: chrono::Utc::now().to_rfc3339(),
+            version: 1,
         }
     }
 
@@ -1,7 +1,7 @@
 //! Evidence repository for managing evidence records
 
 use crate::error::{DatabaseError, DbResult};
-use crate::repositories::Repository;
+use crate::repositories::{version_conflict, Repository};
 use rusqlite::{params, Connection, Row};
 use serde::{Deserialize, Serialize};
 
@@ -27,10 +27,12 @@ pub struct Evidence {
     pub is_verified: bool,
     pub created_at: String,
     pub updated_at: String,
+    #[serde(default)]
+    pub version: i64,
 }
 
 impl Evidence {
-    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+    pub(crate) fn from_row(row: &Row) -> rusqlite::Result<Self> {
         let custody_json: Option<String> = row.get(14)?;
         let chain_of_custody = custody_json.and_then(|s| serde_json::from_str(&s).ok());
 
@@ -61,6 +63,7 @@ impl Evidence {
             is_verified: row.get::<_, i32>(17)? != 0,
             created_at: row.get(18)?,
             updated_at: row.get(19)?,
+            version: row.get(20)?,
         })
     }
 }
@@ -77,8 +80,8 @@ impl EvidenceRepository {
             "SELECT id, case_id, accident_id, evidence_type, title, description,
                     file_path, file_name, file_size, file_mime_type, file_hash,
                     collected_by, collected_at, location, chain_of_custody, tags, metadata,
-                    is_verified, created_at, updated_at
-             FROM evidence WHERE case_id = ? ORDER BY collected_at DESC",
+                    is_verified, created_at, updated_at, version
+             FROM evidence WHERE case_id = ? AND deleted_at IS NULL ORDER BY collected_at DESC",
         )?;
 
         let evidence = stmt
@@ -93,8 +96,8 @@ impl EvidenceRepository {
             "SELECT id, case_id, accident_id, evidence_type, title, description,
                     file_path, file_name, file_size, file_mime_type, file_hash,
                     collected_by, collected_at, location, chain_of_custody, tags, metadata,
-                    is_verified, created_at, updated_at
-             FROM evidence WHERE accident_id = ? ORDER BY collected_at DESC",
+                    is_verified, created_at, updated_at, version
+             FROM evidence WHERE accident_id = ? AND deleted_at IS NULL ORDER BY collected_at DESC",
         )?;
 
         let evidence = stmt
@@ -109,8 +112,8 @@ impl EvidenceRepository {
             "SELECT id, case_id, accident_id, evidence_type, title, description,
                     file_path, file_name, file_size, file_mime_type, file_hash,
                     collected_by, collected_at, location, chain_of_custody, tags, metadata,
-                    is_verified, created_at, updated_at
-             FROM evidence WHERE evidence_type = ? ORDER BY collected_at DESC",
+                    is_verified, created_at, updated_at, version
+             FROM evidence WHERE evidence_type = ? AND deleted_at IS NULL ORDER BY collected_at DESC",
         )?;
 
         let evidence = stmt
@@ -122,7 +125,7 @@ impl EvidenceRepository {
 
     pub fn verify_evidence(&self, conn: &Connection, id: &str) -> DbResult<()> {
         let affected = conn.execute(
-            "UPDATE evidence SET is_verified = 1 WHERE id = ?",
+            "UPDATE evidence SET is_verified = 1, version = version + 1 WHERE id = ?",
             [id],
         )?;
 
@@ -149,8 +152,8 @@ impl Repository for EvidenceRepository {
             "SELECT id, case_id, accident_id, evidence_type, title, description,
                     file_path, file_name, file_size, file_mime_type, file_hash,
                     collected_by, collected_at, location, chain_of_custody, tags, metadata,
-                    is_verified, created_at, updated_at
-             FROM evidence WHERE id = ?",
+                    is_verified, created_at, updated_at, version
+             FROM evidence WHERE id = ? AND deleted_at IS NULL",
         )?;
 
         let mut rows = stmt.query_map([id], Evidence::from_row)?;
@@ -162,8 +165,8 @@ impl Repository for EvidenceRepository {
             "SELECT id, case_id, accident_id, evidence_type, title, description,
                     file_path, file_name, file_size, file_mime_type, file_hash,
                     collected_by, collected_at, location, chain_of_custody, tags, metadata,
-                    is_verified, created_at, updated_at
-             FROM evidence ORDER BY collected_at DESC",
+                    is_verified, created_at, updated_at, version
+             FROM evidence WHERE deleted_at IS NULL ORDER BY collected_at DESC",
         )?;
 
         let evidence = stmt
@@ -205,19 +208,19 @@ impl Repository for EvidenceRepository {
                                description = ?, file_path = ?, file_name = ?, file_size = ?,
                                file_mime_type = ?, file_hash = ?, collected_by = ?, collected_at = ?,
                                location = ?, chain_of_custody = ?, tags = ?, metadata = ?,
-                               is_verified = ?
-             WHERE id = ?",
+                               is_verified = ?, version = version + 1
+             WHERE id = ? AND version = ?",
             params![
                 entity.case_id, entity.accident_id, entity.evidence_type, entity.title,
                 entity.description, entity.file_path, entity.file_name, entity.file_size,
                 entity.file_mime_type, entity.file_hash, entity.collected_by, entity.collected_at,
                 entity.location, custody_json, tags_json, metadata_json, entity.is_verified as i32,
-                entity.id,
+                entity.id, entity.version,
             ],
         )?;
 
         if affected == 0 {
-            Err(DatabaseError::not_found("Evidence", "id", &entity.id))
+            Err(version_conflict(conn, "evidence", "Evidence", &entity.id, entity.version))
         } else {
             Ok(())
         }
@@ -233,7 +236,15 @@ impl Repository for EvidenceRepository {
     }
 
     fn count(&self, conn: &Connection) -> DbResult<i64> {
-        let count = conn.query_row("SELECT COUNT(*) FROM evidence", [], |row| row.get(0))?;
+        let count = conn.query_row(
+            "SELECT COUNT(*) FROM evidence WHERE deleted_at IS NULL",
+            [],
+            |row| row.get(0),
+        )?;
         Ok(count)
     }
+
+    fn trash_table(&self) -> Option<&'static str> {
+        Some("evidence")
+    }
 }
@@ -8,15 +8,31 @@ pub mod accident;
 pub mod vehicle;
 pub mod evidence;
 pub mod user;
+pub mod organization;
+pub mod party;
+pub mod secured;
 
 pub use case::CaseRepository;
 pub use accident::AccidentRepository;
 pub use vehicle::VehicleRepository;
 pub use evidence::EvidenceRepository;
 pub use user::UserRepository;
+pub use organization::OrganizationRepository;
+pub use party::PartyRepository;
+pub use secured::{EntityId, SecuredRepository};
 
-use crate::error::DbResult;
-use rusqlite::Connection;
+use crate::error::{DatabaseError, DbResult};
+use rusqlite::{Connection, OptionalExtension, ToSql};
+use serde::{Deserialize, Serialize};
+
+/// Trashed entity awaiting restore or purge
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+    /// Entity ID
+    pub id: String,
+    /// When the entity was moved to the trash
+    pub deleted_at: String,
+}
 
 /// Base repository trait
 pub trait Repository {
@@ -45,4 +61,244 @@ pub trait Repository {
 
     /// Count all entities
     fn count(&self, conn: &Connection) -> DbResult<i64>;
+
+    /// Table backing the repository, for repositories with soft delete support
+    ///
+    /// The table needs a `deleted_at` column. Finders of such repositories
+    /// skip trashed rows.
+    fn trash_table(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Move an entity to the trash
+    fn soft_delete(&self, conn: &Connection, id: &Self::Id) -> DbResult<()>
+    where
+        Self::Id: ToSql + ToString,
+    {
+        let table = require_trash_table::<Self>(self.trash_table())?;
+        let affected = conn.execute(
+            &format!(
+                "UPDATE {} SET deleted_at = datetime('now') WHERE id = ? AND deleted_at IS NULL",
+                table
+            ),
+            [id],
+        )?;
+
+        if affected == 0 {
+            Err(DatabaseError::not_found(table, "id", id.to_string()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Restore an entity from the trash
+    fn restore(&self, conn: &Connection, id: &Self::Id) -> DbResult<()>
+    where
+        Self::Id: ToSql + ToString,
+    {
+        let table = require_trash_table::<Self>(self.trash_table())?;
+        let affected = conn.execute(
+            &format!(
+                "UPDATE {} SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL",
+                table
+            ),
+            [id],
+        )?;
+
+        if affected == 0 {
+            Err(DatabaseError::not_found(table, "id", id.to_string()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// List trashed entities, most recently deleted first
+    fn list_trash(&self, conn: &Connection) -> DbResult<Vec<TrashEntry>> {
+        let table = require_trash_table::<Self>(self.trash_table())?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, deleted_at FROM {} WHERE deleted_at IS NOT NULL
+             ORDER BY deleted_at DESC",
+            table
+        ))?;
+
+        let entries = stmt
+            .query_map([], |row| {
+                Ok(TrashEntry {
+                    id: row.get(0)?,
+                    deleted_at: row.get(1)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(entries)
+    }
+
+    /// Permanently delete entities that have been in the trash longer than `age`
+    ///
+    /// Returns the number of purged entities.
+    fn purge_older_than(&self, conn: &Connection, age: chrono::Duration) -> DbResult<usize> {
+        let table = require_trash_table::<Self>(self.trash_table())?;
+        let cutoff = (chrono::Utc::now() - age).format("%Y-%m-%d %H:%M:%S").to_string();
+        let purged = conn.execute(
+            &format!(
+                "DELETE FROM {} WHERE deleted_at IS NOT NULL AND deleted_at <= ?",
+                table
+            ),
+            [cutoff],
+        )?;
+
+        Ok(purged)
+    }
+}
+
+/// Resolve a compare-and-swap update that matched no rows
+///
+/// Returns a conflict error carrying the stored version, or a not found error
+/// if the row does not exist.
+pub(crate) fn version_conflict(
+    conn: &Connection,
+    table: &str,
+    entity: &str,
+    id: &str,
+    expected_version: i64,
+) -> DatabaseError {
+    let current_version = conn
+        .query_row(
+            &format!("SELECT version FROM {} WHERE id = ?", table),
+            [id],
+            |row| row.get::<_, i64>(0),
+        )
+        .optional();
+
+    match current_version {
+        Ok(Some(current_version)) => DatabaseError::Conflict {
+            entity: entity.to_string(),
+            id: id.to_string(),
+            expected_version,
+            current_version,
+        },
+        Ok(None) => DatabaseError::not_found(entity, "id", id),
+        Err(err) => err.into(),
+    }
+}
+
+fn require_trash_table<R: ?Sized>(table: Option<&'static str>) -> DbResult<&'static str> {
+    table.ok_or_else(|| {
+        DatabaseError::QueryError(format!(
+            "{} does not support soft delete",
+            std::any::type_name::<R>()
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::v001_initial::InitialMigration;
+    use crate::migrations::v007_soft_delete::SoftDeleteMigration;
+    use crate::migrations::v008_row_versioning::RowVersioningMigration;
+    use crate::migrations::Migration;
+
+    fn setup_db() -> Connection {
+        let mut conn = Connection::open_in_memory().unwrap();
+        InitialMigration.up(&mut conn).unwrap();
+        SoftDeleteMigration.up(&mut conn).unwrap();
+        RowVersioningMigration.up(&mut conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO users (id, email, username, full_name, password_hash)
+             VALUES ('u1', 'analyst@example.com', 'analyst', 'Analyst', 'hash');
+             INSERT INTO cases (id, case_number, title, created_by)
+             VALUES ('c1', 'CASE-1', 'Intersection collision', 'u1');
+             INSERT INTO cases (id, case_number, title, created_by)
+             VALUES ('c2', 'CASE-2', 'Highway collision', 'u1');",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_soft_delete_and_restore() {
+        let conn = setup_db();
+        let repo = CaseRepository::new();
+        let id = "c1".to_string();
+
+        repo.soft_delete(&conn, &id).unwrap();
+        assert!(repo.find_by_id(&conn, &id).unwrap().is_none());
+        assert_eq!(repo.count(&conn).unwrap(), 1);
+        assert!(repo.soft_delete(&conn, &id).is_err());
+
+        let trash = repo.list_trash(&conn).unwrap();
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].id, "c1");
+
+        repo.restore(&conn, &id).unwrap();
+        assert!(repo.find_by_id(&conn, &id).unwrap().is_some());
+        assert!(repo.list_trash(&conn).unwrap().is_empty());
+        assert!(repo.restore(&conn, &id).is_err());
+    }
+
+    #[test]
+    fn test_purge_older_than() {
+        let conn = setup_db();
+        let repo = CaseRepository::new();
+
+        repo.soft_delete(&conn, &"c1".to_string()).unwrap();
+        repo.soft_delete(&conn, &"c2".to_string()).unwrap();
+        conn.execute(
+            "UPDATE cases SET deleted_at = datetime('now', '-40 days') WHERE id = 'c1'",
+            [],
+        )
+        .unwrap();
+
+        assert_eq!(repo.purge_older_than(&conn, chrono::Duration::days(30)).unwrap(), 1);
+
+        let remaining: Vec<String> =
+            repo.list_trash(&conn).unwrap().into_iter().map(|entry| entry.id).collect();
+        assert_eq!(remaining, vec!["c2".to_string()]);
+        let total: i64 = conn
+            .query_row("SELECT COUNT(*) FROM cases", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(total, 1);
+    }
+
+    #[test]
+    fn test_update_version_conflict() {
+        let conn = setup_db();
+        let repo = CaseRepository::new();
+        let id = "c1".to_string();
+
+        let mut first = repo.find_by_id(&conn, &id).unwrap().unwrap();
+        let mut second = first.clone();
+        assert_eq!(first.version, 1);
+
+        first.title = "Updated by first client".to_string();
+        repo.update(&conn, &first).unwrap();
+
+        second.title = "Updated by second client".to_string();
+        match repo.update(&conn, &second) {
+            Err(DatabaseError::Conflict {
+                expected_version,
+                current_version,
+                ..
+            }) => {
+                assert_eq!(expected_version, 1);
+                assert_eq!(current_version, 2);
+            }
+            other => panic!("expected conflict, got {:?}", other),
+        }
+
+        let stored = repo.find_by_id(&conn, &id).unwrap().unwrap();
+        assert_eq!(stored.title, "Updated by first client");
+        assert_eq!(stored.version, 2);
+
+        second.id = "missing".to_string();
+        assert!(repo.update(&conn, &second).unwrap_err().is_not_found());
+    }
+
+    #[test]
+    fn test_soft_delete_unsupported() {
+        let conn = setup_db();
+        let repo = UserRepository::new();
+        assert!(repo.soft_delete(&conn, &"u1".to_string()).is_err());
+    }
 }
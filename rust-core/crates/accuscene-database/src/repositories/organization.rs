@@ -0,0 +1,519 @@
+//! Organization repository for the tenancy hierarchy
+//!
+//! Persists organizations, teams, workspaces, memberships and resource
+//! ownership links, and loads them back as an `OrgHierarchy` so that
+//! authorization scopes can be resolved.
+
+use crate::error::{DatabaseError, DbResult};
+use crate::repositories::Repository;
+use accuscene_core::types::organization::{
+    MemberRole, Membership, OrgHierarchy, OrgScope, Organization, OwnedResourceKind,
+    ResourceOwnership, Team, Workspace,
+};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use rusqlite::{params, Connection, Row};
+
+fn parse_timestamp(value: String) -> rusqlite::Result<DateTime<Utc>> {
+    if let Ok(ts) = DateTime::parse_from_rfc3339(&value) {
+        return Ok(ts.with_timezone(&Utc));
+    }
+
+    NaiveDateTime::parse_from_str(&value, "%Y-%m-%d %H:%M:%S")
+        .map(|ts| ts.and_utc())
+        .map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+        })
+}
+
+fn invalid_column(message: String) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(
+        0,
+        rusqlite::types::Type::Text,
+        message.into(),
+    )
+}
+
+fn organization_from_row(row: &Row) -> rusqlite::Result<Organization> {
+    Ok(Organization {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        slug: row.get(2)?,
+        created_at: parse_timestamp(row.get(3)?)?,
+        updated_at: parse_timestamp(row.get(4)?)?,
+    })
+}
+
+fn team_from_row(row: &Row) -> rusqlite::Result<Team> {
+    Ok(Team {
+        id: row.get(0)?,
+        organization_id: row.get(1)?,
+        name: row.get(2)?,
+        created_at: parse_timestamp(row.get(3)?)?,
+        updated_at: parse_timestamp(row.get(4)?)?,
+    })
+}
+
+fn workspace_from_row(row: &Row) -> rusqlite::Result<Workspace> {
+    Ok(Workspace {
+        id: row.get(0)?,
+        organization_id: row.get(1)?,
+        team_id: row.get(2)?,
+        name: row.get(3)?,
+        created_at: parse_timestamp(row.get(4)?)?,
+        updated_at: parse_timestamp(row.get(5)?)?,
+    })
+}
+
+fn membership_from_row(row: &Row) -> rusqlite::Result<Membership> {
+    let scope_type: String = row.get(1)?;
+    let scope_id: String = row.get(2)?;
+    let role: String = row.get(3)?;
+
+    Ok(Membership {
+        user_id: row.get(0)?,
+        scope: OrgScope::from_parts(&scope_type, scope_id)
+            .ok_or_else(|| invalid_column(format!("unknown scope type: {}", scope_type)))?,
+        role: MemberRole::parse(&role)
+            .ok_or_else(|| invalid_column(format!("unknown member role: {}", role)))?,
+        joined_at: parse_timestamp(row.get(4)?)?,
+    })
+}
+
+fn ownership_from_row(row: &Row) -> rusqlite::Result<ResourceOwnership> {
+    let resource_type: String = row.get(0)?;
+
+    Ok(ResourceOwnership {
+        resource_kind: OwnedResourceKind::parse(&resource_type)
+            .ok_or_else(|| invalid_column(format!("unknown resource type: {}", resource_type)))?,
+        resource_id: row.get(1)?,
+        workspace_id: row.get(2)?,
+        owner_id: row.get(3)?,
+        created_at: parse_timestamp(row.get(4)?)?,
+    })
+}
+
+/// Organization repository
+pub struct OrganizationRepository;
+
+impl OrganizationRepository {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Find an organization by slug
+    pub fn find_by_slug(&self, conn: &Connection, slug: &str) -> DbResult<Option<Organization>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, name, slug, created_at, updated_at FROM organizations WHERE slug = ?",
+        )?;
+
+        let mut rows = stmt.query_map([slug], organization_from_row)?;
+        Ok(rows.next().transpose()?)
+    }
+
+    /// Create a team
+    pub fn create_team(&self, conn: &Connection, team: &Team) -> DbResult<()> {
+        conn.execute(
+            "INSERT INTO teams (id, organization_id, name, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?)",
+            params![
+                team.id, team.organization_id, team.name,
+                team.created_at.to_rfc3339(), team.updated_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Delete a team; its workspaces move up to the organization
+    pub fn delete_team(&self, conn: &Connection, id: &str) -> DbResult<()> {
+        let affected = conn.execute("DELETE FROM teams WHERE id = ?", [id])?;
+        if affected == 0 {
+            return Err(DatabaseError::not_found("Team", "id", id));
+        }
+
+        conn.execute(
+            "DELETE FROM memberships WHERE scope_type = 'team' AND scope_id = ?",
+            [id],
+        )?;
+        Ok(())
+    }
+
+    /// List teams of an organization
+    pub fn find_teams(&self, conn: &Connection, organization_id: &str) -> DbResult<Vec<Team>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, organization_id, name, created_at, updated_at
+             FROM teams WHERE organization_id = ? ORDER BY name",
+        )?;
+
+        let teams = stmt
+            .query_map([organization_id], team_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(teams)
+    }
+
+    /// Create a workspace
+    pub fn create_workspace(&self, conn: &Connection, workspace: &Workspace) -> DbResult<()> {
+        conn.execute(
+            "INSERT INTO workspaces (id, organization_id, team_id, name, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?)",
+            params![
+                workspace.id, workspace.organization_id, workspace.team_id, workspace.name,
+                workspace.created_at.to_rfc3339(), workspace.updated_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Delete a workspace and its ownership links
+    pub fn delete_workspace(&self, conn: &Connection, id: &str) -> DbResult<()> {
+        conn.execute("DELETE FROM resource_ownership WHERE workspace_id = ?", [id])?;
+        conn.execute(
+            "DELETE FROM memberships WHERE scope_type = 'workspace' AND scope_id = ?",
+            [id],
+        )?;
+
+        let affected = conn.execute("DELETE FROM workspaces WHERE id = ?", [id])?;
+        if affected == 0 {
+            Err(DatabaseError::not_found("Workspace", "id", id))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// List workspaces of an organization
+    pub fn find_workspaces(&self, conn: &Connection, organization_id: &str) -> DbResult<Vec<Workspace>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, organization_id, team_id, name, created_at, updated_at
+             FROM workspaces WHERE organization_id = ? ORDER BY name",
+        )?;
+
+        let workspaces = stmt
+            .query_map([organization_id], workspace_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(workspaces)
+    }
+
+    /// Add or update a membership
+    pub fn upsert_member(&self, conn: &Connection, membership: &Membership) -> DbResult<()> {
+        conn.execute(
+            "INSERT INTO memberships (user_id, scope_type, scope_id, role, joined_at)
+             VALUES (?, ?, ?, ?, ?)
+             ON CONFLICT(user_id, scope_type, scope_id) DO UPDATE SET role = excluded.role",
+            params![
+                membership.user_id, membership.scope.kind(), membership.scope.id(),
+                membership.role.as_str(), membership.joined_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Remove a membership
+    pub fn remove_member(&self, conn: &Connection, user_id: &str, scope: &OrgScope) -> DbResult<()> {
+        let affected = conn.execute(
+            "DELETE FROM memberships WHERE user_id = ? AND scope_type = ? AND scope_id = ?",
+            params![user_id, scope.kind(), scope.id()],
+        )?;
+
+        if affected == 0 {
+            Err(DatabaseError::not_found("Membership", "user_id", user_id))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// List direct members of a scope
+    pub fn find_members(&self, conn: &Connection, scope: &OrgScope) -> DbResult<Vec<Membership>> {
+        let mut stmt = conn.prepare(
+            "SELECT user_id, scope_type, scope_id, role, joined_at
+             FROM memberships WHERE scope_type = ? AND scope_id = ? ORDER BY joined_at",
+        )?;
+
+        let members = stmt
+            .query_map(params![scope.kind(), scope.id()], membership_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(members)
+    }
+
+    /// List a user's memberships
+    pub fn find_memberships_of(&self, conn: &Connection, user_id: &str) -> DbResult<Vec<Membership>> {
+        let mut stmt = conn.prepare(
+            "SELECT user_id, scope_type, scope_id, role, joined_at
+             FROM memberships WHERE user_id = ? ORDER BY joined_at",
+        )?;
+
+        let memberships = stmt
+            .query_map([user_id], membership_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(memberships)
+    }
+
+    /// Resolve the organization a scope belongs to
+    pub fn find_scope_organization(&self, conn: &Connection, scope: &OrgScope) -> DbResult<Option<String>> {
+        let sql = match scope {
+            OrgScope::Organization(_) => "SELECT id FROM organizations WHERE id = ?",
+            OrgScope::Team(_) => "SELECT organization_id FROM teams WHERE id = ?",
+            OrgScope::Workspace(_) => "SELECT organization_id FROM workspaces WHERE id = ?",
+        };
+
+        let mut stmt = conn.prepare(sql)?;
+        let mut rows = stmt.query_map([scope.id()], |row| row.get(0))?;
+        Ok(rows.next().transpose()?)
+    }
+
+    /// Record or transfer ownership of a resource
+    pub fn set_owner(&self, conn: &Connection, ownership: &ResourceOwnership) -> DbResult<()> {
+        conn.execute(
+            "INSERT INTO resource_ownership (resource_type, resource_id, workspace_id, owner_id, created_at)
+             VALUES (?, ?, ?, ?, ?)
+             ON CONFLICT(resource_type, resource_id)
+             DO UPDATE SET workspace_id = excluded.workspace_id, owner_id = excluded.owner_id",
+            params![
+                ownership.resource_kind.as_str(), ownership.resource_id, ownership.workspace_id,
+                ownership.owner_id, ownership.created_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Find the ownership link of a resource
+    pub fn find_owner(
+        &self,
+        conn: &Connection,
+        kind: OwnedResourceKind,
+        resource_id: &str,
+    ) -> DbResult<Option<ResourceOwnership>> {
+        let mut stmt = conn.prepare(
+            "SELECT resource_type, resource_id, workspace_id, owner_id, created_at
+             FROM resource_ownership WHERE resource_type = ? AND resource_id = ?",
+        )?;
+
+        let mut rows = stmt.query_map(params![kind.as_str(), resource_id], ownership_from_row)?;
+        Ok(rows.next().transpose()?)
+    }
+
+    /// List resources owned by a workspace, optionally filtered by kind
+    pub fn find_workspace_resources(
+        &self,
+        conn: &Connection,
+        workspace_id: &str,
+        kind: Option<OwnedResourceKind>,
+    ) -> DbResult<Vec<ResourceOwnership>> {
+        let mut stmt = conn.prepare(
+            "SELECT resource_type, resource_id, workspace_id, owner_id, created_at
+             FROM resource_ownership
+             WHERE workspace_id = ?1 AND (?2 IS NULL OR resource_type = ?2)
+             ORDER BY created_at DESC",
+        )?;
+
+        let resources = stmt
+            .query_map(params![workspace_id, kind.map(|k| k.as_str().to_string())], ownership_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(resources)
+    }
+
+    /// Load the full hierarchy of an organization, including memberships
+    pub fn load_hierarchy(&self, conn: &Connection, organization_id: &str) -> DbResult<OrgHierarchy> {
+        let organization = self
+            .find_by_id(conn, &organization_id.to_string())?
+            .ok_or_else(|| DatabaseError::not_found("Organization", "id", organization_id))?;
+
+        let to_invalid = |e: accuscene_core::AccuSceneError| DatabaseError::InvalidData(e.to_string());
+
+        let mut hierarchy = OrgHierarchy::new();
+        hierarchy.add_organization(organization).map_err(to_invalid)?;
+
+        for team in self.find_teams(conn, organization_id)? {
+            hierarchy.add_team(team).map_err(to_invalid)?;
+        }
+
+        for workspace in self.find_workspaces(conn, organization_id)? {
+            hierarchy.add_workspace(workspace).map_err(to_invalid)?;
+        }
+
+        let mut stmt = conn.prepare(
+            "SELECT m.user_id, m.scope_type, m.scope_id, m.role, m.joined_at
+             FROM memberships m
+             WHERE (m.scope_type = 'organization' AND m.scope_id = ?1)
+                OR (m.scope_type = 'team' AND m.scope_id IN
+                    (SELECT id FROM teams WHERE organization_id = ?1))
+                OR (m.scope_type = 'workspace' AND m.scope_id IN
+                    (SELECT id FROM workspaces WHERE organization_id = ?1))",
+        )?;
+
+        let memberships = stmt
+            .query_map([organization_id], membership_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for membership in memberships {
+            hierarchy.add_member(membership).map_err(to_invalid)?;
+        }
+
+        Ok(hierarchy)
+    }
+}
+
+impl Default for OrganizationRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Repository for OrganizationRepository {
+    type Entity = Organization;
+    type Id = String;
+
+    fn find_by_id(&self, conn: &Connection, id: &String) -> DbResult<Option<Organization>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, name, slug, created_at, updated_at FROM organizations WHERE id = ?",
+        )?;
+
+        let mut rows = stmt.query_map([id], organization_from_row)?;
+        Ok(rows.next().transpose()?)
+    }
+
+    fn find_all(&self, conn: &Connection) -> DbResult<Vec<Organization>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, name, slug, created_at, updated_at FROM organizations ORDER BY name",
+        )?;
+
+        let organizations = stmt
+            .query_map([], organization_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(organizations)
+    }
+
+    fn create(&self, conn: &Connection, entity: &Organization) -> DbResult<()> {
+        conn.execute(
+            "INSERT INTO organizations (id, name, slug, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?)",
+            params![
+                entity.id, entity.name, entity.slug,
+                entity.created_at.to_rfc3339(), entity.updated_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn update(&self, conn: &Connection, entity: &Organization) -> DbResult<()> {
+        let affected = conn.execute(
+            "UPDATE organizations SET name = ?, slug = ? WHERE id = ?",
+            params![entity.name, entity.slug, entity.id],
+        )?;
+
+        if affected == 0 {
+            Err(DatabaseError::not_found("Organization", "id", &entity.id))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn delete(&self, conn: &Connection, id: &String) -> DbResult<()> {
+        let affected = conn.execute("DELETE FROM organizations WHERE id = ?", [id])?;
+        if affected == 0 {
+            Err(DatabaseError::not_found("Organization", "id", id))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn count(&self, conn: &Connection) -> DbResult<i64> {
+        let count = conn.query_row("SELECT COUNT(*) FROM organizations", [], |row| row.get(0))?;
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::v001_initial::InitialMigration;
+    use crate::migrations::v003_organizations::OrganizationsMigration;
+    use crate::migrations::Migration;
+
+    fn setup() -> Connection {
+        let mut conn = Connection::open_in_memory().unwrap();
+        InitialMigration.up(&mut conn).unwrap();
+        OrganizationsMigration.up(&mut conn).unwrap();
+        conn.execute(
+            "INSERT INTO users (id, email, username, full_name, password_hash)
+             VALUES ('alice', 'alice@example.com', 'alice', 'Alice', 'hash')",
+            [],
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_hierarchy_roundtrip() {
+        let conn = setup();
+        let repo = OrganizationRepository::new();
+
+        let org = Organization::new("Acme Forensics".to_string(), "acme".to_string());
+        let team = Team::new(org.id.clone(), "Reconstruction".to_string());
+        let workspace = Workspace::for_team(org.id.clone(), team.id.clone(), "I-95".to_string());
+
+        repo.create(&conn, &org).unwrap();
+        repo.create_team(&conn, &team).unwrap();
+        repo.create_workspace(&conn, &workspace).unwrap();
+        repo.upsert_member(
+            &conn,
+            &Membership::new("alice".to_string(), OrgScope::Team(team.id.clone()), MemberRole::Admin),
+        )
+        .unwrap();
+
+        let ownership = ResourceOwnership::new(
+            OwnedResourceKind::Dashboard,
+            "dash-1".to_string(),
+            workspace.id.clone(),
+            "alice".to_string(),
+        );
+        repo.set_owner(&conn, &ownership).unwrap();
+
+        let hierarchy = repo.load_hierarchy(&conn, &org.id).unwrap();
+        assert_eq!(
+            hierarchy.effective_role("alice", &OrgScope::Workspace(workspace.id.clone())),
+            Some(MemberRole::Admin)
+        );
+
+        let found = repo
+            .find_owner(&conn, OwnedResourceKind::Dashboard, "dash-1")
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.workspace_id, workspace.id);
+
+        let dashboards = repo
+            .find_workspace_resources(&conn, &workspace.id, Some(OwnedResourceKind::Dashboard))
+            .unwrap();
+        assert_eq!(dashboards.len(), 1);
+        let cases = repo
+            .find_workspace_resources(&conn, &workspace.id, Some(OwnedResourceKind::Case))
+            .unwrap();
+        assert!(cases.is_empty());
+    }
+
+    #[test]
+    fn test_membership_upsert_and_remove() {
+        let conn = setup();
+        let repo = OrganizationRepository::new();
+
+        let org = Organization::new("Acme".to_string(), "acme".to_string());
+        repo.create(&conn, &org).unwrap();
+        let scope = OrgScope::Organization(org.id.clone());
+
+        repo.upsert_member(&conn, &Membership::new("alice".to_string(), scope.clone(), MemberRole::Viewer))
+            .unwrap();
+        repo.upsert_member(&conn, &Membership::new("alice".to_string(), scope.clone(), MemberRole::Owner))
+            .unwrap();
+
+        let members = repo.find_members(&conn, &scope).unwrap();
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].role, MemberRole::Owner);
+
+        repo.remove_member(&conn, "alice", &scope).unwrap();
+        assert!(repo.find_memberships_of(&conn, "alice").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,499 @@
+//! Party repository for the deduplicated person registry
+//!
+//! Stores parties with their matching keys, links them to cases and
+//! evidence, finds likely duplicates and merges them while keeping a
+//! snapshot of every merged record.
+
+use crate::error::{DatabaseError, DbResult};
+use crate::repositories::Repository;
+use accuscene_core::traits::Mergeable;
+use accuscene_core::types::party::{
+    MatchScore, Party, PartyLink, PartyMatcher, PartyMerge, PartyRole, PrivacyClass,
+};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use rusqlite::{params, params_from_iter, Connection, Row};
+
+const PARTY_COLUMNS: &str = "id, full_name, normalized_name, phonetic_key, date_of_birth, \
+     licence_number, phone, address, privacy_class, merged_into, created_at, updated_at";
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+fn parse_timestamp(value: String) -> rusqlite::Result<DateTime<Utc>> {
+    if let Ok(ts) = DateTime::parse_from_rfc3339(&value) {
+        return Ok(ts.with_timezone(&Utc));
+    }
+
+    NaiveDateTime::parse_from_str(&value, "%Y-%m-%d %H:%M:%S")
+        .map(|ts| ts.and_utc())
+        .map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e))
+        })
+}
+
+fn invalid_column(message: String) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(
+        0,
+        rusqlite::types::Type::Text,
+        message.into(),
+    )
+}
+
+fn party_from_row(row: &Row) -> rusqlite::Result<Party> {
+    let date_of_birth: Option<String> = row.get(4)?;
+    let privacy_class: String = row.get(8)?;
+
+    Ok(Party {
+        id: row.get(0)?,
+        full_name: row.get(1)?,
+        normalized_name: row.get(2)?,
+        phonetic_key: row.get(3)?,
+        date_of_birth: date_of_birth
+            .map(|dob| {
+                NaiveDate::parse_from_str(&dob, DATE_FORMAT)
+                    .map_err(|e| invalid_column(format!("invalid date of birth: {}", e)))
+            })
+            .transpose()?,
+        licence_number: row.get(5)?,
+        phone: row.get(6)?,
+        address: row.get(7)?,
+        privacy_class: PrivacyClass::parse(&privacy_class)
+            .ok_or_else(|| invalid_column(format!("unknown privacy class: {}", privacy_class)))?,
+        merged_into: row.get(9)?,
+        created_at: parse_timestamp(row.get(10)?)?,
+        updated_at: parse_timestamp(row.get(11)?)?,
+    })
+}
+
+fn link_from_row(row: &Row) -> rusqlite::Result<PartyLink> {
+    let role: String = row.get(4)?;
+
+    Ok(PartyLink {
+        id: row.get(0)?,
+        party_id: row.get(1)?,
+        case_id: row.get(2)?,
+        evidence_id: row.get(3)?,
+        role: PartyRole::parse(&role)
+            .ok_or_else(|| invalid_column(format!("unknown party role: {}", role)))?,
+        created_at: parse_timestamp(row.get(5)?)?,
+    })
+}
+
+fn merge_from_row(row: &Row) -> rusqlite::Result<PartyMerge> {
+    let snapshot: String = row.get(5)?;
+
+    Ok(PartyMerge {
+        id: row.get(0)?,
+        survivor_id: row.get(1)?,
+        merged_id: row.get(2)?,
+        merged_by: row.get(3)?,
+        reason: row.get(4)?,
+        snapshot: serde_json::from_str(&snapshot)
+            .map_err(|e| invalid_column(format!("invalid party snapshot: {}", e)))?,
+        merged_at: parse_timestamp(row.get(6)?)?,
+    })
+}
+
+/// Party repository
+pub struct PartyRepository;
+
+impl PartyRepository {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Find a party with fields above `clearance` redacted
+    pub fn find_redacted(
+        &self,
+        conn: &Connection,
+        id: &str,
+        clearance: PrivacyClass,
+    ) -> DbResult<Option<Party>> {
+        Ok(self
+            .find_by_id(conn, &id.to_string())?
+            .map(|party| party.redacted(clearance)))
+    }
+
+    /// Find the party a record currently resolves to, following merges
+    pub fn resolve(&self, conn: &Connection, id: &str) -> DbResult<Party> {
+        let mut party = self
+            .find_by_id(conn, &id.to_string())?
+            .ok_or_else(|| DatabaseError::not_found("Party", "id", id))?;
+
+        while let Some(survivor) = party.merged_into.clone() {
+            party = self
+                .find_by_id(conn, &survivor)?
+                .ok_or_else(|| DatabaseError::not_found("Party", "id", &survivor))?;
+        }
+
+        Ok(party)
+    }
+
+    /// Find likely duplicates of a party, best match first
+    ///
+    /// Candidates are blocked on any shared phonetic token, licence number
+    /// or date of birth, then scored with `matcher`. Merged records are
+    /// never returned.
+    pub fn find_candidates(
+        &self,
+        conn: &Connection,
+        party: &Party,
+        matcher: &PartyMatcher,
+    ) -> DbResult<Vec<(Party, MatchScore)>> {
+        let mut clauses = Vec::new();
+        let mut values = Vec::new();
+
+        for code in party.phonetic_key.split_whitespace() {
+            clauses.push("(' ' || phonetic_key || ' ') LIKE ?");
+            values.push(format!("% {} %", code));
+        }
+        if let Some(licence) = &party.licence_number {
+            clauses.push("licence_number = ?");
+            values.push(licence.clone());
+        }
+        if let Some(dob) = party.date_of_birth {
+            clauses.push("date_of_birth = ?");
+            values.push(dob.format(DATE_FORMAT).to_string());
+        }
+
+        if clauses.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let sql = format!(
+            "SELECT {} FROM parties WHERE merged_into IS NULL AND id != ? AND ({})",
+            PARTY_COLUMNS,
+            clauses.join(" OR ")
+        );
+        values.insert(0, party.id.clone());
+
+        let mut stmt = conn.prepare(&sql)?;
+        let candidates = stmt
+            .query_map(params_from_iter(values.iter()), party_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut matches: Vec<_> = candidates
+            .into_iter()
+            .map(|candidate| {
+                let score = matcher.score(party, &candidate);
+                (candidate, score)
+            })
+            .filter(|(_, score)| score.score >= matcher.threshold)
+            .collect();
+
+        matches.sort_by(|a, b| {
+            b.1.score
+                .partial_cmp(&a.1.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        Ok(matches)
+    }
+
+    /// Link a party to a case or piece of evidence
+    pub fn link(&self, conn: &Connection, link: &PartyLink) -> DbResult<()> {
+        conn.execute(
+            "INSERT INTO party_links (id, party_id, case_id, evidence_id, role, created_at)
+             VALUES (?, ?, ?, ?, ?, ?)",
+            params![
+                link.id, link.party_id, link.case_id, link.evidence_id,
+                link.role.as_str(), link.created_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Remove a link
+    pub fn unlink(&self, conn: &Connection, link_id: &str) -> DbResult<()> {
+        let affected = conn.execute("DELETE FROM party_links WHERE id = ?", [link_id])?;
+        if affected == 0 {
+            return Err(DatabaseError::not_found("PartyLink", "id", link_id));
+        }
+        Ok(())
+    }
+
+    /// List the case and evidence links of a party
+    pub fn links_of(&self, conn: &Connection, party_id: &str) -> DbResult<Vec<PartyLink>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, party_id, case_id, evidence_id, role, created_at
+             FROM party_links WHERE party_id = ? ORDER BY created_at",
+        )?;
+
+        let links = stmt
+            .query_map([party_id], link_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(links)
+    }
+
+    /// List the parties involved in a case with their roles
+    pub fn parties_in_case(&self, conn: &Connection, case_id: &str) -> DbResult<Vec<(Party, PartyRole)>> {
+        let sql = format!(
+            "SELECT DISTINCT {}, l.role FROM parties p
+             JOIN party_links l ON l.party_id = p.id
+             WHERE l.case_id = ? ORDER BY p.full_name",
+            PARTY_COLUMNS
+                .split(", ")
+                .map(|c| format!("p.{}", c.trim()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        let mut stmt = conn.prepare(&sql)?;
+        let parties = stmt
+            .query_map([case_id], |row| {
+                let role: String = row.get(12)?;
+                let role = PartyRole::parse(&role)
+                    .ok_or_else(|| invalid_column(format!("unknown party role: {}", role)))?;
+                Ok((party_from_row(row)?, role))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(parties)
+    }
+
+    /// List the parties linked to a piece of evidence
+    pub fn parties_for_evidence(&self, conn: &Connection, evidence_id: &str) -> DbResult<Vec<Party>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM parties WHERE id IN
+             (SELECT party_id FROM party_links WHERE evidence_id = ?)
+             ORDER BY full_name",
+            PARTY_COLUMNS
+        ))?;
+
+        let parties = stmt
+            .query_map([evidence_id], party_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(parties)
+    }
+
+    /// Merge a duplicate into a surviving party
+    ///
+    /// The survivor gains any details it was missing, all links of the
+    /// duplicate move to the survivor, and the duplicate is kept with
+    /// `merged_into` set so old references still resolve. A snapshot of
+    /// the duplicate is recorded in the merge history. Run this inside a
+    /// transaction (see `execute_transaction`) so a failed merge leaves
+    /// nothing half-applied.
+    pub fn merge(
+        &self,
+        conn: &Connection,
+        survivor_id: &str,
+        merged_id: &str,
+        merged_by: &str,
+        reason: Option<String>,
+    ) -> DbResult<PartyMerge> {
+        let mut survivor = self
+            .find_by_id(conn, &survivor_id.to_string())?
+            .ok_or_else(|| DatabaseError::not_found("Party", "id", survivor_id))?;
+        let merged = self
+            .find_by_id(conn, &merged_id.to_string())?
+            .ok_or_else(|| DatabaseError::not_found("Party", "id", merged_id))?;
+
+        survivor
+            .merge(&merged)
+            .map_err(|e| DatabaseError::InvalidData(e.to_string()))?;
+        self.update(conn, &survivor)?;
+
+        conn.execute(
+            "UPDATE party_links SET party_id = ? WHERE party_id = ?",
+            params![survivor_id, merged_id],
+        )?;
+        conn.execute(
+            "UPDATE parties SET merged_into = ? WHERE merged_into = ? OR id = ?",
+            params![survivor_id, merged_id, merged_id],
+        )?;
+
+        let mut record = PartyMerge::new(survivor_id.to_string(), &merged, merged_by.to_string());
+        record.reason = reason;
+
+        conn.execute(
+            "INSERT INTO party_merges (id, survivor_id, merged_id, merged_by, reason, snapshot, merged_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+            params![
+                record.id, record.survivor_id, record.merged_id, record.merged_by, record.reason,
+                serde_json::to_string(&record.snapshot)?, record.merged_at.to_rfc3339(),
+            ],
+        )?;
+
+        Ok(record)
+    }
+
+    /// Merge history of a surviving party, oldest first
+    pub fn merge_history(&self, conn: &Connection, survivor_id: &str) -> DbResult<Vec<PartyMerge>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, survivor_id, merged_id, merged_by, reason, snapshot, merged_at
+             FROM party_merges WHERE survivor_id = ? ORDER BY merged_at",
+        )?;
+
+        let history = stmt
+            .query_map([survivor_id], merge_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(history)
+    }
+}
+
+impl Default for PartyRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Repository for PartyRepository {
+    type Entity = Party;
+    type Id = String;
+
+    fn find_by_id(&self, conn: &Connection, id: &String) -> DbResult<Option<Party>> {
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM parties WHERE id = ?", PARTY_COLUMNS))?;
+
+        let mut rows = stmt.query_map([id], party_from_row)?;
+        Ok(rows.next().transpose()?)
+    }
+
+    fn find_all(&self, conn: &Connection) -> DbResult<Vec<Party>> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM parties WHERE merged_into IS NULL ORDER BY full_name",
+            PARTY_COLUMNS
+        ))?;
+
+        let parties = stmt
+            .query_map([], party_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(parties)
+    }
+
+    fn create(&self, conn: &Connection, entity: &Party) -> DbResult<()> {
+        conn.execute(
+            &format!(
+                "INSERT INTO parties ({}) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                PARTY_COLUMNS
+            ),
+            params![
+                entity.id, entity.full_name, entity.normalized_name, entity.phonetic_key,
+                entity.date_of_birth.map(|dob| dob.format(DATE_FORMAT).to_string()),
+                entity.licence_number, entity.phone, entity.address,
+                entity.privacy_class.as_str(), entity.merged_into,
+                entity.created_at.to_rfc3339(), entity.updated_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn update(&self, conn: &Connection, entity: &Party) -> DbResult<()> {
+        let affected = conn.execute(
+            "UPDATE parties SET full_name = ?, normalized_name = ?, phonetic_key = ?,
+             date_of_birth = ?, licence_number = ?, phone = ?, address = ?,
+             privacy_class = ?, merged_into = ? WHERE id = ?",
+            params![
+                entity.full_name, entity.normalized_name, entity.phonetic_key,
+                entity.date_of_birth.map(|dob| dob.format(DATE_FORMAT).to_string()),
+                entity.licence_number, entity.phone, entity.address,
+                entity.privacy_class.as_str(), entity.merged_into, entity.id,
+            ],
+        )?;
+
+        if affected == 0 {
+            Err(DatabaseError::not_found("Party", "id", &entity.id))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn delete(&self, conn: &Connection, id: &String) -> DbResult<()> {
+        let affected = conn.execute("DELETE FROM parties WHERE id = ?", [id])?;
+        if affected == 0 {
+            Err(DatabaseError::not_found("Party", "id", id))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn count(&self, conn: &Connection) -> DbResult<i64> {
+        let count = conn.query_row(
+            "SELECT COUNT(*) FROM parties WHERE merged_into IS NULL",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::v001_initial::InitialMigration;
+    use crate::migrations::v005_party_registry::PartyRegistryMigration;
+    use crate::migrations::Migration;
+
+    fn setup() -> Connection {
+        let mut conn = Connection::open_in_memory().unwrap();
+        InitialMigration.up(&mut conn).unwrap();
+        PartyRegistryMigration.up(&mut conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO users (id, email, username, full_name, password_hash)
+             VALUES ('alice', 'alice@example.com', 'alice', 'Alice', 'hash');
+             INSERT INTO cases (id, case_number, title, created_by)
+             VALUES ('case-1', 'C-1', 'Intersection collision', 'alice'),
+                    ('case-2', 'C-2', 'Rear-end collision', 'alice');",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_find_candidates() {
+        let conn = setup();
+        let repo = PartyRepository::new();
+        let dob = NaiveDate::from_ymd_opt(1985, 4, 12).unwrap();
+
+        let existing = Party::new("John Smith".to_string()).with_date_of_birth(dob);
+        let unrelated = Party::new("Maria Garcia".to_string());
+        repo.create(&conn, &existing).unwrap();
+        repo.create(&conn, &unrelated).unwrap();
+
+        let incoming = Party::new("Jon Smyth".to_string()).with_date_of_birth(dob);
+        let candidates = repo
+            .find_candidates(&conn, &incoming, &PartyMatcher::default())
+            .unwrap();
+
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].0.id, existing.id);
+        assert_eq!(candidates[0].1.dob_match, Some(true));
+    }
+
+    #[test]
+    fn test_merge_moves_links_and_keeps_history() {
+        let conn = setup();
+        let repo = PartyRepository::new();
+
+        let survivor = Party::new("Jane Doe".to_string());
+        let duplicate = Party::new("Jane Do".to_string()).with_licence_number("D-123");
+        repo.create(&conn, &survivor).unwrap();
+        repo.create(&conn, &duplicate).unwrap();
+
+        repo.link(&conn, &PartyLink::new(survivor.id.clone(), "case-1".to_string(), PartyRole::Driver))
+            .unwrap();
+        repo.link(&conn, &PartyLink::new(duplicate.id.clone(), "case-2".to_string(), PartyRole::Witness))
+            .unwrap();
+
+        repo.merge(&conn, &survivor.id, &duplicate.id, "alice", Some("same licence".to_string()))
+            .unwrap();
+
+        assert_eq!(repo.links_of(&conn, &survivor.id).unwrap().len(), 2);
+        assert!(repo.links_of(&conn, &duplicate.id).unwrap().is_empty());
+        assert_eq!(repo.parties_in_case(&conn, "case-2").unwrap()[0].0.id, survivor.id);
+
+        let resolved = repo.resolve(&conn, &duplicate.id).unwrap();
+        assert_eq!(resolved.id, survivor.id);
+        assert_eq!(resolved.licence_number.as_deref(), Some("D123"));
+        assert_eq!(repo.count(&conn).unwrap(), 1);
+
+        let history = repo.merge_history(&conn, &survivor.id).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].snapshot.full_name, "Jane Do");
+        assert!(history[0].snapshot.merged_into.is_none());
+
+        assert!(repo.merge(&conn, &survivor.id, &duplicate.id, "alice", None).is_err());
+    }
+}
@@ -0,0 +1,296 @@
+//! Repositories enforcing row-level security
+//!
+//! [`SecuredRepository`] wraps a repository and checks every operation
+//! against a [`RowPolicy`] for the caller's [`AuthContext`]. Reads only
+//! return rows the policy grants; writes are rejected for rows outside the
+//! caller's scope, and rolled back when they would move a row out of it.
+
+use crate::error::{DatabaseError, DbResult};
+use crate::repositories::accident::Accident;
+use crate::repositories::case::Case;
+use crate::repositories::evidence::Evidence;
+use crate::repositories::vehicle::Vehicle;
+use crate::repositories::Repository;
+use crate::row_security::{AuthContext, RowAction, RowFilter, RowPolicy};
+use rusqlite::Connection;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Savepoint wrapping writes until the written row has been checked
+const CHECK_SAVEPOINT: &str = "row_security_check";
+
+/// Entity with a string ID
+pub trait EntityId {
+    /// Entity ID
+    fn entity_id(&self) -> &str;
+}
+
+impl EntityId for Case {
+    fn entity_id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl EntityId for Accident {
+    fn entity_id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl EntityId for Vehicle {
+    fn entity_id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl EntityId for Evidence {
+    fn entity_id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// Repository checked against a row policy
+pub struct SecuredRepository<R> {
+    repository: R,
+    table: &'static str,
+    policy: Arc<dyn RowPolicy>,
+}
+
+impl<R> SecuredRepository<R>
+where
+    R: Repository<Id = String>,
+    R::Entity: EntityId,
+{
+    /// Wrap a repository backed by `table`
+    pub fn new(repository: R, table: &'static str, policy: Arc<dyn RowPolicy>) -> Self {
+        Self {
+            repository,
+            table,
+            policy,
+        }
+    }
+
+    /// Get the wrapped repository
+    pub fn inner(&self) -> &R {
+        &self.repository
+    }
+
+    /// Find an entity by ID, if the caller may read it
+    pub fn find_by_id(&self, conn: &Connection, context: &AuthContext, id: &String) -> DbResult<Option<R::Entity>> {
+        if !self.is_accessible(conn, context, id, RowAction::Read)? {
+            return Ok(None);
+        }
+
+        self.repository.find_by_id(conn, id)
+    }
+
+    /// Find all entities the caller may read
+    pub fn find_all(&self, conn: &Connection, context: &AuthContext) -> DbResult<Vec<R::Entity>> {
+        let predicate = match self.filter(context, RowAction::Read)? {
+            RowFilter::Unrestricted => return self.repository.find_all(conn),
+            RowFilter::Denied => return Ok(Vec::new()),
+            RowFilter::Predicate(predicate) => predicate,
+        };
+
+        let mut stmt = conn.prepare(&format!("SELECT id FROM {} WHERE {}", self.table, predicate))?;
+        let visible = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<HashSet<_>, _>>()?;
+
+        let mut entities = self.repository.find_all(conn)?;
+        entities.retain(|entity| visible.contains(entity.entity_id()));
+        Ok(entities)
+    }
+
+    /// Count the entities the caller may read
+    pub fn count(&self, conn: &Connection, context: &AuthContext) -> DbResult<i64> {
+        let predicate = match self.filter(context, RowAction::Read)? {
+            RowFilter::Unrestricted => return self.repository.count(conn),
+            RowFilter::Denied => return Ok(0),
+            RowFilter::Predicate(predicate) => predicate,
+        };
+
+        let live = if self.repository.trash_table().is_some() {
+            format!("{}.deleted_at IS NULL AND ", self.table)
+        } else {
+            String::new()
+        };
+        let count = conn.query_row(
+            &format!("SELECT COUNT(*) FROM {} WHERE {}{}", self.table, live, predicate),
+            [],
+            |row| row.get(0),
+        )?;
+
+        Ok(count)
+    }
+
+    /// Create an entity the caller may read afterwards
+    pub fn create(&self, conn: &Connection, context: &AuthContext, entity: &R::Entity) -> DbResult<()> {
+        self.checked_write(conn, context, entity.entity_id(), || self.repository.create(conn, entity))
+    }
+
+    /// Update an entity the caller may update, keeping it readable by the caller
+    pub fn update(&self, conn: &Connection, context: &AuthContext, entity: &R::Entity) -> DbResult<()> {
+        let id = entity.entity_id();
+        self.require(conn, context, id, RowAction::Update)?;
+        self.checked_write(conn, context, id, || self.repository.update(conn, entity))
+    }
+
+    /// Delete an entity the caller may delete
+    pub fn delete(&self, conn: &Connection, context: &AuthContext, id: &String) -> DbResult<()> {
+        self.require(conn, context, id, RowAction::Delete)?;
+        self.repository.delete(conn, id)
+    }
+
+    /// Move an entity the caller may delete to the trash
+    pub fn soft_delete(&self, conn: &Connection, context: &AuthContext, id: &String) -> DbResult<()> {
+        self.require(conn, context, id, RowAction::Delete)?;
+        self.repository.soft_delete(conn, id)
+    }
+
+    /// Restore an entity the caller may update from the trash
+    pub fn restore(&self, conn: &Connection, context: &AuthContext, id: &String) -> DbResult<()> {
+        self.require(conn, context, id, RowAction::Update)?;
+        self.repository.restore(conn, id)
+    }
+
+    /// Check whether the caller may perform an action on a row
+    pub fn is_accessible(&self, conn: &Connection, context: &AuthContext, id: &str, action: RowAction) -> DbResult<bool> {
+        match self.filter(context, action)? {
+            RowFilter::Unrestricted => Ok(true),
+            RowFilter::Denied => Ok(false),
+            RowFilter::Predicate(predicate) => {
+                let accessible = conn.query_row(
+                    &format!(
+                        "SELECT EXISTS(SELECT 1 FROM {table} WHERE {table}.id = ?1 AND {predicate})",
+                        table = self.table,
+                        predicate = predicate
+                    ),
+                    [id],
+                    |row| row.get(0),
+                )?;
+                Ok(accessible)
+            },
+        }
+    }
+
+    fn filter(&self, context: &AuthContext, action: RowAction) -> DbResult<RowFilter> {
+        self.policy.row_filter(context, self.table, action)
+    }
+
+    fn require(&self, conn: &Connection, context: &AuthContext, id: &str, action: RowAction) -> DbResult<()> {
+        if self.is_accessible(conn, context, id, action)? {
+            Ok(())
+        } else {
+            Err(self.denied(context, id, action))
+        }
+    }
+
+    /// Run a write and roll it back unless the row is readable by the caller afterwards
+    fn checked_write<F>(&self, conn: &Connection, context: &AuthContext, id: &str, write: F) -> DbResult<()>
+    where
+        F: FnOnce() -> DbResult<()>,
+    {
+        conn.execute_batch(&format!("SAVEPOINT {}", CHECK_SAVEPOINT))?;
+
+        let result = write().and_then(|()| self.require(conn, context, id, RowAction::Read));
+
+        match result {
+            Ok(()) => conn.execute_batch(&format!("RELEASE {}", CHECK_SAVEPOINT))?,
+            Err(_) => conn.execute_batch(&format!(
+                "ROLLBACK TO {savepoint}; RELEASE {savepoint}",
+                savepoint = CHECK_SAVEPOINT
+            ))?,
+        }
+
+        result
+    }
+
+    fn denied(&self, context: &AuthContext, id: &str, action: RowAction) -> DatabaseError {
+        DatabaseError::PermissionDenied(format!(
+            "User {} may not {} {} {}",
+            context.user_id,
+            action.as_str(),
+            self.table,
+            id
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::v001_initial::InitialMigration;
+    use crate::migrations::v007_soft_delete::SoftDeleteMigration;
+    use crate::migrations::v008_row_versioning::RowVersioningMigration;
+    use crate::migrations::Migration;
+    use crate::repositories::CaseRepository;
+    use crate::row_security::ScopedRowPolicy;
+
+    fn setup() -> (Connection, SecuredRepository<CaseRepository>) {
+        let mut conn = Connection::open_in_memory().unwrap();
+        InitialMigration.up(&mut conn).unwrap();
+        SoftDeleteMigration.up(&mut conn).unwrap();
+        RowVersioningMigration.up(&mut conn).unwrap();
+        conn.execute_batch(
+            r#"
+            INSERT INTO users (id, email, username, full_name, password_hash) VALUES
+                ('u1', 'u1@example.com', 'u1', 'User One', 'hash'),
+                ('u2', 'u2@example.com', 'u2', 'User Two', 'hash');
+            INSERT INTO cases (id, case_number, title, created_by, organization) VALUES
+                ('c1', 'C-1', 'Own case', 'u1', 'org2'),
+                ('c2', 'C-2', 'Organization case', 'u2', 'org1'),
+                ('c3', 'C-3', 'Other case', 'u2', 'org2');
+            "#,
+        )
+        .unwrap();
+
+        let policy = ScopedRowPolicy::case_data().with_bypass_role("admin");
+        (conn, SecuredRepository::new(CaseRepository::new(), "cases", Arc::new(policy)))
+    }
+
+    #[test]
+    fn test_secured_reads() {
+        let (conn, repository) = setup();
+        let context = AuthContext::new("u1").with_organization("org1");
+
+        let mut ids: Vec<String> = repository
+            .find_all(&conn, &context)
+            .unwrap()
+            .into_iter()
+            .map(|case| case.id)
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec!["c1".to_string(), "c2".to_string()]);
+        assert_eq!(repository.count(&conn, &context).unwrap(), 2);
+
+        assert!(repository.find_by_id(&conn, &context, &"c2".to_string()).unwrap().is_some());
+        assert!(repository.find_by_id(&conn, &context, &"c3".to_string()).unwrap().is_none());
+
+        let admin = AuthContext::new("u3").with_roles(vec!["admin".to_string()]);
+        assert_eq!(repository.count(&conn, &admin).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_secured_writes() {
+        let (conn, repository) = setup();
+        let context = AuthContext::new("u1").with_organization("org1");
+
+        let err = repository.soft_delete(&conn, &context, &"c3".to_string()).unwrap_err();
+        assert!(err.is_permission_denied());
+
+        repository.soft_delete(&conn, &context, &"c1".to_string()).unwrap();
+        repository.restore(&conn, &context, &"c1".to_string()).unwrap();
+
+        // Moving a case out of the caller's scope is rolled back
+        let mut case = repository.find_by_id(&conn, &context, &"c2".to_string()).unwrap().unwrap();
+        case.organization = Some("org2".to_string());
+        let err = repository.update(&conn, &context, &case).unwrap_err();
+        assert!(err.is_permission_denied());
+
+        let organization: String = conn
+            .query_row("SELECT organization FROM cases WHERE id = 'c2'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(organization, "org1");
+    }
+}
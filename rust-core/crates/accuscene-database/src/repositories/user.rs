@@ -136,6 +136,88 @@ impl UserRepository {
             Ok(())
         }
     }
+
+    pub fn update_role(&self, conn: &Connection, id: &str, role: &str) -> DbResult<()> {
+        let affected = conn.execute(
+            "UPDATE users SET role = ? WHERE id = ?",
+            params![role, id],
+        )?;
+
+        if affected == 0 {
+            Err(DatabaseError::not_found("User", "id", id))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn find_active(&self, conn: &Connection) -> DbResult<Vec<User>> {
+        let mut stmt = conn.prepare(
+            "SELECT id, email, username, full_name, password_hash, role, organization,
+                    phone, is_active, last_login_at, created_at, updated_at
+             FROM users WHERE is_active = 1 ORDER BY created_at DESC",
+        )?;
+
+        let users = stmt
+            .query_map([], User::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(users)
+    }
+
+    pub fn set_mfa(&self, conn: &Connection, id: &str, secret: Option<&str>) -> DbResult<()> {
+        let affected = conn.execute(
+            "UPDATE users SET mfa_enabled = ?, mfa_secret = ? WHERE id = ?",
+            params![secret.is_some() as i32, secret, id],
+        )?;
+
+        if affected == 0 {
+            Err(DatabaseError::not_found("User", "id", id))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn reset_mfa(&self, conn: &Connection, id: &str) -> DbResult<()> {
+        self.set_mfa(conn, id, None)
+    }
+
+    pub fn is_mfa_enabled(&self, conn: &Connection, id: &str) -> DbResult<bool> {
+        let mut stmt = conn.prepare("SELECT mfa_enabled FROM users WHERE id = ?")?;
+        let mut rows = stmt.query_map([id], |row| row.get::<_, i32>(0))?;
+
+        match rows.next().transpose()? {
+            Some(enabled) => Ok(enabled != 0),
+            None => Err(DatabaseError::not_found("User", "id", id)),
+        }
+    }
+
+    pub fn set_password_reset_required(
+        &self,
+        conn: &Connection,
+        id: &str,
+        required: bool,
+    ) -> DbResult<()> {
+        let affected = conn.execute(
+            "UPDATE users SET password_reset_required = ? WHERE id = ?",
+            params![required as i32, id],
+        )?;
+
+        if affected == 0 {
+            Err(DatabaseError::not_found("User", "id", id))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn is_password_reset_required(&self, conn: &Connection, id: &str) -> DbResult<bool> {
+        let mut stmt = conn.prepare("SELECT password_reset_required FROM users WHERE id = ?")?;
+        let mut rows = stmt.query_map([id], |row| row.get::<_, i32>(0))?;
+
+        match rows.next().transpose()? {
+            Some(required) => Ok(required != 0),
+            None => Err(DatabaseError::not_found("User", "id", id)),
+        }
+    }
 }
 
 impl Default for UserRepository {
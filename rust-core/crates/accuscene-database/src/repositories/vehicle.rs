@@ -1,7 +1,7 @@
 //! Vehicle repository for managing vehicle records
 
 use crate::error::{DatabaseError, DbResult};
-use crate::repositories::Repository;
+use crate::repositories::{version_conflict, Repository};
 use rusqlite::{params, Connection, Row};
 use serde::{Deserialize, Serialize};
 
@@ -29,6 +29,8 @@ pub struct Vehicle {
     pub metadata: Option<serde_json::Value>,
     pub created_at: String,
     pub updated_at: String,
+    #[serde(default)]
+    pub version: i64,
 }
 
 impl Vehicle {
@@ -65,6 +67,7 @@ impl Vehicle {
             metadata,
             created_at: row.get(20)?,
             updated_at: row.get(21)?,
+            version: row.get(22)?,
         })
     }
 }
@@ -82,8 +85,8 @@ impl VehicleRepository {
                     license_plate, vehicle_type, damage_description, occupants,
                     speed_estimate, direction_of_travel, final_position_lat, final_position_lng,
                     airbag_deployment, driver_info, insurance_info, metadata,
-                    created_at, updated_at
-             FROM vehicles WHERE accident_id = ? ORDER BY vehicle_number",
+                    created_at, updated_at, version
+             FROM vehicles WHERE accident_id = ? AND deleted_at IS NULL ORDER BY vehicle_number",
         )?;
 
         let vehicles = stmt
@@ -99,8 +102,8 @@ impl VehicleRepository {
                     license_plate, vehicle_type, damage_description, occupants,
                     speed_estimate, direction_of_travel, final_position_lat, final_position_lng,
                     airbag_deployment, driver_info, insurance_info, metadata,
-                    created_at, updated_at
-             FROM vehicles WHERE vin = ?",
+                    created_at, updated_at, version
+             FROM vehicles WHERE vin = ? AND deleted_at IS NULL",
         )?;
 
         let mut rows = stmt.query_map([vin], Vehicle::from_row)?;
@@ -124,8 +127,8 @@ impl Repository for VehicleRepository {
                     license_plate, vehicle_type, damage_description, occupants,
                     speed_estimate, direction_of_travel, final_position_lat, final_position_lng,
                     airbag_deployment, driver_info, insurance_info, metadata,
-                    created_at, updated_at
-             FROM vehicles WHERE id = ?",
+                    created_at, updated_at, version
+             FROM vehicles WHERE id = ? AND deleted_at IS NULL",
         )?;
 
         let mut rows = stmt.query_map([id], Vehicle::from_row)?;
@@ -138,8 +141,8 @@ impl Repository for VehicleRepository {
                     license_plate, vehicle_type, damage_description, occupants,
                     speed_estimate, direction_of_travel, final_position_lat, final_position_lng,
                     airbag_deployment, driver_info, insurance_info, metadata,
-                    created_at, updated_at
-             FROM vehicles ORDER BY created_at DESC",
+                    created_at, updated_at, version
+             FROM vehicles WHERE deleted_at IS NULL ORDER BY created_at DESC",
         )?;
 
         let vehicles = stmt
@@ -181,19 +184,21 @@ impl Repository for VehicleRepository {
                                 color = ?, vin = ?, license_plate = ?, vehicle_type = ?,
                                 damage_description = ?, occupants = ?, speed_estimate = ?,
                                 direction_of_travel = ?, final_position_lat = ?, final_position_lng = ?,
-                                airbag_deployment = ?, driver_info = ?, insurance_info = ?, metadata = ?
-             WHERE id = ?",
+                                airbag_deployment = ?, driver_info = ?, insurance_info = ?, metadata = ?,
+                                version = version + 1
+             WHERE id = ? AND version = ?",
             params![
                 entity.accident_id, entity.vehicle_number, entity.make, entity.model, entity.year,
                 entity.color, entity.vin, entity.license_plate, entity.vehicle_type,
                 entity.damage_description, entity.occupants, entity.speed_estimate,
                 entity.direction_of_travel, entity.final_position_lat, entity.final_position_lng,
                 entity.airbag_deployment, driver_json, insurance_json, metadata_json, entity.id,
+                entity.version,
             ],
         )?;
 
         if affected == 0 {
-            Err(DatabaseError::not_found("Vehicle", "id", &entity.id))
+            Err(version_conflict(conn, "vehicles", "Vehicle", &entity.id, entity.version))
         } else {
             Ok(())
         }
@@ -209,7 +214,15 @@ impl Repository for VehicleRepository {
     }
 
     fn count(&self, conn: &Connection) -> DbResult<i64> {
-        let count = conn.query_row("SELECT COUNT(*) FROM vehicles", [], |row| row.get(0))?;
+        let count = conn.query_row(
+            "SELECT COUNT(*) FROM vehicles WHERE deleted_at IS NULL",
+            [],
+            |row| row.get(0),
+        )?;
         Ok(count)
     }
+
+    fn trash_table(&self) -> Option<&'static str> {
+        Some("vehicles")
+    }
 }
@@ -0,0 +1,496 @@
+//! Row-level security hooks
+//!
+//! Queries can carry an [`AuthContext`] describing the caller. A pluggable
+//! [`RowPolicy`] turns the context into a [`RowFilter`], a SQL predicate that
+//! is appended to queries before they run so callers only see and modify the
+//! rows they are entitled to (e.g. their own cases, or their organization's).
+//!
+//! [`ScopedRowPolicy`] covers ownership, organization and parent-row scoping
+//! with plain configuration, and can enforce hard tenant boundaries on top. Policies backed by the RBAC/ABAC engine of
+//! `accuscene-security` implement [`RowPolicy`] on top of it.
+
+use crate::error::DbResult;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Caller identity attached to a query
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthContext {
+    /// Authenticated user ID
+    pub user_id: String,
+    /// User roles
+    pub roles: Vec<String>,
+    /// User permissions
+    pub permissions: Vec<String>,
+    /// Organization the user acts for
+    pub organization: Option<String>,
+    /// Additional attributes for attribute-based policies
+    pub attributes: HashMap<String, String>,
+}
+
+impl AuthContext {
+    /// Create a context for a user
+    pub fn new(user_id: impl Into<String>) -> Self {
+        Self {
+            user_id: user_id.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the user roles
+    pub fn with_roles(mut self, roles: Vec<String>) -> Self {
+        self.roles = roles;
+        self
+    }
+
+    /// Set the user permissions
+    pub fn with_permissions(mut self, permissions: Vec<String>) -> Self {
+        self.permissions = permissions;
+        self
+    }
+
+    /// Set the organization
+    pub fn with_organization(mut self, organization: impl Into<String>) -> Self {
+        self.organization = Some(organization.into());
+        self
+    }
+
+    /// Add an attribute
+    pub fn with_attribute(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.attributes.insert(key.into(), value.into());
+        self
+    }
+
+    /// Check if the user has a role
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r == role)
+    }
+
+    /// Check if the user has a permission
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.permissions.iter().any(|p| p == permission)
+    }
+
+    /// Get an attribute
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes.get(key).map(String::as_str)
+    }
+}
+
+/// Operation a row filter is requested for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RowAction {
+    Read,
+    Update,
+    Delete,
+}
+
+impl RowAction {
+    /// Action name, as used in permissions like `cases:read`
+    pub fn as_str(&self) -> &str {
+        match self {
+            RowAction::Read => "read",
+            RowAction::Update => "update",
+            RowAction::Delete => "delete",
+        }
+    }
+}
+
+/// Rows a caller may access
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RowFilter {
+    /// All rows
+    Unrestricted,
+    /// No rows
+    Denied,
+    /// Rows matching a SQL predicate
+    Predicate(String),
+}
+
+impl RowFilter {
+    /// Rows whose column equals a value
+    pub fn column_eq(column: &str, value: &str) -> Self {
+        RowFilter::Predicate(format!("{} = {}", column, quote_literal(value)))
+    }
+
+    /// Rows whose column is one of the values
+    pub fn column_in(column: &str, values: &[String]) -> Self {
+        if values.is_empty() {
+            return RowFilter::Denied;
+        }
+
+        let values: Vec<String> = values.iter().map(|v| quote_literal(v)).collect();
+        RowFilter::Predicate(format!("{} IN ({})", column, values.join(", ")))
+    }
+
+    /// Rows matching any of the filters
+    pub fn any(filters: Vec<RowFilter>) -> Self {
+        let mut predicates = Vec::new();
+        for filter in filters {
+            match filter {
+                RowFilter::Unrestricted => return RowFilter::Unrestricted,
+                RowFilter::Denied => {},
+                RowFilter::Predicate(predicate) => predicates.push(predicate),
+            }
+        }
+
+        match predicates.len() {
+            0 => RowFilter::Denied,
+            1 => RowFilter::Predicate(predicates.remove(0)),
+            _ => RowFilter::Predicate(format!("({})", predicates.join(" OR "))),
+        }
+    }
+
+    /// Rows matching all of the filters
+    pub fn all(filters: Vec<RowFilter>) -> Self {
+        let mut predicates = Vec::new();
+        for filter in filters {
+            match filter {
+                RowFilter::Unrestricted => {},
+                RowFilter::Denied => return RowFilter::Denied,
+                RowFilter::Predicate(predicate) => predicates.push(predicate),
+            }
+        }
+
+        match predicates.len() {
+            0 => RowFilter::Unrestricted,
+            1 => RowFilter::Predicate(predicates.remove(0)),
+            _ => RowFilter::Predicate(format!("({})", predicates.join(" AND "))),
+        }
+    }
+
+    /// SQL predicate for a WHERE clause
+    pub fn to_sql(&self) -> String {
+        match self {
+            RowFilter::Unrestricted => "1 = 1".to_string(),
+            RowFilter::Denied => "0 = 1".to_string(),
+            RowFilter::Predicate(predicate) => predicate.clone(),
+        }
+    }
+}
+
+/// Source of row filters for a caller
+pub trait RowPolicy: Send + Sync {
+    /// Filter for the rows of `table` the caller may access for an action
+    fn row_filter(&self, context: &AuthContext, table: &str, action: RowAction) -> DbResult<RowFilter>;
+}
+
+/// Policy granting access to all rows
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnrestrictedPolicy;
+
+impl RowPolicy for UnrestrictedPolicy {
+    fn row_filter(&self, _context: &AuthContext, _table: &str, _action: RowAction) -> DbResult<RowFilter> {
+        Ok(RowFilter::Unrestricted)
+    }
+}
+
+/// Scoping rules for one table
+#[derive(Debug, Clone, Default)]
+struct TableScope {
+    owner_columns: Vec<String>,
+    organization_column: Option<String>,
+    tenant_column: Option<String>,
+    parent: Option<(String, String)>,
+}
+
+/// Policy scoping rows by owner, organization and parent row
+///
+/// A row is accessible when any rule of its table grants it: the user is in
+/// one of the owner columns, the row belongs to the user's organization, or
+/// its parent row is accessible. Tables without rules are unrestricted
+/// unless [`deny_unscoped`](Self::deny_unscoped) is set.
+///
+/// Tenant columns are a boundary rather than a grant: rows of a tenant-scoped
+/// table (or of its child tables) must also belong to the user's
+/// organization. Bypass roles stay within that boundary; only cross-tenant
+/// roles leave it.
+#[derive(Debug, Clone, Default)]
+pub struct ScopedRowPolicy {
+    tables: HashMap<String, TableScope>,
+    bypass_roles: Vec<String>,
+    cross_tenant_roles: Vec<String>,
+    deny_unscoped: bool,
+}
+
+impl ScopedRowPolicy {
+    /// Create an empty policy
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Default scoping for case data
+    ///
+    /// Cases are scoped to their creator, assignee and organization;
+    /// accidents and evidence follow their case, vehicles their accident.
+    pub fn case_data() -> Self {
+        Self::new()
+            .with_owner_columns("cases", &["created_by", "assigned_to"])
+            .with_organization_column("cases", "organization")
+            .with_parent("accidents", "case_id", "cases")
+            .with_parent("evidence", "case_id", "cases")
+            .with_parent("vehicles", "accident_id", "accidents")
+    }
+
+    /// Case data scoping for deployments hosting several organizations
+    ///
+    /// Like [`case_data`](Self::case_data), with cases confined to their
+    /// organization.
+    pub fn tenant_case_data() -> Self {
+        Self::case_data().with_tenant_column("cases", "organization")
+    }
+
+    /// Let a role access all rows
+    pub fn with_bypass_role(mut self, role: impl Into<String>) -> Self {
+        self.bypass_roles.push(role.into());
+        self
+    }
+
+    /// Grant rows whose owner columns hold the user ID
+    pub fn with_owner_columns(mut self, table: &str, columns: &[&str]) -> Self {
+        self.scope(table)
+            .owner_columns
+            .extend(columns.iter().map(|c| c.to_string()));
+        self
+    }
+
+    /// Grant rows whose organization column holds the user's organization
+    pub fn with_organization_column(mut self, table: &str, column: &str) -> Self {
+        self.scope(table).organization_column = Some(column.to_string());
+        self
+    }
+
+    /// Confine rows to those whose tenant column holds the user's organization
+    pub fn with_tenant_column(mut self, table: &str, column: &str) -> Self {
+        self.scope(table).tenant_column = Some(column.to_string());
+        self
+    }
+
+    /// Let a role access rows of all organizations
+    pub fn with_cross_tenant_role(mut self, role: impl Into<String>) -> Self {
+        self.cross_tenant_roles.push(role.into());
+        self
+    }
+
+    /// Tenant column of a table, if it is tenant-scoped itself
+    pub fn tenant_column(&self, table: &str) -> Option<&str> {
+        self.tables.get(table)?.tenant_column.as_deref()
+    }
+
+    /// Check if a table is tenant-scoped, directly or through its parents
+    pub fn is_tenant_scoped(&self, table: &str) -> bool {
+        !matches!(
+            self.tenant_filter(&AuthContext::default(), table, 0),
+            RowFilter::Unrestricted
+        )
+    }
+
+    /// Check if the caller may leave its organization
+    pub fn crosses_tenants(&self, context: &AuthContext) -> bool {
+        self.cross_tenant_roles.iter().any(|role| context.has_role(role))
+    }
+
+    /// Grant rows whose parent row, referenced by `column`, is accessible
+    pub fn with_parent(mut self, table: &str, column: &str, parent_table: &str) -> Self {
+        self.scope(table).parent = Some((column.to_string(), parent_table.to_string()));
+        self
+    }
+
+    /// Deny access to tables without rules
+    pub fn deny_unscoped(mut self) -> Self {
+        self.deny_unscoped = true;
+        self
+    }
+
+    fn scope(&mut self, table: &str) -> &mut TableScope {
+        self.tables.entry(table.to_string()).or_default()
+    }
+
+    /// Rows of `table` inside the user's organization
+    fn tenant_filter(&self, context: &AuthContext, table: &str, depth: usize) -> RowFilter {
+        let Some(scope) = self.tables.get(table) else {
+            return RowFilter::Unrestricted;
+        };
+
+        if let Some(column) = &scope.tenant_column {
+            return match &context.organization {
+                Some(organization) => {
+                    RowFilter::column_eq(&format!("{}.{}", table, column), organization)
+                },
+                None => RowFilter::Denied,
+            };
+        }
+
+        match &scope.parent {
+            Some((column, parent_table)) if depth < self.tables.len() => {
+                match self.tenant_filter(context, parent_table, depth + 1) {
+                    RowFilter::Predicate(predicate) => RowFilter::Predicate(format!(
+                        "{}.{} IN (SELECT id FROM {} WHERE {})",
+                        table, column, parent_table, predicate
+                    )),
+                    filter => filter,
+                }
+            },
+            _ => RowFilter::Unrestricted,
+        }
+    }
+
+    fn table_filter(
+        &self,
+        context: &AuthContext,
+        table: &str,
+        depth: usize,
+        cross_tenant: bool,
+    ) -> RowFilter {
+        let Some(scope) = self.tables.get(table) else {
+            return if self.deny_unscoped {
+                RowFilter::Denied
+            } else {
+                RowFilter::Unrestricted
+            };
+        };
+
+        let mut grants: Vec<RowFilter> = scope
+            .owner_columns
+            .iter()
+            .map(|column| RowFilter::column_eq(&format!("{}.{}", table, column), &context.user_id))
+            .collect();
+
+        if let (Some(column), Some(organization)) = (&scope.organization_column, &context.organization) {
+            grants.push(RowFilter::column_eq(&format!("{}.{}", table, column), organization));
+        }
+
+        // Parent chains are short, the depth limit only guards against cycles
+        if let Some((column, parent_table)) = &scope.parent {
+            if depth < self.tables.len() {
+                match self.table_filter(context, parent_table, depth + 1, cross_tenant) {
+                    RowFilter::Denied => {},
+                    parent_filter => grants.push(RowFilter::Predicate(format!(
+                        "{}.{} IN (SELECT id FROM {} WHERE {})",
+                        table,
+                        column,
+                        parent_table,
+                        parent_filter.to_sql()
+                    ))),
+                }
+            }
+        }
+
+        let granted = RowFilter::any(grants);
+        match &scope.tenant_column {
+            Some(_) if !cross_tenant => {
+                RowFilter::all(vec![self.tenant_filter(context, table, depth), granted])
+            },
+            _ => granted,
+        }
+    }
+}
+
+impl RowPolicy for ScopedRowPolicy {
+    fn row_filter(&self, context: &AuthContext, table: &str, _action: RowAction) -> DbResult<RowFilter> {
+        let cross_tenant = self.crosses_tenants(context);
+
+        if self.bypass_roles.iter().any(|role| context.has_role(role)) {
+            return Ok(if cross_tenant {
+                RowFilter::Unrestricted
+            } else {
+                self.tenant_filter(context, table, 0)
+            });
+        }
+
+        Ok(self.table_filter(context, table, 0, cross_tenant))
+    }
+}
+
+/// Quote a string as a SQL literal
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_row_filter_combinators() {
+        let owner = RowFilter::column_eq("cases.created_by", "o'brien");
+        assert_eq!(owner.to_sql(), "cases.created_by = 'o''brien'");
+
+        assert_eq!(RowFilter::any(vec![RowFilter::Denied, owner.clone()]), owner);
+        assert_eq!(
+            RowFilter::any(vec![owner.clone(), RowFilter::Unrestricted]),
+            RowFilter::Unrestricted
+        );
+        assert_eq!(RowFilter::all(vec![owner.clone(), RowFilter::Denied]), RowFilter::Denied);
+        assert_eq!(RowFilter::any(Vec::new()), RowFilter::Denied);
+        assert_eq!(RowFilter::column_in("cases.id", &[]), RowFilter::Denied);
+    }
+
+    #[test]
+    fn test_scoped_policy() {
+        let policy = ScopedRowPolicy::case_data().with_bypass_role("admin");
+        let context = AuthContext::new("u1").with_organization("org1");
+
+        let filter = policy.row_filter(&context, "cases", RowAction::Read).unwrap();
+        assert_eq!(
+            filter.to_sql(),
+            "(cases.created_by = 'u1' OR cases.assigned_to = 'u1' OR cases.organization = 'org1')"
+        );
+
+        let filter = policy.row_filter(&context, "vehicles", RowAction::Read).unwrap();
+        assert!(filter
+            .to_sql()
+            .starts_with("vehicles.accident_id IN (SELECT id FROM accidents WHERE accidents.case_id IN"));
+
+        let filter = policy.row_filter(&context, "users", RowAction::Read).unwrap();
+        assert_eq!(filter, RowFilter::Unrestricted);
+
+        let admin = AuthContext::new("u2").with_roles(vec!["admin".to_string()]);
+        let filter = policy.row_filter(&admin, "cases", RowAction::Delete).unwrap();
+        assert_eq!(filter, RowFilter::Unrestricted);
+
+        let policy = ScopedRowPolicy::new().deny_unscoped();
+        let filter = policy.row_filter(&context, "users", RowAction::Read).unwrap();
+        assert_eq!(filter, RowFilter::Denied);
+    }
+
+    #[test]
+    fn test_tenant_scoping() {
+        let policy = ScopedRowPolicy::tenant_case_data()
+            .with_bypass_role("admin")
+            .with_cross_tenant_role("super_admin");
+        assert_eq!(policy.tenant_column("cases"), Some("organization"));
+        assert!(policy.is_tenant_scoped("vehicles"));
+        assert!(!policy.is_tenant_scoped("users"));
+
+        let context = AuthContext::new("u1").with_organization("org1");
+        let filter = policy.row_filter(&context, "cases", RowAction::Read).unwrap();
+        assert_eq!(
+            filter.to_sql(),
+            "(cases.organization = 'org1' AND (cases.created_by = 'u1' OR cases.assigned_to = 'u1' \
+             OR cases.organization = 'org1'))"
+        );
+
+        let admin = AuthContext::new("u2")
+            .with_roles(vec!["admin".to_string()])
+            .with_organization("org1");
+        let filter = policy.row_filter(&admin, "evidence", RowAction::Read).unwrap();
+        assert_eq!(
+            filter.to_sql(),
+            "evidence.case_id IN (SELECT id FROM cases WHERE cases.organization = 'org1')"
+        );
+
+        let orphan = AuthContext::new("u3").with_roles(vec!["admin".to_string()]);
+        let filter = policy.row_filter(&orphan, "cases", RowAction::Read).unwrap();
+        assert_eq!(filter, RowFilter::Denied);
+
+        let operator = AuthContext::new("u4").with_roles(vec!["super_admin".to_string()]);
+        let filter = policy.row_filter(&operator, "vehicles", RowAction::Read).unwrap();
+        assert_eq!(filter, RowFilter::Predicate(
+            "vehicles.accident_id IN (SELECT id FROM accidents WHERE accidents.case_id IN \
+             (SELECT id FROM cases WHERE (cases.created_by = 'u4' OR cases.assigned_to = 'u4')))"
+                .to_string()
+        ));
+        assert!(policy.crosses_tenants(&operator));
+    }
+}
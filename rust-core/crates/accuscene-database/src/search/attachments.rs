@@ -0,0 +1,470 @@
+//! Attachment content indexing
+//!
+//! Indexes text extracted from evidence attachments so that the contents of
+//! reports, statements and scanned documents can be searched alongside the
+//! structured evidence fields. Text extraction (PDF text layers, OCR) is done
+//! by the caller; this module normalizes the text, stores it in the
+//! `evidence_attachment_text` table and queries it through the
+//! `evidence_attachment_fts` FTS5 index.
+
+use super::{SearchManager, SearchOptions};
+use crate::error::{DatabaseError, DbResult};
+use crate::repositories::evidence::Evidence;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// Maximum size of the text indexed for a single attachment page
+pub const MAX_ATTACHMENT_TEXT_LEN: usize = 16 * 1024 * 1024;
+
+/// How the attachment text was obtained
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExtractionSource {
+    /// Plain text or a document with a native text format
+    Text,
+    /// Text layer of a PDF document
+    Pdf,
+    /// Optical character recognition of an image or scan
+    Ocr,
+}
+
+impl ExtractionSource {
+    /// Database representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExtractionSource::Text => "text",
+            ExtractionSource::Pdf => "pdf",
+            ExtractionSource::Ocr => "ocr",
+        }
+    }
+
+    fn from_db(value: &str) -> Self {
+        match value {
+            "pdf" => ExtractionSource::Pdf,
+            "ocr" => ExtractionSource::Ocr,
+            _ => ExtractionSource::Text,
+        }
+    }
+}
+
+/// Extracted text of an evidence attachment, ready for indexing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentText {
+    /// Evidence the attachment belongs to
+    pub evidence_id: String,
+    /// Attachment file name
+    pub file_name: String,
+    /// Attachment MIME type
+    pub mime_type: Option<String>,
+    /// Page number for per-page extraction
+    pub page: Option<u32>,
+    /// How the text was obtained
+    pub source: ExtractionSource,
+    /// Extracted text
+    pub content: String,
+}
+
+impl AttachmentText {
+    /// Create attachment text for a whole file
+    pub fn new(
+        evidence_id: impl Into<String>,
+        file_name: impl Into<String>,
+        content: impl Into<String>,
+    ) -> Self {
+        Self {
+            evidence_id: evidence_id.into(),
+            file_name: file_name.into(),
+            mime_type: None,
+            page: None,
+            source: ExtractionSource::Text,
+            content: content.into(),
+        }
+    }
+
+    /// Set the MIME type
+    pub fn with_mime_type(mut self, mime_type: impl Into<String>) -> Self {
+        self.mime_type = Some(mime_type.into());
+        self
+    }
+
+    /// Set the page number
+    pub fn with_page(mut self, page: u32) -> Self {
+        self.page = Some(page);
+        self
+    }
+
+    /// Set the extraction source
+    pub fn with_source(mut self, source: ExtractionSource) -> Self {
+        self.source = source;
+        self
+    }
+}
+
+/// Attachment search match joined with its evidence record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentMatch {
+    /// Attachment text record ID
+    pub attachment_id: String,
+    /// Attachment file name
+    pub file_name: String,
+    /// Matching page, if indexed per page
+    pub page: Option<u32>,
+    /// How the text was obtained
+    pub source: ExtractionSource,
+    /// Relevance rank (lower is better)
+    pub rank: f64,
+    /// Snippet with highlighted matches
+    pub snippet: Option<String>,
+    /// Evidence the attachment belongs to
+    pub evidence: Evidence,
+}
+
+impl SearchManager {
+    /// Index extracted attachment text
+    ///
+    /// Re-indexing the same evidence, file and page replaces the previous
+    /// text. Returns the attachment text record ID.
+    pub fn index_attachment(&self, conn: &Connection, text: &AttachmentText) -> DbResult<String> {
+        let content = normalize_extracted_text(&text.content);
+        if content.is_empty() {
+            return Err(DatabaseError::InvalidData(format!(
+                "No text extracted from attachment '{}'",
+                text.file_name
+            )));
+        }
+        if content.len() > MAX_ATTACHMENT_TEXT_LEN {
+            return Err(DatabaseError::InvalidData(format!(
+                "Attachment text for '{}' exceeds {} bytes",
+                text.file_name, MAX_ATTACHMENT_TEXT_LEN
+            )));
+        }
+
+        let evidence_exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM evidence WHERE id = ?)",
+            [&text.evidence_id],
+            |row| row.get(0),
+        )?;
+        if !evidence_exists {
+            return Err(DatabaseError::not_found("Evidence", "id", &text.evidence_id));
+        }
+
+        let existing: Option<String> = conn
+            .query_row(
+                "SELECT id FROM evidence_attachment_text
+                 WHERE evidence_id = ? AND file_name = ? AND page IS ?",
+                params![text.evidence_id, text.file_name, text.page],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        match existing {
+            Some(id) => {
+                conn.execute(
+                    "UPDATE evidence_attachment_text
+                     SET mime_type = ?, source = ?, content = ?, indexed_at = datetime('now')
+                     WHERE id = ?",
+                    params![text.mime_type, text.source.as_str(), content, id],
+                )?;
+                Ok(id)
+            }
+            None => {
+                let id = uuid::Uuid::new_v4().to_string();
+                conn.execute(
+                    "INSERT INTO evidence_attachment_text
+                     (id, evidence_id, file_name, mime_type, page, source, content)
+                     VALUES (?, ?, ?, ?, ?, ?, ?)",
+                    params![
+                        id,
+                        text.evidence_id,
+                        text.file_name,
+                        text.mime_type,
+                        text.page,
+                        text.source.as_str(),
+                        content
+                    ],
+                )?;
+                Ok(id)
+            }
+        }
+    }
+
+    /// Index a batch of extracted attachment texts atomically
+    pub fn index_attachments(
+        &self,
+        conn: &Connection,
+        texts: &[AttachmentText],
+    ) -> DbResult<Vec<String>> {
+        let tx = conn.unchecked_transaction()?;
+        let ids = texts
+            .iter()
+            .map(|text| self.index_attachment(&tx, text))
+            .collect::<DbResult<Vec<_>>>()?;
+        tx.commit()?;
+        Ok(ids)
+    }
+
+    /// Remove indexed attachment text for an evidence record
+    ///
+    /// Removes all attachments when `file_name` is `None`. Returns the number
+    /// of removed records.
+    pub fn remove_attachment_text(
+        &self,
+        conn: &Connection,
+        evidence_id: &str,
+        file_name: Option<&str>,
+    ) -> DbResult<usize> {
+        let removed = match file_name {
+            Some(file_name) => conn.execute(
+                "DELETE FROM evidence_attachment_text WHERE evidence_id = ? AND file_name = ?",
+                params![evidence_id, file_name],
+            )?,
+            None => conn.execute(
+                "DELETE FROM evidence_attachment_text WHERE evidence_id = ?",
+                [evidence_id],
+            )?,
+        };
+        Ok(removed)
+    }
+
+    /// Search attachment contents
+    pub fn search_attachments(
+        &self,
+        conn: &Connection,
+        query: &str,
+        options: &SearchOptions,
+    ) -> DbResult<Vec<AttachmentMatch>> {
+        self.search_attachment_fts(conn, query, None, options)
+    }
+
+    /// Search attachment contents within a case
+    pub fn search_case_attachments(
+        &self,
+        conn: &Connection,
+        case_id: &str,
+        query: &str,
+        options: &SearchOptions,
+    ) -> DbResult<Vec<AttachmentMatch>> {
+        self.search_attachment_fts(conn, query, Some(case_id), options)
+    }
+
+    fn search_attachment_fts(
+        &self,
+        conn: &Connection,
+        query: &str,
+        case_id: Option<&str>,
+        options: &SearchOptions,
+    ) -> DbResult<Vec<AttachmentMatch>> {
+        let sanitized_query = self.sanitize_fts_query(query);
+        if sanitized_query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let snippet = if options.include_snippets {
+            format!(
+                "snippet(evidence_attachment_fts, 1, '<mark>', '</mark>', '...', {})",
+                options.snippet_context.clamp(1, 64)
+            )
+        } else {
+            "NULL".to_string()
+        };
+
+        let sql = format!(
+            "SELECT e.id, e.case_id, e.accident_id, e.evidence_type, e.title, e.description,
+                    e.file_path, e.file_name, e.file_size, e.file_mime_type, e.file_hash,
+                    e.collected_by, e.collected_at, e.location, e.chain_of_custody, e.tags,
+                    e.metadata, e.is_verified, e.created_at, e.updated_at, e.version,
+                    a.id, a.file_name, a.page, a.source, evidence_attachment_fts.rank, {}
+             FROM evidence_attachment_fts
+             JOIN evidence_attachment_text a ON a.rowid = evidence_attachment_fts.rowid
+             JOIN evidence e ON e.id = a.evidence_id
+             WHERE evidence_attachment_fts MATCH ?1 AND e.deleted_at IS NULL
+               AND (?2 IS NULL OR e.case_id = ?2)
+             ORDER BY evidence_attachment_fts.rank
+             LIMIT ?3 OFFSET ?4",
+            snippet
+        );
+
+        let mut stmt = conn.prepare(&sql).map_err(|e| {
+            DatabaseError::SearchError(format!("Failed to prepare attachment search: {}", e))
+        })?;
+
+        let results = stmt
+            .query_map(
+                params![
+                    sanitized_query,
+                    case_id,
+                    options.limit as i64,
+                    options.offset as i64
+                ],
+                |row| {
+                    let source: String = row.get(24)?;
+                    Ok(AttachmentMatch {
+                        evidence: Evidence::from_row(row)?,
+                        attachment_id: row.get(21)?,
+                        file_name: row.get(22)?,
+                        page: row.get(23)?,
+                        source: ExtractionSource::from_db(&source),
+                        rank: row.get(25)?,
+                        snippet: row.get(26)?,
+                    })
+                },
+            )
+            .map_err(|e| DatabaseError::SearchError(format!("Attachment search failed: {}", e)))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| DatabaseError::SearchError(format!("Failed to collect results: {}", e)))?;
+
+        Ok(results)
+    }
+}
+
+/// Normalize extracted text before indexing
+///
+/// Drops control characters left behind by PDF and OCR extraction, collapses
+/// runs of horizontal whitespace and limits consecutive blank lines.
+fn normalize_extracted_text(text: &str) -> String {
+    let mut normalized = String::with_capacity(text.len());
+    let mut blank_lines = 0;
+
+    for line in text.lines() {
+        let words: Vec<&str> = line
+            .split(|c: char| c.is_whitespace() || (c.is_control() && c != '\n'))
+            .filter(|word| !word.is_empty())
+            .collect();
+
+        if words.is_empty() {
+            blank_lines += 1;
+            continue;
+        }
+
+        if !normalized.is_empty() {
+            normalized.push_str(if blank_lines > 0 { "\n\n" } else { "\n" });
+        }
+        normalized.push_str(&words.join(" "));
+        blank_lines = 0;
+    }
+
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::v001_initial::InitialMigration;
+    use crate::migrations::v006_attachment_text::AttachmentTextMigration;
+    use crate::migrations::v007_soft_delete::SoftDeleteMigration;
+    use crate::migrations::v008_row_versioning::RowVersioningMigration;
+    use crate::migrations::Migration;
+
+    fn setup_db() -> Connection {
+        let mut conn = Connection::open_in_memory().unwrap();
+        InitialMigration.up(&mut conn).unwrap();
+        AttachmentTextMigration.up(&mut conn).unwrap();
+        SoftDeleteMigration.up(&mut conn).unwrap();
+        RowVersioningMigration.up(&mut conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO users (id, email, username, full_name, password_hash)
+             VALUES ('u1', 'analyst@example.com', 'analyst', 'Analyst', 'hash');
+             INSERT INTO cases (id, case_number, title, status, created_by)
+             VALUES ('c1', 'CASE-1', 'Intersection collision', 'open', 'u1');
+             INSERT INTO cases (id, case_number, title, status, created_by)
+             VALUES ('c2', 'CASE-2', 'Highway collision', 'open', 'u1');
+             INSERT INTO evidence (id, case_id, evidence_type, title)
+             VALUES ('e1', 'c1', 'document', 'Police report');
+             INSERT INTO evidence (id, case_id, evidence_type, title)
+             VALUES ('e2', 'c2', 'document', 'Witness statement');",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_index_and_search_attachments() {
+        let conn = setup_db();
+        let manager = SearchManager::new();
+
+        let report = AttachmentText::new(
+            "e1",
+            "report.pdf",
+            "The driver reported\tbrake failure\u{0}  before the intersection.",
+        )
+        .with_source(ExtractionSource::Pdf)
+        .with_page(2);
+        manager.index_attachment(&conn, &report).unwrap();
+
+        let statement = AttachmentText::new("e2", "statement.png", "Witness saw brake lights")
+            .with_source(ExtractionSource::Ocr);
+        manager.index_attachment(&conn, &statement).unwrap();
+
+        let options = SearchOptions::default();
+        let results = manager.search_attachments(&conn, "brake", &options).unwrap();
+        assert_eq!(results.len(), 2);
+
+        let matched = manager.search_attachments(&conn, "failure", &options).unwrap();
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].evidence.id, "e1");
+        assert_eq!(matched[0].evidence.title, "Police report");
+        assert_eq!(matched[0].page, Some(2));
+        assert_eq!(matched[0].source, ExtractionSource::Pdf);
+        assert!(matched[0].snippet.as_deref().unwrap().contains("<mark>failure</mark>"));
+
+        let in_case = manager
+            .search_case_attachments(&conn, "c2", "brake", &options)
+            .unwrap();
+        assert_eq!(in_case.len(), 1);
+        assert_eq!(in_case[0].file_name, "statement.png");
+
+        conn.execute("UPDATE evidence SET deleted_at = datetime('now') WHERE id = 'e2'", [])
+            .unwrap();
+        let results = manager.search_attachments(&conn, "brake", &options).unwrap();
+        assert_eq!(results.len(), 1);
+    }
+
+    #[test]
+    fn test_reindex_replaces_text() {
+        let conn = setup_db();
+        let manager = SearchManager::new();
+        let options = SearchOptions::default();
+
+        let first = manager
+            .index_attachment(&conn, &AttachmentText::new("e1", "report.pdf", "skid marks"))
+            .unwrap();
+        let second = manager
+            .index_attachment(&conn, &AttachmentText::new("e1", "report.pdf", "debris field"))
+            .unwrap();
+        assert_eq!(first, second);
+
+        assert!(manager.search_attachments(&conn, "skid", &options).unwrap().is_empty());
+        assert_eq!(manager.search_attachments(&conn, "debris", &options).unwrap().len(), 1);
+
+        assert_eq!(manager.remove_attachment_text(&conn, "e1", None).unwrap(), 1);
+        assert!(manager.search_attachments(&conn, "debris", &options).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_index_attachment_rejects_invalid_input() {
+        let conn = setup_db();
+        let manager = SearchManager::new();
+
+        let empty = AttachmentText::new("e1", "blank.png", " \u{0}\n\t ");
+        assert!(manager.index_attachment(&conn, &empty).is_err());
+
+        let orphan = AttachmentText::new("missing", "report.pdf", "text");
+        assert!(manager.index_attachment(&conn, &orphan).is_err());
+
+        let batch = vec![
+            AttachmentText::new("e1", "a.pdf", "first page"),
+            AttachmentText::new("missing", "b.pdf", "second page"),
+        ];
+        assert!(manager.index_attachments(&conn, &batch).is_err());
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM evidence_attachment_text", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_normalize_extracted_text() {
+        let text = "Line  one\r\n\u{c}\n\n\nLine\u{0}two \t end";
+        assert_eq!(normalize_extracted_text(text), "Line one\n\nLine two end");
+    }
+}
@@ -1,7 +1,12 @@
 //! Full-text search with FTS5
 //!
 //! Provides high-performance full-text search capabilities using SQLite FTS5,
-//! with support for ranked results, snippets, and highlighting.
+//! with support for ranked results, snippets, and highlighting. Text extracted
+//! from evidence attachments is indexed separately, see [`attachments`].
+
+pub mod attachments;
+
+pub use self::attachments::{AttachmentMatch, AttachmentText, ExtractionSource};
 
 use crate::error::{DatabaseError, DbResult};
 use rusqlite::{params, Connection, Row};
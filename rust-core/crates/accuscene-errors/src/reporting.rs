@@ -172,7 +172,7 @@ impl ErrorReporter {
     pub fn suggest_actions(error: &AccuSceneError) -> Vec<String> {
         use crate::ErrorCode;
 
-        let mut actions = Vec::new();
+        let mut actions = error.suggested_actions().to_vec();
 
         match error.code() {
             ErrorCode::Validation => {
@@ -217,6 +217,11 @@ impl ErrorReporter {
                 actions.push("Include the error ID in your report".to_string());
                 actions.push("Check system logs for more details".to_string());
             }
+            ErrorCode::Physics => {
+                actions.push("Reduce the simulation time step".to_string());
+                actions.push("Check body masses, positions and initial velocities for unrealistic values".to_string());
+                actions.push("Review solver iteration and tolerance settings".to_string());
+            }
             _ => {
                 actions.push("Review error details and context".to_string());
                 actions.push("Check application logs for more information".to_string());
@@ -318,4 +323,14 @@ mod tests {
         assert!(!actions.is_empty());
         assert!(actions.iter().any(|a| a.contains("credentials")));
     }
+
+    #[test]
+    fn test_error_specific_actions_come_first() {
+        let error = AccuSceneError::physics("Solver diverged")
+            .with_suggested_action("Reduce the time step to 0.005 s");
+        let actions = ErrorReporter::suggest_actions(&error);
+
+        assert_eq!(actions[0], "Reduce the time step to 0.005 s");
+        assert!(actions.len() > 1);
+    }
 }
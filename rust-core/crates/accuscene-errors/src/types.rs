@@ -49,6 +49,10 @@ pub struct AccuSceneError {
 
     /// Whether this error can be recovered from
     recoverable: bool,
+
+    /// Error-specific suggested actions, most relevant first
+    #[serde(default)]
+    suggested_actions: Vec<String>,
 }
 
 impl AccuSceneError {
@@ -67,6 +71,7 @@ impl AccuSceneError {
             location: None,
             metadata: std::collections::HashMap::new(),
             recoverable: code.is_recoverable(),
+            suggested_actions: Vec::new(),
         }
     }
 
@@ -120,6 +125,16 @@ impl AccuSceneError {
         Self::new(ErrorCode::RateLimit, message)
     }
 
+    /// Creates a service unavailable error
+    pub fn unavailable(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Unavailable, message)
+    }
+
+    /// Creates a physics simulation error
+    pub fn physics(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Physics, message)
+    }
+
     /// Adds detailed description to the error
     pub fn with_details(mut self, details: impl Into<String>) -> Self {
         self.details = Some(details.into());
@@ -148,6 +163,14 @@ impl AccuSceneError {
         self
     }
 
+    /// Adds an action suggested for resolving this specific error
+    ///
+    /// These are reported ahead of the generic actions for the error code.
+    pub fn with_suggested_action(mut self, action: impl Into<String>) -> Self {
+        self.suggested_actions.push(action.into());
+        self
+    }
+
     /// Sets the severity of the error
     pub fn with_severity(mut self, severity: ErrorSeverity) -> Self {
         self.severity = severity;
@@ -212,6 +235,11 @@ impl AccuSceneError {
         self.recoverable
     }
 
+    /// Returns the error-specific suggested actions
+    pub fn suggested_actions(&self) -> &[String] {
+        &self.suggested_actions
+    }
+
     /// Returns a formatted error report
     pub fn report(&self) -> String {
         use crate::reporting::ErrorReporter;
@@ -0,0 +1,82 @@
+//! Entity change events captured from the relational database.
+//!
+//! Every repository mutation is recorded by the database change capture and
+//! republished as an [`EntityChanged`] event, so projections and notification
+//! handlers can react to writes that do not go through an aggregate.
+
+use crate::event::Event;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Kind of entity change.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// Entity created.
+    Created,
+
+    /// Entity fields updated.
+    Updated,
+
+    /// Entity moved to the trash.
+    Trashed,
+
+    /// Entity restored from the trash.
+    Restored,
+
+    /// Entity permanently deleted.
+    Deleted,
+}
+
+/// Entity changed event.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EntityChanged {
+    /// Entity type (Case, Accident, Vehicle, Evidence).
+    pub entity_type: String,
+
+    /// Entity identifier.
+    pub entity_id: String,
+
+    /// Kind of change.
+    pub change: ChangeKind,
+
+    /// Fields whose value changed.
+    pub changed_fields: Vec<String>,
+
+    /// Entity state before the change.
+    pub before: Option<serde_json::Value>,
+
+    /// Entity state after the change.
+    pub after: Option<serde_json::Value>,
+
+    /// Position of the change in the database changelog.
+    pub change_sequence: u64,
+
+    /// Timestamp when the change was captured.
+    pub changed_at: DateTime<Utc>,
+}
+
+impl Event for EntityChanged {
+    fn event_type(&self) -> &'static str {
+        match self.change {
+            ChangeKind::Created => "EntityCreated",
+            ChangeKind::Updated => "EntityUpdated",
+            ChangeKind::Trashed => "EntityTrashed",
+            ChangeKind::Restored => "EntityRestored",
+            ChangeKind::Deleted => "EntityDeleted",
+        }
+    }
+
+    fn aggregate_id(&self) -> &str {
+        &self.entity_id
+    }
+
+    fn aggregate_type(&self) -> &'static str {
+        match self.entity_type.as_str() {
+            "Case" => "Case",
+            "Accident" => "Accident",
+            "Vehicle" => "Vehicle",
+            "Evidence" => "Evidence",
+            _ => "Entity",
+        }
+    }
+}
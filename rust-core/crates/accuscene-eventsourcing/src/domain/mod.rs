@@ -1,11 +1,13 @@
 //! Domain-specific events for accident reconstruction.
 
 pub mod case_events;
+pub mod change_events;
 pub mod report_events;
 pub mod scene_events;
 pub mod simulation_events;
 
 pub use case_events::*;
+pub use change_events::*;
 pub use report_events::*;
 pub use scene_events::*;
 pub use simulation_events::*;
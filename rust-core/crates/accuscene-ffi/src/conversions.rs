@@ -4,6 +4,8 @@
 //! the FFI boundary with proper type safety.
 
 use accuscene_core::types::*;
+use accuscene_physics_v3::interpolation::BodyTransform;
+use accuscene_physics_v3::prelude::{Quaternion, UnitQuaternion, Vector3};
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 use serde::{Deserialize, Serialize};
@@ -69,6 +71,81 @@ impl From<JsVector3D> for Vector3D {
     }
 }
 
+impl From<Vector3<f64>> for JsVector3D {
+    fn from(v: Vector3<f64>) -> Self {
+        Self { x: v.x, y: v.y, z: v.z }
+    }
+}
+
+impl From<JsVector3D> for Vector3<f64> {
+    fn from(v: JsVector3D) -> Self {
+        Self::new(v.x, v.y, v.z)
+    }
+}
+
+/// JavaScript-compatible quaternion
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct JsQuaternion {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub w: f64,
+}
+
+impl From<UnitQuaternion<f64>> for JsQuaternion {
+    fn from(q: UnitQuaternion<f64>) -> Self {
+        Self {
+            x: q.i,
+            y: q.j,
+            z: q.k,
+            w: q.w,
+        }
+    }
+}
+
+impl From<JsQuaternion> for UnitQuaternion<f64> {
+    fn from(q: JsQuaternion) -> Self {
+        // Normalizes, so slightly drifted quaternions from JS are accepted
+        Self::from_quaternion(Quaternion::new(q.w, q.x, q.y, q.z))
+    }
+}
+
+/// JavaScript-compatible rigid body transform
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct JsBodyTransform {
+    pub id: u32,
+    pub position: JsVector3D,
+    pub orientation: JsQuaternion,
+    pub linear_velocity: JsVector3D,
+    pub angular_velocity: JsVector3D,
+}
+
+impl From<BodyTransform> for JsBodyTransform {
+    fn from(t: BodyTransform) -> Self {
+        Self {
+            id: t.id as u32,
+            position: t.position.into(),
+            orientation: t.orientation.into(),
+            linear_velocity: t.linear_velocity.into(),
+            angular_velocity: t.angular_velocity.into(),
+        }
+    }
+}
+
+impl From<JsBodyTransform> for BodyTransform {
+    fn from(t: JsBodyTransform) -> Self {
+        Self {
+            id: t.id as usize,
+            position: t.position.into(),
+            orientation: t.orientation.into(),
+            linear_velocity: t.linear_velocity.into(),
+            angular_velocity: t.angular_velocity.into(),
+        }
+    }
+}
+
 /// JavaScript-compatible vehicle metadata
 #[napi(object)]
 #[derive(Debug, Clone)]
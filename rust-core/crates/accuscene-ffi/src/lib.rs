@@ -26,6 +26,7 @@ mod error;
 
 use accuscene_core::prelude::*;
 use accuscene_core::utils;
+use accuscene_physics_v3::interpolation::{interpolate_transforms, BodyTransform};
 use conversions::*;
 use error::to_ffi_result;
 use napi::bindgen_prelude::*;
@@ -434,6 +435,31 @@ pub fn evidence_summary(evidence_json: String) -> NapiResult<String> {
     to_json_string(&summary)
 }
 
+// ============================================================================
+// Render Interpolation
+// ============================================================================
+
+/// Blend body transforms between two physics steps for rendering
+///
+/// `alpha` is the fraction of a step elapsed since the latest step: 0 gives
+/// `previous`, 1 gives `current`, values above 1 extrapolate from the
+/// current velocities.
+#[napi]
+pub fn interpolate_body_transforms(
+    previous: Vec<JsBodyTransform>,
+    current: Vec<JsBodyTransform>,
+    alpha: f64,
+    dt: f64,
+) -> Vec<JsBodyTransform> {
+    let previous: Vec<BodyTransform> = previous.into_iter().map(Into::into).collect();
+    let current: Vec<BodyTransform> = current.into_iter().map(Into::into).collect();
+
+    interpolate_transforms(&previous, &current, alpha, dt)
+        .into_iter()
+        .map(Into::into)
+        .collect()
+}
+
 // ============================================================================
 // Configuration Operations
 // ============================================================================
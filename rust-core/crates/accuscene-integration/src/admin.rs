@@ -0,0 +1,781 @@
+//! User and role administration
+//!
+//! This module combines the user repository from the database layer with the
+//! security framework to provide the administrative management surface:
+//! creating and disabling users, assigning roles, resetting MFA, listing
+//! active sessions and forcing password resets. It also manages memberships
+//! in the organization hierarchy and propagates them into authorization
+//! scopes. Every action is gated by a permission check and recorded in the
+//! audit trail.
+
+use accuscene_core::types::organization::{MemberRole, Membership, OrgScope, OwnedResourceKind};
+use accuscene_database::repositories::Repository;
+use accuscene_database::{DatabaseError, DatabasePool, OrganizationRepository, User, UserRepository};
+use accuscene_security::audit::{AuditEvent, EventResult, EventSeverity, EventType, ResourceInfo};
+use accuscene_security::auth::Session;
+use accuscene_security::authz::{AttributeValue, PolicyRequest};
+use accuscene_security::{AuthContext, SecurityError, SecurityService};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Permission required for read-only admin actions
+pub const PERMISSION_USERS_READ: &str = "users:read";
+
+/// Permission required for mutating admin actions
+pub const PERMISSION_USERS_WRITE: &str = "users:write";
+
+/// Permission required to view organization memberships
+pub const PERMISSION_ORGS_READ: &str = "organizations:read";
+
+/// Permission required to manage organization memberships
+pub const PERMISSION_ORGS_WRITE: &str = "organizations:write";
+
+/// Admin error
+#[derive(Debug, thiserror::Error)]
+pub enum AdminError {
+    /// Actor lacks the required permission
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
+    /// User not found
+    #[error("User not found: {0}")]
+    UserNotFound(String),
+
+    /// Malformed request parameter
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+
+    /// Database error
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+
+    /// Security error
+    #[error("Security error: {0}")]
+    Security(#[from] SecurityError),
+
+    /// Serialization error
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+/// Admin result type
+pub type AdminResult<T> = Result<T, AdminError>;
+
+/// Request to create a new user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewUser {
+    /// Email address
+    pub email: String,
+    /// Username
+    pub username: String,
+    /// Full name
+    pub full_name: String,
+    /// Initial password
+    pub password: String,
+    /// Role ID
+    pub role: String,
+    /// Organization
+    pub organization: Option<String>,
+    /// Phone number
+    pub phone: Option<String>,
+}
+
+/// Administrative request, as received from the API server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum AdminRequest {
+    /// List users
+    ListUsers {
+        /// Only include active users
+        #[serde(default)]
+        active_only: bool,
+    },
+    /// Create a user
+    CreateUser(NewUser),
+    /// Disable a user
+    DisableUser {
+        /// User ID
+        user_id: String,
+    },
+    /// Re-enable a user
+    EnableUser {
+        /// User ID
+        user_id: String,
+    },
+    /// Assign a role to a user
+    AssignRole {
+        /// User ID
+        user_id: String,
+        /// Role ID
+        role: String,
+    },
+    /// Reset a user's MFA enrollment
+    ResetMfa {
+        /// User ID
+        user_id: String,
+    },
+    /// List a user's active sessions
+    ListSessions {
+        /// User ID
+        user_id: String,
+    },
+    /// Force a password reset at next login
+    ForcePasswordReset {
+        /// User ID
+        user_id: String,
+    },
+    /// Add a member to an organization, team or workspace
+    AddMember {
+        /// User ID
+        user_id: String,
+        /// Scope string, e.g. `team:<id>`
+        scope: String,
+        /// Member role, e.g. `viewer` or `admin`
+        role: String,
+    },
+    /// Remove a member from a scope
+    RemoveMember {
+        /// User ID
+        user_id: String,
+        /// Scope string
+        scope: String,
+    },
+    /// List direct members of a scope
+    ListMembers {
+        /// Scope string
+        scope: String,
+    },
+    /// List the authorization scopes a user holds
+    UserScopes {
+        /// User ID
+        user_id: String,
+    },
+}
+
+/// User and role administration service
+pub struct AdminService {
+    pool: Arc<DatabasePool>,
+    security: Arc<SecurityService>,
+    users: UserRepository,
+    organizations: OrganizationRepository,
+}
+
+impl AdminService {
+    /// Create a new admin service
+    pub fn new(pool: Arc<DatabasePool>, security: Arc<SecurityService>) -> Self {
+        Self {
+            pool,
+            security,
+            users: UserRepository::new(),
+            organizations: OrganizationRepository::new(),
+        }
+    }
+
+    /// Dispatch an administrative request and return a JSON response
+    pub async fn handle(
+        &self,
+        actor: &AuthContext,
+        request: AdminRequest,
+    ) -> AdminResult<serde_json::Value> {
+        let response = match request {
+            AdminRequest::ListUsers { active_only } => {
+                serde_json::to_value(self.list_users(actor, active_only).await?)?
+            }
+            AdminRequest::CreateUser(new_user) => {
+                serde_json::to_value(self.create_user(actor, new_user).await?)?
+            }
+            AdminRequest::DisableUser { user_id } => {
+                self.disable_user(actor, &user_id).await?;
+                serde_json::Value::Null
+            }
+            AdminRequest::EnableUser { user_id } => {
+                self.enable_user(actor, &user_id).await?;
+                serde_json::Value::Null
+            }
+            AdminRequest::AssignRole { user_id, role } => {
+                self.assign_role(actor, &user_id, &role).await?;
+                serde_json::Value::Null
+            }
+            AdminRequest::ResetMfa { user_id } => {
+                self.reset_mfa(actor, &user_id).await?;
+                serde_json::Value::Null
+            }
+            AdminRequest::ListSessions { user_id } => {
+                serde_json::to_value(self.list_sessions(actor, &user_id).await?)?
+            }
+            AdminRequest::ForcePasswordReset { user_id } => {
+                self.force_password_reset(actor, &user_id).await?;
+                serde_json::Value::Null
+            }
+            AdminRequest::AddMember { user_id, scope, role } => {
+                let scope = Self::parse_scope(&scope)?;
+                let role = MemberRole::parse(&role)
+                    .ok_or_else(|| AdminError::InvalidRequest(format!("unknown role: {}", role)))?;
+                serde_json::to_value(self.add_member(actor, &user_id, scope, role).await?)?
+            }
+            AdminRequest::RemoveMember { user_id, scope } => {
+                self.remove_member(actor, &user_id, &Self::parse_scope(&scope)?)
+                    .await?;
+                serde_json::Value::Null
+            }
+            AdminRequest::ListMembers { scope } => {
+                serde_json::to_value(self.list_members(actor, &Self::parse_scope(&scope)?).await?)?
+            }
+            AdminRequest::UserScopes { user_id } => {
+                serde_json::to_value(self.user_scopes(actor, &user_id).await?)?
+            }
+        };
+
+        Ok(response)
+    }
+
+    /// Authenticate a bearer token and dispatch a request on its behalf
+    ///
+    /// This is the entry point for the API server, which forwards the
+    /// caller's token instead of a resolved [`AuthContext`].
+    pub async fn handle_authenticated(
+        &self,
+        token: &str,
+        request: AdminRequest,
+    ) -> AdminResult<serde_json::Value> {
+        let actor = self
+            .security
+            .auth()
+            .write()
+            .await
+            .validate_request(token)
+            .await?;
+        self.handle(&actor, request).await
+    }
+
+    /// List users
+    pub async fn list_users(&self, actor: &AuthContext, active_only: bool) -> AdminResult<Vec<User>> {
+        self.require(actor, PERMISSION_USERS_READ).await?;
+
+        let users = self.pool.with_connection(|conn| {
+            if active_only {
+                self.users.find_active(conn)
+            } else {
+                self.users.find_all(conn)
+            }
+        })?;
+
+        let resource = ResourceInfo::new("user", "*");
+        self.audit_resource(actor, EventType::DataRead, "user.list", resource)
+            .await;
+        Ok(users)
+    }
+
+    /// Create a user
+    pub async fn create_user(&self, actor: &AuthContext, new_user: NewUser) -> AdminResult<User> {
+        self.require(actor, PERMISSION_USERS_WRITE).await?;
+
+        let password_hash = {
+            let auth = self.security.auth();
+            let auth = auth.read().await;
+            auth.password_service().validate_password(&new_user.password)?;
+            auth.password_service().hash_password(&new_user.password)?
+        };
+
+        // Make sure the role exists before persisting anything
+        self.security.authz().read().await.rbac().get_role(&new_user.role)?;
+
+        let now = chrono::Utc::now().to_rfc3339();
+        let user = User {
+            id: uuid::Uuid::new_v4().to_string(),
+            email: new_user.email,
+            username: new_user.username,
+            full_name: new_user.full_name,
+            password_hash,
+            role: new_user.role,
+            organization: new_user.organization,
+            phone: new_user.phone,
+            is_active: true,
+            last_login_at: None,
+            created_at: now.clone(),
+            updated_at: now,
+        };
+
+        self.pool.with_connection(|conn| self.users.create(conn, &user))?;
+        self.security
+            .authz()
+            .write()
+            .await
+            .rbac_mut()
+            .assign_role(&user.id, &user.role)?;
+
+        self.audit(actor, EventType::UserCreated, "user.create", &user.id)
+            .await;
+        info!("User {} created by {}", user.id, actor.user_id);
+
+        Ok(user)
+    }
+
+    /// Disable a user and invalidate all their sessions
+    pub async fn disable_user(&self, actor: &AuthContext, user_id: &str) -> AdminResult<()> {
+        self.require(actor, PERMISSION_USERS_WRITE).await?;
+
+        self.pool
+            .with_connection(|conn| self.users.deactivate(conn, user_id))
+            .map_err(|e| Self::map_not_found(e, user_id))?;
+        self.security
+            .auth()
+            .read()
+            .await
+            .session_manager()
+            .invalidate_user_sessions(user_id)
+            .await?;
+
+        self.audit(actor, EventType::UserDisabled, "user.disable", user_id)
+            .await;
+        Ok(())
+    }
+
+    /// Re-enable a previously disabled user
+    pub async fn enable_user(&self, actor: &AuthContext, user_id: &str) -> AdminResult<()> {
+        self.require(actor, PERMISSION_USERS_WRITE).await?;
+
+        self.pool
+            .with_connection(|conn| self.users.activate(conn, user_id))
+            .map_err(|e| Self::map_not_found(e, user_id))?;
+
+        self.audit(actor, EventType::UserEnabled, "user.enable", user_id)
+            .await;
+        Ok(())
+    }
+
+    /// Assign a role to a user, replacing their previous primary role
+    pub async fn assign_role(&self, actor: &AuthContext, user_id: &str, role: &str) -> AdminResult<()> {
+        self.require(actor, PERMISSION_USERS_WRITE).await?;
+
+        let user = self.find_user(user_id)?;
+
+        {
+            let authz = self.security.authz();
+            let mut authz = authz.write().await;
+            let rbac = authz.rbac_mut();
+            rbac.get_role(role)?;
+
+            // Persist first so a failed write leaves the RBAC assignments
+            // untouched; the role exists, so the swap below cannot fail
+            self.pool
+                .with_connection(|conn| self.users.update_role(conn, user_id, role))?;
+
+            if rbac.has_role(user_id, &user.role) {
+                rbac.revoke_role(user_id, &user.role)?;
+            }
+            rbac.assign_role(user_id, role)?;
+        }
+
+        self.audit(actor, EventType::AuthzRoleAssigned, "user.assign_role", user_id)
+            .await;
+        Ok(())
+    }
+
+    /// Reset a user's MFA enrollment so they must enroll again
+    pub async fn reset_mfa(&self, actor: &AuthContext, user_id: &str) -> AdminResult<()> {
+        self.require(actor, PERMISSION_USERS_WRITE).await?;
+
+        self.pool
+            .with_connection(|conn| self.users.reset_mfa(conn, user_id))
+            .map_err(|e| Self::map_not_found(e, user_id))?;
+        self.security
+            .auth()
+            .read()
+            .await
+            .session_manager()
+            .invalidate_user_sessions(user_id)
+            .await?;
+
+        self.audit(actor, EventType::UserMfaReset, "user.reset_mfa", user_id)
+            .await;
+        Ok(())
+    }
+
+    /// List a user's active sessions
+    pub async fn list_sessions(&self, actor: &AuthContext, user_id: &str) -> AdminResult<Vec<Session>> {
+        self.require(actor, PERMISSION_USERS_READ).await?;
+
+        self.find_user(user_id)?;
+        let sessions = self
+            .security
+            .auth()
+            .read()
+            .await
+            .session_manager()
+            .list_user_sessions(user_id)
+            .await?;
+
+        self.audit(actor, EventType::DataRead, "user.list_sessions", user_id)
+            .await;
+        Ok(sessions)
+    }
+
+    /// Require a password change at next login and end all current sessions
+    pub async fn force_password_reset(&self, actor: &AuthContext, user_id: &str) -> AdminResult<()> {
+        self.require(actor, PERMISSION_USERS_WRITE).await?;
+
+        self.pool
+            .with_connection(|conn| self.users.set_password_reset_required(conn, user_id, true))
+            .map_err(|e| Self::map_not_found(e, user_id))?;
+        self.security
+            .auth()
+            .read()
+            .await
+            .session_manager()
+            .invalidate_user_sessions(user_id)
+            .await?;
+
+        self.audit(actor, EventType::AuthPasswordReset, "user.force_password_reset", user_id)
+            .await;
+        Ok(())
+    }
+
+    /// Add a member to a scope, or change their role if already a member
+    pub async fn add_member(
+        &self,
+        actor: &AuthContext,
+        user_id: &str,
+        scope: OrgScope,
+        role: MemberRole,
+    ) -> AdminResult<Membership> {
+        self.require(actor, PERMISSION_ORGS_WRITE).await?;
+
+        self.find_user(user_id)?;
+        self.pool
+            .with_connection(|conn| self.organizations.find_scope_organization(conn, &scope))?
+            .ok_or_else(|| AdminError::InvalidRequest(format!("unknown scope: {}", scope)))?;
+
+        let membership = Membership::new(user_id.to_string(), scope, role);
+        self.pool
+            .with_connection(|conn| self.organizations.upsert_member(conn, &membership))?;
+
+        self.audit(actor, EventType::AuthzRoleAssigned, "membership.add", user_id)
+            .await;
+        Ok(membership)
+    }
+
+    /// Remove a member from a scope
+    pub async fn remove_member(&self, actor: &AuthContext, user_id: &str, scope: &OrgScope) -> AdminResult<()> {
+        self.require(actor, PERMISSION_ORGS_WRITE).await?;
+
+        self.pool
+            .with_connection(|conn| self.organizations.remove_member(conn, user_id, scope))
+            .map_err(|e| Self::map_not_found(e, user_id))?;
+
+        self.audit(actor, EventType::AuthzRoleRevoked, "membership.remove", user_id)
+            .await;
+        Ok(())
+    }
+
+    /// List direct members of a scope
+    pub async fn list_members(&self, actor: &AuthContext, scope: &OrgScope) -> AdminResult<Vec<Membership>> {
+        self.require(actor, PERMISSION_ORGS_READ).await?;
+
+        let members = self
+            .pool
+            .with_connection(|conn| self.organizations.find_members(conn, scope))?;
+
+        let resource = ResourceInfo::new("scope", scope.to_scope_string());
+        self.audit_resource(actor, EventType::DataRead, "membership.list", resource)
+            .await;
+        Ok(members)
+    }
+
+    /// List the authorization scopes a user holds
+    pub async fn user_scopes(&self, actor: &AuthContext, user_id: &str) -> AdminResult<Vec<String>> {
+        self.require(actor, PERMISSION_ORGS_READ).await?;
+
+        let scopes = self.authorization_scopes(user_id)?;
+        self.audit(actor, EventType::DataRead, "membership.user_scopes", user_id)
+            .await;
+        Ok(scopes)
+    }
+
+    /// Authorization scopes granted to a user by their memberships
+    pub fn authorization_scopes(&self, user_id: &str) -> AdminResult<Vec<String>> {
+        let memberships = self
+            .pool
+            .with_connection(|conn| self.organizations.find_memberships_of(conn, user_id))?;
+
+        Ok(memberships
+            .iter()
+            .map(|m| m.scope.to_scope_string())
+            .collect())
+    }
+
+    /// Build a policy request for a resource, carrying the organization
+    /// scopes of both the user and the resource
+    pub fn scoped_request(
+        &self,
+        user_id: &str,
+        permission: &str,
+        kind: OwnedResourceKind,
+        resource_id: &str,
+    ) -> AdminResult<PolicyRequest> {
+        let subject_scopes = self.authorization_scopes(user_id)?;
+
+        let resource_scopes = self.pool.with_connection(|conn| {
+            let Some(ownership) = self.organizations.find_owner(conn, kind, resource_id)? else {
+                return Ok(Vec::new());
+            };
+            let scope = OrgScope::Workspace(ownership.workspace_id.clone());
+            match self.organizations.find_scope_organization(conn, &scope)? {
+                Some(organization_id) => Ok(self
+                    .organizations
+                    .load_hierarchy(conn, &organization_id)?
+                    .resource_scopes(&ownership)),
+                None => Ok(Vec::new()),
+            }
+        })?;
+
+        Ok(PolicyRequest::simple(user_id.to_string(), permission.to_string())
+            .with_resource_attr(
+                "resource_type".to_string(),
+                AttributeValue::String(kind.as_str().to_string()),
+            )
+            .with_scopes(subject_scopes, resource_scopes))
+    }
+
+    fn parse_scope(scope: &str) -> AdminResult<OrgScope> {
+        OrgScope::parse(scope).ok_or_else(|| AdminError::InvalidRequest(format!("invalid scope: {}", scope)))
+    }
+
+    fn find_user(&self, user_id: &str) -> AdminResult<User> {
+        self.pool
+            .with_connection(|conn| self.users.find_by_id(conn, &user_id.to_string()))?
+            .ok_or_else(|| AdminError::UserNotFound(user_id.to_string()))
+    }
+
+    fn map_not_found(error: DatabaseError, user_id: &str) -> AdminError {
+        match error {
+            DatabaseError::NotFound { .. } => AdminError::UserNotFound(user_id.to_string()),
+            other => AdminError::Database(other),
+        }
+    }
+
+    async fn require(&self, actor: &AuthContext, permission: &str) -> AdminResult<()> {
+        let allowed = actor.has_permission(permission)
+            || self
+                .security
+                .authz()
+                .write()
+                .await
+                .has_permission(actor, permission);
+
+        if allowed {
+            return Ok(());
+        }
+
+        let event = AuditEvent::new(EventType::AuthzAccessDenied, format!("admin.{}", permission))
+            .with_user(actor.user_id.clone())
+            .with_result(EventResult::Failure)
+            .with_severity(EventSeverity::Warning);
+        if let Err(e) = self.security.audit().audit(event).await {
+            warn!("Failed to audit denied admin action: {}", e);
+        }
+
+        Err(AdminError::PermissionDenied(permission.to_string()))
+    }
+
+    async fn audit(&self, actor: &AuthContext, event_type: EventType, action: &str, user_id: &str) {
+        self.audit_resource(actor, event_type, action, ResourceInfo::new("user", user_id))
+            .await;
+    }
+
+    async fn audit_resource(
+        &self,
+        actor: &AuthContext,
+        event_type: EventType,
+        action: &str,
+        resource: ResourceInfo,
+    ) {
+        let mut event = AuditEvent::new(event_type, action.to_string())
+            .with_user(actor.user_id.clone())
+            .with_resource(resource)
+            .with_result(EventResult::Success);
+        if let Some(session_id) = &actor.session_id {
+            event = event.with_session(session_id.clone());
+        }
+
+        if let Err(e) = self.security.audit().audit(event).await {
+            warn!("Failed to audit admin action {}: {}", action, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use accuscene_core::types::organization::Organization;
+    use accuscene_database::{DatabaseConfig, MigrationRunner};
+    use accuscene_security::audit::AuditQuery;
+    use accuscene_security::SecurityConfig;
+
+    async fn service() -> AdminService {
+        let pool = DatabasePool::new(DatabaseConfig::in_memory()).unwrap();
+        MigrationRunner::new().migrate(&mut pool.get().unwrap()).unwrap();
+        let mut config = SecurityConfig::default();
+        config.audit.tamper_detection = false;
+        let security = SecurityService::new(config).await.unwrap();
+
+        AdminService::new(Arc::new(pool), Arc::new(security))
+    }
+
+    fn actor(user_id: &str, permissions: &[&str]) -> AuthContext {
+        AuthContext {
+            user_id: user_id.to_string(),
+            session_id: Some("session-1".to_string()),
+            roles: Vec::new(),
+            permissions: permissions.iter().map(|p| p.to_string()).collect(),
+            org_id: None,
+            mfa_verified: true,
+            session_metadata: None,
+        }
+    }
+
+    fn admin() -> AuthContext {
+        actor(
+            "admin-1",
+            &[PERMISSION_USERS_READ, PERMISSION_USERS_WRITE, PERMISSION_ORGS_READ, PERMISSION_ORGS_WRITE],
+        )
+    }
+
+    fn new_user(username: &str) -> NewUser {
+        NewUser {
+            email: format!("{}@example.com", username),
+            username: username.to_string(),
+            full_name: "Dana Reyes".to_string(),
+            password: "Quartz-Harbor-Lantern-7391!".to_string(),
+            role: "viewer".to_string(),
+            organization: None,
+            phone: None,
+        }
+    }
+
+    async fn audit_trail(service: &AdminService, actor_id: &str) -> Vec<AuditEvent> {
+        service
+            .security
+            .audit()
+            .query(AuditQuery::new().user_id(actor_id))
+            .await
+            .unwrap()
+            .events
+    }
+
+    #[tokio::test]
+    async fn test_require_gates_actions() {
+        let service = service().await;
+        let reader = actor("reader-1", &[PERMISSION_USERS_READ]);
+
+        assert!(service.list_users(&reader, false).await.is_ok());
+        let denied = service
+            .handle(&reader, AdminRequest::DisableUser { user_id: "u1".to_string() })
+            .await;
+        assert!(matches!(denied, Err(AdminError::PermissionDenied(p)) if p == PERMISSION_USERS_WRITE));
+
+        let events = audit_trail(&service, "reader-1").await;
+        let denial = events
+            .iter()
+            .find(|e| e.event_type == EventType::AuthzAccessDenied)
+            .unwrap();
+        assert_eq!(denial.action, "admin.users:write");
+        assert_eq!(denial.result, EventResult::Failure);
+
+        let denied = service
+            .handle(&reader, AdminRequest::UserScopes { user_id: "u1".to_string() })
+            .await;
+        assert!(matches!(denied, Err(AdminError::PermissionDenied(p)) if p == PERMISSION_ORGS_READ));
+    }
+
+    #[tokio::test]
+    async fn test_every_request_is_audited() {
+        let service = service().await;
+        let admin = admin();
+
+        let user = service
+            .handle(&admin, AdminRequest::CreateUser(new_user("dana")))
+            .await
+            .unwrap();
+        let user_id = user["id"].as_str().unwrap().to_string();
+        let organization = Organization::new("Acme Forensics".to_string(), "acme".to_string());
+        service
+            .pool
+            .with_connection(|conn| service.organizations.create(conn, &organization))
+            .unwrap();
+        let scope = format!("org:{}", organization.id);
+
+        let requests = vec![
+            (AdminRequest::ListUsers { active_only: true }, "user.list"),
+            (AdminRequest::DisableUser { user_id: user_id.clone() }, "user.disable"),
+            (AdminRequest::EnableUser { user_id: user_id.clone() }, "user.enable"),
+            (
+                AdminRequest::AssignRole { user_id: user_id.clone(), role: "investigator".to_string() },
+                "user.assign_role",
+            ),
+            (AdminRequest::ResetMfa { user_id: user_id.clone() }, "user.reset_mfa"),
+            (AdminRequest::ListSessions { user_id: user_id.clone() }, "user.list_sessions"),
+            (
+                AdminRequest::ForcePasswordReset { user_id: user_id.clone() },
+                "user.force_password_reset",
+            ),
+            (
+                AdminRequest::AddMember {
+                    user_id: user_id.clone(),
+                    scope: scope.clone(),
+                    role: "member".to_string(),
+                },
+                "membership.add",
+            ),
+            (AdminRequest::ListMembers { scope: scope.clone() }, "membership.list"),
+            (AdminRequest::UserScopes { user_id: user_id.clone() }, "membership.user_scopes"),
+            (
+                AdminRequest::RemoveMember { user_id: user_id.clone(), scope: scope.clone() },
+                "membership.remove",
+            ),
+        ];
+
+        for (request, action) in requests {
+            service.handle(&admin, request).await.unwrap();
+
+            let events = audit_trail(&service, "admin-1").await;
+            let event = events.iter().find(|e| e.action == action).unwrap();
+            assert_eq!(event.result, EventResult::Success);
+            assert_eq!(event.session_id.as_deref(), Some("session-1"));
+        }
+
+        let events = audit_trail(&service, "admin-1").await;
+        assert!(events.iter().any(|e| e.action == "user.create"));
+    }
+
+    #[tokio::test]
+    async fn test_assign_role_keeps_rbac_when_save_fails() {
+        let service = service().await;
+        let admin = admin();
+        let user = service.create_user(&admin, new_user("dana")).await.unwrap();
+
+        service
+            .pool
+            .with_connection(|conn| {
+                conn.execute_batch(
+                    "CREATE TRIGGER reject_role BEFORE UPDATE OF role ON users
+                     BEGIN SELECT RAISE(ABORT, 'role changes are frozen'); END;",
+                )?;
+                Ok(())
+            })
+            .unwrap();
+
+        let result = service.assign_role(&admin, &user.id, "investigator").await;
+        assert!(matches!(result, Err(AdminError::Database(_))));
+
+        let authz = service.security.authz();
+        let authz = authz.read().await;
+        assert!(authz.rbac().has_role(&user.id, "viewer"));
+        assert!(!authz.rbac().has_role(&user.id, "investigator"));
+        assert_eq!(service.find_user(&user.id).unwrap().role, "viewer");
+
+        let events = audit_trail(&service, "admin-1").await;
+        assert!(!events.iter().any(|e| e.action == "user.assign_role"));
+    }
+}
@@ -0,0 +1,325 @@
+//! Database change feed to the event buses
+//!
+//! Polls the database changelog (see [`accuscene_database::cdc`]) and
+//! republishes every captured row change as an [`EntityChanged`] domain event
+//! on the event sourcing bus, so projections see repository writes that do
+//! not go through an aggregate. When an integration event bus is attached,
+//! each change is also published there with the entity named in its
+//! metadata, which lets the watch fan-out notify followers of the entity.
+//!
+//! Changes are acknowledged only after they have been published, so a crash
+//! between polls replays the unacknowledged changes (at-least-once delivery).
+
+use crate::events::{Event, EventBus, EventMetadata, EventType};
+use accuscene_database::cdc::{ChangeFeed, ChangeOperation, ChangeRecord};
+use accuscene_database::{DatabaseError, DatabasePool};
+use accuscene_eventsourcing::bus::EventBus as DomainEventBus;
+use accuscene_eventsourcing::domain::{ChangeKind, EntityChanged};
+use accuscene_eventsourcing::event::EventEnvelope;
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+
+/// Consumer name used for the changelog cursor
+pub const DEFAULT_CONSUMER: &str = "event-bus";
+
+/// Default delay between polls when the changelog is drained
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Custom metadata key for the changelog sequence of a change
+pub const CHANGE_SEQUENCE_KEY: &str = "change_sequence";
+
+/// Custom metadata key for the fields a change touched
+pub const CHANGED_FIELDS_KEY: &str = "changed_fields";
+
+/// Change feed errors
+#[derive(Debug, thiserror::Error)]
+pub enum ChangeFeedError {
+    /// Database error
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+
+    /// Publishing to an event bus failed
+    #[error("Publish failed: {0}")]
+    Publish(String),
+}
+
+/// Change feed result type
+pub type ChangeFeedResult<T> = Result<T, ChangeFeedError>;
+
+/// Entity type name for a captured table
+pub fn entity_type_for_table(table: &str) -> &str {
+    match table {
+        "cases" => "Case",
+        "accidents" => "Accident",
+        "vehicles" => "Vehicle",
+        "evidence" => "Evidence",
+        other => other,
+    }
+}
+
+/// Convert a captured row change into a domain event
+pub fn entity_changed_from_record(record: &ChangeRecord) -> EntityChanged {
+    let change = match record.operation {
+        ChangeOperation::Insert => ChangeKind::Created,
+        ChangeOperation::Delete => ChangeKind::Deleted,
+        ChangeOperation::Update => {
+            let deleted_at = |values: &Option<serde_json::Value>| {
+                values.as_ref().and_then(|v| v.get("deleted_at")).is_some_and(|v| !v.is_null())
+            };
+            match (
+                deleted_at(&record.old_values),
+                deleted_at(&record.new_values),
+            ) {
+                (false, true) => ChangeKind::Trashed,
+                (true, false) => ChangeKind::Restored,
+                _ => ChangeKind::Updated,
+            }
+        },
+    };
+
+    let changed_at = NaiveDateTime::parse_from_str(&record.changed_at, "%Y-%m-%d %H:%M:%S")
+        .map_or_else(|_| Utc::now(), |naive| naive.and_utc());
+
+    EntityChanged {
+        entity_type: entity_type_for_table(&record.table_name).to_string(),
+        entity_id: record.row_id.clone(),
+        change,
+        changed_fields: record.changed_columns(),
+        before: record.old_values.clone(),
+        after: record.new_values.clone(),
+        change_sequence: u64::try_from(record.sequence).unwrap_or_default(),
+        changed_at,
+    }
+}
+
+/// Entity change published on the integration event bus
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityChangeEvent {
+    /// Event metadata
+    pub metadata: EventMetadata,
+
+    /// The domain event
+    pub change: EntityChanged,
+}
+
+impl EntityChangeEvent {
+    /// Wrap a domain event
+    ///
+    /// The event type is `<entity>.<change>`, e.g. `case.updated`.
+    pub fn new(change: EntityChanged) -> Self {
+        let entity_type = change.entity_type.to_lowercase();
+        let event_type = format!("{}.{}", entity_type, change_name(change.change));
+        let metadata = EventMetadata::new(event_type)
+            .with_source("database".to_string())
+            .with_entity(&entity_type, &change.entity_id)
+            .with_custom(
+                CHANGE_SEQUENCE_KEY.to_string(),
+                change.change_sequence.into(),
+            )
+            .with_custom(
+                CHANGED_FIELDS_KEY.to_string(),
+                change.changed_fields.clone().into(),
+            );
+
+        Self { metadata, change }
+    }
+}
+
+#[async_trait]
+impl Event for EntityChangeEvent {
+    fn event_type(&self) -> EventType {
+        self.metadata.event_type.clone()
+    }
+
+    fn timestamp(&self) -> DateTime<Utc> {
+        self.change.changed_at
+    }
+
+    fn metadata(&self) -> EventMetadata {
+        self.metadata.clone()
+    }
+}
+
+fn change_name(change: ChangeKind) -> &'static str {
+    match change {
+        ChangeKind::Created => "created",
+        ChangeKind::Updated => "updated",
+        ChangeKind::Trashed => "trashed",
+        ChangeKind::Restored => "restored",
+        ChangeKind::Deleted => "deleted",
+    }
+}
+
+/// Publishes database changes on the event buses
+pub struct ChangeFeedPoller {
+    pool: Arc<DatabasePool>,
+    feed: ChangeFeed,
+    domain_bus: DomainEventBus,
+    event_bus: Option<Arc<EventBus>>,
+    poll_interval: Duration,
+}
+
+impl ChangeFeedPoller {
+    /// Create a poller publishing to the event sourcing bus
+    pub fn new(pool: Arc<DatabasePool>, domain_bus: DomainEventBus) -> Self {
+        Self {
+            pool,
+            feed: ChangeFeed::new(DEFAULT_CONSUMER),
+            domain_bus,
+            event_bus: None,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    /// Use a custom changelog consumer, e.g. one per process
+    pub fn with_feed(mut self, feed: ChangeFeed) -> Self {
+        self.feed = feed;
+        self
+    }
+
+    /// Also publish changes on the integration event bus
+    pub fn with_event_bus(mut self, event_bus: Arc<EventBus>) -> Self {
+        self.event_bus = Some(event_bus);
+        self
+    }
+
+    /// Set the delay between polls when the changelog is drained
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Publish the next batch of changes
+    ///
+    /// Returns the number of published changes.
+    pub async fn poll_once(&self) -> ChangeFeedResult<usize> {
+        let changes = self.pool.with_connection(|conn| self.feed.poll(conn))?;
+
+        for record in &changes {
+            self.publish(record).await?;
+            self.pool.with_connection(|conn| self.feed.acknowledge(conn, record.sequence))?;
+        }
+
+        if !changes.is_empty() {
+            debug!("Published {} database change(s)", changes.len());
+        }
+        Ok(changes.len())
+    }
+
+    /// Poll until cancelled
+    pub async fn run(&self, token: CancellationToken) {
+        while !token.is_cancelled() {
+            match self.poll_once().await {
+                Ok(0) => {},
+                Ok(_) => continue,
+                Err(e) => warn!("Change feed poll failed: {}", e),
+            }
+
+            tokio::select! {
+                () = token.cancelled() => break,
+                () = tokio::time::sleep(self.poll_interval) => {}
+            }
+        }
+    }
+
+    async fn publish(&self, record: &ChangeRecord) -> ChangeFeedResult<()> {
+        let change = entity_changed_from_record(record);
+        let envelope = EventEnvelope::new(change.clone(), change.change_sequence);
+        self.domain_bus
+            .publish(&envelope)
+            .await
+            .map_err(|e| ChangeFeedError::Publish(e.to_string()))?;
+
+        if let Some(event_bus) = &self.event_bus {
+            event_bus
+                .publish(Arc::new(EntityChangeEvent::new(change)))
+                .await
+                .map_err(|e| ChangeFeedError::Publish(e.to_string()))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn record(
+        operation: ChangeOperation,
+        old: Option<serde_json::Value>,
+        new: Option<serde_json::Value>,
+    ) -> ChangeRecord {
+        ChangeRecord {
+            sequence: 7,
+            table_name: "cases".to_string(),
+            row_id: "c1".to_string(),
+            operation,
+            old_values: old,
+            new_values: new,
+            changed_at: "2024-03-01 12:30:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_entity_changed_from_record() {
+        let created = entity_changed_from_record(&record(
+            ChangeOperation::Insert,
+            None,
+            Some(json!({"id": "c1", "title": "Collision"})),
+        ));
+        assert_eq!(created.entity_type, "Case");
+        assert_eq!(created.change, ChangeKind::Created);
+        assert_eq!(created.change_sequence, 7);
+        assert_eq!(created.changed_at.to_rfc3339(), "2024-03-01T12:30:00+00:00");
+
+        let updated = entity_changed_from_record(&record(
+            ChangeOperation::Update,
+            Some(json!({"id": "c1", "title": "Collision", "deleted_at": null})),
+            Some(json!({"id": "c1", "title": "Rear-end collision", "deleted_at": null})),
+        ));
+        assert_eq!(updated.change, ChangeKind::Updated);
+        assert_eq!(updated.changed_fields, vec!["title".to_string()]);
+
+        let trashed = entity_changed_from_record(&record(
+            ChangeOperation::Update,
+            Some(json!({"id": "c1", "deleted_at": null})),
+            Some(json!({"id": "c1", "deleted_at": "2024-03-01 12:30:00"})),
+        ));
+        assert_eq!(trashed.change, ChangeKind::Trashed);
+
+        let restored = entity_changed_from_record(&record(
+            ChangeOperation::Update,
+            Some(json!({"id": "c1", "deleted_at": "2024-03-01 12:30:00"})),
+            Some(json!({"id": "c1", "deleted_at": null})),
+        ));
+        assert_eq!(restored.change, ChangeKind::Restored);
+    }
+
+    #[test]
+    fn test_entity_change_event_metadata() {
+        let change = entity_changed_from_record(&record(
+            ChangeOperation::Delete,
+            Some(json!({"id": "c1"})),
+            None,
+        ));
+        let event = EntityChangeEvent::new(change);
+
+        assert_eq!(event.event_type(), "case.deleted");
+        assert_eq!(
+            event.metadata.custom_str(crate::events::ENTITY_TYPE_KEY),
+            Some("case")
+        );
+        assert_eq!(
+            event.metadata.custom_str(crate::events::ENTITY_ID_KEY),
+            Some("c1")
+        );
+        assert_eq!(event.metadata.custom[CHANGE_SEQUENCE_KEY], json!(7));
+    }
+}
@@ -78,6 +78,14 @@ pub struct AppConfig {
 
     /// Temporary directory
     pub temp_dir: PathBuf,
+
+    /// Time background tasks get to stop on shutdown, in seconds
+    #[serde(default = "default_shutdown_timeout")]
+    pub shutdown_timeout: u64,
+}
+
+fn default_shutdown_timeout() -> u64 {
+    30
 }
 
 /// Database configuration
@@ -409,6 +417,7 @@ impl Default for AppConfig {
             log_level: LogLevel::Info,
             data_dir: PathBuf::from("./data"),
             temp_dir: PathBuf::from("./tmp"),
+            shutdown_timeout: default_shutdown_timeout(),
         }
     }
 }
@@ -0,0 +1,216 @@
+//! GDPR data subject requests over the database
+//!
+//! Personal data sources for the security framework's DSAR engine:
+//! [`UserDataSource`] covers user accounts and [`PartyDataSource`] the party
+//! registry. Both anonymize rather than delete on erasure, so cases, audit
+//! entries and party links keep pointing at a real row.
+//!
+//! Party merge snapshots are part of the merge history and are not erased.
+
+use accuscene_core::types::party::{normalize_name, Party, PrivacyClass};
+use accuscene_database::repositories::Repository;
+use accuscene_database::{DatabaseError, DatabasePool, PartyRepository, User, UserRepository};
+use accuscene_security::compliance::{DataSubject, PersonalDataRecord, PersonalDataSource};
+use accuscene_security::{SecurityError, SecurityService};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Source name of user accounts
+pub const USER_SOURCE: &str = "users";
+
+/// Source name of the party registry
+pub const PARTY_SOURCE: &str = "parties";
+
+/// Register the database sources with the security service's DSAR engine
+pub async fn register_dsar_sources(
+    pool: Arc<DatabasePool>,
+    security: Arc<SecurityService>,
+) -> accuscene_security::Result<()> {
+    let compliance = security.compliance();
+    let dsar = compliance.dsar();
+    dsar.add_source(Arc::new(UserDataSource::new(Arc::clone(&pool), security)))
+        .await?;
+    dsar.add_source(Arc::new(PartyDataSource::new(pool))).await
+}
+
+/// User accounts
+pub struct UserDataSource {
+    pool: Arc<DatabasePool>,
+    security: Arc<SecurityService>,
+    users: UserRepository,
+}
+
+impl UserDataSource {
+    /// Create a source on the database pool
+    pub fn new(pool: Arc<DatabasePool>, security: Arc<SecurityService>) -> Self {
+        Self {
+            pool,
+            security,
+            users: UserRepository::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl PersonalDataSource for UserDataSource {
+    fn name(&self) -> &str {
+        USER_SOURCE
+    }
+
+    async fn locate(
+        &self,
+        subject: &DataSubject,
+    ) -> accuscene_security::Result<Vec<PersonalDataRecord>> {
+        let mut found: Vec<User> = Vec::new();
+        self.pool
+            .with_connection(|conn| {
+                if let Some(id) = &subject.user_id {
+                    found.extend(self.users.find_by_id(conn, id)?);
+                }
+                if let Some(email) = &subject.email {
+                    found.extend(self.users.find_by_email(conn, email)?);
+                }
+                Ok(())
+            })
+            .map_err(repository_error)?;
+        found.sort_by(|a, b| a.id.cmp(&b.id));
+        found.dedup_by(|a, b| a.id == b.id);
+
+        found
+            .into_iter()
+            .map(|user| {
+                let data = serde_json::to_value(&user)
+                    .map_err(|e| SecurityError::Internal(e.to_string()))?;
+                Ok(PersonalDataRecord::new(USER_SOURCE, "user", user.id, data))
+            })
+            .collect()
+    }
+
+    async fn erase(&self, record: &PersonalDataRecord) -> accuscene_security::Result<()> {
+        let id = &record.record_id;
+        let mut user = self
+            .pool
+            .with_connection(|conn| self.users.find_by_id(conn, id))
+            .map_err(repository_error)?
+            .ok_or_else(|| SecurityError::DsarNotFound(format!("user {}", id)))?;
+
+        user.email = format!("erased-{}@erased.invalid", id);
+        user.username = format!("erased-{}", id);
+        user.full_name = "Erased user".to_string();
+        user.phone = None;
+        user.password_hash = String::new();
+        user.is_active = false;
+        user.updated_at = chrono::Utc::now().to_rfc3339();
+
+        self.pool
+            .with_connection(|conn| self.users.update(conn, &user))
+            .map_err(repository_error)?;
+        self.security
+            .auth()
+            .read()
+            .await
+            .session_manager()
+            .invalidate_user_sessions(id)
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// Parties involved in cases
+///
+/// A party matches when its normalized name equals the subject's and the
+/// dates of birth, where both are known, agree.
+pub struct PartyDataSource {
+    pool: Arc<DatabasePool>,
+    parties: PartyRepository,
+}
+
+impl PartyDataSource {
+    /// Create a source on the database pool
+    pub fn new(pool: Arc<DatabasePool>) -> Self {
+        Self {
+            pool,
+            parties: PartyRepository::new(),
+        }
+    }
+
+    fn matches(subject: &DataSubject, name: &str, party: &Party) -> bool {
+        party.normalized_name == name
+            && !matches!(
+                (subject.date_of_birth, party.date_of_birth),
+                (Some(a), Some(b)) if a != b
+            )
+    }
+}
+
+#[async_trait]
+impl PersonalDataSource for PartyDataSource {
+    fn name(&self) -> &str {
+        PARTY_SOURCE
+    }
+
+    async fn locate(
+        &self,
+        subject: &DataSubject,
+    ) -> accuscene_security::Result<Vec<PersonalDataRecord>> {
+        let Some(full_name) = &subject.full_name else {
+            return Ok(Vec::new());
+        };
+        let name = normalize_name(full_name);
+
+        let found = self
+            .pool
+            .with_connection(|conn| {
+                let mut found = Vec::new();
+                for party in self.parties.find_all(conn)? {
+                    if Self::matches(subject, &name, &party) {
+                        let links = self.parties.links_of(conn, &party.id)?;
+                        found.push((party, links));
+                    }
+                }
+                Ok(found)
+            })
+            .map_err(repository_error)?;
+
+        found
+            .into_iter()
+            .map(|(party, links)| {
+                let data = serde_json::to_value(&party)
+                    .map_err(|e| SecurityError::Internal(e.to_string()))?;
+                let mut record = PersonalDataRecord::new(PARTY_SOURCE, "party", party.id, data);
+                for link in links {
+                    if !record.case_ids.contains(&link.case_id) {
+                        record.case_ids.push(link.case_id);
+                    }
+                }
+                Ok(record)
+            })
+            .collect()
+    }
+
+    async fn erase(&self, record: &PersonalDataRecord) -> accuscene_security::Result<()> {
+        let id = &record.record_id;
+        let mut party = self
+            .pool
+            .with_connection(|conn| self.parties.find_by_id(conn, id))
+            .map_err(repository_error)?
+            .ok_or_else(|| SecurityError::DsarNotFound(format!("party {}", id)))?;
+
+        // Keep the row and its case links; drop everything identifying
+        party.rename("Erased party".to_string());
+        party.date_of_birth = None;
+        party.licence_number = None;
+        party.phone = None;
+        party.address = None;
+        party.privacy_class = PrivacyClass::Restricted;
+
+        self.pool
+            .with_connection(|conn| self.parties.update(conn, &party))
+            .map_err(repository_error)
+    }
+}
+
+fn repository_error(error: DatabaseError) -> SecurityError {
+    SecurityError::Internal(format!("Personal data repository: {}", error))
+}
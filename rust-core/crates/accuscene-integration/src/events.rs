@@ -16,6 +16,15 @@ pub type EventType = String;
 /// Event handler ID
 pub type HandlerId = uuid::Uuid;
 
+/// Custom metadata key for the type of the entity an event is about
+pub const ENTITY_TYPE_KEY: &str = "entity_type";
+
+/// Custom metadata key for the ID of the entity an event is about
+pub const ENTITY_ID_KEY: &str = "entity_id";
+
+/// Custom metadata key for the user who caused an event
+pub const ACTOR_ID_KEY: &str = "actor_id";
+
 /// Base event trait
 #[async_trait]
 pub trait Event: Send + Sync {
@@ -76,6 +85,22 @@ impl EventMetadata {
         self
     }
 
+    /// Set the entity the event is about
+    pub fn with_entity(self, entity_type: &str, entity_id: &str) -> Self {
+        self.with_custom(ENTITY_TYPE_KEY.to_string(), entity_type.into())
+            .with_custom(ENTITY_ID_KEY.to_string(), entity_id.into())
+    }
+
+    /// Set the user who caused the event
+    pub fn with_actor(self, actor_id: &str) -> Self {
+        self.with_custom(ACTOR_ID_KEY.to_string(), actor_id.into())
+    }
+
+    /// Get a custom metadata value as a string
+    pub fn custom_str(&self, key: &str) -> Option<&str> {
+        self.custom.get(key).and_then(serde_json::Value::as_str)
+    }
+
     /// Add custom metadata
     pub fn with_custom(mut self, key: String, value: serde_json::Value) -> Self {
         self.custom.insert(key, value);
@@ -3,10 +3,16 @@
 //! This module provides a unified facade that simplifies access to all
 //! AccuScene Enterprise services through a single, convenient interface.
 
+use crate::admin::AdminService;
 use crate::config::Config;
-use crate::events::EventBus;
+use crate::events::{EventBus, EventType};
+use crate::metering::MeteringService;
+use crate::operations::OperationsService;
 use crate::registry::Registry;
-use std::sync::Arc;
+use crate::tiering::TieringService;
+use crate::watch::WatchFanout;
+use accuscene_notifications::{EntitySubscription, NotificationError, NotificationSystem};
+use std::sync::{Arc, OnceLock};
 
 /// The main facade for AccuScene Enterprise services
 ///
@@ -16,15 +22,27 @@ pub struct Facade {
     config: Config,
     registry: Arc<Registry>,
     event_bus: Arc<EventBus>,
+    operations: Arc<OperationsService>,
+    metering: Arc<MeteringService>,
+    admin: OnceLock<Arc<AdminService>>,
+    notifications: OnceLock<Arc<NotificationSystem>>,
+    tiering: OnceLock<Arc<TieringService>>,
 }
 
 impl Facade {
     /// Create a new facade
     pub fn new(config: Config, registry: Arc<Registry>, event_bus: Arc<EventBus>) -> Self {
+        let operations = Arc::new(OperationsService::new(Arc::clone(&event_bus)));
+
         Self {
             config,
             registry,
             event_bus,
+            operations,
+            metering: Arc::new(MeteringService::new()),
+            admin: OnceLock::new(),
+            notifications: OnceLock::new(),
+            tiering: OnceLock::new(),
         }
     }
 
@@ -43,6 +61,102 @@ impl Facade {
         Arc::clone(&self.event_bus)
     }
 
+    /// Get the long-running operations service
+    pub fn operations(&self) -> Arc<OperationsService> {
+        Arc::clone(&self.operations)
+    }
+
+    /// Get the usage metering service
+    pub fn metering(&self) -> Arc<MeteringService> {
+        Arc::clone(&self.metering)
+    }
+
+    /// Attach the user administration service
+    ///
+    /// The admin service needs a live database pool and security service, so it
+    /// is attached once those are available. Returns `false` if one was already attached.
+    pub fn attach_admin(&self, admin: Arc<AdminService>) -> bool {
+        self.admin.set(admin).is_ok()
+    }
+
+    /// Get the user administration service, if attached
+    pub fn admin(&self) -> Option<Arc<AdminService>> {
+        self.admin.get().map(Arc::clone)
+    }
+
+    /// Attach the notification system
+    ///
+    /// Returns `false` if one was already attached.
+    pub fn attach_notifications(&self, notifications: Arc<NotificationSystem>) -> bool {
+        self.notifications.set(notifications).is_ok()
+    }
+
+    /// Get the notification system, if attached
+    pub fn notifications(&self) -> Option<Arc<NotificationSystem>> {
+        self.notifications.get().map(Arc::clone)
+    }
+
+    /// Attach the cold-storage tiering service
+    ///
+    /// Returns `false` if one was already attached.
+    pub fn attach_tiering(&self, tiering: Arc<TieringService>) -> bool {
+        self.tiering.set(tiering).is_ok()
+    }
+
+    /// Get the cold-storage tiering service, if attached
+    pub fn tiering(&self) -> Option<Arc<TieringService>> {
+        self.tiering.get().map(Arc::clone)
+    }
+
+    /// Notify entity watchers of the given event types published on the event bus
+    ///
+    /// Returns `false` if no notification system is attached.
+    pub async fn enable_watch_fanout(&self, event_types: Vec<EventType>) -> bool {
+        let Some(notifications) = self.notifications() else {
+            return false;
+        };
+
+        self.event_bus
+            .subscribe(Arc::new(WatchFanout::new(notifications, event_types)))
+            .await;
+        true
+    }
+
+    /// Follow an entity on behalf of a user
+    pub async fn follow(
+        &self,
+        user_id: &str,
+        entity_type: &str,
+        entity_id: &str,
+        event_types: Vec<String>,
+    ) -> accuscene_notifications::Result<EntitySubscription> {
+        self.attached_notifications()?
+            .watch(user_id, entity_type, entity_id, event_types)
+            .await
+    }
+
+    /// Stop following an entity on behalf of a user
+    pub async fn unfollow(
+        &self,
+        user_id: &str,
+        entity_type: &str,
+        entity_id: &str,
+    ) -> accuscene_notifications::Result<bool> {
+        self.attached_notifications()?
+            .unwatch(user_id, entity_type, entity_id)
+            .await
+    }
+
+    /// Get the entities a user follows
+    pub async fn followed(&self, user_id: &str) -> accuscene_notifications::Result<Vec<EntitySubscription>> {
+        self.attached_notifications()?.watched(user_id).await
+    }
+
+    fn attached_notifications(&self) -> accuscene_notifications::Result<Arc<NotificationSystem>> {
+        self.notifications()
+            .ok_or_else(|| NotificationError::Internal("Notification system not attached".to_string()))
+    }
+
     // ========================================================================
     // Core Services
     // ========================================================================
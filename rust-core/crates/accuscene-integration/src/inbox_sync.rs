@@ -0,0 +1,535 @@
+//! Notification inbox state sync across devices
+//!
+//! Read and archive state of in-app notifications is replicated through the
+//! offline sync engine so that marking a notification read on one device
+//! shows up on every other device of the same user. Each flag is a
+//! last-writer-wins register versioned with a vector clock: operations that
+//! are already dominated by the local version are ignored, which makes
+//! replaying an operation harmless, and concurrent writes are settled by
+//! timestamp and then node ID so every device converges on the same state.
+
+use accuscene_notifications::{NotificationError, NotificationStore};
+use accuscene_offline::{
+    NodeId, OfflineError, OperationType, Ordering, Priority, Storage, StorageRecord, SyncEngine,
+    SyncOperation, VectorClock, Version,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::debug;
+use uuid::Uuid;
+
+/// Entity type used for inbox state records and sync operations
+pub const INBOX_ENTITY_TYPE: &str = "notification_inbox";
+
+/// Tag attached to inbox sync operations
+pub const INBOX_SYNC_TAG: &str = "inbox";
+
+/// Inbox sync error
+#[derive(Debug, thiserror::Error)]
+pub enum InboxSyncError {
+    /// Offline engine or storage error
+    #[error("Offline sync error: {0}")]
+    Offline(#[from] OfflineError),
+
+    /// Notification store error
+    #[error("Notification store error: {0}")]
+    Notification(#[from] NotificationError),
+
+    /// Serialization error
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    /// The operation is not an inbox operation or is malformed
+    #[error("Invalid inbox operation: {0}")]
+    InvalidOperation(String),
+}
+
+/// Inbox sync result type
+pub type InboxSyncResult<T> = Result<T, InboxSyncError>;
+
+/// Change to the inbox state of a notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InboxAction {
+    /// Mark the notification read
+    Read,
+    /// Mark the notification unread
+    Unread,
+    /// Move the notification to the archive
+    Archive,
+    /// Restore the notification from the archive
+    Unarchive,
+}
+
+/// Payload of an inbox sync operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InboxOperation {
+    /// Notification the change applies to
+    pub notification_id: Uuid,
+    /// Owner of the notification
+    pub user_id: String,
+    /// Change to apply
+    pub action: InboxAction,
+}
+
+/// Last-writer-wins boolean register
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FlagRegister {
+    /// Current value
+    pub value: bool,
+    /// Version of the write that set the value, with every seen clock merged in
+    pub version: Option<Version>,
+}
+
+impl FlagRegister {
+    /// Time the current value was written
+    pub fn changed_at(&self) -> Option<DateTime<Utc>> {
+        self.version.as_ref().map(|v| v.timestamp)
+    }
+
+    /// Version for a new local write that supersedes everything seen so far
+    fn next_version(&self, node_id: &NodeId) -> Version {
+        let mut clock = self
+            .version
+            .as_ref()
+            .map(|v| v.clock.clone())
+            .unwrap_or_default();
+        clock.increment(node_id);
+
+        Version {
+            clock,
+            node_id: node_id.clone(),
+            timestamp: Utc::now(),
+            content_hash: String::new(),
+        }
+    }
+
+    /// Apply a write; returns whether it replaced the current value
+    fn apply(&mut self, value: bool, version: &Version) -> bool {
+        let Some(current) = self.version.as_mut() else {
+            self.value = value;
+            self.version = Some(version.clone());
+            return true;
+        };
+
+        let wins = match version.clock.compare(&current.clock) {
+            Ordering::Greater => true,
+            Ordering::Less | Ordering::Equal => false,
+            Ordering::Concurrent => {
+                (version.timestamp, &version.node_id) > (current.timestamp, &current.node_id)
+            }
+        };
+
+        if wins {
+            let mut clock = current.clock.clone();
+            clock.merge(&version.clock);
+            *current = Version {
+                clock,
+                ..version.clone()
+            };
+            self.value = value;
+        } else {
+            current.clock.merge(&version.clock);
+        }
+
+        wins
+    }
+}
+
+/// Replicated inbox state of one notification
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InboxState {
+    /// Notification ID
+    pub notification_id: Uuid,
+    /// Owner of the notification
+    pub user_id: String,
+    /// Read flag
+    pub read: FlagRegister,
+    /// Archived flag
+    pub archived: FlagRegister,
+}
+
+impl InboxState {
+    /// Create an empty state for a notification
+    pub fn new(notification_id: Uuid, user_id: String) -> Self {
+        Self {
+            notification_id,
+            user_id,
+            read: FlagRegister::default(),
+            archived: FlagRegister::default(),
+        }
+    }
+
+    /// Time the notification was read, if it is read
+    pub fn read_at(&self) -> Option<DateTime<Utc>> {
+        if self.read.value {
+            self.read.changed_at()
+        } else {
+            None
+        }
+    }
+
+    fn register_mut(&mut self, action: InboxAction) -> (&mut FlagRegister, bool) {
+        match action {
+            InboxAction::Read => (&mut self.read, true),
+            InboxAction::Unread => (&mut self.read, false),
+            InboxAction::Archive => (&mut self.archived, true),
+            InboxAction::Unarchive => (&mut self.archived, false),
+        }
+    }
+
+    /// Merged clock of both registers
+    fn clock(&self) -> VectorClock {
+        let mut clock = VectorClock::new();
+        for version in [&self.read.version, &self.archived.version].into_iter().flatten() {
+            clock.merge(&version.clock);
+        }
+        clock
+    }
+}
+
+/// Bridges `NotificationStore` read/archive changes through the offline sync engine
+pub struct InboxSync<S: Storage> {
+    engine: Arc<SyncEngine<S>>,
+    store: Arc<NotificationStore>,
+    node_id: NodeId,
+}
+
+impl<S: Storage> InboxSync<S> {
+    /// Create a bridge for the device identified by `node_id`
+    pub fn new(engine: Arc<SyncEngine<S>>, store: Arc<NotificationStore>, node_id: NodeId) -> Self {
+        Self {
+            engine,
+            store,
+            node_id,
+        }
+    }
+
+    /// Device node ID
+    pub fn node_id(&self) -> &NodeId {
+        &self.node_id
+    }
+
+    /// Mark a notification read on this device
+    pub async fn mark_read(&self, user_id: &str, notification_id: Uuid) -> InboxSyncResult<InboxState> {
+        self.record(user_id, notification_id, InboxAction::Read).await
+    }
+
+    /// Mark a notification unread on this device
+    pub async fn mark_unread(&self, user_id: &str, notification_id: Uuid) -> InboxSyncResult<InboxState> {
+        self.record(user_id, notification_id, InboxAction::Unread).await
+    }
+
+    /// Archive a notification on this device
+    pub async fn archive(&self, user_id: &str, notification_id: Uuid) -> InboxSyncResult<InboxState> {
+        self.record(user_id, notification_id, InboxAction::Archive).await
+    }
+
+    /// Restore an archived notification on this device
+    pub async fn unarchive(&self, user_id: &str, notification_id: Uuid) -> InboxSyncResult<InboxState> {
+        self.record(user_id, notification_id, InboxAction::Unarchive).await
+    }
+
+    /// Mark every unread notification of a user read, one operation each
+    pub async fn mark_all_read(&self, user_id: &str) -> InboxSyncResult<usize> {
+        let unread = self.store.get_unread(user_id, i64::MAX).await?;
+        for notification in &unread {
+            self.record(user_id, notification.id, InboxAction::Read).await?;
+        }
+        Ok(unread.len())
+    }
+
+    /// Apply a local change and queue it for the other devices
+    pub async fn record(
+        &self,
+        user_id: &str,
+        notification_id: Uuid,
+        action: InboxAction,
+    ) -> InboxSyncResult<InboxState> {
+        let mut state = self
+            .state(notification_id)
+            .await?
+            .unwrap_or_else(|| InboxState::new(notification_id, user_id.to_string()));
+
+        let (register, value) = state.register_mut(action);
+        let version = register.next_version(&self.node_id);
+        register.apply(value, &version);
+
+        self.persist(&state).await?;
+
+        let payload = InboxOperation {
+            notification_id,
+            user_id: user_id.to_string(),
+            action,
+        };
+        let operation = SyncOperation::new(
+            notification_id.to_string(),
+            INBOX_ENTITY_TYPE.to_string(),
+            OperationType::Patch,
+            serde_json::to_value(&payload)?,
+            version,
+        )
+        .with_priority(Priority::Low)
+        .with_tag(INBOX_SYNC_TAG.to_string());
+        self.engine.enqueue_operation(operation)?;
+
+        Ok(state)
+    }
+
+    /// Apply an operation received from another device
+    ///
+    /// Returns `true` if the local state changed. Operations for other
+    /// entity types are rejected; duplicates and operations superseded by
+    /// newer local changes are accepted and ignored.
+    pub async fn apply_remote(&self, operation: &SyncOperation) -> InboxSyncResult<bool> {
+        if operation.entity_type != INBOX_ENTITY_TYPE {
+            return Err(InboxSyncError::InvalidOperation(format!(
+                "unexpected entity type {}",
+                operation.entity_type
+            )));
+        }
+
+        let payload: InboxOperation = serde_json::from_value(operation.data.clone())?;
+        let mut state = self
+            .state(payload.notification_id)
+            .await?
+            .unwrap_or_else(|| InboxState::new(payload.notification_id, payload.user_id.clone()));
+
+        if state.user_id != payload.user_id {
+            return Err(InboxSyncError::InvalidOperation(format!(
+                "notification {} does not belong to {}",
+                payload.notification_id, payload.user_id
+            )));
+        }
+
+        let (register, value) = state.register_mut(payload.action);
+        let changed = register.apply(value, &operation.version);
+
+        // Persist even when the value is unchanged so the merged clock is kept
+        self.persist(&state).await?;
+
+        if changed {
+            debug!(
+                "Applied {:?} on notification {} from node {}",
+                payload.action, payload.notification_id, operation.version.node_id
+            );
+        }
+
+        Ok(changed)
+    }
+
+    /// Apply a batch of remote operations, skipping non-inbox operations
+    ///
+    /// Returns the number of operations that changed local state.
+    pub async fn apply_remote_batch(&self, operations: &[SyncOperation]) -> InboxSyncResult<usize> {
+        let mut changed = 0;
+        for operation in operations.iter().filter(|op| op.entity_type == INBOX_ENTITY_TYPE) {
+            if self.apply_remote(operation).await? {
+                changed += 1;
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Replicated state of a notification, if any change was recorded
+    pub async fn state(&self, notification_id: Uuid) -> InboxSyncResult<Option<InboxState>> {
+        let record = self
+            .engine
+            .storage()
+            .get(&notification_id.to_string(), INBOX_ENTITY_TYPE)
+            .await?;
+
+        match record {
+            Some(record) if !record.deleted => Ok(Some(serde_json::from_value(record.data)?)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Re-apply the replicated state to a notification that just arrived
+    ///
+    /// Returns `false` if no state was recorded or the notification is
+    /// still missing from the store.
+    pub async fn reconcile(&self, notification_id: Uuid) -> InboxSyncResult<bool> {
+        let Some(state) = self.state(notification_id).await? else {
+            return Ok(false);
+        };
+
+        let updated = self
+            .store
+            .set_state(
+                notification_id,
+                state.read.value,
+                state.read_at(),
+                state.archived.value,
+            )
+            .await?;
+        Ok(updated)
+    }
+
+    /// Write the converged state to offline storage and the notification store
+    async fn persist(&self, state: &InboxState) -> InboxSyncResult<()> {
+        let now = Utc::now();
+        let storage = self.engine.storage();
+        let entity_id = state.notification_id.to_string();
+        let created_at = storage
+            .get(&entity_id, INBOX_ENTITY_TYPE)
+            .await?
+            .map_or(now, |record| record.created_at);
+
+        storage
+            .put(StorageRecord {
+                entity_id,
+                entity_type: INBOX_ENTITY_TYPE.to_string(),
+                data: serde_json::to_value(state)?,
+                version: Version {
+                    clock: state.clock(),
+                    node_id: self.node_id.clone(),
+                    timestamp: now,
+                    content_hash: String::new(),
+                },
+                created_at,
+                updated_at: now,
+                deleted: false,
+            })
+            .await?;
+
+        // The notification itself may not have reached this device yet;
+        // `reconcile` applies the stored state once it does
+        self.store
+            .set_state(
+                state.notification_id,
+                state.read.value,
+                state.read_at(),
+                state.archived.value,
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// Write version from `node_id` after it has seen the given clock entries
+    fn version(node_id: &str, clock: &[(&str, u64)], at_secs: i64) -> Version {
+        let mut vector = VectorClock::new();
+        for (node, count) in clock {
+            for _ in 0..*count {
+                vector.increment(&node.to_string());
+            }
+        }
+
+        Version {
+            clock: vector,
+            node_id: node_id.to_string(),
+            timestamp: Utc.timestamp_opt(at_secs, 0).unwrap(),
+            content_hash: String::new(),
+        }
+    }
+
+    /// Apply writes in order to a fresh register
+    fn replay(writes: &[(bool, &Version)]) -> FlagRegister {
+        let mut register = FlagRegister::default();
+        for (value, version) in writes {
+            register.apply(*value, version);
+        }
+        register
+    }
+
+    #[test]
+    fn test_local_writes_supersede_each_other() {
+        let node = "phone".to_string();
+        let mut register = FlagRegister::default();
+
+        let read = register.next_version(&node);
+        assert!(register.apply(true, &read));
+        let unread = register.next_version(&node);
+        assert!(register.apply(false, &unread));
+
+        assert!(!register.value);
+        assert_eq!(register.version.as_ref().unwrap().clock.get(&node), 2);
+    }
+
+    #[test]
+    fn test_replayed_operation_is_ignored() {
+        let read = version("phone", &[("phone", 1)], 10);
+        let unread = version("phone", &[("phone", 2)], 20);
+
+        let mut register = replay(&[(true, &read), (false, &unread)]);
+        assert!(!register.apply(false, &unread));
+        assert!(!register.apply(true, &read));
+        assert!(!register.value);
+        assert_eq!(register.changed_at(), Some(unread.timestamp));
+    }
+
+    #[test]
+    fn test_concurrent_writes_converge() {
+        let read_on_phone = version("phone", &[("phone", 1)], 10);
+        let unread_on_laptop = version("laptop", &[("laptop", 1)], 20);
+
+        let phone = replay(&[(true, &read_on_phone), (false, &unread_on_laptop)]);
+        let laptop = replay(&[(false, &unread_on_laptop), (true, &read_on_phone)]);
+
+        // The later write wins on both devices, and both have seen every write
+        assert!(!phone.value);
+        assert!(!laptop.value);
+        let (phone, laptop) = (phone.version.unwrap(), laptop.version.unwrap());
+        assert_eq!(phone.node_id, "laptop");
+        assert_eq!(phone.clock, laptop.clock);
+        assert_eq!(phone.clock.get(&"phone".to_string()), 1);
+        assert_eq!(phone.clock.get(&"laptop".to_string()), 1);
+    }
+
+    #[test]
+    fn test_concurrent_writes_at_same_time_broken_by_node_id() {
+        let archive_on_phone = version("phone", &[("phone", 1)], 10);
+        let unarchive_on_laptop = version("laptop", &[("laptop", 1)], 10);
+
+        let phone = replay(&[(true, &archive_on_phone), (false, &unarchive_on_laptop)]);
+        let laptop = replay(&[(false, &unarchive_on_laptop), (true, &archive_on_phone)]);
+
+        assert!(phone.value);
+        assert!(laptop.value);
+        assert_eq!(phone.version.unwrap().node_id, "phone");
+        assert_eq!(laptop.version.unwrap().node_id, "phone");
+    }
+
+    #[test]
+    fn test_causal_write_wins_over_clock_skew() {
+        let read_on_phone = version("phone", &[("phone", 1)], 100);
+        // The laptop saw the read before marking the notification unread,
+        // but its wall clock is behind
+        let unread_on_laptop = version("laptop", &[("phone", 1), ("laptop", 1)], 50);
+
+        let register = replay(&[(true, &read_on_phone), (false, &unread_on_laptop)]);
+        assert!(!register.value);
+        assert_eq!(register.changed_at(), Some(unread_on_laptop.timestamp));
+    }
+
+    #[test]
+    fn test_inbox_state_flags() {
+        let node = "phone".to_string();
+        let mut state = InboxState::new(Uuid::new_v4(), "user-1".to_string());
+        assert_eq!(state.read_at(), None);
+
+        for action in [InboxAction::Read, InboxAction::Archive] {
+            let (register, value) = state.register_mut(action);
+            let version = register.next_version(&node);
+            register.apply(value, &version);
+        }
+        assert!(state.read.value);
+        assert!(state.archived.value);
+        assert_eq!(state.read_at(), state.read.changed_at());
+        assert_eq!(state.clock().get(&node), 1);
+
+        let (register, value) = state.register_mut(InboxAction::Unread);
+        let version = register.next_version(&node);
+        register.apply(value, &version);
+        assert_eq!(state.read_at(), None);
+        assert!(state.archived.value);
+    }
+}
@@ -0,0 +1,524 @@
+//! Job progress bridge
+//!
+//! Job progress is tracked locally by [`ProgressReporter`]. This module
+//! forwards those updates to the streaming event bus so dashboards can follow
+//! running jobs, and publishes a final event when a [`JobExecutor`] finishes
+//! a job. Job types can additionally be configured to notify users on
+//! completion or failure, with the notification text rendered from a
+//! template and delivered through the [`NotificationDispatcher`], so channel
+//! retries, rate limits and delivery tracking apply.
+
+use accuscene_jobs::executor::JobExecutor;
+use accuscene_jobs::progress::{JobProgress, ProgressReporter};
+use accuscene_jobs::result::{JobResult, JobResultStatus};
+use accuscene_notifications::{
+    Notification, NotificationCategory, NotificationDispatcher, NotificationError,
+    NotificationLevel, TemplateEngine,
+};
+use accuscene_streaming::bus::EventBus;
+use accuscene_streaming::{Event, EventMetadata, EventPayload, EventType, StreamingError};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+
+/// Streaming event type for progress updates
+pub const JOB_PROGRESS_EVENT: &str = "job_progress";
+
+/// Streaming event type for finished jobs
+pub const JOB_FINISHED_EVENT: &str = "job_finished";
+
+/// Job progress bridge error
+#[derive(Debug, thiserror::Error)]
+pub enum JobBridgeError {
+    /// Publishing to the event bus failed
+    #[error("Streaming error: {0}")]
+    Streaming(#[from] StreamingError),
+
+    /// Rendering a notification failed
+    #[error("Notification error: {0}")]
+    Notification(#[from] NotificationError),
+}
+
+/// Job progress bridge result type
+pub type JobBridgeResult<T> = Result<T, JobBridgeError>;
+
+/// Notification settings for one job type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobNotificationRule {
+    /// User IDs notified about the job
+    pub recipients: Vec<String>,
+    /// Names of the channels used for delivery
+    pub channels: Vec<String>,
+    /// Template rendered when the job completes
+    pub completion_template: Option<String>,
+    /// Template rendered when the job fails
+    pub failure_template: Option<String>,
+    /// Category of the notifications
+    pub category: NotificationCategory,
+}
+
+impl JobNotificationRule {
+    /// Create a rule notifying the given users
+    #[must_use]
+    pub fn new(recipients: Vec<String>) -> Self {
+        Self {
+            recipients,
+            channels: Vec::new(),
+            completion_template: None,
+            failure_template: None,
+            category: NotificationCategory::System,
+        }
+    }
+
+    /// Deliver through a channel
+    #[must_use]
+    pub fn with_channel(mut self, channel: impl Into<String>) -> Self {
+        self.channels.push(channel.into());
+        self
+    }
+
+    /// Notify on completion using a template
+    #[must_use]
+    pub fn on_completion(mut self, template_id: impl Into<String>) -> Self {
+        self.completion_template = Some(template_id.into());
+        self
+    }
+
+    /// Notify on failure using a template
+    #[must_use]
+    pub fn on_failure(mut self, template_id: impl Into<String>) -> Self {
+        self.failure_template = Some(template_id.into());
+        self
+    }
+
+    /// Set the notification category
+    #[must_use]
+    pub fn with_category(mut self, category: NotificationCategory) -> Self {
+        self.category = category;
+        self
+    }
+}
+
+/// Job update queued for the publishing task
+enum JobUpdate {
+    Progress { job_type: String, progress: JobProgress },
+    Finished { job_type: String, result: JobResult },
+}
+
+/// Forwards job progress to the event bus and notifies on job completion
+pub struct JobProgressBridge {
+    event_bus: Arc<dyn EventBus>,
+    templates: Option<Arc<TemplateEngine>>,
+    dispatcher: Option<Arc<NotificationDispatcher>>,
+    rules: DashMap<String, JobNotificationRule>,
+    updates: mpsc::UnboundedSender<JobUpdate>,
+    receiver: Mutex<Option<mpsc::UnboundedReceiver<JobUpdate>>>,
+}
+
+impl JobProgressBridge {
+    /// Create a bridge publishing to an event bus
+    pub fn new(event_bus: Arc<dyn EventBus>) -> Self {
+        let (updates, receiver) = mpsc::unbounded_channel();
+
+        Self {
+            event_bus,
+            templates: None,
+            dispatcher: None,
+            rules: DashMap::new(),
+            updates,
+            receiver: Mutex::new(Some(receiver)),
+        }
+    }
+
+    /// Render notifications with a template engine
+    #[must_use]
+    pub fn with_templates(mut self, templates: Arc<TemplateEngine>) -> Self {
+        self.templates = Some(templates);
+        self
+    }
+
+    /// Deliver notifications through a dispatcher
+    #[must_use]
+    pub fn with_dispatcher(mut self, dispatcher: Arc<NotificationDispatcher>) -> Self {
+        self.dispatcher = Some(dispatcher);
+        self
+    }
+
+    /// Configure notifications for a job type
+    pub fn set_rule(&self, job_type: impl Into<String>, rule: JobNotificationRule) {
+        self.rules.insert(job_type.into(), rule);
+    }
+
+    /// Stop notifying about a job type
+    pub fn remove_rule(&self, job_type: &str) -> Option<JobNotificationRule> {
+        self.rules.remove(job_type).map(|(_, rule)| rule)
+    }
+
+    /// Forward updates from a progress reporter
+    ///
+    /// Updates are queued and published in order by the task returned from
+    /// [`JobProgressBridge::start`].
+    pub fn attach(&self, job_type: impl Into<String>, reporter: &ProgressReporter) {
+        let job_type = job_type.into();
+        let updates = self.updates.clone();
+
+        reporter.add_callback(Arc::new(move |progress| {
+            let _ = updates.send(JobUpdate::Progress {
+                job_type: job_type.clone(),
+                progress,
+            });
+        }));
+    }
+
+    /// Publish and notify about the jobs an executor finishes
+    ///
+    /// The job name is used as the job type.
+    pub fn attach_executor(&self, executor: &JobExecutor) {
+        let updates = self.updates.clone();
+
+        executor.add_completion_callback(Arc::new(move |job_type, result| {
+            let _ = updates.send(JobUpdate::Finished {
+                job_type: job_type.to_string(),
+                result: result.clone(),
+            });
+        }));
+    }
+
+    /// Start publishing queued updates until `token` is cancelled
+    ///
+    /// Updates queued before cancellation are still published. Register the
+    /// returned handle with a shutdown scope so shutdown waits for them.
+    /// Returns `None` if the bridge was already started.
+    pub fn start(self: Arc<Self>, token: CancellationToken) -> Option<JoinHandle<()>> {
+        let mut receiver = self.receiver.lock().take()?;
+
+        Some(tokio::spawn(async move {
+            loop {
+                let update = tokio::select! {
+                    update = receiver.recv() => update,
+                    () = token.cancelled() => None,
+                };
+                let Some(update) = update else {
+                    break;
+                };
+                self.handle(update).await;
+            }
+
+            while let Ok(update) = receiver.try_recv() {
+                self.handle(update).await;
+            }
+        }))
+    }
+
+    async fn handle(&self, update: JobUpdate) {
+        match update {
+            JobUpdate::Progress { job_type, progress } => {
+                if let Err(e) = self.publish_progress(&job_type, &progress).await {
+                    warn!("Publishing progress for job {} failed: {}", progress.job_id, e);
+                }
+            }
+            JobUpdate::Finished { job_type, result } => {
+                if let Err(e) = self.job_finished(&job_type, &result).await {
+                    warn!("Publishing completion of job {} failed: {}", result.job_id, e);
+                }
+            }
+        }
+    }
+
+    /// Publish a progress update
+    pub async fn publish_progress(
+        &self,
+        job_type: &str,
+        progress: &JobProgress,
+    ) -> JobBridgeResult<()> {
+        let payload = serde_json::json!({
+            "job_id": progress.job_id,
+            "job_type": job_type,
+            "current": progress.current,
+            "total": progress.total,
+            "percentage": progress.percentage,
+            "message": progress.message,
+            "metadata": progress.metadata,
+            "updated_at": progress.updated_at,
+        });
+
+        self.publish(JOB_PROGRESS_EVENT, &progress.job_id, payload).await
+    }
+
+    /// Publish a finished job and notify according to its job type's rule
+    pub async fn job_finished(&self, job_type: &str, result: &JobResult) -> JobBridgeResult<()> {
+        let payload = serde_json::json!({
+            "job_id": result.job_id,
+            "job_type": job_type,
+            "status": result.status,
+            "error": result.error,
+            "duration_ms": result.duration_ms,
+            "completed_at": result.completed_at,
+        });
+        self.publish(JOB_FINISHED_EVENT, &result.job_id, payload).await?;
+
+        let Some(rule) = self.rules.get(job_type).map(|rule| rule.clone()) else {
+            return Ok(());
+        };
+
+        let (template_id, level) = match result.status {
+            JobResultStatus::Success => (&rule.completion_template, NotificationLevel::Success),
+            JobResultStatus::Partial => (&rule.completion_template, NotificationLevel::Warning),
+            JobResultStatus::Failure => (&rule.failure_template, NotificationLevel::Error),
+        };
+        let Some(template_id) = template_id else {
+            return Ok(());
+        };
+        let Some(dispatcher) = &self.dispatcher else {
+            debug!("No dispatcher, not notifying about job {}", result.job_id);
+            return Ok(());
+        };
+
+        for recipient in &rule.recipients {
+            let notification = self
+                .render(recipient, job_type, result, template_id, level, &rule)
+                .await?;
+            if let Err(e) = dispatcher.enqueue(notification, rule.channels.clone()).await {
+                warn!(
+                    "Queueing notification about job {} for {} failed: {}",
+                    result.job_id, recipient, e
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn publish(
+        &self,
+        event_type: &str,
+        job_id: &str,
+        payload: serde_json::Value,
+    ) -> JobBridgeResult<()> {
+        let metadata = EventMetadata {
+            correlation_id: Some(job_id.to_string()),
+            ..EventMetadata::default()
+        };
+        let event = Event::with_metadata(
+            EventType::Custom(event_type.to_string()),
+            EventPayload::Json(payload),
+            metadata,
+        );
+
+        self.event_bus.publish(event).await?;
+        Ok(())
+    }
+
+    async fn render(
+        &self,
+        recipient: &str,
+        job_type: &str,
+        result: &JobResult,
+        template_id: &str,
+        level: NotificationLevel,
+        rule: &JobNotificationRule,
+    ) -> JobBridgeResult<Notification> {
+        let mut notification = Notification::new(
+            recipient,
+            level,
+            format!("Job {job_type} {:?}", result.status),
+            result
+                .error
+                .clone()
+                .unwrap_or_else(|| format!("Job {} finished", result.job_id)),
+        );
+        notification.category = rule.category.clone();
+        notification.related_entity_id = Some(result.job_id.clone());
+        notification.related_entity_type = Some("job".to_string());
+        notification.template_vars = [
+            ("job_id".to_string(), serde_json::json!(result.job_id)),
+            ("job_type".to_string(), serde_json::json!(job_type)),
+            ("status".to_string(), serde_json::json!(result.status)),
+            ("error".to_string(), serde_json::json!(result.error)),
+            ("output".to_string(), result.output.clone()),
+            ("duration_ms".to_string(), serde_json::json!(result.duration_ms)),
+        ]
+        .into_iter()
+        .collect();
+
+        if let Some(templates) = &self.templates {
+            templates.apply_template(&mut notification, template_id).await?;
+        } else {
+            debug!("No template engine, sending default text for job {}", result.job_id);
+        }
+
+        Ok(notification)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use accuscene_jobs::job::{Job, JobContext, PhysicsSimulationJob};
+    use accuscene_jobs::sandbox::ResourceLimits;
+    use accuscene_jobs::retry::RetryPolicy;
+    use accuscene_notifications::config::DispatcherConfig;
+    use accuscene_notifications::{
+        Channel, ChannelRegistry, DeliveryState, DeliveryStatus, PreferenceManager,
+    };
+    use accuscene_streaming::bus::memory::MemoryEventBus;
+    use accuscene_streaming::EventFilter;
+    use async_trait::async_trait;
+    use futures::StreamExt;
+    use std::time::Duration;
+
+    /// Channel remembering what it delivered
+    #[derive(Default)]
+    struct RecordingChannel {
+        delivered: Mutex<Vec<Notification>>,
+    }
+
+    #[async_trait]
+    impl Channel for RecordingChannel {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        async fn deliver(
+            &self,
+            notification: &Notification,
+        ) -> accuscene_notifications::Result<DeliveryStatus> {
+            self.delivered.lock().push(notification.clone());
+            Ok(DeliveryStatus {
+                notification_id: notification.id,
+                channel: "recording".to_string(),
+                status: DeliveryState::Delivered,
+                attempts: 1,
+                last_attempt_at: None,
+                delivered_at: None,
+                error_message: None,
+            })
+        }
+
+        fn supports(&self, _notification: &Notification) -> bool {
+            true
+        }
+
+        fn is_enabled(&self) -> bool {
+            true
+        }
+    }
+
+    async fn dispatcher(
+        channel: Arc<RecordingChannel>,
+        token: CancellationToken,
+    ) -> Arc<NotificationDispatcher> {
+        let mut registry = ChannelRegistry::new();
+        registry.register(channel);
+        // Preferences are only read when no channels are given
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/unused")
+            .unwrap();
+        let config = DispatcherConfig {
+            worker_count: 1,
+            ..DispatcherConfig::default()
+        };
+
+        let mut dispatcher = NotificationDispatcher::new(
+            config,
+            Arc::new(registry),
+            Arc::new(PreferenceManager::new(pool)),
+        );
+        dispatcher.set_shutdown_token(token);
+        dispatcher.start().await.unwrap();
+        Arc::new(dispatcher)
+    }
+
+    async fn run_job(executor: &JobExecutor) {
+        let job = Box::new(PhysicsSimulationJob::new(
+            "scenario-1".to_string(),
+            serde_json::json!({}),
+        ));
+        let context = Arc::new(JobContext::new(job.id().to_string(), "worker-1".to_string()));
+        executor.execute(job, context).await.unwrap();
+    }
+
+    async fn wait_for(channel: &RecordingChannel, count: usize) -> Vec<Notification> {
+        for _ in 0..200 {
+            if channel.delivered.lock().len() >= count {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        channel.delivered.lock().clone()
+    }
+
+    #[tokio::test]
+    async fn test_progress_is_forwarded_until_cancelled() {
+        let bus = Arc::new(MemoryEventBus::new());
+        let mut events = bus.subscribe(EventFilter::default()).await.unwrap();
+        let bridge = Arc::new(JobProgressBridge::new(bus));
+
+        let reporter = ProgressReporter::new("job-1".to_string(), 10);
+        bridge.attach("export", &reporter);
+        reporter.update(4, "halfway");
+
+        let token = CancellationToken::new();
+        let handle = Arc::clone(&bridge).start(token.clone()).unwrap();
+        assert!(Arc::clone(&bridge).start(token.clone()).is_none());
+
+        let event = events.next().await.unwrap();
+        assert_eq!(event.event_type, EventType::Custom(JOB_PROGRESS_EVENT.to_string()));
+        assert_eq!(event.metadata.correlation_id.as_deref(), Some("job-1"));
+        let EventPayload::Json(payload) = event.payload else {
+            panic!("expected a JSON payload");
+        };
+        assert_eq!(payload["job_type"], "export");
+        assert_eq!(payload["current"], 4);
+
+        token.cancel();
+        tokio::time::timeout(Duration::from_secs(1), handle)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_finished_jobs_notify_through_dispatcher() {
+        let token = CancellationToken::new();
+        let channel = Arc::new(RecordingChannel::default());
+        let bridge = Arc::new(
+            JobProgressBridge::new(Arc::new(MemoryEventBus::new()))
+                .with_dispatcher(dispatcher(Arc::clone(&channel), token.clone()).await),
+        );
+        bridge.set_rule(
+            "physics_simulation",
+            JobNotificationRule::new(vec!["user-1".to_string()])
+                .with_channel("recording")
+                .on_completion("job_done")
+                .on_failure("job_failed"),
+        );
+
+        let executor = JobExecutor::new(RetryPolicy::none());
+        bridge.attach_executor(&executor);
+        let handle = Arc::clone(&bridge).start(token.clone()).unwrap();
+
+        run_job(&executor).await;
+        let delivered = wait_for(&channel, 1).await;
+        assert_eq!(delivered.len(), 1);
+        assert_eq!(delivered[0].user_id, "user-1");
+        assert_eq!(delivered[0].level, NotificationLevel::Success);
+        assert_eq!(delivered[0].template_vars["job_type"], "physics_simulation");
+
+        let executor = executor
+            .with_limits(ResourceLimits::none().with_wall_time(Duration::from_millis(1)));
+        run_job(&executor).await;
+        let delivered = wait_for(&channel, 2).await;
+        assert_eq!(delivered.len(), 2);
+        assert_eq!(delivered[1].level, NotificationLevel::Error);
+        assert_eq!(delivered[1].related_entity_type.as_deref(), Some("job"));
+
+        token.cancel();
+        handle.await.unwrap();
+    }
+}
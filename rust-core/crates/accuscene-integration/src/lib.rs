@@ -49,6 +49,21 @@
 //! - Cross-crate event system
 //! - Service registry for dependency injection
 //! - Aggregated health checks
+//! - Long-running operation tracking
+//! - Job progress streaming and completion notifications
+//! - User and role administration
+//! - SCIM provisioning of users into the user repository
+//! - RBAC roles from LDAP/Active Directory group membership at SSO login
+//! - GDPR data subject requests over user accounts and the party registry
+//! - Per-tenant usage metering and quotas
+//! - Encrypted tenant import and export
+//! - Cold-storage tiering of closed cases with transparent retrieval
+//! - Notification inbox state sync across devices
+//! - Entity follow subscriptions with event fan-out
+//! - Change data capture feed from the database to the event buses
+//! - Row-level security for repositories backed by RBAC/ABAC authorization
+//! - Organization tenancy with audited cross-tenant row access
+//! - Structured shutdown with cancellation scopes and drain reports
 //!
 //! ## Usage
 //!
@@ -84,12 +99,26 @@
 // Public Modules
 // ============================================================================
 
+pub mod admin;
+pub mod change_feed;
 pub mod config;
+pub mod dsar;
 pub mod events;
 pub mod facade;
 pub mod health;
+pub mod inbox_sync;
+pub mod job_progress;
+pub mod metering;
+pub mod operations;
 pub mod registry;
+pub mod row_policy;
 pub mod runtime;
+pub mod scim;
+pub mod shutdown;
+pub mod sso_roles;
+pub mod tenant_transfer;
+pub mod tiering;
+pub mod watch;
 
 // ============================================================================
 // Re-exports from Core Crates
@@ -290,7 +319,12 @@ pub mod prelude {
     pub use crate::events::{Event, EventBus, EventHandler};
     pub use crate::facade::Facade;
     pub use crate::health::{HealthCheck, HealthStatus};
+    pub use crate::job_progress::{JobNotificationRule, JobProgressBridge};
+    pub use crate::metering::{MeteringService, QuotaPolicy, UsageMetric, UsagePeriod};
+    pub use crate::operations::{OperationRecord, OperationSpec, OperationStatus, OperationsService};
     pub use crate::registry::{Registry, ServiceDescriptor};
     pub use crate::runtime::Runtime;
+    pub use crate::shutdown::{ShutdownController, ShutdownReport, ShutdownScope};
+    pub use crate::tiering::{Availability, TieringPolicy, TieringService};
     pub use crate::{BuildInfo, ENTERPRISE_VERSION, VERSION};
 }
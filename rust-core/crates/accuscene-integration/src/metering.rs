@@ -0,0 +1,667 @@
+//! Usage metering and quota reporting
+//!
+//! This module counts billable usage per tenant: simulation run time,
+//! storage bytes, notifications sent and API calls. Usage is fed from
+//! telemetry counters and job metrics, rolled up per calendar month and can
+//! be exported as CSV or JSON for billing. Soft and hard quotas are checked
+//! on every recording; hooks are notified when a limit is crossed and hard
+//! limits reject the usage outright.
+
+use accuscene_jobs::metrics::{JobMetric, JobMetricStatus};
+use accuscene_telemetry::metrics::Counter;
+use chrono::{DateTime, Datelike, Utc};
+use dashmap::DashMap;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Tenant identifier used for metering
+pub type TenantId = String;
+
+/// Metering error
+#[derive(Debug, thiserror::Error)]
+pub enum MeteringError {
+    /// Recording the usage would exceed the tenant's hard quota
+    #[error("Hard quota exceeded for tenant {tenant_id} on {metric}: {usage} > {limit}")]
+    QuotaExceeded {
+        tenant_id: TenantId,
+        metric: UsageMetric,
+        usage: f64,
+        limit: f64,
+    },
+
+    /// Serialization error
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+/// Metering result type
+pub type MeteringResult<T> = Result<T, MeteringError>;
+
+/// Billable usage metric
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageMetric {
+    /// Wall-clock run time of simulations and jobs (s)
+    SimulationSeconds,
+    /// Bytes of stored data
+    StorageBytes,
+    /// Notifications delivered
+    NotificationsSent,
+    /// API requests served
+    ApiCalls,
+}
+
+impl UsageMetric {
+    /// All metered metrics
+    pub const ALL: [UsageMetric; 4] = [
+        UsageMetric::SimulationSeconds,
+        UsageMetric::StorageBytes,
+        UsageMetric::NotificationsSent,
+        UsageMetric::ApiCalls,
+    ];
+
+    /// Get the metric name
+    pub fn as_str(&self) -> &str {
+        match self {
+            UsageMetric::SimulationSeconds => "simulation_seconds",
+            UsageMetric::StorageBytes => "storage_bytes",
+            UsageMetric::NotificationsSent => "notifications_sent",
+            UsageMetric::ApiCalls => "api_calls",
+        }
+    }
+
+    /// Whether the monthly rollup keeps the peak value rather than the sum
+    ///
+    /// Storage is a level, not a flow: billing uses the highest amount held
+    /// during the month.
+    pub fn is_peak(&self) -> bool {
+        matches!(self, UsageMetric::StorageBytes)
+    }
+}
+
+impl fmt::Display for UsageMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Calendar month used for rollups
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct UsagePeriod {
+    /// Year
+    pub year: i32,
+    /// Month (1-12)
+    pub month: u32,
+}
+
+impl UsagePeriod {
+    /// Create a period for a year and month
+    pub fn new(year: i32, month: u32) -> Self {
+        Self { year, month }
+    }
+
+    /// Period containing the given instant
+    pub fn of(time: DateTime<Utc>) -> Self {
+        Self::new(time.year(), time.month())
+    }
+
+    /// Current period
+    pub fn current() -> Self {
+        Self::of(Utc::now())
+    }
+}
+
+impl fmt::Display for UsagePeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}", self.year, self.month)
+    }
+}
+
+/// Rolled-up usage of one metric for a tenant and month
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageRecord {
+    /// Tenant ID
+    pub tenant_id: TenantId,
+    /// Metric
+    pub metric: UsageMetric,
+    /// Month
+    pub period: UsagePeriod,
+    /// Total (or peak, for level metrics)
+    pub value: f64,
+}
+
+/// Soft and hard limit for one metric
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct QuotaLimit {
+    /// Limit above which hooks are warned but usage is accepted
+    pub soft: Option<f64>,
+    /// Limit above which usage is rejected
+    pub hard: Option<f64>,
+}
+
+impl QuotaLimit {
+    /// Create a limit with only a soft threshold
+    pub fn soft(limit: f64) -> Self {
+        Self {
+            soft: Some(limit),
+            hard: None,
+        }
+    }
+
+    /// Create a limit with only a hard threshold
+    pub fn hard(limit: f64) -> Self {
+        Self {
+            soft: None,
+            hard: Some(limit),
+        }
+    }
+
+    /// Set the soft threshold
+    pub fn with_soft(mut self, limit: f64) -> Self {
+        self.soft = Some(limit);
+        self
+    }
+
+    /// Set the hard threshold
+    pub fn with_hard(mut self, limit: f64) -> Self {
+        self.hard = Some(limit);
+        self
+    }
+}
+
+/// Monthly quota policy
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QuotaPolicy {
+    /// Limits per metric
+    pub limits: BTreeMap<UsageMetric, QuotaLimit>,
+}
+
+impl QuotaPolicy {
+    /// Create an empty (unlimited) policy
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the limit for a metric
+    pub fn with_limit(mut self, metric: UsageMetric, limit: QuotaLimit) -> Self {
+        self.limits.insert(metric, limit);
+        self
+    }
+
+    /// Get the limit for a metric
+    pub fn limit(&self, metric: UsageMetric) -> Option<&QuotaLimit> {
+        self.limits.get(&metric)
+    }
+}
+
+/// Outcome of a quota check
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum QuotaStatus {
+    /// Usage is within all limits
+    WithinLimit,
+    /// Usage is above the soft limit
+    SoftLimitExceeded { usage: f64, limit: f64 },
+    /// Usage would be above the hard limit
+    HardLimitExceeded { usage: f64, limit: f64 },
+}
+
+impl QuotaStatus {
+    /// Check if usage is allowed
+    pub fn is_allowed(&self) -> bool {
+        !matches!(self, QuotaStatus::HardLimitExceeded { .. })
+    }
+}
+
+/// Hook notified when a tenant crosses a quota threshold
+pub trait QuotaHook: Send + Sync {
+    /// Called when usage first goes above the soft limit in a period
+    fn on_soft_limit(&self, _tenant_id: &str, _metric: UsageMetric, _usage: f64, _limit: f64) {}
+
+    /// Called when usage is rejected by the hard limit
+    fn on_hard_limit(&self, _tenant_id: &str, _metric: UsageMetric, _usage: f64, _limit: f64) {}
+}
+
+type UsageKey = (TenantId, UsageMetric, UsagePeriod);
+
+/// Per-tenant usage metering service
+pub struct MeteringService {
+    usage: DashMap<UsageKey, f64>,
+    policies: DashMap<TenantId, QuotaPolicy>,
+    default_policy: RwLock<QuotaPolicy>,
+    hooks: RwLock<Vec<Arc<dyn QuotaHook>>>,
+    counters: BTreeMap<UsageMetric, Counter>,
+    counter_offsets: DashMap<(TenantId, UsageMetric, String), f64>,
+    metered_jobs: DashMap<String, UsagePeriod>,
+}
+
+impl MeteringService {
+    /// Create a new metering service with no quotas
+    pub fn new() -> Self {
+        let counters = UsageMetric::ALL
+            .iter()
+            .map(|metric| {
+                (
+                    *metric,
+                    Counter::new(
+                        format!("usage_{}_total", metric.as_str()),
+                        format!("Metered {} across all tenants", metric.as_str()),
+                    ),
+                )
+            })
+            .collect();
+
+        Self {
+            usage: DashMap::new(),
+            policies: DashMap::new(),
+            default_policy: RwLock::new(QuotaPolicy::new()),
+            hooks: RwLock::new(Vec::new()),
+            counters,
+            counter_offsets: DashMap::new(),
+            metered_jobs: DashMap::new(),
+        }
+    }
+
+    /// Set the policy applied to tenants without their own policy
+    pub fn set_default_policy(&self, policy: QuotaPolicy) {
+        *self.default_policy.write() = policy;
+    }
+
+    /// Set a tenant's quota policy
+    pub fn set_policy(&self, tenant_id: impl Into<TenantId>, policy: QuotaPolicy) {
+        self.policies.insert(tenant_id.into(), policy);
+    }
+
+    /// Get the effective policy for a tenant
+    pub fn policy(&self, tenant_id: &str) -> QuotaPolicy {
+        self.policies
+            .get(tenant_id)
+            .map(|p| p.clone())
+            .unwrap_or_else(|| self.default_policy.read().clone())
+    }
+
+    /// Register a quota enforcement hook
+    pub fn add_hook(&self, hook: Arc<dyn QuotaHook>) {
+        self.hooks.write().push(hook);
+    }
+
+    /// Aggregate telemetry counter for a metric, across all tenants
+    pub fn counter(&self, metric: UsageMetric) -> &Counter {
+        &self.counters[&metric]
+    }
+
+    /// Check whether recording `amount` would be allowed, without recording it
+    pub fn check(&self, tenant_id: &str, metric: UsageMetric, amount: f64) -> QuotaStatus {
+        let current = self.usage(tenant_id, metric, UsagePeriod::current());
+        let projected = Self::apply(metric, current, amount);
+        self.evaluate(tenant_id, metric, projected)
+    }
+
+    /// Record usage for the current period
+    ///
+    /// Fails with [`MeteringError::QuotaExceeded`] when the hard limit would
+    /// be crossed; the usage is not recorded in that case.
+    pub fn record(&self, tenant_id: &str, metric: UsageMetric, amount: f64) -> MeteringResult<QuotaStatus> {
+        self.record_at(tenant_id, metric, amount, Utc::now())
+    }
+
+    /// Record usage at a specific time
+    pub fn record_at(
+        &self,
+        tenant_id: &str,
+        metric: UsageMetric,
+        amount: f64,
+        at: DateTime<Utc>,
+    ) -> MeteringResult<QuotaStatus> {
+        if amount < 0.0 {
+            return Ok(QuotaStatus::WithinLimit);
+        }
+
+        let key = (tenant_id.to_string(), metric, UsagePeriod::of(at));
+        let mut entry = self.usage.entry(key).or_insert(0.0);
+        let previous = *entry;
+        let projected = Self::apply(metric, previous, amount);
+
+        let status = self.evaluate(tenant_id, metric, projected);
+        match status {
+            QuotaStatus::HardLimitExceeded { usage, limit } => {
+                drop(entry);
+                warn!(
+                    "Tenant {} hit hard quota on {}: {} > {}",
+                    tenant_id, metric, usage, limit
+                );
+                for hook in self.hooks.read().iter() {
+                    hook.on_hard_limit(tenant_id, metric, usage, limit);
+                }
+                return Err(MeteringError::QuotaExceeded {
+                    tenant_id: tenant_id.to_string(),
+                    metric,
+                    usage,
+                    limit,
+                });
+            }
+            QuotaStatus::SoftLimitExceeded { usage, limit } => {
+                *entry = projected;
+                drop(entry);
+                if previous <= limit {
+                    for hook in self.hooks.read().iter() {
+                        hook.on_soft_limit(tenant_id, metric, usage, limit);
+                    }
+                }
+            }
+            QuotaStatus::WithinLimit => {
+                *entry = projected;
+            }
+        }
+
+        if !metric.is_peak() {
+            self.counters[&metric].increment_by(amount);
+        }
+        debug!("Metered {} {} for tenant {}", amount, metric, tenant_id);
+
+        Ok(status)
+    }
+
+    /// Record the run time of a finished job
+    ///
+    /// Jobs are billed by wall-clock duration. Each job is metered once;
+    /// repeated calls for the same job are ignored.
+    pub fn record_job(&self, tenant_id: &str, job: &JobMetric) -> MeteringResult<QuotaStatus> {
+        let finished = matches!(
+            job.status,
+            JobMetricStatus::Completed | JobMetricStatus::Failed | JobMetricStatus::Cancelled
+        );
+        if !finished {
+            return Ok(QuotaStatus::WithinLimit);
+        }
+        let at = job.completed_at.unwrap_or_else(Utc::now);
+        if self.metered_jobs.insert(job.job_id.clone(), UsagePeriod::of(at)).is_some() {
+            return Ok(QuotaStatus::WithinLimit);
+        }
+
+        let seconds = job.duration_ms as f64 / 1000.0;
+        self.record_at(tenant_id, UsageMetric::SimulationSeconds, seconds, at)
+    }
+
+    /// Meter the growth of a per-tenant telemetry counter since the last sync
+    pub fn sync_counter(&self, tenant_id: &str, metric: UsageMetric, counter: &Counter) -> MeteringResult<QuotaStatus> {
+        let key = (tenant_id.to_string(), metric, counter.name().to_string());
+        let current = counter.value();
+        let last = self.counter_offsets.get(&key).map(|v| *v).unwrap_or(0.0);
+
+        // A counter reset restarts the baseline
+        let delta = if current >= last { current - last } else { current };
+        let status = self.record(tenant_id, metric, delta)?;
+        self.counter_offsets.insert(key, current);
+        Ok(status)
+    }
+
+    /// Set the current storage level of a tenant
+    pub fn set_storage_bytes(&self, tenant_id: &str, bytes: u64) -> MeteringResult<QuotaStatus> {
+        self.record(tenant_id, UsageMetric::StorageBytes, bytes as f64)
+    }
+
+    /// Get a tenant's usage of a metric in a period
+    pub fn usage(&self, tenant_id: &str, metric: UsageMetric, period: UsagePeriod) -> f64 {
+        self.usage
+            .get(&(tenant_id.to_string(), metric, period))
+            .map(|v| *v)
+            .unwrap_or(0.0)
+    }
+
+    /// Monthly rollup for a tenant
+    pub fn tenant_rollup(&self, tenant_id: &str, period: UsagePeriod) -> Vec<UsageRecord> {
+        UsageMetric::ALL
+            .iter()
+            .map(|metric| UsageRecord {
+                tenant_id: tenant_id.to_string(),
+                metric: *metric,
+                period,
+                value: self.usage(tenant_id, *metric, period),
+            })
+            .collect()
+    }
+
+    /// Monthly rollup for all tenants with recorded usage
+    pub fn rollup(&self, period: UsagePeriod) -> Vec<UsageRecord> {
+        let mut records: Vec<UsageRecord> = self
+            .usage
+            .iter()
+            .filter(|entry| entry.key().2 == period)
+            .map(|entry| {
+                let (tenant_id, metric, period) = entry.key().clone();
+                UsageRecord {
+                    tenant_id,
+                    metric,
+                    period,
+                    value: *entry.value(),
+                }
+            })
+            .collect();
+
+        records.sort_by(|a, b| {
+            a.tenant_id
+                .cmp(&b.tenant_id)
+                .then_with(|| a.metric.as_str().cmp(b.metric.as_str()))
+        });
+        records
+    }
+
+    /// Export a monthly rollup as JSON
+    pub fn export_json(&self, period: UsagePeriod) -> MeteringResult<String> {
+        Ok(serde_json::to_string_pretty(&self.rollup(period))?)
+    }
+
+    /// Export a monthly rollup as CSV
+    pub fn export_csv(&self, period: UsagePeriod) -> String {
+        let mut csv = String::from("tenant_id,period,metric,value\n");
+        for record in self.rollup(period) {
+            csv.push_str(&format!(
+                "{},{},{},{}\n",
+                Self::escape_csv(&record.tenant_id),
+                record.period,
+                record.metric,
+                record.value
+            ));
+        }
+        csv
+    }
+
+    /// Drop usage and metered jobs older than the given period
+    ///
+    /// Returns the number of usage records dropped.
+    pub fn purge_before(&self, period: UsagePeriod) -> usize {
+        self.metered_jobs.retain(|_, metered| *metered >= period);
+
+        let before = self.usage.len();
+        self.usage.retain(|key, _| key.2 >= period);
+        before - self.usage.len()
+    }
+
+    fn apply(metric: UsageMetric, current: f64, amount: f64) -> f64 {
+        if metric.is_peak() {
+            current.max(amount)
+        } else {
+            current + amount
+        }
+    }
+
+    fn evaluate(&self, tenant_id: &str, metric: UsageMetric, usage: f64) -> QuotaStatus {
+        let policy = self.policy(tenant_id);
+        let Some(limit) = policy.limit(metric) else {
+            return QuotaStatus::WithinLimit;
+        };
+
+        match (limit.hard, limit.soft) {
+            (Some(hard), _) if usage > hard => QuotaStatus::HardLimitExceeded { usage, limit: hard },
+            (_, Some(soft)) if usage > soft => QuotaStatus::SoftLimitExceeded { usage, limit: soft },
+            _ => QuotaStatus::WithinLimit,
+        }
+    }
+
+    fn escape_csv(value: &str) -> String {
+        if value.contains(',') || value.contains('"') || value.contains('\n') {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    }
+}
+
+impl Default for MeteringService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use parking_lot::Mutex;
+
+    /// Hook recording the thresholds it was told about
+    #[derive(Default)]
+    struct RecordingHook {
+        soft: Mutex<Vec<(String, UsageMetric, f64)>>,
+        hard: Mutex<Vec<(String, UsageMetric, f64)>>,
+    }
+
+    impl QuotaHook for RecordingHook {
+        fn on_soft_limit(&self, tenant_id: &str, metric: UsageMetric, usage: f64, _limit: f64) {
+            self.soft.lock().push((tenant_id.to_string(), metric, usage));
+        }
+
+        fn on_hard_limit(&self, tenant_id: &str, metric: UsageMetric, usage: f64, _limit: f64) {
+            self.hard.lock().push((tenant_id.to_string(), metric, usage));
+        }
+    }
+
+    fn march(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, day, 12, 0, 0).unwrap()
+    }
+
+    fn job(job_id: &str, duration_ms: u64, status: JobMetricStatus) -> JobMetric {
+        JobMetric {
+            job_id: job_id.to_string(),
+            job_name: "physics_simulation".to_string(),
+            started_at: march(1),
+            completed_at: Some(march(1)),
+            duration_ms,
+            status,
+            retry_count: 0,
+            limit_breaches: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_soft_and_hard_quotas() {
+        let metering = MeteringService::new();
+        let hook = Arc::new(RecordingHook::default());
+        metering.add_hook(hook.clone());
+        metering.set_policy(
+            "t1",
+            QuotaPolicy::new()
+                .with_limit(UsageMetric::ApiCalls, QuotaLimit::soft(10.0).with_hard(15.0)),
+        );
+
+        let status = metering.record("t1", UsageMetric::ApiCalls, 8.0).unwrap();
+        assert_eq!(status, QuotaStatus::WithinLimit);
+
+        let status = metering.record("t1", UsageMetric::ApiCalls, 4.0).unwrap();
+        assert_eq!(status, QuotaStatus::SoftLimitExceeded { usage: 12.0, limit: 10.0 });
+        // The soft hook fires once, when the limit is first crossed
+        metering.record("t1", UsageMetric::ApiCalls, 1.0).unwrap();
+        assert_eq!(*hook.soft.lock(), vec![("t1".to_string(), UsageMetric::ApiCalls, 12.0)]);
+
+        assert!(!metering.check("t1", UsageMetric::ApiCalls, 5.0).is_allowed());
+        let err = metering.record("t1", UsageMetric::ApiCalls, 5.0).unwrap_err();
+        assert!(matches!(
+            err,
+            MeteringError::QuotaExceeded { usage, limit, .. } if usage == 18.0 && limit == 15.0
+        ));
+        assert_eq!(hook.hard.lock().len(), 1);
+        // Rejected usage is not recorded
+        assert_eq!(metering.usage("t1", UsageMetric::ApiCalls, UsagePeriod::current()), 13.0);
+
+        // Tenants without a policy fall back to the unlimited default
+        assert!(metering.record("t2", UsageMetric::ApiCalls, 1e9).is_ok());
+    }
+
+    #[test]
+    fn test_rollup_sums_flows_and_keeps_storage_peak() {
+        let metering = MeteringService::new();
+        metering.record_at("t1", UsageMetric::ApiCalls, 3.0, march(1)).unwrap();
+        metering.record_at("t1", UsageMetric::ApiCalls, 4.0, march(20)).unwrap();
+        metering.record_at("t1", UsageMetric::StorageBytes, 500.0, march(1)).unwrap();
+        metering.record_at("t1", UsageMetric::StorageBytes, 900.0, march(10)).unwrap();
+        metering.record_at("t1", UsageMetric::StorageBytes, 200.0, march(20)).unwrap();
+
+        let period = UsagePeriod::new(2024, 3);
+        assert_eq!(metering.usage("t1", UsageMetric::ApiCalls, period), 7.0);
+        assert_eq!(metering.usage("t1", UsageMetric::StorageBytes, period), 900.0);
+        assert_eq!(metering.counter(UsageMetric::ApiCalls).value(), 7.0);
+
+        let rollup = metering.tenant_rollup("t1", period);
+        assert_eq!(rollup.len(), UsageMetric::ALL.len());
+        assert!(metering.rollup(UsagePeriod::new(2024, 4)).is_empty());
+    }
+
+    #[test]
+    fn test_record_job_meters_once_and_purges() {
+        let metering = MeteringService::new();
+        let period = UsagePeriod::new(2024, 3);
+
+        metering.record_job("t1", &job("j1", 1500, JobMetricStatus::Completed)).unwrap();
+        metering.record_job("t1", &job("j1", 1500, JobMetricStatus::Completed)).unwrap();
+        metering.record_job("t1", &job("j2", 9000, JobMetricStatus::Running)).unwrap();
+        assert_eq!(metering.usage("t1", UsageMetric::SimulationSeconds, period), 1.5);
+
+        assert_eq!(metering.purge_before(UsagePeriod::new(2024, 4)), 1);
+        assert!(metering.metered_jobs.is_empty());
+        assert_eq!(metering.usage("t1", UsageMetric::SimulationSeconds, period), 0.0);
+    }
+
+    #[test]
+    fn test_sync_counter_handles_resets() {
+        let metering = MeteringService::new();
+        let counter = Counter::new("tenant_api_calls", "API calls");
+        let period = UsagePeriod::current();
+
+        counter.increment_by(5.0);
+        metering.sync_counter("t1", UsageMetric::ApiCalls, &counter).unwrap();
+        counter.increment_by(3.0);
+        metering.sync_counter("t1", UsageMetric::ApiCalls, &counter).unwrap();
+        assert_eq!(metering.usage("t1", UsageMetric::ApiCalls, period), 8.0);
+
+        // After a reset the new value is all growth since the last sync
+        counter.reset();
+        counter.increment_by(2.0);
+        metering.sync_counter("t1", UsageMetric::ApiCalls, &counter).unwrap();
+        assert_eq!(metering.usage("t1", UsageMetric::ApiCalls, period), 10.0);
+    }
+
+    #[test]
+    fn test_exports() {
+        let metering = MeteringService::new();
+        metering.record_at("acme, inc", UsageMetric::ApiCalls, 2.0, march(1)).unwrap();
+        metering.record_at("beta", UsageMetric::NotificationsSent, 3.0, march(1)).unwrap();
+        let period = UsagePeriod::new(2024, 3);
+
+        assert_eq!(
+            metering.export_csv(period),
+            "tenant_id,period,metric,value\n\
+             \"acme, inc\",2024-03,api_calls,2\n\
+             beta,2024-03,notifications_sent,3\n"
+        );
+
+        let records: Vec<UsageRecord> =
+            serde_json::from_str(&metering.export_json(period).unwrap()).unwrap();
+        assert_eq!(records, metering.rollup(period));
+        assert_eq!(records[1].metric, UsageMetric::NotificationsSent);
+    }
+}
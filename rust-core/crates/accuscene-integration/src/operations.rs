@@ -0,0 +1,694 @@
+//! Long-running operation (LRO) service
+//!
+//! This module provides a consistent async contract for simulations, imports,
+//! exports and report generation. Every long task is tracked by an operation
+//! record that clients can poll for status, progress and partial results.
+//! State changes are published on the event bus and, when configured,
+//! delivered to webhooks. Cancelling an operation propagates to the control
+//! handle of the running work, typically a cancellation token it polls.
+
+use crate::events::{Event, EventBus, EventMetadata, EventType};
+use accuscene_jobs::progress::JobProgress;
+use accuscene_notifications::{Channel, Notification, NotificationLevel};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+/// Operation identifier
+pub type OperationId = Uuid;
+
+/// Event type used for all operation events on the bus
+pub const OPERATION_EVENT_TYPE: &str = "operation";
+
+/// Operation error
+#[derive(Debug, thiserror::Error)]
+pub enum OperationError {
+    /// Operation not found
+    #[error("Operation not found: {0}")]
+    NotFound(OperationId),
+
+    /// Operation already finished
+    #[error("Operation {0} is already in terminal state {1:?}")]
+    AlreadyFinished(OperationId, OperationStatus),
+
+    /// Cancellation failed
+    #[error("Cancellation failed: {0}")]
+    CancellationFailed(String),
+
+    /// Event publishing failed
+    #[error("Event error: {0}")]
+    Event(#[from] crate::events::EventError),
+}
+
+/// Operation result type
+pub type OperationResult<T> = Result<T, OperationError>;
+
+/// Kind of long-running operation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationKind {
+    /// Physics simulation
+    Simulation,
+    /// Data import
+    Import,
+    /// Data export
+    Export,
+    /// Report generation
+    ReportGeneration,
+    /// Custom operation kind
+    Custom(String),
+}
+
+/// Operation status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OperationStatus {
+    /// Operation is created but not yet running
+    Pending,
+    /// Operation is running
+    Running,
+    /// Operation completed successfully
+    Succeeded,
+    /// Operation failed
+    Failed,
+    /// Operation was cancelled
+    Cancelled,
+}
+
+impl OperationStatus {
+    /// Check if the status is terminal
+    pub fn is_terminal(self) -> bool {
+        matches!(
+            self,
+            OperationStatus::Succeeded | OperationStatus::Failed | OperationStatus::Cancelled
+        )
+    }
+}
+
+/// Operation record returned to polling clients
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationRecord {
+    /// Operation ID
+    pub id: OperationId,
+
+    /// Operation kind
+    pub kind: OperationKind,
+
+    /// Human readable name
+    pub name: String,
+
+    /// Owner (user ID) of the operation
+    pub owner: Option<String>,
+
+    /// Current status
+    pub status: OperationStatus,
+
+    /// Progress percentage (0-100)
+    pub progress: f64,
+
+    /// Latest progress message
+    pub message: String,
+
+    /// Partial results published while running
+    pub partial_results: Vec<serde_json::Value>,
+
+    /// Final result
+    pub result: Option<serde_json::Value>,
+
+    /// Error message if failed
+    pub error: Option<String>,
+
+    /// Underlying job ID, if any
+    pub job_id: Option<String>,
+
+    /// Webhook URL notified on state changes
+    pub webhook_url: Option<String>,
+
+    /// Creation timestamp
+    pub created_at: DateTime<Utc>,
+
+    /// Last update timestamp
+    pub updated_at: DateTime<Utc>,
+
+    /// Completion timestamp
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Specification for a new operation
+#[derive(Debug, Clone)]
+pub struct OperationSpec {
+    /// Operation kind
+    pub kind: OperationKind,
+    /// Human readable name
+    pub name: String,
+    /// Owner (user ID)
+    pub owner: Option<String>,
+    /// Underlying job ID
+    pub job_id: Option<String>,
+    /// Webhook URL
+    pub webhook_url: Option<String>,
+}
+
+impl OperationSpec {
+    /// Create a new operation spec
+    pub fn new(kind: OperationKind, name: impl Into<String>) -> Self {
+        Self {
+            kind,
+            name: name.into(),
+            owner: None,
+            job_id: None,
+            webhook_url: None,
+        }
+    }
+
+    /// Set the owner
+    pub fn with_owner(mut self, owner: impl Into<String>) -> Self {
+        self.owner = Some(owner.into());
+        self
+    }
+
+    /// Set the underlying job ID
+    pub fn with_job_id(mut self, job_id: impl Into<String>) -> Self {
+        self.job_id = Some(job_id.into());
+        self
+    }
+
+    /// Set the webhook URL
+    pub fn with_webhook(mut self, url: impl Into<String>) -> Self {
+        self.webhook_url = Some(url.into());
+        self
+    }
+}
+
+/// Control handle used to cancel the work behind an operation
+#[async_trait]
+pub trait OperationControl: Send + Sync {
+    /// Request cancellation of the underlying work
+    async fn cancel(&self) -> OperationResult<()>;
+}
+
+#[async_trait]
+impl OperationControl for tokio::task::AbortHandle {
+    async fn cancel(&self) -> OperationResult<()> {
+        self.abort();
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OperationControl for CancellationToken {
+    async fn cancel(&self) -> OperationResult<()> {
+        CancellationToken::cancel(self);
+        Ok(())
+    }
+}
+
+/// Operation event action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationAction {
+    /// Operation created
+    Created,
+    /// Operation started running
+    Started,
+    /// Progress updated
+    Progress,
+    /// Partial result published
+    PartialResult,
+    /// Operation completed successfully
+    Completed,
+    /// Operation failed
+    Failed,
+    /// Operation cancelled
+    Cancelled,
+}
+
+/// Operation event published on the event bus
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationEvent {
+    /// Event metadata
+    pub metadata: EventMetadata,
+
+    /// Event action
+    pub action: OperationAction,
+
+    /// Snapshot of the operation at the time of the event
+    pub operation: OperationRecord,
+}
+
+impl OperationEvent {
+    /// Create a new operation event
+    pub fn new(action: OperationAction, operation: OperationRecord) -> Self {
+        Self {
+            metadata: EventMetadata::new(OPERATION_EVENT_TYPE.to_string())
+                .with_source("operations".to_string())
+                .with_correlation_id(operation.id),
+            action,
+            operation,
+        }
+    }
+}
+
+#[async_trait]
+impl Event for OperationEvent {
+    fn event_type(&self) -> EventType {
+        OPERATION_EVENT_TYPE.to_string()
+    }
+
+    fn timestamp(&self) -> chrono::DateTime<chrono::Utc> {
+        self.metadata.timestamp
+    }
+
+    fn metadata(&self) -> EventMetadata {
+        self.metadata.clone()
+    }
+}
+
+/// Service tracking long-running operations
+pub struct OperationsService {
+    operations: DashMap<OperationId, OperationRecord>,
+    controls: DashMap<OperationId, Arc<dyn OperationControl>>,
+    event_bus: Arc<EventBus>,
+    webhook_channel: Option<Arc<dyn Channel>>,
+}
+
+impl OperationsService {
+    /// Create a new operations service
+    pub fn new(event_bus: Arc<EventBus>) -> Self {
+        Self {
+            operations: DashMap::new(),
+            controls: DashMap::new(),
+            event_bus,
+            webhook_channel: None,
+        }
+    }
+
+    /// Set the channel used to deliver webhook callbacks
+    pub fn with_webhook_channel(mut self, channel: Arc<dyn Channel>) -> Self {
+        self.webhook_channel = Some(channel);
+        self
+    }
+
+    /// Create an operation record for a long task
+    pub async fn create(&self, spec: OperationSpec) -> OperationResult<OperationRecord> {
+        let now = Utc::now();
+        let record = OperationRecord {
+            id: Uuid::new_v4(),
+            kind: spec.kind,
+            name: spec.name,
+            owner: spec.owner,
+            status: OperationStatus::Pending,
+            progress: 0.0,
+            message: String::new(),
+            partial_results: Vec::new(),
+            result: None,
+            error: None,
+            job_id: spec.job_id,
+            webhook_url: spec.webhook_url,
+            created_at: now,
+            updated_at: now,
+            completed_at: None,
+        };
+
+        self.operations.insert(record.id, record.clone());
+        info!("Operation created: {} ({:?})", record.id, record.kind);
+
+        self.emit(OperationAction::Created, record.clone()).await?;
+        Ok(record)
+    }
+
+    /// Attach the control handle used for cancellation
+    pub fn attach_control(&self, id: OperationId, control: Arc<dyn OperationControl>) {
+        self.controls.insert(id, control);
+    }
+
+    /// Attach a cancellation token for work that polls for cancellation
+    ///
+    /// The running work must observe the returned token; cancelling the
+    /// operation only signals it and does not touch any schedule that
+    /// started the work.
+    pub fn cancellation_token(&self, id: OperationId) -> CancellationToken {
+        let token = CancellationToken::new();
+        self.attach_control(id, Arc::new(token.clone()));
+        token
+    }
+
+    /// Poll an operation
+    pub fn get(&self, id: OperationId) -> OperationResult<OperationRecord> {
+        self.operations
+            .get(&id)
+            .map(|r| r.clone())
+            .ok_or(OperationError::NotFound(id))
+    }
+
+    /// List operations, optionally filtered by owner
+    pub fn list(&self, owner: Option<&str>) -> Vec<OperationRecord> {
+        let mut records: Vec<_> = self
+            .operations
+            .iter()
+            .filter(|r| owner.is_none() || r.owner.as_deref() == owner)
+            .map(|r| r.clone())
+            .collect();
+        records.sort_by_key(|r| std::cmp::Reverse(r.created_at));
+        records
+    }
+
+    /// Mark an operation as running
+    pub async fn start(&self, id: OperationId) -> OperationResult<OperationRecord> {
+        let record = self.update(id, |r| r.status = OperationStatus::Running)?;
+        self.emit(OperationAction::Started, record.clone()).await?;
+        Ok(record)
+    }
+
+    /// Report progress for an operation
+    pub async fn report_progress(
+        &self,
+        id: OperationId,
+        percentage: f64,
+        message: impl Into<String>,
+    ) -> OperationResult<OperationRecord> {
+        let message = message.into();
+        let record = self.update(id, |r| {
+            r.status = OperationStatus::Running;
+            r.progress = percentage.clamp(0.0, 100.0);
+            r.message = message;
+        })?;
+        self.emit(OperationAction::Progress, record.clone()).await?;
+        Ok(record)
+    }
+
+    /// Report progress from a job progress tracker
+    pub async fn report_job_progress(
+        &self,
+        id: OperationId,
+        progress: &JobProgress,
+    ) -> OperationResult<OperationRecord> {
+        self.report_progress(id, progress.percentage, progress.message.clone())
+            .await
+    }
+
+    /// Publish a partial result
+    pub async fn publish_partial(
+        &self,
+        id: OperationId,
+        partial: serde_json::Value,
+    ) -> OperationResult<OperationRecord> {
+        let record = self.update(id, |r| r.partial_results.push(partial))?;
+        self.emit(OperationAction::PartialResult, record.clone()).await?;
+        Ok(record)
+    }
+
+    /// Complete an operation successfully
+    pub async fn complete(
+        &self,
+        id: OperationId,
+        result: serde_json::Value,
+    ) -> OperationResult<OperationRecord> {
+        let record = self.finish(id, OperationStatus::Succeeded, |r| {
+            r.progress = 100.0;
+            r.result = Some(result);
+        })?;
+        self.emit(OperationAction::Completed, record.clone()).await?;
+        Ok(record)
+    }
+
+    /// Mark an operation as failed
+    pub async fn fail(
+        &self,
+        id: OperationId,
+        error: impl Into<String>,
+    ) -> OperationResult<OperationRecord> {
+        let error = error.into();
+        let record = self.finish(id, OperationStatus::Failed, |r| r.error = Some(error))?;
+        self.emit(OperationAction::Failed, record.clone()).await?;
+        Ok(record)
+    }
+
+    /// Cancel an operation, propagating to its control handle
+    pub async fn cancel(&self, id: OperationId) -> OperationResult<OperationRecord> {
+        let current = self.get(id)?;
+        if current.status.is_terminal() {
+            return Err(OperationError::AlreadyFinished(id, current.status));
+        }
+
+        if let Some(control) = self.controls.get(&id).map(|c| Arc::clone(&c)) {
+            control.cancel().await?;
+        } else {
+            debug!("Operation {} has no control handle attached", id);
+        }
+
+        let record = self.finish(id, OperationStatus::Cancelled, |_| {})?;
+        self.emit(OperationAction::Cancelled, record.clone()).await?;
+        Ok(record)
+    }
+
+    /// Remove finished operations completed before the given time
+    pub fn purge_finished(&self, before: DateTime<Utc>) -> usize {
+        let before_count = self.operations.len();
+        self.operations.retain(|_, r| {
+            !(r.status.is_terminal() && r.completed_at.is_some_and(|t| t < before))
+        });
+        before_count - self.operations.len()
+    }
+
+    fn update<F>(&self, id: OperationId, apply: F) -> OperationResult<OperationRecord>
+    where
+        F: FnOnce(&mut OperationRecord),
+    {
+        let mut entry = self
+            .operations
+            .get_mut(&id)
+            .ok_or(OperationError::NotFound(id))?;
+
+        if entry.status.is_terminal() {
+            return Err(OperationError::AlreadyFinished(id, entry.status));
+        }
+
+        apply(&mut entry);
+        entry.updated_at = Utc::now();
+        Ok(entry.clone())
+    }
+
+    fn finish<F>(
+        &self,
+        id: OperationId,
+        status: OperationStatus,
+        apply: F,
+    ) -> OperationResult<OperationRecord>
+    where
+        F: FnOnce(&mut OperationRecord),
+    {
+        let record = self.update(id, |r| {
+            apply(r);
+            r.status = status;
+            r.completed_at = Some(Utc::now());
+        })?;
+        self.controls.remove(&id);
+        Ok(record)
+    }
+
+    async fn emit(&self, action: OperationAction, record: OperationRecord) -> OperationResult<()> {
+        if let Some(url) = record.webhook_url.clone() {
+            self.deliver_webhook(&url, &action, &record).await;
+        }
+
+        self.event_bus
+            .publish(Arc::new(OperationEvent::new(action, record)))
+            .await?;
+        Ok(())
+    }
+
+    async fn deliver_webhook(&self, url: &str, action: &OperationAction, record: &OperationRecord) {
+        let Some(channel) = &self.webhook_channel else {
+            return;
+        };
+
+        let level = match record.status {
+            OperationStatus::Failed => NotificationLevel::Error,
+            OperationStatus::Cancelled => NotificationLevel::Warning,
+            OperationStatus::Succeeded => NotificationLevel::Success,
+            _ => NotificationLevel::Info,
+        };
+
+        let mut notification = Notification::new(
+            record.owner.clone().unwrap_or_default(),
+            level,
+            format!("Operation {}", record.name),
+            format!("{:?}: {}", action, record.message),
+        );
+        notification.related_entity_id = Some(record.id.to_string());
+        notification.related_entity_type = Some("operation".to_string());
+        notification.set_metadata("webhook_url", serde_json::json!(url));
+        notification.set_metadata(
+            "operation",
+            serde_json::to_value(record).unwrap_or(serde_json::Value::Null),
+        );
+
+        if let Err(e) = channel.deliver(&notification).await {
+            warn!("Webhook delivery for operation {} failed: {}", record.id, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use accuscene_notifications::types::{DeliveryState, DeliveryStatus};
+    use parking_lot::Mutex;
+    use serde_json::json;
+    use std::time::Duration;
+
+    /// Webhook channel recording every delivered notification
+    #[derive(Default)]
+    struct RecordingChannel {
+        delivered: Mutex<Vec<Notification>>,
+    }
+
+    #[async_trait]
+    impl Channel for RecordingChannel {
+        fn name(&self) -> &str {
+            "webhook"
+        }
+
+        async fn deliver(
+            &self,
+            notification: &Notification,
+        ) -> accuscene_notifications::Result<DeliveryStatus> {
+            self.delivered.lock().push(notification.clone());
+            Ok(DeliveryStatus {
+                notification_id: notification.id,
+                channel: "webhook".to_string(),
+                status: DeliveryState::Delivered,
+                attempts: 1,
+                last_attempt_at: Some(Utc::now()),
+                delivered_at: Some(Utc::now()),
+                error_message: None,
+            })
+        }
+
+        fn supports(&self, _notification: &Notification) -> bool {
+            true
+        }
+
+        fn is_enabled(&self) -> bool {
+            true
+        }
+    }
+
+    fn service() -> OperationsService {
+        OperationsService::new(Arc::new(EventBus::new()))
+    }
+
+    fn spec(name: &str) -> OperationSpec {
+        OperationSpec::new(OperationKind::Import, name).with_owner("user-1")
+    }
+
+    #[tokio::test]
+    async fn test_state_transitions() {
+        let operations = service();
+        let operation = operations.create(spec("import")).await.unwrap();
+        assert_eq!(operation.status, OperationStatus::Pending);
+
+        operations.start(operation.id).await.unwrap();
+        let running = operations
+            .report_progress(operation.id, 40.0, "rows imported")
+            .await
+            .unwrap();
+        assert_eq!(running.status, OperationStatus::Running);
+        assert_eq!(running.progress, 40.0);
+
+        operations
+            .publish_partial(operation.id, json!({ "rows": 10 }))
+            .await
+            .unwrap();
+        let done = operations
+            .complete(operation.id, json!({ "rows": 25 }))
+            .await
+            .unwrap();
+        assert_eq!(done.status, OperationStatus::Succeeded);
+        assert_eq!(done.progress, 100.0);
+        assert_eq!(done.partial_results.len(), 1);
+        assert!(done.completed_at.is_some());
+
+        // Terminal operations reject further updates
+        assert!(matches!(
+            operations.fail(operation.id, "late failure").await,
+            Err(OperationError::AlreadyFinished(_, OperationStatus::Succeeded))
+        ));
+
+        assert_eq!(operations.list(Some("user-1")).len(), 1);
+        assert!(operations.list(Some("user-2")).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_signals_running_work() {
+        let operations = service();
+        let operation = operations.create(spec("simulation")).await.unwrap();
+        let token = operations.cancellation_token(operation.id);
+        operations.start(operation.id).await.unwrap();
+
+        let work = tokio::spawn(async move { token.cancelled().await });
+
+        let cancelled = operations.cancel(operation.id).await.unwrap();
+        assert_eq!(cancelled.status, OperationStatus::Cancelled);
+        tokio::time::timeout(Duration::from_secs(1), work)
+            .await
+            .expect("running work was not cancelled")
+            .unwrap();
+
+        assert!(matches!(
+            operations.cancel(operation.id).await,
+            Err(OperationError::AlreadyFinished(_, OperationStatus::Cancelled))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_webhook_fan_out() {
+        let channel = Arc::new(RecordingChannel::default());
+        let operations = service().with_webhook_channel(channel.clone());
+
+        let hooked = operations
+            .create(spec("export").with_webhook("https://example.com/hook"))
+            .await
+            .unwrap();
+        let silent = operations.create(spec("report")).await.unwrap();
+        operations.start(hooked.id).await.unwrap();
+        operations.start(silent.id).await.unwrap();
+        operations.fail(hooked.id, "disk full").await.unwrap();
+
+        // Created, started and failed, for the hooked operation only
+        let delivered = channel.delivered.lock();
+        assert_eq!(delivered.len(), 3);
+        assert!(delivered
+            .iter()
+            .all(|n| n.related_entity_id == Some(hooked.id.to_string())));
+        assert_eq!(
+            delivered[0].metadata.get("webhook_url"),
+            Some(&json!("https://example.com/hook"))
+        );
+        assert_eq!(delivered[2].level, NotificationLevel::Error);
+    }
+
+    #[tokio::test]
+    async fn test_purge_finished() {
+        let operations = service();
+        let finished = operations.create(spec("finished")).await.unwrap();
+        operations.complete(finished.id, json!(null)).await.unwrap();
+        let running = operations.create(spec("running")).await.unwrap();
+        operations.start(running.id).await.unwrap();
+
+        assert_eq!(operations.purge_finished(Utc::now() - chrono::Duration::hours(1)), 0);
+        assert_eq!(operations.purge_finished(Utc::now() + chrono::Duration::seconds(1)), 1);
+
+        assert!(matches!(
+            operations.get(finished.id),
+            Err(OperationError::NotFound(_))
+        ));
+        assert_eq!(operations.get(running.id).unwrap().status, OperationStatus::Running);
+    }
+}
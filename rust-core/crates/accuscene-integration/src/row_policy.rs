@@ -0,0 +1,254 @@
+//! Row-level security backed by the authorization service
+//!
+//! [`AuthzRowPolicy`] plugs the RBAC/ABAC engine of `accuscene-security`
+//! into the repository layer's [`RowPolicy`] hook. The authorization service
+//! decides whether the caller may perform an action on a resource at all
+//! (e.g. `cases:read`); a [`ScopedRowPolicy`] then narrows the rows to the
+//! caller's own and organization's records.
+//!
+//! On multi-tenant deployments [`AuthzRowPolicy::tenant_scoped`] confines
+//! callers to their organization's rows and writes an audit event through a
+//! [`TenantGuard`] whenever a platform operator reads across organizations.
+
+use accuscene_database::row_security::{
+    AuthContext as RowAuthContext, RowAction, RowFilter, RowPolicy, ScopedRowPolicy,
+};
+use accuscene_database::{DatabaseError, DbResult};
+use accuscene_security::authz::{AttributeValue, PolicyRequest};
+use accuscene_security::audit::ResourceInfo;
+use accuscene_security::{AuthContext, AuthorizationService, TenantGuard};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// Subject attribute carrying the caller's organization
+pub const ORGANIZATION_ATTRIBUTE: &str = "organization";
+
+/// Resource attribute carrying the table a row filter is requested for
+pub const TABLE_ATTRIBUTE: &str = "table";
+
+/// Organization marker for accesses spanning all organizations
+pub const ALL_ORGANIZATIONS: &str = "*";
+
+/// Row auth context for an authenticated caller
+///
+/// `organization` overrides the organization carried by the auth context.
+pub fn row_auth_context(context: &AuthContext, organization: Option<&str>) -> RowAuthContext {
+    let mut row_context = RowAuthContext::new(context.user_id.clone())
+        .with_roles(context.roles.clone())
+        .with_permissions(context.permissions.clone());
+
+    if let Some(organization) = organization.or(context.org_id.as_deref()) {
+        row_context = row_context.with_organization(organization);
+    }
+
+    row_context
+}
+
+/// Auth context for a row caller, as recorded in audit events
+fn caller_context(context: &RowAuthContext) -> AuthContext {
+    AuthContext {
+        user_id: context.user_id.clone(),
+        session_id: None,
+        roles: context.roles.clone(),
+        permissions: context.permissions.clone(),
+        org_id: context.organization.clone(),
+        mfa_verified: false,
+        session_metadata: None,
+    }
+}
+
+/// Permission resource guarding a table
+///
+/// Accidents and vehicles are part of the case record and share its
+/// permissions.
+pub fn resource_for_table(table: &str) -> &str {
+    match table {
+        "accidents" | "vehicles" => "cases",
+        other => other,
+    }
+}
+
+/// Permission action required for a row action
+pub fn permission_action(action: RowAction) -> &'static str {
+    match action {
+        RowAction::Read => "read",
+        RowAction::Update => "write",
+        RowAction::Delete => "delete",
+    }
+}
+
+/// Row policy checking the authorization service before scoping rows
+pub struct AuthzRowPolicy {
+    authz: Arc<Mutex<AuthorizationService>>,
+    scopes: ScopedRowPolicy,
+    tenant_guard: Option<TenantGuard>,
+}
+
+impl AuthzRowPolicy {
+    /// Create a policy with the default case data scoping
+    ///
+    /// Users with the `admin` role see all rows they are authorized for.
+    pub fn new(authz: Arc<Mutex<AuthorizationService>>) -> Self {
+        Self {
+            authz,
+            scopes: ScopedRowPolicy::case_data().with_bypass_role("admin"),
+            tenant_guard: None,
+        }
+    }
+
+    /// Create a policy confining case data to the caller's organization
+    ///
+    /// `admin` sees all rows of its own organization; only `super_admin`
+    /// reads across organizations, which is audited through `guard`.
+    pub fn tenant_scoped(authz: Arc<Mutex<AuthorizationService>>, guard: TenantGuard) -> Self {
+        Self::new(authz)
+            .with_scopes(
+                ScopedRowPolicy::tenant_case_data()
+                    .with_bypass_role("admin")
+                    .with_cross_tenant_role("super_admin"),
+            )
+            .with_tenant_guard(guard)
+    }
+
+    /// Use custom row scoping
+    #[must_use]
+    pub fn with_scopes(mut self, scopes: ScopedRowPolicy) -> Self {
+        self.scopes = scopes;
+        self
+    }
+
+    /// Audit cross-tenant row access through a tenant guard
+    #[must_use]
+    pub fn with_tenant_guard(mut self, guard: TenantGuard) -> Self {
+        self.tenant_guard = Some(guard);
+        self
+    }
+
+    /// Record a cross-tenant access on a tenant-scoped table
+    fn audit_cross_tenant(&self, context: &RowAuthContext, table: &str, action: RowAction) {
+        let Some(guard) = self.tenant_guard.clone() else {
+            return;
+        };
+        if !self.scopes.is_tenant_scoped(table) || !self.scopes.crosses_tenants(context) {
+            return;
+        }
+
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            warn!(
+                "Cross-tenant {} of {} by {} not audited: no async runtime",
+                action.as_str(),
+                table,
+                context.user_id
+            );
+            return;
+        };
+
+        let caller = caller_context(context);
+        let action = format!("{}.{}", table, action.as_str());
+        let resource = ResourceInfo::new(table, ALL_ORGANIZATIONS);
+        handle.spawn(async move {
+            if let Err(e) = guard
+                .record(&caller, ALL_ORGANIZATIONS, Some(resource), &action, true)
+                .await
+            {
+                warn!("Failed to audit cross-tenant access by {}: {}", caller.user_id, e);
+            }
+        });
+    }
+
+    fn request(context: &RowAuthContext, table: &str, action: RowAction) -> PolicyRequest {
+        let permission = format!("{}:{}", resource_for_table(table), permission_action(action));
+        let mut request = PolicyRequest::simple(context.user_id.clone(), permission)
+            .with_org(context.organization.clone())
+            .with_resource_attr(
+                TABLE_ATTRIBUTE.to_string(),
+                AttributeValue::String(table.to_string()),
+            );
+
+        if let Some(organization) = &context.organization {
+            request = request.with_subject_attr(
+                ORGANIZATION_ATTRIBUTE.to_string(),
+                AttributeValue::String(organization.clone()),
+            );
+        }
+
+        for (key, value) in &context.attributes {
+            request = request.with_subject_attr(key.clone(), AttributeValue::String(value.clone()));
+        }
+
+        request
+    }
+}
+
+impl RowPolicy for AuthzRowPolicy {
+    fn row_filter(
+        &self,
+        context: &RowAuthContext,
+        table: &str,
+        action: RowAction,
+    ) -> DbResult<RowFilter> {
+        let request = Self::request(context, table, action);
+
+        match self.authz.lock().authorize_request(request) {
+            Ok(()) => {
+                self.audit_cross_tenant(context, table, action);
+                self.scopes.row_filter(context, table, action)
+            },
+            Err(e) if e.is_authz_error() => {
+                debug!(
+                    "User {} may not {} {}: {}",
+                    context.user_id,
+                    action.as_str(),
+                    table,
+                    e
+                );
+                Ok(RowFilter::Denied)
+            },
+            Err(e) => Err(DatabaseError::PermissionDenied(format!(
+                "Authorization failed: {e}"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_row_auth_context() {
+        let context = AuthContext {
+            user_id: "u1".to_string(),
+            session_id: None,
+            roles: vec!["investigator".to_string()],
+            permissions: vec!["cases:read".to_string()],
+            org_id: Some("org2".to_string()),
+            mfa_verified: false,
+            session_metadata: None,
+        };
+
+        let row_context = row_auth_context(&context, Some("org1"));
+        assert_eq!(row_context.user_id, "u1");
+        assert!(row_context.has_role("investigator"));
+        assert_eq!(row_context.organization.as_deref(), Some("org1"));
+
+        let row_context = row_auth_context(&context, None);
+        assert_eq!(row_context.organization.as_deref(), Some("org2"));
+    }
+
+    #[test]
+    fn test_authz_row_policy() {
+        let mut authz = AuthorizationService::default();
+        authz.rbac_mut().assign_role("admin-user", "admin").unwrap();
+        let policy = AuthzRowPolicy::new(Arc::new(Mutex::new(authz)));
+
+        let admin = RowAuthContext::new("admin-user").with_roles(vec!["admin".to_string()]);
+        let filter = policy.row_filter(&admin, "vehicles", RowAction::Read).unwrap();
+        assert_eq!(filter, RowFilter::Unrestricted);
+
+        let stranger = RowAuthContext::new("stranger");
+        let filter = policy.row_filter(&stranger, "cases", RowAction::Delete).unwrap();
+        assert_eq!(filter, RowFilter::Denied);
+    }
+}
@@ -8,8 +8,10 @@ use crate::events::EventBus;
 use crate::facade::Facade;
 use crate::health::HealthChecker;
 use crate::registry::Registry;
+use crate::shutdown::{ShutdownController, ShutdownReport};
 use anyhow::Result;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 use tracing::{info, warn};
 
@@ -49,6 +51,9 @@ pub struct Runtime {
 
     /// Facade for simplified API access
     facade: Arc<Facade>,
+
+    /// Cancellation tree for background tasks
+    shutdown: Arc<ShutdownController>,
 }
 
 impl Runtime {
@@ -74,6 +79,10 @@ impl Runtime {
             Arc::clone(&event_bus),
         ));
 
+        let shutdown = Arc::new(ShutdownController::new(Duration::from_secs(
+            config.app.shutdown_timeout,
+        )));
+
         Ok(Self {
             config,
             state,
@@ -81,6 +90,7 @@ impl Runtime {
             event_bus,
             health_checker,
             facade,
+            shutdown,
         })
     }
 
@@ -407,18 +417,29 @@ impl Runtime {
     }
 
     /// Stop the runtime and all services
-    pub async fn stop(&self) -> Result<()> {
+    ///
+    /// Cancels every shutdown scope and waits for the registered tasks up to
+    /// their drain deadline. The returned report lists tasks that had to be
+    /// aborted or panicked.
+    pub async fn stop(&self) -> Result<ShutdownReport> {
         info!("Stopping AccuScene Enterprise runtime");
 
         *self.state.write().await = RuntimeState::ShuttingDown;
 
-        // Stop services in reverse order
         warn!("Gracefully shutting down services...");
+        let report = self.shutdown.shutdown().await;
 
         *self.state.write().await = RuntimeState::Stopped;
-        info!("AccuScene Enterprise runtime stopped");
+        if report.is_clean() {
+            info!("AccuScene Enterprise runtime stopped");
+        } else {
+            warn!(
+                "AccuScene Enterprise runtime stopped with {} task(s) not stopping cleanly",
+                report.failed().count()
+            );
+        }
 
-        Ok(())
+        Ok(report)
     }
 
     /// Get the current runtime state
@@ -450,6 +471,11 @@ impl Runtime {
     pub fn facade(&self) -> Arc<Facade> {
         Arc::clone(&self.facade)
     }
+
+    /// Get the shutdown controller services register their tasks with
+    pub fn shutdown_controller(&self) -> Arc<ShutdownController> {
+        Arc::clone(&self.shutdown)
+    }
 }
 
 impl Drop for Runtime {
@@ -0,0 +1,237 @@
+//! SCIM provisioning into the user repository
+//!
+//! [`DatabaseScimDirectory`] backs the security framework's SCIM service
+//! with the `users` table, so identity providers provision the same
+//! accounts that administrators manage. Provisioned users get the default
+//! role and organization of the directory and sign in through SSO; they have
+//! no local password. Deactivating a user also ends their sessions.
+//!
+//! The `users` table has no columns for `externalId` or name components, so
+//! those are not persisted. Groups are kept in a separate directory
+//! (in memory unless another is supplied).
+
+use accuscene_database::repositories::Repository;
+use accuscene_database::{DatabaseError, DatabasePool, User, UserRepository};
+use accuscene_security::scim::{
+    Meta, MemoryScimDirectory, MultiValue, Name, ScimDirectory, ScimGroup, ScimUser,
+};
+use accuscene_security::{SecurityError, SecurityService};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Role given to provisioned users
+pub const DEFAULT_PROVISIONED_ROLE: &str = "viewer";
+
+/// SCIM directory over [`UserRepository`]
+pub struct DatabaseScimDirectory {
+    pool: Arc<DatabasePool>,
+    security: Arc<SecurityService>,
+    users: UserRepository,
+    groups: Arc<dyn ScimDirectory>,
+    default_role: String,
+    organization: Option<String>,
+}
+
+impl DatabaseScimDirectory {
+    /// Create a directory on the database pool
+    pub fn new(pool: Arc<DatabasePool>, security: Arc<SecurityService>) -> Self {
+        Self {
+            pool,
+            security,
+            users: UserRepository::new(),
+            groups: Arc::new(MemoryScimDirectory::new()),
+            default_role: DEFAULT_PROVISIONED_ROLE.to_string(),
+            organization: None,
+        }
+    }
+
+    /// Role given to newly provisioned users
+    #[must_use]
+    pub fn with_default_role(mut self, role: impl Into<String>) -> Self {
+        self.default_role = role.into();
+        self
+    }
+
+    /// Organization newly provisioned users belong to
+    #[must_use]
+    pub fn with_organization(mut self, organization: impl Into<String>) -> Self {
+        self.organization = Some(organization.into());
+        self
+    }
+
+    /// Keep groups in another directory
+    #[must_use]
+    pub fn with_group_directory(mut self, groups: Arc<dyn ScimDirectory>) -> Self {
+        self.groups = groups;
+        self
+    }
+
+    fn find(&self, id: &str) -> Result<Option<User>, SecurityError> {
+        self.pool
+            .with_connection(|conn| self.users.find_by_id(conn, &id.to_string()))
+            .map_err(repository_error)
+    }
+
+    fn to_scim(user: User) -> ScimUser {
+        ScimUser {
+            name: Some(Name {
+                formatted: Some(user.full_name.clone()),
+                ..Name::default()
+            }),
+            display_name: Some(user.full_name),
+            emails: vec![MultiValue {
+                value: user.email,
+                kind: Some("work".to_string()),
+                primary: true,
+            }],
+            phone_numbers: user
+                .phone
+                .into_iter()
+                .map(|phone| MultiValue {
+                    value: phone,
+                    kind: Some("work".to_string()),
+                    primary: true,
+                })
+                .collect(),
+            active: user.is_active,
+            meta: Some(Meta {
+                resource_type: "User".to_string(),
+                created: parse_timestamp(&user.created_at),
+                last_modified: parse_timestamp(&user.updated_at),
+                location: None,
+            }),
+            id: Some(user.id),
+            ..ScimUser::new(user.username)
+        }
+    }
+
+    /// Copy SCIM attributes onto a stored (or new) user row
+    fn apply(user: &ScimUser, row: &mut User) -> Result<(), SecurityError> {
+        row.email = user
+            .primary_email()
+            .ok_or_else(|| {
+                SecurityError::ScimInvalidValue("an email address is required".to_string())
+            })?
+            .to_string();
+        row.username.clone_from(&user.user_name);
+        row.full_name = user.full_name().unwrap_or_else(|| user.user_name.clone());
+        row.phone = user.primary_phone().map(str::to_string);
+        row.is_active = user.active;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ScimDirectory for DatabaseScimDirectory {
+    async fn create_user(&self, user: &ScimUser) -> accuscene_security::Result<()> {
+        // Fail before persisting if the role does not exist
+        self.security
+            .authz()
+            .read()
+            .await
+            .rbac()
+            .get_role(&self.default_role)?;
+
+        let now = chrono::Utc::now().to_rfc3339();
+        let mut row = User {
+            id: user.id().to_string(),
+            email: String::new(),
+            username: String::new(),
+            full_name: String::new(),
+            // Provisioned accounts sign in through the identity provider
+            password_hash: String::new(),
+            role: self.default_role.clone(),
+            organization: self.organization.clone(),
+            phone: None,
+            is_active: true,
+            last_login_at: None,
+            created_at: now.clone(),
+            updated_at: now,
+        };
+        Self::apply(user, &mut row)?;
+
+        self.pool
+            .with_connection(|conn| self.users.create(conn, &row))
+            .map_err(repository_error)?;
+        self.security
+            .authz()
+            .write()
+            .await
+            .rbac_mut()
+            .assign_role(&row.id, &row.role)?;
+
+        Ok(())
+    }
+
+    async fn get_user(&self, id: &str) -> accuscene_security::Result<Option<ScimUser>> {
+        Ok(self.find(id)?.map(Self::to_scim))
+    }
+
+    async fn update_user(&self, user: &ScimUser) -> accuscene_security::Result<()> {
+        let mut row = self.find(user.id())?.ok_or_else(|| {
+            SecurityError::ScimResourceNotFound(format!("User {}", user.id()))
+        })?;
+        let was_active = row.is_active;
+        Self::apply(user, &mut row)?;
+
+        self.pool
+            .with_connection(|conn| self.users.update(conn, &row))
+            .map_err(repository_error)?;
+
+        if was_active && !row.is_active {
+            self.security
+                .auth()
+                .read()
+                .await
+                .session_manager()
+                .invalidate_user_sessions(&row.id)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn list_users(&self) -> accuscene_security::Result<Vec<ScimUser>> {
+        let users = self
+            .pool
+            .with_connection(|conn| self.users.find_all(conn))
+            .map_err(repository_error)?;
+        Ok(users.into_iter().map(Self::to_scim).collect())
+    }
+
+    async fn create_group(&self, group: &ScimGroup) -> accuscene_security::Result<()> {
+        self.groups.create_group(group).await
+    }
+
+    async fn get_group(&self, id: &str) -> accuscene_security::Result<Option<ScimGroup>> {
+        self.groups.get_group(id).await
+    }
+
+    async fn update_group(&self, group: &ScimGroup) -> accuscene_security::Result<()> {
+        self.groups.update_group(group).await
+    }
+
+    async fn delete_group(&self, id: &str) -> accuscene_security::Result<bool> {
+        self.groups.delete_group(id).await
+    }
+
+    async fn list_groups(&self) -> accuscene_security::Result<Vec<ScimGroup>> {
+        self.groups.list_groups().await
+    }
+}
+
+fn repository_error(error: DatabaseError) -> SecurityError {
+    SecurityError::Internal(format!("User repository: {}", error))
+}
+
+/// Parse RFC 3339 or SQLite `datetime('now')` timestamps
+fn parse_timestamp(value: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&chrono::Utc))
+        .ok()
+        .or_else(|| {
+            chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+                .ok()
+                .map(|t| t.and_utc())
+        })
+}
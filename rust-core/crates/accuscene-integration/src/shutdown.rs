@@ -0,0 +1,428 @@
+//! Structured shutdown of background tasks
+//!
+//! Services spawn long-running tasks (dispatcher workers, schedulers,
+//! checkpointing, heartbeats). The [`ShutdownController`] gives them a common
+//! cancellation tree: every service gets a [`ShutdownScope`] whose token is a
+//! child of the runtime token, spawns or registers its tasks there, and stops
+//! them when the token is cancelled. On shutdown the controller cancels the
+//! tree, waits for each scope's tasks up to its drain deadline, aborts the
+//! stragglers and returns a [`ShutdownReport`] listing tasks that failed to
+//! stop on their own.
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::{JoinHandle, JoinSet};
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+/// Default time a scope's tasks get to stop after cancellation
+pub const DEFAULT_DRAIN_DEADLINE: Duration = Duration::from_secs(30);
+
+/// Time to wait for an aborted task to unwind
+const ABORT_GRACE: Duration = Duration::from_millis(100);
+
+/// How a task ended during shutdown
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "outcome", content = "detail")]
+pub enum TaskOutcome {
+    /// Stopped on its own within the drain deadline
+    Completed,
+    /// Panicked while stopping
+    Panicked(String),
+    /// Aborted by someone else before the drain
+    Cancelled,
+    /// Did not stop within the drain deadline and was aborted
+    TimedOut,
+}
+
+/// Shutdown result of one task
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskReport {
+    /// Path of the owning scope (e.g. `runtime/notifications/dispatcher`)
+    pub scope: String,
+    /// Task name
+    pub name: String,
+    /// How the task ended
+    pub outcome: TaskOutcome,
+    /// Time from cancellation until the task ended or was aborted
+    pub elapsed: Duration,
+}
+
+/// Result of a shutdown
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ShutdownReport {
+    /// Total shutdown duration
+    pub duration: Duration,
+    /// Every task that was tracked
+    pub tasks: Vec<TaskReport>,
+}
+
+impl ShutdownReport {
+    /// Tasks that did not stop cleanly on their own
+    pub fn failed(&self) -> impl Iterator<Item = &TaskReport> {
+        self.tasks.iter().filter(|task| task.outcome != TaskOutcome::Completed)
+    }
+
+    /// Whether every task stopped on its own
+    pub fn is_clean(&self) -> bool {
+        self.failed().next().is_none()
+    }
+}
+
+/// Task tracked by a scope
+struct TrackedTask {
+    name: String,
+    handle: JoinHandle<()>,
+}
+
+struct ScopeInner {
+    path: String,
+    token: CancellationToken,
+    drain_deadline: Mutex<Duration>,
+    tasks: Mutex<Vec<TrackedTask>>,
+    children: Mutex<Vec<ShutdownScope>>,
+}
+
+/// Node of the cancellation tree owning a set of tasks
+#[derive(Clone)]
+pub struct ShutdownScope {
+    inner: Arc<ScopeInner>,
+}
+
+impl ShutdownScope {
+    fn new(path: String, token: CancellationToken, drain_deadline: Duration) -> Self {
+        Self {
+            inner: Arc::new(ScopeInner {
+                path,
+                token,
+                drain_deadline: Mutex::new(drain_deadline),
+                tasks: Mutex::new(Vec::new()),
+                children: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Scope path from the root
+    pub fn path(&self) -> &str {
+        &self.inner.path
+    }
+
+    /// Cancellation token of the scope; cancelled when any ancestor is
+    pub fn token(&self) -> CancellationToken {
+        self.inner.token.clone()
+    }
+
+    /// Whether shutdown of this scope has begun
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.token.is_cancelled()
+    }
+
+    /// Time the scope's tasks get to stop after cancellation
+    pub fn drain_deadline(&self) -> Duration {
+        *self.inner.drain_deadline.lock()
+    }
+
+    /// Set the time the scope's tasks get to stop after cancellation
+    pub fn set_drain_deadline(&self, deadline: Duration) {
+        *self.inner.drain_deadline.lock() = deadline;
+    }
+
+    /// Create a child scope inheriting this scope's drain deadline
+    pub fn child(&self, name: &str) -> ShutdownScope {
+        let child = ShutdownScope::new(
+            format!("{}/{}", self.inner.path, name),
+            self.inner.token.child_token(),
+            self.drain_deadline(),
+        );
+        self.inner.children.lock().push(child.clone());
+        child
+    }
+
+    /// Spawn a task that watches [`token`](Self::token) and stops on its own
+    pub fn spawn<F>(&self, name: &str, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.register(name, tokio::spawn(task));
+    }
+
+    /// Spawn a task that is dropped at its next await point once the scope is cancelled
+    pub fn spawn_until_cancelled<F>(&self, name: &str, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let token = self.token();
+        self.spawn(name, async move {
+            tokio::select! {
+                _ = token.cancelled() => {}
+                _ = task => {}
+            }
+        });
+    }
+
+    /// Track a task spawned elsewhere
+    pub fn register(&self, name: &str, handle: JoinHandle<()>) {
+        self.inner.tasks.lock().push(TrackedTask {
+            name: name.to_string(),
+            handle,
+        });
+    }
+
+    /// Track the task handles handed over by a service
+    pub fn adopt(&self, handles: impl IntoIterator<Item = (String, JoinHandle<()>)>) {
+        let mut tasks = self.inner.tasks.lock();
+        tasks.extend(handles.into_iter().map(|(name, handle)| TrackedTask { name, handle }));
+    }
+
+    /// Number of tracked tasks in this scope and its descendants
+    pub fn task_count(&self) -> usize {
+        let own = self.inner.tasks.lock().len();
+        let children = self.inner.children.lock().clone();
+        own + children.iter().map(ShutdownScope::task_count).sum::<usize>()
+    }
+
+    /// This scope followed by all its descendants
+    fn flatten(&self) -> Vec<ShutdownScope> {
+        let children = self.inner.children.lock().clone();
+        let mut scopes = vec![self.clone()];
+        for child in &children {
+            scopes.extend(child.flatten());
+        }
+        scopes
+    }
+
+    /// Wait for the scope's own tasks until its drain deadline
+    async fn drain(self, started: Instant) -> Vec<TaskReport> {
+        let tasks = std::mem::take(&mut *self.inner.tasks.lock());
+        let deadline = started + self.drain_deadline();
+        let mut reports = Vec::with_capacity(tasks.len());
+
+        for TrackedTask { name, mut handle } in tasks {
+            let outcome = match tokio::time::timeout_at(deadline, &mut handle).await {
+                Ok(Ok(())) => TaskOutcome::Completed,
+                Ok(Err(error)) if error.is_panic() => TaskOutcome::Panicked(panic_message(error)),
+                Ok(Err(_)) => TaskOutcome::Cancelled,
+                Err(_) => {
+                    handle.abort();
+                    let _ = tokio::time::timeout(ABORT_GRACE, handle).await;
+                    TaskOutcome::TimedOut
+                }
+            };
+
+            reports.push(TaskReport {
+                scope: self.inner.path.clone(),
+                name,
+                outcome,
+                elapsed: started.elapsed(),
+            });
+        }
+
+        reports
+    }
+}
+
+/// Extract the message of a panicked task
+fn panic_message(error: tokio::task::JoinError) -> String {
+    let payload = error.into_panic();
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| (*s).to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// Root of the cancellation tree
+pub struct ShutdownController {
+    root: ShutdownScope,
+}
+
+impl ShutdownController {
+    /// Create a controller whose scopes default to the given drain deadline
+    pub fn new(drain_deadline: Duration) -> Self {
+        Self {
+            root: ShutdownScope::new(
+                "runtime".to_string(),
+                CancellationToken::new(),
+                drain_deadline,
+            ),
+        }
+    }
+
+    /// Root cancellation token
+    pub fn token(&self) -> CancellationToken {
+        self.root.token()
+    }
+
+    /// Root scope, for tasks that belong to the runtime itself
+    pub fn root(&self) -> &ShutdownScope {
+        &self.root
+    }
+
+    /// Create a scope for a service
+    pub fn scope(&self, name: &str) -> ShutdownScope {
+        self.root.child(name)
+    }
+
+    /// Whether shutdown has begun
+    pub fn is_shutting_down(&self) -> bool {
+        self.root.is_cancelled()
+    }
+
+    /// Cancel the tree and drain every scope
+    ///
+    /// Scopes drain concurrently, each against its own deadline measured
+    /// from the moment of cancellation. Tasks still running at their deadline
+    /// are aborted and reported as [`TaskOutcome::TimedOut`]. Tasks
+    /// registered after a shutdown are drained by the next call.
+    pub async fn shutdown(&self) -> ShutdownReport {
+        let started = Instant::now();
+        let scopes = self.root.flatten();
+        info!(
+            "Shutting down {} task(s) in {} scope(s)",
+            self.root.task_count(),
+            scopes.len()
+        );
+
+        self.root.token().cancel();
+
+        let mut drains = JoinSet::new();
+        for scope in scopes {
+            drains.spawn(scope.drain(started));
+        }
+
+        let mut tasks = Vec::new();
+        while let Some(result) = drains.join_next().await {
+            match result {
+                Ok(reports) => tasks.extend(reports),
+                Err(error) => warn!("Shutdown drain failed: {}", error),
+            }
+        }
+        tasks.sort_by(|a, b| (&a.scope, &a.name).cmp(&(&b.scope, &b.name)));
+
+        let report = ShutdownReport {
+            duration: started.elapsed(),
+            tasks,
+        };
+
+        for task in report.failed() {
+            warn!(
+                "Task {}/{} did not stop cleanly: {:?}",
+                task.scope, task.name, task.outcome
+            );
+        }
+        debug!(
+            "Shutdown finished in {:?} ({} task(s))",
+            report.duration,
+            report.tasks.len()
+        );
+
+        report
+    }
+}
+
+impl Default for ShutdownController {
+    fn default() -> Self {
+        Self::new(DEFAULT_DRAIN_DEADLINE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome<'a>(report: &'a ShutdownReport, name: &str) -> &'a TaskOutcome {
+        &report.tasks.iter().find(|task| task.name == name).unwrap().outcome
+    }
+
+    #[tokio::test]
+    async fn test_cooperative_tasks_stop_cleanly() {
+        let controller = ShutdownController::default();
+        let scope = controller.scope("notifications");
+
+        let token = scope.token();
+        scope.spawn("dispatcher", async move { token.cancelled().await });
+        scope.spawn_until_cancelled("heartbeat", std::future::pending());
+        assert_eq!(controller.root().task_count(), 2);
+
+        let report = controller.shutdown().await;
+        assert!(controller.is_shutting_down());
+        assert!(report.is_clean());
+        assert_eq!(report.tasks.len(), 2);
+        assert!(report.tasks.iter().all(|task| task.scope == "runtime/notifications"));
+        assert_eq!(controller.root().task_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_stuck_task_is_aborted_at_deadline() {
+        let controller = ShutdownController::new(Duration::from_millis(50));
+        let scope = controller.scope("jobs");
+
+        let token = scope.token();
+        scope.spawn("worker", async move { token.cancelled().await });
+        scope.spawn("stuck", async {
+            tokio::time::sleep(Duration::from_secs(3600)).await;
+        });
+
+        let report = controller.shutdown().await;
+        assert!(!report.is_clean());
+        assert_eq!(outcome(&report, "worker"), &TaskOutcome::Completed);
+        assert_eq!(outcome(&report, "stuck"), &TaskOutcome::TimedOut);
+        assert_eq!(report.failed().count(), 1);
+        assert!(report.duration < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_panicked_and_cancelled_tasks_are_reported() {
+        let controller = ShutdownController::default();
+        let scope = controller.scope("sync");
+
+        let token = scope.token();
+        scope.spawn("checkpoint", async move {
+            token.cancelled().await;
+            panic!("checkpoint failed");
+        });
+        let aborted = tokio::spawn(std::future::pending::<()>());
+        aborted.abort();
+        scope.register("aborted", aborted);
+
+        let report = controller.shutdown().await;
+        assert_eq!(
+            outcome(&report, "checkpoint"),
+            &TaskOutcome::Panicked("checkpoint failed".to_string())
+        );
+        assert_eq!(outcome(&report, "aborted"), &TaskOutcome::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_child_scopes_follow_their_parent() {
+        let controller = ShutdownController::new(Duration::from_millis(50));
+        let service = controller.scope("notifications");
+        service.set_drain_deadline(Duration::from_secs(5));
+        let dispatcher = service.child("dispatcher");
+        assert_eq!(dispatcher.path(), "runtime/notifications/dispatcher");
+        assert_eq!(dispatcher.drain_deadline(), Duration::from_secs(5));
+
+        // Cancelling a scope leaves its siblings and the runtime running
+        let other = controller.scope("jobs");
+        service.token().cancel();
+        assert!(dispatcher.is_cancelled());
+        assert!(!other.is_cancelled());
+        assert!(!controller.is_shutting_down());
+
+        let token = dispatcher.token();
+        dispatcher.adopt([(
+            "worker-1".to_string(),
+            tokio::spawn(async move { token.cancelled().await }),
+        )]);
+        assert_eq!(service.task_count(), 1);
+
+        let report = controller.shutdown().await;
+        assert!(report.is_clean());
+        assert_eq!(report.tasks[0].scope, "runtime/notifications/dispatcher");
+        assert!(other.is_cancelled());
+    }
+}
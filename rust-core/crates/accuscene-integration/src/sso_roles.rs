@@ -0,0 +1,77 @@
+//! Directory roles from SSO logins
+//!
+//! Providers with a group-to-role mapping (LDAP/Active Directory) report
+//! the roles a user's groups grant and the roles the mapping manages.
+//! [`sync_sso_roles`] applies them to the security framework's RBAC after a
+//! login: granted roles are assigned and managed roles the user no longer
+//! holds are revoked. Roles outside the mapping, such as those assigned by
+//! an administrator, are left alone.
+
+use accuscene_security::SecurityService;
+use accuscene_sso::SSOUser;
+
+/// Role changes made for a login
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoleSync {
+    /// Roles newly assigned
+    pub assigned: Vec<String>,
+    /// Roles revoked
+    pub revoked: Vec<String>,
+}
+
+impl RoleSync {
+    /// Check whether nothing changed
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.assigned.is_empty() && self.revoked.is_empty()
+    }
+}
+
+/// Apply the directory roles of an SSO user to RBAC
+///
+/// # Errors
+///
+/// Fails with `RoleNotFound` when the mapping grants a role RBAC does not
+/// define; no roles are changed in that case.
+pub async fn sync_sso_roles(
+    security: &SecurityService,
+    user: &SSOUser,
+) -> accuscene_security::Result<RoleSync> {
+    let granted = user.roles();
+    let managed = user.managed_roles();
+
+    let authz = security.authz();
+    let mut authz = authz.write().await;
+    let rbac = authz.rbac_mut();
+
+    for role in &granted {
+        rbac.get_role(role)?;
+    }
+
+    let held = rbac.get_user_roles(&user.id);
+    let mut sync = RoleSync::default();
+
+    for role in granted.iter().filter(|role| !held.contains(role)) {
+        rbac.assign_role(&user.id, role)?;
+        sync.assigned.push(role.clone());
+    }
+    for role in managed
+        .iter()
+        .filter(|role| held.contains(role) && !granted.contains(role))
+    {
+        rbac.revoke_role(&user.id, role)?;
+        sync.revoked.push(role.clone());
+    }
+
+    if !sync.is_empty() {
+        tracing::info!(
+            user_id = %user.id,
+            provider = %user.provider,
+            assigned = ?sync.assigned,
+            revoked = ?sync.revoked,
+            "Applied directory roles"
+        );
+    }
+
+    Ok(sync)
+}
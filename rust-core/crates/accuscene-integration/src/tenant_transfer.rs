@@ -0,0 +1,1105 @@
+//! Tenant import and export
+//!
+//! This module moves a complete tenant between deployments (on-prem and
+//! cloud) or into long-term archival. Each kind of tenant data — database
+//! rows, search index documents, attachments, preferences and dashboards — is
+//! contributed by a [`TenantSource`]. The exporter streams the records of all
+//! sources into a versioned archive of AES-256-GCM encrypted chunks, closed by
+//! a trailer carrying record counts and a digest of the plaintext.
+//!
+//! The importer verifies the archive, remaps identifiers so the tenant can be
+//! loaded next to existing data, and can run as a dry run that only produces a
+//! compatibility report.
+
+use accuscene_core::types::organization::{
+    Membership, Organization, OwnedResourceKind, ResourceOwnership, Team, Workspace,
+};
+use accuscene_crypto::hash::Sha256Hasher;
+use accuscene_crypto::symmetric::aes::EncryptedData;
+use accuscene_crypto::symmetric::{Aes256Gcm, SymmetricKey};
+use accuscene_crypto::CryptoError;
+use accuscene_dashboard::persistence::PersistenceStorage;
+use accuscene_database::repositories::Repository;
+use accuscene_database::{execute_transaction, DatabaseError, DatabasePool, OrganizationRepository};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Write};
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Magic bytes at the start of every tenant archive
+pub const ARCHIVE_MAGIC: &[u8; 4] = b"ACTA";
+
+/// Current archive format version
+pub const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+/// Default number of records per encrypted chunk
+pub const DEFAULT_CHUNK_SIZE: usize = 500;
+
+const FRAME_CHUNK: u8 = 1;
+const FRAME_TRAILER: u8 = 2;
+
+/// Upper bound on a single frame, to reject corrupt length prefixes early
+const MAX_FRAME_BYTES: u32 = 256 * 1024 * 1024;
+
+/// Tenant transfer error
+#[derive(Debug, thiserror::Error)]
+pub enum TransferError {
+    /// I/O error
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Serialization error
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    /// Encryption or decryption error
+    #[error("Crypto error: {0}")]
+    Crypto(#[from] CryptoError),
+
+    /// Database error
+    #[error("Database error: {0}")]
+    Database(#[from] DatabaseError),
+
+    /// Archive is malformed or has been tampered with
+    #[error("Invalid archive: {0}")]
+    InvalidArchive(String),
+
+    /// Archive format version is not supported
+    #[error("Unsupported archive format version: {0}")]
+    UnsupportedVersion(u32),
+
+    /// A source failed to export or import its records
+    #[error("{section} source error: {message}")]
+    Source {
+        section: ArchiveSection,
+        message: String,
+    },
+
+    /// Archive cannot be imported into this deployment
+    #[error("Incompatible archive: {0}")]
+    Incompatible(String),
+}
+
+impl TransferError {
+    /// Create a source error
+    pub fn source(section: ArchiveSection, message: impl ToString) -> Self {
+        Self::Source {
+            section,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Tenant transfer result type
+pub type TransferResult<T> = Result<T, TransferError>;
+
+/// Kind of tenant data in an archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveSection {
+    /// Relational database rows
+    DatabaseRows,
+    /// Search index documents
+    SearchDocuments,
+    /// Binary attachments
+    Attachments,
+    /// User and tenant preferences
+    Preferences,
+    /// Dashboards
+    Dashboards,
+}
+
+impl std::fmt::Display for ArchiveSection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ArchiveSection::DatabaseRows => "database_rows",
+            ArchiveSection::SearchDocuments => "search_documents",
+            ArchiveSection::Attachments => "attachments",
+            ArchiveSection::Preferences => "preferences",
+            ArchiveSection::Dashboards => "dashboards",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A single exported record
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveRecord {
+    /// Section the record belongs to
+    pub section: ArchiveSection,
+    /// Collection within the section (table, index, ...)
+    pub collection: String,
+    /// Identifier to remap on import, if the record owns one
+    pub id: Option<String>,
+    /// Record payload
+    pub data: serde_json::Value,
+}
+
+impl ArchiveRecord {
+    /// Create a record that owns an identifier
+    pub fn new(
+        section: ArchiveSection,
+        collection: impl Into<String>,
+        id: impl Into<String>,
+        data: serde_json::Value,
+    ) -> Self {
+        Self {
+            section,
+            collection: collection.into(),
+            id: Some(id.into()),
+            data,
+        }
+    }
+
+    /// Create a record without an identifier of its own (e.g. a join row)
+    pub fn link(section: ArchiveSection, collection: impl Into<String>, data: serde_json::Value) -> Self {
+        Self {
+            section,
+            collection: collection.into(),
+            id: None,
+            data,
+        }
+    }
+}
+
+/// Archive header, stored unencrypted so that archives can be inspected
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveHeader {
+    /// Archive format version
+    pub format_version: u32,
+    /// Exported tenant
+    pub tenant_id: String,
+    /// Export timestamp
+    pub created_at: DateTime<Utc>,
+    /// Platform version that produced the archive
+    pub producer_version: String,
+    /// Encryption algorithm of the chunks
+    pub algorithm: String,
+    /// Schema version of each exported section
+    pub sections: BTreeMap<ArchiveSection, u32>,
+}
+
+/// Archive trailer, stored encrypted after the last chunk
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ArchiveTrailer {
+    /// Number of chunks written
+    pub chunk_count: u64,
+    /// Number of records per section
+    pub record_counts: BTreeMap<ArchiveSection, usize>,
+    /// SHA-256 of all chunk plaintexts, hex encoded
+    pub digest: String,
+}
+
+/// Contributor of one kind of tenant data
+#[async_trait]
+pub trait TenantSource: Send + Sync {
+    /// Section this source handles
+    fn section(&self) -> ArchiveSection;
+
+    /// Schema version of the records this source produces and accepts
+    fn schema_version(&self) -> u32 {
+        1
+    }
+
+    /// Export all records of a tenant
+    async fn export(&self, tenant_id: &str) -> TransferResult<Vec<ArchiveRecord>>;
+
+    /// Report problems that would prevent a record from importing
+    async fn check(&self, _record: &ArchiveRecord) -> Option<String> {
+        None
+    }
+
+    /// Import records that have already been remapped; returns the number imported
+    async fn import(&self, records: Vec<ArchiveRecord>) -> TransferResult<usize>;
+}
+
+/// Identifier remapping applied on import
+#[derive(Debug, Clone, Default)]
+pub struct IdRemapper {
+    mapping: HashMap<String, String>,
+}
+
+impl IdRemapper {
+    /// Create an empty remapper
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Assign a fresh identifier to every record that owns one
+    pub fn assign_all(&mut self, records: &[ArchiveRecord]) {
+        for id in records.iter().filter_map(|r| r.id.as_ref()) {
+            self.mapping
+                .entry(id.clone())
+                .or_insert_with(|| Uuid::new_v4().to_string());
+        }
+    }
+
+    /// Map an identifier, leaving unknown ones untouched
+    pub fn map<'a>(&'a self, id: &'a str) -> &'a str {
+        self.mapping.get(id).map(String::as_str).unwrap_or(id)
+    }
+
+    /// Rewrite a record's identifier and every reference in its payload
+    pub fn apply(&self, record: &mut ArchiveRecord) {
+        if let Some(id) = &record.id {
+            record.id = Some(self.map(id).to_string());
+        }
+        self.remap_value(&mut record.data);
+    }
+
+    /// Get the full old-to-new mapping
+    pub fn mapping(&self) -> &HashMap<String, String> {
+        &self.mapping
+    }
+
+    /// Number of remapped identifiers
+    pub fn len(&self) -> usize {
+        self.mapping.len()
+    }
+
+    /// Check if no identifiers are remapped
+    pub fn is_empty(&self) -> bool {
+        self.mapping.is_empty()
+    }
+
+    fn remap_value(&self, value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::String(s) => {
+                if let Some(new_id) = self.mapping.get(s.as_str()) {
+                    *s = new_id.clone();
+                }
+            }
+            serde_json::Value::Array(items) => items.iter_mut().for_each(|v| self.remap_value(v)),
+            serde_json::Value::Object(map) => map.values_mut().for_each(|v| self.remap_value(v)),
+            _ => {}
+        }
+    }
+}
+
+/// Exports tenants into encrypted archives
+pub struct TenantExporter {
+    sources: Vec<Arc<dyn TenantSource>>,
+    chunk_size: usize,
+}
+
+impl TenantExporter {
+    /// Create an exporter with no sources
+    pub fn new() -> Self {
+        Self {
+            sources: Vec::new(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Add a data source
+    pub fn with_source(mut self, source: Arc<dyn TenantSource>) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    /// Set the number of records per chunk
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Export a tenant into `writer`, encrypting with `key`
+    pub async fn export<W: Write>(
+        &self,
+        tenant_id: &str,
+        key: &SymmetricKey,
+        mut writer: W,
+    ) -> TransferResult<ArchiveTrailer> {
+        let header = ArchiveHeader {
+            format_version: ARCHIVE_FORMAT_VERSION,
+            tenant_id: tenant_id.to_string(),
+            created_at: Utc::now(),
+            producer_version: crate::VERSION.to_string(),
+            algorithm: "aes-256-gcm".to_string(),
+            sections: self
+                .sources
+                .iter()
+                .map(|s| (s.section(), s.schema_version()))
+                .collect(),
+        };
+
+        writer.write_all(ARCHIVE_MAGIC)?;
+        writer.write_all(&ARCHIVE_FORMAT_VERSION.to_be_bytes())?;
+        write_block(&mut writer, &serde_json::to_vec(&header)?)?;
+
+        let cipher = Aes256Gcm::new(key);
+        let mut hasher = Sha256Hasher::new();
+        let mut trailer = ArchiveTrailer::default();
+
+        for source in &self.sources {
+            let records = source.export(tenant_id).await?;
+            *trailer.record_counts.entry(source.section()).or_default() += records.len();
+
+            for chunk in records.chunks(self.chunk_size) {
+                let plaintext = serde_json::to_vec(chunk)?;
+                hasher.update(&plaintext);
+
+                let aad = chunk_aad(tenant_id, trailer.chunk_count);
+                let encrypted = cipher.encrypt(&plaintext, Some(&aad))?;
+                writer.write_all(&[FRAME_CHUNK])?;
+                write_block(&mut writer, &serde_json::to_vec(&encrypted)?)?;
+                trailer.chunk_count += 1;
+            }
+        }
+
+        trailer.digest = hasher.finalize_hex();
+        let encrypted = cipher.encrypt(
+            &serde_json::to_vec(&trailer)?,
+            Some(format!("{}:trailer", tenant_id).as_bytes()),
+        )?;
+        writer.write_all(&[FRAME_TRAILER])?;
+        write_block(&mut writer, &serde_json::to_vec(&encrypted)?)?;
+        writer.flush()?;
+
+        info!(
+            "Exported tenant {} in {} chunks ({} records)",
+            tenant_id,
+            trailer.chunk_count,
+            trailer.record_counts.values().sum::<usize>()
+        );
+        Ok(trailer)
+    }
+}
+
+impl Default for TenantExporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Decrypted and verified archive contents
+#[derive(Debug, Clone)]
+pub struct TenantArchive {
+    /// Archive header
+    pub header: ArchiveHeader,
+    /// Archive trailer
+    pub trailer: ArchiveTrailer,
+    /// Records in export order
+    pub records: Vec<ArchiveRecord>,
+}
+
+impl TenantArchive {
+    /// Read, decrypt and verify an archive
+    pub fn read<R: Read>(mut reader: R, key: &SymmetricKey) -> TransferResult<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != ARCHIVE_MAGIC {
+            return Err(TransferError::InvalidArchive("bad magic bytes".to_string()));
+        }
+
+        let mut version = [0u8; 4];
+        reader.read_exact(&mut version)?;
+        let version = u32::from_be_bytes(version);
+        if version > ARCHIVE_FORMAT_VERSION {
+            return Err(TransferError::UnsupportedVersion(version));
+        }
+
+        let header: ArchiveHeader = serde_json::from_slice(&read_block(&mut reader)?)?;
+        let cipher = Aes256Gcm::new(key);
+        let mut hasher = Sha256Hasher::new();
+        let mut records = Vec::new();
+        let mut chunk_index = 0u64;
+
+        let trailer = loop {
+            let mut tag = [0u8; 1];
+            reader.read_exact(&mut tag).map_err(|_| {
+                TransferError::InvalidArchive("archive is truncated (missing trailer)".to_string())
+            })?;
+            let encrypted: EncryptedData = serde_json::from_slice(&read_block(&mut reader)?)?;
+
+            match tag[0] {
+                FRAME_CHUNK => {
+                    let aad = chunk_aad(&header.tenant_id, chunk_index);
+                    let plaintext = cipher.decrypt(&encrypted, Some(&aad))?;
+                    hasher.update(plaintext.as_bytes());
+                    let chunk: Vec<ArchiveRecord> = serde_json::from_slice(plaintext.as_bytes())?;
+                    records.extend(chunk);
+                    chunk_index += 1;
+                }
+                FRAME_TRAILER => {
+                    let aad = format!("{}:trailer", header.tenant_id);
+                    let plaintext = cipher.decrypt(&encrypted, Some(aad.as_bytes()))?;
+                    break serde_json::from_slice::<ArchiveTrailer>(plaintext.as_bytes())?;
+                }
+                other => {
+                    return Err(TransferError::InvalidArchive(format!("unknown frame tag {}", other)));
+                }
+            }
+        };
+
+        let archive = Self {
+            header,
+            trailer,
+            records,
+        };
+        archive.verify(chunk_index, hasher.finalize_hex())?;
+        Ok(archive)
+    }
+
+    /// Count records per section
+    pub fn record_counts(&self) -> BTreeMap<ArchiveSection, usize> {
+        let mut counts = BTreeMap::new();
+        for record in &self.records {
+            *counts.entry(record.section).or_default() += 1;
+        }
+        counts
+    }
+
+    fn verify(&self, chunk_count: u64, digest: String) -> TransferResult<()> {
+        if chunk_count != self.trailer.chunk_count {
+            return Err(TransferError::InvalidArchive(format!(
+                "expected {} chunks, found {}",
+                self.trailer.chunk_count, chunk_count
+            )));
+        }
+
+        if digest != self.trailer.digest {
+            return Err(TransferError::InvalidArchive("digest mismatch".to_string()));
+        }
+
+        let counts = self.record_counts();
+        for (section, expected) in &self.trailer.record_counts {
+            let actual = counts.get(section).copied().unwrap_or(0);
+            if actual != *expected {
+                return Err(TransferError::InvalidArchive(format!(
+                    "{} has {} records, trailer says {}",
+                    section, actual, expected
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Compatibility of one archive section with this deployment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectionReport {
+    /// Section
+    pub section: ArchiveSection,
+    /// Number of records in the archive
+    pub records: usize,
+    /// Schema version in the archive
+    pub archive_schema: u32,
+    /// Schema version of the local source, if one is registered
+    pub local_schema: Option<u32>,
+    /// Blocking problems
+    pub errors: Vec<String>,
+    /// Non-blocking problems
+    pub warnings: Vec<String>,
+}
+
+/// Result of an import or dry run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReport {
+    /// Tenant ID in the archive
+    pub tenant_id: String,
+    /// Archive format version
+    pub format_version: u32,
+    /// Whether this was a dry run
+    pub dry_run: bool,
+    /// Per-section compatibility and counts
+    pub sections: Vec<SectionReport>,
+    /// Number of remapped identifiers
+    pub remapped_ids: usize,
+    /// Number of records imported per section
+    pub imported: BTreeMap<ArchiveSection, usize>,
+}
+
+impl ImportReport {
+    /// Check if the archive can be imported without errors
+    pub fn is_compatible(&self) -> bool {
+        self.sections.iter().all(|s| s.errors.is_empty())
+    }
+}
+
+/// Imports tenants from encrypted archives
+pub struct TenantImporter {
+    sources: HashMap<ArchiveSection, Arc<dyn TenantSource>>,
+    remap_ids: bool,
+}
+
+impl TenantImporter {
+    /// Create an importer with no sources
+    pub fn new() -> Self {
+        Self {
+            sources: HashMap::new(),
+            remap_ids: true,
+        }
+    }
+
+    /// Add a data source
+    pub fn with_source(mut self, source: Arc<dyn TenantSource>) -> Self {
+        self.sources.insert(source.section(), source);
+        self
+    }
+
+    /// Keep original identifiers instead of assigning fresh ones (restore mode)
+    pub fn preserve_ids(mut self) -> Self {
+        self.remap_ids = false;
+        self
+    }
+
+    /// Produce a compatibility report without importing anything
+    pub async fn dry_run<R: Read>(&self, reader: R, key: &SymmetricKey) -> TransferResult<ImportReport> {
+        let archive = TenantArchive::read(reader, key)?;
+        let (report, _) = self.prepare(&archive).await;
+        Ok(report)
+    }
+
+    /// Verify, remap and import an archive
+    pub async fn import<R: Read>(&self, reader: R, key: &SymmetricKey) -> TransferResult<ImportReport> {
+        let archive = TenantArchive::read(reader, key)?;
+        let (mut report, records) = self.prepare(&archive).await;
+        report.dry_run = false;
+
+        if !report.is_compatible() {
+            let errors: Vec<String> = report
+                .sections
+                .iter()
+                .flat_map(|s| s.errors.iter().map(move |e| format!("{}: {}", s.section, e)))
+                .collect();
+            return Err(TransferError::Incompatible(errors.join("; ")));
+        }
+
+        let mut by_section: BTreeMap<ArchiveSection, Vec<ArchiveRecord>> = BTreeMap::new();
+        for record in records {
+            by_section.entry(record.section).or_default().push(record);
+        }
+
+        for (section, records) in by_section {
+            let expected = records.len();
+            let source = &self.sources[&section];
+            let imported = source.import(records).await?;
+            if imported != expected {
+                warn!(
+                    "Imported {} of {} {} records for tenant {}",
+                    imported, expected, section, archive.header.tenant_id
+                );
+            }
+            report.imported.insert(section, imported);
+        }
+
+        info!(
+            "Imported tenant {} ({} identifiers remapped)",
+            archive.header.tenant_id, report.remapped_ids
+        );
+        Ok(report)
+    }
+
+    async fn prepare(&self, archive: &TenantArchive) -> (ImportReport, Vec<ArchiveRecord>) {
+        let mut remapper = IdRemapper::new();
+        if self.remap_ids {
+            remapper.assign_all(&archive.records);
+        }
+
+        let mut records = archive.records.clone();
+        records.iter_mut().for_each(|r| remapper.apply(r));
+
+        let counts = archive.record_counts();
+        let mut sections = Vec::new();
+        for (section, archive_schema) in &archive.header.sections {
+            let source = self.sources.get(section);
+            let mut report = SectionReport {
+                section: *section,
+                records: counts.get(section).copied().unwrap_or(0),
+                archive_schema: *archive_schema,
+                local_schema: source.map(|s| s.schema_version()),
+                errors: Vec::new(),
+                warnings: Vec::new(),
+            };
+
+            match source {
+                None if report.records > 0 => {
+                    report.errors.push("no local source registered".to_string());
+                }
+                None => {}
+                Some(source) => {
+                    let local = source.schema_version();
+                    if *archive_schema > local {
+                        report.errors.push(format!(
+                            "archive schema {} is newer than local schema {}",
+                            archive_schema, local
+                        ));
+                    } else if *archive_schema < local {
+                        report.warnings.push(format!(
+                            "archive schema {} is older than local schema {}",
+                            archive_schema, local
+                        ));
+                    }
+
+                    for record in records.iter().filter(|r| r.section == *section) {
+                        if let Some(problem) = source.check(record).await {
+                            report.errors.push(problem);
+                        }
+                    }
+                }
+            }
+
+            sections.push(report);
+        }
+
+        let report = ImportReport {
+            tenant_id: archive.header.tenant_id.clone(),
+            format_version: archive.header.format_version,
+            dry_run: true,
+            sections,
+            remapped_ids: remapper.len(),
+            imported: BTreeMap::new(),
+        };
+
+        (report, records)
+    }
+}
+
+impl Default for TenantImporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Organization hierarchy rows from the database
+///
+/// The tenant is an organization: its teams, workspaces, memberships and
+/// resource ownership links are exported as database rows.
+pub struct OrganizationSource {
+    pool: Arc<DatabasePool>,
+    organizations: OrganizationRepository,
+}
+
+impl OrganizationSource {
+    /// Create a new organization source
+    pub fn new(pool: Arc<DatabasePool>) -> Self {
+        Self {
+            pool,
+            organizations: OrganizationRepository::new(),
+        }
+    }
+
+    fn row<T: Serialize>(collection: &str, id: Option<&str>, value: &T) -> TransferResult<ArchiveRecord> {
+        let data = serde_json::to_value(value)?;
+        Ok(match id {
+            Some(id) => ArchiveRecord::new(ArchiveSection::DatabaseRows, collection, id, data),
+            None => ArchiveRecord::link(ArchiveSection::DatabaseRows, collection, data),
+        })
+    }
+
+    fn collection_order(collection: &str) -> usize {
+        match collection {
+            "organizations" => 0,
+            "teams" => 1,
+            "workspaces" => 2,
+            "memberships" => 3,
+            "resource_ownership" => 4,
+            _ => 5,
+        }
+    }
+}
+
+#[async_trait]
+impl TenantSource for OrganizationSource {
+    fn section(&self) -> ArchiveSection {
+        ArchiveSection::DatabaseRows
+    }
+
+    async fn export(&self, tenant_id: &str) -> TransferResult<Vec<ArchiveRecord>> {
+        let hierarchy = self
+            .pool
+            .with_connection(|conn| self.organizations.load_hierarchy(conn, tenant_id))?;
+
+        let mut records = Vec::new();
+        let organization = hierarchy
+            .organization(tenant_id)
+            .ok_or_else(|| TransferError::source(self.section(), "organization missing from hierarchy"))?;
+        records.push(Self::row("organizations", Some(&organization.id), organization)?);
+
+        for team in hierarchy.teams_of(tenant_id) {
+            records.push(Self::row("teams", Some(&team.id), team)?);
+        }
+
+        let workspaces = self
+            .pool
+            .with_connection(|conn| self.organizations.find_workspaces(conn, tenant_id))?;
+        for workspace in &workspaces {
+            records.push(Self::row("workspaces", Some(&workspace.id), workspace)?);
+        }
+
+        for membership in hierarchy.memberships() {
+            records.push(Self::row("memberships", None, membership)?);
+        }
+
+        for workspace in &workspaces {
+            let owned = self.pool.with_connection(|conn| {
+                self.organizations
+                    .find_workspace_resources(conn, &workspace.id, None)
+            })?;
+            for ownership in owned {
+                records.push(Self::row("resource_ownership", None, &ownership)?);
+            }
+        }
+
+        Ok(records)
+    }
+
+    async fn check(&self, record: &ArchiveRecord) -> Option<String> {
+        if record.collection != "organizations" {
+            return None;
+        }
+
+        let organization: Organization = match serde_json::from_value(record.data.clone()) {
+            Ok(organization) => organization,
+            Err(e) => return Some(format!("malformed organization row: {}", e)),
+        };
+
+        match self
+            .pool
+            .with_connection(|conn| self.organizations.find_by_slug(conn, &organization.slug))
+        {
+            Ok(Some(_)) => Some(format!("organization slug '{}' already exists", organization.slug)),
+            Ok(None) => None,
+            Err(e) => Some(e.to_string()),
+        }
+    }
+
+    async fn import(&self, mut records: Vec<ArchiveRecord>) -> TransferResult<usize> {
+        records.sort_by_key(|r| Self::collection_order(&r.collection));
+
+        self.pool.with_connection_mut(|conn| execute_transaction(conn, |tx| {
+            let mut imported = 0;
+            for record in &records {
+                let data = record.data.clone();
+                let result = match record.collection.as_str() {
+                    "organizations" => serde_json::from_value::<Organization>(data)
+                        .map(|o| self.organizations.create(tx, &o)),
+                    "teams" => serde_json::from_value::<Team>(data)
+                        .map(|t| self.organizations.create_team(tx, &t)),
+                    "workspaces" => serde_json::from_value::<Workspace>(data)
+                        .map(|w| self.organizations.create_workspace(tx, &w)),
+                    "memberships" => serde_json::from_value::<Membership>(data)
+                        .map(|m| self.organizations.upsert_member(tx, &m)),
+                    "resource_ownership" => serde_json::from_value::<ResourceOwnership>(data)
+                        .map(|o| self.organizations.set_owner(tx, &o)),
+                    other => {
+                        warn!("Skipping unknown database collection {}", other);
+                        continue;
+                    }
+                };
+
+                result.map_err(|e| DatabaseError::InvalidData(e.to_string()))??;
+                imported += 1;
+            }
+            Ok(imported)
+        }))
+        .map_err(TransferError::from)
+    }
+}
+
+/// Dashboards owned by the tenant's workspaces
+pub struct DashboardSource {
+    pool: Arc<DatabasePool>,
+    storage: Arc<dyn PersistenceStorage>,
+    organizations: OrganizationRepository,
+}
+
+impl DashboardSource {
+    /// Create a new dashboard source
+    pub fn new(pool: Arc<DatabasePool>, storage: Arc<dyn PersistenceStorage>) -> Self {
+        Self {
+            pool,
+            storage,
+            organizations: OrganizationRepository::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl TenantSource for DashboardSource {
+    fn section(&self) -> ArchiveSection {
+        ArchiveSection::Dashboards
+    }
+
+    async fn export(&self, tenant_id: &str) -> TransferResult<Vec<ArchiveRecord>> {
+        let dashboard_ids = self.pool.with_connection(|conn| {
+            let mut ids = Vec::new();
+            for workspace in self.organizations.find_workspaces(conn, tenant_id)? {
+                let owned = self.organizations.find_workspace_resources(
+                    conn,
+                    &workspace.id,
+                    Some(OwnedResourceKind::Dashboard),
+                )?;
+                ids.extend(owned.into_iter().map(|o| o.resource_id));
+            }
+            Ok(ids)
+        })?;
+
+        let mut records = Vec::with_capacity(dashboard_ids.len());
+        for dashboard_id in dashboard_ids {
+            let state = match self.storage.load(&dashboard_id).await {
+                Ok(state) => state,
+                Err(e) => {
+                    warn!("Skipping dashboard {} during export: {}", dashboard_id, e);
+                    continue;
+                }
+            };
+            let metadata = self
+                .storage
+                .get_metadata(&dashboard_id)
+                .await
+                .map_err(|e| TransferError::source(self.section(), e))?;
+
+            records.push(ArchiveRecord::new(
+                self.section(),
+                "dashboards",
+                dashboard_id,
+                serde_json::json!({ "metadata": metadata, "state": state }),
+            ));
+        }
+
+        Ok(records)
+    }
+
+    async fn import(&self, records: Vec<ArchiveRecord>) -> TransferResult<usize> {
+        let mut imported = 0;
+        for record in records {
+            let dashboard_id = record
+                .id
+                .clone()
+                .ok_or_else(|| TransferError::source(self.section(), "dashboard record without id"))?;
+            let persisted: accuscene_dashboard::persistence::PersistedDashboard =
+                serde_json::from_value(record.data)?;
+
+            self.storage
+                .save(&dashboard_id, &persisted.state)
+                .await
+                .map_err(|e| TransferError::source(self.section(), e))?;
+            self.storage
+                .update_metadata(&dashboard_id, persisted.metadata)
+                .await
+                .map_err(|e| TransferError::source(self.section(), e))?;
+            imported += 1;
+        }
+
+        Ok(imported)
+    }
+}
+
+fn chunk_aad(tenant_id: &str, index: u64) -> Vec<u8> {
+    format!("{}:{}", tenant_id, index).into_bytes()
+}
+
+fn write_block<W: Write>(writer: &mut W, bytes: &[u8]) -> TransferResult<()> {
+    let len = u32::try_from(bytes.len())
+        .map_err(|_| TransferError::InvalidArchive("block exceeds 4 GiB".to_string()))?;
+    writer.write_all(&len.to_be_bytes())?;
+    writer.write_all(bytes)?;
+    Ok(())
+}
+
+fn read_block<R: Read>(reader: &mut R) -> TransferResult<Vec<u8>> {
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len);
+    if len > MAX_FRAME_BYTES {
+        return Err(TransferError::InvalidArchive(format!("block of {} bytes is too large", len)));
+    }
+
+    let mut buf = vec![0u8; len as usize];
+    reader.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+    use serde_json::json;
+    use std::io::Cursor;
+
+    /// In-memory source exporting fixed records and collecting imports
+    struct MemorySource {
+        records: Vec<ArchiveRecord>,
+        imported: Mutex<Vec<ArchiveRecord>>,
+    }
+
+    impl MemorySource {
+        fn new(records: Vec<ArchiveRecord>) -> Arc<Self> {
+            Arc::new(Self {
+                records,
+                imported: Mutex::new(Vec::new()),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl TenantSource for MemorySource {
+        fn section(&self) -> ArchiveSection {
+            ArchiveSection::Preferences
+        }
+
+        async fn export(&self, _tenant_id: &str) -> TransferResult<Vec<ArchiveRecord>> {
+            Ok(self.records.clone())
+        }
+
+        async fn import(&self, records: Vec<ArchiveRecord>) -> TransferResult<usize> {
+            let count = records.len();
+            self.imported.lock().extend(records);
+            Ok(count)
+        }
+    }
+
+    fn preferences() -> Vec<ArchiveRecord> {
+        vec![
+            ArchiveRecord::new(
+                ArchiveSection::Preferences,
+                "profiles",
+                "p1",
+                json!({ "id": "p1", "units": "metric" }),
+            ),
+            ArchiveRecord::new(
+                ArchiveSection::Preferences,
+                "profiles",
+                "p2",
+                json!({ "id": "p2", "units": "imperial" }),
+            ),
+            ArchiveRecord::link(
+                ArchiveSection::Preferences,
+                "defaults",
+                json!({ "profile_id": "p1", "user_id": "u1" }),
+            ),
+        ]
+    }
+
+    /// Export the preference records into an archive with one record per chunk
+    async fn export(key: &SymmetricKey) -> Vec<u8> {
+        let mut archive = Vec::new();
+        TenantExporter::new()
+            .with_source(MemorySource::new(preferences()))
+            .with_chunk_size(1)
+            .export("tenant-1", key, &mut archive)
+            .await
+            .unwrap();
+        archive
+    }
+
+    /// Split an archive into its preamble and its frames
+    fn frames(archive: &[u8]) -> (Vec<u8>, Vec<Vec<u8>>) {
+        let mut reader = Cursor::new(archive);
+        reader.set_position(8);
+        read_block(&mut reader).unwrap();
+        let preamble = archive[..reader.position() as usize].to_vec();
+
+        let mut frames = Vec::new();
+        while (reader.position() as usize) < archive.len() {
+            let start = reader.position() as usize;
+            reader.set_position(start as u64 + 1);
+            read_block(&mut reader).unwrap();
+            frames.push(archive[start..reader.position() as usize].to_vec());
+        }
+        (preamble, frames)
+    }
+
+    #[tokio::test]
+    async fn test_export_import_round_trip() {
+        let key = SymmetricKey::generate().unwrap();
+        let archive = export(&key).await;
+
+        let target = MemorySource::new(Vec::new());
+        let report = TenantImporter::new()
+            .with_source(target.clone())
+            .import(Cursor::new(&archive), &key)
+            .await
+            .unwrap();
+
+        assert!(!report.dry_run);
+        assert_eq!(report.tenant_id, "tenant-1");
+        assert_eq!(report.remapped_ids, 2);
+        assert_eq!(report.imported[&ArchiveSection::Preferences], 3);
+
+        // Identifiers are fresh, and references follow them
+        let imported = target.imported.lock();
+        let new_p1 = imported[0].id.clone().unwrap();
+        assert_ne!(new_p1, "p1");
+        assert_eq!(imported[0].data["id"], json!(new_p1));
+        assert_eq!(imported[1].data["units"], json!("imperial"));
+        assert_eq!(imported[2].data["profile_id"], json!(new_p1));
+        assert_eq!(imported[2].data["user_id"], json!("u1"));
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_imports_nothing() {
+        let key = SymmetricKey::generate().unwrap();
+        let archive = export(&key).await;
+
+        let target = MemorySource::new(Vec::new());
+        let report = TenantImporter::new()
+            .with_source(target.clone())
+            .dry_run(Cursor::new(&archive), &key)
+            .await
+            .unwrap();
+
+        assert!(report.dry_run);
+        assert!(report.is_compatible());
+        assert!(target.imported.lock().is_empty());
+
+        // Without a matching source the archive is reported as incompatible
+        let report = TenantImporter::new()
+            .dry_run(Cursor::new(&archive), &key)
+            .await
+            .unwrap();
+        assert!(!report.is_compatible());
+    }
+
+    #[tokio::test]
+    async fn test_wrong_key_is_rejected() {
+        let archive = export(&SymmetricKey::generate().unwrap()).await;
+        let wrong_key = SymmetricKey::generate().unwrap();
+
+        assert!(matches!(
+            TenantArchive::read(Cursor::new(&archive), &wrong_key),
+            Err(TransferError::Crypto(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tampered_archive_is_rejected() {
+        let key = SymmetricKey::generate().unwrap();
+        let archive = export(&key).await;
+        let (preamble, frames) = frames(&archive);
+        assert_eq!(frames.len(), 4);
+
+        // Reordered chunks fail authentication
+        let mut reordered = preamble.clone();
+        for index in [1, 0, 2, 3] {
+            reordered.extend_from_slice(&frames[index]);
+        }
+        assert!(matches!(
+            TenantArchive::read(Cursor::new(&reordered), &key),
+            Err(TransferError::Crypto(_))
+        ));
+
+        // A dropped trailing chunk is caught by the trailer
+        let mut dropped = preamble.clone();
+        for index in [0, 1, 3] {
+            dropped.extend_from_slice(&frames[index]);
+        }
+        assert!(matches!(
+            TenantArchive::read(Cursor::new(&dropped), &key),
+            Err(TransferError::InvalidArchive(_))
+        ));
+
+        // A truncated archive is missing its trailer
+        assert!(matches!(
+            TenantArchive::read(Cursor::new(&archive[..archive.len() - frames[3].len()]), &key),
+            Err(TransferError::InvalidArchive(_))
+        ));
+    }
+}
@@ -0,0 +1,983 @@
+//! Cold-storage tiering for closed cases
+//!
+//! Closed cases keep their attachments and simulation artifacts in hot
+//! storage long after anyone looks at them. This module moves them to a
+//! cheaper [`ArchiveBackend`] once a case has been closed for longer than the
+//! [`TieringPolicy`] allows: each artifact is compressed, encrypted with
+//! AES-256-GCM and written to the archive, and the hot copy is replaced by an
+//! [`ArchiveStub`] recording where it went.
+//!
+//! Retrieval is transparent. Opening an archived artifact starts a restore in
+//! the background and reports [`Availability::Restoring`] so clients can show
+//! a "restoring from archive" status until the artifact is back in hot
+//! storage. Every tier change is recorded in the audit trail.
+
+use accuscene_compression::{
+    compress_with_header, decompress_auto, Algorithm, CompressionError, CompressionLevel,
+};
+use accuscene_core::types::case::CaseSummary;
+use accuscene_crypto::hash::Sha256Hasher;
+use accuscene_crypto::symmetric::aes::EncryptedData;
+use accuscene_crypto::symmetric::{Aes256Gcm, SymmetricKey};
+use accuscene_crypto::CryptoError;
+use accuscene_security::audit::{AuditEvent, AuditService, EventResult, EventType, ResourceInfo};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// User recorded in the audit trail for policy-driven migrations
+pub const TIERING_ACTOR: &str = "system:tiering";
+
+/// AES-256-GCM nonce length at the start of every archived object
+const NONCE_LEN: usize = 12;
+
+/// Tiering error
+#[derive(Debug, thiserror::Error)]
+pub enum TieringError {
+    /// Artifact not found
+    #[error("Artifact not found: {0}")]
+    NotFound(String),
+
+    /// Artifact is in hot storage, there is nothing to restore
+    #[error("Artifact is not archived: {0}")]
+    NotArchived(String),
+
+    /// Restored data does not match the checksum recorded at archive time
+    #[error("Integrity check failed for {0}")]
+    IntegrityMismatch(String),
+
+    /// Hot storage error
+    #[error("Artifact store error: {0}")]
+    Store(String),
+
+    /// Archive backend error
+    #[error("Archive backend error: {0}")]
+    Backend(String),
+
+    /// I/O error
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Compression error
+    #[error("Compression error: {0}")]
+    Compression(#[from] CompressionError),
+
+    /// Encryption or decryption error
+    #[error("Crypto error: {0}")]
+    Crypto(#[from] CryptoError),
+}
+
+/// Tiering result type
+pub type TieringResult<T> = Result<T, TieringError>;
+
+/// Storage tier of an artifact
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageTier {
+    /// Primary storage, readable immediately
+    Hot,
+    /// Archive backend, readable after a restore
+    Cold,
+}
+
+impl StorageTier {
+    /// Get the tier name
+    pub fn as_str(&self) -> &str {
+        match self {
+            StorageTier::Hot => "hot",
+            StorageTier::Cold => "cold",
+        }
+    }
+}
+
+/// Kind of case artifact subject to tiering
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    /// Uploaded attachment (photos, documents, video)
+    Attachment,
+    /// Simulation output (trajectories, frames, renders)
+    SimulationArtifact,
+}
+
+impl ArtifactKind {
+    /// Get the kind name, used as the audited resource type
+    pub fn as_str(&self) -> &str {
+        match self {
+            ArtifactKind::Attachment => "attachment",
+            ArtifactKind::SimulationArtifact => "simulation_artifact",
+        }
+    }
+}
+
+/// Which artifacts are moved to cold storage, and when
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TieringPolicy {
+    /// Minimum time since the case was closed
+    pub min_closed_days: i64,
+    /// Artifacts accessed more recently than this stay hot, so restored
+    /// artifacts are not archived again right away
+    pub min_idle_days: i64,
+    /// Artifact kinds to migrate
+    pub kinds: Vec<ArtifactKind>,
+    /// Maximum number of artifacts migrated per run
+    pub max_artifacts_per_run: usize,
+    /// Compression algorithm for archived artifacts (e.g. `zstd`, `brotli`)
+    pub compression: String,
+}
+
+impl Default for TieringPolicy {
+    fn default() -> Self {
+        Self {
+            min_closed_days: 3 * 365,
+            min_idle_days: 90,
+            kinds: vec![ArtifactKind::Attachment, ArtifactKind::SimulationArtifact],
+            max_artifacts_per_run: 1_000,
+            compression: Algorithm::Zstd.as_str().to_string(),
+        }
+    }
+}
+
+impl TieringPolicy {
+    /// Check if an artifact of a closed case is due for cold storage
+    pub fn is_eligible(&self, artifact: &ArtifactRecord, now: DateTime<Utc>) -> bool {
+        let idle_since = now - Duration::days(self.min_idle_days);
+
+        artifact.tier == StorageTier::Hot
+            && self.kinds.contains(&artifact.kind)
+            && artifact.last_accessed_at.unwrap_or(DateTime::<Utc>::MIN_UTC) <= idle_since
+    }
+
+    /// Compression algorithm, falling back to Zstandard for unknown names
+    pub fn algorithm(&self) -> Algorithm {
+        Algorithm::from_str(&self.compression).unwrap_or(Algorithm::Zstd)
+    }
+}
+
+/// Record left in hot storage in place of an archived artifact
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveStub {
+    /// Artifact ID
+    pub artifact_id: String,
+    /// Case the artifact belongs to
+    pub case_id: String,
+    /// Artifact kind
+    pub kind: ArtifactKind,
+    /// Object key in the archive backend
+    pub archive_key: String,
+    /// Identifier of the key the object is encrypted with
+    pub key_id: String,
+    /// Size of the artifact in bytes
+    pub original_size: u64,
+    /// Size of the archived object in bytes
+    pub archived_size: u64,
+    /// SHA-256 of the artifact, hex encoded
+    pub checksum: String,
+    /// When the artifact was archived
+    pub archived_at: DateTime<Utc>,
+}
+
+/// Artifact as known to hot storage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactRecord {
+    /// Artifact ID
+    pub id: String,
+    /// Case the artifact belongs to
+    pub case_id: String,
+    /// Artifact kind
+    pub kind: ArtifactKind,
+    /// Display name
+    pub name: String,
+    /// Size in bytes
+    pub size_bytes: u64,
+    /// Current tier
+    pub tier: StorageTier,
+    /// Last time the artifact was read
+    pub last_accessed_at: Option<DateTime<Utc>>,
+    /// Stub, for archived artifacts
+    pub stub: Option<ArchiveStub>,
+}
+
+/// Hot storage holding case artifacts
+#[async_trait]
+pub trait ArtifactStore: Send + Sync {
+    /// Cases closed before the given time
+    async fn closed_cases(&self, closed_before: DateTime<Utc>) -> TieringResult<Vec<CaseSummary>>;
+
+    /// Artifacts of a case, in either tier
+    async fn artifacts(&self, case_id: &str) -> TieringResult<Vec<ArtifactRecord>>;
+
+    /// Look up a single artifact
+    async fn artifact(&self, artifact_id: &str) -> TieringResult<Option<ArtifactRecord>>;
+
+    /// Read the contents of a hot artifact
+    async fn read(&self, artifact_id: &str) -> TieringResult<Vec<u8>>;
+
+    /// Release the hot copy and keep the stub in its place
+    async fn replace_with_stub(&self, artifact_id: &str, stub: ArchiveStub) -> TieringResult<()>;
+
+    /// Put restored contents back into hot storage and drop the stub
+    async fn restore(&self, artifact_id: &str, contents: Vec<u8>) -> TieringResult<()>;
+}
+
+/// Cheap, slow storage for archived artifacts
+#[async_trait]
+pub trait ArchiveBackend: Send + Sync {
+    /// Store an object
+    async fn put(&self, key: &str, contents: Vec<u8>) -> TieringResult<()>;
+
+    /// Fetch an object
+    async fn get(&self, key: &str) -> TieringResult<Vec<u8>>;
+
+    /// Delete an object
+    async fn delete(&self, key: &str) -> TieringResult<()>;
+}
+
+/// Archive backend on a local or mounted file system (NFS, tape gateway)
+pub struct FileArchiveBackend {
+    root: PathBuf,
+}
+
+impl FileArchiveBackend {
+    /// Create a backend storing objects under `root`
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        key.split('/').fold(self.root.clone(), |path, part| path.join(part))
+    }
+}
+
+#[async_trait]
+impl ArchiveBackend for FileArchiveBackend {
+    async fn put(&self, key: &str, contents: Vec<u8>) -> TieringResult<()> {
+        let path = self.path(key);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, contents).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> TieringResult<Vec<u8>> {
+        match tokio::fs::read(self.path(key)).await {
+            Ok(contents) => Ok(contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Err(TieringError::Backend(format!("archive object {} is missing", key)))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> TieringResult<()> {
+        match tokio::fs::remove_file(self.path(key)).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Whether an artifact can be read right now
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Availability {
+    /// In hot storage
+    Available,
+    /// In cold storage; opening it starts a restore
+    Archived {
+        /// When the artifact was archived
+        archived_at: DateTime<Utc>,
+    },
+    /// Being restored from the archive
+    Restoring {
+        /// When the restore started
+        started_at: DateTime<Utc>,
+    },
+    /// The last restore failed; opening the artifact retries
+    RestoreFailed {
+        /// Failure reason
+        error: String,
+    },
+}
+
+impl Availability {
+    /// Status line to show to users
+    pub fn message(&self) -> &str {
+        match self {
+            Availability::Available => "Available",
+            Availability::Archived { .. } => "Archived",
+            Availability::Restoring { .. } => "Restoring from archive",
+            Availability::RestoreFailed { .. } => "Restore from archive failed",
+        }
+    }
+}
+
+/// Result of opening an artifact
+#[derive(Debug, Clone)]
+pub enum Retrieval {
+    /// Artifact contents
+    Ready(Vec<u8>),
+    /// The artifact is being restored; try again later
+    Restoring {
+        /// When the restore started
+        started_at: DateTime<Utc>,
+    },
+}
+
+/// Outcome of a migration run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MigrationReport {
+    /// Closed cases past the policy age
+    pub cases_scanned: usize,
+    /// Artifacts moved to cold storage
+    pub artifacts_archived: usize,
+    /// Hot storage released, in bytes
+    pub bytes_released: u64,
+    /// Space taken in the archive, in bytes
+    pub bytes_archived: u64,
+    /// Artifacts that failed to migrate, with the reason
+    pub failures: Vec<(String, String)>,
+}
+
+/// Moves artifacts of old closed cases between hot and cold storage
+pub struct TieringService {
+    store: Arc<dyn ArtifactStore>,
+    backend: Arc<dyn ArchiveBackend>,
+    audit: Arc<AuditService>,
+    key: SymmetricKey,
+    key_id: String,
+    policy: TieringPolicy,
+    restores: DashMap<String, Availability>,
+}
+
+impl TieringService {
+    /// Create a new tiering service
+    ///
+    /// Archived objects are encrypted with `key`; `key_id` is recorded in
+    /// each stub so the right key can be found after a rotation.
+    pub fn new(
+        store: Arc<dyn ArtifactStore>,
+        backend: Arc<dyn ArchiveBackend>,
+        audit: Arc<AuditService>,
+        key: SymmetricKey,
+        key_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            store,
+            backend,
+            audit,
+            key,
+            key_id: key_id.into(),
+            policy: TieringPolicy::default(),
+            restores: DashMap::new(),
+        }
+    }
+
+    /// Set the tiering policy
+    pub fn with_policy(mut self, policy: TieringPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Get the tiering policy
+    pub fn policy(&self) -> &TieringPolicy {
+        &self.policy
+    }
+
+    /// Archive every eligible artifact of cases closed longer than the policy allows
+    pub async fn migrate(&self) -> TieringResult<MigrationReport> {
+        let now = Utc::now();
+        let closed_before = now - Duration::days(self.policy.min_closed_days);
+        let mut report = MigrationReport::default();
+
+        let cases = self.store.closed_cases(closed_before).await?;
+        for case in cases {
+            let closed = case.status.is_finalized()
+                && case.closed_at.is_some_and(|closed_at| closed_at <= closed_before);
+            if !closed {
+                continue;
+            }
+            report.cases_scanned += 1;
+
+            for artifact in self.store.artifacts(&case.id).await? {
+                if report.artifacts_archived >= self.policy.max_artifacts_per_run {
+                    info!(
+                        "Tiering run stopped at the per-run limit of {} artifacts",
+                        self.policy.max_artifacts_per_run
+                    );
+                    return Ok(report);
+                }
+                if !self.policy.is_eligible(&artifact, now) {
+                    continue;
+                }
+
+                match self.archive(&artifact).await {
+                    Ok(stub) => {
+                        report.artifacts_archived += 1;
+                        report.bytes_released += stub.original_size;
+                        report.bytes_archived += stub.archived_size;
+                    }
+                    Err(e) => {
+                        warn!("Failed to archive artifact {}: {}", artifact.id, e);
+                        report.failures.push((artifact.id.clone(), e.to_string()));
+                    }
+                }
+            }
+        }
+
+        info!(
+            "Tiering run archived {} artifacts from {} cases ({} bytes released, {} failures)",
+            report.artifacts_archived,
+            report.cases_scanned,
+            report.bytes_released,
+            report.failures.len()
+        );
+        Ok(report)
+    }
+
+    /// Move a single hot artifact to cold storage
+    pub async fn archive(&self, artifact: &ArtifactRecord) -> TieringResult<ArchiveStub> {
+        let contents = self.store.read(&artifact.id).await?;
+        let checksum = checksum(&contents);
+
+        let compressed =
+            compress_with_header(&contents, self.policy.algorithm(), CompressionLevel::Default)?;
+        let encrypted =
+            Aes256Gcm::new(&self.key).encrypt(&compressed, Some(artifact.id.as_bytes()))?;
+        let mut object = encrypted.nonce;
+        object.extend_from_slice(&encrypted.ciphertext);
+
+        let stub = ArchiveStub {
+            artifact_id: artifact.id.clone(),
+            case_id: artifact.case_id.clone(),
+            kind: artifact.kind,
+            archive_key: archive_key(&artifact.case_id),
+            key_id: self.key_id.clone(),
+            original_size: contents.len() as u64,
+            archived_size: object.len() as u64,
+            checksum,
+            archived_at: Utc::now(),
+        };
+
+        self.backend.put(&stub.archive_key, object).await?;
+        if let Err(e) = self.store.replace_with_stub(&artifact.id, stub.clone()).await {
+            // Keep the hot copy authoritative; don't leave an orphaned object behind
+            if let Err(cleanup) = self.backend.delete(&stub.archive_key).await {
+                warn!("Failed to remove orphaned archive object {}: {}", stub.archive_key, cleanup);
+            }
+            return Err(e);
+        }
+
+        self.restores.remove(&artifact.id);
+        self.audit_tier_change(EventType::DataArchived, TIERING_ACTOR, &stub)
+            .await;
+
+        Ok(stub)
+    }
+
+    /// Current availability of an artifact
+    pub async fn availability(&self, artifact_id: &str) -> TieringResult<Availability> {
+        if let Some(state) = self.restores.get(artifact_id) {
+            return Ok(state.clone());
+        }
+
+        let artifact = self.find(artifact_id).await?;
+        Ok(match (artifact.tier, artifact.stub) {
+            (StorageTier::Cold, Some(stub)) => Availability::Archived {
+                archived_at: stub.archived_at,
+            },
+            _ => Availability::Available,
+        })
+    }
+
+    /// Open an artifact, restoring it in the background if it is archived
+    pub async fn open(
+        self: &Arc<Self>,
+        artifact_id: &str,
+        user_id: &str,
+    ) -> TieringResult<Retrieval> {
+        let artifact = self.find(artifact_id).await?;
+        if artifact.tier == StorageTier::Hot {
+            return Ok(Retrieval::Ready(self.store.read(artifact_id).await?));
+        }
+
+        let started_at = self.request_restore(artifact_id, user_id);
+        Ok(Retrieval::Restoring { started_at })
+    }
+
+    /// Start restoring an artifact in the background
+    ///
+    /// Returns when the restore started; a restore already in progress is
+    /// not started twice.
+    pub fn request_restore(self: &Arc<Self>, artifact_id: &str, user_id: &str) -> DateTime<Utc> {
+        let started_at = Utc::now();
+        let mut first = false;
+        let state = self
+            .restores
+            .entry(artifact_id.to_string())
+            .and_modify(|state| {
+                if !matches!(state, Availability::Restoring { .. }) {
+                    *state = Availability::Restoring { started_at };
+                    first = true;
+                }
+            })
+            .or_insert_with(|| {
+                first = true;
+                Availability::Restoring { started_at }
+            })
+            .clone();
+
+        if first {
+            let service = Arc::clone(self);
+            let artifact_id = artifact_id.to_string();
+            let user_id = user_id.to_string();
+            tokio::spawn(async move {
+                if let Err(e) = service.restore(&artifact_id, &user_id).await {
+                    warn!("Failed to restore artifact {} from archive: {}", artifact_id, e);
+                }
+            });
+        }
+
+        match state {
+            Availability::Restoring { started_at: existing } => existing,
+            _ => started_at,
+        }
+    }
+
+    /// Restore an archived artifact to hot storage and return its contents
+    pub async fn restore(&self, artifact_id: &str, user_id: &str) -> TieringResult<Vec<u8>> {
+        self.restores
+            .entry(artifact_id.to_string())
+            .or_insert_with(|| Availability::Restoring { started_at: Utc::now() });
+
+        let result = self.restore_inner(artifact_id, user_id).await;
+        match &result {
+            Ok(_) => {
+                self.restores.remove(artifact_id);
+            }
+            Err(e) => {
+                self.restores.insert(
+                    artifact_id.to_string(),
+                    Availability::RestoreFailed { error: e.to_string() },
+                );
+            }
+        }
+        result
+    }
+
+    async fn restore_inner(&self, artifact_id: &str, user_id: &str) -> TieringResult<Vec<u8>> {
+        let artifact = self.find(artifact_id).await?;
+        let stub = match (artifact.tier, artifact.stub) {
+            (StorageTier::Cold, Some(stub)) => stub,
+            _ => return Err(TieringError::NotArchived(artifact_id.to_string())),
+        };
+
+        let object = self.backend.get(&stub.archive_key).await?;
+        if object.len() < NONCE_LEN {
+            return Err(TieringError::IntegrityMismatch(artifact_id.to_string()));
+        }
+        let (nonce, ciphertext) = object.split_at(NONCE_LEN);
+        let encrypted = EncryptedData {
+            nonce: nonce.to_vec(),
+            ciphertext: ciphertext.to_vec(),
+            algorithm: "aes-256-gcm".to_string(),
+        };
+
+        let compressed =
+            Aes256Gcm::new(&self.key).decrypt(&encrypted, Some(artifact_id.as_bytes()))?;
+        let contents = decompress_auto(compressed.as_bytes())?;
+        if checksum(&contents) != stub.checksum {
+            return Err(TieringError::IntegrityMismatch(artifact_id.to_string()));
+        }
+
+        self.store.restore(artifact_id, contents.clone()).await?;
+        if let Err(e) = self.backend.delete(&stub.archive_key).await {
+            warn!("Failed to delete archive object {} after restore: {}", stub.archive_key, e);
+        }
+
+        self.audit_tier_change(EventType::DataRestored, user_id, &stub)
+            .await;
+
+        Ok(contents)
+    }
+
+    async fn find(&self, artifact_id: &str) -> TieringResult<ArtifactRecord> {
+        self.store
+            .artifact(artifact_id)
+            .await?
+            .ok_or_else(|| TieringError::NotFound(artifact_id.to_string()))
+    }
+
+    async fn audit_tier_change(&self, event_type: EventType, user_id: &str, stub: &ArchiveStub) {
+        let (from, to) = if event_type == EventType::DataArchived {
+            (StorageTier::Hot, StorageTier::Cold)
+        } else {
+            (StorageTier::Cold, StorageTier::Hot)
+        };
+        let action = format!("{}.tier_change", stub.kind.as_str());
+        let event = AuditEvent::new(event_type, action)
+            .with_user(user_id.to_string())
+            .with_resource(ResourceInfo::new(stub.kind.as_str(), &stub.artifact_id))
+            .with_result(EventResult::Success)
+            .add_metadata("case_id".to_string(), stub.case_id.clone())
+            .add_metadata("from_tier".to_string(), from.as_str().to_string())
+            .add_metadata("to_tier".to_string(), to.as_str().to_string())
+            .add_metadata("archive_key".to_string(), stub.archive_key.clone())
+            .add_metadata("size_bytes".to_string(), stub.original_size.to_string());
+
+        if let Err(e) = self.audit.audit(event).await {
+            warn!("Failed to audit tier change of {}: {}", stub.artifact_id, e);
+        }
+    }
+}
+
+/// Archive object key, grouped by case
+fn archive_key(case_id: &str) -> String {
+    let case: String = case_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("{}/{}.bin", case, Uuid::new_v4())
+}
+
+/// SHA-256 of artifact contents, hex encoded
+fn checksum(contents: &[u8]) -> String {
+    let mut hasher = Sha256Hasher::new();
+    hasher.update(contents);
+    hasher.finalize_hex()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use accuscene_core::types::case::{CasePriority, CaseStatus};
+    use accuscene_security::audit::AuditQuery;
+    use parking_lot::Mutex;
+    use std::collections::HashMap;
+
+    /// Artifact record and its hot contents, if any
+    type StoredArtifact = (ArtifactRecord, Option<Vec<u8>>);
+
+    /// Hot storage holding artifacts and their contents in memory
+    #[derive(Default)]
+    struct MemoryStore {
+        cases: Vec<CaseSummary>,
+        artifacts: Mutex<HashMap<String, StoredArtifact>>,
+    }
+
+    impl MemoryStore {
+        fn record(&self, artifact_id: &str) -> ArtifactRecord {
+            self.artifacts.lock()[artifact_id].0.clone()
+        }
+    }
+
+    #[async_trait]
+    impl ArtifactStore for MemoryStore {
+        async fn closed_cases(&self, _closed_before: DateTime<Utc>) -> TieringResult<Vec<CaseSummary>> {
+            Ok(self.cases.clone())
+        }
+
+        async fn artifacts(&self, case_id: &str) -> TieringResult<Vec<ArtifactRecord>> {
+            let mut artifacts: Vec<_> = self
+                .artifacts
+                .lock()
+                .values()
+                .filter(|(artifact, _)| artifact.case_id == case_id)
+                .map(|(artifact, _)| artifact.clone())
+                .collect();
+            artifacts.sort_by(|a, b| a.id.cmp(&b.id));
+            Ok(artifacts)
+        }
+
+        async fn artifact(&self, artifact_id: &str) -> TieringResult<Option<ArtifactRecord>> {
+            Ok(self.artifacts.lock().get(artifact_id).map(|(artifact, _)| artifact.clone()))
+        }
+
+        async fn read(&self, artifact_id: &str) -> TieringResult<Vec<u8>> {
+            self.artifacts
+                .lock()
+                .get(artifact_id)
+                .and_then(|(_, contents)| contents.clone())
+                .ok_or_else(|| TieringError::NotFound(artifact_id.to_string()))
+        }
+
+        async fn replace_with_stub(&self, artifact_id: &str, stub: ArchiveStub) -> TieringResult<()> {
+            let mut artifacts = self.artifacts.lock();
+            let (artifact, contents) = artifacts
+                .get_mut(artifact_id)
+                .ok_or_else(|| TieringError::NotFound(artifact_id.to_string()))?;
+            artifact.tier = StorageTier::Cold;
+            artifact.stub = Some(stub);
+            *contents = None;
+            Ok(())
+        }
+
+        async fn restore(&self, artifact_id: &str, contents: Vec<u8>) -> TieringResult<()> {
+            let mut artifacts = self.artifacts.lock();
+            let (artifact, stored) = artifacts
+                .get_mut(artifact_id)
+                .ok_or_else(|| TieringError::NotFound(artifact_id.to_string()))?;
+            artifact.tier = StorageTier::Hot;
+            artifact.stub = None;
+            artifact.last_accessed_at = Some(Utc::now());
+            *stored = Some(contents);
+            Ok(())
+        }
+    }
+
+    /// Archive backend keeping objects in memory
+    #[derive(Default)]
+    struct MemoryBackend {
+        objects: DashMap<String, Vec<u8>>,
+    }
+
+    #[async_trait]
+    impl ArchiveBackend for MemoryBackend {
+        async fn put(&self, key: &str, contents: Vec<u8>) -> TieringResult<()> {
+            self.objects.insert(key.to_string(), contents);
+            Ok(())
+        }
+
+        async fn get(&self, key: &str) -> TieringResult<Vec<u8>> {
+            self.objects
+                .get(key)
+                .map(|object| object.clone())
+                .ok_or_else(|| TieringError::Backend(format!("no object {}", key)))
+        }
+
+        async fn delete(&self, key: &str) -> TieringResult<()> {
+            self.objects.remove(key);
+            Ok(())
+        }
+    }
+
+    fn case(id: &str, status: CaseStatus, closed_days_ago: Option<i64>) -> CaseSummary {
+        CaseSummary {
+            id: id.to_string(),
+            title: id.to_string(),
+            status,
+            priority: CasePriority::Normal,
+            vehicle_count: 2,
+            investigator_count: 1,
+            is_overdue: false,
+            duration_days: 10,
+            opened_at: Utc::now() - Duration::days(4000),
+            closed_at: closed_days_ago.map(|days| Utc::now() - Duration::days(days)),
+        }
+    }
+
+    fn artifact(id: &str, case_id: &str, kind: ArtifactKind) -> ArtifactRecord {
+        ArtifactRecord {
+            id: id.to_string(),
+            case_id: case_id.to_string(),
+            kind,
+            name: format!("{}.bin", id),
+            size_bytes: 0,
+            tier: StorageTier::Hot,
+            last_accessed_at: None,
+            stub: None,
+        }
+    }
+
+    fn contents(artifact_id: &str) -> Vec<u8> {
+        format!("{} contents ", artifact_id).repeat(100).into_bytes()
+    }
+
+    struct Fixture {
+        store: Arc<MemoryStore>,
+        backend: Arc<MemoryBackend>,
+        audit: Arc<AuditService>,
+        service: Arc<TieringService>,
+    }
+
+    /// An old closed case `c1` with two attachments and an active case `c2`
+    fn fixture() -> Fixture {
+        let store = MemoryStore {
+            cases: vec![
+                case("c1", CaseStatus::Completed, Some(2000)),
+                case("c2", CaseStatus::Active, None),
+            ],
+            ..MemoryStore::default()
+        };
+        for (id, case_id) in [("a1", "c1"), ("a2", "c1"), ("a3", "c2")] {
+            store.artifacts.lock().insert(
+                id.to_string(),
+                (artifact(id, case_id, ArtifactKind::Attachment), Some(contents(id))),
+            );
+        }
+
+        let store = Arc::new(store);
+        let backend = Arc::new(MemoryBackend::default());
+        let audit = Arc::new(AuditService::default());
+        let service = Arc::new(TieringService::new(
+            store.clone(),
+            backend.clone(),
+            audit.clone(),
+            SymmetricKey::generate().unwrap(),
+            "key-1",
+        ));
+
+        Fixture {
+            store,
+            backend,
+            audit,
+            service,
+        }
+    }
+
+    async fn audited(audit: &AuditService, event_type: EventType) -> usize {
+        audit
+            .query(AuditQuery::new().event_type(event_type))
+            .await
+            .unwrap()
+            .total_count
+    }
+
+    #[test]
+    fn test_policy_eligibility() {
+        let policy = TieringPolicy::default();
+        let now = Utc::now();
+
+        let mut attachment = artifact("a1", "c1", ArtifactKind::Attachment);
+        assert!(policy.is_eligible(&attachment, now));
+
+        attachment.last_accessed_at = Some(now - Duration::days(policy.min_idle_days + 1));
+        assert!(policy.is_eligible(&attachment, now));
+
+        // Recently read artifacts stay hot
+        attachment.last_accessed_at = Some(now - Duration::days(1));
+        assert!(!policy.is_eligible(&attachment, now));
+
+        let mut archived = artifact("a2", "c1", ArtifactKind::Attachment);
+        archived.tier = StorageTier::Cold;
+        assert!(!policy.is_eligible(&archived, now));
+
+        let policy = TieringPolicy {
+            kinds: vec![ArtifactKind::SimulationArtifact],
+            ..TieringPolicy::default()
+        };
+        assert!(!policy.is_eligible(&artifact("a3", "c1", ArtifactKind::Attachment), now));
+    }
+
+    #[tokio::test]
+    async fn test_migrate_demotes_old_closed_cases() {
+        let fixture = fixture();
+
+        let report = fixture.service.migrate().await.unwrap();
+        assert_eq!(report.cases_scanned, 1);
+        assert_eq!(report.artifacts_archived, 2);
+        assert_eq!(report.bytes_released, 2 * contents("a1").len() as u64);
+        assert!(report.failures.is_empty());
+
+        let archived = fixture.store.record("a1");
+        assert_eq!(archived.tier, StorageTier::Cold);
+        let stub = archived.stub.unwrap();
+        assert_eq!(stub.key_id, "key-1");
+        assert!(stub.archive_key.starts_with("c1/"));
+        assert!(fixture.backend.objects.contains_key(&stub.archive_key));
+        assert!(matches!(
+            fixture.service.availability("a1").await.unwrap(),
+            Availability::Archived { .. }
+        ));
+
+        // Artifacts of the active case stay hot
+        assert_eq!(fixture.store.record("a3").tier, StorageTier::Hot);
+        assert_eq!(fixture.service.availability("a3").await.unwrap(), Availability::Available);
+
+        assert_eq!(audited(&fixture.audit, EventType::DataArchived).await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_respects_run_limit() {
+        let fixture = fixture();
+        let service = TieringService::new(
+            fixture.store.clone(),
+            fixture.backend.clone(),
+            fixture.audit.clone(),
+            SymmetricKey::generate().unwrap(),
+            "key-1",
+        )
+        .with_policy(TieringPolicy {
+            max_artifacts_per_run: 1,
+            ..TieringPolicy::default()
+        });
+
+        assert_eq!(service.migrate().await.unwrap().artifacts_archived, 1);
+        assert_eq!(service.migrate().await.unwrap().artifacts_archived, 1);
+        assert_eq!(service.migrate().await.unwrap().artifacts_archived, 0);
+    }
+
+    #[tokio::test]
+    async fn test_restore_promotes_to_hot_storage() {
+        let fixture = fixture();
+        fixture.service.migrate().await.unwrap();
+        let archive_key = fixture.store.record("a2").stub.unwrap().archive_key;
+
+        let restored = fixture.service.restore("a2", "user-1").await.unwrap();
+        assert_eq!(restored, contents("a2"));
+
+        let record = fixture.store.record("a2");
+        assert_eq!(record.tier, StorageTier::Hot);
+        assert!(record.stub.is_none());
+        assert!(!fixture.backend.objects.contains_key(&archive_key));
+        assert_eq!(audited(&fixture.audit, EventType::DataRestored).await, 1);
+
+        assert!(matches!(
+            fixture.service.restore("a2", "user-1").await,
+            Err(TieringError::NotArchived(_))
+        ));
+
+        // The restored artifact was just read, so it is not archived again
+        assert_eq!(fixture.service.migrate().await.unwrap().artifacts_archived, 0);
+    }
+
+    #[tokio::test]
+    async fn test_open_restores_in_background() {
+        let fixture = fixture();
+        fixture.service.migrate().await.unwrap();
+
+        assert!(matches!(
+            fixture.service.open("a1", "user-1").await.unwrap(),
+            Retrieval::Restoring { .. }
+        ));
+
+        for _ in 0..100 {
+            if fixture.service.availability("a1").await.unwrap() == Availability::Available {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+
+        match fixture.service.open("a1", "user-1").await.unwrap() {
+            Retrieval::Ready(restored) => assert_eq!(restored, contents("a1")),
+            Retrieval::Restoring { .. } => panic!("artifact was not restored"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tampered_archive_object_is_not_restored() {
+        let fixture = fixture();
+        fixture.service.migrate().await.unwrap();
+        let archive_key = fixture.store.record("a1").stub.unwrap().archive_key;
+
+        fixture.backend.objects.get_mut(&archive_key).unwrap()[NONCE_LEN] ^= 0xff;
+
+        assert!(matches!(
+            fixture.service.restore("a1", "user-1").await,
+            Err(TieringError::Crypto(_))
+        ));
+        assert!(matches!(
+            fixture.service.availability("a1").await.unwrap(),
+            Availability::RestoreFailed { .. }
+        ));
+        assert_eq!(fixture.store.record("a1").tier, StorageTier::Cold);
+    }
+}
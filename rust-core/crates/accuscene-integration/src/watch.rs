@@ -0,0 +1,176 @@
+//! Fan-out of domain events to entity watchers
+//!
+//! Events published on the event bus that name an entity in their metadata
+//! (see [`EventMetadata::with_entity`]) are turned into notifications for
+//! every user following that entity.
+
+use crate::events::{
+    Event, EventError, EventHandler, EventMetadata, EventType, HandlerId, ACTOR_ID_KEY,
+    ENTITY_ID_KEY, ENTITY_TYPE_KEY,
+};
+use accuscene_notifications::{NotificationLevel, NotificationSystem, WatchEvent};
+use async_trait::async_trait;
+use std::sync::Arc;
+use tracing::debug;
+
+/// Custom metadata key for the notification title
+pub const TITLE_KEY: &str = "title";
+
+/// Custom metadata key for the notification message
+pub const MESSAGE_KEY: &str = "message";
+
+/// Custom metadata key for the notification level
+pub const LEVEL_KEY: &str = "level";
+
+/// Custom metadata key for the organization the entity belongs to
+pub const ORGANIZATION_ID_KEY: &str = "organization_id";
+
+/// Build a watch event from event metadata
+///
+/// Returns `None` if the metadata does not name an entity.
+pub fn watch_event_from_metadata(metadata: &EventMetadata) -> Option<WatchEvent> {
+    let entity_type = metadata.custom_str(ENTITY_TYPE_KEY)?;
+    let entity_id = metadata.custom_str(ENTITY_ID_KEY)?;
+
+    let title = metadata
+        .custom_str(TITLE_KEY)
+        .map_or_else(|| format!("{entity_type} {entity_id}: {}", metadata.event_type), str::to_string);
+    let message = metadata.custom_str(MESSAGE_KEY).unwrap_or_default();
+
+    let mut event = WatchEvent::new(entity_type, entity_id, metadata.event_type.clone(), title, message);
+    if let Some(actor_id) = metadata.custom_str(ACTOR_ID_KEY) {
+        event = event.with_actor(actor_id);
+    }
+    if let Some(organization_id) = metadata.custom_str(ORGANIZATION_ID_KEY) {
+        event = event.with_organization(organization_id);
+    }
+    if let Some(level) = metadata
+        .custom
+        .get(LEVEL_KEY)
+        .and_then(|value| serde_json::from_value::<NotificationLevel>(value.clone()).ok())
+    {
+        event = event.with_level(level);
+    }
+
+    event.metadata = metadata
+        .custom
+        .iter()
+        .filter(|(key, _)| {
+            ![ENTITY_TYPE_KEY, ENTITY_ID_KEY, ACTOR_ID_KEY, TITLE_KEY, MESSAGE_KEY, LEVEL_KEY]
+                .contains(&key.as_str())
+        })
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    event
+        .metadata
+        .insert("event_id".to_string(), serde_json::json!(metadata.id));
+
+    Some(event)
+}
+
+/// Event handler that notifies the watchers of the entity an event names
+pub struct WatchFanout {
+    id: HandlerId,
+    system: Arc<NotificationSystem>,
+    event_types: Vec<EventType>,
+}
+
+impl WatchFanout {
+    /// Create a fan-out handler for the given event types
+    pub fn new(system: Arc<NotificationSystem>, event_types: Vec<EventType>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4(),
+            system,
+            event_types,
+        }
+    }
+}
+
+#[async_trait]
+impl EventHandler for WatchFanout {
+    async fn handle(&self, event: Arc<dyn Event>) -> Result<(), EventError> {
+        let Some(watch_event) = watch_event_from_metadata(&event.metadata()) else {
+            return Ok(());
+        };
+
+        let sent = self
+            .system
+            .notify_watchers(&watch_event)
+            .await
+            .map_err(|e| EventError::HandlerError(e.to_string()))?;
+
+        debug!(
+            "Notified {} watcher(s) of {} {} ({})",
+            sent.len(),
+            watch_event.entity_type,
+            watch_event.entity_id,
+            watch_event.event_type
+        );
+        Ok(())
+    }
+
+    fn handler_id(&self) -> HandlerId {
+        self.id
+    }
+
+    fn subscribes_to(&self) -> Vec<EventType> {
+        self.event_types.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_metadata_without_entity_is_ignored() {
+        let metadata = EventMetadata::new("case.updated".to_string());
+        assert!(watch_event_from_metadata(&metadata).is_none());
+
+        let metadata = metadata.with_custom(ENTITY_TYPE_KEY.to_string(), json!("case"));
+        assert!(watch_event_from_metadata(&metadata).is_none());
+    }
+
+    #[test]
+    fn test_watch_event_from_metadata() {
+        let metadata = EventMetadata::new("case.status_changed".to_string())
+            .with_entity("case", "case-1")
+            .with_actor("user-1")
+            .with_custom(ORGANIZATION_ID_KEY.to_string(), json!("org-1"))
+            .with_custom(MESSAGE_KEY.to_string(), json!("Case moved to review"))
+            .with_custom(LEVEL_KEY.to_string(), json!("warning"))
+            .with_custom("status".to_string(), json!("review"));
+
+        let event = watch_event_from_metadata(&metadata).unwrap();
+        assert_eq!(event.entity_type, "case");
+        assert_eq!(event.entity_id, "case-1");
+        assert_eq!(event.event_type, "case.status_changed");
+        assert_eq!(event.title, "case case-1: case.status_changed");
+        assert_eq!(event.message, "Case moved to review");
+        assert_eq!(event.actor_id.as_deref(), Some("user-1"));
+        assert_eq!(event.organization_id.as_deref(), Some("org-1"));
+        assert_eq!(event.level, NotificationLevel::Warning);
+
+        // Keys mapped to event fields are not repeated in the metadata
+        let mut keys: Vec<_> = event.metadata.keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        assert_eq!(keys, ["event_id", ORGANIZATION_ID_KEY, "status"]);
+        assert_eq!(event.metadata["event_id"], json!(metadata.id));
+    }
+
+    #[test]
+    fn test_custom_title_and_unknown_level() {
+        let metadata = EventMetadata::new("report.published".to_string())
+            .with_entity("report", "report-7")
+            .with_custom(TITLE_KEY.to_string(), json!("Final report published"))
+            .with_custom(LEVEL_KEY.to_string(), json!("urgent"));
+
+        let event = watch_event_from_metadata(&metadata).unwrap();
+        assert_eq!(event.title, "Final report published");
+        assert_eq!(event.message, "");
+        assert_eq!(event.level, NotificationLevel::Info);
+        assert!(event.actor_id.is_none());
+        assert!(event.organization_id.is_none());
+    }
+}
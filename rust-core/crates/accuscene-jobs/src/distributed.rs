@@ -0,0 +1,524 @@
+//! Distributed job execution across cluster nodes.
+//!
+//! A [`DistributedQueue`] spreads jobs over the cluster by consistent
+//! hashing of job IDs. Jobs owned by the local node go to the local queue;
+//! jobs owned by another node are serialized and handed to the transport
+//! through an outbound queue, and the receiving node decodes them with the
+//! job types registered on it. Dispatched jobs are remembered until the
+//! owner reports them complete, so the jobs of a failed node can be routed
+//! again to the nodes that take over its share of the ring.
+//!
+//! Scheduled jobs should go through [`DistributedQueue::leader_only`] so
+//! that only the cluster leader enqueues them, however many nodes run the
+//! same schedules.
+//!
+//! Idempotency keys are tracked by the node a job is pushed on, before the
+//! job is routed, so duplicates are caught when retries are submitted to the
+//! same node.
+
+use crate::error::{JobError, Result};
+use crate::job::Job;
+use crate::metrics::{JobMetrics, JobStatistics};
+use crate::queue::{DuplicatePolicy, IdempotencyKeys, JobQueue};
+use accuscene_cluster::{ClusterCoordinator, ConsistentHashRing};
+use async_trait::async_trait;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// Virtual nodes per cluster node on the job ring
+const VIRTUAL_NODES: usize = 150;
+
+/// Decodes a serialized job of one type
+pub type JobDecoder = Arc<dyn Fn(&str) -> Result<Box<dyn Job>> + Send + Sync>;
+
+/// Messages for other nodes, with the node each is for
+pub type OutboundJobMessages = mpsc::UnboundedReceiver<(Uuid, ClusterJobMessage)>;
+
+/// Message exchanged between the job queues of cluster nodes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ClusterJobMessage {
+    /// Run a job owned by the receiving node
+    Dispatch {
+        /// Job ID
+        job_id: String,
+        /// Job type, selecting the decoder
+        job_name: String,
+        /// Serialized job
+        payload: String,
+    },
+
+    /// A dispatched job has finished
+    Completed {
+        /// Job ID
+        job_id: String,
+    },
+
+    /// Job statistics of the sending node
+    Metrics {
+        /// Statistics of the sender
+        statistics: JobStatistics,
+    },
+}
+
+/// Job statistics for the whole cluster
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusterJobStatistics {
+    /// Statistics reported by each node
+    pub nodes: HashMap<Uuid, JobStatistics>,
+
+    /// Sum over all nodes
+    pub total: JobStatistics,
+}
+
+/// A job sent to another node and not yet reported complete
+struct Assignment {
+    owner: Uuid,
+    job_name: String,
+    payload: String,
+}
+
+/// Job queue partitioned across cluster nodes
+pub struct DistributedQueue {
+    local_id: Uuid,
+    coordinator: Arc<ClusterCoordinator>,
+    local: Arc<dyn JobQueue>,
+    ring: RwLock<ConsistentHashRing>,
+    decoders: RwLock<HashMap<String, JobDecoder>>,
+    assignments: RwLock<HashMap<String, Assignment>>,
+    origins: RwLock<HashMap<String, Uuid>>,
+    keys: IdempotencyKeys,
+    duplicate_policy: DuplicatePolicy,
+    metrics: JobMetrics,
+    remote_metrics: RwLock<HashMap<Uuid, JobStatistics>>,
+    outbound_tx: mpsc::UnboundedSender<(Uuid, ClusterJobMessage)>,
+    outbound_rx: Mutex<Option<OutboundJobMessages>>,
+}
+
+impl DistributedQueue {
+    /// Create a distributed queue running local jobs from the given queue
+    pub fn new(coordinator: Arc<ClusterCoordinator>, local: Arc<dyn JobQueue>) -> Self {
+        let local_id = coordinator.local_id();
+        let mut ring = ConsistentHashRing::new(VIRTUAL_NODES);
+        ring.add_node(local_id);
+        let (outbound_tx, outbound_rx) = mpsc::unbounded_channel();
+
+        Self {
+            local_id,
+            coordinator,
+            local,
+            ring: RwLock::new(ring),
+            decoders: RwLock::new(HashMap::new()),
+            assignments: RwLock::new(HashMap::new()),
+            origins: RwLock::new(HashMap::new()),
+            keys: IdempotencyKeys::new(),
+            duplicate_policy: DuplicatePolicy::default(),
+            metrics: JobMetrics::new(),
+            remote_metrics: RwLock::new(HashMap::new()),
+            outbound_tx,
+            outbound_rx: Mutex::new(Some(outbound_rx)),
+        }
+    }
+
+    /// Set how pushes with an idempotency key already taken are handled
+    pub fn with_duplicate_policy(mut self, policy: DuplicatePolicy) -> Self {
+        self.duplicate_policy = policy;
+        self
+    }
+
+    /// Register a job type this node can run for other nodes
+    pub fn register_job_type<J: Job + 'static>(&self, job_name: impl Into<String>) {
+        let decoder: JobDecoder = Arc::new(|data: &str| J::deserialize(data));
+        self.decoders.write().insert(job_name.into(), decoder);
+    }
+
+    /// Take the queue of messages for other nodes
+    pub fn take_outbound(&self) -> Option<OutboundJobMessages> {
+        self.outbound_rx.lock().take()
+    }
+
+    /// Get the metrics collector for jobs run on this node
+    pub fn metrics(&self) -> &JobMetrics {
+        &self.metrics
+    }
+
+    /// Get the node that owns a job
+    pub fn owner(&self, job_id: &str) -> Uuid {
+        self.ring.read().get_node(job_id).unwrap_or(self.local_id)
+    }
+
+    /// Get the number of jobs dispatched to other nodes and not yet complete
+    pub fn pending_remote(&self) -> usize {
+        self.assignments.read().len()
+    }
+
+    /// Add a node to the job ring
+    pub fn add_node(&self, node_id: Uuid) {
+        self.ring.write().add_node(node_id);
+    }
+
+    /// Remove a failed node and route its unfinished jobs to their new owners
+    ///
+    /// Returns the number of jobs routed again.
+    pub async fn handle_node_failure(&self, node_id: Uuid) -> Result<usize> {
+        if node_id == self.local_id {
+            return Ok(0);
+        }
+
+        self.ring.write().remove_node(&node_id);
+        self.remote_metrics.write().remove(&node_id);
+
+        let orphaned: Vec<(String, Assignment)> = {
+            let mut assignments = self.assignments.write();
+            let job_ids: Vec<String> = assignments
+                .iter()
+                .filter(|(_, assignment)| assignment.owner == node_id)
+                .map(|(job_id, _)| job_id.clone())
+                .collect();
+            job_ids
+                .into_iter()
+                .filter_map(|job_id| assignments.remove(&job_id).map(|a| (job_id, a)))
+                .collect()
+        };
+
+        let count = orphaned.len();
+        for (job_id, assignment) in orphaned {
+            self.route(job_id, assignment.job_name, assignment.payload)
+                .await?;
+        }
+
+        if count > 0 {
+            tracing::info!(node_id = %node_id, jobs = count, "Rebalanced jobs of failed node");
+        }
+        Ok(count)
+    }
+
+    /// Report a job run on this node as finished
+    ///
+    /// Tells the node that dispatched the job, if any, so it stops tracking it.
+    pub fn complete(&self, job_id: &str) {
+        if let Some(origin) = self.origins.write().remove(job_id) {
+            self.send(
+                origin,
+                ClusterJobMessage::Completed {
+                    job_id: job_id.to_string(),
+                },
+            );
+        }
+    }
+
+    /// Handle a message from another node
+    pub async fn handle_message(&self, from: Uuid, message: ClusterJobMessage) -> Result<()> {
+        match message {
+            ClusterJobMessage::Dispatch {
+                job_id,
+                job_name,
+                payload,
+            } => {
+                let job = self.decode(&job_name, &payload)?;
+                self.origins.write().insert(job_id, from);
+                self.local.push(job).await
+            }
+            ClusterJobMessage::Completed { job_id } => {
+                self.assignments.write().remove(&job_id);
+                Ok(())
+            }
+            ClusterJobMessage::Metrics { statistics } => {
+                self.remote_metrics.write().insert(from, statistics);
+                Ok(())
+            }
+        }
+    }
+
+    /// Send this node's job statistics to every other node
+    pub fn broadcast_metrics(&self) {
+        let statistics = self.metrics.get_statistics();
+        let nodes: Vec<Uuid> = self.ring.read().nodes().to_vec();
+
+        for node_id in nodes.into_iter().filter(|id| *id != self.local_id) {
+            self.send(
+                node_id,
+                ClusterJobMessage::Metrics {
+                    statistics: statistics.clone(),
+                },
+            );
+        }
+    }
+
+    /// Get job statistics for the whole cluster
+    pub fn cluster_metrics(&self) -> ClusterJobStatistics {
+        let mut nodes = self.remote_metrics.read().clone();
+        nodes.insert(self.local_id, self.metrics.get_statistics());
+
+        let mut total = JobStatistics::default();
+        for statistics in nodes.values() {
+            total.merge(statistics);
+        }
+
+        ClusterJobStatistics { nodes, total }
+    }
+
+    /// Get a queue that only accepts jobs while this node leads the cluster
+    pub fn leader_only(self: &Arc<Self>) -> Arc<dyn JobQueue> {
+        Arc::new(LeaderQueue {
+            inner: Arc::clone(self),
+        })
+    }
+
+    /// Send a job to its owner
+    async fn route(&self, job_id: String, job_name: String, payload: String) -> Result<()> {
+        let owner = self.owner(&job_id);
+        if owner == self.local_id {
+            let job = self.decode(&job_name, &payload)?;
+            return self.local.push(job).await;
+        }
+
+        self.assignments.write().insert(
+            job_id.clone(),
+            Assignment {
+                owner,
+                job_name: job_name.clone(),
+                payload: payload.clone(),
+            },
+        );
+        self.send(
+            owner,
+            ClusterJobMessage::Dispatch {
+                job_id,
+                job_name,
+                payload,
+            },
+        );
+        Ok(())
+    }
+
+    fn decode(&self, job_name: &str, payload: &str) -> Result<Box<dyn Job>> {
+        let decoder = self
+            .decoders
+            .read()
+            .get(job_name)
+            .cloned()
+            .ok_or_else(|| JobError::ClusterError(format!("Unknown job type: {}", job_name)))?;
+        decoder(payload)
+    }
+
+    fn send(&self, node_id: Uuid, message: ClusterJobMessage) {
+        if self.outbound_tx.send((node_id, message)).is_err() {
+            tracing::warn!(node_id = %node_id, "Job message dropped, transport is gone");
+        }
+    }
+}
+
+#[async_trait]
+impl JobQueue for DistributedQueue {
+    async fn push(&self, job: Box<dyn Job>) -> Result<()> {
+        let job_id = job.id().to_string();
+        if self.owner(&job_id) == self.local_id {
+            return self.local.push(job).await;
+        }
+
+        let payload = job.serialize()?;
+        self.route(job_id, job.name().to_string(), payload).await
+    }
+
+    async fn push_with_key(
+        &self,
+        job: Box<dyn Job>,
+        key: &str,
+        window: Duration,
+    ) -> Result<String> {
+        let job_id = job.id().to_string();
+        if let Some(existing) = self.keys.reserve(key, &job_id, window) {
+            return self.duplicate_policy.resolve(key, existing);
+        }
+
+        if let Err(e) = self.push(job).await {
+            self.keys.release(key, &job_id);
+            return Err(e);
+        }
+
+        Ok(job_id)
+    }
+
+    async fn pop(&self) -> Result<Option<Box<dyn Job>>> {
+        self.local.pop().await
+    }
+
+    async fn peek(&self) -> Result<Option<Box<dyn Job>>> {
+        self.local.peek().await
+    }
+
+    async fn len(&self) -> Result<usize> {
+        self.local.len().await
+    }
+
+    async fn clear(&self) -> Result<()> {
+        self.keys.clear();
+        self.local.clear().await
+    }
+
+    async fn get(&self, job_id: &str) -> Result<Option<Box<dyn Job>>> {
+        self.local.get(job_id).await
+    }
+
+    async fn remove(&self, job_id: &str) -> Result<bool> {
+        self.local.remove(job_id).await
+    }
+}
+
+/// Queue for schedulers that drops jobs unless this node is the leader
+struct LeaderQueue {
+    inner: Arc<DistributedQueue>,
+}
+
+#[async_trait]
+impl JobQueue for LeaderQueue {
+    async fn push(&self, job: Box<dyn Job>) -> Result<()> {
+        if !self.inner.coordinator.is_leader() {
+            tracing::debug!(job_id = %job.id(), "Not the leader, skipping scheduled job");
+            return Ok(());
+        }
+        self.inner.push(job).await
+    }
+
+    async fn push_with_key(
+        &self,
+        job: Box<dyn Job>,
+        key: &str,
+        window: Duration,
+    ) -> Result<String> {
+        if !self.inner.coordinator.is_leader() {
+            tracing::debug!(job_id = %job.id(), "Not the leader, skipping scheduled job");
+            return Ok(job.id().to_string());
+        }
+        self.inner.push_with_key(job, key, window).await
+    }
+
+    async fn pop(&self) -> Result<Option<Box<dyn Job>>> {
+        self.inner.pop().await
+    }
+
+    async fn peek(&self) -> Result<Option<Box<dyn Job>>> {
+        self.inner.peek().await
+    }
+
+    async fn len(&self) -> Result<usize> {
+        self.inner.len().await
+    }
+
+    async fn clear(&self) -> Result<()> {
+        self.inner.clear().await
+    }
+
+    async fn get(&self, job_id: &str) -> Result<Option<Box<dyn Job>>> {
+        self.inner.get(job_id).await
+    }
+
+    async fn remove(&self, job_id: &str) -> Result<bool> {
+        self.inner.remove(job_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::job::PhysicsSimulationJob;
+    use crate::queue::memory::MemoryQueue;
+    use accuscene_cluster::node::NodeId;
+    use accuscene_cluster::ClusterConfig;
+
+    fn node(port: u16) -> (Arc<DistributedQueue>, Arc<MemoryQueue>) {
+        let addr = format!("127.0.0.1:{}", port).parse().unwrap();
+        let coordinator = Arc::new(ClusterCoordinator::new(
+            NodeId::new(addr),
+            ClusterConfig::default(),
+        ));
+        let local = Arc::new(MemoryQueue::new());
+        let queue = Arc::new(DistributedQueue::new(coordinator, local.clone()));
+        queue.register_job_type::<PhysicsSimulationJob>("physics_simulation");
+        (queue, local)
+    }
+
+    fn job(n: usize) -> Box<dyn Job> {
+        Box::new(PhysicsSimulationJob::new(
+            format!("scenario-{}", n),
+            serde_json::json!({}),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_jobs_partitioned_and_rebalanced() {
+        let (a, a_local) = node(7001);
+        let (b, b_local) = node(7002);
+        let (a_id, b_id) = (a.local_id, b.local_id);
+        a.add_node(b_id);
+        b.add_node(a_id);
+        let mut a_out = a.take_outbound().unwrap();
+
+        for n in 0..40 {
+            a.push(job(n)).await.unwrap();
+        }
+        while let Ok((to, message)) = a_out.try_recv() {
+            assert_eq!(to, b_id);
+            b.handle_message(a_id, message).await.unwrap();
+        }
+
+        let (on_a, on_b) = (a_local.len().await.unwrap(), b_local.len().await.unwrap());
+        assert!(on_a > 0 && on_b > 0);
+        assert_eq!(on_a + on_b, 40);
+        assert_eq!(a.pending_remote(), on_b);
+
+        // Finishing one on b clears it from a's ledger
+        let finished = b_local.pop().await.unwrap().unwrap();
+        b.complete(finished.id());
+        let (to, message) = b.take_outbound().unwrap().try_recv().unwrap();
+        assert_eq!(to, a_id);
+        a.handle_message(b_id, message).await.unwrap();
+        assert_eq!(a.pending_remote(), on_b - 1);
+
+        // b fails; a takes over what it had not finished
+        assert_eq!(a.handle_node_failure(b_id).await.unwrap(), on_b - 1);
+        assert_eq!(a_local.len().await.unwrap(), 39);
+        assert_eq!(a.pending_remote(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_leader_only_scheduling() {
+        let (a, a_local) = node(7003);
+        let scheduled = a.leader_only();
+
+        scheduled.push(job(0)).await.unwrap();
+        assert_eq!(a_local.len().await.unwrap(), 0);
+
+        a.coordinator.consensus().start_election().unwrap();
+        a.coordinator.consensus().become_leader();
+        scheduled.push(job(1)).await.unwrap();
+        assert_eq!(a_local.len().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cluster_metrics() {
+        let (a, _) = node(7004);
+        let (b, _) = node(7005);
+        a.add_node(b.local_id);
+        b.add_node(a.local_id);
+
+        a.metrics().record_started("job-1".to_string(), "test".to_string());
+        a.metrics().record_completed("job-1", 100);
+        b.metrics().record_started("job-2".to_string(), "test".to_string());
+        b.metrics().record_completed("job-2", 300);
+
+        b.broadcast_metrics();
+        let (_, message) = b.take_outbound().unwrap().try_recv().unwrap();
+        a.handle_message(b.local_id, message).await.unwrap();
+
+        let cluster = a.cluster_metrics();
+        assert_eq!(cluster.nodes.len(), 2);
+        assert_eq!(cluster.total.completed_jobs, 2);
+        assert_eq!(cluster.total.average_duration_ms, 200);
+    }
+}
@@ -64,6 +64,10 @@ pub enum JobError {
     #[error("Job already exists: {job_id}")]
     AlreadyExists { job_id: String },
 
+    /// A job with the same idempotency key was pushed within its window
+    #[error("Duplicate job for idempotency key {key}: already pushed as {job_id}")]
+    DuplicateJob { key: String, job_id: String },
+
     /// Invalid job state transition
     #[error("Invalid state transition from {from} to {to}")]
     InvalidStateTransition { from: String, to: String },
@@ -88,6 +92,22 @@ pub enum JobError {
     #[error("Batch processing error: {0}")]
     BatchError(String),
 
+    /// Cluster coordination error
+    #[error("Cluster error: {0}")]
+    ClusterError(String),
+
+    /// Resource limit exceeded
+    #[error("Resource limit exceeded: {resource} reached {observed}, limit is {limit}")]
+    ResourceLimitExceeded {
+        resource: String,
+        limit: u64,
+        observed: u64,
+    },
+
+    /// Result store error
+    #[error("Storage error: {0}")]
+    StorageError(String),
+
     /// IO error
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
@@ -107,6 +127,7 @@ impl JobError {
                 | JobError::WorkerPoolError(_)
                 | JobError::NoWorkersAvailable
                 | JobError::DatabaseError(_)
+                | JobError::StorageError(_)
         )
     }
 
@@ -132,18 +153,36 @@ impl JobError {
             JobError::SchedulingError(_) => ErrorSeverity::Medium,
             JobError::InvalidCronExpression(_) => ErrorSeverity::Medium,
             JobError::AlreadyExists { .. } => ErrorSeverity::Low,
+            JobError::DuplicateJob { .. } => ErrorSeverity::Low,
             JobError::InvalidStateTransition { .. } => ErrorSeverity::Medium,
             JobError::NoWorkersAvailable => ErrorSeverity::High,
             JobError::RateLimitExceeded { .. } => ErrorSeverity::Low,
             JobError::DependencyError(_) => ErrorSeverity::High,
             JobError::PipelineError(_) => ErrorSeverity::High,
             JobError::BatchError(_) => ErrorSeverity::High,
+            JobError::ClusterError(_) => ErrorSeverity::High,
+            JobError::ResourceLimitExceeded { .. } => ErrorSeverity::High,
+            JobError::StorageError(_) => ErrorSeverity::High,
             JobError::IoError(_) => ErrorSeverity::Medium,
             JobError::Internal(_) => ErrorSeverity::Critical,
         }
     }
 }
 
+#[cfg(feature = "distributed")]
+impl From<accuscene_cluster::ClusterError> for JobError {
+    fn from(err: accuscene_cluster::ClusterError) -> Self {
+        JobError::ClusterError(err.to_string())
+    }
+}
+
+#[cfg(feature = "result-store")]
+impl From<accuscene_database::DatabaseError> for JobError {
+    fn from(err: accuscene_database::DatabaseError) -> Self {
+        JobError::StorageError(err.to_string())
+    }
+}
+
 /// Error severity levels
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ErrorSeverity {
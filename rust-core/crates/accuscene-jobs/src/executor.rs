@@ -1,18 +1,26 @@
-//! Job executor with retry logic and timeout handling.
+//! Job executor with retry logic, timeout handling and resource limits.
 
-use crate::error::{JobError, Result};
+use crate::error::Result;
 use crate::job::{Job, JobContext};
+use crate::metrics::JobMetrics;
 use crate::result::JobResult;
 use crate::retry::RetryPolicy;
+use crate::sandbox::{self, LimitBreach, ResourceKind, ResourceLimits, SandboxOutcome};
 use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+/// Completion callback type, called with the job name and its final result
+pub type CompletionCallback = Arc<dyn Fn(&str, &JobResult) + Send + Sync>;
 
 /// Job executor
 pub struct JobExecutor {
     retry_policy: RetryPolicy,
+    limits: ResourceLimits,
+    metrics: Option<JobMetrics>,
     results: Arc<RwLock<HashMap<String, JobResult>>>,
+    callbacks: Arc<RwLock<Vec<CompletionCallback>>>,
 }
 
 impl JobExecutor {
@@ -20,19 +28,45 @@ impl JobExecutor {
     pub fn new(retry_policy: RetryPolicy) -> Self {
         Self {
             retry_policy,
+            limits: ResourceLimits::none(),
+            metrics: None,
             results: Arc::new(RwLock::new(HashMap::new())),
+            callbacks: Arc::new(RwLock::new(Vec::new())),
         }
     }
 
+    /// Set default resource limits for jobs that do not define their own
+    pub fn with_limits(mut self, limits: ResourceLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Record limit breaches in job metrics
+    pub fn with_metrics(mut self, metrics: JobMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Get the default resource limits
+    pub fn limits(&self) -> &ResourceLimits {
+        &self.limits
+    }
+
+    /// Add a callback run when a job succeeds or fails for good
+    pub fn add_completion_callback(&self, callback: CompletionCallback) {
+        self.callbacks.write().push(callback);
+    }
+
     /// Execute a job with retry logic
     pub async fn execute(&self, mut job: Box<dyn Job>, context: Arc<JobContext>) -> Result<JobResult> {
         let job_id = job.id().to_string();
+        let job_name = job.name().to_string();
         let max_retries = job.max_retries();
-        let timeout = job.timeout_secs();
+        let limits = self.effective_limits(job.as_ref());
 
         tracing::info!(
             job_id = %job_id,
-            job_name = %job.name(),
+            job_name = %job_name,
             "Starting job execution"
         );
 
@@ -53,23 +87,73 @@ impl JobExecutor {
                 return Err(e);
             }
 
-            // Execute the job with optional timeout
-            let result = if let Some(timeout_secs) = timeout {
-                match tokio::time::timeout(
-                    tokio::time::Duration::from_secs(timeout_secs),
-                    job.execute(attempt_context.clone()),
-                )
-                .await
+            // Execute the job under its resource limits
+            let (result, breach) = if limits.needs_sandbox() {
+                match sandbox::run(job, attempt_context.clone(), &limits).await? {
+                    SandboxOutcome::Finished {
+                        job: returned,
+                        result,
+                        usage,
+                    } => {
+                        tracing::debug!(
+                            job_id = %job_id,
+                            usage = ?usage,
+                            "Governed job attempt finished"
+                        );
+                        job = returned;
+                        (result, None)
+                    }
+                    SandboxOutcome::Breached {
+                        job: Some(returned),
+                        breach,
+                        usage,
+                    } => {
+                        tracing::warn!(
+                            job_id = %job_id,
+                            breach = ?breach,
+                            usage = ?usage,
+                            "Job cancelled for exceeding a resource limit"
+                        );
+                        job = returned;
+                        (Err(breach.to_error()), Some(breach))
+                    }
+                    SandboxOutcome::Breached {
+                        job: None,
+                        breach,
+                        usage,
+                    } => {
+                        // The job did not yield after cancellation, its thread is abandoned
+                        tracing::error!(
+                            job_id = %job_id,
+                            breach = ?breach,
+                            usage = ?usage,
+                            "Job abandoned after exceeding a resource limit"
+                        );
+                        let final_result =
+                            JobResult::failure(job_id.clone(), breach.to_error().to_string())
+                                .with_duration(start_time.elapsed().as_millis() as u64);
+                        self.record_breach(&job_id, breach);
+                        return Ok(self.finish(&job_id, &job_name, final_result));
+                    }
+                }
+            } else if let Some(wall_time) = limits.wall_time {
+                match tokio::time::timeout(wall_time, job.execute(attempt_context.clone())).await
                 {
-                    Ok(result) => result,
-                    Err(_) => Err(JobError::Timeout {
-                        duration_secs: timeout_secs,
-                    }),
+                    Ok(result) => (result, None),
+                    Err(_) => {
+                        let limit_ms = wall_time.as_millis() as u64;
+                        let breach = LimitBreach::new(ResourceKind::WallTime, limit_ms, limit_ms);
+                        (Err(breach.to_error()), Some(breach))
+                    }
                 }
             } else {
-                job.execute(attempt_context.clone()).await
+                (job.execute(attempt_context.clone()).await, None)
             };
 
+            if let Some(breach) = breach {
+                self.record_breach(&job_id, breach);
+            }
+
             let duration_ms = start_time.elapsed().as_millis() as u64;
 
             match result {
@@ -91,10 +175,7 @@ impl JobExecutor {
                         "Job completed successfully"
                     );
 
-                    // Store result
-                    self.results.write().insert(job_id.clone(), final_result.clone());
-
-                    return Ok(final_result);
+                    return Ok(self.finish(&job_id, &job_name, final_result));
                 }
                 Err(error) => {
                     // Call on_failure hook
@@ -135,15 +216,40 @@ impl JobExecutor {
                         "Job failed permanently"
                     );
 
-                    // Store result
-                    self.results.write().insert(job_id.clone(), final_result.clone());
-
-                    return Ok(final_result);
+                    return Ok(self.finish(&job_id, &job_name, final_result));
                 }
             }
         }
     }
 
+    /// Combine the job's limits with the executor defaults and the job timeout
+    fn effective_limits(&self, job: &dyn Job) -> ResourceLimits {
+        let mut limits = job.resource_limits().unwrap_or_default().or(self.limits);
+        if let Some(timeout_secs) = job.timeout_secs() {
+            let timeout = Duration::from_secs(timeout_secs);
+            limits.wall_time = Some(limits.wall_time.map_or(timeout, |wall| wall.min(timeout)));
+        }
+        limits
+    }
+
+    /// Store a final result and notify the completion callbacks
+    fn finish(&self, job_id: &str, job_name: &str, result: JobResult) -> JobResult {
+        self.results.write().insert(job_id.to_string(), result.clone());
+
+        let callbacks = self.callbacks.read();
+        for callback in callbacks.iter() {
+            callback(job_name, &result);
+        }
+        result
+    }
+
+    /// Record a limit breach in the metrics, if attached
+    fn record_breach(&self, job_id: &str, breach: LimitBreach) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_limit_breach(job_id, breach);
+        }
+    }
+
     /// Get result for a job
     pub fn get_result(&self, job_id: &str) -> Option<JobResult> {
         self.results.read().get(job_id).cloned()
@@ -169,6 +275,7 @@ impl JobExecutor {
 mod tests {
     use super::*;
     use crate::job::PhysicsSimulationJob;
+    use crate::result::JobResultStatus;
     use crate::retry::RetryPolicy;
 
     #[tokio::test]
@@ -236,4 +343,63 @@ mod tests {
         let result = executor.execute(job, context).await.unwrap();
         assert!(result.is_failure());
     }
+
+    #[tokio::test]
+    async fn test_executor_records_limit_breach() {
+        let metrics = JobMetrics::new();
+        let executor = JobExecutor::new(RetryPolicy::none())
+            .with_limits(ResourceLimits::none().with_wall_time(Duration::from_millis(20)))
+            .with_metrics(metrics.clone());
+
+        let job = Box::new(PhysicsSimulationJob::new(
+            "test-scenario".to_string(),
+            serde_json::json!({}),
+        ));
+        let job_id = job.id().to_string();
+        metrics.record_started(job_id.clone(), job.name().to_string());
+        let context = Arc::new(JobContext::new(job_id.clone(), "worker-1".to_string()));
+
+        let result = executor.execute(job, context).await.unwrap();
+        assert!(result.is_failure());
+
+        assert_eq!(metrics.get_statistics().limit_breaches, 1);
+        let breaches = metrics.get_job_metric(&job_id).unwrap().limit_breaches;
+        assert_eq!(breaches.len(), 1);
+        assert_eq!(breaches[0].resource, ResourceKind::WallTime);
+        assert_eq!(breaches[0].limit, 20);
+    }
+
+    #[tokio::test]
+    async fn test_completion_callbacks() {
+        let executor = JobExecutor::new(RetryPolicy::none());
+        let finished = Arc::new(RwLock::new(Vec::new()));
+        let seen = Arc::clone(&finished);
+        executor.add_completion_callback(Arc::new(move |job_name, result| {
+            seen.write().push((job_name.to_string(), result.status));
+        }));
+
+        let job = Box::new(PhysicsSimulationJob::new(
+            "test-scenario".to_string(),
+            serde_json::json!({}),
+        ));
+        let context = Arc::new(JobContext::new(job.id().to_string(), "worker-1".to_string()));
+        executor.execute(job, context).await.unwrap();
+
+        let executor = executor
+            .with_limits(ResourceLimits::none().with_wall_time(Duration::from_millis(1)));
+        let job = Box::new(PhysicsSimulationJob::new(
+            "test-scenario".to_string(),
+            serde_json::json!({}),
+        ));
+        let context = Arc::new(JobContext::new(job.id().to_string(), "worker-1".to_string()));
+        executor.execute(job, context).await.unwrap();
+
+        assert_eq!(
+            *finished.read(),
+            vec![
+                ("physics_simulation".to_string(), JobResultStatus::Success),
+                ("physics_simulation".to_string(), JobResultStatus::Failure),
+            ]
+        );
+    }
 }
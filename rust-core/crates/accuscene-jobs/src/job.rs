@@ -2,6 +2,7 @@
 
 use crate::error::{JobError, Result};
 use crate::result::JobResult;
+use crate::sandbox::ResourceLimits;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -36,6 +37,11 @@ pub trait Job: Send + Sync + Debug {
         Some(300) // 5 minutes default
     }
 
+    /// Get resource limits for this job (None = executor defaults)
+    fn resource_limits(&self) -> Option<ResourceLimits> {
+        None
+    }
+
     /// Check if job can be retried after this error
     fn can_retry(&self, error: &JobError, attempt: u32) -> bool {
         error.is_retryable() && attempt < self.max_retries()
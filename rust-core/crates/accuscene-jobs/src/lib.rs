@@ -2,14 +2,18 @@
 //!
 //! This crate provides a robust job processing system with the following features:
 //! - Multiple queue implementations (in-memory, persistent, priority-based)
+//! - Idempotency keys to reject or coalesce duplicate enqueues
 //! - Configurable worker pools with dynamic scaling
-//! - Job scheduling (cron, delayed, recurring)
+//! - Job scheduling (cron with timezones, delayed, recurring)
 //! - Retry strategies with exponential backoff
+//! - Per-job memory, CPU time and wall-clock limits
 //! - Progress tracking and metrics
-//! - Job pipelines and workflows
+//! - Job pipelines and DAG workflows with conditional branches
 //! - Batch processing
 //! - Rate limiting and throttling
 //! - Job state persistence
+//! - Queryable result archive with TTL purging (`result-store` feature)
+//! - Distributed execution across cluster nodes (`distributed` feature)
 //!
 //! # Examples
 //!
@@ -71,6 +75,8 @@ pub use accuscene_core;
 
 // Public modules
 pub mod batch;
+#[cfg(feature = "distributed")]
+pub mod distributed;
 pub mod error;
 pub mod executor;
 pub mod job;
@@ -80,16 +86,22 @@ pub mod pipeline;
 pub mod progress;
 pub mod queue;
 pub mod result;
+#[cfg(feature = "result-store")]
+pub mod result_store;
 pub mod retry;
+pub mod sandbox;
 pub mod scheduler;
 pub mod throttle;
 pub mod worker;
+pub mod workflow;
 
 /// Prelude module for convenient imports
 pub mod prelude {
     //! Convenient re-exports of commonly used types
 
     pub use crate::batch::{BatchJobBuilder, BatchProcessor, BatchStrategy};
+    #[cfg(feature = "distributed")]
+    pub use crate::distributed::{ClusterJobMessage, ClusterJobStatistics, DistributedQueue};
     pub use crate::error::{JobError, Result};
     pub use crate::executor::JobExecutor;
     pub use crate::job::{
@@ -101,23 +113,34 @@ pub mod prelude {
     pub use crate::pipeline::{JobPipeline, ParallelPipeline, PipelineStage, PipelineStatus};
     pub use crate::progress::{JobProgress, ProgressReporter, ProgressTracker};
     pub use crate::queue::{
-        memory::MemoryQueue, persistent::PersistentQueue, priority::PriorityQueue, JobQueue,
-        QueueConfig,
+        memory::MemoryQueue, persistent::PersistentQueue, priority::PriorityQueue,
+        DuplicatePolicy, JobQueue, QueueConfig,
     };
     pub use crate::result::{BatchJobResult, JobResult, JobResultStatus};
+    #[cfg(feature = "result-store")]
+    pub use crate::result_store::{ResultQuery, ResultStore, ResultStoreConfig, StoredResult};
     pub use crate::retry::{
         ExponentialBackoff, FibonacciBackoff, FixedRetry, LinearBackoff, RetryPolicy,
         RetryStrategy,
     };
+    pub use crate::sandbox::{
+        LimitBreach, ResourceKind, ResourceLimits, ResourceUsage, TrackingAllocator,
+    };
     pub use crate::scheduler::{
-        cron::CronScheduler, delayed::DelayedScheduler, recurring::RecurringScheduler, Schedule,
-        ScheduledJob,
+        cron::{CronScheduler, CronTrigger},
+        delayed::DelayedScheduler,
+        recurring::RecurringScheduler,
+        MisfirePolicy, Schedule, ScheduledJob,
     };
     pub use crate::throttle::{JobThrottler, RateLimitConfig, RateLimiter, SlidingWindowLimiter};
     pub use crate::worker::{
         async_worker::AsyncWorker, pool::WorkerPool, pool::WorkerPoolConfig, thread::ThreadWorker,
         Worker, WorkerConfig, WorkerState,
     };
+    pub use crate::workflow::{
+        EdgeCondition, JoinRule, NodeInput, NodeRetry, WorkflowDefinition, WorkflowEdge,
+        WorkflowEngine, WorkflowNode, WorkflowResult,
+    };
 }
 
 // Version information
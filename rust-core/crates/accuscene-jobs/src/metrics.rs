@@ -1,5 +1,6 @@
 //! Job metrics and statistics tracking.
 
+use crate::sandbox::LimitBreach;
 use chrono::{DateTime, Utc};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
@@ -37,6 +38,7 @@ impl JobMetrics {
             duration_ms: 0,
             status: JobMetricStatus::Running,
             retry_count: 0,
+            limit_breaches: Vec::new(),
         };
 
         self.job_metrics.write().insert(job_id, metric);
@@ -85,6 +87,16 @@ impl JobMetrics {
         }
     }
 
+    /// Record a resource limit breached by a job
+    pub fn record_limit_breach(&self, job_id: &str, breach: LimitBreach) {
+        let mut stats = self.stats.write();
+        stats.limit_breaches += 1;
+
+        if let Some(metric) = self.job_metrics.write().get_mut(job_id) {
+            metric.limit_breaches.push(breach);
+        }
+    }
+
     /// Record job cancelled
     pub fn record_cancelled(&self, job_id: &str) {
         let mut stats = self.stats.write();
@@ -169,6 +181,8 @@ pub struct JobStatistics {
     pub total_retries: u64,
     pub total_duration_ms: u64,
     pub average_duration_ms: u64,
+    #[serde(default)]
+    pub limit_breaches: u64,
 }
 
 impl Default for JobStatistics {
@@ -182,6 +196,25 @@ impl Default for JobStatistics {
             total_retries: 0,
             total_duration_ms: 0,
             average_duration_ms: 0,
+            limit_breaches: 0,
+        }
+    }
+}
+
+impl JobStatistics {
+    /// Add another node's statistics to these
+    pub fn merge(&mut self, other: &JobStatistics) {
+        self.total_jobs += other.total_jobs;
+        self.running_jobs += other.running_jobs;
+        self.completed_jobs += other.completed_jobs;
+        self.failed_jobs += other.failed_jobs;
+        self.cancelled_jobs += other.cancelled_jobs;
+        self.total_retries += other.total_retries;
+        self.total_duration_ms += other.total_duration_ms;
+        self.limit_breaches += other.limit_breaches;
+
+        if self.completed_jobs > 0 {
+            self.average_duration_ms = self.total_duration_ms / self.completed_jobs;
         }
     }
 }
@@ -196,6 +229,8 @@ pub struct JobMetric {
     pub duration_ms: u64,
     pub status: JobMetricStatus,
     pub retry_count: u32,
+    #[serde(default)]
+    pub limit_breaches: Vec<LimitBreach>,
 }
 
 /// Job metric status
@@ -0,0 +1,76 @@
+//! Idempotency key tracking for in-memory queues.
+
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Idempotency keys with the job each was claimed by and its expiry
+///
+/// A key whose window overflows `Instant` never expires.
+#[derive(Debug, Default)]
+pub(crate) struct IdempotencyKeys {
+    keys: Mutex<HashMap<String, (String, Option<Instant>)>>,
+}
+
+impl IdempotencyKeys {
+    /// Create an empty key set
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Claim a key for a job until `window` has passed
+    ///
+    /// Returns the ID of the job holding the key if it is still claimed.
+    pub(crate) fn reserve(&self, key: &str, job_id: &str, window: Duration) -> Option<String> {
+        let now = Instant::now();
+        let mut keys = self.keys.lock();
+        keys.retain(|_, (_, expires_at)| !expires_at.is_some_and(|at| at <= now));
+
+        if let Some((existing, _)) = keys.get(key) {
+            return Some(existing.clone());
+        }
+
+        keys.insert(key.to_string(), (job_id.to_string(), now.checked_add(window)));
+        None
+    }
+
+    /// Give up a key claimed by a job that could not be pushed
+    pub(crate) fn release(&self, key: &str, job_id: &str) {
+        let mut keys = self.keys.lock();
+        if keys.get(key).is_some_and(|(holder, _)| holder == job_id) {
+            keys.remove(key);
+        }
+    }
+
+    /// Forget all keys
+    pub(crate) fn clear(&self) {
+        self.keys.lock().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_expire_after_window() {
+        let keys = IdempotencyKeys::new();
+
+        assert_eq!(keys.reserve("report-42", "job-1", Duration::from_millis(20)), None);
+        assert_eq!(
+            keys.reserve("report-42", "job-2", Duration::from_millis(20)),
+            Some("job-1".to_string())
+        );
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(keys.reserve("report-42", "job-3", Duration::from_millis(20)), None);
+
+        keys.release("report-42", "job-1");
+        assert_eq!(
+            keys.reserve("report-42", "job-4", Duration::from_millis(20)),
+            Some("job-3".to_string())
+        );
+        keys.release("report-42", "job-3");
+        assert_eq!(keys.reserve("report-42", "job-4", Duration::from_millis(20)), None);
+    }
+}
@@ -2,18 +2,20 @@
 
 use crate::error::{JobError, Result};
 use crate::job::Job;
-use crate::queue::{JobQueue, QueueConfig};
+use crate::queue::{IdempotencyKeys, JobQueue, QueueConfig};
 use async_trait::async_trait;
 use crossbeam_queue::SegQueue;
 use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 /// In-memory job queue using crossbeam
 #[derive(Clone)]
 pub struct MemoryQueue {
     queue: Arc<SegQueue<Box<dyn Job>>>,
     jobs: Arc<RwLock<HashMap<String, Box<dyn Job>>>>,
+    keys: Arc<IdempotencyKeys>,
     config: QueueConfig,
 }
 
@@ -28,6 +30,7 @@ impl MemoryQueue {
         Self {
             queue: Arc::new(SegQueue::new()),
             jobs: Arc::new(RwLock::new(HashMap::new())),
+            keys: Arc::new(IdempotencyKeys::new()),
             config,
         }
     }
@@ -85,6 +88,25 @@ impl JobQueue for MemoryQueue {
         Ok(())
     }
 
+    async fn push_with_key(
+        &self,
+        job: Box<dyn Job>,
+        key: &str,
+        window: Duration,
+    ) -> Result<String> {
+        let job_id = job.id().to_string();
+        if let Some(existing) = self.keys.reserve(key, &job_id, window) {
+            return self.config.duplicate_policy.resolve(key, existing);
+        }
+
+        if let Err(e) = self.push(job).await {
+            self.keys.release(key, &job_id);
+            return Err(e);
+        }
+
+        Ok(job_id)
+    }
+
     async fn pop(&self) -> Result<Option<Box<dyn Job>>> {
         if let Some(job) = self.queue.pop() {
             let job_id = job.id().to_string();
@@ -121,6 +143,7 @@ impl JobQueue for MemoryQueue {
     async fn clear(&self) -> Result<()> {
         while self.queue.pop().is_some() {}
         self.jobs.write().clear();
+        self.keys.clear();
         tracing::info!("Memory queue cleared");
         Ok(())
     }
@@ -215,4 +238,32 @@ mod tests {
         assert!(retrieved.is_some());
         assert_eq!(retrieved.unwrap().id(), job_id);
     }
+
+    #[tokio::test]
+    async fn test_memory_queue_rejects_duplicate_key() {
+        let queue = MemoryQueue::new();
+        let window = Duration::from_secs(60);
+
+        let job1 = Box::new(PhysicsSimulationJob::new(
+            "scenario-1".to_string(),
+            serde_json::json!({}),
+        ));
+        let job2 = Box::new(PhysicsSimulationJob::new(
+            "scenario-1".to_string(),
+            serde_json::json!({}),
+        ));
+        let job1_id = job1.id().to_string();
+
+        assert_eq!(
+            queue.push_with_key(job1, "report-1", window).await.unwrap(),
+            job1_id
+        );
+
+        let result = queue.push_with_key(job2, "report-1", window).await;
+        assert!(matches!(
+            result,
+            Err(JobError::DuplicateJob { job_id, .. }) if job_id == job1_id
+        ));
+        assert_eq!(queue.len().await.unwrap(), 1);
+    }
 }
@@ -1,12 +1,22 @@
 //! Job queue implementations for AccuScene.
+//!
+//! Besides plain pushes, every queue accepts pushes carrying an idempotency
+//! key. A second push with the same key inside the key's window is either
+//! rejected or coalesced into the first, depending on the queue's
+//! [`DuplicatePolicy`], so retried enqueues don't produce duplicate jobs.
 
+mod dedup;
 pub mod memory;
 pub mod persistent;
 pub mod priority;
 
-use crate::error::Result;
+pub(crate) use dedup::IdempotencyKeys;
+
+use crate::error::{JobError, Result};
 use crate::job::Job;
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 /// Job queue trait
 #[async_trait]
@@ -14,6 +24,17 @@ pub trait JobQueue: Send + Sync {
     /// Push a job to the queue
     async fn push(&self, job: Box<dyn Job>) -> Result<()>;
 
+    /// Push a job unless a job with the same key was pushed within `window`
+    ///
+    /// Returns the ID of the job the key refers to: the pushed job, or the
+    /// earlier one when the duplicate is coalesced.
+    async fn push_with_key(
+        &self,
+        job: Box<dyn Job>,
+        key: &str,
+        window: Duration,
+    ) -> Result<String>;
+
     /// Pop a job from the queue
     async fn pop(&self) -> Result<Option<Box<dyn Job>>>;
 
@@ -38,12 +59,40 @@ pub trait JobQueue: Send + Sync {
     async fn remove(&self, job_id: &str) -> Result<bool>;
 }
 
+/// Handling of a push whose idempotency key is already taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicatePolicy {
+    /// Fail the push with [`JobError::DuplicateJob`]
+    #[default]
+    Reject,
+    /// Drop the new job and return the ID of the earlier one
+    Coalesce,
+}
+
+impl DuplicatePolicy {
+    /// Resolve a duplicate push of `key`, first pushed as `job_id`
+    pub fn resolve(self, key: &str, job_id: String) -> Result<String> {
+        match self {
+            DuplicatePolicy::Reject => Err(JobError::DuplicateJob {
+                key: key.to_string(),
+                job_id,
+            }),
+            DuplicatePolicy::Coalesce => {
+                tracing::debug!(key = %key, job_id = %job_id, "Duplicate job coalesced");
+                Ok(job_id)
+            }
+        }
+    }
+}
+
 /// Queue configuration
 #[derive(Debug, Clone)]
 pub struct QueueConfig {
     pub max_size: Option<usize>,
     pub persistence_enabled: bool,
     pub priority_enabled: bool,
+    pub duplicate_policy: DuplicatePolicy,
 }
 
 impl Default for QueueConfig {
@@ -52,6 +101,7 @@ impl Default for QueueConfig {
             max_size: None,
             persistence_enabled: false,
             priority_enabled: false,
+            duplicate_policy: DuplicatePolicy::default(),
         }
     }
 }
@@ -75,4 +125,9 @@ impl QueueConfig {
         self.priority_enabled = true;
         self
     }
+
+    pub fn with_duplicate_policy(mut self, policy: DuplicatePolicy) -> Self {
+        self.duplicate_policy = policy;
+        self
+    }
 }
@@ -6,9 +6,10 @@ use crate::queue::{JobQueue, QueueConfig};
 use async_trait::async_trait;
 use chrono::Utc;
 use parking_lot::Mutex;
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Persistent job queue using SQLite
 #[derive(Clone)]
@@ -61,6 +62,15 @@ impl PersistentQueue {
             [],
         )?;
 
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS job_idempotency_keys (
+                key TEXT PRIMARY KEY,
+                job_id TEXT NOT NULL,
+                expires_at INTEGER NOT NULL
+            )",
+            [],
+        )?;
+
         Ok(())
     }
 
@@ -75,19 +85,15 @@ impl PersistentQueue {
         queue.initialize_schema()?;
         Ok(queue)
     }
-}
 
-#[async_trait]
-impl JobQueue for PersistentQueue {
-    async fn push(&self, job: Box<dyn Job>) -> Result<()> {
+    /// Insert a job into the queue table
+    fn insert(&self, conn: &Connection, job: &dyn Job) -> Result<()> {
         let job_id = job.id().to_string();
         let job_name = job.name().to_string();
         let priority = job.priority();
         let data = job.serialize()?;
         let now = Utc::now().to_rfc3339();
 
-        let conn = self.conn.lock();
-
         // Check capacity
         if let Some(max_size) = self.config.max_size {
             let count: i64 = conn.query_row(
@@ -112,6 +118,55 @@ impl JobQueue for PersistentQueue {
         tracing::debug!(job_id = %job_id, "Job persisted to database queue");
         Ok(())
     }
+}
+
+#[async_trait]
+impl JobQueue for PersistentQueue {
+    async fn push(&self, job: Box<dyn Job>) -> Result<()> {
+        let conn = self.conn.lock();
+        self.insert(&conn, job.as_ref())
+    }
+
+    async fn push_with_key(
+        &self,
+        job: Box<dyn Job>,
+        key: &str,
+        window: Duration,
+    ) -> Result<String> {
+        let job_id = job.id().to_string();
+        let now = Utc::now().timestamp_millis();
+        let window_ms = i64::try_from(window.as_millis()).unwrap_or(i64::MAX);
+
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "DELETE FROM job_idempotency_keys WHERE expires_at <= ?1",
+            params![now],
+        )?;
+
+        let existing = tx
+            .query_row(
+                "SELECT job_id FROM job_idempotency_keys WHERE key = ?1",
+                params![key],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?;
+
+        if let Some(existing) = existing {
+            tx.commit()?;
+            return self.config.duplicate_policy.resolve(key, existing);
+        }
+
+        self.insert(&tx, job.as_ref())?;
+        tx.execute(
+            "INSERT INTO job_idempotency_keys (key, job_id, expires_at) VALUES (?1, ?2, ?3)",
+            params![key, job_id, now.saturating_add(window_ms)],
+        )?;
+        tx.commit()?;
+
+        Ok(job_id)
+    }
 
     async fn pop(&self) -> Result<Option<Box<dyn Job>>> {
         let conn = self.conn.lock();
@@ -183,6 +238,7 @@ impl JobQueue for PersistentQueue {
     async fn clear(&self) -> Result<()> {
         let conn = self.conn.lock();
         conn.execute("DELETE FROM jobs WHERE state = 'queued'", [])?;
+        conn.execute("DELETE FROM job_idempotency_keys", [])?;
         tracing::info!("Persistent queue cleared");
         Ok(())
     }
@@ -280,4 +336,25 @@ mod tests {
             assert_eq!(queue.len().await.unwrap(), 1);
         }
     }
+
+    #[tokio::test]
+    async fn test_persistent_queue_idempotency_keys() {
+        let queue = PersistentQueue::in_memory().unwrap();
+        let window = Duration::from_secs(60);
+
+        let job1 = PhysicsSimulationJob::new("scenario-1".to_string(), serde_json::json!({}));
+        let job2 = PhysicsSimulationJob::new("scenario-1".to_string(), serde_json::json!({}));
+        let job1_id = job1.id().to_string();
+
+        queue.push_with_key(Box::new(job1), "report-1", window).await.unwrap();
+
+        // The key outlives the job it was claimed by
+        queue.pop().await.unwrap().unwrap();
+        let result = queue.push_with_key(Box::new(job2), "report-1", window).await;
+        assert!(matches!(
+            result,
+            Err(JobError::DuplicateJob { job_id, .. }) if job_id == job1_id
+        ));
+        assert_eq!(queue.len().await.unwrap(), 0);
+    }
 }
@@ -2,12 +2,13 @@
 
 use crate::error::{JobError, Result};
 use crate::job::Job;
-use crate::queue::{JobQueue, QueueConfig};
+use crate::queue::{IdempotencyKeys, JobQueue, QueueConfig};
 use async_trait::async_trait;
 use parking_lot::RwLock;
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap};
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Priority queue entry
 #[derive(Debug)]
@@ -47,6 +48,7 @@ impl Ord for PriorityJob {
 pub struct PriorityQueue {
     heap: Arc<RwLock<BinaryHeap<PriorityJob>>>,
     jobs: Arc<RwLock<HashMap<String, Box<dyn Job>>>>,
+    keys: Arc<IdempotencyKeys>,
     sequence: Arc<RwLock<u64>>,
     config: QueueConfig,
 }
@@ -62,6 +64,7 @@ impl PriorityQueue {
         Self {
             heap: Arc::new(RwLock::new(BinaryHeap::new())),
             jobs: Arc::new(RwLock::new(HashMap::new())),
+            keys: Arc::new(IdempotencyKeys::new()),
             sequence: Arc::new(RwLock::new(0)),
             config,
         }
@@ -135,6 +138,25 @@ impl JobQueue for PriorityQueue {
         Ok(())
     }
 
+    async fn push_with_key(
+        &self,
+        job: Box<dyn Job>,
+        key: &str,
+        window: Duration,
+    ) -> Result<String> {
+        let job_id = job.id().to_string();
+        if let Some(existing) = self.keys.reserve(key, &job_id, window) {
+            return self.config.duplicate_policy.resolve(key, existing);
+        }
+
+        if let Err(e) = self.push(job).await {
+            self.keys.release(key, &job_id);
+            return Err(e);
+        }
+
+        Ok(job_id)
+    }
+
     async fn pop(&self) -> Result<Option<Box<dyn Job>>> {
         if let Some(priority_job) = self.heap.write().pop() {
             let job_id = priority_job.job.id().to_string();
@@ -169,6 +191,7 @@ impl JobQueue for PriorityQueue {
     async fn clear(&self) -> Result<()> {
         self.heap.write().clear();
         self.jobs.write().clear();
+        self.keys.clear();
         *self.sequence.write() = 0;
         tracing::info!("Priority queue cleared");
         Ok(())
@@ -207,6 +230,7 @@ impl JobQueue for PriorityQueue {
 mod tests {
     use super::*;
     use crate::job::PhysicsSimulationJob;
+    use crate::queue::DuplicatePolicy;
 
     #[tokio::test]
     async fn test_priority_queue_ordering() {
@@ -277,4 +301,31 @@ mod tests {
         let result = queue.push(job3).await;
         assert!(matches!(result, Err(JobError::QueueFull { .. })));
     }
+
+    #[tokio::test]
+    async fn test_priority_queue_coalesces_duplicate_key() {
+        let config = QueueConfig::new()
+            .with_priority()
+            .with_duplicate_policy(DuplicatePolicy::Coalesce);
+        let queue = PriorityQueue::with_config(config);
+        let window = Duration::from_millis(50);
+
+        let job1 = PhysicsSimulationJob::new("scenario-1".to_string(), serde_json::json!({}));
+        let job2 = PhysicsSimulationJob::new("scenario-1".to_string(), serde_json::json!({}));
+        let job3 = PhysicsSimulationJob::new("scenario-1".to_string(), serde_json::json!({}));
+        let job1_id = job1.id().to_string();
+        let job3_id = job3.id().to_string();
+
+        let first = queue.push_with_key(Box::new(job1), "report-1", window).await;
+        let second = queue.push_with_key(Box::new(job2), "report-1", window).await;
+        assert_eq!(first.unwrap(), job1_id);
+        assert_eq!(second.unwrap(), job1_id);
+        assert_eq!(queue.len().await.unwrap(), 1);
+
+        // The key is free again once the window has passed
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        let third = queue.push_with_key(Box::new(job3), "report-1", window).await;
+        assert_eq!(third.unwrap(), job3_id);
+        assert_eq!(queue.len().await.unwrap(), 2);
+    }
 }
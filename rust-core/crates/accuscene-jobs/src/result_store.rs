@@ -0,0 +1,497 @@
+//! Durable job result archive backed by `accuscene-database`.
+//!
+//! [`JobPersistence`](crate::persistence::JobPersistence) tracks the live state of
+//! jobs and their event history. The result store keeps finished results together
+//! with the job metadata and timings so they can be queried by job type, state and
+//! completion date long after the job left the queue. Every record carries an
+//! expiry derived from the configured TTL and is removed by
+//! [`ResultStore::purge_expired`].
+
+use crate::error::Result;
+use crate::job::{JobMetadata, JobState};
+use crate::result::{JobResult, JobResultStatus};
+use accuscene_database::DatabasePool;
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS job_result_archive (
+        job_id TEXT PRIMARY KEY,
+        job_name TEXT NOT NULL,
+        state TEXT NOT NULL,
+        status TEXT NOT NULL,
+        result TEXT NOT NULL,
+        metadata TEXT NOT NULL,
+        started_at INTEGER,
+        completed_at INTEGER NOT NULL,
+        duration_ms INTEGER NOT NULL,
+        attempts INTEGER NOT NULL,
+        stored_at INTEGER NOT NULL,
+        expires_at INTEGER
+    );
+    CREATE INDEX IF NOT EXISTS idx_job_result_archive_name
+        ON job_result_archive(job_name, completed_at);
+    CREATE INDEX IF NOT EXISTS idx_job_result_archive_state
+        ON job_result_archive(state, completed_at);
+    CREATE INDEX IF NOT EXISTS idx_job_result_archive_expires
+        ON job_result_archive(expires_at);
+";
+
+const COLUMNS: &str = "result, metadata, stored_at, expires_at";
+
+/// Result store configuration
+#[derive(Debug, Clone)]
+pub struct ResultStoreConfig {
+    /// Retention for job types without an explicit TTL, `None` keeps results forever
+    pub default_ttl: Option<Duration>,
+    /// Retention overrides keyed by job name
+    pub ttl_by_job: HashMap<String, Duration>,
+    /// How often the background purge runs
+    pub purge_interval: Duration,
+}
+
+impl Default for ResultStoreConfig {
+    fn default() -> Self {
+        Self {
+            default_ttl: Some(Duration::from_secs(30 * 24 * 60 * 60)),
+            ttl_by_job: HashMap::new(),
+            purge_interval: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+impl ResultStoreConfig {
+    /// Set the default retention, `None` disables expiry
+    pub fn with_default_ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.default_ttl = ttl;
+        self
+    }
+
+    /// Set the retention for a single job type
+    pub fn with_job_ttl(mut self, job_name: impl Into<String>, ttl: Duration) -> Self {
+        self.ttl_by_job.insert(job_name.into(), ttl);
+        self
+    }
+
+    /// Set the background purge interval
+    pub fn with_purge_interval(mut self, interval: Duration) -> Self {
+        self.purge_interval = interval;
+        self
+    }
+
+    /// Retention that applies to the given job type
+    pub fn ttl_for(&self, job_name: &str) -> Option<Duration> {
+        self.ttl_by_job.get(job_name).copied().or(self.default_ttl)
+    }
+}
+
+/// Archived job result with its metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredResult {
+    /// Job metadata at completion
+    pub metadata: JobMetadata,
+    /// Job result payload and timings
+    pub result: JobResult,
+    /// When the result was archived
+    pub stored_at: DateTime<Utc>,
+    /// When the result becomes eligible for purging
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Filter for querying archived results
+#[derive(Debug, Clone, Default)]
+pub struct ResultQuery {
+    /// Only results of this job type
+    pub job_name: Option<String>,
+    /// Only results whose job ended in this state
+    pub state: Option<JobState>,
+    /// Only results with this status
+    pub status: Option<JobResultStatus>,
+    /// Only results completed at or after this time
+    pub completed_after: Option<DateTime<Utc>>,
+    /// Only results completed before this time
+    pub completed_before: Option<DateTime<Utc>>,
+    /// Maximum number of results
+    pub limit: Option<usize>,
+    /// Number of results to skip
+    pub offset: usize,
+}
+
+impl ResultQuery {
+    /// Create an unfiltered query
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Filter by job type
+    pub fn with_job_name(mut self, job_name: impl Into<String>) -> Self {
+        self.job_name = Some(job_name.into());
+        self
+    }
+
+    /// Filter by final job state
+    pub fn with_state(mut self, state: JobState) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Filter by result status
+    pub fn with_status(mut self, status: JobResultStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Filter by completion time, `from` inclusive and `to` exclusive
+    pub fn between(mut self, from: DateTime<Utc>, to: DateTime<Utc>) -> Self {
+        self.completed_after = Some(from);
+        self.completed_before = Some(to);
+        self
+    }
+
+    /// Limit the number of results
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Skip the first results
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    fn where_clause(&self) -> (String, Vec<Value>) {
+        let mut conditions = Vec::new();
+        let mut values = Vec::new();
+
+        if let Some(job_name) = &self.job_name {
+            conditions.push("job_name = ?");
+            values.push(Value::Text(job_name.clone()));
+        }
+        if let Some(state) = self.state {
+            conditions.push("state = ?");
+            values.push(Value::Text(format!("{:?}", state)));
+        }
+        if let Some(status) = self.status {
+            conditions.push("status = ?");
+            values.push(Value::Text(format!("{:?}", status)));
+        }
+        if let Some(from) = self.completed_after {
+            conditions.push("completed_at >= ?");
+            values.push(Value::Integer(from.timestamp_millis()));
+        }
+        if let Some(to) = self.completed_before {
+            conditions.push("completed_at < ?");
+            values.push(Value::Integer(to.timestamp_millis()));
+        }
+
+        if conditions.is_empty() {
+            (String::new(), values)
+        } else {
+            (format!(" WHERE {}", conditions.join(" AND ")), values)
+        }
+    }
+}
+
+/// Queryable archive of finished job results
+pub struct ResultStore {
+    pool: Arc<DatabasePool>,
+    config: ResultStoreConfig,
+}
+
+impl ResultStore {
+    /// Create a result store, creating its table if needed
+    pub fn new(pool: Arc<DatabasePool>, config: ResultStoreConfig) -> Result<Self> {
+        pool.with_connection(|conn| {
+            conn.execute_batch(SCHEMA)?;
+            Ok(())
+        })?;
+
+        Ok(Self { pool, config })
+    }
+
+    /// Get the store configuration
+    pub fn config(&self) -> &ResultStoreConfig {
+        &self.config
+    }
+
+    /// Archive a job result, replacing any earlier result for the same job
+    pub fn store(&self, metadata: &JobMetadata, result: &JobResult) -> Result<StoredResult> {
+        let stored_at = Utc::now();
+        let expires_at = self
+            .config
+            .ttl_for(&metadata.name)
+            .and_then(|ttl| chrono::Duration::from_std(ttl).ok())
+            .map(|ttl| result.completed_at + ttl);
+
+        let result_json = serde_json::to_string(result)?;
+        let metadata_json = serde_json::to_string(metadata)?;
+
+        self.pool.with_connection(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO job_result_archive
+                 (job_id, job_name, state, status, result, metadata, started_at,
+                  completed_at, duration_ms, attempts, stored_at, expires_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                params![
+                    result.job_id,
+                    metadata.name,
+                    format!("{:?}", metadata.state),
+                    format!("{:?}", result.status),
+                    result_json,
+                    metadata_json,
+                    metadata.started_at.map(|t| t.timestamp_millis()),
+                    result.completed_at.timestamp_millis(),
+                    result.duration_ms as i64,
+                    metadata.attempt,
+                    stored_at.timestamp_millis(),
+                    expires_at.map(|t| t.timestamp_millis()),
+                ],
+            )?;
+            Ok(())
+        })?;
+
+        Ok(StoredResult {
+            metadata: metadata.clone(),
+            result: result.clone(),
+            stored_at,
+            expires_at,
+        })
+    }
+
+    /// Get the archived result of a job
+    pub fn get(&self, job_id: &str) -> Result<Option<StoredResult>> {
+        let stored = self.pool.with_connection(|conn| {
+            let row = conn
+                .query_row(
+                    &format!(
+                        "SELECT {} FROM job_result_archive WHERE job_id = ?1",
+                        COLUMNS
+                    ),
+                    params![job_id],
+                    read_row,
+                )
+                .optional()?;
+            Ok(row)
+        })?;
+
+        stored.map(decode).transpose()
+    }
+
+    /// Query archived results, most recently completed first
+    pub fn query(&self, query: &ResultQuery) -> Result<Vec<StoredResult>> {
+        let (filter, mut values) = query.where_clause();
+        let mut sql = format!(
+            "SELECT {} FROM job_result_archive{} ORDER BY completed_at DESC, job_id",
+            COLUMNS, filter
+        );
+        if query.limit.is_some() || query.offset > 0 {
+            sql.push_str(" LIMIT ? OFFSET ?");
+            values.push(Value::Integer(query.limit.map_or(-1, |limit| limit as i64)));
+            values.push(Value::Integer(query.offset as i64));
+        }
+
+        let rows = self.pool.with_connection(|conn| {
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt
+                .query_map(params_from_iter(values.iter()), read_row)?
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            Ok(rows)
+        })?;
+
+        rows.into_iter().map(decode).collect()
+    }
+
+    /// Count archived results matching a query, ignoring limit and offset
+    pub fn count(&self, query: &ResultQuery) -> Result<usize> {
+        let (filter, values) = query.where_clause();
+        let sql = format!("SELECT COUNT(*) FROM job_result_archive{}", filter);
+
+        let count = self.pool.with_connection(|conn| {
+            let count: i64 =
+                conn.query_row(&sql, params_from_iter(values.iter()), |row| row.get(0))?;
+            Ok(count)
+        })?;
+
+        Ok(count as usize)
+    }
+
+    /// Remove the archived result of a job
+    pub fn remove(&self, job_id: &str) -> Result<bool> {
+        let deleted = self.pool.with_connection(|conn| {
+            let deleted = conn.execute(
+                "DELETE FROM job_result_archive WHERE job_id = ?1",
+                params![job_id],
+            )?;
+            Ok(deleted)
+        })?;
+
+        Ok(deleted > 0)
+    }
+
+    /// Delete results whose TTL has elapsed
+    pub fn purge_expired(&self) -> Result<usize> {
+        self.purge_expired_at(Utc::now())
+    }
+
+    fn purge_expired_at(&self, now: DateTime<Utc>) -> Result<usize> {
+        let deleted = self.pool.with_connection(|conn| {
+            let deleted = conn.execute(
+                "DELETE FROM job_result_archive
+                 WHERE expires_at IS NOT NULL AND expires_at <= ?1",
+                params![now.timestamp_millis()],
+            )?;
+            Ok(deleted)
+        })?;
+
+        if deleted > 0 {
+            tracing::debug!(deleted, "Purged expired job results");
+        }
+
+        Ok(deleted)
+    }
+
+    /// Purge expired results in the background at the configured interval
+    pub fn start_purging(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.config.purge_interval);
+
+            loop {
+                interval.tick().await;
+
+                if let Err(e) = self.purge_expired() {
+                    tracing::error!(error = %e, "Job result purge failed");
+                }
+            }
+        })
+    }
+}
+
+type RawRow = (String, String, i64, Option<i64>);
+
+fn read_row(row: &Row<'_>) -> rusqlite::Result<RawRow> {
+    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+}
+
+fn decode((result, metadata, stored_at, expires_at): RawRow) -> Result<StoredResult> {
+    Ok(StoredResult {
+        result: serde_json::from_str(&result)?,
+        metadata: serde_json::from_str(&metadata)?,
+        stored_at: from_millis(stored_at),
+        expires_at: expires_at.map(from_millis),
+    })
+}
+
+fn from_millis(millis: i64) -> DateTime<Utc> {
+    Utc.timestamp_millis_opt(millis).single().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use accuscene_database::DatabaseConfig;
+
+    fn store(config: ResultStoreConfig) -> ResultStore {
+        let pool = DatabasePool::new(DatabaseConfig::in_memory()).unwrap();
+        ResultStore::new(Arc::new(pool), config).unwrap()
+    }
+
+    fn finished(
+        id: &str,
+        name: &str,
+        state: JobState,
+        completed_at: DateTime<Utc>,
+    ) -> (JobMetadata, JobResult) {
+        let mut metadata = JobMetadata::new(id.to_string(), name.to_string());
+        metadata.state = state;
+        metadata.completed_at = Some(completed_at);
+
+        let mut result = if state == JobState::Completed {
+            JobResult::success(id.to_string(), serde_json::json!({ "id": id }))
+        } else {
+            JobResult::failure(id.to_string(), "boom".to_string())
+        };
+        result.completed_at = completed_at;
+        (metadata, result.with_duration(42))
+    }
+
+    #[test]
+    fn test_store_and_get() {
+        let store = store(ResultStoreConfig::default());
+        let (metadata, result) = finished("job-1", "physics", JobState::Completed, Utc::now());
+
+        store.store(&metadata, &result).unwrap();
+
+        let stored = store.get("job-1").unwrap().unwrap();
+        assert_eq!(stored.metadata.name, "physics");
+        assert_eq!(stored.result.output, serde_json::json!({ "id": "job-1" }));
+        assert_eq!(stored.result.duration_ms, 42);
+        assert!(stored.expires_at.is_some());
+        assert!(store.get("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_query_filters() {
+        let store = store(ResultStoreConfig::default());
+        let now = Utc::now();
+
+        for (id, name, state, hours_ago) in [
+            ("a", "physics", JobState::Completed, 1),
+            ("b", "physics", JobState::Failed, 2),
+            ("c", "report", JobState::Completed, 3),
+            ("d", "physics", JobState::Completed, 48),
+        ] {
+            let (metadata, result) =
+                finished(id, name, state, now - chrono::Duration::hours(hours_ago));
+            store.store(&metadata, &result).unwrap();
+        }
+
+        let physics = store.query(&ResultQuery::new().with_job_name("physics")).unwrap();
+        let ids: Vec<_> = physics.iter().map(|r| r.result.job_id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "d"]);
+
+        let failed = ResultQuery::new().with_state(JobState::Failed);
+        assert_eq!(store.count(&failed).unwrap(), 1);
+
+        let recent = ResultQuery::new()
+            .with_status(JobResultStatus::Success)
+            .between(now - chrono::Duration::days(1), now);
+        let ids: Vec<_> =
+            store.query(&recent).unwrap().into_iter().map(|r| r.result.job_id).collect();
+        assert_eq!(ids, vec!["a", "c"]);
+
+        let page = store.query(&ResultQuery::new().with_limit(2).with_offset(1)).unwrap();
+        assert_eq!(page.len(), 2);
+        assert_eq!(page[0].result.job_id, "b");
+    }
+
+    #[test]
+    fn test_purge_expired() {
+        let config = ResultStoreConfig::default()
+            .with_default_ttl(None)
+            .with_job_ttl("report", Duration::from_secs(60));
+        let store = store(config);
+        let now = Utc::now();
+
+        let (metadata, result) = finished("kept", "physics", JobState::Completed, now);
+        store.store(&metadata, &result).unwrap();
+        let (metadata, result) = finished("expiring", "report", JobState::Completed, now);
+        let stored = store.store(&metadata, &result).unwrap();
+        assert!(stored.expires_at.is_some());
+
+        assert_eq!(store.purge_expired_at(now).unwrap(), 0);
+        assert_eq!(
+            store.purge_expired_at(now + chrono::Duration::minutes(2)).unwrap(),
+            1
+        );
+
+        assert!(store.get("kept").unwrap().is_some());
+        assert!(store.get("expiring").unwrap().is_none());
+    }
+}
@@ -0,0 +1,538 @@
+//! Resource governance for job execution.
+//!
+//! Jobs with a memory or CPU time limit run on a dedicated thread with their
+//! own single-threaded runtime, so their usage can be attributed to them. A
+//! governor polls that usage and cancels the job as soon as a limit is
+//! breached. Cancellation takes effect at the job's next `.await`; a job that
+//! does not yield within a grace period is abandoned and reported as failed.
+//!
+//! Memory is counted by [`TrackingAllocator`], which must be installed as the
+//! global allocator for memory ceilings to be enforced. CPU time is read from
+//! the kernel's per-thread scheduler statistics and is only available on Linux.
+
+use crate::error::JobError;
+use crate::job::{Job, JobContext};
+use crate::result::JobResult;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{oneshot, Notify};
+
+/// How often the governor samples resource usage
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How long a cancelled job gets to reach an await point and hand itself back
+const CANCEL_GRACE: Duration = Duration::from_millis(500);
+
+static ALLOCATOR_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static CURRENT_METER: Cell<*const Meter> = const { Cell::new(std::ptr::null()) };
+}
+
+/// Resource limits applied to a job
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    /// Ceiling on memory allocated by the job
+    pub memory_bytes: Option<u64>,
+    /// Ceiling on CPU time consumed by the job
+    pub cpu_time: Option<Duration>,
+    /// Ceiling on wall-clock time per attempt
+    pub wall_time: Option<Duration>,
+}
+
+impl ResourceLimits {
+    /// Create limits that do not restrict anything
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Set the memory ceiling
+    pub fn with_memory_bytes(mut self, bytes: u64) -> Self {
+        self.memory_bytes = Some(bytes);
+        self
+    }
+
+    /// Set the CPU time limit
+    pub fn with_cpu_time(mut self, cpu_time: Duration) -> Self {
+        self.cpu_time = Some(cpu_time);
+        self
+    }
+
+    /// Set the wall-clock timeout
+    pub fn with_wall_time(mut self, wall_time: Duration) -> Self {
+        self.wall_time = Some(wall_time);
+        self
+    }
+
+    /// Fill limits unset here from `defaults`
+    pub fn or(self, defaults: ResourceLimits) -> Self {
+        Self {
+            memory_bytes: self.memory_bytes.or(defaults.memory_bytes),
+            cpu_time: self.cpu_time.or(defaults.cpu_time),
+            wall_time: self.wall_time.or(defaults.wall_time),
+        }
+    }
+
+    /// Whether the job has to run on its own thread to be governed
+    pub fn needs_sandbox(&self) -> bool {
+        self.memory_bytes.is_some() || self.cpu_time.is_some()
+    }
+
+    /// Find the first limit exceeded by `usage`
+    pub fn check(&self, usage: &ResourceUsage) -> Option<LimitBreach> {
+        if let Some(limit) = self.memory_bytes {
+            if usage.memory_bytes > limit {
+                return Some(LimitBreach::new(ResourceKind::Memory, limit, usage.memory_bytes));
+            }
+        }
+        if let (Some(limit), Some(used)) = (self.cpu_time, usage.cpu_time) {
+            if used > limit {
+                return Some(LimitBreach::new(
+                    ResourceKind::CpuTime,
+                    limit.as_millis() as u64,
+                    used.as_millis() as u64,
+                ));
+            }
+        }
+        if let Some(limit) = self.wall_time {
+            if usage.wall_time > limit {
+                return Some(LimitBreach::new(
+                    ResourceKind::WallTime,
+                    limit.as_millis() as u64,
+                    usage.wall_time.as_millis() as u64,
+                ));
+            }
+        }
+        None
+    }
+}
+
+/// Resource usage of a running job
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    /// Memory currently allocated by the job
+    pub memory_bytes: u64,
+    /// Highest memory allocated by the job
+    pub peak_memory_bytes: u64,
+    /// CPU time consumed, if the platform reports it
+    pub cpu_time: Option<Duration>,
+    /// Wall-clock time since the attempt started
+    pub wall_time: Duration,
+}
+
+/// Governed resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceKind {
+    /// Allocated memory, in bytes
+    Memory,
+    /// CPU time, in milliseconds
+    CpuTime,
+    /// Wall-clock time, in milliseconds
+    WallTime,
+}
+
+impl ResourceKind {
+    /// Get the resource name including its unit
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResourceKind::Memory => "memory_bytes",
+            ResourceKind::CpuTime => "cpu_time_ms",
+            ResourceKind::WallTime => "wall_time_ms",
+        }
+    }
+}
+
+impl fmt::Display for ResourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A resource limit exceeded by a job
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LimitBreach {
+    /// Resource that exceeded its limit
+    pub resource: ResourceKind,
+    /// Configured limit
+    pub limit: u64,
+    /// Usage observed when the breach was detected
+    pub observed: u64,
+    /// When the breach was detected
+    pub detected_at: DateTime<Utc>,
+}
+
+impl LimitBreach {
+    /// Create a breach detected now
+    pub fn new(resource: ResourceKind, limit: u64, observed: u64) -> Self {
+        Self {
+            resource,
+            limit,
+            observed,
+            detected_at: Utc::now(),
+        }
+    }
+
+    /// Convert the breach into the error the job fails with
+    pub fn to_error(&self) -> JobError {
+        match self.resource {
+            ResourceKind::WallTime => JobError::Timeout {
+                duration_secs: self.limit / 1000,
+            },
+            resource => JobError::ResourceLimitExceeded {
+                resource: resource.to_string(),
+                limit: self.limit,
+                observed: self.observed,
+            },
+        }
+    }
+}
+
+/// Global allocator wrapper that attributes allocations to governed jobs
+///
+/// Install it to enforce memory ceilings:
+///
+/// ```rust,ignore
+/// #[global_allocator]
+/// static ALLOCATOR: TrackingAllocator = TrackingAllocator::new(std::alloc::System);
+/// ```
+pub struct TrackingAllocator<A = System> {
+    inner: A,
+}
+
+impl<A> TrackingAllocator<A> {
+    /// Wrap an allocator
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+
+    /// Whether a tracking allocator is the active global allocator
+    pub fn is_installed() -> bool {
+        ALLOCATOR_INSTALLED.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    fn record(delta: i64) {
+        ALLOCATOR_INSTALLED.store(true, Ordering::Relaxed);
+        let _ = CURRENT_METER.try_with(|meter| {
+            let meter = meter.get();
+            if !meter.is_null() {
+                // SAFETY: the job thread keeps the meter alive while it is bound
+                unsafe { (*meter).add(delta) };
+            }
+        });
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for TrackingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc(layout);
+        if !ptr.is_null() {
+            Self::record(layout.size() as i64);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            Self::record(layout.size() as i64);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout);
+        Self::record(-(layout.size() as i64));
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = self.inner.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            Self::record(new_size as i64 - layout.size() as i64);
+        }
+        new_ptr
+    }
+}
+
+/// Usage counters of one governed job
+#[derive(Default)]
+struct Meter {
+    allocated: AtomicI64,
+    peak: AtomicI64,
+    cpu_stat: OnceLock<PathBuf>,
+}
+
+impl Meter {
+    fn add(&self, delta: i64) {
+        let allocated = self.allocated.fetch_add(delta, Ordering::Relaxed) + delta;
+        self.peak.fetch_max(allocated, Ordering::Relaxed);
+    }
+
+    /// Attribute the calling thread's allocations and CPU time to this meter
+    fn bind(self: &Arc<Self>) {
+        CURRENT_METER.with(|meter| meter.set(Arc::as_ptr(self)));
+
+        #[cfg(target_os = "linux")]
+        if let Ok(thread) = std::fs::read_link("/proc/thread-self") {
+            let _ = self.cpu_stat.set(PathBuf::from("/proc").join(thread).join("schedstat"));
+        }
+    }
+
+    fn unbind() {
+        CURRENT_METER.with(|meter| meter.set(std::ptr::null()));
+    }
+
+    fn cpu_time(&self) -> Option<Duration> {
+        let stat = std::fs::read_to_string(self.cpu_stat.get()?).ok()?;
+        let nanos = stat.split_whitespace().next()?.parse().ok()?;
+        Some(Duration::from_nanos(nanos))
+    }
+
+    fn usage(&self, started: Instant) -> ResourceUsage {
+        ResourceUsage {
+            memory_bytes: self.allocated.load(Ordering::Relaxed).max(0) as u64,
+            peak_memory_bytes: self.peak.load(Ordering::Relaxed).max(0) as u64,
+            cpu_time: self.cpu_time(),
+            wall_time: started.elapsed(),
+        }
+    }
+}
+
+/// Result of a governed job attempt
+pub(crate) enum SandboxOutcome {
+    /// The attempt ran to completion
+    Finished {
+        job: Box<dyn Job>,
+        result: crate::error::Result<JobResult>,
+        usage: ResourceUsage,
+    },
+    /// A limit was breached; the job is `None` if it did not yield in time
+    Breached {
+        job: Option<Box<dyn Job>>,
+        breach: LimitBreach,
+        usage: ResourceUsage,
+    },
+}
+
+type Handoff = (Box<dyn Job>, Option<crate::error::Result<JobResult>>);
+
+/// Run one attempt of a job on its own thread under the given limits
+pub(crate) async fn run(
+    job: Box<dyn Job>,
+    context: Arc<JobContext>,
+    limits: &ResourceLimits,
+) -> crate::error::Result<SandboxOutcome> {
+    if limits.memory_bytes.is_some() && !TrackingAllocator::<System>::is_installed() {
+        tracing::warn!(
+            job_id = %job.id(),
+            "Memory ceiling set but TrackingAllocator is not the global allocator"
+        );
+    }
+
+    let meter = Arc::new(Meter::default());
+    let cancel = Arc::new(Notify::new());
+    let (handoff, mut returned) = oneshot::channel::<Handoff>();
+
+    let thread_meter = meter.clone();
+    let thread_cancel = cancel.clone();
+    std::thread::Builder::new()
+        .name(format!("job-{}", job.id()))
+        .spawn(move || {
+            let mut job = job;
+            thread_meter.bind();
+
+            let result = match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => runtime.block_on(async {
+                    tokio::select! {
+                        result = job.execute(context) => Some(result),
+                        _ = thread_cancel.notified() => None,
+                    }
+                }),
+                Err(e) => Some(Err(JobError::Internal(format!(
+                    "Failed to start job runtime: {}",
+                    e
+                )))),
+            };
+
+            Meter::unbind();
+            let _ = handoff.send((job, result));
+        })?;
+
+    let started = Instant::now();
+    let mut poll = tokio::time::interval(POLL_INTERVAL);
+
+    loop {
+        tokio::select! {
+            handed_back = &mut returned => {
+                let usage = meter.usage(started);
+                let (job, result) = handed_back
+                    .map_err(|_| JobError::Internal("Job thread panicked".to_string()))?;
+                let result = result.unwrap_or_else(|| {
+                    Err(JobError::Cancelled("Job cancelled by governor".to_string()))
+                });
+                return Ok(SandboxOutcome::Finished { job, result, usage });
+            }
+            _ = poll.tick() => {
+                let usage = meter.usage(started);
+                let Some(breach) = limits.check(&usage) else {
+                    continue;
+                };
+
+                cancel.notify_one();
+                let job = match tokio::time::timeout(CANCEL_GRACE, &mut returned).await {
+                    Ok(Ok((job, _))) => Some(job),
+                    _ => None,
+                };
+                return Ok(SandboxOutcome::Breached { job, breach, usage });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    #[global_allocator]
+    static ALLOCATOR: TrackingAllocator = TrackingAllocator::new(System);
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    enum Workload {
+        Sleep(u64),
+        Allocate(usize),
+        Spin(u64),
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct WorkloadJob {
+        id: String,
+        workload: Workload,
+    }
+
+    #[async_trait]
+    impl Job for WorkloadJob {
+        async fn execute(&mut self, _context: Arc<JobContext>) -> crate::error::Result<JobResult> {
+            match self.workload {
+                Workload::Sleep(ms) => tokio::time::sleep(Duration::from_millis(ms)).await,
+                Workload::Allocate(bytes) => {
+                    let buffer = vec![1u8; bytes];
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    drop(buffer);
+                }
+                Workload::Spin(ms) => {
+                    let until = Instant::now() + Duration::from_millis(ms);
+                    while Instant::now() < until {
+                        tokio::task::yield_now().await;
+                    }
+                }
+            }
+            Ok(JobResult::success(self.id.clone(), serde_json::json!({})))
+        }
+
+        fn id(&self) -> &str {
+            &self.id
+        }
+
+        fn name(&self) -> &str {
+            "workload"
+        }
+
+        fn serialize(&self) -> crate::error::Result<String> {
+            serde_json::to_string(self).map_err(Into::into)
+        }
+
+        fn deserialize(data: &str) -> crate::error::Result<Box<dyn Job>> {
+            let job: WorkloadJob = serde_json::from_str(data)?;
+            Ok(Box::new(job))
+        }
+    }
+
+    async fn run_workload(workload: Workload, limits: ResourceLimits) -> SandboxOutcome {
+        let job = Box::new(WorkloadJob {
+            id: "job-1".to_string(),
+            workload,
+        });
+        let context = Arc::new(JobContext::new("job-1".to_string(), "worker-1".to_string()));
+        run(job, context, &limits).await.unwrap()
+    }
+
+    #[test]
+    fn test_limits_check() {
+        let limits = ResourceLimits::none()
+            .with_memory_bytes(1024)
+            .or(ResourceLimits::none().with_memory_bytes(1).with_wall_time(Duration::from_secs(1)));
+        assert_eq!(limits.memory_bytes, Some(1024));
+        assert_eq!(limits.wall_time, Some(Duration::from_secs(1)));
+
+        let usage = ResourceUsage {
+            memory_bytes: 2048,
+            ..ResourceUsage::default()
+        };
+        let breach = limits.check(&usage).unwrap();
+        assert_eq!(breach.resource, ResourceKind::Memory);
+        assert_eq!(breach.observed, 2048);
+        assert!(limits.check(&ResourceUsage::default()).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sandbox_completes_within_limits() {
+        let limits = ResourceLimits::none().with_memory_bytes(64 * 1024 * 1024);
+        match run_workload(Workload::Sleep(20), limits).await {
+            SandboxOutcome::Finished { result, .. } => assert!(result.unwrap().is_success()),
+            SandboxOutcome::Breached { breach, .. } => panic!("unexpected breach: {:?}", breach),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sandbox_memory_ceiling() {
+        let limits = ResourceLimits::none().with_memory_bytes(4 * 1024 * 1024);
+        match run_workload(Workload::Allocate(32 * 1024 * 1024), limits).await {
+            SandboxOutcome::Breached { job, breach, usage } => {
+                assert_eq!(breach.resource, ResourceKind::Memory);
+                assert!(usage.peak_memory_bytes >= 32 * 1024 * 1024);
+                assert!(job.is_some());
+            }
+            SandboxOutcome::Finished { .. } => panic!("memory ceiling not enforced"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sandbox_wall_time_cancels_job() {
+        let limits = ResourceLimits::none()
+            .with_memory_bytes(u64::MAX)
+            .with_wall_time(Duration::from_millis(50));
+        let started = Instant::now();
+        match run_workload(Workload::Sleep(5_000), limits).await {
+            SandboxOutcome::Breached { breach, .. } => {
+                assert_eq!(breach.resource, ResourceKind::WallTime);
+                assert!(matches!(breach.to_error(), JobError::Timeout { .. }));
+            }
+            SandboxOutcome::Finished { .. } => panic!("wall-clock limit not enforced"),
+        }
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_sandbox_cpu_time_limit() {
+        let limits = ResourceLimits::none().with_cpu_time(Duration::from_millis(50));
+        match run_workload(Workload::Spin(5_000), limits).await {
+            SandboxOutcome::Breached { breach, .. } => {
+                assert_eq!(breach.resource, ResourceKind::CpuTime);
+            }
+            SandboxOutcome::Finished { .. } => panic!("CPU time limit not enforced"),
+        }
+    }
+}
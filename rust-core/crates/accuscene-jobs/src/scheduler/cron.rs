@@ -3,8 +3,9 @@
 use crate::error::{JobError, Result};
 use crate::job::Job;
 use crate::queue::JobQueue;
-use crate::scheduler::{Schedule, ScheduledJob};
-use chrono::{DateTime, Utc};
+use crate::scheduler::{MisfirePolicy, Schedule, ScheduledJob};
+use chrono::{DateTime, Duration, NaiveDateTime, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
 use cron::Schedule as CronSchedule;
 use parking_lot::RwLock;
 use std::collections::HashMap;
@@ -12,10 +13,86 @@ use std::str::FromStr;
 use std::sync::Arc;
 use uuid::Uuid;
 
+/// Longest run of non-existent local minutes skipped when resolving a fire time
+const MAX_GAP_MINUTES: u32 = 24 * 60;
+
+/// Upper bound on wall-clock candidates examined for a single fire time
+const MAX_CANDIDATES: usize = 10_000;
+
+/// Upper bound on runs queued for one schedule by a single catch-up tick
+const MAX_CATCH_UP_RUNS: usize = 1_000;
+
+/// Cron expression evaluated against wall-clock time in an IANA timezone
+#[derive(Debug, Clone)]
+pub struct CronTrigger {
+    schedule: CronSchedule,
+    timezone: Tz,
+}
+
+impl CronTrigger {
+    /// Parse a cron expression, evaluated in UTC when no timezone is given
+    pub fn new(expression: &str, timezone: Option<&str>) -> Result<Self> {
+        let schedule = CronSchedule::from_str(expression).map_err(|e| {
+            JobError::InvalidCronExpression(format!("Invalid cron expression: {}", e))
+        })?;
+
+        let timezone = match timezone {
+            Some(name) => Tz::from_str(name).map_err(|e| {
+                JobError::InvalidConfiguration(format!("Unknown timezone '{}': {}", name, e))
+            })?,
+            None => Tz::UTC,
+        };
+
+        Ok(Self { schedule, timezone })
+    }
+
+    /// Get the timezone the expression is evaluated in
+    pub fn timezone(&self) -> Tz {
+        self.timezone
+    }
+
+    /// Compute the first fire time strictly after `after`
+    ///
+    /// Fire times that fall into a daylight-saving gap run at the first local time
+    /// after the gap. Fire times in a repeated hour run on their first occurrence only.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let local = after.with_timezone(&self.timezone).naive_local();
+
+        self.schedule
+            .after(&Utc.from_utc_datetime(&local))
+            .take(MAX_CANDIDATES)
+            .filter_map(|wall| self.resolve(wall.naive_utc()))
+            .find(|fire| *fire > after)
+    }
+
+    /// Map a wall-clock time to the instant it first occurs
+    fn resolve(&self, wall: NaiveDateTime) -> Option<DateTime<Utc>> {
+        let mut candidate = wall;
+
+        for _ in 0..=MAX_GAP_MINUTES {
+            if let Some(instant) = self.timezone.from_local_datetime(&candidate).earliest() {
+                return Some(instant.with_timezone(&Utc));
+            }
+            candidate = (candidate + Duration::minutes(1)).with_second(0)?;
+        }
+
+        None
+    }
+}
+
+/// Cron schedule entry
+struct CronEntry {
+    scheduled_job: ScheduledJob,
+    job: Box<dyn Job>,
+    trigger: CronTrigger,
+    misfire_policy: MisfirePolicy,
+}
+
 /// Cron-based job scheduler
 pub struct CronScheduler {
-    scheduled_jobs: Arc<RwLock<HashMap<String, (ScheduledJob, Box<dyn Job>, CronSchedule)>>>,
+    scheduled_jobs: Arc<RwLock<HashMap<String, CronEntry>>>,
     queue: Arc<dyn JobQueue>,
+    misfire_threshold: Duration,
 }
 
 impl CronScheduler {
@@ -24,22 +101,39 @@ impl CronScheduler {
         Self {
             scheduled_jobs: Arc::new(RwLock::new(HashMap::new())),
             queue,
+            misfire_threshold: Duration::minutes(2),
         }
     }
 
-    /// Schedule a job with a cron expression
+    /// Set how late a fire time may be picked up before it counts as missed
+    pub fn with_misfire_threshold(mut self, threshold: std::time::Duration) -> Self {
+        self.misfire_threshold = Duration::from_std(threshold).unwrap_or(self.misfire_threshold);
+        self
+    }
+
+    /// Schedule a job with a cron expression evaluated in UTC
     pub async fn schedule_cron(&self, job: Box<dyn Job>, expression: String) -> Result<String> {
+        self.schedule_cron_with(job, expression, None, MisfirePolicy::default()).await
+    }
+
+    /// Schedule a job with a cron expression evaluated in an IANA timezone
+    pub async fn schedule_cron_with(
+        &self,
+        job: Box<dyn Job>,
+        expression: String,
+        timezone: Option<String>,
+        misfire_policy: MisfirePolicy,
+    ) -> Result<String> {
         let schedule_id = Uuid::new_v4().to_string();
         let job_id = job.id().to_string();
         let job_name = job.name().to_string();
 
-        // Parse cron expression
-        let cron_schedule = CronSchedule::from_str(&expression)
-            .map_err(|e| JobError::InvalidCronExpression(format!("Invalid cron expression: {}", e)))?;
+        let trigger = CronTrigger::new(&expression, timezone.as_deref())?;
 
         let schedule = Schedule::Cron {
             expression: expression.clone(),
-            timezone: None,
+            timezone,
+            misfire_policy,
         };
 
         let mut scheduled_job = ScheduledJob::new(
@@ -50,21 +144,27 @@ impl CronScheduler {
         );
 
         // Calculate next run time
-        scheduled_job.next_run = cron_schedule.upcoming(Utc).next();
-
-        self.scheduled_jobs.write().insert(
-            schedule_id.clone(),
-            (scheduled_job, job, cron_schedule),
-        );
+        scheduled_job.next_run = trigger.next_after(Utc::now());
 
         tracing::info!(
             schedule_id = %schedule_id,
             job_id = %job_id,
             expression = %expression,
+            timezone = %trigger.timezone(),
             next_run = ?scheduled_job.next_run,
             "Job scheduled with cron"
         );
 
+        self.scheduled_jobs.write().insert(
+            schedule_id.clone(),
+            CronEntry {
+                scheduled_job,
+                job,
+                trigger,
+                misfire_policy,
+            },
+        );
+
         Ok(schedule_id)
     }
 
@@ -84,46 +184,103 @@ impl CronScheduler {
         let scheduled_jobs = self.scheduled_jobs.read();
         Ok(scheduled_jobs
             .get(schedule_id)
-            .and_then(|(job, _, _)| job.next_run))
+            .and_then(|entry| entry.scheduled_job.next_run))
     }
 
     /// Tick the scheduler - check for jobs that need to run
     pub async fn tick(&self) -> Result<Vec<String>> {
-        let now = Utc::now();
+        self.tick_at(Utc::now()).await
+    }
+
+    async fn tick_at(&self, now: DateTime<Utc>) -> Result<Vec<String>> {
         let mut executed_jobs = Vec::new();
+        let mut pending: Vec<Box<dyn Job>> = Vec::new();
+
+        {
+            let mut scheduled_jobs = self.scheduled_jobs.write();
 
-        let mut scheduled_jobs = self.scheduled_jobs.write();
+            for (schedule_id, entry) in scheduled_jobs.iter_mut() {
+                let next_run = match entry.scheduled_job.next_run {
+                    Some(next_run) if next_run <= now => next_run,
+                    _ => continue,
+                };
+
+                let runs = self.runs_due(entry, next_run, now);
+                if now - next_run > self.misfire_threshold {
+                    tracing::warn!(
+                        schedule_id = %schedule_id,
+                        missed_since = %next_run,
+                        policy = ?entry.misfire_policy,
+                        runs,
+                        "Cron job misfired"
+                    );
+                }
 
-        for (schedule_id, (scheduled_job, job, cron_schedule)) in scheduled_jobs.iter_mut() {
-            if let Some(next_run) = scheduled_job.next_run {
-                if next_run <= now {
+                for run in 0..runs {
                     // Clone the job for execution
-                    let job_clone_data = job.serialize()?;
+                    let mut job_clone_data = entry.job.serialize()?;
+                    if run > 0 {
+                        job_clone_data = with_fresh_id(&job_clone_data)?;
+                    }
                     let job_clone: Box<dyn Job> = serde_json::from_str(&job_clone_data)?;
+                    pending.push(job_clone);
+                }
 
-                    // Queue the job
-                    self.queue.push(job_clone).await?;
-
-                    // Update scheduled job info
+                // Update scheduled job info
+                let scheduled_job = &mut entry.scheduled_job;
+                if runs > 0 {
                     scheduled_job.last_run = Some(now);
-                    scheduled_job.run_count += 1;
-                    scheduled_job.next_run = cron_schedule.upcoming(Utc).next();
-
+                    scheduled_job.run_count += runs as u64;
                     executed_jobs.push(schedule_id.clone());
-
-                    tracing::info!(
-                        schedule_id = %schedule_id,
-                        job_id = %job.id(),
-                        next_run = ?scheduled_job.next_run,
-                        "Cron job triggered"
-                    );
                 }
+                scheduled_job.next_run = entry.trigger.next_after(now);
+
+                tracing::info!(
+                    schedule_id = %schedule_id,
+                    job_id = %entry.job.id(),
+                    runs,
+                    next_run = ?scheduled_job.next_run,
+                    "Cron job triggered"
+                );
             }
         }
 
+        // Queue the jobs
+        for job in pending {
+            self.queue.push(job).await?;
+        }
+
         Ok(executed_jobs)
     }
 
+    /// Number of runs to queue for fire times between `next_run` and `now`
+    fn runs_due(&self, entry: &CronEntry, next_run: DateTime<Utc>, now: DateTime<Utc>) -> usize {
+        match entry.misfire_policy {
+            MisfirePolicy::Skip => {
+                let on_time = entry
+                    .trigger
+                    .next_after(now - self.misfire_threshold)
+                    .is_some_and(|fire| fire >= next_run && fire <= now);
+                usize::from(on_time || now - next_run <= self.misfire_threshold)
+            }
+            MisfirePolicy::FireOnce => 1,
+            MisfirePolicy::CatchUp => {
+                let mut runs = 1;
+                let mut fire = next_run;
+                while runs < MAX_CATCH_UP_RUNS {
+                    match entry.trigger.next_after(fire) {
+                        Some(next) if next <= now => {
+                            runs += 1;
+                            fire = next;
+                        }
+                        _ => break,
+                    }
+                }
+                runs
+            }
+        }
+    }
+
     /// Start the scheduler in the background
     pub fn start(self: Arc<Self>) {
         tokio::spawn(async move {
@@ -144,11 +301,31 @@ impl CronScheduler {
         self.scheduled_jobs
             .read()
             .values()
-            .map(|(job, _, _)| job.clone())
+            .map(|entry| entry.scheduled_job.clone())
             .collect()
     }
 }
 
+/// Give a serialized job a new ID so queues accept repeated catch-up runs
+fn with_fresh_id(data: &str) -> Result<String> {
+    let mut value: serde_json::Value = serde_json::from_str(data)?;
+    let id = serde_json::Value::String(Uuid::new_v4().to_string());
+
+    if let Some(fields) = value.as_object_mut() {
+        if let Some(job_id) = fields.get_mut("id") {
+            *job_id = id.clone();
+        }
+        if let Some(metadata_id) = fields
+            .get_mut("metadata")
+            .and_then(|metadata| metadata.get_mut("id"))
+        {
+            *metadata_id = id;
+        }
+    }
+
+    Ok(serde_json::to_string(&value)?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -199,4 +376,78 @@ mod tests {
         let scheduled_jobs = scheduler.list_scheduled().await;
         assert_eq!(scheduled_jobs.len(), 0);
     }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_trigger_follows_local_time_across_dst() {
+        let trigger = CronTrigger::new("0 0 6 * * *", Some("America/New_York")).unwrap();
+
+        // 06:00 EST, then 06:00 EDT after the spring-forward change
+        let first = trigger.next_after(utc("2024-03-09T00:00:00Z")).unwrap();
+        assert_eq!(first, utc("2024-03-09T11:00:00Z"));
+        assert_eq!(trigger.next_after(first).unwrap(), utc("2024-03-10T10:00:00Z"));
+    }
+
+    #[test]
+    fn test_trigger_dst_gap_and_overlap() {
+        let trigger = CronTrigger::new("0 30 2 * * *", Some("America/New_York")).unwrap();
+
+        // 02:30 does not exist on 2024-03-10, runs at 03:00 EDT instead
+        let gap = trigger.next_after(utc("2024-03-10T05:00:00Z")).unwrap();
+        assert_eq!(gap, utc("2024-03-10T07:00:00Z"));
+
+        let trigger = CronTrigger::new("0 30 1 * * *", Some("America/New_York")).unwrap();
+
+        // 01:30 happens twice on 2024-11-03, runs on the first occurrence only
+        let overlap = trigger.next_after(utc("2024-11-03T04:00:00Z")).unwrap();
+        assert_eq!(overlap, utc("2024-11-03T05:30:00Z"));
+        assert_eq!(trigger.next_after(overlap).unwrap(), utc("2024-11-04T06:30:00Z"));
+    }
+
+    #[test]
+    fn test_trigger_rejects_unknown_timezone() {
+        assert!(CronTrigger::new("0 0 * * * *", Some("Mars/Olympus_Mons")).is_err());
+        assert!(CronTrigger::new("not a cron", None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_misfire_policies() {
+        let now = utc("2024-06-01T12:30:00Z");
+
+        for (policy, expected) in [
+            (MisfirePolicy::Skip, 0),
+            (MisfirePolicy::FireOnce, 1),
+            (MisfirePolicy::CatchUp, 5),
+        ] {
+            let queue = Arc::new(MemoryQueue::new());
+            let scheduler = CronScheduler::new(queue.clone());
+
+            let job = Box::new(PhysicsSimulationJob::new(
+                "test-scenario".to_string(),
+                serde_json::json!({}),
+            ));
+            let schedule_id = scheduler
+                .schedule_cron_with(job, "0 0 * * * *".to_string(), None, policy)
+                .await
+                .unwrap();
+
+            // Pretend the scheduler was down since 08:00
+            scheduler
+                .scheduled_jobs
+                .write()
+                .get_mut(&schedule_id)
+                .unwrap()
+                .scheduled_job
+                .next_run = Some(utc("2024-06-01T08:00:00Z"));
+
+            scheduler.tick_at(now).await.unwrap();
+
+            assert_eq!(queue.len().await.unwrap(), expected, "{:?}", policy);
+            let next_run = scheduler.next_execution(&schedule_id).await.unwrap();
+            assert_eq!(next_run, Some(utc("2024-06-01T13:00:00Z")));
+        }
+    }
 }
@@ -47,10 +47,27 @@ pub enum Schedule {
     /// Execute based on cron expression
     Cron {
         expression: String,
+        /// IANA timezone the expression is evaluated in, UTC when unset
         timezone: Option<String>,
+        /// Handling of fire times missed while the scheduler was down
+        #[serde(default)]
+        misfire_policy: MisfirePolicy,
     },
 }
 
+/// What to do with fire times that passed while the scheduler was not ticking
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MisfirePolicy {
+    /// Drop missed runs and wait for the next fire time
+    Skip,
+    /// Run once for all missed fire times
+    #[default]
+    FireOnce,
+    /// Run once for every missed fire time
+    CatchUp,
+}
+
 /// Scheduled job information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScheduledJob {
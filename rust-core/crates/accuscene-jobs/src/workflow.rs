@@ -0,0 +1,763 @@
+//! DAG workflows with conditional branches.
+//!
+//! A workflow is a set of named nodes, each running one job, connected by
+//! edges. A node runs once every edge into it is resolved: an edge is
+//! active when its upstream node ran and the edge condition holds for the
+//! upstream result, and inactive when the condition fails or the upstream
+//! node was skipped. Nodes whose join rule isn't met are skipped, which
+//! deactivates their own outgoing edges, so untaken branches drop out of
+//! the run. Independent nodes run concurrently.
+//!
+//! Definitions are plain data and load from JSON or YAML at runtime; job
+//! types are resolved through factories registered on the engine.
+
+use crate::error::{JobError, Result};
+use crate::executor::JobExecutor;
+use crate::job::{Job, JobContext};
+use crate::pipeline::PipelineStatus;
+use crate::result::JobResult;
+use crate::retry::RetryPolicy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Creates the job for a workflow node
+pub type JobFactory = Arc<dyn Fn(NodeInput) -> Result<Box<dyn Job>> + Send + Sync>;
+
+/// Serializable workflow definition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowDefinition {
+    /// Workflow name
+    pub name: String,
+
+    /// Nodes, each running one job
+    pub nodes: Vec<WorkflowNode>,
+
+    /// Dependencies between nodes
+    #[serde(default)]
+    pub edges: Vec<WorkflowEdge>,
+}
+
+impl WorkflowDefinition {
+    /// Create an empty workflow definition
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    /// Add a node
+    pub fn with_node(mut self, node: WorkflowNode) -> Self {
+        self.nodes.push(node);
+        self
+    }
+
+    /// Add an edge
+    pub fn with_edge(mut self, edge: WorkflowEdge) -> Self {
+        self.edges.push(edge);
+        self
+    }
+
+    /// Load a definition from JSON
+    pub fn from_json(data: &str) -> Result<Self> {
+        let definition: Self = serde_json::from_str(data)?;
+        definition.validate()?;
+        Ok(definition)
+    }
+
+    /// Load a definition from YAML
+    pub fn from_yaml(data: &str) -> Result<Self> {
+        let definition: Self = serde_yaml::from_str(data)
+            .map_err(|e| JobError::InvalidConfiguration(format!("Invalid workflow YAML: {}", e)))?;
+        definition.validate()?;
+        Ok(definition)
+    }
+
+    /// Serialize the definition to JSON
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(Into::into)
+    }
+
+    /// Serialize the definition to YAML
+    pub fn to_yaml(&self) -> Result<String> {
+        serde_yaml::to_string(self)
+            .map_err(|e| JobError::InvalidConfiguration(format!("Invalid workflow YAML: {}", e)))
+    }
+
+    /// Check that node names are unique, edges connect known nodes and the
+    /// graph has no cycles
+    pub fn validate(&self) -> Result<()> {
+        let mut names = HashSet::new();
+        for node in &self.nodes {
+            if node.name.is_empty() {
+                return Err(JobError::InvalidConfiguration(
+                    "Workflow node name is empty".to_string(),
+                ));
+            }
+            if !names.insert(node.name.as_str()) {
+                return Err(JobError::InvalidConfiguration(format!(
+                    "Duplicate workflow node: {}",
+                    node.name
+                )));
+            }
+        }
+
+        for edge in &self.edges {
+            for name in [&edge.from, &edge.to] {
+                if !names.contains(name.as_str()) {
+                    return Err(JobError::InvalidConfiguration(format!(
+                        "Workflow edge references unknown node: {}",
+                        name
+                    )));
+                }
+            }
+        }
+
+        if self.topological_order().len() < self.nodes.len() {
+            return Err(JobError::DependencyError(format!(
+                "Workflow {} has a dependency cycle",
+                self.name
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Node names in dependency order; nodes on a cycle are left out
+    fn topological_order(&self) -> Vec<&str> {
+        let mut in_degree: HashMap<&str, usize> =
+            self.nodes.iter().map(|node| (node.name.as_str(), 0)).collect();
+        for edge in &self.edges {
+            *in_degree.entry(edge.to.as_str()).or_insert(0) += 1;
+        }
+
+        let mut ready: VecDeque<&str> = self
+            .nodes
+            .iter()
+            .map(|node| node.name.as_str())
+            .filter(|name| in_degree[name] == 0)
+            .collect();
+        let mut order = Vec::with_capacity(self.nodes.len());
+
+        while let Some(name) = ready.pop_front() {
+            order.push(name);
+            for edge in self.edges.iter().filter(|edge| edge.from == name) {
+                let degree = in_degree.get_mut(edge.to.as_str()).unwrap();
+                *degree -= 1;
+                if *degree == 0 {
+                    ready.push_back(edge.to.as_str());
+                }
+            }
+        }
+
+        order
+    }
+}
+
+/// Workflow node running one job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowNode {
+    /// Node name, unique in the workflow
+    pub name: String,
+
+    /// Job type, selecting the registered factory
+    pub job_type: String,
+
+    /// Data passed to the job factory
+    #[serde(default)]
+    pub job_data: serde_json::Value,
+
+    /// Which incoming edges must be active for the node to run
+    #[serde(default)]
+    pub join: JoinRule,
+
+    /// Retry policy overriding the engine default
+    #[serde(default)]
+    pub retry: Option<NodeRetry>,
+}
+
+impl WorkflowNode {
+    /// Create a new workflow node
+    pub fn new(name: impl Into<String>, job_type: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            job_type: job_type.into(),
+            job_data: serde_json::Value::Null,
+            join: JoinRule::All,
+            retry: None,
+        }
+    }
+
+    /// Set job data
+    pub fn with_data(mut self, data: serde_json::Value) -> Self {
+        self.job_data = data;
+        self
+    }
+
+    /// Set the join rule
+    pub fn with_join(mut self, join: JoinRule) -> Self {
+        self.join = join;
+        self
+    }
+
+    /// Set the retry policy
+    pub fn with_retry(mut self, retry: NodeRetry) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+}
+
+/// Rule for running a node with several upstream edges
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JoinRule {
+    /// Run when every incoming edge is active
+    #[default]
+    All,
+
+    /// Run when at least one incoming edge is active
+    Any,
+}
+
+/// Per-node retry policy
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+pub enum NodeRetry {
+    /// Retry after a fixed delay
+    Fixed {
+        /// Maximum attempts
+        max_attempts: u32,
+        /// Delay between attempts
+        delay_ms: u64,
+    },
+
+    /// Retry with exponential backoff
+    Exponential {
+        /// Maximum attempts
+        max_attempts: u32,
+    },
+
+    /// Retry with linear backoff
+    Linear {
+        /// Maximum attempts
+        max_attempts: u32,
+    },
+
+    /// Retry with Fibonacci backoff
+    Fibonacci {
+        /// Maximum attempts
+        max_attempts: u32,
+    },
+}
+
+impl NodeRetry {
+    /// Get the retry policy
+    pub fn policy(&self) -> RetryPolicy {
+        match *self {
+            NodeRetry::Fixed {
+                max_attempts,
+                delay_ms,
+            } => RetryPolicy::fixed(max_attempts, delay_ms),
+            NodeRetry::Exponential { max_attempts } => RetryPolicy::exponential(max_attempts),
+            NodeRetry::Linear { max_attempts } => RetryPolicy::linear(max_attempts),
+            NodeRetry::Fibonacci { max_attempts } => RetryPolicy::fibonacci(max_attempts),
+        }
+    }
+}
+
+/// Dependency between two nodes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowEdge {
+    /// Upstream node
+    pub from: String,
+
+    /// Downstream node
+    pub to: String,
+
+    /// Condition on the upstream result for the edge to be taken
+    #[serde(default)]
+    pub condition: EdgeCondition,
+}
+
+impl WorkflowEdge {
+    /// Create an edge taken when the upstream node succeeds
+    pub fn new(from: impl Into<String>, to: impl Into<String>) -> Self {
+        Self {
+            from: from.into(),
+            to: to.into(),
+            condition: EdgeCondition::Success,
+        }
+    }
+
+    /// Set the edge condition
+    pub fn when(mut self, condition: EdgeCondition) -> Self {
+        self.condition = condition;
+        self
+    }
+}
+
+/// Condition evaluated on an upstream job result
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "when", rename_all = "snake_case")]
+pub enum EdgeCondition {
+    /// Whatever the upstream result
+    Always,
+
+    /// When the upstream job succeeded
+    #[default]
+    Success,
+
+    /// When the upstream job failed
+    Failure,
+
+    /// When a value in the upstream output equals the given one
+    OutputEquals {
+        /// JSON pointer into the output, such as `/status`
+        pointer: String,
+        /// Expected value
+        value: serde_json::Value,
+    },
+}
+
+impl EdgeCondition {
+    /// Evaluate the condition on an upstream result
+    pub fn evaluate(&self, result: &JobResult) -> bool {
+        match self {
+            EdgeCondition::Always => true,
+            EdgeCondition::Success => result.is_success(),
+            EdgeCondition::Failure => result.is_failure(),
+            EdgeCondition::OutputEquals { pointer, value } => {
+                result.output.pointer(pointer) == Some(value)
+            }
+        }
+    }
+}
+
+/// Input for creating a node's job
+#[derive(Debug, Clone)]
+pub struct NodeInput {
+    /// Node name
+    pub node: String,
+
+    /// Job data from the definition
+    pub data: serde_json::Value,
+
+    /// Outputs of upstream nodes along active edges
+    pub upstream: HashMap<String, serde_json::Value>,
+}
+
+/// Outcome of one node in a workflow run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum NodeOutcome {
+    /// The node's job ran
+    Completed(JobResult),
+
+    /// The node was skipped
+    Skipped,
+}
+
+/// Workflow execution result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowResult {
+    /// Run ID
+    pub workflow_id: String,
+
+    /// Workflow name
+    pub workflow_name: String,
+
+    /// Overall status
+    pub status: PipelineStatus,
+
+    /// Outcome of each node
+    pub nodes: HashMap<String, NodeOutcome>,
+
+    /// Run duration
+    pub duration_ms: u64,
+}
+
+impl WorkflowResult {
+    /// Check if every node that ran succeeded
+    pub fn is_success(&self) -> bool {
+        matches!(self.status, PipelineStatus::Completed)
+    }
+
+    /// Get the result of a node that ran
+    pub fn result(&self, node: &str) -> Option<&JobResult> {
+        match self.nodes.get(node) {
+            Some(NodeOutcome::Completed(result)) => Some(result),
+            _ => None,
+        }
+    }
+
+    /// Get the names of skipped nodes
+    pub fn skipped(&self) -> Vec<&str> {
+        self.nodes
+            .iter()
+            .filter(|(_, outcome)| matches!(outcome, NodeOutcome::Skipped))
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+}
+
+/// Runs workflow definitions
+pub struct WorkflowEngine {
+    factories: RwLock<HashMap<String, JobFactory>>,
+    default_retry: RetryPolicy,
+}
+
+impl WorkflowEngine {
+    /// Create a workflow engine with a default retry policy for nodes
+    pub fn new(default_retry: RetryPolicy) -> Self {
+        Self {
+            factories: RwLock::new(HashMap::new()),
+            default_retry,
+        }
+    }
+
+    /// Register a factory creating jobs of a type
+    pub fn register_job_type<F>(&self, job_type: impl Into<String>, factory: F)
+    where
+        F: Fn(NodeInput) -> Result<Box<dyn Job>> + Send + Sync + 'static,
+    {
+        self.factories.write().insert(job_type.into(), Arc::new(factory));
+    }
+
+    /// Run a workflow to completion
+    pub async fn run(&self, definition: &WorkflowDefinition) -> Result<WorkflowResult> {
+        definition.validate()?;
+        for node in &definition.nodes {
+            if !self.factories.read().contains_key(&node.job_type) {
+                return Err(JobError::InvalidConfiguration(format!(
+                    "Unknown job type {} for workflow node {}",
+                    node.job_type, node.name
+                )));
+            }
+        }
+
+        let workflow_id = Uuid::new_v4().to_string();
+        let start_time = std::time::Instant::now();
+
+        tracing::info!(
+            workflow_id = %workflow_id,
+            workflow_name = %definition.name,
+            nodes = definition.nodes.len(),
+            "Starting workflow execution"
+        );
+
+        // Resolved edges, by index: whether each was taken
+        let mut edges: Vec<Option<bool>> = vec![None; definition.edges.len()];
+        let mut outcomes: HashMap<String, NodeOutcome> = HashMap::new();
+
+        loop {
+            let ready: Vec<&WorkflowNode> = definition
+                .nodes
+                .iter()
+                .filter(|node| !outcomes.contains_key(&node.name))
+                .filter(|node| {
+                    definition
+                        .edges
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, edge)| edge.to == node.name)
+                        .all(|(index, _)| edges[index].is_some())
+                })
+                .collect();
+            if ready.is_empty() {
+                break;
+            }
+
+            let mut to_run = Vec::new();
+            for node in ready {
+                let incoming: Vec<bool> = definition
+                    .edges
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, edge)| edge.to == node.name)
+                    .map(|(index, _)| edges[index] == Some(true))
+                    .collect();
+                let runs = incoming.is_empty()
+                    || match node.join {
+                        JoinRule::All => incoming.iter().all(|active| *active),
+                        JoinRule::Any => incoming.iter().any(|active| *active),
+                    };
+
+                if runs {
+                    to_run.push(node);
+                } else {
+                    tracing::debug!(workflow_id = %workflow_id, node = %node.name, "Skipping workflow node");
+                    outcomes.insert(node.name.clone(), NodeOutcome::Skipped);
+                    Self::resolve_edges(definition, &mut edges, &node.name, None);
+                }
+            }
+
+            let runs = to_run.iter().map(|node| {
+                let upstream = definition
+                    .edges
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, edge)| edge.to == node.name && edges[*index] == Some(true))
+                    .filter_map(|(_, edge)| match outcomes.get(&edge.from) {
+                        Some(NodeOutcome::Completed(result)) => {
+                            Some((edge.from.clone(), result.output.clone()))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                self.run_node(definition, node, upstream)
+            });
+            let results = futures::future::join_all(runs).await;
+
+            for (node, result) in to_run.into_iter().zip(results) {
+                let result = result?;
+                Self::resolve_edges(definition, &mut edges, &node.name, Some(&result));
+                outcomes.insert(node.name.clone(), NodeOutcome::Completed(result));
+            }
+        }
+
+        let ran: Vec<&JobResult> = outcomes
+            .values()
+            .filter_map(|outcome| match outcome {
+                NodeOutcome::Completed(result) => Some(result),
+                NodeOutcome::Skipped => None,
+            })
+            .collect();
+        let status = if ran.iter().all(|result| !result.is_failure()) {
+            PipelineStatus::Completed
+        } else if ran.iter().any(|result| result.is_success()) {
+            PipelineStatus::PartialSuccess
+        } else {
+            PipelineStatus::Failed
+        };
+
+        tracing::info!(
+            workflow_id = %workflow_id,
+            status = ?status,
+            duration_ms = start_time.elapsed().as_millis(),
+            "Workflow execution completed"
+        );
+
+        Ok(WorkflowResult {
+            workflow_id,
+            workflow_name: definition.name.clone(),
+            status,
+            nodes: outcomes,
+            duration_ms: start_time.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Create and execute a node's job
+    async fn run_node(
+        &self,
+        definition: &WorkflowDefinition,
+        node: &WorkflowNode,
+        upstream: HashMap<String, serde_json::Value>,
+    ) -> Result<JobResult> {
+        let factory = self.factories.read().get(&node.job_type).cloned().ok_or_else(|| {
+            JobError::InvalidConfiguration(format!("Unknown job type: {}", node.job_type))
+        })?;
+        let job = factory(NodeInput {
+            node: node.name.clone(),
+            data: node.job_data.clone(),
+            upstream,
+        })?;
+
+        let retry = match &node.retry {
+            Some(retry) => retry.policy(),
+            None => self.default_retry.clone(),
+        };
+        let context = Arc::new(
+            JobContext::new(job.id().to_string(), format!("workflow:{}", definition.name))
+                .with_metadata(serde_json::json!({
+                    "workflow": definition.name,
+                    "node": node.name,
+                })),
+        );
+
+        tracing::info!(workflow_name = %definition.name, node = %node.name, "Executing workflow node");
+        JobExecutor::new(retry).execute(job, context).await
+    }
+
+    /// Mark the edges out of a node as taken or not
+    fn resolve_edges(
+        definition: &WorkflowDefinition,
+        edges: &mut [Option<bool>],
+        node: &str,
+        result: Option<&JobResult>,
+    ) {
+        for (index, edge) in definition.edges.iter().enumerate() {
+            if edge.from == node {
+                edges[index] = Some(result.is_some_and(|result| edge.condition.evaluate(result)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    /// Echoes its input, failing its first `fail_attempts` attempts
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct EchoJob {
+        id: String,
+        node: String,
+        data: serde_json::Value,
+        upstream: HashMap<String, serde_json::Value>,
+    }
+
+    #[async_trait]
+    impl Job for EchoJob {
+        async fn execute(&mut self, context: Arc<JobContext>) -> Result<JobResult> {
+            let fail_attempts = self.data["fail_attempts"].as_u64().unwrap_or(0);
+            if u64::from(context.attempt) <= fail_attempts {
+                return Err(JobError::ExecutionFailed(format!("{} failed", self.node)));
+            }
+
+            Ok(JobResult::success(
+                self.id.clone(),
+                serde_json::json!({
+                    "node": self.node,
+                    "status": self.data["status"],
+                    "attempt": context.attempt,
+                    "upstream": self.upstream.keys().collect::<Vec<_>>(),
+                }),
+            ))
+        }
+
+        fn id(&self) -> &str {
+            &self.id
+        }
+
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn serialize(&self) -> Result<String> {
+            serde_json::to_string(self).map_err(Into::into)
+        }
+
+        fn deserialize(data: &str) -> Result<Box<dyn Job>> {
+            let job: EchoJob = serde_json::from_str(data)?;
+            Ok(Box::new(job))
+        }
+    }
+
+    fn engine() -> WorkflowEngine {
+        let engine = WorkflowEngine::new(RetryPolicy::none());
+        engine.register_job_type("echo", |input: NodeInput| {
+            Ok(Box::new(EchoJob {
+                id: Uuid::new_v4().to_string(),
+                node: input.node,
+                data: input.data,
+                upstream: input.upstream,
+            }) as Box<dyn Job>)
+        });
+        engine
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_fan_in_from_yaml() {
+        let definition = WorkflowDefinition::from_yaml(
+            r#"
+name: reconstruction
+nodes:
+  - name: extract
+    job_type: echo
+  - name: physics
+    job_type: echo
+  - name: damage
+    job_type: echo
+    retry:
+      strategy: fixed
+      max_attempts: 3
+      delay_ms: 0
+    job_data:
+      fail_attempts: 2
+  - name: report
+    job_type: echo
+edges:
+  - { from: extract, to: physics }
+  - { from: extract, to: damage }
+  - { from: physics, to: report }
+  - { from: damage, to: report }
+"#,
+        )
+        .unwrap();
+
+        let result = engine().run(&definition).await.unwrap();
+        assert!(result.is_success());
+        assert!(result.skipped().is_empty());
+
+        let damage = result.result("damage").unwrap();
+        assert_eq!(damage.output["attempt"], 3);
+
+        let mut upstream: Vec<String> =
+            serde_json::from_value(result.result("report").unwrap().output["upstream"].clone())
+                .unwrap();
+        upstream.sort();
+        assert_eq!(upstream, vec!["damage", "physics"]);
+    }
+
+    #[tokio::test]
+    async fn test_conditional_branches() {
+        let definition = WorkflowDefinition::new("review")
+            .with_node(
+                WorkflowNode::new("validate", "echo").with_data(serde_json::json!({"fail_attempts": 1})),
+            )
+            .with_node(WorkflowNode::new("publish", "echo"))
+            .with_node(WorkflowNode::new("archive", "echo"))
+            .with_node(
+                WorkflowNode::new("notify", "echo").with_data(serde_json::json!({"status": "flagged"})),
+            )
+            .with_node(WorkflowNode::new("escalate", "echo"))
+            .with_node(WorkflowNode::new("close", "echo").with_join(JoinRule::Any))
+            .with_edge(WorkflowEdge::new("validate", "publish"))
+            .with_edge(WorkflowEdge::new("publish", "archive"))
+            .with_edge(WorkflowEdge::new("validate", "notify").when(EdgeCondition::Failure))
+            .with_edge(WorkflowEdge::new("notify", "escalate").when(EdgeCondition::OutputEquals {
+                pointer: "/status".to_string(),
+                value: serde_json::json!("flagged"),
+            }))
+            .with_edge(WorkflowEdge::new("archive", "close"))
+            .with_edge(WorkflowEdge::new("escalate", "close"));
+
+        let result = engine().run(&definition).await.unwrap();
+        assert_eq!(result.status, PipelineStatus::PartialSuccess);
+        assert!(result.result("validate").unwrap().is_failure());
+
+        let mut skipped = result.skipped();
+        skipped.sort();
+        assert_eq!(skipped, vec!["archive", "publish"]);
+        assert!(result.result("escalate").is_some());
+        assert!(result.result("close").is_some());
+    }
+
+    #[test]
+    fn test_definition_validation() {
+        let cyclic = WorkflowDefinition::new("cyclic")
+            .with_node(WorkflowNode::new("a", "echo"))
+            .with_node(WorkflowNode::new("b", "echo"))
+            .with_edge(WorkflowEdge::new("a", "b"))
+            .with_edge(WorkflowEdge::new("b", "a"));
+        assert!(matches!(cyclic.validate(), Err(JobError::DependencyError(_))));
+
+        let dangling = WorkflowDefinition::new("dangling")
+            .with_node(WorkflowNode::new("a", "echo"))
+            .with_edge(WorkflowEdge::new("a", "missing"));
+        assert!(dangling.validate().is_err());
+
+        let definition = WorkflowDefinition::new("roundtrip")
+            .with_node(WorkflowNode::new("a", "echo").with_retry(NodeRetry::Exponential { max_attempts: 2 }))
+            .with_node(WorkflowNode::new("b", "echo"))
+            .with_edge(WorkflowEdge::new("a", "b").when(EdgeCondition::Always));
+        let json = definition.to_json().unwrap();
+        assert_eq!(WorkflowDefinition::from_json(&json).unwrap(), definition);
+        let yaml = definition.to_yaml().unwrap();
+        assert_eq!(WorkflowDefinition::from_yaml(&yaml).unwrap(), definition);
+    }
+}
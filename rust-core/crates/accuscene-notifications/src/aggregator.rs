@@ -8,6 +8,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
 /// Aggregation rule
@@ -159,7 +161,8 @@ pub struct NotificationAggregator {
     rules: Arc<RwLock<HashMap<String, AggregationRule>>>,
     batches: Arc<RwLock<HashMap<String, Vec<NotificationBatch>>>>,
     dispatcher: Arc<NotificationDispatcher>,
-    shutdown_tx: Option<tokio::sync::mpsc::Sender<()>>,
+    shutdown: CancellationToken,
+    tasks: Vec<(String, JoinHandle<()>)>,
 }
 
 impl NotificationAggregator {
@@ -169,21 +172,33 @@ impl NotificationAggregator {
             rules: Arc::new(RwLock::new(HashMap::new())),
             batches: Arc::new(RwLock::new(HashMap::new())),
             dispatcher,
-            shutdown_tx: None,
+            shutdown: CancellationToken::new(),
+            tasks: Vec::new(),
         }
     }
 
+    /// Use a cancellation token from an enclosing shutdown scope
+    ///
+    /// Must be called before [`start`](Self::start).
+    pub fn set_shutdown_token(&mut self, token: CancellationToken) {
+        self.shutdown = token;
+    }
+
+    /// Hand over the spawned task handles so a shutdown scope can await them
+    pub fn take_task_handles(&mut self) -> Vec<(String, JoinHandle<()>)> {
+        std::mem::take(&mut self.tasks)
+    }
+
     /// Start the aggregator
     pub async fn start(&mut self) -> Result<()> {
-        let (shutdown_tx, mut shutdown_rx) = tokio::sync::mpsc::channel::<()>(1);
-        self.shutdown_tx = Some(shutdown_tx);
+        let shutdown = self.shutdown.clone();
 
         let rules = Arc::clone(&self.rules);
         let batches = Arc::clone(&self.batches);
         let dispatcher = Arc::clone(&self.dispatcher);
 
         // Spawn batch processor task
-        tokio::spawn(async move {
+        let handle = tokio::spawn(async move {
             tracing::info!("Notification aggregator started");
 
             let mut interval = tokio::time::interval(std::time::Duration::from_secs(10));
@@ -193,13 +208,14 @@ impl NotificationAggregator {
                     _ = interval.tick() => {
                         Self::process_batches(&rules, &batches, &dispatcher).await;
                     }
-                    _ = shutdown_rx.recv() => {
+                    _ = shutdown.cancelled() => {
                         tracing::info!("Notification aggregator shutting down");
                         break;
                     }
                 }
             }
         });
+        self.tasks.push(("batch-processor".to_string(), handle));
 
         Ok(())
     }
@@ -401,9 +417,7 @@ impl NotificationAggregator {
 
     /// Shutdown the aggregator
     pub async fn shutdown(&self) {
-        if let Some(tx) = &self.shutdown_tx {
-            let _ = tx.send(()).await;
-        }
+        self.shutdown.cancel();
     }
 
     /// Register default aggregation rules
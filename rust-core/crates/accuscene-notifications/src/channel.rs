@@ -116,8 +116,8 @@ impl Channel for EmailChannel {
         // Send email
         match client.send(email).await {
             Ok(_) => {
-                status.status = DeliveryState::Delivered;
-                status.delivered_at = Some(Utc::now());
+                // Delivery is confirmed later by the provider's status callbacks
+                status.status = DeliveryState::Sent;
             }
             Err(e) => {
                 status.status = DeliveryState::Failed;
@@ -192,8 +192,7 @@ impl Channel for SmsChannel {
             notification.message
         );
 
-        status.status = DeliveryState::Delivered;
-        status.delivered_at = Some(Utc::now());
+        status.status = DeliveryState::Sent;
 
         Ok(status)
     }
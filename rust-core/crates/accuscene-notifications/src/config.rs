@@ -1,6 +1,7 @@
 //! Notification system configuration
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::time::Duration;
 
 /// Main notification system configuration
@@ -246,6 +247,20 @@ pub struct WebSocketConfig {
     pub path: String,
     pub heartbeat_interval_ms: u64,
     pub client_timeout_ms: u64,
+    /// Frames buffered per socket before new frames are dropped
+    #[serde(default = "default_send_buffer")]
+    pub send_buffer: usize,
+    /// How long a delivery waits for a client receipt
+    #[serde(default = "default_ack_timeout_ms")]
+    pub ack_timeout_ms: u64,
+}
+
+fn default_send_buffer() -> usize {
+    256
+}
+
+fn default_ack_timeout_ms() -> u64 {
+    5000
 }
 
 impl Default for WebSocketConfig {
@@ -257,6 +272,8 @@ impl Default for WebSocketConfig {
             path: "/ws/notifications".to_string(),
             heartbeat_interval_ms: 30000,
             client_timeout_ms: 60000,
+            send_buffer: default_send_buffer(),
+            ack_timeout_ms: default_ack_timeout_ms(),
         }
     }
 }
@@ -269,6 +286,22 @@ pub struct DispatcherConfig {
     pub batch_size: usize,
     pub batch_timeout_ms: u64,
     pub priority_levels: u8,
+    /// Retry policy for channels without their own
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    /// Retry policies by channel name
+    #[serde(default)]
+    pub channel_retry: HashMap<String, RetryPolicy>,
+    /// Sharing the queue between notification processes
+    #[serde(default)]
+    pub coordination: CoordinationConfig,
+}
+
+impl DispatcherConfig {
+    /// Retry policy for a channel
+    pub fn retry_policy(&self, channel: &str) -> &RetryPolicy {
+        self.channel_retry.get(channel).unwrap_or(&self.retry)
+    }
 }
 
 impl Default for DispatcherConfig {
@@ -279,10 +312,149 @@ impl Default for DispatcherConfig {
             batch_size: 100,
             batch_timeout_ms: 1000,
             priority_levels: 5,
+            retry: RetryPolicy::default(),
+            channel_retry: HashMap::new(),
+            coordination: CoordinationConfig::default(),
+        }
+    }
+}
+
+/// Where queued notifications live
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoordinationBackend {
+    /// In this process only; run a single notification process
+    #[default]
+    Local,
+    /// In Redis, shared by every process using the same key prefix
+    Redis,
+}
+
+/// Dispatch coordination across notification processes
+///
+/// With a shared backend each notification is claimed by one worker under a
+/// lease. Leases not completed within `lease_ms` (the worker crashed or
+/// stalled) are reclaimed and the notification is dispatched again, so
+/// `lease_ms` should exceed the slowest channel's timeout.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoordinationConfig {
+    pub backend: CoordinationBackend,
+    pub redis_url: Option<String>,
+    /// Prefix of the Redis keys holding the queue
+    pub key_prefix: String,
+    /// Identifies this process in leases; random when unset
+    pub node_id: Option<String>,
+    pub lease_ms: u64,
+}
+
+impl Default for CoordinationConfig {
+    fn default() -> Self {
+        Self {
+            backend: CoordinationBackend::Local,
+            redis_url: None,
+            key_prefix: "accuscene:notifications:dispatch".to_string(),
+            node_id: None,
+            lease_ms: 60_000,
+        }
+    }
+}
+
+impl CoordinationConfig {
+    /// Lease duration
+    pub fn lease(&self) -> Duration {
+        Duration::from_millis(self.lease_ms)
+    }
+}
+
+/// Retry policy for failed deliveries
+///
+/// Delays grow exponentially from `initial_backoff_ms` up to
+/// `max_backoff_ms`, each spread by up to `jitter` (a fraction) in either
+/// direction so failed deliveries do not retry in lockstep.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Total delivery attempts, including the first
+    pub max_attempts: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    pub multiplier: f64,
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_backoff_ms: 1000,
+            max_backoff_ms: 300_000,
+            multiplier: 2.0,
+            jitter: 0.2,
         }
     }
 }
 
+impl RetryPolicy {
+    /// Builder: Set total delivery attempts
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Builder: Set backoff bounds
+    pub fn with_backoff(mut self, initial_ms: u64, max_ms: u64) -> Self {
+        self.initial_backoff_ms = initial_ms;
+        self.max_backoff_ms = max_ms;
+        self
+    }
+
+    /// Check if another attempt is allowed after `attempts` failures
+    pub fn should_retry(&self, attempts: u32) -> bool {
+        attempts < self.max_attempts
+    }
+
+    /// Delay before the attempt following failed attempt number `attempt`
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        self.backoff(attempt, rand::random::<f64>())
+    }
+
+    /// Delay for a uniform `sample` in `[0, 1)`
+    fn backoff(&self, attempt: u32, sample: f64) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(32) as i32;
+        let max = self.max_backoff_ms as f64;
+        let base = (self.initial_backoff_ms as f64 * self.multiplier.max(1.0).powi(exponent)).min(max);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let delay = base * (1.0 - jitter + 2.0 * jitter * sample);
+        Duration::from_millis(delay.min(max).round() as u64)
+    }
+}
+
+/// Token bucket rate: `per_second` sustained, with bursts of up to `burst`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RateLimit {
+    pub per_second: f64,
+    pub burst: u32,
+}
+
+impl RateLimit {
+    /// Create a rate limit
+    pub fn new(per_second: f64, burst: u32) -> Self {
+        Self { per_second, burst }
+    }
+}
+
+/// Handling of deliveries over a rate limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowStrategy {
+    /// Fold overflow for a user and channel into one summary notification
+    #[default]
+    Collapse,
+    /// Discard the delivery and count it
+    Drop,
+    /// Deliver once the limits allow, up to `max_delay_ms` later
+    Delay,
+}
+
 /// Storage configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StorageConfig {
@@ -309,6 +481,23 @@ pub struct TemplateConfig {
     pub templates_dir: String,
     pub cache_enabled: bool,
     pub cache_size: usize,
+    /// Locale templates are written in
+    #[serde(default = "default_locale")]
+    pub default_locale: String,
+    /// Locales templates should be translated into
+    #[serde(default = "default_locales")]
+    pub locales: Vec<String>,
+    /// Extra locales to try, by locale, before the default locale
+    #[serde(default)]
+    pub fallback_locales: HashMap<String, Vec<String>>,
+}
+
+fn default_locale() -> String {
+    "en".to_string()
+}
+
+fn default_locales() -> Vec<String> {
+    ["en", "es", "fr", "de", "ja"].iter().map(|l| l.to_string()).collect()
 }
 
 impl Default for TemplateConfig {
@@ -317,17 +506,46 @@ impl Default for TemplateConfig {
             templates_dir: "./templates".to_string(),
             cache_enabled: true,
             cache_size: 100,
+            default_locale: default_locale(),
+            locales: default_locales(),
+            fallback_locales: HashMap::new(),
         }
     }
 }
 
 /// Rate limiting configuration
+///
+/// Per-user limits apply to each channel separately, so a user receives at
+/// most `max_per_user_per_minute` emails a minute; zero disables a window.
+/// A delivery must also fit the global and per-channel limits that are set.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RateLimitConfig {
     pub enabled: bool,
     pub max_per_user_per_minute: u32,
     pub max_per_user_per_hour: u32,
     pub max_per_user_per_day: u32,
+    /// Limit across all deliveries
+    #[serde(default)]
+    pub global: Option<RateLimit>,
+    /// Limits by channel name
+    #[serde(default)]
+    pub per_channel: HashMap<String, RateLimit>,
+    #[serde(default)]
+    pub overflow: OverflowStrategy,
+    /// Delayed deliveries that would wait longer are dropped
+    #[serde(default = "default_max_delay_ms")]
+    pub max_delay_ms: u64,
+    /// Critical notifications bypass the limits
+    #[serde(default = "default_exempt_critical")]
+    pub exempt_critical: bool,
+}
+
+fn default_max_delay_ms() -> u64 {
+    300_000
+}
+
+fn default_exempt_critical() -> bool {
+    true
 }
 
 impl Default for RateLimitConfig {
@@ -337,10 +555,56 @@ impl Default for RateLimitConfig {
             max_per_user_per_minute: 10,
             max_per_user_per_hour: 100,
             max_per_user_per_day: 1000,
+            global: None,
+            per_channel: HashMap::new(),
+            overflow: OverflowStrategy::default(),
+            max_delay_ms: default_max_delay_ms(),
+            exempt_critical: default_exempt_critical(),
         }
     }
 }
 
+impl RateLimitConfig {
+    /// Builder: Set the limit across all deliveries
+    pub fn with_global(mut self, limit: RateLimit) -> Self {
+        self.global = Some(limit);
+        self
+    }
+
+    /// Builder: Set the limit for a channel
+    pub fn with_channel(mut self, channel: impl Into<String>, limit: RateLimit) -> Self {
+        self.per_channel.insert(channel.into(), limit);
+        self
+    }
+
+    /// Builder: Set the overflow strategy
+    pub fn with_overflow(mut self, overflow: OverflowStrategy) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Per-user limits as token buckets, one per non-zero window
+    pub fn user_limits(&self) -> Vec<RateLimit> {
+        [
+            (self.max_per_user_per_minute, 60.0),
+            (self.max_per_user_per_hour, 3600.0),
+            (self.max_per_user_per_day, 86_400.0),
+        ]
+        .into_iter()
+        .filter(|(max, _)| *max > 0)
+        .map(|(max, window)| RateLimit::new(max as f64 / window, max))
+        .collect()
+    }
+
+    /// Check if any limit is enforced
+    pub fn is_active(&self) -> bool {
+        self.enabled
+            && (self.global.is_some()
+                || !self.per_channel.is_empty()
+                || !self.user_limits().is_empty())
+    }
+}
+
 impl NotificationConfig {
     /// Load configuration from file
     pub fn from_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
@@ -366,6 +630,51 @@ impl NotificationConfig {
             return Err("queue_capacity must be > 0".to_string());
         }
 
+        let retry = &self.dispatcher.retry;
+        for policy in std::iter::once(retry).chain(self.dispatcher.channel_retry.values()) {
+            if policy.max_attempts == 0 {
+                return Err("retry max_attempts must be > 0".to_string());
+            }
+            if policy.initial_backoff_ms > policy.max_backoff_ms {
+                return Err("retry initial_backoff_ms must be <= max_backoff_ms".to_string());
+            }
+        }
+
+        let coordination = &self.dispatcher.coordination;
+        if coordination.lease_ms == 0 {
+            return Err("coordination lease_ms must be > 0".to_string());
+        }
+        if coordination.backend == CoordinationBackend::Redis && coordination.redis_url.is_none() {
+            return Err("coordination backend redis requires redis_url".to_string());
+        }
+
+        let rate_limiting = &self.rate_limiting;
+        for limit in rate_limiting.global.iter().chain(rate_limiting.per_channel.values()) {
+            if !limit.per_second.is_finite() || limit.per_second <= 0.0 || limit.burst == 0 {
+                return Err("rate limit per_second and burst must be > 0".to_string());
+            }
+        }
+
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_backoff() {
+        let policy = RetryPolicy::default().with_backoff(1000, 10_000);
+        assert_eq!(policy.backoff(1, 0.5), Duration::from_millis(1000));
+        assert_eq!(policy.backoff(3, 0.5), Duration::from_millis(4000));
+        assert_eq!(policy.backoff(10, 0.5), Duration::from_millis(10_000));
+
+        // Jitter spreads by 20% either way but never past the cap
+        assert_eq!(policy.backoff(1, 0.0), Duration::from_millis(800));
+        assert_eq!(policy.backoff(10, 0.99), Duration::from_millis(10_000));
+
+        assert!(policy.should_retry(3));
+        assert!(!policy.should_retry(4));
+    }
+}
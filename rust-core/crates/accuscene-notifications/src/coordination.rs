@@ -0,0 +1,424 @@
+//! Dispatch coordination across notification processes
+//!
+//! When several notification processes share one queue, each queued
+//! notification must be dispatched by exactly one worker. Workers claim jobs
+//! under a lease and complete them once delivered; leases that expire because
+//! a worker crashed or stalled are reclaimed and the job is queued again.
+//!
+//! [`InMemoryCoordinator`] shares a queue between dispatchers in one process.
+//! [`RedisCoordinator`] (feature `redis`) shares it between processes.
+
+use crate::error::Result;
+use crate::types::Notification;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Queued notification with its dispatch metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DispatchJob {
+    pub notification: Notification,
+    pub channels: Vec<String>,
+    pub retry_count: u32,
+    /// Jobs with a higher priority are claimed first
+    pub priority: u32,
+}
+
+/// Job held by one worker until its lease expires
+#[derive(Debug, Clone)]
+pub struct Lease {
+    pub job: DispatchJob,
+    pub worker_id: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Queue shared by dispatch workers
+#[async_trait]
+pub trait DispatchCoordinator: Send + Sync {
+    /// Queue a job, replacing any queued job for the same notification
+    async fn submit(&self, job: DispatchJob) -> Result<()>;
+
+    /// Claim up to `max` of the highest-priority jobs for `worker_id`
+    async fn claim(&self, worker_id: &str, max: usize, lease: Duration) -> Result<Vec<Lease>>;
+
+    /// Remove a dispatched job; `false` if `worker_id` no longer holds its lease
+    async fn complete(&self, worker_id: &str, notification_id: Uuid) -> Result<bool>;
+
+    /// Queue jobs whose lease expired again and return how many
+    async fn reclaim_expired(&self) -> Result<usize>;
+
+    /// Number of queued jobs not yet claimed
+    async fn queued(&self) -> Result<usize>;
+
+    /// Number of claimed jobs not yet completed
+    async fn leased(&self) -> Result<usize>;
+}
+
+#[derive(Default)]
+struct InMemoryState {
+    /// Claim order: highest priority, then oldest
+    queue: BTreeSet<(Reverse<u32>, u64, Uuid)>,
+    jobs: HashMap<Uuid, (DispatchJob, u64)>,
+    leases: HashMap<Uuid, (String, DateTime<Utc>)>,
+    next_seq: u64,
+}
+
+impl InMemoryState {
+    fn enqueue(&mut self, id: Uuid) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+
+        if let Some((job, job_seq)) = self.jobs.get_mut(&id) {
+            *job_seq = seq;
+            self.queue.insert((Reverse(job.priority), seq, id));
+        }
+    }
+}
+
+/// Coordinator for dispatchers within one process
+#[derive(Default)]
+pub struct InMemoryCoordinator {
+    state: Mutex<InMemoryState>,
+}
+
+impl InMemoryCoordinator {
+    /// Create an empty coordinator
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl DispatchCoordinator for InMemoryCoordinator {
+    async fn submit(&self, job: DispatchJob) -> Result<()> {
+        let mut state = self.state.lock();
+        let id = job.notification.id;
+
+        if let Some((old, seq)) = state.jobs.remove(&id) {
+            state.queue.remove(&(Reverse(old.priority), seq, id));
+        }
+        state.leases.remove(&id);
+        state.jobs.insert(id, (job, 0));
+        state.enqueue(id);
+
+        Ok(())
+    }
+
+    async fn claim(&self, worker_id: &str, max: usize, lease: Duration) -> Result<Vec<Lease>> {
+        let mut state = self.state.lock();
+        let expires_at = Utc::now() + chrono::Duration::from_std(lease).unwrap_or_default();
+        let mut claimed = Vec::new();
+
+        while claimed.len() < max {
+            let Some(entry) = state.queue.pop_first() else {
+                break;
+            };
+            let id = entry.2;
+            let Some((job, _)) = state.jobs.get(&id) else {
+                continue;
+            };
+
+            claimed.push(Lease {
+                job: job.clone(),
+                worker_id: worker_id.to_string(),
+                expires_at,
+            });
+            state.leases.insert(id, (worker_id.to_string(), expires_at));
+        }
+
+        Ok(claimed)
+    }
+
+    async fn complete(&self, worker_id: &str, notification_id: Uuid) -> Result<bool> {
+        let mut state = self.state.lock();
+
+        match state.leases.get(&notification_id) {
+            Some((holder, _)) if holder == worker_id => {
+                state.leases.remove(&notification_id);
+                state.jobs.remove(&notification_id);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn reclaim_expired(&self) -> Result<usize> {
+        let mut state = self.state.lock();
+        let now = Utc::now();
+
+        let expired: Vec<Uuid> = state
+            .leases
+            .iter()
+            .filter(|(_, (_, expires_at))| *expires_at <= now)
+            .map(|(id, _)| *id)
+            .collect();
+
+        for id in &expired {
+            state.leases.remove(id);
+            state.enqueue(*id);
+        }
+
+        Ok(expired.len())
+    }
+
+    async fn queued(&self) -> Result<usize> {
+        Ok(self.state.lock().queue.len())
+    }
+
+    async fn leased(&self) -> Result<usize> {
+        Ok(self.state.lock().leases.len())
+    }
+}
+
+#[cfg(feature = "redis")]
+pub use self::redis_backend::RedisCoordinator;
+
+#[cfg(feature = "redis")]
+mod redis_backend {
+    use super::{DispatchCoordinator, DispatchJob, Lease};
+    use crate::error::Result;
+    use async_trait::async_trait;
+    use chrono::Utc;
+    use redis::aio::ConnectionManager;
+    use redis::{Script, ScriptInvocation};
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    /// Pop the highest-priority jobs and lease them, timed by the Redis clock
+    const CLAIM_SCRIPT: &str = r#"
+        local time = redis.call('TIME')
+        local expires_at = time[1] * 1000 + math.floor(time[2] / 1000) + tonumber(ARGV[3])
+        local popped = redis.call('ZPOPMIN', KEYS[1], ARGV[1])
+        local claimed = {}
+        for i = 1, #popped, 2 do
+            local job = redis.call('HGET', KEYS[2], popped[i])
+            if job then
+                redis.call('ZADD', KEYS[3], expires_at, popped[i])
+                redis.call('HSET', KEYS[4], popped[i], ARGV[2])
+                table.insert(claimed, job)
+            end
+        end
+        return claimed
+    "#;
+
+    /// Remove a job if the worker still holds its lease
+    const COMPLETE_SCRIPT: &str = r#"
+        if redis.call('HGET', KEYS[4], ARGV[1]) ~= ARGV[2] then
+            return 0
+        end
+        redis.call('ZREM', KEYS[3], ARGV[1])
+        redis.call('HDEL', KEYS[4], ARGV[1])
+        redis.call('HDEL', KEYS[2], ARGV[1])
+        return 1
+    "#;
+
+    /// Queue jobs with expired leases again at their original priority
+    const RECLAIM_SCRIPT: &str = r#"
+        local time = redis.call('TIME')
+        local now = time[1] * 1000 + math.floor(time[2] / 1000)
+        local expired = redis.call('ZRANGEBYSCORE', KEYS[3], '-inf', now)
+        for _, id in ipairs(expired) do
+            redis.call('ZREM', KEYS[3], id)
+            redis.call('HDEL', KEYS[4], id)
+            local job = redis.call('HGET', KEYS[2], id)
+            if job then
+                redis.call('ZADD', KEYS[1], -cjson.decode(job).priority, id)
+            end
+        end
+        return #expired
+    "#;
+
+    /// Coordinator sharing the queue between processes through Redis
+    ///
+    /// Keys under the prefix: `queue` (sorted set of ids by negated
+    /// priority), `jobs` (hash of serialized jobs), `leases` (sorted set of
+    /// ids by lease expiry in milliseconds) and `owners` (hash of lease
+    /// holders). Claims, completions and reclaims run as Lua scripts, so each
+    /// is atomic across processes.
+    pub struct RedisCoordinator {
+        connection: ConnectionManager,
+        claim_script: Script,
+        complete_script: Script,
+        reclaim_script: Script,
+        queue_key: String,
+        jobs_key: String,
+        leases_key: String,
+        owners_key: String,
+    }
+
+    impl RedisCoordinator {
+        /// Connect to Redis and use the keys under `key_prefix`
+        pub async fn connect(url: &str, key_prefix: &str) -> Result<Self> {
+            let client = redis::Client::open(url)?;
+            let connection = ConnectionManager::new(client).await?;
+
+            Ok(Self {
+                connection,
+                claim_script: Script::new(CLAIM_SCRIPT),
+                complete_script: Script::new(COMPLETE_SCRIPT),
+                reclaim_script: Script::new(RECLAIM_SCRIPT),
+                queue_key: format!("{}:queue", key_prefix),
+                jobs_key: format!("{}:jobs", key_prefix),
+                leases_key: format!("{}:leases", key_prefix),
+                owners_key: format!("{}:owners", key_prefix),
+            })
+        }
+
+        /// Invoke a script with the coordinator's keys
+        fn invoke<'a>(&self, script: &'a Script) -> ScriptInvocation<'a> {
+            let mut invocation = script.prepare_invoke();
+            invocation
+                .key(&self.queue_key)
+                .key(&self.jobs_key)
+                .key(&self.leases_key)
+                .key(&self.owners_key);
+            invocation
+        }
+    }
+
+    #[async_trait]
+    impl DispatchCoordinator for RedisCoordinator {
+        async fn submit(&self, job: DispatchJob) -> Result<()> {
+            let id = job.notification.id.to_string();
+            let payload = serde_json::to_string(&job)?;
+
+            redis::pipe()
+                .atomic()
+                .hset(&self.jobs_key, &id, payload)
+                .zrem(&self.leases_key, &id)
+                .hdel(&self.owners_key, &id)
+                .zadd(&self.queue_key, &id, -(job.priority as f64))
+                .query_async::<_, ()>(&mut self.connection.clone())
+                .await?;
+
+            Ok(())
+        }
+
+        async fn claim(&self, worker_id: &str, max: usize, lease: Duration) -> Result<Vec<Lease>> {
+            let expires_at = Utc::now() + chrono::Duration::from_std(lease).unwrap_or_default();
+            let payloads: Vec<String> = self
+                .invoke(&self.claim_script)
+                .arg(max)
+                .arg(worker_id)
+                .arg(lease.as_millis() as u64)
+                .invoke_async(&mut self.connection.clone())
+                .await?;
+
+            payloads
+                .iter()
+                .map(|payload| {
+                    Ok(Lease {
+                        job: serde_json::from_str(payload)?,
+                        worker_id: worker_id.to_string(),
+                        expires_at,
+                    })
+                })
+                .collect()
+        }
+
+        async fn complete(&self, worker_id: &str, notification_id: Uuid) -> Result<bool> {
+            let removed: i64 = self
+                .invoke(&self.complete_script)
+                .arg(notification_id.to_string())
+                .arg(worker_id)
+                .invoke_async(&mut self.connection.clone())
+                .await?;
+
+            Ok(removed == 1)
+        }
+
+        async fn reclaim_expired(&self) -> Result<usize> {
+            let reclaimed: usize = self
+                .invoke(&self.reclaim_script)
+                .invoke_async(&mut self.connection.clone())
+                .await?;
+
+            Ok(reclaimed)
+        }
+
+        async fn queued(&self) -> Result<usize> {
+            let queued: usize = redis::cmd("ZCARD")
+                .arg(&self.queue_key)
+                .query_async(&mut self.connection.clone())
+                .await?;
+            Ok(queued)
+        }
+
+        async fn leased(&self) -> Result<usize> {
+            let leased: usize = redis::cmd("ZCARD")
+                .arg(&self.leases_key)
+                .query_async(&mut self.connection.clone())
+                .await?;
+            Ok(leased)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::NotificationLevel;
+
+    fn job(priority: u32) -> DispatchJob {
+        DispatchJob {
+            notification: Notification::new("user1", NotificationLevel::Info, "Title", "Message"),
+            channels: vec!["in_app".to_string()],
+            retry_count: 0,
+            priority,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_claim_is_exclusive_and_ordered() {
+        let coordinator = InMemoryCoordinator::new();
+        let low = job(2000);
+        let high = job(8000);
+        coordinator.submit(low.clone()).await.unwrap();
+        coordinator.submit(high.clone()).await.unwrap();
+
+        let lease = Duration::from_secs(60);
+        let first = coordinator.claim("node-a/worker-0", 1, lease).await.unwrap();
+        let second = coordinator.claim("node-b/worker-0", 10, lease).await.unwrap();
+        let third = coordinator.claim("node-c/worker-0", 10, lease).await.unwrap();
+
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].job.notification.id, high.notification.id);
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].job.notification.id, low.notification.id);
+        assert!(third.is_empty());
+
+        assert!(!coordinator.complete("node-b/worker-0", high.notification.id).await.unwrap());
+        assert!(coordinator.complete("node-a/worker-0", high.notification.id).await.unwrap());
+        assert_eq!(coordinator.leased().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_lease_is_reclaimed() {
+        let coordinator = InMemoryCoordinator::new();
+        let job = job(4000);
+        coordinator.submit(job.clone()).await.unwrap();
+
+        let claimed = coordinator.claim("node-a/worker-0", 10, Duration::ZERO).await.unwrap();
+        assert_eq!(claimed.len(), 1);
+        assert_eq!(coordinator.queued().await.unwrap(), 0);
+
+        // node-a crashed; its lease lapses and another node picks the job up
+        assert_eq!(coordinator.reclaim_expired().await.unwrap(), 1);
+        let reclaimed = coordinator
+            .claim("node-b/worker-0", 10, Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert_eq!(reclaimed.len(), 1);
+        assert_eq!(reclaimed[0].job.notification.id, job.notification.id);
+
+        assert!(!coordinator.complete("node-a/worker-0", job.notification.id).await.unwrap());
+        assert!(coordinator.complete("node-b/worker-0", job.notification.id).await.unwrap());
+        assert_eq!(coordinator.queued().await.unwrap(), 0);
+        assert_eq!(coordinator.leased().await.unwrap(), 0);
+    }
+}
@@ -1,15 +1,25 @@
 //! Multi-channel notification dispatcher with priority queue
 
 use crate::channel::{Channel, ChannelRegistry};
-use crate::config::DispatcherConfig;
+use crate::config::{DispatcherConfig, RateLimitConfig};
+use crate::coordination::{DispatchCoordinator, DispatchJob};
 use crate::error::{NotificationError, Result};
 use crate::preferences::PreferenceManager;
-use crate::types::{DeliveryState, DeliveryStatus, Notification, Priority};
+use crate::store::NotificationStore;
+use crate::throttle::{Admission, Throttle, ThrottleStats};
+use crate::topics;
+use crate::tracking::{DeliveryTracker, DISPATCHER_SOURCE};
+use crate::types::{
+    DeadLetter, DeliveryEvent, DeliveryState, DeliveryStatus, Notification, Priority,
+};
+use chrono::{DateTime, Utc};
 use priority_queue::PriorityQueue;
 use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::{mpsc, RwLock};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
 /// Notification with dispatch metadata
@@ -20,7 +30,27 @@ struct DispatchItem {
     retry_count: u32,
 }
 
+impl From<DispatchJob> for DispatchItem {
+    fn from(job: DispatchJob) -> Self {
+        Self {
+            notification: job.notification,
+            channels: job.channels,
+            retry_count: job.retry_count,
+        }
+    }
+}
+
 impl DispatchItem {
+    fn into_job(self) -> DispatchJob {
+        let priority = self.priority_score();
+        DispatchJob {
+            notification: self.notification,
+            channels: self.channels,
+            retry_count: self.retry_count,
+            priority,
+        }
+    }
+
     fn priority_score(&self) -> u32 {
         // Higher score = higher priority
         let base_score = match self.notification.priority {
@@ -38,6 +68,96 @@ impl DispatchItem {
     }
 }
 
+/// Failed channel delivery waiting for its next attempt
+struct RetryItem {
+    notification: Notification,
+    channel: String,
+    attempts: u32,
+    due_at: DateTime<Utc>,
+}
+
+/// Scheduled retries, with exhausted deliveries sent to the dead-letter store
+struct RetryQueue {
+    config: DispatcherConfig,
+    items: RwLock<Vec<RetryItem>>,
+    dead_letters: Option<Arc<NotificationStore>>,
+}
+
+impl RetryQueue {
+    fn new(config: DispatcherConfig, dead_letters: Option<Arc<NotificationStore>>) -> Self {
+        Self {
+            config,
+            items: RwLock::new(Vec::new()),
+            dead_letters,
+        }
+    }
+
+    /// Schedule another attempt under the channel's policy or dead-letter the delivery
+    #[allow(clippy::too_many_arguments)]
+    async fn record_failure(
+        &self,
+        notification: &Notification,
+        channel: &str,
+        attempts: u32,
+        error: Option<String>,
+        retryable: bool,
+    ) {
+        let policy = self.config.retry_policy(channel);
+        if retryable && policy.should_retry(attempts) {
+            let delay = policy.delay_for(attempts);
+            tracing::debug!(
+                "Retrying notification {} via {} in {:?} (attempt {})",
+                notification.id,
+                channel,
+                delay,
+                attempts + 1
+            );
+            let delay =
+                chrono::Duration::from_std(delay).unwrap_or_else(|_| chrono::Duration::zero());
+            self.items.write().await.push(RetryItem {
+                notification: notification.clone(),
+                channel: channel.to_string(),
+                attempts,
+                due_at: Utc::now() + delay,
+            });
+            return;
+        }
+
+        tracing::warn!(
+            "Notification {} via {} failed after {} attempt(s)",
+            notification.id,
+            channel,
+            attempts
+        );
+        if let Some(store) = &self.dead_letters {
+            let letter = DeadLetter::new(notification.clone(), channel, attempts, error);
+            if let Err(e) = store.save_dead_letter(&letter).await {
+                tracing::error!(
+                    "Failed to dead-letter notification {} via {}: {}",
+                    notification.id,
+                    channel,
+                    e
+                );
+            }
+        }
+    }
+
+    /// Remove and return the retries that are due
+    async fn take_due(&self) -> Vec<RetryItem> {
+        let now = Utc::now();
+        let mut items = self.items.write().await;
+        let (due, waiting) = std::mem::take(&mut *items)
+            .into_iter()
+            .partition(|item| item.due_at <= now);
+        *items = waiting;
+        due
+    }
+
+    async fn len(&self) -> usize {
+        self.items.read().await.len()
+    }
+}
+
 /// Multi-channel notification dispatcher
 pub struct NotificationDispatcher {
     config: DispatcherConfig,
@@ -46,7 +166,14 @@ pub struct NotificationDispatcher {
     queue: Arc<RwLock<PriorityQueue<Uuid, Reverse<u32>>>>,
     pending_items: Arc<RwLock<HashMap<Uuid, DispatchItem>>>,
     delivery_statuses: Arc<RwLock<HashMap<Uuid, Vec<DeliveryStatus>>>>,
-    shutdown_tx: Option<mpsc::Sender<()>>,
+    retries: Arc<RetryQueue>,
+    throttle: Arc<Throttle>,
+    tracker: Option<Arc<DeliveryTracker>>,
+    /// Shared queue replacing the local one when several processes dispatch
+    coordinator: Option<Arc<dyn DispatchCoordinator>>,
+    node_id: String,
+    shutdown: CancellationToken,
+    tasks: Vec<(String, JoinHandle<()>)>,
 }
 
 impl NotificationDispatcher {
@@ -57,26 +184,82 @@ impl NotificationDispatcher {
         preference_manager: Arc<PreferenceManager>,
     ) -> Self {
         Self {
+            retries: Arc::new(RetryQueue::new(config.clone(), None)),
+            throttle: Arc::new(Throttle::new(RateLimitConfig {
+                enabled: false,
+                ..Default::default()
+            })),
             config,
             channel_registry,
             preference_manager,
             queue: Arc::new(RwLock::new(PriorityQueue::new())),
             pending_items: Arc::new(RwLock::new(HashMap::new())),
             delivery_statuses: Arc::new(RwLock::new(HashMap::new())),
-            shutdown_tx: None,
+            tracker: None,
+            coordinator: None,
+            node_id: Uuid::new_v4().simple().to_string(),
+            shutdown: CancellationToken::new(),
+            tasks: Vec::new(),
+        }
+    }
+
+    /// Persist deliveries that exhaust their retries as dead letters
+    pub fn with_dead_letter_store(mut self, store: Arc<NotificationStore>) -> Self {
+        self.retries = Arc::new(RetryQueue::new(self.config.clone(), Some(store)));
+        self
+    }
+
+    /// Enforce rate limits on channel deliveries
+    pub fn with_rate_limits(mut self, config: RateLimitConfig) -> Self {
+        self.throttle = Arc::new(Throttle::new(config));
+        self
+    }
+
+    /// Persist delivery status transitions through a tracker
+    pub fn with_delivery_tracker(mut self, tracker: Arc<DeliveryTracker>) -> Self {
+        self.tracker = Some(tracker);
+        self
+    }
+
+    /// Queue notifications through a coordinator shared with other dispatchers
+    ///
+    /// Workers are identified in leases as `{node_id}/worker-{n}`.
+    pub fn with_coordinator(
+        mut self,
+        coordinator: Arc<dyn DispatchCoordinator>,
+        node_id: Option<String>,
+    ) -> Self {
+        self.coordinator = Some(coordinator);
+        if let Some(node_id) = node_id {
+            self.node_id = node_id;
         }
+        self
+    }
+
+    /// Use a cancellation token from an enclosing shutdown scope
+    ///
+    /// Must be called before [`start`](Self::start).
+    pub fn set_shutdown_token(&mut self, token: CancellationToken) {
+        self.shutdown = token;
+    }
+
+    /// Hand over the spawned task handles so a shutdown scope can await them
+    pub fn take_task_handles(&mut self) -> Vec<(String, JoinHandle<()>)> {
+        std::mem::take(&mut self.tasks)
     }
 
     /// Start the dispatcher
     pub async fn start(&mut self) -> Result<()> {
-        let (shutdown_tx, mut shutdown_rx) = mpsc::channel::<()>(1);
-        self.shutdown_tx = Some(shutdown_tx);
-
         let worker_count = self.config.worker_count;
         let queue = Arc::clone(&self.queue);
         let pending_items = Arc::clone(&self.pending_items);
         let delivery_statuses = Arc::clone(&self.delivery_statuses);
         let channel_registry = Arc::clone(&self.channel_registry);
+        let retries = Arc::clone(&self.retries);
+        let throttle = Arc::clone(&self.throttle);
+        let tracker = self.tracker.clone();
+        let coordinator = self.coordinator.clone();
+        let lease = self.config.coordination.lease();
         let batch_size = self.config.batch_size;
         let batch_timeout = std::time::Duration::from_millis(self.config.batch_timeout_ms);
 
@@ -86,12 +269,41 @@ impl NotificationDispatcher {
             let pending_items = Arc::clone(&pending_items);
             let delivery_statuses = Arc::clone(&delivery_statuses);
             let channel_registry = Arc::clone(&channel_registry);
+            let retries = Arc::clone(&retries);
+            let throttle = Arc::clone(&throttle);
+            let tracker = tracker.clone();
+            let coordinator = coordinator.clone();
+            let worker_name = format!("{}/worker-{}", self.node_id, worker_id);
+            let shutdown = self.shutdown.clone();
 
-            tokio::spawn(async move {
+            let handle = tokio::spawn(async move {
                 tracing::info!("Dispatcher worker {} started", worker_id);
 
                 loop {
-                    tokio::time::sleep(batch_timeout).await;
+                    // Finish the batch in flight before stopping
+                    tokio::select! {
+                        _ = tokio::time::sleep(batch_timeout) => {}
+                        _ = shutdown.cancelled() => {
+                            tracing::info!("Dispatcher worker {} shutting down", worker_id);
+                            break;
+                        }
+                    }
+
+                    if let Some(coordinator) = &coordinator {
+                        Self::dispatch_claimed(
+                            coordinator.as_ref(),
+                            &worker_name,
+                            batch_size,
+                            lease,
+                            &channel_registry,
+                            &delivery_statuses,
+                            &retries,
+                            &throttle,
+                            tracker.as_deref(),
+                        )
+                        .await;
+                        continue;
+                    }
 
                     // Get batch of items from queue
                     let mut batch = Vec::new();
@@ -122,6 +334,9 @@ impl NotificationDispatcher {
                                 item,
                                 &channel_registry,
                                 &delivery_statuses,
+                                &retries,
+                                &throttle,
+                                tracker.as_deref(),
                             )
                             .await;
 
@@ -131,22 +346,132 @@ impl NotificationDispatcher {
                     }
                 }
             });
+            self.tasks.push((format!("worker-{}", worker_id), handle));
         }
 
-        // Wait for shutdown signal
-        tokio::spawn(async move {
-            shutdown_rx.recv().await;
-            tracing::info!("Dispatcher shutting down");
+        // Spawn task for retries, rate-limited deliveries and expired leases
+        let shutdown = self.shutdown.clone();
+        let handle = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = tokio::time::sleep(batch_timeout) => {}
+                    _ = shutdown.cancelled() => break,
+                }
+
+                if let Some(coordinator) = &coordinator {
+                    match coordinator.reclaim_expired().await {
+                        Ok(0) => {}
+                        Ok(n) => tracing::warn!("Requeued {} notifications with expired leases", n),
+                        Err(e) => tracing::error!("Failed to reclaim expired leases: {}", e),
+                    }
+                }
+
+                let retry_items = retries
+                    .take_due()
+                    .await
+                    .into_iter()
+                    .map(|item| (item.notification, item.channel, item.attempts));
+                let throttled_items = throttle
+                    .take_due()
+                    .into_iter()
+                    .map(|(notification, channel)| (notification, channel, 0));
+
+                let due = retry_items.chain(throttled_items);
+                for (notification, channel_name, previous_attempts) in due {
+                    let attempts = previous_attempts + 1;
+                    let Some(channel) = channel_registry.get_channel(&channel_name) else {
+                        retries
+                            .record_failure(
+                                &notification,
+                                &channel_name,
+                                previous_attempts,
+                                Some(format!("Channel {} is not registered", channel_name)),
+                                false,
+                            )
+                            .await;
+                        continue;
+                    };
+
+                    let status =
+                        Self::attempt_delivery(channel.as_ref(), &notification, attempts, &retries)
+                            .await;
+                    Self::track(tracker.as_deref(), &status).await;
+
+                    let mut statuses = delivery_statuses.write().await;
+                    let entry = statuses.entry(notification.id).or_default();
+                    match entry.iter_mut().find(|s| s.channel == status.channel) {
+                        Some(existing) => *existing = status,
+                        None => entry.push(status),
+                    }
+                }
+
+                throttle.prune();
+            }
         });
+        self.tasks.push(("retry".to_string(), handle));
 
         Ok(())
     }
 
+    /// Claim a batch from the coordinator, dispatch it and release the leases
+    #[allow(clippy::too_many_arguments)]
+    async fn dispatch_claimed(
+        coordinator: &dyn DispatchCoordinator,
+        worker_name: &str,
+        batch_size: usize,
+        lease: std::time::Duration,
+        channel_registry: &Arc<ChannelRegistry>,
+        delivery_statuses: &Arc<RwLock<HashMap<Uuid, Vec<DeliveryStatus>>>>,
+        retries: &RetryQueue,
+        throttle: &Throttle,
+        tracker: Option<&DeliveryTracker>,
+    ) {
+        let leases = match coordinator.claim(worker_name, batch_size, lease).await {
+            Ok(leases) => leases,
+            Err(e) => {
+                tracing::error!("Worker {} failed to claim notifications: {}", worker_name, e);
+                return;
+            }
+        };
+
+        for lease in leases {
+            let notification_id = lease.job.notification.id;
+            Self::dispatch_notification(
+                lease.job.into(),
+                channel_registry,
+                delivery_statuses,
+                retries,
+                throttle,
+                tracker,
+            )
+            .await;
+
+            match coordinator.complete(worker_name, notification_id).await {
+                Ok(true) => {}
+                Ok(false) => tracing::warn!(
+                    "Lease on {} expired before worker {} finished; it may be delivered twice",
+                    notification_id,
+                    worker_name
+                ),
+                Err(e) => tracing::error!(
+                    "Worker {} failed to complete notification {}: {}",
+                    worker_name,
+                    notification_id,
+                    e
+                ),
+            }
+        }
+    }
+
     /// Dispatch a notification
+    #[allow(clippy::too_many_arguments)]
     async fn dispatch_notification(
         item: DispatchItem,
         channel_registry: &Arc<ChannelRegistry>,
         delivery_statuses: &Arc<RwLock<HashMap<Uuid, Vec<DeliveryStatus>>>>,
+        retries: &RetryQueue,
+        throttle: &Throttle,
+        tracker: Option<&DeliveryTracker>,
     ) {
         let notification = &item.notification;
         let mut statuses = Vec::new();
@@ -162,35 +487,34 @@ impl NotificationDispatcher {
         };
 
         // Deliver to each channel
+        let attempts = item.retry_count + 1;
         for channel in channels_to_use {
-            match channel.deliver(notification).await {
-                Ok(status) => {
-                    tracing::info!(
-                        "Notification {} delivered via {}: {:?}",
-                        notification.id,
-                        channel.name(),
-                        status.status
-                    );
-                    statuses.push(status);
+            let status = match throttle.admit(notification, channel.name()) {
+                Admission::Deliver => {
+                    Self::attempt_delivery(channel.as_ref(), notification, attempts, retries).await
                 }
-                Err(e) => {
-                    tracing::error!(
-                        "Failed to deliver notification {} via {}: {}",
+                // Delivered later by the retry task
+                Admission::Delayed | Admission::Collapsed => continue,
+                Admission::Dropped => {
+                    tracing::warn!(
+                        "Dropped notification {} via {} for {}: rate limit exceeded",
                         notification.id,
                         channel.name(),
-                        e
+                        notification.user_id
                     );
-                    statuses.push(DeliveryStatus {
+                    DeliveryStatus {
                         notification_id: notification.id,
                         channel: channel.name().to_string(),
-                        status: DeliveryState::Failed,
-                        attempts: item.retry_count + 1,
-                        last_attempt_at: Some(chrono::Utc::now()),
+                        status: DeliveryState::Cancelled,
+                        attempts: 0,
+                        last_attempt_at: None,
                         delivered_at: None,
-                        error_message: Some(e.to_string()),
-                    });
+                        error_message: Some(NotificationError::RateLimitExceeded.to_string()),
+                    }
                 }
-            }
+            };
+            Self::track(tracker, &status).await;
+            statuses.push(status);
         }
 
         // Store delivery statuses
@@ -200,6 +524,78 @@ impl NotificationDispatcher {
             .insert(notification.id, statuses);
     }
 
+    /// Persist a delivery attempt's outcome; tracking failures don't affect delivery
+    async fn track(tracker: Option<&DeliveryTracker>, status: &DeliveryStatus) {
+        if let Some(tracker) = tracker {
+            if let Err(e) = tracker.record_status(status).await {
+                tracing::warn!(
+                    "Failed to track delivery of {} via {}: {}",
+                    status.notification_id,
+                    status.channel,
+                    e
+                );
+            }
+        }
+    }
+
+    /// Deliver through one channel, scheduling a retry if it fails
+    async fn attempt_delivery(
+        channel: &dyn Channel,
+        notification: &Notification,
+        attempts: u32,
+        retries: &RetryQueue,
+    ) -> DeliveryStatus {
+        match channel.deliver(notification).await {
+            Ok(mut status) => {
+                tracing::info!(
+                    "Notification {} delivered via {}: {:?}",
+                    notification.id,
+                    channel.name(),
+                    status.status
+                );
+                status.attempts = attempts;
+                if status.status == DeliveryState::Failed {
+                    retries
+                        .record_failure(
+                            notification,
+                            channel.name(),
+                            attempts,
+                            status.error_message.clone(),
+                            true,
+                        )
+                        .await;
+                }
+                status
+            }
+            Err(e) => {
+                tracing::error!(
+                    "Failed to deliver notification {} via {}: {}",
+                    notification.id,
+                    channel.name(),
+                    e
+                );
+                retries
+                    .record_failure(
+                        notification,
+                        channel.name(),
+                        attempts,
+                        Some(e.to_string()),
+                        e.is_retryable(),
+                    )
+                    .await;
+                DeliveryStatus {
+                    notification_id: notification.id,
+                    channel: channel.name().to_string(),
+                    status: DeliveryState::Failed,
+                    attempts,
+                    last_attempt_at: Some(Utc::now()),
+                    delivered_at: None,
+                    error_message: Some(e.to_string()),
+                }
+            }
+        }
+    }
+
     /// Enqueue a notification for dispatch
     pub async fn enqueue(
         &self,
@@ -207,11 +603,8 @@ impl NotificationDispatcher {
         channels: Vec<String>,
     ) -> Result<Uuid> {
         // Check queue capacity
-        {
-            let queue = self.queue.read().await;
-            if queue.len() >= self.config.queue_capacity {
-                return Err(NotificationError::QueueFull);
-            }
+        if self.queue_size().await >= self.config.queue_capacity {
+            return Err(NotificationError::QueueFull);
         }
 
         // Apply user preferences
@@ -224,6 +617,20 @@ impl NotificationDispatcher {
             channels
         };
 
+        if let Some(tracker) = &self.tracker {
+            for channel in &channels {
+                let event = DeliveryEvent::new(
+                    notification.id,
+                    channel.clone(),
+                    DeliveryState::Pending,
+                    DISPATCHER_SOURCE,
+                );
+                if let Err(e) = tracker.record(event).await {
+                    tracing::warn!("Failed to track queued notification {}: {}", notification.id, e);
+                }
+            }
+        }
+
         let item = DispatchItem {
             notification: notification.clone(),
             channels,
@@ -233,6 +640,17 @@ impl NotificationDispatcher {
         let notification_id = notification.id;
         let priority = Reverse(item.priority_score());
 
+        if let Some(coordinator) = &self.coordinator {
+            coordinator.submit(item.into_job()).await?;
+
+            tracing::debug!(
+                "Submitted notification {} with priority {:?}",
+                notification_id,
+                notification.priority
+            );
+            return Ok(notification_id);
+        }
+
         // Add to pending items
         self.pending_items
             .write()
@@ -271,6 +689,59 @@ impl NotificationDispatcher {
         Ok(ids)
     }
 
+    /// Expand a notification published to a topic into one per subscriber
+    ///
+    /// Each subscriber's channel override wins over `channels`; when both are
+    /// empty the subscriber's enabled channels are used at enqueue time. The
+    /// sender is not notified about their own publication.
+    pub async fn topic_recipients(
+        &self,
+        topic: &str,
+        notification: &Notification,
+        channels: &[String],
+    ) -> Result<Vec<(Notification, Vec<String>)>> {
+        topics::validate_topic(topic, false)?;
+
+        let subscribers = self.preference_manager.get_topic_subscribers(topic).await?;
+        let sender_id = notification.sender.as_ref().map(|s| s.id.as_str());
+
+        Ok(subscribers
+            .iter()
+            .filter(|subscription| sender_id != Some(subscription.user_id.as_str()))
+            .map(|subscription| {
+                (
+                    subscription.notification_for(topic, notification),
+                    subscription.resolve_channels(channels),
+                )
+            })
+            .collect())
+    }
+
+    /// Publish a notification to every subscriber of a topic
+    pub async fn publish(
+        &self,
+        topic: &str,
+        notification: Notification,
+        channels: Vec<String>,
+    ) -> Result<Vec<Uuid>> {
+        let mut ids = Vec::new();
+
+        for (notification, channels) in self
+            .topic_recipients(topic, &notification, &channels)
+            .await?
+        {
+            match self.enqueue(notification, channels).await {
+                Ok(id) => ids.push(id),
+                Err(NotificationError::QueueFull) => return Err(NotificationError::QueueFull),
+                Err(e) => tracing::error!("Failed to enqueue topic notification: {}", e),
+            }
+        }
+
+        tracing::debug!("Published to topic {} for {} subscribers", topic, ids.len());
+
+        Ok(ids)
+    }
+
     /// Get delivery status for a notification
     pub async fn get_delivery_status(&self, notification_id: Uuid) -> Option<Vec<DeliveryStatus>> {
         self.delivery_statuses
@@ -282,12 +753,32 @@ impl NotificationDispatcher {
 
     /// Get queue size
     pub async fn queue_size(&self) -> usize {
-        self.queue.read().await.len()
+        match &self.coordinator {
+            Some(coordinator) => coordinator.queued().await.unwrap_or_else(|e| {
+                tracing::warn!("Failed to read shared queue size: {}", e);
+                0
+            }),
+            None => self.queue.read().await.len(),
+        }
     }
 
     /// Get pending items count
+    ///
+    /// With a coordinator, counts notifications claimed by any worker but not
+    /// yet completed.
     pub async fn pending_count(&self) -> usize {
-        self.pending_items.read().await.len()
+        match &self.coordinator {
+            Some(coordinator) => coordinator.leased().await.unwrap_or_else(|e| {
+                tracing::warn!("Failed to read shared lease count: {}", e);
+                0
+            }),
+            None => self.pending_items.read().await.len(),
+        }
+    }
+
+    /// Get number of deliveries waiting for a retry
+    pub async fn retry_count(&self) -> usize {
+        self.retries.len().await
     }
 
     /// Retry failed deliveries
@@ -336,21 +827,21 @@ impl NotificationDispatcher {
 
     /// Shutdown the dispatcher
     pub async fn shutdown(&self) {
-        if let Some(tx) = &self.shutdown_tx {
-            let _ = tx.send(()).await;
-        }
+        self.shutdown.cancel();
     }
 
     /// Get statistics
     pub async fn stats(&self) -> DispatcherStats {
         let queue_size = self.queue_size().await;
         let pending_count = self.pending_count().await;
+        let retry_count = self.retry_count().await;
+        let throttle = self.throttle.stats();
 
         let delivery_statuses = self.delivery_statuses.read().await;
         let total_delivered = delivery_statuses
             .values()
             .flatten()
-            .filter(|s| s.status == DeliveryState::Delivered)
+            .filter(|s| s.status.is_success())
             .count();
         let total_failed = delivery_statuses
             .values()
@@ -361,8 +852,10 @@ impl NotificationDispatcher {
         DispatcherStats {
             queue_size,
             pending_count,
+            retry_count,
             total_delivered,
             total_failed,
+            throttle,
         }
     }
 }
@@ -372,6 +865,214 @@ impl NotificationDispatcher {
 pub struct DispatcherStats {
     pub queue_size: usize,
     pub pending_count: usize,
+    pub retry_count: usize,
     pub total_delivered: usize,
     pub total_failed: usize,
+    /// Rate limiting activity
+    pub throttle: ThrottleStats,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RetryPolicy;
+    use crate::types::NotificationLevel;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+
+    /// Channel whose every delivery fails with the given error
+    struct FailingChannel {
+        retryable: bool,
+        calls: AtomicU32,
+    }
+
+    impl FailingChannel {
+        fn new(retryable: bool) -> Arc<Self> {
+            Arc::new(Self {
+                retryable,
+                calls: AtomicU32::new(0),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl Channel for FailingChannel {
+        fn name(&self) -> &str {
+            "failing"
+        }
+
+        async fn deliver(&self, _notification: &Notification) -> Result<DeliveryStatus> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.retryable {
+                Err(NotificationError::EmailDelivery("mailbox unavailable".to_string()))
+            } else {
+                Err(NotificationError::InvalidNotification("no address".to_string()))
+            }
+        }
+
+        fn supports(&self, _notification: &Notification) -> bool {
+            true
+        }
+
+        fn is_enabled(&self) -> bool {
+            true
+        }
+    }
+
+    fn config(max_attempts: u32, backoff_ms: u64) -> DispatcherConfig {
+        let policy = RetryPolicy {
+            jitter: 0.0,
+            ..RetryPolicy::default()
+        };
+        let mut config = DispatcherConfig {
+            worker_count: 1,
+            batch_timeout_ms: 5,
+            ..DispatcherConfig::default()
+        };
+        config.channel_retry.insert(
+            "failing".to_string(),
+            policy
+                .with_max_attempts(max_attempts)
+                .with_backoff(backoff_ms, backoff_ms),
+        );
+        config
+    }
+
+    /// Pool that is never reachable; connecting fails fast
+    fn unreachable_pool() -> sqlx::PgPool {
+        sqlx::postgres::PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(100))
+            .connect_lazy("postgres://localhost:1/unused")
+            .unwrap()
+    }
+
+    fn notification() -> Notification {
+        Notification::new("user1", NotificationLevel::Warning, "Test", "Test message")
+    }
+
+    async fn start(
+        config: DispatcherConfig,
+        channel: Arc<FailingChannel>,
+    ) -> NotificationDispatcher {
+        let mut registry = ChannelRegistry::new();
+        registry.register(channel);
+        let mut dispatcher = NotificationDispatcher::new(
+            config,
+            Arc::new(registry),
+            Arc::new(PreferenceManager::new(unreachable_pool())),
+        );
+        dispatcher.start().await.unwrap();
+        dispatcher
+    }
+
+    async fn settle(dispatcher: &NotificationDispatcher, channel: &FailingChannel, calls: u32) {
+        for _ in 0..200 {
+            let called = channel.calls.load(Ordering::SeqCst) >= calls;
+            if called && dispatcher.retry_count().await == 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        // Give a stray retry the chance to show up
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    #[tokio::test]
+    async fn test_retry_queue_schedules_with_backoff() {
+        let retries = RetryQueue::new(config(3, 50), None);
+        let notification = notification();
+
+        retries
+            .record_failure(&notification, "failing", 1, Some("timeout".to_string()), true)
+            .await;
+        assert_eq!(retries.len().await, 1);
+        assert!(retries.take_due().await.is_empty());
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        let due = retries.take_due().await;
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].attempts, 1);
+        assert_eq!(due[0].channel, "failing");
+        assert_eq!(retries.len().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_retry_queue_stops_when_exhausted() {
+        let retries = RetryQueue::new(config(3, 0), None);
+        let notification = notification();
+
+        // Out of attempts, or an error no retry can fix
+        retries.record_failure(&notification, "failing", 3, None, true).await;
+        retries.record_failure(&notification, "failing", 1, None, false).await;
+        assert_eq!(retries.len().await, 0);
+
+        // Channels without their own policy fall back to the default one
+        let default_attempts = DispatcherConfig::default().retry.max_attempts;
+        retries
+            .record_failure(&notification, "email", default_attempts - 1, None, true)
+            .await;
+        retries
+            .record_failure(&notification, "email", default_attempts, None, true)
+            .await;
+        assert_eq!(retries.len().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_dead_letter_store_failure_is_not_retried() {
+        let store = Arc::new(NotificationStore::new(unreachable_pool()));
+        let retries = RetryQueue::new(config(2, 0), Some(store));
+
+        retries
+            .record_failure(&notification(), "failing", 2, Some("bounced".to_string()), true)
+            .await;
+        assert_eq!(retries.len().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_delivery_retries_until_attempts_exhausted() {
+        let channel = FailingChannel::new(true);
+        let dispatcher = start(config(3, 1), Arc::clone(&channel)).await;
+
+        let id = dispatcher
+            .enqueue(notification(), vec!["failing".to_string()])
+            .await
+            .unwrap();
+        settle(&dispatcher, &channel, 3).await;
+
+        assert_eq!(channel.calls.load(Ordering::SeqCst), 3);
+        assert_eq!(dispatcher.retry_count().await, 0);
+        let statuses = dispatcher.get_delivery_status(id).await.unwrap();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].status, DeliveryState::Failed);
+        assert_eq!(statuses[0].attempts, 3);
+        assert!(statuses[0]
+            .error_message
+            .as_deref()
+            .unwrap()
+            .contains("mailbox unavailable"));
+        assert_eq!(dispatcher.stats().await.total_failed, 1);
+
+        dispatcher.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_non_retryable_failure_is_attempted_once() {
+        let channel = FailingChannel::new(false);
+        let dispatcher = start(config(3, 1), Arc::clone(&channel)).await;
+
+        let id = dispatcher
+            .enqueue(notification(), vec!["failing".to_string()])
+            .await
+            .unwrap();
+        settle(&dispatcher, &channel, 1).await;
+
+        assert_eq!(channel.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(dispatcher.retry_count().await, 0);
+        let statuses = dispatcher.get_delivery_status(id).await.unwrap();
+        assert_eq!(statuses[0].status, DeliveryState::Failed);
+        assert_eq!(statuses[0].attempts, 1);
+
+        dispatcher.shutdown().await;
+    }
 }
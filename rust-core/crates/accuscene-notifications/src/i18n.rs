@@ -0,0 +1,269 @@
+//! Locale handling for notification templates
+//!
+//! Locales are BCP 47 tags such as `en`, `pt-BR` or `zh-Hant`. A requested
+//! locale resolves through a fallback chain (the tag itself, then each
+//! shorter prefix, then configured fallbacks, then the default locale).
+//! Plural forms follow the CLDR categories for integer counts.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// CLDR plural category
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    /// Name used as the form argument of the `plural` template filter
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PluralCategory::Zero => "zero",
+            PluralCategory::One => "one",
+            PluralCategory::Two => "two",
+            PluralCategory::Few => "few",
+            PluralCategory::Many => "many",
+            PluralCategory::Other => "other",
+        }
+    }
+}
+
+/// Plural category of an integer count in a locale
+pub fn plural_category(locale: &str, count: u64) -> PluralCategory {
+    let language = normalize_locale(locale);
+    let language = language.split('-').next().unwrap_or_default();
+    let (mod10, mod100) = (count % 10, count % 100);
+
+    match language {
+        "ja" | "zh" | "ko" | "vi" | "th" | "id" | "ms" => PluralCategory::Other,
+        "fr" | "pt" if count <= 1 => PluralCategory::One,
+        "ru" | "uk" | "be" => {
+            if mod10 == 1 && mod100 != 11 {
+                PluralCategory::One
+            } else if (2..=4).contains(&mod10) && !(12..=14).contains(&mod100) {
+                PluralCategory::Few
+            } else {
+                PluralCategory::Many
+            }
+        }
+        "pl" => {
+            if count == 1 {
+                PluralCategory::One
+            } else if (2..=4).contains(&mod10) && !(12..=14).contains(&mod100) {
+                PluralCategory::Few
+            } else {
+                PluralCategory::Many
+            }
+        }
+        "cs" | "sk" => match count {
+            1 => PluralCategory::One,
+            2..=4 => PluralCategory::Few,
+            _ => PluralCategory::Other,
+        },
+        "ar" => match (count, mod100) {
+            (0, _) => PluralCategory::Zero,
+            (1, _) => PluralCategory::One,
+            (2, _) => PluralCategory::Two,
+            (_, 3..=10) => PluralCategory::Few,
+            (_, 11..=99) => PluralCategory::Many,
+            _ => PluralCategory::Other,
+        },
+        _ if count == 1 => PluralCategory::One,
+        _ => PluralCategory::Other,
+    }
+}
+
+/// Pick the plural form for `count` and replace `#` in it with the count
+///
+/// `forms` maps category names to text. An explicit `zero` form is used for a
+/// count of zero in any locale; missing categories fall back to `other`.
+pub fn format_plural(locale: &str, count: u64, forms: &HashMap<String, String>) -> Option<String> {
+    let category = plural_category(locale, count);
+    let form = (count == 0)
+        .then(|| forms.get(PluralCategory::Zero.as_str()))
+        .flatten()
+        .or_else(|| forms.get(category.as_str()))
+        .or_else(|| forms.get(PluralCategory::Other.as_str()))?;
+
+    Some(form.replace('#', &count.to_string()))
+}
+
+/// Normalize a locale tag: `pt_br` becomes `pt-BR`, `ZH-hant` becomes `zh-Hant`
+pub fn normalize_locale(locale: &str) -> String {
+    locale
+        .trim()
+        .split(['-', '_'])
+        .filter(|part| !part.is_empty())
+        .enumerate()
+        .map(|(i, part)| match (i, part.len()) {
+            (0, _) => part.to_ascii_lowercase(),
+            (_, 2) => part.to_ascii_uppercase(),
+            (_, 4) => {
+                let mut script = part.to_ascii_lowercase();
+                script[..1].make_ascii_uppercase();
+                script
+            }
+            _ => part.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Locales to try, most specific first, when rendering for `locale`
+///
+/// `fallbacks` maps a locale to further locales to try before the default,
+/// e.g. `pt-BR` to `["pt-PT"]`.
+pub fn fallback_chain(
+    locale: &str,
+    fallbacks: &HashMap<String, Vec<String>>,
+    default_locale: &str,
+) -> Vec<String> {
+    let mut chain: Vec<String> = Vec::new();
+    let mut push = |locale: String| {
+        if !locale.is_empty() && !chain.contains(&locale) {
+            chain.push(locale);
+        }
+    };
+
+    let locale = normalize_locale(locale);
+    let parts: Vec<&str> = locale.split('-').collect();
+    let prefixes: Vec<String> = (1..=parts.len()).rev().map(|n| parts[..n].join("-")).collect();
+
+    for prefix in &prefixes {
+        push(prefix.clone());
+    }
+    for prefix in &prefixes {
+        if let Some(extra) = fallbacks.get(prefix) {
+            for fallback in extra {
+                push(normalize_locale(fallback));
+            }
+        }
+    }
+    push(normalize_locale(default_locale));
+
+    chain
+}
+
+/// Translation that is absent for a template
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MissingTranslation {
+    pub template_id: String,
+    pub locale: String,
+    /// Part of the template that is untranslated: `all` or `html`
+    pub part: String,
+    /// Locale actually rendered instead
+    pub resolved_locale: String,
+}
+
+/// Result of checking templates against the supported locales
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TranslationReport {
+    pub templates_checked: usize,
+    pub locales: Vec<String>,
+    pub missing: Vec<MissingTranslation>,
+    /// Translations for locales that aren't supported, by template
+    pub unused: HashMap<String, Vec<String>>,
+}
+
+impl TranslationReport {
+    /// Check if every template is translated into every supported locale
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+
+    /// Missing translations grouped by locale
+    pub fn missing_by_locale(&self) -> HashMap<String, Vec<String>> {
+        let mut grouped: HashMap<String, Vec<String>> = HashMap::new();
+        for missing in &self.missing {
+            grouped
+                .entry(missing.locale.clone())
+                .or_default()
+                .push(missing.template_id.clone());
+        }
+        grouped
+    }
+}
+
+impl fmt::Display for TranslationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Checked {} templates against {} locales: {} missing",
+            self.templates_checked,
+            self.locales.len(),
+            self.missing.len()
+        )?;
+
+        for missing in &self.missing {
+            writeln!(
+                f,
+                "  {} [{}]: {} missing, renders as {}",
+                missing.template_id, missing.locale, missing.part, missing.resolved_locale
+            )?;
+        }
+
+        let mut unused: Vec<_> = self.unused.iter().collect();
+        unused.sort();
+        for (template_id, locales) in unused {
+            writeln!(f, "  {}: unsupported locales {}", template_id, locales.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plural_categories() {
+        assert_eq!(plural_category("en", 1), PluralCategory::One);
+        assert_eq!(plural_category("en", 0), PluralCategory::Other);
+        assert_eq!(plural_category("fr", 0), PluralCategory::One);
+        assert_eq!(plural_category("pt-BR", 1), PluralCategory::One);
+        assert_eq!(plural_category("ja", 1), PluralCategory::Other);
+
+        assert_eq!(plural_category("ru", 21), PluralCategory::One);
+        assert_eq!(plural_category("ru", 11), PluralCategory::Many);
+        assert_eq!(plural_category("ru", 23), PluralCategory::Few);
+        assert_eq!(plural_category("pl", 12), PluralCategory::Many);
+        assert_eq!(plural_category("pl", 22), PluralCategory::Few);
+        assert_eq!(plural_category("ar", 2), PluralCategory::Two);
+        assert_eq!(plural_category("ar", 105), PluralCategory::Few);
+
+        let forms: HashMap<String, String> = [
+            ("one", "# файл"),
+            ("few", "# файла"),
+            ("other", "# файлов"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        assert_eq!(format_plural("ru", 3, &forms).as_deref(), Some("3 файла"));
+        assert_eq!(format_plural("ru", 5, &forms).as_deref(), Some("5 файлов"));
+        assert!(format_plural("ru", 5, &HashMap::new()).is_none());
+    }
+
+    #[test]
+    fn test_fallback_chain() {
+        assert_eq!(normalize_locale("pt_br"), "pt-BR");
+        assert_eq!(normalize_locale("ZH-hant-tw"), "zh-Hant-TW");
+
+        let mut fallbacks = HashMap::new();
+        fallbacks.insert("pt".to_string(), vec!["es".to_string()]);
+
+        assert_eq!(
+            fallback_chain("pt_BR", &fallbacks, "en"),
+            vec!["pt-BR", "pt", "es", "en"]
+        );
+        assert_eq!(fallback_chain("en-GB", &fallbacks, "en"), vec!["en-GB", "en"]);
+    }
+}
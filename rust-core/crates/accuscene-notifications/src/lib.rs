@@ -3,8 +3,15 @@
 //! A comprehensive, enterprise-grade real-time notification system with:
 //! - Multi-channel delivery (Email, SMS, Push, WebSocket, Webhooks)
 //! - Priority-based dispatching
+//! - Exactly-once claiming across processes through a shared Redis queue
+//! - Global, per-user and per-channel rate limits
+//! - Per-channel delivery retries with a dead-letter queue
+//! - Delivery tracking with read receipts and provider status webhooks
 //! - User preferences and quiet hours
+//! - Entity subscriptions ("follow this case")
+//! - Topic subscriptions with unsubscribe tokens
 //! - Template engine with built-in templates
+//! - Localized templates with plural rules and translation validation
 //! - Scheduled notifications with cron support
 //! - Notification batching and aggregation
 //! - Persistent storage and history
@@ -37,42 +44,70 @@
 pub mod aggregator;
 pub mod channel;
 pub mod config;
+pub mod coordination;
 pub mod dispatcher;
 pub mod error;
+pub mod i18n;
 pub mod preferences;
 pub mod scheduler;
 pub mod store;
+pub mod subscriptions;
 pub mod templates;
+pub mod throttle;
+pub mod topics;
+pub mod tracking;
 pub mod types;
+pub mod websocket;
 
 // Re-exports
 pub use aggregator::{AggregationRule, NotificationAggregator, NotificationBatch};
 pub use channel::{Channel, ChannelRegistry, EmailChannel, InAppChannel, PushChannel, SmsChannel, WebhookChannel};
-pub use config::{NotificationConfig, EmailConfig, SmsConfig, PushConfig, WebhookConfig};
+pub use config::{
+    NotificationConfig, CoordinationBackend, CoordinationConfig, EmailConfig, OverflowStrategy, RateLimit,
+    RateLimitConfig, SmsConfig, PushConfig, RetryPolicy, WebhookConfig,
+};
+pub use coordination::{DispatchCoordinator, DispatchJob, InMemoryCoordinator, Lease};
+#[cfg(feature = "redis")]
+pub use coordination::RedisCoordinator;
 pub use dispatcher::{NotificationDispatcher, DispatcherStats};
 pub use error::{NotificationError, Result};
-pub use preferences::{NotificationPreferences, PreferenceManager, QuietHours};
+pub use i18n::{MissingTranslation, PluralCategory, TranslationReport};
+pub use preferences::{
+    NotificationPreferences, PreferenceManager, QuietHours, TopicPreferences, WatchPreferences,
+};
 pub use scheduler::{NotificationScheduler, ScheduledNotification, SchedulerStats};
 pub use store::NotificationStore;
-pub use templates::{NotificationTemplate, TemplateEngine};
+pub use subscriptions::{EntitySubscription, SubscriptionManager, WatchEvent};
+pub use templates::{NotificationTemplate, RenderedTemplate, TemplateEngine, TemplateTranslation};
+pub use throttle::ThrottleStats;
+pub use topics::TopicSubscription;
+pub use tracking::{DeliveryTimeline, DeliveryTracker, IngestSummary, ProviderCallback, ProviderEvent};
 pub use types::{
     Notification, NotificationAction, NotificationCategory, NotificationLevel,
-    NotificationStats, Priority, DeliveryStatus, DeliveryState
+    NotificationStats, Priority, DeadLetter, DeliveryEvent, DeliveryStatus, DeliveryState
 };
+pub use websocket::{ClientMessage, ConnectionId, ConnectionRegistry, ServerMessage, WebSocketChannel};
 
 use sqlx::PgPool;
 use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 
 /// Main notification system
 pub struct NotificationSystem {
     config: NotificationConfig,
     store: Arc<NotificationStore>,
     preference_manager: Arc<PreferenceManager>,
+    subscriptions: Arc<SubscriptionManager>,
     channel_registry: Arc<ChannelRegistry>,
+    connections: Arc<ConnectionRegistry>,
+    tracker: Arc<DeliveryTracker>,
     dispatcher: Arc<NotificationDispatcher>,
     scheduler: Arc<NotificationScheduler>,
     aggregator: Arc<NotificationAggregator>,
     template_engine: Arc<TemplateEngine>,
+    shutdown: CancellationToken,
+    tasks: Vec<(String, JoinHandle<()>)>,
 }
 
 impl NotificationSystem {
@@ -93,6 +128,10 @@ impl NotificationSystem {
         let preference_manager = Arc::new(PreferenceManager::new(pool.clone()));
         preference_manager.initialize().await?;
 
+        // Initialize subscription manager
+        let subscriptions = Arc::new(SubscriptionManager::new(pool.clone()));
+        subscriptions.initialize().await?;
+
         // Initialize channel registry
         let mut channel_registry = ChannelRegistry::new();
         channel_registry.register(Arc::new(EmailChannel::new(config.channels.email.clone())));
@@ -100,14 +139,44 @@ impl NotificationSystem {
         channel_registry.register(Arc::new(PushChannel::new(config.channels.push.clone())));
         channel_registry.register(Arc::new(WebhookChannel::new(config.channels.webhook.clone())));
         channel_registry.register(Arc::new(InAppChannel::new(config.channels.in_app.enabled)));
+        let connections = Arc::new(ConnectionRegistry::new(config.websocket.send_buffer));
+        channel_registry.register(Arc::new(WebSocketChannel::new(
+            config.websocket.clone(),
+            Arc::clone(&connections),
+        )));
         let channel_registry = Arc::new(channel_registry);
 
+        // Channels without an explicit retry policy retry up to their max_retries
+        let mut dispatcher_config = config.dispatcher.clone();
+        for (channel, max_retries) in [
+            ("email", config.channels.email.max_retries),
+            ("sms", config.channels.sms.max_retries),
+            ("push", config.channels.push.max_retries),
+            ("webhook", config.channels.webhook.max_retries),
+        ] {
+            dispatcher_config
+                .channel_retry
+                .entry(channel.to_string())
+                .or_insert_with(|| dispatcher_config.retry.clone().with_max_attempts(max_retries + 1));
+        }
+
+        // Initialize delivery tracker
+        let tracker = Arc::new(DeliveryTracker::new(Arc::clone(&store)));
+
         // Initialize dispatcher
-        let dispatcher = Arc::new(NotificationDispatcher::new(
-            config.dispatcher.clone(),
+        let coordination = &config.dispatcher.coordination;
+        let mut dispatcher = NotificationDispatcher::new(
+            dispatcher_config,
             Arc::clone(&channel_registry),
             Arc::clone(&preference_manager),
-        ));
+        )
+        .with_dead_letter_store(Arc::clone(&store))
+        .with_rate_limits(config.rate_limiting.clone())
+        .with_delivery_tracker(Arc::clone(&tracker));
+        if let Some(coordinator) = Self::connect_coordinator(coordination).await? {
+            dispatcher = dispatcher.with_coordinator(coordinator, coordination.node_id.clone());
+        }
+        let dispatcher = Arc::new(dispatcher);
 
         // Initialize scheduler
         let scheduler = Arc::new(NotificationScheduler::new(Arc::clone(&dispatcher)));
@@ -116,42 +185,87 @@ impl NotificationSystem {
         let aggregator = Arc::new(NotificationAggregator::new(Arc::clone(&dispatcher)));
 
         // Initialize template engine
-        let template_engine = Arc::new(TemplateEngine::new(Some(&config.templates.templates_dir))?);
+        let template_engine = Arc::new(
+            TemplateEngine::new(Some(&config.templates.templates_dir))?
+                .with_default_locale(&config.templates.default_locale)
+                .with_fallbacks(config.templates.fallback_locales.clone()),
+        );
         template_engine.register_builtin_templates().await?;
 
         Ok(Self {
             config,
             store,
             preference_manager,
+            subscriptions,
             channel_registry,
+            connections,
+            tracker,
             dispatcher,
             scheduler,
             aggregator,
             template_engine,
+            shutdown: CancellationToken::new(),
+            tasks: Vec::new(),
         })
     }
 
+    /// Connect the queue shared with other processes, if one is configured
+    async fn connect_coordinator(
+        config: &CoordinationConfig,
+    ) -> Result<Option<Arc<dyn DispatchCoordinator>>> {
+        match config.backend {
+            CoordinationBackend::Local => Ok(None),
+            #[cfg(feature = "redis")]
+            CoordinationBackend::Redis => {
+                let url = config.redis_url.as_deref().unwrap_or_default();
+                let coordinator = RedisCoordinator::connect(url, &config.key_prefix).await?;
+                tracing::info!("Dispatching through the shared queue at {}", config.key_prefix);
+                Ok(Some(Arc::new(coordinator)))
+            }
+            #[cfg(not(feature = "redis"))]
+            CoordinationBackend::Redis => Err(NotificationError::Configuration(
+                "coordination backend redis requires the `redis` feature".to_string(),
+            )),
+        }
+    }
+
+    /// Run background tasks under a cancellation token from an enclosing shutdown scope
+    pub fn with_shutdown_token(mut self, token: CancellationToken) -> Self {
+        self.shutdown = token;
+        self
+    }
+
+    /// Hand over the background task handles so a shutdown scope can await them
+    ///
+    /// Task names are prefixed with the owning component, e.g. `dispatcher/worker-0`.
+    pub fn take_task_handles(&mut self) -> Vec<(String, JoinHandle<()>)> {
+        std::mem::take(&mut self.tasks)
+    }
+
     /// Start the notification system
     pub async fn start(&mut self) -> Result<()> {
         tracing::info!("Starting AccuScene Notification System v0.2.5");
 
         // Start dispatcher
-        Arc::get_mut(&mut self.dispatcher)
-            .ok_or_else(|| NotificationError::Internal("Cannot start dispatcher".to_string()))?
-            .start()
-            .await?;
+        let dispatcher = Arc::get_mut(&mut self.dispatcher)
+            .ok_or_else(|| NotificationError::Internal("Cannot start dispatcher".to_string()))?;
+        dispatcher.set_shutdown_token(self.shutdown.child_token());
+        dispatcher.start().await?;
+        Self::collect_tasks(&mut self.tasks, "dispatcher", dispatcher.take_task_handles());
 
         // Start scheduler
-        Arc::get_mut(&mut self.scheduler)
-            .ok_or_else(|| NotificationError::Internal("Cannot start scheduler".to_string()))?
-            .start()
-            .await?;
+        let scheduler = Arc::get_mut(&mut self.scheduler)
+            .ok_or_else(|| NotificationError::Internal("Cannot start scheduler".to_string()))?;
+        scheduler.set_shutdown_token(self.shutdown.child_token());
+        scheduler.start().await?;
+        Self::collect_tasks(&mut self.tasks, "scheduler", scheduler.take_task_handles());
 
         // Start aggregator
-        Arc::get_mut(&mut self.aggregator)
-            .ok_or_else(|| NotificationError::Internal("Cannot start aggregator".to_string()))?
-            .start()
-            .await?;
+        let aggregator = Arc::get_mut(&mut self.aggregator)
+            .ok_or_else(|| NotificationError::Internal("Cannot start aggregator".to_string()))?;
+        aggregator.set_shutdown_token(self.shutdown.child_token());
+        aggregator.start().await?;
+        Self::collect_tasks(&mut self.tasks, "aggregator", aggregator.take_task_handles());
 
         // Register default aggregation rules
         self.aggregator.register_default_rules().await?;
@@ -177,6 +291,9 @@ impl NotificationSystem {
     }
 
     /// Send a notification using a template
+    ///
+    /// Renders in the user's preferred language, falling back to the default
+    /// locale when the template has no variant for it.
     pub async fn send_from_template(
         &self,
         user_id: String,
@@ -188,6 +305,10 @@ impl NotificationSystem {
         let mut notification = Notification::new(user_id, level, "", "");
         notification.template_vars = template_vars;
 
+        if let Some(language) = self.preference_manager.get(&notification.user_id).await?.language {
+            notification.set_metadata("locale", serde_json::json!(language));
+        }
+
         self.template_engine
             .apply_template(&mut notification, template_id)
             .await?;
@@ -225,8 +346,15 @@ impl NotificationSystem {
     }
 
     /// Mark notification as read
+    ///
+    /// Also records a read receipt on the in-app delivery, if one is tracked.
     pub async fn mark_read(&self, id: uuid::Uuid) -> Result<()> {
-        self.store.mark_read(id).await
+        self.store.mark_read(id).await?;
+
+        let receipt = DeliveryEvent::new(id, "in_app", DeliveryState::Read, "client");
+        self.tracker.advance(receipt).await?;
+
+        Ok(())
     }
 
     /// Mark all as read
@@ -249,6 +377,45 @@ impl NotificationSystem {
         self.store.get_stats(user_id).await
     }
 
+    /// Get a notification's per-channel delivery statuses and their history
+    pub async fn delivery_timeline(&self, id: uuid::Uuid) -> Result<DeliveryTimeline> {
+        self.tracker.timeline(id).await
+    }
+
+    /// Apply a status webhook from an email or SMS provider
+    pub async fn ingest_provider_callback(&self, callback: ProviderCallback) -> Result<IngestSummary> {
+        self.tracker.ingest(callback).await
+    }
+
+    /// Get deliveries that exhausted their retries, oldest first
+    pub async fn dead_letters(&self, limit: i64) -> Result<Vec<DeadLetter>> {
+        self.store.get_dead_letters(limit).await
+    }
+
+    /// Requeue up to `limit` dead letters on the channel that failed them
+    ///
+    /// Returns the number requeued. Stops early if the dispatch queue fills up.
+    pub async fn requeue_dead_letters(&self, limit: i64) -> Result<usize> {
+        let mut requeued = 0;
+        for letter in self.store.get_dead_letters(limit).await? {
+            match self
+                .dispatcher
+                .enqueue(letter.notification, vec![letter.channel])
+                .await
+            {
+                Ok(_) => {
+                    self.store.mark_dead_letter_requeued(letter.id).await?;
+                    requeued += 1;
+                }
+                Err(NotificationError::QueueFull) => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        tracing::info!("Requeued {} dead-lettered deliveries", requeued);
+        Ok(requeued)
+    }
+
     /// Get user preferences
     pub async fn get_preferences(&self, user_id: &str) -> Result<NotificationPreferences> {
         self.preference_manager.get(user_id).await
@@ -259,6 +426,155 @@ impl NotificationSystem {
         self.preference_manager.save(preferences).await
     }
 
+    /// Follow an entity
+    ///
+    /// With no event types, the user's default watch filters apply.
+    pub async fn watch(
+        &self,
+        user_id: &str,
+        entity_type: &str,
+        entity_id: &str,
+        event_types: Vec<String>,
+    ) -> Result<EntitySubscription> {
+        let event_types = if event_types.is_empty() {
+            self.preference_manager.get(user_id).await?.watch.default_event_types
+        } else {
+            event_types
+        };
+
+        let subscription = EntitySubscription::new(user_id, entity_type, entity_id)
+            .with_event_types(event_types);
+        self.subscriptions.subscribe(&subscription).await
+    }
+
+    /// Stop following an entity
+    pub async fn unwatch(&self, user_id: &str, entity_type: &str, entity_id: &str) -> Result<bool> {
+        self.subscriptions
+            .unsubscribe(user_id, entity_type, entity_id)
+            .await
+    }
+
+    /// Get the entities a user follows
+    pub async fn watched(&self, user_id: &str) -> Result<Vec<EntitySubscription>> {
+        self.subscriptions.get_for_user(user_id).await
+    }
+
+    /// Fan out a domain event to the users following the entity
+    ///
+    /// The actor and users who turned off notifications for followed entities
+    /// are skipped. Each notification goes through `send`, so aggregation,
+    /// quiet hours and category preferences still apply.
+    pub async fn notify_watchers(&self, event: &WatchEvent) -> Result<Vec<uuid::Uuid>> {
+        let watchers = self
+            .subscriptions
+            .get_watchers(&event.entity_type, &event.entity_id, &event.event_type)
+            .await?;
+
+        let mut ids = Vec::new();
+        for subscription in watchers {
+            if event.actor_id.as_deref() == Some(subscription.user_id.as_str()) {
+                continue;
+            }
+
+            let preferences = self.preference_manager.get(&subscription.user_id).await?;
+            if !preferences.watch.enabled {
+                continue;
+            }
+
+            let channels = if subscription.channels.is_empty() {
+                preferences.enabled_channels
+            } else {
+                subscription.channels.clone()
+            };
+
+            let mut notification = Notification::new(
+                subscription.user_id.clone(),
+                event.level,
+                event.title.clone(),
+                event.message.clone(),
+            );
+            notification.organization_id = event.organization_id.clone();
+            notification.category = event.category();
+            notification.related_entity_id = Some(event.entity_id.clone());
+            notification.related_entity_type = Some(event.entity_type.clone());
+            notification.metadata = event.metadata.clone();
+            notification.set_metadata("event_type", serde_json::json!(event.event_type));
+            notification.set_metadata("subscription_id", serde_json::json!(subscription.id));
+
+            match self.send(notification, channels).await {
+                Ok(id) => ids.push(id),
+                Err(e) => tracing::error!(
+                    "Failed to notify watcher {}: {}",
+                    subscription.user_id,
+                    e
+                ),
+            }
+        }
+
+        Ok(ids)
+    }
+
+    /// Subscribe a user to a topic such as `case:1234` or `org:acme:*`
+    ///
+    /// Empty `channels` fall back to the user's default topic channels.
+    pub async fn subscribe_topic(
+        &self,
+        user_id: &str,
+        topic: &str,
+        channels: Vec<String>,
+    ) -> Result<TopicSubscription> {
+        let channels = if channels.is_empty() {
+            self.preference_manager.get(user_id).await?.topics.default_channels
+        } else {
+            channels
+        };
+
+        let subscription = TopicSubscription::new(user_id, topic).with_channels(channels);
+        self.preference_manager.subscribe_topic(&subscription).await
+    }
+
+    /// Unsubscribe a user from a topic
+    pub async fn unsubscribe_topic(&self, user_id: &str, topic: &str) -> Result<bool> {
+        self.preference_manager.unsubscribe_topic(user_id, topic).await
+    }
+
+    /// Unsubscribe using the token carried in a topic notification's metadata
+    pub async fn unsubscribe_with_token(&self, token: &str) -> Result<Option<TopicSubscription>> {
+        self.preference_manager.unsubscribe_by_token(token).await
+    }
+
+    /// Get the topics a user is subscribed to
+    pub async fn topic_subscriptions(&self, user_id: &str) -> Result<Vec<TopicSubscription>> {
+        self.preference_manager.get_topic_subscriptions(user_id).await
+    }
+
+    /// Publish a notification to every subscriber of a topic
+    ///
+    /// The notification's `user_id` is replaced per subscriber. Each copy goes
+    /// through `send`, so storage, aggregation and preferences still apply.
+    pub async fn publish(
+        &self,
+        topic: &str,
+        notification: Notification,
+        channels: Vec<String>,
+    ) -> Result<Vec<uuid::Uuid>> {
+        let recipients = self
+            .dispatcher
+            .topic_recipients(topic, &notification, &channels)
+            .await?;
+
+        let mut ids = Vec::new();
+        for (notification, channels) in recipients {
+            let user_id = notification.user_id.clone();
+            match self.send(notification, channels).await {
+                Ok(id) => ids.push(id),
+                Err(e) => tracing::error!("Failed to notify topic subscriber {}: {}", user_id, e),
+            }
+        }
+
+        Ok(ids)
+    }
+
     /// Schedule a notification
     pub async fn schedule(
         &self,
@@ -271,6 +587,13 @@ impl NotificationSystem {
             .await
     }
 
+    /// Report templates missing a translation for any configured locale
+    pub async fn validate_translations(&self) -> TranslationReport {
+        self.template_engine
+            .validate_translations(&self.config.templates.locales)
+            .await
+    }
+
     /// Get template engine
     pub fn template_engine(&self) -> &Arc<TemplateEngine> {
         &self.template_engine
@@ -286,6 +609,21 @@ impl NotificationSystem {
         &self.preference_manager
     }
 
+    /// Get subscription manager
+    pub fn subscriptions(&self) -> &Arc<SubscriptionManager> {
+        &self.subscriptions
+    }
+
+    /// Get WebSocket connection registry
+    pub fn connections(&self) -> &Arc<ConnectionRegistry> {
+        &self.connections
+    }
+
+    /// Get delivery tracker
+    pub fn delivery_tracker(&self) -> &Arc<DeliveryTracker> {
+        &self.tracker
+    }
+
     /// Get dispatcher
     pub fn dispatcher(&self) -> &Arc<NotificationDispatcher> {
         &self.dispatcher
@@ -296,6 +634,19 @@ impl NotificationSystem {
         &self.scheduler
     }
 
+    /// Prefix component task names and keep the handles
+    fn collect_tasks(
+        tasks: &mut Vec<(String, JoinHandle<()>)>,
+        component: &str,
+        handles: Vec<(String, JoinHandle<()>)>,
+    ) {
+        tasks.extend(
+            handles
+                .into_iter()
+                .map(|(name, handle)| (format!("{}/{}", component, name), handle)),
+        );
+    }
+
     /// Get aggregator
     pub fn aggregator(&self) -> &Arc<NotificationAggregator> {
         &self.aggregator
@@ -304,9 +655,8 @@ impl NotificationSystem {
     /// Shutdown the system
     pub async fn shutdown(&self) {
         tracing::info!("Shutting down notification system");
-        self.dispatcher.shutdown().await;
-        self.scheduler.shutdown().await;
-        self.aggregator.shutdown().await;
+        // Cancels the dispatcher, scheduler and aggregator tokens as well
+        self.shutdown.cancel();
     }
 
     /// Get system configuration
@@ -1,9 +1,10 @@
 //! User notification preferences management
 
 use crate::error::{NotificationError, Result};
+use crate::topics::{self, TopicSubscription};
 use crate::types::{NotificationCategory, NotificationLevel};
 use serde::{Deserialize, Serialize};
-use sqlx::PgPool;
+use sqlx::{PgPool, Row};
 use std::collections::HashMap;
 
 /// User notification preferences
@@ -16,6 +17,13 @@ pub struct NotificationPreferences {
     pub level_preferences: HashMap<String, bool>,
     pub digest_enabled: bool,
     pub digest_frequency: DigestFrequency,
+    #[serde(default)]
+    pub watch: WatchPreferences,
+    #[serde(default)]
+    pub topics: TopicPreferences,
+    /// Locale for templated notifications; mirrors the `general.language` preference
+    #[serde(default)]
+    pub language: Option<String>,
 }
 
 impl Default for NotificationPreferences {
@@ -28,6 +36,9 @@ impl Default for NotificationPreferences {
             level_preferences: HashMap::new(),
             digest_enabled: false,
             digest_frequency: DigestFrequency::Daily,
+            watch: WatchPreferences::default(),
+            topics: TopicPreferences::default(),
+            language: None,
         }
     }
 }
@@ -50,6 +61,42 @@ pub struct CategoryPreference {
     pub min_priority: u8, // 1-5
 }
 
+/// Preferences for followed entities
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchPreferences {
+    /// Receive notifications for followed entities
+    pub enabled: bool,
+    /// Event types applied to new subscriptions that don't specify any; empty means all
+    pub default_event_types: Vec<String>,
+}
+
+impl Default for WatchPreferences {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            default_event_types: Vec::new(),
+        }
+    }
+}
+
+/// Preferences for topic subscriptions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopicPreferences {
+    /// Receive notifications published to subscribed topics
+    pub enabled: bool,
+    /// Channels applied to new subscriptions that don't specify any; empty means the publisher's
+    pub default_channels: Vec<String>,
+}
+
+impl Default for TopicPreferences {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            default_channels: Vec::new(),
+        }
+    }
+}
+
 /// Notification digest frequency
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -87,6 +134,28 @@ impl PreferenceManager {
             );
 
             CREATE INDEX IF NOT EXISTS idx_preferences_user_id ON notification_preferences(user_id);
+
+            ALTER TABLE notification_preferences
+                ADD COLUMN IF NOT EXISTS watch_preferences JSONB;
+
+            ALTER TABLE notification_preferences
+                ADD COLUMN IF NOT EXISTS topic_preferences JSONB;
+
+            ALTER TABLE notification_preferences
+                ADD COLUMN IF NOT EXISTS language VARCHAR(35);
+
+            CREATE TABLE IF NOT EXISTS topic_subscriptions (
+                id UUID PRIMARY KEY,
+                user_id VARCHAR(255) NOT NULL,
+                topic VARCHAR(255) NOT NULL,
+                channels JSONB NOT NULL DEFAULT '[]',
+                unsubscribe_token VARCHAR(64) NOT NULL UNIQUE,
+                created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+                UNIQUE (user_id, topic)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_topic_subscriptions_topic ON topic_subscriptions(topic);
+            CREATE INDEX IF NOT EXISTS idx_topic_subscriptions_user ON topic_subscriptions(user_id);
             "#,
         )
         .execute(&self.pool)
@@ -107,8 +176,6 @@ impl PreferenceManager {
         .await?;
 
         if let Some(row) = row {
-            use sqlx::Row;
-
             Ok(NotificationPreferences {
                 user_id: row.get("user_id"),
                 enabled_channels: serde_json::from_value(row.get("enabled_channels"))?,
@@ -120,6 +187,17 @@ impl PreferenceManager {
                     "\"{}\"",
                     row.get::<String, _>("digest_frequency")
                 ))?,
+                watch: row
+                    .get::<Option<serde_json::Value>, _>("watch_preferences")
+                    .map(serde_json::from_value)
+                    .transpose()?
+                    .unwrap_or_default(),
+                topics: row
+                    .get::<Option<serde_json::Value>, _>("topic_preferences")
+                    .map(serde_json::from_value)
+                    .transpose()?
+                    .unwrap_or_default(),
+                language: row.get("language"),
             })
         } else {
             // Return defaults if no preferences exist
@@ -136,8 +214,9 @@ impl PreferenceManager {
             r#"
             INSERT INTO notification_preferences (
                 user_id, enabled_channels, quiet_hours, category_preferences,
-                level_preferences, digest_enabled, digest_frequency, updated_at
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
+                level_preferences, digest_enabled, digest_frequency, watch_preferences,
+                topic_preferences, language, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, NOW())
             ON CONFLICT (user_id) DO UPDATE SET
                 enabled_channels = EXCLUDED.enabled_channels,
                 quiet_hours = EXCLUDED.quiet_hours,
@@ -145,6 +224,9 @@ impl PreferenceManager {
                 level_preferences = EXCLUDED.level_preferences,
                 digest_enabled = EXCLUDED.digest_enabled,
                 digest_frequency = EXCLUDED.digest_frequency,
+                watch_preferences = EXCLUDED.watch_preferences,
+                topic_preferences = EXCLUDED.topic_preferences,
+                language = EXCLUDED.language,
                 updated_at = NOW()
             "#,
         )
@@ -155,6 +237,9 @@ impl PreferenceManager {
         .bind(serde_json::to_value(&preferences.level_preferences)?)
         .bind(preferences.digest_enabled)
         .bind(format!("{:?}", preferences.digest_frequency).to_lowercase())
+        .bind(serde_json::to_value(&preferences.watch)?)
+        .bind(serde_json::to_value(&preferences.topics)?)
+        .bind(&preferences.language)
         .execute(&self.pool)
         .await?;
 
@@ -295,6 +380,25 @@ impl PreferenceManager {
         Ok(())
     }
 
+    /// Set preferences for followed entities
+    pub async fn set_watch_preferences(&self, user_id: &str, watch: WatchPreferences) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO notification_preferences (user_id, watch_preferences, updated_at)
+            VALUES ($1, $2, NOW())
+            ON CONFLICT (user_id) DO UPDATE SET
+                watch_preferences = EXCLUDED.watch_preferences,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(user_id)
+        .bind(serde_json::to_value(watch)?)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     /// Set level preference
     pub async fn set_level_preference(
         &self,
@@ -349,6 +453,125 @@ impl PreferenceManager {
         Ok(true)
     }
 
+    /// Subscribe a user to a topic or topic pattern
+    ///
+    /// Re-subscribing updates the channels but keeps the existing unsubscribe
+    /// token so links already sent keep working.
+    pub async fn subscribe_topic(
+        &self,
+        subscription: &TopicSubscription,
+    ) -> Result<TopicSubscription> {
+        topics::validate_topic(&subscription.topic, true)?;
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO topic_subscriptions (id, user_id, topic, channels, unsubscribe_token, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (user_id, topic) DO UPDATE SET
+                channels = EXCLUDED.channels
+            RETURNING *
+            "#,
+        )
+        .bind(subscription.id)
+        .bind(&subscription.user_id)
+        .bind(&subscription.topic)
+        .bind(serde_json::to_value(&subscription.channels)?)
+        .bind(&subscription.unsubscribe_token)
+        .bind(subscription.created_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        self.row_to_topic_subscription(row)
+    }
+
+    /// Unsubscribe a user from a topic; returns whether a subscription existed
+    pub async fn unsubscribe_topic(&self, user_id: &str, topic: &str) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM topic_subscriptions WHERE user_id = $1 AND topic = $2
+            "#,
+        )
+        .bind(user_id)
+        .bind(topic)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Remove the subscription owning an unsubscribe token
+    pub async fn unsubscribe_by_token(&self, token: &str) -> Result<Option<TopicSubscription>> {
+        let row = sqlx::query(
+            r#"
+            DELETE FROM topic_subscriptions WHERE unsubscribe_token = $1
+            RETURNING *
+            "#,
+        )
+        .bind(token)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| self.row_to_topic_subscription(row)).transpose()
+    }
+
+    /// Get the topics a user is subscribed to
+    pub async fn get_topic_subscriptions(&self, user_id: &str) -> Result<Vec<TopicSubscription>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM topic_subscriptions WHERE user_id = $1 ORDER BY topic
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| self.row_to_topic_subscription(row))
+            .collect()
+    }
+
+    /// Get the subscriptions matching a published topic
+    ///
+    /// Users who turned off topic notifications are excluded.
+    pub async fn get_topic_subscribers(&self, topic: &str) -> Result<Vec<TopicSubscription>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM topic_subscriptions
+            WHERE topic = $1 OR topic LIKE '%*%'
+            "#,
+        )
+        .bind(topic)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut subscribers: Vec<TopicSubscription> = Vec::new();
+        for row in rows {
+            let subscription = self.row_to_topic_subscription(row)?;
+            // A user matching both an exact topic and a pattern is notified once
+            if !subscription.matches(topic)
+                || subscribers.iter().any(|s| s.user_id == subscription.user_id)
+            {
+                continue;
+            }
+            if self.get(&subscription.user_id).await?.topics.enabled {
+                subscribers.push(subscription);
+            }
+        }
+
+        Ok(subscribers)
+    }
+
+    fn row_to_topic_subscription(&self, row: sqlx::postgres::PgRow) -> Result<TopicSubscription> {
+        Ok(TopicSubscription {
+            id: row.get("id"),
+            user_id: row.get("user_id"),
+            topic: row.get("topic"),
+            channels: serde_json::from_value(row.get("channels"))?,
+            unsubscribe_token: row.get("unsubscribe_token"),
+            created_at: row.get("created_at"),
+        })
+    }
+
     /// Delete preferences for a user
     pub async fn delete(&self, user_id: &str) -> Result<()> {
         sqlx::query(
@@ -10,6 +10,8 @@ use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
 /// Scheduled notification
@@ -74,7 +76,8 @@ impl ScheduledNotification {
 pub struct NotificationScheduler {
     scheduled: Arc<RwLock<HashMap<Uuid, ScheduledNotification>>>,
     dispatcher: Arc<NotificationDispatcher>,
-    shutdown_tx: Option<tokio::sync::mpsc::Sender<()>>,
+    shutdown: CancellationToken,
+    tasks: Vec<(String, JoinHandle<()>)>,
 }
 
 impl NotificationScheduler {
@@ -83,20 +86,32 @@ impl NotificationScheduler {
         Self {
             scheduled: Arc::new(RwLock::new(HashMap::new())),
             dispatcher,
-            shutdown_tx: None,
+            shutdown: CancellationToken::new(),
+            tasks: Vec::new(),
         }
     }
 
+    /// Use a cancellation token from an enclosing shutdown scope
+    ///
+    /// Must be called before [`start`](Self::start).
+    pub fn set_shutdown_token(&mut self, token: CancellationToken) {
+        self.shutdown = token;
+    }
+
+    /// Hand over the spawned task handles so a shutdown scope can await them
+    pub fn take_task_handles(&mut self) -> Vec<(String, JoinHandle<()>)> {
+        std::mem::take(&mut self.tasks)
+    }
+
     /// Start the scheduler
     pub async fn start(&mut self) -> Result<()> {
-        let (shutdown_tx, mut shutdown_rx) = tokio::sync::mpsc::channel::<()>(1);
-        self.shutdown_tx = Some(shutdown_tx);
+        let shutdown = self.shutdown.clone();
 
         let scheduled = Arc::clone(&self.scheduled);
         let dispatcher = Arc::clone(&self.dispatcher);
 
         // Spawn scheduler task
-        tokio::spawn(async move {
+        let handle = tokio::spawn(async move {
             tracing::info!("Notification scheduler started");
 
             let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
@@ -106,13 +121,14 @@ impl NotificationScheduler {
                     _ = interval.tick() => {
                         Self::process_scheduled(&scheduled, &dispatcher).await;
                     }
-                    _ = shutdown_rx.recv() => {
+                    _ = shutdown.cancelled() => {
                         tracing::info!("Notification scheduler shutting down");
                         break;
                     }
                 }
             }
         });
+        self.tasks.push(("scheduler".to_string(), handle));
 
         Ok(())
     }
@@ -281,9 +297,7 @@ impl NotificationScheduler {
 
     /// Shutdown the scheduler
     pub async fn shutdown(&self) {
-        if let Some(tx) = &self.shutdown_tx {
-            let _ = tx.send(()).await;
-        }
+        self.shutdown.cancel();
     }
 
     /// Get scheduler statistics
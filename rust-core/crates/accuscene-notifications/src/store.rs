@@ -1,7 +1,9 @@
 //! Notification persistence and history management
 
 use crate::error::{NotificationError, Result};
-use crate::types::{Notification, NotificationStats};
+use crate::types::{
+    DeadLetter, DeliveryEvent, DeliveryState, DeliveryStatus, Notification, NotificationStats,
+};
 use chrono::{DateTime, Duration, Utc};
 use sqlx::{PgPool, Row};
 use uuid::Uuid;
@@ -67,6 +69,32 @@ impl NotificationStore {
 
             CREATE INDEX IF NOT EXISTS idx_delivery_status_notification_id ON notification_delivery_status(notification_id);
             CREATE INDEX IF NOT EXISTS idx_delivery_status_channel ON notification_delivery_status(channel);
+            CREATE UNIQUE INDEX IF NOT EXISTS idx_delivery_status_notification_channel ON notification_delivery_status(notification_id, channel);
+
+            CREATE TABLE IF NOT EXISTS notification_delivery_events (
+                id UUID PRIMARY KEY,
+                notification_id UUID NOT NULL REFERENCES notifications(id) ON DELETE CASCADE,
+                channel VARCHAR(50) NOT NULL,
+                status VARCHAR(50) NOT NULL,
+                source VARCHAR(100) NOT NULL,
+                detail TEXT,
+                occurred_at TIMESTAMP WITH TIME ZONE NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_delivery_events_notification_id ON notification_delivery_events(notification_id, occurred_at);
+
+            CREATE TABLE IF NOT EXISTS notification_dead_letters (
+                id UUID PRIMARY KEY,
+                notification_id UUID NOT NULL,
+                channel VARCHAR(50) NOT NULL,
+                notification JSONB NOT NULL,
+                attempts INTEGER NOT NULL,
+                last_error TEXT,
+                failed_at TIMESTAMP WITH TIME ZONE NOT NULL,
+                requeued_at TIMESTAMP WITH TIME ZONE
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_dead_letters_pending ON notification_dead_letters(failed_at) WHERE requeued_at IS NULL;
             "#,
         )
         .execute(&self.pool)
@@ -250,6 +278,34 @@ impl NotificationStore {
         Ok(())
     }
 
+    /// Overwrite the read and archive state of a notification
+    ///
+    /// Used when applying inbox state converged from another device.
+    /// Returns `false` if the notification is not stored locally.
+    pub async fn set_state(
+        &self,
+        id: Uuid,
+        read: bool,
+        read_at: Option<DateTime<Utc>>,
+        archived: bool,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE notifications
+            SET read = $2, read_at = $3, archived = $4
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(read)
+        .bind(read_at)
+        .bind(archived)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Delete notification
     pub async fn delete(&self, id: Uuid) -> Result<()> {
         sqlx::query(
@@ -379,6 +435,220 @@ impl NotificationStore {
         })
     }
 
+    /// Save a delivery that exhausted its retries
+    pub async fn save_dead_letter(&self, letter: &DeadLetter) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO notification_dead_letters (
+                id, notification_id, channel, notification, attempts, last_error, failed_at, requeued_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(letter.id)
+        .bind(letter.notification.id)
+        .bind(&letter.channel)
+        .bind(serde_json::to_value(&letter.notification)?)
+        .bind(letter.attempts as i32)
+        .bind(&letter.last_error)
+        .bind(letter.failed_at)
+        .bind(letter.requeued_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get dead letters not yet requeued, oldest first
+    pub async fn get_dead_letters(&self, limit: i64) -> Result<Vec<DeadLetter>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM notification_dead_letters
+            WHERE requeued_at IS NULL
+            ORDER BY failed_at ASC
+            LIMIT $1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| -> Result<DeadLetter> {
+                Ok(DeadLetter {
+                    id: row.get("id"),
+                    notification: serde_json::from_value(row.get("notification"))?,
+                    channel: row.get("channel"),
+                    attempts: row.get::<i32, _>("attempts") as u32,
+                    last_error: row.get("last_error"),
+                    failed_at: row.get("failed_at"),
+                    requeued_at: row.get("requeued_at"),
+                })
+            })
+            .collect()
+    }
+
+    /// Mark a dead letter as requeued
+    pub async fn mark_dead_letter_requeued(&self, id: Uuid) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE notification_dead_letters
+            SET requeued_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Count dead letters not yet requeued
+    pub async fn count_dead_letters(&self) -> Result<u64> {
+        let row = sqlx::query(
+            r#"
+            SELECT COUNT(*) as count FROM notification_dead_letters
+            WHERE requeued_at IS NULL
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.get::<i64, _>("count") as u64)
+    }
+
+    /// Insert or replace the delivery status of a notification on a channel
+    pub async fn save_delivery_status(&self, status: &DeliveryStatus) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO notification_delivery_status (
+                notification_id, channel, status, attempts, last_attempt_at, delivered_at, error_message
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (notification_id, channel) DO UPDATE SET
+                status = EXCLUDED.status,
+                attempts = EXCLUDED.attempts,
+                last_attempt_at = EXCLUDED.last_attempt_at,
+                delivered_at = EXCLUDED.delivered_at,
+                error_message = EXCLUDED.error_message
+            "#,
+        )
+        .bind(status.notification_id)
+        .bind(&status.channel)
+        .bind(format!("{:?}", status.status).to_lowercase())
+        .bind(status.attempts as i32)
+        .bind(status.last_attempt_at)
+        .bind(status.delivered_at)
+        .bind(&status.error_message)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get the delivery status of a notification on one channel
+    pub async fn get_delivery_status(
+        &self,
+        notification_id: Uuid,
+        channel: &str,
+    ) -> Result<Option<DeliveryStatus>> {
+        let row = sqlx::query(
+            r#"
+            SELECT * FROM notification_delivery_status
+            WHERE notification_id = $1 AND channel = $2
+            "#,
+        )
+        .bind(notification_id)
+        .bind(channel)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| self.row_to_delivery_status(row)).transpose()
+    }
+
+    /// Get the delivery status of a notification on every channel
+    pub async fn get_delivery_statuses(&self, notification_id: Uuid) -> Result<Vec<DeliveryStatus>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM notification_delivery_status
+            WHERE notification_id = $1
+            ORDER BY channel
+            "#,
+        )
+        .bind(notification_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| self.row_to_delivery_status(row))
+            .collect()
+    }
+
+    /// Append an entry to a notification's delivery timeline
+    pub async fn save_delivery_event(&self, event: &DeliveryEvent) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO notification_delivery_events (
+                id, notification_id, channel, status, source, detail, occurred_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(event.id)
+        .bind(event.notification_id)
+        .bind(&event.channel)
+        .bind(format!("{:?}", event.state).to_lowercase())
+        .bind(&event.source)
+        .bind(&event.detail)
+        .bind(event.occurred_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get a notification's delivery timeline, oldest first
+    pub async fn get_delivery_events(&self, notification_id: Uuid) -> Result<Vec<DeliveryEvent>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM notification_delivery_events
+            WHERE notification_id = $1
+            ORDER BY occurred_at ASC
+            "#,
+        )
+        .bind(notification_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| -> Result<DeliveryEvent> {
+                Ok(DeliveryEvent {
+                    id: row.get("id"),
+                    notification_id: row.get("notification_id"),
+                    channel: row.get("channel"),
+                    state: Self::parse_delivery_state(row.get("status"))?,
+                    source: row.get("source"),
+                    detail: row.get("detail"),
+                    occurred_at: row.get("occurred_at"),
+                })
+            })
+            .collect()
+    }
+
+    fn row_to_delivery_status(&self, row: sqlx::postgres::PgRow) -> Result<DeliveryStatus> {
+        Ok(DeliveryStatus {
+            notification_id: row.get("notification_id"),
+            channel: row.get("channel"),
+            status: Self::parse_delivery_state(row.get("status"))?,
+            attempts: row.get::<i32, _>("attempts") as u32,
+            last_attempt_at: row.get("last_attempt_at"),
+            delivered_at: row.get("delivered_at"),
+            error_message: row.get("error_message"),
+        })
+    }
+
+    fn parse_delivery_state(value: String) -> Result<DeliveryState> {
+        Ok(serde_json::from_value(serde_json::Value::String(value))?)
+    }
+
     /// Convert database row to Notification
     fn row_to_notification(&self, row: sqlx::postgres::PgRow) -> Result<Notification> {
         use sqlx::Row;
@@ -0,0 +1,332 @@
+//! Entity subscriptions ("follow this case")
+//!
+//! Users subscribe to individual entities such as a case or a report and
+//! receive notifications only for domain events on the entities they
+//! follow, optionally narrowed to specific event types.
+
+use crate::error::Result;
+use crate::types::{NotificationCategory, NotificationLevel};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// A user's subscription to an entity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntitySubscription {
+    pub id: Uuid,
+    pub user_id: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    /// Event types to notify on; empty means all events
+    pub event_types: Vec<String>,
+    /// Channels to deliver on; empty means the user's enabled channels
+    pub channels: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl EntitySubscription {
+    /// Create a subscription to all events of an entity
+    pub fn new(
+        user_id: impl Into<String>,
+        entity_type: impl Into<String>,
+        entity_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            user_id: user_id.into(),
+            entity_type: entity_type.into(),
+            entity_id: entity_id.into(),
+            event_types: Vec::new(),
+            channels: Vec::new(),
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Restrict the subscription to the given event types
+    pub fn with_event_types(mut self, event_types: Vec<String>) -> Self {
+        self.event_types = event_types;
+        self
+    }
+
+    /// Deliver on the given channels instead of the user's defaults
+    pub fn with_channels(mut self, channels: Vec<String>) -> Self {
+        self.channels = channels;
+        self
+    }
+
+    /// Check if the subscription covers an event type
+    pub fn matches(&self, event_type: &str) -> bool {
+        self.event_types.is_empty() || self.event_types.iter().any(|t| t == event_type)
+    }
+}
+
+/// Domain event on a watchable entity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchEvent {
+    pub entity_type: String,
+    pub entity_id: String,
+    pub event_type: String,
+    /// User who caused the event; they are not notified about their own change
+    pub actor_id: Option<String>,
+    pub organization_id: Option<String>,
+    pub level: NotificationLevel,
+    pub title: String,
+    pub message: String,
+    pub metadata: HashMap<String, serde_json::Value>,
+}
+
+impl WatchEvent {
+    /// Create a new watch event
+    pub fn new(
+        entity_type: impl Into<String>,
+        entity_id: impl Into<String>,
+        event_type: impl Into<String>,
+        title: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            entity_type: entity_type.into(),
+            entity_id: entity_id.into(),
+            event_type: event_type.into(),
+            actor_id: None,
+            organization_id: None,
+            level: NotificationLevel::Info,
+            title: title.into(),
+            message: message.into(),
+            metadata: HashMap::new(),
+        }
+    }
+
+    /// Set the user who caused the event
+    pub fn with_actor(mut self, actor_id: impl Into<String>) -> Self {
+        self.actor_id = Some(actor_id.into());
+        self
+    }
+
+    /// Set the organization
+    pub fn with_organization(mut self, organization_id: impl Into<String>) -> Self {
+        self.organization_id = Some(organization_id.into());
+        self
+    }
+
+    /// Set the notification level
+    pub fn with_level(mut self, level: NotificationLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Notification category for the watched entity type
+    pub fn category(&self) -> NotificationCategory {
+        match self.entity_type.as_str() {
+            "case" => NotificationCategory::Case,
+            "analysis" | "simulation" => NotificationCategory::Analysis,
+            "report" => NotificationCategory::Report,
+            "comment" | "annotation" => NotificationCategory::Collaboration,
+            other => NotificationCategory::Custom(other.to_string()),
+        }
+    }
+}
+
+/// Subscription manager
+pub struct SubscriptionManager {
+    pool: PgPool,
+}
+
+impl SubscriptionManager {
+    /// Create a new subscription manager
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Initialize database schema
+    pub async fn initialize(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS entity_subscriptions (
+                id UUID PRIMARY KEY,
+                user_id VARCHAR(255) NOT NULL,
+                entity_type VARCHAR(100) NOT NULL,
+                entity_id VARCHAR(255) NOT NULL,
+                event_types JSONB NOT NULL DEFAULT '[]',
+                channels JSONB NOT NULL DEFAULT '[]',
+                created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+                UNIQUE (user_id, entity_type, entity_id)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_subscriptions_entity ON entity_subscriptions(entity_type, entity_id);
+            CREATE INDEX IF NOT EXISTS idx_subscriptions_user_id ON entity_subscriptions(user_id);
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Subscribe a user to an entity, replacing filters of an existing subscription
+    pub async fn subscribe(&self, subscription: &EntitySubscription) -> Result<EntitySubscription> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO entity_subscriptions (
+                id, user_id, entity_type, entity_id, event_types, channels, created_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (user_id, entity_type, entity_id) DO UPDATE SET
+                event_types = EXCLUDED.event_types,
+                channels = EXCLUDED.channels
+            RETURNING *
+            "#,
+        )
+        .bind(subscription.id)
+        .bind(&subscription.user_id)
+        .bind(&subscription.entity_type)
+        .bind(&subscription.entity_id)
+        .bind(serde_json::to_value(&subscription.event_types)?)
+        .bind(serde_json::to_value(&subscription.channels)?)
+        .bind(subscription.created_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        self.row_to_subscription(row)
+    }
+
+    /// Unsubscribe a user from an entity; returns whether a subscription existed
+    pub async fn unsubscribe(&self, user_id: &str, entity_type: &str, entity_id: &str) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM entity_subscriptions
+            WHERE user_id = $1 AND entity_type = $2 AND entity_id = $3
+            "#,
+        )
+        .bind(user_id)
+        .bind(entity_type)
+        .bind(entity_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Get a user's subscription to an entity
+    pub async fn get(
+        &self,
+        user_id: &str,
+        entity_type: &str,
+        entity_id: &str,
+    ) -> Result<Option<EntitySubscription>> {
+        let row = sqlx::query(
+            r#"
+            SELECT * FROM entity_subscriptions
+            WHERE user_id = $1 AND entity_type = $2 AND entity_id = $3
+            "#,
+        )
+        .bind(user_id)
+        .bind(entity_type)
+        .bind(entity_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| self.row_to_subscription(row)).transpose()
+    }
+
+    /// Get all subscriptions of a user
+    pub async fn get_for_user(&self, user_id: &str) -> Result<Vec<EntitySubscription>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM entity_subscriptions
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| self.row_to_subscription(row))
+            .collect()
+    }
+
+    /// Get the subscriptions of an entity that cover an event type
+    pub async fn get_watchers(
+        &self,
+        entity_type: &str,
+        entity_id: &str,
+        event_type: &str,
+    ) -> Result<Vec<EntitySubscription>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM entity_subscriptions
+            WHERE entity_type = $1 AND entity_id = $2
+            "#,
+        )
+        .bind(entity_type)
+        .bind(entity_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let subscriptions = rows
+            .into_iter()
+            .map(|row| self.row_to_subscription(row))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(subscriptions
+            .into_iter()
+            .filter(|s| s.matches(event_type))
+            .collect())
+    }
+
+    /// Remove all subscriptions to an entity (e.g. when it is deleted)
+    pub async fn remove_entity(&self, entity_type: &str, entity_id: &str) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM entity_subscriptions
+            WHERE entity_type = $1 AND entity_id = $2
+            "#,
+        )
+        .bind(entity_type)
+        .bind(entity_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Convert database row to EntitySubscription
+    fn row_to_subscription(&self, row: sqlx::postgres::PgRow) -> Result<EntitySubscription> {
+        Ok(EntitySubscription {
+            id: row.get("id"),
+            user_id: row.get("user_id"),
+            entity_type: row.get("entity_type"),
+            entity_id: row.get("entity_id"),
+            event_types: serde_json::from_value(row.get("event_types"))?,
+            channels: serde_json::from_value(row.get("channels"))?,
+            created_at: row.get("created_at"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscription_matches_event_types() {
+        let all = EntitySubscription::new("alice", "case", "case-1");
+        assert!(all.matches("case.updated"));
+
+        let filtered = all.with_event_types(vec!["case.closed".to_string()]);
+        assert!(filtered.matches("case.closed"));
+        assert!(!filtered.matches("case.updated"));
+    }
+
+    #[test]
+    fn test_watch_event_category() {
+        let event = WatchEvent::new("case", "case-1", "case.updated", "Case updated", "");
+        assert_eq!(event.category(), NotificationCategory::Case);
+
+        let event = WatchEvent::new("vehicle", "v-1", "vehicle.updated", "Vehicle updated", "");
+        assert_eq!(event.category(), NotificationCategory::Custom("vehicle".to_string()));
+    }
+}
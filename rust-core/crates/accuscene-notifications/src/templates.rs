@@ -1,6 +1,13 @@
 //! Notification template engine
+//!
+//! Templates are written in the default locale and may carry per-locale
+//! variants. Rendering resolves the requested locale through the fallback
+//! chain in [`i18n`](crate::i18n). Counts are pluralized with the `plural`
+//! filter, which takes one argument per CLDR category plus the locale, e.g.
+//! `{{ count | plural(one="# file", other="# files", locale=locale) }}`.
 
 use crate::error::{NotificationError, Result};
+use crate::i18n::{self, MissingTranslation, TranslationReport};
 use crate::types::Notification;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -18,50 +25,102 @@ pub struct NotificationTemplate {
     pub message_template: String,
     pub html_template: Option<String>,
     pub default_vars: HashMap<String, serde_json::Value>,
+    /// Variants by locale; the fields above are the default locale
+    #[serde(default)]
+    pub translations: HashMap<String, TemplateTranslation>,
+}
+
+impl NotificationTemplate {
+    /// Builder: Add a variant for a locale
+    pub fn with_translation(mut self, locale: &str, translation: TemplateTranslation) -> Self {
+        self.translations.insert(i18n::normalize_locale(locale), translation);
+        self
+    }
+}
+
+/// Locale-specific variant of a template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateTranslation {
+    pub title_template: String,
+    pub message_template: String,
+    /// Falls back to the default locale's HTML when absent
+    pub html_template: Option<String>,
 }
 
 /// Template engine
 pub struct TemplateEngine {
     tera: Arc<RwLock<Tera>>,
     templates: Arc<RwLock<HashMap<String, NotificationTemplate>>>,
+    default_locale: String,
+    fallbacks: HashMap<String, Vec<String>>,
 }
 
 impl TemplateEngine {
     /// Create a new template engine
     pub fn new(templates_dir: Option<&str>) -> Result<Self> {
-        let tera = if let Some(dir) = templates_dir {
+        let mut tera = if let Some(dir) = templates_dir {
             Tera::new(&format!("{}/**/*.html", dir))
                 .map_err(|e| NotificationError::Template(format!("Failed to load templates: {}", e)))?
         } else {
             Tera::default()
         };
+        tera.register_filter("plural", plural_filter);
 
         Ok(Self {
             tera: Arc::new(RwLock::new(tera)),
             templates: Arc::new(RwLock::new(HashMap::new())),
+            default_locale: "en".to_string(),
+            fallbacks: HashMap::new(),
         })
     }
 
+    /// Builder: Set the locale templates are written in
+    pub fn with_default_locale(mut self, locale: &str) -> Self {
+        self.default_locale = i18n::normalize_locale(locale);
+        self
+    }
+
+    /// Builder: Set extra fallback locales by locale, tried before the default
+    pub fn with_fallbacks(mut self, fallbacks: HashMap<String, Vec<String>>) -> Self {
+        self.fallbacks = fallbacks
+            .into_iter()
+            .map(|(locale, chain)| (i18n::normalize_locale(&locale), chain))
+            .collect();
+        self
+    }
+
+    /// Locale templates are written in
+    pub fn default_locale(&self) -> &str {
+        &self.default_locale
+    }
+
     /// Register a template
-    pub async fn register_template(&self, template: NotificationTemplate) -> Result<()> {
+    pub async fn register_template(&self, mut template: NotificationTemplate) -> Result<()> {
+        template.translations = template
+            .translations
+            .into_iter()
+            .map(|(locale, translation)| (i18n::normalize_locale(&locale), translation))
+            .collect();
+
         // Add templates to Tera
         let mut tera = self.tera.write().await;
 
-        tera.add_raw_template(
-            &format!("{}_title", template.id),
+        Self::add_parts(
+            &mut tera,
+            &template.id,
             &template.title_template,
-        )
-        .map_err(|e| NotificationError::Template(format!("Invalid title template: {}", e)))?;
-
-        tera.add_raw_template(
-            &format!("{}_message", template.id),
             &template.message_template,
-        )
-        .map_err(|e| NotificationError::Template(format!("Invalid message template: {}", e)))?;
-
-        if let Some(html) = &template.html_template {
-            tera.add_raw_template(&format!("{}_html", template.id), html)
-                .map_err(|e| NotificationError::Template(format!("Invalid HTML template: {}", e)))?;
+            template.html_template.as_deref(),
+        )?;
+
+        for (locale, translation) in &template.translations {
+            Self::add_parts(
+                &mut tera,
+                &Self::variant_name(&template.id, Some(locale)),
+                &translation.title_template,
+                &translation.message_template,
+                translation.html_template.as_deref(),
+            )?;
         }
 
         drop(tera);
@@ -75,11 +134,83 @@ impl TemplateEngine {
         Ok(())
     }
 
-    /// Render a template
+    /// Add or replace the variant of a registered template for a locale
+    pub async fn register_translation(
+        &self,
+        template_id: &str,
+        locale: &str,
+        translation: TemplateTranslation,
+    ) -> Result<()> {
+        let locale = i18n::normalize_locale(locale);
+        let mut templates = self.templates.write().await;
+        let template = templates
+            .get_mut(template_id)
+            .ok_or_else(|| NotificationError::Template(format!("Template not found: {}", template_id)))?;
+
+        Self::add_parts(
+            &mut *self.tera.write().await,
+            &Self::variant_name(template_id, Some(&locale)),
+            &translation.title_template,
+            &translation.message_template,
+            translation.html_template.as_deref(),
+        )?;
+
+        template.translations.insert(locale, translation);
+        Ok(())
+    }
+
+    fn add_parts(
+        tera: &mut Tera,
+        name: &str,
+        title: &str,
+        message: &str,
+        html: Option<&str>,
+    ) -> Result<()> {
+        tera.add_raw_template(&format!("{}_title", name), title)
+            .map_err(|e| NotificationError::Template(format!("Invalid title template: {}", e)))?;
+
+        tera.add_raw_template(&format!("{}_message", name), message)
+            .map_err(|e| NotificationError::Template(format!("Invalid message template: {}", e)))?;
+
+        if let Some(html) = html {
+            tera.add_raw_template(&format!("{}_html", name), html)
+                .map_err(|e| NotificationError::Template(format!("Invalid HTML template: {}", e)))?;
+        }
+
+        Ok(())
+    }
+
+    /// Tera name prefix of a template variant; `None` is the default locale
+    fn variant_name(template_id: &str, locale: Option<&str>) -> String {
+        match locale {
+            Some(locale) => format!("{}@{}", template_id, locale),
+            None => template_id.to_string(),
+        }
+    }
+
+    /// Pick the variant to render for `locale`; `None` is the default locale
+    fn resolve_locale(&self, template: &NotificationTemplate, locale: &str) -> Option<String> {
+        i18n::fallback_chain(locale, &self.fallbacks, &self.default_locale)
+            .into_iter()
+            .take_while(|candidate| *candidate != self.default_locale)
+            .find(|candidate| template.translations.contains_key(candidate))
+    }
+
+    /// Render a template in the default locale
     pub async fn render(
         &self,
         template_id: &str,
         vars: &HashMap<String, serde_json::Value>,
+    ) -> Result<RenderedTemplate> {
+        self.render_localized(template_id, vars, None).await
+    }
+
+    /// Render a template in a locale, falling back along its fallback chain
+    pub async fn render_localized(
+        &self,
+        template_id: &str,
+        vars: &HashMap<String, serde_json::Value>,
+        locale: Option<&str>,
     ) -> Result<RenderedTemplate> {
         let template = self
             .templates
@@ -98,22 +229,33 @@ impl TemplateEngine {
             context.insert(key, value);
         }
 
+        let variant = self.resolve_locale(&template, locale.unwrap_or(&self.default_locale));
+        let resolved_locale = variant.clone().unwrap_or_else(|| self.default_locale.clone());
+        context.insert("locale", &resolved_locale);
+
+        let name = Self::variant_name(template_id, variant.as_deref());
+        let has_html = match &variant {
+            Some(locale) => template.translations[locale].html_template.is_some(),
+            None => false,
+        };
+        let html_name = if has_html { name.clone() } else { template_id.to_string() };
+
         let tera = self.tera.read().await;
 
         // Render title
         let title = tera
-            .render(&format!("{}_title", template_id), &context)
+            .render(&format!("{}_title", name), &context)
             .map_err(|e| NotificationError::Template(format!("Failed to render title: {}", e)))?;
 
         // Render message
         let message = tera
-            .render(&format!("{}_message", template_id), &context)
+            .render(&format!("{}_message", name), &context)
             .map_err(|e| NotificationError::Template(format!("Failed to render message: {}", e)))?;
 
         // Render HTML if available
-        let html_message = if template.html_template.is_some() {
+        let html_message = if has_html || template.html_template.is_some() {
             Some(
-                tera.render(&format!("{}_html", template_id), &context)
+                tera.render(&format!("{}_html", html_name), &context)
                     .map_err(|e| NotificationError::Template(format!("Failed to render HTML: {}", e)))?,
             )
         } else {
@@ -124,25 +266,92 @@ impl TemplateEngine {
             title,
             message,
             html_message,
+            locale: resolved_locale,
         })
     }
 
     /// Apply template to a notification
+    ///
+    /// Renders in the locale from the notification's `locale` metadata, if
+    /// set, and records the locale actually rendered there.
     pub async fn apply_template(
         &self,
         notification: &mut Notification,
         template_id: &str,
     ) -> Result<()> {
-        let rendered = self.render(template_id, &notification.template_vars).await?;
+        let locale = notification
+            .metadata
+            .get("locale")
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+        let rendered = self
+            .render_localized(template_id, &notification.template_vars, locale.as_deref())
+            .await?;
 
         notification.title = rendered.title;
         notification.message = rendered.message;
         notification.html_message = rendered.html_message;
         notification.template_id = Some(template_id.to_string());
+        notification.set_metadata("locale", serde_json::json!(rendered.locale));
 
         Ok(())
     }
 
+    /// Report templates lacking a variant for any of `locales`
+    ///
+    /// The default locale is always complete. A variant without HTML is
+    /// reported when the default has HTML.
+    pub async fn validate_translations(&self, locales: &[String]) -> TranslationReport {
+        let locales: Vec<String> = locales
+            .iter()
+            .map(|locale| i18n::normalize_locale(locale))
+            .filter(|locale| *locale != self.default_locale)
+            .collect();
+
+        let templates = self.templates.read().await;
+        let mut ids: Vec<&String> = templates.keys().collect();
+        ids.sort();
+
+        let mut report = TranslationReport {
+            templates_checked: ids.len(),
+            locales: locales.clone(),
+            ..Default::default()
+        };
+
+        for id in ids {
+            let template = &templates[id];
+
+            for locale in &locales {
+                let part = match template.translations.get(locale) {
+                    None => "all",
+                    Some(t) if t.html_template.is_none() && template.html_template.is_some() => "html",
+                    Some(_) => continue,
+                };
+                report.missing.push(MissingTranslation {
+                    template_id: id.clone(),
+                    locale: locale.clone(),
+                    part: part.to_string(),
+                    resolved_locale: self
+                        .resolve_locale(template, locale)
+                        .unwrap_or_else(|| self.default_locale.clone()),
+                });
+            }
+
+            let mut unused: Vec<String> = template
+                .translations
+                .keys()
+                .filter(|locale| !locales.contains(locale))
+                .cloned()
+                .collect();
+            if !unused.is_empty() {
+                unused.sort();
+                report.unused.insert(id.clone(), unused);
+            }
+        }
+
+        report
+    }
+
     /// Get template
     pub async fn get_template(&self, template_id: &str) -> Option<NotificationTemplate> {
         self.templates.read().await.get(template_id).cloned()
@@ -181,6 +390,7 @@ impl TemplateEngine {
                 <p>Get started by creating your first case.</p>"#.to_string(),
             ),
             default_vars: HashMap::new(),
+            translations: HashMap::new(),
         })
         .await?;
 
@@ -197,6 +407,7 @@ impl TemplateEngine {
                 <p><a href="{{ case_url }}">View Case</a></p>"#.to_string(),
             ),
             default_vars: HashMap::new(),
+            translations: HashMap::new(),
         })
         .await?;
 
@@ -213,6 +424,7 @@ impl TemplateEngine {
                 <p><a href="{{ report_url }}">Download Report</a></p>"#.to_string(),
             ),
             default_vars: HashMap::new(),
+            translations: HashMap::new(),
         })
         .await?;
 
@@ -230,6 +442,7 @@ impl TemplateEngine {
                 <p><a href="{{ comment_url }}">View Comment</a></p>"#.to_string(),
             ),
             default_vars: HashMap::new(),
+            translations: HashMap::new(),
         })
         .await?;
 
@@ -246,6 +459,7 @@ impl TemplateEngine {
                 <p><a href="{{ results_url }}">View Results</a></p>"#.to_string(),
             ),
             default_vars: HashMap::new(),
+            translations: HashMap::new(),
         })
         .await?;
 
@@ -262,6 +476,7 @@ impl TemplateEngine {
                 <p><a href="{{ action_url }}">Take Action</a></p>"#.to_string(),
             ),
             default_vars: HashMap::new(),
+            translations: HashMap::new(),
         })
         .await?;
 
@@ -275,6 +490,29 @@ pub struct RenderedTemplate {
     pub title: String,
     pub message: String,
     pub html_message: Option<String>,
+    /// Locale of the variant rendered
+    pub locale: String,
+}
+
+/// `plural` filter: picks the form for the count's CLDR category in `locale`
+fn plural_filter(
+    value: &tera::Value,
+    args: &HashMap<String, tera::Value>,
+) -> tera::Result<tera::Value> {
+    let count = value
+        .as_u64()
+        .or_else(|| value.as_f64().filter(|v| *v >= 0.0).map(|v| v as u64))
+        .ok_or_else(|| tera::Error::msg("plural filter expects a non-negative count"))?;
+    let locale = args.get("locale").and_then(|v| v.as_str()).unwrap_or("en");
+    let forms: HashMap<String, String> = args
+        .iter()
+        .filter(|(name, _)| name.as_str() != "locale")
+        .filter_map(|(name, form)| form.as_str().map(|form| (name.clone(), form.to_string())))
+        .collect();
+
+    i18n::format_plural(locale, count, &forms)
+        .map(tera::Value::String)
+        .ok_or_else(|| tera::Error::msg("plural filter needs an `other` form"))
 }
 
 #[cfg(test)]
@@ -293,6 +531,7 @@ mod tests {
             message_template: "Welcome {{ name }}, you have {{ count }} messages.".to_string(),
             html_template: None,
             default_vars: HashMap::new(),
+            translations: HashMap::new(),
         };
 
         engine.register_template(template).await.unwrap();
@@ -306,4 +545,59 @@ mod tests {
         assert_eq!(rendered.title, "Hello Alice!");
         assert_eq!(rendered.message, "Welcome Alice, you have 5 messages.");
     }
+
+    #[tokio::test]
+    async fn test_localized_rendering() {
+        let engine = TemplateEngine::new(None).unwrap();
+
+        let template = NotificationTemplate {
+            id: "files".to_string(),
+            name: "Files".to_string(),
+            description: "Files uploaded".to_string(),
+            title_template: "Upload complete".to_string(),
+            message_template: "{{ count | plural(one=\"# file\", other=\"# files\", locale=locale) }}"
+                .to_string(),
+            html_template: Some("<p>{{ count }}</p>".to_string()),
+            default_vars: HashMap::new(),
+            translations: HashMap::new(),
+        }
+        .with_translation(
+            "fr",
+            TemplateTranslation {
+                title_template: "Envoi terminé".to_string(),
+                message_template:
+                    "{{ count | plural(one=\"# fichier\", other=\"# fichiers\", locale=locale) }}"
+                        .to_string(),
+                html_template: None,
+            },
+        );
+        engine.register_template(template).await.unwrap();
+
+        let mut vars = HashMap::new();
+        vars.insert("count".to_string(), serde_json::json!(0));
+
+        let rendered = engine.render_localized("files", &vars, Some("fr_CA")).await.unwrap();
+        assert_eq!(rendered.locale, "fr");
+        assert_eq!(rendered.title, "Envoi terminé");
+        assert_eq!(rendered.message, "0 fichier");
+        assert_eq!(rendered.html_message.as_deref(), Some("<p>0</p>"));
+
+        let rendered = engine.render_localized("files", &vars, Some("de")).await.unwrap();
+        assert_eq!(rendered.locale, "en");
+        assert_eq!(rendered.message, "0 files");
+
+        let report = engine
+            .validate_translations(&["en".to_string(), "fr".to_string(), "de".to_string()])
+            .await;
+        assert_eq!(report.templates_checked, 1);
+        assert_eq!(report.missing.len(), 2);
+        assert!(report
+            .missing
+            .iter()
+            .any(|m| m.locale == "fr" && m.part == "html"));
+        assert!(report
+            .missing
+            .iter()
+            .any(|m| m.locale == "de" && m.part == "all" && m.resolved_locale == "en"));
+    }
 }
@@ -0,0 +1,421 @@
+//! Delivery rate limiting
+//!
+//! Token buckets bound deliveries globally, per recipient and channel, and
+//! per channel.
+//! Deliveries over a limit are delayed, collapsed into a summary or dropped
+//! according to the configured [`OverflowStrategy`].
+
+use crate::config::{OverflowStrategy, RateLimit, RateLimitConfig};
+use crate::types::{Notification, Priority};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Token bucket refilled continuously at a fixed rate
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    limit: RateLimit,
+    /// May go negative when deliveries are reserved ahead of time
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: RateLimit, now: Instant) -> Self {
+        Self {
+            limit,
+            tokens: limit.burst as f64,
+            updated_at: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.limit.per_second).min(self.limit.burst as f64);
+        self.updated_at = now;
+    }
+
+    /// Time until a token is available
+    fn wait(&mut self, now: Instant) -> Duration {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - self.tokens) / self.limit.per_second)
+        }
+    }
+
+    fn take(&mut self) {
+        self.tokens -= 1.0;
+    }
+
+    /// Check if the bucket is back to full and can be forgotten
+    fn is_full(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        self.tokens >= self.limit.burst as f64
+    }
+}
+
+/// Outcome of checking a delivery against the rate limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Admission {
+    /// Deliver now
+    Deliver,
+    /// Held until the limits allow; returned later by `take_due`
+    Delayed,
+    /// Folded into a pending summary for the recipient and channel
+    Collapsed,
+    /// Discarded
+    Dropped,
+}
+
+struct Deferred {
+    notification: Notification,
+    channel: String,
+    due_at: Instant,
+}
+
+/// Overflow for one recipient and channel awaiting a summary delivery
+struct CollapsedBatch {
+    latest: Notification,
+    count: usize,
+    due_at: Instant,
+}
+
+/// Rate limiter statistics
+#[derive(Debug, Clone, Default)]
+pub struct ThrottleStats {
+    /// Delayed deliveries and summaries waiting for capacity
+    pub waiting: usize,
+    pub delayed: usize,
+    pub collapsed: usize,
+    pub dropped: usize,
+}
+
+/// Applies rate limits and overflow handling to channel deliveries
+pub(crate) struct Throttle {
+    config: RateLimitConfig,
+    user_limits: Vec<RateLimit>,
+    global: Mutex<Option<TokenBucket>>,
+    users: DashMap<(String, String), Vec<TokenBucket>>,
+    channels: DashMap<String, TokenBucket>,
+    deferred: Mutex<Vec<Deferred>>,
+    collapsed: Mutex<HashMap<(String, String), CollapsedBatch>>,
+    delayed_count: AtomicUsize,
+    collapsed_count: AtomicUsize,
+    dropped_count: AtomicUsize,
+}
+
+impl Throttle {
+    pub(crate) fn new(config: RateLimitConfig) -> Self {
+        let now = Instant::now();
+        Self {
+            global: Mutex::new(config.global.map(|limit| TokenBucket::new(limit, now))),
+            user_limits: config.user_limits(),
+            config,
+            users: DashMap::new(),
+            channels: DashMap::new(),
+            deferred: Mutex::new(Vec::new()),
+            collapsed: Mutex::new(HashMap::new()),
+            delayed_count: AtomicUsize::new(0),
+            collapsed_count: AtomicUsize::new(0),
+            dropped_count: AtomicUsize::new(0),
+        }
+    }
+
+    /// Check a delivery of `notification` via `channel` against the limits
+    pub(crate) fn admit(&self, notification: &Notification, channel: &str) -> Admission {
+        self.admit_at(notification, channel, Instant::now())
+    }
+
+    fn admit_at(&self, notification: &Notification, channel: &str, now: Instant) -> Admission {
+        if !self.config.is_active()
+            || (self.config.exempt_critical && notification.priority == Priority::Critical)
+        {
+            return Admission::Deliver;
+        }
+
+        let key = (notification.user_id.clone(), channel.to_string());
+        if self.config.overflow == OverflowStrategy::Collapse {
+            let mut collapsed = self.collapsed.lock();
+            if let Some(batch) = collapsed.get_mut(&key) {
+                batch.latest = notification.clone();
+                batch.count += 1;
+                self.collapsed_count.fetch_add(1, Ordering::Relaxed);
+                return Admission::Collapsed;
+            }
+        }
+
+        let wait = self.acquire(&notification.user_id, channel, now);
+        if wait.is_zero() {
+            return Admission::Deliver;
+        }
+
+        match self.config.overflow {
+            OverflowStrategy::Drop => {
+                self.dropped_count.fetch_add(1, Ordering::Relaxed);
+                Admission::Dropped
+            }
+            OverflowStrategy::Delay if wait > Duration::from_millis(self.config.max_delay_ms) => {
+                self.dropped_count.fetch_add(1, Ordering::Relaxed);
+                Admission::Dropped
+            }
+            OverflowStrategy::Delay => {
+                self.reserve(&notification.user_id, channel, now);
+                self.deferred.lock().push(Deferred {
+                    notification: notification.clone(),
+                    channel: channel.to_string(),
+                    due_at: now + wait,
+                });
+                self.delayed_count.fetch_add(1, Ordering::Relaxed);
+                Admission::Delayed
+            }
+            OverflowStrategy::Collapse => {
+                self.reserve(&notification.user_id, channel, now);
+                self.collapsed.lock().insert(
+                    key,
+                    CollapsedBatch {
+                        latest: notification.clone(),
+                        count: 1,
+                        due_at: now + wait,
+                    },
+                );
+                self.collapsed_count.fetch_add(1, Ordering::Relaxed);
+                Admission::Collapsed
+            }
+        }
+    }
+
+    /// Take a token from every applicable bucket if all have one
+    ///
+    /// Returns the wait until they all would, without taking anything.
+    fn acquire(&self, user_id: &str, channel: &str, now: Instant) -> Duration {
+        let wait = self.with_buckets(user_id, channel, now, |buckets| {
+            buckets.iter_mut().map(|bucket| bucket.wait(now)).max().unwrap_or_default()
+        });
+        if wait.is_zero() {
+            self.reserve(user_id, channel, now);
+        }
+        wait
+    }
+
+    /// Take a token from every applicable bucket, going into debt if needed
+    fn reserve(&self, user_id: &str, channel: &str, now: Instant) {
+        self.with_buckets(user_id, channel, now, |buckets| {
+            for bucket in buckets {
+                bucket.take();
+            }
+        });
+    }
+
+    fn with_buckets<T>(
+        &self,
+        user_id: &str,
+        channel: &str,
+        now: Instant,
+        f: impl FnOnce(&mut [&mut TokenBucket]) -> T,
+    ) -> T {
+        // Lock order is always global, user, channel
+        let mut global = self.global.lock();
+        let mut user = (!self.user_limits.is_empty()).then(|| {
+            self.users.entry((user_id.to_string(), channel.to_string())).or_insert_with(|| {
+                self.user_limits.iter().map(|limit| TokenBucket::new(*limit, now)).collect()
+            })
+        });
+        let mut channel = self.config.per_channel.get(channel).map(|limit| {
+            self.channels
+                .entry(channel.to_string())
+                .or_insert_with(|| TokenBucket::new(*limit, now))
+        });
+
+        let mut buckets: Vec<&mut TokenBucket> = Vec::with_capacity(self.user_limits.len() + 2);
+        buckets.extend(global.as_mut());
+        if let Some(user) = user.as_mut() {
+            buckets.extend(user.iter_mut());
+        }
+        buckets.extend(channel.as_deref_mut());
+        f(&mut buckets)
+    }
+
+    /// Remove and return the delayed deliveries and summaries that are due
+    pub(crate) fn take_due(&self) -> Vec<(Notification, String)> {
+        self.take_due_at(Instant::now())
+    }
+
+    fn take_due_at(&self, now: Instant) -> Vec<(Notification, String)> {
+        let mut due = Vec::new();
+
+        {
+            let mut deferred = self.deferred.lock();
+            let (ready, waiting): (Vec<_>, Vec<_>) =
+                std::mem::take(&mut *deferred).into_iter().partition(|d| d.due_at <= now);
+            *deferred = waiting;
+            due.extend(ready.into_iter().map(|d| (d.notification, d.channel)));
+        }
+
+        let mut collapsed = self.collapsed.lock();
+        let ready: Vec<_> = collapsed
+            .iter()
+            .filter(|(_, batch)| batch.due_at <= now)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in ready {
+            if let Some(batch) = collapsed.remove(&key) {
+                due.push((summarize(batch), key.1));
+            }
+        }
+
+        due
+    }
+
+    /// Forget buckets of idle users and channels
+    pub(crate) fn prune(&self) {
+        let now = Instant::now();
+        self.users
+            .retain(|_, buckets| !buckets.iter_mut().all(|bucket| bucket.is_full(now)));
+        self.channels.retain(|_, bucket| !bucket.is_full(now));
+    }
+
+    pub(crate) fn stats(&self) -> ThrottleStats {
+        ThrottleStats {
+            waiting: self.deferred.lock().len() + self.collapsed.lock().len(),
+            delayed: self.delayed_count.load(Ordering::Relaxed),
+            collapsed: self.collapsed_count.load(Ordering::Relaxed),
+            dropped: self.dropped_count.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Build the notification delivered for a collapsed batch
+fn summarize(batch: CollapsedBatch) -> Notification {
+    let mut notification = batch.latest;
+    if batch.count > 1 {
+        notification.message = format!(
+            "{} and {} more notifications",
+            notification.title,
+            batch.count - 1
+        );
+        notification.title = format!("{} new notifications", batch.count);
+        notification.html_message = None;
+        notification.set_metadata("collapsed_count", serde_json::json!(batch.count));
+    }
+    notification
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::NotificationLevel;
+
+    fn notification(user_id: &str) -> Notification {
+        Notification::new(user_id, NotificationLevel::Info, "Case updated", "Details")
+    }
+
+    #[test]
+    fn test_token_bucket() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(RateLimit::new(2.0, 2), now);
+
+        assert_eq!(bucket.wait(now), Duration::ZERO);
+        bucket.take();
+        bucket.take();
+        assert_eq!(bucket.wait(now), Duration::from_millis(500));
+
+        // Reserving ahead pushes the next slot back
+        bucket.take();
+        assert_eq!(bucket.wait(now), Duration::from_secs(1));
+        assert_eq!(bucket.wait(now + Duration::from_secs(1)), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_delay_and_drop() {
+        let config = RateLimitConfig {
+            max_per_user_per_minute: 1,
+            max_per_user_per_hour: 0,
+            max_per_user_per_day: 0,
+            overflow: OverflowStrategy::Delay,
+            ..Default::default()
+        };
+        let throttle = Throttle::new(config.clone());
+        let now = Instant::now();
+
+        assert_eq!(
+            throttle.admit_at(&notification("a"), "email", now),
+            Admission::Deliver
+        );
+        assert_eq!(
+            throttle.admit_at(&notification("a"), "email", now),
+            Admission::Delayed
+        );
+        // Limits are per user and channel
+        assert_eq!(
+            throttle.admit_at(&notification("b"), "email", now),
+            Admission::Deliver
+        );
+        assert_eq!(
+            throttle.admit_at(&notification("a"), "in_app", now),
+            Admission::Deliver
+        );
+
+        assert!(throttle.take_due_at(now).is_empty());
+        assert_eq!(throttle.take_due_at(now + Duration::from_secs(60)).len(), 1);
+
+        let mut critical = notification("a");
+        critical.priority = Priority::Critical;
+        assert_eq!(
+            throttle.admit_at(&critical, "email", now),
+            Admission::Deliver
+        );
+
+        let throttle = Throttle::new(config.with_overflow(OverflowStrategy::Drop));
+        assert_eq!(
+            throttle.admit_at(&notification("a"), "sms", now),
+            Admission::Deliver
+        );
+        assert_eq!(
+            throttle.admit_at(&notification("a"), "sms", now),
+            Admission::Dropped
+        );
+        assert_eq!(throttle.stats().dropped, 1);
+    }
+
+    #[test]
+    fn test_collapse() {
+        let config = RateLimitConfig {
+            max_per_user_per_minute: 0,
+            max_per_user_per_hour: 0,
+            max_per_user_per_day: 0,
+            ..Default::default()
+        }
+        .with_channel("email", RateLimit::new(1.0, 1))
+        .with_overflow(OverflowStrategy::Collapse);
+        let throttle = Throttle::new(config);
+        let now = Instant::now();
+
+        assert_eq!(
+            throttle.admit_at(&notification("a"), "email", now),
+            Admission::Deliver
+        );
+        for _ in 0..3 {
+            assert_eq!(
+                throttle.admit_at(&notification("a"), "email", now),
+                Admission::Collapsed
+            );
+        }
+        // Other channels are not limited
+        assert_eq!(
+            throttle.admit_at(&notification("a"), "in_app", now),
+            Admission::Deliver
+        );
+        assert_eq!(throttle.stats().waiting, 1);
+
+        let due = throttle.take_due_at(now + Duration::from_secs(1));
+        assert_eq!(due.len(), 1);
+        let (summary, channel) = &due[0];
+        assert_eq!(channel, "email");
+        assert_eq!(summary.title, "3 new notifications");
+        assert_eq!(summary.metadata["collapsed_count"], 3);
+    }
+}
@@ -0,0 +1,209 @@
+//! Topic subscriptions
+//!
+//! Topics are colon-separated names such as `case:1234` or
+//! `org:acme:alerts`. A notification published to a topic fans out to every
+//! user subscribed to it. Subscriptions may use `*` to match any single
+//! segment (`org:acme:*`) and carry an opaque unsubscribe token suitable for
+//! one-click unsubscribe links.
+
+use crate::error::{NotificationError, Result};
+use crate::types::Notification;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Separator between topic segments
+pub const TOPIC_SEPARATOR: char = ':';
+
+/// Segment matching any single segment in a subscription pattern
+pub const TOPIC_WILDCARD: &str = "*";
+
+/// Maximum topic length in bytes
+const MAX_TOPIC_LEN: usize = 255;
+
+/// A user's subscription to a topic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopicSubscription {
+    pub id: Uuid,
+    pub user_id: String,
+    /// Topic or pattern; `*` segments match any single segment
+    pub topic: String,
+    /// Channels to deliver on; empty means the publisher's or user's defaults
+    pub channels: Vec<String>,
+    /// Opaque token that removes this subscription without authentication
+    pub unsubscribe_token: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TopicSubscription {
+    /// Create a subscription to a topic
+    pub fn new(user_id: impl Into<String>, topic: impl Into<String>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            user_id: user_id.into(),
+            topic: topic.into(),
+            channels: Vec::new(),
+            unsubscribe_token: generate_unsubscribe_token(),
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Deliver on the given channels instead of the defaults
+    pub fn with_channels(mut self, channels: Vec<String>) -> Self {
+        self.channels = channels;
+        self
+    }
+
+    /// Check if the subscription covers a published topic
+    pub fn matches(&self, topic: &str) -> bool {
+        topic_matches(&self.topic, topic)
+    }
+
+    /// Build the notification delivered to this subscriber
+    ///
+    /// The published notification is copied with a fresh id and the
+    /// subscriber as recipient; the topic and unsubscribe token are added to
+    /// the metadata so channels can render an unsubscribe link.
+    pub fn notification_for(&self, topic: &str, published: &Notification) -> Notification {
+        let mut notification = published.clone();
+        notification.id = Uuid::new_v4();
+        notification.user_id = self.user_id.clone();
+        notification.created_at = Utc::now();
+        notification.set_metadata("topic", serde_json::json!(topic));
+        notification.set_metadata("topic_subscription_id", serde_json::json!(self.id));
+        notification.set_metadata(
+            "unsubscribe_token",
+            serde_json::json!(self.unsubscribe_token),
+        );
+        notification
+    }
+
+    /// Resolve delivery channels: subscription override, then publisher's choice
+    pub fn resolve_channels(&self, published: &[String]) -> Vec<String> {
+        if self.channels.is_empty() {
+            published.to_vec()
+        } else {
+            self.channels.clone()
+        }
+    }
+}
+
+/// Validate a topic or subscription pattern
+///
+/// Segments must be non-empty and contain only ASCII letters, digits, `-`,
+/// `_` or `.`. Wildcard segments are accepted only when `allow_wildcards` is
+/// set, i.e. for subscriptions but not for publishing.
+pub fn validate_topic(topic: &str, allow_wildcards: bool) -> Result<()> {
+    if topic.is_empty() || topic.len() > MAX_TOPIC_LEN {
+        return Err(NotificationError::InvalidNotification(format!(
+            "Topic must be between 1 and {} bytes",
+            MAX_TOPIC_LEN
+        )));
+    }
+
+    for segment in topic.split(TOPIC_SEPARATOR) {
+        if segment == TOPIC_WILDCARD {
+            if allow_wildcards {
+                continue;
+            }
+            return Err(NotificationError::InvalidNotification(format!(
+                "Cannot publish to wildcard topic: {}",
+                topic
+            )));
+        }
+
+        let valid = !segment.is_empty()
+            && segment
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            return Err(NotificationError::InvalidNotification(format!(
+                "Invalid topic segment '{}' in {}",
+                segment, topic
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check if a subscription pattern matches a published topic
+pub fn topic_matches(pattern: &str, topic: &str) -> bool {
+    let mut pattern_segments = pattern.split(TOPIC_SEPARATOR);
+    let mut topic_segments = topic.split(TOPIC_SEPARATOR);
+
+    loop {
+        match (pattern_segments.next(), topic_segments.next()) {
+            (None, None) => return true,
+            (Some(p), Some(t)) if p == TOPIC_WILDCARD || p == t => {}
+            _ => return false,
+        }
+    }
+}
+
+/// Check if a subscription pattern contains wildcards
+pub fn is_pattern(topic: &str) -> bool {
+    topic.split(TOPIC_SEPARATOR).any(|s| s == TOPIC_WILDCARD)
+}
+
+/// Generate a new unsubscribe token
+fn generate_unsubscribe_token() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::NotificationLevel;
+
+    #[test]
+    fn test_validate_topic() {
+        assert!(validate_topic("case:1234", false).is_ok());
+        assert!(validate_topic("org:acme:alerts", false).is_ok());
+        assert!(validate_topic("org:acme:*", true).is_ok());
+
+        assert!(validate_topic("org:acme:*", false).is_err());
+        assert!(validate_topic("", false).is_err());
+        assert!(validate_topic("case::1234", false).is_err());
+        assert!(validate_topic("case:12 34", false).is_err());
+    }
+
+    #[test]
+    fn test_topic_matches() {
+        assert!(topic_matches("case:1234", "case:1234"));
+        assert!(topic_matches("org:acme:*", "org:acme:alerts"));
+        assert!(topic_matches("org:*:alerts", "org:acme:alerts"));
+
+        assert!(!topic_matches("case:1234", "case:5678"));
+        assert!(!topic_matches("org:acme:*", "org:acme"));
+        assert!(!topic_matches("org:acme:*", "org:acme:alerts:critical"));
+        assert!(is_pattern("org:acme:*"));
+        assert!(!is_pattern("org:acme:alerts"));
+    }
+
+    #[test]
+    fn test_notification_for_subscriber() {
+        let subscription =
+            TopicSubscription::new("user1", "case:*").with_channels(vec!["email".to_string()]);
+        let other = TopicSubscription::new("user2", "case:1234");
+        assert_ne!(subscription.unsubscribe_token, other.unsubscribe_token);
+
+        let published = Notification::new("", NotificationLevel::Info, "Updated", "Case updated");
+        let notification = subscription.notification_for("case:1234", &published);
+
+        assert_ne!(notification.id, published.id);
+        assert_eq!(notification.user_id, "user1");
+        assert_eq!(notification.metadata["topic"], "case:1234");
+        assert_eq!(
+            notification.metadata["unsubscribe_token"],
+            subscription.unsubscribe_token.as_str()
+        );
+
+        let published_channels = vec!["in_app".to_string()];
+        assert_eq!(
+            subscription.resolve_channels(&published_channels),
+            vec!["email"]
+        );
+        assert_eq!(other.resolve_channels(&published_channels), vec!["in_app"]);
+    }
+}
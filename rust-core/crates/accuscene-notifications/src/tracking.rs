@@ -0,0 +1,375 @@
+//! Delivery tracking and provider status callbacks
+//!
+//! Every channel delivery moves through queued → sent → delivered → read,
+//! or ends bounced/failed. Transitions are persisted in the store together
+//! with a per-notification timeline. Email and SMS providers report the
+//! later stages through status webhooks; they correlate events by the
+//! `notification_id` passed to them (SendGrid custom args, a Twilio status
+//! callback query parameter).
+
+use crate::error::{NotificationError, Result};
+use crate::store::NotificationStore;
+use crate::types::{DeliveryEvent, DeliveryState, DeliveryStatus};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Source recorded for transitions made by the dispatcher
+pub const DISPATCHER_SOURCE: &str = "dispatcher";
+
+/// Status event reported by a delivery provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderEvent {
+    pub provider: String,
+    pub notification_id: Option<Uuid>,
+    pub channel: String,
+    pub state: DeliveryState,
+    pub occurred_at: DateTime<Utc>,
+    pub detail: Option<String>,
+}
+
+impl ProviderEvent {
+    fn into_delivery_event(self) -> Option<DeliveryEvent> {
+        let notification_id = self.notification_id?;
+        let mut event =
+            DeliveryEvent::new(notification_id, self.channel, self.state, self.provider)
+                .with_occurred_at(self.occurred_at);
+        event.detail = self.detail;
+        Some(event)
+    }
+}
+
+/// Raw status callback received from a provider webhook
+#[derive(Debug, Clone)]
+pub enum ProviderCallback {
+    /// SendGrid event webhook body (a JSON array of events)
+    SendGrid(serde_json::Value),
+    /// Twilio status callback form parameters, including the callback URL's query
+    Twilio(HashMap<String, String>),
+    /// Events already normalized by the caller
+    Generic(Vec<ProviderEvent>),
+}
+
+impl ProviderCallback {
+    /// Normalize the callback into provider events
+    ///
+    /// Provider statuses with no delivery meaning (e.g. SendGrid `deferred`,
+    /// Twilio `queued`) are dropped.
+    pub fn into_events(self) -> Result<Vec<ProviderEvent>> {
+        match self {
+            ProviderCallback::SendGrid(body) => parse_sendgrid(body),
+            ProviderCallback::Twilio(params) => parse_twilio(&params),
+            ProviderCallback::Generic(events) => Ok(events),
+        }
+    }
+}
+
+fn parse_sendgrid(body: serde_json::Value) -> Result<Vec<ProviderEvent>> {
+    let serde_json::Value::Array(items) = body else {
+        return Err(NotificationError::InvalidNotification(
+            "SendGrid callback must be a JSON array".to_string(),
+        ));
+    };
+
+    let events = items
+        .iter()
+        .filter_map(|item| {
+            let state = match item.get("event")?.as_str()? {
+                "processed" => DeliveryState::Sent,
+                "delivered" => DeliveryState::Delivered,
+                "open" | "click" => DeliveryState::Read,
+                "bounce" => DeliveryState::Bounced,
+                "dropped" => DeliveryState::Failed,
+                _ => return None,
+            };
+
+            Some(ProviderEvent {
+                provider: "sendgrid".to_string(),
+                notification_id: item
+                    .get("notification_id")
+                    .and_then(|v| v.as_str())
+                    .and_then(|v| v.parse().ok()),
+                channel: "email".to_string(),
+                state,
+                occurred_at: item
+                    .get("timestamp")
+                    .and_then(|v| v.as_i64())
+                    .and_then(|ts| Utc.timestamp_opt(ts, 0).single())
+                    .unwrap_or_else(Utc::now),
+                detail: item
+                    .get("reason")
+                    .or_else(|| item.get("response"))
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+            })
+        })
+        .collect();
+
+    Ok(events)
+}
+
+fn parse_twilio(params: &HashMap<String, String>) -> Result<Vec<ProviderEvent>> {
+    let status =
+        params.get("MessageStatus").or_else(|| params.get("SmsStatus")).ok_or_else(|| {
+            NotificationError::InvalidNotification(
+                "Twilio callback is missing MessageStatus".to_string(),
+            )
+        })?;
+
+    let state = match status.as_str() {
+        "sent" => DeliveryState::Sent,
+        "delivered" => DeliveryState::Delivered,
+        "read" => DeliveryState::Read,
+        "undelivered" => DeliveryState::Bounced,
+        "failed" => DeliveryState::Failed,
+        "canceled" => DeliveryState::Cancelled,
+        _ => return Ok(Vec::new()),
+    };
+
+    let detail = params.get("ErrorCode").map(|code| match params.get("ErrorMessage") {
+        Some(message) => format!("{}: {}", code, message),
+        None => code.clone(),
+    });
+
+    Ok(vec![ProviderEvent {
+        provider: "twilio".to_string(),
+        notification_id: params.get("notification_id").and_then(|v| v.parse().ok()),
+        channel: "sms".to_string(),
+        state,
+        occurred_at: Utc::now(),
+        detail,
+    }])
+}
+
+/// Outcome of ingesting a provider callback
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IngestSummary {
+    /// Events that advanced a delivery
+    pub accepted: usize,
+    /// Events for unknown deliveries, or out of order and rejected
+    pub ignored: usize,
+}
+
+/// Current per-channel statuses and full history of a notification
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryTimeline {
+    pub notification_id: Uuid,
+    pub statuses: Vec<DeliveryStatus>,
+    pub events: Vec<DeliveryEvent>,
+}
+
+/// Persists delivery status transitions
+pub struct DeliveryTracker {
+    store: Arc<NotificationStore>,
+}
+
+impl DeliveryTracker {
+    /// Create a new delivery tracker
+    pub fn new(store: Arc<NotificationStore>) -> Self {
+        Self { store }
+    }
+
+    /// Record a transition, creating the delivery if it isn't tracked yet
+    ///
+    /// Returns `false` if the transition is not allowed from the current state.
+    pub async fn record(&self, event: DeliveryEvent) -> Result<bool> {
+        let current = self.store.get_delivery_status(event.notification_id, &event.channel).await?;
+        self.apply(current, event, None).await
+    }
+
+    /// Record the outcome of a dispatcher delivery attempt
+    pub async fn record_status(&self, status: &DeliveryStatus) -> Result<bool> {
+        let mut event = DeliveryEvent::new(
+            status.notification_id,
+            status.channel.clone(),
+            status.status,
+            DISPATCHER_SOURCE,
+        );
+        event.detail = status.error_message.clone();
+
+        let current =
+            self.store.get_delivery_status(status.notification_id, &status.channel).await?;
+        self.apply(current, event, Some(status)).await
+    }
+
+    /// Record a transition only for a delivery that is already tracked
+    pub async fn advance(&self, event: DeliveryEvent) -> Result<bool> {
+        match self.store.get_delivery_status(event.notification_id, &event.channel).await? {
+            Some(current) => self.apply(Some(current), event, None).await,
+            None => Ok(false),
+        }
+    }
+
+    /// Apply the status events of a provider webhook
+    pub async fn ingest(&self, callback: ProviderCallback) -> Result<IngestSummary> {
+        let mut summary = IngestSummary::default();
+
+        for event in callback.into_events()? {
+            let accepted = match event.into_delivery_event() {
+                Some(event) => self.advance(event).await?,
+                None => false,
+            };
+
+            if accepted {
+                summary.accepted += 1;
+            } else {
+                summary.ignored += 1;
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Get the delivery timeline of a notification
+    pub async fn timeline(&self, notification_id: Uuid) -> Result<DeliveryTimeline> {
+        Ok(DeliveryTimeline {
+            notification_id,
+            statuses: self.store.get_delivery_statuses(notification_id).await?,
+            events: self.store.get_delivery_events(notification_id).await?,
+        })
+    }
+
+    async fn apply(
+        &self,
+        current: Option<DeliveryStatus>,
+        event: DeliveryEvent,
+        attempt: Option<&DeliveryStatus>,
+    ) -> Result<bool> {
+        if let Some(current) = &current {
+            if !current.status.can_transition_to(event.state) {
+                tracing::debug!(
+                    "Ignoring {:?} for notification {} via {}: already {:?}",
+                    event.state,
+                    event.notification_id,
+                    event.channel,
+                    current.status
+                );
+                return Ok(false);
+            }
+        }
+
+        let status = match attempt {
+            Some(attempt) => attempt.clone(),
+            None => next_status(current, &event),
+        };
+
+        self.store.save_delivery_status(&status).await?;
+        self.store.save_delivery_event(&event).await?;
+
+        Ok(true)
+    }
+}
+
+/// Derive the stored status after a provider or read event
+fn next_status(current: Option<DeliveryStatus>, event: &DeliveryEvent) -> DeliveryStatus {
+    let mut status = current.unwrap_or(DeliveryStatus {
+        notification_id: event.notification_id,
+        channel: event.channel.clone(),
+        status: event.state,
+        attempts: 0,
+        last_attempt_at: None,
+        delivered_at: None,
+        error_message: None,
+    });
+
+    status.status = event.state;
+    match event.state {
+        DeliveryState::Delivered | DeliveryState::Read => {
+            status.delivered_at = status.delivered_at.or(Some(event.occurred_at));
+        }
+        DeliveryState::Bounced | DeliveryState::Failed => {
+            status.error_message = event.detail.clone();
+        }
+        _ => {}
+    }
+
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_transitions() {
+        use DeliveryState::*;
+
+        assert!(Pending.can_transition_to(Sent));
+        assert!(Sent.can_transition_to(Read));
+        assert!(Failed.can_transition_to(Sent));
+        assert!(Sent.can_transition_to(Bounced));
+
+        assert!(!Delivered.can_transition_to(Sent));
+        assert!(!Delivered.can_transition_to(Bounced));
+        assert!(!Sent.can_transition_to(Sent));
+        assert!(!Read.can_transition_to(Delivered));
+        assert!(!Bounced.can_transition_to(Delivered));
+    }
+
+    #[test]
+    fn test_sendgrid_callback() {
+        let id = Uuid::new_v4();
+        let body = serde_json::json!([
+            {"event": "processed", "timestamp": 1700000000, "notification_id": id.to_string()},
+            {"event": "deferred", "timestamp": 1700000001, "notification_id": id.to_string()},
+            {"event": "bounce", "timestamp": 1700000002, "reason": "550 mailbox unavailable"},
+        ]);
+
+        let events = ProviderCallback::SendGrid(body).into_events().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].state, DeliveryState::Sent);
+        assert_eq!(events[0].notification_id, Some(id));
+        assert_eq!(events[0].occurred_at.timestamp(), 1700000000);
+        assert_eq!(events[1].state, DeliveryState::Bounced);
+        assert_eq!(events[1].notification_id, None);
+        assert_eq!(events[1].detail.as_deref(), Some("550 mailbox unavailable"));
+        assert!(events[1].clone().into_delivery_event().is_none());
+
+        assert!(ProviderCallback::SendGrid(serde_json::json!({})).into_events().is_err());
+    }
+
+    #[test]
+    fn test_twilio_callback() {
+        let id = Uuid::new_v4();
+        let mut params = HashMap::new();
+        params.insert("notification_id".to_string(), id.to_string());
+        params.insert("MessageStatus".to_string(), "undelivered".to_string());
+        params.insert("ErrorCode".to_string(), "30003".to_string());
+
+        let events = ProviderCallback::Twilio(params.clone()).into_events().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].channel, "sms");
+        assert_eq!(events[0].state, DeliveryState::Bounced);
+        assert_eq!(events[0].detail.as_deref(), Some("30003"));
+
+        params.insert("MessageStatus".to_string(), "queued".to_string());
+        assert!(ProviderCallback::Twilio(params).into_events().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_next_status() {
+        let id = Uuid::new_v4();
+        let sent = next_status(
+            None,
+            &DeliveryEvent::new(id, "email", DeliveryState::Sent, "sendgrid"),
+        );
+        assert_eq!(sent.status, DeliveryState::Sent);
+        assert!(sent.delivered_at.is_none());
+
+        let delivered = next_status(
+            Some(sent),
+            &DeliveryEvent::new(id, "email", DeliveryState::Delivered, "sendgrid"),
+        );
+        assert_eq!(delivered.status, DeliveryState::Delivered);
+        let delivered_at = delivered.delivered_at;
+        assert!(delivered_at.is_some());
+
+        let read = next_status(
+            Some(delivered),
+            &DeliveryEvent::new(id, "email", DeliveryState::Read, "sendgrid"),
+        );
+        assert_eq!(read.delivered_at, delivered_at);
+    }
+}
@@ -262,13 +262,145 @@ pub struct DeliveryStatus {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum DeliveryState {
+    /// Queued for dispatch
     Pending,
     Processing,
+    /// Handed to the provider; awaiting delivery confirmation
+    Sent,
     Delivered,
+    Read,
+    /// Rejected by the recipient's server or carrier
+    Bounced,
     Failed,
     Cancelled,
 }
 
+impl DeliveryState {
+    /// Position in the queued → sent → delivered → read progression
+    fn progress(self) -> u8 {
+        match self {
+            DeliveryState::Pending => 0,
+            DeliveryState::Processing => 1,
+            DeliveryState::Sent => 2,
+            DeliveryState::Delivered => 3,
+            DeliveryState::Read => 4,
+            DeliveryState::Bounced | DeliveryState::Failed | DeliveryState::Cancelled => 0,
+        }
+    }
+
+    /// Check if the delivery reached the provider or recipient
+    pub fn is_success(self) -> bool {
+        matches!(
+            self,
+            DeliveryState::Sent | DeliveryState::Delivered | DeliveryState::Read
+        )
+    }
+
+    /// Check if no further transitions are accepted
+    pub fn is_terminal(self) -> bool {
+        matches!(
+            self,
+            DeliveryState::Read | DeliveryState::Bounced | DeliveryState::Cancelled
+        )
+    }
+
+    /// Check if a status update may move a delivery from this state to `next`
+    ///
+    /// Progress only moves forward, so late or duplicate provider events are
+    /// rejected. Failed deliveries may be retried; failures, bounces and
+    /// cancellations are only accepted before delivery is confirmed.
+    pub fn can_transition_to(self, next: DeliveryState) -> bool {
+        if self.is_terminal() {
+            return false;
+        }
+
+        match next {
+            DeliveryState::Failed | DeliveryState::Bounced | DeliveryState::Cancelled => {
+                self.progress() < DeliveryState::Delivered.progress()
+            }
+            _ if self == DeliveryState::Failed => next != DeliveryState::Read,
+            _ => next.progress() > self.progress(),
+        }
+    }
+}
+
+/// Entry in a notification's delivery timeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryEvent {
+    pub id: Uuid,
+    pub notification_id: Uuid,
+    pub channel: String,
+    pub state: DeliveryState,
+    /// Origin of the event, e.g. `dispatcher` or a provider name
+    pub source: String,
+    pub detail: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl DeliveryEvent {
+    /// Create an event that occurred now
+    pub fn new(
+        notification_id: Uuid,
+        channel: impl Into<String>,
+        state: DeliveryState,
+        source: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            notification_id,
+            channel: channel.into(),
+            state,
+            source: source.into(),
+            detail: None,
+            occurred_at: Utc::now(),
+        }
+    }
+
+    /// Attach a provider message or error
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Set when the event occurred
+    pub fn with_occurred_at(mut self, occurred_at: DateTime<Utc>) -> Self {
+        self.occurred_at = occurred_at;
+        self
+    }
+}
+
+/// Delivery that exhausted its retries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub id: Uuid,
+    pub notification: Notification,
+    pub channel: String,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub failed_at: DateTime<Utc>,
+    /// Set once an administrator has requeued the delivery
+    pub requeued_at: Option<DateTime<Utc>>,
+}
+
+impl DeadLetter {
+    pub fn new(
+        notification: Notification,
+        channel: impl Into<String>,
+        attempts: u32,
+        last_error: Option<String>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            notification,
+            channel: channel.into(),
+            attempts,
+            last_error,
+            failed_at: Utc::now(),
+            requeued_at: None,
+        }
+    }
+}
+
 /// Bulk notification request
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BulkNotification {
@@ -0,0 +1,437 @@
+//! WebSocket delivery channel and per-user connection registry
+//!
+//! Each open socket registers with the [`ConnectionRegistry`] under its
+//! user. [`WebSocketChannel`] fans a notification out to every socket of the
+//! recipient and waits for the first client acknowledgement, which becomes
+//! the delivery receipt in the returned [`DeliveryStatus`].
+
+use crate::channel::Channel;
+use crate::config::WebSocketConfig;
+use crate::error::{NotificationError, Result};
+use crate::types::{DeliveryState, DeliveryStatus, Notification};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::{mpsc, oneshot};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+use uuid::Uuid;
+
+/// Identifier of a registered socket
+pub type ConnectionId = Uuid;
+
+/// Frame sent to a client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// Notification to display; clients answer with [`ClientMessage::Ack`]
+    Notification { notification: Box<Notification> },
+    /// Application-level heartbeat
+    Ping,
+}
+
+/// Frame received from a client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Delivery receipt for a notification
+    Ack { notification_id: Uuid },
+    /// Heartbeat reply
+    Pong,
+}
+
+/// Registered socket of a user
+struct Connection {
+    id: ConnectionId,
+    sender: mpsc::Sender<ServerMessage>,
+    connected_at: DateTime<Utc>,
+}
+
+/// Delivery waiting for a client receipt
+struct PendingReceipt {
+    user_id: String,
+    receipt: oneshot::Sender<ConnectionId>,
+}
+
+/// Summary of an open socket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionInfo {
+    pub id: ConnectionId,
+    pub user_id: String,
+    pub connected_at: DateTime<Utc>,
+}
+
+/// Active sockets by user
+pub struct ConnectionRegistry {
+    connections: DashMap<String, Vec<Connection>>,
+    pending: DashMap<Uuid, PendingReceipt>,
+    buffer_size: usize,
+}
+
+impl ConnectionRegistry {
+    /// Create a registry whose sockets buffer up to `buffer_size` frames
+    pub fn new(buffer_size: usize) -> Self {
+        Self {
+            connections: DashMap::new(),
+            pending: DashMap::new(),
+            buffer_size: buffer_size.max(1),
+        }
+    }
+
+    /// Register a socket for a user
+    ///
+    /// Frames for the socket arrive on the returned receiver.
+    pub fn register(&self, user_id: &str) -> (ConnectionId, mpsc::Receiver<ServerMessage>) {
+        let (sender, receiver) = mpsc::channel(self.buffer_size);
+        let id = Uuid::new_v4();
+        self.connections.entry(user_id.to_string()).or_default().push(Connection {
+            id,
+            sender,
+            connected_at: Utc::now(),
+        });
+        tracing::debug!("WebSocket {} registered for user {}", id, user_id);
+        (id, receiver)
+    }
+
+    /// Remove a socket; returns whether it was registered
+    pub fn unregister(&self, user_id: &str, connection_id: ConnectionId) -> bool {
+        let removed = match self.connections.get_mut(user_id) {
+            Some(mut sockets) => {
+                let before = sockets.len();
+                sockets.retain(|c| c.id != connection_id);
+                before != sockets.len()
+            }
+            None => false,
+        };
+        self.connections.remove_if(user_id, |_, sockets| sockets.is_empty());
+        removed
+    }
+
+    /// Check if a user has an open socket
+    pub fn is_connected(&self, user_id: &str) -> bool {
+        self.connections
+            .get(user_id)
+            .map(|sockets| !sockets.is_empty())
+            .unwrap_or(false)
+    }
+
+    /// Number of open sockets for a user
+    pub fn connection_count(&self, user_id: &str) -> usize {
+        self.connections.get(user_id).map(|s| s.len()).unwrap_or(0)
+    }
+
+    /// Number of open sockets across all users
+    pub fn total_connections(&self) -> usize {
+        self.connections.iter().map(|entry| entry.value().len()).sum()
+    }
+
+    /// Open sockets of a user
+    pub fn connections_for(&self, user_id: &str) -> Vec<ConnectionInfo> {
+        self.connections
+            .get(user_id)
+            .map(|sockets| {
+                sockets
+                    .iter()
+                    .map(|c| ConnectionInfo {
+                        id: c.id,
+                        user_id: user_id.to_string(),
+                        connected_at: c.connected_at,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Send a frame to every socket of a user; returns how many accepted it
+    ///
+    /// Closed sockets are dropped. Sockets whose buffer is full miss the
+    /// frame.
+    pub fn send_to_user(&self, user_id: &str, message: &ServerMessage) -> usize {
+        let Some(mut sockets) = self.connections.get_mut(user_id) else {
+            return 0;
+        };
+
+        let mut sent = 0;
+        sockets.retain(|c| match c.sender.try_send(message.clone()) {
+            Ok(()) => {
+                sent += 1;
+                true
+            }
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                tracing::warn!("WebSocket {} buffer full, dropping frame", c.id);
+                true
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => false,
+        });
+        drop(sockets);
+
+        self.connections.remove_if(user_id, |_, sockets| sockets.is_empty());
+        sent
+    }
+
+    /// Expect a receipt for a notification from one of a user's sockets
+    fn expect_receipt(
+        &self,
+        user_id: &str,
+        notification_id: Uuid,
+    ) -> oneshot::Receiver<ConnectionId> {
+        let (receipt, receiver) = oneshot::channel();
+        self.pending.insert(
+            notification_id,
+            PendingReceipt {
+                user_id: user_id.to_string(),
+                receipt,
+            },
+        );
+        receiver
+    }
+
+    /// Record a client receipt; returns whether a delivery was waiting for it
+    ///
+    /// Receipts from sockets of other users are ignored.
+    pub fn acknowledge(
+        &self,
+        user_id: &str,
+        connection_id: ConnectionId,
+        notification_id: Uuid,
+    ) -> bool {
+        match self
+            .pending
+            .remove_if(&notification_id, |_, pending| pending.user_id == user_id)
+        {
+            Some((_, pending)) => pending.receipt.send(connection_id).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Pump frames between a WebSocket and the registry until it closes
+    ///
+    /// Registers the socket for `user_id`, forwards queued frames as JSON
+    /// text, sends heartbeats and turns client acknowledgements into
+    /// delivery receipts. The socket is closed when the client has been
+    /// silent for `client_timeout_ms`.
+    pub async fn serve<S>(
+        &self,
+        user_id: &str,
+        socket: WebSocketStream<S>,
+        config: &WebSocketConfig,
+    ) -> Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let (connection_id, mut outbound) = self.register(user_id);
+        let (mut sink, mut stream) = socket.split();
+        let mut heartbeat =
+            tokio::time::interval(Duration::from_millis(config.heartbeat_interval_ms));
+        let client_timeout = Duration::from_millis(config.client_timeout_ms);
+        let mut last_seen = tokio::time::Instant::now();
+
+        let result: Result<()> = async {
+            loop {
+                tokio::select! {
+                    frame = outbound.recv() => {
+                        let Some(frame) = frame else { break };
+                        sink.send(Message::Text(serde_json::to_string(&frame)?))
+                            .await
+                            .map_err(|e| NotificationError::WebSocket(e.to_string()))?;
+                    }
+                    incoming = stream.next() => {
+                        let message = match incoming {
+                            Some(message) => message.map_err(|e| NotificationError::WebSocket(e.to_string()))?,
+                            None => break,
+                        };
+                        last_seen = tokio::time::Instant::now();
+                        match message {
+                            Message::Text(text) => match serde_json::from_str::<ClientMessage>(&text) {
+                                Ok(ClientMessage::Ack { notification_id }) => {
+                                    self.acknowledge(user_id, connection_id, notification_id);
+                                }
+                                Ok(ClientMessage::Pong) => {}
+                                Err(e) => tracing::debug!("Ignoring WebSocket frame from {}: {}", connection_id, e),
+                            },
+                            Message::Close(_) => break,
+                            _ => {}
+                        }
+                    }
+                    _ = heartbeat.tick() => {
+                        if last_seen.elapsed() > client_timeout {
+                            tracing::info!("WebSocket {} timed out", connection_id);
+                            break;
+                        }
+                        sink.send(Message::Text(serde_json::to_string(&ServerMessage::Ping)?))
+                            .await
+                            .map_err(|e| NotificationError::WebSocket(e.to_string()))?;
+                    }
+                }
+            }
+            Ok(())
+        }
+        .await;
+
+        self.unregister(user_id, connection_id);
+        let _ = sink.close().await;
+        result
+    }
+}
+
+impl Default for ConnectionRegistry {
+    fn default() -> Self {
+        Self::new(WebSocketConfig::default().send_buffer)
+    }
+}
+
+/// WebSocket delivery channel
+pub struct WebSocketChannel {
+    config: WebSocketConfig,
+    registry: Arc<ConnectionRegistry>,
+}
+
+impl WebSocketChannel {
+    pub fn new(config: WebSocketConfig, registry: Arc<ConnectionRegistry>) -> Self {
+        Self { config, registry }
+    }
+
+    /// Connection registry backing the channel
+    pub fn registry(&self) -> &Arc<ConnectionRegistry> {
+        &self.registry
+    }
+}
+
+#[async_trait]
+impl Channel for WebSocketChannel {
+    fn name(&self) -> &str {
+        "websocket"
+    }
+
+    async fn deliver(&self, notification: &Notification) -> Result<DeliveryStatus> {
+        let mut status = DeliveryStatus {
+            notification_id: notification.id,
+            channel: self.name().to_string(),
+            status: DeliveryState::Processing,
+            attempts: 1,
+            last_attempt_at: Some(Utc::now()),
+            delivered_at: None,
+            error_message: None,
+        };
+
+        if !self.is_enabled() {
+            status.status = DeliveryState::Failed;
+            status.error_message = Some("WebSocket channel is disabled".to_string());
+            return Ok(status);
+        }
+
+        let receipt = self.registry.expect_receipt(&notification.user_id, notification.id);
+        let message = ServerMessage::Notification {
+            notification: Box::new(notification.clone()),
+        };
+        let sent = self.registry.send_to_user(&notification.user_id, &message);
+        if sent == 0 {
+            self.registry.pending.remove(&notification.id);
+            status.status = DeliveryState::Failed;
+            status.error_message = Some(format!(
+                "No active WebSocket connections for user {}",
+                notification.user_id
+            ));
+            return Ok(status);
+        }
+
+        match tokio::time::timeout(Duration::from_millis(self.config.ack_timeout_ms), receipt).await
+        {
+            Ok(Ok(connection_id)) => {
+                tracing::debug!(
+                    "Notification {} acknowledged by WebSocket {}",
+                    notification.id,
+                    connection_id
+                );
+                status.status = DeliveryState::Delivered;
+                status.delivered_at = Some(Utc::now());
+            }
+            _ => {
+                self.registry.pending.remove(&notification.id);
+                status.status = DeliveryState::Failed;
+                status.error_message = Some(format!(
+                    "No delivery receipt from {} socket(s) within {}ms",
+                    sent, self.config.ack_timeout_ms
+                ));
+            }
+        }
+
+        Ok(status)
+    }
+
+    fn supports(&self, notification: &Notification) -> bool {
+        self.registry.is_connected(&notification.user_id)
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::NotificationLevel;
+
+    fn channel(ack_timeout_ms: u64) -> WebSocketChannel {
+        let config = WebSocketConfig {
+            ack_timeout_ms,
+            ..WebSocketConfig::default()
+        };
+        WebSocketChannel::new(config, Arc::new(ConnectionRegistry::new(8)))
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_with_receipt() {
+        let channel = channel(1000);
+        let registry = Arc::clone(channel.registry());
+        let (first, mut first_rx) = registry.register("alice");
+        let (_, mut second_rx) = registry.register("alice");
+        assert_eq!(registry.connection_count("alice"), 2);
+
+        let notification = Notification::new("alice", NotificationLevel::Info, "Case updated", "");
+        assert!(channel.supports(&notification));
+
+        let client = Arc::clone(&registry);
+        let ack = tokio::spawn(async move {
+            let Some(ServerMessage::Notification { notification }) = first_rx.recv().await else {
+                panic!("expected notification frame");
+            };
+            assert!(!client.acknowledge("bob", first, notification.id));
+            assert!(client.acknowledge("alice", first, notification.id));
+        });
+
+        let status = channel.deliver(&notification).await.unwrap();
+        ack.await.unwrap();
+        assert_eq!(status.status, DeliveryState::Delivered);
+        assert!(status.delivered_at.is_some());
+        assert!(matches!(
+            second_rx.recv().await,
+            Some(ServerMessage::Notification { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_undelivered_notifications() {
+        let channel = channel(20);
+        let registry = Arc::clone(channel.registry());
+        let notification = Notification::new("carol", NotificationLevel::Info, "Report ready", "");
+
+        let status = channel.deliver(&notification).await.unwrap();
+        assert_eq!(status.status, DeliveryState::Failed);
+
+        let (id, _rx) = registry.register("carol");
+        let status = channel.deliver(&notification).await.unwrap();
+        assert_eq!(status.status, DeliveryState::Failed);
+        assert!(status.error_message.unwrap().contains("receipt"));
+
+        assert!(registry.unregister("carol", id));
+        assert!(!registry.is_connected("carol"));
+        assert_eq!(registry.total_connections(), 0);
+    }
+}
@@ -289,6 +289,11 @@ impl<S: Storage> SyncEngine<S> {
         &self.queue
     }
 
+    /// Get storage backend reference
+    pub fn storage(&self) -> &Arc<S> {
+        &self.storage
+    }
+
     /// Get conflict resolver reference
     pub fn resolver(&self) -> Arc<RwLock<ConflictResolver>> {
         Arc::clone(&self.resolver)
@@ -0,0 +1,581 @@
+//! Batch simulation for parameter sweeps.
+//!
+//! Reconstruction results are only as good as the inputs behind them, so a
+//! sensitivity analysis re-runs the same scene with friction, restitution and
+//! pre-impact velocities varied over their plausible ranges. A
+//! [`ParameterSweep`] describes those ranges; the [`BatchSimulator`] expands
+//! them into the full grid of variants, runs one clone of a base
+//! [`PhysicsWorld`] per variant in parallel and returns a [`RunSummary`] of
+//! the energy and contact history of each run.
+//!
+//! ```rust,no_run
+//! use accuscene_physics_v3::prelude::*;
+//! use accuscene_physics_v3::PhysicsWorld;
+//!
+//! let world = PhysicsWorld::new(PhysicsConfig::default());
+//! // ... add the vehicles at their pre-impact positions ...
+//!
+//! let sweep = ParameterSweep::new()
+//!     .with_friction(ParameterSweep::linspace(0.5, 0.9, 5))
+//!     .with_restitution([0.1, 0.2, 0.3])
+//!     .with_initial_velocities(0, [Vector3::new(12.0, 0.0, 0.0), Vector3::new(15.0, 0.0, 0.0)]);
+//!
+//! let summaries = BatchSimulator::new(world)
+//!     .with_duration(2.0)
+//!     .run(&sweep)
+//!     .unwrap();
+//! ```
+
+use nalgebra::Vector3;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{PhysicsError, PhysicsResult};
+use crate::rigid_body::constraints::ContactConstraint;
+use crate::PhysicsWorld;
+
+/// Default simulated duration of each run (s).
+pub const DEFAULT_BATCH_DURATION: f64 = 1.0;
+
+/// One set of parameters of a sweep.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SweepVariant {
+    /// Position of the variant in the sweep grid.
+    pub index: usize,
+
+    /// Contact friction coefficient.
+    pub friction: f64,
+
+    /// Contact restitution coefficient.
+    pub restitution: f64,
+
+    /// Initial linear velocities (m/s), by body ID.
+    pub initial_velocities: Vec<(usize, Vector3<f64>)>,
+}
+
+/// Parameter ranges to sweep.
+///
+/// The sweep is the Cartesian product of all ranges. A parameter without a
+/// range keeps the value of the base world.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ParameterSweep {
+    /// Friction coefficients.
+    friction: Vec<f64>,
+
+    /// Restitution coefficients.
+    restitution: Vec<f64>,
+
+    /// Initial linear velocities, by body ID.
+    initial_velocities: Vec<(usize, Vec<Vector3<f64>>)>,
+}
+
+impl ParameterSweep {
+    /// Creates an empty sweep, which yields only the base world.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `steps` evenly spaced values from `start` to `end` inclusive.
+    pub fn linspace(start: f64, end: f64, steps: usize) -> Vec<f64> {
+        match steps {
+            0 => Vec::new(),
+            1 => vec![start],
+            _ => {
+                let increment = (end - start) / (steps - 1) as f64;
+                (0..steps).map(|i| start + increment * i as f64).collect()
+            },
+        }
+    }
+
+    /// Sweeps the contact friction coefficient.
+    pub fn with_friction(mut self, values: impl IntoIterator<Item = f64>) -> Self {
+        self.friction = values.into_iter().collect();
+        self
+    }
+
+    /// Sweeps the contact restitution coefficient.
+    pub fn with_restitution(mut self, values: impl IntoIterator<Item = f64>) -> Self {
+        self.restitution = values.into_iter().collect();
+        self
+    }
+
+    /// Sweeps the initial linear velocity of a body.
+    ///
+    /// Each body gets its own range, so two bodies with five velocities each
+    /// contribute 25 combinations.
+    pub fn with_initial_velocities(
+        mut self,
+        body_id: usize,
+        values: impl IntoIterator<Item = Vector3<f64>>,
+    ) -> Self {
+        let values: Vec<Vector3<f64>> = values.into_iter().collect();
+        match self.initial_velocities.iter_mut().find(|(id, _)| *id == body_id) {
+            Some((_, existing)) => *existing = values,
+            None => self.initial_velocities.push((body_id, values)),
+        }
+        self
+    }
+
+    /// Number of variants in the sweep.
+    pub fn len(&self) -> usize {
+        let axis_len = |len: usize| if len == 0 { 1 } else { len };
+
+        self.initial_velocities.iter().fold(
+            axis_len(self.friction.len()) * axis_len(self.restitution.len()),
+            |count, (_, values)| count * values.len(),
+        )
+    }
+
+    /// Whether the sweep has no variants (a velocity range is empty).
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Expands the sweep into variants of `base`.
+    pub fn variants(&self, base: &PhysicsWorld) -> Vec<SweepVariant> {
+        let collision = &base.config().collision;
+        let friction = if self.friction.is_empty() {
+            vec![collision.default_friction]
+        } else {
+            self.friction.clone()
+        };
+        let restitution = if self.restitution.is_empty() {
+            vec![collision.default_restitution]
+        } else {
+            self.restitution.clone()
+        };
+
+        // Cartesian product of the velocity ranges of all bodies
+        let mut velocity_sets: Vec<Vec<(usize, Vector3<f64>)>> = vec![Vec::new()];
+        for (body_id, values) in &self.initial_velocities {
+            velocity_sets = velocity_sets
+                .iter()
+                .flat_map(|set| {
+                    values.iter().map(move |velocity| {
+                        let mut set = set.clone();
+                        set.push((*body_id, *velocity));
+                        set
+                    })
+                })
+                .collect();
+        }
+
+        let mut variants = Vec::with_capacity(self.len());
+        for &friction in &friction {
+            for &restitution in &restitution {
+                for initial_velocities in &velocity_sets {
+                    variants.push(SweepVariant {
+                        index: variants.len(),
+                        friction,
+                        restitution,
+                        initial_velocities: initial_velocities.clone(),
+                    });
+                }
+            }
+        }
+
+        variants
+    }
+}
+
+/// Energy and contact history of one run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunSummary {
+    /// Parameters of the run.
+    pub variant: SweepVariant,
+
+    /// Steps completed.
+    pub steps: usize,
+
+    /// Simulated time reached (s).
+    pub simulated_time: f64,
+
+    /// Kinetic energy after applying the variant (J).
+    pub initial_kinetic_energy: f64,
+
+    /// Kinetic energy at the end of the run (J).
+    pub final_kinetic_energy: f64,
+
+    /// Deformation energy at the end of the run (J).
+    pub deformation_energy: f64,
+
+    /// Energy lost over the run (J): initial kinetic energy minus final
+    /// kinetic and deformation energy.
+    pub energy_dissipated: f64,
+
+    /// Contacts resolved, summed over all steps.
+    pub total_contacts: usize,
+
+    /// Most contacts resolved in a single step.
+    pub max_contacts: usize,
+
+    /// Time of the first contact (s), if any.
+    pub first_contact_time: Option<f64>,
+
+    /// Deepest penetration seen (m).
+    pub max_penetration: f64,
+
+    /// Normal impulse applied at contacts, summed over all steps (N·s).
+    pub total_normal_impulse: f64,
+
+    /// Error that ended the run early, e.g. solver divergence.
+    pub error: Option<String>,
+}
+
+impl RunSummary {
+    /// Whether the run reached the requested duration.
+    pub fn completed(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Accumulates the contacts of a completed step.
+    fn record_step(&mut self, dt: f64, contacts: &[ContactConstraint]) {
+        self.steps += 1;
+        self.simulated_time += dt;
+
+        if !contacts.is_empty() && self.first_contact_time.is_none() {
+            self.first_contact_time = Some(self.simulated_time);
+        }
+        self.total_contacts += contacts.len();
+        self.max_contacts = self.max_contacts.max(contacts.len());
+        for contact in contacts {
+            self.max_penetration = self.max_penetration.max(contact.penetration);
+            self.total_normal_impulse += contact.accumulated_normal_impulse;
+        }
+    }
+}
+
+/// Runs variants of a base world in parallel.
+#[derive(Clone)]
+pub struct BatchSimulator {
+    /// World every variant starts from.
+    base: PhysicsWorld,
+
+    /// Simulated duration of each run (s).
+    duration: f64,
+
+    /// Time step (s).
+    dt: f64,
+}
+
+impl BatchSimulator {
+    /// Creates a batch simulator stepping at the base world's configured time step.
+    pub fn new(base: PhysicsWorld) -> Self {
+        let dt = base.config().time_step.dt;
+
+        Self {
+            base,
+            duration: DEFAULT_BATCH_DURATION,
+            dt,
+        }
+    }
+
+    /// Sets the simulated duration of each run (s).
+    pub fn with_duration(mut self, duration: f64) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Sets the time step (s).
+    pub fn with_time_step(mut self, dt: f64) -> Self {
+        self.dt = dt;
+        self
+    }
+
+    /// Runs every variant of the sweep.
+    pub fn run(&self, sweep: &ParameterSweep) -> PhysicsResult<Vec<RunSummary>> {
+        self.run_variants(&sweep.variants(&self.base))
+    }
+
+    /// Runs the given variants, returning summaries in the same order.
+    ///
+    /// Runs execute in parallel unless parallel execution is disabled in the
+    /// base world's configuration. A run that fails mid-way (e.g. the solver
+    /// diverges) does not abort the batch; its summary records the error.
+    pub fn run_variants(&self, variants: &[SweepVariant]) -> PhysicsResult<Vec<RunSummary>> {
+        self.validate()?;
+        for variant in variants {
+            self.validate_variant(variant)?;
+        }
+
+        #[cfg(feature = "parallel")]
+        if self.base.config().parallel.enabled {
+            return Ok(variants.par_iter().map(|variant| self.run_variant(variant)).collect());
+        }
+
+        Ok(variants.iter().map(|variant| self.run_variant(variant)).collect())
+    }
+
+    /// Checks the run length.
+    fn validate(&self) -> PhysicsResult<()> {
+        if !(self.dt.is_finite() && self.dt > 0.0) {
+            return Err(invalid("dt", self.dt, "a positive time step"));
+        }
+        if !(self.duration.is_finite() && self.duration >= 0.0) {
+            return Err(invalid(
+                "duration",
+                self.duration,
+                "a non-negative duration",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks that a variant is physically meaningful for the base world.
+    fn validate_variant(&self, variant: &SweepVariant) -> PhysicsResult<()> {
+        if !(variant.friction.is_finite() && variant.friction >= 0.0) {
+            return Err(invalid(
+                "friction",
+                variant.friction,
+                "a non-negative coefficient",
+            ));
+        }
+        if !(0.0..=1.0).contains(&variant.restitution) {
+            return Err(invalid(
+                "restitution",
+                variant.restitution,
+                "a coefficient in [0, 1]",
+            ));
+        }
+        for (body_id, velocity) in &variant.initial_velocities {
+            if self.base.body(*body_id).is_none() {
+                return Err(invalid(
+                    "body_id",
+                    *body_id,
+                    "an ID of a body in the base world",
+                ));
+            }
+            if !velocity.iter().all(|component| component.is_finite()) {
+                return Err(PhysicsError::invalid_state(format!(
+                    "non-finite initial velocity for body {}",
+                    body_id
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Simulates one variant on its own copy of the base world.
+    fn run_variant(&self, variant: &SweepVariant) -> RunSummary {
+        let mut world = self.base.clone();
+        let collision = world.collision_config_mut();
+        collision.default_friction = variant.friction;
+        collision.default_restitution = variant.restitution;
+        for (body_id, velocity) in &variant.initial_velocities {
+            if let Some(body) = world.body_mut(*body_id) {
+                body.linear_velocity = *velocity;
+                body.is_awake = true;
+                body.time_at_rest = 0.0;
+            }
+        }
+
+        let mut energy = world.energy_analysis().clone();
+        energy.analyze_rigid_bodies(world.bodies());
+
+        let mut summary = RunSummary {
+            variant: variant.clone(),
+            steps: 0,
+            simulated_time: 0.0,
+            initial_kinetic_energy: energy.total_kinetic,
+            final_kinetic_energy: energy.total_kinetic,
+            deformation_energy: energy.deformation_energy,
+            energy_dissipated: 0.0,
+            total_contacts: 0,
+            max_contacts: 0,
+            first_contact_time: None,
+            max_penetration: 0.0,
+            total_normal_impulse: 0.0,
+            error: None,
+        };
+
+        let steps = (self.duration / self.dt).ceil() as usize;
+        for _ in 0..steps {
+            if let Err(error) = world.step(self.dt) {
+                summary.error = Some(error.to_string());
+                break;
+            }
+
+            summary.record_step(self.dt, world.contacts());
+        }
+
+        // The world's analysis is only refreshed by successful steps
+        if summary.steps > 0 {
+            let energy = world.energy_analysis();
+            summary.final_kinetic_energy = energy.total_kinetic;
+            summary.deformation_energy = energy.deformation_energy;
+        }
+        summary.energy_dissipated = summary.initial_kinetic_energy
+            - summary.final_kinetic_energy
+            - summary.deformation_energy;
+
+        summary
+    }
+}
+
+/// Builds an invalid configuration error for a batch parameter.
+fn invalid(parameter: &str, value: impl ToString, constraint: &str) -> PhysicsError {
+    PhysicsError::InvalidConfiguration {
+        parameter: parameter.to_string(),
+        value: value.to_string(),
+        constraint: constraint.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collision::CollisionShape;
+    use crate::config::PhysicsConfig;
+    use crate::rigid_body::{dynamics::MassProperties, RigidBody};
+
+    fn two_body_world() -> PhysicsWorld {
+        let mut world = PhysicsWorld::new(PhysicsConfig::default());
+        world.set_gravity(Vector3::zeros());
+
+        for (id, x) in [(0, -2.0), (1, 2.0)] {
+            let mut body = RigidBody::new(id, MassProperties::from_sphere(1000.0, 0.5));
+            body.position = Vector3::new(x, 0.0, 0.0);
+            world.add_body(body, CollisionShape::Sphere { radius: 0.5 });
+        }
+
+        world
+    }
+
+    #[test]
+    fn test_sweep_grid() {
+        let world = two_body_world();
+        let sweep = ParameterSweep::new()
+            .with_friction([0.5, 0.7])
+            .with_initial_velocities(0, [Vector3::x(), Vector3::x() * 2.0, Vector3::x() * 3.0])
+            .with_initial_velocities(1, [-Vector3::x(), -Vector3::x() * 2.0]);
+
+        let variants = sweep.variants(&world);
+
+        assert_eq!(sweep.len(), 12);
+        assert_eq!(variants.len(), 12);
+        assert!(variants.iter().enumerate().all(|(i, v)| v.index == i));
+        // Restitution is not swept and keeps the base value
+        assert!(variants.iter().all(|v| v.restitution == 0.3));
+        assert_eq!(variants[11].friction, 0.7);
+        assert_eq!(variants[11].initial_velocities[0].1, Vector3::x() * 3.0);
+        assert_eq!(variants[11].initial_velocities[1].1, -Vector3::x() * 2.0);
+    }
+
+    #[test]
+    fn test_linspace() {
+        assert_eq!(
+            ParameterSweep::linspace(0.0, 1.0, 5),
+            vec![0.0, 0.25, 0.5, 0.75, 1.0]
+        );
+        assert_eq!(ParameterSweep::linspace(0.3, 0.9, 1), vec![0.3]);
+        assert!(ParameterSweep::linspace(0.0, 1.0, 0).is_empty());
+    }
+
+    #[test]
+    fn test_batch_runs_every_variant() {
+        let sweep = ParameterSweep::new().with_restitution([0.1, 0.5]).with_initial_velocities(
+            1,
+            [Vector3::new(0.0, 5.0, 0.0), Vector3::new(0.0, 10.0, 0.0)],
+        );
+
+        let summaries = BatchSimulator::new(two_body_world())
+            .with_duration(1.0)
+            .with_time_step(0.01)
+            .run(&sweep)
+            .unwrap();
+
+        assert_eq!(summaries.len(), 4);
+        for (i, summary) in summaries.iter().enumerate() {
+            assert_eq!(summary.variant.index, i);
+            assert!(summary.completed(), "{:?}", summary.error);
+            assert_eq!(summary.steps, 100);
+            assert!((summary.simulated_time - 1.0).abs() < 1e-9);
+
+            // Bodies move apart without gravity, so no energy is lost
+            let speed = summary.variant.initial_velocities[0].1.y;
+            assert!((summary.initial_kinetic_energy - 500.0 * speed * speed).abs() < 1e-6);
+            assert!(summary.energy_dissipated.abs() < 1e-6);
+            assert_eq!(summary.total_contacts, 0);
+            assert_eq!(summary.first_contact_time, None);
+        }
+    }
+
+    #[test]
+    fn test_summary_records_contacts() {
+        let variant = ParameterSweep::new().variants(&two_body_world()).remove(0);
+        let mut summary = RunSummary {
+            variant,
+            steps: 0,
+            simulated_time: 0.0,
+            initial_kinetic_energy: 0.0,
+            final_kinetic_energy: 0.0,
+            deformation_energy: 0.0,
+            energy_dissipated: 0.0,
+            total_contacts: 0,
+            max_contacts: 0,
+            first_contact_time: None,
+            max_penetration: 0.0,
+            total_normal_impulse: 0.0,
+            error: None,
+        };
+
+        let contact = |penetration: f64, impulse: f64| {
+            let mut contact = ContactConstraint::new(
+                0,
+                1,
+                Vector3::zeros(),
+                Vector3::zeros(),
+                Vector3::x(),
+                penetration,
+                0.3,
+                0.7,
+            );
+            contact.accumulated_normal_impulse = impulse;
+            contact
+        };
+
+        summary.record_step(0.01, &[]);
+        summary.record_step(0.01, &[contact(0.02, 150.0), contact(0.05, 50.0)]);
+        summary.record_step(0.01, &[contact(0.01, 25.0)]);
+
+        assert_eq!(summary.steps, 3);
+        assert_eq!(summary.first_contact_time, Some(0.02));
+        assert_eq!(summary.total_contacts, 3);
+        assert_eq!(summary.max_contacts, 2);
+        assert_eq!(summary.max_penetration, 0.05);
+        assert_eq!(summary.total_normal_impulse, 225.0);
+    }
+
+    #[test]
+    fn test_variants_leave_base_untouched() {
+        let world = two_body_world();
+        let simulator = BatchSimulator::new(world.clone()).with_duration(0.1);
+        let sweep = ParameterSweep::new().with_initial_velocities(0, [Vector3::new(3.0, 0.0, 0.0)]);
+
+        simulator.run(&sweep).unwrap();
+
+        assert_eq!(
+            simulator.base.body(0).unwrap().linear_velocity,
+            Vector3::zeros()
+        );
+        assert_eq!(simulator.base.time(), 0.0);
+    }
+
+    #[test]
+    fn test_invalid_variants_are_rejected() {
+        let simulator = BatchSimulator::new(two_body_world());
+
+        let unknown_body = ParameterSweep::new().with_initial_velocities(7, [Vector3::x()]);
+        assert!(matches!(
+            simulator.run(&unknown_body),
+            Err(PhysicsError::InvalidConfiguration { .. })
+        ));
+
+        let restitution = ParameterSweep::new().with_restitution([1.5]);
+        assert!(simulator.run(&restitution).is_err());
+
+        let zero_step = simulator.clone().with_time_step(0.0);
+        assert!(zero_step.run(&ParameterSweep::new()).is_err());
+    }
+}
@@ -4,15 +4,22 @@
 //! - Sweep and Prune (Sort and Sweep)
 //! - Spatial Hashing
 //! - Bounding Volume Hierarchies
+//!
+//! With the `parallel` feature, AABB updates and spatial hash and BVH pair
+//! generation run on the rayon thread pool once a scene has enough bodies.
+//! Pairs are returned sorted, so the solver sees contacts in the same order
+//! whichever method and thread count is used.
 
 use std::collections::{HashMap, HashSet};
 
 use nalgebra::Vector3;
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use super::{CollisionPair, AABB};
-use crate::config::BroadPhaseMethod;
+use super::{CollisionPair, CollisionShape, AABB};
+use crate::config::{BroadPhaseMethod, PhysicsConfig};
+use crate::rigid_body::RigidBody;
 
 /// Broad phase collision detector.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +32,12 @@ pub struct BroadPhase {
 
     /// Spatial hash state.
     spatial_hash: SpatialHash,
+
+    /// Bounding volume hierarchy.
+    bvh: Bvh,
+
+    /// Minimum number of bodies for parallel detection (`None` = serial).
+    parallel_threshold: Option<usize>,
 }
 
 impl BroadPhase {
@@ -34,32 +47,79 @@ impl BroadPhase {
             method,
             sweep_and_prune: SweepAndPrune::new(),
             spatial_hash: SpatialHash::new(2.0), // 2m cell size
+            bvh: Bvh::new(),
+            parallel_threshold: None,
         }
     }
 
-    /// Detects potentially colliding pairs.
+    /// Creates a broad phase detector from the physics configuration.
+    pub fn from_config(config: &PhysicsConfig) -> Self {
+        let mut broad_phase = Self::new(config.collision.broad_phase);
+        broad_phase.configure(config);
+        broad_phase
+    }
+
+    /// Applies the method, cell size and parallelism from the configuration.
+    pub fn configure(&mut self, config: &PhysicsConfig) {
+        self.method = config.collision.broad_phase;
+        self.spatial_hash.cell_size = config.collision.spatial_hash_cell_size;
+        self.parallel_threshold = config
+            .parallel
+            .enabled
+            .then_some(config.collision.parallel_broad_phase_threshold);
+    }
+
+    /// Computes the world-space AABB of every body.
+    pub fn compute_aabbs(
+        &self,
+        bodies: &[RigidBody],
+        shapes: &[CollisionShape],
+    ) -> Vec<(usize, AABB)> {
+        let aabb = |(id, (body, shape)): (usize, (&RigidBody, &CollisionShape))| {
+            (id, shape.compute_aabb(body.position, &body.orientation))
+        };
+
+        #[cfg(feature = "parallel")]
+        if self.is_parallel(bodies.len()) {
+            return bodies.par_iter().zip(shapes).enumerate().map(aabb).collect();
+        }
+
+        bodies.iter().zip(shapes).enumerate().map(aabb).collect()
+    }
+
+    /// Detects potentially colliding pairs, sorted by body IDs.
     pub fn detect_pairs(
         &mut self,
         bodies: &[(usize, AABB)],
     ) -> Vec<CollisionPair> {
-        match self.method {
+        let parallel = self.is_parallel(bodies.len());
+
+        let mut pairs = match self.method {
             BroadPhaseMethod::SweepAndPrune => {
                 self.sweep_and_prune.detect_pairs(bodies)
             }
             BroadPhaseMethod::SpatialHash => {
-                self.spatial_hash.detect_pairs(bodies)
-            }
-            BroadPhaseMethod::BVH => {
-                // Simplified: fall back to sweep and prune
-                self.sweep_and_prune.detect_pairs(bodies)
+                self.spatial_hash.detect_pairs_with(bodies, parallel)
             }
-        }
+            BroadPhaseMethod::BVH => self.bvh.detect_pairs(bodies, parallel),
+        };
+
+        pairs.sort_unstable_by_key(|pair| (pair.body_a, pair.body_b));
+        pairs
     }
 
     /// Updates configuration.
     pub fn set_method(&mut self, method: BroadPhaseMethod) {
         self.method = method;
     }
+
+    /// Whether a scene of `body_count` bodies is processed in parallel.
+    fn is_parallel(&self, body_count: usize) -> bool {
+        cfg!(feature = "parallel")
+            && self
+                .parallel_threshold
+                .is_some_and(|threshold| body_count >= threshold)
+    }
 }
 
 /// Sweep and Prune algorithm.
@@ -177,80 +237,88 @@ struct Endpoint {
     is_min: bool,
 }
 
+/// Largest number of cells a body is inserted into; bigger bodies (ground
+/// planes, barriers) are tested against every body instead.
+const MAX_CELLS_PER_BODY: i64 = 64;
+
 /// Spatial hashing for broad phase.
 ///
-/// Divides space into grid cells and checks only nearby cells.
+/// Divides space into grid cells and checks only bodies sharing a cell.
+/// Each overlapping pair is reported once, from the first cell both bodies
+/// occupy.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpatialHash {
     /// Cell size.
     cell_size: f64,
-
-    /// Hash table: cell key -> body IDs.
-    #[serde(skip)]
-    cells: HashMap<CellKey, Vec<usize>>,
 }
 
 impl SpatialHash {
     /// Creates a new spatial hash with given cell size.
     pub fn new(cell_size: f64) -> Self {
-        Self {
-            cell_size,
-            cells: HashMap::new(),
-        }
+        Self { cell_size }
+    }
+
+    /// Cell size (m).
+    pub fn cell_size(&self) -> f64 {
+        self.cell_size
     }
 
     /// Detects potentially colliding pairs.
     pub fn detect_pairs(&mut self, bodies: &[(usize, AABB)]) -> Vec<CollisionPair> {
-        // Clear and rebuild hash
-        self.cells.clear();
+        self.detect_pairs_with(bodies, false)
+    }
 
-        // Insert bodies into cells
-        for (id, aabb) in bodies {
-            let cells = self.get_cells_for_aabb(aabb);
-            for cell in cells {
-                self.cells.entry(cell).or_insert_with(Vec::new).push(*id);
+    /// Detects potentially colliding pairs, in parallel if requested.
+    pub fn detect_pairs_with(
+        &self,
+        bodies: &[(usize, AABB)],
+        parallel: bool,
+    ) -> Vec<CollisionPair> {
+        let ranges: Vec<(CellKey, CellKey)> = bodies
+            .iter()
+            .map(|(_, aabb)| (self.world_to_cell(aabb.min), self.world_to_cell(aabb.max)))
+            .collect();
+        let (oversized, hashed): (Vec<usize>, Vec<usize>) = (0..bodies.len())
+            .partition(|&index| cell_count(&ranges[index]) > MAX_CELLS_PER_BODY);
+
+        // (cell, body index) entries, grouped by cell
+        let mut entries: Vec<(CellKey, usize)> = hashed
+            .iter()
+            .flat_map(|&index| cells_in(&ranges[index]).map(move |cell| (cell, index)))
+            .collect();
+        sort_entries(&mut entries, parallel);
+        let groups: Vec<&[(CellKey, usize)]> = entries.chunk_by(|a, b| a.0 == b.0).collect();
+
+        let cell_pairs = |group: &&[(CellKey, usize)]| {
+            let mut pairs = Vec::new();
+            for (i, &(cell, a)) in group.iter().enumerate() {
+                for &(_, b) in &group[i + 1..] {
+                    let first_shared = ranges[a].0.component_max(&ranges[b].0);
+                    if first_shared == cell && bodies[a].1.overlaps(&bodies[b].1) {
+                        pairs.push(CollisionPair::new(bodies[a].0, bodies[b].0));
+                    }
+                }
             }
-        }
+            pairs
+        };
 
-        // Find pairs
-        let mut pairs = HashSet::new();
+        let mut pairs: Vec<CollisionPair> = if parallel {
+            par_flat_map(&groups, cell_pairs)
+        } else {
+            groups.iter().flat_map(cell_pairs).collect()
+        };
 
-        for cell_bodies in self.cells.values() {
-            for i in 0..cell_bodies.len() {
-                for j in (i + 1)..cell_bodies.len() {
-                    pairs.insert(CollisionPair::new(cell_bodies[i], cell_bodies[j]));
+        // Oversized bodies against everything, without repeating pairs
+        for (position, &a) in oversized.iter().enumerate() {
+            for b in (0..bodies.len()).filter(|&b| b != a) {
+                let repeated = oversized[..position].contains(&b);
+                if !repeated && bodies[a].1.overlaps(&bodies[b].1) {
+                    pairs.push(CollisionPair::new(bodies[a].0, bodies[b].0));
                 }
             }
         }
 
-        // Filter by actual AABB overlap
-        let aabb_map: HashMap<usize, AABB> = bodies.iter().copied().collect();
         pairs
-            .into_iter()
-            .filter(|pair| {
-                if let (Some(aabb_a), Some(aabb_b)) = (aabb_map.get(&pair.body_a), aabb_map.get(&pair.body_b)) {
-                    aabb_a.overlaps(aabb_b)
-                } else {
-                    false
-                }
-            })
-            .collect()
-    }
-
-    /// Gets all cells overlapping an AABB.
-    fn get_cells_for_aabb(&self, aabb: &AABB) -> Vec<CellKey> {
-        let min_cell = self.world_to_cell(aabb.min);
-        let max_cell = self.world_to_cell(aabb.max);
-
-        let mut cells = Vec::new();
-        for x in min_cell.x..=max_cell.x {
-            for y in min_cell.y..=max_cell.y {
-                for z in min_cell.z..=max_cell.z {
-                    cells.push(CellKey { x, y, z });
-                }
-            }
-        }
-        cells
     }
 
     /// Converts world position to cell coordinates.
@@ -264,13 +332,190 @@ impl SpatialHash {
 }
 
 /// Cell key for spatial hash.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 struct CellKey {
     x: i32,
     y: i32,
     z: i32,
 }
 
+impl CellKey {
+    /// Componentwise maximum.
+    fn component_max(&self, other: &CellKey) -> CellKey {
+        CellKey {
+            x: self.x.max(other.x),
+            y: self.y.max(other.y),
+            z: self.z.max(other.z),
+        }
+    }
+}
+
+/// Number of cells in an inclusive cell range.
+fn cell_count((min, max): &(CellKey, CellKey)) -> i64 {
+    let span = |min: i32, max: i32| i64::from(max) - i64::from(min) + 1;
+    span(min.x, max.x) * span(min.y, max.y) * span(min.z, max.z)
+}
+
+/// Cells in an inclusive cell range.
+fn cells_in(&(min, max): &(CellKey, CellKey)) -> impl Iterator<Item = CellKey> {
+    (min.x..=max.x).flat_map(move |x| {
+        (min.y..=max.y).flat_map(move |y| (min.z..=max.z).map(move |z| CellKey { x, y, z }))
+    })
+}
+
+fn sort_entries(entries: &mut [(CellKey, usize)], parallel: bool) {
+    #[cfg(feature = "parallel")]
+    if parallel {
+        entries.par_sort_unstable();
+        return;
+    }
+
+    let _ = parallel;
+    entries.sort_unstable();
+}
+
+/// Maps each item to pairs on the rayon thread pool.
+#[cfg(feature = "parallel")]
+fn par_flat_map<T: Sync>(
+    items: &[T],
+    pairs: impl Fn(&T) -> Vec<CollisionPair> + Sync + Send,
+) -> Vec<CollisionPair> {
+    items.par_iter().flat_map_iter(pairs).collect()
+}
+
+#[cfg(not(feature = "parallel"))]
+fn par_flat_map<T>(items: &[T], pairs: impl Fn(&T) -> Vec<CollisionPair>) -> Vec<CollisionPair> {
+    items.iter().flat_map(pairs).collect()
+}
+
+/// Bodies per BVH leaf.
+const BVH_LEAF_SIZE: usize = 4;
+
+/// Smallest BVH subtree built on a separate task.
+#[cfg(feature = "parallel")]
+const BVH_PARALLEL_BUILD_SIZE: usize = 1024;
+
+/// Bounding volume hierarchy, rebuilt every step.
+///
+/// Built top-down by splitting at the median centroid along the longest
+/// axis; every body is then queried against the tree.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Bvh {}
+
+/// BVH node over indices into the body list.
+#[derive(Debug)]
+enum BvhNode {
+    Leaf {
+        aabb: AABB,
+        items: Vec<usize>,
+    },
+    Internal {
+        aabb: AABB,
+        children: Box<[BvhNode; 2]>,
+    },
+}
+
+impl BvhNode {
+    fn aabb(&self) -> &AABB {
+        match self {
+            Self::Leaf { aabb, .. } | Self::Internal { aabb, .. } => aabb,
+        }
+    }
+
+    /// Collects bodies after `index` whose AABBs overlap body `index`.
+    fn query(&self, bodies: &[(usize, AABB)], index: usize, pairs: &mut Vec<CollisionPair>) {
+        let aabb = &bodies[index].1;
+        if !self.aabb().overlaps(aabb) {
+            return;
+        }
+
+        match self {
+            Self::Leaf { items, .. } => {
+                for &other in items {
+                    if other > index && aabb.overlaps(&bodies[other].1) {
+                        pairs.push(CollisionPair::new(bodies[index].0, bodies[other].0));
+                    }
+                }
+            }
+            Self::Internal { children, .. } => {
+                children[0].query(bodies, index, pairs);
+                children[1].query(bodies, index, pairs);
+            }
+        }
+    }
+}
+
+impl Bvh {
+    /// Creates a new BVH detector.
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Detects potentially colliding pairs, in parallel if requested.
+    pub fn detect_pairs(&self, bodies: &[(usize, AABB)], parallel: bool) -> Vec<CollisionPair> {
+        if bodies.len() < 2 {
+            return Vec::new();
+        }
+
+        let mut items: Vec<usize> = (0..bodies.len()).collect();
+        let root = build_bvh(bodies, &mut items, parallel);
+
+        let body_pairs = |index: &usize| {
+            let mut pairs = Vec::new();
+            root.query(bodies, *index, &mut pairs);
+            pairs
+        };
+
+        if parallel {
+            par_flat_map(&items, body_pairs)
+        } else {
+            items.iter().flat_map(body_pairs).collect()
+        }
+    }
+}
+
+/// Builds a BVH over `items`, reordering them.
+fn build_bvh(bodies: &[(usize, AABB)], items: &mut [usize], parallel: bool) -> BvhNode {
+    let aabb = items
+        .iter()
+        .map(|&index| bodies[index].1)
+        .reduce(|a, b| a.merge(&b))
+        .unwrap_or_else(|| AABB::new(Vector3::zeros(), Vector3::zeros()));
+
+    if items.len() <= BVH_LEAF_SIZE {
+        return BvhNode::Leaf {
+            aabb,
+            items: items.to_vec(),
+        };
+    }
+
+    let extents = aabb.max - aabb.min;
+    let axis = extents.imax();
+    let mid = items.len() / 2;
+    items.select_nth_unstable_by(mid, |&a, &b| {
+        bodies[a].1.center()[axis].total_cmp(&bodies[b].1.center()[axis])
+    });
+    let (left, right) = items.split_at_mut(mid);
+
+    #[cfg(feature = "parallel")]
+    let children = if parallel && left.len() + right.len() >= BVH_PARALLEL_BUILD_SIZE {
+        rayon::join(
+            || build_bvh(bodies, left, parallel),
+            || build_bvh(bodies, right, parallel),
+        )
+    } else {
+        (build_bvh(bodies, left, parallel), build_bvh(bodies, right, parallel))
+    };
+
+    #[cfg(not(feature = "parallel"))]
+    let children = (build_bvh(bodies, left, parallel), build_bvh(bodies, right, parallel));
+
+    BvhNode::Internal {
+        aabb,
+        children: Box::new([children.0, children.1]),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -307,4 +552,91 @@ mod tests {
         assert_eq!(pairs.len(), 1);
         assert!(pairs.contains(&CollisionPair::new(0, 1)));
     }
+
+    /// Deterministic debris field: small boxes scattered over a 60 m square,
+    /// plus a ground slab under everything.
+    fn debris_field(count: usize) -> Vec<(usize, AABB)> {
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 11) as f64 / (1u64 << 53) as f64
+        };
+
+        let mut bodies: Vec<(usize, AABB)> = (0..count)
+            .map(|id| {
+                let center = Vector3::new(next() * 60.0, next() * 60.0, next() * 2.0);
+                let half = Vector3::new(0.2 + next(), 0.2 + next(), 0.2 + next() * 0.5);
+                (id, AABB::from_center_half_extents(center, half))
+            })
+            .collect();
+        bodies.push((
+            count,
+            AABB::new(Vector3::new(-10.0, -10.0, -1.0), Vector3::new(70.0, 70.0, 0.1)),
+        ));
+        bodies
+    }
+
+    fn brute_force(bodies: &[(usize, AABB)]) -> Vec<CollisionPair> {
+        let mut pairs = Vec::new();
+        for (i, (a, aabb_a)) in bodies.iter().enumerate() {
+            for (b, aabb_b) in &bodies[i + 1..] {
+                if aabb_a.overlaps(aabb_b) {
+                    pairs.push(CollisionPair::new(*a, *b));
+                }
+            }
+        }
+        pairs.sort_unstable_by_key(|pair| (pair.body_a, pair.body_b));
+        pairs
+    }
+
+    #[test]
+    fn test_methods_agree_with_brute_force() {
+        let bodies = debris_field(400);
+        let expected = brute_force(&bodies);
+        assert!(expected.len() > 100);
+
+        for method in [
+            BroadPhaseMethod::SweepAndPrune,
+            BroadPhaseMethod::SpatialHash,
+            BroadPhaseMethod::BVH,
+        ] {
+            let mut broad_phase = BroadPhase::new(method);
+            assert_eq!(broad_phase.detect_pairs(&bodies), expected, "{method:?}");
+        }
+    }
+
+    #[test]
+    fn test_parallel_detection_matches_serial() {
+        let bodies = debris_field(3000);
+        let mut config = PhysicsConfig::default();
+        config.collision.parallel_broad_phase_threshold = 1000;
+
+        for method in [BroadPhaseMethod::SpatialHash, BroadPhaseMethod::BVH] {
+            config.collision.broad_phase = method;
+            let mut parallel = BroadPhase::from_config(&config);
+            let mut serial = BroadPhase::new(method);
+
+            assert_eq!(parallel.detect_pairs(&bodies), serial.detect_pairs(&bodies));
+        }
+    }
+
+    #[test]
+    fn test_compute_aabbs() {
+        use crate::rigid_body::MassProperties;
+
+        let mut config = PhysicsConfig::default();
+        config.collision.parallel_broad_phase_threshold = 2;
+        let broad_phase = BroadPhase::from_config(&config);
+
+        let mut body = RigidBody::new(0, MassProperties::from_sphere(10.0, 1.0));
+        body.position = Vector3::new(3.0, 0.0, 0.0);
+        let bodies = vec![RigidBody::new(0, MassProperties::from_sphere(10.0, 1.0)), body];
+        let shapes = vec![CollisionShape::Sphere { radius: 1.0 }; 2];
+
+        let aabbs = broad_phase.compute_aabbs(&bodies, &shapes);
+
+        assert_eq!(aabbs.len(), 2);
+        assert_eq!(aabbs[1].0, 1);
+        assert_eq!(aabbs[1].1.min, Vector3::new(2.0, -1.0, -1.0));
+    }
 }
@@ -150,6 +150,15 @@ pub struct CollisionConfig {
 
     /// Default friction coefficient.
     pub default_friction: f64,
+
+    /// Spatial hash cell size (m); about the size of a typical body.
+    #[serde(default = "default_spatial_hash_cell_size")]
+    pub spatial_hash_cell_size: f64,
+
+    /// Body count from which the broad phase runs in parallel (with
+    /// [`ParallelConfig::enabled`]).
+    #[serde(default = "default_parallel_broad_phase_threshold")]
+    pub parallel_broad_phase_threshold: usize,
 }
 
 impl Default for CollisionConfig {
@@ -162,10 +171,20 @@ impl Default for CollisionConfig {
             max_contacts: 4,
             default_restitution: 0.3,
             default_friction: 0.7,
+            spatial_hash_cell_size: default_spatial_hash_cell_size(),
+            parallel_broad_phase_threshold: default_parallel_broad_phase_threshold(),
         }
     }
 }
 
+fn default_spatial_hash_cell_size() -> f64 {
+    2.0
+}
+
+fn default_parallel_broad_phase_threshold() -> usize {
+    1024
+}
+
 /// Broad phase collision detection methods.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BroadPhaseMethod {
@@ -175,7 +194,7 @@ pub enum BroadPhaseMethod {
     /// Spatial hashing.
     SpatialHash,
 
-    /// Bounding volume hierarchy.
+    /// Bounding volume hierarchy; scales best to large debris fields.
     BVH,
 }
 
@@ -6,12 +6,15 @@
 //! - Deformation energy (crush analysis)
 //! - Energy dissipation (friction, air resistance)
 //! - Conservation of energy checks
+//! - Rollover detection (roll angle, trip point, quarter-turns)
 
 pub mod deformation;
 pub mod kinetic;
+pub mod rollover;
 
 pub use deformation::*;
 pub use kinetic::*;
+pub use rollover::*;
 
 use nalgebra::Vector3;
 use serde::{Deserialize, Serialize};
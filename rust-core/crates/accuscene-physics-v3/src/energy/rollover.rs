@@ -0,0 +1,529 @@
+//! Rollover detection and analysis.
+//!
+//! Tracks each vehicle's roll angle and roll rate during the simulation and
+//! records a [`RolloverEvent`] when a vehicle rolls past its tip-over angle.
+//! The tip-over angle follows from the static stability factor:
+//!
+//! ```text
+//! SSF = T / (2 h)
+//! φ_c = atan(SSF)
+//! ```
+//!
+//! Where T is the track width and h the center of gravity height. Past φ_c
+//! the center of gravity is outboard of the tire contact line and the
+//! vehicle keeps rolling without further lateral load.
+//!
+//! Roll is measured about the vehicle's longitudinal axis; a positive angle
+//! means the left side has lifted and the vehicle rolls toward its right.
+
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
+
+use crate::rigid_body::RigidBody;
+use crate::vehicle::Vehicle;
+
+/// Default roll angle at which the wheels on one side are taken to have
+/// lifted (rad).
+pub const DEFAULT_LIFT_ANGLE: f64 = PI / 36.0;
+
+/// Direction a vehicle rolls in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RollDirection {
+    /// Left side lifts; vehicle rolls onto its right side.
+    Right,
+
+    /// Right side lifts; vehicle rolls onto its left side.
+    Left,
+}
+
+/// Orientation in which a vehicle came to rest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RestOrientation {
+    /// Upright on its wheels.
+    Wheels,
+
+    /// On its right side.
+    RightSide,
+
+    /// On its roof.
+    Roof,
+
+    /// On its left side.
+    LeftSide,
+
+    /// On its front or rear end.
+    End,
+}
+
+impl RestOrientation {
+    /// Classifies a body's orientation by the axis closest to vertical.
+    pub fn from_body(body: &RigidBody) -> Self {
+        let forward = body.orientation * Vector3::x();
+        let left = body.orientation * Vector3::y();
+        let up = body.orientation * Vector3::z();
+
+        if forward.z.abs() > left.z.abs() && forward.z.abs() > up.z.abs() {
+            Self::End
+        } else if left.z.abs() > up.z.abs() {
+            if left.z > 0.0 {
+                Self::RightSide
+            } else {
+                Self::LeftSide
+            }
+        } else if up.z > 0.0 {
+            Self::Wheels
+        } else {
+            Self::Roof
+        }
+    }
+}
+
+/// Thresholds for rollover detection.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RolloverThresholds {
+    /// Roll angle at which the wheels on one side are taken to have lifted
+    /// (rad).
+    pub lift_angle: f64,
+
+    /// Speed below which a rolled vehicle is at rest (m/s).
+    pub rest_speed: f64,
+
+    /// Angular speed below which a rolled vehicle is at rest (rad/s).
+    pub rest_angular_speed: f64,
+}
+
+impl Default for RolloverThresholds {
+    fn default() -> Self {
+        Self {
+            lift_angle: DEFAULT_LIFT_ANGLE,
+            rest_speed: 0.1,
+            rest_angular_speed: 0.1,
+        }
+    }
+}
+
+/// Where and how a rollover started.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TripPoint {
+    /// Time at which the wheels lifted (s).
+    pub time: f64,
+
+    /// Center of gravity position when the wheels lifted (m).
+    pub position: Vector3<f64>,
+
+    /// Speed when the wheels lifted (m/s).
+    pub speed: f64,
+
+    /// Roll rate when the wheels lifted (rad/s).
+    pub roll_rate: f64,
+}
+
+/// Record of a vehicle rolling over.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RolloverEvent {
+    /// Vehicle ID.
+    pub vehicle: usize,
+
+    /// Chassis body ID.
+    pub body: usize,
+
+    /// Where the rollover started.
+    pub trip: TripPoint,
+
+    /// Time at which the roll passed the tip-over angle (s).
+    pub tip_over_time: f64,
+
+    /// Direction of roll.
+    pub direction: RollDirection,
+
+    /// Completed quarter-turns (rounded to the nearest once at rest).
+    pub quarter_turns: u32,
+
+    /// Largest roll rate magnitude (rad/s).
+    pub max_roll_rate: f64,
+
+    /// Time at which the vehicle came to rest (s).
+    pub rest_time: Option<f64>,
+
+    /// Center of gravity position at rest (m).
+    pub rest_position: Option<Vector3<f64>>,
+
+    /// Orientation at rest.
+    pub rest_orientation: Option<RestOrientation>,
+}
+
+impl RolloverEvent {
+    /// Whether the vehicle has come to rest.
+    pub fn is_complete(&self) -> bool {
+        self.rest_time.is_some()
+    }
+
+    /// Horizontal distance from the trip point to the rest position (m).
+    pub fn roll_distance(&self) -> Option<f64> {
+        self.rest_position.map(|rest| (rest - self.trip.position).xy().norm())
+    }
+}
+
+/// Rollover phase of a vehicle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RolloverPhase {
+    /// All wheels down (or a rollover has been resolved).
+    #[default]
+    Upright,
+
+    /// Wheels on one side have lifted but the tip-over angle is not reached.
+    Lifted,
+
+    /// Past the tip-over angle and still moving.
+    Rolling,
+
+    /// Came to rest after rolling over.
+    AtRest,
+}
+
+/// Roll state of one vehicle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RolloverState {
+    /// Vehicle ID.
+    pub vehicle: usize,
+
+    /// Static stability factor T / (2 h).
+    pub static_stability_factor: f64,
+
+    /// Tip-over roll angle atan(SSF) (rad).
+    pub critical_angle: f64,
+
+    /// Roll angle, in (-π, π] (rad).
+    pub roll_angle: f64,
+
+    /// Roll rate about the vehicle's longitudinal axis (rad/s).
+    pub roll_rate: f64,
+
+    /// Unwrapped roll angle since tracking began (rad).
+    pub cumulative_roll: f64,
+
+    /// Largest roll angle magnitude seen (rad).
+    pub max_roll_angle: f64,
+
+    /// Current phase.
+    pub phase: RolloverPhase,
+
+    /// Trip point of the current excursion, while lifted or rolling.
+    trip: Option<TripPoint>,
+
+    /// Unwrapped roll angle of the upright pose the excursion started from.
+    upright_roll: f64,
+
+    /// Index of the event being recorded.
+    active_event: Option<usize>,
+}
+
+impl RolloverState {
+    /// Creates the roll state of a vehicle.
+    pub fn new(vehicle: &Vehicle) -> Self {
+        let static_stability_factor = vehicle.static_stability_factor();
+
+        Self {
+            vehicle: vehicle.id,
+            static_stability_factor,
+            critical_angle: static_stability_factor.atan(),
+            roll_angle: 0.0,
+            roll_rate: 0.0,
+            cumulative_roll: 0.0,
+            max_roll_angle: 0.0,
+            phase: RolloverPhase::Upright,
+            trip: None,
+            upright_roll: 0.0,
+            active_event: None,
+        }
+    }
+
+    /// Roll since the excursion started (rad).
+    fn excursion(&self) -> f64 {
+        self.cumulative_roll - self.upright_roll
+    }
+}
+
+/// Rollover analysis for all vehicles in a simulation.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RolloverAnalysis {
+    /// Detection thresholds.
+    pub thresholds: RolloverThresholds,
+
+    /// Roll state per vehicle, indexed by vehicle ID.
+    states: Vec<RolloverState>,
+
+    /// Rollovers, in the order they tipped over.
+    events: Vec<RolloverEvent>,
+}
+
+impl RolloverAnalysis {
+    /// Creates a new rollover analysis.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the detection thresholds.
+    pub fn with_thresholds(mut self, thresholds: RolloverThresholds) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    /// Updates roll state for each vehicle at simulation time `time`.
+    ///
+    /// `bodies` is indexed by body ID.
+    pub fn update(&mut self, vehicles: &[Vehicle], bodies: &[RigidBody], time: f64) {
+        for (index, vehicle) in vehicles.iter().enumerate() {
+            if self.states.len() <= index {
+                self.states.push(RolloverState::new(vehicle));
+            }
+
+            let Some(body) = bodies.get(vehicle.chassis) else {
+                continue;
+            };
+
+            self.update_vehicle(index, vehicle.chassis, body, time);
+        }
+    }
+
+    fn update_vehicle(&mut self, index: usize, body_id: usize, body: &RigidBody, time: f64) {
+        let thresholds = self.thresholds;
+        let state = &mut self.states[index];
+
+        let roll = roll_angle(body);
+        state.cumulative_roll += wrap_angle(roll - state.roll_angle);
+        state.roll_angle = roll;
+        state.roll_rate = body.angular_velocity.dot(&(body.orientation * Vector3::x()));
+        state.max_roll_angle = state.max_roll_angle.max(roll.abs());
+
+        match state.phase {
+            RolloverPhase::Upright => {
+                if roll.abs() >= thresholds.lift_angle {
+                    state.phase = RolloverPhase::Lifted;
+                    state.upright_roll = state.cumulative_roll - roll;
+                    state.trip = Some(TripPoint {
+                        time,
+                        position: body.position,
+                        speed: body.linear_velocity.norm(),
+                        roll_rate: state.roll_rate,
+                    });
+                }
+            },
+            RolloverPhase::Lifted => {
+                if state.excursion().abs() < thresholds.lift_angle {
+                    // Two-wheel excursion that settled back
+                    state.phase = RolloverPhase::Upright;
+                    state.trip = None;
+                } else if state.excursion().abs() >= state.critical_angle {
+                    state.phase = RolloverPhase::Rolling;
+                    state.active_event = Some(self.events.len());
+                    self.events.push(RolloverEvent {
+                        vehicle: state.vehicle,
+                        body: body_id,
+                        trip: state.trip.clone().unwrap_or(TripPoint {
+                            time,
+                            position: body.position,
+                            speed: body.linear_velocity.norm(),
+                            roll_rate: state.roll_rate,
+                        }),
+                        tip_over_time: time,
+                        direction: if state.excursion() > 0.0 {
+                            RollDirection::Right
+                        } else {
+                            RollDirection::Left
+                        },
+                        quarter_turns: 0,
+                        max_roll_rate: 0.0,
+                        rest_time: None,
+                        rest_position: None,
+                        rest_orientation: None,
+                    });
+                }
+            },
+            RolloverPhase::Rolling => {},
+            RolloverPhase::AtRest => {
+                // Re-arm once the vehicle is back on its wheels
+                if RestOrientation::from_body(body) == RestOrientation::Wheels
+                    && roll.abs() < thresholds.lift_angle
+                {
+                    state.phase = RolloverPhase::Upright;
+                }
+            },
+        }
+
+        let Some(event) = state.active_event.and_then(|active| self.events.get_mut(active)) else {
+            return;
+        };
+
+        let quarters = state.excursion().abs() / FRAC_PI_2;
+        event.quarter_turns = quarters.floor() as u32;
+        event.max_roll_rate = event.max_roll_rate.max(state.roll_rate.abs());
+
+        let at_rest = !body.is_awake
+            || (body.linear_velocity.norm() < thresholds.rest_speed
+                && body.angular_velocity.norm() < thresholds.rest_angular_speed);
+        if at_rest {
+            event.quarter_turns = quarters.round() as u32;
+            event.rest_time = Some(time);
+            event.rest_position = Some(body.position);
+            event.rest_orientation = Some(RestOrientation::from_body(body));
+
+            state.phase = RolloverPhase::AtRest;
+            state.trip = None;
+            state.active_event = None;
+        }
+    }
+
+    /// Roll state of a vehicle.
+    pub fn state(&self, vehicle: usize) -> Option<&RolloverState> {
+        self.states.get(vehicle)
+    }
+
+    /// Rollovers recorded so far, including ones still in progress.
+    pub fn events(&self) -> &[RolloverEvent] {
+        &self.events
+    }
+
+    /// Generates a human-readable summary.
+    pub fn summary(&self) -> String {
+        let mut summary = format!("Rollover Analysis: {} event(s)", self.events.len());
+
+        for event in &self.events {
+            let rest = match (event.rest_time, event.rest_orientation) {
+                (Some(time), Some(orientation)) => {
+                    format!("at rest on {orientation:?} at {time:.2} s")
+                },
+                _ => "still rolling".to_string(),
+            };
+            summary.push_str(&format!(
+                "\nVehicle {}: tripped at {:.2} s, rolled {:?}, {} quarter-turn(s), {}",
+                event.vehicle, event.trip.time, event.direction, event.quarter_turns, rest
+            ));
+        }
+
+        summary
+    }
+}
+
+/// Roll angle of a body about its longitudinal axis, in (-π, π] (rad).
+///
+/// Positive when the left side has lifted.
+pub fn roll_angle(body: &RigidBody) -> f64 {
+    let left = body.orientation * Vector3::y();
+    let up = body.orientation * Vector3::z();
+    left.z.atan2(up.z)
+}
+
+/// Wraps an angle difference into (-π, π].
+fn wrap_angle(angle: f64) -> f64 {
+    let wrapped = (angle + PI).rem_euclid(2.0 * PI) - PI;
+    if wrapped == -PI {
+        PI
+    } else {
+        wrapped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rigid_body::MassProperties;
+    use approx::assert_relative_eq;
+    use nalgebra::UnitQuaternion;
+
+    fn rolled_body(roll: f64, roll_rate: f64) -> RigidBody {
+        let mass_props = MassProperties::from_box(1500.0, Vector3::new(4.5, 1.8, 1.5));
+        let mut body = RigidBody::new(0, mass_props);
+        body.orientation = UnitQuaternion::from_axis_angle(&Vector3::x_axis(), roll);
+        body.position = Vector3::new(roll, 0.0, 0.75);
+        body.angular_velocity = Vector3::new(roll_rate, 0.0, 0.0);
+        body.linear_velocity = Vector3::new(roll_rate, 0.0, 0.0);
+        body
+    }
+
+    #[test]
+    fn test_static_stability_factor() {
+        let vehicle = Vehicle::create_passenger_car(0, 0);
+        let state = RolloverState::new(&vehicle);
+
+        assert_relative_eq!(state.static_stability_factor, 1.5);
+        assert_relative_eq!(state.critical_angle, 1.5_f64.atan());
+    }
+
+    #[test]
+    fn test_roll_angle_and_rest_orientation() {
+        assert_relative_eq!(roll_angle(&rolled_body(0.4, 0.0)), 0.4, epsilon = 1e-9);
+        assert_relative_eq!(roll_angle(&rolled_body(-0.4, 0.0)), -0.4, epsilon = 1e-9);
+
+        let orientations = [
+            (0.0, RestOrientation::Wheels),
+            (FRAC_PI_2, RestOrientation::RightSide),
+            (PI, RestOrientation::Roof),
+            (-FRAC_PI_2, RestOrientation::LeftSide),
+        ];
+        for (roll, expected) in orientations {
+            assert_eq!(
+                RestOrientation::from_body(&rolled_body(roll, 0.0)),
+                expected
+            );
+        }
+
+        let mut body = rolled_body(0.0, 0.0);
+        body.orientation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), FRAC_PI_2);
+        assert_eq!(RestOrientation::from_body(&body), RestOrientation::End);
+    }
+
+    #[test]
+    fn test_rollover_event() {
+        let vehicles = vec![Vehicle::create_passenger_car(0, 0)];
+        let mut analysis = RolloverAnalysis::new();
+
+        // Roll three quarter-turns to the right, then come to rest
+        let mut time = 0.0;
+        let mut roll: f64 = 0.0;
+        while roll < 1.5 * PI {
+            analysis.update(&vehicles, &[rolled_body(roll, 4.0)], time);
+            roll += 0.1;
+            time += 0.025;
+        }
+
+        let event = &analysis.events()[0];
+        assert_eq!(event.direction, RollDirection::Right);
+        assert!(!event.is_complete());
+        assert_eq!(event.quarter_turns, 2);
+        assert_relative_eq!(event.trip.time, 0.025);
+        assert!(event.tip_over_time > event.trip.time);
+
+        analysis.update(&vehicles, &[rolled_body(1.5 * PI, 0.0)], time);
+
+        let event = &analysis.events()[0];
+        assert_eq!(analysis.events().len(), 1);
+        assert_eq!(event.quarter_turns, 3);
+        assert_eq!(event.rest_orientation, Some(RestOrientation::LeftSide));
+        assert_relative_eq!(event.rest_time.unwrap(), time);
+        assert_relative_eq!(event.max_roll_rate, 4.0);
+        assert_relative_eq!(
+            event.roll_distance().unwrap(),
+            1.5 * PI - 0.1,
+            epsilon = 1e-9
+        );
+        assert_eq!(analysis.state(0).unwrap().phase, RolloverPhase::AtRest);
+    }
+
+    #[test]
+    fn test_two_wheel_excursion_is_not_a_rollover() {
+        let vehicles = vec![Vehicle::create_passenger_car(0, 0)];
+        let mut analysis = RolloverAnalysis::new();
+
+        for (step, roll) in [0.0, -0.2, -0.5, -0.3, 0.0].into_iter().enumerate() {
+            analysis.update(&vehicles, &[rolled_body(roll, -1.0)], step as f64 * 0.1);
+        }
+
+        let state = analysis.state(0).unwrap();
+        assert!(analysis.events().is_empty());
+        assert_eq!(state.phase, RolloverPhase::Upright);
+        assert_relative_eq!(state.max_roll_angle, 0.5);
+    }
+}
@@ -5,6 +5,8 @@
 
 use thiserror::Error;
 
+use crate::solver::diagnostics::{DivergenceReport, Remediation};
+
 /// Result type alias for physics operations.
 pub type PhysicsResult<T> = Result<T, PhysicsError>;
 
@@ -39,6 +41,10 @@ pub enum PhysicsError {
     #[error("Collision detection error: {0}")]
     CollisionDetectionError(String),
 
+    /// Solver diverged (NaNs, exploding velocities or growing residual).
+    #[error("Solver divergence: {0}")]
+    SolverDivergence(Box<DivergenceReport>),
+
     /// Constraint violation beyond tolerance.
     #[error("Constraint violation: {constraint_type}, error = {error}")]
     ConstraintViolation { constraint_type: String, error: f64 },
@@ -51,6 +57,15 @@ pub enum PhysicsError {
     #[error("Vehicle dynamics error: {0}")]
     VehicleDynamicsError(String),
 
+    /// Replayed state differs from the recording.
+    #[error("Replay diverged at step {step} (t = {time} s): state hash {actual:016x}, recorded {expected:016x}")]
+    ReplayDivergence {
+        step: usize,
+        time: f64,
+        expected: u64,
+        actual: u64,
+    },
+
     /// Thread synchronization error.
     #[error("Thread synchronization error: {0}")]
     SyncError(String),
@@ -77,6 +92,42 @@ impl PhysicsError {
     pub fn generic(message: impl Into<String>) -> Self {
         Self::Generic(message.into())
     }
+
+    /// Suggested fixes for the error, most likely first.
+    pub fn remediation(&self) -> Vec<Remediation> {
+        match self {
+            Self::SolverDivergence(report) => report.remediation.clone(),
+            Self::ConvergenceFailure(iterations, _) => vec![Remediation::IncreaseIterations {
+                current: *iterations,
+                suggested: (*iterations * 2).max(1),
+            }],
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(feature = "errors")]
+impl From<PhysicsError> for accuscene_errors::AccuSceneError {
+    fn from(error: PhysicsError) -> Self {
+        let mut converted = Self::physics(error.to_string());
+
+        if let PhysicsError::SolverDivergence(report) = &error {
+            // The world rolls back a diverged step, so it can be retried
+            converted = converted
+                .with_metadata("solver_stage", report.stage.to_string())
+                .with_recoverable(true);
+            if !report.bodies.is_empty() {
+                converted = converted.with_metadata("bodies", format!("{:?}", report.bodies));
+            }
+        }
+
+        error
+            .remediation()
+            .iter()
+            .fold(converted, |converted, remedy| {
+                converted.with_suggested_action(remedy.to_string())
+            })
+    }
 }
 
 #[cfg(test)]
@@ -95,4 +146,17 @@ mod tests {
         assert!(error.to_string().contains("100"));
         assert!(error.to_string().contains("0.000001"));
     }
+
+    #[cfg(feature = "errors")]
+    #[test]
+    fn test_into_accuscene_error() {
+        use accuscene_errors::{AccuSceneError, ErrorCode};
+
+        let error: AccuSceneError = PhysicsError::ConvergenceFailure(10, 1e-6).into();
+        assert_eq!(error.code(), ErrorCode::Physics);
+        assert_eq!(
+            error.suggested_actions(),
+            ["increase solver iterations from 10 to 20".to_string()]
+        );
+    }
 }
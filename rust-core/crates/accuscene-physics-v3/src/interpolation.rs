@@ -0,0 +1,199 @@
+//! State interpolation for rendering between physics steps.
+//!
+//! Physics runs at a fixed time step while rendering runs at display rate.
+//! Rendering the latest physics state directly makes motion judder whenever
+//! the two rates do not line up. Instead, the renderer accumulates frame
+//! time, steps physics while a full `dt` is available, and draws the state
+//! blended between the last two steps by the leftover fraction:
+//!
+//! ```text
+//! alpha = accumulator / dt
+//! x     = lerp(x_prev, x_curr, alpha)
+//! q     = slerp(q_prev, q_curr, alpha)
+//! ```
+//!
+//! For `alpha > 1` (physics fell behind) the state is extrapolated from the
+//! latest velocities instead.
+
+use nalgebra::{UnitQuaternion, Vector3};
+use serde::{Deserialize, Serialize};
+
+use crate::rigid_body::{BodyState, RigidBody};
+
+/// Maximum extrapolation, in steps beyond the latest state.
+pub const MAX_EXTRAPOLATION_STEPS: f64 = 1.0;
+
+/// Renderable transform of a rigid body at one physics step.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BodyTransform {
+    /// Body identifier.
+    pub id: usize,
+
+    /// Position in world space (m).
+    pub position: Vector3<f64>,
+
+    /// Orientation.
+    pub orientation: UnitQuaternion<f64>,
+
+    /// Linear velocity (m/s), used for extrapolation.
+    pub linear_velocity: Vector3<f64>,
+
+    /// Angular velocity (rad/s) in world space, used for extrapolation.
+    pub angular_velocity: Vector3<f64>,
+}
+
+impl BodyTransform {
+    /// Captures the transform of a rigid body.
+    pub fn from_body(body: &RigidBody) -> Self {
+        Self {
+            id: body.id,
+            position: body.position,
+            orientation: body.orientation,
+            linear_velocity: body.linear_velocity,
+            angular_velocity: body.angular_velocity,
+        }
+    }
+
+    /// Captures the transform of a body from saved state.
+    pub fn from_state(id: usize, state: &BodyState) -> Self {
+        Self {
+            id,
+            position: state.position,
+            orientation: state.orientation,
+            linear_velocity: state.linear_velocity,
+            angular_velocity: state.angular_velocity,
+        }
+    }
+
+    /// Blends from `previous` towards this transform.
+    ///
+    /// `alpha` in `[0, 1]` interpolates between the two steps; values above
+    /// 1 extrapolate this transform by `(alpha - 1) * dt` using its
+    /// velocities, capped at [`MAX_EXTRAPOLATION_STEPS`].
+    pub fn blend_from(&self, previous: &BodyTransform, alpha: f64, dt: f64) -> BodyTransform {
+        let alpha = alpha.clamp(0.0, 1.0 + MAX_EXTRAPOLATION_STEPS);
+
+        if alpha <= 1.0 {
+            let position = previous.position.lerp(&self.position, alpha);
+            let orientation = previous
+                .orientation
+                .try_slerp(&self.orientation, alpha, 1e-9)
+                .unwrap_or(self.orientation);
+
+            return BodyTransform {
+                position,
+                orientation,
+                ..self.clone()
+            };
+        }
+
+        self.extrapolate((alpha - 1.0) * dt)
+    }
+
+    /// Advances the transform by `time` seconds at constant velocity.
+    pub fn extrapolate(&self, time: f64) -> BodyTransform {
+        let rotation = UnitQuaternion::from_scaled_axis(self.angular_velocity * time);
+
+        BodyTransform {
+            position: self.position + self.linear_velocity * time,
+            orientation: rotation * self.orientation,
+            ..self.clone()
+        }
+    }
+}
+
+/// Blends two sets of body transforms, matching bodies by ID.
+///
+/// Bodies without a previous transform (added during the last step) are
+/// returned at their current transform, extrapolated if `alpha > 1`.
+pub fn interpolate_transforms(
+    previous: &[BodyTransform],
+    current: &[BodyTransform],
+    alpha: f64,
+    dt: f64,
+) -> Vec<BodyTransform> {
+    current
+        .iter()
+        .enumerate()
+        .map(|(index, transform)| {
+            // Bodies are usually stored in the same order in both steps
+            let previous = previous
+                .get(index)
+                .filter(|p| p.id == transform.id)
+                .or_else(|| previous.iter().find(|p| p.id == transform.id))
+                .unwrap_or(transform);
+
+            transform.blend_from(previous, alpha, dt)
+        })
+        .collect()
+}
+
+/// Interpolated world state for rendering.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterpolatedState {
+    /// Simulation time the state corresponds to (s).
+    pub time: f64,
+
+    /// Blend factor that was used.
+    pub alpha: f64,
+
+    /// Body transforms.
+    pub bodies: Vec<BodyTransform>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use std::f64::consts::FRAC_PI_2;
+
+    fn transform(id: usize, x: f64, yaw: f64) -> BodyTransform {
+        BodyTransform {
+            id,
+            position: Vector3::new(x, 0.0, 0.0),
+            orientation: UnitQuaternion::from_euler_angles(0.0, 0.0, yaw),
+            linear_velocity: Vector3::new(10.0, 0.0, 0.0),
+            angular_velocity: Vector3::new(0.0, 0.0, 1.0),
+        }
+    }
+
+    #[test]
+    fn test_interpolation_lerps_and_slerps() {
+        let previous = transform(0, 0.0, 0.0);
+        let current = transform(0, 1.0, FRAC_PI_2);
+
+        let halfway = current.blend_from(&previous, 0.5, 0.1);
+
+        assert_relative_eq!(halfway.position.x, 0.5);
+        assert_relative_eq!(halfway.orientation.euler_angles().2, FRAC_PI_2 / 2.0, epsilon = 1e-9);
+
+        assert_eq!(current.blend_from(&previous, 0.0, 0.1).position, previous.position);
+        assert_eq!(current.blend_from(&previous, 1.0, 0.1).position, current.position);
+    }
+
+    #[test]
+    fn test_extrapolation_uses_velocity_and_is_capped() {
+        let previous = transform(0, 0.0, 0.0);
+        let current = transform(0, 1.0, 0.0);
+
+        let ahead = current.blend_from(&previous, 1.5, 0.1);
+        assert_relative_eq!(ahead.position.x, 1.5);
+        assert_relative_eq!(ahead.orientation.euler_angles().2, 0.05, epsilon = 1e-9);
+
+        let capped = current.blend_from(&previous, 10.0, 0.1);
+        assert_relative_eq!(capped.position.x, 2.0);
+    }
+
+    #[test]
+    fn test_interpolate_matches_by_id() {
+        let previous = vec![transform(1, 0.0, 0.0), transform(0, 2.0, 0.0)];
+        let current = vec![transform(0, 4.0, 0.0), transform(1, 1.0, 0.0), transform(2, 9.0, 0.0)];
+
+        let blended = interpolate_transforms(&previous, &current, 0.5, 0.1);
+
+        assert_relative_eq!(blended[0].position.x, 3.0);
+        assert_relative_eq!(blended[1].position.x, 0.5);
+        // New body has no previous state
+        assert_relative_eq!(blended[2].position.x, 9.0);
+    }
+}
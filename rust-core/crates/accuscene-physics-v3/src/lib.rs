@@ -8,8 +8,16 @@
 //! - **Advanced Collision Detection**: GJK/EPA narrow phase, sweep-and-prune broad phase
 //! - **Deformable Bodies**: Finite Element Method (FEM) for crush analysis
 //! - **Vehicle Physics**: Pacejka tire model, suspension, powertrain
+//! - **Articulated Vehicles**: Fifth-wheel trailers with jackknife detection
+//! - **Pedestrians**: Articulated pedestrian and cyclist models with throw-distance analysis
 //! - **Constraint Solvers**: Sequential Impulse and Projected Gauss-Seidel
+//! - **Joints**: Ball, hinge, prismatic and fixed joints, optionally breakable
+//! - **Solver Diagnostics**: Residual tracking and divergence detection with remediation hints
+//! - **Render Interpolation**: Blended body transforms between fixed physics steps
+//! - **Batch Simulation**: Parallel parameter sweeps over friction, restitution and initial velocities
+//! - **Deterministic Replay**: Recorded step inputs and state hashes for bit-identical replays
 //! - **Energy Analysis**: Kinetic, deformation, and dissipation tracking
+//! - **Rollover Analysis**: Roll angle, static stability factor and rollover events per vehicle
 //!
 //! # Example
 //!
@@ -87,22 +95,30 @@
 #![warn(missing_docs)]
 #![warn(clippy::all)]
 
+pub mod batch;
 pub mod collision;
 pub mod config;
 pub mod deformable;
 pub mod energy;
 pub mod error;
+pub mod interpolation;
+pub mod pedestrian;
+pub mod replay;
 pub mod rigid_body;
 pub mod solver;
 pub mod vehicle;
 
 /// Prelude module for convenient imports.
 pub mod prelude {
+    pub use crate::batch::*;
     pub use crate::collision::*;
     pub use crate::config::*;
     pub use crate::deformable::*;
     pub use crate::energy::*;
     pub use crate::error::*;
+    pub use crate::interpolation::*;
+    pub use crate::pedestrian::*;
+    pub use crate::replay::*;
     pub use crate::rigid_body::*;
     pub use crate::solver::*;
     pub use crate::vehicle::*;
@@ -110,16 +126,27 @@ pub mod prelude {
     pub use nalgebra::{Matrix3, Quaternion, UnitQuaternion, Vector3};
 }
 
-use collision::{BroadPhase, CollisionShape, NarrowPhase, AABB};
-use config::PhysicsConfig;
+use serde::{Deserialize, Serialize};
+
+use collision::{BroadPhase, CollisionShape, NarrowPhase};
+use config::{CollisionConfig, PhysicsConfig};
 use deformable::DeformableBody;
-use energy::EnergyAnalysis;
+use energy::{EnergyAnalysis, RolloverAnalysis, RolloverEvent};
 use error::PhysicsResult;
-use rigid_body::{constraints::ContactConstraint, RigidBody};
-use solver::PhysicsSolver;
-use vehicle::Vehicle;
+use interpolation::{BodyTransform, InterpolatedState};
+use pedestrian::{Pedestrian, PedestrianModel, PedestrianParameters, Segment};
+use replay::{Recorder, Recording, Replay};
+use error::PhysicsError;
+use rigid_body::{
+    constraints::ContactConstraint,
+    joints::{Joint, JointBreak},
+    BodyState, RigidBody,
+};
+use solver::{PhysicsSolver, SolverDiagnostics, SolverStage};
+use vehicle::{ArticulationStatus, Trailer, Vehicle};
 
 /// Main physics world containing all simulation state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PhysicsWorld {
     /// Configuration.
     config: PhysicsConfig,
@@ -136,6 +163,9 @@ pub struct PhysicsWorld {
     /// Vehicles.
     vehicles: Vec<Vehicle>,
 
+    /// Pedestrians and cyclists.
+    pedestrians: Vec<Pedestrian>,
+
     /// Broad phase collision detector.
     broad_phase: BroadPhase,
 
@@ -153,12 +183,30 @@ pub struct PhysicsWorld {
 
     /// Energy analysis.
     energy_analysis: EnergyAnalysis,
+
+    /// Rollover analysis of the vehicles.
+    rollover_analysis: RolloverAnalysis,
+
+    /// Body transforms before the last step, for render interpolation.
+    previous_transforms: Vec<BodyTransform>,
+
+    /// Time step of the last step.
+    last_dt: f64,
+
+    /// Contacts resolved in the last step.
+    contacts: Vec<ContactConstraint>,
+
+    /// Joints between rigid bodies.
+    joints: Vec<Joint>,
+
+    /// Joints that broke, in order.
+    joint_breaks: Vec<JointBreak>,
 }
 
 impl PhysicsWorld {
     /// Creates a new physics world.
     pub fn new(config: PhysicsConfig) -> Self {
-        let broad_phase = BroadPhase::new(config.collision.broad_phase);
+        let broad_phase = BroadPhase::from_config(&config);
         let narrow_phase = NarrowPhase::new();
         let solver = PhysicsSolver::new(config.solver.clone());
 
@@ -168,12 +216,19 @@ impl PhysicsWorld {
             deformable_bodies: Vec::new(),
             shapes: Vec::new(),
             vehicles: Vec::new(),
+            pedestrians: Vec::new(),
             broad_phase,
             narrow_phase,
             solver,
             time: 0.0,
             gravity: nalgebra::Vector3::new(0.0, 0.0, -9.81),
             energy_analysis: EnergyAnalysis::new(),
+            rollover_analysis: RolloverAnalysis::new(),
+            previous_transforms: Vec::new(),
+            last_dt: 0.0,
+            contacts: Vec::new(),
+            joints: Vec::new(),
+            joint_breaks: Vec::new(),
         }
     }
 
@@ -185,6 +240,33 @@ impl PhysicsWorld {
         id
     }
 
+    /// Adds a joint between two rigid bodies.
+    ///
+    /// The joint is bound to the bodies' current poses, so add it once the
+    /// bodies are in their assembled positions.
+    pub fn add_joint(&mut self, mut joint: Joint) -> PhysicsResult<usize> {
+        let (Some(body_a), Some(body_b)) =
+            (self.bodies.get(joint.body_a), self.bodies.get(joint.body_b))
+        else {
+            return Err(PhysicsError::invalid_state(format!(
+                "joint references unknown body ({} or {})",
+                joint.body_a, joint.body_b
+            )));
+        };
+        if joint.body_a == joint.body_b {
+            return Err(PhysicsError::invalid_state(format!(
+                "joint connects body {} to itself",
+                joint.body_a
+            )));
+        }
+
+        joint.bind(body_a, body_b);
+
+        let id = self.joints.len();
+        self.joints.push(joint);
+        Ok(id)
+    }
+
     /// Adds a deformable body to the world.
     pub fn add_deformable_body(&mut self, body: DeformableBody) -> usize {
         let id = self.deformable_bodies.len();
@@ -199,19 +281,120 @@ impl PhysicsWorld {
         id
     }
 
+    /// Adds a pedestrian (or cyclist) standing at `position` on the ground,
+    /// facing `heading` (rad from the x axis).
+    ///
+    /// Adds a body per segment, the bicycle for cyclists, and the joints
+    /// linking the segments.
+    pub fn add_pedestrian(
+        &mut self,
+        params: PedestrianParameters,
+        position: nalgebra::Vector3<f64>,
+        heading: f64,
+    ) -> PhysicsResult<usize> {
+        let model = PedestrianModel::build(&params, self.bodies.len(), position, heading);
+
+        let segments = model
+            .segments
+            .into_iter()
+            .map(|segment| Segment {
+                kind: segment.kind,
+                body: self.add_body(segment.body, segment.shape),
+                length: segment.length,
+            })
+            .collect();
+        let bicycle = model
+            .bicycle
+            .map(|(body, shape)| self.add_body(body, shape));
+        let joints = model
+            .joints
+            .into_iter()
+            .map(|joint| self.add_joint(joint))
+            .collect::<PhysicsResult<Vec<_>>>()?;
+
+        let id = self.pedestrians.len();
+        let mut pedestrian = Pedestrian::new(id, params, segments, joints);
+        pedestrian.bicycle = bicycle;
+        self.pedestrians.push(pedestrian);
+        Ok(id)
+    }
+
+    /// Hitches a trailer behind a vehicle, returning the trailer's index.
+    ///
+    /// The trailer body must already be in the world with its kingpin over
+    /// the fifth wheel of the vehicle's rearmost unit. Creates the coupling
+    /// joint.
+    pub fn hitch_trailer(
+        &mut self,
+        vehicle_id: usize,
+        mut trailer: Trailer,
+    ) -> PhysicsResult<usize> {
+        let vehicle = self.vehicles.get(vehicle_id).ok_or_else(|| {
+            PhysicsError::VehicleDynamicsError(format!("unknown vehicle {vehicle_id}"))
+        })?;
+        let lead = vehicle.rear_unit();
+        let (Some(lead_body), Some(trailer_body)) =
+            (self.bodies.get(lead), self.bodies.get(trailer.body))
+        else {
+            return Err(PhysicsError::VehicleDynamicsError(format!(
+                "trailer {} references unknown body ({} or {})",
+                trailer.id, lead, trailer.body
+            )));
+        };
+
+        let joint = vehicle.coupling_joint(lead_body, trailer_body, &trailer)?;
+        trailer.coupling = Some(self.add_joint(joint)?);
+
+        Ok(self.vehicles[vehicle_id].hitch(trailer))
+    }
+
+    /// Updates a vehicle's trailer axles and checks for a jackknife.
+    ///
+    /// See [`Vehicle::update_trailers`].
+    pub fn update_trailers(
+        &mut self,
+        vehicle_id: usize,
+        dt: f64,
+        brake: f64,
+    ) -> PhysicsResult<ArticulationStatus> {
+        let vehicle = self.vehicles.get_mut(vehicle_id).ok_or_else(|| {
+            PhysicsError::VehicleDynamicsError(format!("unknown vehicle {vehicle_id}"))
+        })?;
+        vehicle.update_trailers(&mut self.bodies, dt, brake)
+    }
+
     /// Performs one physics simulation step.
+    ///
+    /// If the step fails (e.g. the solver diverges), rigid bodies are
+    /// restored to their state before the step and the error is returned.
     pub fn step(&mut self, dt: f64) -> PhysicsResult<()> {
-        // Broad phase collision detection
-        let aabbs: Vec<(usize, AABB)> = self
+        let states: Vec<BodyState> = self.bodies.iter().map(RigidBody::state).collect();
+        let joints = self.joints.clone();
+
+        if let Err(error) = self.advance(dt) {
+            for (body, state) in self.bodies.iter_mut().zip(&states) {
+                body.restore(state);
+            }
+            self.joints = joints;
+            return Err(error);
+        }
+
+        self.previous_transforms = self
             .bodies
             .iter()
-            .enumerate()
-            .map(|(id, body)| {
-                let shape = &self.shapes[id];
-                let aabb = shape.compute_aabb(body.position, &body.orientation);
-                (id, aabb)
-            })
+            .zip(&states)
+            .map(|(body, state)| BodyTransform::from_state(body.id, state))
             .collect();
+        self.last_dt = dt;
+
+        Ok(())
+    }
+
+    /// Runs collision detection, constraint solving and integration.
+    fn advance(&mut self, dt: f64) -> PhysicsResult<()> {
+        // Broad phase collision detection
+        self.broad_phase.configure(&self.config);
+        let aabbs = self.broad_phase.compute_aabbs(&self.bodies, &self.shapes);
 
         let pairs = self.broad_phase.detect_pairs(&aabbs);
 
@@ -219,6 +402,10 @@ impl PhysicsWorld {
         let mut contacts = Vec::new();
 
         for pair in pairs {
+            if self.is_jointed_without_collision(pair.body_a, pair.body_b) {
+                continue;
+            }
+
             let body_a = &self.bodies[pair.body_a];
             let body_b = &self.bodies[pair.body_b];
             let shape_a = &self.shapes[pair.body_a];
@@ -255,7 +442,13 @@ impl PhysicsWorld {
         }
 
         // Solve constraints
-        self.solver.solve_constraints(&mut self.bodies, &mut contacts, dt)?;
+        let intact: Vec<bool> = self.joints.iter().map(Joint::is_active).collect();
+        self.solver.solve_constraints_with_joints(
+            &mut self.bodies,
+            &mut contacts,
+            &mut self.joints,
+            dt,
+        )?;
 
         // Integrate motion
         for body in &mut self.bodies {
@@ -269,6 +462,13 @@ impl PhysicsWorld {
             body.check_sleep(dt);
         }
 
+        self.solver.diagnostics().check_state(
+            SolverStage::Integration,
+            None,
+            &self.bodies,
+            &contacts,
+        )?;
+
         // Update deformable bodies
         for deformable_body in &mut self.deformable_bodies {
             let fem_solver = deformable::fem::FEMSolver::new();
@@ -280,10 +480,38 @@ impl PhysicsWorld {
         self.energy_analysis.analyze_deformation(&self.deformable_bodies);
 
         self.time += dt;
+        self.contacts = contacts;
+
+        self.rollover_analysis
+            .update(&self.vehicles, &self.bodies, self.time);
+        for pedestrian in &mut self.pedestrians {
+            pedestrian.update(&self.bodies, self.time);
+        }
+
+        for (id, joint) in self.joints.iter().enumerate() {
+            if intact[id] && joint.broken {
+                self.joint_breaks.push(JointBreak {
+                    joint: id,
+                    time: self.time,
+                    force: joint.reaction_force,
+                    torque: joint.reaction_torque,
+                });
+            }
+        }
 
         Ok(())
     }
 
+    /// Whether an intact joint between the bodies disables their collisions.
+    fn is_jointed_without_collision(&self, body_a: usize, body_b: usize) -> bool {
+        self.joints.iter().any(|joint| {
+            joint.is_active()
+                && !joint.collide_connected
+                && ((joint.body_a == body_a && joint.body_b == body_b)
+                    || (joint.body_a == body_b && joint.body_b == body_a))
+        })
+    }
+
     /// Gets a reference to a rigid body.
     pub fn body(&self, id: usize) -> Option<&RigidBody> {
         self.bodies.get(id)
@@ -294,20 +522,137 @@ impl PhysicsWorld {
         self.bodies.get_mut(id)
     }
 
+    /// Gets all rigid bodies.
+    pub fn bodies(&self) -> &[RigidBody] {
+        &self.bodies
+    }
+
+    /// Gets a vehicle.
+    pub fn vehicle(&self, id: usize) -> Option<&Vehicle> {
+        self.vehicles.get(id)
+    }
+
+    /// Gets a mutable reference to a vehicle.
+    pub fn vehicle_mut(&mut self, id: usize) -> Option<&mut Vehicle> {
+        self.vehicles.get_mut(id)
+    }
+
+    /// Gets a pedestrian.
+    pub fn pedestrian(&self, id: usize) -> Option<&Pedestrian> {
+        self.pedestrians.get(id)
+    }
+
+    /// Gets all pedestrians.
+    pub fn pedestrians(&self) -> &[Pedestrian] {
+        &self.pedestrians
+    }
+
+    /// Gets a joint.
+    pub fn joint(&self, id: usize) -> Option<&Joint> {
+        self.joints.get(id)
+    }
+
+    /// Gets all joints.
+    pub fn joints(&self) -> &[Joint] {
+        &self.joints
+    }
+
+    /// Gets the joints that broke so far, in the order they broke.
+    pub fn joint_breaks(&self) -> &[JointBreak] {
+        &self.joint_breaks
+    }
+
+    /// Gets the contacts resolved in the last step, with their accumulated impulses.
+    pub fn contacts(&self) -> &[ContactConstraint] {
+        &self.contacts
+    }
+
     /// Gets current simulation time.
     pub fn time(&self) -> f64 {
         self.time
     }
 
+    /// Gets the body state blended between the last two steps for rendering.
+    ///
+    /// `alpha` is the fraction of a step elapsed since the last step
+    /// (typically `accumulator / dt`): 0 gives the state before the last
+    /// step, 1 the current state, and values above 1 extrapolate from the
+    /// current velocities.
+    pub fn interpolated_state(&self, alpha: f64) -> InterpolatedState {
+        let current: Vec<BodyTransform> = self.bodies.iter().map(BodyTransform::from_body).collect();
+        let bodies = interpolation::interpolate_transforms(
+            &self.previous_transforms,
+            &current,
+            alpha,
+            self.last_dt,
+        );
+
+        InterpolatedState {
+            time: self.time + (alpha - 1.0) * self.last_dt,
+            alpha,
+            bodies,
+        }
+    }
+
     /// Gets energy analysis.
     pub fn energy_analysis(&self) -> &EnergyAnalysis {
         &self.energy_analysis
     }
 
+    /// Gets rollover analysis.
+    pub fn rollover_analysis(&self) -> &RolloverAnalysis {
+        &self.rollover_analysis
+    }
+
+    /// Gets the rollovers recorded so far, including ones still in progress.
+    pub fn rollover_events(&self) -> &[RolloverEvent] {
+        self.rollover_analysis.events()
+    }
+
+    /// Gets diagnostics of the last constraint solve.
+    pub fn solver_diagnostics(&self) -> &SolverDiagnostics {
+        self.solver.diagnostics()
+    }
+
+    /// Gets gravity vector.
+    pub fn gravity(&self) -> nalgebra::Vector3<f64> {
+        self.gravity
+    }
+
     /// Sets gravity vector.
     pub fn set_gravity(&mut self, gravity: nalgebra::Vector3<f64>) {
         self.gravity = gravity;
     }
+
+    /// Gets the configuration.
+    pub fn config(&self) -> &PhysicsConfig {
+        &self.config
+    }
+
+    /// Gets the collision configuration for modification.
+    ///
+    /// Contact friction, restitution and the broad phase settings are read
+    /// from here on every step, so changes apply from the next step on.
+    pub fn collision_config_mut(&mut self) -> &mut CollisionConfig {
+        &mut self.config.collision
+    }
+
+    /// Starts recording the world for deterministic replay.
+    ///
+    /// Step through the returned [`Recorder`]; every step's inputs and
+    /// resulting state hash are captured.
+    pub fn record(self) -> Recorder {
+        Recorder::new(self)
+    }
+
+    /// Replays a recording, checking that every step reproduces the
+    /// recorded state bit for bit.
+    ///
+    /// Fails with [`PhysicsError::ReplayDivergence`](error::PhysicsError::ReplayDivergence)
+    /// at the first step whose state hash differs from the recording.
+    pub fn replay(recording: &Recording) -> PhysicsResult<Replay> {
+        Replay::new(recording, replay::DEFAULT_KEYFRAME_INTERVAL)
+    }
 }
 
 #[cfg(test)]
@@ -362,4 +707,252 @@ mod tests {
         // Should have fallen
         assert!(final_height < initial_height);
     }
+
+    #[test]
+    fn test_diverged_step_is_rolled_back() {
+        let config = PhysicsConfig::default();
+        let mut world = PhysicsWorld::new(config);
+
+        let mass_props = MassProperties::from_sphere(1.0, 0.1);
+        let mut body = RigidBody::new(0, mass_props);
+        body.position = Vector3::new(0.0, 0.0, 10.0);
+        body.linear_velocity = Vector3::new(5000.0, 0.0, 0.0);
+
+        world.add_body(body, CollisionShape::Sphere { radius: 0.1 });
+
+        let error = world.step(0.01).unwrap_err();
+        assert!(matches!(error, error::PhysicsError::SolverDivergence(_)));
+        assert!(!error.remediation().is_empty());
+
+        // State and time are untouched
+        assert_eq!(world.body(0).unwrap().position, Vector3::new(0.0, 0.0, 10.0));
+        assert_eq!(world.time(), 0.0);
+    }
+
+    #[test]
+    fn test_interpolated_state_between_steps() {
+        let config = PhysicsConfig::default();
+        let mut world = PhysicsWorld::new(config);
+        world.set_gravity(Vector3::zeros());
+
+        let mass_props = MassProperties::from_sphere(1.0, 0.1);
+        let mut body = RigidBody::new(0, mass_props);
+        body.linear_velocity = Vector3::new(10.0, 0.0, 0.0);
+        world.add_body(body, CollisionShape::Sphere { radius: 0.1 });
+
+        // Before any step there is nothing to blend from
+        assert_eq!(world.interpolated_state(0.5).bodies[0].position.x, 0.0);
+
+        world.step(0.1).unwrap();
+        let x = world.body(0).unwrap().position.x;
+
+        let state = world.interpolated_state(0.25);
+        assert!((state.bodies[0].position.x - 0.25 * x).abs() < 1e-9);
+        assert!((state.time - 0.025).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_ball_joint_tows_trailer() {
+        let config = PhysicsConfig::default();
+        let mut world = PhysicsWorld::new(config);
+        world.set_gravity(Vector3::zeros());
+
+        let mass_props = MassProperties::from_box(8000.0, Vector3::new(6.0, 2.5, 3.0));
+        let mut truck = RigidBody::new(0, mass_props);
+        truck.linear_velocity = Vector3::new(5.0, 0.0, 0.0);
+        let truck_shape = CollisionShape::Box {
+            half_extents: Vector3::new(3.0, 1.25, 1.5),
+        };
+        world.add_body(truck, truck_shape);
+
+        let mass_props = MassProperties::from_box(4000.0, Vector3::new(8.0, 2.5, 3.0));
+        let mut trailer = RigidBody::new(1, mass_props);
+        trailer.position = Vector3::new(-7.5, 0.0, 0.0);
+        let trailer_shape = CollisionShape::Box {
+            half_extents: Vector3::new(4.0, 1.25, 1.5),
+        };
+        world.add_body(trailer, trailer_shape);
+
+        world
+            .add_joint(Joint::ball(0, 1, Vector3::new(-3.5, 0.0, 0.0)))
+            .unwrap();
+
+        for _ in 0..100 {
+            world.step(0.01).unwrap();
+        }
+
+        // Momentum is shared and the hitch holds
+        let truck = world.body(0).unwrap();
+        let trailer = world.body(1).unwrap();
+        assert!((truck.linear_velocity.x - trailer.linear_velocity.x).abs() < 0.05);
+        assert!(trailer.linear_velocity.x > 3.0);
+        assert!((truck.position.x - trailer.position.x - 7.5).abs() < 0.05);
+        assert!(world.joint_breaks().is_empty());
+    }
+
+    #[test]
+    fn test_hitched_semi_trailer_follows_tractor() {
+        let config = PhysicsConfig::default();
+        let mut world = PhysicsWorld::new(config);
+        world.set_gravity(Vector3::zeros());
+
+        let tractor = Vehicle::create_tractor(0, 0);
+        let trailer = Trailer::create_semi_trailer(0, 1);
+
+        let mass_props = MassProperties::from_box(8000.0, Vector3::new(6.0, 2.5, 3.0));
+        let mut tractor_body = RigidBody::new(0, mass_props);
+        tractor_body.linear_velocity = Vector3::new(10.0, 0.0, 0.0);
+        let tractor_shape = CollisionShape::Box {
+            half_extents: Vector3::new(3.0, 1.25, 1.5),
+        };
+        world.add_body(tractor_body, tractor_shape);
+
+        let mass_props = MassProperties::from_box(10000.0, Vector3::new(13.6, 2.5, 3.0));
+        let mut trailer_body = RigidBody::new(1, mass_props);
+        trailer_body.position = tractor.fifth_wheel.unwrap() - trailer.kingpin;
+        let trailer_shape = CollisionShape::Box {
+            half_extents: Vector3::new(6.8, 1.25, 1.5),
+        };
+        world.add_body(trailer_body, trailer_shape);
+
+        let vehicle_id = world.add_vehicle(tractor);
+        assert!(world.hitch_trailer(vehicle_id + 1, trailer.clone()).is_err());
+        assert_eq!(world.hitch_trailer(vehicle_id, trailer).unwrap(), 0);
+
+        for _ in 0..50 {
+            world.step(0.01).unwrap();
+            let status = world.update_trailers(vehicle_id, 0.01, 0.0).unwrap();
+            assert_eq!(status, ArticulationStatus::Stable);
+        }
+
+        let vehicle = world.vehicle(vehicle_id).unwrap();
+        let coupling = world.joint(vehicle.trailers[0].coupling.unwrap()).unwrap();
+        assert_eq!(coupling.kind, rigid_body::JointKind::Hinge);
+
+        // The kingpin stays on the fifth wheel
+        let fifth_wheel = world.body(0).unwrap().local_to_world(vehicle.fifth_wheel.unwrap());
+        let kingpin = world.body(1).unwrap().local_to_world(vehicle.trailers[0].kingpin);
+        assert!((fifth_wheel - kingpin).norm() < 0.05);
+        assert!(world.body(1).unwrap().linear_velocity.x > 3.0);
+        assert!(!vehicle.is_jackknifed());
+    }
+
+    #[test]
+    fn test_rollover_recorded_for_vehicle() {
+        let config = PhysicsConfig::default();
+        let mut world = PhysicsWorld::new(config);
+        world.set_gravity(Vector3::zeros());
+
+        let mass_props = MassProperties::from_box(1500.0, Vector3::new(4.5, 1.8, 1.5));
+        let mut car = RigidBody::new(0, mass_props);
+        car.linear_velocity = Vector3::new(0.0, -8.0, 0.0);
+        car.angular_velocity = Vector3::new(6.0, 0.0, 0.0);
+        let shape = CollisionShape::Box {
+            half_extents: Vector3::new(2.25, 0.9, 0.75),
+        };
+        world.add_body(car, shape);
+        world.add_vehicle(Vehicle::create_passenger_car(0, 0));
+
+        // Rolling to the right at 6 rad/s: past π/2 in under 0.3 s
+        for _ in 0..30 {
+            world.step(0.01).unwrap();
+        }
+
+        let events = world.rollover_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].direction, energy::RollDirection::Right);
+        assert_eq!(events[0].quarter_turns, 1);
+        assert!(!events[0].is_complete());
+
+        let state = world.rollover_analysis().state(0).unwrap();
+        assert_eq!(state.phase, energy::RolloverPhase::Rolling);
+        assert!((state.roll_rate - 6.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_pedestrian_segments_stay_linked() {
+        let config = PhysicsConfig::default();
+        let mut world = PhysicsWorld::new(config);
+
+        let params = pedestrian::PedestrianParameters::adult();
+        let id = world
+            .add_pedestrian(params, Vector3::new(5.0, 0.0, 0.0), 0.0)
+            .unwrap();
+        world
+            .add_pedestrian(pedestrian::PedestrianParameters::cyclist(), Vector3::zeros(), 0.0)
+            .unwrap();
+        assert_eq!(world.bodies().len(), 21);
+        assert_eq!(world.joints().len(), 18);
+
+        // Kick the torso: the limbs follow without the joints opening up
+        world.step(0.01).unwrap();
+        let torso = world.pedestrian(id).unwrap().segments[1].body;
+        world.body_mut(torso).unwrap().linear_velocity = Vector3::new(3.0, 0.0, 0.0);
+        for _ in 0..50 {
+            world.step(0.01).unwrap();
+        }
+
+        for joint in &world.joints()[..9] {
+            let body_a = world.body(joint.body_a).unwrap();
+            let body_b = world.body(joint.body_b).unwrap();
+            let anchor_a = body_a.local_to_world(joint.local_anchor_a);
+            let anchor_b = body_b.local_to_world(joint.local_anchor_b);
+            assert!((anchor_a - anchor_b).norm() < 0.05);
+        }
+
+        let pedestrian = world.pedestrian(id).unwrap();
+        let velocity = pedestrian.center_of_mass_velocity(world.bodies());
+        assert!(velocity.x > 1.0);
+        assert!(pedestrian.throw.is_struck());
+        assert!(world.pedestrian(1).unwrap().bicycle.is_some());
+    }
+
+    #[test]
+    fn test_breakable_joint_records_break() {
+        let config = PhysicsConfig::default();
+        let mut world = PhysicsWorld::new(config);
+        world.set_gravity(Vector3::zeros());
+
+        let mass_props = MassProperties::from_box(1500.0, Vector3::new(4.5, 1.8, 1.5));
+        let mut vehicle = RigidBody::new(0, mass_props);
+        vehicle.linear_velocity = Vector3::new(10.0, 0.0, 0.0);
+        world.add_body(vehicle, CollisionShape::Sphere { radius: 0.5 });
+
+        let mass_props = MassProperties::from_box(20.0, Vector3::new(0.2, 1.8, 0.3));
+        let mut bumper = RigidBody::new(1, mass_props);
+        bumper.position = Vector3::new(2.3, 0.0, 0.0);
+        bumper.linear_velocity = Vector3::new(10.0, 0.0, 0.0);
+        world.add_body(bumper, CollisionShape::Sphere { radius: 0.1 });
+
+        let joint = Joint::fixed(0, 1, Vector3::new(2.25, 0.0, 0.0)).with_break_force(5_000.0);
+        world.add_joint(joint).unwrap();
+
+        // The bumper is held on while cruising
+        world.step(0.01).unwrap();
+        assert!(world.joint(0).unwrap().is_active());
+
+        // A sudden stop of the bumper exceeds the bolts' strength
+        world.body_mut(1).unwrap().linear_velocity = Vector3::zeros();
+        world.step(0.01).unwrap();
+
+        let breaks = world.joint_breaks();
+        assert_eq!(breaks.len(), 1);
+        assert_eq!(breaks[0].joint, 0);
+        assert!((breaks[0].time - 0.02).abs() < 1e-12);
+        assert!(breaks[0].force > 5_000.0);
+        assert!(!world.joint(0).unwrap().is_active());
+    }
+
+    #[test]
+    fn test_add_joint_rejects_unknown_body() {
+        let config = PhysicsConfig::default();
+        let mut world = PhysicsWorld::new(config);
+        world.add_body(
+            RigidBody::new(0, MassProperties::from_sphere(1.0, 0.1)),
+            CollisionShape::Sphere { radius: 0.1 },
+        );
+
+        assert!(world.add_joint(Joint::ball(0, 3, Vector3::zeros())).is_err());
+        assert!(world.add_joint(Joint::ball(0, 0, Vector3::zeros())).is_err());
+    }
 }
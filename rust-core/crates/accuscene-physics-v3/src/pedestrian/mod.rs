@@ -0,0 +1,593 @@
+//! Pedestrian and cyclist models.
+//!
+//! Vulnerable road users are modelled as ten rigid segments (head, torso,
+//! upper arms, forearms, thighs and shanks) linked by joints:
+//! - Neck, shoulders and hips: ball joints
+//! - Elbows and knees: hinge joints limited to their anatomical range
+//!
+//! Segment lengths and masses are fractions of the body height and mass
+//! (Winter's anthropometric tables). A cyclist uses the same segments in a
+//! riding posture on a separate bicycle body, so rider and bicycle separate
+//! on impact.
+//!
+//! Throw-distance analysis lives in [`throw`].
+
+pub mod throw;
+
+pub use throw::*;
+
+use std::f64::consts::PI;
+
+use nalgebra::{UnitQuaternion, Vector3};
+use serde::{Deserialize, Serialize};
+
+use crate::collision::CollisionShape;
+use crate::rigid_body::{Joint, MassProperties, RigidBody};
+
+/// Knee flexion range (rad).
+const KNEE_RANGE: (f64, f64) = (-0.05, 2.44);
+
+/// Elbow flexion range (rad).
+const ELBOW_RANGE: (f64, f64) = (-0.05, 2.53);
+
+/// Body segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SegmentKind {
+    /// Head and neck.
+    Head,
+
+    /// Thorax and abdomen.
+    Torso,
+
+    /// Left upper arm.
+    UpperArmLeft,
+
+    /// Right upper arm.
+    UpperArmRight,
+
+    /// Left forearm and hand.
+    ForearmLeft,
+
+    /// Right forearm and hand.
+    ForearmRight,
+
+    /// Left thigh.
+    ThighLeft,
+
+    /// Right thigh.
+    ThighRight,
+
+    /// Left shank and foot.
+    ShankLeft,
+
+    /// Right shank and foot.
+    ShankRight,
+}
+
+impl SegmentKind {
+    /// Fraction of total body mass.
+    pub fn mass_fraction(&self) -> f64 {
+        match self {
+            Self::Head => 0.081,
+            Self::Torso => 0.497,
+            Self::UpperArmLeft | Self::UpperArmRight => 0.028,
+            Self::ForearmLeft | Self::ForearmRight => 0.022,
+            Self::ThighLeft | Self::ThighRight => 0.100,
+            Self::ShankLeft | Self::ShankRight => 0.061,
+        }
+    }
+
+    /// Segment radius as a fraction of body height.
+    fn radius_fraction(&self) -> f64 {
+        match self {
+            Self::Head => 0.06,
+            Self::Torso => 0.09,
+            Self::UpperArmLeft | Self::UpperArmRight => 0.025,
+            Self::ForearmLeft | Self::ForearmRight => 0.022,
+            Self::ThighLeft | Self::ThighRight => 0.04,
+            Self::ShankLeft | Self::ShankRight => 0.03,
+        }
+    }
+}
+
+/// Body posture at the start of the simulation.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Posture {
+    /// Standing upright, arms at the sides.
+    Standing,
+
+    /// Riding a bicycle.
+    Cycling {
+        /// Saddle height above the ground (m).
+        saddle_height: f64,
+
+        /// Bicycle mass (kg).
+        bicycle_mass: f64,
+    },
+}
+
+/// Pedestrian anthropometry.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PedestrianParameters {
+    /// Standing height (m).
+    pub height: f64,
+
+    /// Body mass (kg).
+    pub mass: f64,
+
+    /// Initial posture.
+    pub posture: Posture,
+}
+
+impl PedestrianParameters {
+    /// Creates parameters for a standing pedestrian.
+    pub fn new(height: f64, mass: f64) -> Self {
+        Self {
+            height,
+            mass,
+            posture: Posture::Standing,
+        }
+    }
+
+    /// Creates parameters for an average adult (50th percentile male).
+    pub fn adult() -> Self {
+        Self::new(1.75, 78.0)
+    }
+
+    /// Creates parameters for a six-year-old child.
+    pub fn child() -> Self {
+        Self::new(1.17, 21.0)
+    }
+
+    /// Creates parameters for an adult riding a bicycle.
+    pub fn cyclist() -> Self {
+        Self::adult().with_posture(Posture::Cycling {
+            saddle_height: 0.95,
+            bicycle_mass: 15.0,
+        })
+    }
+
+    /// Sets the initial posture.
+    pub fn with_posture(mut self, posture: Posture) -> Self {
+        self.posture = posture;
+        self
+    }
+}
+
+impl Default for PedestrianParameters {
+    fn default() -> Self {
+        Self::adult()
+    }
+}
+
+/// Segment end points in the pedestrian's frame (x forward, y left, z up,
+/// origin on the ground).
+struct Skeleton {
+    head_top: Vector3<f64>,
+    neck: Vector3<f64>,
+    hip_center: Vector3<f64>,
+    shoulders: [Vector3<f64>; 2],
+    elbows: [Vector3<f64>; 2],
+    hands: [Vector3<f64>; 2],
+    hips: [Vector3<f64>; 2],
+    knees: [Vector3<f64>; 2],
+    feet: [Vector3<f64>; 2],
+}
+
+impl Skeleton {
+    fn new(params: &PedestrianParameters) -> Self {
+        let h = params.height;
+        let sides = [1.0, -1.0];
+        let lateral = |width: f64| sides.map(|side| Vector3::new(0.0, side * width * h, 0.0));
+        let shoulder_offsets = lateral(0.13);
+        let hip_offsets = lateral(0.05);
+
+        match params.posture {
+            Posture::Standing => {
+                let up = |fraction: f64| Vector3::new(0.0, 0.0, fraction * h);
+
+                Self {
+                    head_top: up(1.0),
+                    neck: up(0.87),
+                    hip_center: up(0.53),
+                    shoulders: shoulder_offsets.map(|offset| offset + up(0.818)),
+                    elbows: shoulder_offsets.map(|offset| offset + up(0.63)),
+                    hands: shoulder_offsets.map(|offset| offset + up(0.377)),
+                    hips: hip_offsets.map(|offset| offset + up(0.53)),
+                    knees: hip_offsets.map(|offset| offset + up(0.285)),
+                    feet: hip_offsets,
+                }
+            },
+            Posture::Cycling { saddle_height, .. } => {
+                // Torso leaning 40° forward, hands on the bars, feet on the pedals
+                let lean = 40f64.to_radians();
+                let torso = Vector3::new(lean.sin(), 0.0, lean.cos());
+                let upper_arm = Vector3::new(0.5, 0.0, -1.0).normalize() * 0.188 * h;
+                let forearm = Vector3::new(1.0, 0.0, -0.2).normalize() * 0.253 * h;
+                let thigh = Vector3::new(1.0, 0.0, -0.35).normalize() * 0.245 * h;
+                let shank = Vector3::new(-0.25, 0.0, -1.0).normalize() * 0.285 * h;
+
+                let hip_center = Vector3::new(0.0, 0.0, saddle_height);
+                let neck = hip_center + torso * 0.34 * h;
+                let shoulders =
+                    shoulder_offsets.map(|offset| hip_center + torso * 0.288 * h + offset);
+                let hips = hip_offsets.map(|offset| hip_center + offset);
+
+                Self {
+                    head_top: neck + torso * 0.13 * h,
+                    neck,
+                    hip_center,
+                    shoulders,
+                    elbows: shoulders.map(|shoulder| shoulder + upper_arm),
+                    hands: shoulders.map(|shoulder| shoulder + upper_arm + forearm),
+                    hips,
+                    knees: hips.map(|hip| hip + thigh),
+                    feet: hips.map(|hip| hip + thigh + shank),
+                }
+            },
+        }
+    }
+}
+
+/// Rigid body of one segment, before it is added to a world.
+#[derive(Debug, Clone)]
+pub struct SegmentBody {
+    /// Segment.
+    pub kind: SegmentKind,
+
+    /// Rigid body.
+    pub body: RigidBody,
+
+    /// Collision shape (capsule along the segment).
+    pub shape: CollisionShape,
+
+    /// Segment length (m).
+    pub length: f64,
+}
+
+/// Bodies and joints of a pedestrian, before they are added to a world.
+///
+/// Body IDs are assigned consecutively from the first ID given to
+/// [`build`](Self::build): segments first, then the bicycle.
+#[derive(Debug, Clone)]
+pub struct PedestrianModel {
+    /// Segment bodies.
+    pub segments: Vec<SegmentBody>,
+
+    /// Neck, shoulder, elbow, hip and knee joints.
+    pub joints: Vec<Joint>,
+
+    /// Bicycle body and shape (cyclists only).
+    pub bicycle: Option<(RigidBody, CollisionShape)>,
+}
+
+impl PedestrianModel {
+    /// Builds the model standing (or riding) at `position` on the ground,
+    /// facing `heading` (rad from the x axis).
+    pub fn build(
+        params: &PedestrianParameters,
+        first_body_id: usize,
+        position: Vector3<f64>,
+        heading: f64,
+    ) -> Self {
+        let yaw = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), heading);
+        let to_world = |point: Vector3<f64>| position + yaw * point;
+        let skeleton = Skeleton::new(params);
+
+        let ends = [
+            (SegmentKind::Head, skeleton.head_top, skeleton.neck),
+            (SegmentKind::Torso, skeleton.neck, skeleton.hip_center),
+            (
+                SegmentKind::UpperArmLeft,
+                skeleton.shoulders[0],
+                skeleton.elbows[0],
+            ),
+            (
+                SegmentKind::UpperArmRight,
+                skeleton.shoulders[1],
+                skeleton.elbows[1],
+            ),
+            (
+                SegmentKind::ForearmLeft,
+                skeleton.elbows[0],
+                skeleton.hands[0],
+            ),
+            (
+                SegmentKind::ForearmRight,
+                skeleton.elbows[1],
+                skeleton.hands[1],
+            ),
+            (SegmentKind::ThighLeft, skeleton.hips[0], skeleton.knees[0]),
+            (SegmentKind::ThighRight, skeleton.hips[1], skeleton.knees[1]),
+            (SegmentKind::ShankLeft, skeleton.knees[0], skeleton.feet[0]),
+            (SegmentKind::ShankRight, skeleton.knees[1], skeleton.feet[1]),
+        ];
+
+        let segments = ends
+            .iter()
+            .enumerate()
+            .map(|(index, &(kind, top, bottom))| {
+                let axis = top - bottom;
+                let length = axis.norm();
+                let radius = kind.radius_fraction() * params.height;
+                let mass = kind.mass_fraction() * params.mass;
+
+                let mut body = RigidBody::new(
+                    first_body_id + index,
+                    MassProperties::from_cylinder(mass, radius, length),
+                );
+                body.position = to_world((top + bottom) / 2.0);
+                body.orientation = yaw
+                    * UnitQuaternion::rotation_between(&Vector3::z(), &axis)
+                        .unwrap_or_else(|| UnitQuaternion::from_axis_angle(&Vector3::x_axis(), PI));
+
+                SegmentBody {
+                    kind,
+                    body,
+                    shape: CollisionShape::Capsule {
+                        radius,
+                        half_height: (length / 2.0 - radius).max(0.0),
+                    },
+                    length,
+                }
+            })
+            .collect();
+
+        let id = |kind: SegmentKind| {
+            first_body_id
+                + ends.iter().position(|&(segment, _, _)| segment == kind).unwrap_or_default()
+        };
+        let lateral = yaw * Vector3::y();
+
+        let mut joints = vec![Joint::ball(
+            id(SegmentKind::Torso),
+            id(SegmentKind::Head),
+            to_world(skeleton.neck),
+        )];
+        let limbs = [
+            (SegmentKind::UpperArmLeft, SegmentKind::ForearmLeft, 0),
+            (SegmentKind::UpperArmRight, SegmentKind::ForearmRight, 1),
+        ];
+        for (upper_arm, forearm, side) in limbs {
+            let flexion = flexion_angle(
+                skeleton.elbows[side] - skeleton.shoulders[side],
+                skeleton.hands[side] - skeleton.elbows[side],
+                -Vector3::y(),
+            );
+            joints.push(Joint::ball(
+                id(SegmentKind::Torso),
+                id(upper_arm),
+                to_world(skeleton.shoulders[side]),
+            ));
+            joints.push(
+                Joint::hinge(
+                    id(upper_arm),
+                    id(forearm),
+                    to_world(skeleton.elbows[side]),
+                    -lateral,
+                )
+                .with_limits(ELBOW_RANGE.0 - flexion, ELBOW_RANGE.1 - flexion),
+            );
+        }
+        let limbs = [
+            (SegmentKind::ThighLeft, SegmentKind::ShankLeft, 0),
+            (SegmentKind::ThighRight, SegmentKind::ShankRight, 1),
+        ];
+        for (thigh, shank, side) in limbs {
+            let flexion = flexion_angle(
+                skeleton.knees[side] - skeleton.hips[side],
+                skeleton.feet[side] - skeleton.knees[side],
+                Vector3::y(),
+            );
+            joints.push(Joint::ball(
+                id(SegmentKind::Torso),
+                id(thigh),
+                to_world(skeleton.hips[side]),
+            ));
+            joints.push(
+                Joint::hinge(
+                    id(thigh),
+                    id(shank),
+                    to_world(skeleton.knees[side]),
+                    lateral,
+                )
+                .with_limits(KNEE_RANGE.0 - flexion, KNEE_RANGE.1 - flexion),
+            );
+        }
+
+        let bicycle = match params.posture {
+            Posture::Standing => None,
+            Posture::Cycling { bicycle_mass, .. } => {
+                let dimensions = Vector3::new(1.75, 0.1, 1.0);
+                let mut body = RigidBody::new(
+                    first_body_id + ends.len(),
+                    MassProperties::from_box(bicycle_mass, dimensions),
+                );
+                body.position = to_world(Vector3::new(0.25, 0.0, dimensions.z / 2.0));
+                body.orientation = yaw;
+
+                let shape = CollisionShape::Box {
+                    half_extents: dimensions / 2.0,
+                };
+                Some((body, shape))
+            },
+        };
+
+        Self {
+            segments,
+            joints,
+            bicycle,
+        }
+    }
+}
+
+/// Segment of a pedestrian in a world.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Segment {
+    /// Segment.
+    pub kind: SegmentKind,
+
+    /// Body ID.
+    pub body: usize,
+
+    /// Segment length (m).
+    pub length: f64,
+}
+
+/// Pedestrian or cyclist in a world.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pedestrian {
+    /// Unique identifier.
+    pub id: usize,
+
+    /// Anthropometry.
+    pub params: PedestrianParameters,
+
+    /// Segments and their body IDs.
+    pub segments: Vec<Segment>,
+
+    /// Joint IDs.
+    pub joints: Vec<usize>,
+
+    /// Bicycle body ID (cyclists only).
+    pub bicycle: Option<usize>,
+
+    /// Throw-distance analysis.
+    pub throw: ThrowAnalysis,
+}
+
+impl Pedestrian {
+    /// Creates a pedestrian from segments already added to a world.
+    pub fn new(
+        id: usize,
+        params: PedestrianParameters,
+        segments: Vec<Segment>,
+        joints: Vec<usize>,
+    ) -> Self {
+        Self {
+            id,
+            params,
+            segments,
+            joints,
+            bicycle: None,
+            throw: ThrowAnalysis::default(),
+        }
+    }
+
+    /// Gets a segment.
+    pub fn segment(&self, kind: SegmentKind) -> Option<&Segment> {
+        self.segments.iter().find(|segment| segment.kind == kind)
+    }
+
+    /// Center of mass of the segments.
+    ///
+    /// `bodies` is indexed by body ID.
+    pub fn center_of_mass(&self, bodies: &[RigidBody]) -> Vector3<f64> {
+        self.mass_weighted(bodies, |body| body.position)
+    }
+
+    /// Velocity of the center of mass.
+    pub fn center_of_mass_velocity(&self, bodies: &[RigidBody]) -> Vector3<f64> {
+        self.mass_weighted(bodies, |body| body.linear_velocity)
+    }
+
+    /// Updates the throw analysis after a step ending at `time`.
+    pub fn update(&mut self, bodies: &[RigidBody], time: f64) {
+        let max_speed = self
+            .segments
+            .iter()
+            .filter_map(|segment| bodies.get(segment.body))
+            .map(|body| body.linear_velocity.norm())
+            .fold(0.0, f64::max);
+
+        self.throw.update(
+            self.center_of_mass(bodies),
+            self.center_of_mass_velocity(bodies),
+            max_speed,
+            time,
+        );
+    }
+
+    fn mass_weighted(
+        &self,
+        bodies: &[RigidBody],
+        quantity: impl Fn(&RigidBody) -> Vector3<f64>,
+    ) -> Vector3<f64> {
+        let mut total = Vector3::zeros();
+        let mut mass = 0.0;
+
+        for body in self.segments.iter().filter_map(|segment| bodies.get(segment.body)) {
+            total += quantity(body) * body.mass_props.mass;
+            mass += body.mass_props.mass;
+        }
+
+        if mass > 0.0 {
+            total / mass
+        } else {
+            total
+        }
+    }
+}
+
+/// Signed angle from `proximal` to `distal` about `axis` (rad).
+fn flexion_angle(proximal: Vector3<f64>, distal: Vector3<f64>, axis: Vector3<f64>) -> f64 {
+    axis.dot(&proximal.cross(&distal)).atan2(proximal.dot(&distal))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_standing_model() {
+        let params = PedestrianParameters::adult();
+        let model = PedestrianModel::build(&params, 3, Vector3::new(10.0, 2.0, 0.0), 0.0);
+
+        assert_eq!(model.segments.len(), 10);
+        assert_eq!(model.joints.len(), 9);
+        assert!(model.bicycle.is_none());
+
+        let mass: f64 = model.segments.iter().map(|s| s.body.mass_props.mass).sum();
+        assert_relative_eq!(mass, params.mass, epsilon = 1e-9);
+
+        let head = &model.segments[0];
+        assert_eq!(head.kind, SegmentKind::Head);
+        assert_eq!(head.body.id, 3);
+        assert_relative_eq!(head.body.position.z + head.length / 2.0, params.height);
+
+        // Each joint sits at the proximal end of its distal segment
+        for joint in &model.joints {
+            let segment = &model.segments[joint.body_b - 3];
+            let distance = (joint.anchor - segment.body.position).norm();
+            assert_relative_eq!(distance, segment.length / 2.0, epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_cyclist_model() {
+        let params = PedestrianParameters::cyclist();
+        let model = PedestrianModel::build(&params, 0, Vector3::zeros(), PI / 2.0);
+
+        let (bicycle, _) = model.bicycle.unwrap();
+        assert_eq!(bicycle.id, 10);
+
+        // Facing +y: the torso leans toward +y, the knees are bent
+        let torso = &model.segments[1].body;
+        assert!(torso.position.y > 0.0);
+        assert!(torso.position.z > 0.95);
+
+        let knee = model.joints.last().unwrap();
+        let (min, max) = knee.limits.unwrap();
+        assert!(min < -1.0 && max > 0.5);
+    }
+
+    #[test]
+    fn test_flexion_angle() {
+        let thigh = Vector3::new(0.0, 0.0, -1.0);
+        let shank = Vector3::new(-1.0, 0.0, 0.0);
+
+        assert_relative_eq!(flexion_angle(thigh, shank, Vector3::y()), PI / 2.0);
+        assert_relative_eq!(flexion_angle(thigh, thigh, Vector3::y()), 0.0);
+    }
+}
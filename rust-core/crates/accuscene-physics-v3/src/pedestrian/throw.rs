@@ -0,0 +1,252 @@
+//! Pedestrian throw-distance analysis.
+//!
+//! Tracks a pedestrian's center of mass from impact to rest and relates the
+//! throw distance to the impact speed with Searle's formula, which models
+//! the throw as a projectile flight followed by sliding to rest:
+//!
+//! ```text
+//! v = √(2 μ g (s - μ h)) / (cos θ + μ sin θ)
+//! ```
+//!
+//! Where s is the throw distance, μ the pedestrian-ground friction, h the
+//! launch height and θ the launch angle. Over all launch angles the speed
+//! is bounded by:
+//!
+//! ```text
+//! v_min = √(2 μ g (s - μ h) / (1 + μ²))    (tan θ = μ)
+//! v_max = √(2 μ g (s - μ h))               (θ = 0)
+//! ```
+
+use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
+
+/// Gravitational acceleration used by the throw formulas (m/s²).
+const GRAVITY: f64 = 9.81;
+
+/// Default change of center of mass velocity in one step that counts as an
+/// impact (m/s).
+pub const DEFAULT_IMPACT_DELTA_V: f64 = 1.0;
+
+/// Impact speed implied by a throw distance at a given launch angle (m/s).
+///
+/// `distance` is the horizontal throw distance (m), `friction` the
+/// pedestrian-ground friction coefficient, `launch_angle` in radians and
+/// `launch_height` the center of mass height at launch (m).
+pub fn searle_speed(distance: f64, friction: f64, launch_angle: f64, launch_height: f64) -> f64 {
+    let slide = (distance - friction * launch_height).max(0.0);
+    (2.0 * friction * GRAVITY * slide).sqrt() / (launch_angle.cos() + friction * launch_angle.sin())
+}
+
+/// Lower and upper bounds of the impact speed implied by a throw distance
+/// over all launch angles (m/s).
+pub fn searle_speed_bounds(distance: f64, friction: f64, launch_height: f64) -> (f64, f64) {
+    let max = searle_speed(distance, friction, 0.0, launch_height);
+    let min = max / (1.0 + friction * friction).sqrt();
+    (min, max)
+}
+
+/// Throw of a pedestrian from impact to rest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThrowAnalysis {
+    /// Center of mass velocity change in one step that counts as an
+    /// impact (m/s).
+    pub impact_delta_v: f64,
+
+    /// Speed below which every segment is at rest (m/s).
+    pub rest_speed: f64,
+
+    /// Time of impact (s).
+    pub impact_time: Option<f64>,
+
+    /// Center of mass position before impact (m).
+    pub impact_position: Option<Vector3<f64>>,
+
+    /// Fastest center of mass velocity after impact (m/s).
+    pub launch_velocity: Option<Vector3<f64>>,
+
+    /// Center of mass height at launch (m).
+    pub launch_height: Option<f64>,
+
+    /// Time the pedestrian came to rest (s).
+    pub rest_time: Option<f64>,
+
+    /// Center of mass position at rest (m).
+    pub rest_position: Option<Vector3<f64>>,
+
+    /// Center of mass state at the previous update.
+    previous: Option<(Vector3<f64>, Vector3<f64>)>,
+}
+
+impl Default for ThrowAnalysis {
+    fn default() -> Self {
+        Self {
+            impact_delta_v: DEFAULT_IMPACT_DELTA_V,
+            rest_speed: 0.1,
+            impact_time: None,
+            impact_position: None,
+            launch_velocity: None,
+            launch_height: None,
+            rest_time: None,
+            rest_position: None,
+            previous: None,
+        }
+    }
+}
+
+impl ThrowAnalysis {
+    /// Records the center of mass state after a step ending at `time`.
+    ///
+    /// `max_speed` is the speed of the fastest segment, used to decide when
+    /// the pedestrian has come to rest.
+    pub fn update(
+        &mut self,
+        position: Vector3<f64>,
+        velocity: Vector3<f64>,
+        max_speed: f64,
+        time: f64,
+    ) {
+        let previous = self.previous.replace((position, velocity));
+        if self.rest_time.is_some() {
+            return;
+        }
+
+        if self.impact_time.is_none() {
+            if let Some((previous_position, previous_velocity)) = previous {
+                if (velocity - previous_velocity).norm() >= self.impact_delta_v {
+                    self.impact_time = Some(time);
+                    self.impact_position = Some(previous_position);
+                }
+            }
+        }
+        if self.impact_time.is_none() {
+            return;
+        }
+
+        let faster = self.launch_velocity.is_none_or(|launch| velocity.norm() > launch.norm());
+        if faster {
+            self.launch_velocity = Some(velocity);
+            self.launch_height = Some(position.z);
+        }
+
+        if max_speed < self.rest_speed {
+            self.rest_time = Some(time);
+            self.rest_position = Some(position);
+        }
+    }
+
+    /// Whether the pedestrian has been struck.
+    pub fn is_struck(&self) -> bool {
+        self.impact_time.is_some()
+    }
+
+    /// Whether the pedestrian came to rest after being struck.
+    pub fn is_complete(&self) -> bool {
+        self.rest_time.is_some()
+    }
+
+    /// Horizontal distance from the impact position to the rest position (m).
+    pub fn throw_distance(&self) -> Option<f64> {
+        let (impact, rest) = (self.impact_position?, self.rest_position?);
+        Some((rest - impact).xy().norm())
+    }
+
+    /// Launch speed of the center of mass (m/s).
+    pub fn launch_speed(&self) -> Option<f64> {
+        self.launch_velocity.map(|velocity| velocity.norm())
+    }
+
+    /// Launch angle above the horizontal (rad).
+    pub fn launch_angle(&self) -> Option<f64> {
+        self.launch_velocity.map(|velocity| velocity.z.atan2(velocity.xy().norm()))
+    }
+
+    /// Impact speed implied by the simulated throw distance, launch angle
+    /// and launch height (m/s).
+    pub fn searle_speed(&self, friction: f64) -> Option<f64> {
+        Some(searle_speed(
+            self.throw_distance()?,
+            friction,
+            self.launch_angle()?,
+            self.launch_height?,
+        ))
+    }
+
+    /// Bounds of the impact speed implied by the simulated throw distance
+    /// over all launch angles (m/s).
+    pub fn searle_speed_bounds(&self, friction: f64) -> Option<(f64, f64)> {
+        Some(searle_speed_bounds(
+            self.throw_distance()?,
+            friction,
+            self.launch_height?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_searle_speed() {
+        // 20 m throw on asphalt from the ground
+        let (min, max) = searle_speed_bounds(20.0, 0.7, 0.0);
+        assert_relative_eq!(max, (2.0 * 0.7 * GRAVITY * 20.0).sqrt());
+        assert_relative_eq!(min, max / 1.49_f64.sqrt());
+
+        let optimal = searle_speed(20.0, 0.7, 0.7_f64.atan(), 0.0);
+        assert_relative_eq!(optimal, min, epsilon = 1e-9);
+        assert!(searle_speed(20.0, 0.7, 0.3, 0.0) > min);
+
+        // Launching from a height shortens the slide
+        assert!(searle_speed(20.0, 0.7, 0.0, 1.0) < max);
+    }
+
+    #[test]
+    fn test_throw_analysis() {
+        let mut throw = ThrowAnalysis::default();
+        let standing = Vector3::new(0.0, 0.0, 1.0);
+
+        throw.update(standing, Vector3::zeros(), 0.0, 0.0);
+        throw.update(standing, Vector3::zeros(), 0.0, 0.01);
+        assert!(!throw.is_struck());
+
+        // Struck at 10 m/s, launched slightly upward
+        throw.update(standing, Vector3::new(8.0, 0.0, 1.0), 9.0, 0.02);
+        throw.update(
+            Vector3::new(0.1, 0.0, 1.0),
+            Vector3::new(8.5, 0.0, 1.5),
+            9.0,
+            0.03,
+        );
+        throw.update(
+            Vector3::new(5.0, 0.0, 0.3),
+            Vector3::new(3.0, 0.0, 0.0),
+            3.0,
+            1.0,
+        );
+        assert!(throw.is_struck());
+        assert!(!throw.is_complete());
+
+        throw.update(Vector3::new(12.0, 0.0, 0.2), Vector3::zeros(), 0.05, 3.0);
+        assert!(throw.is_complete());
+
+        assert_relative_eq!(throw.impact_time.unwrap(), 0.02);
+        assert_relative_eq!(throw.throw_distance().unwrap(), 12.0);
+        assert_relative_eq!(throw.launch_speed().unwrap(), 8.5_f64.hypot(1.5));
+        assert_relative_eq!(throw.launch_height.unwrap(), 1.0);
+
+        let speed = throw.searle_speed(0.7).unwrap();
+        let (min, max) = throw.searle_speed_bounds(0.7).unwrap();
+        assert!(min <= speed && speed <= max);
+
+        // Later motion does not change the result
+        throw.update(
+            Vector3::new(20.0, 0.0, 0.2),
+            Vector3::new(5.0, 0.0, 0.0),
+            5.0,
+            4.0,
+        );
+        assert_relative_eq!(throw.throw_distance().unwrap(), 12.0);
+    }
+}
@@ -0,0 +1,530 @@
+//! Deterministic recording and replay.
+//!
+//! A reconstruction shown in court must replay exactly as it was computed.
+//! A [`Recorder`] steps a world and captures, per step, everything fed into
+//! it from outside (time step, gravity, applied forces and any bodies edited
+//! between steps) together with a hash of the resulting state. Replaying the
+//! [`Recording`] re-runs the simulation from the initial world, compares the
+//! state hash after every step and keeps keyframes so the state at any
+//! recorded time can be reproduced without replaying from the start.
+//!
+//! State hashes are FNV-1a over the raw bit patterns of the simulation state,
+//! so they are stable across platforms and builds and any difference, down
+//! to the last bit of a float, is detected.
+//!
+//! ```rust,no_run
+//! use accuscene_physics_v3::prelude::*;
+//! use accuscene_physics_v3::PhysicsWorld;
+//!
+//! let mut recorder = PhysicsWorld::new(PhysicsConfig::default()).record();
+//! for _ in 0..240 {
+//!     recorder.step(1.0 / 120.0).unwrap();
+//! }
+//! let recording = recorder.finish().1;
+//!
+//! let replay = PhysicsWorld::replay(&recording).unwrap();
+//! let at_impact = replay.snapshot_at(1.25).unwrap();
+//! ```
+
+use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{PhysicsError, PhysicsResult};
+use crate::rigid_body::RigidBody;
+use crate::PhysicsWorld;
+
+/// Version of the recording format.
+pub const RECORDING_FORMAT_VERSION: u32 = 1;
+
+/// Default number of steps between replay keyframes.
+pub const DEFAULT_KEYFRAME_INTERVAL: usize = 100;
+
+/// External force and torque applied to a body for one step.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppliedLoad {
+    /// Body identifier.
+    pub body_id: usize,
+
+    /// Force (N) in world space.
+    pub force: Vector3<f64>,
+
+    /// Torque (N·m) in world space.
+    pub torque: Vector3<f64>,
+}
+
+/// Inputs to one step.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepInput {
+    /// Time step (s).
+    pub dt: f64,
+
+    /// Gravity vector (m/s²).
+    pub gravity: Vector3<f64>,
+
+    /// Forces and torques applied before the step.
+    pub loads: Vec<AppliedLoad>,
+
+    /// All rigid bodies, if any were edited since the previous step
+    /// (e.g. teleported or given a new velocity).
+    pub body_overrides: Option<Vec<RigidBody>>,
+}
+
+/// One recorded step.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepRecord {
+    /// Inputs to the step.
+    pub input: StepInput,
+
+    /// Simulation time after the step (s).
+    pub time: f64,
+
+    /// State hash after the step.
+    pub state_hash: u64,
+}
+
+/// Recorded simulation: initial world plus per-step inputs and state hashes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recording {
+    /// Recording format version.
+    pub format_version: u32,
+
+    /// World before the first step.
+    pub initial: PhysicsWorld,
+
+    /// State hash of the initial world.
+    pub initial_hash: u64,
+
+    /// Recorded steps, in order.
+    pub steps: Vec<StepRecord>,
+}
+
+impl Recording {
+    /// Simulation time at the end of the recording (s).
+    pub fn end_time(&self) -> f64 {
+        self.steps.last().map_or(self.initial.time(), |step| step.time)
+    }
+
+    /// State hash at the end of the recording.
+    ///
+    /// Identifies the outcome of the whole simulation; two recordings with
+    /// the same final hash ended in bit-identical states.
+    pub fn final_hash(&self) -> u64 {
+        self.steps.last().map_or(self.initial_hash, |step| step.state_hash)
+    }
+}
+
+/// Steps a world while recording it.
+pub struct Recorder {
+    /// World being recorded.
+    world: PhysicsWorld,
+
+    /// Recording so far.
+    recording: Recording,
+
+    /// State hash after the last step, to detect edits between steps.
+    last_hash: u64,
+}
+
+impl Recorder {
+    /// Starts recording a world from its current state.
+    pub fn new(world: PhysicsWorld) -> Self {
+        let initial_hash = state_hash(&world);
+
+        Self {
+            recording: Recording {
+                format_version: RECORDING_FORMAT_VERSION,
+                initial: world.clone(),
+                initial_hash,
+                steps: Vec::new(),
+            },
+            world,
+            last_hash: initial_hash,
+        }
+    }
+
+    /// Gets the world being recorded.
+    pub fn world(&self) -> &PhysicsWorld {
+        &self.world
+    }
+
+    /// Gets the world being recorded for modification.
+    ///
+    /// Forces, torques and gravity set here are recorded as inputs of the
+    /// next step; any other change to rigid bodies records a full copy of
+    /// them. Configuration changes are not recorded and will make the replay
+    /// diverge.
+    pub fn world_mut(&mut self) -> &mut PhysicsWorld {
+        &mut self.world
+    }
+
+    /// Performs and records one step.
+    ///
+    /// A failed step is not recorded; like [`PhysicsWorld::step`] it leaves
+    /// the bodies as they were.
+    pub fn step(&mut self, dt: f64) -> PhysicsResult<()> {
+        let body_overrides =
+            (state_hash(&self.world) != self.last_hash).then(|| self.world.bodies.clone());
+        let input = StepInput {
+            dt,
+            gravity: self.world.gravity,
+            loads: applied_loads(&self.world.bodies),
+            body_overrides,
+        };
+
+        self.world.step(dt)?;
+
+        let hash = state_hash(&self.world);
+        self.recording.steps.push(StepRecord {
+            input,
+            time: self.world.time(),
+            state_hash: hash,
+        });
+        self.last_hash = hash;
+
+        Ok(())
+    }
+
+    /// Gets the recording so far.
+    pub fn recording(&self) -> &Recording {
+        &self.recording
+    }
+
+    /// Stops recording, returning the world and the recording.
+    pub fn finish(self) -> (PhysicsWorld, Recording) {
+        (self.world, self.recording)
+    }
+}
+
+/// Validated replay of a recording.
+pub struct Replay {
+    /// Worlds after every `keyframe_interval` steps, starting with the initial world.
+    keyframes: Vec<PhysicsWorld>,
+
+    /// Steps between keyframes.
+    keyframe_interval: usize,
+
+    /// Replayed steps.
+    steps: Vec<StepRecord>,
+
+    /// World after the last step.
+    final_world: PhysicsWorld,
+}
+
+impl Replay {
+    /// Replays a recording, keeping a keyframe every `keyframe_interval` steps.
+    ///
+    /// Smaller intervals make [`snapshot_at`](Self::snapshot_at) faster at
+    /// the cost of memory.
+    pub fn new(recording: &Recording, keyframe_interval: usize) -> PhysicsResult<Self> {
+        if recording.format_version != RECORDING_FORMAT_VERSION {
+            return Err(PhysicsError::InvalidConfiguration {
+                parameter: "format_version".to_string(),
+                value: recording.format_version.to_string(),
+                constraint: RECORDING_FORMAT_VERSION.to_string(),
+            });
+        }
+
+        let mut world = recording.initial.clone();
+        check_hash(&world, 0, recording.initial_hash)?;
+
+        let keyframe_interval = keyframe_interval.max(1);
+        let mut keyframes = vec![world.clone()];
+
+        for (index, record) in recording.steps.iter().enumerate() {
+            apply_step(&mut world, &record.input)?;
+            check_hash(&world, index + 1, record.state_hash)?;
+
+            if (index + 1) % keyframe_interval == 0 {
+                keyframes.push(world.clone());
+            }
+        }
+
+        Ok(Self {
+            keyframes,
+            keyframe_interval,
+            steps: recording.steps.clone(),
+            final_world: world,
+        })
+    }
+
+    /// Number of replayed steps.
+    pub fn step_count(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Simulation time at the end of the replay (s).
+    pub fn end_time(&self) -> f64 {
+        self.final_world.time()
+    }
+
+    /// Gets the world after the last step.
+    pub fn final_world(&self) -> &PhysicsWorld {
+        &self.final_world
+    }
+
+    /// Reproduces the world after `step` steps (0 = initial world).
+    pub fn snapshot_at_step(&self, step: usize) -> PhysicsResult<Option<PhysicsWorld>> {
+        if step > self.steps.len() {
+            return Ok(None);
+        }
+
+        self.world_after(step).map(Some)
+    }
+
+    /// Reproduces the world at simulation time `time`.
+    ///
+    /// Returns the state after the last step ending at or before `time`
+    /// (the initial world for earlier times, the final world for later
+    /// ones). Use [`PhysicsWorld::interpolated_state`] on the following step
+    /// for a state strictly between two steps.
+    pub fn snapshot_at(&self, time: f64) -> PhysicsResult<PhysicsWorld> {
+        let step = self.steps.partition_point(|record| record.time <= time);
+        self.world_after(step)
+    }
+
+    /// Replays from the nearest keyframe up to `step` steps.
+    fn world_after(&self, step: usize) -> PhysicsResult<PhysicsWorld> {
+        let keyframe = step / self.keyframe_interval;
+        let mut world = self.keyframes[keyframe].clone();
+        for record in &self.steps[keyframe * self.keyframe_interval..step] {
+            apply_step(&mut world, &record.input)?;
+        }
+
+        Ok(world)
+    }
+}
+
+/// Applies a recorded step to a world.
+fn apply_step(world: &mut PhysicsWorld, input: &StepInput) -> PhysicsResult<()> {
+    if let Some(bodies) = &input.body_overrides {
+        world.bodies = bodies.clone();
+    }
+    world.gravity = input.gravity;
+
+    for body in &mut world.bodies {
+        body.clear_forces();
+    }
+    for load in &input.loads {
+        if let Some(body) = world.bodies.get_mut(load.body_id) {
+            body.force = load.force;
+            body.torque = load.torque;
+        }
+    }
+
+    world.step(input.dt)
+}
+
+/// Fails if the world's state hash differs from the recorded one.
+fn check_hash(world: &PhysicsWorld, step: usize, expected: u64) -> PhysicsResult<()> {
+    let actual = state_hash(world);
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(PhysicsError::ReplayDivergence {
+            step,
+            time: world.time(),
+            expected,
+            actual,
+        })
+    }
+}
+
+/// Forces and torques currently applied to bodies.
+fn applied_loads(bodies: &[RigidBody]) -> Vec<AppliedLoad> {
+    bodies
+        .iter()
+        .enumerate()
+        .filter(|(_, body)| body.force != Vector3::zeros() || body.torque != Vector3::zeros())
+        .map(|(body_id, body)| AppliedLoad {
+            body_id,
+            force: body.force,
+            torque: body.torque,
+        })
+        .collect()
+}
+
+/// Hashes the simulation state of a world.
+///
+/// Covers time, rigid body kinematics and sleep state, joint breakage and
+/// deformable body nodes. Applied forces are excluded; they are recorded as
+/// step inputs.
+pub fn state_hash(world: &PhysicsWorld) -> u64 {
+    let mut hasher = StateHasher::new();
+    hasher.write_f64(world.time);
+
+    hasher.write_usize(world.bodies.len());
+    for body in &world.bodies {
+        hasher.write_usize(body.id);
+        hasher.write_vector(&body.position);
+        hasher.write_f64s(body.orientation.coords.as_slice());
+        hasher.write_vector(&body.linear_velocity);
+        hasher.write_vector(&body.angular_velocity);
+        hasher.write_bytes(&[u8::from(body.is_awake)]);
+        hasher.write_f64(body.time_at_rest);
+    }
+
+    hasher.write_usize(world.joints.len());
+    for joint in &world.joints {
+        hasher.write_bytes(&[u8::from(joint.broken)]);
+    }
+
+    hasher.write_usize(world.deformable_bodies.len());
+    for body in &world.deformable_bodies {
+        hasher.write_usize(body.id);
+        for (node, velocity) in body.nodes.iter().zip(&body.velocities) {
+            hasher.write_vector(node);
+            hasher.write_vector(velocity);
+        }
+        hasher.write_f64s(&body.plastic_strain);
+    }
+
+    hasher.finish()
+}
+
+/// 64-bit FNV-1a hasher.
+///
+/// Unlike `std`'s `DefaultHasher`, the output is specified and never changes
+/// between Rust releases, so hashes stored in recordings stay valid.
+struct StateHasher(u64);
+
+impl StateHasher {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn write_usize(&mut self, value: usize) {
+        self.write_bytes(&(value as u64).to_le_bytes());
+    }
+
+    fn write_f64(&mut self, value: f64) {
+        self.write_bytes(&value.to_bits().to_le_bytes());
+    }
+
+    fn write_f64s(&mut self, values: &[f64]) {
+        for &value in values {
+            self.write_f64(value);
+        }
+    }
+
+    fn write_vector(&mut self, vector: &Vector3<f64>) {
+        self.write_f64s(vector.as_slice());
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collision::CollisionShape;
+    use crate::config::PhysicsConfig;
+    use crate::rigid_body::dynamics::MassProperties;
+
+    fn recorded_fall(steps: usize) -> Recording {
+        let mut world = PhysicsWorld::new(PhysicsConfig::default());
+        let mut body = RigidBody::new(0, MassProperties::from_sphere(1.0, 0.1));
+        body.position = Vector3::new(0.0, 0.0, 10.0);
+        world.add_body(body, CollisionShape::Sphere { radius: 0.1 });
+
+        let mut recorder = world.record();
+        for step in 0..steps {
+            if step == 5 {
+                recorder.world_mut().body_mut(0).unwrap().force = Vector3::new(20.0, 0.0, 0.0);
+            }
+            if step == 10 {
+                recorder.world_mut().body_mut(0).unwrap().linear_velocity.y = 1.0;
+            }
+            if step == 15 {
+                recorder.world_mut().set_gravity(Vector3::new(0.0, 0.0, -1.62));
+            }
+            recorder.step(0.01).unwrap();
+        }
+
+        recorder.finish().1
+    }
+
+    #[test]
+    fn test_recording_captures_inputs() {
+        let recording = recorded_fall(20);
+
+        assert_eq!(recording.steps.len(), 20);
+        assert_eq!(recording.steps[5].input.loads.len(), 1);
+        assert!(recording.steps[6].input.loads.is_empty());
+        assert!(recording.steps[10].input.body_overrides.is_some());
+        assert!(recording.steps[11].input.body_overrides.is_none());
+        assert_eq!(recording.steps[15].input.gravity.z, -1.62);
+        assert!((recording.end_time() - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_replay_is_bit_identical() {
+        let recording = recorded_fall(250);
+
+        let replay = PhysicsWorld::replay(&recording).unwrap();
+
+        assert_eq!(replay.step_count(), 250);
+        assert_eq!(state_hash(replay.final_world()), recording.final_hash());
+    }
+
+    #[test]
+    fn test_snapshot_at_time() {
+        let recording = recorded_fall(250);
+        let replay = Replay::new(&recording, 16).unwrap();
+
+        let snapshot = replay.snapshot_at(1.234).unwrap();
+        let step = recording.steps.iter().position(|s| s.time > 1.234).unwrap();
+        assert_eq!(state_hash(&snapshot), recording.steps[step - 1].state_hash);
+
+        let start = replay.snapshot_at(-1.0).unwrap();
+        assert_eq!(state_hash(&start), recording.initial_hash);
+
+        let end = replay.snapshot_at(100.0).unwrap();
+        assert_eq!(state_hash(&end), recording.final_hash());
+
+        assert!(replay.snapshot_at_step(251).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_tampered_recording_diverges() {
+        let mut recording = recorded_fall(30);
+        recording.steps[12].input.loads.push(AppliedLoad {
+            body_id: 0,
+            force: Vector3::new(0.0, 1e-9, 0.0),
+            torque: Vector3::zeros(),
+        });
+
+        let error = PhysicsWorld::replay(&recording).err().unwrap();
+
+        assert!(matches!(
+            error,
+            PhysicsError::ReplayDivergence { step: 13, .. }
+        ));
+    }
+
+    #[test]
+    fn test_state_hash_sees_every_bit() {
+        let mut world = PhysicsWorld::new(PhysicsConfig::default());
+        world.add_body(
+            RigidBody::new(0, MassProperties::from_sphere(1.0, 0.1)),
+            CollisionShape::Sphere { radius: 0.1 },
+        );
+        let before = state_hash(&world);
+
+        let body = world.body_mut(0).unwrap();
+        body.position.x = f64::from_bits(body.position.x.to_bits() + 1);
+
+        assert_ne!(state_hash(&world), before);
+    }
+}
@@ -0,0 +1,684 @@
+//! Joints between rigid bodies.
+//!
+//! Joints model mechanical connections that survive or fail during a crash:
+//! trailer hitches (ball), articulated bus turntables (hinge), telescoping
+//! couplings (prismatic) and bolted parts such as bumpers (fixed). Any joint
+//! can be made breakable by giving it a force or torque threshold; once the
+//! reaction load in a step exceeds it, the joint breaks and the bodies
+//! separate.
+//!
+//! Joints are solved by the sequential impulse solver alongside contacts.
+//! Each joint removes degrees of freedom from the relative motion of its two
+//! bodies:
+//!
+//! | Joint     | Linear DOF removed | Angular DOF removed |
+//! |-----------|--------------------|---------------------|
+//! | Ball      | 3                  | 0                   |
+//! | Hinge     | 3                  | 2                   |
+//! | Prismatic | 2                  | 3                   |
+//! | Fixed     | 3                  | 3                   |
+//!
+//! ```text
+//! λ = -K⁻¹ (J v + β/dt C)
+//! ```
+//!
+//! Where J v is the constraint velocity, C the position error and K the
+//! effective mass of the constrained directions.
+
+use std::f64::consts::PI;
+
+use nalgebra::{Matrix2, Matrix3, Matrix3x2, UnitQuaternion, Vector3};
+use serde::{Deserialize, Serialize};
+
+use super::RigidBody;
+use crate::error::{PhysicsError, PhysicsResult};
+
+/// Type of joint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JointKind {
+    /// Anchors coincide; free rotation (trailer hitch).
+    Ball,
+
+    /// Anchors coincide; rotation about one axis only (articulation turntable).
+    Hinge,
+
+    /// No rotation; translation along one axis only (telescoping coupling).
+    Prismatic,
+
+    /// No relative motion (bolted part).
+    Fixed,
+}
+
+/// Record of a joint breaking.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JointBreak {
+    /// Index of the joint in the world.
+    pub joint: usize,
+
+    /// Simulation time at the end of the step in which the joint broke (s).
+    pub time: f64,
+
+    /// Reaction force in that step (N).
+    pub force: f64,
+
+    /// Reaction torque in that step (N·m).
+    pub torque: f64,
+}
+
+/// Joint between two rigid bodies.
+///
+/// Joints are defined in world space at the bodies' current poses and bound
+/// to the bodies when added to the world.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Joint {
+    /// Type of joint.
+    pub kind: JointKind,
+
+    /// First body ID.
+    pub body_a: usize,
+
+    /// Second body ID.
+    pub body_b: usize,
+
+    /// Anchor point in world space at creation.
+    pub anchor: Vector3<f64>,
+
+    /// Hinge or slide axis in world space at creation (unit vector).
+    pub axis: Vector3<f64>,
+
+    /// Anchor point in body A's local space.
+    pub local_anchor_a: Vector3<f64>,
+
+    /// Anchor point in body B's local space.
+    pub local_anchor_b: Vector3<f64>,
+
+    /// Axis in body A's local space.
+    pub local_axis_a: Vector3<f64>,
+
+    /// Axis in body B's local space.
+    pub local_axis_b: Vector3<f64>,
+
+    /// Orientation of body B relative to body A when bound.
+    pub reference_rotation: UnitQuaternion<f64>,
+
+    /// Limits of the joint coordinate: hinge angle (rad) or slide
+    /// translation (m). Ignored for ball and fixed joints.
+    pub limits: Option<(f64, f64)>,
+
+    /// Reaction force at which the joint breaks (N).
+    pub break_force: Option<f64>,
+
+    /// Reaction torque at which the joint breaks (N·m).
+    pub break_torque: Option<f64>,
+
+    /// Whether the connected bodies still collide with each other.
+    pub collide_connected: bool,
+
+    /// Has the joint broken?
+    pub broken: bool,
+
+    /// Linear impulse applied in the current step (N·s).
+    pub linear_impulse: Vector3<f64>,
+
+    /// Angular impulse applied in the current step (N·m·s).
+    pub angular_impulse: Vector3<f64>,
+
+    /// Limit impulse applied in the current step.
+    pub limit_impulse: f64,
+
+    /// Reaction force in the last step (N).
+    pub reaction_force: f64,
+
+    /// Reaction torque in the last step (N·m).
+    pub reaction_torque: f64,
+}
+
+impl Joint {
+    /// Creates a joint of the given kind.
+    pub fn new(
+        kind: JointKind,
+        body_a: usize,
+        body_b: usize,
+        anchor: Vector3<f64>,
+        axis: Vector3<f64>,
+    ) -> Self {
+        let axis = if axis.norm_squared() > 1e-12 {
+            axis.normalize()
+        } else {
+            Vector3::x()
+        };
+
+        Self {
+            kind,
+            body_a,
+            body_b,
+            anchor,
+            axis,
+            local_anchor_a: Vector3::zeros(),
+            local_anchor_b: Vector3::zeros(),
+            local_axis_a: axis,
+            local_axis_b: axis,
+            reference_rotation: UnitQuaternion::identity(),
+            limits: None,
+            break_force: None,
+            break_torque: None,
+            collide_connected: false,
+            broken: false,
+            linear_impulse: Vector3::zeros(),
+            angular_impulse: Vector3::zeros(),
+            limit_impulse: 0.0,
+            reaction_force: 0.0,
+            reaction_torque: 0.0,
+        }
+    }
+
+    /// Creates a ball joint at a world-space anchor.
+    pub fn ball(body_a: usize, body_b: usize, anchor: Vector3<f64>) -> Self {
+        Self::new(JointKind::Ball, body_a, body_b, anchor, Vector3::x())
+    }
+
+    /// Creates a hinge joint rotating about a world-space axis.
+    pub fn hinge(body_a: usize, body_b: usize, anchor: Vector3<f64>, axis: Vector3<f64>) -> Self {
+        Self::new(JointKind::Hinge, body_a, body_b, anchor, axis)
+    }
+
+    /// Creates a prismatic joint sliding along a world-space axis.
+    pub fn prismatic(
+        body_a: usize,
+        body_b: usize,
+        anchor: Vector3<f64>,
+        axis: Vector3<f64>,
+    ) -> Self {
+        Self::new(JointKind::Prismatic, body_a, body_b, anchor, axis)
+    }
+
+    /// Creates a fixed joint at a world-space anchor.
+    pub fn fixed(body_a: usize, body_b: usize, anchor: Vector3<f64>) -> Self {
+        Self::new(JointKind::Fixed, body_a, body_b, anchor, Vector3::x())
+    }
+
+    /// Sets limits of the hinge angle (rad) or slide translation (m).
+    pub fn with_limits(mut self, min: f64, max: f64) -> Self {
+        self.limits = Some((min.min(max), min.max(max)));
+        self
+    }
+
+    /// Makes the joint break when its reaction force exceeds `force` (N).
+    pub fn with_break_force(mut self, force: f64) -> Self {
+        self.break_force = Some(force);
+        self
+    }
+
+    /// Makes the joint break when its reaction torque exceeds `torque` (N·m).
+    pub fn with_break_torque(mut self, torque: f64) -> Self {
+        self.break_torque = Some(torque);
+        self
+    }
+
+    /// Sets whether the connected bodies collide with each other.
+    pub fn with_collide_connected(mut self, collide: bool) -> Self {
+        self.collide_connected = collide;
+        self
+    }
+
+    /// Binds the joint to the current poses of its bodies.
+    ///
+    /// Captures the anchor and axis in each body's local space and the
+    /// relative orientation the joint will maintain.
+    pub fn bind(&mut self, body_a: &RigidBody, body_b: &RigidBody) {
+        self.local_anchor_a = body_a.world_to_local(self.anchor);
+        self.local_anchor_b = body_b.world_to_local(self.anchor);
+        self.local_axis_a = body_a.orientation.inverse() * self.axis;
+        self.local_axis_b = body_b.orientation.inverse() * self.axis;
+        self.reference_rotation = body_a.orientation.inverse() * body_b.orientation;
+    }
+
+    /// Whether the joint is active (not broken).
+    pub fn is_active(&self) -> bool {
+        !self.broken
+    }
+
+    /// Current joint coordinate: hinge angle (rad) or slide translation (m).
+    ///
+    /// Zero at the pose the joint was bound in; always zero for ball and
+    /// fixed joints.
+    pub fn coordinate(&self, body_a: &RigidBody, body_b: &RigidBody) -> f64 {
+        let axis = body_a.orientation * self.local_axis_a;
+
+        match self.kind {
+            JointKind::Hinge => twist_angle(&self.rotation_error(body_a, body_b), &axis),
+            JointKind::Prismatic => {
+                let anchor_a = body_a.local_to_world(self.local_anchor_a);
+                let anchor_b = body_b.local_to_world(self.local_anchor_b);
+                axis.dot(&(anchor_b - anchor_a))
+            },
+            JointKind::Ball | JointKind::Fixed => 0.0,
+        }
+    }
+
+    /// Resets the per-step impulses before solving a step.
+    pub fn begin_step(&mut self) {
+        self.linear_impulse = Vector3::zeros();
+        self.angular_impulse = Vector3::zeros();
+        self.limit_impulse = 0.0;
+    }
+
+    /// Computes the step's reaction loads and breaks the joint if they
+    /// exceed its thresholds.
+    ///
+    /// Returns true if the joint broke in this step.
+    pub fn end_step(&mut self, dt: f64) -> bool {
+        if self.broken || dt <= 0.0 {
+            return false;
+        }
+
+        self.reaction_force = self.linear_impulse.norm() / dt;
+        self.reaction_torque = self.angular_impulse.norm() / dt;
+
+        let over_force = self.break_force.is_some_and(|limit| self.reaction_force > limit);
+        let over_torque = self.break_torque.is_some_and(|limit| self.reaction_torque > limit);
+        self.broken = over_force || over_torque;
+        self.broken
+    }
+
+    /// Solves the joint for one velocity iteration.
+    ///
+    /// Returns the squared constraint velocity error before the impulse was
+    /// applied, for convergence checks.
+    pub fn solve(
+        &mut self,
+        body_a: &mut RigidBody,
+        body_b: &mut RigidBody,
+        dt: f64,
+        baumgarte_factor: f64,
+    ) -> PhysicsResult<f64> {
+        if self.broken {
+            return Ok(0.0);
+        }
+
+        let inv_mass_a = inverse_mass(body_a);
+        let inv_mass_b = inverse_mass(body_b);
+        if inv_mass_a + inv_mass_b < 1e-12 {
+            return Ok(0.0);
+        }
+
+        let inv_inertia_a = body_a.world_inverse_inertia_tensor();
+        let inv_inertia_b = body_b.world_inverse_inertia_tensor();
+        let axis = body_a.orientation * self.local_axis_a;
+        let bias_factor = baumgarte_factor / dt;
+        let mut error = 0.0;
+
+        // --- Angular constraint ---
+
+        let rotation_error = self.rotation_error(body_a, body_b);
+        let angular_mass = inv_inertia_a + inv_inertia_b;
+
+        match self.kind {
+            JointKind::Fixed | JointKind::Prismatic => {
+                let velocity_error = body_b.angular_velocity - body_a.angular_velocity;
+                error += velocity_error.norm_squared();
+
+                if let Some(k_inv) = angular_mass.try_inverse() {
+                    let lambda =
+                        -(k_inv * (velocity_error + rotation_error.scaled_axis() * bias_factor));
+                    apply_angular_impulse(body_a, body_b, &inv_inertia_a, &inv_inertia_b, lambda);
+                    self.angular_impulse += lambda;
+                }
+            },
+            JointKind::Hinge => {
+                // Keep body B's hinge axis aligned with body A's
+                let axis_b = body_b.orientation * self.local_axis_b;
+                let basis = perpendicular_basis(&axis);
+                let velocity_error =
+                    basis.transpose() * (body_b.angular_velocity - body_a.angular_velocity);
+                error += velocity_error.norm_squared();
+
+                let position_error = basis.transpose() * axis.cross(&axis_b);
+                let k = basis.transpose() * angular_mass * basis;
+                if let Some(k_inv) = k.try_inverse() {
+                    let lambda = basis * -(k_inv * (velocity_error + position_error * bias_factor));
+                    apply_angular_impulse(body_a, body_b, &inv_inertia_a, &inv_inertia_b, lambda);
+                    self.angular_impulse += lambda;
+                }
+
+                if self.limits.is_some() {
+                    let angle = twist_angle(&rotation_error, &axis);
+                    let k = axis.dot(&(angular_mass * axis));
+                    let velocity = axis.dot(&(body_b.angular_velocity - body_a.angular_velocity));
+
+                    if let Some(lambda) = self.limit_lambda(angle, velocity, k, bias_factor) {
+                        let impulse = axis * lambda;
+                        apply_angular_impulse(
+                            body_a,
+                            body_b,
+                            &inv_inertia_a,
+                            &inv_inertia_b,
+                            impulse,
+                        );
+                        self.angular_impulse += impulse;
+                    }
+                }
+            },
+            JointKind::Ball => {},
+        }
+
+        // --- Linear constraint ---
+
+        let anchor_a = body_a.local_to_world(self.local_anchor_a);
+        let anchor_b = body_b.local_to_world(self.local_anchor_b);
+        let ra = anchor_a - body_a.position;
+        let rb = anchor_b - body_b.position;
+        let position_error = anchor_b - anchor_a;
+        let velocity_error =
+            body_b.velocity_at_point(anchor_b) - body_a.velocity_at_point(anchor_a);
+
+        let ra_skew = skew(&ra);
+        let rb_skew = skew(&rb);
+        let k = Matrix3::identity() * (inv_mass_a + inv_mass_b)
+            + ra_skew.transpose() * inv_inertia_a * ra_skew
+            + rb_skew.transpose() * inv_inertia_b * rb_skew;
+
+        let lambda = match self.kind {
+            JointKind::Prismatic => {
+                let basis = perpendicular_basis(&axis);
+                let velocity_error = basis.transpose() * velocity_error;
+                error += velocity_error.norm_squared();
+
+                let k = basis.transpose() * k * basis;
+                let k_inv = invert2(&k, "prismatic joint effective mass")?;
+                basis
+                    * -(k_inv * (velocity_error + basis.transpose() * position_error * bias_factor))
+            },
+            JointKind::Ball | JointKind::Hinge | JointKind::Fixed => {
+                error += velocity_error.norm_squared();
+
+                let k_inv = k.try_inverse().ok_or_else(|| PhysicsError::SingularMatrix {
+                    operation: "joint effective mass".to_string(),
+                    determinant: k.determinant(),
+                })?;
+                -(k_inv * (velocity_error + position_error * bias_factor))
+            },
+        };
+        apply_linear_impulse(body_a, body_b, ra, rb, lambda);
+        self.linear_impulse += lambda;
+
+        if self.kind == JointKind::Prismatic && self.limits.is_some() {
+            let translation = axis.dot(&position_error);
+            let velocity = axis
+                .dot(&(body_b.velocity_at_point(anchor_b) - body_a.velocity_at_point(anchor_a)));
+            let k_axis = axis.dot(&(k * axis));
+
+            if let Some(lambda) = self.limit_lambda(translation, velocity, k_axis, bias_factor) {
+                apply_linear_impulse(body_a, body_b, ra, rb, axis * lambda);
+                self.linear_impulse += axis * lambda;
+            }
+        }
+
+        Ok(error)
+    }
+
+    /// Rotation of body B away from its bound orientation, in world space.
+    fn rotation_error(&self, body_a: &RigidBody, body_b: &RigidBody) -> UnitQuaternion<f64> {
+        body_b.orientation * (body_a.orientation * self.reference_rotation).inverse()
+    }
+
+    /// Impulse along the limited axis, if the coordinate is at a limit.
+    ///
+    /// The accumulated limit impulse may only push the coordinate back
+    /// inside the limits.
+    fn limit_lambda(
+        &mut self,
+        coordinate: f64,
+        velocity: f64,
+        effective_mass: f64,
+        bias_factor: f64,
+    ) -> Option<f64> {
+        let (min, max) = self.limits?;
+        if effective_mass < 1e-12 {
+            return None;
+        }
+
+        let (violation, lower) = if coordinate <= min {
+            (coordinate - min, true)
+        } else if coordinate >= max {
+            (coordinate - max, false)
+        } else {
+            return None;
+        };
+
+        let lambda = -(velocity + violation * bias_factor) / effective_mass;
+        let old_impulse = self.limit_impulse;
+        self.limit_impulse = if lower {
+            (old_impulse + lambda).max(0.0)
+        } else {
+            (old_impulse + lambda).min(0.0)
+        };
+
+        Some(self.limit_impulse - old_impulse)
+    }
+}
+
+/// Inverse mass, zero for static bodies.
+fn inverse_mass(body: &RigidBody) -> f64 {
+    if body.is_static {
+        0.0
+    } else {
+        body.mass_props.inverse_mass
+    }
+}
+
+/// Applies an equal and opposite linear impulse at the anchors.
+fn apply_linear_impulse(
+    body_a: &mut RigidBody,
+    body_b: &mut RigidBody,
+    ra: Vector3<f64>,
+    rb: Vector3<f64>,
+    impulse: Vector3<f64>,
+) {
+    if !body_a.is_static {
+        body_a.linear_velocity -= impulse * body_a.mass_props.inverse_mass;
+        body_a.angular_velocity -= body_a.world_inverse_inertia_tensor() * ra.cross(&impulse);
+    }
+
+    if !body_b.is_static {
+        body_b.linear_velocity += impulse * body_b.mass_props.inverse_mass;
+        body_b.angular_velocity += body_b.world_inverse_inertia_tensor() * rb.cross(&impulse);
+    }
+}
+
+/// Applies an equal and opposite angular impulse.
+fn apply_angular_impulse(
+    body_a: &mut RigidBody,
+    body_b: &mut RigidBody,
+    inv_inertia_a: &Matrix3<f64>,
+    inv_inertia_b: &Matrix3<f64>,
+    impulse: Vector3<f64>,
+) {
+    body_a.angular_velocity -= inv_inertia_a * impulse;
+    body_b.angular_velocity += inv_inertia_b * impulse;
+}
+
+/// Two unit vectors perpendicular to `axis` and to each other, as columns.
+fn perpendicular_basis(axis: &Vector3<f64>) -> Matrix3x2<f64> {
+    let helper = if axis.x.abs() < 0.9 {
+        Vector3::x()
+    } else {
+        Vector3::y()
+    };
+    let t1 = axis.cross(&helper).normalize();
+    let t2 = axis.cross(&t1);
+
+    Matrix3x2::from_columns(&[t1, t2])
+}
+
+/// Rotation angle of `rotation` about `axis` (swing-twist decomposition).
+fn twist_angle(rotation: &UnitQuaternion<f64>, axis: &Vector3<f64>) -> f64 {
+    let angle = 2.0 * rotation.imag().dot(axis).atan2(rotation.w);
+    if angle > PI {
+        angle - 2.0 * PI
+    } else if angle < -PI {
+        angle + 2.0 * PI
+    } else {
+        angle
+    }
+}
+
+/// Inverts a 2x2 effective mass matrix.
+fn invert2(k: &Matrix2<f64>, operation: &str) -> PhysicsResult<Matrix2<f64>> {
+    k.try_inverse().ok_or_else(|| PhysicsError::SingularMatrix {
+        operation: operation.to_string(),
+        determinant: k.determinant(),
+    })
+}
+
+/// Skew-symmetric cross product matrix: a × b = [a]ₓ b.
+fn skew(v: &Vector3<f64>) -> Matrix3<f64> {
+    Matrix3::new(0.0, -v.z, v.y, v.z, 0.0, -v.x, -v.y, v.x, 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rigid_body::dynamics::MassProperties;
+
+    fn body_at(id: usize, x: f64) -> RigidBody {
+        let mut body = RigidBody::new(
+            id,
+            MassProperties::from_box(1000.0, Vector3::new(2.0, 1.0, 1.0)),
+        );
+        body.position = Vector3::new(x, 0.0, 0.0);
+        body
+    }
+
+    fn solve_steps(joint: &mut Joint, a: &mut RigidBody, b: &mut RigidBody, steps: usize) {
+        let dt = 0.01;
+        for _ in 0..steps {
+            joint.begin_step();
+            for _ in 0..10 {
+                joint.solve(a, b, dt, 0.2).unwrap();
+            }
+            joint.end_step(dt);
+            for body in [&mut *a, &mut *b] {
+                body.position += body.linear_velocity * dt;
+                body.orientation =
+                    UnitQuaternion::from_scaled_axis(body.angular_velocity * dt) * body.orientation;
+            }
+        }
+    }
+
+    #[test]
+    fn test_ball_joint_keeps_anchors_together() {
+        let mut a = body_at(0, 0.0);
+        let mut b = body_at(1, 2.0);
+        let mut joint = Joint::ball(0, 1, Vector3::new(1.0, 0.0, 0.0));
+        joint.bind(&a, &b);
+
+        b.linear_velocity = Vector3::new(0.0, 3.0, 0.0);
+        solve_steps(&mut joint, &mut a, &mut b, 50);
+
+        let gap = b.local_to_world(joint.local_anchor_b) - a.local_to_world(joint.local_anchor_a);
+        assert!(gap.norm() < 1e-2, "gap {}", gap.norm());
+        // The bodies swing about the hitch instead of separating
+        assert!(b.angular_velocity.norm() > 0.0);
+    }
+
+    #[test]
+    fn test_hinge_allows_only_axis_rotation() {
+        let mut a = RigidBody::new_static(0);
+        let mut b = body_at(1, 1.0);
+        let mut joint = Joint::hinge(0, 1, Vector3::zeros(), Vector3::z());
+        joint.bind(&a, &b);
+
+        b.angular_velocity = Vector3::new(1.0, 1.0, 2.0);
+        solve_steps(&mut joint, &mut a, &mut b, 20);
+
+        assert!(b.angular_velocity.x.abs() < 1e-3);
+        assert!(b.angular_velocity.y.abs() < 1e-3);
+        assert!(b.angular_velocity.z.abs() > 0.1);
+        assert!(joint.coordinate(&a, &b) > 0.0);
+    }
+
+    #[test]
+    fn test_hinge_limit() {
+        let mut a = RigidBody::new_static(0);
+        let mut b = body_at(1, 1.0);
+        let mut joint = Joint::hinge(0, 1, Vector3::zeros(), Vector3::z()).with_limits(-0.3, 0.3);
+        joint.bind(&a, &b);
+
+        b.angular_velocity = Vector3::new(0.0, 0.0, 2.0);
+        solve_steps(&mut joint, &mut a, &mut b, 100);
+
+        assert!(joint.coordinate(&a, &b) < 0.35);
+    }
+
+    #[test]
+    fn test_prismatic_slides_along_axis_only() {
+        let mut a = RigidBody::new_static(0);
+        let mut b = body_at(1, 2.0);
+        let mut joint = Joint::prismatic(0, 1, Vector3::new(2.0, 0.0, 0.0), Vector3::x())
+            .with_limits(-0.5, 0.5);
+        joint.bind(&a, &b);
+
+        b.linear_velocity = Vector3::new(1.0, 1.0, 0.0);
+        b.angular_velocity = Vector3::new(0.0, 0.0, 1.0);
+        solve_steps(&mut joint, &mut a, &mut b, 100);
+
+        assert!(b.position.y.abs() < 1e-2);
+        assert!(b.angular_velocity.norm() < 1e-3);
+        // Slid out to the limit and stopped there
+        let travel = joint.coordinate(&a, &b);
+        assert!(travel > 0.4 && travel < 0.55, "travel {}", travel);
+    }
+
+    #[test]
+    fn test_fixed_joint_locks_relative_motion() {
+        let mut a = body_at(0, 0.0);
+        let mut b = body_at(1, 2.0);
+        let mut joint = Joint::fixed(0, 1, Vector3::new(1.0, 0.0, 0.0));
+        joint.bind(&a, &b);
+
+        a.linear_velocity = Vector3::new(10.0, 0.0, 0.0);
+        b.angular_velocity = Vector3::new(0.0, 2.0, 0.0);
+        solve_steps(&mut joint, &mut a, &mut b, 10);
+
+        // The pair moves as one rigid body: same spin, same velocity at the
+        // joint and unchanged separation
+        let anchor = a.local_to_world(joint.local_anchor_a);
+        assert!((a.angular_velocity - b.angular_velocity).norm() < 1e-3);
+        assert!((a.velocity_at_point(anchor) - b.velocity_at_point(anchor)).norm() < 1e-2);
+        assert!(((b.position - a.position).norm() - 2.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_breakable_joint() {
+        let mut a = RigidBody::new_static(0);
+        let mut b = body_at(1, 1.0);
+        let mut joint = Joint::fixed(0, 1, Vector3::zeros()).with_break_force(50_000.0);
+        joint.bind(&a, &b);
+
+        // 1000 kg stopped from 2 m/s in one 10 ms step needs 200 kN
+        b.linear_velocity = Vector3::new(2.0, 0.0, 0.0);
+        joint.begin_step();
+        for _ in 0..10 {
+            joint.solve(&mut a, &mut b, 0.01, 0.2).unwrap();
+        }
+
+        assert!(joint.end_step(0.01));
+        assert!(joint.reaction_force > 50_000.0);
+        assert!(!joint.is_active());
+
+        // A broken joint no longer constrains the bodies
+        b.linear_velocity = Vector3::new(2.0, 0.0, 0.0);
+        joint.begin_step();
+        joint.solve(&mut a, &mut b, 0.01, 0.2).unwrap();
+        assert_eq!(b.linear_velocity.x, 2.0);
+        assert!(!joint.end_step(0.01));
+    }
+
+    #[test]
+    fn test_twist_angle() {
+        let rotation = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 0.7);
+        assert!((twist_angle(&rotation, &Vector3::z()) - 0.7).abs() < 1e-12);
+        assert!(twist_angle(&rotation, &Vector3::x()).abs() < 1e-12);
+    }
+}
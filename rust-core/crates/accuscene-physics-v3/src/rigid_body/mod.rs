@@ -5,12 +5,15 @@
 //! - Linear and angular momentum integration
 //! - Force and torque accumulation
 //! - Constraint systems
+//! - Joints (ball, hinge, prismatic, fixed, breakable)
 
 pub mod constraints;
 pub mod dynamics;
+pub mod joints;
 
 pub use constraints::*;
 pub use dynamics::*;
+pub use joints::*;
 
 use nalgebra::{Matrix3, UnitQuaternion, Vector3};
 use serde::{Deserialize, Serialize};
@@ -199,6 +202,63 @@ impl RigidBody {
     pub fn world_to_local(&self, world_point: Vector3<f64>) -> Vector3<f64> {
         self.orientation.inverse() * (world_point - self.position)
     }
+
+    /// Captures the state a simulation step mutates.
+    pub fn state(&self) -> BodyState {
+        BodyState {
+            position: self.position,
+            orientation: self.orientation,
+            linear_velocity: self.linear_velocity,
+            angular_velocity: self.angular_velocity,
+            force: self.force,
+            torque: self.torque,
+            is_awake: self.is_awake,
+            time_at_rest: self.time_at_rest,
+        }
+    }
+
+    /// Restores state captured by [`RigidBody::state`].
+    pub fn restore(&mut self, state: &BodyState) {
+        self.position = state.position;
+        self.orientation = state.orientation;
+        self.linear_velocity = state.linear_velocity;
+        self.angular_velocity = state.angular_velocity;
+        self.force = state.force;
+        self.torque = state.torque;
+        self.is_awake = state.is_awake;
+        self.time_at_rest = state.time_at_rest;
+    }
+}
+
+/// Dynamic state of a rigid body, used to roll back a failed step.
+///
+/// Mass properties and configuration are left out since stepping never
+/// changes them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BodyState {
+    /// Position in world space (m).
+    pub position: Vector3<f64>,
+
+    /// Orientation as unit quaternion.
+    pub orientation: UnitQuaternion<f64>,
+
+    /// Linear velocity (m/s).
+    pub linear_velocity: Vector3<f64>,
+
+    /// Angular velocity (rad/s) in world space.
+    pub angular_velocity: Vector3<f64>,
+
+    /// Accumulated forces (N).
+    pub force: Vector3<f64>,
+
+    /// Accumulated torques (N·m) in world space.
+    pub torque: Vector3<f64>,
+
+    /// Is the body awake?
+    pub is_awake: bool,
+
+    /// Time at rest (for sleep detection).
+    pub time_at_rest: f64,
 }
 
 #[cfg(test)]
@@ -234,4 +294,17 @@ mod tests {
         let expected = 0.5 * 1000.0 * 100.0; // 0.5 * m * v²
         assert_relative_eq!(ke, expected);
     }
+
+    #[test]
+    fn test_restore_state() {
+        let mass_props = MassProperties::from_box(1000.0, Vector3::new(1.0, 1.0, 1.0));
+        let mut body = RigidBody::new(0, mass_props);
+        let state = body.state();
+
+        body.position = Vector3::new(1.0, 2.0, 3.0);
+        body.apply_force(Vector3::new(100.0, 0.0, 0.0));
+        body.restore(&state);
+
+        assert_eq!(body.state(), state);
+    }
 }
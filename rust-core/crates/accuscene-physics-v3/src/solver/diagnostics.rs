@@ -0,0 +1,610 @@
+//! Solver diagnostics and divergence detection.
+//!
+//! Tracks the residual of every solver iteration and checks body and
+//! constraint state for NaNs, infinities and exploding velocities. When the
+//! solver diverges the step is aborted with a [`PhysicsError::SolverDivergence`]
+//! carrying a [`DivergenceReport`] that names the offending bodies and
+//! constraints and suggests how to fix the setup.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::config::SolverConfig;
+use crate::error::{PhysicsError, PhysicsResult};
+use crate::rigid_body::{constraints::ContactConstraint, RigidBody};
+
+/// Limits beyond which the solver is considered to have diverged.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct DivergenceThresholds {
+    /// Maximum plausible linear speed (m/s).
+    pub max_linear_speed: f64,
+
+    /// Maximum plausible angular speed (rad/s).
+    pub max_angular_speed: f64,
+
+    /// Residual growth, relative to the first iteration, that counts as divergence.
+    pub residual_growth_factor: f64,
+
+    /// Mass ratio between constrained bodies above which the solver is likely ill-conditioned.
+    pub max_mass_ratio: f64,
+}
+
+impl Default for DivergenceThresholds {
+    fn default() -> Self {
+        Self {
+            max_linear_speed: 1000.0,
+            max_angular_speed: 1000.0,
+            residual_growth_factor: 1e3,
+            max_mass_ratio: 100.0,
+        }
+    }
+}
+
+/// Phase of the step in which divergence was detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SolverStage {
+    /// Velocity (impulse) iterations.
+    Velocity,
+
+    /// Position correction iterations.
+    Position,
+
+    /// Integration after the solve.
+    Integration,
+}
+
+impl fmt::Display for SolverStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Velocity => write!(f, "velocity solve"),
+            Self::Position => write!(f, "position correction"),
+            Self::Integration => write!(f, "integration"),
+        }
+    }
+}
+
+/// What went wrong.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DivergenceCause {
+    /// NaN or infinite values in body state or constraint impulses.
+    NonFinite,
+
+    /// Body velocity beyond the plausible limit.
+    VelocityExplosion {
+        /// Largest linear speed observed (m/s).
+        linear_speed: f64,
+        /// Largest angular speed observed (rad/s).
+        angular_speed: f64,
+    },
+
+    /// Residual grew instead of shrinking.
+    ResidualGrowth {
+        /// Residual of the first iteration.
+        initial: f64,
+        /// Residual when divergence was detected.
+        current: f64,
+    },
+}
+
+impl fmt::Display for DivergenceCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NonFinite => write!(f, "non-finite values"),
+            Self::VelocityExplosion {
+                linear_speed,
+                angular_speed,
+            } => write!(
+                f,
+                "velocity explosion ({:.3e} m/s, {:.3e} rad/s)",
+                linear_speed, angular_speed
+            ),
+            Self::ResidualGrowth { initial, current } => {
+                write!(f, "residual grew from {:.3e} to {:.3e}", initial, current)
+            }
+        }
+    }
+}
+
+/// Suggested fix for a solver failure.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Remediation {
+    /// Use a smaller time step.
+    ReduceTimeStep {
+        /// Time step that failed (s).
+        current: f64,
+        /// Suggested time step (s).
+        suggested: f64,
+    },
+
+    /// Allow more solver iterations.
+    IncreaseIterations {
+        /// Iterations that were used.
+        current: usize,
+        /// Suggested iteration count.
+        suggested: usize,
+    },
+
+    /// Use a softer Baumgarte factor for position correction.
+    ReduceBaumgarteFactor {
+        /// Factor that was used.
+        current: f64,
+        /// Suggested factor.
+        suggested: f64,
+    },
+
+    /// Constrained bodies have very different masses.
+    ReviewMassRatio {
+        /// Bodies involved.
+        bodies: (usize, usize),
+        /// Heavier-to-lighter mass ratio.
+        ratio: f64,
+    },
+
+    /// Initial state of the bodies contains invalid values.
+    ReviewInitialState {
+        /// Bodies with invalid state.
+        bodies: Vec<usize>,
+    },
+}
+
+impl fmt::Display for Remediation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ReduceTimeStep { current, suggested } => {
+                write!(f, "reduce dt from {} to {}", current, suggested)
+            }
+            Self::IncreaseIterations { current, suggested } => {
+                write!(f, "increase solver iterations from {} to {}", current, suggested)
+            }
+            Self::ReduceBaumgarteFactor { current, suggested } => {
+                write!(f, "reduce Baumgarte factor from {} to {}", current, suggested)
+            }
+            Self::ReviewMassRatio { bodies, ratio } => write!(
+                f,
+                "review masses of bodies {} and {} (ratio {:.0}:1)",
+                bodies.0, bodies.1, ratio
+            ),
+            Self::ReviewInitialState { bodies } => {
+                write!(f, "check initial state of bodies {:?}", bodies)
+            }
+        }
+    }
+}
+
+/// Structured description of a solver divergence.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DivergenceReport {
+    /// What went wrong.
+    pub cause: DivergenceCause,
+
+    /// Phase of the step in which it was detected.
+    pub stage: SolverStage,
+
+    /// Iteration in which it was detected (for solver stages).
+    pub iteration: Option<usize>,
+
+    /// IDs of the offending bodies.
+    pub bodies: Vec<usize>,
+
+    /// Indices of the offending constraints in the step's contact list.
+    pub constraints: Vec<usize>,
+
+    /// Residual of every iteration up to the failure.
+    pub residual_history: Vec<f64>,
+
+    /// Suggested fixes, most likely first.
+    pub remediation: Vec<Remediation>,
+}
+
+impl fmt::Display for DivergenceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} during {}", self.cause, self.stage)?;
+        if let Some(iteration) = self.iteration {
+            write!(f, " (iteration {})", iteration)?;
+        }
+        if !self.bodies.is_empty() {
+            write!(f, "; bodies {:?}", self.bodies)?;
+        }
+        if !self.constraints.is_empty() {
+            write!(f, "; constraints {:?}", self.constraints)?;
+        }
+        if !self.remediation.is_empty() {
+            let fixes: Vec<String> = self.remediation.iter().map(|r| r.to_string()).collect();
+            write!(f, "; try: {}", fixes.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Per-step solver diagnostics.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SolverDiagnostics {
+    /// Divergence thresholds.
+    pub thresholds: DivergenceThresholds,
+
+    /// Residual of every velocity iteration of the current step.
+    residual_history: Vec<f64>,
+
+    /// Time step of the current step.
+    dt: f64,
+
+    /// Solver configuration of the current step.
+    config: SolverConfig,
+}
+
+impl SolverDiagnostics {
+    /// Creates diagnostics with custom thresholds.
+    pub fn with_thresholds(thresholds: DivergenceThresholds) -> Self {
+        Self {
+            thresholds,
+            ..Self::default()
+        }
+    }
+
+    /// Resets tracking for a new step.
+    pub fn begin_step(&mut self, dt: f64, config: &SolverConfig) {
+        self.residual_history.clear();
+        self.dt = dt;
+        self.config = config.clone();
+    }
+
+    /// Residual of every velocity iteration of the last step.
+    pub fn residual_history(&self) -> &[f64] {
+        &self.residual_history
+    }
+
+    /// Average factor by which the residual shrank per iteration in the last step.
+    ///
+    /// Values below 1.0 mean the solver was converging.
+    pub fn convergence_rate(&self) -> Option<f64> {
+        let first = *self.residual_history.first()?;
+        let last = *self.residual_history.last()?;
+        let steps = self.residual_history.len() - 1;
+
+        if steps == 0 || first <= 0.0 || !last.is_finite() {
+            return None;
+        }
+
+        Some((last / first).powf(1.0 / steps as f64))
+    }
+
+    /// Records the residual of a velocity iteration and checks the state for divergence.
+    pub fn record_iteration(
+        &mut self,
+        iteration: usize,
+        residual: f64,
+        bodies: &[RigidBody],
+        contacts: &[ContactConstraint],
+    ) -> PhysicsResult<()> {
+        self.residual_history.push(residual);
+
+        self.check_state(SolverStage::Velocity, Some(iteration), bodies, contacts)?;
+
+        let initial = self.residual_history[0].max(self.config.tolerance);
+        if !residual.is_finite() || residual > initial * self.thresholds.residual_growth_factor {
+            let cause = if residual.is_finite() {
+                DivergenceCause::ResidualGrowth {
+                    initial: self.residual_history[0],
+                    current: residual,
+                }
+            } else {
+                DivergenceCause::NonFinite
+            };
+            let constraints: Vec<usize> = (0..contacts.len()).collect();
+            return Err(self.divergence(
+                cause,
+                SolverStage::Velocity,
+                Some(iteration),
+                Vec::new(),
+                constraints,
+                bodies,
+                contacts,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Checks impulses held outside the contacts (e.g. PGS multipliers) for NaNs.
+    pub fn check_impulses(
+        &self,
+        iteration: usize,
+        impulses: &[f64],
+        bodies: &[RigidBody],
+        contacts: &[ContactConstraint],
+    ) -> PhysicsResult<()> {
+        let constraints: Vec<usize> = impulses
+            .iter()
+            .enumerate()
+            .filter(|(_, impulse)| !impulse.is_finite())
+            .map(|(i, _)| i)
+            .collect();
+
+        if constraints.is_empty() {
+            return Ok(());
+        }
+
+        Err(self.divergence(
+            DivergenceCause::NonFinite,
+            SolverStage::Velocity,
+            Some(iteration),
+            Vec::new(),
+            constraints,
+            bodies,
+            contacts,
+        ))
+    }
+
+    /// Checks body state and contact impulses for NaNs and exploding velocities.
+    pub fn check_state(
+        &self,
+        stage: SolverStage,
+        iteration: Option<usize>,
+        bodies: &[RigidBody],
+        contacts: &[ContactConstraint],
+    ) -> PhysicsResult<()> {
+        let non_finite_bodies: Vec<usize> = bodies
+            .iter()
+            .filter(|body| !is_finite_state(body))
+            .map(|body| body.id)
+            .collect();
+
+        let non_finite_constraints: Vec<usize> = contacts
+            .iter()
+            .enumerate()
+            .filter(|(_, contact)| {
+                !contact.accumulated_normal_impulse.is_finite()
+                    || !contact.accumulated_tangent_impulse.iter().all(|v| v.is_finite())
+            })
+            .map(|(i, _)| i)
+            .collect();
+
+        if !non_finite_bodies.is_empty() || !non_finite_constraints.is_empty() {
+            let mut constraints = non_finite_constraints;
+            constraints.extend(constraints_touching(contacts, bodies, &non_finite_bodies));
+            constraints.sort_unstable();
+            constraints.dedup();
+
+            return Err(self.divergence(
+                DivergenceCause::NonFinite,
+                stage,
+                iteration,
+                non_finite_bodies,
+                constraints,
+                bodies,
+                contacts,
+            ));
+        }
+
+        let exploding: Vec<&RigidBody> = bodies
+            .iter()
+            .filter(|body| {
+                !body.is_static
+                    && (body.linear_velocity.norm() > self.thresholds.max_linear_speed
+                        || body.angular_velocity.norm() > self.thresholds.max_angular_speed)
+            })
+            .collect();
+
+        if !exploding.is_empty() {
+            let cause = DivergenceCause::VelocityExplosion {
+                linear_speed: exploding
+                    .iter()
+                    .map(|b| b.linear_velocity.norm())
+                    .fold(0.0, f64::max),
+                angular_speed: exploding
+                    .iter()
+                    .map(|b| b.angular_velocity.norm())
+                    .fold(0.0, f64::max),
+            };
+            let ids: Vec<usize> = exploding.iter().map(|b| b.id).collect();
+            let constraints = constraints_touching(contacts, bodies, &ids);
+
+            return Err(self.divergence(cause, stage, iteration, ids, constraints, bodies, contacts));
+        }
+
+        Ok(())
+    }
+
+    /// Builds the divergence error with remediation suggestions.
+    #[allow(clippy::too_many_arguments)]
+    fn divergence(
+        &self,
+        cause: DivergenceCause,
+        stage: SolverStage,
+        iteration: Option<usize>,
+        offending_bodies: Vec<usize>,
+        constraints: Vec<usize>,
+        bodies: &[RigidBody],
+        contacts: &[ContactConstraint],
+    ) -> PhysicsError {
+        let config = &self.config;
+        let mut remediation = Vec::new();
+
+        // Invalid values before the first iteration point at the input, not the solver
+        if cause == DivergenceCause::NonFinite && stage == SolverStage::Velocity && iteration.is_none() {
+            remediation.push(Remediation::ReviewInitialState {
+                bodies: offending_bodies.clone(),
+            });
+        }
+
+        if self.dt > 0.0 {
+            remediation.push(Remediation::ReduceTimeStep {
+                current: self.dt,
+                suggested: self.dt / 2.0,
+            });
+        }
+
+        match stage {
+            SolverStage::Velocity => remediation.push(Remediation::IncreaseIterations {
+                current: config.velocity_iterations,
+                suggested: (config.velocity_iterations * 2).max(1),
+            }),
+            SolverStage::Position => remediation.push(Remediation::ReduceBaumgarteFactor {
+                current: config.baumgarte_factor,
+                suggested: config.baumgarte_factor / 2.0,
+            }),
+            SolverStage::Integration => {}
+        }
+
+        if let Some((pair, ratio)) = worst_mass_ratio(bodies, contacts, &constraints) {
+            if ratio > self.thresholds.max_mass_ratio {
+                remediation.push(Remediation::ReviewMassRatio {
+                    bodies: pair,
+                    ratio,
+                });
+            }
+        }
+
+        PhysicsError::SolverDivergence(Box::new(DivergenceReport {
+            cause,
+            stage,
+            iteration,
+            bodies: offending_bodies,
+            constraints,
+            residual_history: self.residual_history.clone(),
+            remediation,
+        }))
+    }
+}
+
+/// Checks that every state component of a body is finite.
+fn is_finite_state(body: &RigidBody) -> bool {
+    body.position.iter().all(|v| v.is_finite())
+        && body.linear_velocity.iter().all(|v| v.is_finite())
+        && body.angular_velocity.iter().all(|v| v.is_finite())
+        && body.orientation.coords.iter().all(|v| v.is_finite())
+}
+
+/// Indices of contacts involving any of the given body IDs.
+fn constraints_touching(contacts: &[ContactConstraint], bodies: &[RigidBody], ids: &[usize]) -> Vec<usize> {
+    let involves = |index: usize| bodies.get(index).is_some_and(|b| ids.contains(&b.id));
+
+    contacts
+        .iter()
+        .enumerate()
+        .filter(|(_, contact)| involves(contact.body_a) || involves(contact.body_b))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Body pair with the largest mass ratio among the given constraints.
+fn worst_mass_ratio(
+    bodies: &[RigidBody],
+    contacts: &[ContactConstraint],
+    constraints: &[usize],
+) -> Option<((usize, usize), f64)> {
+    constraints
+        .iter()
+        .filter_map(|&i| contacts.get(i))
+        .filter_map(|contact| {
+            let a = bodies.get(contact.body_a)?;
+            let b = bodies.get(contact.body_b)?;
+            if a.is_static || b.is_static {
+                return None;
+            }
+
+            let (inv_a, inv_b) = (a.mass_props.inverse_mass, b.mass_props.inverse_mass);
+            if inv_a <= 0.0 || inv_b <= 0.0 {
+                return None;
+            }
+
+            Some(((a.id, b.id), inv_a.max(inv_b) / inv_a.min(inv_b)))
+        })
+        .max_by(|x, y| x.1.total_cmp(&y.1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rigid_body::dynamics::MassProperties;
+    use nalgebra::Vector3;
+
+    fn contact(a: usize, b: usize) -> ContactConstraint {
+        ContactConstraint::new(
+            a,
+            b,
+            Vector3::zeros(),
+            Vector3::zeros(),
+            Vector3::new(0.0, 0.0, 1.0),
+            0.01,
+            0.5,
+            0.3,
+        )
+    }
+
+    #[test]
+    fn test_nan_velocity_names_body_and_constraint() {
+        let config = SolverConfig::default();
+        let mut diagnostics = SolverDiagnostics::default();
+        diagnostics.begin_step(0.01, &config);
+
+        let mut bodies = vec![
+            RigidBody::new(0, MassProperties::from_sphere(1000.0, 1.0)),
+            RigidBody::new(1, MassProperties::from_sphere(1000.0, 1.0)),
+            RigidBody::new(2, MassProperties::from_sphere(1000.0, 1.0)),
+        ];
+        bodies[1].linear_velocity.x = f64::NAN;
+        let contacts = vec![contact(0, 2), contact(0, 1)];
+
+        let error = diagnostics
+            .record_iteration(0, 0.5, &bodies, &contacts)
+            .unwrap_err();
+
+        let PhysicsError::SolverDivergence(report) = &error else {
+            panic!("unexpected error {error}");
+        };
+        assert_eq!(report.cause, DivergenceCause::NonFinite);
+        assert_eq!(report.bodies, vec![1]);
+        assert_eq!(report.constraints, vec![1]);
+        assert!(report
+            .remediation
+            .contains(&Remediation::ReduceTimeStep { current: 0.01, suggested: 0.005 }));
+        assert!(!error.remediation().is_empty());
+    }
+
+    #[test]
+    fn test_residual_growth_is_divergence() {
+        let config = SolverConfig::default();
+        let mut diagnostics = SolverDiagnostics::default();
+        diagnostics.begin_step(0.01, &config);
+
+        let bodies = vec![
+            RigidBody::new(0, MassProperties::from_sphere(1.0, 1.0)),
+            RigidBody::new(1, MassProperties::from_sphere(1000.0, 1.0)),
+        ];
+        let contacts = vec![contact(0, 1)];
+
+        diagnostics.record_iteration(0, 1.0, &bodies, &contacts).unwrap();
+        diagnostics.record_iteration(1, 0.5, &bodies, &contacts).unwrap();
+        assert!((diagnostics.convergence_rate().unwrap() - 0.5).abs() < 1e-12);
+
+        let error = diagnostics
+            .record_iteration(2, 5e3, &bodies, &contacts)
+            .unwrap_err();
+        let PhysicsError::SolverDivergence(report) = error else {
+            panic!("expected divergence");
+        };
+
+        assert!(matches!(report.cause, DivergenceCause::ResidualGrowth { .. }));
+        assert_eq!(report.iteration, Some(2));
+        assert_eq!(report.residual_history, vec![1.0, 0.5, 5e3]);
+        assert!(report
+            .remediation
+            .iter()
+            .any(|r| matches!(r, Remediation::ReviewMassRatio { bodies: (0, 1), .. })));
+    }
+
+    #[test]
+    fn test_velocity_explosion() {
+        let diagnostics = SolverDiagnostics::default();
+
+        let mut body = RigidBody::new(7, MassProperties::from_sphere(1000.0, 1.0));
+        body.angular_velocity.z = 5e4;
+
+        let error = diagnostics
+            .check_state(SolverStage::Integration, None, &[body], &[])
+            .unwrap_err();
+        assert!(error.to_string().contains("velocity explosion"));
+        assert!(error.to_string().contains("[7]"));
+    }
+}
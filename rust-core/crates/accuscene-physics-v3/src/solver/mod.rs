@@ -4,10 +4,13 @@
 //! - Contact constraints
 //! - Joint constraints
 //! - Velocity and position corrections
+//! - Divergence detection and diagnostics
 
+pub mod diagnostics;
 pub mod pgs;
 pub mod sequential_impulse;
 
+pub use diagnostics::*;
 pub use pgs::*;
 pub use sequential_impulse::*;
 
@@ -15,7 +18,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::config::SolverConfig;
 use crate::error::PhysicsResult;
-use crate::rigid_body::{constraints::ContactConstraint, RigidBody};
+use crate::rigid_body::{constraints::ContactConstraint, joints::Joint, RigidBody};
 
 /// Physics solver for constraint resolution.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,9 +49,25 @@ impl PhysicsSolver {
         bodies: &mut [RigidBody],
         contacts: &mut [ContactConstraint],
         dt: f64,
+    ) -> PhysicsResult<SolverStats> {
+        self.solve_constraints_with_joints(bodies, contacts, &mut [], dt)
+    }
+
+    /// Solves contacts and joints for one step, breaking overloaded joints.
+    pub fn solve_constraints_with_joints(
+        &mut self,
+        bodies: &mut [RigidBody],
+        contacts: &mut [ContactConstraint],
+        joints: &mut [Joint],
+        dt: f64,
     ) -> PhysicsResult<SolverStats> {
         // Use sequential impulse solver
-        self.si_solver.solve(bodies, contacts, dt)
+        self.si_solver.solve_with_joints(bodies, contacts, joints, dt)
+    }
+
+    /// Diagnostics of the last constraint solve.
+    pub fn diagnostics(&self) -> &SolverDiagnostics {
+        &self.si_solver.diagnostics
     }
 
     /// Applies position correction (Baumgarte stabilization).
@@ -10,7 +10,7 @@ use std::time::Instant;
 use crate::config::SolverConfig;
 use crate::error::PhysicsResult;
 use crate::rigid_body::{constraints::ContactConstraint, RigidBody};
-use crate::solver::SolverStats;
+use crate::solver::{SolverDiagnostics, SolverStage, SolverStats};
 
 /// Projected Gauss-Seidel constraint solver.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +20,10 @@ pub struct PGSSolver {
 
     /// Over-relaxation factor (1.0 = standard Gauss-Seidel, >1.0 = SOR).
     pub omega: f64,
+
+    /// Residual tracking and divergence detection.
+    #[serde(default)]
+    pub diagnostics: SolverDiagnostics,
 }
 
 impl PGSSolver {
@@ -28,6 +32,7 @@ impl PGSSolver {
         Self {
             config,
             omega: 1.0, // Standard Gauss-Seidel (can use 1.2-1.8 for SOR)
+            diagnostics: SolverDiagnostics::default(),
         }
     }
 
@@ -42,6 +47,7 @@ impl PGSSolver {
         dt: f64,
     ) -> PhysicsResult<SolverStats> {
         let start_time = Instant::now();
+        self.diagnostics.begin_step(dt, &self.config);
 
         if contacts.is_empty() {
             return Ok(SolverStats {
@@ -53,6 +59,8 @@ impl PGSSolver {
             });
         }
 
+        self.diagnostics.check_state(SolverStage::Velocity, None, bodies, contacts)?;
+
         let num_constraints = contacts.len();
         let mut lambda = vec![0.0; num_constraints]; // Impulse magnitudes
 
@@ -96,6 +104,9 @@ impl PGSSolver {
             let residual = self.compute_residual(&lambda, &old_lambda);
             final_residual = residual;
 
+            self.diagnostics.check_impulses(iter, &lambda, bodies, contacts)?;
+            self.diagnostics.record_iteration(iter, residual, bodies, contacts)?;
+
             if residual < self.config.tolerance {
                 converged = true;
                 break;
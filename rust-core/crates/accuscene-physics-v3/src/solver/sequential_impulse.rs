@@ -8,8 +8,8 @@ use std::time::Instant;
 
 use crate::config::SolverConfig;
 use crate::error::PhysicsResult;
-use crate::rigid_body::{constraints::ContactConstraint, RigidBody};
-use crate::solver::SolverStats;
+use crate::rigid_body::{constraints::ContactConstraint, joints::Joint, RigidBody};
+use crate::solver::{SolverDiagnostics, SolverStage, SolverStats};
 
 /// Sequential impulse constraint solver.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +19,10 @@ pub struct SequentialImpulseSolver {
 
     /// Warm start factor (0-1).
     pub warm_start_factor: f64,
+
+    /// Residual tracking and divergence detection.
+    #[serde(default)]
+    pub diagnostics: SolverDiagnostics,
 }
 
 impl SequentialImpulseSolver {
@@ -27,6 +31,7 @@ impl SequentialImpulseSolver {
         Self {
             config,
             warm_start_factor: 0.8,
+            diagnostics: SolverDiagnostics::default(),
         }
     }
 
@@ -36,10 +41,31 @@ impl SequentialImpulseSolver {
         bodies: &mut [RigidBody],
         contacts: &mut [ContactConstraint],
         dt: f64,
+    ) -> PhysicsResult<SolverStats> {
+        self.solve_with_joints(bodies, contacts, &mut [], dt)
+    }
+
+    /// Solves contacts and joints together for the specified number of iterations.
+    ///
+    /// Joints are solved before contacts in every velocity iteration. After
+    /// the last iteration, joints whose reaction load exceeded their break
+    /// thresholds are marked broken.
+    pub fn solve_with_joints(
+        &mut self,
+        bodies: &mut [RigidBody],
+        contacts: &mut [ContactConstraint],
+        joints: &mut [Joint],
+        dt: f64,
     ) -> PhysicsResult<SolverStats> {
         let start_time = Instant::now();
+        self.diagnostics.begin_step(dt, &self.config);
+
+        for joint in joints.iter_mut() {
+            joint.begin_step();
+        }
+        let active_joints = joints.iter().filter(|joint| joint.is_active()).count();
 
-        if contacts.is_empty() {
+        if contacts.is_empty() && active_joints == 0 {
             return Ok(SolverStats {
                 iterations: 0,
                 residual: 0.0,
@@ -49,6 +75,8 @@ impl SequentialImpulseSolver {
             });
         }
 
+        self.diagnostics.check_state(SolverStage::Velocity, None, bodies, contacts)?;
+
         // Warm start (apply cached impulses)
         if self.config.warm_starting {
             self.warm_start(bodies, contacts);
@@ -62,18 +90,31 @@ impl SequentialImpulseSolver {
         for iter in 0..self.config.velocity_iterations {
             iterations = iter + 1;
 
-            let residual = self.solve_velocity_iteration(bodies, contacts, dt)?;
+            let residual = self.solve_velocity_iteration(bodies, contacts, joints, dt)?;
             final_residual = residual;
 
+            self.diagnostics.record_iteration(iter, residual, bodies, contacts)?;
+
             if residual < self.config.tolerance {
                 converged = true;
                 break;
             }
         }
 
+        for joint in joints.iter_mut() {
+            joint.end_step(dt);
+        }
+
         // Position correction iterations
-        for _ in 0..self.config.position_iterations {
+        for iter in 0..self.config.position_iterations {
             self.solve_position_iteration(bodies, contacts)?;
+
+            self.diagnostics.check_state(
+                SolverStage::Position,
+                Some(iter),
+                bodies,
+                contacts,
+            )?;
         }
 
         let solve_time = start_time.elapsed().as_secs_f64();
@@ -82,7 +123,7 @@ impl SequentialImpulseSolver {
             iterations,
             residual: final_residual,
             converged,
-            num_constraints: contacts.len(),
+            num_constraints: contacts.len() + active_joints,
             solve_time,
         })
     }
@@ -152,9 +193,18 @@ impl SequentialImpulseSolver {
         &self,
         bodies: &mut [RigidBody],
         contacts: &mut [ContactConstraint],
+        joints: &mut [Joint],
         dt: f64,
     ) -> PhysicsResult<f64> {
         let mut total_error = 0.0;
+        let mut num_rows = contacts.len();
+
+        for joint in joints.iter_mut().filter(|joint| joint.is_active()) {
+            if let Some((body_a, body_b)) = body_pair(bodies, joint.body_a, joint.body_b) {
+                total_error += joint.solve(body_a, body_b, dt, self.config.baumgarte_factor)?;
+                num_rows += 1;
+            }
+        }
 
         for contact in contacts.iter_mut() {
             // Get mutable references to both bodies
@@ -196,7 +246,11 @@ impl SequentialImpulseSolver {
             total_error += error * error;
         }
 
-        Ok((total_error / contacts.len() as f64).sqrt())
+        if num_rows == 0 {
+            return Ok(0.0);
+        }
+
+        Ok((total_error / num_rows as f64).sqrt())
     }
 
     /// Solves one position iteration (Baumgarte stabilization).
@@ -254,10 +308,31 @@ impl SequentialImpulseSolver {
     }
 }
 
+/// Mutable references to two distinct bodies.
+fn body_pair(
+    bodies: &mut [RigidBody],
+    a: usize,
+    b: usize,
+) -> Option<(&mut RigidBody, &mut RigidBody)> {
+    if a == b || a >= bodies.len() || b >= bodies.len() {
+        return None;
+    }
+
+    if a < b {
+        let (left, right) = bodies.split_at_mut(b);
+        Some((&mut left[a], &mut right[0]))
+    } else {
+        let (left, right) = bodies.split_at_mut(a);
+        Some((&mut right[0], &mut left[b]))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::PhysicsError;
     use crate::rigid_body::dynamics::MassProperties;
+    use crate::solver::Remediation;
     use nalgebra::Vector3;
 
     #[test]
@@ -318,5 +393,39 @@ mod tests {
 
         let stats = result.unwrap();
         assert_eq!(stats.num_constraints, 1);
+        assert_eq!(solver.diagnostics.residual_history().len(), stats.iterations);
+    }
+
+    #[test]
+    fn test_si_solver_aborts_on_nan() {
+        let config = SolverConfig::default();
+        let mut solver = SequentialImpulseSolver::new(config);
+
+        let mass_props = MassProperties::from_sphere(1000.0, 1.0);
+        let body_a = RigidBody::new(0, mass_props.clone());
+        let mut body_b = RigidBody::new(1, mass_props);
+        body_b.linear_velocity = Vector3::new(f64::NAN, 0.0, 0.0);
+
+        let contact = ContactConstraint::new(
+            0,
+            1,
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+            0.1,
+            0.5,
+            0.3,
+        );
+
+        let mut bodies = vec![body_a, body_b];
+        let mut contacts = vec![contact];
+
+        let error = solver.solve(&mut bodies, &mut contacts, 0.01).unwrap_err();
+        let PhysicsError::SolverDivergence(report) = error else {
+            panic!("expected divergence");
+        };
+        assert_eq!(report.bodies, vec![1]);
+        assert_eq!(report.constraints, vec![0]);
+        assert!(matches!(report.remediation[0], Remediation::ReviewInitialState { .. }));
     }
 }
@@ -4,19 +4,26 @@
 //! - Tire models (Pacejka Magic Formula)
 //! - Suspension systems (spring-damper)
 //! - Powertrain dynamics (engine, transmission, drivetrain)
+//! - Trailers (fifth-wheel coupling, jackknife detection)
+//! - Skid marks (tire mark tracing from slip)
 
 pub mod powertrain;
+pub mod skid_marks;
 pub mod suspension;
 pub mod tire_model;
+pub mod trailer;
 
 pub use powertrain::*;
+pub use skid_marks::*;
 pub use suspension::*;
 pub use tire_model::*;
+pub use trailer::*;
 
 use nalgebra::Vector3;
 use serde::{Deserialize, Serialize};
 
-use crate::rigid_body::RigidBody;
+use crate::error::{PhysicsError, PhysicsResult};
+use crate::rigid_body::{Joint, RigidBody};
 
 /// Complete vehicle dynamics model.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +51,14 @@ pub struct Vehicle {
 
     /// Center of gravity height (m).
     pub cg_height: f64,
+
+    /// Fifth wheel position relative to chassis center (tractors only).
+    #[serde(default)]
+    pub fifth_wheel: Option<Vector3<f64>>,
+
+    /// Hitched trailers, front to rear.
+    #[serde(default)]
+    pub trailers: Vec<Trailer>,
 }
 
 impl Vehicle {
@@ -64,9 +79,17 @@ impl Vehicle {
             wheelbase,
             track_width,
             cg_height,
+            fifth_wheel: None,
+            trailers: Vec::new(),
         }
     }
 
+    /// Adds a fifth wheel for hitching a semi-trailer.
+    pub fn with_fifth_wheel(mut self, position: Vector3<f64>) -> Self {
+        self.fifth_wheel = Some(position);
+        self
+    }
+
     /// Adds a wheel to the vehicle.
     pub fn add_wheel(&mut self, wheel: Wheel, suspension: SuspensionSystem) {
         self.wheels.push(wheel);
@@ -114,6 +137,124 @@ impl Vehicle {
         vehicle
     }
 
+    /// Creates a typical 6x4 tractor unit: a steer axle and a driven tandem
+    /// axle group, with the fifth wheel just ahead of the tandem.
+    pub fn create_tractor(id: usize, chassis_body_id: usize) -> Self {
+        let mut vehicle = Self::new(id, chassis_body_id, 3.9, 2.0, 1.1);
+        let front = vehicle.wheelbase / 2.0;
+        let rear = -vehicle.wheelbase / 2.0;
+        vehicle.fifth_wheel = Some(Vector3::new(rear + 0.3, 0.0, 0.2));
+
+        // Steer axle first: the wheel order drives steering and drivetrain
+        let tire_params = TireParameters::suv_truck();
+        let suspension_params = SuspensionParameters::suv_truck();
+        for x in [front, rear + 0.65, rear - 0.65] {
+            for side in [0.5, -0.5] {
+                let wheel = Wheel::new(
+                    Vector3::new(x, side * vehicle.track_width, 0.0),
+                    tire_params.clone(),
+                );
+                vehicle.add_wheel(wheel, SuspensionSystem::new(suspension_params.clone()));
+            }
+        }
+
+        vehicle.powertrain = Powertrain::suv();
+        vehicle.powertrain.drivetrain = DrivetrainType::RearWheelDrive;
+
+        vehicle
+    }
+
+    /// Body ID of the rearmost unit, to which the next trailer is hitched.
+    pub fn rear_unit(&self) -> usize {
+        self.trailers.last().map_or(self.chassis, |trailer| trailer.body)
+    }
+
+    /// Builds the fifth-wheel coupling between the rearmost unit and a
+    /// trailer.
+    ///
+    /// The trailer must be positioned with its kingpin over the fifth wheel.
+    /// The coupling is a hinge about the lead unit's vertical axis.
+    pub fn coupling_joint(
+        &self,
+        lead_body: &RigidBody,
+        trailer_body: &RigidBody,
+        trailer: &Trailer,
+    ) -> PhysicsResult<Joint> {
+        let fifth_wheel = match self.trailers.last() {
+            Some(lead) => lead.fifth_wheel,
+            None => self.fifth_wheel,
+        }
+        .ok_or_else(|| {
+            PhysicsError::VehicleDynamicsError(format!(
+                "body {} has no fifth wheel to hitch trailer {} to",
+                lead_body.id, trailer.id
+            ))
+        })?;
+
+        let anchor = lead_body.local_to_world(fifth_wheel);
+        let kingpin = trailer_body.local_to_world(trailer.kingpin);
+        let offset = (kingpin - anchor).norm();
+        if offset > KINGPIN_TOLERANCE {
+            return Err(PhysicsError::VehicleDynamicsError(format!(
+                "kingpin of trailer {} is {offset:.2} m from the fifth wheel",
+                trailer.id
+            )));
+        }
+
+        let axis = lead_body.orientation * Vector3::z();
+        let mut joint = Joint::hinge(self.rear_unit(), trailer.body, anchor, axis);
+        if let Some(force) = trailer.coupling_break_force {
+            joint = joint.with_break_force(force);
+        }
+
+        Ok(joint)
+    }
+
+    /// Adds a trailer behind the rearmost unit, returning its index.
+    ///
+    /// Use [`PhysicsWorld::hitch_trailer`](crate::PhysicsWorld::hitch_trailer)
+    /// to also create the coupling joint.
+    pub fn hitch(&mut self, trailer: Trailer) -> usize {
+        self.trailers.push(trailer);
+        self.trailers.len() - 1
+    }
+
+    /// Updates the trailer axles for one time step and checks each trailer
+    /// for a jackknife.
+    ///
+    /// `bodies` is indexed by body ID. Returns the most severe articulation
+    /// status across the trailers.
+    pub fn update_trailers(
+        &mut self,
+        bodies: &mut [RigidBody],
+        dt: f64,
+        brake: f64,
+    ) -> PhysicsResult<ArticulationStatus> {
+        let mut status = ArticulationStatus::Stable;
+        let mut lead = self.chassis;
+
+        for trailer in &mut self.trailers {
+            let lead_body = bodies.get(lead).cloned().ok_or_else(|| {
+                PhysicsError::VehicleDynamicsError(format!("unknown body {lead}"))
+            })?;
+            let trailer_body = bodies.get_mut(trailer.body).ok_or_else(|| {
+                PhysicsError::VehicleDynamicsError(format!("unknown body {}", trailer.body))
+            })?;
+
+            status = status.max(trailer.update(&lead_body, trailer_body, dt, brake));
+            lead = trailer.body;
+        }
+
+        Ok(status)
+    }
+
+    /// Whether any trailer has jackknifed.
+    pub fn is_jackknifed(&self) -> bool {
+        self.trailers
+            .iter()
+            .any(|trailer| trailer.jackknife.has_jackknifed())
+    }
+
     /// Updates vehicle dynamics for one time step.
     pub fn update(
         &mut self,
@@ -126,17 +267,19 @@ impl Vehicle {
         // Update powertrain
         let drive_torque = self.powertrain.update(dt, throttle, brake);
 
+        // Determine which wheels are driven; tractors drive more than two
+        let drivetrain = self.powertrain.drivetrain;
+        let is_driven = |i: usize| match drivetrain {
+            DrivetrainType::FrontWheelDrive => i < 2,
+            DrivetrainType::RearWheelDrive => i >= 2,
+            DrivetrainType::AllWheelDrive => true,
+        };
+        let num_driven = (0..self.wheels.len()).filter(|&i| is_driven(i)).count().max(1);
+
         // Update each wheel
         for (i, wheel) in self.wheels.iter_mut().enumerate() {
-            // Determine if this is a driven wheel
-            let is_driven = match self.powertrain.drivetrain {
-                DrivetrainType::FrontWheelDrive => i < 2,
-                DrivetrainType::RearWheelDrive => i >= 2,
-                DrivetrainType::AllWheelDrive => true,
-            };
-
-            let wheel_torque = if is_driven {
-                drive_torque / self.powertrain.num_driven_wheels() as f64
+            let wheel_torque = if is_driven(i) {
+                drive_torque / num_driven as f64
             } else {
                 0.0
             };
@@ -153,6 +296,18 @@ impl Vehicle {
     pub fn downforce(&self) -> f64 {
         self.wheels.iter().map(|w| w.normal_force).sum()
     }
+
+    /// Computes the static stability factor T / (2 h).
+    ///
+    /// The lateral acceleration (in g) at which a rigid vehicle on a flat
+    /// road starts to tip; lower values roll over more readily.
+    pub fn static_stability_factor(&self) -> f64 {
+        if self.cg_height > 1e-6 {
+            self.track_width / (2.0 * self.cg_height)
+        } else {
+            f64::INFINITY
+        }
+    }
 }
 
 /// Individual wheel.
@@ -276,6 +431,46 @@ mod tests {
         assert!(vehicle.wheelbase > 0.0);
     }
 
+    #[test]
+    fn test_tractor_creation() {
+        let tractor = Vehicle::create_tractor(0, 100);
+
+        assert_eq!(tractor.wheels.len(), 6);
+        assert_eq!(tractor.powertrain.drivetrain, DrivetrainType::RearWheelDrive);
+        assert!(tractor.fifth_wheel.is_some());
+        assert_eq!(tractor.rear_unit(), 100);
+    }
+
+    #[test]
+    fn test_coupling_joint_requires_kingpin_over_fifth_wheel() {
+        use crate::rigid_body::{JointKind, MassProperties};
+
+        let tractor = Vehicle::create_tractor(0, 0);
+        let trailer = Trailer::create_semi_trailer(0, 1);
+        let fifth_wheel = tractor.fifth_wheel.unwrap();
+
+        let mass_props = MassProperties::from_box(8000.0, Vector3::new(6.0, 2.5, 3.0));
+        let tractor_body = RigidBody::new(0, mass_props);
+        let mass_props = MassProperties::from_box(10000.0, Vector3::new(13.6, 2.5, 3.0));
+        let mut trailer_body = RigidBody::new(1, mass_props);
+        trailer_body.position = fifth_wheel - trailer.kingpin;
+
+        let joint = tractor
+            .coupling_joint(&tractor_body, &trailer_body, &trailer)
+            .unwrap();
+        assert_eq!(joint.kind, JointKind::Hinge);
+        assert_eq!((joint.body_a, joint.body_b), (0, 1));
+
+        trailer_body.position.x -= 1.0;
+        assert!(tractor
+            .coupling_joint(&tractor_body, &trailer_body, &trailer)
+            .is_err());
+
+        // A car has nothing to hitch to
+        let car = Vehicle::create_passenger_car(0, 0);
+        assert!(car.coupling_joint(&tractor_body, &trailer_body, &trailer).is_err());
+    }
+
     #[test]
     fn test_wheel_creation() {
         let tire = TireParameters::passenger_car();
@@ -0,0 +1,395 @@
+//! Skid mark generation.
+//!
+//! Traces the tire contact patches of a vehicle while a tire slides and
+//! turns them into polyline marks that can be laid over surveyed scene
+//! evidence. Marks are classified by the slip that produced them:
+//! - Skid marks: locked or nearly locked wheels under braking
+//! - Yaw marks: tires sliding sideways (critical speed scuffs)
+//! - Acceleration scuffs: wheelspin
+//!
+//! Intensity grows with how far the slip exceeds the threshold and with the
+//! load on the tire, so heavily loaded, fully locked wheels leave the
+//! darkest marks.
+
+use std::collections::BTreeMap;
+use std::f64::consts::FRAC_PI_4;
+
+use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
+
+use super::{SuspensionSystem, Vehicle, Wheel};
+
+/// Type of tire mark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SkidMarkKind {
+    /// Braking with (nearly) locked wheels.
+    Skid,
+
+    /// Sideways sliding.
+    Yaw,
+
+    /// Wheelspin.
+    Acceleration,
+}
+
+/// Slip thresholds above which a tire leaves a mark.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SkidMarkThresholds {
+    /// Slip ratio magnitude.
+    pub slip_ratio: f64,
+
+    /// Slip angle magnitude (rad).
+    pub slip_angle: f64,
+
+    /// Minimum tire load (N).
+    pub min_normal_force: f64,
+
+    /// Tire load at which marks reach full intensity (N).
+    pub nominal_normal_force: f64,
+
+    /// Minimum spacing between recorded points (m).
+    pub point_spacing: f64,
+}
+
+impl Default for SkidMarkThresholds {
+    fn default() -> Self {
+        Self {
+            slip_ratio: 0.2,
+            slip_angle: 0.1,
+            min_normal_force: 100.0,
+            nominal_normal_force: 4000.0,
+            point_spacing: 0.1,
+        }
+    }
+}
+
+/// Point on a tire mark.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SkidMarkPoint {
+    /// Contact patch position on the ground (m).
+    pub position: Vector3<f64>,
+
+    /// Simulation time (s).
+    pub time: f64,
+
+    /// Mark intensity (0 = faint, 1 = dark).
+    pub intensity: f64,
+}
+
+/// Tire mark left by one wheel.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkidMark {
+    /// Body ID of the unit (chassis or trailer) the wheel belongs to.
+    pub body: usize,
+
+    /// Wheel index on that unit.
+    pub wheel: usize,
+
+    /// Type of mark.
+    pub kind: SkidMarkKind,
+
+    /// Points along the mark, in time order.
+    pub points: Vec<SkidMarkPoint>,
+}
+
+impl SkidMark {
+    /// Length along the mark (m).
+    pub fn length(&self) -> f64 {
+        self.points
+            .windows(2)
+            .map(|pair| (pair[1].position - pair[0].position).norm())
+            .sum()
+    }
+
+    /// Mean intensity of the points.
+    pub fn mean_intensity(&self) -> f64 {
+        if self.points.is_empty() {
+            return 0.0;
+        }
+        self.points.iter().map(|point| point.intensity).sum::<f64>() / self.points.len() as f64
+    }
+
+    /// Mark as a ground-plane polyline of (x, y) points.
+    pub fn polyline(&self) -> Vec<[f64; 2]> {
+        self.points.iter().map(|point| [point.position.x, point.position.y]).collect()
+    }
+
+    /// Compares the mark with a surveyed mark, given as a ground-plane
+    /// polyline.
+    ///
+    /// Returns `None` if either mark has fewer than two points.
+    pub fn compare(&self, evidence: &[[f64; 2]]) -> Option<SkidMarkComparison> {
+        let simulated = self.polyline();
+        if simulated.len() < 2 || evidence.len() < 2 {
+            return None;
+        }
+
+        let deviations: Vec<f64> =
+            simulated.iter().map(|point| distance_to_polyline(*point, evidence)).collect();
+        let evidence_length: f64 = evidence
+            .windows(2)
+            .map(|pair| (pair[1][0] - pair[0][0]).hypot(pair[1][1] - pair[0][1]))
+            .sum();
+
+        Some(SkidMarkComparison {
+            mean_deviation: deviations.iter().sum::<f64>() / deviations.len() as f64,
+            max_deviation: deviations.iter().copied().fold(0.0, f64::max),
+            length_difference: self.length() - evidence_length,
+        })
+    }
+}
+
+/// Agreement between a simulated mark and a surveyed mark.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SkidMarkComparison {
+    /// Mean distance from the simulated points to the surveyed mark (m).
+    pub mean_deviation: f64,
+
+    /// Largest distance from a simulated point to the surveyed mark (m).
+    pub max_deviation: f64,
+
+    /// Simulated length minus surveyed length (m).
+    pub length_difference: f64,
+}
+
+/// Records tire marks from a vehicle's wheels during a simulation.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SkidMarkTracer {
+    /// Slip thresholds.
+    pub thresholds: SkidMarkThresholds,
+
+    /// Recorded marks, in the order they started.
+    marks: Vec<SkidMark>,
+
+    /// Mark each (body, wheel) is currently laying down.
+    #[serde(skip)]
+    active: BTreeMap<(usize, usize), usize>,
+}
+
+impl SkidMarkTracer {
+    /// Creates a new tracer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the slip thresholds.
+    pub fn with_thresholds(mut self, thresholds: SkidMarkThresholds) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    /// Records the contact patches of a vehicle and its trailers after a
+    /// wheel update at simulation time `time`.
+    pub fn record(&mut self, vehicle: &Vehicle, time: f64) {
+        self.record_wheels(vehicle.chassis, &vehicle.wheels, &vehicle.suspensions, time);
+        for trailer in &vehicle.trailers {
+            self.record_wheels(trailer.body, &trailer.wheels, &trailer.suspensions, time);
+        }
+    }
+
+    /// Records the contact patches of one unit's wheels.
+    pub fn record_wheels(
+        &mut self,
+        body: usize,
+        wheels: &[Wheel],
+        suspensions: &[SuspensionSystem],
+        time: f64,
+    ) {
+        for (index, (wheel, suspension)) in wheels.iter().zip(suspensions).enumerate() {
+            let key = (body, index);
+            let Some((kind, intensity)) = self.classify(wheel, suspension) else {
+                self.active.remove(&key);
+                continue;
+            };
+
+            let point = SkidMarkPoint {
+                position: suspension.contact_point,
+                time,
+                intensity,
+            };
+
+            let active =
+                self.active.get(&key).copied().filter(|&mark| self.marks[mark].kind == kind);
+            match active {
+                Some(mark) => {
+                    let points = &mut self.marks[mark].points;
+                    let spaced = points.last().is_none_or(|last| {
+                        (point.position - last.position).norm() >= self.thresholds.point_spacing
+                    });
+                    if spaced {
+                        points.push(point);
+                    }
+                },
+                None => {
+                    self.active.insert(key, self.marks.len());
+                    self.marks.push(SkidMark {
+                        body,
+                        wheel: index,
+                        kind,
+                        points: vec![point],
+                    });
+                },
+            }
+        }
+    }
+
+    /// Type and intensity of the mark a wheel is leaving, if any.
+    fn classify(
+        &self,
+        wheel: &Wheel,
+        suspension: &SuspensionSystem,
+    ) -> Option<(SkidMarkKind, f64)> {
+        let thresholds = &self.thresholds;
+        if !suspension.is_grounded || wheel.normal_force < thresholds.min_normal_force {
+            return None;
+        }
+
+        // Excess slip, scaled to 1 at full lock (slip ratio) or 45° (slip angle)
+        let longitudinal = (wheel.slip_ratio.abs() - thresholds.slip_ratio)
+            / (1.0 - thresholds.slip_ratio).max(1e-6);
+        let lateral = (wheel.slip_angle.abs() - thresholds.slip_angle)
+            / (FRAC_PI_4 - thresholds.slip_angle).max(1e-6);
+        if longitudinal <= 0.0 && lateral <= 0.0 {
+            return None;
+        }
+
+        let kind = if lateral > longitudinal {
+            SkidMarkKind::Yaw
+        } else if wheel.slip_ratio < 0.0 {
+            SkidMarkKind::Skid
+        } else {
+            SkidMarkKind::Acceleration
+        };
+
+        let load = (wheel.normal_force / thresholds.nominal_normal_force).min(1.0);
+        let intensity = (longitudinal.max(lateral).min(1.0) * load).clamp(0.0, 1.0);
+        Some((kind, intensity))
+    }
+
+    /// Recorded marks, in the order they started.
+    pub fn marks(&self) -> &[SkidMark] {
+        &self.marks
+    }
+
+    /// Total length of all marks (m).
+    pub fn total_length(&self) -> f64 {
+        self.marks.iter().map(SkidMark::length).sum()
+    }
+}
+
+/// Distance from a point to a polyline.
+fn distance_to_polyline(point: [f64; 2], polyline: &[[f64; 2]]) -> f64 {
+    polyline
+        .windows(2)
+        .map(|segment| {
+            let (a, b) = (segment[0], segment[1]);
+            let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+            let length_squared = dx * dx + dy * dy;
+            let t = if length_squared > 0.0 {
+                (((point[0] - a[0]) * dx + (point[1] - a[1]) * dy) / length_squared).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            (point[0] - (a[0] + t * dx)).hypot(point[1] - (a[1] + t * dy))
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    /// Sets every wheel of the car sliding at `x` with the given slip.
+    fn slide(vehicle: &mut Vehicle, x: f64, slip_ratio: f64, slip_angle: f64) {
+        for (wheel, suspension) in vehicle.wheels.iter_mut().zip(&mut vehicle.suspensions) {
+            wheel.slip_ratio = slip_ratio;
+            wheel.slip_angle = slip_angle;
+            wheel.normal_force = 4000.0;
+            suspension.is_grounded = true;
+            suspension.contact_point = Vector3::new(x + wheel.position.x, wheel.position.y, 0.0);
+        }
+    }
+
+    #[test]
+    fn test_locked_wheels_leave_skid_marks() {
+        let mut vehicle = Vehicle::create_passenger_car(0, 7);
+        let mut tracer = SkidMarkTracer::new();
+
+        // Rolling: no marks
+        slide(&mut vehicle, 0.0, -0.05, 0.0);
+        tracer.record(&vehicle, 0.0);
+        assert!(tracer.marks().is_empty());
+
+        // Locked for 10 m
+        for step in 0..=20 {
+            slide(&mut vehicle, step as f64 * 0.5, -1.0, 0.0);
+            tracer.record(&vehicle, 0.1 + step as f64 * 0.05);
+        }
+
+        assert_eq!(tracer.marks().len(), 4);
+        let mark = &tracer.marks()[0];
+        assert_eq!(
+            (mark.body, mark.wheel, mark.kind),
+            (7, 0, SkidMarkKind::Skid)
+        );
+        assert_relative_eq!(mark.length(), 10.0, epsilon = 1e-9);
+        assert_relative_eq!(mark.mean_intensity(), 1.0);
+        assert_relative_eq!(tracer.total_length(), 40.0, epsilon = 1e-9);
+
+        // Releasing the brakes ends the marks; locking again starts new ones
+        slide(&mut vehicle, 11.0, 0.0, 0.0);
+        tracer.record(&vehicle, 1.2);
+        slide(&mut vehicle, 12.0, -1.0, 0.0);
+        tracer.record(&vehicle, 1.3);
+        assert_eq!(tracer.marks().len(), 8);
+    }
+
+    #[test]
+    fn test_mark_kind_and_intensity() {
+        let mut vehicle = Vehicle::create_passenger_car(0, 0);
+        let mut tracer = SkidMarkTracer::new();
+
+        slide(&mut vehicle, 0.0, 0.05, 0.4);
+        tracer.record(&vehicle, 0.0);
+        slide(&mut vehicle, 1.0, 0.6, 0.0);
+        tracer.record(&vehicle, 0.1);
+
+        let marks = tracer.marks();
+        assert_eq!(marks.len(), 8);
+        assert_eq!(marks[0].kind, SkidMarkKind::Yaw);
+        assert_eq!(marks[4].kind, SkidMarkKind::Acceleration);
+        assert_relative_eq!(marks[4].points[0].intensity, 0.5, epsilon = 1e-9);
+
+        // Light load: fainter marks
+        slide(&mut vehicle, 2.0, 0.6, 0.0);
+        vehicle.wheels[0].normal_force = 1000.0;
+        tracer.record(&vehicle, 0.2);
+        let points = &tracer.marks()[4].points;
+        assert_eq!(points.len(), 2);
+        assert_relative_eq!(points[1].intensity, 0.125, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_compare_with_evidence() {
+        let mark = SkidMark {
+            body: 0,
+            wheel: 0,
+            kind: SkidMarkKind::Skid,
+            points: (0..=10)
+                .map(|i| SkidMarkPoint {
+                    position: Vector3::new(i as f64, 0.2, 0.0),
+                    time: i as f64 * 0.1,
+                    intensity: 1.0,
+                })
+                .collect(),
+        };
+
+        let comparison = mark.compare(&[[0.0, 0.0], [12.0, 0.0]]).unwrap();
+
+        assert_relative_eq!(comparison.mean_deviation, 0.2, epsilon = 1e-9);
+        assert_relative_eq!(comparison.max_deviation, 0.2, epsilon = 1e-9);
+        assert_relative_eq!(comparison.length_difference, -2.0, epsilon = 1e-9);
+        assert!(mark.compare(&[[0.0, 0.0]]).is_none());
+    }
+}
@@ -0,0 +1,396 @@
+//! Trailers and articulated vehicles.
+//!
+//! A trailer is a separate rigid body with its own axles, coupled to the
+//! unit ahead of it (the tractor or, for doubles, the leading trailer) by a
+//! fifth wheel. The coupling is a hinge [`Joint`](crate::rigid_body::Joint)
+//! about the lead unit's vertical axis at the kingpin, so the trailer can
+//! articulate (yaw) freely but not pitch or roll relative to the lead unit.
+//!
+//! Jackknifing is detected from the articulation angle between the two
+//! units:
+//!
+//! ```text
+//! ψ = atan2(x_t · y_l, x_t · x_l)
+//! ```
+//!
+//! Where x_t is the trailer's heading and x_l, y_l the lead unit's forward
+//! and left axes. A positive angle means the trailer has swung to the lead
+//! unit's left.
+
+use std::f64::consts::PI;
+
+use nalgebra::Vector3;
+use serde::{Deserialize, Serialize};
+
+use super::{SuspensionParameters, SuspensionSystem, TireParameters, Wheel};
+use crate::rigid_body::RigidBody;
+
+/// Default articulation angle above which a growing angle is flagged (rad).
+pub const DEFAULT_WARNING_ANGLE: f64 = PI / 9.0;
+
+/// Default articulation angle treated as a jackknife (rad).
+pub const DEFAULT_JACKKNIFE_ANGLE: f64 = PI / 4.0;
+
+/// Largest distance between the kingpin and the fifth wheel accepted when
+/// hitching (m).
+pub const KINGPIN_TOLERANCE: f64 = 0.25;
+
+/// Articulation state of a trailer relative to the unit ahead of it.
+///
+/// Ordered by severity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ArticulationStatus {
+    /// Trailer tracks the lead unit.
+    #[default]
+    Stable,
+
+    /// Articulation angle is large and still growing.
+    Diverging,
+
+    /// Articulation angle is past the jackknife threshold.
+    Jackknifed,
+}
+
+/// Thresholds for jackknife detection.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct JackknifeThresholds {
+    /// Articulation angle above which a growing angle is flagged (rad).
+    pub warning_angle: f64,
+
+    /// Articulation angle treated as a jackknife (rad).
+    pub jackknife_angle: f64,
+
+    /// Articulation rate above which a growing angle is flagged (rad/s).
+    pub divergence_rate: f64,
+
+    /// Lead unit speed below which articulation is treated as manoeuvring
+    /// rather than instability (m/s).
+    pub min_speed: f64,
+}
+
+impl Default for JackknifeThresholds {
+    fn default() -> Self {
+        Self {
+            warning_angle: DEFAULT_WARNING_ANGLE,
+            jackknife_angle: DEFAULT_JACKKNIFE_ANGLE,
+            divergence_rate: 0.2,
+            min_speed: 3.0,
+        }
+    }
+}
+
+/// Tracks the articulation angle of one trailer and flags jackknifing.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JackknifeMonitor {
+    /// Detection thresholds.
+    pub thresholds: JackknifeThresholds,
+
+    /// Articulation angle at the last update (rad).
+    pub angle: f64,
+
+    /// Articulation rate at the last update (rad/s).
+    pub rate: f64,
+
+    /// Largest articulation angle magnitude seen (rad).
+    pub peak_angle: f64,
+
+    /// Status at the last update.
+    pub status: ArticulationStatus,
+
+    /// Time since monitoring began (s).
+    pub elapsed: f64,
+
+    /// Time at which the trailer first jackknifed, since monitoring began (s).
+    pub onset_time: Option<f64>,
+}
+
+impl JackknifeMonitor {
+    /// Creates a monitor with the given thresholds.
+    pub fn new(thresholds: JackknifeThresholds) -> Self {
+        Self {
+            thresholds,
+            ..Self::default()
+        }
+    }
+
+    /// Records the articulation angle after a step of `dt` seconds.
+    ///
+    /// `speed` is the lead unit's ground speed (m/s).
+    pub fn update(&mut self, angle: f64, speed: f64, dt: f64) -> ArticulationStatus {
+        self.rate = if self.elapsed > 0.0 && dt > 0.0 {
+            wrap_angle(angle - self.angle) / dt
+        } else {
+            0.0
+        };
+        self.angle = angle;
+        self.elapsed += dt;
+
+        let magnitude = angle.abs();
+        self.peak_angle = self.peak_angle.max(magnitude);
+
+        let growing = angle * self.rate > 0.0 && self.rate.abs() >= self.thresholds.divergence_rate;
+        self.status = if speed < self.thresholds.min_speed {
+            ArticulationStatus::Stable
+        } else if magnitude >= self.thresholds.jackknife_angle {
+            ArticulationStatus::Jackknifed
+        } else if magnitude >= self.thresholds.warning_angle && growing {
+            ArticulationStatus::Diverging
+        } else {
+            ArticulationStatus::Stable
+        };
+
+        if self.status == ArticulationStatus::Jackknifed && self.onset_time.is_none() {
+            self.onset_time = Some(self.elapsed);
+        }
+
+        self.status
+    }
+
+    /// Whether the trailer has jackknifed at any point.
+    pub fn has_jackknifed(&self) -> bool {
+        self.onset_time.is_some()
+    }
+}
+
+/// Trailer coupled to a tractor or another trailer by a fifth wheel.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trailer {
+    /// Unique identifier.
+    pub id: usize,
+
+    /// Trailer rigid body.
+    pub body: usize, // Body ID
+
+    /// Kingpin position relative to the trailer body center.
+    pub kingpin: Vector3<f64>,
+
+    /// Fifth wheel position relative to the trailer body center, for
+    /// hitching a further trailer (doubles).
+    pub fifth_wheel: Option<Vector3<f64>>,
+
+    /// Wheels (tire models).
+    pub wheels: Vec<Wheel>,
+
+    /// Suspension systems.
+    pub suspensions: Vec<SuspensionSystem>,
+
+    /// Brake torque per wheel at full brake application (N·m).
+    pub max_brake_torque: f64,
+
+    /// Reaction force at which the coupling breaks (N).
+    pub coupling_break_force: Option<f64>,
+
+    /// Coupling joint ID, once hitched.
+    pub coupling: Option<usize>,
+
+    /// Jackknife detection.
+    pub jackknife: JackknifeMonitor,
+}
+
+impl Trailer {
+    /// Creates a trailer without axles.
+    pub fn new(id: usize, body_id: usize, kingpin: Vector3<f64>) -> Self {
+        Self {
+            id,
+            body: body_id,
+            kingpin,
+            fifth_wheel: None,
+            wheels: Vec::new(),
+            suspensions: Vec::new(),
+            max_brake_torque: 15000.0,
+            coupling_break_force: None,
+            coupling: None,
+            jackknife: JackknifeMonitor::default(),
+        }
+    }
+
+    /// Creates a typical 13.6 m semi-trailer with a tandem axle group.
+    pub fn create_semi_trailer(id: usize, body_id: usize) -> Self {
+        let mut trailer = Self::new(id, body_id, Vector3::new(5.6, 0.0, -0.6));
+
+        let tire = TireParameters::suv_truck();
+        let suspension = SuspensionParameters::suv_truck();
+        trailer.add_axle(-4.0, 2.0, tire.clone(), suspension.clone());
+        trailer.add_axle(-5.3, 2.0, tire, suspension);
+
+        trailer
+    }
+
+    /// Adds a fifth wheel for hitching a further trailer.
+    pub fn with_fifth_wheel(mut self, position: Vector3<f64>) -> Self {
+        self.fifth_wheel = Some(position);
+        self
+    }
+
+    /// Makes the coupling break when its reaction force exceeds `force` (N).
+    pub fn with_coupling_break_force(mut self, force: f64) -> Self {
+        self.coupling_break_force = Some(force);
+        self
+    }
+
+    /// Sets the jackknife detection thresholds.
+    pub fn with_jackknife_thresholds(mut self, thresholds: JackknifeThresholds) -> Self {
+        self.jackknife = JackknifeMonitor::new(thresholds);
+        self
+    }
+
+    /// Adds an axle with a wheel on each side, `x` metres ahead of the body
+    /// center (negative behind it).
+    pub fn add_axle(
+        &mut self,
+        x: f64,
+        track_width: f64,
+        tire: TireParameters,
+        suspension: SuspensionParameters,
+    ) {
+        for side in [0.5, -0.5] {
+            self.wheels.push(Wheel::new(
+                Vector3::new(x, side * track_width, 0.0),
+                tire.clone(),
+            ));
+            self.suspensions.push(SuspensionSystem::new(suspension.clone()));
+        }
+    }
+
+    /// Number of axles.
+    pub fn axle_count(&self) -> usize {
+        self.wheels.len() / 2
+    }
+
+    /// Updates the trailer axles for one time step and checks for a
+    /// jackknife.
+    ///
+    /// `brake` is the brake application (0-1) relayed from the tractor.
+    pub fn update(
+        &mut self,
+        lead_body: &RigidBody,
+        trailer_body: &mut RigidBody,
+        dt: f64,
+        brake: f64,
+    ) -> ArticulationStatus {
+        let brake_torque = brake.clamp(0.0, 1.0) * self.max_brake_torque;
+        for (wheel, suspension) in self.wheels.iter_mut().zip(&mut self.suspensions) {
+            wheel.update(trailer_body, suspension, dt, 0.0, 0.0, brake_torque);
+        }
+
+        let angle = articulation_angle(lead_body, trailer_body);
+        let speed = lead_body.linear_velocity.xy().norm();
+        self.jackknife.update(angle, speed, dt)
+    }
+
+    /// Computes total downforce on the trailer axles.
+    pub fn downforce(&self) -> f64 {
+        self.wheels.iter().map(|w| w.normal_force).sum()
+    }
+}
+
+/// Articulation angle of a trailer relative to the unit ahead of it (rad).
+///
+/// Measured about the lead unit's vertical axis, in (-π, π]; positive when
+/// the trailer has swung to the lead unit's left.
+pub fn articulation_angle(lead_body: &RigidBody, trailer_body: &RigidBody) -> f64 {
+    let heading = lead_body.orientation.inverse() * (trailer_body.orientation * Vector3::x());
+    heading.y.atan2(heading.x)
+}
+
+/// Wraps an angle difference into (-π, π].
+fn wrap_angle(angle: f64) -> f64 {
+    let wrapped = (angle + PI).rem_euclid(2.0 * PI) - PI;
+    if wrapped == -PI {
+        PI
+    } else {
+        wrapped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rigid_body::MassProperties;
+    use approx::assert_relative_eq;
+    use nalgebra::UnitQuaternion;
+
+    fn body_with_yaw(id: usize, yaw: f64) -> RigidBody {
+        let mass_props = MassProperties::from_box(10000.0, Vector3::new(13.6, 2.5, 3.0));
+        let mut body = RigidBody::new(id, mass_props);
+        body.orientation = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), yaw);
+        body
+    }
+
+    #[test]
+    fn test_semi_trailer_creation() {
+        let trailer = Trailer::create_semi_trailer(0, 1);
+
+        assert_eq!(trailer.axle_count(), 2);
+        assert_eq!(trailer.wheels.len(), trailer.suspensions.len());
+        assert!(trailer.kingpin.x > 0.0);
+        assert!(trailer.coupling.is_none());
+    }
+
+    #[test]
+    fn test_articulation_angle() {
+        let tractor = body_with_yaw(0, 0.3);
+        let trailer = body_with_yaw(1, 0.8);
+        assert_relative_eq!(articulation_angle(&tractor, &trailer), 0.5, epsilon = 1e-9);
+        assert_relative_eq!(articulation_angle(&trailer, &tractor), -0.5, epsilon = 1e-9);
+
+        // Wraps across ±π
+        let tractor = body_with_yaw(0, 3.0);
+        let trailer = body_with_yaw(1, -3.0);
+        assert_relative_eq!(
+            articulation_angle(&tractor, &trailer),
+            2.0 * PI - 6.0,
+            epsilon = 1e-9
+        );
+    }
+
+    #[test]
+    fn test_jackknife_monitor() {
+        let mut monitor = JackknifeMonitor::default();
+        let dt = 0.1;
+
+        assert_eq!(monitor.update(0.05, 20.0, dt), ArticulationStatus::Stable);
+        assert_eq!(monitor.update(0.4, 20.0, dt), ArticulationStatus::Diverging);
+
+        // Large but steady angle in a turn is not flagged
+        assert_eq!(monitor.update(0.4, 20.0, dt), ArticulationStatus::Stable);
+
+        assert_eq!(monitor.update(0.6, 20.0, dt), ArticulationStatus::Diverging);
+        assert!(!monitor.has_jackknifed());
+
+        assert_eq!(
+            monitor.update(0.9, 20.0, dt),
+            ArticulationStatus::Jackknifed
+        );
+        assert_relative_eq!(monitor.onset_time.unwrap(), 0.5, epsilon = 1e-9);
+        assert_relative_eq!(monitor.peak_angle, 0.9);
+
+        // Onset time is kept after the trailer comes back into line
+        assert_eq!(monitor.update(0.1, 20.0, dt), ArticulationStatus::Stable);
+        assert_relative_eq!(monitor.onset_time.unwrap(), 0.5, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_low_speed_articulation_is_manoeuvring() {
+        let mut monitor = JackknifeMonitor::default();
+
+        monitor.update(0.0, 1.0, 0.1);
+        assert_eq!(monitor.update(1.5, 1.0, 0.1), ArticulationStatus::Stable);
+        assert!(!monitor.has_jackknifed());
+        assert_relative_eq!(monitor.rate, 15.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_trailer_update() {
+        let tractor = body_with_yaw(0, 0.0);
+        let mut trailer_body = body_with_yaw(1, -0.2);
+        trailer_body.position = Vector3::new(-7.0, 0.0, 0.55);
+        let mut trailer = Trailer::create_semi_trailer(0, 1);
+
+        let status = trailer.update(&tractor, &mut trailer_body, 0.01, 1.0);
+
+        assert_eq!(status, ArticulationStatus::Stable);
+        assert!(trailer.downforce() > 0.0);
+        assert_relative_eq!(trailer.jackknife.angle, -0.2, epsilon = 1e-9);
+    }
+}
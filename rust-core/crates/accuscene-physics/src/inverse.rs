@@ -0,0 +1,552 @@
+//! Time-reversed (inverse) simulation from rest positions.
+//!
+//! Works backwards from where the vehicles came to rest:
+//! 1. Each post-impact runout is run in reverse, accelerating the vehicle
+//!    from rest back to the impact point at the friction deceleration, which
+//!    recovers its separation velocity and yaw rate.
+//! 2. Pre-impact velocities are searched along each vehicle's approach
+//!    heading, scoring every candidate by how well it conserves momentum and
+//!    how plausibly it dissipates energy.
+//!
+//! Candidates are returned best first with their residual errors, so the
+//! spread of near-optimal solutions shows how well the evidence constrains
+//! the answer.
+
+use crate::friction::SurfaceType;
+use crate::kinematics::{Trajectory, TrajectoryPoint};
+use nalgebra::{Point3, Vector3};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Gravitational acceleration (m/s²)
+const GRAVITY: f64 = 9.81;
+
+/// Default radius of gyration about the yaw axis (m)
+const DEFAULT_YAW_RADIUS: f64 = 1.2;
+
+/// Evidence for one vehicle: where it was struck and where it came to rest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestEvidence {
+    /// Vehicle mass (kg)
+    pub mass: f64,
+    /// Yaw moment of inertia (kg·m²)
+    pub yaw_inertia: f64,
+    /// Center of mass position at impact
+    pub impact_position: Point3<f64>,
+    /// Heading at impact (rad)
+    pub impact_heading: f64,
+    /// Center of mass position at rest
+    pub rest_position: Point3<f64>,
+    /// Heading at rest (rad)
+    pub rest_heading: f64,
+    /// Tire-road friction (drag factor) during the runout
+    pub friction: f64,
+    /// Direction of travel before impact (rad)
+    pub approach_heading: f64,
+}
+
+impl RestEvidence {
+    /// Creates evidence for a vehicle that slid from `impact_position` to
+    /// `rest_position` without rotating, on dry asphalt.
+    pub fn new(mass: f64, impact_position: Point3<f64>, rest_position: Point3<f64>) -> Self {
+        Self {
+            mass,
+            yaw_inertia: mass * DEFAULT_YAW_RADIUS * DEFAULT_YAW_RADIUS,
+            impact_position,
+            impact_heading: 0.0,
+            rest_position,
+            rest_heading: 0.0,
+            friction: SurfaceType::AsphaltDry.kinetic_friction(),
+            approach_heading: 0.0,
+        }
+    }
+
+    /// Sets the headings at impact and at rest.
+    pub fn with_headings(mut self, impact_heading: f64, rest_heading: f64) -> Self {
+        self.impact_heading = impact_heading;
+        self.rest_heading = rest_heading;
+        self
+    }
+
+    /// Sets the direction of travel before impact.
+    pub fn with_approach_heading(mut self, heading: f64) -> Self {
+        self.approach_heading = heading;
+        self
+    }
+
+    /// Sets the yaw moment of inertia.
+    pub fn with_yaw_inertia(mut self, yaw_inertia: f64) -> Self {
+        self.yaw_inertia = yaw_inertia;
+        self
+    }
+
+    /// Sets the runout friction.
+    pub fn with_friction(mut self, friction: f64) -> Self {
+        self.friction = friction;
+        self
+    }
+
+    /// Sets the runout friction from the surface type.
+    pub fn with_surface(mut self, surface: SurfaceType) -> Self {
+        self.friction = surface.kinetic_friction();
+        self
+    }
+
+    /// Horizontal runout distance from impact to rest (m).
+    pub fn runout_distance(&self) -> f64 {
+        (self.rest_position - self.impact_position).xy().norm()
+    }
+
+    /// Velocity leaving the impact, from the runout distance.
+    ///
+    /// Uses v = sqrt(2 * μ * g * d) along the line from impact to rest.
+    pub fn separation_velocity(&self) -> Vector3<f64> {
+        let displacement = self.rest_position - self.impact_position;
+        let horizontal = Vector3::new(displacement.x, displacement.y, 0.0);
+        let distance = horizontal.norm();
+        if distance < 1e-9 {
+            return Vector3::zeros();
+        }
+
+        let speed = (2.0 * self.friction * GRAVITY * distance).sqrt();
+        horizontal / distance * speed
+    }
+
+    /// Yaw rate leaving the impact, from the rotation to rest (rad/s).
+    ///
+    /// Friction acting at the radius of gyration k = sqrt(I / m) does the
+    /// rotational work, giving ω = sqrt(2 * μ * g * |Δψ| / k).
+    pub fn separation_yaw_rate(&self) -> f64 {
+        let rotation = wrap_angle(self.rest_heading - self.impact_heading);
+        if rotation.abs() < 1e-9 || self.mass <= 0.0 || self.yaw_inertia <= 0.0 {
+            return 0.0;
+        }
+
+        let radius = (self.yaw_inertia / self.mass).sqrt();
+        rotation.signum() * (2.0 * self.friction * GRAVITY * rotation.abs() / radius).sqrt()
+    }
+
+    /// Kinetic energy leaving the impact, translational and rotational (J).
+    pub fn separation_energy(&self) -> f64 {
+        let yaw_rate = self.separation_yaw_rate();
+        0.5 * self.mass * self.separation_velocity().norm_squared()
+            + 0.5 * self.yaw_inertia * yaw_rate * yaw_rate
+    }
+
+    /// Runs the post-impact runout backwards from rest to impact.
+    ///
+    /// The returned trajectory is in forward time, starting at impact
+    /// (t = 0) with the separation velocity and ending at rest.
+    pub fn reverse_runout(&self, time_step: f64) -> Trajectory {
+        let mut trajectory = Trajectory::new();
+        let velocity = self.separation_velocity();
+        let speed = velocity.norm();
+        let deceleration = self.friction * GRAVITY;
+        if speed < 1e-9 || deceleration <= 0.0 || time_step <= 0.0 {
+            trajectory.add_point(TrajectoryPoint {
+                time: 0.0,
+                position: self.rest_position,
+                velocity: Vector3::zeros(),
+                acceleration: Vector3::zeros(),
+            });
+            return trajectory;
+        }
+
+        let direction = velocity / speed;
+        let duration = speed / deceleration;
+        let steps = (duration / time_step).ceil() as usize;
+
+        // Reversed time τ runs from rest; the vehicle speeds up backwards
+        let mut points: Vec<TrajectoryPoint> = (0..=steps)
+            .map(|step| {
+                let tau = (step as f64 * time_step).min(duration);
+                let distance = 0.5 * deceleration * tau * tau;
+                TrajectoryPoint {
+                    time: duration - tau,
+                    position: self.rest_position - direction * distance,
+                    velocity: direction * deceleration * tau,
+                    acceleration: -direction * deceleration,
+                }
+            })
+            .collect();
+        points.reverse();
+
+        for point in points {
+            trajectory.add_point(point);
+        }
+        trajectory
+    }
+}
+
+/// Search settings for the inverse solver.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InverseSolverConfig {
+    /// Highest pre-impact speed searched (m/s)
+    pub max_speed: f64,
+    /// Speed grid spacing (m/s)
+    pub speed_step: f64,
+    /// Approach headings are searched within ± this tolerance (rad)
+    pub heading_tolerance: f64,
+    /// Heading grid spacing (rad)
+    pub heading_step: f64,
+    /// Energy absorbed by crush, if known (J)
+    pub crush_energy: Option<f64>,
+    /// Number of candidates returned
+    pub max_candidates: usize,
+    /// Speed below which two refined candidates are considered the same (m/s)
+    pub merge_tolerance: f64,
+}
+
+impl Default for InverseSolverConfig {
+    fn default() -> Self {
+        Self {
+            max_speed: 60.0,
+            speed_step: 0.5,
+            heading_tolerance: 0.0,
+            heading_step: 2.0_f64.to_radians(),
+            crush_energy: None,
+            max_candidates: 5,
+            merge_tolerance: 0.1,
+        }
+    }
+}
+
+/// Pre-impact velocities consistent with the rest evidence.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InverseCandidate {
+    /// Pre-impact velocities of vehicles A and B (m/s)
+    pub pre_impact_velocities: [Vector3<f64>; 2],
+    /// Pre-impact speeds of vehicles A and B (m/s)
+    pub pre_impact_speeds: [f64; 2],
+    /// Momentum after impact minus momentum before (N·s)
+    pub momentum_residual: Vector3<f64>,
+    /// Momentum residual relative to the post-impact momentum
+    pub momentum_error: f64,
+    /// Kinetic energy lost in the impact (J)
+    pub dissipated_energy: f64,
+    /// Energy imbalance relative to the pre-impact kinetic energy
+    pub energy_error: f64,
+    /// Combined residual used for ranking (lower is better)
+    pub residual: f64,
+}
+
+/// Result of an inverse simulation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InverseSolution {
+    /// Separation velocities of vehicles A and B (m/s)
+    pub separation_velocities: [Vector3<f64>; 2],
+    /// Separation yaw rates of vehicles A and B (rad/s)
+    pub separation_yaw_rates: [f64; 2],
+    /// Candidates, best first
+    pub candidates: Vec<InverseCandidate>,
+}
+
+impl InverseSolution {
+    /// Best candidate, if any.
+    pub fn best(&self) -> Option<&InverseCandidate> {
+        self.candidates.first()
+    }
+}
+
+/// Searches for pre-impact velocities from rest positions.
+#[derive(Debug, Clone, Default)]
+pub struct InverseSolver {
+    /// Search settings
+    pub config: InverseSolverConfig,
+}
+
+impl InverseSolver {
+    /// Creates a solver with the given settings.
+    pub fn new(config: InverseSolverConfig) -> Self {
+        Self { config }
+    }
+
+    /// Solves for the pre-impact velocities of two colliding vehicles.
+    ///
+    /// Searches a grid of approach speeds (and headings, within the
+    /// tolerance) in parallel, then refines the best grid points locally.
+    pub fn solve(&self, vehicle_a: &RestEvidence, vehicle_b: &RestEvidence) -> InverseSolution {
+        let separation_velocities = [
+            vehicle_a.separation_velocity(),
+            vehicle_b.separation_velocity(),
+        ];
+        let separation_yaw_rates = [
+            vehicle_a.separation_yaw_rate(),
+            vehicle_b.separation_yaw_rate(),
+        ];
+        let mut solution = InverseSolution {
+            separation_velocities,
+            separation_yaw_rates,
+            candidates: Vec::new(),
+        };
+
+        let config = &self.config;
+        if vehicle_a.mass <= 0.0
+            || vehicle_b.mass <= 0.0
+            || config.speed_step <= 0.0
+            || config.max_speed <= 0.0
+        {
+            return solution;
+        }
+
+        let problem = Problem {
+            masses: [vehicle_a.mass, vehicle_b.mass],
+            momentum_after: vehicle_a.mass * separation_velocities[0]
+                + vehicle_b.mass * separation_velocities[1],
+            energy_after: vehicle_a.separation_energy() + vehicle_b.separation_energy(),
+            crush_energy: config.crush_energy,
+        };
+
+        let speeds = grid(0.0, config.max_speed, config.speed_step);
+        let headings_a = heading_grid(vehicle_a.approach_heading, config);
+        let headings_b = heading_grid(vehicle_b.approach_heading, config);
+
+        let mut grid_candidates: Vec<InverseCandidate> = headings_a
+            .iter()
+            .flat_map(|&heading_a| headings_b.iter().map(move |&heading_b| (heading_a, heading_b)))
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .flat_map_iter(|(heading_a, heading_b)| {
+                let speeds = &speeds;
+                let problem = &problem;
+                speeds.iter().flat_map(move |&speed_a| {
+                    speeds.iter().map(move |&speed_b| {
+                        problem.evaluate([heading_a, heading_b], [speed_a, speed_b])
+                    })
+                })
+            })
+            .collect();
+        grid_candidates.sort_by(|a, b| a.residual.total_cmp(&b.residual));
+
+        // Refine the best grid points; neighbours often converge together
+        let refine_count = (config.max_candidates * 4).max(1);
+        let mut refined: Vec<InverseCandidate> = grid_candidates
+            .par_iter()
+            .take(refine_count)
+            .map(|candidate| problem.refine(candidate, config.speed_step))
+            .collect();
+        refined.sort_by(|a, b| a.residual.total_cmp(&b.residual));
+
+        for candidate in refined {
+            let duplicate = solution.candidates.iter().any(|kept| {
+                (kept.pre_impact_velocities[0] - candidate.pre_impact_velocities[0]).norm()
+                    < config.merge_tolerance
+                    && (kept.pre_impact_velocities[1] - candidate.pre_impact_velocities[1]).norm()
+                        < config.merge_tolerance
+            });
+            if !duplicate {
+                solution.candidates.push(candidate);
+            }
+            if solution.candidates.len() >= config.max_candidates {
+                break;
+            }
+        }
+
+        solution
+    }
+}
+
+/// Post-impact state shared by every candidate.
+struct Problem {
+    masses: [f64; 2],
+    momentum_after: Vector3<f64>,
+    energy_after: f64,
+    crush_energy: Option<f64>,
+}
+
+impl Problem {
+    /// Scores pre-impact speeds along the given headings.
+    fn evaluate(&self, headings: [f64; 2], speeds: [f64; 2]) -> InverseCandidate {
+        let velocities = [
+            heading_vector(headings[0]) * speeds[0],
+            heading_vector(headings[1]) * speeds[1],
+        ];
+        let momentum_before = self.masses[0] * velocities[0] + self.masses[1] * velocities[1];
+        let momentum_residual = self.momentum_after - momentum_before;
+        let momentum_error = momentum_residual.norm()
+            / self.momentum_after.norm().max(momentum_before.norm()).max(1e-9);
+
+        let energy_before = 0.5 * self.masses[0] * speeds[0] * speeds[0]
+            + 0.5 * self.masses[1] * speeds[1] * speeds[1];
+        let dissipated_energy = energy_before - self.energy_after;
+
+        // Without crush energy, only an energy gain is penalised
+        let imbalance = match self.crush_energy {
+            Some(crush) => (dissipated_energy - crush).abs(),
+            None => (-dissipated_energy).max(0.0),
+        };
+        let energy_error = imbalance / energy_before.max(self.energy_after).max(1e-9);
+
+        InverseCandidate {
+            pre_impact_velocities: velocities,
+            pre_impact_speeds: speeds,
+            momentum_residual,
+            momentum_error,
+            dissipated_energy,
+            energy_error,
+            residual: momentum_error + energy_error,
+        }
+    }
+
+    /// Pattern search around a candidate, halving the step until it is
+    /// below 1 mm/s.
+    fn refine(&self, candidate: &InverseCandidate, initial_step: f64) -> InverseCandidate {
+        let headings = [
+            heading_of(
+                candidate.pre_impact_velocities[0],
+                candidate.pre_impact_speeds[0],
+            ),
+            heading_of(
+                candidate.pre_impact_velocities[1],
+                candidate.pre_impact_speeds[1],
+            ),
+        ];
+        let mut best = candidate.clone();
+        let mut step = initial_step;
+
+        while step > 1e-3 {
+            let [speed_a, speed_b] = best.pre_impact_speeds;
+            let improved = [(step, 0.0), (-step, 0.0), (0.0, step), (0.0, -step)]
+                .iter()
+                .map(|(da, db)| [(speed_a + da).max(0.0), (speed_b + db).max(0.0)])
+                .map(|speeds| self.evaluate(headings, speeds))
+                .filter(|trial| trial.residual < best.residual)
+                .min_by(|a, b| a.residual.total_cmp(&b.residual));
+
+            match improved {
+                Some(trial) => best = trial,
+                None => step *= 0.5,
+            }
+        }
+
+        best
+    }
+}
+
+/// Evenly spaced values from `start` to `end` inclusive.
+fn grid(start: f64, end: f64, step: f64) -> Vec<f64> {
+    let count = ((end - start) / step).floor() as usize;
+    (0..=count).map(|i| start + i as f64 * step).collect()
+}
+
+/// Approach headings searched around the nominal heading.
+fn heading_grid(nominal: f64, config: &InverseSolverConfig) -> Vec<f64> {
+    if config.heading_tolerance <= 0.0 || config.heading_step <= 0.0 {
+        return vec![nominal];
+    }
+
+    grid(
+        nominal - config.heading_tolerance,
+        nominal + config.heading_tolerance + 1e-9,
+        config.heading_step,
+    )
+}
+
+/// Unit vector in the ground plane for a heading.
+fn heading_vector(heading: f64) -> Vector3<f64> {
+    Vector3::new(heading.cos(), heading.sin(), 0.0)
+}
+
+/// Heading of a velocity, or zero when stationary.
+fn heading_of(velocity: Vector3<f64>, speed: f64) -> f64 {
+    if speed > 1e-9 {
+        velocity.y.atan2(velocity.x)
+    } else {
+        0.0
+    }
+}
+
+/// Wraps an angle to [-π, π).
+fn wrap_angle(angle: f64) -> f64 {
+    (angle + std::f64::consts::PI).rem_euclid(2.0 * std::f64::consts::PI) - std::f64::consts::PI
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::FRAC_PI_2;
+
+    /// Rest position reached by sliding from the origin at `velocity`.
+    fn rest_after(velocity: Vector3<f64>, friction: f64) -> Point3<f64> {
+        let distance = velocity.norm_squared() / (2.0 * friction * GRAVITY);
+        Point3::origin() + velocity.normalize() * distance
+    }
+
+    #[test]
+    fn test_separation_from_runout() {
+        let evidence = RestEvidence::new(1500.0, Point3::origin(), Point3::new(20.0, 0.0, 0.0))
+            .with_friction(0.7)
+            .with_headings(0.0, FRAC_PI_2);
+
+        let velocity = evidence.separation_velocity();
+        assert!((velocity.x - (2.0 * 0.7 * GRAVITY * 20.0).sqrt()).abs() < 1e-9);
+        assert!(velocity.y.abs() < 1e-9);
+        assert!(evidence.separation_yaw_rate() > 0.0);
+
+        let trajectory = evidence.reverse_runout(0.01);
+        let first = trajectory.points.first().unwrap();
+        let last = trajectory.points.last().unwrap();
+        assert!(first.time.abs() < 1e-9);
+        assert!((first.position - Point3::origin()).norm() < 1e-6);
+        assert!((first.velocity - velocity).norm() < 1e-6);
+        assert!((last.position - Point3::new(20.0, 0.0, 0.0)).norm() < 1e-9);
+        assert!(last.velocity.norm() < 1e-9);
+        assert!((trajectory.total_distance - 20.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_recovers_pre_impact_speeds() {
+        // A eastbound at 20 m/s, B northbound at 15 m/s, locking together
+        let (mass_a, mass_b) = (1500.0, 1200.0);
+        let velocity_a = Vector3::new(20.0, 0.0, 0.0);
+        let velocity_b = Vector3::new(0.0, 15.0, 0.0);
+        let common = (mass_a * velocity_a + mass_b * velocity_b) / (mass_a + mass_b);
+        let rest = rest_after(common, 0.7);
+
+        let vehicle_a = RestEvidence::new(mass_a, Point3::origin(), rest).with_friction(0.7);
+        let vehicle_b = RestEvidence::new(mass_b, Point3::origin(), rest)
+            .with_friction(0.7)
+            .with_approach_heading(FRAC_PI_2);
+
+        let solution = InverseSolver::default().solve(&vehicle_a, &vehicle_b);
+        let best = solution.best().unwrap();
+
+        assert!((best.pre_impact_speeds[0] - 20.0).abs() < 0.05);
+        assert!((best.pre_impact_speeds[1] - 15.0).abs() < 0.05);
+        assert!(best.momentum_error < 1e-3);
+        assert!(best.dissipated_energy > 0.0);
+        assert!(solution.candidates.len() <= 5);
+        assert!(solution.candidates.windows(2).all(|pair| pair[0].residual <= pair[1].residual));
+    }
+
+    #[test]
+    fn test_crush_energy_and_heading_search() {
+        let (mass_a, mass_b) = (1500.0, 1500.0);
+        let velocity_a = Vector3::new(15.0, 0.0, 0.0);
+        let velocity_b = Vector3::new(0.0, 10.0, 0.0);
+        let common = (mass_a * velocity_a + mass_b * velocity_b) / (mass_a + mass_b);
+        let rest = rest_after(common, 0.7);
+        let crush = 0.5 * mass_a * 225.0 + 0.5 * mass_b * 100.0
+            - 0.5 * (mass_a + mass_b) * common.norm_squared();
+
+        // Approach headings are only known to within 10 degrees
+        let vehicle_a = RestEvidence::new(mass_a, Point3::origin(), rest)
+            .with_friction(0.7)
+            .with_approach_heading(6.0_f64.to_radians());
+        let vehicle_b = RestEvidence::new(mass_b, Point3::origin(), rest)
+            .with_friction(0.7)
+            .with_approach_heading(FRAC_PI_2 - 4.0_f64.to_radians());
+
+        let solver = InverseSolver::new(InverseSolverConfig {
+            heading_tolerance: 10.0_f64.to_radians(),
+            crush_energy: Some(crush),
+            max_speed: 30.0,
+            ..InverseSolverConfig::default()
+        });
+        let best = solver.solve(&vehicle_a, &vehicle_b).candidates.remove(0);
+
+        assert!((best.pre_impact_velocities[0] - velocity_a).norm() < 0.2);
+        assert!((best.pre_impact_velocities[1] - velocity_b).norm() < 0.2);
+        assert!(best.energy_error < 0.01);
+    }
+}
@@ -8,6 +8,7 @@
 //! - **Kinematics**: Trajectory prediction and momentum conservation analysis
 //! - **Energy Analysis**: Crush energy, speed estimation from skid marks
 //! - **Reconstruction Tools**: Complete accident reconstruction with validation
+//! - **Inverse Simulation**: Pre-impact velocities searched backwards from rest positions
 //! - **Parallel Processing**: High-performance simulation using Rayon
 //!
 //! # Examples
@@ -74,6 +75,7 @@ pub mod dynamics;
 pub mod energy;
 pub mod engine;
 pub mod friction;
+pub mod inverse;
 pub mod kinematics;
 pub mod parallel;
 pub mod reconstruction;
@@ -97,6 +99,10 @@ pub use engine::{EngineConfig, PhysicsEngine};
 
 pub use friction::{FrictionModel, SurfaceType};
 
+pub use inverse::{
+    InverseCandidate, InverseSolution, InverseSolver, InverseSolverConfig, RestEvidence,
+};
+
 pub use kinematics::{
     MomentumAnalysis, MomentumConservation, Trajectory, TrajectoryPoint, TrajectoryPredictor,
 };
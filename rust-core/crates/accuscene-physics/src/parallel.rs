@@ -3,6 +3,7 @@
 use crate::collision::Collision;
 use crate::dynamics::VehicleState;
 use crate::simulation::RigidBody;
+use accuscene_core::measurements::{Measurement, MeasurementSampler};
 use nalgebra::Vector3;
 use rayon::prelude::*;
 use std::sync::{Arc, Mutex};
@@ -233,6 +234,31 @@ impl ParameterSweep {
             })
             .collect()
     }
+
+    /// Monte Carlo sweep over measured inputs.
+    ///
+    /// Each run draws one value per measurement from its uncertainty
+    /// distribution. Run `i` is seeded with `seed + i`, so results are
+    /// reproducible regardless of how rayon schedules the runs.
+    pub fn monte_carlo<F>(
+        inputs: &[Measurement],
+        num_runs: usize,
+        seed: u64,
+        simulation_fn: F,
+    ) -> Vec<(Vec<f64>, SimulationResult)>
+    where
+        F: Fn(usize, &[f64]) -> SimulationResult + Send + Sync,
+    {
+        (0..num_runs)
+            .into_par_iter()
+            .map(|run| {
+                let mut sampler = MeasurementSampler::new(seed.wrapping_add(run as u64));
+                let values = sampler.sample_all(inputs);
+                let result = simulation_fn(run, &values);
+                (values, result)
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -322,4 +348,29 @@ mod tests {
         assert_eq!(results.len(), 5);
         assert!((results[2].1.total_energy - 700.0).abs() < 1.0);
     }
+
+    #[test]
+    fn test_monte_carlo_samples_measurements() {
+        use accuscene_core::measurements::{MeasurementKind, MeasurementMethod};
+
+        let skid = Measurement::new(MeasurementKind::SkidLength, 30.0, MeasurementMethod::Tape)
+            .with_instrument("tape".to_string(), 0.5);
+        let run = |id: usize, values: &[f64]| SimulationResult {
+            id,
+            final_states: vec![],
+            total_energy: values[0],
+            num_collisions: 0,
+            duration: 1.0,
+            success: true,
+        };
+
+        let results = ParameterSweep::monte_carlo(std::slice::from_ref(&skid), 200, 7, run);
+        let again = ParameterSweep::monte_carlo(std::slice::from_ref(&skid), 200, 7, run);
+
+        assert_eq!(results.len(), 200);
+        assert_eq!(results[17].0, again[17].0);
+
+        let mean = results.iter().map(|(v, _)| v[0]).sum::<f64>() / results.len() as f64;
+        assert!((mean - 30.0).abs() < 0.2);
+    }
 }
@@ -2,6 +2,7 @@
 
 use crate::energy::EnergyCalculator;
 use crate::friction::SurfaceType;
+use accuscene_core::measurements::{self, Measurement, UncertainValue};
 use serde::{Deserialize, Serialize};
 
 /// Speed estimation result.
@@ -21,6 +22,9 @@ pub struct SpeedEstimate {
     pub max_speed_mps: f64,
     /// Method used for estimation
     pub method: String,
+    /// Standard uncertainty (m/s) propagated from measured inputs
+    #[serde(default)]
+    pub input_uncertainty_mps: Option<f64>,
 }
 
 impl SpeedEstimate {
@@ -34,6 +38,7 @@ impl SpeedEstimate {
             min_speed_mps: speed_mps * 0.9,
             max_speed_mps: speed_mps * 1.1,
             method,
+            input_uncertainty_mps: None,
         }
     }
 
@@ -43,6 +48,21 @@ impl SpeedEstimate {
         self.max_speed_mps = max_mps;
         self
     }
+
+    /// Folds the propagated measurement uncertainty into the range.
+    ///
+    /// The model range and `coverage_factor * σ` are treated as independent
+    /// and combined by root-sum-square on each side of the estimate.
+    pub fn with_input_uncertainty(mut self, standard_uncertainty: f64, coverage_factor: f64) -> Self {
+        let expanded = coverage_factor * standard_uncertainty;
+        let below = (self.speed_mps - self.min_speed_mps).hypot(expanded);
+        let above = (self.max_speed_mps - self.speed_mps).hypot(expanded);
+
+        self.min_speed_mps = (self.speed_mps - below).max(0.0);
+        self.max_speed_mps = self.speed_mps + above;
+        self.input_uncertainty_mps = Some(standard_uncertainty);
+        self
+    }
 }
 
 /// Speed estimator using various physical evidence.
@@ -237,6 +257,69 @@ impl SpeedEstimator {
         .with_range(slide_speed * 0.8, slide_speed * 1.2)
     }
 
+    /// Estimates speed from a measured skid mark, propagating its uncertainty.
+    pub fn from_measured_skid_marks(
+        skid_length: &Measurement,
+        surface: SurfaceType,
+        grade_percent: f64,
+    ) -> SpeedEstimate {
+        Self::from_measured(&[skid_length], |values| {
+            Self::from_skid_marks(values[0], surface, grade_percent)
+        })
+    }
+
+    /// Estimates speed from a measured yaw mark radius, propagating its uncertainty.
+    pub fn from_measured_yaw_marks(
+        radius: &Measurement,
+        surface: SurfaceType,
+        superelevation: f64,
+    ) -> SpeedEstimate {
+        Self::from_measured(&[radius], |values| {
+            Self::from_yaw_marks(values[0], surface, superelevation)
+        })
+    }
+
+    /// Estimates speed from a measured crush depth, propagating its uncertainty.
+    pub fn from_measured_crush_depth(
+        crush_depth: &Measurement,
+        vehicle_mass: f64,
+        vehicle_stiffness: f64,
+        contact_area: f64,
+    ) -> SpeedEstimate {
+        Self::from_measured(&[crush_depth], |values| {
+            Self::from_crush_depth(values[0], vehicle_mass, vehicle_stiffness, contact_area)
+        })
+    }
+
+    /// Estimates speed from measured airborne distances, propagating their uncertainty.
+    pub fn from_measured_fall_distance(
+        horizontal_distance: &Measurement,
+        vertical_drop: &Measurement,
+    ) -> SpeedEstimate {
+        Self::from_measured(&[horizontal_distance, vertical_drop], |values| {
+            Self::from_fall_distance(values[0], values[1])
+        })
+    }
+
+    /// Runs an estimator on nominal measured values and widens its range by
+    /// the propagated input uncertainty, at the widest coverage factor of the inputs.
+    fn from_measured<F>(inputs: &[&Measurement], estimator: F) -> SpeedEstimate
+    where
+        F: Fn(&[f64]) -> SpeedEstimate,
+    {
+        let UncertainValue {
+            standard_uncertainty,
+            ..
+        } = measurements::propagate(inputs, |values| estimator(values).speed_mps);
+        let coverage_factor = inputs
+            .iter()
+            .map(|m| m.coverage_factor)
+            .fold(measurements::DEFAULT_COVERAGE_FACTOR, f64::max);
+
+        let nominal: Vec<f64> = inputs.iter().map(|m| m.value).collect();
+        estimator(&nominal).with_input_uncertainty(standard_uncertainty, coverage_factor)
+    }
+
     /// Combines multiple speed estimates using weighted average.
     pub fn combine_estimates(estimates: &[SpeedEstimate]) -> Option<SpeedEstimate> {
         if estimates.is_empty() {
@@ -329,6 +412,26 @@ mod tests {
         assert!((combined.speed_mps - 20.86).abs() < 0.1);
     }
 
+    #[test]
+    fn test_measured_skid_marks_widen_range() {
+        use accuscene_core::measurements::{MeasurementKind, MeasurementMethod};
+
+        let skid = Measurement::new(MeasurementKind::SkidLength, 50.0, MeasurementMethod::Tape)
+            .with_uncertainty("end point definition", 1.0);
+
+        let bare = SpeedEstimator::from_skid_marks(50.0, SurfaceType::AsphaltDry, 0.0);
+        let measured = SpeedEstimator::from_measured_skid_marks(&skid, SurfaceType::AsphaltDry, 0.0);
+
+        assert!((measured.speed_mps - bare.speed_mps).abs() < 1e-9);
+        assert!(measured.min_speed_mps < bare.min_speed_mps);
+        assert!(measured.max_speed_mps > bare.max_speed_mps);
+
+        // σ(v)/v = ½ σ(d)/d for v ∝ √d
+        let relative = measured.input_uncertainty_mps.unwrap() / measured.speed_mps;
+        let expected = 0.5 * skid.standard_uncertainty() / 50.0;
+        assert!((relative - expected).abs() < 1e-6);
+    }
+
     #[test]
     fn test_unit_conversions() {
         let estimate = SpeedEstimate::new(20.0, 0.9, "Test".to_string());
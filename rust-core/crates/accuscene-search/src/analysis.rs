@@ -0,0 +1,275 @@
+//! Language analysis for multilingual search
+//!
+//! Case files mix English, Spanish and French reports. Each supported
+//! language has an analyzer (tokenization, lowercasing, stop words,
+//! stemming and accent folding) registered on the index under
+//! [`AnalyzerLanguage::tokenizer_name`]. Text is always indexed into the base
+//! full-text fields, which use the English analyzer; text in another language
+//! is also indexed into that language's fields (e.g. `content_es`), which
+//! queries in that language search instead.
+
+use crate::config::LanguageConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tantivy::tokenizer::{
+    AsciiFoldingFilter, Language, LowerCaser, RemoveLongFilter, SimpleTokenizer, Stemmer,
+    StopWordFilter, TextAnalyzer, TokenStream,
+};
+use tantivy::Index;
+use whatlang::{Detector, Lang};
+
+/// Longest token kept by the stock analyzers
+const MAX_TOKEN_LENGTH: usize = 40;
+
+/// Languages with a dedicated analyzer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnalyzerLanguage {
+    English,
+    Spanish,
+    French,
+}
+
+impl AnalyzerLanguage {
+    /// All supported languages
+    pub const ALL: [AnalyzerLanguage; 3] = [
+        AnalyzerLanguage::English,
+        AnalyzerLanguage::Spanish,
+        AnalyzerLanguage::French,
+    ];
+
+    /// ISO 639-1 code
+    pub fn code(&self) -> &'static str {
+        match self {
+            AnalyzerLanguage::English => "en",
+            AnalyzerLanguage::Spanish => "es",
+            AnalyzerLanguage::French => "fr",
+        }
+    }
+
+    /// Parse an ISO 639-1/639-3 code, a locale (`es-MX`) or an English name
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code.split(['-', '_']).next().unwrap_or(code);
+        match code.to_lowercase().as_str() {
+            "en" | "eng" | "english" => Some(AnalyzerLanguage::English),
+            "es" | "spa" | "spanish" => Some(AnalyzerLanguage::Spanish),
+            "fr" | "fra" | "fre" | "french" => Some(AnalyzerLanguage::French),
+            _ => None,
+        }
+    }
+
+    /// Name the language's analyzer is registered under
+    ///
+    /// English replaces Tantivy's built-in `en_stem` analyzer so the base
+    /// fields of existing indexes pick it up without reindexing.
+    pub fn tokenizer_name(&self) -> &'static str {
+        match self {
+            AnalyzerLanguage::English => "en_stem",
+            AnalyzerLanguage::Spanish => "lang_es",
+            AnalyzerLanguage::French => "lang_fr",
+        }
+    }
+
+    /// Stock analyzer for the language
+    pub fn default_analyzer(&self) -> TextAnalyzer {
+        let language = self.stemmer_language();
+        let stop_words = StopWordFilter::new(language)
+            .unwrap_or_else(|| StopWordFilter::remove(Vec::<String>::new()));
+
+        TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(RemoveLongFilter::limit(MAX_TOKEN_LENGTH))
+            .filter(LowerCaser)
+            .filter(stop_words)
+            .filter(Stemmer::new(language))
+            .filter(AsciiFoldingFilter)
+            .build()
+    }
+
+    fn stemmer_language(&self) -> Language {
+        match self {
+            AnalyzerLanguage::English => Language::English,
+            AnalyzerLanguage::Spanish => Language::Spanish,
+            AnalyzerLanguage::French => Language::French,
+        }
+    }
+
+    fn to_whatlang(self) -> Lang {
+        match self {
+            AnalyzerLanguage::English => Lang::Eng,
+            AnalyzerLanguage::Spanish => Lang::Spa,
+            AnalyzerLanguage::French => Lang::Fra,
+        }
+    }
+
+    fn from_whatlang(lang: Lang) -> Option<Self> {
+        Self::ALL.into_iter().find(|language| language.to_whatlang() == lang)
+    }
+}
+
+/// Analyzers to register on the index, one per language
+///
+/// Starts with the stock analyzer of every language; deployments can plug in
+/// their own (e.g. with domain synonyms) via [`with_analyzer`](Self::with_analyzer).
+#[derive(Clone)]
+pub struct LanguageAnalyzers {
+    analyzers: HashMap<AnalyzerLanguage, TextAnalyzer>,
+}
+
+impl LanguageAnalyzers {
+    pub fn new() -> Self {
+        Self {
+            analyzers: AnalyzerLanguage::ALL
+                .into_iter()
+                .map(|language| (language, language.default_analyzer()))
+                .collect(),
+        }
+    }
+
+    /// Replace the analyzer of a language
+    pub fn with_analyzer(mut self, language: AnalyzerLanguage, analyzer: TextAnalyzer) -> Self {
+        self.analyzers.insert(language, analyzer);
+        self
+    }
+
+    /// Get the analyzer of a language
+    pub fn get(&self, language: AnalyzerLanguage) -> Option<&TextAnalyzer> {
+        self.analyzers.get(&language)
+    }
+
+    /// Register every analyzer on the index
+    ///
+    /// Must happen before the index is written to or searched, as the
+    /// language fields reference the analyzers by name.
+    pub fn register(&self, index: &Index) {
+        for (language, analyzer) in &self.analyzers {
+            index
+                .tokenizers()
+                .register(language.tokenizer_name(), analyzer.clone());
+        }
+    }
+}
+
+impl Default for LanguageAnalyzers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Detects the language of document and query text
+#[derive(Debug, Clone)]
+pub struct LanguageDetector {
+    config: LanguageConfig,
+}
+
+impl LanguageDetector {
+    pub fn new(config: LanguageConfig) -> Self {
+        Self { config }
+    }
+
+    /// Detection configuration
+    pub fn config(&self) -> &LanguageConfig {
+        &self.config
+    }
+
+    /// Language assumed when detection is disabled or inconclusive
+    pub fn default_language(&self) -> AnalyzerLanguage {
+        self.config.default_language
+    }
+
+    /// Detect the language of the text among the configured languages
+    ///
+    /// Returns `None` for text shorter than the configured minimum and when
+    /// the detector is not confident enough.
+    pub fn detect(&self, text: &str) -> Option<AnalyzerLanguage> {
+        let letters = text.chars().filter(|c| c.is_alphabetic()).count();
+        if letters < self.config.min_detection_length {
+            return None;
+        }
+
+        let candidates: Vec<Lang> = self
+            .config
+            .languages
+            .iter()
+            .map(|language| language.to_whatlang())
+            .collect();
+        if candidates.len() < 2 {
+            return self.config.languages.first().copied();
+        }
+
+        let info = Detector::with_allowlist(candidates).detect(text)?;
+        if info.confidence() < self.config.min_confidence {
+            return None;
+        }
+
+        AnalyzerLanguage::from_whatlang(info.lang())
+    }
+
+    /// Detect the language of the text, falling back to the default language
+    pub fn detect_or_default(&self, text: &str) -> AnalyzerLanguage {
+        self.detect(text).unwrap_or(self.config.default_language)
+    }
+}
+
+/// Run text through an analyzer and collect the resulting tokens
+pub fn analyze(analyzer: &mut TextAnalyzer, text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut stream = analyzer.token_stream(text);
+    while stream.advance() {
+        tokens.push(stream.token().text.clone());
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_codes() {
+        assert_eq!(AnalyzerLanguage::from_code("es"), Some(AnalyzerLanguage::Spanish));
+        assert_eq!(AnalyzerLanguage::from_code("fr-CA"), Some(AnalyzerLanguage::French));
+        assert_eq!(AnalyzerLanguage::from_code("English"), Some(AnalyzerLanguage::English));
+        assert_eq!(AnalyzerLanguage::from_code("de"), None);
+
+        for language in AnalyzerLanguage::ALL {
+            assert_eq!(AnalyzerLanguage::from_code(language.code()), Some(language));
+        }
+    }
+
+    #[test]
+    fn test_stock_analyzers() {
+        let mut spanish = AnalyzerLanguage::Spanish.default_analyzer();
+        assert_eq!(
+            analyze(&mut spanish, "Los vehículos en la intersección"),
+            analyze(&mut spanish, "vehiculo interseccion")
+        );
+
+        let mut french = AnalyzerLanguage::French.default_analyzer();
+        let tokens = analyze(&mut french, "Le conducteur a freiné devant les piétons");
+        assert!(!tokens.contains(&"le".to_string()));
+        assert!(!tokens.contains(&"les".to_string()));
+        assert!(tokens.iter().all(|token| token.is_ascii()));
+    }
+
+    #[test]
+    fn test_language_detection() {
+        let detector = LanguageDetector::new(LanguageConfig::default());
+
+        assert_eq!(
+            detector.detect("El conductor perdió el control del vehículo en la curva mojada"),
+            Some(AnalyzerLanguage::Spanish)
+        );
+        assert_eq!(
+            detector.detect("Le conducteur a perdu le contrôle du véhicule dans le virage"),
+            Some(AnalyzerLanguage::French)
+        );
+        assert_eq!(
+            detector.detect("The driver lost control of the vehicle on the wet curve"),
+            Some(AnalyzerLanguage::English)
+        );
+
+        // Too short to tell
+        assert_eq!(detector.detect("Rojo"), None);
+        assert_eq!(detector.detect_or_default("Rojo"), AnalyzerLanguage::English);
+    }
+}
@@ -1,5 +1,6 @@
 //! Search engine configuration
 
+use crate::analysis::AnalyzerLanguage;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -49,6 +50,10 @@ pub struct SearchConfig {
 
     /// Facet configuration
     pub facet_config: FacetConfig,
+
+    /// Language analysis configuration
+    #[serde(default)]
+    pub language_config: LanguageConfig,
 }
 
 impl Default for SearchConfig {
@@ -69,6 +74,7 @@ impl Default for SearchConfig {
             search_timeout_ms: 5000,
             bm25_config: BM25Config::default(),
             facet_config: FacetConfig::default(),
+            language_config: LanguageConfig::default(),
         }
     }
 }
@@ -121,6 +127,40 @@ impl Default for FacetConfig {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageConfig {
+    /// Language assumed when detection is disabled or inconclusive
+    pub default_language: AnalyzerLanguage,
+
+    /// Languages detection may choose from
+    pub languages: Vec<AnalyzerLanguage>,
+
+    /// Detect the language of indexed fields without an explicit `language`
+    pub detect_documents: bool,
+
+    /// Detect the language of queries that don't select one
+    pub detect_queries: bool,
+
+    /// Minimum detector confidence (0-1)
+    pub min_confidence: f64,
+
+    /// Minimum number of letters before detection is attempted
+    pub min_detection_length: usize,
+}
+
+impl Default for LanguageConfig {
+    fn default() -> Self {
+        Self {
+            default_language: AnalyzerLanguage::English,
+            languages: AnalyzerLanguage::ALL.to_vec(),
+            detect_documents: true,
+            detect_queries: false,
+            min_confidence: 0.5,
+            min_detection_length: 20,
+        }
+    }
+}
+
 impl SearchConfig {
     pub fn builder() -> SearchConfigBuilder {
         SearchConfigBuilder::default()
@@ -151,6 +191,15 @@ impl SearchConfig {
             return Err("BM25 b must be between 0 and 1".to_string());
         }
 
+        let language = &self.language_config;
+        if !language.languages.contains(&language.default_language) {
+            return Err("default_language must be one of the configured languages".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&language.min_confidence) {
+            return Err("Language min_confidence must be between 0 and 1".to_string());
+        }
+
         Ok(())
     }
 }
@@ -196,6 +245,21 @@ impl SearchConfigBuilder {
         self
     }
 
+    pub fn default_language(mut self, language: AnalyzerLanguage) -> Self {
+        self.config.language_config.default_language = language;
+        self
+    }
+
+    pub fn languages(mut self, languages: Vec<AnalyzerLanguage>) -> Self {
+        self.config.language_config.languages = languages;
+        self
+    }
+
+    pub fn detect_query_language(mut self, enabled: bool) -> Self {
+        self.config.language_config.detect_queries = enabled;
+        self
+    }
+
     pub fn build(self) -> Result<SearchConfig, String> {
         self.config.validate()?;
         Ok(self.config)
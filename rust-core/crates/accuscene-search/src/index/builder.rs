@@ -1,5 +1,6 @@
 //! Index builder for bulk indexing operations
 
+use crate::analysis::LanguageAnalyzers;
 use crate::config::SearchConfig;
 use crate::error::{SearchError, SearchResult};
 use crate::index::schema::IndexSchema;
@@ -17,7 +18,7 @@ pub struct IndexBuilder {
 impl IndexBuilder {
     /// Create a new index builder
     pub fn new(index_path: &Path, config: SearchConfig) -> SearchResult<Self> {
-        let schema = IndexSchema::default();
+        let schema = IndexSchema::new(config.language_config.clone());
 
         std::fs::create_dir_all(index_path)?;
 
@@ -28,6 +29,7 @@ impl IndexBuilder {
             info!("Creating new index at {:?}", index_path);
             Index::create_in_dir(index_path, schema.tantivy_schema.clone())?
         };
+        LanguageAnalyzers::default().register(&index);
 
         Ok(Self {
             index,
@@ -4,10 +4,12 @@ pub mod builder;
 pub mod schema;
 pub mod writer;
 
+use crate::analysis::LanguageAnalyzers;
 use crate::config::SearchConfig;
 use crate::error::{SearchError, SearchResult};
 use crate::query::{Query, SearchFilters};
 use crate::ranking::SearchResults;
+use crate::suggestions::{PopularQueries, SuggestOptions, Suggestion};
 use schema::IndexSchema;
 use std::path::Path;
 use tantivy::{Index, IndexReader, IndexWriter, ReloadPolicy};
@@ -24,8 +26,17 @@ pub struct SearchIndex {
 impl SearchIndex {
     /// Create a new search index
     pub async fn new(config: &SearchConfig) -> SearchResult<Self> {
-        let schema = IndexSchema::default();
+        Self::with_analyzers(config, LanguageAnalyzers::default()).await
+    }
+
+    /// Create a new search index with custom language analyzers
+    pub async fn with_analyzers(
+        config: &SearchConfig,
+        analyzers: LanguageAnalyzers,
+    ) -> SearchResult<Self> {
+        let schema = IndexSchema::new(config.language_config.clone());
         let index = Self::create_or_open_index(&config.index_path, &schema)?;
+        analyzers.register(&index);
 
         let reader = index
             .reader_builder()
@@ -113,6 +124,21 @@ impl SearchIndex {
         engine.suggest(prefix, limit).await
     }
 
+    /// Get typo-tolerant, ranked and categorized suggestions
+    pub async fn suggest_ranked(
+        &self,
+        prefix: &str,
+        options: &SuggestOptions,
+        popular: &PopularQueries,
+    ) -> SearchResult<Vec<Suggestion>> {
+        use crate::suggestions::SuggestionEngine;
+
+        let searcher = self.reader.searcher();
+        let engine = SuggestionEngine::new(searcher, &self.schema).with_popular_queries(popular);
+
+        engine.suggest_ranked(prefix, options).await
+    }
+
     /// Get facet counts
     pub async fn facets(&self, field: &str) -> SearchResult<Vec<(String, u64)>> {
         use crate::ranking::facets::FacetCollector;
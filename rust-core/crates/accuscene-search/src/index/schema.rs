@@ -1,10 +1,24 @@
 //! Index schema definitions
 
+use crate::analysis::{AnalyzerLanguage, LanguageDetector};
+use crate::config::LanguageConfig;
 use crate::error::{SearchError, SearchResult};
-use serde_json::Value;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
 use tantivy::schema::*;
 use tantivy::{doc, Document};
 
+/// Per-language variants of the full-text fields
+///
+/// Indexed with the language's analyzer and not stored; the text is stored
+/// in the base fields.
+#[derive(Debug, Clone, Copy)]
+pub struct LanguageFields {
+    pub title: Field,
+    pub content: Field,
+    pub description: Field,
+}
+
 /// Index schema for AccuScene documents
 pub struct IndexSchema {
     pub tantivy_schema: Schema,
@@ -21,10 +35,20 @@ pub struct IndexSchema {
     pub updated_at: Field,
     pub created_by: Field,
     pub metadata: Field,
+    pub language: Field,
+    language_fields: HashMap<AnalyzerLanguage, LanguageFields>,
+    detector: LanguageDetector,
 }
 
 impl Default for IndexSchema {
     fn default() -> Self {
+        Self::new(LanguageConfig::default())
+    }
+}
+
+impl IndexSchema {
+    /// Create the schema, detecting document languages as configured
+    pub fn new(language_config: LanguageConfig) -> Self {
         let mut schema_builder = Schema::builder();
 
         // Document ID (indexed, stored)
@@ -75,6 +99,35 @@ impl Default for IndexSchema {
         // Metadata (JSON)
         let metadata = schema_builder.add_json_field("metadata", STORED);
 
+        // Document language (ISO 639-1 code)
+        let language = schema_builder.add_text_field("language", facet_options);
+
+        // Language-specific full-text fields; English uses the base fields.
+        // Created for every supported language so the schema does not depend
+        // on configuration.
+        let mut language_fields = HashMap::new();
+        for analyzer_language in AnalyzerLanguage::ALL {
+            if analyzer_language == AnalyzerLanguage::English {
+                continue;
+            }
+
+            let options = TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer(analyzer_language.tokenizer_name())
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            );
+            let code = analyzer_language.code();
+
+            language_fields.insert(
+                analyzer_language,
+                LanguageFields {
+                    title: schema_builder.add_text_field(&format!("title_{}", code), options.clone()),
+                    content: schema_builder.add_text_field(&format!("content_{}", code), options.clone()),
+                    description: schema_builder.add_text_field(&format!("description_{}", code), options),
+                },
+            );
+        }
+
         Self {
             tantivy_schema: schema_builder.build(),
             id,
@@ -90,12 +143,17 @@ impl Default for IndexSchema {
             updated_at,
             created_by,
             metadata,
+            language,
+            language_fields,
+            detector: LanguageDetector::new(language_config),
         }
     }
-}
 
-impl IndexSchema {
     /// Create a Tantivy document from a JSON value
+    ///
+    /// The document language is taken from a `language` property if present,
+    /// otherwise each full-text field is detected separately, falling back to
+    /// the language of the whole document.
     pub fn create_document<T: serde::Serialize>(
         &self,
         id: &str,
@@ -110,17 +168,30 @@ impl IndexSchema {
 
         // Extract and add fields from JSON
         if let Value::Object(map) = json {
-            // Text fields
-            if let Some(Value::String(title)) = map.get("title") {
-                doc.add_text(self.title, title);
-            }
+            let explicit_language = map
+                .get("language")
+                .and_then(Value::as_str)
+                .and_then(AnalyzerLanguage::from_code);
+            let document_language =
+                explicit_language.unwrap_or_else(|| self.detect_document_language(&map));
+            doc.add_text(self.language, document_language.code());
 
-            if let Some(Value::String(content)) = map.get("content") {
-                doc.add_text(self.content, content);
-            }
-
-            if let Some(Value::String(description)) = map.get("description") {
-                doc.add_text(self.description, description);
+            // Text fields
+            for (name, field) in [
+                ("title", self.title),
+                ("content", self.content),
+                ("description", self.description),
+            ] {
+                if let Some(Value::String(text)) = map.get(name) {
+                    doc.add_text(field, text);
+
+                    let field_language = explicit_language
+                        .unwrap_or_else(|| self.detect_field_language(text, document_language));
+                    let localized = self.language_field(field, field_language);
+                    if localized != field {
+                        doc.add_text(localized, text);
+                    }
+                }
             }
 
             // Faceted fields
@@ -176,6 +247,59 @@ impl IndexSchema {
         Ok(doc)
     }
 
+    /// Detect the language of the whole document from its full-text fields
+    fn detect_document_language(&self, map: &Map<String, Value>) -> AnalyzerLanguage {
+        if !self.detector_enabled() {
+            return self.detector.default_language();
+        }
+
+        let text = ["title", "description", "content"]
+            .iter()
+            .filter_map(|name| map.get(*name).and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        self.detector.detect_or_default(&text)
+    }
+
+    /// Detect the language of one field, falling back to the document language
+    fn detect_field_language(&self, text: &str, fallback: AnalyzerLanguage) -> AnalyzerLanguage {
+        if !self.detector_enabled() {
+            return fallback;
+        }
+
+        self.detector.detect(text).unwrap_or(fallback)
+    }
+
+    fn detector_enabled(&self) -> bool {
+        self.detector.config().detect_documents
+    }
+
+    /// Get the variant of a full-text field for a language
+    ///
+    /// Returns the field itself for English and for fields without
+    /// language variants.
+    pub fn language_field(&self, field: Field, language: AnalyzerLanguage) -> Field {
+        let Some(fields) = self.language_fields.get(&language) else {
+            return field;
+        };
+
+        if field == self.title {
+            fields.title
+        } else if field == self.content {
+            fields.content
+        } else if field == self.description {
+            fields.description
+        } else {
+            field
+        }
+    }
+
+    /// Get the language-specific fields of a language (none for English)
+    pub fn language_fields(&self, language: AnalyzerLanguage) -> Option<&LanguageFields> {
+        self.language_fields.get(&language)
+    }
+
     fn parse_date(value: &Value) -> Option<tantivy::DateTime> {
         match value {
             Value::String(s) => {
@@ -215,6 +339,7 @@ impl IndexSchema {
             ("status", self.status),
             ("severity", self.severity),
             ("created_by", self.created_by),
+            ("language", self.language),
         ]
     }
 }
@@ -245,4 +370,29 @@ mod tests {
         let doc = schema.create_document("doc1", data).unwrap();
         assert!(!doc.field_values().is_empty());
     }
+
+    #[test]
+    fn test_document_language_fields() {
+        let schema = IndexSchema::default();
+        let spanish = schema.language_fields(AnalyzerLanguage::Spanish).unwrap();
+        let data = json!({
+            "title": "Choque",
+            "content": "El conductor perdió el control del vehículo en la curva mojada"
+        });
+
+        let doc = schema.create_document("doc1", data).unwrap();
+        assert_eq!(doc.get_first(schema.language).and_then(|v| v.as_text()), Some("es"));
+        // The short title follows the document language
+        assert!(doc.get_first(spanish.title).is_some());
+        assert!(doc.get_first(spanish.content).is_some());
+        assert!(doc.get_first(schema.content).is_some());
+
+        let data = json!({
+            "content": "El conductor perdió el control del vehículo en la curva mojada",
+            "language": "en"
+        });
+        let doc = schema.create_document("doc2", data).unwrap();
+        assert_eq!(doc.get_first(schema.language).and_then(|v| v.as_text()), Some("en"));
+        assert!(doc.get_first(spanish.content).is_none());
+    }
 }
@@ -176,6 +176,7 @@ mod tests {
             schema.tantivy_schema.clone(),
         )
         .unwrap();
+        crate::analysis::LanguageAnalyzers::default().register(&index);
 
         let config = SearchConfig::default();
         let writer = BatchWriter::new(index, config).unwrap();
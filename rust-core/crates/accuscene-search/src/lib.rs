@@ -1,8 +1,9 @@
 //! AccuScene Enterprise Search Engine
 //!
 //! Advanced full-text search with BM25 ranking, faceted search, fuzzy matching,
-//! and real-time suggestions.
+//! real-time suggestions and multilingual (English, Spanish, French) analysis.
 
+pub mod analysis;
 pub mod config;
 pub mod error;
 pub mod highlighting;
@@ -11,28 +12,49 @@ pub mod query;
 pub mod ranking;
 pub mod suggestions;
 
+pub use analysis::{AnalyzerLanguage, LanguageAnalyzers};
 pub use config::SearchConfig;
+pub use suggestions::{SuggestionCategory, SuggestionContext};
 pub use error::{SearchError, SearchResult};
 
 use index::SearchIndex;
 use query::{Query, QueryBuilder, SearchFilters};
 use ranking::SearchResults;
 use std::sync::Arc;
+use suggestions::{PopularQueries, RecentCases, SuggestOptions, Suggestion};
 use tokio::sync::RwLock;
 
+/// Queries remembered for popularity ranking of suggestions
+const MAX_TRACKED_QUERIES: usize = 10_000;
+
+/// Recently opened cases remembered per user
+const MAX_RECENT_CASES: usize = 20;
+
 /// Main search engine interface
 pub struct SearchEngine {
     index: Arc<RwLock<SearchIndex>>,
     config: SearchConfig,
+    popular_queries: Arc<PopularQueries>,
+    recent_cases: Arc<RecentCases>,
 }
 
 impl SearchEngine {
     /// Create a new search engine with the given configuration
     pub async fn new(config: SearchConfig) -> SearchResult<Self> {
-        let index = SearchIndex::new(&config).await?;
+        Self::with_analyzers(config, LanguageAnalyzers::default()).await
+    }
+
+    /// Create a new search engine with custom language analyzers
+    pub async fn with_analyzers(
+        config: SearchConfig,
+        analyzers: LanguageAnalyzers,
+    ) -> SearchResult<Self> {
+        let index = SearchIndex::with_analyzers(&config, analyzers).await?;
         Ok(Self {
             index: Arc::new(RwLock::new(index)),
             config,
+            popular_queries: Arc::new(PopularQueries::new(MAX_TRACKED_QUERIES)),
+            recent_cases: Arc::new(RecentCases::new(MAX_RECENT_CASES)),
         })
     }
 
@@ -61,6 +83,8 @@ impl SearchEngine {
         query: &Query,
         filters: Option<SearchFilters>,
     ) -> SearchResult<SearchResults> {
+        self.popular_queries.record(&query.text);
+
         let index = self.index.read().await;
         index.search(query, filters).await
     }
@@ -75,6 +99,39 @@ impl SearchEngine {
         index.suggest(prefix, limit).await
     }
 
+    /// Get typo-tolerant, ranked and categorized suggestions
+    pub async fn suggest_ranked(
+        &self,
+        prefix: &str,
+        options: &SuggestOptions,
+    ) -> SearchResult<Vec<Suggestion>> {
+        let index = self.index.read().await;
+        index
+            .suggest_ranked(prefix, options, &self.popular_queries)
+            .await
+    }
+
+    /// Get suggestions boosted by the cases the user opened recently
+    pub async fn suggest_for_user(
+        &self,
+        user_id: &str,
+        prefix: &str,
+        options: SuggestOptions,
+    ) -> SearchResult<Vec<Suggestion>> {
+        let options = options.context(self.recent_cases.context(user_id));
+        self.suggest_ranked(prefix, &options).await
+    }
+
+    /// Record that a user opened a case, for context-aware suggestions
+    pub fn record_case_opened(&self, user_id: &str, case_id: &str) {
+        self.recent_cases.record(user_id, case_id);
+    }
+
+    /// Query log used for suggestion popularity
+    pub fn popular_queries(&self) -> &PopularQueries {
+        &self.popular_queries
+    }
+
     /// Get facet counts for a field
     pub async fn facets(&self, field: &str) -> SearchResult<Vec<(String, u64)>> {
         let index = self.index.read().await;
@@ -147,4 +204,74 @@ mod tests {
         let results = engine.search(&query, None).await.unwrap();
         assert!(results.total > 0);
     }
+
+    #[tokio::test]
+    async fn test_search_engine_multilingual() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = SearchConfig::builder()
+            .index_path(temp_dir.path().to_path_buf())
+            .build()
+            .unwrap();
+        let engine = SearchEngine::new(config).await.unwrap();
+
+        engine.index_document("es1", serde_json::json!({
+            "title": "Informe del accidente",
+            "content": "Los vehículos colisionaron en la intersección durante la lluvia"
+        })).await.unwrap();
+        engine.index_document("fr1", serde_json::json!({
+            "title": "Rapport d'accident",
+            "content": "Les véhicules sont entrés en collision au carrefour sous la pluie"
+        })).await.unwrap();
+        engine.commit().await.unwrap();
+
+        // Unaccented, singular query matches the stemmed, accent-folded terms
+        let query = QueryBuilder::new()
+            .text("vehiculo interseccion")
+            .language(AnalyzerLanguage::Spanish)
+            .and_operator()
+            .build();
+        let results = engine.search(&query, None).await.unwrap();
+        assert_eq!(results.total, 1);
+
+        let query = QueryBuilder::new()
+            .text("vehicule carrefour")
+            .language(AnalyzerLanguage::French)
+            .and_operator()
+            .build();
+        let results = engine.search(&query, None).await.unwrap();
+        assert_eq!(results.total, 1);
+    }
+
+    #[tokio::test]
+    async fn test_suggest_for_user() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = SearchConfig::builder()
+            .index_path(temp_dir.path().to_path_buf())
+            .build()
+            .unwrap();
+        let engine = SearchEngine::new(config).await.unwrap();
+
+        engine.index_document("case-1", serde_json::json!({
+            "title": "Rear-end collision",
+            "created_by": "martinez",
+            "location": "Main Street"
+        })).await.unwrap();
+        engine.index_document("case-2", serde_json::json!({
+            "title": "Side impact",
+            "created_by": "martin",
+            "location": "Maple Avenue"
+        })).await.unwrap();
+        engine.commit().await.unwrap();
+
+        // Typo ("nartin") still finds both authors
+        let options = SuggestOptions::new(5).categories(vec![SuggestionCategory::Person]);
+        let suggestions = engine.suggest_ranked("nartin", &options).await.unwrap();
+        assert_eq!(suggestions.len(), 2);
+        assert!(suggestions.iter().all(|s| s.category == SuggestionCategory::Person));
+
+        // Opening case-1 ranks its author first
+        engine.record_case_opened("alice", "case-1");
+        let suggestions = engine.suggest_for_user("alice", "martin", options).await.unwrap();
+        assert_eq!(suggestions[0].text, "martinez");
+    }
 }
@@ -1,5 +1,6 @@
 //! Query builder for fluent query construction
 
+use crate::analysis::AnalyzerLanguage;
 use crate::query::{Query, QueryOperator};
 
 pub struct QueryBuilder {
@@ -53,6 +54,11 @@ impl QueryBuilder {
         self
     }
 
+    pub fn language(mut self, language: AnalyzerLanguage) -> Self {
+        self.query.language = Some(language);
+        self
+    }
+
     pub fn build(self) -> Query {
         self.query
     }
@@ -84,6 +90,13 @@ mod tests {
         assert!(query.fuzzy);
         assert_eq!(query.boost, Some(2.0));
         assert!(matches!(query.operator, QueryOperator::And));
+        assert!(query.language.is_none());
+
+        let query = QueryBuilder::new()
+            .text("colisión")
+            .language(AnalyzerLanguage::Spanish)
+            .build();
+        assert_eq!(query.language, Some(AnalyzerLanguage::Spanish));
     }
 
     #[test]
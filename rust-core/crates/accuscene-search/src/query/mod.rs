@@ -4,12 +4,14 @@ pub mod builder;
 pub mod filters;
 pub mod parser;
 
+use crate::analysis::{self, AnalyzerLanguage, LanguageDetector};
 use crate::config::SearchConfig;
 use crate::error::{SearchError, SearchResult};
 use crate::index::schema::IndexSchema;
 use crate::ranking::SearchResults;
 use serde::{Deserialize, Serialize};
 use tantivy::query::*;
+use tantivy::schema::Field;
 use tantivy::{Searcher, Term};
 
 pub use builder::QueryBuilder;
@@ -24,6 +26,9 @@ pub struct Query {
     pub fuzzy: bool,
     pub boost: Option<f32>,
     pub operator: QueryOperator,
+    /// Analyze the query text as this language; see [`QueryExecutor`]
+    #[serde(default)]
+    pub language: Option<AnalyzerLanguage>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,6 +45,7 @@ impl Default for Query {
             fuzzy: false,
             boost: None,
             operator: QueryOperator::Or,
+            language: None,
         }
     }
 }
@@ -134,6 +140,10 @@ impl<'a> QueryExecutor<'a> {
                 .collect()
         };
 
+        if let Some(language) = self.query_language(query) {
+            return self.build_analyzed_query(query, &fields, language);
+        }
+
         for field in fields {
             if query.fuzzy && self.config.enable_fuzzy {
                 // Fuzzy query
@@ -159,6 +169,69 @@ impl<'a> QueryExecutor<'a> {
         Ok(Box::new(BooleanQuery::new(sub_queries)))
     }
 
+    /// Language selected by the query, or detected if enabled
+    fn query_language(&self, query: &Query) -> Option<AnalyzerLanguage> {
+        let config = &self.config.language_config;
+        if query.language.is_some() || !config.detect_queries {
+            return query.language;
+        }
+
+        LanguageDetector::new(config.clone()).detect(&query.text)
+    }
+
+    /// Build a query searching the language's fields for the analyzed text
+    ///
+    /// The text is run through the analyzer of each target field, so stop
+    /// words are dropped and terms are stemmed the same way as the indexed
+    /// documents. A document matches if any field matches; within a field
+    /// the terms are combined with the query operator.
+    fn build_analyzed_query(
+        &self,
+        query: &Query,
+        fields: &[Field],
+        language: AnalyzerLanguage,
+    ) -> SearchResult<Box<dyn tantivy::query::Query>> {
+        let occur = match query.operator {
+            QueryOperator::And => Occur::Must,
+            QueryOperator::Or => Occur::Should,
+        };
+
+        let mut field_queries: Vec<(Occur, Box<dyn tantivy::query::Query>)> = Vec::new();
+
+        for &field in fields {
+            let field = self.schema.language_field(field, language);
+            let mut analyzer = self.searcher.index().tokenizer_for_field(field)?;
+
+            let term_queries: Vec<(Occur, Box<dyn tantivy::query::Query>)> =
+                analysis::analyze(&mut analyzer, &query.text)
+                    .into_iter()
+                    .map(|token| {
+                        let term = Term::from_field_text(field, &token);
+                        let term_query: Box<dyn tantivy::query::Query> =
+                            if query.fuzzy && self.config.enable_fuzzy {
+                                Box::new(FuzzyTermQuery::new(term, self.config.fuzzy_distance, true))
+                            } else {
+                                Box::new(TermQuery::new(
+                                    term,
+                                    tantivy::schema::IndexRecordOption::WithFreqs,
+                                ))
+                            };
+                        (occur, term_query)
+                    })
+                    .collect();
+
+            if !term_queries.is_empty() {
+                field_queries.push((Occur::Should, Box::new(BooleanQuery::new(term_queries))));
+            }
+        }
+
+        if field_queries.is_empty() {
+            return Ok(Box::new(AllQuery));
+        }
+
+        Ok(Box::new(BooleanQuery::new(field_queries)))
+    }
+
     fn build_filter_query(
         &self,
         filters: &SearchFilters,
@@ -1,5 +1,6 @@
 //! Query DSL parser
 
+use crate::analysis::AnalyzerLanguage;
 use crate::error::{SearchError, SearchResult};
 use crate::query::{Query, QueryOperator};
 use serde::{Deserialize, Serialize};
@@ -11,6 +12,8 @@ pub struct QueryDSL {
     pub fuzzy: Option<bool>,
     pub boost: Option<f32>,
     pub operator: Option<String>,
+    #[serde(default)]
+    pub language: Option<String>,
 }
 
 pub struct QueryParser;
@@ -29,12 +32,23 @@ impl QueryParser {
             }
         };
 
+        let language = dsl
+            .language
+            .as_deref()
+            .map(|code| {
+                AnalyzerLanguage::from_code(code).ok_or_else(|| {
+                    SearchError::QueryParseError(format!("Unsupported language: {}", code))
+                })
+            })
+            .transpose()?;
+
         Ok(Query {
             text: dsl.query,
             fields: dsl.fields.unwrap_or_default(),
             fuzzy: dsl.fuzzy.unwrap_or(false),
             boost: dsl.boost,
             operator,
+            language,
         })
     }
 
@@ -53,11 +67,15 @@ impl QueryParser {
         let mut fields = Vec::new();
         let mut fuzzy = false;
         let mut operator = QueryOperator::Or;
+        let mut language = None;
 
         let tokens: Vec<&str> = query_str.split_whitespace().collect();
 
         for token in tokens {
-            if token.contains(':') {
+            if let Some(code) = token.strip_prefix("lang:") {
+                // Analyzer selection: lang:es
+                language = AnalyzerLanguage::from_code(code);
+            } else if token.contains(':') {
                 // Field-specific query: field:value
                 let parts: Vec<&str> = token.splitn(2, ':').collect();
                 if parts.len() == 2 {
@@ -86,6 +104,7 @@ impl QueryParser {
             fuzzy,
             boost: None,
             operator,
+            language,
         })
     }
 
@@ -149,12 +168,32 @@ mod tests {
             fuzzy: Some(true),
             boost: Some(2.0),
             operator: Some("AND".to_string()),
+            language: Some("es".to_string()),
         };
 
         let query = QueryParser::parse(dsl).unwrap();
         assert_eq!(query.text, "search query");
         assert!(query.fuzzy);
         assert_eq!(query.boost, Some(2.0));
+        assert_eq!(query.language, Some(AnalyzerLanguage::Spanish));
+    }
+
+    #[test]
+    fn test_parse_language() {
+        let query = QueryParser::parse_lucene("lang:fr conducteur").unwrap();
+        assert_eq!(query.language, Some(AnalyzerLanguage::French));
+        assert_eq!(query.text, "conducteur");
+        assert!(query.fields.is_empty());
+
+        let dsl = QueryDSL {
+            query: "vehicle".to_string(),
+            fields: None,
+            fuzzy: None,
+            boost: None,
+            operator: None,
+            language: Some("klingon".to_string()),
+        };
+        assert!(QueryParser::parse(dsl).is_err());
     }
 
     #[test]
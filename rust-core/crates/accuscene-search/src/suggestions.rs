@@ -1,18 +1,150 @@
 //! Auto-complete and query suggestions
+//!
+//! Auto-complete is typo tolerant: the typed prefix is compared with the
+//! start of each indexed term using an edit distance in which substituting a
+//! neighbouring key on a QWERTY keyboard costs half an edit. Candidates are
+//! ranked by similarity, popularity (document frequency and query log),
+//! recency of use and the user's context (recently opened cases, and the
+//! people and locations on them), and tagged with the category of the field
+//! they were found in.
 
 use crate::error::{SearchError, SearchResult};
 use crate::index::schema::IndexSchema;
-use std::collections::HashSet;
-use tantivy::Searcher;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+use tantivy::collector::TopDocs;
+use tantivy::query::TermQuery;
+use tantivy::schema::{Field, IndexRecordOption, Value};
+use tantivy::{Searcher, TantivyDocument, Term};
+
+/// Cost of substituting a key with a neighbouring key
+const ADJACENT_KEY_COST: f32 = 0.5;
+
+/// Number of recent cases whose people and locations boost suggestions
+const MAX_CONTEXT_CASES: usize = 10;
+
+/// QWERTY rows; each key sits between the two keys below it
+const KEYBOARD_ROWS: [&str; 4] = ["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+/// What a suggestion refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionCategory {
+    /// Case ID
+    Case,
+    /// Case author
+    Person,
+    /// Location term
+    Location,
+    /// Full-text term
+    Term,
+}
+
+/// Ranked auto-complete suggestion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Suggestion {
+    pub text: String,
+    pub category: SuggestionCategory,
+    pub score: f32,
+    /// Weighted edit distance between the typed prefix and the suggestion
+    pub distance: f32,
+}
+
+/// Weights of the ranking signals
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankingWeights {
+    /// Similarity to the typed prefix (0-1)
+    pub similarity: f32,
+    /// Log of document frequency plus query count
+    pub popularity: f32,
+    /// Decays with hours since the suggestion was last searched (0-1)
+    pub recency: f32,
+    /// Relation to the user's recent cases (0-1)
+    pub context: f32,
+}
+
+impl Default for RankingWeights {
+    fn default() -> Self {
+        Self {
+            similarity: 10.0,
+            popularity: 1.0,
+            recency: 2.0,
+            context: 5.0,
+        }
+    }
+}
+
+/// Per-user context for suggestions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SuggestionContext {
+    /// Recently opened case IDs, most recent first
+    pub recent_cases: Vec<String>,
+}
+
+/// Auto-complete options
+#[derive(Debug, Clone)]
+pub struct SuggestOptions {
+    pub limit: usize,
+    /// Maximum weighted edit distance; by default grows with the prefix length
+    pub max_typos: Option<f32>,
+    /// Categories to suggest; empty for all
+    pub categories: Vec<SuggestionCategory>,
+    pub context: SuggestionContext,
+    pub weights: RankingWeights,
+}
+
+impl SuggestOptions {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            max_typos: None,
+            categories: Vec::new(),
+            context: SuggestionContext::default(),
+            weights: RankingWeights::default(),
+        }
+    }
+
+    pub fn max_typos(mut self, max_typos: f32) -> Self {
+        self.max_typos = Some(max_typos);
+        self
+    }
+
+    pub fn categories(mut self, categories: Vec<SuggestionCategory>) -> Self {
+        self.categories = categories;
+        self
+    }
+
+    pub fn context(mut self, context: SuggestionContext) -> Self {
+        self.context = context;
+        self
+    }
+
+    pub fn weights(mut self, weights: RankingWeights) -> Self {
+        self.weights = weights;
+        self
+    }
+}
 
 pub struct SuggestionEngine<'a> {
     searcher: Searcher,
     schema: &'a IndexSchema,
+    popular: Option<&'a PopularQueries>,
 }
 
 impl<'a> SuggestionEngine<'a> {
     pub fn new(searcher: Searcher, schema: &'a IndexSchema) -> Self {
-        Self { searcher, schema }
+        Self {
+            searcher,
+            schema,
+            popular: None,
+        }
+    }
+
+    /// Use the query log for the popularity and recency signals
+    pub fn with_popular_queries(mut self, popular: &'a PopularQueries) -> Self {
+        self.popular = Some(popular);
+        self
     }
 
     /// Generate auto-complete suggestions
@@ -21,83 +153,164 @@ impl<'a> SuggestionEngine<'a> {
         prefix: &str,
         limit: usize,
     ) -> SearchResult<Vec<String>> {
+        let suggestions = self.suggest_ranked(prefix, &SuggestOptions::new(limit)).await?;
+        Ok(suggestions.into_iter().map(|s| s.text).collect())
+    }
+
+    /// Generate typo-tolerant, ranked and categorized suggestions
+    pub async fn suggest_ranked(
+        &self,
+        prefix: &str,
+        options: &SuggestOptions,
+    ) -> SearchResult<Vec<Suggestion>> {
+        let prefix = prefix.trim().to_lowercase();
         if prefix.is_empty() {
             return Ok(Vec::new());
         }
 
-        let prefix_lower = prefix.to_lowercase();
-        let mut suggestions = HashSet::new();
+        let max_typos = options.max_typos.unwrap_or_else(|| default_max_typos(&prefix));
+        let context = self.context_signals(&options.context)?;
+        let mut best: HashMap<(SuggestionCategory, String), Suggestion> = HashMap::new();
 
-        // Search across text fields
-        for field in self.schema.text_fields() {
-            let field_suggestions = self
-                .suggest_from_field(field, &prefix_lower, limit * 2)
-                .await?;
+        for (field, category) in self.sources() {
+            if !options.categories.is_empty() && !options.categories.contains(&category) {
+                continue;
+            }
 
-            suggestions.extend(field_suggestions);
+            for (term, doc_freq) in self.field_terms(field)? {
+                let normalized = term.to_lowercase();
+                let Some(distance) = prefix_edit_distance(&prefix, &normalized, max_typos) else {
+                    continue;
+                };
+
+                let (popularity, recency) = self.usage_signals(&normalized, doc_freq);
+                let signals = Signals {
+                    similarity: similarity(&prefix, &normalized, distance),
+                    popularity,
+                    recency,
+                    context: context
+                        .get(&(category, normalized.clone()))
+                        .copied()
+                        .unwrap_or(0.0),
+                };
+                let score = signals.score(&options.weights);
+
+                let key = (category, normalized);
+                if best.get(&key).is_none_or(|existing| existing.score < score) {
+                    best.insert(
+                        key,
+                        Suggestion {
+                            text: term,
+                            category,
+                            score,
+                            distance,
+                        },
+                    );
+                }
+            }
         }
 
-        // Convert to sorted vector
-        let mut results: Vec<String> = suggestions.into_iter().collect();
-
-        // Rank by relevance
-        results.sort_by(|a, b| {
-            let a_score = self.relevance_score(a, &prefix_lower);
-            let b_score = self.relevance_score(b, &prefix_lower);
-            b_score.partial_cmp(&a_score).unwrap_or(std::cmp::Ordering::Equal)
+        let mut suggestions: Vec<Suggestion> = best.into_values().collect();
+        suggestions.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.text.cmp(&b.text))
         });
+        suggestions.truncate(options.limit);
 
-        results.truncate(limit);
+        Ok(suggestions)
+    }
 
-        Ok(results)
+    /// Fields suggestions are drawn from
+    fn sources(&self) -> Vec<(Field, SuggestionCategory)> {
+        vec![
+            (self.schema.id, SuggestionCategory::Case),
+            (self.schema.created_by, SuggestionCategory::Person),
+            (self.schema.location, SuggestionCategory::Location),
+            (self.schema.title, SuggestionCategory::Term),
+            (self.schema.content, SuggestionCategory::Term),
+            (self.schema.description, SuggestionCategory::Term),
+        ]
     }
 
-    async fn suggest_from_field(
-        &self,
-        field: tantivy::schema::Field,
-        prefix: &str,
-        limit: usize,
-    ) -> SearchResult<Vec<String>> {
-        let mut suggestions = Vec::new();
+    /// All terms of a field with their document frequency across segments
+    fn field_terms(&self, field: Field) -> SearchResult<HashMap<String, u64>> {
+        let mut terms = HashMap::new();
 
         for segment_reader in self.searcher.segment_readers() {
             let inv_index = segment_reader.inverted_index(field)?;
             let mut stream = inv_index.terms().stream()?;
 
-            while let Some(term) = stream.next() {
-                let (term_bytes, _) = term?;
-                let term_str = std::str::from_utf8(term_bytes)
+            while stream.advance() {
+                let term_str = std::str::from_utf8(stream.key())
                     .map_err(|e| SearchError::IndexError(e.to_string()))?;
+                *terms.entry(term_str.to_string()).or_insert(0) += u64::from(stream.value().doc_freq);
+            }
+        }
 
-                if term_str.to_lowercase().starts_with(prefix) {
-                    suggestions.push(term_str.to_string());
+        Ok(terms)
+    }
 
-                    if suggestions.len() >= limit {
-                        break;
+    /// Context boost per (category, lowercased term)
+    ///
+    /// Recent cases boost their own ID and the people and locations on them,
+    /// more recent cases more strongly.
+    fn context_signals(
+        &self,
+        context: &SuggestionContext,
+    ) -> SearchResult<HashMap<(SuggestionCategory, String), f32>> {
+        let mut signals = HashMap::new();
+
+        for (rank, case_id) in context.recent_cases.iter().take(MAX_CONTEXT_CASES).enumerate() {
+            let boost = 1.0 / (1.0 + rank as f32);
+            let mut add = |category, text: String| {
+                let entry = signals.entry((category, text)).or_insert(0.0f32);
+                *entry = entry.max(boost);
+            };
+
+            add(SuggestionCategory::Case, case_id.to_lowercase());
+
+            let query = TermQuery::new(
+                Term::from_field_text(self.schema.id, case_id),
+                IndexRecordOption::Basic,
+            );
+            let Some((_, address)) = self
+                .searcher
+                .search(&query, &TopDocs::with_limit(1))?
+                .into_iter()
+                .next()
+            else {
+                continue;
+            };
+
+            let doc: TantivyDocument = self.searcher.doc(address)?;
+            for value in doc.get_all(self.schema.created_by) {
+                if let Some(person) = value.as_str() {
+                    add(SuggestionCategory::Person, person.to_lowercase());
+                }
+            }
+            for value in doc.get_all(self.schema.location) {
+                if let Some(location) = value.as_str() {
+                    for word in location.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()) {
+                        add(SuggestionCategory::Location, word.to_lowercase());
                     }
                 }
             }
         }
 
-        Ok(suggestions)
+        Ok(signals)
     }
 
-    fn relevance_score(&self, suggestion: &str, prefix: &str) -> f32 {
-        let mut score = 0.0;
-
-        // Exact prefix match
-        if suggestion.to_lowercase().starts_with(prefix) {
-            score += 10.0;
-        }
-
-        // Length penalty (prefer shorter suggestions)
-        score -= suggestion.len() as f32 * 0.1;
-
-        // Fuzzy match boost
-        let distance = strsim::levenshtein(&suggestion.to_lowercase(), prefix);
-        score -= distance as f32;
+    /// Popularity and recency signals of a term
+    fn usage_signals(&self, term: &str, doc_freq: u64) -> (f32, f32) {
+        let (query_count, recency) = self
+            .popular
+            .and_then(|popular| popular.usage(term))
+            .map(|(count, elapsed)| (count, 1.0 / (1.0 + elapsed.as_secs_f32() / 3600.0)))
+            .unwrap_or((0, 0.0));
 
-        score
+        (((1 + doc_freq + query_count) as f32).ln(), recency)
     }
 
     /// Get suggestions with fuzzy matching
@@ -177,6 +390,169 @@ impl<'a> SuggestionEngine<'a> {
     }
 }
 
+/// Ranking signals of a candidate
+struct Signals {
+    similarity: f32,
+    popularity: f32,
+    recency: f32,
+    context: f32,
+}
+
+impl Signals {
+    fn score(&self, weights: &RankingWeights) -> f32 {
+        weights.similarity * self.similarity
+            + weights.popularity * self.popularity
+            + weights.recency * self.recency
+            + weights.context * self.context
+    }
+}
+
+/// Similarity of a term to the typed prefix, preferring shorter completions
+fn similarity(prefix: &str, term: &str, distance: f32) -> f32 {
+    let prefix_len = prefix.chars().count() as f32;
+    let remaining = (term.chars().count() as f32 - prefix_len).max(0.0);
+    (1.0 - distance / prefix_len).max(0.0) - remaining * 0.01
+}
+
+/// Maximum weighted edit distance allowed for a prefix of this length
+fn default_max_typos(prefix: &str) -> f32 {
+    match prefix.chars().count() {
+        0..=2 => 0.0,
+        3..=5 => 1.0,
+        _ => 2.0,
+    }
+}
+
+/// Position of a key on the keyboard
+fn key_position(c: char) -> Option<(usize, usize)> {
+    KEYBOARD_ROWS
+        .iter()
+        .enumerate()
+        .find_map(|(row, keys)| keys.find(c).map(|col| (row, col)))
+}
+
+/// Whether two keys are neighbours on a QWERTY keyboard
+pub fn keyboard_adjacent(a: char, b: char) -> bool {
+    let (Some((row_a, col_a)), Some((row_b, col_b))) = (key_position(a), key_position(b)) else {
+        return false;
+    };
+
+    if row_a == row_b {
+        return col_a.abs_diff(col_b) == 1;
+    }
+
+    // Each key touches the key below it and the one below-left
+    let (upper_col, lower_col) = if row_a + 1 == row_b {
+        (col_a, col_b)
+    } else if row_b + 1 == row_a {
+        (col_b, col_a)
+    } else {
+        return false;
+    };
+
+    upper_col == lower_col || upper_col == lower_col + 1
+}
+
+fn substitution_cost(a: char, b: char) -> f32 {
+    if a == b {
+        0.0
+    } else if keyboard_adjacent(a, b) {
+        ADJACENT_KEY_COST
+    } else {
+        1.0
+    }
+}
+
+/// Weighted edit distance between `prefix` and the closest prefix of `candidate`
+///
+/// Insertions, deletions and transpositions cost one edit, substitutions of
+/// neighbouring keys half an edit. Returns `None` if the distance exceeds
+/// `max_distance`.
+pub fn prefix_edit_distance(prefix: &str, candidate: &str, max_distance: f32) -> Option<f32> {
+    let prefix: Vec<char> = prefix.chars().collect();
+    let candidate: Vec<char> = candidate.chars().collect();
+    let width = candidate.len() + 1;
+
+    // rows[i][j]: distance between prefix[..i] and candidate[..j]
+    let mut rows = vec![vec![0.0f32; width]; prefix.len() + 1];
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j as f32;
+    }
+
+    for i in 1..=prefix.len() {
+        rows[i][0] = i as f32;
+        for j in 1..width {
+            let mut cost = (rows[i - 1][j] + 1.0)
+                .min(rows[i][j - 1] + 1.0)
+                .min(rows[i - 1][j - 1] + substitution_cost(prefix[i - 1], candidate[j - 1]));
+
+            if i > 1
+                && j > 1
+                && prefix[i - 1] == candidate[j - 2]
+                && prefix[i - 2] == candidate[j - 1]
+            {
+                cost = cost.min(rows[i - 2][j - 2] + 1.0);
+            }
+
+            rows[i][j] = cost;
+        }
+
+        // No completion can get closer once every cell exceeds the budget
+        if rows[i].iter().all(|&cost| cost > max_distance) {
+            return None;
+        }
+    }
+
+    let distance = rows[prefix.len()]
+        .iter()
+        .copied()
+        .fold(f32::INFINITY, f32::min);
+
+    (distance <= max_distance).then_some(distance)
+}
+
+/// Recently opened cases per user, for context-aware suggestions
+pub struct RecentCases {
+    users: dashmap::DashMap<String, VecDeque<String>>,
+    max_per_user: usize,
+}
+
+impl RecentCases {
+    pub fn new(max_per_user: usize) -> Self {
+        Self {
+            users: dashmap::DashMap::new(),
+            max_per_user,
+        }
+    }
+
+    /// Record that a user opened a case
+    pub fn record(&self, user_id: &str, case_id: &str) {
+        let mut cases = self.users.entry(user_id.to_string()).or_default();
+        cases.retain(|id| id != case_id);
+        cases.push_front(case_id.to_string());
+        cases.truncate(self.max_per_user);
+    }
+
+    /// Recently opened cases of a user, most recent first
+    pub fn recent(&self, user_id: &str) -> Vec<String> {
+        self.users
+            .get(user_id)
+            .map(|cases| cases.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Suggestion context of a user
+    pub fn context(&self, user_id: &str) -> SuggestionContext {
+        SuggestionContext {
+            recent_cases: self.recent(user_id),
+        }
+    }
+
+    pub fn clear(&self, user_id: &str) {
+        self.users.remove(user_id);
+    }
+}
+
 /// Popular query tracker for trending suggestions
 pub struct PopularQueries {
     queries: dashmap::DashMap<String, QueryStats>,
@@ -222,6 +598,14 @@ impl PopularQueries {
         }
     }
 
+    /// Times a query was recorded and time since it was last recorded
+    pub fn usage(&self, query: &str) -> Option<(u64, Duration)> {
+        let normalized = query.to_lowercase().trim().to_string();
+        self.queries
+            .get(&normalized)
+            .map(|stats| (stats.count, stats.last_used.elapsed()))
+    }
+
     /// Get top N popular queries
     pub fn top(&self, n: usize) -> Vec<(String, u64)> {
         let mut queries: Vec<(String, u64)> = self
@@ -311,5 +695,49 @@ mod tests {
 
         let trending = tracker.trending(2);
         assert_eq!(trending.len(), 2);
+
+        let (count, _) = tracker.usage("Query2").unwrap();
+        assert_eq!(count, 2);
+        assert!(tracker.usage("query3").is_none());
+    }
+
+    #[test]
+    fn test_keyboard_adjacency() {
+        assert!(keyboard_adjacent('a', 's'));
+        assert!(keyboard_adjacent('w', 'a'));
+        assert!(keyboard_adjacent('s', 'z'));
+        assert!(keyboard_adjacent('b', 'h'));
+        assert!(!keyboard_adjacent('a', 'l'));
+        assert!(!keyboard_adjacent('q', 'z'));
+        assert!(!keyboard_adjacent('a', '-'));
+    }
+
+    #[test]
+    fn test_prefix_edit_distance() {
+        assert_eq!(prefix_edit_distance("inter", "intersection", 0.0), Some(0.0));
+        // Neighbouring key typo
+        assert_eq!(prefix_edit_distance("intet", "intersection", 1.0), Some(0.5));
+        // Distant key typo
+        assert_eq!(prefix_edit_distance("intez", "intersection", 1.0), Some(1.0));
+        // Transposition
+        assert_eq!(prefix_edit_distance("itner", "intersection", 1.0), Some(1.0));
+        // Missing letter
+        assert_eq!(prefix_edit_distance("intrse", "intersection", 1.0), Some(1.0));
+        assert_eq!(prefix_edit_distance("xyzzy", "intersection", 2.0), None);
+    }
+
+    #[test]
+    fn test_recent_cases() {
+        let recent = RecentCases::new(2);
+
+        recent.record("alice", "case-1");
+        recent.record("alice", "case-2");
+        recent.record("alice", "case-1");
+        recent.record("alice", "case-3");
+        recent.record("bob", "case-9");
+
+        assert_eq!(recent.recent("alice"), vec!["case-3", "case-1"]);
+        assert_eq!(recent.context("bob").recent_cases, vec!["case-9"]);
+        assert!(recent.recent("carol").is_empty());
     }
 }
@@ -128,6 +128,9 @@ pub enum EventType {
     AuthSessionCreated,
     AuthSessionExpired,
     AuthSessionInvalidated,
+    AuthApiKeyCreated,
+    AuthApiKeyRotated,
+    AuthApiKeyRevoked,
 
     // Authorization events
     AuthzPermissionCheck,
@@ -136,6 +139,22 @@ pub enum EventType {
     AuthzRoleAssigned,
     AuthzRoleRevoked,
     AuthzPolicyEvaluated,
+    AuthzCrossTenantAccess,
+
+    // User administration events
+    UserCreated,
+    UserDisabled,
+    UserEnabled,
+    UserMfaReset,
+
+    // Provisioning events
+    ProvisioningUserCreated,
+    ProvisioningUserUpdated,
+    ProvisioningUserDeactivated,
+    ProvisioningUserReactivated,
+    ProvisioningGroupCreated,
+    ProvisioningGroupUpdated,
+    ProvisioningGroupDeleted,
 
     // Data access events
     DataRead,
@@ -143,6 +162,18 @@ pub enum EventType {
     DataModified,
     DataDeleted,
     DataExported,
+    DataArchived,
+    DataRestored,
+
+    // Data subject request events
+    DsarReceived,
+    DsarDataLocated,
+    DsarExported,
+    DsarRecordErased,
+    DsarErasureWithheld,
+    DsarCompleted,
+    LegalHoldPlaced,
+    LegalHoldReleased,
 
     // Case events
     CaseCreated,
@@ -175,6 +206,8 @@ pub enum EventType {
     SecurityThreatDetected,
     SecurityRateLimitExceeded,
     SecurityBruteForceDetected,
+    SecurityLockout,
+    SecurityLockoutReleased,
     SecurityAnomalyDetected,
     SecurityPolicyViolation,
 
@@ -1,6 +1,8 @@
 //! Audit system
 //!
-//! Comprehensive audit logging with tamper detection and querying.
+//! Comprehensive audit logging with tamper detection and querying. Events
+//! are hash-chained into the trail, sealed by signed Merkle checkpoints and
+//! can be anchored externally; see [`trail`].
 
 pub mod event;
 pub mod logger;
@@ -14,9 +16,10 @@ pub use event::{
 pub use logger::{AuditHandler, AuditLogger, ConsoleHandler, FileHandler};
 pub use query::{AuditQuery, QueryResult, SortField, SortOrder};
 pub use storage::{AuditStorage, FileStorage, StorageConfig};
-pub use trail::{AuditTrail, MerkleTree, TrailEntry};
+pub use trail::{AnchorRecord, AuditTrail, Checkpoint, MerkleProof, MerkleTree, TrailEntry};
 
 use crate::error::Result;
+use accuscene_crypto::asymmetric::Ed25519Signer;
 use std::sync::Arc;
 
 /// Complete audit service combining all components
@@ -24,6 +27,8 @@ pub struct AuditService {
     logger: Arc<AuditLogger>,
     storage: Arc<AuditStorage>,
     trail: Arc<tokio::sync::RwLock<AuditTrail>>,
+    signer: Option<Arc<Ed25519Signer>>,
+    checkpoint_every: Option<usize>,
 }
 
 impl AuditService {
@@ -33,9 +38,23 @@ impl AuditService {
             logger: Arc::new(AuditLogger::new()),
             storage: Arc::new(AuditStorage::new(storage_config)),
             trail: Arc::new(tokio::sync::RwLock::new(AuditTrail::new())),
+            signer: None,
+            checkpoint_every: None,
         }
     }
 
+    /// Sign trail checkpoints with an Ed25519 key
+    pub fn with_signer(mut self, signer: Ed25519Signer) -> Self {
+        self.signer = Some(Arc::new(signer));
+        self
+    }
+
+    /// Take a checkpoint whenever this many entries are pending
+    pub fn with_checkpoint_every(mut self, entries: usize) -> Self {
+        self.checkpoint_every = Some(entries.max(1));
+        self
+    }
+
     /// Get the logger
     pub fn logger(&self) -> Arc<AuditLogger> {
         Arc::clone(&self.logger)
@@ -63,9 +82,62 @@ impl AuditService {
         let mut trail = self.trail.write().await;
         trail.add_event(event)?;
 
+        if self
+            .checkpoint_every
+            .is_some_and(|every| trail.pending() >= every)
+        {
+            trail.checkpoint(self.signer.as_deref())?;
+        }
+
         Ok(())
     }
 
+    /// Seal the events added since the last checkpoint
+    pub async fn checkpoint(&self) -> Result<Option<Checkpoint>> {
+        let mut trail = self.trail.write().await;
+        trail.checkpoint(self.signer.as_deref())
+    }
+
+    /// Run [`checkpoint`](Self::checkpoint) periodically
+    pub fn spawn_checkpoint_task(self: Arc<Self>, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match self.checkpoint().await {
+                    Ok(Some(checkpoint)) => tracing::debug!(
+                        "Audit checkpoint {} sealed entries {}-{}",
+                        checkpoint.index,
+                        checkpoint.first_sequence,
+                        checkpoint.last_sequence
+                    ),
+                    Ok(None) => {}
+                    Err(e) => tracing::error!("Audit checkpoint failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// List trail checkpoints
+    pub async fn checkpoints(&self) -> Vec<Checkpoint> {
+        self.trail.read().await.checkpoints().to_vec()
+    }
+
+    /// Export the latest checkpoint for external notarization
+    pub async fn export_anchor(&self) -> Option<AnchorRecord> {
+        self.trail.read().await.anchor()
+    }
+
+    /// Check that a previously exported anchor still matches the trail
+    pub async fn verify_anchor(&self, anchor: &AnchorRecord) -> Result<()> {
+        self.trail.read().await.verify_anchor(anchor)
+    }
+
+    /// Prove that the entry with a sequence number is covered by a checkpoint
+    pub async fn inclusion_proof(&self, sequence: u64) -> Option<(Checkpoint, MerkleProof)> {
+        self.trail.read().await.inclusion_proof(sequence)
+    }
+
     /// Query audit logs
     pub async fn query(&self, query: AuditQuery) -> Result<QueryResult> {
         let events = self.storage.get_all().await?;
@@ -84,6 +156,8 @@ impl AuditService {
         TrailStats {
             entry_count: trail.len(),
             last_hash: trail.last_hash().map(|s| s.to_string()),
+            checkpoint_count: trail.checkpoints().len(),
+            pending_entries: trail.pending(),
         }
     }
 
@@ -112,6 +186,10 @@ pub struct TrailStats {
     pub entry_count: usize,
     /// Last hash in the chain
     pub last_hash: Option<String>,
+    /// Number of checkpoints
+    pub checkpoint_count: usize,
+    /// Entries not yet covered by a checkpoint
+    pub pending_entries: usize,
 }
 
 #[cfg(test)]
@@ -195,4 +273,34 @@ mod tests {
         let exported = service.export_trail().await.unwrap();
         assert!(!exported.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_signed_checkpoints_and_anchor() {
+        let signer = Ed25519Signer::generate().unwrap();
+        let public_key = signer.public_key().clone();
+        let service = AuditService::default()
+            .with_signer(signer)
+            .with_checkpoint_every(3);
+
+        for i in 0..7 {
+            let event = AuditEvent::new(EventType::AuthLogin, format!("login-{}", i));
+            service.audit(event).await.unwrap();
+        }
+
+        let stats = service.trail_stats().await;
+        assert_eq!((stats.checkpoint_count, stats.pending_entries), (2, 1));
+        assert!(service.checkpoint().await.unwrap().is_some());
+
+        let checkpoints = service.checkpoints().await;
+        assert!(checkpoints.iter().all(|c| c.is_signed_by(&public_key)));
+
+        let (checkpoint, proof) = service.inclusion_proof(4).await.unwrap();
+        assert_eq!(checkpoint.index, 1);
+        assert!(proof.verify(&checkpoint.merkle_root));
+
+        let anchor = service.export_anchor().await.unwrap();
+        assert_eq!(anchor.checkpoint.last_sequence, 6);
+        assert!(service.verify_anchor(&anchor).await.is_ok());
+        assert!(service.verify_trail().await.is_ok());
+    }
 }
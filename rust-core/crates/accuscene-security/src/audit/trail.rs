@@ -1,16 +1,42 @@
 //! Audit trail with tamper detection
 //!
-//! Implements cryptographically secured audit trail using hash chains.
+//! Every entry commits to its sequence number and the previous entry's hash,
+//! so editing, removing or reordering an entry breaks the chain. Checkpoints
+//! periodically compute a Merkle root over the entries added since the last
+//! checkpoint, commit to the previous checkpoint and are signed with Ed25519.
+//!
+//! A checkpoint exported as an [`AnchorRecord`] can be notarized outside the
+//! system (a timestamping authority, a transparency log). The notarized
+//! digest pins the trail as of that checkpoint, even against someone able to
+//! rewrite the trail and re-sign it with the local key.
 
 use crate::audit::event::AuditEvent;
 use crate::error::{Result, SecurityError};
+use accuscene_crypto::asymmetric::{verify_signature, Ed25519PublicKey, Ed25519Signer, Signature};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use sha2::{Digest, Sha256};
 
+/// Domain separation of entry hashes
+const ENTRY_DOMAIN: &[u8] = b"accuscene-audit-entry/1";
+
+/// Domain separation of checkpoint digests
+const CHECKPOINT_DOMAIN: &[u8] = b"accuscene-audit-checkpoint/1";
+
+/// Domain separation prefix of Merkle leaf hashes
+const LEAF_PREFIX: u8 = 0x00;
+
+/// Domain separation prefix of Merkle interior node hashes
+const NODE_PREFIX: u8 = 0x01;
+
+/// Format identifier of exported anchors
+pub const ANCHOR_FORMAT: &str = "accuscene-audit-anchor/1";
+
 /// Audit trail with tamper detection
 pub struct AuditTrail {
     entries: Vec<TrailEntry>,
     last_hash: Option<String>,
+    checkpoints: Vec<Checkpoint>,
 }
 
 impl AuditTrail {
@@ -19,12 +45,13 @@ impl AuditTrail {
         Self {
             entries: Vec::new(),
             last_hash: None,
+            checkpoints: Vec::new(),
         }
     }
 
     /// Add an event to the trail
     pub fn add_event(&mut self, event: AuditEvent) -> Result<String> {
-        let entry = TrailEntry::new(event, self.last_hash.clone());
+        let entry = TrailEntry::new(event, self.last_hash.clone(), self.entries.len() as u64);
         let entry_hash = entry.hash.clone();
 
         self.entries.push(entry);
@@ -33,13 +60,13 @@ impl AuditTrail {
         Ok(entry_hash)
     }
 
-    /// Verify trail integrity
+    /// Verify trail integrity, including checkpoints and their signatures
     pub fn verify_integrity(&self) -> Result<()> {
         let mut expected_hash: Option<String> = None;
 
         for (i, entry) in self.entries.iter().enumerate() {
             // Check if previous hash matches
-            if entry.previous_hash != expected_hash {
+            if entry.previous_hash != expected_hash || entry.sequence != i as u64 {
                 return Err(SecurityError::AuditTrailCompromised(format!(
                     "Hash chain broken at entry {}",
                     i
@@ -57,7 +84,90 @@ impl AuditTrail {
             expected_hash = Some(entry.hash.clone());
         }
 
-        Ok(())
+        self.verify_checkpoints()
+    }
+
+    /// Seal the entries added since the last checkpoint
+    ///
+    /// Returns `None` if there is nothing new to seal.
+    pub fn checkpoint(&mut self, signer: Option<&Ed25519Signer>) -> Result<Option<Checkpoint>> {
+        let first = self.next_checkpoint_start();
+        if first >= self.entries.len() {
+            return Ok(None);
+        }
+
+        let covered = &self.entries[first..];
+        let tree = MerkleTree::from_hashes(covered.iter().map(|e| e.hash.clone()).collect());
+        let mut checkpoint = Checkpoint {
+            index: self.checkpoints.len() as u64,
+            first_sequence: first as u64,
+            last_sequence: (self.entries.len() - 1) as u64,
+            merkle_root: tree.root().unwrap_or_default().to_string(),
+            head_hash: self.last_hash.clone().unwrap_or_default(),
+            previous_digest: self.checkpoints.last().map(Checkpoint::digest),
+            created_at: chrono::Utc::now(),
+            signature: None,
+            public_key: None,
+        };
+
+        if let Some(signer) = signer {
+            let signature = signer
+                .sign(checkpoint.digest().as_bytes())
+                .map_err(|e| SecurityError::Internal(format!("Signing checkpoint failed: {}", e)))?;
+            checkpoint.signature = Some(signature.to_base64());
+            checkpoint.public_key = Some(signer.public_key().to_base64());
+        }
+
+        self.checkpoints.push(checkpoint.clone());
+        Ok(Some(checkpoint))
+    }
+
+    /// Get all checkpoints
+    pub fn checkpoints(&self) -> &[Checkpoint] {
+        &self.checkpoints
+    }
+
+    /// Number of entries not yet covered by a checkpoint
+    pub fn pending(&self) -> usize {
+        self.entries.len() - self.next_checkpoint_start()
+    }
+
+    /// Prove that an entry is covered by a checkpoint's Merkle root
+    pub fn inclusion_proof(&self, sequence: u64) -> Option<(Checkpoint, MerkleProof)> {
+        let checkpoint = self
+            .checkpoints
+            .iter()
+            .find(|c| (c.first_sequence..=c.last_sequence).contains(&sequence))?;
+
+        let covered = &self.entries
+            [checkpoint.first_sequence as usize..=checkpoint.last_sequence as usize];
+        let tree = MerkleTree::from_hashes(covered.iter().map(|e| e.hash.clone()).collect());
+        let proof = tree.proof((sequence - checkpoint.first_sequence) as usize)?;
+
+        Some((checkpoint.clone(), proof))
+    }
+
+    /// Export the latest checkpoint for external notarization
+    pub fn anchor(&self) -> Option<AnchorRecord> {
+        self.checkpoints.last().map(AnchorRecord::new)
+    }
+
+    /// Check that a previously exported anchor still matches the trail
+    pub fn verify_anchor(&self, anchor: &AnchorRecord) -> Result<()> {
+        anchor.verify()?;
+
+        let matches = self
+            .checkpoints
+            .get(anchor.checkpoint.index as usize)
+            .is_some_and(|checkpoint| checkpoint.digest() == anchor.digest);
+        if !matches {
+            return Err(SecurityError::AuditTrailCompromised(format!(
+                "Checkpoint {} no longer matches its anchor",
+                anchor.checkpoint.index
+            )));
+        }
+
+        self.verify_integrity()
     }
 
     /// Get all entries
@@ -82,20 +192,25 @@ impl AuditTrail {
 
     /// Export trail for archival
     pub fn export(&self) -> Result<Vec<u8>> {
-        serde_json::to_vec(&self.entries)
+        let archive = TrailArchive {
+            entries: self.entries.clone(),
+            checkpoints: self.checkpoints.clone(),
+        };
+        serde_json::to_vec(&archive)
             .map_err(|e| SecurityError::Internal(format!("Export failed: {}", e)))
     }
 
     /// Import trail from archive
     pub fn import(data: &[u8]) -> Result<Self> {
-        let entries: Vec<TrailEntry> = serde_json::from_slice(data)
+        let archive: TrailArchive = serde_json::from_slice(data)
             .map_err(|e| SecurityError::Internal(format!("Import failed: {}", e)))?;
 
-        let last_hash = entries.last().map(|e| e.hash.clone());
+        let last_hash = archive.entries.last().map(|e| e.hash.clone());
 
         let trail = Self {
-            entries,
+            entries: archive.entries,
             last_hash,
+            checkpoints: archive.checkpoints,
         };
 
         // Verify imported trail
@@ -103,6 +218,50 @@ impl AuditTrail {
 
         Ok(trail)
     }
+
+    fn next_checkpoint_start(&self) -> usize {
+        self.checkpoints
+            .last()
+            .map_or(0, |c| c.last_sequence as usize + 1)
+    }
+
+    fn verify_checkpoints(&self) -> Result<()> {
+        let mut previous: Option<&Checkpoint> = None;
+
+        for (i, checkpoint) in self.checkpoints.iter().enumerate() {
+            let broken = |reason: &str| {
+                SecurityError::AuditTrailCompromised(format!("Checkpoint {} {}", i, reason))
+            };
+
+            let first = previous.map_or(0, |p| p.last_sequence + 1);
+            if checkpoint.index != i as u64
+                || checkpoint.first_sequence != first
+                || checkpoint.last_sequence < first
+                || checkpoint.previous_digest != previous.map(Checkpoint::digest)
+            {
+                return Err(broken("is out of sequence"));
+            }
+
+            let covered = self
+                .entries
+                .get(first as usize..=checkpoint.last_sequence as usize)
+                .ok_or_else(|| broken("covers missing entries"))?;
+            let tree = MerkleTree::from_hashes(covered.iter().map(|e| e.hash.clone()).collect());
+            if tree.root() != Some(checkpoint.merkle_root.as_str())
+                || covered.last().map(|e| e.hash.as_str()) != Some(checkpoint.head_hash.as_str())
+            {
+                return Err(broken("root mismatch"));
+            }
+
+            if checkpoint.is_signed() {
+                checkpoint.verify_signature()?;
+            }
+
+            previous = Some(checkpoint);
+        }
+
+        Ok(())
+    }
 }
 
 impl Default for AuditTrail {
@@ -111,6 +270,14 @@ impl Default for AuditTrail {
     }
 }
 
+/// Serialized form of a trail
+#[derive(Serialize, Deserialize)]
+struct TrailArchive {
+    entries: Vec<TrailEntry>,
+    #[serde(default)]
+    checkpoints: Vec<Checkpoint>,
+}
+
 /// Audit trail entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrailEntry {
@@ -128,12 +295,7 @@ pub struct TrailEntry {
 
 impl TrailEntry {
     /// Create a new trail entry
-    pub fn new(event: AuditEvent, previous_hash: Option<String>) -> Self {
-        let sequence = previous_hash
-            .as_ref()
-            .map(|_| 1u64) // In production, would increment from last
-            .unwrap_or(0);
-
+    pub fn new(event: AuditEvent, previous_hash: Option<String>, sequence: u64) -> Self {
         let trail_timestamp = chrono::Utc::now();
 
         let hash = Self::calculate_hash(&event, &previous_hash, sequence, &trail_timestamp);
@@ -148,6 +310,9 @@ impl TrailEntry {
     }
 
     /// Calculate hash for this entry
+    ///
+    /// Fields are length-prefixed and the event is hashed as canonical JSON,
+    /// so the hash does not depend on map iteration order.
     fn calculate_hash(
         event: &AuditEvent,
         previous_hash: &Option<String>,
@@ -155,22 +320,15 @@ impl TrailEntry {
         timestamp: &chrono::DateTime<chrono::Utc>,
     ) -> String {
         let mut hasher = Sha256::new();
+        hasher.update(ENTRY_DOMAIN);
+        hasher.update(sequence.to_be_bytes());
+        update_field(&mut hasher, previous_hash.as_deref().unwrap_or_default().as_bytes());
+        update_field(&mut hasher, timestamp.to_rfc3339().as_bytes());
 
-        // Hash the event
-        if let Ok(event_json) = serde_json::to_string(event) {
-            hasher.update(event_json.as_bytes());
-        }
-
-        // Hash previous hash
-        if let Some(prev) = previous_hash {
-            hasher.update(prev.as_bytes());
-        }
-
-        // Hash sequence number
-        hasher.update(sequence.to_le_bytes());
-
-        // Hash timestamp
-        hasher.update(timestamp.to_rfc3339().as_bytes());
+        let event_json = serde_json::to_value(event)
+            .map(|value| canonicalize(value).to_string())
+            .unwrap_or_default();
+        update_field(&mut hasher, event_json.as_bytes());
 
         hex::encode(hasher.finalize())
     }
@@ -187,72 +345,296 @@ impl TrailEntry {
     }
 }
 
+/// Signed Merkle root over a contiguous range of trail entries
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Position of the checkpoint in the trail
+    pub index: u64,
+    /// Sequence number of the first covered entry
+    pub first_sequence: u64,
+    /// Sequence number of the last covered entry
+    pub last_sequence: u64,
+    /// Hex-encoded Merkle root of the covered entry hashes
+    pub merkle_root: String,
+    /// Hash of the last covered entry
+    pub head_hash: String,
+    /// Digest of the previous checkpoint
+    pub previous_digest: Option<String>,
+    /// When the checkpoint was taken
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Base64 Ed25519 signature over the digest
+    pub signature: Option<String>,
+    /// Base64 public key of the signer
+    pub public_key: Option<String>,
+}
+
+impl Checkpoint {
+    /// Hex-encoded digest of the checkpoint, excluding its signature
+    pub fn digest(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(CHECKPOINT_DOMAIN);
+        hasher.update(self.index.to_be_bytes());
+        hasher.update(self.first_sequence.to_be_bytes());
+        hasher.update(self.last_sequence.to_be_bytes());
+        update_field(&mut hasher, self.merkle_root.as_bytes());
+        update_field(&mut hasher, self.head_hash.as_bytes());
+        update_field(&mut hasher, self.previous_digest.as_deref().unwrap_or_default().as_bytes());
+        update_field(&mut hasher, self.created_at.to_rfc3339().as_bytes());
+        hex::encode(hasher.finalize())
+    }
+
+    /// Check if the checkpoint carries a signature
+    pub fn is_signed(&self) -> bool {
+        self.signature.is_some()
+    }
+
+    /// Check if the checkpoint was signed with a key
+    pub fn is_signed_by(&self, public_key: &Ed25519PublicKey) -> bool {
+        self.public_key.as_deref() == Some(public_key.to_base64().as_str())
+            && self.verify_signature().is_ok()
+    }
+
+    /// Verify the signature against the embedded public key
+    pub fn verify_signature(&self) -> Result<()> {
+        let invalid = |reason: String| {
+            SecurityError::AuditTrailCompromised(format!(
+                "Checkpoint {} signature {}",
+                self.index, reason
+            ))
+        };
+
+        let (Some(signature), Some(public_key)) = (&self.signature, &self.public_key) else {
+            return Err(invalid("is missing".to_string()));
+        };
+        let signature = Signature::from_base64(signature).map_err(|e| invalid(e.to_string()))?;
+        let public_key =
+            Ed25519PublicKey::from_base64(public_key).map_err(|e| invalid(e.to_string()))?;
+
+        match verify_signature(&public_key, self.digest().as_bytes(), &signature) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(invalid("is invalid".to_string())),
+            Err(e) => Err(invalid(e.to_string())),
+        }
+    }
+}
+
+/// Checkpoint exported for external notarization
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnchorRecord {
+    /// Format identifier
+    pub format: String,
+    /// Hex-encoded digest to notarize
+    pub digest: String,
+    /// The anchored checkpoint
+    pub checkpoint: Checkpoint,
+    /// When the anchor was exported
+    pub exported_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl AnchorRecord {
+    /// Create an anchor for a checkpoint
+    pub fn new(checkpoint: &Checkpoint) -> Self {
+        Self {
+            format: ANCHOR_FORMAT.to_string(),
+            digest: checkpoint.digest(),
+            checkpoint: checkpoint.clone(),
+            exported_at: chrono::Utc::now(),
+        }
+    }
+
+    /// Check the digest matches the checkpoint and the signature is valid
+    pub fn verify(&self) -> Result<()> {
+        if self.format != ANCHOR_FORMAT {
+            return Err(SecurityError::ValidationFailed(format!(
+                "Unsupported anchor format: {}",
+                self.format
+            )));
+        }
+        if self.checkpoint.digest() != self.digest {
+            return Err(SecurityError::AuditTrailCompromised(format!(
+                "Anchor digest does not match checkpoint {}",
+                self.checkpoint.index
+            )));
+        }
+        if self.checkpoint.is_signed() {
+            self.checkpoint.verify_signature()?;
+        }
+        Ok(())
+    }
+
+    /// Serialize as JSON
+    pub fn to_json(&self) -> Result<Vec<u8>> {
+        serde_json::to_vec_pretty(self)
+            .map_err(|e| SecurityError::Internal(format!("Anchor export failed: {}", e)))
+    }
+
+    /// Parse from JSON
+    pub fn from_json(data: &[u8]) -> Result<Self> {
+        serde_json::from_slice(data)
+            .map_err(|e| SecurityError::ValidationFailed(format!("Invalid anchor: {}", e)))
+    }
+}
+
 /// Merkle tree for batch verification
+///
+/// Leaves and interior nodes are hashed with distinct prefixes; an odd node
+/// is promoted to the next level unchanged.
 pub struct MerkleTree {
     root: Option<String>,
     leaves: Vec<String>,
+    levels: Vec<Vec<[u8; 32]>>,
 }
 
 impl MerkleTree {
     /// Build Merkle tree from trail entries
     pub fn from_trail(trail: &AuditTrail) -> Self {
-        let leaves: Vec<String> = trail.entries().iter().map(|e| e.hash.clone()).collect();
+        Self::from_hashes(trail.entries().iter().map(|e| e.hash.clone()).collect())
+    }
 
-        let root = if !leaves.is_empty() {
-            Some(Self::build_root(&leaves))
-        } else {
-            None
-        };
+    /// Build Merkle tree from entry hashes
+    pub fn from_hashes(leaves: Vec<String>) -> Self {
+        let mut levels = vec![leaves.iter().map(|leaf| leaf_hash(leaf)).collect::<Vec<_>>()];
+
+        while levels.last().is_some_and(|level| level.len() > 1) {
+            let next = levels[levels.len() - 1]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
 
-        Self { root, leaves }
-    }
+        let root = levels
+            .last()
+            .and_then(|level| level.first())
+            .map(hex::encode);
 
-    /// Build Merkle root from leaves
-    fn build_root(leaves: &[String]) -> String {
-        if leaves.is_empty() {
-            return String::new();
+        Self {
+            root,
+            leaves,
+            levels,
         }
+    }
 
-        if leaves.len() == 1 {
-            return leaves[0].clone();
+    /// Get Merkle root
+    pub fn root(&self) -> Option<&str> {
+        self.root.as_deref()
+    }
+
+    /// Verify a leaf is in the tree
+    pub fn verify_leaf(&self, leaf_hash: &str) -> bool {
+        self.leaves.iter().any(|leaf| leaf == leaf_hash)
+    }
+
+    /// Inclusion proof for the leaf at an index
+    pub fn proof(&self, index: usize) -> Option<MerkleProof> {
+        let leaf = self.leaves.get(index)?.clone();
+
+        let mut siblings = Vec::new();
+        let mut position = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(hash) = level.get(position ^ 1) {
+                siblings.push(hex::encode(hash));
+            }
+            position /= 2;
         }
 
-        let mut level = leaves.to_vec();
+        Some(MerkleProof {
+            leaf,
+            index,
+            leaf_count: self.leaves.len(),
+            siblings,
+        })
+    }
+}
 
-        while level.len() > 1 {
-            let mut next_level = Vec::new();
+/// Proof that an entry hash is covered by a Merkle root
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    /// Entry hash being proven
+    pub leaf: String,
+    /// Position of the entry among the covered entries
+    pub index: usize,
+    /// Number of covered entries
+    pub leaf_count: usize,
+    /// Hex-encoded sibling hashes from the leaf level upwards
+    pub siblings: Vec<String>,
+}
+
+impl MerkleProof {
+    /// Verify the proof against a hex-encoded root
+    pub fn verify(&self, root: &str) -> bool {
+        if self.index >= self.leaf_count {
+            return false;
+        }
 
-            for chunk in level.chunks(2) {
-                let hash = if chunk.len() == 2 {
-                    Self::hash_pair(&chunk[0], &chunk[1])
+        let mut hash = leaf_hash(&self.leaf);
+        let mut siblings = self.siblings.iter();
+        let mut position = self.index;
+        let mut width = self.leaf_count;
+
+        while width > 1 {
+            let has_sibling = position % 2 == 1 || position + 1 < width;
+            if has_sibling {
+                let Some(sibling) = siblings.next().and_then(|s| decode_hash(s)) else {
+                    return false;
+                };
+                hash = if position % 2 == 1 {
+                    node_hash(&sibling, &hash)
                 } else {
-                    chunk[0].clone()
+                    node_hash(&hash, &sibling)
                 };
-                next_level.push(hash);
             }
-
-            level = next_level;
+            position /= 2;
+            width = width.div_ceil(2);
         }
 
-        level[0].clone()
+        siblings.next().is_none() && hex::encode(hash).eq_ignore_ascii_case(root)
     }
+}
 
-    /// Hash a pair of nodes
-    fn hash_pair(left: &str, right: &str) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(left.as_bytes());
-        hasher.update(right.as_bytes());
-        hex::encode(hasher.finalize())
-    }
+fn leaf_hash(entry_hash: &str) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update(entry_hash.as_bytes());
+    hasher.finalize().into()
+}
 
-    /// Get Merkle root
-    pub fn root(&self) -> Option<&str> {
-        self.root.as_deref()
-    }
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
 
-    /// Verify a leaf is in the tree
-    pub fn verify_leaf(&self, leaf_hash: &str) -> bool {
-        self.leaves.contains(&leaf_hash.to_string())
+fn decode_hash(hex_hash: &str) -> Option<[u8; 32]> {
+    hex::decode(hex_hash).ok()?.try_into().ok()
+}
+
+fn update_field(hasher: &mut Sha256, bytes: &[u8]) {
+    hasher.update((bytes.len() as u64).to_be_bytes());
+    hasher.update(bytes);
+}
+
+/// Sort object keys recursively so equal values serialize identically
+fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut fields: Vec<(String, Value)> = map.into_iter().collect();
+            fields.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(key, value)| (key, canonicalize(value)))
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(canonicalize).collect()),
+        other => other,
     }
 }
 
@@ -363,4 +745,87 @@ mod tests {
             assert!(tree.verify_leaf(&entry.hash));
         }
     }
+
+    fn trail_with(count: usize) -> AuditTrail {
+        let mut trail = AuditTrail::new();
+        for i in 0..count {
+            let event = AuditEvent::new(EventType::AuthLogin, format!("login-{}", i))
+                .add_metadata("ip".to_string(), "10.0.0.1".to_string())
+                .add_metadata("agent".to_string(), "test".to_string());
+            trail.add_event(event).unwrap();
+        }
+        trail
+    }
+
+    #[test]
+    fn test_signed_checkpoints() {
+        let signer = Ed25519Signer::generate().unwrap();
+        let mut trail = trail_with(5);
+
+        let first = trail.checkpoint(Some(&signer)).unwrap().unwrap();
+        assert_eq!((first.first_sequence, first.last_sequence), (0, 4));
+        assert!(first.is_signed_by(signer.public_key()));
+        assert!(trail.checkpoint(Some(&signer)).unwrap().is_none());
+
+        trail.add_event(AuditEvent::new(EventType::AuthLogout, "logout".to_string())).unwrap();
+        assert_eq!(trail.pending(), 1);
+        let second = trail.checkpoint(Some(&signer)).unwrap().unwrap();
+        assert_eq!(second.first_sequence, 5);
+        assert_eq!(second.previous_digest, Some(first.digest()));
+        assert!(trail.verify_integrity().is_ok());
+
+        // Survives an export round trip
+        let imported = AuditTrail::import(&trail.export().unwrap()).unwrap();
+        assert_eq!(imported.checkpoints(), trail.checkpoints());
+
+        // A re-hashed entry keeps the chain valid but no longer matches the checkpoint root
+        let mut rewritten = trail_with(6);
+        rewritten.checkpoints = trail.checkpoints.clone();
+        assert!(rewritten.verify_integrity().is_err());
+
+        // So does a tampered signature
+        trail.checkpoints[1].signature = first.signature.clone();
+        assert!(trail.verify_integrity().is_err());
+    }
+
+    #[test]
+    fn test_inclusion_proofs() {
+        for count in [1, 2, 5, 8] {
+            let mut trail = trail_with(count);
+            let checkpoint = trail.checkpoint(None).unwrap().unwrap();
+
+            for sequence in 0..count as u64 {
+                let (covering, proof) = trail.inclusion_proof(sequence).unwrap();
+                assert_eq!(covering, checkpoint);
+                assert!(proof.verify(&checkpoint.merkle_root), "{} of {}", sequence, count);
+
+                let mut forged = proof.clone();
+                forged.leaf = "0".repeat(64);
+                assert!(!forged.verify(&checkpoint.merkle_root));
+            }
+        }
+        assert!(trail_with(3).inclusion_proof(0).is_none());
+    }
+
+    #[test]
+    fn test_anchor_export() {
+        let signer = Ed25519Signer::generate().unwrap();
+        let mut trail = trail_with(3);
+        assert!(trail.anchor().is_none());
+        trail.checkpoint(Some(&signer)).unwrap();
+
+        let anchor = AnchorRecord::from_json(&trail.anchor().unwrap().to_json().unwrap()).unwrap();
+        assert_eq!(anchor.digest, trail.checkpoints()[0].digest());
+        assert!(trail.verify_anchor(&anchor).is_ok());
+
+        // A trail rebuilt and re-signed with the same key no longer matches the anchor
+        let mut rebuilt = trail_with(3);
+        rebuilt.checkpoint(Some(&signer)).unwrap();
+        assert!(rebuilt.verify_integrity().is_ok());
+        assert!(rebuilt.verify_anchor(&anchor).is_err());
+
+        let mut altered = anchor.clone();
+        altered.checkpoint.merkle_root = "0".repeat(64);
+        assert!(altered.verify().is_err());
+    }
 }
@@ -0,0 +1,629 @@
+//! API keys for service-to-service callers
+//!
+//! Integrations authenticate with long-lived API keys instead of user JWTs.
+//! A key looks like `ak_<id>_<secret>`: the ID locates the key and the
+//! 256-bit secret is checked against a keyed BLAKE3 hash, so the secret
+//! itself is never stored and is shown only once, when the key is issued.
+//!
+//! Keys carry scopes rather than permissions; each scope expands to a fixed
+//! set of permissions (see [`ApiKeyService::with_scope`]). Keys expire,
+//! can be rotated with a grace period during which the old key keeps
+//! working, and are rate limited per key through an [`ApiKeyRateLimit`]
+//! hook.
+
+use super::AuthContext;
+use crate::audit::{AuditEvent, AuditService, EventResult, EventType, ResourceInfo};
+use crate::config::ApiKeyConfig;
+use crate::error::{Result, SecurityError};
+use accuscene_crypto::hash::blake3::blake3_keyed_hash;
+use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Duration, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Prefix of API key tokens
+pub const API_KEY_PREFIX: &str = "ak_";
+
+/// Scopes available by default, with the permissions they grant
+pub const DEFAULT_SCOPES: &[(&str, &[&str])] = &[
+    (
+        "read",
+        &[
+            "cases:read",
+            "evidence:read",
+            "reports:read",
+            "analysis:read",
+        ],
+    ),
+    ("write", &["cases:write", "evidence:write", "reports:write"]),
+    (
+        "analysis",
+        &["analysis:execute", "physics:simulate", "ml:predict"],
+    ),
+    ("provisioning", &["users:read", "users:write"]),
+    ("audit", &["audit:read", "audit:export"]),
+];
+
+/// Don't write back `last_used_at` more often than this
+const LAST_USED_RESOLUTION_SECS: i64 = 60;
+
+/// Stored API key; the secret is kept only as a hash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKey {
+    /// Key ID, part of the token
+    pub id: String,
+    /// Human-readable name
+    pub name: String,
+    /// Service account or user the key acts as
+    pub owner: String,
+    /// Organization the key is scoped to
+    pub org_id: Option<String>,
+    /// Granted scopes
+    pub scopes: Vec<String>,
+    /// Keyed BLAKE3 hash of the secret, hex encoded
+    pub secret_hash: String,
+    /// Requests per minute; the configured default when unset
+    pub rate_limit_per_minute: Option<u32>,
+    /// Who created the key
+    pub created_by: String,
+    /// Creation time
+    pub created_at: DateTime<Utc>,
+    /// Expiry time
+    pub expires_at: DateTime<Utc>,
+    /// Last successful use
+    pub last_used_at: Option<DateTime<Utc>>,
+    /// Revocation time
+    pub revoked_at: Option<DateTime<Utc>>,
+    /// Key that replaced this one on rotation
+    pub replaced_by: Option<String>,
+}
+
+impl ApiKey {
+    /// Check whether the key is usable at `now`
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && now < self.expires_at
+    }
+}
+
+/// Parameters of a new API key
+#[derive(Debug, Clone)]
+pub struct NewApiKey {
+    /// Human-readable name
+    pub name: String,
+    /// Service account or user the key acts as
+    pub owner: String,
+    /// Organization the key is scoped to
+    pub org_id: Option<String>,
+    /// Requested scopes
+    pub scopes: Vec<String>,
+    /// Lifetime; the configured default when unset
+    pub ttl: Option<Duration>,
+    /// Requests per minute; the configured default when unset
+    pub rate_limit_per_minute: Option<u32>,
+}
+
+impl NewApiKey {
+    /// Describe a key acting as `owner`
+    pub fn new(name: impl Into<String>, owner: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            owner: owner.into(),
+            org_id: None,
+            scopes: Vec::new(),
+            ttl: None,
+            rate_limit_per_minute: None,
+        }
+    }
+
+    /// Grant a scope
+    pub fn with_scope(mut self, scope: impl Into<String>) -> Self {
+        self.scopes.push(scope.into());
+        self
+    }
+
+    /// Scope the key to an organization
+    pub fn with_org(mut self, org_id: impl Into<String>) -> Self {
+        self.org_id = Some(org_id.into());
+        self
+    }
+
+    /// Set the lifetime
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Set the rate limit
+    pub fn with_rate_limit(mut self, requests_per_minute: u32) -> Self {
+        self.rate_limit_per_minute = Some(requests_per_minute);
+        self
+    }
+}
+
+/// Newly issued key with its token
+///
+/// The token cannot be recovered later; hand it to the caller once.
+#[derive(Debug, Clone)]
+pub struct IssuedApiKey {
+    /// The stored key
+    pub key: ApiKey,
+    /// Token to present, `ak_<id>_<secret>`
+    pub token: String,
+}
+
+/// Storage backend for API keys
+#[async_trait]
+pub trait ApiKeyStore: Send + Sync {
+    /// Insert or update a key
+    async fn save(&self, key: &ApiKey) -> Result<()>;
+
+    /// Load a key by ID
+    async fn load(&self, id: &str) -> Result<Option<ApiKey>>;
+
+    /// Load all keys of an owner, including revoked and expired ones
+    async fn list_for_owner(&self, owner: &str) -> Result<Vec<ApiKey>>;
+}
+
+/// In-memory key store; keys are lost on restart
+#[derive(Default)]
+pub struct MemoryApiKeyStore {
+    keys: RwLock<HashMap<String, ApiKey>>,
+}
+
+impl MemoryApiKeyStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ApiKeyStore for MemoryApiKeyStore {
+    async fn save(&self, key: &ApiKey) -> Result<()> {
+        self.keys.write().await.insert(key.id.clone(), key.clone());
+        Ok(())
+    }
+
+    async fn load(&self, id: &str) -> Result<Option<ApiKey>> {
+        Ok(self.keys.read().await.get(id).cloned())
+    }
+
+    async fn list_for_owner(&self, owner: &str) -> Result<Vec<ApiKey>> {
+        Ok(self.keys.read().await.values().filter(|k| k.owner == owner).cloned().collect())
+    }
+}
+
+/// Per-key rate limiting hook, consulted on every validated request
+#[async_trait]
+pub trait ApiKeyRateLimit: Send + Sync {
+    /// Count a request, failing with `RateLimitExceeded` over the limit
+    async fn check(&self, key: &ApiKey, requests_per_minute: u32) -> Result<()>;
+}
+
+/// Fixed one-minute windows per key, in memory
+#[derive(Default)]
+pub struct FixedWindowRateLimit {
+    windows: RwLock<HashMap<String, (i64, u32)>>,
+}
+
+impl FixedWindowRateLimit {
+    /// Create a limiter with no history
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ApiKeyRateLimit for FixedWindowRateLimit {
+    async fn check(&self, key: &ApiKey, requests_per_minute: u32) -> Result<()> {
+        let now = Utc::now().timestamp();
+        let minute = now / 60;
+
+        let mut windows = self.windows.write().await;
+        let window = windows.entry(key.id.clone()).or_insert((minute, 0));
+        if window.0 != minute {
+            *window = (minute, 0);
+        }
+        if window.1 >= requests_per_minute {
+            return Err(SecurityError::RateLimitExceeded {
+                retry_after_secs: (60 - now % 60) as u64,
+            });
+        }
+        window.1 += 1;
+        Ok(())
+    }
+}
+
+/// Issues, validates, rotates and revokes API keys
+pub struct ApiKeyService {
+    config: ApiKeyConfig,
+    pepper: [u8; 32],
+    store: Arc<dyn ApiKeyStore>,
+    scopes: HashMap<String, Vec<String>>,
+    rate_limit: Option<Arc<dyn ApiKeyRateLimit>>,
+    audit: Option<Arc<AuditService>>,
+}
+
+impl ApiKeyService {
+    /// Create a service hashing secrets with `pepper`
+    pub fn new(config: ApiKeyConfig, pepper: &[u8; 32]) -> Self {
+        let scopes = DEFAULT_SCOPES
+            .iter()
+            .map(|(scope, permissions)| {
+                let permissions = permissions.iter().map(|p| p.to_string()).collect();
+                (scope.to_string(), permissions)
+            })
+            .collect();
+
+        Self {
+            config,
+            pepper: *pepper,
+            store: Arc::new(MemoryApiKeyStore::new()),
+            scopes,
+            rate_limit: Some(Arc::new(FixedWindowRateLimit::new())),
+            audit: None,
+        }
+    }
+
+    /// Keep keys in another store
+    pub fn with_store(mut self, store: Arc<dyn ApiKeyStore>) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Define or replace a scope
+    pub fn with_scope(mut self, scope: impl Into<String>, permissions: Vec<String>) -> Self {
+        self.scopes.insert(scope.into(), permissions);
+        self
+    }
+
+    /// Replace the rate limiting hook; `None` disables rate limiting
+    pub fn with_rate_limit(mut self, rate_limit: Option<Arc<dyn ApiKeyRateLimit>>) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    /// Audit key creation, rotation and revocation
+    pub fn with_audit(mut self, audit: Arc<AuditService>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Check whether a bearer token is an API key rather than a JWT
+    pub fn is_api_key(token: &str) -> bool {
+        token.starts_with(API_KEY_PREFIX)
+    }
+
+    /// Permissions granted by a scope
+    pub fn scope_permissions(&self, scope: &str) -> Option<&[String]> {
+        self.scopes.get(scope).map(Vec::as_slice)
+    }
+
+    /// Issue a new key
+    pub async fn create(&self, request: NewApiKey, created_by: &str) -> Result<IssuedApiKey> {
+        if request.scopes.is_empty() {
+            return Err(SecurityError::ValidationFailed(
+                "an API key needs at least one scope".to_string(),
+            ));
+        }
+        if let Some(scope) = request.scopes.iter().find(|s| !self.scopes.contains_key(*s)) {
+            return Err(SecurityError::ValidationFailed(format!(
+                "unknown API key scope '{}'",
+                scope
+            )));
+        }
+
+        let ttl = request
+            .ttl
+            .unwrap_or_else(|| Duration::days(self.config.default_ttl_days as i64));
+        if ttl <= Duration::zero() || ttl > Duration::days(self.config.max_ttl_days as i64) {
+            return Err(SecurityError::ValidationFailed(format!(
+                "API key lifetime must be between 1 second and {} days",
+                self.config.max_ttl_days
+            )));
+        }
+
+        let (issued, _) = self.issue(request, ttl, created_by).await?;
+        self.audit(
+            EventType::AuthApiKeyCreated,
+            "api_key.create",
+            created_by,
+            &issued.key,
+        )
+        .await?;
+        Ok(issued)
+    }
+
+    /// Authenticate a request made with an API key token
+    pub async fn validate(&self, token: &str) -> Result<AuthContext> {
+        let invalid = || SecurityError::InvalidToken("Invalid API key".to_string());
+        let (id, secret) = token
+            .strip_prefix(API_KEY_PREFIX)
+            .and_then(|rest| rest.split_once('_'))
+            .ok_or_else(invalid)?;
+
+        let mut key = self.store.load(id).await?.ok_or_else(invalid)?;
+        if !constant_time_eq(
+            self.hash_secret(secret).as_bytes(),
+            key.secret_hash.as_bytes(),
+        ) {
+            return Err(invalid());
+        }
+
+        let now = Utc::now();
+        if key.revoked_at.is_some() {
+            return Err(SecurityError::InvalidToken("API key revoked".to_string()));
+        }
+        if now >= key.expires_at {
+            return Err(SecurityError::InvalidToken("API key expired".to_string()));
+        }
+
+        if let Some(rate_limit) = &self.rate_limit {
+            let limit =
+                key.rate_limit_per_minute.unwrap_or(self.config.default_rate_limit_per_minute);
+            rate_limit.check(&key, limit).await?;
+        }
+
+        let recently_used = key
+            .last_used_at
+            .is_some_and(|t| (now - t).num_seconds() < LAST_USED_RESOLUTION_SECS);
+        if !recently_used {
+            key.last_used_at = Some(now);
+            self.store.save(&key).await?;
+        }
+
+        let mut permissions: Vec<String> = Vec::new();
+        for permission in key.scopes.iter().filter_map(|s| self.scopes.get(s)).flatten() {
+            if !permissions.contains(permission) {
+                permissions.push(permission.clone());
+            }
+        }
+
+        Ok(AuthContext {
+            user_id: key.owner,
+            session_id: None,
+            roles: Vec::new(),
+            permissions,
+            org_id: key.org_id,
+            mfa_verified: false,
+            session_metadata: None,
+        })
+    }
+
+    /// Replace a key with a new one; the old key keeps working for the
+    /// configured grace period
+    pub async fn rotate(&self, id: &str, rotated_by: &str) -> Result<IssuedApiKey> {
+        let mut old = self.load_active(id).await?;
+
+        let request = NewApiKey {
+            name: old.name.clone(),
+            owner: old.owner.clone(),
+            org_id: old.org_id.clone(),
+            scopes: old.scopes.clone(),
+            ttl: None,
+            rate_limit_per_minute: old.rate_limit_per_minute,
+        };
+        let (issued, now) =
+            self.issue(request, old.expires_at - old.created_at, rotated_by).await?;
+
+        let grace = Duration::seconds(self.config.rotation_grace_secs as i64);
+        old.expires_at = old.expires_at.min(now + grace);
+        old.replaced_by = Some(issued.key.id.clone());
+        self.store.save(&old).await?;
+
+        self.audit(
+            EventType::AuthApiKeyRotated,
+            "api_key.rotate",
+            rotated_by,
+            &old,
+        )
+        .await?;
+        Ok(issued)
+    }
+
+    /// Revoke a key immediately
+    pub async fn revoke(&self, id: &str, revoked_by: &str) -> Result<()> {
+        let mut key = self.load_active(id).await?;
+        key.revoked_at = Some(Utc::now());
+        self.store.save(&key).await?;
+
+        self.audit(
+            EventType::AuthApiKeyRevoked,
+            "api_key.revoke",
+            revoked_by,
+            &key,
+        )
+        .await
+    }
+
+    /// List the keys of an owner, newest first
+    pub async fn list_for_owner(&self, owner: &str) -> Result<Vec<ApiKey>> {
+        let mut keys = self.store.list_for_owner(owner).await?;
+        keys.sort_by_key(|k| std::cmp::Reverse(k.created_at));
+        Ok(keys)
+    }
+
+    async fn load_active(&self, id: &str) -> Result<ApiKey> {
+        self.store
+            .load(id)
+            .await?
+            .filter(|k| k.is_active(Utc::now()))
+            .ok_or_else(|| SecurityError::KeyNotFound(id.to_string()))
+    }
+
+    async fn issue(
+        &self,
+        request: NewApiKey,
+        ttl: Duration,
+        created_by: &str,
+    ) -> Result<(IssuedApiKey, DateTime<Utc>)> {
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        let mut secret = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut secret);
+        let secret = URL_SAFE_NO_PAD.encode(secret);
+
+        let now = Utc::now();
+        let key = ApiKey {
+            id: id.clone(),
+            name: request.name,
+            owner: request.owner,
+            org_id: request.org_id,
+            scopes: request.scopes,
+            secret_hash: self.hash_secret(&secret),
+            rate_limit_per_minute: request.rate_limit_per_minute,
+            created_by: created_by.to_string(),
+            created_at: now,
+            expires_at: now + ttl,
+            last_used_at: None,
+            revoked_at: None,
+            replaced_by: None,
+        };
+        self.store.save(&key).await?;
+
+        let token = format!("{}{}_{}", API_KEY_PREFIX, id, secret);
+        Ok((IssuedApiKey { key, token }, now))
+    }
+
+    fn hash_secret(&self, secret: &str) -> String {
+        hex::encode(blake3_keyed_hash(&self.pepper, secret.as_bytes()))
+    }
+
+    async fn audit(
+        &self,
+        event_type: EventType,
+        action: &str,
+        actor: &str,
+        key: &ApiKey,
+    ) -> Result<()> {
+        let Some(audit) = &self.audit else {
+            return Ok(());
+        };
+
+        let mut event = AuditEvent::new(event_type, action.to_string())
+            .with_user(actor.to_string())
+            .with_resource(ResourceInfo::new("api_key", key.id.clone()).with_name(key.name.clone()))
+            .with_result(EventResult::Success)
+            .add_metadata("owner".to_string(), key.owner.clone())
+            .add_metadata("scopes".to_string(), key.scopes.join(" "))
+            .add_metadata("expires_at".to_string(), key.expires_at.to_rfc3339());
+        if let Some(replaced_by) = &key.replaced_by {
+            event = event.add_metadata("replaced_by".to_string(), replaced_by.clone());
+        }
+
+        audit.audit(event).await
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service() -> ApiKeyService {
+        ApiKeyService::new(ApiKeyConfig::default(), &[3u8; 32])
+    }
+
+    #[tokio::test]
+    async fn test_create_and_validate() {
+        let service = service();
+        let issued = service
+            .create(
+                NewApiKey::new("ingest", "svc-ingest")
+                    .with_scope("read")
+                    .with_scope("analysis")
+                    .with_org("acme"),
+                "admin",
+            )
+            .await
+            .unwrap();
+        assert!(ApiKeyService::is_api_key(&issued.token));
+        assert!(!issued.key.secret_hash.contains(issued.token.rsplit('_').next().unwrap()));
+
+        let context = service.validate(&issued.token).await.unwrap();
+        assert_eq!(context.user_id, "svc-ingest");
+        assert_eq!(context.org_id.as_deref(), Some("acme"));
+        assert!(context.has_permission("cases:read"));
+        assert!(context.has_permission("physics:simulate"));
+        assert!(!context.has_permission("cases:write"));
+
+        // A tampered secret is refused
+        let mut tampered = issued.token.clone();
+        tampered.push('x');
+        assert!(service.validate(&tampered).await.is_err());
+
+        assert!(service
+            .create(
+                NewApiKey::new("bad", "svc").with_scope("everything"),
+                "admin"
+            )
+            .await
+            .is_err());
+        assert!(service
+            .create(
+                NewApiKey::new("long", "svc").with_scope("read").with_ttl(Duration::days(1000)),
+                "admin"
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rotate_and_revoke() {
+        let service = service();
+        let old = service
+            .create(
+                NewApiKey::new("sync", "svc-sync").with_scope("write"),
+                "admin",
+            )
+            .await
+            .unwrap();
+
+        let new = service.rotate(&old.key.id, "admin").await.unwrap();
+        // The old key keeps working during the grace period
+        assert!(service.validate(&old.token).await.is_ok());
+        assert!(service.validate(&new.token).await.is_ok());
+
+        let keys = service.list_for_owner("svc-sync").await.unwrap();
+        let stored_old = keys.iter().find(|k| k.id == old.key.id).unwrap();
+        assert_eq!(stored_old.replaced_by.as_deref(), Some(new.key.id.as_str()));
+        assert!(stored_old.expires_at <= Utc::now() + Duration::days(1));
+
+        service.revoke(&new.key.id, "admin").await.unwrap();
+        assert!(matches!(
+            service.validate(&new.token).await,
+            Err(SecurityError::InvalidToken(_))
+        ));
+        assert!(service.revoke(&new.key.id, "admin").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_per_key() {
+        let service = service();
+        let limited = service
+            .create(
+                NewApiKey::new("burst", "svc").with_scope("read").with_rate_limit(2),
+                "admin",
+            )
+            .await
+            .unwrap();
+        let other = service
+            .create(NewApiKey::new("other", "svc").with_scope("read"), "admin")
+            .await
+            .unwrap();
+
+        assert!(service.validate(&limited.token).await.is_ok());
+        assert!(service.validate(&limited.token).await.is_ok());
+        // The third request may land in a new window at a minute boundary
+        if let Err(error) = service.validate(&limited.token).await {
+            assert!(matches!(error, SecurityError::RateLimitExceeded { .. }));
+        }
+        assert!(service.validate(&other.token).await.is_ok());
+    }
+}
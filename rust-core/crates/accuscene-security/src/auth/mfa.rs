@@ -1,7 +1,10 @@
 //! Multi-factor authentication (TOTP and WebAuthn)
 //!
-//! Implements MFA following security best practices.
+//! Implements MFA following security best practices. WebAuthn ceremonies
+//! live in [`super::webauthn`]; registered credentials are kept on the
+//! user's [`MfaEnrollment`].
 
+use super::webauthn::AttestationType;
 use crate::config::MfaConfig;
 use crate::error::{Result, SecurityError};
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
@@ -172,14 +175,67 @@ impl MfaEnrollment {
     pub fn requires_verification(&self) -> bool {
         self.is_enrolled()
     }
+
+    /// Available MFA methods
+    pub fn methods(&self) -> Vec<MfaMethod> {
+        let mut methods = Vec::new();
+        if self.webauthn_enabled {
+            methods.push(MfaMethod::WebAuthn);
+        }
+        if self.totp_enabled {
+            methods.push(MfaMethod::Totp);
+        }
+        if self.backup_codes.len() > self.backup_codes_used {
+            methods.push(MfaMethod::BackupCode);
+        }
+        methods
+    }
+
+    /// Get a WebAuthn credential by ID
+    pub fn webauthn_credential(&self, credential_id: &str) -> Option<&WebAuthnCredential> {
+        self.webauthn_credentials.iter().find(|c| c.id == credential_id)
+    }
+
+    /// Add a WebAuthn credential, enabling WebAuthn
+    pub fn add_webauthn_credential(&mut self, credential: WebAuthnCredential) -> Result<()> {
+        if self.webauthn_credential(&credential.id).is_some() {
+            return Err(SecurityError::WebAuthnFailed(format!(
+                "Credential '{}' already registered",
+                credential.id
+            )));
+        }
+
+        self.webauthn_credentials.push(credential);
+        self.webauthn_enabled = true;
+        Ok(())
+    }
+
+    /// Remove a WebAuthn credential, disabling WebAuthn when none are left
+    pub fn remove_webauthn_credential(&mut self, credential_id: &str) -> bool {
+        let before = self.webauthn_credentials.len();
+        self.webauthn_credentials.retain(|c| c.id != credential_id);
+        self.webauthn_enabled = !self.webauthn_credentials.is_empty();
+        self.webauthn_credentials.len() < before
+    }
+
+    /// Rename a WebAuthn credential
+    pub fn rename_webauthn_credential(&mut self, credential_id: &str, nickname: String) -> bool {
+        match self.webauthn_credentials.iter_mut().find(|c| c.id == credential_id) {
+            Some(credential) => {
+                credential.nickname = Some(nickname);
+                true
+            }
+            None => false,
+        }
+    }
 }
 
 /// WebAuthn credential
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebAuthnCredential {
-    /// Credential ID
+    /// Credential ID (base64url)
     pub id: String,
-    /// Credential public key
+    /// COSE-encoded credential public key
     pub public_key: Vec<u8>,
     /// Sign count
     pub sign_count: u32,
@@ -189,6 +245,12 @@ pub struct WebAuthnCredential {
     pub created_at: chrono::DateTime<chrono::Utc>,
     /// Last used
     pub last_used: Option<chrono::DateTime<chrono::Utc>>,
+    /// Authenticator model (hex AAGUID, all zeros when not attested)
+    #[serde(default)]
+    pub aaguid: String,
+    /// Attestation verified at registration
+    #[serde(default)]
+    pub attestation: AttestationType,
 }
 
 /// MFA verification result
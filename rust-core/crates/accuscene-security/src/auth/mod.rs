@@ -2,30 +2,51 @@
 //!
 //! Comprehensive authentication system with password hashing, MFA, SSO, sessions, and JWT tokens.
 
+pub mod api_key;
 pub mod mfa;
 pub mod password;
 pub mod session;
+pub mod session_store;
 pub mod sso;
 pub mod token;
+pub mod webauthn;
 
 use crate::config::AuthConfig;
 use crate::error::{Result, SecurityError};
+use crate::threat::{ThreatAssessment, ThreatDetector};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
+pub use api_key::{
+    ApiKey, ApiKeyRateLimit, ApiKeyService, ApiKeyStore, FixedWindowRateLimit, IssuedApiKey,
+    MemoryApiKeyStore, NewApiKey,
+};
 pub use mfa::{MfaEnrollment, MfaMethod, MfaService, TotpSecret};
 pub use password::{PasswordHashService, PasswordHistory};
-pub use session::{GeoLocation, Session, SessionManager, SessionMetadata};
+pub use session::{DeviceSummary, GeoLocation, Session, SessionManager, SessionMetadata};
+pub use session_store::{MemorySessionStore, SessionStore};
+#[cfg(feature = "postgres")]
+pub use session_store::PostgresSessionStore;
+#[cfg(feature = "sqlite")]
+pub use session_store::SqliteSessionStore;
 pub use sso::{OidcClaims, SamlAssertion, SsoProvider, SsoService};
 pub use token::{JwtClaims, TokenBlacklist, TokenClaims, TokenPair, TokenService, TokenType};
+pub use webauthn::{
+    AssertionResponse, CredentialCreationOptions, CredentialRequestOptions, RegistrationResponse,
+    WebAuthnService,
+};
 
 /// Authentication service coordinating all auth mechanisms
 pub struct AuthenticationService {
     password_service: PasswordHashService,
     mfa_service: MfaService,
+    webauthn_service: WebAuthnService,
     sso_service: SsoService,
     session_manager: SessionManager,
     token_service: TokenService,
     token_blacklist: TokenBlacklist,
+    threat_detector: Option<Arc<ThreatDetector>>,
+    api_keys: Option<Arc<ApiKeyService>>,
 }
 
 impl AuthenticationService {
@@ -34,25 +55,174 @@ impl AuthenticationService {
         Self {
             password_service: PasswordHashService::new(config.password_policy.clone()),
             mfa_service: MfaService::new(config.mfa.clone()),
+            webauthn_service: WebAuthnService::new(config.mfa.webauthn.clone()),
             sso_service: SsoService::new(config.sso.clone()),
             session_manager: SessionManager::new(config.session.clone()),
             token_service: TokenService::new(config.jwt.clone(), jwt_secret),
             token_blacklist: TokenBlacklist::new(),
+            threat_detector: None,
+            api_keys: None,
         }
     }
 
+    /// Consult a threat detector on password logins
+    pub fn with_threat_detector(mut self, detector: Arc<ThreatDetector>) -> Self {
+        self.threat_detector = Some(detector);
+        self
+    }
+
+    /// Get the threat detector, if one is configured
+    pub fn threat_detector(&self) -> Option<&Arc<ThreatDetector>> {
+        self.threat_detector.as_ref()
+    }
+
+    /// Accept API keys as bearer tokens
+    pub fn with_api_keys(mut self, api_keys: Arc<ApiKeyService>) -> Self {
+        self.api_keys = Some(api_keys);
+        self
+    }
+
+    /// Get the API key service, if one is configured
+    pub fn api_keys(&self) -> Option<&Arc<ApiKeyService>> {
+        self.api_keys.as_ref()
+    }
+
+    /// Get the password hashing service
+    pub fn password_service(&self) -> &PasswordHashService {
+        &self.password_service
+    }
+
+    /// Get the MFA service
+    pub fn mfa_service(&self) -> &MfaService {
+        &self.mfa_service
+    }
+
+    /// Get the WebAuthn service
+    pub fn webauthn_service(&self) -> &WebAuthnService {
+        &self.webauthn_service
+    }
+
+    /// Begin registering a WebAuthn credential (security key or passkey)
+    pub fn start_webauthn_registration(
+        &mut self,
+        user_id: &str,
+        user_name: &str,
+        enrollment: &MfaEnrollment,
+    ) -> CredentialCreationOptions {
+        self.webauthn_service
+            .start_registration(user_id, user_name, enrollment)
+    }
+
+    /// Complete a WebAuthn registration, adding the credential to the enrollment
+    pub fn finish_webauthn_registration(
+        &mut self,
+        user_id: &str,
+        response: &RegistrationResponse,
+        nickname: Option<String>,
+        enrollment: &mut MfaEnrollment,
+    ) -> Result<mfa::WebAuthnCredential> {
+        self.webauthn_service
+            .finish_registration(user_id, response, nickname, enrollment)
+    }
+
+    /// Begin a WebAuthn assertion; the response is passed to [`verify_mfa`](Self::verify_mfa)
+    pub fn start_webauthn_authentication(
+        &mut self,
+        user_id: &str,
+        enrollment: &MfaEnrollment,
+    ) -> Result<CredentialRequestOptions> {
+        self.webauthn_service.start_authentication(user_id, enrollment)
+    }
+
+    /// Get the session manager
+    pub fn session_manager(&self) -> &SessionManager {
+        &self.session_manager
+    }
+
+    /// Keep sessions in a persistent store instead of memory
+    pub fn with_session_store(mut self, store: Arc<dyn SessionStore>) -> Self {
+        let config = self.session_manager.config().clone();
+        self.session_manager = SessionManager::with_store(config, store);
+        self
+    }
+
+    /// List the caller's active sessions
+    pub async fn list_my_sessions(&self, context: &AuthContext) -> Result<Vec<Session>> {
+        self.session_manager.list_user_sessions(&context.user_id).await
+    }
+
+    /// List the devices the caller is signed in on
+    pub async fn list_my_devices(&self, context: &AuthContext) -> Result<Vec<DeviceSummary>> {
+        self.session_manager.list_devices(&context.user_id).await
+    }
+
+    /// End one of the caller's sessions
+    pub async fn revoke_my_session(&self, context: &AuthContext, session_id: &str) -> Result<()> {
+        self.session_manager
+            .revoke_session(&context.user_id, session_id)
+            .await
+    }
+
+    /// Sign the caller out of a device, returning the number of sessions ended
+    pub async fn revoke_my_device(&self, context: &AuthContext, device_id: &str) -> Result<usize> {
+        self.session_manager
+            .revoke_device(&context.user_id, device_id)
+            .await
+    }
+
     /// Authenticate user with password
+    ///
+    /// With a threat detector, locked-out users and IP addresses are rejected
+    /// before the password is checked, a CAPTCHA is requested once failures
+    /// pile up, and risky logins are stepped up to MFA.
     pub async fn authenticate_password(
         &mut self,
         credentials: PasswordCredentials,
     ) -> Result<AuthenticationResult> {
+        let ip = credentials.metadata.ip_address.clone();
+
+        let assessment = match &self.threat_detector {
+            Some(detector) => {
+                detector
+                    .check_login(
+                        &credentials.user_id,
+                        &ip,
+                        credentials.metadata.user_agent.as_deref(),
+                    )
+                    .await?
+            }
+            None => ThreatAssessment::clear(),
+        };
+
+        if assessment.require_captcha && !credentials.captcha_verified {
+            return Ok(AuthenticationResult::CaptchaRequired {
+                user_id: credentials.user_id,
+            });
+        }
+
         // In production, load user from database
         // For now, we'll validate the password format
-        self.password_service
+        let verified = self
+            .password_service
             .verify_password(&credentials.password, &credentials.password_hash)?;
 
+        if !verified {
+            if let Some(detector) = &self.threat_detector {
+                if let Some(lockout) = detector.record_failure(&credentials.user_id, &ip).await? {
+                    return Err(SecurityError::LoginLocked {
+                        retry_after_secs: lockout.retry_after_secs(),
+                    });
+                }
+            }
+            return Err(SecurityError::InvalidCredentials);
+        }
+
+        if let Some(detector) = &self.threat_detector {
+            detector.record_success(&credentials.user_id).await;
+        }
+
         // Check if MFA is required
-        if credentials.mfa_required {
+        if credentials.mfa_required || assessment.require_step_up {
             return Ok(AuthenticationResult::MfaRequired {
                 user_id: credentials.user_id,
                 available_methods: vec![MfaMethod::Totp],
@@ -63,7 +233,7 @@ impl AuthenticationService {
         let session = self.session_manager.create_session(
             credentials.user_id.clone(),
             credentials.metadata,
-        )?;
+        ).await?;
 
         // Generate tokens
         let token_claims = TokenClaims {
@@ -87,12 +257,15 @@ impl AuthenticationService {
     }
 
     /// Verify MFA and complete authentication
+    ///
+    /// For WebAuthn, `code` is the JSON-encoded [`AssertionResponse`]; the
+    /// credential's sign count in `enrollment` is updated and must be persisted.
     pub async fn verify_mfa(
         &mut self,
         user_id: String,
         method: MfaMethod,
         code: &str,
-        enrollment: &MfaEnrollment,
+        enrollment: &mut MfaEnrollment,
         metadata: SessionMetadata,
     ) -> Result<AuthenticationResult> {
         // Verify MFA code
@@ -109,8 +282,12 @@ impl AuthenticationService {
                 false // Simplified for now
             }
             MfaMethod::WebAuthn => {
-                // Verify WebAuthn assertion
-                false // Simplified for now
+                let assertion: AssertionResponse = serde_json::from_str(code).map_err(|e| {
+                    SecurityError::WebAuthnFailed(format!("Malformed assertion: {}", e))
+                })?;
+                self.webauthn_service
+                    .finish_authentication(&user_id, &assertion, enrollment)?;
+                true
             }
         };
 
@@ -119,7 +296,7 @@ impl AuthenticationService {
         }
 
         // Create session
-        let session = self.session_manager.create_session(user_id.clone(), metadata)?;
+        let session = self.session_manager.create_session(user_id.clone(), metadata).await?;
 
         // Generate tokens with MFA verified
         let token_claims = TokenClaims {
@@ -167,7 +344,8 @@ impl AuthenticationService {
         // Create session
         let session = self
             .session_manager
-            .create_session(user_id.clone(), metadata)?;
+            .create_session(user_id.clone(), metadata)
+            .await?;
 
         // Generate tokens
         let token_claims = TokenClaims {
@@ -204,6 +382,7 @@ impl AuthenticationService {
             mfa_verified: claims.custom.mfa_verified,
             roles: claims.custom.roles.clone(),
             permissions: claims.custom.permissions.clone(),
+            org_id: claims.custom.org_id.clone(),
             ..Default::default()
         };
 
@@ -225,7 +404,7 @@ impl AuthenticationService {
     /// Logout user
     pub async fn logout(&mut self, session_id: &str, token_jti: &str) -> Result<()> {
         // Invalidate session
-        self.session_manager.invalidate_session(session_id)?;
+        self.session_manager.invalidate_session(session_id).await?;
 
         // Blacklist token
         let exp = chrono::Utc::now() + chrono::Duration::hours(24);
@@ -236,6 +415,12 @@ impl AuthenticationService {
 
     /// Validate authentication token
     pub async fn validate_request(&mut self, token: &str) -> Result<AuthContext> {
+        if let Some(api_keys) = &self.api_keys {
+            if ApiKeyService::is_api_key(token) {
+                return api_keys.validate(token).await;
+            }
+        }
+
         // Validate token
         let claims = self.token_service.validate_token(token)?;
 
@@ -246,18 +431,17 @@ impl AuthenticationService {
 
         // Validate session if present
         if let Some(session_id) = &claims.custom.session_id {
-            let session = self.session_manager.get_session(session_id)?;
-
-            // Touch session to update activity
-            self.session_manager.touch_session(session_id)?;
+            // Touch session to update activity; fails if expired or idle
+            let session = self.session_manager.touch_session(session_id).await?;
 
             return Ok(AuthContext {
                 user_id: claims.sub,
                 session_id: Some(session_id.clone()),
                 roles: claims.custom.roles,
                 permissions: claims.custom.permissions,
+                org_id: claims.custom.org_id,
                 mfa_verified: claims.custom.mfa_verified,
-                session_metadata: Some(session.metadata.clone()),
+                session_metadata: Some(session.metadata),
             });
         }
 
@@ -266,6 +450,7 @@ impl AuthenticationService {
             session_id: None,
             roles: claims.custom.roles,
             permissions: claims.custom.permissions,
+            org_id: claims.custom.org_id,
             mfa_verified: claims.custom.mfa_verified,
             session_metadata: None,
         })
@@ -283,6 +468,8 @@ pub struct PasswordCredentials {
     pub password_hash: String,
     /// MFA required flag
     pub mfa_required: bool,
+    /// Whether the client solved a CAPTCHA for this attempt
+    pub captcha_verified: bool,
     /// Session metadata
     pub metadata: SessionMetadata,
 }
@@ -313,6 +500,11 @@ pub enum AuthenticationResult {
         /// Available MFA methods
         available_methods: Vec<MfaMethod>,
     },
+    /// CAPTCHA required before the password is checked
+    CaptchaRequired {
+        /// User ID
+        user_id: String,
+    },
 }
 
 /// Authentication context for authorized requests
@@ -326,6 +518,9 @@ pub struct AuthContext {
     pub roles: Vec<String>,
     /// User permissions
     pub permissions: Vec<String>,
+    /// Organization (tenant) the user acts for; `None` for platform users
+    #[serde(default)]
+    pub org_id: Option<String>,
     /// MFA verified
     pub mfa_verified: bool,
     /// Session metadata
@@ -347,6 +542,11 @@ impl AuthContext {
     pub fn is_admin(&self) -> bool {
         self.has_role("admin")
     }
+
+    /// Check if user acts for an organization
+    pub fn acts_for(&self, org_id: &str) -> bool {
+        self.org_id.as_deref() == Some(org_id)
+    }
 }
 
 #[cfg(test)]
@@ -370,6 +570,7 @@ mod tests {
             session_id: Some("session123".to_string()),
             roles: vec!["admin".to_string(), "user".to_string()],
             permissions: vec!["read".to_string(), "write".to_string()],
+            org_id: None,
             mfa_verified: true,
             session_metadata: None,
         };
@@ -387,6 +588,7 @@ mod tests {
             session_id: None,
             roles: vec![],
             permissions: vec!["read".to_string()],
+            org_id: None,
             mfa_verified: false,
             session_metadata: None,
         };
@@ -394,4 +596,47 @@ mod tests {
         assert!(context.has_permission("read"));
         assert!(!context.has_permission("write"));
     }
+
+    #[tokio::test]
+    async fn test_failed_logins_lock_out_user() {
+        let config = SecurityConfig::default();
+        let detector = Arc::new(ThreatDetector::new(crate::config::ThreatConfig {
+            brute_force_threshold: 3,
+            captcha_threshold: 10,
+            ..config.threat.clone()
+        }));
+        let mut service =
+            AuthenticationService::new(config.auth, b"test-secret-key-32-bytes-long!!!")
+                .with_threat_detector(detector);
+
+        let password_hash = service
+            .password_service()
+            .hash_password("Correct-Horse-Battery-9")
+            .unwrap();
+        let credentials = |password: &str| PasswordCredentials {
+            user_id: "user123".to_string(),
+            password: password.to_string(),
+            password_hash: password_hash.clone(),
+            mfa_required: false,
+            captcha_verified: false,
+            metadata: SessionMetadata::basic("10.0.0.1".to_string()),
+        };
+
+        for _ in 0..2 {
+            assert!(matches!(
+                service.authenticate_password(credentials("wrong")).await,
+                Err(SecurityError::InvalidCredentials)
+            ));
+        }
+        assert!(matches!(
+            service.authenticate_password(credentials("wrong")).await,
+            Err(SecurityError::LoginLocked { .. })
+        ));
+        assert!(matches!(
+            service
+                .authenticate_password(credentials("Correct-Horse-Battery-9"))
+                .await,
+            Err(SecurityError::LoginLocked { .. })
+        ));
+    }
 }
@@ -4,33 +4,47 @@
 
 use crate::config::SessionConfig;
 use crate::error::{Result, SecurityError};
+use super::session_store::{MemorySessionStore, SessionStore};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Session manager
+///
+/// Sessions live in a [`SessionStore`]; the default in-memory store loses them on
+/// restart, so production deployments should use [`with_store`](Self::with_store).
 pub struct SessionManager {
     config: SessionConfig,
-    sessions: HashMap<String, Session>,
+    store: Arc<dyn SessionStore>,
 }
 
 impl SessionManager {
-    /// Create a new session manager
+    /// Create a new session manager backed by memory
     pub fn new(config: SessionConfig) -> Self {
-        Self {
-            config,
-            sessions: HashMap::new(),
-        }
+        Self::with_store(config, Arc::new(MemorySessionStore::new()))
+    }
+
+    /// Create a session manager on a persistent store
+    pub fn with_store(config: SessionConfig, store: Arc<dyn SessionStore>) -> Self {
+        Self { config, store }
+    }
+
+    /// Get the session configuration
+    pub fn config(&self) -> &SessionConfig {
+        &self.config
+    }
+
+    /// Get the underlying session store
+    pub fn store(&self) -> &Arc<dyn SessionStore> {
+        &self.store
     }
 
     /// Create a new session
-    pub fn create_session(&mut self, user_id: String, metadata: SessionMetadata) -> Result<Session> {
+    pub async fn create_session(&self, user_id: String, metadata: SessionMetadata) -> Result<Session> {
         // Check concurrent session limit
         if let Some(max) = self.config.max_concurrent_sessions {
-            let user_sessions = self.sessions.values()
-                .filter(|s| s.user_id == user_id && s.is_valid())
-                .count();
-
-            if user_sessions >= max {
+            if self.user_session_count(&user_id).await? >= max {
                 return Err(SecurityError::AuthenticationFailed(
                     "Maximum concurrent sessions reached".to_string(),
                 ));
@@ -38,29 +52,28 @@ impl SessionManager {
         }
 
         let session = Session::new(user_id, metadata, &self.config);
-        self.sessions.insert(session.id.clone(), session.clone());
+        self.store.save(&session).await?;
 
         Ok(session)
     }
 
     /// Get session by ID
-    pub fn get_session(&self, session_id: &str) -> Result<&Session> {
-        let session = self.sessions.get(session_id)
+    ///
+    /// Sessions past their idle or absolute timeout are invalidated on access.
+    pub async fn get_session(&self, session_id: &str) -> Result<Session> {
+        let mut session = self
+            .store
+            .load(session_id)
+            .await?
             .ok_or(SecurityError::SessionNotFound)?;
 
-        if !session.is_valid() {
+        if session.invalidated {
             return Err(SecurityError::SessionExpired);
         }
 
-        Ok(session)
-    }
-
-    /// Get mutable session by ID
-    pub fn get_session_mut(&mut self, session_id: &str) -> Result<&mut Session> {
-        let session = self.sessions.get_mut(session_id)
-            .ok_or(SecurityError::SessionNotFound)?;
-
-        if !session.is_valid() {
+        if !session.is_active(self.config.idle_timeout_secs) {
+            session.invalidate();
+            self.store.save(&session).await?;
             return Err(SecurityError::SessionExpired);
         }
 
@@ -68,69 +81,160 @@ impl SessionManager {
     }
 
     /// Update session activity
-    pub fn touch_session(&mut self, session_id: &str) -> Result<()> {
-        let session = self.get_session_mut(session_id)?;
+    pub async fn touch_session(&self, session_id: &str) -> Result<Session> {
+        let mut session = self.get_session(session_id).await?;
         session.touch();
-        Ok(())
+        self.store.save(&session).await?;
+        Ok(session)
     }
 
     /// Renew session
-    pub fn renew_session(&mut self, session_id: &str) -> Result<String> {
-        let old_session = self.get_session(session_id)?;
-
-        // Create new session with same user
-        let new_session = Session::new(
-            old_session.user_id.clone(),
-            old_session.metadata.clone(),
-            &self.config,
-        );
-
+    ///
+    /// The new session keeps the absolute expiry of the one it replaces.
+    pub async fn renew_session(&self, session_id: &str) -> Result<String> {
+        let mut old_session = self.get_session(session_id).await?;
+        let new_session = old_session.renewed();
         let new_id = new_session.id.clone();
 
-        // Invalidate old session
-        if let Some(session) = self.sessions.get_mut(session_id) {
-            session.invalidate();
-        }
-
-        // Store new session
-        self.sessions.insert(new_id.clone(), new_session);
+        self.store.save(&new_session).await?;
+        old_session.invalidate();
+        self.store.save(&old_session).await?;
 
         Ok(new_id)
     }
 
     /// Invalidate session
-    pub fn invalidate_session(&mut self, session_id: &str) -> Result<()> {
-        let session = self.sessions.get_mut(session_id)
+    pub async fn invalidate_session(&self, session_id: &str) -> Result<()> {
+        let mut session = self
+            .store
+            .load(session_id)
+            .await?
             .ok_or(SecurityError::SessionNotFound)?;
 
         session.invalidate();
-        Ok(())
+        self.store.save(&session).await
     }
 
-    /// Invalidate all sessions for a user
-    pub fn invalidate_user_sessions(&mut self, user_id: &str) {
-        for session in self.sessions.values_mut() {
-            if session.user_id == user_id {
-                session.invalidate();
+    /// Invalidate all sessions for a user, returning how many were active
+    pub async fn invalidate_user_sessions(&self, user_id: &str) -> Result<usize> {
+        self.invalidate_where(user_id, |_| true).await
+    }
+
+    /// Clean up invalidated, expired and idle sessions
+    pub async fn cleanup_expired(&self) -> Result<usize> {
+        let now = chrono::Utc::now();
+        let idle_cutoff = now - chrono::Duration::seconds(self.config.idle_timeout_secs as i64);
+        self.store.delete_expired(now, idle_cutoff).await
+    }
+
+    /// Run [`cleanup_expired`](Self::cleanup_expired) periodically
+    pub fn spawn_cleanup_task(self: Arc<Self>, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match self.cleanup_expired().await {
+                    Ok(0) => {}
+                    Ok(removed) => tracing::debug!("Removed {} expired sessions", removed),
+                    Err(e) => tracing::warn!("Session cleanup failed: {}", e),
+                }
             }
-        }
+        })
     }
 
-    /// Clean up expired sessions
-    pub fn cleanup_expired(&mut self) -> usize {
-        let before_count = self.sessions.len();
-        self.sessions.retain(|_, session| session.is_valid());
-        before_count - self.sessions.len()
+    /// List active sessions for user, most recently used first
+    pub async fn list_user_sessions(&self, user_id: &str) -> Result<Vec<Session>> {
+        let idle_timeout = self.config.idle_timeout_secs;
+        let mut sessions: Vec<_> = self
+            .store
+            .list_for_user(user_id)
+            .await?
+            .into_iter()
+            .filter(|s| s.is_active(idle_timeout))
+            .collect();
+        sessions.sort_by(|a, b| b.last_activity.cmp(&a.last_activity));
+        Ok(sessions)
     }
 
     /// Get active session count for user
-    pub fn user_session_count(&self, user_id: &str) -> usize {
-        self.sessions.values()
-            .filter(|s| s.user_id == user_id && s.is_valid())
-            .count()
+    pub async fn user_session_count(&self, user_id: &str) -> Result<usize> {
+        Ok(self.list_user_sessions(user_id).await?.len())
+    }
+
+    /// List the devices a user is signed in on, most recently used first
+    pub async fn list_devices(&self, user_id: &str) -> Result<Vec<DeviceSummary>> {
+        let mut devices: Vec<DeviceSummary> = Vec::new();
+
+        for session in self.list_user_sessions(user_id).await? {
+            match devices.iter_mut().find(|d| d.device_id == session.device_id) {
+                Some(device) => device.session_ids.push(session.id),
+                None => devices.push(DeviceSummary {
+                    device_id: session.device_id.clone(),
+                    label: session.metadata.device_label(),
+                    ip_address: session.metadata.ip_address.clone(),
+                    location: session.metadata.location.clone(),
+                    last_activity: session.last_activity,
+                    session_ids: vec![session.id],
+                }),
+            }
+        }
+
+        Ok(devices)
+    }
+
+    /// Sign a user out of one device, returning the number of sessions ended
+    pub async fn revoke_device(&self, user_id: &str, device_id: &str) -> Result<usize> {
+        self.invalidate_where(user_id, |s| s.device_id == device_id)
+            .await
+    }
+
+    /// End one of the user's own sessions
+    ///
+    /// Sessions belonging to someone else are reported as not found.
+    pub async fn revoke_session(&self, user_id: &str, session_id: &str) -> Result<()> {
+        match self.store.load(session_id).await? {
+            Some(mut session) if session.user_id == user_id => {
+                session.invalidate();
+                self.store.save(&session).await
+            }
+            _ => Err(SecurityError::SessionNotFound),
+        }
+    }
+
+    async fn invalidate_where<F>(&self, user_id: &str, predicate: F) -> Result<usize>
+    where
+        F: Fn(&Session) -> bool,
+    {
+        let mut count = 0;
+        for mut session in self.store.list_for_user(user_id).await? {
+            if session.invalidated || !predicate(&session) {
+                continue;
+            }
+            session.invalidate();
+            self.store.save(&session).await?;
+            count += 1;
+        }
+        Ok(count)
     }
 }
 
+/// A device with at least one active session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceSummary {
+    /// Stable device identifier
+    pub device_id: String,
+    /// Human-readable device label
+    pub label: String,
+    /// IP address of the most recent session
+    pub ip_address: String,
+    /// Location of the most recent session
+    pub location: Option<GeoLocation>,
+    /// Last activity across the device's sessions
+    pub last_activity: chrono::DateTime<chrono::Utc>,
+    /// Active sessions on this device
+    pub session_ids: Vec<String>,
+}
+
 /// Session information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Session {
@@ -138,6 +242,9 @@ pub struct Session {
     pub id: String,
     /// User ID
     pub user_id: String,
+    /// Device the session was created on
+    #[serde(default)]
+    pub device_id: String,
     /// Session metadata
     pub metadata: SessionMetadata,
     /// Created at
@@ -161,6 +268,7 @@ impl Session {
         Self {
             id: uuid::Uuid::new_v4().to_string(),
             user_id,
+            device_id: metadata.device_id(),
             metadata,
             created_at: now,
             last_activity: now,
@@ -180,6 +288,23 @@ impl Session {
         now < self.expires_at
     }
 
+    /// Check if session is valid and has not been idle too long
+    pub fn is_active(&self, idle_timeout_secs: u64) -> bool {
+        self.is_valid() && !self.is_idle(idle_timeout_secs)
+    }
+
+    /// Create a replacement session with a fresh ID and the same absolute expiry
+    pub fn renewed(&self) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            last_activity: now,
+            last_renewal: now,
+            invalidated: false,
+            ..self.clone()
+        }
+    }
+
     /// Check if session has been idle too long
     pub fn is_idle(&self, idle_timeout_secs: u64) -> bool {
         let now = chrono::Utc::now();
@@ -226,6 +351,9 @@ pub struct SessionMetadata {
     pub user_agent: Option<String>,
     /// Device fingerprint
     pub device_fingerprint: Option<String>,
+    /// User-assigned device name
+    #[serde(default)]
+    pub device_name: Option<String>,
     /// Geographic location
     pub location: Option<GeoLocation>,
     /// Additional custom data
@@ -239,10 +367,90 @@ impl SessionMetadata {
             ip_address,
             user_agent: None,
             device_fingerprint: None,
+            device_name: None,
             location: None,
             custom_data: HashMap::new(),
         }
     }
+
+    /// Set the user agent
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Set the client-provided device fingerprint
+    pub fn with_device_fingerprint(mut self, fingerprint: impl Into<String>) -> Self {
+        self.device_fingerprint = Some(fingerprint.into());
+        self
+    }
+
+    /// Set a user-assigned device name
+    pub fn with_device_name(mut self, name: impl Into<String>) -> Self {
+        self.device_name = Some(name.into());
+        self
+    }
+
+    /// Set the geographic location
+    pub fn with_location(mut self, location: GeoLocation) -> Self {
+        self.location = Some(location);
+        self
+    }
+
+    /// Derive a stable device identifier
+    ///
+    /// Uses the fingerprint and user agent; without a fingerprint the IP address
+    /// stands in, so the same browser on another network counts as a new device.
+    pub fn device_id(&self) -> String {
+        let mut hasher = Sha256::new();
+        match &self.device_fingerprint {
+            Some(fingerprint) => hasher.update(fingerprint.as_bytes()),
+            None => hasher.update(self.ip_address.as_bytes()),
+        }
+        hasher.update([0u8]);
+        hasher.update(self.user_agent.as_deref().unwrap_or_default().as_bytes());
+        hex::encode(&hasher.finalize()[..16])
+    }
+
+    /// Human-readable device label, e.g. "Firefox on Windows"
+    pub fn device_label(&self) -> String {
+        if let Some(name) = &self.device_name {
+            return name.clone();
+        }
+
+        let Some(user_agent) = &self.user_agent else {
+            return "Unknown device".to_string();
+        };
+
+        let browser = [
+            ("Edg/", "Edge"),
+            ("OPR/", "Opera"),
+            ("Firefox/", "Firefox"),
+            ("Chrome/", "Chrome"),
+            ("Safari/", "Safari"),
+        ]
+        .iter()
+        .find(|(token, _)| user_agent.contains(token))
+        .map(|(_, name)| *name);
+
+        let os = [
+            ("Windows", "Windows"),
+            ("iPhone", "iOS"),
+            ("iPad", "iPadOS"),
+            ("Android", "Android"),
+            ("Mac OS X", "macOS"),
+            ("Linux", "Linux"),
+        ]
+        .iter()
+        .find(|(token, _)| user_agent.contains(token))
+        .map(|(_, name)| *name);
+
+        match (browser, os) {
+            (Some(browser), Some(os)) => format!("{} on {}", browser, os),
+            (Some(name), None) | (None, Some(name)) => name.to_string(),
+            (None, None) => "Unknown device".to_string(),
+        }
+    }
 }
 
 /// Geographic location information
@@ -275,87 +483,176 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_session_creation() {
-        let mut manager = SessionManager::new(test_config());
+    #[tokio::test]
+    async fn test_session_creation() {
+        let manager = SessionManager::new(test_config());
         let metadata = SessionMetadata::basic("192.168.1.1".to_string());
 
-        let session = manager.create_session("user123".to_string(), metadata).unwrap();
+        let session = manager.create_session("user123".to_string(), metadata).await.unwrap();
 
         assert!(!session.id.is_empty());
         assert_eq!(session.user_id, "user123");
         assert!(session.is_valid());
     }
 
-    #[test]
-    fn test_session_retrieval() {
-        let mut manager = SessionManager::new(test_config());
+    #[tokio::test]
+    async fn test_session_retrieval() {
+        let manager = SessionManager::new(test_config());
         let metadata = SessionMetadata::basic("192.168.1.1".to_string());
 
-        let session = manager.create_session("user123".to_string(), metadata).unwrap();
+        let session = manager.create_session("user123".to_string(), metadata).await.unwrap();
         let session_id = session.id.clone();
 
-        let retrieved = manager.get_session(&session_id).unwrap();
+        let retrieved = manager.get_session(&session_id).await.unwrap();
         assert_eq!(retrieved.id, session_id);
     }
 
-    #[test]
-    fn test_session_touch() {
-        let mut manager = SessionManager::new(test_config());
+    #[tokio::test]
+    async fn test_list_user_sessions() {
+        let manager = SessionManager::new(test_config());
+
+        let first = manager
+            .create_session("user123".to_string(), SessionMetadata::basic("10.0.0.1".to_string()))
+            .await
+            .unwrap();
+        manager
+            .create_session("user123".to_string(), SessionMetadata::basic("10.0.0.2".to_string()))
+            .await
+            .unwrap();
+        manager
+            .create_session("other".to_string(), SessionMetadata::basic("10.0.0.3".to_string()))
+            .await
+            .unwrap();
+
+        manager.invalidate_session(&first.id).await.unwrap();
+
+        let sessions = manager.list_user_sessions("user123").await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].metadata.ip_address, "10.0.0.2");
+    }
+
+    #[tokio::test]
+    async fn test_session_touch() {
+        let manager = SessionManager::new(test_config());
         let metadata = SessionMetadata::basic("192.168.1.1".to_string());
 
-        let session = manager.create_session("user123".to_string(), metadata).unwrap();
+        let session = manager.create_session("user123".to_string(), metadata).await.unwrap();
         let session_id = session.id.clone();
 
         std::thread::sleep(std::time::Duration::from_millis(100));
 
-        manager.touch_session(&session_id).unwrap();
-        let updated = manager.get_session(&session_id).unwrap();
+        manager.touch_session(&session_id).await.unwrap();
+        let updated = manager.get_session(&session_id).await.unwrap();
 
         assert!(updated.last_activity > session.created_at);
     }
 
-    #[test]
-    fn test_session_invalidation() {
-        let mut manager = SessionManager::new(test_config());
+    #[tokio::test]
+    async fn test_session_invalidation() {
+        let manager = SessionManager::new(test_config());
         let metadata = SessionMetadata::basic("192.168.1.1".to_string());
 
-        let session = manager.create_session("user123".to_string(), metadata).unwrap();
+        let session = manager.create_session("user123".to_string(), metadata).await.unwrap();
         let session_id = session.id.clone();
 
-        manager.invalidate_session(&session_id).unwrap();
+        manager.invalidate_session(&session_id).await.unwrap();
 
-        let result = manager.get_session(&session_id);
+        let result = manager.get_session(&session_id).await;
         assert!(result.is_err());
     }
 
-    #[test]
-    fn test_concurrent_session_limit() {
-        let mut manager = SessionManager::new(test_config());
+    #[tokio::test]
+    async fn test_concurrent_session_limit() {
+        let manager = SessionManager::new(test_config());
         let metadata = SessionMetadata::basic("192.168.1.1".to_string());
 
         // Create 3 sessions (the limit)
         for _ in 0..3 {
-            manager.create_session("user123".to_string(), metadata.clone()).unwrap();
+            manager.create_session("user123".to_string(), metadata.clone()).await.unwrap();
         }
 
         // Fourth session should fail
-        let result = manager.create_session("user123".to_string(), metadata);
+        let result = manager.create_session("user123".to_string(), metadata).await;
         assert!(result.is_err());
     }
 
-    #[test]
-    fn test_user_session_invalidation() {
-        let mut manager = SessionManager::new(test_config());
+    #[tokio::test]
+    async fn test_user_session_invalidation() {
+        let manager = SessionManager::new(test_config());
         let metadata = SessionMetadata::basic("192.168.1.1".to_string());
 
-        manager.create_session("user123".to_string(), metadata.clone()).unwrap();
-        manager.create_session("user123".to_string(), metadata).unwrap();
+        manager.create_session("user123".to_string(), metadata.clone()).await.unwrap();
+        manager.create_session("user123".to_string(), metadata).await.unwrap();
+
+        assert_eq!(manager.user_session_count("user123").await.unwrap(), 2);
 
-        assert_eq!(manager.user_session_count("user123"), 2);
+        assert_eq!(manager.invalidate_user_sessions("user123").await.unwrap(), 2);
 
-        manager.invalidate_user_sessions("user123");
+        assert_eq!(manager.user_session_count("user123").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_idle_expiry() {
+        let manager = SessionManager::new(test_config());
+        let mut session = Session::new(
+            "user123".to_string(),
+            SessionMetadata::basic("192.168.1.1".to_string()),
+            manager.config(),
+        );
+        session.last_activity = chrono::Utc::now() - chrono::Duration::hours(1);
+        manager.store().save(&session).await.unwrap();
+
+        assert!(matches!(
+            manager.get_session(&session.id).await,
+            Err(SecurityError::SessionExpired)
+        ));
+        assert_eq!(manager.cleanup_expired().await.unwrap(), 1);
+        assert!(matches!(
+            manager.get_session(&session.id).await,
+            Err(SecurityError::SessionNotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_renewal_keeps_absolute_expiry() {
+        let manager = SessionManager::new(test_config());
+        let session = manager
+            .create_session("user123".to_string(), SessionMetadata::basic("10.0.0.1".to_string()))
+            .await
+            .unwrap();
+
+        let new_id = manager.renew_session(&session.id).await.unwrap();
+        let renewed = manager.get_session(&new_id).await.unwrap();
+
+        assert_eq!(renewed.expires_at, session.expires_at);
+        assert!(manager.get_session(&session.id).await.is_err());
+    }
 
-        assert_eq!(manager.user_session_count("user123"), 0);
+    #[tokio::test]
+    async fn test_devices() {
+        let manager = SessionManager::new(test_config());
+        let laptop = SessionMetadata::basic("10.0.0.1".to_string())
+            .with_user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) Firefox/121.0")
+            .with_device_fingerprint("laptop");
+        let phone = SessionMetadata::basic("10.0.0.2".to_string())
+            .with_user_agent("Mozilla/5.0 (iPhone; CPU iPhone OS 17_0) Safari/604.1")
+            .with_device_fingerprint("phone");
+
+        manager.create_session("user123".to_string(), laptop.clone()).await.unwrap();
+        manager.create_session("user123".to_string(), laptop.clone()).await.unwrap();
+        let phone_session = manager.create_session("user123".to_string(), phone).await.unwrap();
+
+        let devices = manager.list_devices("user123").await.unwrap();
+        assert_eq!(devices.len(), 2);
+        let laptop_device = devices.iter().find(|d| d.device_id == laptop.device_id()).unwrap();
+        assert_eq!(laptop_device.label, "Firefox on Windows");
+        assert_eq!(laptop_device.session_ids.len(), 2);
+
+        assert!(manager.revoke_session("other", &phone_session.id).await.is_err());
+        assert_eq!(manager.revoke_device("user123", &laptop.device_id()).await.unwrap(), 2);
+
+        let devices = manager.list_devices("user123").await.unwrap();
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].label, "Safari on iOS");
     }
 }
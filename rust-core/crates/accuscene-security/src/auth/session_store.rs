@@ -0,0 +1,274 @@
+//! Session persistence
+//!
+//! [`SessionManager`](super::SessionManager) keeps sessions in a pluggable
+//! [`SessionStore`], so sessions survive restarts and can be shared between
+//! instances. Sessions are stored as JSON alongside the columns needed to
+//! look them up and expire them.
+//!
+//! Backends: [`MemorySessionStore`] (default), `SqliteSessionStore` (feature
+//! `sqlite`) and `PostgresSessionStore` (feature `postgres`).
+
+use super::session::Session;
+use crate::error::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+/// Storage backend for sessions
+#[async_trait]
+pub trait SessionStore: Send + Sync {
+    /// Insert or update a session
+    async fn save(&self, session: &Session) -> Result<()>;
+
+    /// Load a session by ID
+    async fn load(&self, session_id: &str) -> Result<Option<Session>>;
+
+    /// Load all sessions of a user, including expired ones
+    async fn list_for_user(&self, user_id: &str) -> Result<Vec<Session>>;
+
+    /// Delete sessions that are invalidated, expired at `now`, or idle since `idle_cutoff`
+    async fn delete_expired(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        idle_cutoff: chrono::DateTime<chrono::Utc>,
+    ) -> Result<usize>;
+}
+
+/// In-memory session store; sessions are lost on restart
+#[derive(Default)]
+pub struct MemorySessionStore {
+    sessions: RwLock<HashMap<String, Session>>,
+}
+
+impl MemorySessionStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl SessionStore for MemorySessionStore {
+    async fn save(&self, session: &Session) -> Result<()> {
+        self.sessions.write().await.insert(session.id.clone(), session.clone());
+        Ok(())
+    }
+
+    async fn load(&self, session_id: &str) -> Result<Option<Session>> {
+        Ok(self.sessions.read().await.get(session_id).cloned())
+    }
+
+    async fn list_for_user(&self, user_id: &str) -> Result<Vec<Session>> {
+        Ok(self
+            .sessions
+            .read()
+            .await
+            .values()
+            .filter(|s| s.user_id == user_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn delete_expired(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        idle_cutoff: chrono::DateTime<chrono::Utc>,
+    ) -> Result<usize> {
+        let mut sessions = self.sessions.write().await;
+        let before = sessions.len();
+        sessions
+            .retain(|_, s| !s.invalidated && s.expires_at > now && s.last_activity > idle_cutoff);
+        Ok(before - sessions.len())
+    }
+}
+
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+mod sql {
+    use super::*;
+    use crate::error::SecurityError;
+
+    pub(super) const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS auth_sessions (
+        id TEXT PRIMARY KEY,
+        user_id TEXT NOT NULL,
+        device_id TEXT NOT NULL,
+        expires_at BIGINT NOT NULL,
+        last_activity BIGINT NOT NULL,
+        invalidated BOOLEAN NOT NULL,
+        data TEXT NOT NULL
+    )";
+
+    pub(super) const CREATE_INDEX: &str =
+        "CREATE INDEX IF NOT EXISTS idx_auth_sessions_user ON auth_sessions (user_id)";
+
+    pub(super) const UPSERT: &str = "INSERT INTO auth_sessions
+        (id, user_id, device_id, expires_at, last_activity, invalidated, data)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (id) DO UPDATE SET
+            user_id = excluded.user_id,
+            device_id = excluded.device_id,
+            expires_at = excluded.expires_at,
+            last_activity = excluded.last_activity,
+            invalidated = excluded.invalidated,
+            data = excluded.data";
+
+    pub(super) const SELECT_ONE: &str = "SELECT data FROM auth_sessions WHERE id = $1";
+
+    pub(super) const SELECT_USER: &str = "SELECT data FROM auth_sessions WHERE user_id = $1";
+
+    pub(super) const DELETE_EXPIRED: &str = "DELETE FROM auth_sessions
+        WHERE invalidated OR expires_at <= $1 OR last_activity <= $2";
+
+    pub(super) fn store_error(e: sqlx::Error) -> SecurityError {
+        SecurityError::SessionStoreFailed(e.to_string())
+    }
+
+    pub(super) fn encode(session: &Session) -> Result<String> {
+        serde_json::to_string(session)
+            .map_err(|e| SecurityError::SessionStoreFailed(format!("Encoding session: {}", e)))
+    }
+
+    pub(super) fn decode(data: &str) -> Result<Session> {
+        serde_json::from_str(data)
+            .map_err(|e| SecurityError::SessionStoreFailed(format!("Decoding session: {}", e)))
+    }
+}
+
+/// SQL session store over an sqlx pool; both backends share the same schema
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+macro_rules! sql_session_store {
+    ($(#[$meta:meta])* $name:ident, $pool:ty) => {
+        $(#[$meta])*
+        pub struct $name {
+            pool: $pool,
+        }
+
+        impl $name {
+            /// Create a store on a connection pool
+            pub fn new(pool: $pool) -> Self {
+                Self { pool }
+            }
+
+            /// Create the sessions table if it does not exist
+            pub async fn migrate(&self) -> Result<()> {
+                sqlx::query(sql::CREATE_TABLE)
+                    .execute(&self.pool)
+                    .await
+                    .map_err(sql::store_error)?;
+                sqlx::query(sql::CREATE_INDEX)
+                    .execute(&self.pool)
+                    .await
+                    .map_err(sql::store_error)?;
+                Ok(())
+            }
+        }
+
+        #[async_trait]
+        impl SessionStore for $name {
+            async fn save(&self, session: &Session) -> Result<()> {
+                sqlx::query(sql::UPSERT)
+                    .bind(&session.id)
+                    .bind(&session.user_id)
+                    .bind(&session.device_id)
+                    .bind(session.expires_at.timestamp_millis())
+                    .bind(session.last_activity.timestamp_millis())
+                    .bind(session.invalidated)
+                    .bind(sql::encode(session)?)
+                    .execute(&self.pool)
+                    .await
+                    .map_err(sql::store_error)?;
+                Ok(())
+            }
+
+            async fn load(&self, session_id: &str) -> Result<Option<Session>> {
+                let row: Option<(String,)> = sqlx::query_as(sql::SELECT_ONE)
+                    .bind(session_id)
+                    .fetch_optional(&self.pool)
+                    .await
+                    .map_err(sql::store_error)?;
+                row.map(|(data,)| sql::decode(&data)).transpose()
+            }
+
+            async fn list_for_user(&self, user_id: &str) -> Result<Vec<Session>> {
+                let rows: Vec<(String,)> = sqlx::query_as(sql::SELECT_USER)
+                    .bind(user_id)
+                    .fetch_all(&self.pool)
+                    .await
+                    .map_err(sql::store_error)?;
+                rows.iter().map(|(data,)| sql::decode(data)).collect()
+            }
+
+            async fn delete_expired(
+                &self,
+                now: chrono::DateTime<chrono::Utc>,
+                idle_cutoff: chrono::DateTime<chrono::Utc>,
+            ) -> Result<usize> {
+                let result = sqlx::query(sql::DELETE_EXPIRED)
+                    .bind(now.timestamp_millis())
+                    .bind(idle_cutoff.timestamp_millis())
+                    .execute(&self.pool)
+                    .await
+                    .map_err(sql::store_error)?;
+                Ok(result.rows_affected() as usize)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "sqlite")]
+sql_session_store!(
+    /// Session store on SQLite
+    SqliteSessionStore,
+    sqlx::SqlitePool
+);
+
+#[cfg(feature = "postgres")]
+sql_session_store!(
+    /// Session store on PostgreSQL
+    PostgresSessionStore,
+    sqlx::PgPool
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::SessionMetadata;
+    use crate::config::SessionConfig;
+
+    async fn exercise(store: &dyn SessionStore) {
+        let config = SessionConfig::default();
+        let mut session = Session::new(
+            "user123".to_string(),
+            SessionMetadata::basic("10.0.0.1".to_string()),
+            &config,
+        );
+        store.save(&session).await.unwrap();
+
+        let loaded = store.load(&session.id).await.unwrap().unwrap();
+        assert_eq!(loaded.user_id, "user123");
+        assert_eq!(loaded.device_id, session.device_id);
+        assert!(store.load("missing").await.unwrap().is_none());
+
+        session.invalidate();
+        store.save(&session).await.unwrap();
+        assert!(store.list_for_user("user123").await.unwrap()[0].invalidated);
+
+        let now = chrono::Utc::now();
+        let removed = store.delete_expired(now, now - chrono::Duration::hours(1)).await.unwrap();
+        assert_eq!(removed, 1);
+        assert!(store.list_for_user("user123").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_memory_store() {
+        exercise(&MemorySessionStore::new()).await;
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_sqlite_store() {
+        let pool = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+        let store = SqliteSessionStore::new(pool);
+        store.migrate().await.unwrap();
+        exercise(&store).await;
+    }
+}
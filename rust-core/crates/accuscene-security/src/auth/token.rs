@@ -169,6 +169,9 @@ pub struct TokenClaims {
     /// MFA verified
     #[serde(default)]
     pub mfa_verified: bool,
+    /// Organization (tenant) the user acts for
+    #[serde(default)]
+    pub org_id: Option<String>,
     /// Additional metadata
     #[serde(default)]
     pub metadata: HashMap<String, String>,
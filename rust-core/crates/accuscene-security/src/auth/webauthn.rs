@@ -0,0 +1,1001 @@
+//! WebAuthn (FIDO2) registration and assertion ceremonies
+//!
+//! Implements the relying party side of WebAuthn for security keys and
+//! passkeys: issuing single-use challenges, verifying `none` and `packed`
+//! attestation at registration, and verifying assertions at sign-in.
+//! Credential keys may be ES256, EdDSA (Ed25519) or RS256.
+
+use super::mfa::{MfaEnrollment, WebAuthnCredential};
+use crate::config::WebAuthnConfig;
+use crate::error::{Result, SecurityError};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64URL, Engine};
+use ciborium::value::Value;
+use rand::RngCore;
+use ring::signature::{self, UnparsedPublicKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// COSE algorithm identifier for ECDSA P-256 with SHA-256
+pub const COSE_ALG_ES256: i64 = -7;
+/// COSE algorithm identifier for EdDSA
+pub const COSE_ALG_EDDSA: i64 = -8;
+/// COSE algorithm identifier for RSASSA-PKCS1-v1_5 with SHA-256
+pub const COSE_ALG_RS256: i64 = -257;
+
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_CREDENTIAL: u8 = 0x40;
+
+/// Certificate extension carrying the authenticator AAGUID
+const AAGUID_EXTENSION_OID: &str = "1.3.6.1.4.1.45724.1.1.4";
+
+/// WebAuthn relying party service
+pub struct WebAuthnService {
+    config: WebAuthnConfig,
+    challenges: HashMap<String, PendingChallenge>,
+}
+
+impl WebAuthnService {
+    /// Create a new WebAuthn service
+    pub fn new(config: WebAuthnConfig) -> Self {
+        Self {
+            config,
+            challenges: HashMap::new(),
+        }
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &WebAuthnConfig {
+        &self.config
+    }
+
+    /// Begin registering a new credential for a user
+    pub fn start_registration(
+        &mut self,
+        user_id: &str,
+        user_name: &str,
+        enrollment: &MfaEnrollment,
+    ) -> CredentialCreationOptions {
+        let challenge = self.issue_challenge(user_id, Ceremony::Registration);
+
+        CredentialCreationOptions {
+            challenge,
+            rp: RelyingParty {
+                id: self.config.rp_id.clone(),
+                name: self.config.rp_name.clone(),
+            },
+            user: UserEntity {
+                id: BASE64URL.encode(user_id.as_bytes()),
+                name: user_name.to_string(),
+                display_name: user_name.to_string(),
+            },
+            pub_key_cred_params: [COSE_ALG_ES256, COSE_ALG_EDDSA, COSE_ALG_RS256]
+                .into_iter()
+                .map(CredentialParameter::public_key)
+                .collect(),
+            timeout: self.config.timeout_ms,
+            attestation: self.config.attestation.clone(),
+            exclude_credentials: descriptors(enrollment),
+            authenticator_selection: AuthenticatorSelection {
+                resident_key: "preferred".to_string(),
+                user_verification: self.user_verification().to_string(),
+            },
+        }
+    }
+
+    /// Verify a registration response and add the credential to the enrollment
+    pub fn finish_registration(
+        &mut self,
+        user_id: &str,
+        response: &RegistrationResponse,
+        nickname: Option<String>,
+        enrollment: &mut MfaEnrollment,
+    ) -> Result<WebAuthnCredential> {
+        let client_data_json = decode_field(&response.client_data_json, "clientDataJSON")?;
+        self.verify_client_data(&client_data_json, user_id, Ceremony::Registration)?;
+
+        let attestation_object = decode_field(&response.attestation_object, "attestationObject")?;
+        let attestation = AttestationObject::parse(&attestation_object)?;
+        let auth_data = AuthenticatorData::parse(&attestation.auth_data)?;
+        self.verify_authenticator_data(&auth_data)?;
+
+        let attested = auth_data
+            .attested
+            .as_ref()
+            .ok_or_else(|| webauthn_error("Attested credential data missing"))?;
+        let credential_id = BASE64URL.encode(&attested.credential_id);
+        if credential_id != response.id {
+            return Err(webauthn_error("Credential ID mismatch"));
+        }
+        if enrollment.webauthn_credential(&credential_id).is_some() {
+            return Err(webauthn_error("Credential already registered"));
+        }
+
+        let public_key = CosePublicKey::parse(&attested.public_key)?;
+        let client_data_hash = Sha256::digest(&client_data_json);
+        let attestation_type =
+            attestation.verify(attested, &public_key, client_data_hash.as_slice())?;
+
+        let credential = WebAuthnCredential {
+            id: credential_id,
+            public_key: attested.public_key.clone(),
+            sign_count: auth_data.sign_count,
+            nickname,
+            created_at: chrono::Utc::now(),
+            last_used: None,
+            aaguid: hex::encode(attested.aaguid),
+            attestation: attestation_type,
+        };
+        enrollment.add_webauthn_credential(credential.clone())?;
+
+        Ok(credential)
+    }
+
+    /// Begin an assertion against the user's registered credentials
+    pub fn start_authentication(
+        &mut self,
+        user_id: &str,
+        enrollment: &MfaEnrollment,
+    ) -> Result<CredentialRequestOptions> {
+        if enrollment.webauthn_credentials.is_empty() {
+            return Err(webauthn_error("No WebAuthn credentials registered"));
+        }
+
+        Ok(CredentialRequestOptions {
+            challenge: self.issue_challenge(user_id, Ceremony::Authentication),
+            timeout: self.config.timeout_ms,
+            rp_id: self.config.rp_id.clone(),
+            allow_credentials: descriptors(enrollment),
+            user_verification: self.user_verification().to_string(),
+        })
+    }
+
+    /// Verify an assertion, updating the credential's sign count and last use
+    pub fn finish_authentication(
+        &mut self,
+        user_id: &str,
+        response: &AssertionResponse,
+        enrollment: &mut MfaEnrollment,
+    ) -> Result<()> {
+        let credential = enrollment
+            .webauthn_credentials
+            .iter_mut()
+            .find(|c| c.id == response.id)
+            .ok_or_else(|| webauthn_error("Unknown credential"))?;
+
+        if let Some(user_handle) = &response.user_handle {
+            if decode_field(user_handle, "userHandle")? != user_id.as_bytes() {
+                return Err(webauthn_error("User handle mismatch"));
+            }
+        }
+
+        let client_data_json = decode_field(&response.client_data_json, "clientDataJSON")?;
+        self.verify_client_data(&client_data_json, user_id, Ceremony::Authentication)?;
+
+        let raw_auth_data = decode_field(&response.authenticator_data, "authenticatorData")?;
+        let auth_data = AuthenticatorData::parse(&raw_auth_data)?;
+        self.verify_authenticator_data(&auth_data)?;
+
+        let signature = decode_field(&response.signature, "signature")?;
+        let mut signed = raw_auth_data.clone();
+        signed.extend_from_slice(&Sha256::digest(&client_data_json));
+        CosePublicKey::parse(&credential.public_key)?.verify(&signed, &signature)?;
+
+        // Counters that stop increasing indicate a cloned authenticator;
+        // authenticators without counters always report zero
+        if (auth_data.sign_count != 0 || credential.sign_count != 0)
+            && auth_data.sign_count <= credential.sign_count
+        {
+            return Err(webauthn_error("Sign count did not increase"));
+        }
+
+        credential.sign_count = auth_data.sign_count;
+        credential.last_used = Some(chrono::Utc::now());
+        Ok(())
+    }
+
+    /// Number of outstanding challenges
+    pub fn pending_challenges(&self) -> usize {
+        self.challenges.len()
+    }
+
+    /// Drop expired challenges
+    pub fn purge_expired_challenges(&mut self) -> usize {
+        let before = self.challenges.len();
+        let now = chrono::Utc::now();
+        self.challenges.retain(|_, pending| pending.expires_at > now);
+        before - self.challenges.len()
+    }
+
+    fn issue_challenge(&mut self, user_id: &str, ceremony: Ceremony) -> String {
+        self.purge_expired_challenges();
+
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let challenge = BASE64URL.encode(bytes);
+
+        self.challenges.insert(
+            challenge.clone(),
+            PendingChallenge {
+                user_id: user_id.to_string(),
+                ceremony,
+                expires_at: chrono::Utc::now()
+                    + chrono::Duration::milliseconds(self.config.timeout_ms as i64),
+            },
+        );
+
+        challenge
+    }
+
+    /// Check client data against the pending challenge, consuming it
+    fn verify_client_data(
+        &mut self,
+        client_data_json: &[u8],
+        user_id: &str,
+        ceremony: Ceremony,
+    ) -> Result<()> {
+        let client_data: ClientData = serde_json::from_slice(client_data_json)
+            .map_err(|e| webauthn_error(format!("Malformed client data: {}", e)))?;
+
+        if client_data.kind != ceremony.client_data_type() {
+            return Err(webauthn_error(format!(
+                "Unexpected client data type '{}'",
+                client_data.kind
+            )));
+        }
+
+        let pending = self
+            .challenges
+            .remove(&client_data.challenge)
+            .ok_or_else(|| webauthn_error("Unknown or already used challenge"))?;
+        if pending.user_id != user_id || pending.ceremony != ceremony {
+            return Err(webauthn_error("Challenge was issued for another ceremony"));
+        }
+        if pending.expires_at <= chrono::Utc::now() {
+            return Err(webauthn_error("Challenge expired"));
+        }
+
+        if client_data.origin != self.config.origin {
+            return Err(webauthn_error(format!(
+                "Unexpected origin '{}'",
+                client_data.origin
+            )));
+        }
+        if client_data.cross_origin {
+            return Err(webauthn_error("Cross-origin ceremonies are not allowed"));
+        }
+
+        Ok(())
+    }
+
+    /// Check relying party binding and user presence flags
+    fn verify_authenticator_data(&self, auth_data: &AuthenticatorData) -> Result<()> {
+        if auth_data.rp_id_hash != Sha256::digest(self.config.rp_id.as_bytes()).as_slice() {
+            return Err(webauthn_error("Relying party ID hash mismatch"));
+        }
+        if auth_data.flags & FLAG_USER_PRESENT == 0 {
+            return Err(webauthn_error("User not present"));
+        }
+        if self.config.require_user_verification && auth_data.flags & FLAG_USER_VERIFIED == 0 {
+            return Err(webauthn_error("User not verified"));
+        }
+        Ok(())
+    }
+
+    fn user_verification(&self) -> &'static str {
+        if self.config.require_user_verification {
+            "required"
+        } else {
+            "preferred"
+        }
+    }
+}
+
+/// Credential descriptors for a user's registered credentials
+fn descriptors(enrollment: &MfaEnrollment) -> Vec<CredentialDescriptor> {
+    enrollment
+        .webauthn_credentials
+        .iter()
+        .map(|c| CredentialDescriptor {
+            kind: "public-key".to_string(),
+            id: c.id.clone(),
+        })
+        .collect()
+}
+
+fn decode_field(value: &str, field: &str) -> Result<Vec<u8>> {
+    BASE64URL
+        .decode(value.trim_end_matches('='))
+        .map_err(|e| webauthn_error(format!("Invalid {} encoding: {}", field, e)))
+}
+
+fn webauthn_error(message: impl Into<String>) -> SecurityError {
+    SecurityError::WebAuthnFailed(message.into())
+}
+
+/// Ceremony a challenge was issued for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ceremony {
+    Registration,
+    Authentication,
+}
+
+impl Ceremony {
+    fn client_data_type(&self) -> &'static str {
+        match self {
+            Ceremony::Registration => "webauthn.create",
+            Ceremony::Authentication => "webauthn.get",
+        }
+    }
+}
+
+/// Challenge awaiting a response
+#[derive(Debug, Clone)]
+struct PendingChallenge {
+    user_id: String,
+    ceremony: Ceremony,
+    expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// How a credential's provenance was attested at registration
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AttestationType {
+    /// No attestation was provided
+    #[default]
+    None,
+    /// Signed with the credential key itself
+    SelfAttestation,
+    /// Signed with an attestation certificate
+    Basic,
+}
+
+/// Options for `navigator.credentials.create()`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialCreationOptions {
+    /// Base64url challenge
+    pub challenge: String,
+    /// Relying party
+    pub rp: RelyingParty,
+    /// User account
+    pub user: UserEntity,
+    /// Accepted credential algorithms, in order of preference
+    pub pub_key_cred_params: Vec<CredentialParameter>,
+    /// Timeout in milliseconds
+    pub timeout: u64,
+    /// Attestation conveyance preference
+    pub attestation: String,
+    /// Credentials already registered for the user
+    pub exclude_credentials: Vec<CredentialDescriptor>,
+    /// Authenticator requirements
+    pub authenticator_selection: AuthenticatorSelection,
+}
+
+/// Options for `navigator.credentials.get()`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialRequestOptions {
+    /// Base64url challenge
+    pub challenge: String,
+    /// Timeout in milliseconds
+    pub timeout: u64,
+    /// Relying party ID
+    pub rp_id: String,
+    /// Credentials the user may sign with
+    pub allow_credentials: Vec<CredentialDescriptor>,
+    /// User verification requirement
+    pub user_verification: String,
+}
+
+/// Relying party entity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelyingParty {
+    /// Relying party ID (domain)
+    pub id: String,
+    /// Display name
+    pub name: String,
+}
+
+/// User entity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserEntity {
+    /// Base64url user handle
+    pub id: String,
+    /// Account name
+    pub name: String,
+    /// Display name
+    pub display_name: String,
+}
+
+/// Accepted credential type and algorithm
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialParameter {
+    /// Credential type
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// COSE algorithm identifier
+    pub alg: i64,
+}
+
+impl CredentialParameter {
+    fn public_key(alg: i64) -> Self {
+        Self {
+            kind: "public-key".to_string(),
+            alg,
+        }
+    }
+}
+
+/// Reference to a registered credential
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialDescriptor {
+    /// Credential type
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// Base64url credential ID
+    pub id: String,
+}
+
+/// Authenticator selection criteria
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticatorSelection {
+    /// Discoverable credential (passkey) preference
+    pub resident_key: String,
+    /// User verification requirement
+    pub user_verification: String,
+}
+
+/// Response of `navigator.credentials.create()`, base64url encoded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistrationResponse {
+    /// Credential ID
+    pub id: String,
+    /// Client data JSON
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    /// CBOR attestation object
+    pub attestation_object: String,
+}
+
+/// Response of `navigator.credentials.get()`, base64url encoded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertionResponse {
+    /// Credential ID
+    pub id: String,
+    /// Client data JSON
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    /// Authenticator data
+    pub authenticator_data: String,
+    /// Signature over authenticator data and client data hash
+    pub signature: String,
+    /// User handle, returned for discoverable credentials
+    #[serde(default)]
+    pub user_handle: Option<String>,
+}
+
+/// Collected client data
+#[derive(Debug, Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    kind: String,
+    challenge: String,
+    origin: String,
+    #[serde(default, rename = "crossOrigin")]
+    cross_origin: bool,
+}
+
+/// Parsed authenticator data
+#[derive(Debug)]
+struct AuthenticatorData {
+    rp_id_hash: Vec<u8>,
+    flags: u8,
+    sign_count: u32,
+    attested: Option<AttestedCredential>,
+}
+
+/// Attested credential data of a registration
+#[derive(Debug)]
+struct AttestedCredential {
+    aaguid: [u8; 16],
+    credential_id: Vec<u8>,
+    /// COSE-encoded public key
+    public_key: Vec<u8>,
+}
+
+impl AuthenticatorData {
+    fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < 37 {
+            return Err(webauthn_error("Authenticator data too short"));
+        }
+
+        let flags = data[32];
+        let sign_count = u32::from_be_bytes([data[33], data[34], data[35], data[36]]);
+
+        let attested = if flags & FLAG_ATTESTED_CREDENTIAL != 0 {
+            let rest = &data[37..];
+            if rest.len() < 18 {
+                return Err(webauthn_error("Attested credential data truncated"));
+            }
+
+            let mut aaguid = [0u8; 16];
+            aaguid.copy_from_slice(&rest[..16]);
+            let id_len = u16::from_be_bytes([rest[16], rest[17]]) as usize;
+            let rest = &rest[18..];
+            if rest.len() < id_len {
+                return Err(webauthn_error("Credential ID truncated"));
+            }
+
+            let (credential_id, mut key_bytes) = rest.split_at(id_len);
+            let before = key_bytes.len();
+            let _: Value = ciborium::de::from_reader(&mut key_bytes)
+                .map_err(|e| webauthn_error(format!("Malformed credential public key: {}", e)))?;
+            let key_len = before - key_bytes.len();
+
+            Some(AttestedCredential {
+                aaguid,
+                credential_id: credential_id.to_vec(),
+                public_key: rest[id_len..id_len + key_len].to_vec(),
+            })
+        } else {
+            None
+        };
+
+        Ok(Self {
+            rp_id_hash: data[..32].to_vec(),
+            flags,
+            sign_count,
+            attested,
+        })
+    }
+}
+
+/// Decoded attestation object
+struct AttestationObject {
+    fmt: String,
+    att_stmt: Vec<(Value, Value)>,
+    auth_data: Vec<u8>,
+}
+
+impl AttestationObject {
+    fn parse(bytes: &[u8]) -> Result<Self> {
+        let value: Value = ciborium::de::from_reader(bytes)
+            .map_err(|e| webauthn_error(format!("Malformed attestation object: {}", e)))?;
+        let map = value
+            .into_map()
+            .map_err(|_| webauthn_error("Attestation object is not a map"))?;
+
+        let mut fmt = None;
+        let mut att_stmt = None;
+        let mut auth_data = None;
+        for (key, value) in map {
+            match key.as_text() {
+                Some("fmt") => fmt = value.into_text().ok(),
+                Some("attStmt") => att_stmt = value.into_map().ok(),
+                Some("authData") => auth_data = value.into_bytes().ok(),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            fmt: fmt.ok_or_else(|| webauthn_error("Attestation format missing"))?,
+            att_stmt: att_stmt.ok_or_else(|| webauthn_error("Attestation statement missing"))?,
+            auth_data: auth_data.ok_or_else(|| webauthn_error("Authenticator data missing"))?,
+        })
+    }
+
+    /// Verify the attestation statement
+    fn verify(
+        &self,
+        attested: &AttestedCredential,
+        credential_key: &CosePublicKey,
+        client_data_hash: &[u8],
+    ) -> Result<AttestationType> {
+        match self.fmt.as_str() {
+            "none" => {
+                if !self.att_stmt.is_empty() {
+                    return Err(webauthn_error("Non-empty statement for 'none' attestation"));
+                }
+                Ok(AttestationType::None)
+            }
+            "packed" => self.verify_packed(attested, credential_key, client_data_hash),
+            other => Err(webauthn_error(format!(
+                "Unsupported attestation format '{}'",
+                other
+            ))),
+        }
+    }
+
+    fn verify_packed(
+        &self,
+        attested: &AttestedCredential,
+        credential_key: &CosePublicKey,
+        client_data_hash: &[u8],
+    ) -> Result<AttestationType> {
+        let alg = text_get(&self.att_stmt, "alg")
+            .and_then(value_to_i64)
+            .ok_or_else(|| webauthn_error("Packed attestation algorithm missing"))?;
+        let sig = text_get(&self.att_stmt, "sig")
+            .and_then(Value::as_bytes)
+            .ok_or_else(|| webauthn_error("Packed attestation signature missing"))?;
+
+        let mut signed = self.auth_data.clone();
+        signed.extend_from_slice(client_data_hash);
+
+        match text_get(&self.att_stmt, "x5c") {
+            Some(x5c) => {
+                let certificate = x5c
+                    .as_array()
+                    .and_then(|chain| chain.first())
+                    .and_then(Value::as_bytes)
+                    .ok_or_else(|| webauthn_error("Attestation certificate missing"))?;
+                verify_attestation_certificate(certificate, &attested.aaguid, alg, &signed, sig)?;
+                Ok(AttestationType::Basic)
+            }
+            None => {
+                if alg != credential_key.alg {
+                    return Err(webauthn_error("Self attestation algorithm mismatch"));
+                }
+                credential_key.verify(&signed, sig)?;
+                Ok(AttestationType::SelfAttestation)
+            }
+        }
+    }
+}
+
+/// Check a packed attestation certificate and the signature made with it
+fn verify_attestation_certificate(
+    der: &[u8],
+    aaguid: &[u8; 16],
+    alg: i64,
+    signed: &[u8],
+    sig: &[u8],
+) -> Result<()> {
+    use x509_parser::prelude::*;
+
+    let (_, certificate) = X509Certificate::from_der(der)
+        .map_err(|e| webauthn_error(format!("Malformed attestation certificate: {}", e)))?;
+
+    if certificate.version() != X509Version::V3 {
+        return Err(webauthn_error("Attestation certificate must be X.509 v3"));
+    }
+    if !certificate.validity().is_valid() {
+        return Err(webauthn_error("Attestation certificate is not valid now"));
+    }
+    let authenticator_unit = certificate
+        .subject()
+        .iter_organizational_unit()
+        .any(|unit| unit.as_str().ok() == Some("Authenticator Attestation"));
+    if !authenticator_unit {
+        return Err(webauthn_error("Attestation certificate subject OU mismatch"));
+    }
+    if let Ok(Some(constraints)) = certificate.basic_constraints() {
+        if constraints.value.ca {
+            return Err(webauthn_error("Attestation certificate must not be a CA"));
+        }
+    }
+    if let Some(extension) = certificate
+        .extensions()
+        .iter()
+        .find(|e| e.oid.to_id_string() == AAGUID_EXTENSION_OID)
+    {
+        // The extension value is an OCTET STRING wrapping the 16 byte AAGUID
+        if extension.value.len() != 18 || &extension.value[2..] != aaguid.as_slice() {
+            return Err(webauthn_error("Attestation certificate AAGUID mismatch"));
+        }
+    }
+
+    let public_key = certificate.public_key().subject_public_key.data.as_ref();
+    let algorithm = verification_algorithm(alg)?;
+    UnparsedPublicKey::new(algorithm, public_key)
+        .verify(signed, sig)
+        .map_err(|_| webauthn_error("Attestation signature invalid"))
+}
+
+fn verification_algorithm(alg: i64) -> Result<&'static dyn signature::VerificationAlgorithm> {
+    match alg {
+        COSE_ALG_ES256 => Ok(&signature::ECDSA_P256_SHA256_ASN1),
+        COSE_ALG_EDDSA => Ok(&signature::ED25519),
+        COSE_ALG_RS256 => Ok(&signature::RSA_PKCS1_2048_8192_SHA256),
+        other => Err(webauthn_error(format!("Unsupported algorithm {}", other))),
+    }
+}
+
+/// Credential public key decoded from COSE
+#[derive(Debug)]
+struct CosePublicKey {
+    alg: i64,
+    material: KeyMaterial,
+}
+
+#[derive(Debug)]
+enum KeyMaterial {
+    /// Uncompressed P-256 point
+    Ec2(Vec<u8>),
+    /// Ed25519 public key
+    Okp(Vec<u8>),
+    /// RSA modulus and exponent
+    Rsa { n: Vec<u8>, e: Vec<u8> },
+}
+
+impl CosePublicKey {
+    fn parse(bytes: &[u8]) -> Result<Self> {
+        let value: Value = ciborium::de::from_reader(bytes)
+            .map_err(|e| webauthn_error(format!("Malformed COSE key: {}", e)))?;
+        let map = value
+            .as_map()
+            .ok_or_else(|| webauthn_error("COSE key is not a map"))?;
+
+        let int = |key: i64| int_get(map, key).and_then(value_to_i64);
+        let param = |key: i64| -> Result<Vec<u8>> {
+            int_get(map, key)
+                .and_then(Value::as_bytes)
+                .cloned()
+                .ok_or_else(|| webauthn_error(format!("COSE key parameter {} missing", key)))
+        };
+
+        let kty = int(1).ok_or_else(|| webauthn_error("COSE key type missing"))?;
+        let alg = int(3).ok_or_else(|| webauthn_error("COSE key algorithm missing"))?;
+
+        let material = match (kty, alg) {
+            (2, COSE_ALG_ES256) => {
+                if int(-1) != Some(1) {
+                    return Err(webauthn_error("Only the P-256 curve is supported"));
+                }
+                let (x, y) = (param(-2)?, param(-3)?);
+                if x.len() != 32 || y.len() != 32 {
+                    return Err(webauthn_error("Invalid P-256 coordinates"));
+                }
+                let mut point = Vec::with_capacity(65);
+                point.push(0x04);
+                point.extend_from_slice(&x);
+                point.extend_from_slice(&y);
+                KeyMaterial::Ec2(point)
+            }
+            (1, COSE_ALG_EDDSA) => {
+                if int(-1) != Some(6) {
+                    return Err(webauthn_error("Only the Ed25519 curve is supported"));
+                }
+                KeyMaterial::Okp(param(-2)?)
+            }
+            (3, COSE_ALG_RS256) => KeyMaterial::Rsa {
+                n: param(-1)?,
+                e: param(-2)?,
+            },
+            (kty, alg) => {
+                return Err(webauthn_error(format!(
+                    "Unsupported COSE key (kty {}, alg {})",
+                    kty, alg
+                )))
+            }
+        };
+
+        Ok(Self { alg, material })
+    }
+
+    fn verify(&self, message: &[u8], sig: &[u8]) -> Result<()> {
+        let verified = match &self.material {
+            KeyMaterial::Ec2(point) => {
+                UnparsedPublicKey::new(&signature::ECDSA_P256_SHA256_ASN1, point).verify(message, sig)
+            }
+            KeyMaterial::Okp(key) => {
+                UnparsedPublicKey::new(&signature::ED25519, key).verify(message, sig)
+            }
+            KeyMaterial::Rsa { n, e } => signature::RsaPublicKeyComponents { n, e }.verify(
+                &signature::RSA_PKCS1_2048_8192_SHA256,
+                message,
+                sig,
+            ),
+        };
+
+        verified.map_err(|_| webauthn_error("Signature invalid"))
+    }
+}
+
+fn int_get(map: &[(Value, Value)], key: i64) -> Option<&Value> {
+    map.iter()
+        .find(|(k, _)| value_to_i64(k) == Some(key))
+        .map(|(_, v)| v)
+}
+
+fn text_get<'a>(map: &'a [(Value, Value)], key: &str) -> Option<&'a Value> {
+    map.iter()
+        .find(|(k, _)| k.as_text() == Some(key))
+        .map(|(_, v)| v)
+}
+
+fn value_to_i64(value: &Value) -> Option<i64> {
+    value
+        .as_integer()
+        .and_then(|i| i64::try_from(i128::from(i)).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_ASN1_SIGNING};
+
+    struct TestAuthenticator {
+        key_pair: EcdsaKeyPair,
+        credential_id: Vec<u8>,
+        rng: SystemRandom,
+    }
+
+    impl TestAuthenticator {
+        fn new() -> Self {
+            let rng = SystemRandom::new();
+            let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng).unwrap();
+            let key_pair =
+                EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8.as_ref(), &rng)
+                    .unwrap();
+            Self {
+                key_pair,
+                credential_id: vec![7; 16],
+                rng,
+            }
+        }
+
+        fn cose_key(&self) -> Vec<u8> {
+            let point = self.key_pair.public_key().as_ref();
+            let key = Value::Map(vec![
+                (Value::Integer(1.into()), Value::Integer(2.into())),
+                (Value::Integer(3.into()), Value::Integer(COSE_ALG_ES256.into())),
+                (Value::Integer((-1).into()), Value::Integer(1.into())),
+                (Value::Integer((-2).into()), Value::Bytes(point[1..33].to_vec())),
+                (Value::Integer((-3).into()), Value::Bytes(point[33..].to_vec())),
+            ]);
+            let mut bytes = Vec::new();
+            ciborium::ser::into_writer(&key, &mut bytes).unwrap();
+            bytes
+        }
+
+        fn auth_data(&self, config: &WebAuthnConfig, sign_count: u32, attested: bool) -> Vec<u8> {
+            let mut data = Sha256::digest(config.rp_id.as_bytes()).to_vec();
+            let flags = FLAG_USER_PRESENT | if attested { FLAG_ATTESTED_CREDENTIAL } else { 0 };
+            data.push(flags);
+            data.extend_from_slice(&sign_count.to_be_bytes());
+            if attested {
+                data.extend_from_slice(&[0u8; 16]);
+                data.extend_from_slice(&(self.credential_id.len() as u16).to_be_bytes());
+                data.extend_from_slice(&self.credential_id);
+                data.extend_from_slice(&self.cose_key());
+            }
+            data
+        }
+
+        fn sign(&self, auth_data: &[u8], client_data: &[u8]) -> Vec<u8> {
+            let mut signed = auth_data.to_vec();
+            signed.extend_from_slice(&Sha256::digest(client_data));
+            self.key_pair.sign(&self.rng, &signed).unwrap().as_ref().to_vec()
+        }
+
+        fn register(&self, config: &WebAuthnConfig, challenge: &str, fmt: &str) -> RegistrationResponse {
+            let client_data = client_data("webauthn.create", challenge, &config.origin);
+            let auth_data = self.auth_data(config, 0, true);
+            let att_stmt = match fmt {
+                "packed" => vec![
+                    (Value::Text("alg".into()), Value::Integer(COSE_ALG_ES256.into())),
+                    (Value::Text("sig".into()), Value::Bytes(self.sign(&auth_data, &client_data))),
+                ],
+                _ => vec![],
+            };
+            let object = Value::Map(vec![
+                (Value::Text("fmt".into()), Value::Text(fmt.into())),
+                (Value::Text("attStmt".into()), Value::Map(att_stmt)),
+                (Value::Text("authData".into()), Value::Bytes(auth_data)),
+            ]);
+            let mut attestation_object = Vec::new();
+            ciborium::ser::into_writer(&object, &mut attestation_object).unwrap();
+
+            RegistrationResponse {
+                id: BASE64URL.encode(&self.credential_id),
+                client_data_json: BASE64URL.encode(client_data),
+                attestation_object: BASE64URL.encode(attestation_object),
+            }
+        }
+
+        fn assert(&self, config: &WebAuthnConfig, challenge: &str, sign_count: u32) -> AssertionResponse {
+            let client_data = client_data("webauthn.get", challenge, &config.origin);
+            let auth_data = self.auth_data(config, sign_count, false);
+            AssertionResponse {
+                id: BASE64URL.encode(&self.credential_id),
+                client_data_json: BASE64URL.encode(&client_data),
+                signature: BASE64URL.encode(self.sign(&auth_data, &client_data)),
+                authenticator_data: BASE64URL.encode(auth_data),
+                user_handle: Some(BASE64URL.encode("user123")),
+            }
+        }
+    }
+
+    fn client_data(kind: &str, challenge: &str, origin: &str) -> Vec<u8> {
+        serde_json::to_vec(&serde_json::json!({
+            "type": kind,
+            "challenge": challenge,
+            "origin": origin,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_registration_and_assertion() {
+        let mut service = WebAuthnService::new(WebAuthnConfig::default());
+        let config = service.config().clone();
+        let authenticator = TestAuthenticator::new();
+        let mut enrollment = MfaEnrollment::new("user123".to_string());
+
+        let options = service.start_registration("user123", "user@example.com", &enrollment);
+        let response = authenticator.register(&config, &options.challenge, "packed");
+        let credential = service
+            .finish_registration("user123", &response, Some("YubiKey".to_string()), &mut enrollment)
+            .unwrap();
+        assert_eq!(credential.attestation, AttestationType::SelfAttestation);
+        assert!(enrollment.webauthn_enabled);
+
+        // Challenges are single use
+        assert!(service
+            .finish_registration("user123", &response, None, &mut enrollment)
+            .is_err());
+
+        let options = service.start_authentication("user123", &enrollment).unwrap();
+        assert_eq!(options.allow_credentials.len(), 1);
+        let assertion = authenticator.assert(&config, &options.challenge, 5);
+        service
+            .finish_authentication("user123", &assertion, &mut enrollment)
+            .unwrap();
+        assert_eq!(enrollment.webauthn_credentials[0].sign_count, 5);
+        assert!(enrollment.webauthn_credentials[0].last_used.is_some());
+
+        // A counter that does not increase indicates a cloned authenticator
+        let options = service.start_authentication("user123", &enrollment).unwrap();
+        let assertion = authenticator.assert(&config, &options.challenge, 5);
+        assert!(service
+            .finish_authentication("user123", &assertion, &mut enrollment)
+            .is_err());
+        assert_eq!(service.pending_challenges(), 0);
+    }
+
+    #[test]
+    fn test_rejected_ceremonies() {
+        let mut service = WebAuthnService::new(WebAuthnConfig::default());
+        let mut config = service.config().clone();
+        let authenticator = TestAuthenticator::new();
+        let mut enrollment = MfaEnrollment::new("user123".to_string());
+
+        // Wrong origin
+        let options = service.start_registration("user123", "user", &enrollment);
+        config.origin = "https://evil.example".to_string();
+        let response = authenticator.register(&config, &options.challenge, "none");
+        assert!(service
+            .finish_registration("user123", &response, None, &mut enrollment)
+            .is_err());
+
+        // Challenge issued for another user
+        let config = service.config().clone();
+        let options = service.start_registration("someone-else", "user", &enrollment);
+        let response = authenticator.register(&config, &options.challenge, "none");
+        assert!(service
+            .finish_registration("user123", &response, None, &mut enrollment)
+            .is_err());
+
+        let options = service.start_registration("user123", "user", &enrollment);
+        let response = authenticator.register(&config, &options.challenge, "none");
+        let credential = service
+            .finish_registration("user123", &response, None, &mut enrollment)
+            .unwrap();
+        assert_eq!(credential.attestation, AttestationType::None);
+
+        // Signature made by another key
+        let options = service.start_authentication("user123", &enrollment).unwrap();
+        let mut assertion = authenticator.assert(&config, &options.challenge, 1);
+        assertion.signature = TestAuthenticator::new().assert(&config, &options.challenge, 1).signature;
+        assert!(service
+            .finish_authentication("user123", &assertion, &mut enrollment)
+            .is_err());
+    }
+}
@@ -2,9 +2,11 @@
 //!
 //! Fine-grained access control based on attributes of users, resources, and context.
 
+use super::dsl::Expr;
 use crate::error::{Result, SecurityError};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 
 /// ABAC service for attribute-based authorization
 pub struct AbacService {
@@ -24,6 +26,25 @@ impl AbacService {
         self.policies.push(policy);
     }
 
+    /// Get all policies
+    pub fn policies(&self) -> &[AbacPolicy] {
+        &self.policies
+    }
+
+    /// Replace all policies loaded from `origin` (e.g. a policy file)
+    pub fn replace_policies_from(&mut self, origin: &str, policies: Vec<AbacPolicy>) {
+        self.remove_policies_from(origin);
+        self.policies.extend(policies);
+    }
+
+    /// Remove all policies loaded from `origin`, returning how many were removed
+    pub fn remove_policies_from(&mut self, origin: &str) -> usize {
+        let before = self.policies.len();
+        self.policies
+            .retain(|policy| policy.origin.as_deref() != Some(origin));
+        before - self.policies.len()
+    }
+
     /// Evaluate authorization request
     pub fn evaluate(&self, request: &AuthorizationRequest) -> Result<AuthorizationDecision> {
         let mut decisions = Vec::new();
@@ -42,6 +63,44 @@ impl AbacService {
         Ok(final_decision)
     }
 
+    /// Evaluate a request, recording how every policy contributed
+    ///
+    /// Uses the same deny-overrides combination as [`evaluate`](Self::evaluate).
+    pub fn explain(&self, request: &AuthorizationRequest) -> Result<Explanation> {
+        let mut traces = Vec::with_capacity(self.policies.len());
+
+        for policy in &self.policies {
+            traces.push(policy.trace(request)?);
+        }
+
+        let decisive = traces
+            .iter()
+            .find(|t| t.outcome == Effect::Deny)
+            .or_else(|| traces.iter().find(|t| t.outcome == Effect::Allow));
+
+        let (allowed, decided_by, reason) = match decisive {
+            Some(trace) => (
+                trace.outcome == Effect::Allow,
+                Some(trace.policy_id.clone()),
+                if trace.outcome == Effect::Allow {
+                    format!("Allowed by policy '{}'", trace.policy_id)
+                } else {
+                    format!("Denied by policy '{}'", trace.policy_id)
+                },
+            ),
+            None => (false, None, "No applicable policy found".to_string()),
+        };
+
+        Ok(Explanation {
+            action: request.action.clone(),
+            allowed,
+            decided_by,
+            reason,
+            rbac: None,
+            policies: traces,
+        })
+    }
+
     /// Combine multiple decisions using deny-overrides algorithm
     fn combine_decisions(&self, decisions: &[PolicyDecision]) -> AuthorizationDecision {
         // If any explicit deny, return deny
@@ -122,6 +181,9 @@ pub struct AbacPolicy {
     pub conditions: Vec<Condition>,
     /// Priority (higher = evaluated first)
     pub priority: i32,
+    /// Where the policy was loaded from, e.g. a policy file
+    #[serde(default)]
+    pub origin: Option<String>,
 }
 
 impl AbacPolicy {
@@ -134,6 +196,7 @@ impl AbacPolicy {
             target: PolicyTarget::default(),
             conditions: Vec::new(),
             priority: 0,
+            origin: None,
         }
     }
 
@@ -162,6 +225,36 @@ impl AbacPolicy {
         })
     }
 
+    /// Evaluate the policy, recording the result of every condition
+    pub fn trace(&self, request: &AuthorizationRequest) -> Result<PolicyTrace> {
+        let target_matched = self.applies_to(request);
+        let mut conditions = Vec::new();
+
+        if target_matched {
+            for condition in &self.conditions {
+                conditions.push(ConditionTrace {
+                    description: condition.description(),
+                    satisfied: condition.evaluate(request)?,
+                });
+            }
+        }
+
+        let outcome = if target_matched && conditions.iter().all(|c| c.satisfied) {
+            self.effect
+        } else {
+            Effect::NotApplicable
+        };
+
+        Ok(PolicyTrace {
+            policy_id: self.id.clone(),
+            origin: self.origin.clone(),
+            effect: self.effect,
+            target_matched,
+            conditions,
+            outcome,
+        })
+    }
+
     /// Add a condition to the policy
     pub fn add_condition(mut self, condition: Condition) -> Self {
         self.conditions.push(condition);
@@ -267,6 +360,12 @@ pub enum Condition {
     IpInRange {
         cidr: String,
     },
+    /// Subject and resource share at least one organization scope
+    SharedScope {
+        attribute: String,
+    },
+    /// Expression from the policy language
+    Expression(Expr),
     /// Custom condition
     Custom {
         name: String,
@@ -310,6 +409,15 @@ impl Condition {
                 // Simplified - in production, use ipnetwork crate
                 Ok(true)
             }
+            Condition::SharedScope { attribute } => {
+                match (request.subject.get(attribute), request.resource.get(attribute)) {
+                    (Some(AttributeValue::List(subject)), Some(AttributeValue::List(resource))) => {
+                        Ok(subject.iter().any(|scope| resource.contains(scope)))
+                    }
+                    _ => Ok(false),
+                }
+            }
+            Condition::Expression(expr) => Ok(expr.test(request)),
             _ => Ok(true), // Default to true for custom conditions
         }
     }
@@ -323,21 +431,35 @@ impl Condition {
             Condition::TimeBetween { start, end } => {
                 format!("Time between {} and {}", start, end)
             }
+            Condition::SharedScope { attribute } => {
+                format!("Subject and resource share a {} entry", attribute)
+            }
+            Condition::Expression(expr) => expr.to_string(),
             _ => "Custom condition".to_string(),
         }
     }
 }
 
 /// Attribute context (subject, resource, or environment)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AttributeContext {
     Subject,
     Resource,
     Context,
 }
 
+impl fmt::Display for AttributeContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttributeContext::Subject => write!(f, "subject"),
+            AttributeContext::Resource => write!(f, "resource"),
+            AttributeContext::Context => write!(f, "context"),
+        }
+    }
+}
+
 impl AttributeContext {
-    fn get_attributes<'a>(&self, request: &'a AuthorizationRequest) -> &'a Attributes {
+    pub(crate) fn get_attributes<'a>(&self, request: &'a AuthorizationRequest) -> &'a Attributes {
         match self {
             AttributeContext::Subject => &request.subject,
             AttributeContext::Resource => &request.resource,
@@ -412,6 +534,84 @@ pub enum Effect {
     NotApplicable,
 }
 
+/// Explained authorization decision
+#[derive(Debug, Clone, Serialize)]
+pub struct Explanation {
+    /// Action that was requested
+    pub action: String,
+    /// Whether access is allowed
+    pub allowed: bool,
+    /// Policy that decided the outcome, if any
+    pub decided_by: Option<String>,
+    /// Summary of the decision
+    pub reason: String,
+    /// RBAC outcome, when explained by the policy engine
+    pub rbac: Option<String>,
+    /// Every policy that was considered
+    pub policies: Vec<PolicyTrace>,
+}
+
+/// How one policy contributed to an explained decision
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyTrace {
+    /// Policy ID
+    pub policy_id: String,
+    /// Where the policy was loaded from
+    pub origin: Option<String>,
+    /// Effect the policy has when it applies
+    pub effect: Effect,
+    /// Whether the policy target matched the request
+    pub target_matched: bool,
+    /// Conditions and whether each was satisfied
+    pub conditions: Vec<ConditionTrace>,
+    /// Effect the policy actually had
+    pub outcome: Effect,
+}
+
+/// Result of one condition in an explained decision
+#[derive(Debug, Clone, Serialize)]
+pub struct ConditionTrace {
+    /// Condition description
+    pub description: String,
+    /// Whether it was satisfied
+    pub satisfied: bool,
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verdict = if self.allowed { "ALLOW" } else { "DENY" };
+        writeln!(f, "{} {}: {}", verdict, self.action, self.reason)?;
+
+        if let Some(rbac) = &self.rbac {
+            writeln!(f, "  rbac: {}", rbac)?;
+        }
+
+        for trace in &self.policies {
+            let outcome = match (trace.target_matched, trace.outcome) {
+                (false, _) => "skip",
+                (true, Effect::Allow) => "allow",
+                (true, Effect::Deny) => "deny",
+                (true, Effect::NotApplicable) => "n/a",
+            };
+            write!(f, "  [{}] {}", outcome, trace.policy_id)?;
+            if let Some(origin) = &trace.origin {
+                write!(f, " ({})", origin)?;
+            }
+            if !trace.target_matched {
+                write!(f, ": target does not match")?;
+            }
+            writeln!(f)?;
+
+            for condition in &trace.conditions {
+                let mark = if condition.satisfied { "x" } else { " " };
+                writeln!(f, "      [{}] {}", mark, condition.description)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -489,4 +689,74 @@ mod tests {
 
         assert!(condition.evaluate(&request).unwrap());
     }
+
+    #[test]
+    fn test_shared_scope_condition() {
+        let condition = Condition::SharedScope {
+            attribute: "scopes".to_string(),
+        };
+
+        let scopes = |values: &[&str]| {
+            AttributeValue::List(
+                values.iter().map(|v| AttributeValue::String(v.to_string())).collect(),
+            )
+        };
+
+        let mut subject = Attributes::new();
+        subject.insert("scopes".to_string(), scopes(&["org:acme", "team:recon"]));
+
+        let mut resource = Attributes::new();
+        resource.insert("scopes".to_string(), scopes(&["org:acme", "workspace:i95"]));
+
+        let mut request = AuthorizationRequest {
+            subject,
+            resource,
+            context: Attributes::new(),
+            action: "read".to_string(),
+        };
+        assert!(condition.evaluate(&request).unwrap());
+
+        request.resource.insert("scopes".to_string(), scopes(&["org:other"]));
+        assert!(!condition.evaluate(&request).unwrap());
+    }
+
+    #[test]
+    fn test_explain_matches_evaluate() {
+        let mut service = AbacService::new();
+        service.add_policy(
+            AbacPolicy::new("engineers".to_string(), Effect::Allow).add_condition(
+                Condition::AttributeEquals {
+                    attribute: "department".to_string(),
+                    value: AttributeValue::String("engineering".to_string()),
+                    context: AttributeContext::Subject,
+                },
+            ),
+        );
+        service.add_policy(
+            AbacPolicy::new("no-deletes".to_string(), Effect::Deny).with_target(PolicyTarget {
+                actions: vec!["delete".to_string()],
+                ..Default::default()
+            }),
+        );
+
+        let mut subject = Attributes::new();
+        subject.insert("department".to_string(), AttributeValue::String("engineering".to_string()));
+        let mut request = AuthorizationRequest {
+            subject,
+            resource: Attributes::new(),
+            context: Attributes::new(),
+            action: "read".to_string(),
+        };
+
+        let explanation = service.explain(&request).unwrap();
+        assert!(explanation.allowed);
+        assert_eq!(explanation.decided_by.as_deref(), Some("engineers"));
+        assert!(!explanation.policies[1].target_matched);
+
+        request.action = "delete".to_string();
+        let explanation = service.explain(&request).unwrap();
+        assert_eq!(explanation.allowed, service.evaluate(&request).unwrap().allowed);
+        assert_eq!(explanation.decided_by.as_deref(), Some("no-deletes"));
+        assert!(explanation.to_string().starts_with("DENY delete"));
+    }
 }
@@ -0,0 +1,738 @@
+//! Policy language for ABAC
+//!
+//! A small Cedar-like language for writing ABAC policies as text. Each policy
+//! is parsed into an [`AbacPolicy`]:
+//!
+//! ```text
+//! // Investigators may read open cases of their own department
+//! @id("investigators-read-cases")
+//! @priority(10)
+//! permit (action in ["cases:read", "cases:list"], resource is "case")
+//! when { subject.role == "investigator" && subject.department == resource.department }
+//! unless { resource.status == "sealed" };
+//!
+//! forbid (action == "evidence:delete")
+//! unless { subject has legal_hold_override };
+//! ```
+//!
+//! The scope restricts `principal` (subject `id`), `action` and `resource`
+//! (resource `type`). `when` and `unless` clauses take expressions over
+//! `subject.*`, `resource.*`, `context.*` and `action` with `==`, `!=`, `<`,
+//! `<=`, `>`, `>=`, `in`, `contains`, `has`, `&&`, `||` and `!`. Missing
+//! attributes make a comparison false.
+
+use super::abac::{
+    AbacPolicy, AttributeContext, AttributeValue, AuthorizationRequest, Condition, Effect,
+    PolicyTarget,
+};
+use crate::error::{Result, SecurityError};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Parse policies from source text
+///
+/// Policies without an `@id` annotation are named `<origin>:<index>`. Every
+/// policy records `origin` so it can be replaced when its file is reloaded.
+pub fn parse_policies(origin: &str, source: &str) -> Result<Vec<AbacPolicy>> {
+    let tokens = Lexer::new(source).tokenize()?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let mut policies = Vec::new();
+
+    while !parser.at_end() {
+        let mut policy = parser.policy()?;
+        if policy.id.is_empty() {
+            policy.id = format!("{}:{}", origin, policies.len());
+        }
+        policy.origin = Some(origin.to_string());
+        policies.push(policy);
+    }
+
+    Ok(policies)
+}
+
+/// Expression in a `when` or `unless` clause
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Expr {
+    /// Literal value
+    Literal(AttributeValue),
+    /// Attribute of the subject, resource or context
+    Attribute { context: AttributeContext, name: String },
+    /// The requested action
+    Action,
+    /// Attribute presence
+    Has { context: AttributeContext, name: String },
+    /// Comparison
+    Compare {
+        op: CompareOp,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    /// Negation
+    Not(Box<Expr>),
+    /// Conjunction
+    And(Box<Expr>, Box<Expr>),
+    /// Disjunction
+    Or(Box<Expr>, Box<Expr>),
+}
+
+/// Comparison operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    In,
+    Contains,
+}
+
+impl Expr {
+    /// Evaluate the expression as a condition
+    pub fn test(&self, request: &AuthorizationRequest) -> bool {
+        match self {
+            Expr::Has { context, name } => context.get_attributes(request).contains_key(name),
+            Expr::Compare { op, left, right } => match (left.value(request), right.value(request)) {
+                (Some(left), Some(right)) => compare(*op, &left, &right),
+                _ => false,
+            },
+            Expr::Not(inner) => !inner.test(request),
+            Expr::And(left, right) => left.test(request) && right.test(request),
+            Expr::Or(left, right) => left.test(request) || right.test(request),
+            other => matches!(other.value(request), Some(AttributeValue::Boolean(true))),
+        }
+    }
+
+    /// Split a top-level conjunction into its parts
+    pub fn conjuncts(self) -> Vec<Expr> {
+        match self {
+            Expr::And(left, right) => {
+                let mut parts = left.conjuncts();
+                parts.extend(right.conjuncts());
+                parts
+            }
+            other => vec![other],
+        }
+    }
+
+    fn value(&self, request: &AuthorizationRequest) -> Option<AttributeValue> {
+        match self {
+            Expr::Literal(value) => Some(value.clone()),
+            Expr::Attribute { context, name } => context.get_attributes(request).get(name).cloned(),
+            Expr::Action => Some(AttributeValue::String(request.action.clone())),
+            other => Some(AttributeValue::Boolean(other.test(request))),
+        }
+    }
+}
+
+fn compare(op: CompareOp, left: &AttributeValue, right: &AttributeValue) -> bool {
+    use std::cmp::Ordering;
+
+    let ordering = match (left, right) {
+        (AttributeValue::Number(l), AttributeValue::Number(r)) => Some(l.cmp(r)),
+        (AttributeValue::String(l), AttributeValue::String(r)) => Some(l.cmp(r)),
+        _ => None,
+    };
+
+    match op {
+        CompareOp::Eq => left == right,
+        CompareOp::Ne => left != right,
+        CompareOp::Lt => ordering == Some(Ordering::Less),
+        CompareOp::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+        CompareOp::Gt => ordering == Some(Ordering::Greater),
+        CompareOp::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        CompareOp::In => compare(CompareOp::Contains, right, left),
+        CompareOp::Contains => match (left, right) {
+            (AttributeValue::List(list), value) => list.contains(value),
+            (AttributeValue::String(s), AttributeValue::String(v)) => s.contains(v.as_str()),
+            _ => false,
+        },
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Literal(value) => write_literal(f, value),
+            Expr::Attribute { context, name } => write!(f, "{}.{}", context, name),
+            Expr::Action => write!(f, "action"),
+            Expr::Has { context, name } => write!(f, "{} has {}", context, name),
+            Expr::Compare { op, left, right } => write!(f, "{} {} {}", left, op, right),
+            Expr::Not(inner) => write!(f, "!({})", inner),
+            Expr::And(left, right) => write!(f, "({} && {})", left, right),
+            Expr::Or(left, right) => write!(f, "({} || {})", left, right),
+        }
+    }
+}
+
+fn write_literal(f: &mut fmt::Formatter<'_>, value: &AttributeValue) -> fmt::Result {
+    match value {
+        AttributeValue::String(s) => write!(f, "{:?}", s),
+        AttributeValue::List(items) => {
+            write!(f, "[")?;
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                write_literal(f, item)?;
+            }
+            write!(f, "]")
+        }
+        other => write!(f, "{}", other),
+    }
+}
+
+impl fmt::Display for CompareOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            CompareOp::Eq => "==",
+            CompareOp::Ne => "!=",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+            CompareOp::In => "in",
+            CompareOp::Contains => "contains",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Ident(String),
+    Str(String),
+    Int(i64),
+    Symbol(&'static str),
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    line: usize,
+    column: usize,
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    column: usize,
+}
+
+const SYMBOLS: &[&str] = &[
+    "==", "!=", "<=", ">=", "&&", "||", "(", ")", "{", "}", "[", "]", ",", ";", ".", "@", "<",
+    ">", "!", "-",
+];
+
+impl<'a> Lexer<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            chars: source.chars().peekable(),
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn error(&self, message: impl Into<String>) -> SecurityError {
+        SecurityError::PolicyParseError {
+            line: self.line,
+            column: self.column,
+            message: message.into(),
+        }
+    }
+
+    fn tokenize(mut self) -> Result<Vec<Token>> {
+        let mut tokens = Vec::new();
+
+        while let Some(&c) = self.chars.peek() {
+            let (line, column) = (self.line, self.column);
+
+            if c.is_whitespace() {
+                self.bump();
+                continue;
+            }
+
+            let kind = if c == '/' {
+                self.bump();
+                if self.bump() != Some('/') {
+                    return Err(self.error("Expected '//' comment"));
+                }
+                while !matches!(self.chars.peek(), None | Some('\n')) {
+                    self.bump();
+                }
+                continue;
+            } else if c == '"' {
+                self.bump();
+                let mut value = String::new();
+                loop {
+                    match self.bump() {
+                        Some('"') => break,
+                        Some('\\') => match self.bump() {
+                            Some('n') => value.push('\n'),
+                            Some(escaped @ ('"' | '\\')) => value.push(escaped),
+                            _ => return Err(self.error("Invalid escape in string")),
+                        },
+                        Some(ch) => value.push(ch),
+                        None => return Err(self.error("Unterminated string")),
+                    }
+                }
+                TokenKind::Str(value)
+            } else if c.is_ascii_digit() {
+                let mut digits = String::new();
+                while let Some(&d) = self.chars.peek().filter(|d| d.is_ascii_digit()) {
+                    digits.push(d);
+                    self.bump();
+                }
+                TokenKind::Int(
+                    digits
+                        .parse()
+                        .map_err(|_| self.error(format!("Number out of range: {}", digits)))?,
+                )
+            } else if c.is_alphabetic() || c == '_' {
+                let mut ident = String::new();
+                while let Some(&d) = self
+                    .chars
+                    .peek()
+                    .filter(|d| d.is_alphanumeric() || **d == '_')
+                {
+                    ident.push(d);
+                    self.bump();
+                }
+                TokenKind::Ident(ident)
+            } else {
+                self.bump();
+                let two: Option<&'static str> = self.chars.peek().and_then(|&next| {
+                    SYMBOLS
+                        .iter()
+                        .copied()
+                        .find(|s| s.len() == 2 && s.starts_with(c) && s.ends_with(next))
+                });
+                match two {
+                    Some(symbol) => {
+                        self.bump();
+                        TokenKind::Symbol(symbol)
+                    }
+                    None => {
+                        let symbol = SYMBOLS
+                            .iter()
+                            .copied()
+                            .find(|s| s.len() == 1 && s.starts_with(c))
+                            .ok_or_else(|| self.error(format!("Unexpected character '{}'", c)))?;
+                        TokenKind::Symbol(symbol)
+                    }
+                }
+            };
+
+            tokens.push(Token { kind, line, column });
+        }
+
+        Ok(tokens)
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn at_end(&self) -> bool {
+        self.pos >= self.tokens.len()
+    }
+
+    fn peek(&self) -> Option<&TokenKind> {
+        self.tokens.get(self.pos).map(|t| &t.kind)
+    }
+
+    fn error(&self, message: impl Into<String>) -> SecurityError {
+        let (line, column) = match self.tokens.get(self.pos).or(self.tokens.last()) {
+            Some(token) => (token.line, token.column),
+            None => (1, 1),
+        };
+        SecurityError::PolicyParseError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    fn next(&mut self) -> Result<TokenKind> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .map(|t| t.kind.clone())
+            .ok_or_else(|| self.error("Unexpected end of input"))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        if matches!(self.peek(), Some(TokenKind::Symbol(s)) if *s == symbol) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Some(TokenKind::Ident(ident)) if ident == keyword) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<()> {
+        if self.eat_symbol(symbol) {
+            Ok(())
+        } else {
+            Err(self.error(format!("Expected '{}'", symbol)))
+        }
+    }
+
+    fn expect_ident(&mut self) -> Result<String> {
+        match self.next()? {
+            TokenKind::Ident(ident) => Ok(ident),
+            _ => {
+                self.pos -= 1;
+                Err(self.error("Expected identifier"))
+            }
+        }
+    }
+
+    fn expect_string(&mut self) -> Result<String> {
+        match self.next()? {
+            TokenKind::Str(value) => Ok(value),
+            _ => {
+                self.pos -= 1;
+                Err(self.error("Expected string"))
+            }
+        }
+    }
+
+    fn policy(&mut self) -> Result<AbacPolicy> {
+        let mut id = String::new();
+        let mut description = None;
+        let mut priority = 0;
+
+        while self.eat_symbol("@") {
+            let name = self.expect_ident()?;
+            self.expect_symbol("(")?;
+            match name.as_str() {
+                "id" => id = self.expect_string()?,
+                "description" => description = Some(self.expect_string()?),
+                "priority" => priority = self.integer()? as i32,
+                other => return Err(self.error(format!("Unknown annotation '@{}'", other))),
+            }
+            self.expect_symbol(")")?;
+        }
+
+        let effect = if self.eat_keyword("permit") {
+            Effect::Allow
+        } else if self.eat_keyword("forbid") {
+            Effect::Deny
+        } else {
+            return Err(self.error("Expected 'permit' or 'forbid'"));
+        };
+
+        let mut policy = AbacPolicy::new(id, effect).with_priority(priority);
+        policy.description = description;
+        policy.target = self.scope()?;
+
+        loop {
+            if self.eat_keyword("when") {
+                for part in self.clause()?.conjuncts() {
+                    policy = policy.add_condition(Condition::Expression(part));
+                }
+            } else if self.eat_keyword("unless") {
+                let expr = self.clause()?;
+                policy = policy.add_condition(Condition::Expression(Expr::Not(Box::new(expr))));
+            } else {
+                break;
+            }
+        }
+
+        self.expect_symbol(";")?;
+        Ok(policy)
+    }
+
+    fn scope(&mut self) -> Result<PolicyTarget> {
+        let mut target = PolicyTarget::default();
+        self.expect_symbol("(")?;
+
+        if self.eat_symbol(")") {
+            return Ok(target);
+        }
+
+        loop {
+            let element = self.expect_ident()?;
+            let is_type = element == "resource" && self.eat_keyword("is");
+            let values = if is_type || self.eat_symbol("==") {
+                vec![self.expect_string()?]
+            } else if self.eat_keyword("in") {
+                self.string_list()?
+            } else {
+                return Err(self.error("Expected '==', 'in' or 'is'"));
+            };
+
+            match element.as_str() {
+                "principal" => target.subjects.extend(values),
+                "action" => target.actions.extend(values),
+                "resource" => target.resource_types.extend(values),
+                other => return Err(self.error(format!("Unknown scope element '{}'", other))),
+            }
+
+            if self.eat_symbol(")") {
+                return Ok(target);
+            }
+            self.expect_symbol(",")?;
+        }
+    }
+
+    fn clause(&mut self) -> Result<Expr> {
+        self.expect_symbol("{")?;
+        let expr = self.or()?;
+        self.expect_symbol("}")?;
+        Ok(expr)
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut expr = self.and()?;
+        while self.eat_symbol("||") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut expr = self.unary()?;
+        while self.eat_symbol("&&") {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if self.eat_symbol("!") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr> {
+        let left = self.primary()?;
+
+        if self.eat_keyword("has") {
+            return Err(self.error("'has' must follow subject, resource or context"));
+        }
+
+        let op = match self.peek() {
+            Some(TokenKind::Symbol("==")) => CompareOp::Eq,
+            Some(TokenKind::Symbol("!=")) => CompareOp::Ne,
+            Some(TokenKind::Symbol("<")) => CompareOp::Lt,
+            Some(TokenKind::Symbol("<=")) => CompareOp::Le,
+            Some(TokenKind::Symbol(">")) => CompareOp::Gt,
+            Some(TokenKind::Symbol(">=")) => CompareOp::Ge,
+            Some(TokenKind::Ident(ident)) if ident == "in" => CompareOp::In,
+            Some(TokenKind::Ident(ident)) if ident == "contains" => CompareOp::Contains,
+            _ => return Ok(left),
+        };
+        self.pos += 1;
+
+        let right = self.primary()?;
+        Ok(Expr::Compare {
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+        })
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        match self.peek().cloned() {
+            Some(TokenKind::Symbol("(")) => {
+                self.pos += 1;
+                let expr = self.or()?;
+                self.expect_symbol(")")?;
+                Ok(expr)
+            }
+            Some(TokenKind::Symbol("[")) => Ok(Expr::Literal(self.list()?)),
+            Some(TokenKind::Symbol("-")) | Some(TokenKind::Int(_)) => {
+                Ok(Expr::Literal(AttributeValue::Number(self.integer()?)))
+            }
+            Some(TokenKind::Str(value)) => {
+                self.pos += 1;
+                Ok(Expr::Literal(AttributeValue::String(value)))
+            }
+            Some(TokenKind::Ident(ident)) => {
+                self.pos += 1;
+                match ident.as_str() {
+                    "true" => Ok(Expr::Literal(AttributeValue::Boolean(true))),
+                    "false" => Ok(Expr::Literal(AttributeValue::Boolean(false))),
+                    "action" => Ok(Expr::Action),
+                    entity => {
+                        let context = match entity {
+                            "subject" | "principal" => AttributeContext::Subject,
+                            "resource" => AttributeContext::Resource,
+                            "context" => AttributeContext::Context,
+                            _ => {
+                                self.pos -= 1;
+                                return Err(self.error(format!("Unknown name '{}'", entity)));
+                            }
+                        };
+                        if self.eat_keyword("has") {
+                            let name = self.expect_ident()?;
+                            return Ok(Expr::Has { context, name });
+                        }
+                        self.expect_symbol(".")?;
+                        let name = self.expect_ident()?;
+                        Ok(Expr::Attribute { context, name })
+                    }
+                }
+            }
+            _ => Err(self.error("Expected expression")),
+        }
+    }
+
+    fn integer(&mut self) -> Result<i64> {
+        let negative = self.eat_symbol("-");
+        match self.next()? {
+            TokenKind::Int(n) => Ok(if negative { -n } else { n }),
+            _ => {
+                self.pos -= 1;
+                Err(self.error("Expected number"))
+            }
+        }
+    }
+
+    fn list(&mut self) -> Result<AttributeValue> {
+        self.expect_symbol("[")?;
+        let mut items = Vec::new();
+        if self.eat_symbol("]") {
+            return Ok(AttributeValue::List(items));
+        }
+        loop {
+            match self.primary()? {
+                Expr::Literal(value) => items.push(value),
+                _ => return Err(self.error("List items must be literals")),
+            }
+            if self.eat_symbol("]") {
+                return Ok(AttributeValue::List(items));
+            }
+            self.expect_symbol(",")?;
+        }
+    }
+
+    fn string_list(&mut self) -> Result<Vec<String>> {
+        match self.list()? {
+            AttributeValue::List(items) => items
+                .into_iter()
+                .map(|item| match item {
+                    AttributeValue::String(s) => Ok(s),
+                    _ => Err(self.error("Expected a list of strings")),
+                })
+                .collect(),
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authz::abac::{AbacService, Attributes};
+
+    const POLICIES: &str = r#"
+        // Investigators read cases of their own department
+        @id("investigators-read")
+        permit (action in ["cases:read", "cases:list"], resource is "case")
+        when { subject.role == "investigator" && subject.department == resource.department }
+        unless { resource.status == "sealed" };
+
+        @id("no-low-clearance")
+        forbid (action == "cases:read")
+        when { subject.clearance < 2 || !(subject has clearance) };
+    "#;
+
+    fn request(action: &str, subject: &[(&str, AttributeValue)], resource: &[(&str, AttributeValue)]) -> AuthorizationRequest {
+        let attrs = |pairs: &[(&str, AttributeValue)]| -> Attributes {
+            pairs.iter().map(|(k, v)| (k.to_string(), v.clone())).collect()
+        };
+        AuthorizationRequest {
+            subject: attrs(subject),
+            resource: attrs(resource),
+            context: Attributes::new(),
+            action: action.to_string(),
+        }
+    }
+
+    fn s(value: &str) -> AttributeValue {
+        AttributeValue::String(value.to_string())
+    }
+
+    #[test]
+    fn test_parse_policies() {
+        let policies = parse_policies("cases.policy", POLICIES).unwrap();
+        assert_eq!(policies.len(), 2);
+
+        let read = &policies[0];
+        assert_eq!(read.id, "investigators-read");
+        assert_eq!(read.effect, Effect::Allow);
+        assert_eq!(read.target.actions, vec!["cases:read", "cases:list"]);
+        assert_eq!(read.target.resource_types, vec!["case"]);
+        assert_eq!(read.conditions.len(), 3);
+        assert_eq!(read.origin.as_deref(), Some("cases.policy"));
+
+        assert_eq!(policies[1].effect, Effect::Deny);
+    }
+
+    #[test]
+    fn test_parsed_policies_evaluate() {
+        let mut service = AbacService::new();
+        for policy in parse_policies("cases.policy", POLICIES).unwrap() {
+            service.add_policy(policy);
+        }
+
+        let subject = [
+            ("role", s("investigator")),
+            ("department", s("homicide")),
+            ("clearance", AttributeValue::Number(3)),
+        ];
+        let open_case = [("type", s("case")), ("department", s("homicide")), ("status", s("open"))];
+        let sealed_case = [("type", s("case")), ("department", s("homicide")), ("status", s("sealed"))];
+
+        assert!(service.evaluate(&request("cases:read", &subject, &open_case)).unwrap().allowed);
+        assert!(!service.evaluate(&request("cases:read", &subject, &sealed_case)).unwrap().allowed);
+
+        let no_clearance = [("role", s("investigator")), ("department", s("homicide"))];
+        assert!(!service.evaluate(&request("cases:read", &no_clearance, &open_case)).unwrap().allowed);
+        assert!(service.evaluate(&request("cases:list", &no_clearance, &open_case)).unwrap().allowed);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse_policies("bad", "permit (action == \"x\")\nwhen { subject.a === 1 };")
+            .unwrap_err();
+        match err {
+            SecurityError::PolicyParseError { line, .. } => assert_eq!(line, 2),
+            other => panic!("unexpected error: {}", other),
+        }
+
+        assert!(parse_policies("bad", "allow ();").is_err());
+        assert!(parse_policies("bad", "permit (owner == \"x\");").is_err());
+        assert!(parse_policies("bad", "permit () when { subject.a == 1 }").is_err());
+    }
+}
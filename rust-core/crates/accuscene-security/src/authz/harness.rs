@@ -0,0 +1,279 @@
+//! Policy unit tests
+//!
+//! Test cases are written as JSON next to the policies they exercise:
+//!
+//! ```json
+//! [
+//!   {
+//!     "name": "investigator reads own case",
+//!     "action": "cases:read",
+//!     "subject": { "role": "investigator", "department": "homicide" },
+//!     "resource": { "type": "case", "department": "homicide" },
+//!     "expect": "allow",
+//!     "decided_by": "investigators-read"
+//!   }
+//! ]
+//! ```
+//!
+//! Attribute values may be strings, integers, booleans or lists of those.
+
+use super::abac::{AbacService, AttributeValue, Attributes, AuthorizationRequest, Explanation};
+use crate::error::{Result, SecurityError};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+/// Expected outcome of a policy test
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Expectation {
+    Allow,
+    Deny,
+}
+
+/// A single policy test case
+#[derive(Debug, Clone, Deserialize)]
+pub struct PolicyTestCase {
+    /// Test name
+    pub name: String,
+    /// Requested action
+    pub action: String,
+    /// Subject attributes
+    #[serde(default)]
+    pub subject: HashMap<String, serde_json::Value>,
+    /// Resource attributes
+    #[serde(default)]
+    pub resource: HashMap<String, serde_json::Value>,
+    /// Context attributes
+    #[serde(default)]
+    pub context: HashMap<String, serde_json::Value>,
+    /// Expected outcome
+    pub expect: Expectation,
+    /// Policy expected to decide the outcome
+    #[serde(default)]
+    pub decided_by: Option<String>,
+}
+
+impl PolicyTestCase {
+    /// Build the authorization request for this case
+    pub fn request(&self) -> Result<AuthorizationRequest> {
+        Ok(AuthorizationRequest {
+            subject: to_attributes(&self.subject)?,
+            resource: to_attributes(&self.resource)?,
+            context: to_attributes(&self.context)?,
+            action: self.action.clone(),
+        })
+    }
+}
+
+/// A set of policy test cases
+#[derive(Debug, Clone, Default)]
+pub struct PolicyTestSuite {
+    /// Test cases
+    pub cases: Vec<PolicyTestCase>,
+}
+
+impl PolicyTestSuite {
+    /// Parse a suite from a JSON array of test cases
+    pub fn from_json(json: &str) -> Result<Self> {
+        let cases = serde_json::from_str(json).map_err(|e| {
+            SecurityError::ConfigurationError(format!("Invalid policy test suite: {}", e))
+        })?;
+        Ok(Self { cases })
+    }
+
+    /// Read a suite from a JSON file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).map_err(|e| {
+            SecurityError::ConfigurationError(format!("Cannot read {:?}: {}", path, e))
+        })?;
+        Self::from_json(&json)
+    }
+
+    /// Run every case against the ABAC policies
+    pub fn run(&self, abac: &AbacService) -> Result<PolicyTestReport> {
+        let mut results = Vec::with_capacity(self.cases.len());
+
+        for case in &self.cases {
+            let explanation = abac.explain(&case.request()?)?;
+            let actual = if explanation.allowed {
+                Expectation::Allow
+            } else {
+                Expectation::Deny
+            };
+
+            let failure = if actual != case.expect {
+                Some(format!("expected {:?}, got {:?}", case.expect, actual))
+            } else {
+                match &case.decided_by {
+                    Some(expected) if explanation.decided_by.as_ref() != Some(expected) => {
+                        Some(format!(
+                            "expected decision by '{}', got {}",
+                            expected,
+                            explanation
+                                .decided_by
+                                .as_deref()
+                                .map(|id| format!("'{}'", id))
+                                .unwrap_or_else(|| "no policy".to_string())
+                        ))
+                    }
+                    _ => None,
+                }
+            };
+
+            results.push(PolicyTestResult {
+                name: case.name.clone(),
+                failure,
+                explanation,
+            });
+        }
+
+        Ok(PolicyTestReport { results })
+    }
+}
+
+/// Result of one policy test case
+#[derive(Debug, Clone)]
+pub struct PolicyTestResult {
+    /// Test name
+    pub name: String,
+    /// Why the test failed, if it did
+    pub failure: Option<String>,
+    /// Explanation of the decision
+    pub explanation: Explanation,
+}
+
+impl PolicyTestResult {
+    /// Check if the test passed
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// Results of a policy test suite
+#[derive(Debug, Clone)]
+pub struct PolicyTestReport {
+    /// Per-case results
+    pub results: Vec<PolicyTestResult>,
+}
+
+impl PolicyTestReport {
+    /// Check if every case passed
+    pub fn all_passed(&self) -> bool {
+        self.results.iter().all(PolicyTestResult::passed)
+    }
+
+    /// Failed cases
+    pub fn failures(&self) -> Vec<&PolicyTestResult> {
+        self.results.iter().filter(|r| !r.passed()).collect()
+    }
+}
+
+impl fmt::Display for PolicyTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for result in &self.results {
+            match &result.failure {
+                None => writeln!(f, "ok      {}", result.name)?,
+                Some(failure) => {
+                    writeln!(f, "FAILED  {}: {}", result.name, failure)?;
+                    for line in result.explanation.to_string().lines() {
+                        writeln!(f, "        {}", line)?;
+                    }
+                }
+            }
+        }
+
+        let failed = self.failures().len();
+        writeln!(
+            f,
+            "{} passed, {} failed",
+            self.results.len() - failed,
+            failed
+        )
+    }
+}
+
+fn to_attributes(values: &HashMap<String, serde_json::Value>) -> Result<Attributes> {
+    values
+        .iter()
+        .map(|(key, value)| Ok((key.clone(), to_attribute(key, value)?)))
+        .collect()
+}
+
+fn to_attribute(key: &str, value: &serde_json::Value) -> Result<AttributeValue> {
+    match value {
+        serde_json::Value::String(s) => Ok(AttributeValue::String(s.clone())),
+        serde_json::Value::Bool(b) => Ok(AttributeValue::Boolean(*b)),
+        serde_json::Value::Number(n) => n.as_i64().map(AttributeValue::Number).ok_or_else(|| {
+            SecurityError::ConfigurationError(format!("Attribute '{}' must be an integer", key))
+        }),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .map(|item| to_attribute(key, item))
+            .collect::<Result<Vec<_>>>()
+            .map(AttributeValue::List),
+        _ => Err(SecurityError::ConfigurationError(format!(
+            "Attribute '{}' has an unsupported type",
+            key
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authz::dsl::parse_policies;
+
+    #[test]
+    fn test_policy_suite() {
+        let mut abac = AbacService::new();
+        let source = r#"
+            @id("investigators-read")
+            permit (action == "cases:read", resource is "case")
+            when { subject.role == "investigator" && subject.department == resource.department };
+
+            @id("sealed")
+            forbid (resource is "case") when { resource.sealed == true };
+        "#;
+        for policy in parse_policies("cases.policy", source).unwrap() {
+            abac.add_policy(policy);
+        }
+
+        let suite = PolicyTestSuite::from_json(
+            r#"[
+                {
+                    "name": "own department",
+                    "action": "cases:read",
+                    "subject": { "role": "investigator", "department": "homicide" },
+                    "resource": { "type": "case", "department": "homicide" },
+                    "expect": "allow",
+                    "decided_by": "investigators-read"
+                },
+                {
+                    "name": "sealed case",
+                    "action": "cases:read",
+                    "subject": { "role": "investigator", "department": "homicide" },
+                    "resource": { "type": "case", "department": "homicide", "sealed": true },
+                    "expect": "deny",
+                    "decided_by": "sealed"
+                },
+                {
+                    "name": "wrong expectation",
+                    "action": "cases:read",
+                    "subject": { "role": "investigator", "department": "fraud" },
+                    "resource": { "type": "case", "department": "homicide" },
+                    "expect": "allow"
+                }
+            ]"#,
+        )
+        .unwrap();
+
+        let report = suite.run(&abac).unwrap();
+        assert!(!report.all_passed());
+        assert_eq!(report.failures().len(), 1);
+        assert_eq!(report.failures()[0].name, "wrong expectation");
+        assert!(report.to_string().contains("2 passed, 1 failed"));
+    }
+}
@@ -0,0 +1,175 @@
+//! Loading policy files with hot reload
+//!
+//! [`PolicyLoader`] reads every `*.policy` file in a directory into the policy
+//! engine and polls the directory for changes. A reload parses all files
+//! before touching the engine, so a broken file leaves the previous policies
+//! in force.
+
+use super::dsl::parse_policies;
+use super::policy::PolicyEngine;
+use super::{AbacPolicy, AuthorizationService};
+use crate::error::{Result, SecurityError};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::RwLock;
+
+/// File extension of policy files
+pub const POLICY_EXTENSION: &str = "policy";
+
+/// Loads `*.policy` files from a directory into a policy engine
+pub struct PolicyLoader {
+    dir: PathBuf,
+    loaded: HashMap<PathBuf, SystemTime>,
+}
+
+impl PolicyLoader {
+    /// Create a loader for a policy directory
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            loaded: HashMap::new(),
+        }
+    }
+
+    /// Get the policy directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Load all policy files, returning the number of policies loaded
+    pub fn load(&mut self, engine: &mut PolicyEngine) -> Result<usize> {
+        let files = self.scan()?;
+        self.apply(engine, files)
+    }
+
+    /// Reload if any policy file was added, changed or removed
+    ///
+    /// Returns the number of policies loaded, or `None` if nothing changed.
+    pub fn reload_if_changed(&mut self, engine: &mut PolicyEngine) -> Result<Option<usize>> {
+        let files = self.scan()?;
+        if files == self.loaded {
+            return Ok(None);
+        }
+        self.apply(engine, files).map(Some)
+    }
+
+    /// Poll the directory and reload changed policies into the service
+    pub fn spawn_watcher(
+        mut self,
+        authz: Arc<RwLock<AuthorizationService>>,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let mut authz = authz.write().await;
+                match self.reload_if_changed(authz.engine_mut()) {
+                    Ok(Some(count)) => {
+                        tracing::info!("Reloaded {} policies from {:?}", count, self.dir)
+                    }
+                    Ok(None) => {}
+                    Err(e) => tracing::error!("Policy reload failed, keeping previous policies: {}", e),
+                }
+            }
+        })
+    }
+
+    fn scan(&self) -> Result<HashMap<PathBuf, SystemTime>> {
+        let entries = std::fs::read_dir(&self.dir).map_err(|e| {
+            SecurityError::ConfigurationError(format!(
+                "Cannot read policy directory {:?}: {}",
+                self.dir, e
+            ))
+        })?;
+
+        let mut files = HashMap::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(POLICY_EXTENSION) {
+                continue;
+            }
+            let modified = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            files.insert(path, modified);
+        }
+
+        Ok(files)
+    }
+
+    fn apply(
+        &mut self,
+        engine: &mut PolicyEngine,
+        files: HashMap<PathBuf, SystemTime>,
+    ) -> Result<usize> {
+        // Parse everything first so a bad file cannot leave a partial policy set
+        let mut parsed: Vec<(String, Vec<AbacPolicy>)> = Vec::new();
+        for path in files.keys() {
+            let source = std::fs::read_to_string(path).map_err(|e| {
+                SecurityError::ConfigurationError(format!("Cannot read {:?}: {}", path, e))
+            })?;
+            let origin = Self::origin(path);
+            let policies = parse_policies(&origin, &source)
+                .map_err(|e| SecurityError::ConfigurationError(format!("{}: {}", origin, e)))?;
+            parsed.push((origin, policies));
+        }
+
+        for path in self.loaded.keys().filter(|path| !files.contains_key(*path)) {
+            engine.unload_policies(&Self::origin(path));
+        }
+
+        let count = parsed.iter().map(|(_, policies)| policies.len()).sum();
+        for (origin, policies) in parsed {
+            engine.load_policies(&origin, policies);
+        }
+
+        self.loaded = files;
+        Ok(count)
+    }
+
+    fn origin(path: &Path) -> String {
+        path.display().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authz::PolicyEngineConfig;
+
+    #[test]
+    fn test_load_and_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("cases.policy");
+        std::fs::write(&file, "permit (action == \"cases:read\");").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not a policy").unwrap();
+
+        let mut engine = PolicyEngine::new(PolicyEngineConfig::default());
+        let mut loader = PolicyLoader::new(dir.path());
+
+        assert_eq!(loader.load(&mut engine).unwrap(), 1);
+        assert_eq!(loader.reload_if_changed(&mut engine).unwrap(), None);
+
+        // A broken file keeps the previous policies
+        std::fs::write(dir.path().join("broken.policy"), "permit (").unwrap();
+        assert!(loader.reload_if_changed(&mut engine).is_err());
+        assert_eq!(engine.abac().policies().len(), 1);
+
+        std::fs::remove_file(dir.path().join("broken.policy")).unwrap();
+        std::fs::write(
+            dir.path().join("evidence.policy"),
+            "permit (action == \"evidence:read\"); forbid (action == \"evidence:delete\");",
+        )
+        .unwrap();
+        assert_eq!(loader.reload_if_changed(&mut engine).unwrap(), Some(3));
+        assert_eq!(engine.abac().policies().len(), 3);
+
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(loader.reload_if_changed(&mut engine).unwrap(), Some(2));
+        assert_eq!(engine.abac().policies().len(), 2);
+    }
+}
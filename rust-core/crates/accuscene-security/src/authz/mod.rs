@@ -1,16 +1,25 @@
 //! Authorization framework
 //!
 //! Comprehensive authorization system combining RBAC and ABAC with a policy engine.
+//! ABAC policies can be written in a text policy language, loaded from files
+//! with hot reload, unit-tested and explained.
 
 pub mod abac;
+pub mod dsl;
+pub mod harness;
+pub mod loader;
 pub mod permission;
 pub mod policy;
 pub mod rbac;
 
 pub use abac::{
     AbacPolicy, AbacService, Attributes, AttributeContext, AttributeValue,
-    AuthorizationDecision, AuthorizationRequest, Condition, Effect,
+    AuthorizationDecision, AuthorizationRequest, Condition, ConditionTrace, Effect, Explanation,
+    PolicyTrace,
 };
+pub use dsl::{parse_policies, Expr};
+pub use harness::{Expectation, PolicyTestCase, PolicyTestReport, PolicyTestSuite};
+pub use loader::PolicyLoader;
 pub use permission::{Permission, PermissionSet, StandardPermissions};
 pub use policy::{DecisionEffect, PolicyDecision, PolicyEngine, PolicyEngineConfig, PolicyRequest};
 pub use rbac::{RbacService, Role};
@@ -47,7 +56,8 @@ impl AuthorizationService {
         context: &AuthContext,
         permission: &str,
     ) -> Result<()> {
-        let request = PolicyRequest::simple(context.user_id.clone(), permission.to_string());
+        let request = PolicyRequest::simple(context.user_id.clone(), permission.to_string())
+            .with_org(context.org_id.clone());
         let decision = self.engine.authorize(request)?;
         decision.to_result()
     }
@@ -58,6 +68,11 @@ impl AuthorizationService {
         decision.to_result()
     }
 
+    /// Explain the decision for a request, showing which policies allowed or denied it
+    pub fn explain(&self, request: &PolicyRequest) -> Result<Explanation> {
+        self.engine.explain(request)
+    }
+
     /// Check if context has permission
     pub fn has_permission(&mut self, context: &AuthContext, permission: &str) -> bool {
         self.authorize_context(context, permission).is_ok()
@@ -115,6 +130,7 @@ mod tests {
             session_id: Some("session123".to_string()),
             roles: vec!["admin".to_string()],
             permissions: vec!["users:read".to_string(), "users:write".to_string()],
+            org_id: None,
             mfa_verified: true,
             session_metadata: Some(SessionMetadata::basic("192.168.1.1".to_string())),
         }
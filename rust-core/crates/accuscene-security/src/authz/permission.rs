@@ -85,6 +85,9 @@ impl StandardPermissions {
         // Evidence management
         permissions.extend(Self::evidence_permissions());
 
+        // Sensitive data permissions
+        permissions.extend(Self::sensitive_data_permissions());
+
         // Report management
         permissions.extend(Self::report_permissions());
 
@@ -132,6 +135,14 @@ impl StandardPermissions {
         ]
     }
 
+    /// Permissions to read encrypted sensitive fields
+    pub fn sensitive_data_permissions() -> Vec<Permission> {
+        vec![
+            Permission::new("pii", "read", "View names and vehicle identifiers"),
+            Permission::new("medical", "read", "View medical report contents"),
+        ]
+    }
+
     /// Report management permissions
     pub fn report_permissions() -> Vec<Permission> {
         vec![
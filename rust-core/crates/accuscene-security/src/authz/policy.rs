@@ -6,7 +6,7 @@ use crate::error::{Result, SecurityError};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use super::abac::{AbacService, Attributes, AuthorizationRequest};
+use super::abac::{AbacPolicy, AbacService, Attributes, AuthorizationRequest, Explanation};
 use super::rbac::RbacService;
 
 /// Policy engine combining RBAC and ABAC
@@ -48,6 +48,52 @@ impl PolicyEngine {
         &mut self.abac
     }
 
+    /// Replace the policies loaded from `origin`, invalidating cached decisions
+    pub fn load_policies(&mut self, origin: &str, policies: Vec<AbacPolicy>) {
+        self.abac.replace_policies_from(origin, policies);
+        self.clear_cache();
+    }
+
+    /// Remove the policies loaded from `origin`, invalidating cached decisions
+    pub fn unload_policies(&mut self, origin: &str) -> usize {
+        let removed = self.abac.remove_policies_from(origin);
+        self.clear_cache();
+        removed
+    }
+
+    /// Explain the decision for a request without using or filling the cache
+    pub fn explain(&self, request: &PolicyRequest) -> Result<Explanation> {
+        let rbac_decision = if self.config.rbac_enabled {
+            self.evaluate_rbac(request)?
+        } else {
+            PolicyDecision::not_applicable("RBAC disabled")
+        };
+
+        let mut explanation = if self.config.abac_enabled {
+            self.abac.explain(&request.abac_request())?
+        } else {
+            Explanation {
+                action: request.action.clone(),
+                allowed: false,
+                decided_by: None,
+                reason: "ABAC disabled".to_string(),
+                rbac: None,
+                policies: vec![],
+            }
+        };
+
+        // Mirror combine_decisions: an ABAC deny overrides an RBAC allow
+        let abac_denied = self.config.abac_enabled && !explanation.allowed;
+        if !abac_denied && rbac_decision.is_allowed() {
+            explanation.allowed = true;
+            explanation.decided_by = None;
+            explanation.reason = rbac_decision.reason.clone();
+        }
+        explanation.rbac = Some(rbac_decision.reason);
+
+        Ok(explanation)
+    }
+
     /// Make an authorization decision
     pub fn authorize(&mut self, request: PolicyRequest) -> Result<PolicyDecision> {
         // Check cache if enabled
@@ -100,7 +146,11 @@ impl PolicyEngine {
     /// Evaluate RBAC
     fn evaluate_rbac(&self, request: &PolicyRequest) -> Result<PolicyDecision> {
         // Check if user has required permission
-        if self.rbac.has_permission(&request.user_id, &request.permission) {
+        if self.rbac.has_permission_in_org(
+            &request.user_id,
+            request.org_id.as_deref(),
+            &request.permission,
+        ) {
             Ok(PolicyDecision {
                 effect: DecisionEffect::Allow,
                 reason: format!("RBAC: User has permission '{}'", request.permission),
@@ -119,14 +169,7 @@ impl PolicyEngine {
 
     /// Evaluate ABAC
     fn evaluate_abac(&self, request: &PolicyRequest) -> Result<PolicyDecision> {
-        let authz_request = AuthorizationRequest {
-            subject: request.subject_attrs.clone(),
-            resource: request.resource_attrs.clone(),
-            context: request.context_attrs.clone(),
-            action: request.action.clone(),
-        };
-
-        let abac_decision = self.abac.evaluate(&authz_request)?;
+        let abac_decision = self.abac.evaluate(&request.abac_request())?;
 
         Ok(PolicyDecision {
             effect: if abac_decision.allowed {
@@ -178,6 +221,9 @@ impl PolicyEngine {
         hasher.update(request.user_id.as_bytes());
         hasher.update(request.permission.as_bytes());
         hasher.update(request.action.as_bytes());
+        if let Some(org_id) = &request.org_id {
+            hasher.update(org_id.as_bytes());
+        }
         hex::encode(hasher.finalize())
     }
 
@@ -229,6 +275,8 @@ pub struct PolicyRequest {
     pub permission: String,
     /// Action being performed
     pub action: String,
+    /// Organization the user acts for
+    pub org_id: Option<String>,
     /// Subject attributes
     pub subject_attrs: Attributes,
     /// Resource attributes
@@ -244,12 +292,19 @@ impl PolicyRequest {
             user_id,
             permission: permission.clone(),
             action: permission,
+            org_id: None,
             subject_attrs: HashMap::new(),
             resource_attrs: HashMap::new(),
             context_attrs: HashMap::new(),
         }
     }
 
+    /// Evaluate organization-scoped roles for the given organization
+    pub fn with_org(mut self, org_id: Option<String>) -> Self {
+        self.org_id = org_id;
+        self
+    }
+
     /// Add subject attribute
     pub fn with_subject_attr(mut self, key: String, value: super::abac::AttributeValue) -> Self {
         self.subject_attrs.insert(key, value);
@@ -267,6 +322,28 @@ impl PolicyRequest {
         self.context_attrs.insert(key, value);
         self
     }
+
+    /// Build the ABAC request for this policy request
+    pub fn abac_request(&self) -> AuthorizationRequest {
+        AuthorizationRequest {
+            subject: self.subject_attrs.clone(),
+            resource: self.resource_attrs.clone(),
+            context: self.context_attrs.clone(),
+            action: self.action.clone(),
+        }
+    }
+
+    /// Attach organization scopes of the subject and the resource as `scopes` lists
+    pub fn with_scopes(self, subject_scopes: Vec<String>, resource_scopes: Vec<String>) -> Self {
+        use super::abac::AttributeValue;
+
+        let to_list = |scopes: Vec<String>| {
+            AttributeValue::List(scopes.into_iter().map(AttributeValue::String).collect())
+        };
+
+        self.with_subject_attr("scopes".to_string(), to_list(subject_scopes))
+            .with_resource_attr("scopes".to_string(), to_list(resource_scopes))
+    }
 }
 
 /// Policy decision
@@ -11,6 +11,8 @@ pub struct RbacService {
     roles: HashMap<String, Role>,
     role_hierarchy: HashMap<String, Vec<String>>,
     user_roles: HashMap<String, HashSet<String>>,
+    /// Roles granted within an organization, keyed by (user, org)
+    org_roles: HashMap<(String, String), HashSet<String>>,
 }
 
 impl RbacService {
@@ -20,6 +22,7 @@ impl RbacService {
             roles: HashMap::new(),
             role_hierarchy: HashMap::new(),
             user_roles: HashMap::new(),
+            org_roles: HashMap::new(),
         };
 
         // Initialize default roles
@@ -74,6 +77,7 @@ impl RbacService {
                 "cases:write".to_string(),
                 "evidence:read".to_string(),
                 "evidence:write".to_string(),
+                "pii:read".to_string(),
                 "reports:read".to_string(),
                 "reports:write".to_string(),
                 "analysis:execute".to_string(),
@@ -161,15 +165,65 @@ impl RbacService {
             .unwrap_or_default()
     }
 
+    /// Assign role to user within an organization
+    pub fn assign_org_role(&mut self, user_id: &str, org_id: &str, role_id: &str) -> Result<()> {
+        if !self.roles.contains_key(role_id) {
+            return Err(SecurityError::RoleNotFound(role_id.to_string()));
+        }
+
+        self.org_roles
+            .entry((user_id.to_string(), org_id.to_string()))
+            .or_default()
+            .insert(role_id.to_string());
+
+        Ok(())
+    }
+
+    /// Revoke an organization-scoped role from user
+    pub fn revoke_org_role(&mut self, user_id: &str, org_id: &str, role_id: &str) -> Result<()> {
+        if let Some(roles) = self
+            .org_roles
+            .get_mut(&(user_id.to_string(), org_id.to_string()))
+        {
+            roles.remove(role_id);
+        }
+        Ok(())
+    }
+
+    /// Get roles a user holds within an organization
+    pub fn get_org_roles(&self, user_id: &str, org_id: &str) -> Vec<String> {
+        self.org_roles
+            .get(&(user_id.to_string(), org_id.to_string()))
+            .map(|roles| roles.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
     /// Get all permissions for a user (including inherited)
     pub fn get_user_permissions(&self, user_id: &str) -> HashSet<String> {
+        self.get_org_permissions(user_id, None)
+    }
+
+    /// Get permissions for a user acting within an organization
+    ///
+    /// Global roles always apply; organization roles only apply when `org_id` matches.
+    pub fn get_org_permissions(&self, user_id: &str, org_id: Option<&str>) -> HashSet<String> {
         let mut permissions = HashSet::new();
 
-        if let Some(role_ids) = self.user_roles.get(user_id) {
-            for role_id in role_ids {
-                if let Some(role) = self.roles.get(role_id) {
-                    self.collect_permissions(role, &mut permissions);
-                }
+        let org_role_ids = org_id.and_then(|org| {
+            self.org_roles
+                .get(&(user_id.to_string(), org.to_string()))
+        });
+
+        let role_ids = self
+            .user_roles
+            .get(user_id)
+            .into_iter()
+            .chain(org_role_ids)
+            .flatten();
+
+        for role_id in role_ids {
+            if let Some(role) = self.roles.get(role_id) {
+                self.collect_permissions(role, &mut permissions);
             }
         }
 
@@ -191,7 +245,17 @@ impl RbacService {
 
     /// Check if user has a specific permission
     pub fn has_permission(&self, user_id: &str, permission: &str) -> bool {
-        let permissions = self.get_user_permissions(user_id);
+        self.has_permission_in_org(user_id, None, permission)
+    }
+
+    /// Check if user has a permission while acting within an organization
+    pub fn has_permission_in_org(
+        &self,
+        user_id: &str,
+        org_id: Option<&str>,
+        permission: &str,
+    ) -> bool {
+        let permissions = self.get_org_permissions(user_id, org_id);
 
         // Check for wildcard permission
         if permissions.contains("*") {
@@ -369,4 +433,23 @@ mod tests {
 
         assert!(service.has_permission("user123", "analytics:read"));
     }
+
+    #[test]
+    fn test_org_scoped_roles() {
+        let mut service = RbacService::new();
+        service.assign_role("user123", "viewer").unwrap();
+        service
+            .assign_org_role("user123", "firm-a", "investigator")
+            .unwrap();
+
+        assert!(service.has_permission_in_org("user123", Some("firm-a"), "cases:write"));
+        assert!(!service.has_permission_in_org("user123", Some("firm-b"), "cases:write"));
+        assert!(!service.has_permission("user123", "cases:write"));
+        assert!(service.has_permission_in_org("user123", Some("firm-b"), "cases:read"));
+
+        service
+            .revoke_org_role("user123", "firm-a", "investigator")
+            .unwrap();
+        assert!(service.get_org_roles("user123", "firm-a").is_empty());
+    }
 }
@@ -0,0 +1,779 @@
+//! GDPR data subject requests
+//!
+//! [`DsarEngine`] runs access, portability and erasure requests (GDPR
+//! Articles 15, 17 and 20). Personal data lives in several repositories, each
+//! exposed through a [`PersonalDataSource`]; the engine asks every source to
+//! locate the subject's records, bundles them into a machine-readable
+//! [`DsarExport`], or erases them. Records covered by an active
+//! [`LegalHold`] are retained and reported instead of erased, as Article
+//! 17(3) allows for legal claims.
+//!
+//! Every step is recorded in the audit trail. Audit events carry the request
+//! ID and record references, never the personal data itself.
+
+use crate::audit::{AuditEvent, AuditService, EventResult, EventSeverity, EventType, ResourceInfo};
+use crate::error::{Result, SecurityError};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Format identifier of export bundles
+pub const DSAR_EXPORT_FORMAT: &str = "accuscene-dsar-export/1";
+
+/// Days within which a request must be answered (GDPR Article 12(3))
+pub const RESPONSE_DEADLINE_DAYS: i64 = 30;
+
+/// Person whose data is requested
+///
+/// Sources match on whichever identifiers are set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DataSubject {
+    /// User account ID
+    pub user_id: Option<String>,
+    /// Email address
+    pub email: Option<String>,
+    /// Full name
+    pub full_name: Option<String>,
+    /// Date of birth, to tell apart people with the same name
+    pub date_of_birth: Option<NaiveDate>,
+}
+
+impl DataSubject {
+    /// Create a subject with no identifiers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the user account ID
+    pub fn with_user_id(mut self, user_id: impl Into<String>) -> Self {
+        self.user_id = Some(user_id.into());
+        self
+    }
+
+    /// Set the email address
+    pub fn with_email(mut self, email: impl Into<String>) -> Self {
+        self.email = Some(email.into());
+        self
+    }
+
+    /// Set the full name
+    pub fn with_full_name(mut self, full_name: impl Into<String>) -> Self {
+        self.full_name = Some(full_name.into());
+        self
+    }
+
+    /// Set the date of birth
+    pub fn with_date_of_birth(mut self, date_of_birth: NaiveDate) -> Self {
+        self.date_of_birth = Some(date_of_birth);
+        self
+    }
+
+    /// Check whether any identifier is set
+    pub fn is_identified(&self) -> bool {
+        self.user_id.is_some() || self.email.is_some() || self.full_name.is_some()
+    }
+}
+
+/// Right exercised by a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DsarKind {
+    /// Right of access (Article 15)
+    Access,
+    /// Right to data portability (Article 20)
+    Portability,
+    /// Right to erasure (Article 17)
+    Erasure,
+}
+
+/// Progress of a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DsarStatus {
+    /// Received, not yet worked on
+    Received,
+    /// Data has been located
+    InProgress,
+    /// Fulfilled
+    Completed,
+    /// Erasure done except for records under legal hold
+    PartiallyCompleted,
+    /// Some records could not be erased
+    Failed,
+}
+
+/// A data subject request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DsarRequest {
+    /// Request ID
+    pub id: String,
+    /// Right exercised
+    pub kind: DsarKind,
+    /// Person the request is about
+    pub subject: DataSubject,
+    /// Staff member handling the request
+    pub requested_by: String,
+    /// Current status
+    pub status: DsarStatus,
+    /// When the request was received
+    pub received_at: DateTime<Utc>,
+    /// When the response is due
+    pub due_at: DateTime<Utc>,
+    /// When the request was fulfilled
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+impl DsarRequest {
+    /// Check whether the response deadline has passed unanswered
+    pub fn is_overdue(&self) -> bool {
+        self.completed_at.is_none() && Utc::now() > self.due_at
+    }
+}
+
+/// Personal data held about a subject in one record
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PersonalDataRecord {
+    /// Name of the source holding the record
+    pub source: String,
+    /// Kind of record, e.g. `user` or `party`
+    pub record_type: String,
+    /// Record ID within the source
+    pub record_id: String,
+    /// Cases the record is linked to
+    #[serde(default)]
+    pub case_ids: Vec<String>,
+    /// The personal data
+    pub data: serde_json::Value,
+}
+
+impl PersonalDataRecord {
+    /// Create a record
+    pub fn new(
+        source: impl Into<String>,
+        record_type: impl Into<String>,
+        record_id: impl Into<String>,
+        data: serde_json::Value,
+    ) -> Self {
+        Self {
+            source: source.into(),
+            record_type: record_type.into(),
+            record_id: record_id.into(),
+            case_ids: Vec::new(),
+            data,
+        }
+    }
+
+    /// Link the record to a case
+    pub fn with_case(mut self, case_id: impl Into<String>) -> Self {
+        self.case_ids.push(case_id.into());
+        self
+    }
+
+    /// Reference used in audit events and reports
+    pub fn reference(&self) -> String {
+        format!("{}:{}:{}", self.source, self.record_type, self.record_id)
+    }
+}
+
+/// Repository holding personal data
+#[async_trait]
+pub trait PersonalDataSource: Send + Sync {
+    /// Name of the source, unique within an engine
+    fn name(&self) -> &str;
+
+    /// Find the subject's records
+    async fn locate(&self, subject: &DataSubject) -> Result<Vec<PersonalDataRecord>>;
+
+    /// Erase or irreversibly anonymize a record returned by [`locate`](Self::locate)
+    async fn erase(&self, record: &PersonalDataRecord) -> Result<()>;
+}
+
+/// What a legal hold applies to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HoldScope {
+    /// All records linked to a case
+    Case { case_id: String },
+    /// All records of a user account
+    Subject { user_id: String },
+    /// A single record
+    Record { source: String, record_id: String },
+}
+
+/// Preservation order exempting records from erasure
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegalHold {
+    /// Hold ID
+    pub id: String,
+    /// Records covered
+    pub scope: HoldScope,
+    /// Reason, e.g. the litigation or order reference
+    pub reason: String,
+    /// Who placed the hold
+    pub placed_by: String,
+    /// When the hold was placed
+    pub placed_at: DateTime<Utc>,
+    /// When the hold was released
+    pub released_at: Option<DateTime<Utc>>,
+}
+
+impl LegalHold {
+    /// Create an active hold
+    pub fn new(scope: HoldScope, reason: impl Into<String>, placed_by: impl Into<String>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            scope,
+            reason: reason.into(),
+            placed_by: placed_by.into(),
+            placed_at: Utc::now(),
+            released_at: None,
+        }
+    }
+
+    /// Check whether the hold is in force
+    pub fn is_active(&self) -> bool {
+        self.released_at.is_none()
+    }
+
+    /// Check whether the hold covers a subject's record
+    pub fn covers(&self, subject: &DataSubject, record: &PersonalDataRecord) -> bool {
+        if !self.is_active() {
+            return false;
+        }
+        match &self.scope {
+            HoldScope::Case { case_id } => record.case_ids.contains(case_id),
+            HoldScope::Subject { user_id } => subject.user_id.as_ref() == Some(user_id),
+            HoldScope::Record { source, record_id } => {
+                record.source == *source && record.record_id == *record_id
+            },
+        }
+    }
+}
+
+/// Machine-readable bundle of a subject's personal data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DsarExport {
+    /// Bundle format, [`DSAR_EXPORT_FORMAT`]
+    pub format: String,
+    /// Request the bundle answers
+    pub request_id: String,
+    /// Person the data is about
+    pub subject: DataSubject,
+    /// When the bundle was produced
+    pub generated_at: DateTime<Utc>,
+    /// Records grouped by source
+    pub sources: BTreeMap<String, Vec<PersonalDataRecord>>,
+    /// SHA-256 of the serialized `sources`, hex encoded
+    pub digest: String,
+}
+
+impl DsarExport {
+    /// Number of records in the bundle
+    pub fn record_count(&self) -> usize {
+        self.sources.values().map(Vec::len).sum()
+    }
+
+    /// Check the digest against the records
+    pub fn verify(&self) -> bool {
+        digest(&self.sources).is_ok_and(|digest| digest == self.digest)
+    }
+
+    /// Serialize as pretty-printed JSON
+    pub fn to_json(&self) -> Result<Vec<u8>> {
+        serde_json::to_vec_pretty(self).map_err(|e| SecurityError::Internal(e.to_string()))
+    }
+}
+
+/// Record kept despite an erasure request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetainedRecord {
+    /// Record reference
+    pub reference: String,
+    /// Hold that exempted the record
+    pub hold_id: String,
+    /// Reason given for the hold
+    pub reason: String,
+}
+
+/// Outcome of an erasure request
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ErasureReport {
+    /// Request ID
+    pub request_id: String,
+    /// References of erased records
+    pub erased: Vec<String>,
+    /// Records retained under legal hold
+    pub retained: Vec<RetainedRecord>,
+    /// References of records that could not be erased, with the error
+    pub failed: Vec<(String, String)>,
+}
+
+/// Runs data subject requests across personal data sources
+#[derive(Default)]
+pub struct DsarEngine {
+    sources: RwLock<Vec<Arc<dyn PersonalDataSource>>>,
+    requests: RwLock<HashMap<String, DsarRequest>>,
+    holds: RwLock<HashMap<String, LegalHold>>,
+    audit: Option<Arc<AuditService>>,
+}
+
+impl DsarEngine {
+    /// Create an engine with no sources
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record request steps in the audit trail
+    pub fn with_audit(mut self, audit: Arc<AuditService>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Register a personal data source
+    pub async fn add_source(&self, source: Arc<dyn PersonalDataSource>) -> Result<()> {
+        let mut sources = self.sources.write().await;
+        if sources.iter().any(|s| s.name() == source.name()) {
+            return Err(SecurityError::ConfigurationError(format!(
+                "Personal data source '{}' already registered",
+                source.name()
+            )));
+        }
+        sources.push(source);
+        Ok(())
+    }
+
+    /// Open a request
+    pub async fn open_request(
+        &self,
+        kind: DsarKind,
+        subject: DataSubject,
+        requested_by: &str,
+    ) -> Result<DsarRequest> {
+        if !subject.is_identified() {
+            return Err(SecurityError::DsarInvalid(
+                "the subject has no identifiers".to_string(),
+            ));
+        }
+
+        let received_at = Utc::now();
+        let request = DsarRequest {
+            id: uuid::Uuid::new_v4().to_string(),
+            kind,
+            subject,
+            requested_by: requested_by.to_string(),
+            status: DsarStatus::Received,
+            received_at,
+            due_at: received_at + chrono::Duration::days(RESPONSE_DEADLINE_DAYS),
+            completed_at: None,
+        };
+        self.requests.write().await.insert(request.id.clone(), request.clone());
+
+        let mut metadata = vec![("kind", format!("{:?}", kind))];
+        if let Some(user_id) = &request.subject.user_id {
+            metadata.push(("subject_user_id", user_id.clone()));
+        }
+        self.audit(&request, EventType::DsarReceived, "dsar.receive", &metadata).await?;
+
+        Ok(request)
+    }
+
+    /// Get a request
+    pub async fn get_request(&self, request_id: &str) -> Result<DsarRequest> {
+        self.requests
+            .read()
+            .await
+            .get(request_id)
+            .cloned()
+            .ok_or_else(|| SecurityError::DsarNotFound(request_id.to_string()))
+    }
+
+    /// List requests, oldest first
+    pub async fn requests(&self) -> Vec<DsarRequest> {
+        let mut requests: Vec<_> = self.requests.read().await.values().cloned().collect();
+        requests.sort_by_key(|r| r.received_at);
+        requests
+    }
+
+    /// Find the subject's personal data in every source
+    pub async fn locate(&self, request_id: &str) -> Result<Vec<PersonalDataRecord>> {
+        let request = self.get_request(request_id).await?;
+        let sources = self.sources.read().await.clone();
+
+        let mut records = Vec::new();
+        for source in &sources {
+            let found = source.locate(&request.subject).await?;
+            self.audit(
+                &request,
+                EventType::DsarDataLocated,
+                "dsar.locate",
+                &[
+                    ("source", source.name().to_string()),
+                    ("records", found.len().to_string()),
+                ],
+            )
+            .await?;
+            records.extend(found);
+        }
+
+        self.set_status(request_id, DsarStatus::InProgress).await;
+        Ok(records)
+    }
+
+    /// Produce the export bundle for an access or portability request
+    pub async fn export(&self, request_id: &str) -> Result<DsarExport> {
+        let request = self.get_request(request_id).await?;
+        if request.kind == DsarKind::Erasure {
+            return Err(SecurityError::DsarInvalid(format!(
+                "{} is an erasure request",
+                request_id
+            )));
+        }
+
+        let mut sources: BTreeMap<String, Vec<PersonalDataRecord>> = BTreeMap::new();
+        for record in self.locate(request_id).await? {
+            sources.entry(record.source.clone()).or_default().push(record);
+        }
+        let export = DsarExport {
+            format: DSAR_EXPORT_FORMAT.to_string(),
+            request_id: request.id.clone(),
+            subject: request.subject.clone(),
+            generated_at: Utc::now(),
+            digest: digest(&sources)?,
+            sources,
+        };
+
+        self.audit(
+            &request,
+            EventType::DsarExported,
+            "dsar.export",
+            &[
+                ("records", export.record_count().to_string()),
+                ("digest", export.digest.clone()),
+            ],
+        )
+        .await?;
+        self.complete(&request, DsarStatus::Completed).await?;
+
+        Ok(export)
+    }
+
+    /// Erase the subject's personal data, keeping records under legal hold
+    pub async fn erase(&self, request_id: &str) -> Result<ErasureReport> {
+        let request = self.get_request(request_id).await?;
+        if request.kind != DsarKind::Erasure {
+            return Err(SecurityError::DsarInvalid(format!(
+                "{} is not an erasure request",
+                request_id
+            )));
+        }
+
+        let records = self.locate(request_id).await?;
+        let sources = self.sources.read().await.clone();
+        let holds: Vec<LegalHold> = self.holds.read().await.values().cloned().collect();
+        let mut report = ErasureReport {
+            request_id: request.id.clone(),
+            ..ErasureReport::default()
+        };
+
+        for record in &records {
+            let reference = record.reference();
+
+            if let Some(hold) = holds.iter().find(|h| h.covers(&request.subject, record)) {
+                self.audit(
+                    &request,
+                    EventType::DsarErasureWithheld,
+                    "dsar.erase.withhold",
+                    &[("record", reference.clone()), ("hold_id", hold.id.clone())],
+                )
+                .await?;
+                report.retained.push(RetainedRecord {
+                    reference,
+                    hold_id: hold.id.clone(),
+                    reason: hold.reason.clone(),
+                });
+                continue;
+            }
+
+            let outcome = match sources.iter().find(|s| s.name() == record.source) {
+                Some(source) => source.erase(record).await,
+                None => Err(SecurityError::ConfigurationError(format!(
+                    "Personal data source '{}' not registered",
+                    record.source
+                ))),
+            };
+            match outcome {
+                Ok(()) => {
+                    self.audit(
+                        &request,
+                        EventType::DsarRecordErased,
+                        "dsar.erase.record",
+                        &[("record", reference.clone())],
+                    )
+                    .await?;
+                    report.erased.push(reference);
+                },
+                Err(e) => {
+                    tracing::error!("DSAR {} failed to erase {}: {}", request.id, reference, e);
+                    report.failed.push((reference, e.to_string()));
+                },
+            }
+        }
+
+        let status = if !report.failed.is_empty() {
+            DsarStatus::Failed
+        } else if !report.retained.is_empty() {
+            DsarStatus::PartiallyCompleted
+        } else {
+            DsarStatus::Completed
+        };
+        self.complete(&request, status).await?;
+
+        Ok(report)
+    }
+
+    /// Place a legal hold
+    pub async fn place_hold(&self, hold: LegalHold) -> Result<()> {
+        self.audit_hold(
+            &hold,
+            EventType::LegalHoldPlaced,
+            "legal_hold.place",
+            &hold.placed_by,
+        )
+        .await?;
+        self.holds.write().await.insert(hold.id.clone(), hold);
+        Ok(())
+    }
+
+    /// Release a legal hold
+    pub async fn release_hold(&self, hold_id: &str, released_by: &str) -> Result<LegalHold> {
+        let hold = {
+            let mut holds = self.holds.write().await;
+            let hold = holds
+                .get_mut(hold_id)
+                .filter(|h| h.is_active())
+                .ok_or_else(|| SecurityError::DsarNotFound(format!("legal hold {}", hold_id)))?;
+            hold.released_at = Some(Utc::now());
+            hold.clone()
+        };
+        self.audit_hold(
+            &hold,
+            EventType::LegalHoldReleased,
+            "legal_hold.release",
+            released_by,
+        )
+        .await?;
+        Ok(hold)
+    }
+
+    /// List active legal holds
+    pub async fn active_holds(&self) -> Vec<LegalHold> {
+        self.holds.read().await.values().filter(|h| h.is_active()).cloned().collect()
+    }
+
+    async fn set_status(&self, request_id: &str, status: DsarStatus) {
+        if let Some(request) = self.requests.write().await.get_mut(request_id) {
+            request.status = status;
+            if matches!(
+                status,
+                DsarStatus::Completed | DsarStatus::PartiallyCompleted
+            ) {
+                request.completed_at = Some(Utc::now());
+            }
+        }
+    }
+
+    async fn complete(&self, request: &DsarRequest, status: DsarStatus) -> Result<()> {
+        self.set_status(&request.id, status).await;
+        self.audit(
+            request,
+            EventType::DsarCompleted,
+            "dsar.complete",
+            &[("status", format!("{:?}", status))],
+        )
+        .await
+    }
+
+    async fn audit(
+        &self,
+        request: &DsarRequest,
+        event_type: EventType,
+        action: &str,
+        metadata: &[(&str, String)],
+    ) -> Result<()> {
+        let Some(audit) = &self.audit else {
+            return Ok(());
+        };
+
+        let mut event = AuditEvent::new(event_type, action.to_string())
+            .with_user(request.requested_by.clone())
+            .with_resource(ResourceInfo::new("dsar", request.id.clone()))
+            .with_result(EventResult::Success)
+            .add_metadata("channel".to_string(), "gdpr".to_string());
+        if event_type == EventType::DsarErasureWithheld {
+            event = event.with_severity(EventSeverity::Warning);
+        }
+        for (key, value) in metadata {
+            event = event.add_metadata(key.to_string(), value.clone());
+        }
+
+        audit.audit(event).await
+    }
+
+    async fn audit_hold(
+        &self,
+        hold: &LegalHold,
+        event_type: EventType,
+        action: &str,
+        user: &str,
+    ) -> Result<()> {
+        let Some(audit) = &self.audit else {
+            return Ok(());
+        };
+
+        let scope = serde_json::to_string(&hold.scope)
+            .map_err(|e| SecurityError::Internal(e.to_string()))?;
+        let event = AuditEvent::new(event_type, action.to_string())
+            .with_user(user.to_string())
+            .with_resource(ResourceInfo::new("legal_hold", hold.id.clone()))
+            .with_result(EventResult::Success)
+            .add_metadata("channel".to_string(), "gdpr".to_string())
+            .add_metadata("scope".to_string(), scope)
+            .add_metadata("reason".to_string(), hold.reason.clone());
+
+        audit.audit(event).await
+    }
+}
+
+fn digest(sources: &BTreeMap<String, Vec<PersonalDataRecord>>) -> Result<String> {
+    use sha2::{Digest, Sha256};
+    let bytes = serde_json::to_vec(sources).map_err(|e| SecurityError::Internal(e.to_string()))?;
+    Ok(hex::encode(Sha256::digest(&bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::AuditQuery;
+
+    /// Source holding records in memory
+    struct MemorySource {
+        records: RwLock<Vec<PersonalDataRecord>>,
+    }
+
+    #[async_trait]
+    impl PersonalDataSource for MemorySource {
+        fn name(&self) -> &str {
+            "memory"
+        }
+
+        async fn locate(&self, subject: &DataSubject) -> Result<Vec<PersonalDataRecord>> {
+            let email = subject.email.as_deref().unwrap_or_default();
+            Ok(self
+                .records
+                .read()
+                .await
+                .iter()
+                .filter(|r| r.data["email"] == email)
+                .cloned()
+                .collect())
+        }
+
+        async fn erase(&self, record: &PersonalDataRecord) -> Result<()> {
+            self.records.write().await.retain(|r| r.record_id != record.record_id);
+            Ok(())
+        }
+    }
+
+    async fn engine() -> (DsarEngine, Arc<AuditService>) {
+        let audit = Arc::new(AuditService::default());
+        let engine = DsarEngine::new().with_audit(Arc::clone(&audit));
+        let email = serde_json::json!({ "email": "jane@example.com" });
+        let records = vec![
+            PersonalDataRecord::new("memory", "user", "u-1", email.clone()),
+            PersonalDataRecord::new("memory", "party", "p-1", email.clone()).with_case("case-1"),
+            PersonalDataRecord::new("memory", "party", "p-2", email).with_case("case-2"),
+            PersonalDataRecord::new(
+                "memory",
+                "party",
+                "p-3",
+                serde_json::json!({ "email": "someone@example.com" }),
+            ),
+        ];
+        engine
+            .add_source(Arc::new(MemorySource {
+                records: RwLock::new(records),
+            }))
+            .await
+            .unwrap();
+        (engine, audit)
+    }
+
+    fn subject() -> DataSubject {
+        DataSubject::new().with_user_id("u-1").with_email("jane@example.com")
+    }
+
+    #[tokio::test]
+    async fn test_access_request_export() {
+        let (engine, audit) = engine().await;
+        let request = engine.open_request(DsarKind::Access, subject(), "dpo").await.unwrap();
+
+        let export = engine.export(&request.id).await.unwrap();
+        assert_eq!(export.format, DSAR_EXPORT_FORMAT);
+        assert_eq!(export.record_count(), 3);
+        assert!(export.verify());
+        assert!(!export.to_json().unwrap().is_empty());
+
+        let request = engine.get_request(&request.id).await.unwrap();
+        assert_eq!(request.status, DsarStatus::Completed);
+        assert!(engine.erase(&request.id).await.is_err());
+
+        let events = audit.query(AuditQuery::new()).await.unwrap().events;
+        let types: Vec<_> = events.iter().map(|e| e.event_type).collect();
+        for expected in [
+            EventType::DsarReceived,
+            EventType::DsarDataLocated,
+            EventType::DsarExported,
+            EventType::DsarCompleted,
+        ] {
+            assert!(types.contains(&expected));
+        }
+        // Personal data stays out of the audit trail
+        let trail = serde_json::to_string(&events).unwrap();
+        assert!(!trail.contains("jane@example.com"));
+    }
+
+    #[tokio::test]
+    async fn test_erasure_respects_legal_holds() {
+        let (engine, _) = engine().await;
+        let hold = LegalHold::new(
+            HoldScope::Case {
+                case_id: "case-2".to_string(),
+            },
+            "Pending litigation",
+            "counsel",
+        );
+        let hold_id = hold.id.clone();
+        engine.place_hold(hold).await.unwrap();
+
+        let request = engine.open_request(DsarKind::Erasure, subject(), "dpo").await.unwrap();
+        let report = engine.erase(&request.id).await.unwrap();
+        assert_eq!(report.erased.len(), 2);
+        assert_eq!(report.retained.len(), 1);
+        assert_eq!(report.retained[0].hold_id, hold_id);
+        assert_eq!(
+            engine.get_request(&request.id).await.unwrap().status,
+            DsarStatus::PartiallyCompleted
+        );
+
+        // Once the hold is released a new request erases the rest
+        engine.release_hold(&hold_id, "counsel").await.unwrap();
+        assert!(engine.active_holds().await.is_empty());
+        let request = engine.open_request(DsarKind::Erasure, subject(), "dpo").await.unwrap();
+        let report = engine.erase(&request.id).await.unwrap();
+        assert_eq!(report.erased, vec!["memory:party:p-2".to_string()]);
+        assert!(engine.locate(&request.id).await.unwrap().is_empty());
+    }
+}
@@ -1,18 +1,28 @@
 //! Compliance framework
 //!
-//! Implements compliance controls for SOC2, GDPR, and HIPAA.
+//! Implements compliance controls for SOC2, GDPR, and HIPAA, and runs GDPR
+//! data subject requests through [`DsarEngine`].
 
+pub mod dsar;
 pub mod gdpr;
 pub mod hipaa;
 pub mod soc2;
 
+pub use dsar::{
+    DataSubject, DsarEngine, DsarExport, DsarKind, DsarRequest, DsarStatus, ErasureReport,
+    HoldScope, LegalHold, PersonalDataRecord, PersonalDataSource, RetainedRecord,
+};
 pub use gdpr::{DataContext, GdprService, LawfulBasis};
 pub use hipaa::{HipaaService, PhiIdentifier};
 pub use soc2::{ComplianceStatus, Control, ControlStatus, Soc2Service, TrustServiceCategory};
 
+use crate::audit::AuditService;
+use std::sync::Arc;
+
 /// Main compliance service
 pub struct ComplianceService {
     soc2: Soc2Service,
+    dsar: DsarEngine,
 }
 
 impl ComplianceService {
@@ -20,14 +30,26 @@ impl ComplianceService {
     pub fn new() -> Self {
         Self {
             soc2: Soc2Service::new(),
+            dsar: DsarEngine::new(),
         }
     }
 
+    /// Record data subject request steps in the audit trail
+    pub fn with_audit(mut self, audit: Arc<AuditService>) -> Self {
+        self.dsar = self.dsar.with_audit(audit);
+        self
+    }
+
     /// Get SOC2 service
     pub fn soc2(&self) -> &Soc2Service {
         &self.soc2
     }
 
+    /// Get the GDPR data subject request engine
+    pub fn dsar(&self) -> &DsarEngine {
+        &self.dsar
+    }
+
     /// Get compliance summary
     pub fn get_summary(&self) -> ComplianceSummary {
         let soc2_status = self.soc2.get_compliance_status();
@@ -2,7 +2,10 @@
 //!
 //! Centralized security configuration for all components.
 
+use crate::audit::event::EventSeverity;
+use crate::siem::{FieldMapping, SiemFormat, SiemProtocol};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::time::Duration;
 
 /// Main security configuration
@@ -20,6 +23,9 @@ pub struct SecurityConfig {
     pub threat: ThreatConfig,
     /// Compliance configuration
     pub compliance: ComplianceConfig,
+    /// SIEM export configuration
+    #[serde(default)]
+    pub siem: SiemConfig,
 }
 
 impl Default for SecurityConfig {
@@ -31,6 +37,7 @@ impl Default for SecurityConfig {
             encryption: EncryptionConfig::default(),
             threat: ThreatConfig::default(),
             compliance: ComplianceConfig::default(),
+            siem: SiemConfig::default(),
         }
     }
 }
@@ -48,6 +55,9 @@ pub struct AuthConfig {
     pub sso: SsoConfig,
     /// JWT configuration
     pub jwt: JwtConfig,
+    /// API key configuration
+    #[serde(default)]
+    pub api_keys: ApiKeyConfig,
 }
 
 impl Default for AuthConfig {
@@ -58,6 +68,7 @@ impl Default for AuthConfig {
             mfa: MfaConfig::default(),
             sso: SsoConfig::default(),
             jwt: JwtConfig::default(),
+            api_keys: ApiKeyConfig::default(),
         }
     }
 }
@@ -191,8 +202,18 @@ pub struct WebAuthnConfig {
     pub rp_id: String,
     /// Origin URL
     pub origin: String,
-    /// Timeout in milliseconds
+    /// Timeout in milliseconds, also the lifetime of issued challenges
     pub timeout_ms: u64,
+    /// Require user verification (PIN, biometrics) on every ceremony
+    #[serde(default)]
+    pub require_user_verification: bool,
+    /// Attestation conveyance requested at registration ("none" or "direct")
+    #[serde(default = "default_attestation")]
+    pub attestation: String,
+}
+
+fn default_attestation() -> String {
+    "none".to_string()
 }
 
 impl Default for WebAuthnConfig {
@@ -202,6 +223,8 @@ impl Default for WebAuthnConfig {
             rp_id: "accuscene.com".to_string(),
             origin: "https://accuscene.com".to_string(),
             timeout_ms: 60000,
+            require_user_verification: false,
+            attestation: default_attestation(),
         }
     }
 }
@@ -260,6 +283,31 @@ impl Default for JwtConfig {
     }
 }
 
+/// API key configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiKeyConfig {
+    /// Lifetime of keys created without an explicit expiry, in days
+    pub default_ttl_days: u32,
+    /// Longest lifetime a key may be given, in days
+    pub max_ttl_days: u32,
+    /// Requests per minute allowed for keys without their own limit
+    pub default_rate_limit_per_minute: u32,
+    /// How long a rotated key keeps working, in seconds
+    pub rotation_grace_secs: u64,
+}
+
+impl Default for ApiKeyConfig {
+    fn default() -> Self {
+        Self {
+            default_ttl_days: 90,
+            max_ttl_days: 365,
+            default_rate_limit_per_minute: 600,
+            rotation_grace_secs: 86400, // 24 hours
+        }
+    }
+}
+
 /// Authorization configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthzConfig {
@@ -303,6 +351,13 @@ pub struct AuditConfig {
     pub tamper_detection: bool,
     /// Audit log encryption
     pub encrypt_logs: bool,
+    /// Seconds between signed Merkle checkpoints of the trail
+    #[serde(default = "default_checkpoint_interval_secs")]
+    pub checkpoint_interval_secs: u64,
+    /// File holding the base64 Ed25519 secret key that signs checkpoints;
+    /// required for tamper detection and generated when missing
+    #[serde(default)]
+    pub signing_key_path: Option<PathBuf>,
 }
 
 impl Default for AuditConfig {
@@ -316,10 +371,16 @@ impl Default for AuditConfig {
             retention_days: 365, // 1 year minimum for SOC2
             tamper_detection: true,
             encrypt_logs: true,
+            checkpoint_interval_secs: default_checkpoint_interval_secs(),
+            signing_key_path: None,
         }
     }
 }
 
+fn default_checkpoint_interval_secs() -> u64 {
+    900 // 15 minutes
+}
+
 /// Encryption configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EncryptionConfig {
@@ -357,6 +418,18 @@ pub struct ThreatConfig {
     pub brute_force_threshold: u32,
     /// Brute force window in seconds
     pub brute_force_window_secs: u64,
+    /// Failed logins from one IP address within the window before it is locked out
+    #[serde(default = "default_ip_brute_force_threshold")]
+    pub ip_brute_force_threshold: u32,
+    /// How long a lockout lasts in seconds
+    #[serde(default = "default_lockout_duration_secs")]
+    pub lockout_duration_secs: u64,
+    /// Failed logins after which a CAPTCHA is requested
+    #[serde(default = "default_captcha_threshold")]
+    pub captcha_threshold: u32,
+    /// Threat score (0.0-1.0) at which MFA step-up is required
+    #[serde(default = "default_step_up_score")]
+    pub step_up_score: f64,
     /// Enable anomaly detection
     pub anomaly_detection: bool,
     /// Anomaly detection sensitivity (0.0-1.0)
@@ -371,12 +444,32 @@ impl Default for ThreatConfig {
             brute_force_detection: true,
             brute_force_threshold: 5,
             brute_force_window_secs: 300, // 5 minutes
+            ip_brute_force_threshold: default_ip_brute_force_threshold(),
+            lockout_duration_secs: default_lockout_duration_secs(),
+            captcha_threshold: default_captcha_threshold(),
+            step_up_score: default_step_up_score(),
             anomaly_detection: true,
             anomaly_sensitivity: 0.8,
         }
     }
 }
 
+fn default_ip_brute_force_threshold() -> u32 {
+    20
+}
+
+fn default_lockout_duration_secs() -> u64 {
+    900 // 15 minutes
+}
+
+fn default_captcha_threshold() -> u32 {
+    3
+}
+
+fn default_step_up_score() -> f64 {
+    0.5
+}
+
 /// Compliance configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ComplianceConfig {
@@ -407,6 +500,70 @@ impl Default for ComplianceConfig {
     }
 }
 
+/// SIEM export configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SiemConfig {
+    /// Enable SIEM export
+    pub enabled: bool,
+    /// Collector host
+    pub host: String,
+    /// Collector port
+    pub port: u16,
+    /// Transport protocol
+    pub protocol: SiemProtocol,
+    /// PEM file with the CA certificates to trust (Mozilla roots if unset)
+    pub tls_ca_file: Option<PathBuf>,
+    /// Name to verify the collector certificate against (host if unset)
+    pub tls_server_name: Option<String>,
+    /// Message format
+    pub format: SiemFormat,
+    /// Exported fields and their names
+    pub field_mapping: FieldMapping,
+    /// Minimum severity of exported events
+    pub min_severity: EventSeverity,
+    /// Events per batch
+    pub batch_size: usize,
+    /// Interval between flushes of partial batches in seconds
+    pub flush_interval_secs: u64,
+    /// Connection and TLS handshake timeout in seconds
+    pub connect_timeout_secs: u64,
+    /// Directory for events spooled while the collector is down
+    pub spool_dir: PathBuf,
+    /// Spool size limit in bytes; the oldest events are dropped beyond it
+    pub max_spool_bytes: u64,
+    /// Syslog facility (0-23)
+    pub syslog_facility: u8,
+    /// Syslog APP-NAME
+    pub syslog_app_name: String,
+    /// Syslog HOSTNAME (NILVALUE if unset)
+    pub hostname: Option<String>,
+}
+
+impl Default for SiemConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "localhost".to_string(),
+            port: 6514, // syslog over TLS
+            protocol: SiemProtocol::Tls,
+            tls_ca_file: None,
+            tls_server_name: None,
+            format: SiemFormat::Cef,
+            field_mapping: FieldMapping::default(),
+            min_severity: EventSeverity::Info,
+            batch_size: 100,
+            flush_interval_secs: 5,
+            connect_timeout_secs: 10,
+            spool_dir: PathBuf::from("data/siem-spool"),
+            max_spool_bytes: 64 * 1024 * 1024,
+            syslog_facility: 13, // log audit
+            syslog_app_name: "accuscene".to_string(),
+            hostname: None,
+        }
+    }
+}
+
 impl SecurityConfig {
     /// Load configuration from environment variables and defaults
     pub fn from_env() -> Self {
@@ -443,6 +600,27 @@ impl SecurityConfig {
             ));
         }
 
+        // Validate SIEM export
+        if self.siem.enabled {
+            if self.siem.host.is_empty() || self.siem.port == 0 {
+                return Err(crate::error::SecurityError::ConfigurationError(
+                    "SIEM collector host and port are required".to_string(),
+                ));
+            }
+
+            if self.siem.syslog_facility > 23 {
+                return Err(crate::error::SecurityError::ConfigurationError(
+                    "Syslog facility must be 0-23".to_string(),
+                ));
+            }
+
+            if self.siem.batch_size == 0 {
+                return Err(crate::error::SecurityError::ConfigurationError(
+                    "SIEM batch size must be at least 1".to_string(),
+                ));
+            }
+        }
+
         Ok(())
     }
 
@@ -486,4 +664,14 @@ mod tests {
         config.auth.session.absolute_timeout_secs = 5000;
         assert!(config.validate().is_err());
     }
+
+    #[test]
+    fn test_invalid_siem_configuration() {
+        let mut config = SecurityConfig::default();
+        config.siem.enabled = true;
+        assert!(config.validate().is_ok());
+
+        config.siem.syslog_facility = 24;
+        assert!(config.validate().is_err());
+    }
 }
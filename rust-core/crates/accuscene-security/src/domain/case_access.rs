@@ -37,6 +37,7 @@ mod tests {
             session_id: None,
             roles: vec!["admin".to_string()],
             permissions: vec![],
+            org_id: None,
             mfa_verified: true,
             session_metadata: None,
         };
@@ -51,6 +52,7 @@ mod tests {
             session_id: None,
             roles: vec![],
             permissions: vec![],
+            org_id: None,
             mfa_verified: true,
             session_metadata: None,
         };
@@ -3,6 +3,7 @@
 pub mod case_access;
 pub mod evidence_security;
 pub mod report_security;
+pub mod sensitive_fields;
 
 pub use case_access::can_access_case;
 pub use evidence_security::ChainOfCustodyEntry;
@@ -36,6 +36,7 @@ mod tests {
             session_id: None,
             roles: vec![],
             permissions: vec!["reports:read".to_string()],
+            org_id: None,
             mfa_verified: true,
             session_metadata: None,
         };
@@ -0,0 +1,146 @@
+//! Sensitive fields of cases and evidence
+//!
+//! Marks the case and evidence fields that
+//! [`FieldEncryptor`](crate::encryption::FieldEncryptor) encrypts at rest:
+//! vehicle identifiers, the names of clients and witnesses, and medical
+//! report contents.
+
+use crate::encryption::field::{visit_optional, FieldClass, SensitiveField, SensitiveRecord};
+use crate::error::Result;
+use accuscene_core::types::{Case, Evidence, EvidenceType};
+
+/// Custom evidence field holding a VIN
+pub const VIN_CUSTOM_FIELD: &str = "vin";
+
+impl SensitiveRecord for Case {
+    fn record_id(&self) -> &str {
+        &self.id
+    }
+
+    fn visit_sensitive(
+        &mut self,
+        visit: &mut dyn FnMut(SensitiveField<'_>) -> Result<()>,
+    ) -> Result<()> {
+        visit_optional(
+            visit,
+            "metadata.client",
+            FieldClass::PersonalName,
+            &mut self.metadata.client,
+        )?;
+
+        for (i, vehicle) in self.scene.vehicles.iter_mut().enumerate() {
+            let metadata = &mut vehicle.metadata;
+            visit_optional(
+                visit,
+                format!("scene.vehicles[{}].metadata.vin", i),
+                FieldClass::VehicleIdentifier,
+                &mut metadata.vin,
+            )?;
+            visit_optional(
+                visit,
+                format!("scene.vehicles[{}].metadata.license_plate", i),
+                FieldClass::VehicleIdentifier,
+                &mut metadata.license_plate,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SensitiveRecord for Evidence {
+    fn record_id(&self) -> &str {
+        &self.id
+    }
+
+    fn visit_sensitive(
+        &mut self,
+        visit: &mut dyn FnMut(SensitiveField<'_>) -> Result<()>,
+    ) -> Result<()> {
+        match self.evidence_type {
+            EvidenceType::MedicalReport => {
+                visit_optional(visit, "description", FieldClass::MedicalNote, &mut self.description)?;
+                visit_optional(visit, "notes", FieldClass::MedicalNote, &mut self.notes)?;
+            }
+            EvidenceType::WitnessStatement => {
+                // The source of a statement is the witness
+                visit_optional(
+                    visit,
+                    "metadata.source",
+                    FieldClass::PersonalName,
+                    &mut self.metadata.source,
+                )?;
+            }
+            _ => {}
+        }
+
+        if let Some(vin) = self.metadata.custom_fields.get_mut(VIN_CUSTOM_FIELD) {
+            visit(SensitiveField {
+                path: format!("metadata.custom_fields.{}", VIN_CUSTOM_FIELD),
+                class: FieldClass::VehicleIdentifier,
+                value: vin,
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::AuthContext;
+    use crate::encryption::field::REDACTED;
+    use crate::encryption::FieldEncryptor;
+    use accuscene_core::types::{Vehicle, VehicleCategory};
+
+    fn investigator(permissions: &[&str]) -> AuthContext {
+        AuthContext {
+            user_id: "investigator".to_string(),
+            session_id: None,
+            roles: vec!["investigator".to_string()],
+            permissions: permissions.iter().map(|p| p.to_string()).collect(),
+            org_id: Some("acme".to_string()),
+            mfa_verified: true,
+            session_metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_case_fields_encrypted() {
+        let encryptor = FieldEncryptor::new(&[9u8; 32]);
+        let mut case = Case::new("Highway 9 collision".to_string());
+        case.metadata.client = Some("John Smith".to_string());
+        let mut vehicle = Vehicle::new(VehicleCategory::Car);
+        vehicle.metadata.vin = Some("1HGCM82633A004352".to_string());
+        case.scene.vehicles.push(vehicle);
+
+        assert_eq!(encryptor.seal("acme", &mut case).unwrap(), 2);
+        let stored = serde_json::to_string(&case).unwrap();
+        assert!(!stored.contains("John Smith"));
+        assert!(!stored.contains("1HGCM82633A004352"));
+
+        encryptor
+            .open(&investigator(&["pii:read"]), "acme", &mut case)
+            .unwrap();
+        assert_eq!(case.metadata.client.as_deref(), Some("John Smith"));
+        assert_eq!(
+            case.scene.vehicles[0].metadata.vin.as_deref(),
+            Some("1HGCM82633A004352")
+        );
+    }
+
+    #[test]
+    fn test_medical_report_needs_medical_permission() {
+        let encryptor = FieldEncryptor::new(&[9u8; 32]);
+        let mut evidence = Evidence::new("ER admission".to_string(), EvidenceType::MedicalReport);
+        evidence.notes = Some("Whiplash, concussion".to_string());
+        encryptor.seal("acme", &mut evidence).unwrap();
+
+        let redacted = encryptor
+            .open(&investigator(&["pii:read"]), "acme", &mut evidence)
+            .unwrap();
+        assert_eq!(redacted, vec!["notes".to_string()]);
+        assert_eq!(evidence.notes.as_deref(), Some(REDACTED));
+    }
+}
@@ -0,0 +1,328 @@
+//! Field-level encryption of sensitive record fields
+//!
+//! Records mark their sensitive fields through [`SensitiveRecord`]. Before a
+//! record is written, [`FieldEncryptor::seal`] replaces each sensitive value
+//! with an envelope-encrypted token; when a record is read,
+//! [`FieldEncryptor::open`] decrypts the fields the reader is cleared for and
+//! masks the rest.
+//!
+//! Every organization has its own key encryption key, derived from the master
+//! key with HKDF, so one tenant's ciphertext cannot be opened under another's
+//! key. The organization, record ID and field path are bound to each value as
+//! associated data, so a token copied to another record or field fails to
+//! decrypt.
+
+use super::key_management::{EncryptionKey, KeyPurpose, KeyStatus};
+use crate::auth::AuthContext;
+use crate::error::{Result, SecurityError};
+use accuscene_crypto::envelope::{EnvelopeEncrypted, EnvelopeEncryptor};
+use accuscene_crypto::kdf::Hkdf;
+use accuscene_crypto::symmetric::SymmetricKey;
+use serde::{Deserialize, Serialize};
+
+/// Prefix of encrypted field values
+pub const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+/// Mask given to fields the reader may not see
+pub const REDACTED: &str = accuscene_core::types::party::REDACTED;
+
+const KEY_SALT: &[u8] = b"accuscene-field-encryption";
+
+/// Kind of sensitive data held by a field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldClass {
+    /// VINs and licence plates
+    VehicleIdentifier,
+    /// Names of people involved in a case
+    PersonalName,
+    /// Medical findings and notes
+    MedicalNote,
+}
+
+impl FieldClass {
+    /// Permission needed to read the plaintext
+    pub fn permission(&self) -> &'static str {
+        match self {
+            FieldClass::VehicleIdentifier | FieldClass::PersonalName => "pii:read",
+            FieldClass::MedicalNote => "medical:read",
+        }
+    }
+}
+
+/// A sensitive field of a record
+pub struct SensitiveField<'a> {
+    /// Path of the field, e.g. `scene.vehicles[0].metadata.vin`
+    pub path: String,
+    /// Kind of data in the field
+    pub class: FieldClass,
+    /// The stored value
+    pub value: &'a mut String,
+}
+
+/// Record with fields that are encrypted at rest
+pub trait SensitiveRecord {
+    /// ID bound into each field's ciphertext
+    fn record_id(&self) -> &str;
+
+    /// Call `visit` on each sensitive field that holds a value
+    fn visit_sensitive(
+        &mut self,
+        visit: &mut dyn FnMut(SensitiveField<'_>) -> Result<()>,
+    ) -> Result<()>;
+}
+
+/// Encrypts and decrypts the sensitive fields of records
+pub struct FieldEncryptor {
+    master_key: SymmetricKey,
+}
+
+impl FieldEncryptor {
+    /// Create from a 256-bit master key
+    pub fn new(master_key: &[u8; 32]) -> Self {
+        Self {
+            master_key: SymmetricKey::from_array(*master_key),
+        }
+    }
+
+    /// Create from an active data encryption key of the key management service
+    pub fn from_key(key: &EncryptionKey) -> Result<Self> {
+        if key.purpose != KeyPurpose::DataEncryption || key.status != KeyStatus::Active {
+            return Err(SecurityError::KeyNotFound(key.id.clone()));
+        }
+        let master_key = SymmetricKey::from_bytes(&key.key_bytes)
+            .map_err(|e| SecurityError::EncryptionFailed(e.to_string()))?;
+        Ok(Self { master_key })
+    }
+
+    /// Check whether a stored value is encrypted
+    pub fn is_encrypted(value: &str) -> bool {
+        value.starts_with(ENCRYPTED_PREFIX)
+    }
+
+    /// Encrypt the plaintext sensitive fields of a record in place
+    ///
+    /// Returns the number of fields encrypted. Fails on a redacted value,
+    /// which would otherwise overwrite the stored data with the mask.
+    pub fn seal<R: SensitiveRecord>(&self, org_id: &str, record: &mut R) -> Result<usize> {
+        let encryptor = self.org_encryptor(org_id)?;
+        let record_id = record.record_id().to_string();
+        let mut sealed = 0;
+
+        record.visit_sensitive(&mut |field| {
+            if Self::is_encrypted(field.value) {
+                return Ok(());
+            }
+            if field.value == REDACTED {
+                return Err(SecurityError::EncryptionFailed(format!(
+                    "{} is redacted",
+                    field.path
+                )));
+            }
+
+            let aad = associated_data(org_id, &record_id, &field.path);
+            let token = encryptor
+                .encrypt_with_metadata(field.value.as_bytes(), &aad)
+                .and_then(|envelope| envelope.to_base64())
+                .map_err(|e| SecurityError::EncryptionFailed(e.to_string()))?;
+            *field.value = format!("{}{}", ENCRYPTED_PREFIX, token);
+            sealed += 1;
+            Ok(())
+        })?;
+
+        Ok(sealed)
+    }
+
+    /// Decrypt the sensitive fields of a record for a reader
+    ///
+    /// Fields the reader lacks the permission for are replaced with
+    /// [`REDACTED`]; their paths are returned. Readers outside the
+    /// organization are refused unless they are administrators.
+    pub fn open<R: SensitiveRecord>(
+        &self,
+        context: &AuthContext,
+        org_id: &str,
+        record: &mut R,
+    ) -> Result<Vec<String>> {
+        if !context.is_admin() && !context.acts_for(org_id) {
+            return Err(SecurityError::TenantAccessDenied {
+                org_id: org_id.to_string(),
+            });
+        }
+
+        let encryptor = self.org_encryptor(org_id)?;
+        let record_id = record.record_id().to_string();
+        let mut redacted = Vec::new();
+
+        record.visit_sensitive(&mut |field| {
+            if !context.is_admin() && !context.has_permission(field.class.permission()) {
+                *field.value = REDACTED.to_string();
+                redacted.push(field.path);
+                return Ok(());
+            }
+
+            // Values written before encryption was enabled are left as they are
+            let Some(token) = field.value.strip_prefix(ENCRYPTED_PREFIX) else {
+                return Ok(());
+            };
+            let aad = associated_data(org_id, &record_id, &field.path);
+            let plaintext = EnvelopeEncrypted::from_base64(token)
+                .and_then(|envelope| encryptor.decrypt_with_metadata(&envelope, &aad))
+                .map_err(|e| SecurityError::DecryptionFailed(format!("{}: {}", field.path, e)))?;
+            *field.value = String::from_utf8(plaintext.as_bytes().to_vec())
+                .map_err(|e| SecurityError::DecryptionFailed(e.to_string()))?;
+            Ok(())
+        })?;
+
+        Ok(redacted)
+    }
+
+    /// Key encryption key of an organization
+    fn org_encryptor(&self, org_id: &str) -> Result<EnvelopeEncryptor> {
+        let info = format!("org:{}", org_id).into_bytes();
+        let kek = Hkdf::new(KEY_SALT.to_vec(), info)
+            .derive_key(self.master_key.as_bytes())
+            .map_err(|e| SecurityError::EncryptionFailed(e.to_string()))?;
+        Ok(EnvelopeEncryptor::new(kek))
+    }
+}
+
+/// Associated data binding a value to its organization, record and field
+fn associated_data(org_id: &str, record_id: &str, path: &str) -> Vec<u8> {
+    let mut aad = Vec::new();
+    for part in [org_id, record_id, path] {
+        aad.extend_from_slice(&(part.len() as u32).to_be_bytes());
+        aad.extend_from_slice(part.as_bytes());
+    }
+    aad
+}
+
+/// Add a field to a visit when it holds a value
+pub fn visit_optional(
+    visit: &mut dyn FnMut(SensitiveField<'_>) -> Result<()>,
+    path: impl Into<String>,
+    class: FieldClass,
+    value: &mut Option<String>,
+) -> Result<()> {
+    match value {
+        Some(value) => visit(SensitiveField {
+            path: path.into(),
+            class,
+            value,
+        }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Note {
+        id: String,
+        author: Option<String>,
+        body: String,
+    }
+
+    impl SensitiveRecord for Note {
+        fn record_id(&self) -> &str {
+            &self.id
+        }
+
+        fn visit_sensitive(
+            &mut self,
+            visit: &mut dyn FnMut(SensitiveField<'_>) -> Result<()>,
+        ) -> Result<()> {
+            visit_optional(visit, "author", FieldClass::PersonalName, &mut self.author)?;
+            visit(SensitiveField {
+                path: "body".to_string(),
+                class: FieldClass::MedicalNote,
+                value: &mut self.body,
+            })
+        }
+    }
+
+    fn note() -> Note {
+        Note {
+            id: "note-1".to_string(),
+            author: Some("Jane Doe".to_string()),
+            body: "Fractured left tibia".to_string(),
+        }
+    }
+
+    fn reader(org_id: &str, permissions: &[&str]) -> AuthContext {
+        AuthContext {
+            user_id: "reader".to_string(),
+            session_id: None,
+            roles: vec![],
+            permissions: permissions.iter().map(|p| p.to_string()).collect(),
+            org_id: Some(org_id.to_string()),
+            mfa_verified: true,
+            session_metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_seal_and_open() {
+        let encryptor = FieldEncryptor::new(&[7u8; 32]);
+        let mut record = note();
+
+        assert_eq!(encryptor.seal("acme", &mut record).unwrap(), 2);
+        assert!(FieldEncryptor::is_encrypted(&record.body));
+        assert!(!record.body.contains("tibia"));
+        // Sealing again leaves encrypted values alone
+        assert_eq!(encryptor.seal("acme", &mut record).unwrap(), 0);
+
+        let redacted = encryptor
+            .open(
+                &reader("acme", &["pii:read", "medical:read"]),
+                "acme",
+                &mut record,
+            )
+            .unwrap();
+        assert!(redacted.is_empty());
+        assert_eq!(record.author.as_deref(), Some("Jane Doe"));
+        assert_eq!(record.body, "Fractured left tibia");
+    }
+
+    #[test]
+    fn test_open_redacts_without_permission() {
+        let encryptor = FieldEncryptor::new(&[7u8; 32]);
+        let mut record = note();
+        encryptor.seal("acme", &mut record).unwrap();
+
+        let redacted = encryptor.open(&reader("acme", &["pii:read"]), "acme", &mut record).unwrap();
+        assert_eq!(redacted, vec!["body".to_string()]);
+        assert_eq!(record.author.as_deref(), Some("Jane Doe"));
+        assert_eq!(record.body, REDACTED);
+
+        // A redacted record must not be written back
+        assert!(encryptor.seal("acme", &mut record).is_err());
+    }
+
+    #[test]
+    fn test_keys_scoped_to_organization_and_record() {
+        let encryptor = FieldEncryptor::new(&[7u8; 32]);
+        let admin = AuthContext {
+            roles: vec!["admin".to_string()],
+            ..reader("acme", &[])
+        };
+
+        let mut record = note();
+        encryptor.seal("acme", &mut record).unwrap();
+
+        assert!(matches!(
+            encryptor.open(&reader("globex", &["pii:read"]), "acme", &mut note()),
+            Err(SecurityError::TenantAccessDenied { .. })
+        ));
+        // Ciphertext does not open under another organization's key
+        assert!(encryptor.open(&admin, "globex", &mut record).is_err());
+
+        // Nor when moved to another record
+        let mut moved = note();
+        moved.id = "note-2".to_string();
+        moved.author = None;
+        moved.body = record.body.clone();
+        assert!(encryptor.open(&admin, "acme", &mut moved).is_err());
+    }
+}
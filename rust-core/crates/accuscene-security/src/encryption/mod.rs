@@ -1,9 +1,11 @@
 //! Encryption services
 
 pub mod at_rest;
+pub mod field;
 pub mod in_transit;
 pub mod key_management;
 
 pub use at_rest::AtRestEncryption;
+pub use field::{FieldClass, FieldEncryptor, SensitiveField, SensitiveRecord};
 pub use in_transit::{TlsConfig, TlsVersion};
 pub use key_management::{EncryptionKey, KeyManagementService, KeyPurpose, KeyStatus};
@@ -23,12 +23,18 @@ pub enum SecurityError {
     #[error("Invalid MFA token")]
     InvalidMfaToken,
 
+    #[error("WebAuthn verification failed: {0}")]
+    WebAuthnFailed(String),
+
     #[error("Session expired")]
     SessionExpired,
 
     #[error("Session not found")]
     SessionNotFound,
 
+    #[error("Session store error: {0}")]
+    SessionStoreFailed(String),
+
     #[error("Invalid token: {0}")]
     InvalidToken(String),
 
@@ -42,6 +48,13 @@ pub enum SecurityError {
     #[error("Policy violation: {0}")]
     PolicyViolation(String),
 
+    #[error("Policy parse error at line {line}, column {column}: {message}")]
+    PolicyParseError {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+
     #[error("Role not found: {0}")]
     RoleNotFound(String),
 
@@ -55,6 +68,9 @@ pub enum SecurityError {
     #[error("Audit query failed: {0}")]
     AuditQueryFailed(String),
 
+    #[error("SIEM export failed: {0}")]
+    SiemExportFailed(String),
+
     // Encryption errors
     #[error("Encryption failed: {0}")]
     EncryptionFailed(String),
@@ -95,6 +111,9 @@ pub enum SecurityError {
     #[error("Brute force attack detected from {source}")]
     BruteForceDetected { source: String },
 
+    #[error("Login temporarily locked: retry after {retry_after_secs} seconds")]
+    LoginLocked { retry_after_secs: u64 },
+
     #[error("Anomaly detected: {0}")]
     AnomalyDetected(String),
 
@@ -108,6 +127,12 @@ pub enum SecurityError {
     #[error("HIPAA compliance violation: {0}")]
     HipaaViolation(String),
 
+    #[error("Data subject request not found: {0}")]
+    DsarNotFound(String),
+
+    #[error("Invalid data subject request: {0}")]
+    DsarInvalid(String),
+
     // Domain-specific errors
     #[error("Case access denied: {case_id}")]
     CaseAccessDenied { case_id: String },
@@ -118,6 +143,25 @@ pub enum SecurityError {
     #[error("Report access denied: {report_id}")]
     ReportAccessDenied { report_id: String },
 
+    #[error("Cross-tenant access denied: {org_id}")]
+    TenantAccessDenied { org_id: String },
+
+    // Provisioning errors
+    #[error("Invalid SCIM filter: {0}")]
+    ScimInvalidFilter(String),
+
+    #[error("Invalid SCIM path: {0}")]
+    ScimInvalidPath(String),
+
+    #[error("Invalid SCIM value: {0}")]
+    ScimInvalidValue(String),
+
+    #[error("SCIM resource already exists: {0}")]
+    ScimConflict(String),
+
+    #[error("SCIM resource not found: {0}")]
+    ScimResourceNotFound(String),
+
     // Configuration errors
     #[error("Configuration error: {0}")]
     ConfigurationError(String),
@@ -145,6 +189,7 @@ impl SecurityError {
                 | SecurityError::InvalidCredentials
                 | SecurityError::MfaRequired
                 | SecurityError::InvalidMfaToken
+                | SecurityError::WebAuthnFailed(_)
                 | SecurityError::SessionExpired
                 | SecurityError::SessionNotFound
                 | SecurityError::InvalidToken(_)
@@ -158,6 +203,7 @@ impl SecurityError {
             SecurityError::AccessDenied(_)
                 | SecurityError::PermissionDenied(_)
                 | SecurityError::PolicyViolation(_)
+                | SecurityError::TenantAccessDenied { .. }
         )
     }
 
@@ -167,6 +213,7 @@ impl SecurityError {
             self,
             SecurityError::RateLimitExceeded { .. }
                 | SecurityError::BruteForceDetected { .. }
+                | SecurityError::LoginLocked { .. }
                 | SecurityError::AnomalyDetected(_)
         )
     }
@@ -192,9 +239,11 @@ impl SecurityError {
 
             SecurityError::AnomalyDetected(_)
             | SecurityError::PolicyViolation(_)
-            | SecurityError::AccessDenied(_) => Severity::High,
+            | SecurityError::AccessDenied(_)
+            | SecurityError::TenantAccessDenied { .. } => Severity::High,
 
             SecurityError::RateLimitExceeded { .. }
+            | SecurityError::LoginLocked { .. }
             | SecurityError::ValidationFailed(_)
             | SecurityError::PermissionDenied(_) => Severity::Medium,
 
@@ -5,14 +5,21 @@
 //!
 //! # Features
 //!
-//! - **Authentication**: Password hashing (Argon2id), MFA (TOTP, WebAuthn), SSO (SAML, OIDC), sessions, JWT tokens
-//! - **Authorization**: RBAC, ABAC, policy engine
-//! - **Audit**: Structured logging, tamper-proof trail, querying
-//! - **Compliance**: SOC2, GDPR, HIPAA controls
-//! - **Encryption**: AES-256-GCM at rest, TLS in transit, key management
+//! - **Authentication**: Password hashing (Argon2id), MFA (TOTP, WebAuthn), SSO (SAML, OIDC), sessions, JWT tokens,
+//!   scoped API keys with rotation and per-key rate limits
+//! - **Authorization**: RBAC, ABAC, policy engine, text policies with hot reload and explain mode
+//! - **Audit**: Structured logging, hash-chained trail with signed Merkle checkpoints
+//!   and external anchoring, querying
+//! - **Compliance**: SOC2, GDPR, HIPAA controls; GDPR access, portability and erasure
+//!   requests with legal holds
+//! - **Encryption**: AES-256-GCM at rest, TLS in transit, key management,
+//!   per-organization field encryption of sensitive case and evidence data
 //! - **Secrets**: Secure vault, rotation
 //! - **Validation**: Input sanitization and validation
-//! - **Threat Detection**: Rate limiting, brute force detection, anomaly detection
+//! - **Threat Detection**: Rate limiting, brute-force lockouts, adaptive login scoring, anomaly detection
+//! - **SIEM Export**: CEF, LEEF and RFC 5424 syslog over TCP/TLS with batching and spooling
+//! - **Provisioning**: SCIM 2.0 users and groups with filtering and audited changes
+//! - **Tenancy**: Organization-scoped contexts and roles, audited cross-tenant access
 //! - **Domain Security**: Case access, evidence chain of custody, report security
 //!
 //! # Example
@@ -21,8 +28,9 @@
 //! use accuscene_security::{SecurityConfig, SecurityService};
 //!
 //! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-//! // Create security service
-//! let config = SecurityConfig::default();
+//! // Create security service; the audit signing key is generated on first start
+//! let mut config = SecurityConfig::default();
+//! config.audit.signing_key_path = Some("/var/lib/accuscene/audit.key".into());
 //! let service = SecurityService::new(config).await?;
 //!
 //! // Authenticate user
@@ -40,27 +48,36 @@ pub mod config;
 pub mod domain;
 pub mod encryption;
 pub mod error;
+pub mod scim;
 pub mod secrets;
+pub mod siem;
+pub mod tenancy;
 pub mod threat;
 pub mod validation;
 
 // Re-export main types
 pub use audit::AuditService;
-pub use auth::{AuthenticationService, AuthContext};
+pub use auth::{ApiKeyService, AuthenticationService, AuthContext};
 pub use authz::AuthorizationService;
 pub use compliance::ComplianceService;
 pub use config::SecurityConfig;
 pub use error::{Result, SecurityError, Severity};
+pub use scim::ScimService;
+pub use siem::SiemExporter;
+pub use tenancy::{TenantAccess, TenantGuard};
+pub use threat::ThreatDetector;
 
 use std::sync::Arc;
 
 /// Main security service coordinating all components
 pub struct SecurityService {
     config: SecurityConfig,
-    auth: Arc<AuthenticationService>,
+    auth: Arc<tokio::sync::RwLock<AuthenticationService>>,
     authz: Arc<tokio::sync::RwLock<AuthorizationService>>,
     audit: Arc<AuditService>,
     compliance: Arc<ComplianceService>,
+    api_keys: Arc<ApiKeyService>,
+    siem: Option<SiemExporter>,
 }
 
 impl SecurityService {
@@ -69,12 +86,6 @@ impl SecurityService {
         // Validate configuration
         config.validate()?;
 
-        // Initialize authentication service
-        let auth = Arc::new(AuthenticationService::new(
-            config.auth.clone(),
-            b"change-this-secret-key-32-bytes!",
-        ));
-
         // Initialize authorization service
         let authz = Arc::new(tokio::sync::RwLock::new(AuthorizationService::new(
             authz::PolicyEngineConfig {
@@ -85,20 +96,60 @@ impl SecurityService {
             },
         )));
 
-        // Initialize audit service
-        let audit = Arc::new(AuditService::new(audit::StorageConfig {
+        // Initialize audit service, sealing the trail with signed checkpoints
+        let mut audit = AuditService::new(audit::StorageConfig {
             max_memory_events: 10_000,
             retention_days: config.audit.retention_days,
             encrypt: config.audit.encrypt_logs,
-        }));
+        });
+        if config.audit.tamper_detection {
+            audit = audit.with_signer(load_audit_signer(&config.audit)?);
+        }
+        let audit = Arc::new(audit);
+        if config.audit.tamper_detection && config.audit.checkpoint_interval_secs > 0 {
+            Arc::clone(&audit).spawn_checkpoint_task(std::time::Duration::from_secs(
+                config.audit.checkpoint_interval_secs,
+            ));
+        }
 
         // Add audit handlers
         if config.audit.enabled {
             audit.add_handler(Box::new(audit::ConsoleHandler)).await;
         }
 
+        // Forward audit events to the SIEM
+        let siem = if config.siem.enabled {
+            let exporter = SiemExporter::new(&config.siem)?;
+            exporter.spawn_flush_task();
+            audit.add_handler(Box::new(exporter.clone())).await;
+            Some(exporter)
+        } else {
+            None
+        };
+
+        // API key secrets are hashed with a pepper derived from the JWT secret
+        let jwt_secret: &[u8] = b"change-this-secret-key-32-bytes!";
+        let pepper = accuscene_crypto::hash::blake3::blake3_derive_key(
+            "accuscene-security api key pepper v1",
+            jwt_secret,
+        );
+        let api_keys = Arc::new(
+            ApiKeyService::new(config.auth.api_keys.clone(), &pepper)
+                .with_audit(Arc::clone(&audit)),
+        );
+
+        // Initialize authentication service, guarding logins against brute force
+        let threat_detector = Arc::new(
+            ThreatDetector::new(config.threat.clone()).with_audit(Arc::clone(&audit)),
+        );
+        let auth = Arc::new(tokio::sync::RwLock::new(
+            AuthenticationService::new(config.auth.clone(), jwt_secret)
+                .with_threat_detector(threat_detector)
+                .with_api_keys(Arc::clone(&api_keys)),
+        ));
+
         // Initialize compliance service
-        let compliance = Arc::new(ComplianceService::new());
+        let compliance = Arc::new(ComplianceService::new().with_audit(Arc::clone(&audit)));
 
         Ok(Self {
             config,
@@ -106,11 +157,13 @@ impl SecurityService {
             authz,
             audit,
             compliance,
+            api_keys,
+            siem,
         })
     }
 
     /// Get authentication service
-    pub fn auth(&self) -> Arc<AuthenticationService> {
+    pub fn auth(&self) -> Arc<tokio::sync::RwLock<AuthenticationService>> {
         Arc::clone(&self.auth)
     }
 
@@ -124,11 +177,26 @@ impl SecurityService {
         Arc::clone(&self.audit)
     }
 
+    /// Get a tenant guard auditing into this service's trail
+    pub fn tenancy(&self) -> TenantGuard {
+        TenantGuard::new(Arc::clone(&self.audit))
+    }
+
     /// Get compliance service
     pub fn compliance(&self) -> Arc<ComplianceService> {
         Arc::clone(&self.compliance)
     }
 
+    /// Get API key service
+    pub fn api_keys(&self) -> Arc<ApiKeyService> {
+        Arc::clone(&self.api_keys)
+    }
+
+    /// Get SIEM exporter, if SIEM export is enabled
+    pub fn siem(&self) -> Option<&SiemExporter> {
+        self.siem.as_ref()
+    }
+
     /// Get configuration
     pub fn config(&self) -> &SecurityConfig {
         &self.config
@@ -168,6 +236,64 @@ impl SecurityService {
     }
 }
 
+/// Load the checkpoint signing key, generating and saving it on first start
+fn load_audit_signer(
+    config: &config::AuditConfig,
+) -> Result<accuscene_crypto::asymmetric::Ed25519Signer> {
+    use accuscene_crypto::asymmetric::{Ed25519KeyPair, Ed25519SecretKey, Ed25519Signer};
+
+    let path = config.signing_key_path.as_ref().ok_or_else(|| {
+        SecurityError::ConfigurationError(
+            "Audit tamper detection requires audit.signing_key_path".to_string(),
+        )
+    })?;
+
+    if !path.exists() {
+        let keypair = Ed25519KeyPair::generate()
+            .map_err(|e| SecurityError::ConfigurationError(format!("Audit signing key: {}", e)))?;
+        write_audit_key(path, &keypair.secret_key().to_base64())?;
+        tracing::info!("Generated audit signing key at {}", path.display());
+        return Ok(Ed25519Signer::new(keypair));
+    }
+
+    let encoded = std::fs::read_to_string(path).map_err(|e| {
+        SecurityError::ConfigurationError(format!(
+            "Cannot read audit signing key {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    let keypair = Ed25519SecretKey::from_base64(encoded.trim())
+        .and_then(Ed25519KeyPair::from_secret_key)
+        .map_err(|e| SecurityError::ConfigurationError(format!("Audit signing key: {}", e)))?;
+
+    Ok(Ed25519Signer::new(keypair))
+}
+
+/// Save a new signing key, readable only by its owner
+fn write_audit_key(path: &std::path::Path, encoded: &str) -> Result<()> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(encoded.as_bytes()))
+        .map_err(|e| {
+            SecurityError::ConfigurationError(format!(
+                "Cannot write audit signing key {}: {}",
+                path.display(),
+                e
+            ))
+        })
+}
+
 /// System integrity report
 #[derive(Debug, Clone)]
 pub struct IntegrityReport {
@@ -212,18 +338,26 @@ pub enum ComponentStatus {
 mod tests {
     use super::*;
 
+    fn test_config(dir: &tempfile::TempDir) -> SecurityConfig {
+        let mut config = SecurityConfig::default();
+        config.audit.signing_key_path = Some(dir.path().join("audit.key"));
+        config
+    }
+
     #[tokio::test]
     async fn test_security_service_creation() {
-        let config = SecurityConfig::default();
-        let service = SecurityService::new(config).await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let service = SecurityService::new(test_config(&dir)).await.unwrap();
 
-        assert!(service.auth().auth.password_service.validate_password("TestP@ssw0rd123!").is_ok());
+        let auth = service.auth();
+        let auth = auth.read().await;
+        assert!(auth.password_service().validate_password("TestP@ssw0rd123!").is_ok());
     }
 
     #[tokio::test]
     async fn test_health_check() {
-        let config = SecurityConfig::default();
-        let service = SecurityService::new(config).await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let service = SecurityService::new(test_config(&dir)).await.unwrap();
 
         let health = service.health_check().await;
         assert_eq!(health.status, HealthStatus::Healthy);
@@ -232,10 +366,30 @@ mod tests {
 
     #[tokio::test]
     async fn test_integrity_verification() {
-        let config = SecurityConfig::default();
-        let service = SecurityService::new(config).await.unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let service = SecurityService::new(test_config(&dir)).await.unwrap();
 
         let report = service.verify_integrity().await.unwrap();
         assert!(report.audit_trail_valid);
     }
+
+    #[test]
+    fn test_audit_signing_key_persisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = test_config(&dir).audit;
+
+        let generated = load_audit_signer(&config).unwrap();
+        let reloaded = load_audit_signer(&config).unwrap();
+        assert_eq!(generated.public_key(), reloaded.public_key());
+    }
+
+    #[tokio::test]
+    async fn test_tamper_detection_requires_signing_key() {
+        let result = SecurityService::new(SecurityConfig::default()).await;
+        assert!(matches!(result, Err(SecurityError::ConfigurationError(_))));
+
+        let mut config = SecurityConfig::default();
+        config.audit.tamper_detection = false;
+        assert!(SecurityService::new(config).await.is_ok());
+    }
 }
@@ -0,0 +1,106 @@
+//! Storage of provisioned users and groups
+//!
+//! [`ScimService`](super::ScimService) validates, filters and audits
+//! provisioning requests, and calls into a [`ScimDirectory`] to persist them.
+//! Applications implement the trait over their user repository;
+//! [`MemoryScimDirectory`] keeps everything in memory.
+
+use super::resource::{ScimGroup, ScimUser};
+use crate::error::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+/// User repository backing the SCIM endpoints
+///
+/// Resources passed in already carry their ID and metadata.
+#[async_trait]
+pub trait ScimDirectory: Send + Sync {
+    /// Persist a new user
+    async fn create_user(&self, user: &ScimUser) -> Result<()>;
+
+    /// Load a user by ID
+    async fn get_user(&self, id: &str) -> Result<Option<ScimUser>>;
+
+    /// Replace a stored user
+    async fn update_user(&self, user: &ScimUser) -> Result<()>;
+
+    /// Load all users, including inactive ones
+    async fn list_users(&self) -> Result<Vec<ScimUser>>;
+
+    /// Persist a new group
+    async fn create_group(&self, group: &ScimGroup) -> Result<()>;
+
+    /// Load a group by ID
+    async fn get_group(&self, id: &str) -> Result<Option<ScimGroup>>;
+
+    /// Replace a stored group
+    async fn update_group(&self, group: &ScimGroup) -> Result<()>;
+
+    /// Delete a group, returning whether it existed
+    async fn delete_group(&self, id: &str) -> Result<bool>;
+
+    /// Load all groups
+    async fn list_groups(&self) -> Result<Vec<ScimGroup>>;
+}
+
+/// In-memory directory; provisioned resources are lost on restart
+#[derive(Default)]
+pub struct MemoryScimDirectory {
+    users: RwLock<HashMap<String, ScimUser>>,
+    groups: RwLock<HashMap<String, ScimGroup>>,
+}
+
+impl MemoryScimDirectory {
+    /// Create an empty directory
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ScimDirectory for MemoryScimDirectory {
+    async fn create_user(&self, user: &ScimUser) -> Result<()> {
+        self.users
+            .write()
+            .await
+            .insert(user.id().to_string(), user.clone());
+        Ok(())
+    }
+
+    async fn get_user(&self, id: &str) -> Result<Option<ScimUser>> {
+        Ok(self.users.read().await.get(id).cloned())
+    }
+
+    async fn update_user(&self, user: &ScimUser) -> Result<()> {
+        self.create_user(user).await
+    }
+
+    async fn list_users(&self) -> Result<Vec<ScimUser>> {
+        Ok(self.users.read().await.values().cloned().collect())
+    }
+
+    async fn create_group(&self, group: &ScimGroup) -> Result<()> {
+        self.groups
+            .write()
+            .await
+            .insert(group.id().to_string(), group.clone());
+        Ok(())
+    }
+
+    async fn get_group(&self, id: &str) -> Result<Option<ScimGroup>> {
+        Ok(self.groups.read().await.get(id).cloned())
+    }
+
+    async fn update_group(&self, group: &ScimGroup) -> Result<()> {
+        self.create_group(group).await
+    }
+
+    async fn delete_group(&self, id: &str) -> Result<bool> {
+        Ok(self.groups.write().await.remove(id).is_some())
+    }
+
+    async fn list_groups(&self) -> Result<Vec<ScimGroup>> {
+        Ok(self.groups.read().await.values().cloned().collect())
+    }
+}
@@ -0,0 +1,466 @@
+//! SCIM filter expressions (RFC 7644 §3.4.2.2)
+//!
+//! Supports the comparison operators `eq`, `ne`, `co`, `sw`, `ew`, `gt`,
+//! `ge`, `lt` and `le`, presence (`pr`), `and`, `or`, `not (...)`, grouping
+//! and value paths such as `emails[type eq "work" and value co "@acme"]`.
+//!
+//! Filters are evaluated against the JSON form of a resource. Attribute names
+//! and string comparisons are case-insensitive; a comparison on a
+//! multi-valued attribute matches if any of its values match.
+
+use crate::error::{Result, SecurityError};
+use serde_json::Value;
+use std::cmp::Ordering;
+
+/// Attribute path such as `userName` or `name.givenName`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttrPath {
+    /// Attribute name
+    pub attr: String,
+    /// Sub-attribute name
+    pub sub_attr: Option<String>,
+}
+
+impl AttrPath {
+    /// Parse a dotted attribute path, dropping any schema URN prefix
+    pub fn parse(path: &str) -> Result<Self> {
+        let path = strip_schema(path);
+        let mut parts = path.splitn(2, '.');
+        let attr = parts.next().unwrap_or_default();
+        let sub_attr = parts.next();
+
+        let valid = |name: &str| {
+            name.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '$')
+        };
+        if !valid(attr) || sub_attr.is_some_and(|sub| !valid(sub)) {
+            return Err(SecurityError::ScimInvalidPath(format!(
+                "invalid attribute path '{}'",
+                path
+            )));
+        }
+
+        Ok(Self {
+            attr: attr.to_string(),
+            sub_attr: sub_attr.map(str::to_string),
+        })
+    }
+
+    /// Values at this path; multi-valued attributes yield one value per element
+    fn resolve<'a>(&self, resource: &'a Value) -> Vec<&'a Value> {
+        let Some(value) = get_attr(resource, &self.attr) else {
+            return Vec::new();
+        };
+
+        let elements: Vec<&Value> = match value {
+            Value::Array(items) => items.iter().collect(),
+            other => vec![other],
+        };
+
+        match &self.sub_attr {
+            Some(sub) => elements.into_iter().filter_map(|e| get_attr(e, sub)).collect(),
+            // A multi-valued attribute without a sub-attribute compares its `value`
+            None => elements
+                .into_iter()
+                .map(|e| get_attr(e, "value").unwrap_or(e))
+                .collect(),
+        }
+    }
+}
+
+/// Comparison operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterOp {
+    Eq,
+    Ne,
+    Co,
+    Sw,
+    Ew,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl FilterOp {
+    fn parse(word: &str) -> Option<Self> {
+        Some(match word.to_ascii_lowercase().as_str() {
+            "eq" => FilterOp::Eq,
+            "ne" => FilterOp::Ne,
+            "co" => FilterOp::Co,
+            "sw" => FilterOp::Sw,
+            "ew" => FilterOp::Ew,
+            "gt" => FilterOp::Gt,
+            "ge" => FilterOp::Ge,
+            "lt" => FilterOp::Lt,
+            "le" => FilterOp::Le,
+            _ => return None,
+        })
+    }
+}
+
+/// Parsed SCIM filter
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// `path op value`
+    Compare {
+        path: AttrPath,
+        op: FilterOp,
+        value: Value,
+    },
+    /// `path pr`
+    Present(AttrPath),
+    /// `left and right`
+    And(Box<Filter>, Box<Filter>),
+    /// `left or right`
+    Or(Box<Filter>, Box<Filter>),
+    /// `not (filter)`
+    Not(Box<Filter>),
+    /// `attr[filter]`, matching elements of a multi-valued attribute
+    ValuePath { attr: String, filter: Box<Filter> },
+}
+
+impl Filter {
+    /// Parse a filter expression
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            pos: 0,
+        };
+        let filter = parser.or()?;
+        match parser.peek() {
+            None => Ok(filter),
+            Some(token) => Err(parser.unexpected(token)),
+        }
+    }
+
+    /// Check if a resource, in JSON form, matches the filter
+    pub fn matches(&self, resource: &Value) -> bool {
+        match self {
+            Filter::Compare { path, op, value } => {
+                let actual = path.resolve(resource);
+                match op {
+                    // `ne` holds when no value equals the operand, including when absent
+                    FilterOp::Ne => !actual.iter().any(|a| compare(a, FilterOp::Eq, value)),
+                    FilterOp::Eq if value.is_null() => actual.iter().all(|a| a.is_null()),
+                    _ => actual.iter().any(|a| compare(a, *op, value)),
+                }
+            }
+            Filter::Present(path) => path.resolve(resource).into_iter().any(is_present),
+            Filter::And(left, right) => left.matches(resource) && right.matches(resource),
+            Filter::Or(left, right) => left.matches(resource) || right.matches(resource),
+            Filter::Not(inner) => !inner.matches(resource),
+            Filter::ValuePath { attr, filter } => match get_attr(resource, attr) {
+                Some(Value::Array(items)) => items.iter().any(|item| filter.matches(item)),
+                Some(value @ Value::Object(_)) => filter.matches(value),
+                _ => false,
+            },
+        }
+    }
+}
+
+/// Drop a schema URN prefix, e.g. `urn:ietf:params:scim:schemas:core:2.0:User:userName`
+pub(crate) fn strip_schema(path: &str) -> &str {
+    if path.to_ascii_lowercase().starts_with("urn:") {
+        // The attribute follows the last colon of the schema URN
+        path.rsplit_once(':').map_or(path, |(_, attr)| attr)
+    } else {
+        path
+    }
+}
+
+/// Look up an attribute of a JSON object, ignoring case
+pub(crate) fn get_attr<'a>(value: &'a Value, name: &str) -> Option<&'a Value> {
+    value
+        .as_object()?
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value)
+}
+
+fn is_present(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(fields) => !fields.is_empty(),
+        _ => true,
+    }
+}
+
+fn compare(actual: &Value, op: FilterOp, expected: &Value) -> bool {
+    match (actual, expected) {
+        (Value::String(a), Value::String(e)) => {
+            let (a, e) = (a.to_lowercase(), e.to_lowercase());
+            match op {
+                FilterOp::Co => a.contains(&e),
+                FilterOp::Sw => a.starts_with(&e),
+                FilterOp::Ew => a.ends_with(&e),
+                _ => ordered(a.cmp(&e), op),
+            }
+        }
+        (Value::Number(a), Value::Number(e)) => match (a.as_f64(), e.as_f64()) {
+            (Some(a), Some(e)) => a.partial_cmp(&e).is_some_and(|ord| ordered(ord, op)),
+            _ => false,
+        },
+        (Value::Bool(a), Value::Bool(e)) => matches!(op, FilterOp::Eq) && a == e,
+        _ => false,
+    }
+}
+
+fn ordered(ordering: Ordering, op: FilterOp) -> bool {
+    match op {
+        FilterOp::Eq => ordering == Ordering::Equal,
+        FilterOp::Ne => ordering != Ordering::Equal,
+        FilterOp::Gt => ordering == Ordering::Greater,
+        FilterOp::Ge => ordering != Ordering::Less,
+        FilterOp::Lt => ordering == Ordering::Less,
+        FilterOp::Le => ordering != Ordering::Greater,
+        FilterOp::Co | FilterOp::Sw | FilterOp::Ew => false,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Str(String),
+    Open,
+    Close,
+    OpenBracket,
+    CloseBracket,
+}
+
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some(&(pos, c)) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' | '[' | ']' => {
+                chars.next();
+                let token = match c {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    '[' => Token::OpenBracket,
+                    _ => Token::CloseBracket,
+                };
+                tokens.push((token, pos));
+            }
+            '"' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, 'n')) => value.push('\n'),
+                            Some((_, 't')) => value.push('\t'),
+                            Some((_, c)) => value.push(c),
+                            None => return Err(filter_error(pos, "unterminated string")),
+                        },
+                        Some((_, c)) => value.push(c),
+                        None => return Err(filter_error(pos, "unterminated string")),
+                    }
+                }
+                tokens.push((Token::Str(value), pos));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&(_, c)) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '(' | ')' | '[' | ']' | '"') {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push((Token::Word(word), pos));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn filter_error(pos: usize, message: impl std::fmt::Display) -> SecurityError {
+    SecurityError::ScimInvalidFilter(format!("{} at position {}", message, pos + 1))
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&(Token, usize)> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<(Token, usize)> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| SecurityError::ScimInvalidFilter("unexpected end of filter".to_string()))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn unexpected(&self, (token, pos): &(Token, usize)) -> SecurityError {
+        filter_error(*pos, format!("unexpected {:?}", token))
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some((Token::Word(word), _)) if word.eq_ignore_ascii_case(keyword) => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<()> {
+        let token = self.next()?;
+        if token.0 == expected {
+            Ok(())
+        } else {
+            Err(self.unexpected(&token))
+        }
+    }
+
+    fn or(&mut self) -> Result<Filter> {
+        let mut filter = self.and()?;
+        while self.keyword("or") {
+            filter = Filter::Or(Box::new(filter), Box::new(self.and()?));
+        }
+        Ok(filter)
+    }
+
+    fn and(&mut self) -> Result<Filter> {
+        let mut filter = self.unary()?;
+        while self.keyword("and") {
+            filter = Filter::And(Box::new(filter), Box::new(self.unary()?));
+        }
+        Ok(filter)
+    }
+
+    fn unary(&mut self) -> Result<Filter> {
+        if self.keyword("not") {
+            self.expect(Token::Open)?;
+            let inner = self.or()?;
+            self.expect(Token::Close)?;
+            return Ok(Filter::Not(Box::new(inner)));
+        }
+
+        match self.next()? {
+            (Token::Open, _) => {
+                let inner = self.or()?;
+                self.expect(Token::Close)?;
+                Ok(inner)
+            }
+            (Token::Word(path), pos) => self.attribute(&path, pos),
+            token => Err(self.unexpected(&token)),
+        }
+    }
+
+    fn attribute(&mut self, path: &str, pos: usize) -> Result<Filter> {
+        if let Some((Token::OpenBracket, _)) = self.peek() {
+            self.pos += 1;
+            let attr = AttrPath::parse(path).map_err(|e| filter_error(pos, e))?;
+            let filter = self.or()?;
+            self.expect(Token::CloseBracket)?;
+            return Ok(Filter::ValuePath {
+                attr: attr.attr,
+                filter: Box::new(filter),
+            });
+        }
+
+        let path = AttrPath::parse(path).map_err(|e| filter_error(pos, e))?;
+        let (op, op_pos) = match self.next()? {
+            (Token::Word(op), op_pos) => (op, op_pos),
+            token => return Err(self.unexpected(&token)),
+        };
+
+        if op.eq_ignore_ascii_case("pr") {
+            return Ok(Filter::Present(path));
+        }
+
+        let op = FilterOp::parse(&op)
+            .ok_or_else(|| filter_error(op_pos, format!("unknown operator '{}'", op)))?;
+        let value = match self.next()? {
+            (Token::Str(s), _) => Value::String(s),
+            (Token::Word(word), value_pos) => match word.as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                "null" => Value::Null,
+                number => serde_json::from_str::<serde_json::Number>(number)
+                    .map(Value::Number)
+                    .map_err(|_| filter_error(value_pos, format!("invalid value '{}'", word)))?,
+            },
+            token => return Err(self.unexpected(&token)),
+        };
+
+        Ok(Filter::Compare { path, op, value })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn user() -> Value {
+        json!({
+            "userName": "JDoe",
+            "name": { "givenName": "Jane", "familyName": "Doe" },
+            "active": true,
+            "emails": [
+                { "value": "jane@acme.test", "type": "work", "primary": true },
+                { "value": "jane@home.test", "type": "home" }
+            ],
+            "meta": { "lastModified": "2024-05-01T10:00:00Z" }
+        })
+    }
+
+    fn matches(filter: &str) -> bool {
+        Filter::parse(filter).unwrap().matches(&user())
+    }
+
+    #[test]
+    fn test_filter_matching() {
+        assert!(matches(r#"userName eq "jdoe""#));
+        assert!(matches(r#"urn:ietf:params:scim:schemas:core:2.0:User:userName eq "JDOE""#));
+        assert!(matches(r#"name.familyName sw "d" and active eq true"#));
+        assert!(matches(r#"emails co "@home.test""#));
+        assert!(matches(r#"emails[type eq "work" and value ew "acme.test"]"#));
+        assert!(matches(r#"meta.lastModified gt "2024-01-01T00:00:00Z""#));
+        assert!(matches(r#"not (title pr) and (userName eq "x" or name.givenName pr)"#));
+        assert!(matches(r#"title ne "manager""#));
+
+        assert!(!matches(r#"emails[type eq "home" and primary eq true]"#));
+        assert!(!matches(r#"active eq false or userName co "smith""#));
+        assert!(!matches("title pr"));
+    }
+
+    #[test]
+    fn test_filter_errors() {
+        for filter in [
+            "",
+            "userName",
+            r#"userName xx "a""#,
+            r#"userName eq "a"#,
+            r#"(userName eq "a""#,
+            r#"userName eq "a" extra"#,
+            r#"emails[type eq "work""#,
+        ] {
+            assert!(
+                matches!(Filter::parse(filter), Err(SecurityError::ScimInvalidFilter(_))),
+                "{}",
+                filter
+            );
+        }
+    }
+}
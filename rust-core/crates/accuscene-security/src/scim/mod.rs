@@ -0,0 +1,601 @@
+//! SCIM 2.0 provisioning
+//!
+//! Lets enterprise identity providers create, update and deactivate users and
+//! manage groups over SCIM (RFC 7643, RFC 7644). [`ScimService`] implements
+//! the endpoint logic independently of the HTTP layer: it assigns IDs and
+//! metadata, enforces unique user and group names, applies PATCH requests,
+//! evaluates list filters and audits every change. Resources are persisted
+//! through a [`ScimDirectory`].
+//!
+//! `DELETE` on a user deactivates it rather than removing it, so the audit
+//! trail and case history keep pointing at a real account.
+
+pub mod directory;
+pub mod filter;
+pub mod patch;
+pub mod resource;
+
+pub use directory::{MemoryScimDirectory, ScimDirectory};
+pub use filter::{AttrPath, Filter, FilterOp};
+pub use resource::{
+    GroupRef, ListQuery, ListResponse, Member, Meta, MultiValue, Name, PatchOp, PatchOperation,
+    PatchRequest, ScimErrorResponse, ScimGroup, ScimUser, GROUP_SCHEMA, LIST_RESPONSE_SCHEMA,
+    USER_SCHEMA,
+};
+
+use crate::audit::{AuditEvent, AuditService, EventResult, EventType, ResourceInfo};
+use crate::error::{Result, SecurityError};
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Default maximum number of resources per list response
+pub const DEFAULT_MAX_RESULTS: usize = 100;
+
+/// SCIM endpoint logic over a user directory
+pub struct ScimService {
+    directory: Arc<dyn ScimDirectory>,
+    audit: Option<Arc<AuditService>>,
+    base_url: Option<String>,
+    max_results: usize,
+}
+
+impl ScimService {
+    /// Create a service over a directory
+    pub fn new(directory: Arc<dyn ScimDirectory>) -> Self {
+        Self {
+            directory,
+            audit: None,
+            base_url: None,
+            max_results: DEFAULT_MAX_RESULTS,
+        }
+    }
+
+    /// Audit provisioning operations
+    pub fn with_audit(mut self, audit: Arc<AuditService>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Base URL of the SCIM endpoints, used for `meta.location`
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into().trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Cap the number of resources per list response
+    pub fn with_max_results(mut self, max_results: usize) -> Self {
+        self.max_results = max_results;
+        self
+    }
+
+    /// Get the directory
+    pub fn directory(&self) -> &Arc<dyn ScimDirectory> {
+        &self.directory
+    }
+
+    /// Provision a new user on behalf of a provisioning client
+    pub async fn create_user(&self, client: &str, mut user: ScimUser) -> Result<ScimUser> {
+        self.validate_user(&user, None).await?;
+
+        let now = chrono::Utc::now();
+        user.id = Some(uuid::Uuid::new_v4().to_string());
+        user.schemas = vec![USER_SCHEMA.to_string()];
+        user.groups.clear();
+        user.meta = Some(Meta {
+            resource_type: "User".to_string(),
+            created: Some(now),
+            last_modified: Some(now),
+            location: None,
+        });
+
+        self.directory.create_user(&user).await?;
+        self.audit_user(client, EventType::ProvisioningUserCreated, "scim.user.create", &user)
+            .await?;
+        tracing::info!("SCIM client {} provisioned user {}", client, user.user_name);
+
+        let groups = self.directory.list_groups().await?;
+        Ok(self.present_user(user, &groups))
+    }
+
+    /// Get a user by ID
+    pub async fn get_user(&self, id: &str) -> Result<ScimUser> {
+        let user = self.load_user(id).await?;
+        let groups = self.directory.list_groups().await?;
+        Ok(self.present_user(user, &groups))
+    }
+
+    /// Replace a user (`PUT`)
+    pub async fn replace_user(&self, client: &str, id: &str, user: ScimUser) -> Result<ScimUser> {
+        let existing = self.load_user(id).await?;
+        self.store_user(client, existing, user).await
+    }
+
+    /// Modify a user (`PATCH`)
+    pub async fn patch_user(&self, client: &str, id: &str, patch: &PatchRequest) -> Result<ScimUser> {
+        let existing = self.load_user(id).await?;
+        let user = patched(&existing, patch)?;
+        self.store_user(client, existing, user).await
+    }
+
+    /// Deactivate a user (`DELETE`)
+    pub async fn deactivate_user(&self, client: &str, id: &str) -> Result<()> {
+        let existing = self.load_user(id).await?;
+        if existing.active {
+            let mut user = existing.clone();
+            user.active = false;
+            self.store_user(client, existing, user).await?;
+        }
+        Ok(())
+    }
+
+    /// List users matching a query
+    pub async fn list_users(&self, query: &ListQuery) -> Result<ListResponse<ScimUser>> {
+        let groups = self.directory.list_groups().await?;
+        let mut users = self.directory.list_users().await?;
+        users.sort_by(|a, b| created(&a.meta).cmp(&created(&b.meta)).then_with(|| a.id.cmp(&b.id)));
+
+        let users = users
+            .into_iter()
+            .map(|user| self.present_user(user, &groups))
+            .collect();
+        self.page(users, query)
+    }
+
+    /// Create a group
+    pub async fn create_group(&self, client: &str, mut group: ScimGroup) -> Result<ScimGroup> {
+        self.validate_group(&mut group, None).await?;
+
+        let now = chrono::Utc::now();
+        group.id = Some(uuid::Uuid::new_v4().to_string());
+        group.schemas = vec![GROUP_SCHEMA.to_string()];
+        group.meta = Some(Meta {
+            resource_type: "Group".to_string(),
+            created: Some(now),
+            last_modified: Some(now),
+            location: None,
+        });
+
+        self.directory.create_group(&group).await?;
+        let members: Vec<&str> = group.members.iter().map(|m| m.value.as_str()).collect();
+        self.audit_group(
+            client,
+            EventType::ProvisioningGroupCreated,
+            "scim.group.create",
+            &group,
+            &[("added", members.join(","))],
+        )
+        .await?;
+
+        Ok(self.present_group(group))
+    }
+
+    /// Get a group by ID
+    pub async fn get_group(&self, id: &str) -> Result<ScimGroup> {
+        self.load_group(id).await.map(|group| self.present_group(group))
+    }
+
+    /// Replace a group (`PUT`)
+    pub async fn replace_group(&self, client: &str, id: &str, group: ScimGroup) -> Result<ScimGroup> {
+        let existing = self.load_group(id).await?;
+        self.store_group(client, existing, group).await
+    }
+
+    /// Modify a group (`PATCH`), typically to add or remove members
+    pub async fn patch_group(&self, client: &str, id: &str, patch: &PatchRequest) -> Result<ScimGroup> {
+        let existing = self.load_group(id).await?;
+        let group = patched(&existing, patch)?;
+        self.store_group(client, existing, group).await
+    }
+
+    /// Delete a group
+    pub async fn delete_group(&self, client: &str, id: &str) -> Result<()> {
+        let group = self.load_group(id).await?;
+        self.directory.delete_group(id).await?;
+        self.audit_group(
+            client,
+            EventType::ProvisioningGroupDeleted,
+            "scim.group.delete",
+            &group,
+            &[],
+        )
+        .await
+    }
+
+    /// List groups matching a query
+    pub async fn list_groups(&self, query: &ListQuery) -> Result<ListResponse<ScimGroup>> {
+        let mut groups = self.directory.list_groups().await?;
+        groups.sort_by(|a, b| created(&a.meta).cmp(&created(&b.meta)).then_with(|| a.id.cmp(&b.id)));
+
+        let groups = groups
+            .into_iter()
+            .map(|group| self.present_group(group))
+            .collect();
+        self.page(groups, query)
+    }
+
+    async fn load_user(&self, id: &str) -> Result<ScimUser> {
+        self.directory
+            .get_user(id)
+            .await?
+            .ok_or_else(|| SecurityError::ScimResourceNotFound(format!("User {}", id)))
+    }
+
+    async fn load_group(&self, id: &str) -> Result<ScimGroup> {
+        self.directory
+            .get_group(id)
+            .await?
+            .ok_or_else(|| SecurityError::ScimResourceNotFound(format!("Group {}", id)))
+    }
+
+    async fn store_user(&self, client: &str, existing: ScimUser, mut user: ScimUser) -> Result<ScimUser> {
+        self.validate_user(&user, Some(existing.id())).await?;
+
+        // Read-only attributes are kept from the stored user
+        user.id = existing.id.clone();
+        user.schemas = vec![USER_SCHEMA.to_string()];
+        user.groups.clear();
+        user.meta = existing.meta.clone().map(|meta| Meta {
+            last_modified: Some(chrono::Utc::now()),
+            location: None,
+            ..meta
+        });
+
+        self.directory.update_user(&user).await?;
+
+        let (event_type, action) = match (existing.active, user.active) {
+            (true, false) => (EventType::ProvisioningUserDeactivated, "scim.user.deactivate"),
+            (false, true) => (EventType::ProvisioningUserReactivated, "scim.user.reactivate"),
+            _ => (EventType::ProvisioningUserUpdated, "scim.user.update"),
+        };
+        self.audit_user(client, event_type, action, &user).await?;
+
+        let groups = self.directory.list_groups().await?;
+        Ok(self.present_user(user, &groups))
+    }
+
+    async fn store_group(
+        &self,
+        client: &str,
+        existing: ScimGroup,
+        mut group: ScimGroup,
+    ) -> Result<ScimGroup> {
+        self.validate_group(&mut group, Some(existing.id())).await?;
+
+        group.id = existing.id.clone();
+        group.schemas = vec![GROUP_SCHEMA.to_string()];
+        group.meta = existing.meta.clone().map(|meta| Meta {
+            last_modified: Some(chrono::Utc::now()),
+            location: None,
+            ..meta
+        });
+
+        self.directory.update_group(&group).await?;
+
+        let added: Vec<&str> = group
+            .members
+            .iter()
+            .filter(|m| !existing.has_member(&m.value))
+            .map(|m| m.value.as_str())
+            .collect();
+        let removed: Vec<&str> = existing
+            .members
+            .iter()
+            .filter(|m| !group.has_member(&m.value))
+            .map(|m| m.value.as_str())
+            .collect();
+        self.audit_group(
+            client,
+            EventType::ProvisioningGroupUpdated,
+            "scim.group.update",
+            &group,
+            &[("added", added.join(",")), ("removed", removed.join(","))],
+        )
+        .await?;
+
+        Ok(self.present_group(group))
+    }
+
+    async fn validate_user(&self, user: &ScimUser, id: Option<&str>) -> Result<()> {
+        if user.user_name.trim().is_empty() {
+            return Err(SecurityError::ScimInvalidValue("userName is required".to_string()));
+        }
+
+        let taken = self.directory.list_users().await?.iter().any(|other| {
+            other.id.as_deref() != id && other.user_name.eq_ignore_ascii_case(&user.user_name)
+        });
+        if taken {
+            return Err(SecurityError::ScimConflict(format!(
+                "userName '{}' is already taken",
+                user.user_name
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Check the name is unique and every member exists; fills in member display names
+    async fn validate_group(&self, group: &mut ScimGroup, id: Option<&str>) -> Result<()> {
+        if group.display_name.trim().is_empty() {
+            return Err(SecurityError::ScimInvalidValue(
+                "displayName is required".to_string(),
+            ));
+        }
+
+        let taken = self.directory.list_groups().await?.iter().any(|other| {
+            other.id.as_deref() != id && other.display_name.eq_ignore_ascii_case(&group.display_name)
+        });
+        if taken {
+            return Err(SecurityError::ScimConflict(format!(
+                "displayName '{}' is already taken",
+                group.display_name
+            )));
+        }
+
+        let mut members: Vec<Member> = Vec::with_capacity(group.members.len());
+        for member in group.members.drain(..) {
+            if members.iter().any(|m| m.value == member.value) {
+                continue;
+            }
+            let user = self.directory.get_user(&member.value).await?.ok_or_else(|| {
+                SecurityError::ScimInvalidValue(format!("unknown member '{}'", member.value))
+            })?;
+            members.push(Member {
+                display: user.full_name().or(Some(user.user_name)),
+                reference: None,
+                value: member.value,
+            });
+        }
+        group.members = members;
+
+        Ok(())
+    }
+
+    fn present_user(&self, mut user: ScimUser, groups: &[ScimGroup]) -> ScimUser {
+        user.groups = groups
+            .iter()
+            .filter(|group| group.has_member(user.id()))
+            .map(|group| GroupRef {
+                value: group.id().to_string(),
+                display: Some(group.display_name.clone()),
+            })
+            .collect();
+        if let Some(meta) = &mut user.meta {
+            meta.location = self.location("Users", user.id.as_deref());
+        }
+        user
+    }
+
+    fn present_group(&self, mut group: ScimGroup) -> ScimGroup {
+        for member in &mut group.members {
+            member.reference = self.location("Users", Some(&member.value));
+        }
+        if let Some(meta) = &mut group.meta {
+            meta.location = self.location("Groups", group.id.as_deref());
+        }
+        group
+    }
+
+    fn location(&self, endpoint: &str, id: Option<&str>) -> Option<String> {
+        Some(format!("{}/{}/{}", self.base_url.as_ref()?, endpoint, id?))
+    }
+
+    fn page<T: Serialize>(&self, resources: Vec<T>, query: &ListQuery) -> Result<ListResponse<T>> {
+        let filter = query.filter.as_deref().map(Filter::parse).transpose()?;
+
+        let mut matched = Vec::new();
+        for resource in resources {
+            let keep = match &filter {
+                Some(filter) => filter.matches(&to_json(&resource)?),
+                None => true,
+            };
+            if keep {
+                matched.push(resource);
+            }
+        }
+
+        let start_index = query.start_index.unwrap_or(1).max(1);
+        let count = query.count.unwrap_or(self.max_results).min(self.max_results);
+        let total_results = matched.len();
+        let resources: Vec<T> = matched
+            .into_iter()
+            .skip(start_index - 1)
+            .take(count)
+            .collect();
+
+        Ok(ListResponse {
+            schemas: vec![LIST_RESPONSE_SCHEMA.to_string()],
+            total_results,
+            start_index,
+            items_per_page: resources.len(),
+            resources,
+        })
+    }
+
+    async fn audit_user(
+        &self,
+        client: &str,
+        event_type: EventType,
+        action: &str,
+        user: &ScimUser,
+    ) -> Result<()> {
+        let mut metadata = vec![("active", user.active.to_string())];
+        if let Some(external_id) = &user.external_id {
+            metadata.push(("external_id", external_id.clone()));
+        }
+        let resource = ResourceInfo::new("user", user.id()).with_name(&user.user_name);
+        self.audit(client, event_type, action, resource, &metadata).await
+    }
+
+    async fn audit_group(
+        &self,
+        client: &str,
+        event_type: EventType,
+        action: &str,
+        group: &ScimGroup,
+        metadata: &[(&str, String)],
+    ) -> Result<()> {
+        let resource = ResourceInfo::new("group", group.id()).with_name(&group.display_name);
+        self.audit(client, event_type, action, resource, metadata).await
+    }
+
+    async fn audit(
+        &self,
+        client: &str,
+        event_type: EventType,
+        action: &str,
+        resource: ResourceInfo,
+        metadata: &[(&str, String)],
+    ) -> Result<()> {
+        let Some(audit) = &self.audit else {
+            return Ok(());
+        };
+
+        let mut event = AuditEvent::new(event_type, action.to_string())
+            .with_user(client.to_string())
+            .with_resource(resource)
+            .with_result(EventResult::Success)
+            .add_metadata("channel".to_string(), "scim".to_string());
+        for (key, value) in metadata.iter().filter(|(_, value)| !value.is_empty()) {
+            event = event.add_metadata(key.to_string(), value.clone());
+        }
+
+        audit.audit(event).await
+    }
+}
+
+/// Apply a PATCH request to a copy of a resource
+fn patched<T>(resource: &T, patch: &PatchRequest) -> Result<T>
+where
+    T: Serialize + serde::de::DeserializeOwned,
+{
+    let mut json = to_json(resource)?;
+    patch::apply_patch(&mut json, &patch.operations)?;
+    serde_json::from_value(json).map_err(|e| SecurityError::ScimInvalidValue(e.to_string()))
+}
+
+fn to_json<T: Serialize>(resource: &T) -> Result<serde_json::Value> {
+    serde_json::to_value(resource).map_err(|e| SecurityError::Internal(e.to_string()))
+}
+
+fn created(meta: &Option<Meta>) -> Option<chrono::DateTime<chrono::Utc>> {
+    meta.as_ref().and_then(|meta| meta.created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::{AuditQuery, StorageConfig};
+
+    fn service() -> (ScimService, Arc<AuditService>) {
+        let audit = Arc::new(AuditService::new(StorageConfig::default()));
+        let service = ScimService::new(Arc::new(MemoryScimDirectory::new()))
+            .with_audit(audit.clone())
+            .with_base_url("https://accuscene.test/scim/v2/");
+        (service, audit)
+    }
+
+    #[tokio::test]
+    async fn test_user_lifecycle() {
+        let (scim, audit) = service();
+
+        let user = scim
+            .create_user(
+                "okta",
+                ScimUser::new("jdoe").with_external_id("00u1").with_email("jane@acme.test"),
+            )
+            .await
+            .unwrap();
+        let id = user.id().to_string();
+        assert_eq!(
+            user.meta.as_ref().unwrap().location.as_deref(),
+            Some(format!("https://accuscene.test/scim/v2/Users/{}", id).as_str())
+        );
+
+        let duplicate = scim.create_user("okta", ScimUser::new("JDOE")).await;
+        assert!(matches!(duplicate, Err(SecurityError::ScimConflict(_))));
+
+        let patch: PatchRequest = serde_json::from_value(serde_json::json!({
+            "schemas": [resource::PATCH_OP_SCHEMA],
+            "Operations": [
+                { "op": "Replace", "path": "name.familyName", "value": "Doe" },
+                { "op": "Replace", "path": "id", "value": "hijacked" }
+            ]
+        }))
+        .unwrap();
+        let user = scim.patch_user("okta", &id, &patch).await.unwrap();
+        assert_eq!(user.id(), id);
+        assert_eq!(user.name.unwrap().family_name.as_deref(), Some("Doe"));
+
+        scim.deactivate_user("okta", &id).await.unwrap();
+        assert!(!scim.get_user(&id).await.unwrap().active);
+        assert!(matches!(
+            scim.get_user("missing").await,
+            Err(SecurityError::ScimResourceNotFound(_))
+        ));
+
+        let events = audit.query(AuditQuery::new()).await.unwrap().events;
+        let types: Vec<EventType> = events.iter().map(|e| e.event_type).collect();
+        assert!(types.contains(&EventType::ProvisioningUserCreated));
+        assert!(types.contains(&EventType::ProvisioningUserUpdated));
+        assert!(types.contains(&EventType::ProvisioningUserDeactivated));
+        assert!(events.iter().all(|e| e.user_id.as_deref() == Some("okta")));
+    }
+
+    #[tokio::test]
+    async fn test_groups_and_filtering() {
+        let (scim, _) = service();
+
+        let jane = scim.create_user("okta", ScimUser::new("jdoe")).await.unwrap();
+        let john = scim
+            .create_user("okta", ScimUser::new("jsmith").with_display_name("John Smith"))
+            .await
+            .unwrap();
+
+        let group = scim
+            .create_group("okta", ScimGroup::new("Investigators").with_member(jane.id()))
+            .await
+            .unwrap();
+        let unknown = ScimGroup::new("Analysts").with_member("nobody");
+        assert!(matches!(
+            scim.create_group("okta", unknown).await,
+            Err(SecurityError::ScimInvalidValue(_))
+        ));
+
+        let patch = PatchRequest::new(vec![PatchOperation {
+            op: PatchOp::Add,
+            path: Some("members".to_string()),
+            value: Some(serde_json::json!([{ "value": john.id() }])),
+        }]);
+        let group = scim.patch_group("okta", group.id(), &patch).await.unwrap();
+        assert_eq!(group.members.len(), 2);
+        assert_eq!(group.members[1].display.as_deref(), Some("John Smith"));
+
+        let john = scim.get_user(john.id()).await.unwrap();
+        assert_eq!(john.groups[0].display.as_deref(), Some("Investigators"));
+
+        let found = scim
+            .list_users(&ListQuery::filtered(format!(r#"groups.value eq "{}" and userName sw "jd""#, group.id())))
+            .await
+            .unwrap();
+        assert_eq!(found.total_results, 1);
+        assert_eq!(found.resources[0].user_name, "jdoe");
+
+        let page = scim
+            .list_users(&ListQuery {
+                start_index: Some(2),
+                count: Some(1),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!((page.total_results, page.items_per_page), (2, 1));
+
+        assert!(matches!(
+            scim.list_groups(&ListQuery::filtered("displayName eq")).await,
+            Err(SecurityError::ScimInvalidFilter(_))
+        ));
+
+        scim.delete_group("okta", group.id()).await.unwrap();
+        assert!(scim.get_user(john.id()).await.unwrap().groups.is_empty());
+    }
+}
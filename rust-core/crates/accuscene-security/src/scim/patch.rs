@@ -0,0 +1,336 @@
+//! SCIM PATCH (RFC 7644 §3.5.2)
+//!
+//! Operations are applied to the JSON form of a resource. Paths may name an
+//! attribute (`active`), a sub-attribute (`name.givenName`) or elements of a
+//! multi-valued attribute (`emails[type eq "work"].value`). Operations
+//! without a path take an object of attributes, whose keys may themselves be
+//! paths.
+
+use super::filter::{get_attr, AttrPath, Filter};
+use super::resource::{PatchOp, PatchOperation};
+use crate::error::{Result, SecurityError};
+use serde_json::{Map, Value};
+
+/// Attribute names used when a PATCH adds an attribute the resource lacks
+const KNOWN_ATTRIBUTES: &[&str] = &[
+    "externalId",
+    "userName",
+    "name",
+    "displayName",
+    "emails",
+    "phoneNumbers",
+    "active",
+    "members",
+    "formatted",
+    "familyName",
+    "givenName",
+    "value",
+    "type",
+    "primary",
+    "display",
+];
+
+/// Target of a PATCH operation
+#[derive(Debug, Clone, PartialEq)]
+struct PatchPath {
+    attr: String,
+    filter: Option<Filter>,
+    sub_attr: Option<String>,
+}
+
+impl PatchPath {
+    fn parse(path: &str) -> Result<Self> {
+        let Some(open) = path.find('[') else {
+            let AttrPath { attr, sub_attr } = AttrPath::parse(path)?;
+            return Ok(Self {
+                attr,
+                filter: None,
+                sub_attr,
+            });
+        };
+
+        let invalid = || SecurityError::ScimInvalidPath(format!("invalid path '{}'", path));
+        let close = path.rfind(']').filter(|close| *close > open).ok_or_else(invalid)?;
+
+        let attr = AttrPath::parse(&path[..open])?;
+        if attr.sub_attr.is_some() {
+            return Err(invalid());
+        }
+        let filter = Filter::parse(&path[open + 1..close])
+            .map_err(|e| SecurityError::ScimInvalidPath(format!("{}: {}", path, e)))?;
+        let sub_attr = match &path[close + 1..] {
+            "" => None,
+            rest => {
+                let sub = AttrPath::parse(rest.strip_prefix('.').ok_or_else(invalid)?)?;
+                if sub.sub_attr.is_some() {
+                    return Err(invalid());
+                }
+                Some(sub.attr)
+            }
+        };
+
+        Ok(Self {
+            attr: attr.attr,
+            filter: Some(filter),
+            sub_attr,
+        })
+    }
+}
+
+/// Apply PATCH operations, in order, to a resource in JSON form
+pub(crate) fn apply_patch(resource: &mut Value, operations: &[PatchOperation]) -> Result<()> {
+    for operation in operations {
+        apply(resource, operation)?;
+    }
+    Ok(())
+}
+
+fn apply(resource: &mut Value, operation: &PatchOperation) -> Result<()> {
+    let Some(path) = &operation.path else {
+        if operation.op == PatchOp::Remove {
+            return Err(SecurityError::ScimInvalidPath(
+                "remove requires a path".to_string(),
+            ));
+        }
+        let Some(Value::Object(attributes)) = &operation.value else {
+            return Err(SecurityError::ScimInvalidValue(
+                "operation without a path requires an object value".to_string(),
+            ));
+        };
+        for (key, value) in attributes {
+            apply(
+                resource,
+                &PatchOperation {
+                    op: operation.op,
+                    path: Some(key.clone()),
+                    value: Some(value.clone()),
+                },
+            )?;
+        }
+        return Ok(());
+    };
+
+    let path = PatchPath::parse(path)?;
+    let object = resource
+        .as_object_mut()
+        .ok_or_else(|| SecurityError::ScimInvalidValue("resource is not an object".to_string()))?;
+
+    match operation.op {
+        PatchOp::Remove => remove(object, &path, operation.value.as_ref()),
+        op => {
+            let value = operation.value.clone().ok_or_else(|| {
+                SecurityError::ScimInvalidValue(format!("{:?} requires a value", op))
+            })?;
+            set(object, &path, value, op == PatchOp::Add)
+        }
+    }
+}
+
+fn set(object: &mut Map<String, Value>, path: &PatchPath, value: Value, add: bool) -> Result<()> {
+    let key = canonical(object, &path.attr);
+
+    match (&path.filter, &path.sub_attr) {
+        (None, None) => {
+            match (object.get_mut(&key), value) {
+                (Some(Value::Array(existing)), value) if add => {
+                    let values = match value {
+                        Value::Array(values) => values,
+                        value => vec![value],
+                    };
+                    for value in values {
+                        if !existing.contains(&value) {
+                            existing.push(value);
+                        }
+                    }
+                }
+                (Some(Value::Object(existing)), Value::Object(value)) if add => {
+                    existing.extend(value);
+                }
+                (_, value) => {
+                    object.insert(key, value);
+                }
+            }
+            Ok(())
+        }
+        (None, Some(sub)) => {
+            let target = object
+                .entry(key)
+                .or_insert_with(|| Value::Object(Map::new()))
+                .as_object_mut()
+                .ok_or_else(|| no_target(path))?;
+            let sub = canonical(target, sub);
+            target.insert(sub, value);
+            Ok(())
+        }
+        (Some(filter), sub) => {
+            let elements = matching(object, &key, filter);
+            if elements.is_empty() {
+                return Err(no_target(path));
+            }
+            for element in elements {
+                match (sub, &value) {
+                    (Some(sub), value) => {
+                        let sub = canonical(element, sub);
+                        element.insert(sub, value.clone());
+                    }
+                    (None, Value::Object(fields)) => element.extend(fields.clone()),
+                    (None, _) => {
+                        return Err(SecurityError::ScimInvalidValue(
+                            "value of a filtered path must be an object".to_string(),
+                        ))
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+fn remove(object: &mut Map<String, Value>, path: &PatchPath, value: Option<&Value>) -> Result<()> {
+    let key = canonical(object, &path.attr);
+
+    match (&path.filter, &path.sub_attr) {
+        (None, None) => {
+            match (object.get_mut(&key), value) {
+                // Remove listed values, e.g. `{"op": "remove", "path": "members", "value": [{"value": "id"}]}`
+                (Some(Value::Array(existing)), Some(Value::Array(values))) => {
+                    let removed: Vec<Option<&Value>> =
+                        values.iter().map(|v| get_attr(v, "value")).collect();
+                    existing.retain(|e| !removed.contains(&get_attr(e, "value")));
+                }
+                _ => {
+                    object.remove(&key);
+                }
+            }
+            Ok(())
+        }
+        (None, Some(sub)) => {
+            if let Some(Value::Object(target)) = object.get_mut(&key) {
+                let sub = canonical(target, sub);
+                target.remove(&sub);
+            }
+            Ok(())
+        }
+        (Some(filter), None) => {
+            if let Some(Value::Array(existing)) = object.get_mut(&key) {
+                existing.retain(|e| !filter.matches(e));
+            }
+            Ok(())
+        }
+        (Some(filter), Some(sub)) => {
+            for element in matching(object, &key, filter) {
+                let sub = canonical(element, sub);
+                element.remove(&sub);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Elements of a multi-valued attribute that match a filter
+fn matching<'a>(
+    object: &'a mut Map<String, Value>,
+    key: &str,
+    filter: &Filter,
+) -> Vec<&'a mut Map<String, Value>> {
+    match object.get_mut(key) {
+        Some(Value::Array(elements)) => elements
+            .iter_mut()
+            .filter(|e| filter.matches(e))
+            .filter_map(Value::as_object_mut)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Key to use for an attribute: the existing key, the known spelling, or the name as given
+fn canonical(object: &Map<String, Value>, name: &str) -> String {
+    object
+        .keys()
+        .find(|key| key.eq_ignore_ascii_case(name))
+        .map(String::as_str)
+        .or_else(|| {
+            KNOWN_ATTRIBUTES
+                .iter()
+                .copied()
+                .find(|known| known.eq_ignore_ascii_case(name))
+        })
+        .unwrap_or(name)
+        .to_string()
+}
+
+fn no_target(path: &PatchPath) -> SecurityError {
+    SecurityError::ScimInvalidPath(format!("no target for path '{}'", path.attr))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn op(op: PatchOp, path: Option<&str>, value: Option<Value>) -> PatchOperation {
+        PatchOperation {
+            op,
+            path: path.map(str::to_string),
+            value,
+        }
+    }
+
+    #[test]
+    fn test_apply_patch() {
+        let mut user = json!({
+            "userName": "jdoe",
+            "active": true,
+            "emails": [
+                { "value": "jane@acme.test", "type": "work" },
+                { "value": "jane@home.test", "type": "home" }
+            ]
+        });
+
+        apply_patch(
+            &mut user,
+            &[
+                op(PatchOp::Replace, None, Some(json!({ "active": "False", "name.givenName": "Jane" }))),
+                op(PatchOp::Replace, Some(r#"emails[type eq "work"].value"#), Some(json!("jd@acme.test"))),
+                op(PatchOp::Remove, Some(r#"emails[type eq "home"]"#), None),
+                op(PatchOp::Add, Some("displayname"), Some(json!("Jane Doe"))),
+                op(PatchOp::Add, Some("emails"), Some(json!([{ "value": "j@alt.test" }]))),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(user["active"], json!("False"));
+        assert_eq!(user["name"]["givenName"], json!("Jane"));
+        assert_eq!(user["displayName"], json!("Jane Doe"));
+        assert_eq!(
+            user["emails"],
+            json!([{ "value": "jd@acme.test", "type": "work" }, { "value": "j@alt.test" }])
+        );
+    }
+
+    #[test]
+    fn test_patch_members() {
+        let mut group = json!({ "displayName": "Investigators", "members": [{ "value": "u1" }] });
+
+        apply_patch(
+            &mut group,
+            &[
+                op(PatchOp::Add, Some("members"), Some(json!([{ "value": "u2" }, { "value": "u1" }]))),
+                op(PatchOp::Remove, Some("members"), Some(json!([{ "value": "u1" }]))),
+            ],
+        )
+        .unwrap();
+        assert_eq!(group["members"], json!([{ "value": "u2" }]));
+
+        apply_patch(&mut group, &[op(PatchOp::Remove, Some(r#"members[value eq "u2"]"#), None)])
+            .unwrap();
+        assert_eq!(group["members"], json!([]));
+
+        let missing = op(PatchOp::Replace, Some(r#"members[value eq "u9"].display"#), Some(json!("x")));
+        assert!(matches!(
+            apply_patch(&mut group, &[missing]),
+            Err(SecurityError::ScimInvalidPath(_))
+        ));
+        assert!(apply_patch(&mut group, &[op(PatchOp::Remove, None, None)]).is_err());
+    }
+}
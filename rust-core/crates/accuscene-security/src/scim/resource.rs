@@ -0,0 +1,431 @@
+//! SCIM resources and protocol messages (RFC 7643, RFC 7644)
+
+use crate::error::SecurityError;
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Core User schema
+pub const USER_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:User";
+/// Core Group schema
+pub const GROUP_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:Group";
+/// List response message
+pub const LIST_RESPONSE_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:ListResponse";
+/// PATCH request message
+pub const PATCH_OP_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:PatchOp";
+/// Error response message
+pub const ERROR_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:Error";
+
+/// Resource metadata
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Meta {
+    /// `User` or `Group`
+    pub resource_type: String,
+    /// Creation time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<chrono::DateTime<chrono::Utc>>,
+    /// Last modification time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<chrono::DateTime<chrono::Utc>>,
+    /// Resource URI
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+/// Components of a user's name
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Name {
+    /// Full name for display
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub formatted: Option<String>,
+    /// Family name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub family_name: Option<String>,
+    /// Given name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub given_name: Option<String>,
+}
+
+/// Value of a multi-valued attribute such as `emails` or `phoneNumbers`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MultiValue {
+    /// The value
+    pub value: String,
+    /// Label such as `work` or `home`
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// Preferred value of the attribute
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub primary: bool,
+}
+
+/// Reference from a user to a group it belongs to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupRef {
+    /// Group ID
+    pub value: String,
+    /// Group display name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<String>,
+}
+
+/// SCIM User resource
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimUser {
+    /// Schemas of the resource
+    #[serde(default = "user_schemas")]
+    pub schemas: Vec<String>,
+    /// Server-assigned ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// ID of the user at the identity provider
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_id: Option<String>,
+    /// Unique login name
+    pub user_name: String,
+    /// Name components
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<Name>,
+    /// Display name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// Email addresses
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub emails: Vec<MultiValue>,
+    /// Phone numbers
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub phone_numbers: Vec<MultiValue>,
+    /// Whether the user may sign in
+    #[serde(default = "default_active", deserialize_with = "lenient_bool")]
+    pub active: bool,
+    /// Groups the user belongs to (read-only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<GroupRef>,
+    /// Resource metadata
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
+}
+
+impl ScimUser {
+    /// Create an active user
+    pub fn new(user_name: impl Into<String>) -> Self {
+        Self {
+            schemas: user_schemas(),
+            id: None,
+            external_id: None,
+            user_name: user_name.into(),
+            name: None,
+            display_name: None,
+            emails: Vec::new(),
+            phone_numbers: Vec::new(),
+            active: true,
+            groups: Vec::new(),
+            meta: None,
+        }
+    }
+
+    /// Set the identity provider's ID
+    pub fn with_external_id(mut self, external_id: impl Into<String>) -> Self {
+        self.external_id = Some(external_id.into());
+        self
+    }
+
+    /// Set the display name
+    pub fn with_display_name(mut self, display_name: impl Into<String>) -> Self {
+        self.display_name = Some(display_name.into());
+        self
+    }
+
+    /// Add an email address; the first one added is primary
+    pub fn with_email(mut self, email: impl Into<String>) -> Self {
+        self.emails.push(MultiValue {
+            value: email.into(),
+            kind: Some("work".to_string()),
+            primary: self.emails.is_empty(),
+        });
+        self
+    }
+
+    /// Server-assigned ID, or an empty string before creation
+    pub fn id(&self) -> &str {
+        self.id.as_deref().unwrap_or_default()
+    }
+
+    /// Primary email address, falling back to the first one
+    pub fn primary_email(&self) -> Option<&str> {
+        primary(&self.emails)
+    }
+
+    /// Primary phone number, falling back to the first one
+    pub fn primary_phone(&self) -> Option<&str> {
+        primary(&self.phone_numbers)
+    }
+
+    /// Name for display: `displayName`, then `name.formatted`, then the given and family names
+    pub fn full_name(&self) -> Option<String> {
+        if let Some(display_name) = &self.display_name {
+            return Some(display_name.clone());
+        }
+        let name = self.name.as_ref()?;
+        if let Some(formatted) = &name.formatted {
+            return Some(formatted.clone());
+        }
+        let parts: Vec<&str> = [&name.given_name, &name.family_name]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(" "))
+    }
+}
+
+/// Member of a group
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Member {
+    /// User ID
+    pub value: String,
+    /// User display name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<String>,
+    /// User URI
+    #[serde(rename = "$ref", default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+}
+
+impl Member {
+    /// Create a member reference to a user
+    pub fn new(user_id: impl Into<String>) -> Self {
+        Self {
+            value: user_id.into(),
+            display: None,
+            reference: None,
+        }
+    }
+}
+
+/// SCIM Group resource
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimGroup {
+    /// Schemas of the resource
+    #[serde(default = "group_schemas")]
+    pub schemas: Vec<String>,
+    /// Server-assigned ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// ID of the group at the identity provider
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_id: Option<String>,
+    /// Unique group name
+    pub display_name: String,
+    /// Member users
+    #[serde(default)]
+    pub members: Vec<Member>,
+    /// Resource metadata
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Meta>,
+}
+
+impl ScimGroup {
+    /// Create an empty group
+    pub fn new(display_name: impl Into<String>) -> Self {
+        Self {
+            schemas: group_schemas(),
+            id: None,
+            external_id: None,
+            display_name: display_name.into(),
+            members: Vec::new(),
+            meta: None,
+        }
+    }
+
+    /// Add a member
+    pub fn with_member(mut self, user_id: impl Into<String>) -> Self {
+        self.members.push(Member::new(user_id));
+        self
+    }
+
+    /// Server-assigned ID, or an empty string before creation
+    pub fn id(&self) -> &str {
+        self.id.as_deref().unwrap_or_default()
+    }
+
+    /// Check if a user is a member
+    pub fn has_member(&self, user_id: &str) -> bool {
+        self.members.iter().any(|m| m.value == user_id)
+    }
+}
+
+/// Query parameters of a list request
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListQuery {
+    /// Filter expression
+    #[serde(default)]
+    pub filter: Option<String>,
+    /// 1-based index of the first result
+    #[serde(default)]
+    pub start_index: Option<usize>,
+    /// Maximum number of results
+    #[serde(default)]
+    pub count: Option<usize>,
+}
+
+impl ListQuery {
+    /// Query matching a filter
+    pub fn filtered(filter: impl Into<String>) -> Self {
+        Self {
+            filter: Some(filter.into()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Paged list of resources
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListResponse<T> {
+    /// Message schemas
+    pub schemas: Vec<String>,
+    /// Number of matching resources across all pages
+    pub total_results: usize,
+    /// 1-based index of the first returned resource
+    pub start_index: usize,
+    /// Number of returned resources
+    pub items_per_page: usize,
+    /// Returned resources
+    #[serde(rename = "Resources")]
+    pub resources: Vec<T>,
+}
+
+/// PATCH operation kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PatchOp {
+    Add,
+    Replace,
+    Remove,
+}
+
+// Some identity providers send `Add`/`Replace`/`Remove`
+impl<'de> Deserialize<'de> for PatchOp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let op = String::deserialize(deserializer)?;
+        match op.to_ascii_lowercase().as_str() {
+            "add" => Ok(PatchOp::Add),
+            "replace" => Ok(PatchOp::Replace),
+            "remove" => Ok(PatchOp::Remove),
+            _ => Err(serde::de::Error::unknown_variant(&op, &["add", "replace", "remove"])),
+        }
+    }
+}
+
+/// Single PATCH operation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PatchOperation {
+    /// Operation kind
+    pub op: PatchOp,
+    /// Target path; without one the value holds attributes to add or replace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Operation value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<serde_json::Value>,
+}
+
+/// PATCH request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatchRequest {
+    /// Message schemas
+    #[serde(default)]
+    pub schemas: Vec<String>,
+    /// Operations, applied in order
+    #[serde(rename = "Operations")]
+    pub operations: Vec<PatchOperation>,
+}
+
+impl PatchRequest {
+    /// Create a request from operations
+    pub fn new(operations: Vec<PatchOperation>) -> Self {
+        Self {
+            schemas: vec![PATCH_OP_SCHEMA.to_string()],
+            operations,
+        }
+    }
+}
+
+/// Error response body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimErrorResponse {
+    /// Message schemas
+    pub schemas: Vec<String>,
+    /// HTTP status code, as a string
+    pub status: String,
+    /// SCIM error type such as `uniqueness` or `invalidFilter`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scim_type: Option<String>,
+    /// Human-readable detail
+    pub detail: String,
+}
+
+impl ScimErrorResponse {
+    /// HTTP status code
+    pub fn status_code(&self) -> u16 {
+        self.status.parse().unwrap_or(500)
+    }
+}
+
+impl From<&SecurityError> for ScimErrorResponse {
+    fn from(error: &SecurityError) -> Self {
+        let (status, scim_type) = match error {
+            SecurityError::ScimInvalidFilter(_) => (400, Some("invalidFilter")),
+            SecurityError::ScimInvalidPath(_) => (400, Some("invalidPath")),
+            SecurityError::ScimInvalidValue(_) | SecurityError::ValidationFailed(_) => {
+                (400, Some("invalidValue"))
+            }
+            SecurityError::ScimConflict(_) => (409, Some("uniqueness")),
+            SecurityError::ScimResourceNotFound(_) => (404, None),
+            SecurityError::AccessDenied(_) | SecurityError::PermissionDenied(_) => (403, None),
+            _ => (500, None),
+        };
+
+        Self {
+            schemas: vec![ERROR_SCHEMA.to_string()],
+            status: status.to_string(),
+            scim_type: scim_type.map(str::to_string),
+            detail: error.to_string(),
+        }
+    }
+}
+
+fn user_schemas() -> Vec<String> {
+    vec![USER_SCHEMA.to_string()]
+}
+
+fn group_schemas() -> Vec<String> {
+    vec![GROUP_SCHEMA.to_string()]
+}
+
+fn default_active() -> bool {
+    true
+}
+
+fn primary(values: &[MultiValue]) -> Option<&str> {
+    values
+        .iter()
+        .find(|v| v.primary)
+        .or_else(|| values.first())
+        .map(|v| v.value.as_str())
+}
+
+/// Accept `"True"`/`"False"` strings, which some identity providers send for booleans
+fn lenient_bool<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Bool(b) => Ok(b),
+        serde_json::Value::String(s) if s.eq_ignore_ascii_case("true") => Ok(true),
+        serde_json::Value::String(s) if s.eq_ignore_ascii_case("false") => Ok(false),
+        other => Err(serde::de::Error::custom(format!("expected a boolean, got {}", other))),
+    }
+}
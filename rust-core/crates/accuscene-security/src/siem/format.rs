@@ -0,0 +1,448 @@
+//! SIEM message formats
+//!
+//! Renders [`SiemEvent`]s as ArcSight CEF, QRadar LEEF or RFC 5424 syslog
+//! messages. Which event fields end up in the message, and under which key,
+//! is controlled by a [`FieldMapping`].
+
+use crate::audit::event::EventSeverity;
+use crate::siem::SiemEvent;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Device vendor reported in CEF and LEEF headers
+const DEVICE_VENDOR: &str = "AccuScene";
+
+/// Device product reported in CEF and LEEF headers
+const DEVICE_PRODUCT: &str = "AccuScene Enterprise";
+
+/// Device version reported in CEF and LEEF headers
+const DEVICE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// RFC 5424 NILVALUE
+const NIL: &str = "-";
+
+/// Message format expected by the collector
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SiemFormat {
+    /// ArcSight Common Event Format
+    Cef,
+    /// IBM QRadar Log Event Extended Format
+    Leef,
+    /// RFC 5424 syslog with structured data
+    Syslog,
+}
+
+/// Default CEF keys: event field, CEF key and the label of custom string keys
+const CEF_FIELDS: &[(&str, &str, Option<&str>)] = &[
+    ("event_id", "externalId", None),
+    ("category", "cat", None),
+    ("action", "act", None),
+    ("outcome", "outcome", None),
+    ("user_id", "suser", None),
+    ("src_ip", "src", None),
+    ("session_id", "cs1", Some("sessionId")),
+    ("resource_type", "cs2", Some("resourceType")),
+    ("resource_id", "cs3", Some("resourceId")),
+    ("request_id", "cs4", Some("requestId")),
+    ("resource_name", "fname", None),
+    ("error", "reason", None),
+];
+
+/// Default LEEF keys
+const LEEF_FIELDS: &[(&str, &str)] = &[
+    ("event_id", "eventId"),
+    ("category", "cat"),
+    ("action", "action"),
+    ("outcome", "outcome"),
+    ("user_id", "usrName"),
+    ("src_ip", "src"),
+    ("session_id", "sessionId"),
+    ("resource_type", "resourceType"),
+    ("resource_id", "resource"),
+    ("request_id", "requestId"),
+    ("resource_name", "resourceName"),
+    ("error", "reason"),
+];
+
+/// Which event fields are exported and under which key
+///
+/// Every format has default keys (e.g. `user_id` becomes `suser` in CEF and
+/// `usrName` in LEEF); `rename` overrides them, so collectors with their own
+/// field conventions need no parser changes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FieldMapping {
+    /// Event field to output key, overriding the format default
+    pub rename: HashMap<String, String>,
+    /// Event fields left out of the message
+    pub exclude: Vec<String>,
+    /// Export event metadata entries as additional fields
+    pub include_metadata: bool,
+    /// Fields added to every message (e.g. a tenant or environment tag)
+    pub static_fields: BTreeMap<String, String>,
+}
+
+impl FieldMapping {
+    /// Output key of an event field, or `None` if the field is excluded
+    pub fn key(&self, format: SiemFormat, field: &str) -> Option<String> {
+        if self.exclude.iter().any(|excluded| excluded == field) {
+            return None;
+        }
+
+        if let Some(key) = self.rename.get(field) {
+            return Some(key.clone());
+        }
+
+        let key = match format {
+            SiemFormat::Cef => CEF_FIELDS
+                .iter()
+                .find(|(name, _, _)| *name == field)
+                .map(|(_, key, _)| key.to_string()),
+            SiemFormat::Leef => LEEF_FIELDS
+                .iter()
+                .find(|(name, _)| *name == field)
+                .map(|(_, key)| key.to_string()),
+            SiemFormat::Syslog => None,
+        };
+
+        Some(key.unwrap_or_else(|| sanitize_key(field)))
+    }
+
+    /// Map the event's fields to output key/value pairs
+    fn apply(&self, format: SiemFormat, event: &SiemEvent) -> Vec<(String, String)> {
+        let mut pairs: Vec<(String, String)> = event
+            .fields
+            .iter()
+            .filter_map(|(field, value)| Some((self.key(format, field)?, value.clone())))
+            .collect();
+
+        if self.include_metadata {
+            pairs.extend(
+                event
+                    .metadata
+                    .iter()
+                    .filter_map(|(field, value)| Some((self.key(format, field)?, value.clone()))),
+            );
+        }
+
+        pairs.extend(
+            self.static_fields
+                .iter()
+                .map(|(key, value)| (sanitize_key(key), value.clone())),
+        );
+
+        pairs
+    }
+}
+
+/// Syslog header settings
+#[derive(Debug, Clone)]
+pub struct SyslogOptions {
+    /// Syslog facility (0-23)
+    pub facility: u8,
+    /// HOSTNAME header field
+    pub hostname: String,
+    /// APP-NAME header field
+    pub app_name: String,
+    /// SD-ID of the structured data element carrying the event fields
+    pub sd_id: String,
+}
+
+impl Default for SyslogOptions {
+    fn default() -> Self {
+        Self {
+            facility: 13, // log audit
+            hostname: NIL.to_string(),
+            app_name: "accuscene".to_string(),
+            sd_id: "accuscene@32473".to_string(),
+        }
+    }
+}
+
+/// Renders events in the collector's format
+#[derive(Debug, Clone)]
+pub struct SiemFormatter {
+    format: SiemFormat,
+    mapping: FieldMapping,
+    syslog: SyslogOptions,
+}
+
+impl SiemFormatter {
+    /// Create a new formatter
+    pub fn new(format: SiemFormat, mapping: FieldMapping, syslog: SyslogOptions) -> Self {
+        Self {
+            format,
+            mapping,
+            syslog,
+        }
+    }
+
+    /// Output format
+    pub fn format(&self) -> SiemFormat {
+        self.format
+    }
+
+    /// Render an event as a single message
+    pub fn render(&self, event: &SiemEvent) -> String {
+        match self.format {
+            SiemFormat::Cef => self.render_cef(event),
+            SiemFormat::Leef => self.render_leef(event),
+            SiemFormat::Syslog => self.render_syslog(event),
+        }
+    }
+
+    fn render_cef(&self, event: &SiemEvent) -> String {
+        let mut extension = vec![format!("rt={}", event.timestamp.timestamp_millis())];
+
+        for (key, value) in self.mapping.apply(SiemFormat::Cef, event) {
+            if let Some(label) = cef_label(&key) {
+                extension.push(format!("{}Label={}", key, escape_cef_value(label)));
+            }
+            extension.push(format!("{}={}", key, escape_cef_value(&value)));
+        }
+
+        format!(
+            "CEF:0|{}|{}|{}|{}|{}|{}|{}",
+            escape_header(DEVICE_VENDOR),
+            escape_header(DEVICE_PRODUCT),
+            escape_header(DEVICE_VERSION),
+            escape_header(&event.class_id),
+            escape_header(&event.name),
+            cef_severity(event.severity),
+            extension.join(" ")
+        )
+    }
+
+    fn render_leef(&self, event: &SiemEvent) -> String {
+        let mut attributes = vec![
+            format!(
+                "devTime={}",
+                event.timestamp.format("%Y-%m-%dT%H:%M:%S%.3fZ")
+            ),
+            "devTimeFormat=yyyy-MM-dd'T'HH:mm:ss.SSSX".to_string(),
+            format!("sev={}", cef_severity(event.severity)),
+        ];
+
+        attributes.extend(
+            self.mapping
+                .apply(SiemFormat::Leef, event)
+                .into_iter()
+                .map(|(key, value)| format!("{}={}", key, escape_leef_value(&value))),
+        );
+
+        format!(
+            "LEEF:1.0|{}|{}|{}|{}|{}",
+            escape_header(DEVICE_VENDOR),
+            escape_header(DEVICE_PRODUCT),
+            escape_header(DEVICE_VERSION),
+            escape_header(&event.class_id),
+            attributes.join("\t")
+        )
+    }
+
+    fn render_syslog(&self, event: &SiemEvent) -> String {
+        let priority = u16::from(self.syslog.facility) * 8 + u16::from(syslog_severity(event.severity));
+
+        let params: Vec<String> = self
+            .mapping
+            .apply(SiemFormat::Syslog, event)
+            .into_iter()
+            .map(|(key, value)| format!(" {}=\"{}\"", key, escape_sd_value(&value)))
+            .collect();
+
+        format!(
+            "<{}>1 {} {} {} {} {} [{}{}] {}",
+            priority,
+            event.timestamp.format("%Y-%m-%dT%H:%M:%S%.6fZ"),
+            header_field(&self.syslog.hostname, 255),
+            header_field(&self.syslog.app_name, 48),
+            std::process::id(),
+            header_field(&event.class_id, 32),
+            self.syslog.sd_id,
+            params.concat(),
+            event.name
+        )
+    }
+}
+
+/// CEF/LEEF severity (0-10)
+fn cef_severity(severity: EventSeverity) -> u8 {
+    match severity {
+        EventSeverity::Debug => 1,
+        EventSeverity::Info => 3,
+        EventSeverity::Warning => 5,
+        EventSeverity::Error => 7,
+        EventSeverity::Critical => 10,
+    }
+}
+
+/// RFC 5424 severity (0 = emergency, 7 = debug)
+fn syslog_severity(severity: EventSeverity) -> u8 {
+    match severity {
+        EventSeverity::Debug => 7,
+        EventSeverity::Info => 6,
+        EventSeverity::Warning => 4,
+        EventSeverity::Error => 3,
+        EventSeverity::Critical => 2,
+    }
+}
+
+/// Label of a default CEF custom string key
+fn cef_label(key: &str) -> Option<&'static str> {
+    CEF_FIELDS
+        .iter()
+        .find(|(_, cef_key, _)| *cef_key == key)
+        .and_then(|(_, _, label)| *label)
+}
+
+/// Keep only characters valid in CEF/LEEF keys and SD-PARAM names
+fn sanitize_key(key: &str) -> String {
+    key.chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '.')
+        .take(32)
+        .collect()
+}
+
+/// Escape a CEF or LEEF header field
+fn escape_header(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace(['\r', '\n'], " ")
+}
+
+/// Escape a CEF extension value
+fn escape_cef_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('=', "\\=")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// Escape a LEEF attribute value; tabs would end the attribute
+fn escape_leef_value(value: &str) -> String {
+    value.replace(['\t', '\r', '\n'], " ")
+}
+
+/// Escape an RFC 5424 SD-PARAM value
+fn escape_sd_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace(']', "\\]")
+        .replace(['\r', '\n'], " ")
+}
+
+/// RFC 5424 header field: printable ASCII without spaces, NILVALUE if empty
+fn header_field(value: &str, max_len: usize) -> String {
+    let field: String = value
+        .chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(max_len)
+        .collect();
+
+    if field.is_empty() {
+        NIL.to_string()
+    } else {
+        field
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::event::{AuditEvent, EventResult, EventType, ResourceInfo};
+
+    fn login_failure() -> SiemEvent {
+        let event = AuditEvent::new(EventType::AuthLogin, "user.login".to_string())
+            .with_user("jdoe".to_string())
+            .with_ip("10.0.0.7".to_string())
+            .with_resource(ResourceInfo::new("case", "case-42"))
+            .with_error("bad password | retry=1".to_string())
+            .with_severity(EventSeverity::Warning)
+            .add_metadata("client".to_string(), "desktop".to_string());
+
+        SiemEvent::from_audit(&event)
+    }
+
+    #[test]
+    fn test_cef_format() {
+        let formatter =
+            SiemFormatter::new(SiemFormat::Cef, FieldMapping::default(), SyslogOptions::default());
+        let message = formatter.render(&login_failure());
+
+        assert!(message.starts_with("CEF:0|AccuScene|AccuScene Enterprise|"));
+        assert!(message.contains("|AuthLogin|user.login|5|rt="));
+        assert!(message.contains(" suser=jdoe"));
+        assert!(message.contains(" src=10.0.0.7"));
+        assert!(message.contains(" cs2Label=resourceType cs2=case "));
+        assert!(message.contains(&format!(" outcome={} ", EventResult::Failure)));
+        assert!(message.contains(" reason=bad password | retry\\=1"));
+        assert!(!message.contains("client"));
+    }
+
+    #[test]
+    fn test_leef_format() {
+        let formatter =
+            SiemFormatter::new(SiemFormat::Leef, FieldMapping::default(), SyslogOptions::default());
+        let message = formatter.render(&login_failure());
+
+        assert!(message.starts_with("LEEF:1.0|AccuScene|AccuScene Enterprise|"));
+        assert!(message.contains("|AuthLogin|devTime="));
+        assert!(message.contains("\tsev=5\t"));
+        assert!(message.contains("\tusrName=jdoe"));
+        assert!(message.contains("\tresource=case-42"));
+    }
+
+    #[test]
+    fn test_syslog_format() {
+        let options = SyslogOptions {
+            hostname: "sec-01".to_string(),
+            ..SyslogOptions::default()
+        };
+        let formatter = SiemFormatter::new(SiemFormat::Syslog, FieldMapping::default(), options);
+        let mut event = login_failure();
+        event
+            .fields
+            .insert("error".to_string(), "quote \" and ] bracket".to_string());
+
+        let message = formatter.render(&event);
+
+        // log audit (13) * 8 + warning (4)
+        assert!(message.starts_with("<108>1 "));
+        assert!(message.contains(" sec-01 accuscene "));
+        assert!(message.contains(" AuthLogin [accuscene@32473 "));
+        assert!(message.contains(" user_id=\"jdoe\""));
+        assert!(message.contains(" error=\"quote \\\" and \\] bracket\""));
+        assert!(message.ends_with("] user.login"));
+    }
+
+    #[test]
+    fn test_field_mapping() {
+        let mut mapping = FieldMapping {
+            include_metadata: true,
+            ..FieldMapping::default()
+        };
+        mapping.rename.insert("user_id".to_string(), "duser".to_string());
+        mapping.exclude.push("src_ip".to_string());
+        mapping
+            .static_fields
+            .insert("deviceExternalId".to_string(), "prod-east".to_string());
+
+        assert_eq!(mapping.key(SiemFormat::Cef, "user_id"), Some("duser".to_string()));
+        assert_eq!(mapping.key(SiemFormat::Cef, "src_ip"), None);
+        assert_eq!(mapping.key(SiemFormat::Leef, "session_id"), Some("sessionId".to_string()));
+        assert_eq!(mapping.key(SiemFormat::Syslog, "session_id"), Some("session_id".to_string()));
+
+        let formatter = SiemFormatter::new(SiemFormat::Cef, mapping, SyslogOptions::default());
+        let message = formatter.render(&login_failure());
+
+        assert!(message.contains(" duser=jdoe"));
+        assert!(!message.contains("10.0.0.7"));
+        assert!(message.contains(" client=desktop"));
+        assert!(message.contains(" deviceExternalId=prod-east"));
+    }
+}
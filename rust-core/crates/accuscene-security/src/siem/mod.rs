@@ -0,0 +1,348 @@
+//! SIEM export
+//!
+//! Forwards audit and threat events to a SIEM (Splunk, Sentinel, QRadar,
+//! ArcSight) as CEF, LEEF or RFC 5424 syslog messages. Events are batched and
+//! delivered over TCP or TLS; while the collector is down they are spooled to
+//! disk and replayed when it comes back.
+
+pub mod format;
+pub mod transport;
+
+pub use format::{FieldMapping, SiemFormat, SiemFormatter, SyslogOptions};
+pub use transport::{DeliveryReport, Framing, SiemProtocol, SiemTransport, Spool};
+
+use crate::audit::event::{AuditEvent, EventSeverity, EventType};
+use crate::audit::logger::AuditHandler;
+use crate::config::SiemConfig;
+use crate::error::{Result, SecurityError, Severity};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// Format-independent event handed to the SIEM
+///
+/// Audit and threat events are converted with [`from_audit`](Self::from_audit)
+/// and [`from_security_error`](Self::from_security_error); other subsystems
+/// (analytics, search) can build their own with [`new`](Self::new).
+#[derive(Debug, Clone)]
+pub struct SiemEvent {
+    /// Event timestamp
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Event class (CEF signature ID, LEEF event ID, syslog MSGID)
+    pub class_id: String,
+    /// Human-readable event name
+    pub name: String,
+    /// Severity
+    pub severity: EventSeverity,
+    /// Event fields, keyed by canonical field name (e.g. `user_id`)
+    pub fields: BTreeMap<String, String>,
+    /// Free-form metadata, exported only if the field mapping asks for it
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl SiemEvent {
+    /// Create a new event
+    pub fn new(
+        class_id: impl Into<String>,
+        name: impl Into<String>,
+        severity: EventSeverity,
+    ) -> Self {
+        Self {
+            timestamp: chrono::Utc::now(),
+            class_id: class_id.into(),
+            name: name.into(),
+            severity,
+            fields: BTreeMap::new(),
+            metadata: BTreeMap::new(),
+        }
+    }
+
+    /// Set a field
+    pub fn with_field(mut self, field: impl Into<String>, value: impl Into<String>) -> Self {
+        self.fields.insert(field.into(), value.into());
+        self
+    }
+
+    /// Convert an audit event
+    pub fn from_audit(event: &AuditEvent) -> Self {
+        let category = if is_threat(event.event_type) {
+            "threat"
+        } else {
+            "audit"
+        };
+
+        let mut siem_event = Self::new(event.event_type.to_string(), &event.action, event.severity)
+            .with_field("event_id", &event.id)
+            .with_field("category", category)
+            .with_field("action", &event.action)
+            .with_field("outcome", event.result.to_string());
+        siem_event.timestamp = event.timestamp;
+
+        let optional = [
+            ("user_id", event.user_id.as_ref()),
+            ("session_id", event.session_id.as_ref()),
+            ("src_ip", event.ip_address.as_ref()),
+            ("request_id", event.request_id.as_ref()),
+            ("error", event.error.as_ref()),
+        ];
+        for (field, value) in optional {
+            if let Some(value) = value {
+                siem_event = siem_event.with_field(field, value);
+            }
+        }
+
+        if let Some(resource) = &event.resource {
+            siem_event = siem_event
+                .with_field("resource_type", &resource.resource_type)
+                .with_field("resource_id", &resource.resource_id);
+            if let Some(name) = &resource.name {
+                siem_event = siem_event.with_field("resource_name", name);
+            }
+        }
+
+        siem_event.metadata = event
+            .metadata
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
+        siem_event
+    }
+
+    /// Convert a detected threat, e.g. from the brute force detector
+    pub fn from_security_error(error: &SecurityError, source: Option<&str>) -> Self {
+        let class_id = match error {
+            SecurityError::BruteForceDetected { .. } => EventType::SecurityBruteForceDetected,
+            SecurityError::RateLimitExceeded { .. } => EventType::SecurityRateLimitExceeded,
+            SecurityError::LoginLocked { .. } => EventType::SecurityLockout,
+            SecurityError::AnomalyDetected(_) => EventType::SecurityAnomalyDetected,
+            SecurityError::PolicyViolation(_) => EventType::SecurityPolicyViolation,
+            _ => EventType::SecurityThreatDetected,
+        };
+
+        let severity = match error.severity() {
+            Severity::Low => EventSeverity::Info,
+            Severity::Medium => EventSeverity::Warning,
+            Severity::High => EventSeverity::Error,
+            Severity::Critical => EventSeverity::Critical,
+        };
+
+        let mut siem_event = Self::new(class_id.to_string(), error.to_string(), severity)
+            .with_field("event_id", uuid::Uuid::new_v4().to_string())
+            .with_field("category", "threat");
+        if let Some(source) = source {
+            siem_event = siem_event.with_field("src_ip", source);
+        }
+
+        siem_event
+    }
+}
+
+/// Whether the audit event type reports a threat
+fn is_threat(event_type: EventType) -> bool {
+    matches!(
+        event_type,
+        EventType::SecurityThreatDetected
+            | EventType::SecurityRateLimitExceeded
+            | EventType::SecurityBruteForceDetected
+            | EventType::SecurityLockout
+            | EventType::SecurityAnomalyDetected
+            | EventType::SecurityPolicyViolation
+    )
+}
+
+/// Batches events and ships them to the SIEM collector
+///
+/// Cheap to clone; clones share the batch and the connection. Registered as
+/// an [`AuditHandler`], it exports every audit event at or above the
+/// configured severity.
+#[derive(Clone)]
+pub struct SiemExporter {
+    formatter: Arc<SiemFormatter>,
+    min_severity: EventSeverity,
+    batch_size: usize,
+    flush_interval: Duration,
+    pending: Arc<Mutex<Vec<String>>>,
+    transport: Arc<Mutex<SiemTransport>>,
+}
+
+impl SiemExporter {
+    /// Create a new exporter
+    pub fn new(config: &SiemConfig) -> Result<Self> {
+        let syslog = SyslogOptions {
+            facility: config.syslog_facility,
+            hostname: config.hostname.clone().unwrap_or_default(),
+            app_name: config.syslog_app_name.clone(),
+            ..SyslogOptions::default()
+        };
+
+        // Syslog collectors expect RFC 6587 octet counting over TCP
+        let framing = match config.format {
+            SiemFormat::Syslog => Framing::OctetCounting,
+            SiemFormat::Cef | SiemFormat::Leef => Framing::NewlineDelimited,
+        };
+
+        Ok(Self {
+            formatter: Arc::new(SiemFormatter::new(
+                config.format,
+                config.field_mapping.clone(),
+                syslog,
+            )),
+            min_severity: config.min_severity,
+            batch_size: config.batch_size.max(1),
+            flush_interval: Duration::from_secs(config.flush_interval_secs.max(1)),
+            pending: Arc::new(Mutex::new(Vec::new())),
+            transport: Arc::new(Mutex::new(SiemTransport::new(config, framing)?)),
+        })
+    }
+
+    /// Queue an event, flushing when the batch is full
+    pub async fn export(&self, event: &SiemEvent) -> Result<()> {
+        if event.severity < self.min_severity {
+            return Ok(());
+        }
+
+        let message = self.formatter.render(event);
+        let batch_full = {
+            let mut pending = self.pending.lock().await;
+            pending.push(message);
+            pending.len() >= self.batch_size
+        };
+
+        if batch_full {
+            self.flush().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Queue an audit event
+    pub async fn export_audit(&self, event: &AuditEvent) -> Result<()> {
+        self.export(&SiemEvent::from_audit(event)).await
+    }
+
+    /// Queue a detected threat
+    pub async fn export_threat(&self, error: &SecurityError, source: Option<&str>) -> Result<()> {
+        self.export(&SiemEvent::from_security_error(error, source))
+            .await
+    }
+
+    /// Deliver the pending batch and anything spooled
+    pub async fn flush(&self) -> Result<DeliveryReport> {
+        let batch = std::mem::take(&mut *self.pending.lock().await);
+
+        let mut transport = self.transport.lock().await;
+        transport.deliver(batch).await
+    }
+
+    /// Number of events waiting for the next flush
+    pub async fn pending(&self) -> usize {
+        self.pending.lock().await.len()
+    }
+
+    /// Flush periodically in the background
+    ///
+    /// Partial batches would otherwise wait until enough events arrive, and
+    /// spooled events until the next event.
+    pub fn spawn_flush_task(&self) -> tokio::task::JoinHandle<()> {
+        let exporter = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(exporter.flush_interval);
+            loop {
+                interval.tick().await;
+                if let Err(e) = exporter.flush().await {
+                    tracing::error!(error = %e, "SIEM flush failed");
+                }
+            }
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditHandler for SiemExporter {
+    async fn handle(&self, event: &AuditEvent) -> Result<()> {
+        self.export_audit(event).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::event::{EventResult, ResourceInfo};
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_from_audit() {
+        let event = AuditEvent::new(EventType::EvidenceAccessed, "evidence.read".to_string())
+            .with_user("investigator".to_string())
+            .with_resource(ResourceInfo::new("evidence", "ev-9").with_name("Dashcam"))
+            .with_result(EventResult::Success);
+
+        let siem_event = SiemEvent::from_audit(&event);
+
+        assert_eq!(siem_event.class_id, "EvidenceAccessed");
+        assert_eq!(siem_event.timestamp, event.timestamp);
+        assert_eq!(siem_event.fields["category"], "audit");
+        assert_eq!(siem_event.fields["user_id"], "investigator");
+        assert_eq!(siem_event.fields["resource_name"], "Dashcam");
+        assert!(!siem_event.fields.contains_key("src_ip"));
+    }
+
+    #[test]
+    fn test_from_security_error() {
+        let error = SecurityError::BruteForceDetected {
+            source: "203.0.113.5".to_string(),
+        };
+
+        let siem_event = SiemEvent::from_security_error(&error, Some("203.0.113.5"));
+
+        assert_eq!(siem_event.class_id, "SecurityBruteForceDetected");
+        assert_eq!(siem_event.severity, EventSeverity::Critical);
+        assert_eq!(siem_event.fields["category"], "threat");
+        assert_eq!(siem_event.fields["src_ip"], "203.0.113.5");
+    }
+
+    #[tokio::test]
+    async fn test_exporter_batches_audit_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+
+        let config = SiemConfig {
+            enabled: true,
+            host: "127.0.0.1".to_string(),
+            port: listener.local_addr().unwrap().port(),
+            protocol: SiemProtocol::Tcp,
+            format: SiemFormat::Cef,
+            min_severity: EventSeverity::Info,
+            batch_size: 2,
+            spool_dir: dir.path().to_path_buf(),
+            ..SiemConfig::default()
+        };
+        let exporter = SiemExporter::new(&config).unwrap();
+
+        let debug = AuditEvent::new(EventType::DataRead, "data.read".to_string())
+            .with_severity(EventSeverity::Debug);
+        exporter.handle(&debug).await.unwrap();
+        assert_eq!(exporter.pending().await, 0);
+
+        let login = AuditEvent::new(EventType::AuthLogin, "user.login".to_string());
+        exporter.handle(&login).await.unwrap();
+        assert_eq!(exporter.pending().await, 1);
+
+        let threat = SecurityError::AnomalyDetected("impossible travel".to_string());
+        exporter.export_threat(&threat, None).await.unwrap();
+        assert_eq!(exporter.pending().await, 0);
+
+        drop(exporter);
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut received = String::new();
+        socket.read_to_string(&mut received).await.unwrap();
+
+        let lines: Vec<&str> = received.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("|AuthLogin|user.login|3|"));
+        assert!(lines[1].contains("|SecurityAnomalyDetected|Anomaly detected: impossible travel|7|"));
+    }
+}
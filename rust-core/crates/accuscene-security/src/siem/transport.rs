@@ -0,0 +1,425 @@
+//! SIEM collector delivery
+//!
+//! Sends batches of formatted messages to the collector over TCP or TLS.
+//! While the collector is unreachable, batches are spooled to disk and
+//! replayed ahead of new messages once it is back, so delivery is
+//! at-least-once and in order.
+
+use crate::config::SiemConfig;
+use crate::error::{Result, SecurityError};
+use serde::{Deserialize, Serialize};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use tokio_rustls::TlsConnector;
+
+/// Spool file name inside the spool directory
+const SPOOL_FILE: &str = "siem-spool.jsonl";
+
+/// Transport protocol to the collector
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SiemProtocol {
+    /// Plain TCP
+    Tcp,
+    /// TLS over TCP
+    Tls,
+}
+
+/// How messages are delimited on the stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framing {
+    /// Message terminated by a line feed
+    NewlineDelimited,
+    /// Message prefixed by its length in bytes (RFC 6587)
+    OctetCounting,
+}
+
+impl Framing {
+    /// Frame a message for the stream
+    pub fn frame(&self, message: &str) -> Vec<u8> {
+        match self {
+            Framing::NewlineDelimited => format!("{}\n", message).into_bytes(),
+            Framing::OctetCounting => format!("{} {}", message.len(), message).into_bytes(),
+        }
+    }
+}
+
+/// Outcome of a delivery attempt
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeliveryReport {
+    /// Messages written to the collector, including replayed spooled ones
+    pub delivered: usize,
+    /// Messages left in the spool
+    pub spooled: usize,
+    /// Oldest spooled messages dropped to stay within the spool size limit
+    pub dropped: usize,
+}
+
+/// Open connection to the collector
+enum Connection {
+    Plain(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+}
+
+impl Connection {
+    async fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        match self {
+            Connection::Plain(stream) => stream.write_all(buf).await,
+            Connection::Tls(stream) => stream.write_all(buf).await,
+        }
+    }
+
+    async fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Connection::Plain(stream) => stream.flush().await,
+            Connection::Tls(stream) => stream.flush().await,
+        }
+    }
+}
+
+/// Delivers message batches to the collector
+pub struct SiemTransport {
+    host: String,
+    port: u16,
+    server_name: String,
+    connect_timeout: Duration,
+    framing: Framing,
+    tls: Option<TlsConnector>,
+    connection: Option<Connection>,
+    spool: Spool,
+}
+
+impl SiemTransport {
+    /// Create a new transport
+    ///
+    /// Does not connect; the connection is opened on the first delivery.
+    pub fn new(config: &SiemConfig, framing: Framing) -> Result<Self> {
+        let tls = match config.protocol {
+            SiemProtocol::Tcp => None,
+            SiemProtocol::Tls => Some(tls_connector(config.tls_ca_file.as_deref())?),
+        };
+
+        Ok(Self {
+            host: config.host.clone(),
+            port: config.port,
+            server_name: config
+                .tls_server_name
+                .clone()
+                .unwrap_or_else(|| config.host.clone()),
+            connect_timeout: Duration::from_secs(config.connect_timeout_secs),
+            framing,
+            tls,
+            connection: None,
+            spool: Spool::new(config.spool_dir.join(SPOOL_FILE), config.max_spool_bytes),
+        })
+    }
+
+    /// Spool holding undelivered messages
+    pub fn spool(&self) -> &Spool {
+        &self.spool
+    }
+
+    /// Deliver messages, replaying spooled ones first
+    ///
+    /// Collector failures are not errors: the messages are spooled and the
+    /// connection is re-established on the next delivery. Only spool I/O
+    /// failures are returned.
+    pub async fn deliver(&mut self, messages: Vec<String>) -> Result<DeliveryReport> {
+        let spooled = self.spool.load().await?;
+        let had_spool = !spooled.is_empty();
+
+        let mut batch = spooled;
+        batch.extend(messages);
+        if batch.is_empty() {
+            return Ok(DeliveryReport::default());
+        }
+
+        match self.send(&batch).await {
+            Ok(()) => {
+                if had_spool {
+                    self.spool.clear().await?;
+                }
+                Ok(DeliveryReport {
+                    delivered: batch.len(),
+                    ..DeliveryReport::default()
+                })
+            }
+            Err(e) => {
+                tracing::warn!(
+                    collector = %format!("{}:{}", self.host, self.port),
+                    error = %e,
+                    "SIEM collector unavailable, spooling {} messages",
+                    batch.len()
+                );
+                self.connection = None;
+
+                let total = batch.len();
+                let dropped = self.spool.replace(batch).await?;
+                Ok(DeliveryReport {
+                    delivered: 0,
+                    spooled: total - dropped,
+                    dropped,
+                })
+            }
+        }
+    }
+
+    /// Write the batch over the current connection, connecting if needed
+    async fn send(&mut self, batch: &[String]) -> Result<()> {
+        if self.connection.is_none() {
+            self.connection = Some(self.connect().await?);
+        }
+
+        let connection = self
+            .connection
+            .as_mut()
+            .ok_or_else(|| SecurityError::SiemExportFailed("Not connected".to_string()))?;
+
+        let mut buf = Vec::new();
+        for message in batch {
+            buf.extend(self.framing.frame(message));
+        }
+
+        connection
+            .write_all(&buf)
+            .await
+            .map_err(|e| SecurityError::SiemExportFailed(e.to_string()))?;
+        connection
+            .flush()
+            .await
+            .map_err(|e| SecurityError::SiemExportFailed(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn connect(&self) -> Result<Connection> {
+        let address = format!("{}:{}", self.host, self.port);
+        let stream = tokio::time::timeout(self.connect_timeout, TcpStream::connect(&address))
+            .await
+            .map_err(|_| SecurityError::SiemExportFailed(format!("Connecting to {} timed out", address)))?
+            .map_err(|e| SecurityError::SiemExportFailed(format!("Connecting to {}: {}", address, e)))?;
+
+        let Some(tls) = &self.tls else {
+            return Ok(Connection::Plain(stream));
+        };
+
+        let server_name = ServerName::try_from(self.server_name.clone())
+            .map_err(|e| SecurityError::ConfigurationError(format!("Invalid TLS server name: {}", e)))?;
+        let stream = tokio::time::timeout(self.connect_timeout, tls.connect(server_name, stream))
+            .await
+            .map_err(|_| SecurityError::SiemExportFailed(format!("TLS handshake with {} timed out", address)))?
+            .map_err(|e| SecurityError::SiemExportFailed(format!("TLS handshake with {}: {}", address, e)))?;
+
+        Ok(Connection::Tls(Box::new(stream)))
+    }
+}
+
+/// Build the TLS connector, trusting the CA file or the Mozilla root store
+fn tls_connector(ca_file: Option<&Path>) -> Result<TlsConnector> {
+    let mut roots = RootCertStore::empty();
+
+    match ca_file {
+        Some(path) => {
+            let file = std::fs::File::open(path).map_err(|e| {
+                SecurityError::ConfigurationError(format!("Cannot read CA file {:?}: {}", path, e))
+            })?;
+            for cert in rustls_pemfile::certs(&mut BufReader::new(file)) {
+                let cert = cert.map_err(|e| {
+                    SecurityError::ConfigurationError(format!("Invalid CA file {:?}: {}", path, e))
+                })?;
+                roots
+                    .add(cert)
+                    .map_err(|e| SecurityError::ConfigurationError(e.to_string()))?;
+            }
+        }
+        None => roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+    }
+
+    let provider = Arc::new(tokio_rustls::rustls::crypto::ring::default_provider());
+    let config = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| SecurityError::ConfigurationError(e.to_string()))?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+
+    Ok(TlsConnector::from(Arc::new(config)))
+}
+
+/// On-disk queue of undelivered messages, one JSON string per line
+pub struct Spool {
+    path: PathBuf,
+    max_bytes: u64,
+}
+
+impl Spool {
+    /// Create a new spool
+    pub fn new(path: impl Into<PathBuf>, max_bytes: u64) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes,
+        }
+    }
+
+    /// Spool file location
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Load all spooled messages, oldest first
+    pub async fn load(&self) -> Result<Vec<String>> {
+        let contents = match tokio::fs::read_to_string(&self.path).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(SecurityError::SiemExportFailed(e.to_string())),
+        };
+
+        let mut messages = Vec::new();
+        for line in contents.lines().filter(|line| !line.is_empty()) {
+            match serde_json::from_str::<String>(line) {
+                Ok(message) => messages.push(message),
+                Err(e) => tracing::warn!(error = %e, "Skipping corrupt SIEM spool entry"),
+            }
+        }
+
+        Ok(messages)
+    }
+
+    /// Number of spooled messages
+    pub async fn len(&self) -> Result<usize> {
+        Ok(self.load().await?.len())
+    }
+
+    /// Replace the spool contents, dropping the oldest messages over the limit
+    ///
+    /// Returns the number of dropped messages.
+    pub async fn replace(&self, messages: Vec<String>) -> Result<usize> {
+        let mut lines = Vec::with_capacity(messages.len());
+        for message in &messages {
+            let line = serde_json::to_string(message)
+                .map_err(|e| SecurityError::Internal(e.to_string()))?;
+            lines.push(line);
+        }
+
+        // Keep the newest messages that fit
+        let mut size = 0u64;
+        let mut keep = 0;
+        for line in lines.iter().rev() {
+            size += line.len() as u64 + 1;
+            if size > self.max_bytes {
+                break;
+            }
+            keep += 1;
+        }
+
+        let dropped = lines.len() - keep;
+        if dropped > 0 {
+            tracing::warn!("SIEM spool full, dropping {} oldest messages", dropped);
+        }
+
+        let mut contents = lines[dropped..].join("\n");
+        contents.push('\n');
+
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| SecurityError::SiemExportFailed(e.to_string()))?;
+        }
+
+        // Write-then-rename so a crash never leaves a truncated spool
+        let staging = self.path.with_extension("tmp");
+        tokio::fs::write(&staging, contents)
+            .await
+            .map_err(|e| SecurityError::SiemExportFailed(e.to_string()))?;
+        tokio::fs::rename(&staging, &self.path)
+            .await
+            .map_err(|e| SecurityError::SiemExportFailed(e.to_string()))?;
+
+        Ok(dropped)
+    }
+
+    /// Remove all spooled messages
+    pub async fn clear(&self) -> Result<()> {
+        match tokio::fs::remove_file(&self.path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(SecurityError::SiemExportFailed(e.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    fn tcp_config(port: u16, spool_dir: &Path) -> SiemConfig {
+        SiemConfig {
+            host: "127.0.0.1".to_string(),
+            port,
+            protocol: SiemProtocol::Tcp,
+            connect_timeout_secs: 2,
+            spool_dir: spool_dir.to_path_buf(),
+            ..SiemConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_framing() {
+        assert_eq!(Framing::NewlineDelimited.frame("abc"), b"abc\n");
+        assert_eq!(Framing::OctetCounting.frame("<14>1 é"), "8 <14>1 é".as_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_spool_size_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let spool = Spool::new(dir.path().join(SPOOL_FILE), 20);
+
+        let dropped = spool
+            .replace(vec!["first".to_string(), "second".to_string(), "third".to_string()])
+            .await
+            .unwrap();
+
+        assert_eq!(dropped, 1);
+        assert_eq!(spool.load().await.unwrap(), vec!["second", "third"]);
+
+        spool.clear().await.unwrap();
+        assert_eq!(spool.len().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_spools_while_collector_down() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // Reserve a port, then close it so the collector is down
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let config = tcp_config(port, dir.path());
+        let mut transport = SiemTransport::new(&config, Framing::NewlineDelimited).unwrap();
+
+        let report = transport.deliver(vec!["one".to_string()]).await.unwrap();
+        assert_eq!(report.spooled, 1);
+        assert_eq!(report.delivered, 0);
+
+        // Collector comes back: the spooled message goes out first
+        let listener = TcpListener::bind(("127.0.0.1", port)).await.unwrap();
+        let report = transport.deliver(vec!["two".to_string()]).await.unwrap();
+        assert_eq!(report.delivered, 2);
+        assert_eq!(transport.spool().len().await.unwrap(), 0);
+
+        drop(transport);
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut received = String::new();
+        socket.read_to_string(&mut received).await.unwrap();
+        assert_eq!(received, "one\ntwo\n");
+    }
+}
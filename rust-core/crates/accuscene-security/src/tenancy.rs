@@ -0,0 +1,184 @@
+//! Multi-tenant organization scoping
+//!
+//! Every authenticated user acts for at most one organization. Resources owned by
+//! another organization are only reachable by platform operators, and each such
+//! crossing is written to the audit trail.
+
+use crate::audit::{AuditEvent, AuditService, EventResult, EventSeverity, EventType, ResourceInfo};
+use crate::auth::AuthContext;
+use crate::error::{Result, SecurityError};
+use std::sync::Arc;
+
+/// Permission allowing access to resources of other organizations
+pub const CROSS_TENANT_PERMISSION: &str = "tenants:cross_access";
+
+/// Outcome of a tenant check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TenantAccess {
+    /// Resource belongs to the caller's organization
+    SameTenant,
+    /// Resource belongs to another organization and the caller may cross over
+    CrossTenant,
+}
+
+impl TenantAccess {
+    /// Check if the access crosses an organization boundary
+    pub fn is_cross_tenant(&self) -> bool {
+        matches!(self, TenantAccess::CrossTenant)
+    }
+}
+
+/// Check whether a user may touch a resource owned by `resource_org`
+pub fn check_tenant_access(context: &AuthContext, resource_org: &str) -> Result<TenantAccess> {
+    if context.acts_for(resource_org) {
+        return Ok(TenantAccess::SameTenant);
+    }
+
+    if context.has_role("super_admin") || context.has_permission(CROSS_TENANT_PERMISSION) {
+        return Ok(TenantAccess::CrossTenant);
+    }
+
+    Err(SecurityError::TenantAccessDenied {
+        org_id: resource_org.to_string(),
+    })
+}
+
+/// Build an audit event for an access that crossed (or tried to cross) tenants
+pub fn cross_tenant_event(
+    context: &AuthContext,
+    resource_org: &str,
+    resource: Option<ResourceInfo>,
+    action: &str,
+    granted: bool,
+) -> AuditEvent {
+    let mut event = AuditEvent::new(EventType::AuthzCrossTenantAccess, action.to_string())
+        .with_user(context.user_id.clone())
+        .with_result(if granted {
+            EventResult::Success
+        } else {
+            EventResult::Failure
+        })
+        .with_severity(if granted {
+            EventSeverity::Warning
+        } else {
+            EventSeverity::Error
+        })
+        .add_metadata("target_org".to_string(), resource_org.to_string());
+
+    if let Some(org_id) = &context.org_id {
+        event = event.add_metadata("source_org".to_string(), org_id.clone());
+    }
+    if let Some(session_id) = &context.session_id {
+        event = event.with_session(session_id.clone());
+    }
+    if let Some(resource) = resource {
+        event = event.with_resource(resource);
+    }
+
+    event
+}
+
+/// Enforces tenant boundaries and audits every crossing
+#[derive(Clone)]
+pub struct TenantGuard {
+    audit: Arc<AuditService>,
+}
+
+impl TenantGuard {
+    /// Create a tenant guard writing to the given audit service
+    pub fn new(audit: Arc<AuditService>) -> Self {
+        Self { audit }
+    }
+
+    /// Authorize access to a resource of `resource_org`
+    ///
+    /// Same-tenant access is not audited; cross-tenant grants and denials are.
+    pub async fn authorize(
+        &self,
+        context: &AuthContext,
+        resource_org: &str,
+        resource: Option<ResourceInfo>,
+        action: &str,
+    ) -> Result<TenantAccess> {
+        let access = check_tenant_access(context, resource_org);
+
+        match &access {
+            Ok(TenantAccess::SameTenant) => {}
+            Ok(TenantAccess::CrossTenant) => {
+                self.record(context, resource_org, resource, action, true).await?;
+            }
+            Err(_) => {
+                self.record(context, resource_org, resource, action, false).await?;
+            }
+        }
+
+        access
+    }
+
+    /// Record a cross-tenant access decided elsewhere (e.g. by a row policy)
+    pub async fn record(
+        &self,
+        context: &AuthContext,
+        resource_org: &str,
+        resource: Option<ResourceInfo>,
+        action: &str,
+        granted: bool,
+    ) -> Result<()> {
+        let event = cross_tenant_event(context, resource_org, resource, action, granted);
+        self.audit.audit(event).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::{AuditQuery, StorageConfig};
+
+    fn context(org_id: Option<&str>, roles: Vec<&str>) -> AuthContext {
+        AuthContext {
+            user_id: "user123".to_string(),
+            session_id: None,
+            roles: roles.into_iter().map(String::from).collect(),
+            permissions: vec![],
+            org_id: org_id.map(String::from),
+            mfa_verified: true,
+            session_metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_tenant_access() {
+        let member = context(Some("firm-a"), vec!["investigator"]);
+        assert_eq!(
+            check_tenant_access(&member, "firm-a").unwrap(),
+            TenantAccess::SameTenant
+        );
+        assert!(matches!(
+            check_tenant_access(&member, "firm-b"),
+            Err(SecurityError::TenantAccessDenied { .. })
+        ));
+
+        let operator = context(None, vec!["super_admin"]);
+        assert!(check_tenant_access(&operator, "firm-b")
+            .unwrap()
+            .is_cross_tenant());
+    }
+
+    #[tokio::test]
+    async fn test_guard_audits_cross_tenant_access() {
+        let audit = Arc::new(AuditService::new(StorageConfig::default()));
+        let guard = TenantGuard::new(Arc::clone(&audit));
+
+        let member = context(Some("firm-a"), vec![]);
+        guard.authorize(&member, "firm-a", None, "cases.read").await.unwrap();
+        assert!(guard
+            .authorize(&member, "firm-b", None, "cases.read")
+            .await
+            .is_err());
+
+        let result = audit.query(AuditQuery::new()).await.unwrap();
+        assert_eq!(result.events.len(), 1);
+        assert_eq!(result.events[0].event_type, EventType::AuthzCrossTenantAccess);
+        assert_eq!(result.events[0].result, EventResult::Failure);
+    }
+}
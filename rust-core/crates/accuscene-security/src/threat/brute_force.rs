@@ -61,6 +61,17 @@ impl BruteForceDetector {
         }
     }
 
+    /// Count failed attempts for identifier within the window
+    pub async fn attempt_count(&self, identifier: &str) -> u32 {
+        let attempts = self.attempts.read().await;
+        let cutoff = chrono::Utc::now() - chrono::Duration::seconds(self.window_secs as i64);
+
+        attempts
+            .get(identifier)
+            .map(|entry| entry.iter().filter(|t| **t > cutoff).count() as u32)
+            .unwrap_or(0)
+    }
+
     /// Clear attempts for identifier
     pub async fn clear_attempts(&self, identifier: &str) {
         let mut attempts = self.attempts.write().await;
@@ -92,6 +103,7 @@ mod tests {
         let detector = BruteForceDetector::new(3, 300);
 
         detector.record_failed_attempt("user123").await.ok();
+        assert_eq!(detector.attempt_count("user123").await, 1);
         detector.clear_attempts("user123").await;
 
         assert!(!detector.is_locked_out("user123").await);
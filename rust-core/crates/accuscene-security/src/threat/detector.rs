@@ -0,0 +1,436 @@
+//! Login threat detection
+//!
+//! Combines per-user and per-IP failure counters over a sliding window into
+//! lockouts and an adaptive threat score. The score tells the caller when to
+//! ask for a CAPTCHA or step up to MFA before a lockout is reached.
+
+use super::{AnomalyDetector, BruteForceDetector};
+use crate::audit::{AuditEvent, AuditService, EventResult, EventSeverity, EventType};
+use crate::config::ThreatConfig;
+use crate::error::{Result, SecurityError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Weight of the user's own failures in the threat score
+const USER_WEIGHT: f64 = 0.6;
+/// Weight of failures (or password spraying) from the same IP address
+const IP_WEIGHT: f64 = 0.4;
+/// Score added when the login looks anomalous
+const ANOMALY_BONUS: f64 = 0.3;
+
+/// What a lockout applies to
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ThreatSubject {
+    /// A user account
+    User(String),
+    /// A client IP address
+    Ip(String),
+}
+
+impl std::fmt::Display for ThreatSubject {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ThreatSubject::User(user_id) => write!(f, "user:{}", user_id),
+            ThreatSubject::Ip(ip) => write!(f, "ip:{}", ip),
+        }
+    }
+}
+
+/// An active lockout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lockout {
+    /// Locked user or IP address
+    pub subject: ThreatSubject,
+    /// When the lockout started
+    pub locked_at: chrono::DateTime<chrono::Utc>,
+    /// When the lockout ends
+    pub until: chrono::DateTime<chrono::Utc>,
+    /// Failed attempts that triggered it
+    pub failures: u32,
+}
+
+impl Lockout {
+    /// Check if the lockout has ended
+    pub fn is_expired(&self) -> bool {
+        chrono::Utc::now() >= self.until
+    }
+
+    /// Seconds until the lockout ends
+    pub fn retry_after_secs(&self) -> u64 {
+        (self.until - chrono::Utc::now()).num_seconds().max(0) as u64
+    }
+}
+
+/// Threat assessment of a login attempt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreatAssessment {
+    /// Threat score (0.0-1.0)
+    pub score: f64,
+    /// Recent failed logins for the user
+    pub user_failures: u32,
+    /// Recent failed logins from the IP address
+    pub ip_failures: u32,
+    /// Ask for a CAPTCHA before checking the password
+    pub require_captcha: bool,
+    /// Require MFA even if the user would not normally need it
+    pub require_step_up: bool,
+}
+
+impl ThreatAssessment {
+    /// Assessment for a login with no threat signals
+    pub fn clear() -> Self {
+        Self {
+            score: 0.0,
+            user_failures: 0,
+            ip_failures: 0,
+            require_captcha: false,
+            require_step_up: false,
+        }
+    }
+}
+
+/// Brute-force lockout and adaptive scoring for logins
+pub struct ThreatDetector {
+    config: ThreatConfig,
+    users: BruteForceDetector,
+    ips: BruteForceDetector,
+    anomaly: AnomalyDetector,
+    ip_users: RwLock<HashMap<String, HashMap<String, chrono::DateTime<chrono::Utc>>>>,
+    lockouts: RwLock<HashMap<ThreatSubject, Lockout>>,
+    audit: Option<Arc<AuditService>>,
+}
+
+impl ThreatDetector {
+    /// Create a new threat detector
+    pub fn new(config: ThreatConfig) -> Self {
+        // Counters never trip on their own; lockouts are placed here instead
+        Self {
+            users: BruteForceDetector::new(u32::MAX, config.brute_force_window_secs),
+            ips: BruteForceDetector::new(u32::MAX, config.brute_force_window_secs),
+            anomaly: AnomalyDetector::new(config.anomaly_sensitivity),
+            ip_users: RwLock::new(HashMap::new()),
+            lockouts: RwLock::new(HashMap::new()),
+            audit: None,
+            config,
+        }
+    }
+
+    /// Audit lockouts and releases
+    pub fn with_audit(mut self, audit: Arc<AuditService>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Check a login attempt before verifying credentials
+    ///
+    /// Fails with [`SecurityError::LoginLocked`] while the user or IP address is
+    /// locked out. Expired lockouts are released (and audited) here.
+    pub async fn check_login(
+        &self,
+        user_id: &str,
+        ip: &str,
+        user_agent: Option<&str>,
+    ) -> Result<ThreatAssessment> {
+        if !self.config.brute_force_detection {
+            return Ok(ThreatAssessment::clear());
+        }
+
+        self.release_expired().await?;
+
+        {
+            let lockouts = self.lockouts.read().await;
+            let active = [
+                ThreatSubject::User(user_id.to_string()),
+                ThreatSubject::Ip(ip.to_string()),
+            ]
+            .iter()
+            .filter_map(|subject| lockouts.get(subject))
+            .map(Lockout::retry_after_secs)
+            .max();
+
+            if let Some(retry_after_secs) = active {
+                return Err(SecurityError::LoginLocked { retry_after_secs });
+            }
+        }
+
+        Ok(self.assess(user_id, ip, user_agent).await)
+    }
+
+    /// Record a failed login, returning the lockout it triggered, if any
+    pub async fn record_failure(&self, user_id: &str, ip: &str) -> Result<Option<Lockout>> {
+        if !self.config.brute_force_detection {
+            return Ok(None);
+        }
+
+        // Neither detector has a threshold, so recording cannot fail
+        self.users.record_failed_attempt(user_id).await.ok();
+        self.ips.record_failed_attempt(ip).await.ok();
+        self.track_ip_user(ip, user_id).await;
+
+        let user_failures = self.users.attempt_count(user_id).await;
+        if user_failures >= self.config.brute_force_threshold {
+            self.users.clear_attempts(user_id).await;
+            let lockout = self
+                .lock(ThreatSubject::User(user_id.to_string()), user_failures, ip)
+                .await?;
+            return Ok(Some(lockout));
+        }
+
+        let ip_failures = self.ips.attempt_count(ip).await;
+        if ip_failures >= self.config.ip_brute_force_threshold {
+            self.ips.clear_attempts(ip).await;
+            self.ip_users.write().await.remove(ip);
+            let lockout = self
+                .lock(ThreatSubject::Ip(ip.to_string()), ip_failures, ip)
+                .await?;
+            return Ok(Some(lockout));
+        }
+
+        Ok(None)
+    }
+
+    /// Record a successful login, resetting the user's failure counter
+    ///
+    /// IP failures are kept, since one valid account does not clear a spraying source.
+    pub async fn record_success(&self, user_id: &str) {
+        self.users.clear_attempts(user_id).await;
+    }
+
+    /// Release a lockout early, returning whether one was active
+    pub async fn unlock(&self, subject: &ThreatSubject) -> Result<bool> {
+        let lockout = self.lockouts.write().await.remove(subject);
+
+        match lockout {
+            Some(lockout) => {
+                self.audit_release(&lockout, "manual").await?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Release expired lockouts, returning how many ended
+    pub async fn release_expired(&self) -> Result<usize> {
+        let expired: Vec<Lockout> = {
+            let mut lockouts = self.lockouts.write().await;
+            let subjects: Vec<ThreatSubject> = lockouts
+                .values()
+                .filter(|l| l.is_expired())
+                .map(|l| l.subject.clone())
+                .collect();
+            subjects
+                .iter()
+                .filter_map(|subject| lockouts.remove(subject))
+                .collect()
+        };
+
+        for lockout in &expired {
+            self.audit_release(lockout, "expired").await?;
+        }
+
+        Ok(expired.len())
+    }
+
+    /// List active lockouts
+    pub async fn lockouts(&self) -> Vec<Lockout> {
+        self.lockouts
+            .read()
+            .await
+            .values()
+            .filter(|l| !l.is_expired())
+            .cloned()
+            .collect()
+    }
+
+    async fn assess(&self, user_id: &str, ip: &str, user_agent: Option<&str>) -> ThreatAssessment {
+        let user_failures = self.users.attempt_count(user_id).await;
+        let ip_failures = self.ips.attempt_count(ip).await;
+        let sprayed_users = self.recent_ip_users(ip).await;
+
+        let user_ratio = ratio(user_failures, self.config.brute_force_threshold);
+        let ip_ratio = ratio(ip_failures, self.config.ip_brute_force_threshold);
+        // Failures against many accounts from one address suggest password spraying
+        let spray_ratio = ratio(
+            sprayed_users.saturating_sub(1),
+            self.config.brute_force_threshold,
+        );
+
+        let mut score = USER_WEIGHT * user_ratio + IP_WEIGHT * ip_ratio.max(spray_ratio);
+        if self.config.anomaly_detection
+            && self
+                .anomaly
+                .detect_login_anomaly(user_id, ip, user_agent.unwrap_or_default())
+        {
+            score += ANOMALY_BONUS;
+        }
+        let score = score.min(1.0);
+
+        ThreatAssessment {
+            score,
+            user_failures,
+            ip_failures,
+            require_captcha: user_failures.max(ip_failures) >= self.config.captcha_threshold,
+            require_step_up: score >= self.config.step_up_score,
+        }
+    }
+
+    async fn track_ip_user(&self, ip: &str, user_id: &str) {
+        let now = chrono::Utc::now();
+        let cutoff = now - chrono::Duration::seconds(self.config.brute_force_window_secs as i64);
+
+        let mut ip_users = self.ip_users.write().await;
+        let users = ip_users.entry(ip.to_string()).or_default();
+        users.retain(|_, at| *at > cutoff);
+        users.insert(user_id.to_string(), now);
+    }
+
+    async fn recent_ip_users(&self, ip: &str) -> u32 {
+        let cutoff = chrono::Utc::now()
+            - chrono::Duration::seconds(self.config.brute_force_window_secs as i64);
+
+        self.ip_users
+            .read()
+            .await
+            .get(ip)
+            .map(|users| users.values().filter(|at| **at > cutoff).count() as u32)
+            .unwrap_or(0)
+    }
+
+    async fn lock(&self, subject: ThreatSubject, failures: u32, ip: &str) -> Result<Lockout> {
+        let now = chrono::Utc::now();
+        let lockout = Lockout {
+            subject: subject.clone(),
+            locked_at: now,
+            until: now + chrono::Duration::seconds(self.config.lockout_duration_secs as i64),
+            failures,
+        };
+        self.lockouts.write().await.insert(subject, lockout.clone());
+
+        tracing::warn!("Locked out {} after {} failed logins", lockout.subject, failures);
+
+        if let Some(audit) = &self.audit {
+            let mut event = AuditEvent::new(EventType::SecurityLockout, "login.lockout".to_string())
+                .with_ip(ip.to_string())
+                .with_result(EventResult::Failure)
+                .with_severity(EventSeverity::Warning)
+                .add_metadata("subject".to_string(), lockout.subject.to_string())
+                .add_metadata("failures".to_string(), failures.to_string())
+                .add_metadata("until".to_string(), lockout.until.to_rfc3339());
+            if let ThreatSubject::User(user_id) = &lockout.subject {
+                event = event.with_user(user_id.clone());
+            }
+            audit.audit(event).await?;
+        }
+
+        Ok(lockout)
+    }
+
+    async fn audit_release(&self, lockout: &Lockout, reason: &str) -> Result<()> {
+        let Some(audit) = &self.audit else {
+            return Ok(());
+        };
+
+        let mut event = AuditEvent::new(
+            EventType::SecurityLockoutReleased,
+            "login.lockout_released".to_string(),
+        )
+        .add_metadata("subject".to_string(), lockout.subject.to_string())
+        .add_metadata("reason".to_string(), reason.to_string());
+        match &lockout.subject {
+            ThreatSubject::User(user_id) => event = event.with_user(user_id.clone()),
+            ThreatSubject::Ip(ip) => event = event.with_ip(ip.clone()),
+        }
+
+        audit.audit(event).await
+    }
+}
+
+fn ratio(count: u32, threshold: u32) -> f64 {
+    (count as f64 / threshold.max(1) as f64).min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::{AuditQuery, StorageConfig};
+
+    fn config() -> ThreatConfig {
+        ThreatConfig {
+            brute_force_threshold: 3,
+            ip_brute_force_threshold: 5,
+            captcha_threshold: 2,
+            ..ThreatConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_user_lockout_and_release() {
+        let audit = Arc::new(AuditService::new(StorageConfig::default()));
+        let detector = ThreatDetector::new(config()).with_audit(Arc::clone(&audit));
+
+        assert!(detector.record_failure("alice", "10.0.0.1").await.unwrap().is_none());
+        let assessment = detector.check_login("alice", "10.0.0.1", None).await.unwrap();
+        assert_eq!(assessment.user_failures, 1);
+        assert!(!assessment.require_captcha);
+
+        detector.record_failure("alice", "10.0.0.1").await.unwrap();
+        let assessment = detector.check_login("alice", "10.0.0.1", None).await.unwrap();
+        assert!(assessment.require_captcha);
+        assert!(assessment.require_step_up);
+
+        let lockout = detector
+            .record_failure("alice", "10.0.0.1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(lockout.subject, ThreatSubject::User("alice".to_string()));
+        assert!(matches!(
+            detector.check_login("alice", "10.0.0.2", None).await,
+            Err(SecurityError::LoginLocked { .. })
+        ));
+        assert!(detector.check_login("bob", "10.0.0.2", None).await.is_ok());
+
+        assert!(detector.unlock(&lockout.subject).await.unwrap());
+        assert!(detector.check_login("alice", "10.0.0.2", None).await.is_ok());
+
+        let events = audit.query(AuditQuery::new()).await.unwrap().events;
+        let types: Vec<_> = events.iter().map(|e| e.event_type).collect();
+        assert!(types.contains(&EventType::SecurityLockout));
+        assert!(types.contains(&EventType::SecurityLockoutReleased));
+    }
+
+    #[tokio::test]
+    async fn test_password_spraying_locks_ip() {
+        let detector = ThreatDetector::new(config());
+
+        for user in ["a", "b", "c", "d"] {
+            assert!(detector.record_failure(user, "10.0.0.9").await.unwrap().is_none());
+        }
+        let assessment = detector.check_login("e", "10.0.0.9", None).await.unwrap();
+        assert_eq!(assessment.user_failures, 0);
+        assert!(assessment.require_captcha);
+        assert!(assessment.score >= IP_WEIGHT);
+
+        let lockout = detector.record_failure("e", "10.0.0.9").await.unwrap().unwrap();
+        assert_eq!(lockout.subject, ThreatSubject::Ip("10.0.0.9".to_string()));
+        assert!(detector.check_login("f", "10.0.0.9", None).await.is_err());
+        assert_eq!(detector.lockouts().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_lockout_is_released() {
+        let detector = ThreatDetector::new(ThreatConfig {
+            lockout_duration_secs: 0,
+            ..config()
+        });
+
+        for _ in 0..3 {
+            detector.record_failure("alice", "10.0.0.1").await.unwrap();
+        }
+
+        assert!(detector.check_login("alice", "10.0.0.1", None).await.is_ok());
+        assert!(detector.lockouts().await.is_empty());
+    }
+}
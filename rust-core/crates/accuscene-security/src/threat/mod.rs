@@ -2,8 +2,10 @@
 
 pub mod anomaly;
 pub mod brute_force;
+pub mod detector;
 pub mod rate_limiting;
 
 pub use anomaly::AnomalyDetector;
 pub use brute_force::BruteForceDetector;
+pub use detector::{Lockout, ThreatAssessment, ThreatDetector, ThreatSubject};
 pub use rate_limiting::RateLimiter;
@@ -36,6 +36,22 @@ pub enum AuditEvent {
         timestamp: DateTime<Utc>,
     },
 
+    /// Sessions ended at the request of the identity provider
+    RemoteLogout {
+        provider: String,
+        subject: Option<String>,
+        user_ids: Vec<String>,
+        session_ids: Vec<Uuid>,
+        timestamp: DateTime<Utc>,
+    },
+
+    /// Logout request from an identity provider refused
+    RemoteLogoutRejected {
+        provider: String,
+        reason: String,
+        timestamp: DateTime<Utc>,
+    },
+
     /// Token refreshed
     TokenRefreshed {
         user_id: String,
@@ -194,6 +210,7 @@ impl AuditTrail {
                 match &entry.event {
                     AuditEvent::LoginSucceeded { user_id: uid, .. } => uid == user_id,
                     AuditEvent::LoginFailed { .. } => false, // Don't include failed logins
+                    AuditEvent::RemoteLogout { user_ids, .. } => user_ids.iter().any(|uid| uid == user_id),
                     AuditEvent::TokenRefreshed { user_id: uid, .. } => uid == user_id,
                     AuditEvent::MFAEnrollmentStarted { user_id: uid, .. } => uid == user_id,
                     AuditEvent::MFAEnrollmentCompleted { user_id: uid, .. } => uid == user_id,
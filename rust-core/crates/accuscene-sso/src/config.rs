@@ -55,6 +55,14 @@ pub struct SessionConfig {
     /// Audience
     #[serde(default = "default_audience")]
     pub audience: String,
+
+    /// How long provider signing keys are cached, in seconds
+    #[serde(default = "default_jwks_cache_ttl")]
+    pub jwks_cache_ttl: u64,
+
+    /// Shortest time between key refetches for unknown key IDs, in seconds
+    #[serde(default = "default_jwks_min_refresh_interval")]
+    pub jwks_min_refresh_interval: u64,
 }
 
 fn default_access_token_expiry() -> i64 { 900 }
@@ -63,6 +71,8 @@ fn default_session_timeout() -> i64 { 86400 }
 fn default_max_sessions() -> usize { 5 }
 fn default_issuer() -> String { "accuscene-enterprise".to_string() }
 fn default_audience() -> String { "accuscene-users".to_string() }
+fn default_jwks_cache_ttl() -> u64 { 3600 }
+fn default_jwks_min_refresh_interval() -> u64 { 30 }
 
 /// MFA configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -108,16 +118,24 @@ fn default_mfa_grace_period() -> i64 { 300 }
 pub enum ProviderConfig {
     /// SAML 2.0 configuration
     SAML {
+        /// IdP metadata XML; when set, used instead of the entity ID, SSO URL
+        /// and certificate below
+        #[serde(default)]
+        idp_metadata: Option<String>,
+
         /// IdP entity ID
+        #[serde(default)]
         entity_id: String,
 
         /// IdP SSO URL
+        #[serde(default)]
         sso_url: String,
 
         /// IdP SLO URL
         slo_url: Option<String>,
 
-        /// IdP certificate (PEM format)
+        /// IdP signing certificate (PEM format)
+        #[serde(default)]
         idp_certificate: String,
 
         /// SP entity ID
@@ -126,6 +144,10 @@ pub enum ProviderConfig {
         /// SP ACS URL
         acs_url: String,
 
+        /// SP single logout URL; single logout is off when unset
+        #[serde(default)]
+        sp_slo_url: Option<String>,
+
         /// SP private key (PEM format)
         sp_private_key: String,
 
@@ -139,6 +161,14 @@ pub enum ProviderConfig {
         /// Want assertions signed
         #[serde(default = "default_true")]
         want_assertions_signed: bool,
+
+        /// Allowed clock difference to the IdP in seconds
+        #[serde(default = "default_saml_clock_skew")]
+        max_clock_skew: i64,
+
+        /// Assertion attributes read into the user profile
+        #[serde(default)]
+        attribute_mapping: SAMLAttributeMapping,
     },
 
     /// OpenID Connect configuration
@@ -164,6 +194,10 @@ pub enum ProviderConfig {
         /// JWKS URI
         jwks_uri: Option<String>,
 
+        /// Token introspection endpoint (RFC 7662) for opaque tokens
+        #[serde(default)]
+        introspection_endpoint: Option<String>,
+
         /// Scopes to request
         #[serde(default = "default_oidc_scopes")]
         scopes: Vec<String>,
@@ -190,6 +224,14 @@ pub enum ProviderConfig {
         /// UserInfo endpoint
         userinfo_endpoint: String,
 
+        /// Token introspection endpoint (RFC 7662)
+        #[serde(default)]
+        introspection_endpoint: Option<String>,
+
+        /// Device authorization endpoint (RFC 8628)
+        #[serde(default)]
+        device_authorization_endpoint: Option<String>,
+
         /// Scopes to request
         scopes: Vec<String>,
 
@@ -228,16 +270,29 @@ pub enum ProviderConfig {
         #[serde(default = "default_ldap_name_attr")]
         name_attribute: String,
 
-        /// Use TLS
+        /// First name attribute
+        #[serde(default = "default_ldap_given_name_attr")]
+        given_name_attribute: String,
+
+        /// Last name attribute
+        #[serde(default = "default_ldap_family_name_attr")]
+        family_name_attribute: String,
+
+        /// Use StartTLS on `ldap://` URLs; `ldaps://` URLs always use TLS
         #[serde(default = "default_true")]
         use_tls: bool,
 
-        /// TLS CA certificate
+        /// TLS CA certificate (PEM format), added to the system roots
         tls_ca_cert: Option<String>,
+
+        /// Group resolution and group-to-role mapping
+        #[serde(default)]
+        groups: LDAPGroupConfig,
     },
 }
 
 fn default_true() -> bool { true }
+fn default_saml_clock_skew() -> i64 { 180 }
 fn default_oidc_scopes() -> Vec<String> {
     vec!["openid".to_string(), "profile".to_string(), "email".to_string()]
 }
@@ -245,6 +300,111 @@ fn default_ldap_user_filter() -> String { "(objectClass=person)".to_string() }
 fn default_ldap_uid_attr() -> String { "uid".to_string() }
 fn default_ldap_email_attr() -> String { "mail".to_string() }
 fn default_ldap_name_attr() -> String { "cn".to_string() }
+fn default_ldap_given_name_attr() -> String { "givenName".to_string() }
+fn default_ldap_family_name_attr() -> String { "sn".to_string() }
+
+/// LDAP group resolution
+///
+/// Groups are found by searching for entries whose member attribute holds
+/// the user's DN and, when `nested` is set, the DNs of those groups in turn.
+/// On Active Directory the whole chain is resolved by the server with the
+/// `LDAP_MATCHING_RULE_IN_CHAIN` rule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LDAPGroupConfig {
+    /// Base DN for group search; groups are not resolved when unset
+    pub base_dn: Option<String>,
+
+    /// Group search filter
+    pub filter: String,
+
+    /// Group attribute holding member DNs
+    pub member_attribute: String,
+
+    /// Group name attribute
+    pub name_attribute: String,
+
+    /// Resolve groups nested in other groups
+    pub nested: bool,
+
+    /// Maximum nesting depth followed outside Active Directory
+    pub max_depth: usize,
+
+    /// Server is Active Directory
+    pub active_directory: bool,
+
+    /// Roles granted by group, keyed by group name or DN (case-insensitive)
+    pub role_mapping: HashMap<String, Vec<String>>,
+}
+
+impl Default for LDAPGroupConfig {
+    fn default() -> Self {
+        Self {
+            base_dn: None,
+            filter: "(|(objectClass=groupOfNames)(objectClass=group))".to_string(),
+            member_attribute: "member".to_string(),
+            name_attribute: "cn".to_string(),
+            nested: true,
+            max_depth: 10,
+            active_directory: false,
+            role_mapping: HashMap::new(),
+        }
+    }
+}
+
+/// SAML attribute names read into [`SSOUser`](crate::SSOUser) fields
+///
+/// Each field lists attribute names or friendly names in order of
+/// preference; the first one present in the assertion is used.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SAMLAttributeMapping {
+    /// Attribute holding a stable user ID; the subject NameID when unset
+    pub user_id: Option<String>,
+
+    /// Email address attributes
+    pub email: Vec<String>,
+
+    /// Display name attributes
+    pub name: Vec<String>,
+
+    /// First name attributes
+    pub given_name: Vec<String>,
+
+    /// Last name attributes
+    pub family_name: Vec<String>,
+}
+
+impl Default for SAMLAttributeMapping {
+    fn default() -> Self {
+        let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect();
+        Self {
+            user_id: None,
+            email: names(&[
+                "email",
+                "mail",
+                "urn:oid:0.9.2342.19200300.100.1.3",
+                "http://schemas.xmlsoap.org/ws/2005/05/identity/claims/emailaddress",
+            ]),
+            name: names(&[
+                "displayName",
+                "urn:oid:2.16.840.1.113730.3.1.241",
+                "http://schemas.xmlsoap.org/ws/2005/05/identity/claims/name",
+            ]),
+            given_name: names(&[
+                "givenName",
+                "urn:oid:2.5.4.42",
+                "http://schemas.xmlsoap.org/ws/2005/05/identity/claims/givenname",
+            ]),
+            family_name: names(&[
+                "sn",
+                "surname",
+                "urn:oid:2.5.4.4",
+                "http://schemas.xmlsoap.org/ws/2005/05/identity/claims/surname",
+            ]),
+        }
+    }
+}
 
 /// Redirect URI configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -311,6 +471,8 @@ impl Default for SSOConfig {
                 max_sessions_per_user: default_max_sessions(),
                 issuer: default_issuer(),
                 audience: default_audience(),
+                jwks_cache_ttl: default_jwks_cache_ttl(),
+                jwks_min_refresh_interval: default_jwks_min_refresh_interval(),
             },
             mfa: MFAConfig {
                 totp_enabled: true,
@@ -385,6 +547,7 @@ mod tests {
                 token_endpoint: None,
                 userinfo_endpoint: None,
                 jwks_uri: None,
+                introspection_endpoint: None,
                 scopes: default_oidc_scopes(),
                 use_pkce: true,
             }
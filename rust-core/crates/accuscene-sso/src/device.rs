@@ -0,0 +1,225 @@
+//! Device Authorization Grant (RFC 8628)
+//!
+//! Tablets and kiosks sign in without typing credentials: the device shows
+//! a short user code, the investigator enters it at the provider's
+//! verification page on a phone or laptop, and the device polls until the
+//! login completes. The provider's device code never leaves the server;
+//! devices poll with an opaque login ID, and polls arriving faster than the
+//! provider's interval are answered without asking the provider.
+
+use crate::{AuthenticationResult, SSOError, SSOResult, SSOUser};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::sync::Mutex;
+
+/// Grant type of device access token requests
+pub const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+fn default_interval() -> u64 { 5 }
+
+/// Device authorization issued by a provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceAuthorization {
+    /// Code the server polls with
+    pub device_code: String,
+
+    /// Code the user enters at the verification page
+    pub user_code: String,
+
+    /// Verification page
+    pub verification_uri: String,
+
+    /// Verification page with the user code filled in, for QR codes
+    #[serde(default)]
+    pub verification_uri_complete: Option<String>,
+
+    /// Lifetime of the codes in seconds
+    pub expires_in: u64,
+
+    /// Seconds to wait between polls
+    #[serde(default = "default_interval")]
+    pub interval: u64,
+}
+
+/// Outcome of one poll of a provider
+#[derive(Debug, Clone)]
+pub enum DevicePoll {
+    /// The user has not finished yet
+    Pending,
+
+    /// Polling too fast; wait longer between polls
+    SlowDown,
+
+    /// The user approved the login
+    Complete(SSOUser),
+}
+
+/// A device login to show on the device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceLogin {
+    /// ID the device polls with
+    pub login_id: String,
+
+    /// Code the user enters at the verification page
+    pub user_code: String,
+
+    /// Verification page
+    pub verification_uri: String,
+
+    /// Verification page with the user code filled in
+    pub verification_uri_complete: Option<String>,
+
+    /// When the codes expire
+    pub expires_at: DateTime<Utc>,
+
+    /// Seconds to wait between polls
+    pub interval: u64,
+}
+
+/// Status of a device login
+#[derive(Debug, Clone)]
+pub enum DeviceLoginStatus {
+    /// Not finished; poll again after `interval` seconds
+    Pending {
+        /// Seconds to wait before the next poll
+        interval: u64,
+    },
+
+    /// Signed in
+    Complete(Box<AuthenticationResult>),
+}
+
+/// A device login waiting for the user
+struct PendingDeviceLogin {
+    provider: String,
+    device_code: String,
+    interval: u64,
+    next_poll_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+}
+
+/// What a device poll may do now
+pub(crate) enum PollSlot {
+    /// Too early; answer pending without asking the provider
+    Wait(u64),
+
+    /// Ask the provider
+    Due {
+        provider: String,
+        device_code: String,
+        interval: u64,
+    },
+}
+
+/// Device logins waiting for their users
+#[derive(Default)]
+pub(crate) struct DeviceLogins {
+    pending: Mutex<HashMap<String, PendingDeviceLogin>>,
+}
+
+impl DeviceLogins {
+    /// Track a device login started with a provider
+    pub(crate) async fn insert(&self, login_id: &str, provider: &str, authorization: &DeviceAuthorization) -> DateTime<Utc> {
+        let now = Utc::now();
+        let expires_at = now + Duration::seconds(authorization.expires_in as i64);
+
+        let mut pending = self.pending.lock().await;
+        pending.retain(|_, login| login.expires_at > now);
+        pending.insert(login_id.to_string(), PendingDeviceLogin {
+            provider: provider.to_string(),
+            device_code: authorization.device_code.clone(),
+            interval: authorization.interval,
+            next_poll_at: now,
+            expires_at,
+        });
+
+        expires_at
+    }
+
+    /// Claim the next poll of a login
+    ///
+    /// A due poll pushes the next one back by the interval, so concurrent
+    /// polls from the same device reach the provider once.
+    pub(crate) async fn claim_poll(&self, login_id: &str) -> SSOResult<PollSlot> {
+        let now = Utc::now();
+        let mut pending = self.pending.lock().await;
+        let login = pending.get_mut(login_id).ok_or(SSOError::InvalidState)?;
+
+        if login.expires_at <= now {
+            pending.remove(login_id);
+            return Err(SSOError::TokenExpired);
+        }
+        if login.next_poll_at > now {
+            return Ok(PollSlot::Wait(login.interval));
+        }
+
+        login.next_poll_at = now + Duration::seconds(login.interval as i64);
+        Ok(PollSlot::Due {
+            provider: login.provider.clone(),
+            device_code: login.device_code.clone(),
+            interval: login.interval,
+        })
+    }
+
+    /// Lengthen the interval of a login by five seconds, as `slow_down` asks
+    pub(crate) async fn slow_down(&self, login_id: &str) -> u64 {
+        let mut pending = self.pending.lock().await;
+        match pending.get_mut(login_id) {
+            Some(login) => {
+                login.interval += 5;
+                login.next_poll_at = Utc::now() + Duration::seconds(login.interval as i64);
+                login.interval
+            }
+            None => default_interval(),
+        }
+    }
+
+    /// Stop tracking a login
+    pub(crate) async fn remove(&self, login_id: &str) {
+        self.pending.lock().await.remove(login_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn authorization(expires_in: u64) -> DeviceAuthorization {
+        serde_json::from_value(serde_json::json!({
+            "device_code": "GmRhmhcxhwAzkoEqiMEg_DnyEysNkuNhszIySk9eS",
+            "user_code": "WDJB-MJHT",
+            "verification_uri": "https://idp.example.com/device",
+            "expires_in": expires_in,
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_polls_paced_by_interval() {
+        let logins = DeviceLogins::default();
+        logins.insert("login", "okta", &authorization(600)).await;
+
+        assert!(matches!(
+            logins.claim_poll("login").await.unwrap(),
+            PollSlot::Due { interval: 5, ref device_code, .. } if device_code.starts_with("GmRh")
+        ));
+        // Polled again straight away: answered without the provider
+        assert!(matches!(logins.claim_poll("login").await.unwrap(), PollSlot::Wait(5)));
+
+        assert_eq!(logins.slow_down("login").await, 10);
+        assert!(matches!(logins.claim_poll("login").await.unwrap(), PollSlot::Wait(10)));
+
+        logins.remove("login").await;
+        assert!(matches!(logins.claim_poll("login").await, Err(SSOError::InvalidState)));
+    }
+
+    #[tokio::test]
+    async fn test_expired_login_rejected() {
+        let logins = DeviceLogins::default();
+        logins.insert("login", "okta", &authorization(0)).await;
+
+        assert!(matches!(logins.claim_poll("login").await, Err(SSOError::TokenExpired)));
+        assert!(matches!(logins.claim_poll("login").await, Err(SSOError::InvalidState)));
+    }
+}
@@ -144,6 +144,13 @@ impl From<base64::DecodeError> for SSOError {
     }
 }
 
+#[cfg(feature = "redis")]
+impl From<redis::RedisError> for SSOError {
+    fn from(err: redis::RedisError) -> Self {
+        SSOError::DatabaseError(format!("Redis error: {}", err))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
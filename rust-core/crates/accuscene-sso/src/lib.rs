@@ -8,6 +8,11 @@
 //! - Multi-factor Authentication (TOTP, WebAuthn)
 //! - Secure session management with JWT
 //! - PKCE flow for enhanced security
+//! - One-time, expiring state parameters bound to the login request
+//! - Single logout (OIDC back-channel logout, SAML SLO)
+//! - Provider token validation with cached, rotating signing keys and
+//!   token introspection
+//! - Device authorization grant for tablet and kiosk logins
 //! - Complete audit trail
 
 #![warn(missing_docs)]
@@ -15,20 +20,32 @@
 
 pub mod audit;
 pub mod config;
+pub mod device;
 pub mod error;
+pub mod logout;
 pub mod mfa;
 pub mod providers;
 pub mod session;
+pub mod state;
 
 pub use audit::{AuditEvent, AuditLogger, AuditTrail};
-pub use config::{SSOConfig, ProviderConfig, SessionConfig, MFAConfig};
+pub use config::{SSOConfig, ProviderConfig, SessionConfig, MFAConfig, SAMLAttributeMapping, LDAPGroupConfig};
+pub use device::{DeviceAuthorization, DeviceLogin, DeviceLoginStatus, DevicePoll};
 pub use error::{SSOError, SSOResult};
-pub use session::{SessionManager, TokenManager, RefreshTokenManager};
+pub use logout::RemoteLogout;
+pub use session::{Session, SessionManager, TokenManager, RefreshTokenManager, JwksCache, JwksCacheMetrics, TokenIntrospector};
+pub use state::{AuthorizationState, MemoryStateStore, StateStore};
+#[cfg(feature = "redis")]
+pub use state::RedisStateStore;
 
 use async_trait::async_trait;
+use device::{DeviceLogins, PollSlot};
+use logout::ReplayCache;
+use oauth2::PkceCodeChallenge;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use uuid::Uuid;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 
 /// User information from SSO provider
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +68,41 @@ pub struct SSOUser {
     pub provider: String,
 }
 
+impl SSOUser {
+    /// Roles granted through the provider's group-to-role mapping
+    pub fn roles(&self) -> Vec<String> {
+        string_list(&self.metadata["roles"])
+    }
+
+    /// All roles the provider's mapping can grant
+    ///
+    /// Roles in this list but not in [`roles`](Self::roles) should be
+    /// revoked; other roles are not the provider's to manage.
+    pub fn managed_roles(&self) -> Vec<String> {
+        string_list(&self.metadata["managed_roles"])
+    }
+
+    /// Subject the identity provider knows the user by
+    pub fn idp_subject(&self) -> Option<String> {
+        match self.metadata["idp_subject"].as_str() {
+            Some(subject) => Some(subject.to_string()),
+            None => Some(self.id.clone()),
+        }
+    }
+
+    /// Identity provider session the login belongs to
+    pub fn idp_session_id(&self) -> Option<String> {
+        self.metadata["idp_session_id"].as_str().map(str::to_string)
+    }
+}
+
+fn string_list(value: &serde_json::Value) -> Vec<String> {
+    value
+        .as_array()
+        .map(|values| values.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
 /// Authentication result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthenticationResult {
@@ -75,11 +127,52 @@ pub struct AuthenticationResult {
 /// SSO provider trait
 #[async_trait]
 pub trait SSOProvider: Send + Sync {
-    /// Get authorization URL for OAuth flow
-    async fn get_authorization_url(&self, state: &str, nonce: Option<&str>) -> SSOResult<String>;
+    /// Get authorization URL for a pending login
+    async fn get_authorization_url(&self, request: &AuthorizationState) -> SSOResult<String>;
 
     /// Exchange authorization code for tokens
-    async fn exchange_code(&self, code: &str, state: &str) -> SSOResult<SSOUser>;
+    async fn exchange_code(&self, code: &str, request: &AuthorizationState) -> SSOResult<SSOUser>;
+
+    /// Whether logins need a PKCE code verifier
+    fn uses_pkce(&self) -> bool {
+        false
+    }
+
+    /// Verify an OIDC back-channel logout token
+    async fn verify_logout_token(&self, _logout_token: &str) -> SSOResult<RemoteLogout> {
+        Err(SSOError::ConfigError(format!("{} does not support back-channel logout", self.name())))
+    }
+
+    /// URL ending the provider session of a local session that logged out,
+    /// or `None` if the provider has no single logout
+    async fn logout_url(&self, _session: &Session, _request: &AuthorizationState) -> SSOResult<Option<String>> {
+        Ok(None)
+    }
+
+    /// Verify the provider's answer to a [`logout_url`](Self::logout_url) request
+    async fn verify_logout_response(&self, _query: &str, _request: &AuthorizationState) -> SSOResult<()> {
+        Err(SSOError::ConfigError(format!("{} does not support single logout", self.name())))
+    }
+
+    /// Verify a logout request the provider sent through the browser
+    async fn parse_logout_request(&self, _query: &str) -> SSOResult<RemoteLogout> {
+        Err(SSOError::ConfigError(format!("{} does not support single logout", self.name())))
+    }
+
+    /// URL answering a logout request from [`parse_logout_request`](Self::parse_logout_request)
+    async fn logout_response_url(&self, _logout: &RemoteLogout) -> SSOResult<String> {
+        Err(SSOError::ConfigError(format!("{} does not support single logout", self.name())))
+    }
+
+    /// Start a device authorization grant (RFC 8628)
+    async fn start_device_authorization(&self) -> SSOResult<DeviceAuthorization> {
+        Err(SSOError::ConfigError(format!("{} does not support device logins", self.name())))
+    }
+
+    /// Ask whether the user has finished a device authorization
+    async fn poll_device_authorization(&self, _device_code: &str) -> SSOResult<DevicePoll> {
+        Err(SSOError::ConfigError(format!("{} does not support device logins", self.name())))
+    }
 
     /// Validate and decode token
     async fn validate_token(&self, token: &str) -> SSOResult<SSOUser>;
@@ -96,6 +189,9 @@ pub struct SSOManager {
     config: SSOConfig,
     session_manager: SessionManager,
     audit_logger: AuditLogger,
+    state_store: Arc<dyn StateStore>,
+    logout_replays: ReplayCache,
+    device_logins: DeviceLogins,
 }
 
 impl SSOManager {
@@ -104,13 +200,37 @@ impl SSOManager {
         Self {
             session_manager: SessionManager::new(config.session.clone()),
             audit_logger: AuditLogger::new(),
+            state_store: Arc::new(MemoryStateStore::new()),
+            logout_replays: ReplayCache::new(),
+            device_logins: DeviceLogins::default(),
             config,
         }
     }
 
+    /// Keep pending login states in another store
+    pub fn with_state_store(mut self, store: Arc<dyn StateStore>) -> Self {
+        self.state_store = store;
+        self
+    }
+
+    /// Get the audit trail
+    pub fn audit_trail(&self) -> Arc<AuditTrail> {
+        self.audit_logger.trail()
+    }
+
     /// Get SSO provider by name
     pub fn get_provider(&self, name: &str) -> SSOResult<Box<dyn SSOProvider>> {
-        providers::get_provider(&self.config, name)
+        providers::get_provider_with_tokens(&self.config, name, self.session_manager.token_manager())
+    }
+
+    /// Validate a token issued by a provider (access token or ID token)
+    pub async fn validate_provider_token(&self, provider_name: &str, token: &str) -> SSOResult<SSOUser> {
+        self.get_provider(provider_name)?.validate_token(token).await
+    }
+
+    /// Get the counters of the provider signing key cache
+    pub async fn jwks_metrics(&self) -> JwksCacheMetrics {
+        self.session_manager.token_manager().jwks_metrics().await
     }
 
     /// Initiate SSO login
@@ -120,10 +240,11 @@ impl SSOManager {
         redirect_uri: &str,
     ) -> SSOResult<String> {
         let provider = self.get_provider(provider_name)?;
-        let state = self.generate_state();
-        let nonce = Some(self.generate_nonce());
+        self.verify_redirect_uri(redirect_uri)?;
 
-        let auth_url = provider.get_authorization_url(&state, nonce.as_deref()).await?;
+        let request = self.new_request(provider_name, redirect_uri, provider.uses_pkce());
+        let auth_url = provider.get_authorization_url(&request).await?;
+        self.store_request(&request).await?;
 
         self.audit_logger.log(AuditEvent::LoginInitiated {
             provider: provider_name.to_string(),
@@ -142,11 +263,11 @@ impl SSOManager {
     ) -> SSOResult<AuthenticationResult> {
         let provider = self.get_provider(provider_name)?;
 
-        // Verify state to prevent CSRF
-        self.verify_state(state)?;
+        // Verify state to prevent CSRF; each state is accepted once
+        let request = self.verify_state(provider_name, state).await?;
 
         // Exchange code for user info
-        let user = provider.exchange_code(code, state).await?;
+        let user = provider.exchange_code(code, &request).await?;
 
         // Create session
         let result = self.session_manager.create_session(&user).await?;
@@ -160,6 +281,74 @@ impl SSOManager {
         Ok(result)
     }
 
+    /// Start a device login for a tablet or kiosk
+    ///
+    /// Show the user code and verification URI on the device, then call
+    /// [`poll_device_login`](Self::poll_device_login) every `interval`
+    /// seconds until the user has signed in on another device.
+    pub async fn start_device_login(&self, provider_name: &str) -> SSOResult<DeviceLogin> {
+        let provider = self.get_provider(provider_name)?;
+        let authorization = provider.start_device_authorization().await?;
+
+        let login_id = self.generate_state();
+        let expires_at = self.device_logins.insert(&login_id, provider_name, &authorization).await;
+
+        self.audit_logger.log(AuditEvent::LoginInitiated {
+            provider: provider_name.to_string(),
+            timestamp: Utc::now(),
+        }).await;
+
+        Ok(DeviceLogin {
+            login_id,
+            user_code: authorization.user_code,
+            verification_uri: authorization.verification_uri,
+            verification_uri_complete: authorization.verification_uri_complete,
+            expires_at,
+            interval: authorization.interval,
+        })
+    }
+
+    /// Check on a device login, creating the session once the user approves
+    pub async fn poll_device_login(&self, login_id: &str) -> SSOResult<DeviceLoginStatus> {
+        let (provider_name, device_code, interval) = match self.device_logins.claim_poll(login_id).await? {
+            PollSlot::Wait(interval) => return Ok(DeviceLoginStatus::Pending { interval }),
+            PollSlot::Due { provider, device_code, interval } => (provider, device_code, interval),
+        };
+
+        let poll = match self.get_provider(&provider_name) {
+            Ok(provider) => provider.poll_device_authorization(&device_code).await,
+            Err(err) => Err(err),
+        };
+
+        let user = match poll {
+            Ok(DevicePoll::Pending) => return Ok(DeviceLoginStatus::Pending { interval }),
+            Ok(DevicePoll::SlowDown) => {
+                let interval = self.device_logins.slow_down(login_id).await;
+                return Ok(DeviceLoginStatus::Pending { interval });
+            }
+            Ok(DevicePoll::Complete(user)) => user,
+            Err(err) => {
+                // Network trouble may pass; anything else ends the login
+                if !matches!(err, SSOError::NetworkError(_)) {
+                    self.device_logins.remove(login_id).await;
+                    self.log_state_failure(&provider_name, &format!("Device login failed: {}", err)).await;
+                }
+                return Err(err);
+            }
+        };
+        self.device_logins.remove(login_id).await;
+
+        let result = self.session_manager.create_session(&user).await?;
+
+        self.audit_logger.log(AuditEvent::LoginSucceeded {
+            user_id: user.id.clone(),
+            provider: provider_name,
+            timestamp: Utc::now(),
+        }).await;
+
+        Ok(DeviceLoginStatus::Complete(Box::new(result)))
+    }
+
     /// Validate session token
     pub async fn validate_session(&self, token: &str) -> SSOResult<SSOUser> {
         self.session_manager.validate_token(token).await
@@ -182,6 +371,134 @@ impl SSOManager {
         Ok(())
     }
 
+    /// Log out a session and end it at the identity provider too, where the
+    /// provider supports single logout
+    ///
+    /// Returns the URL to send the browser to, or `None` when logging out
+    /// locally was all there was to do. The provider returns to
+    /// [`handle_logout_response`](Self::handle_logout_response), which
+    /// redirects to `return_to`.
+    pub async fn initiate_logout(&self, session_id: &Uuid, return_to: &str) -> SSOResult<Option<String>> {
+        self.verify_redirect_uri(return_to)?;
+        let session = self.session_manager.get_session(session_id).await?;
+        self.logout(session_id).await?;
+
+        // Sessions of providers since removed can only end locally
+        let Ok(provider) = self.get_provider(&session.provider) else {
+            return Ok(None);
+        };
+
+        let request = self.new_request(&session.provider, return_to, false);
+        let Some(url) = provider.logout_url(&session, &request).await? else {
+            return Ok(None);
+        };
+        self.store_request(&request).await?;
+
+        Ok(Some(url))
+    }
+
+    /// Complete a single logout started by [`initiate_logout`](Self::initiate_logout)
+    ///
+    /// Takes the query string the provider returned with and gives back the
+    /// URL to redirect to.
+    pub async fn handle_logout_response(&self, provider_name: &str, query: &str) -> SSOResult<String> {
+        let provider = self.get_provider(provider_name)?;
+        let relay_state = url::form_urlencoded::parse(query.trim_start_matches('?').as_bytes())
+            .find(|(name, _)| name == "RelayState")
+            .map(|(_, value)| value.into_owned())
+            .ok_or(SSOError::InvalidState)?;
+
+        let request = self.verify_state(provider_name, &relay_state).await?;
+        provider.verify_logout_response(query, &request).await?;
+
+        Ok(request.redirect_uri)
+    }
+
+    /// Handle an OIDC back-channel logout token posted by a provider
+    ///
+    /// Returns the number of sessions ended.
+    pub async fn handle_backchannel_logout(&self, provider_name: &str, logout_token: &str) -> SSOResult<usize> {
+        let provider = self.get_provider(provider_name)?;
+        let logout = match provider.verify_logout_token(logout_token).await {
+            Ok(logout) => logout,
+            Err(err) => {
+                self.log_remote_logout_rejected(provider_name, &err.to_string()).await;
+                return Err(err);
+            }
+        };
+
+        Ok(self.end_remote_sessions(&logout).await?.len())
+    }
+
+    /// Handle a SAML LogoutRequest the provider sent through the browser
+    ///
+    /// Takes the query string of the request and returns the URL carrying
+    /// the LogoutResponse back to the provider.
+    pub async fn handle_logout_request(&self, provider_name: &str, query: &str) -> SSOResult<String> {
+        let provider = self.get_provider(provider_name)?;
+        let logout = match provider.parse_logout_request(query).await {
+            Ok(logout) => logout,
+            Err(err) => {
+                self.log_remote_logout_rejected(provider_name, &err.to_string()).await;
+                return Err(err);
+            }
+        };
+
+        self.end_remote_sessions(&logout).await?;
+        provider.logout_response_url(&logout).await
+    }
+
+    /// End the sessions a verified logout message names, once per message
+    async fn end_remote_sessions(&self, logout: &RemoteLogout) -> SSOResult<Vec<Session>> {
+        if !self.logout_replays.insert(&logout.provider, &logout.message_id, logout.expires_at).await {
+            self.log_remote_logout_rejected(&logout.provider, "Replayed logout message").await;
+            return Err(SSOError::TokenValidationFailed("Logout message already processed".to_string()));
+        }
+
+        let ended = self.session_manager.invalidate_remote_sessions(logout).await;
+
+        self.audit_logger.log(AuditEvent::RemoteLogout {
+            provider: logout.provider.clone(),
+            subject: logout.subject.clone(),
+            user_ids: ended.iter().map(|session| session.user_id.clone()).collect(),
+            session_ids: ended.iter().map(|session| session.id).collect(),
+            timestamp: Utc::now(),
+        }).await;
+
+        Ok(ended)
+    }
+
+    async fn log_remote_logout_rejected(&self, provider_name: &str, reason: &str) {
+        self.audit_logger.log(AuditEvent::RemoteLogoutRejected {
+            provider: provider_name.to_string(),
+            reason: reason.to_string(),
+            timestamp: Utc::now(),
+        }).await;
+    }
+
+    fn new_request(&self, provider_name: &str, redirect_uri: &str, pkce: bool) -> AuthorizationState {
+        let now = Utc::now();
+        AuthorizationState {
+            state: self.generate_state(),
+            provider: provider_name.to_string(),
+            redirect_uri: redirect_uri.to_string(),
+            nonce: self.generate_nonce(),
+            pkce_verifier: pkce.then(|| self.generate_pkce_verifier()),
+            created_at: now,
+            expires_at: now + Duration::seconds(self.state_ttl()),
+        }
+    }
+
+    async fn store_request(&self, request: &AuthorizationState) -> SSOResult<()> {
+        self.state_store
+            .insert(request, std::time::Duration::from_secs(self.state_ttl() as u64))
+            .await
+    }
+
+    fn state_ttl(&self) -> i64 {
+        self.config.security.state_token_expiry.max(1)
+    }
+
     fn generate_state(&self) -> String {
         Uuid::new_v4().to_string()
     }
@@ -190,9 +507,50 @@ impl SSOManager {
         Uuid::new_v4().to_string()
     }
 
-    fn verify_state(&self, _state: &str) -> SSOResult<()> {
-        // In production, verify state against stored value
-        // For now, accept any state
+    fn generate_pkce_verifier(&self) -> String {
+        let (_, verifier) = PkceCodeChallenge::new_random_sha256();
+        verifier.secret().to_string()
+    }
+
+    /// Take the pending login for a state, refusing unknown, expired, reused
+    /// and cross-provider states
+    async fn verify_state(&self, provider_name: &str, state: &str) -> SSOResult<AuthorizationState> {
+        let Some(request) = self.state_store.take(state).await? else {
+            self.log_state_failure(provider_name, "Unknown, expired or reused state").await;
+            return Err(SSOError::InvalidState);
+        };
+
+        if request.provider != provider_name {
+            self.log_state_failure(provider_name, "State issued for another provider").await;
+            return Err(SSOError::CSRFTokenMismatch);
+        }
+
+        Ok(request)
+    }
+
+    async fn log_state_failure(&self, provider_name: &str, reason: &str) {
+        self.audit_logger.log(AuditEvent::LoginFailed {
+            reason: reason.to_string(),
+            provider: provider_name.to_string(),
+            timestamp: Utc::now(),
+        }).await;
+    }
+
+    /// Only redirect to the configured domains and their subdomains
+    fn verify_redirect_uri(&self, redirect_uri: &str) -> SSOResult<()> {
+        let url = url::Url::parse(redirect_uri).map_err(|_| SSOError::InvalidRedirectURI)?;
+        if !matches!(url.scheme(), "https" | "http") {
+            return Err(SSOError::InvalidRedirectURI);
+        }
+
+        let host = url.host_str().ok_or(SSOError::InvalidRedirectURI)?;
+        let allowed = self.config.security.allowed_redirect_domains.iter().any(|domain| {
+            host == domain || host.ends_with(&format!(".{}", domain))
+        });
+        if !allowed {
+            return Err(SSOError::InvalidRedirectURI);
+        }
+
         Ok(())
     }
 }
@@ -220,4 +578,105 @@ mod tests {
         assert_eq!(user.id, deserialized.id);
         assert_eq!(user.email, deserialized.email);
     }
+
+    fn ldap_config() -> ProviderConfig {
+        ProviderConfig::LDAP {
+            url: "ldap://127.0.0.1:1".to_string(),
+            bind_dn: "cn=admin,dc=example,dc=com".to_string(),
+            bind_password: "secret".to_string(),
+            user_base_dn: "ou=people,dc=example,dc=com".to_string(),
+            user_filter: "(objectClass=person)".to_string(),
+            uid_attribute: "uid".to_string(),
+            email_attribute: "mail".to_string(),
+            name_attribute: "cn".to_string(),
+            given_name_attribute: "givenName".to_string(),
+            family_name_attribute: "sn".to_string(),
+            use_tls: false,
+            tls_ca_cert: None,
+            groups: LDAPGroupConfig::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_callback_state_checked() {
+        let mut config = SSOConfig::default();
+        config.providers.insert("corp".to_string(), ldap_config());
+        config.providers.insert("partner".to_string(), ldap_config());
+        let manager = SSOManager::new(config);
+
+        assert!(matches!(
+            manager.initiate_login("corp", "https://evil.example.net/callback").await,
+            Err(SSOError::InvalidRedirectURI)
+        ));
+
+        let url = manager
+            .initiate_login("corp", "http://app.localhost/auth/callback")
+            .await
+            .unwrap();
+        let state = url.split("state=").nth(1).unwrap().to_string();
+
+        assert!(matches!(
+            manager.handle_callback("corp", "code", "forged").await,
+            Err(SSOError::InvalidState)
+        ));
+        assert!(matches!(
+            manager.handle_callback("partner", "code", &state).await,
+            Err(SSOError::CSRFTokenMismatch)
+        ));
+        // The state was consumed by the rejected callback
+        assert!(matches!(
+            manager.handle_callback("corp", "code", &state).await,
+            Err(SSOError::InvalidState)
+        ));
+    }
+
+    fn okta_user(subject: &str, idp_session_id: &str) -> SSOUser {
+        SSOUser {
+            id: subject.to_string(),
+            email: format!("{}@example.com", subject),
+            name: None,
+            given_name: None,
+            family_name: None,
+            picture: None,
+            metadata: serde_json::json!({ "idp_subject": subject, "idp_session_id": idp_session_id }),
+            provider: "okta".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remote_logout_ends_matching_sessions() {
+        let manager = SSOManager::new(SSOConfig::default());
+        let ended = manager.session_manager.create_session(&okta_user("alice", "op-1")).await.unwrap();
+        let kept = manager.session_manager.create_session(&okta_user("alice", "op-2")).await.unwrap();
+
+        let logout = RemoteLogout {
+            provider: "okta".to_string(),
+            subject: Some("alice".to_string()),
+            session_ids: vec!["op-1".to_string()],
+            message_id: "jti-1".to_string(),
+            relay_state: None,
+            expires_at: Utc::now() + Duration::minutes(5),
+        };
+        let sessions = manager.end_remote_sessions(&logout).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id, ended.session_id);
+
+        // Access tokens of the ended session stop validating at once
+        assert!(manager.validate_session(&ended.access_token).await.is_err());
+        assert!(manager.validate_session(&kept.access_token).await.is_ok());
+
+        // The same logout message is accepted once
+        assert!(manager.end_remote_sessions(&logout).await.is_err());
+
+        let trail = manager.audit_trail();
+        let logs = trail.get_user_logs("alice").await;
+        assert!(logs.iter().any(|entry| matches!(
+            &entry.event,
+            AuditEvent::RemoteLogout { session_ids, .. } if session_ids == &vec![ended.session_id]
+        )));
+        assert!(trail.get_recent_logs(10).await.iter().any(|entry| matches!(
+            entry.event,
+            AuditEvent::RemoteLogoutRejected { .. }
+        )));
+    }
 }
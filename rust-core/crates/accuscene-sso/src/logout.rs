@@ -0,0 +1,290 @@
+//! Single Logout
+//!
+//! Identity providers end the sessions they started in two ways. OIDC
+//! back-channel logout posts a signed logout token straight to the relying
+//! party; SAML single logout sends a LogoutRequest through the browser.
+//! Providers turn either into a [`RemoteLogout`] naming the IdP subject,
+//! the IdP sessions, or both, and the [`SSOManager`](crate::SSOManager) ends
+//! every local session it matches.
+//!
+//! Each logout message carries an ID (`jti` or the LogoutRequest `ID`) that
+//! is remembered until the message expires, so a captured message cannot
+//! be replayed.
+
+use crate::{session::Session, SSOError, SSOResult};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use jsonwebtoken::{decode, decode_header, jwk::JwkSet, DecodingKey, Validation};
+use serde::Deserialize;
+use std::collections::HashMap;
+use tokio::sync::Mutex;
+
+/// Event type a back-channel logout token must carry
+pub const BACKCHANNEL_LOGOUT_EVENT: &str = "http://schemas.openid.net/event/backchannel-logout";
+
+/// How long after issue a logout token without `exp` is accepted, in seconds
+pub const LOGOUT_TOKEN_MAX_AGE: i64 = 300;
+
+/// Sessions an identity provider asked to end
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteLogout {
+    /// Provider the request came from
+    pub provider: String,
+
+    /// IdP subject (`sub` or NameID); all its sessions end when no session
+    /// IDs are given
+    pub subject: Option<String>,
+
+    /// IdP session IDs (`sid` or SessionIndex)
+    pub session_ids: Vec<String>,
+
+    /// ID of the logout message, used to refuse replays
+    pub message_id: String,
+
+    /// RelayState to return with the logout response
+    pub relay_state: Option<String>,
+
+    /// When the message stops being accepted
+    pub expires_at: DateTime<Utc>,
+}
+
+impl RemoteLogout {
+    /// Check whether a local session is one the provider asked to end
+    pub fn matches(&self, session: &Session) -> bool {
+        if session.provider != self.provider {
+            return false;
+        }
+        if self.subject.is_none() && self.session_ids.is_empty() {
+            return false;
+        }
+
+        let subject_matches = match &self.subject {
+            Some(subject) => session.idp_subject.as_ref() == Some(subject),
+            None => true,
+        };
+        let session_matches = self.session_ids.is_empty()
+            || session
+                .idp_session_id
+                .as_ref()
+                .is_some_and(|id| self.session_ids.contains(id));
+
+        subject_matches && session_matches
+    }
+}
+
+/// Claims of an OIDC back-channel logout token
+#[derive(Debug, Deserialize)]
+struct LogoutTokenClaims {
+    iat: i64,
+    exp: Option<i64>,
+    jti: String,
+    sub: Option<String>,
+    sid: Option<String>,
+    events: serde_json::Map<String, serde_json::Value>,
+    nonce: Option<serde_json::Value>,
+}
+
+/// Verify an OIDC back-channel logout token
+///
+/// Checks the signature against the provider's keys, the issuer and
+/// audience, the issue time, the logout event, that a `sub` or `sid` is
+/// present and that no `nonce` is (which would make it an ID token).
+pub fn verify_logout_token(
+    provider: &str,
+    token: &str,
+    keys: &JwkSet,
+    issuer: &str,
+    client_id: &str,
+) -> SSOResult<RemoteLogout> {
+    let header = decode_header(token)?;
+    let jwk = match header.kid.as_deref() {
+        Some(kid) => keys.find(kid),
+        None if keys.keys.len() == 1 => keys.keys.first(),
+        None => None,
+    }
+    .ok_or_else(|| SSOError::TokenValidationFailed("Unknown logout token signing key".to_string()))?;
+
+    let mut validation = Validation::new(header.alg);
+    validation.set_issuer(&[issuer]);
+    validation.set_audience(&[client_id]);
+    validation.set_required_spec_claims(&["iss", "aud"]);
+
+    let claims = decode::<LogoutTokenClaims>(token, &DecodingKey::from_jwk(jwk)?, &validation)?.claims;
+
+    let now = Utc::now();
+    let issued_at = timestamp(claims.iat)?;
+    if issued_at > now + Duration::seconds(60) {
+        return Err(SSOError::TokenValidationFailed("Logout token issued in the future".to_string()));
+    }
+    let expires_at = match claims.exp {
+        Some(exp) => timestamp(exp)?,
+        None => issued_at + Duration::seconds(LOGOUT_TOKEN_MAX_AGE),
+    };
+    if expires_at <= now {
+        return Err(SSOError::TokenExpired);
+    }
+
+    if !claims.events.get(BACKCHANNEL_LOGOUT_EVENT).is_some_and(|event| event.is_object()) {
+        return Err(SSOError::TokenValidationFailed("Not a back-channel logout token".to_string()));
+    }
+    if claims.nonce.is_some() {
+        return Err(SSOError::TokenValidationFailed("Logout token must not contain a nonce".to_string()));
+    }
+    if claims.sub.is_none() && claims.sid.is_none() {
+        return Err(SSOError::TokenValidationFailed("Logout token names no subject or session".to_string()));
+    }
+
+    Ok(RemoteLogout {
+        provider: provider.to_string(),
+        subject: claims.sub,
+        session_ids: claims.sid.into_iter().collect(),
+        message_id: claims.jti,
+        relay_state: None,
+        expires_at,
+    })
+}
+
+fn timestamp(seconds: i64) -> SSOResult<DateTime<Utc>> {
+    Utc.timestamp_opt(seconds, 0)
+        .single()
+        .ok_or_else(|| SSOError::TokenValidationFailed("Invalid timestamp".to_string()))
+}
+
+/// Logout message IDs seen before they expire
+#[derive(Default)]
+pub struct ReplayCache {
+    seen: Mutex<HashMap<String, DateTime<Utc>>>,
+}
+
+impl ReplayCache {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remember a message ID, returning `false` if it was already seen
+    pub async fn insert(&self, provider: &str, message_id: &str, expires_at: DateTime<Utc>) -> bool {
+        let now = Utc::now();
+        let mut seen = self.seen.lock().await;
+        seen.retain(|_, expiry| *expiry > now);
+
+        let key = format!("{}:{}", provider, message_id);
+        if seen.contains_key(&key) {
+            return false;
+        }
+        seen.insert(key, expires_at);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use uuid::Uuid;
+
+    const SECRET: &[u8] = b"back-channel-logout-test-secret";
+
+    fn keys() -> JwkSet {
+        serde_json::from_value(serde_json::json!({
+            "keys": [{ "kty": "oct", "kid": "k1", "alg": "HS256", "k": "YmFjay1jaGFubmVsLWxvZ291dC10ZXN0LXNlY3JldA" }]
+        }))
+        .unwrap()
+    }
+
+    fn token(claims: serde_json::Value) -> String {
+        let mut header = Header::new(jsonwebtoken::Algorithm::HS256);
+        header.kid = Some("k1".to_string());
+        encode(&header, &claims, &EncodingKey::from_secret(SECRET)).unwrap()
+    }
+
+    fn claims() -> serde_json::Value {
+        serde_json::json!({
+            "iss": "https://op.example.com",
+            "aud": "client",
+            "iat": Utc::now().timestamp(),
+            "jti": "logout-1",
+            "sid": "op-session",
+            "events": { BACKCHANNEL_LOGOUT_EVENT: {} },
+        })
+    }
+
+    fn verify(claims: serde_json::Value) -> SSOResult<RemoteLogout> {
+        verify_logout_token("okta", &token(claims), &keys(), "https://op.example.com", "client")
+    }
+
+    fn session(subject: &str, sid: &str) -> Session {
+        let now = Utc::now();
+        Session {
+            id: Uuid::new_v4(),
+            user_id: subject.to_string(),
+            email: "user@example.com".to_string(),
+            provider: "okta".to_string(),
+            idp_subject: Some(subject.to_string()),
+            idp_session_id: Some(sid.to_string()),
+            created_at: now,
+            last_accessed: now,
+            expires_at: now + Duration::hours(1),
+            ip_address: None,
+            user_agent: None,
+            mfa_verified: false,
+            metadata: serde_json::json!({}),
+        }
+    }
+
+    #[test]
+    fn test_logout_token_verified() {
+        let logout = verify(claims()).unwrap();
+        assert_eq!(logout.session_ids, vec!["op-session".to_string()]);
+        assert_eq!(logout.message_id, "logout-1");
+
+        assert!(logout.matches(&session("alice", "op-session")));
+        assert!(!logout.matches(&session("alice", "other-session")));
+    }
+
+    #[test]
+    fn test_invalid_logout_tokens_rejected() {
+        let mut no_event = claims();
+        no_event["events"] = serde_json::json!({});
+        assert!(verify(no_event).is_err());
+
+        let mut id_token = claims();
+        id_token["nonce"] = serde_json::json!("n");
+        assert!(verify(id_token).is_err());
+
+        let mut anonymous = claims();
+        anonymous.as_object_mut().unwrap().remove("sid");
+        assert!(verify(anonymous).is_err());
+
+        let mut other_audience = claims();
+        other_audience["aud"] = serde_json::json!("someone-else");
+        assert!(verify(other_audience).is_err());
+
+        let mut stale = claims();
+        stale["iat"] = serde_json::json!(Utc::now().timestamp() - LOGOUT_TOKEN_MAX_AGE - 10);
+        assert!(verify(stale).is_err());
+
+        let forged = encode(
+            &Header::new(jsonwebtoken::Algorithm::HS256),
+            &claims(),
+            &EncodingKey::from_secret(b"not-the-provider-key"),
+        )
+        .unwrap();
+        assert!(verify_logout_token("okta", &forged, &keys(), "https://op.example.com", "client").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_subject_logout_and_replay() {
+        let mut claims = claims();
+        claims.as_object_mut().unwrap().remove("sid");
+        claims["sub"] = serde_json::json!("alice");
+        let logout = verify(claims).unwrap();
+
+        assert!(logout.matches(&session("alice", "a")));
+        assert!(logout.matches(&session("alice", "b")));
+        assert!(!logout.matches(&session("bob", "a")));
+
+        let cache = ReplayCache::new();
+        assert!(cache.insert("okta", &logout.message_id, logout.expires_at).await);
+        assert!(!cache.insert("okta", &logout.message_id, logout.expires_at).await);
+    }
+}
@@ -1,9 +1,21 @@
 //! LDAP/Active Directory Provider Implementation
-
-use crate::{SSOProvider, SSOUser, SSOError, SSOResult, AuthenticationResult, config::ProviderConfig};
+//!
+//! Users are found with the service account and authenticated by binding
+//! as their own DN. Connections use TLS: `ldaps://` URLs directly, `ldap://`
+//! URLs through StartTLS unless `use_tls` is off.
+//!
+//! When a group base DN is configured, the user's groups, including nested
+//! ones, are resolved and mapped to roles. Groups and roles are returned in
+//! the user's metadata; see [`SSOUser::roles`].
+
+use crate::{SSOProvider, SSOUser, SSOError, SSOResult, AuthenticationResult, AuthorizationState, config::{LDAPGroupConfig, ProviderConfig}};
 use async_trait::async_trait;
-use ldap3::{LdapConn, LdapConnAsync, Scope, SearchEntry};
+use ldap3::{ldap_escape, Ldap, LdapConnAsync, LdapConnSettings, Scope, SearchEntry};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+
+/// Active Directory rule matching members at any depth of nesting
+const MATCHING_RULE_IN_CHAIN: &str = "1.2.840.113556.1.4.1941";
 
 /// LDAP provider
 pub struct LDAPProvider {
@@ -16,7 +28,20 @@ pub struct LDAPProvider {
     uid_attribute: String,
     email_attribute: String,
     name_attribute: String,
+    given_name_attribute: String,
+    family_name_attribute: String,
     use_tls: bool,
+    tls_ca_cert: Option<String>,
+    groups: LDAPGroupConfig,
+}
+
+/// A group the user belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryGroup {
+    /// Group DN
+    pub dn: String,
+    /// Group name, the DN when the group has no name attribute
+    pub name: String,
 }
 
 impl LDAPProvider {
@@ -32,8 +57,11 @@ impl LDAPProvider {
                 uid_attribute,
                 email_attribute,
                 name_attribute,
+                given_name_attribute,
+                family_name_attribute,
                 use_tls,
-                ..
+                tls_ca_cert,
+                groups,
             } => Ok(Self {
                 name: name.to_string(),
                 url,
@@ -44,141 +72,225 @@ impl LDAPProvider {
                 uid_attribute,
                 email_attribute,
                 name_attribute,
+                given_name_attribute,
+                family_name_attribute,
                 use_tls,
+                tls_ca_cert,
+                groups,
             }),
             _ => Err(SSOError::ConfigError("Invalid LDAP configuration".to_string())),
         }
     }
 
-    /// Connect to LDAP server
-    async fn connect(&self) -> SSOResult<ldap3::Ldap> {
-        let (conn, mut ldap) = LdapConnAsync::new(&self.url)
+    /// Open a connection, with TLS as configured
+    async fn open(&self) -> SSOResult<Ldap> {
+        let mut settings = LdapConnSettings::new()
+            .set_conn_timeout(std::time::Duration::from_secs(10))
+            .set_starttls(self.use_tls && !self.url.starts_with("ldaps://"));
+
+        if let Some(ca_cert) = &self.tls_ca_cert {
+            let certificate = native_tls::Certificate::from_pem(ca_cert.as_bytes())
+                .map_err(|e| SSOError::ConfigError(format!("Invalid LDAP CA certificate: {}", e)))?;
+            let connector = native_tls::TlsConnector::builder()
+                .add_root_certificate(certificate)
+                .build()
+                .map_err(|e| SSOError::ConfigError(format!("LDAP TLS setup failed: {}", e)))?;
+            settings = settings.set_connector(connector);
+        }
+
+        let (conn, ldap) = LdapConnAsync::with_settings(settings, &self.url)
             .await
             .map_err(|e| SSOError::LDAPError(format!("Connection failed: {}", e)))?;
 
         // Drive the connection
         ldap3::drive!(conn);
 
-        // Bind with service account
+        Ok(ldap)
+    }
+
+    /// Connect and bind with the service account
+    async fn connect(&self) -> SSOResult<Ldap> {
+        let mut ldap = self.open().await?;
+
         ldap.simple_bind(&self.bind_dn, &self.bind_password)
             .await
+            .and_then(|result| result.success())
             .map_err(|e| SSOError::LDAPError(format!("Bind failed: {}", e)))?;
 
         Ok(ldap)
     }
 
-    /// Search for user by username
-    async fn search_user(&self, username: &str) -> SSOResult<SearchEntry> {
-        let mut ldap = self.connect().await?;
-
-        // Build search filter
-        let filter = format!("(&{}({}={}))", self.user_filter, self.uid_attribute, username);
-
-        // Perform search
-        let (rs, _res) = ldap
-            .search(
-                &self.user_base_dn,
-                Scope::Subtree,
-                &filter,
-                vec![
-                    &self.uid_attribute,
-                    &self.email_attribute,
-                    &self.name_attribute,
-                    "givenName",
-                    "sn",
-                ],
-            )
+    /// Run a subtree search
+    async fn search(&self, ldap: &mut Ldap, base_dn: &str, filter: &str, attributes: Vec<&str>) -> SSOResult<Vec<SearchEntry>> {
+        let (entries, _res) = ldap
+            .search(base_dn, Scope::Subtree, filter, attributes)
             .await
             .map_err(|e| SSOError::LDAPError(format!("Search failed: {}", e)))?
             .success()
             .map_err(|e| SSOError::LDAPError(format!("Search error: {}", e)))?;
 
-        // Unbind
-        ldap.unbind()
-            .await
-            .map_err(|e| SSOError::LDAPError(format!("Unbind failed: {}", e)))?;
-
-        // Get first result
-        let entry = rs
-            .into_iter()
-            .next()
-            .ok_or_else(|| SSOError::InvalidCredentials)?;
-
-        Ok(SearchEntry::construct(entry))
+        Ok(entries.into_iter().map(SearchEntry::construct).collect())
     }
 
-    /// Authenticate user with LDAP
-    async fn authenticate(&self, username: &str, password: &str) -> SSOResult<SSOUser> {
-        // First, find the user
-        let user_entry = self.search_user(username).await?;
-
-        // Get user DN
-        let user_dn = user_entry.dn.clone();
-
-        // Try to bind with user credentials
-        let (conn, mut ldap) = LdapConnAsync::new(&self.url)
-            .await
-            .map_err(|e| SSOError::LDAPError(format!("Connection failed: {}", e)))?;
+    /// Search for user by username
+    async fn search_user(&self, ldap: &mut Ldap, username: &str) -> SSOResult<SearchEntry> {
+        let filter = format!("(&{}({}={}))", self.user_filter, self.uid_attribute, ldap_escape(username));
+        let attributes = vec![
+            self.uid_attribute.as_str(),
+            self.email_attribute.as_str(),
+            self.name_attribute.as_str(),
+            self.given_name_attribute.as_str(),
+            self.family_name_attribute.as_str(),
+        ];
+
+        let mut entries = self.search(ldap, &self.user_base_dn, &filter, attributes).await?;
+
+        // An ambiguous username must not pick an arbitrary account
+        if entries.len() != 1 {
+            return Err(SSOError::InvalidCredentials);
+        }
+        Ok(entries.remove(0))
+    }
 
-        ldap3::drive!(conn);
+    /// Resolve the groups of a user, following nesting as configured
+    async fn resolve_groups(&self, ldap: &mut Ldap, user_dn: &str) -> SSOResult<Vec<DirectoryGroup>> {
+        let Some(base_dn) = &self.groups.base_dn else {
+            return Ok(Vec::new());
+        };
+        let attributes = vec![self.groups.name_attribute.as_str()];
+
+        if self.groups.active_directory && self.groups.nested {
+            let filter = format!(
+                "(&{}({}:{}:={}))",
+                self.groups.filter,
+                self.groups.member_attribute,
+                MATCHING_RULE_IN_CHAIN,
+                ldap_escape(user_dn)
+            );
+            let entries = self.search(ldap, base_dn, &filter, attributes).await?;
+            return Ok(entries.iter().map(|entry| self.group_of(entry)).collect());
+        }
 
-        ldap.simple_bind(&user_dn, password)
-            .await
-            .map_err(|_| SSOError::InvalidCredentials)?;
+        let mut groups = Vec::new();
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut members = vec![user_dn.to_string()];
+        let max_depth = if self.groups.nested { self.groups.max_depth.max(1) } else { 1 };
+
+        for _ in 0..max_depth {
+            let mut parents = Vec::new();
+            for member in &members {
+                let filter = format!(
+                    "(&{}({}={}))",
+                    self.groups.filter,
+                    self.groups.member_attribute,
+                    ldap_escape(member)
+                );
+                for entry in self.search(ldap, base_dn, &filter, attributes.clone()).await? {
+                    // DNs compare case-insensitively; a seen group also ends a cycle
+                    if seen.insert(entry.dn.to_lowercase()) {
+                        parents.push(entry.dn.clone());
+                        groups.push(self.group_of(&entry));
+                    }
+                }
+            }
+            if parents.is_empty() {
+                break;
+            }
+            members = parents;
+        }
 
-        // Unbind
-        ldap.unbind()
-            .await
-            .map_err(|e| SSOError::LDAPError(format!("Unbind failed: {}", e)))?;
+        Ok(groups)
+    }
 
-        // Extract user attributes
-        let id = user_entry.attrs.get(&self.uid_attribute)
-            .and_then(|v| v.first())
-            .ok_or_else(|| SSOError::LDAPError("Missing UID attribute".to_string()))?
-            .clone();
+    fn group_of(&self, entry: &SearchEntry) -> DirectoryGroup {
+        let name = first_value(entry, &self.groups.name_attribute).unwrap_or_else(|| entry.dn.clone());
+        DirectoryGroup {
+            dn: entry.dn.clone(),
+            name,
+        }
+    }
 
-        let email = user_entry.attrs.get(&self.email_attribute)
-            .and_then(|v| v.first())
-            .ok_or_else(|| SSOError::LDAPError("Missing email attribute".to_string()))?
-            .clone();
+    /// Roles granted by a user's groups, matched by name or DN
+    fn map_roles(&self, groups: &[DirectoryGroup]) -> BTreeSet<String> {
+        let mut roles = BTreeSet::new();
+        for (key, granted) in &self.groups.role_mapping {
+            let matched = groups.iter().any(|group| {
+                group.name.eq_ignore_ascii_case(key) || group.dn.eq_ignore_ascii_case(key)
+            });
+            if matched {
+                roles.extend(granted.iter().cloned());
+            }
+        }
+        roles
+    }
 
-        let name = user_entry.attrs.get(&self.name_attribute)
-            .and_then(|v| v.first())
-            .map(|s| s.clone());
+    /// Build the user from the directory entry and their groups
+    fn build_user(&self, entry: &SearchEntry, groups: &[DirectoryGroup]) -> SSOResult<SSOUser> {
+        let id = first_value(entry, &self.uid_attribute)
+            .ok_or_else(|| SSOError::LDAPError("Missing UID attribute".to_string()))?;
 
-        let given_name = user_entry.attrs.get("givenName")
-            .and_then(|v| v.first())
-            .map(|s| s.clone());
+        let email = first_value(entry, &self.email_attribute)
+            .ok_or_else(|| SSOError::LDAPError("Missing email attribute".to_string()))?;
 
-        let family_name = user_entry.attrs.get("sn")
-            .and_then(|v| v.first())
-            .map(|s| s.clone());
+        let managed_roles: BTreeSet<&String> = self.groups.role_mapping.values().flatten().collect();
 
         Ok(SSOUser {
             id,
             email,
-            name,
-            given_name,
-            family_name,
+            name: first_value(entry, &self.name_attribute),
+            given_name: first_value(entry, &self.given_name_attribute),
+            family_name: first_value(entry, &self.family_name_attribute),
             picture: None,
             metadata: serde_json::json!({
                 "provider": "ldap",
-                "dn": user_dn
+                "dn": entry.dn,
+                "groups": groups.iter().map(|g| &g.name).collect::<Vec<_>>(),
+                "group_dns": groups.iter().map(|g| &g.dn).collect::<Vec<_>>(),
+                "roles": self.map_roles(groups),
+                "managed_roles": managed_roles,
             }),
             provider: self.name.clone(),
         })
     }
+
+    /// Authenticate user with LDAP
+    async fn authenticate(&self, username: &str, password: &str) -> SSOResult<SSOUser> {
+        // An empty password is an unauthenticated bind, which servers accept
+        if username.is_empty() || password.is_empty() {
+            return Err(SSOError::InvalidCredentials);
+        }
+
+        // First, find the user and their groups with the service account
+        let mut ldap = self.connect().await?;
+        let user_entry = self.search_user(&mut ldap, username).await?;
+        let groups = self.resolve_groups(&mut ldap, &user_entry.dn).await?;
+        let _ = ldap.unbind().await;
+
+        // Then bind with the user's credentials
+        let mut user_ldap = self.open().await?;
+        user_ldap.simple_bind(&user_entry.dn, password)
+            .await
+            .and_then(|result| result.success())
+            .map_err(|_| SSOError::InvalidCredentials)?;
+        let _ = user_ldap.unbind().await;
+
+        self.build_user(&user_entry, &groups)
+    }
+}
+
+fn first_value(entry: &SearchEntry, attribute: &str) -> Option<String> {
+    entry.attrs.get(attribute).and_then(|v| v.first()).cloned()
 }
 
 #[async_trait]
 impl SSOProvider for LDAPProvider {
-    async fn get_authorization_url(&self, state: &str, _nonce: Option<&str>) -> SSOResult<String> {
+    async fn get_authorization_url(&self, request: &AuthorizationState) -> SSOResult<String> {
         // LDAP doesn't use OAuth-style flows
         // Return a custom URL for LDAP login form
-        Ok(format!("/auth/ldap/login?state={}", state))
+        Ok(format!("/auth/ldap/login?state={}", request.state))
     }
 
-    async fn exchange_code(&self, code: &str, _state: &str) -> SSOResult<SSOUser> {
+    async fn exchange_code(&self, code: &str, _request: &AuthorizationState) -> SSOResult<SSOUser> {
         // For LDAP, "code" is in format "username:password" (base64 encoded)
         let decoded = base64::decode(code)
             .map_err(|e| SSOError::LDAPError(format!("Invalid credentials format: {}", e)))?;
@@ -197,7 +309,7 @@ impl SSOProvider for LDAPProvider {
         self.authenticate(username, password).await
     }
 
-    async fn validate_token(&self, token: &str) -> SSOResult<SSOUser> {
+    async fn validate_token(&self, _token: &str) -> SSOResult<SSOUser> {
         // LDAP doesn't use tokens
         // This would validate a session token created after LDAP authentication
         Err(SSOError::LDAPError("Token validation not supported for LDAP".to_string()))
@@ -231,10 +343,10 @@ impl LDAPCredentials {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
 
-    #[test]
-    fn test_ldap_provider_creation() {
-        let config = ProviderConfig::LDAP {
+    fn config(groups: LDAPGroupConfig) -> ProviderConfig {
+        ProviderConfig::LDAP {
             url: "ldap://ldap.example.com".to_string(),
             bind_dn: "cn=admin,dc=example,dc=com".to_string(),
             bind_password: "password".to_string(),
@@ -243,11 +355,17 @@ mod tests {
             uid_attribute: "uid".to_string(),
             email_attribute: "mail".to_string(),
             name_attribute: "cn".to_string(),
+            given_name_attribute: "givenName".to_string(),
+            family_name_attribute: "sn".to_string(),
             use_tls: true,
             tls_ca_cert: None,
-        };
+            groups,
+        }
+    }
 
-        let provider = LDAPProvider::new("test-ldap", config);
+    #[test]
+    fn test_ldap_provider_creation() {
+        let provider = LDAPProvider::new("test-ldap", config(LDAPGroupConfig::default()));
         assert!(provider.is_ok());
     }
 
@@ -261,4 +379,45 @@ mod tests {
         let encoded = creds.encode();
         assert!(!encoded.is_empty());
     }
+
+    #[test]
+    fn test_group_role_mapping() {
+        let mut role_mapping = HashMap::new();
+        role_mapping.insert("Investigators".to_string(), vec!["investigator".to_string()]);
+        role_mapping.insert(
+            "cn=admins,ou=groups,dc=example,dc=com".to_string(),
+            vec!["admin".to_string()],
+        );
+        let provider = LDAPProvider::new(
+            "corp",
+            config(LDAPGroupConfig {
+                role_mapping,
+                ..LDAPGroupConfig::default()
+            }),
+        )
+        .unwrap();
+
+        let mut attrs = HashMap::new();
+        attrs.insert("uid".to_string(), vec!["jdoe".to_string()]);
+        attrs.insert("mail".to_string(), vec!["jdoe@example.com".to_string()]);
+        attrs.insert("givenName".to_string(), vec!["Jane".to_string()]);
+        let entry = SearchEntry {
+            dn: "uid=jdoe,ou=users,dc=example,dc=com".to_string(),
+            attrs,
+            bin_attrs: HashMap::new(),
+        };
+        let groups = vec![DirectoryGroup {
+            dn: "cn=investigators,ou=groups,dc=example,dc=com".to_string(),
+            name: "investigators".to_string(),
+        }];
+
+        let user = provider.build_user(&entry, &groups).unwrap();
+        assert_eq!(user.id, "jdoe");
+        assert_eq!(user.given_name.as_deref(), Some("Jane"));
+        assert_eq!(user.roles(), vec!["investigator".to_string()]);
+        assert_eq!(
+            user.managed_roles(),
+            vec!["admin".to_string(), "investigator".to_string()]
+        );
+    }
 }
@@ -11,11 +11,21 @@ pub mod oidc;
 pub mod oauth2;
 pub mod ldap;
 
-use crate::{SSOProvider, SSOError, SSOResult, config::{SSOConfig, ProviderConfig}};
+use crate::{SSOProvider, SSOError, SSOResult, TokenManager, config::{SSOConfig, ProviderConfig}};
 use std::sync::Arc;
 
 /// Get provider instance by name
 pub fn get_provider(config: &SSOConfig, name: &str) -> SSOResult<Box<dyn SSOProvider>> {
+    get_provider_with_tokens(config, name, Arc::new(TokenManager::new(config.session.clone())))
+}
+
+/// Get provider instance by name, validating provider tokens with a shared
+/// token manager and its key cache
+pub fn get_provider_with_tokens(
+    config: &SSOConfig,
+    name: &str,
+    tokens: Arc<TokenManager>,
+) -> SSOResult<Box<dyn SSOProvider>> {
     let provider_config = config.providers.get(name)
         .ok_or_else(|| SSOError::ProviderNotFound(name.to_string()))?;
 
@@ -24,10 +34,10 @@ pub fn get_provider(config: &SSOConfig, name: &str) -> SSOResult<Box<dyn SSOProv
             Ok(Box::new(saml::SAMLProvider::new(name, provider_config.clone())?))
         },
         ProviderConfig::OIDC { .. } => {
-            Ok(Box::new(oidc::OIDCProvider::new(name, provider_config.clone())?))
+            Ok(Box::new(oidc::OIDCProvider::new(name, provider_config.clone())?.with_token_manager(tokens)))
         },
         ProviderConfig::OAuth2 { .. } => {
-            Ok(Box::new(oauth2::OAuth2Provider::new(name, provider_config.clone())?))
+            Ok(Box::new(oauth2::OAuth2Provider::new(name, provider_config.clone())?.with_token_manager(tokens)))
         },
         ProviderConfig::LDAP { .. } => {
             Ok(Box::new(ldap::LDAPProvider::new(name, provider_config.clone())?))
@@ -1,6 +1,6 @@
 //! OAuth 2.0 Provider Implementation
 
-use crate::{SSOProvider, SSOUser, SSOError, SSOResult, AuthenticationResult, config::ProviderConfig};
+use crate::{SSOProvider, SSOUser, SSOError, SSOResult, AuthenticationResult, AuthorizationState, TokenManager, config::ProviderConfig, device::{DeviceAuthorization, DevicePoll, DEVICE_CODE_GRANT}, session::{ExternalTokenSource, TokenIntrospector}};
 use async_trait::async_trait;
 use chrono::{Utc, Duration};
 use oauth2::{
@@ -11,8 +11,8 @@ use oauth2::{
     reqwest::async_http_client,
 };
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::sync::Arc;
-use tokio::sync::RwLock;
 use uuid::Uuid;
 
 /// OAuth 2.0 provider
@@ -23,10 +23,12 @@ pub struct OAuth2Provider {
     auth_endpoint: String,
     token_endpoint: String,
     userinfo_endpoint: String,
+    introspection_endpoint: Option<String>,
+    device_authorization_endpoint: Option<String>,
     scopes: Vec<String>,
     use_pkce: bool,
     client: BasicClient,
-    pkce_verifiers: Arc<RwLock<std::collections::HashMap<String, PkceCodeVerifier>>>,
+    tokens: Option<Arc<TokenManager>>,
 }
 
 impl OAuth2Provider {
@@ -39,6 +41,8 @@ impl OAuth2Provider {
                 auth_endpoint,
                 token_endpoint,
                 userinfo_endpoint,
+                introspection_endpoint,
+                device_authorization_endpoint,
                 scopes,
                 use_pkce,
             } => {
@@ -58,16 +62,40 @@ impl OAuth2Provider {
                     auth_endpoint,
                     token_endpoint,
                     userinfo_endpoint,
+                    introspection_endpoint,
+                    device_authorization_endpoint,
                     scopes,
                     use_pkce,
                     client,
-                    pkce_verifiers: Arc::new(RwLock::new(std::collections::HashMap::new())),
+                    tokens: None,
                 })
             },
             _ => Err(SSOError::ConfigError("Invalid OAuth2 configuration".to_string())),
         }
     }
 
+    /// Validate provider tokens with a shared token manager
+    pub fn with_token_manager(mut self, tokens: Arc<TokenManager>) -> Self {
+        self.tokens = Some(tokens);
+        self
+    }
+
+    /// Send a form to an endpoint authenticated as this client, returning
+    /// the JSON body whatever the status
+    async fn post_form(&self, endpoint: &str, form: &[(&str, &str)]) -> SSOResult<serde_json::Value> {
+        let response = reqwest::Client::new()
+            .post(endpoint)
+            .basic_auth(&self.client_id, Some(&self.client_secret))
+            .form(form)
+            .send()
+            .await?;
+
+        let status = response.status();
+        response.json().await.map_err(|e| {
+            SSOError::OAuth2Error(format!("Unreadable response with status {}: {}", status, e))
+        })
+    }
+
     /// Fetch user info from UserInfo endpoint
     async fn fetch_user_info(&self, access_token: &str) -> SSOResult<SSOUser> {
         let client = reqwest::Client::new();
@@ -123,49 +151,40 @@ impl OAuth2Provider {
 
 #[async_trait]
 impl SSOProvider for OAuth2Provider {
-    async fn get_authorization_url(&self, state: &str, _nonce: Option<&str>) -> SSOResult<String> {
+    async fn get_authorization_url(&self, request: &AuthorizationState) -> SSOResult<String> {
         // Build authorization URL
         let mut auth_request = self.client
-            .authorize_url(|| CsrfToken::new(state.to_string()));
+            .authorize_url(|| CsrfToken::new(request.state.clone()))
+            .set_redirect_uri(Cow::Owned(redirect_url(&request.redirect_uri)?));
 
         // Add scopes
         for scope in &self.scopes {
             auth_request = auth_request.add_scope(Scope::new(scope.clone()));
         }
 
-        // Add PKCE if enabled
-        let (auth_url, _csrf_token) = if self.use_pkce {
-            let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
-
-            // Store verifier for later use
-            {
-                let mut verifiers = self.pkce_verifiers.write().await;
-                verifiers.insert(state.to_string(), pkce_verifier);
+        // Add PKCE if enabled; the verifier is kept with the state
+        let (auth_url, _csrf_token) = match request.pkce_verifier.as_deref().filter(|_| self.use_pkce) {
+            Some(verifier) => {
+                let verifier = PkceCodeVerifier::new(verifier.to_string());
+                auth_request
+                    .set_pkce_challenge(PkceCodeChallenge::from_code_verifier_sha256(&verifier))
+                    .url()
             }
-
-            auth_request.set_pkce_challenge(pkce_challenge).url()
-        } else {
-            auth_request.url()
+            None => auth_request.url(),
         };
 
         Ok(auth_url.to_string())
     }
 
-    async fn exchange_code(&self, code: &str, state: &str) -> SSOResult<SSOUser> {
-        // Get PKCE verifier if used
-        let pkce_verifier = if self.use_pkce {
-            let mut verifiers = self.pkce_verifiers.write().await;
-            verifiers.remove(state)
-        } else {
-            None
-        };
-
-        // Exchange authorization code for tokens
+    async fn exchange_code(&self, code: &str, request: &AuthorizationState) -> SSOResult<SSOUser> {
+        // Exchange authorization code for tokens, with the redirect URI and
+        // PKCE verifier the login was started with
         let mut token_request = self.client
-            .exchange_code(AuthorizationCode::new(code.to_string()));
+            .exchange_code(AuthorizationCode::new(code.to_string()))
+            .set_redirect_uri(Cow::Owned(redirect_url(&request.redirect_uri)?));
 
-        if let Some(verifier) = pkce_verifier {
-            token_request = token_request.set_pkce_verifier(verifier);
+        if let Some(verifier) = request.pkce_verifier.as_deref().filter(|_| self.use_pkce) {
+            token_request = token_request.set_pkce_verifier(PkceCodeVerifier::new(verifier.to_string()));
         }
 
         let token_response = token_request
@@ -180,7 +199,49 @@ impl SSOProvider for OAuth2Provider {
         self.fetch_user_info(access_token).await
     }
 
+    async fn start_device_authorization(&self) -> SSOResult<DeviceAuthorization> {
+        let endpoint = self.device_authorization_endpoint.as_deref().ok_or_else(|| {
+            SSOError::ConfigError(format!("{} has no device authorization endpoint", self.name))
+        })?;
+
+        let scope = self.scopes.join(" ");
+        let body = self.post_form(endpoint, &[("client_id", &self.client_id), ("scope", &scope)]).await?;
+        if let Some(error) = body["error"].as_str() {
+            return Err(SSOError::OAuth2Error(format!("Device authorization failed: {}", error)));
+        }
+
+        serde_json::from_value(body)
+            .map_err(|e| SSOError::OAuth2Error(format!("Invalid device authorization response: {}", e)))
+    }
+
+    async fn poll_device_authorization(&self, device_code: &str) -> SSOResult<DevicePoll> {
+        let body = self.post_form(&self.token_endpoint, &[
+            ("grant_type", DEVICE_CODE_GRANT),
+            ("device_code", device_code),
+            ("client_id", &self.client_id),
+        ]).await?;
+
+        match device_token(&body)? {
+            DeviceToken::Pending => Ok(DevicePoll::Pending),
+            DeviceToken::SlowDown => Ok(DevicePoll::SlowDown),
+            DeviceToken::Issued(access_token) => {
+                Ok(DevicePoll::Complete(self.fetch_user_info(&access_token).await?))
+            }
+        }
+    }
+
     async fn validate_token(&self, token: &str) -> SSOResult<SSOUser> {
+        // Ask the provider whether the token is active before using it
+        if let (Some(endpoint), Some(tokens)) = (&self.introspection_endpoint, &self.tokens) {
+            let source = ExternalTokenSource {
+                issuer: None,
+                audience: self.client_id.clone(),
+                jwks_uri: None,
+                introspection: Some(TokenIntrospector::new(endpoint, &self.client_id, &self.client_secret)),
+            };
+            tokens.validate_external_token(token, &source).await?;
+        }
+
         // Fetch user info using the access token
         self.fetch_user_info(token).await
     }
@@ -190,11 +251,42 @@ impl SSOProvider for OAuth2Provider {
         Err(SSOError::OAuth2Error("Token refresh not yet implemented".to_string()))
     }
 
+    fn uses_pkce(&self) -> bool {
+        self.use_pkce
+    }
+
     fn name(&self) -> &str {
         &self.name
     }
 }
 
+fn redirect_url(redirect_uri: &str) -> SSOResult<RedirectUrl> {
+    RedirectUrl::new(redirect_uri.to_string()).map_err(|_| SSOError::InvalidRedirectURI)
+}
+
+/// Token endpoint answer to a device code poll
+#[derive(Debug, PartialEq, Eq)]
+enum DeviceToken {
+    Pending,
+    SlowDown,
+    Issued(String),
+}
+
+/// Read a device code poll response (RFC 8628 section 3.5)
+fn device_token(body: &serde_json::Value) -> SSOResult<DeviceToken> {
+    match body["error"].as_str() {
+        Some("authorization_pending") => Ok(DeviceToken::Pending),
+        Some("slow_down") => Ok(DeviceToken::SlowDown),
+        Some("access_denied") => Err(SSOError::AuthenticationFailed("User denied the device login".to_string())),
+        Some("expired_token") => Err(SSOError::TokenExpired),
+        Some(error) => Err(SSOError::OAuth2Error(format!("Device token request failed: {}", error))),
+        None => body["access_token"]
+            .as_str()
+            .map(|token| DeviceToken::Issued(token.to_string()))
+            .ok_or_else(|| SSOError::OAuth2Error("Device token response has no access token".to_string())),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -207,6 +299,8 @@ mod tests {
             auth_endpoint: "https://provider.com/oauth/authorize".to_string(),
             token_endpoint: "https://provider.com/oauth/token".to_string(),
             userinfo_endpoint: "https://provider.com/oauth/userinfo".to_string(),
+            introspection_endpoint: None,
+            device_authorization_endpoint: None,
             scopes: vec!["user".to_string(), "email".to_string()],
             use_pkce: true,
         };
@@ -214,4 +308,21 @@ mod tests {
         let provider = OAuth2Provider::new("test-oauth2", config);
         assert!(provider.is_ok());
     }
+
+    #[test]
+    fn test_device_token_responses() {
+        let poll = |body: serde_json::Value| device_token(&body);
+
+        assert_eq!(poll(serde_json::json!({ "error": "authorization_pending" })).unwrap(), DeviceToken::Pending);
+        assert_eq!(poll(serde_json::json!({ "error": "slow_down" })).unwrap(), DeviceToken::SlowDown);
+        assert_eq!(
+            poll(serde_json::json!({ "access_token": "at", "token_type": "Bearer" })).unwrap(),
+            DeviceToken::Issued("at".to_string())
+        );
+
+        assert!(matches!(poll(serde_json::json!({ "error": "access_denied" })), Err(SSOError::AuthenticationFailed(_))));
+        assert!(matches!(poll(serde_json::json!({ "error": "expired_token" })), Err(SSOError::TokenExpired)));
+        assert!(matches!(poll(serde_json::json!({ "error": "invalid_grant" })), Err(SSOError::OAuth2Error(_))));
+        assert!(poll(serde_json::json!({ "token_type": "Bearer" })).is_err());
+    }
 }
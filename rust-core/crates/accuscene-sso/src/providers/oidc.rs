@@ -1,7 +1,8 @@
 //! OpenID Connect Provider Implementation
 
-use crate::{SSOProvider, SSOUser, SSOError, SSOResult, AuthenticationResult, config::ProviderConfig};
+use crate::{SSOProvider, SSOUser, SSOError, SSOResult, AuthenticationResult, AuthorizationState, RemoteLogout, TokenManager, config::ProviderConfig, logout, session::{ExternalTokenSource, TokenIntrospector}};
 use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use chrono::{Utc, Duration};
 use openidconnect::{
     core::{CoreClient, CoreProviderMetadata, CoreResponseType, CoreAuthenticationFlow},
@@ -10,6 +11,7 @@ use openidconnect::{
     PkceCodeChallenge, PkceCodeVerifier, RedirectUrl, Scope, TokenResponse,
 };
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
@@ -20,10 +22,12 @@ pub struct OIDCProvider {
     client_id: String,
     client_secret: String,
     issuer_url: String,
+    jwks_uri: Option<String>,
+    introspection_endpoint: Option<String>,
     scopes: Vec<String>,
     use_pkce: bool,
     client: Arc<RwLock<Option<CoreClient>>>,
-    pkce_verifiers: Arc<RwLock<std::collections::HashMap<String, PkceCodeVerifier>>>,
+    tokens: Option<Arc<TokenManager>>,
 }
 
 impl OIDCProvider {
@@ -34,6 +38,8 @@ impl OIDCProvider {
                 client_id,
                 client_secret,
                 issuer_url,
+                jwks_uri,
+                introspection_endpoint,
                 scopes,
                 use_pkce,
                 ..
@@ -42,15 +48,29 @@ impl OIDCProvider {
                 client_id: client_id.clone(),
                 client_secret: client_secret.clone(),
                 issuer_url: issuer_url.clone(),
+                jwks_uri,
+                introspection_endpoint,
                 scopes,
                 use_pkce,
                 client: Arc::new(RwLock::new(None)),
-                pkce_verifiers: Arc::new(RwLock::new(std::collections::HashMap::new())),
+                tokens: None,
             }),
             _ => Err(SSOError::ConfigError("Invalid OIDC configuration".to_string())),
         }
     }
 
+    /// Validate provider tokens with a shared token manager
+    pub fn with_token_manager(mut self, tokens: Arc<TokenManager>) -> Self {
+        self.tokens = Some(tokens);
+        self
+    }
+
+    fn tokens(&self) -> SSOResult<&TokenManager> {
+        self.tokens.as_deref().ok_or_else(|| {
+            SSOError::ConfigError(format!("{} has no token manager for validating tokens", self.name))
+        })
+    }
+
     /// Initialize OIDC client
     async fn init_client(&self) -> SSOResult<CoreClient> {
         // Check if client already initialized
@@ -62,12 +82,7 @@ impl OIDCProvider {
         }
 
         // Discover provider metadata
-        let issuer_url = IssuerUrl::new(self.issuer_url.clone())
-            .map_err(|e| SSOError::OIDCError(format!("Invalid issuer URL: {}", e)))?;
-
-        let metadata = CoreProviderMetadata::discover_async(issuer_url, async_http_client)
-            .await
-            .map_err(|e| SSOError::OIDCError(format!("Failed to discover provider: {}", e)))?;
+        let metadata = self.discover().await?;
 
         // Create client
         let client = CoreClient::from_provider_metadata(
@@ -85,6 +100,65 @@ impl OIDCProvider {
         Ok(client)
     }
 
+    async fn discover(&self) -> SSOResult<CoreProviderMetadata> {
+        let issuer_url = IssuerUrl::new(self.issuer_url.clone())
+            .map_err(|e| SSOError::OIDCError(format!("Invalid issuer URL: {}", e)))?;
+
+        CoreProviderMetadata::discover_async(issuer_url, async_http_client)
+            .await
+            .map_err(|e| SSOError::OIDCError(format!("Failed to discover provider: {}", e)))
+    }
+
+    /// The provider's JWKS URI, configured or discovered
+    async fn jwks_uri(&self) -> SSOResult<String> {
+        if let Some(jwks_uri) = &self.jwks_uri {
+            return Ok(jwks_uri.clone());
+        }
+
+        let cache = self.tokens()?.jwks_cache();
+        if let Some(jwks_uri) = cache.discovered_uri(&self.issuer_url).await {
+            return Ok(jwks_uri);
+        }
+        let jwks_uri = self.discover().await?.jwks_uri().url().to_string();
+        cache.remember_uri(&self.issuer_url, &jwks_uri).await;
+        Ok(jwks_uri)
+    }
+
+    async fn token_source(&self) -> SSOResult<ExternalTokenSource> {
+        Ok(ExternalTokenSource {
+            issuer: Some(self.issuer_url.clone()),
+            audience: self.client_id.clone(),
+            jwks_uri: Some(self.jwks_uri().await?),
+            introspection: self.introspection_endpoint.as_deref().map(|endpoint| {
+                TokenIntrospector::new(endpoint, &self.client_id, &self.client_secret)
+            }),
+        })
+    }
+
+    /// Build the user from validated token claims
+    fn user_from_claims(&self, claims: &serde_json::Value) -> SSOResult<SSOUser> {
+        let claim = |name: &str| claims[name].as_str().map(str::to_string);
+        let subject = claim("sub")
+            .ok_or_else(|| SSOError::TokenValidationFailed("Token has no subject".to_string()))?;
+
+        Ok(SSOUser {
+            id: subject.clone(),
+            email: claim("email").unwrap_or_default(),
+            name: claim("name"),
+            given_name: claim("given_name"),
+            family_name: claim("family_name"),
+            picture: claim("picture"),
+            metadata: serde_json::json!({
+                "provider": "oidc",
+                "issuer": self.issuer_url,
+                "idp_subject": subject,
+                "idp_session_id": claim("sid"),
+                "scope": claim("scope"),
+            }),
+            provider: self.name.clone(),
+        })
+    }
+
     /// Extract user info from ID token and UserInfo endpoint
     async fn get_user_info(&self, id_token: &str, access_token: &str) -> SSOResult<SSOUser> {
         // In production, validate and decode ID token
@@ -109,63 +183,48 @@ impl OIDCProvider {
 
 #[async_trait]
 impl SSOProvider for OIDCProvider {
-    async fn get_authorization_url(&self, state: &str, nonce: Option<&str>) -> SSOResult<String> {
+    async fn get_authorization_url(&self, request: &AuthorizationState) -> SSOResult<String> {
         let client = self.init_client().await?;
 
         // Build authorization URL
         let mut auth_request = client
             .authorize_url(
                 CoreAuthenticationFlow::AuthorizationCode,
-                || CsrfToken::new(state.to_string()),
-                || Nonce::new(nonce.unwrap_or("default_nonce").to_string()),
-            );
+                || CsrfToken::new(request.state.clone()),
+                || Nonce::new(request.nonce.clone()),
+            )
+            .set_redirect_uri(Cow::Owned(redirect_url(&request.redirect_uri)?));
 
         // Add scopes
         for scope in &self.scopes {
             auth_request = auth_request.add_scope(Scope::new(scope.clone()));
         }
 
-        // Add PKCE if enabled
-        let (pkce_challenge, pkce_verifier) = if self.use_pkce {
-            let (challenge, verifier) = PkceCodeChallenge::new_random_sha256();
-
-            // Store verifier for later use
-            {
-                let mut verifiers = self.pkce_verifiers.write().await;
-                verifiers.insert(state.to_string(), verifier);
+        // Add PKCE if enabled; the verifier is kept with the state
+        let (auth_url, _csrf_token, _nonce) = match request.pkce_verifier.as_deref().filter(|_| self.use_pkce) {
+            Some(verifier) => {
+                let verifier = PkceCodeVerifier::new(verifier.to_string());
+                auth_request
+                    .set_pkce_challenge(PkceCodeChallenge::from_code_verifier_sha256(&verifier))
+                    .url()
             }
-
-            (Some(challenge), true)
-        } else {
-            (None, false)
-        };
-
-        let (auth_url, _csrf_token, _nonce) = if let Some(challenge) = pkce_challenge {
-            auth_request.set_pkce_challenge(challenge).url()
-        } else {
-            auth_request.url()
+            None => auth_request.url(),
         };
 
         Ok(auth_url.to_string())
     }
 
-    async fn exchange_code(&self, code: &str, state: &str) -> SSOResult<SSOUser> {
+    async fn exchange_code(&self, code: &str, request: &AuthorizationState) -> SSOResult<SSOUser> {
         let client = self.init_client().await?;
 
-        // Get PKCE verifier if used
-        let pkce_verifier = if self.use_pkce {
-            let mut verifiers = self.pkce_verifiers.write().await;
-            verifiers.remove(state)
-        } else {
-            None
-        };
-
-        // Exchange authorization code for tokens
+        // Exchange authorization code for tokens, with the redirect URI and
+        // PKCE verifier the login was started with
         let mut token_request = client
-            .exchange_code(AuthorizationCode::new(code.to_string()));
+            .exchange_code(AuthorizationCode::new(code.to_string()))
+            .set_redirect_uri(Cow::Owned(redirect_url(&request.redirect_uri)?));
 
-        if let Some(verifier) = pkce_verifier {
-            token_request = token_request.set_pkce_verifier(verifier);
+        if let Some(verifier) = request.pkce_verifier.as_deref().filter(|_| self.use_pkce) {
+            token_request = token_request.set_pkce_verifier(PkceCodeVerifier::new(verifier.to_string()));
         }
 
         let token_response = token_request
@@ -179,23 +238,33 @@ impl SSOProvider for OIDCProvider {
             .id_token()
             .ok_or_else(|| SSOError::OIDCError("No ID token in response".to_string()))?;
 
-        // Get user info
-        self.get_user_info(id_token.to_string().as_str(), access_token).await
+        // The ID token must carry the nonce of this login
+        let claims = id_token
+            .claims(&client.id_token_verifier(), &Nonce::new(request.nonce.clone()))
+            .map_err(|e| SSOError::OIDCError(format!("ID token verification failed: {}", e)))?;
+        let subject = claims.subject().to_string();
+        let id_token = id_token.to_string();
+
+        // Get user info, remembering the OP session for back-channel logout
+        let mut user = self.get_user_info(&id_token, access_token).await?;
+        user.metadata["idp_subject"] = serde_json::json!(subject);
+        user.metadata["idp_session_id"] = serde_json::json!(session_id(&id_token));
+        Ok(user)
+    }
+
+    async fn verify_logout_token(&self, logout_token: &str) -> SSOResult<RemoteLogout> {
+        let kid = jsonwebtoken::decode_header(logout_token)?.kid;
+        let keys = self.tokens()?
+            .jwks_cache()
+            .key_set(&self.jwks_uri().await?, kid.as_deref())
+            .await?;
+        logout::verify_logout_token(&self.name, logout_token, &keys, &self.issuer_url, &self.client_id)
     }
 
     async fn validate_token(&self, token: &str) -> SSOResult<SSOUser> {
-        // In production, validate JWT signature and claims
-        // This is a placeholder
-        Ok(SSOUser {
-            id: "oidc_user_from_token".to_string(),
-            email: "user@example.com".to_string(),
-            name: Some("OIDC User".to_string()),
-            given_name: None,
-            family_name: None,
-            picture: None,
-            metadata: serde_json::json!({}),
-            provider: self.name.clone(),
-        })
+        let source = self.token_source().await?;
+        let claims = self.tokens()?.validate_external_token(token, &source).await?;
+        self.user_from_claims(&claims)
     }
 
     async fn refresh_token(&self, refresh_token: &str) -> SSOResult<AuthenticationResult> {
@@ -205,11 +274,26 @@ impl SSOProvider for OIDCProvider {
         Err(SSOError::OIDCError("Token refresh not yet implemented".to_string()))
     }
 
+    fn uses_pkce(&self) -> bool {
+        self.use_pkce
+    }
+
     fn name(&self) -> &str {
         &self.name
     }
 }
 
+/// The `sid` claim of a verified ID token
+fn session_id(id_token: &str) -> Option<String> {
+    let payload = URL_SAFE_NO_PAD.decode(id_token.split('.').nth(1)?).ok()?;
+    let claims: serde_json::Value = serde_json::from_slice(&payload).ok()?;
+    claims["sid"].as_str().map(str::to_string)
+}
+
+fn redirect_url(redirect_uri: &str) -> SSOResult<RedirectUrl> {
+    RedirectUrl::new(redirect_uri.to_string()).map_err(|_| SSOError::InvalidRedirectURI)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -224,6 +308,7 @@ mod tests {
             token_endpoint: None,
             userinfo_endpoint: None,
             jwks_uri: None,
+            introspection_endpoint: None,
             scopes: vec!["openid".to_string(), "email".to_string(), "profile".to_string()],
             use_pkce: true,
         };
@@ -231,4 +316,36 @@ mod tests {
         let provider = OIDCProvider::new("test-oidc", config);
         assert!(provider.is_ok());
     }
+
+    #[test]
+    fn test_user_from_claims() {
+        let config = ProviderConfig::OIDC {
+            client_id: "test-client".to_string(),
+            client_secret: "test-secret".to_string(),
+            issuer_url: "https://op.example.com".to_string(),
+            auth_endpoint: None,
+            token_endpoint: None,
+            userinfo_endpoint: None,
+            jwks_uri: Some("https://op.example.com/jwks".to_string()),
+            introspection_endpoint: None,
+            scopes: vec!["openid".to_string()],
+            use_pkce: true,
+        };
+        let provider = OIDCProvider::new("okta", config).unwrap();
+
+        let user = provider
+            .user_from_claims(&serde_json::json!({
+                "sub": "00u1",
+                "email": "jane@example.com",
+                "given_name": "Jane",
+                "sid": "op-session",
+            }))
+            .unwrap();
+        assert_eq!(user.id, "00u1");
+        assert_eq!(user.email, "jane@example.com");
+        assert_eq!(user.idp_session_id().as_deref(), Some("op-session"));
+        assert_eq!(user.provider, "okta");
+
+        assert!(provider.user_from_claims(&serde_json::json!({ "email": "jane@example.com" })).is_err());
+    }
 }
@@ -1,28 +1,65 @@
 //! SAML 2.0 Provider Implementation
+//!
+//! A SAML service provider built on samael. Logins send the AuthnRequest
+//! with the HTTP-Redirect binding, signed when `sign_authn_request` is set,
+//! and receive the response with the HTTP-POST binding.
+//!
+//! Responses are validated with xmlsec before anything is read from them:
+//! the XML signature against the IdP's signing certificates (only signed
+//! elements are kept), the issuer, destination and audience, the
+//! `InResponseTo` ID of the request the login was started with, and the
+//! validity window of the assertion conditions, allowing for the configured
+//! clock skew. Unsolicited (IdP-initiated) responses are refused.
+//!
+//! The IdP is described by its metadata XML, or by the entity ID, SSO URL
+//! and signing certificate of the provider configuration.
+//!
+//! Single logout uses the HTTP-Redirect binding in both directions once
+//! `sp_slo_url` is configured. LogoutRequests and LogoutResponses sent by
+//! the SP are always signed; those received must carry a valid signature
+//! from the IdP, name the IdP as issuer and the SP endpoint as destination.
 
-use crate::{SSOProvider, SSOUser, SSOError, SSOResult, AuthenticationResult, config::ProviderConfig};
+use crate::{SSOProvider, SSOUser, SSOError, SSOResult, AuthenticationResult, AuthorizationState, config::{ProviderConfig, SAMLAttributeMapping}, logout::{RemoteLogout, LOGOUT_TOKEN_MAX_AGE}, session::Session};
 use async_trait::async_trait;
-use chrono::Utc;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use openssl::{hash::MessageDigest, pkey::PKey, rsa::Rsa, sign::{Signer, Verifier}, x509::X509};
+use quick_xml::{events::Event, Reader};
 use samael::{
-    metadata::{EntityDescriptor, ContactPerson, ContactType},
-    schema::{AuthnRequest, Response},
-    service_provider::ServiceProvider,
+    metadata::{de, EntityDescriptor, HTTP_REDIRECT_BINDING},
+    schema::Assertion,
+    service_provider::{ServiceProvider, ServiceProviderBuilder},
 };
-use uuid::Uuid;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Write};
+
+/// Signature algorithm of signed HTTP-Redirect messages
+const RSA_SHA256: &str = "http://www.w3.org/2001/04/xmldsig-more#rsa-sha256";
+
+/// Status of a successful logout
+const STATUS_SUCCESS: &str = "urn:oasis:names:tc:SAML:2.0:status:Success";
+
+/// Largest inflated logout message accepted
+const MAX_MESSAGE_SIZE: u64 = 64 * 1024;
 
 /// SAML 2.0 provider
 pub struct SAMLProvider {
     name: String,
+    idp_metadata: Option<String>,
     entity_id: String,
     sso_url: String,
     slo_url: Option<String>,
     idp_certificate: String,
     sp_entity_id: String,
     acs_url: String,
+    sp_slo_url: Option<String>,
     sp_private_key: String,
     sp_certificate: String,
     sign_authn_request: bool,
     want_assertions_signed: bool,
+    max_clock_skew: i64,
+    attribute_mapping: SAMLAttributeMapping,
 }
 
 impl SAMLProvider {
@@ -30,127 +67,666 @@ impl SAMLProvider {
     pub fn new(name: &str, config: ProviderConfig) -> SSOResult<Self> {
         match config {
             ProviderConfig::SAML {
+                idp_metadata,
                 entity_id,
                 sso_url,
                 slo_url,
                 idp_certificate,
                 sp_entity_id,
                 acs_url,
+                sp_slo_url,
                 sp_private_key,
                 sp_certificate,
                 sign_authn_request,
                 want_assertions_signed,
-            } => Ok(Self {
-                name: name.to_string(),
-                entity_id,
-                sso_url,
-                slo_url,
-                idp_certificate,
-                sp_entity_id,
-                acs_url,
-                sp_private_key,
-                sp_certificate,
-                sign_authn_request,
-                want_assertions_signed,
-            }),
+                max_clock_skew,
+                attribute_mapping,
+            } => {
+                if idp_metadata.is_none() && (entity_id.is_empty() || sso_url.is_empty()) {
+                    return Err(SSOError::ConfigError(
+                        "SAML provider needs IdP metadata or an entity ID and SSO URL".to_string(),
+                    ));
+                }
+                if !want_assertions_signed {
+                    tracing::warn!(
+                        provider = name,
+                        "SAML responses are always signature-checked; want_assertions_signed is ignored"
+                    );
+                }
+
+                Ok(Self {
+                    name: name.to_string(),
+                    idp_metadata,
+                    entity_id,
+                    sso_url,
+                    slo_url,
+                    idp_certificate,
+                    sp_entity_id,
+                    acs_url,
+                    sp_slo_url,
+                    sp_private_key,
+                    sp_certificate,
+                    sign_authn_request,
+                    want_assertions_signed,
+                    max_clock_skew,
+                    attribute_mapping,
+                })
+            },
             _ => Err(SSOError::ConfigError("Invalid SAML configuration".to_string())),
         }
     }
 
-    /// Build SAML authentication request
-    fn build_authn_request(&self, relay_state: &str) -> SSOResult<String> {
-        let request_id = format!("_request_{}", Uuid::new_v4());
-        let issue_instant = Utc::now();
-
-        // In production, use samael crate to build proper SAML request
-        // This is a simplified example
-        let authn_request = format!(
-            r#"<samlp:AuthnRequest xmlns:samlp="urn:oasis:names:tc:SAML:2.0:protocol"
-                xmlns:saml="urn:oasis:names:tc:SAML:2.0:assertion"
-                ID="{}"
-                Version="2.0"
-                IssueInstant="{}"
-                Destination="{}"
-                AssertionConsumerServiceURL="{}">
-                <saml:Issuer>{}</saml:Issuer>
-            </samlp:AuthnRequest>"#,
-            request_id,
-            issue_instant.to_rfc3339(),
-            self.sso_url,
-            self.acs_url,
-            self.sp_entity_id
+    /// Parse the IdP metadata
+    pub fn idp_metadata(&self) -> SSOResult<EntityDescriptor> {
+        de::from_str(&self.idp_metadata_xml())
+            .map_err(|e| SSOError::ConfigError(format!("Invalid IdP metadata: {}", e)))
+    }
+
+    fn idp_metadata_xml(&self) -> String {
+        match &self.idp_metadata {
+            Some(xml) => xml.clone(),
+            None => self.configured_idp_metadata(),
+        }
+    }
+
+    /// Generate the SP metadata to register with the IdP
+    pub fn sp_metadata(&self) -> SSOResult<String> {
+        let metadata = self.service_provider()?
+            .metadata()
+            .map_err(|e| SSOError::SAMLError(format!("Failed to build SP metadata: {}", e)))?;
+
+        metadata.to_xml()
+            .map_err(|e| SSOError::SAMLError(format!("Failed to serialize SP metadata: {}", e)))
+    }
+
+    /// IdP metadata equivalent to the entity ID, SSO URL and certificate
+    fn configured_idp_metadata(&self) -> String {
+        let certificate: String = self.idp_certificate
+            .lines()
+            .filter(|line| !line.starts_with("-----"))
+            .flat_map(|line| line.chars())
+            .filter(|c| !c.is_whitespace())
+            .collect();
+
+        let mut services = format!(
+            r#"<md:SingleSignOnService Binding="{}" Location="{}"/>"#,
+            HTTP_REDIRECT_BINDING,
+            xml_escape(&self.sso_url)
         );
+        if let Some(slo_url) = &self.slo_url {
+            services.push_str(&format!(
+                r#"<md:SingleLogoutService Binding="{}" Location="{}"/>"#,
+                HTTP_REDIRECT_BINDING,
+                xml_escape(slo_url)
+            ));
+        }
 
-        // Sign request if configured
-        let encoded = if self.sign_authn_request {
-            self.sign_and_encode(&authn_request)?
-        } else {
-            base64::encode(&authn_request)
-        };
+        format!(
+            r#"<md:EntityDescriptor xmlns:md="urn:oasis:names:tc:SAML:2.0:metadata" xmlns:ds="http://www.w3.org/2000/09/xmldsig#" entityID="{}"><md:IDPSSODescriptor protocolSupportEnumeration="urn:oasis:names:tc:SAML:2.0:protocol"><md:KeyDescriptor use="signing"><ds:KeyInfo><ds:X509Data><ds:X509Certificate>{}</ds:X509Certificate></ds:X509Data></ds:KeyInfo></md:KeyDescriptor>{}</md:IDPSSODescriptor></md:EntityDescriptor>"#,
+            xml_escape(&self.entity_id),
+            certificate,
+            services
+        )
+    }
+
+    /// Build the samael service provider from the configuration
+    fn service_provider(&self) -> SSOResult<ServiceProvider> {
+        let key = self.private_key()?;
+        let certificate = X509::from_pem(self.sp_certificate.as_bytes())
+            .map_err(|e| SSOError::ConfigError(format!("Invalid SP certificate: {}", e)))?;
 
-        Ok(encoded)
+        ServiceProviderBuilder::default()
+            .entity_id(self.sp_entity_id.clone())
+            .key(key)
+            .certificate(certificate)
+            .acs_url(self.acs_url.clone())
+            .idp_metadata(self.idp_metadata()?)
+            .allow_idp_initiated(false)
+            .max_clock_skew(Duration::seconds(self.max_clock_skew))
+            .build()
+            .map_err(|e| SSOError::ConfigError(format!("Invalid SAML service provider: {}", e)))
     }
 
-    /// Sign and encode SAML message
-    fn sign_and_encode(&self, message: &str) -> SSOResult<String> {
-        // In production, implement proper XML signing
-        // This is a placeholder
-        Ok(base64::encode(message))
+    fn private_key(&self) -> SSOResult<Rsa<openssl::pkey::Private>> {
+        Rsa::private_key_from_pem(self.sp_private_key.as_bytes())
+            .map_err(|e| SSOError::ConfigError(format!("Invalid SP private key: {}", e)))
     }
 
-    /// Parse and validate SAML response
-    fn parse_saml_response(&self, saml_response: &str) -> SSOResult<SSOUser> {
-        // In production, use samael crate to parse and validate
-        // This is a simplified example
+    /// Read the user from a validated assertion
+    fn map_assertion(&self, assertion: &Assertion) -> SSOResult<SSOUser> {
+        let name_id = assertion.subject
+            .as_ref()
+            .and_then(|subject| subject.name_id.as_ref())
+            .map(|name_id| name_id.value.clone());
+        let session_index = assertion.authn_statements
+            .iter()
+            .flatten()
+            .find_map(|statement| statement.session_index.clone());
 
-        // Decode base64
-        let decoded = base64::decode(saml_response)
-            .map_err(|e| SSOError::SAMLError(format!("Invalid base64: {}", e)))?;
+        let mut attributes: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for statement in assertion.attribute_statements.iter().flatten() {
+            for attribute in &statement.attributes {
+                let values: Vec<String> = attribute.values
+                    .iter()
+                    .filter_map(|value| value.value.clone())
+                    .collect();
+                for key in [&attribute.name, &attribute.friendly_name].into_iter().flatten() {
+                    attributes.entry(key.clone()).or_default().extend(values.iter().cloned());
+                }
+            }
+        }
+
+        self.map_attributes(name_id, session_index, attributes)
+    }
 
-        let response_xml = String::from_utf8(decoded)
-            .map_err(|e| SSOError::SAMLError(format!("Invalid UTF-8: {}", e)))?;
+    /// Build the user from the subject NameID, the IdP session and the
+    /// assertion attributes
+    fn map_attributes(
+        &self,
+        name_id: Option<String>,
+        session_index: Option<String>,
+        attributes: BTreeMap<String, Vec<String>>,
+    ) -> SSOResult<SSOUser> {
+        let mapping = &self.attribute_mapping;
+        let first = |names: &[String]| {
+            names.iter()
+                .filter_map(|name| attributes.get(name))
+                .flat_map(|values| values.iter())
+                .find(|value| !value.is_empty())
+                .cloned()
+        };
+
+        let id = match &mapping.user_id {
+            Some(attribute) => first(std::slice::from_ref(attribute)),
+            None => name_id.clone(),
+        }
+        .ok_or_else(|| SSOError::AuthenticationFailed("SAML assertion has no user ID".to_string()))?;
 
-        // In production, validate signature, timestamps, audience, etc.
+        let email = first(&mapping.email)
+            .or_else(|| name_id.clone().filter(|name_id| name_id.contains('@')))
+            .ok_or_else(|| SSOError::AuthenticationFailed("SAML assertion has no email address".to_string()))?;
 
-        // Extract user attributes from assertion
-        // This is a placeholder - real implementation would parse XML
         Ok(SSOUser {
-            id: "saml_user_123".to_string(),
-            email: "user@example.com".to_string(),
-            name: Some("SAML User".to_string()),
-            given_name: Some("SAML".to_string()),
-            family_name: Some("User".to_string()),
+            id,
+            email,
+            name: first(&mapping.name),
+            given_name: first(&mapping.given_name),
+            family_name: first(&mapping.family_name),
             picture: None,
             metadata: serde_json::json!({
                 "provider": "saml",
-                "entity_id": self.entity_id
+                "entity_id": self.entity_id,
+                "name_id": name_id,
+                "idp_subject": name_id,
+                "idp_session_id": session_index,
+                "attributes": attributes,
             }),
             provider: self.name.clone(),
         })
     }
 }
 
+impl SAMLProvider {
+    fn sp_slo_url(&self) -> SSOResult<&str> {
+        self.sp_slo_url.as_deref().ok_or_else(|| {
+            SSOError::ConfigError(format!("Single logout is not configured for {}", self.name))
+        })
+    }
+
+    fn signing_key(&self) -> SSOResult<PKey<openssl::pkey::Private>> {
+        PKey::from_rsa(self.private_key()?)
+            .map_err(|e| SSOError::ConfigError(format!("Invalid SP private key: {}", e)))
+    }
+
+    /// Decode a logout message received with the HTTP-Redirect binding and
+    /// check that the IdP sent it to this SP
+    fn receive_logout_message(&self, query: &str, parameter: &str) -> SSOResult<(LogoutMessage, Option<String>)> {
+        let sp_slo_url = self.sp_slo_url()?;
+        let idp = IdpLogoutMetadata::parse(&self.idp_metadata_xml())?;
+        let (xml, relay_state) = decode_redirect(query, parameter, &idp.certificates()?)?;
+        let message = LogoutMessage::parse(&xml)?;
+
+        if message.issuer.as_deref() != Some(idp.entity_id.as_str()) {
+            return Err(SSOError::AuthenticationFailed("Logout message from an unknown issuer".to_string()));
+        }
+        if message.destination.as_deref().is_some_and(|destination| destination != sp_slo_url) {
+            return Err(SSOError::AuthenticationFailed("Logout message sent to another destination".to_string()));
+        }
+
+        let skew = Duration::seconds(self.max_clock_skew);
+        let issued_at = parse_instant(message.issue_instant.as_deref())?;
+        if issued_at > Utc::now() + skew || issued_at + Duration::seconds(LOGOUT_TOKEN_MAX_AGE) + skew < Utc::now() {
+            return Err(SSOError::AuthenticationFailed("Logout message is not current".to_string()));
+        }
+
+        Ok((message, relay_state))
+    }
+}
+
+/// ID of the AuthnRequest for a login, checked against `InResponseTo`
+fn request_id(request: &AuthorizationState) -> String {
+    format!("_{}", request.nonce)
+}
+
+/// IdP entity ID, logout endpoint and signing certificates from metadata
+#[derive(Debug, Default)]
+struct IdpLogoutMetadata {
+    entity_id: String,
+    slo_url: Option<String>,
+    slo_response_url: Option<String>,
+    certificates: Vec<String>,
+}
+
+impl IdpLogoutMetadata {
+    fn parse(xml: &str) -> SSOResult<Self> {
+        let invalid = |e: quick_xml::Error| SSOError::ConfigError(format!("Invalid IdP metadata: {}", e));
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+
+        let mut metadata = Self::default();
+        let mut in_idp = false;
+        let mut signing_key = true;
+        let mut in_certificate = false;
+
+        loop {
+            match reader.read_event().map_err(invalid)? {
+                Event::Start(element) | Event::Empty(element) => {
+                    let attributes = attributes(&element)?;
+                    match element.local_name().as_ref() {
+                        b"EntityDescriptor" => {
+                            metadata.entity_id = attributes.get("entityID").cloned().unwrap_or_default();
+                        }
+                        b"IDPSSODescriptor" => in_idp = true,
+                        b"KeyDescriptor" if in_idp => {
+                            signing_key = matches!(attributes.get("use").map(String::as_str), None | Some("signing"));
+                        }
+                        b"X509Certificate" if in_idp && signing_key => in_certificate = true,
+                        b"SingleLogoutService"
+                            if in_idp
+                                && metadata.slo_url.is_none()
+                                && attributes.get("Binding").map(String::as_str) == Some(HTTP_REDIRECT_BINDING) =>
+                        {
+                            metadata.slo_url = attributes.get("Location").cloned();
+                            metadata.slo_response_url = attributes.get("ResponseLocation").cloned();
+                        }
+                        _ => {}
+                    }
+                }
+                Event::Text(text) if in_certificate => {
+                    let text = text.unescape().map_err(invalid)?;
+                    metadata.certificates.push(text.split_whitespace().collect());
+                }
+                Event::End(element) => match element.local_name().as_ref() {
+                    b"IDPSSODescriptor" => in_idp = false,
+                    b"X509Certificate" => in_certificate = false,
+                    b"KeyDescriptor" => signing_key = true,
+                    _ => {}
+                },
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        Ok(metadata)
+    }
+
+    fn certificates(&self) -> SSOResult<Vec<X509>> {
+        let certificates = self.certificates
+            .iter()
+            .map(|certificate| {
+                X509::from_der(&STANDARD.decode(certificate)?)
+                    .map_err(|e| SSOError::ConfigError(format!("Invalid IdP certificate: {}", e)))
+            })
+            .collect::<SSOResult<Vec<_>>>()?;
+
+        if certificates.is_empty() {
+            return Err(SSOError::ConfigError("IdP metadata has no signing certificate".to_string()));
+        }
+        Ok(certificates)
+    }
+}
+
+/// The fields of a LogoutRequest or LogoutResponse the SP reads
+#[derive(Debug, Default)]
+struct LogoutMessage {
+    kind: String,
+    id: String,
+    issue_instant: Option<String>,
+    destination: Option<String>,
+    in_response_to: Option<String>,
+    not_on_or_after: Option<String>,
+    issuer: Option<String>,
+    name_id: Option<String>,
+    session_indexes: Vec<String>,
+    status: Option<String>,
+}
+
+impl LogoutMessage {
+    fn parse(xml: &str) -> SSOResult<Self> {
+        let invalid = |e: quick_xml::Error| SSOError::SAMLError(format!("Invalid logout message: {}", e));
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+
+        let mut message = Self::default();
+        let mut text_of: Option<Vec<u8>> = None;
+
+        loop {
+            match reader.read_event().map_err(invalid)? {
+                Event::DocType(_) => {
+                    return Err(SSOError::SAMLError("Logout message must not declare a DTD".to_string()));
+                }
+                Event::Start(element) | Event::Empty(element) => {
+                    let name = element.local_name().as_ref().to_vec();
+                    let mut attributes = attributes(&element)?;
+                    match name.as_slice() {
+                        b"LogoutRequest" | b"LogoutResponse" if message.kind.is_empty() => {
+                            message.kind = String::from_utf8_lossy(&name).into_owned();
+                            message.id = attributes.remove("ID").unwrap_or_default();
+                            message.issue_instant = attributes.remove("IssueInstant");
+                            message.destination = attributes.remove("Destination");
+                            message.in_response_to = attributes.remove("InResponseTo");
+                            message.not_on_or_after = attributes.remove("NotOnOrAfter");
+                        }
+                        b"StatusCode" if message.status.is_none() => {
+                            message.status = attributes.remove("Value");
+                        }
+                        _ => text_of = Some(name),
+                    }
+                }
+                Event::Text(text) => {
+                    let text = text.unescape().map_err(invalid)?.into_owned();
+                    match text_of.as_deref() {
+                        Some(b"Issuer") if message.issuer.is_none() => message.issuer = Some(text),
+                        Some(b"NameID") => message.name_id = Some(text),
+                        Some(b"SessionIndex") => message.session_indexes.push(text),
+                        _ => {}
+                    }
+                }
+                Event::End(_) => text_of = None,
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        if message.kind.is_empty() || message.id.is_empty() {
+            return Err(SSOError::SAMLError("Not a SAML logout message".to_string()));
+        }
+        Ok(message)
+    }
+}
+
+fn attributes(element: &quick_xml::events::BytesStart) -> SSOResult<HashMap<String, String>> {
+    element
+        .attributes()
+        .map(|attribute| {
+            let attribute = attribute
+                .map_err(|e| SSOError::SAMLError(format!("Invalid XML attribute: {}", e)))?;
+            let value = attribute
+                .unescape_value()
+                .map_err(|e| SSOError::SAMLError(format!("Invalid XML attribute: {}", e)))?;
+            Ok((
+                String::from_utf8_lossy(attribute.key.local_name().as_ref()).into_owned(),
+                value.into_owned(),
+            ))
+        })
+        .collect()
+}
+
+fn parse_instant(value: Option<&str>) -> SSOResult<DateTime<Utc>> {
+    let value = value.ok_or_else(|| SSOError::SAMLError("Logout message has no IssueInstant".to_string()))?;
+    DateTime::parse_from_rfc3339(value)
+        .map(|instant| instant.with_timezone(&Utc))
+        .map_err(|e| SSOError::SAMLError(format!("Invalid SAML timestamp: {}", e)))
+}
+
+fn instant_now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn logout_request_xml(id: &str, destination: &str, issuer: &str, name_id: &str, session_index: Option<&str>) -> String {
+    let session_index = session_index
+        .map(|index| format!("<samlp:SessionIndex>{}</samlp:SessionIndex>", xml_escape(index)))
+        .unwrap_or_default();
+
+    format!(
+        r#"<samlp:LogoutRequest xmlns:samlp="urn:oasis:names:tc:SAML:2.0:protocol" xmlns:saml="urn:oasis:names:tc:SAML:2.0:assertion" ID="{}" Version="2.0" IssueInstant="{}" Destination="{}"><saml:Issuer>{}</saml:Issuer><saml:NameID>{}</saml:NameID>{}</samlp:LogoutRequest>"#,
+        xml_escape(id),
+        instant_now(),
+        xml_escape(destination),
+        xml_escape(issuer),
+        xml_escape(name_id),
+        session_index
+    )
+}
+
+fn logout_response_xml(id: &str, in_response_to: &str, destination: &str, issuer: &str) -> String {
+    format!(
+        r#"<samlp:LogoutResponse xmlns:samlp="urn:oasis:names:tc:SAML:2.0:protocol" xmlns:saml="urn:oasis:names:tc:SAML:2.0:assertion" ID="{}" Version="2.0" IssueInstant="{}" Destination="{}" InResponseTo="{}"><saml:Issuer>{}</saml:Issuer><samlp:Status><samlp:StatusCode Value="{}"/></samlp:Status></samlp:LogoutResponse>"#,
+        xml_escape(id),
+        instant_now(),
+        xml_escape(destination),
+        xml_escape(in_response_to),
+        xml_escape(issuer),
+        STATUS_SUCCESS
+    )
+}
+
+/// Encode and sign a message for the HTTP-Redirect binding
+fn encode_redirect(
+    destination: &str,
+    parameter: &str,
+    xml: &str,
+    relay_state: Option<&str>,
+    key: &PKey<openssl::pkey::Private>,
+) -> SSOResult<String> {
+    let mut deflater = DeflateEncoder::new(Vec::new(), Compression::default());
+    deflater.write_all(xml.as_bytes())
+        .and_then(|_| deflater.try_finish())
+        .map_err(|e| SSOError::SAMLError(format!("Failed to encode logout message: {}", e)))?;
+    let deflated = deflater.get_ref();
+
+    let mut query = format!("{}={}", parameter, urlencoding::encode(&STANDARD.encode(deflated)));
+    if let Some(relay_state) = relay_state {
+        query.push_str(&format!("&RelayState={}", urlencoding::encode(relay_state)));
+    }
+    query.push_str(&format!("&SigAlg={}", urlencoding::encode(RSA_SHA256)));
+
+    let signature = Signer::new(MessageDigest::sha256(), key)
+        .and_then(|mut signer| {
+            signer.update(query.as_bytes())?;
+            signer.sign_to_vec()
+        })
+        .map_err(|e| SSOError::SAMLError(format!("Failed to sign logout message: {}", e)))?;
+    query.push_str(&format!("&Signature={}", urlencoding::encode(&STANDARD.encode(signature))));
+
+    let separator = if destination.contains('?') { '&' } else { '?' };
+    Ok(format!("{}{}{}", destination, separator, query))
+}
+
+/// Verify and decode a message received with the HTTP-Redirect binding
+///
+/// The signature covers the parameters exactly as they appear in the query,
+/// so they are checked before URL decoding.
+fn decode_redirect(query: &str, parameter: &str, certificates: &[X509]) -> SSOResult<(String, Option<String>)> {
+    let raw: HashMap<&str, &str> = query
+        .trim_start_matches('?')
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .collect();
+    let unsigned = || SSOError::AuthenticationFailed("Logout message is not signed".to_string());
+    let decode = |value: &str| -> SSOResult<String> {
+        urlencoding::decode(value)
+            .map(|value| value.into_owned())
+            .map_err(|e| SSOError::SAMLError(format!("Invalid query encoding: {}", e)))
+    };
+
+    let message = raw.get(parameter)
+        .ok_or_else(|| SSOError::SAMLError(format!("Query has no {}", parameter)))?;
+    let sig_alg = raw.get("SigAlg").ok_or_else(unsigned)?;
+    let signature = raw.get("Signature").ok_or_else(unsigned)?;
+    if decode(sig_alg)? != RSA_SHA256 {
+        return Err(SSOError::AuthenticationFailed("Unsupported logout signature algorithm".to_string()));
+    }
+
+    let mut signed = format!("{}={}", parameter, message);
+    if let Some(relay_state) = raw.get("RelayState") {
+        signed.push_str(&format!("&RelayState={}", relay_state));
+    }
+    signed.push_str(&format!("&SigAlg={}", sig_alg));
+
+    let signature = STANDARD.decode(decode(signature)?)?;
+    let verified = certificates.iter().any(|certificate| {
+        certificate.public_key()
+            .and_then(|key| {
+                let mut verifier = Verifier::new(MessageDigest::sha256(), &key)?;
+                verifier.update(signed.as_bytes())?;
+                verifier.verify(&signature)
+            })
+            .unwrap_or(false)
+    });
+    if !verified {
+        return Err(SSOError::AuthenticationFailed("Logout message signature is invalid".to_string()));
+    }
+
+    let deflated = STANDARD.decode(decode(message)?)?;
+    let mut xml = String::new();
+    DeflateDecoder::new(deflated.as_slice())
+        .take(MAX_MESSAGE_SIZE)
+        .read_to_string(&mut xml)
+        .map_err(|e| SSOError::SAMLError(format!("Failed to inflate logout message: {}", e)))?;
+
+    let relay_state = raw.get("RelayState").map(|value| decode(value)).transpose()?;
+    Ok((xml, relay_state))
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 #[async_trait]
 impl SSOProvider for SAMLProvider {
-    async fn get_authorization_url(&self, state: &str, _nonce: Option<&str>) -> SSOResult<String> {
-        let saml_request = self.build_authn_request(state)?;
+    async fn get_authorization_url(&self, request: &AuthorizationState) -> SSOResult<String> {
+        let sp = self.service_provider()?;
+        let destination = sp.sso_binding_location(HTTP_REDIRECT_BINDING)
+            .ok_or_else(|| SSOError::SAMLError("IdP has no HTTP-Redirect SSO endpoint".to_string()))?;
+
+        let mut authn_request = sp.make_authentication_request(&destination)
+            .map_err(|e| SSOError::SAMLError(format!("Failed to build AuthnRequest: {}", e)))?;
+        authn_request.id = request_id(request);
+
+        let url = if self.sign_authn_request {
+            let key = self.private_key()?
+                .private_key_to_der()
+                .map_err(|e| SSOError::ConfigError(format!("Invalid SP private key: {}", e)))?;
+            authn_request.signed_redirect(&request.state, &key)
+        } else {
+            authn_request.redirect(&request.state)
+        }
+        .map_err(|e| SSOError::SAMLError(format!("Failed to encode AuthnRequest: {}", e)))?;
+
+        url.map(|url| url.to_string())
+            .ok_or_else(|| SSOError::SAMLError("AuthnRequest has no destination".to_string()))
+    }
+
+    async fn exchange_code(&self, code: &str, request: &AuthorizationState) -> SSOResult<SSOUser> {
+        // For SAML, the "code" is the base64 SAMLResponse posted to the ACS
+        let sp = self.service_provider()?;
+        let request_id = request_id(request);
 
-        let mut url = url::Url::parse(&self.sso_url)
-            .map_err(|e| SSOError::SAMLError(format!("Invalid SSO URL: {}", e)))?;
+        let assertion = sp.parse_base64_response(code, Some(&[request_id.as_str()]))
+            .map_err(|e| SSOError::AuthenticationFailed(format!("SAML response rejected: {}", e)))?;
 
-        url.query_pairs_mut()
-            .append_pair("SAMLRequest", &saml_request)
-            .append_pair("RelayState", state);
+        self.map_assertion(&assertion)
+    }
+
+    async fn logout_url(&self, session: &Session, request: &AuthorizationState) -> SSOResult<Option<String>> {
+        if self.sp_slo_url.is_none() {
+            return Ok(None);
+        }
+        let Some(name_id) = session.idp_subject.as_deref() else {
+            return Ok(None);
+        };
+        let idp = IdpLogoutMetadata::parse(&self.idp_metadata_xml())?;
+        let Some(destination) = idp.slo_url else {
+            return Ok(None);
+        };
+
+        let xml = logout_request_xml(
+            &request_id(request),
+            &destination,
+            &self.sp_entity_id,
+            name_id,
+            session.idp_session_id.as_deref(),
+        );
+        encode_redirect(&destination, "SAMLRequest", &xml, Some(&request.state), &self.signing_key()?).map(Some)
+    }
+
+    async fn verify_logout_response(&self, query: &str, request: &AuthorizationState) -> SSOResult<()> {
+        let (message, _) = self.receive_logout_message(query, "SAMLResponse")?;
 
-        Ok(url.to_string())
+        if message.kind != "LogoutResponse" {
+            return Err(SSOError::SAMLError("Expected a LogoutResponse".to_string()));
+        }
+        if message.in_response_to.as_deref() != Some(request_id(request).as_str()) {
+            return Err(SSOError::AuthenticationFailed("LogoutResponse answers another request".to_string()));
+        }
+        match message.status.as_deref() {
+            Some(STATUS_SUCCESS) => Ok(()),
+            status => Err(SSOError::SAMLError(format!(
+                "IdP reported logout status {}",
+                status.unwrap_or("(none)")
+            ))),
+        }
     }
 
-    async fn exchange_code(&self, code: &str, _state: &str) -> SSOResult<SSOUser> {
-        // For SAML, the "code" is actually the SAMLResponse
-        self.parse_saml_response(code)
+    async fn parse_logout_request(&self, query: &str) -> SSOResult<RemoteLogout> {
+        let (message, relay_state) = self.receive_logout_message(query, "SAMLRequest")?;
+
+        if message.kind != "LogoutRequest" {
+            return Err(SSOError::SAMLError("Expected a LogoutRequest".to_string()));
+        }
+        let name_id = message.name_id
+            .ok_or_else(|| SSOError::SAMLError("LogoutRequest has no NameID".to_string()))?;
+
+        let skew = Duration::seconds(self.max_clock_skew);
+        let expires_at = match message.not_on_or_after.as_deref() {
+            Some(not_on_or_after) => parse_instant(Some(not_on_or_after))? + skew,
+            None => parse_instant(message.issue_instant.as_deref())? + Duration::seconds(LOGOUT_TOKEN_MAX_AGE) + skew,
+        };
+        if expires_at <= Utc::now() {
+            return Err(SSOError::AuthenticationFailed("LogoutRequest has expired".to_string()));
+        }
+
+        Ok(RemoteLogout {
+            provider: self.name.clone(),
+            subject: Some(name_id),
+            session_ids: message.session_indexes,
+            message_id: message.id,
+            relay_state,
+            expires_at,
+        })
+    }
+
+    async fn logout_response_url(&self, logout: &RemoteLogout) -> SSOResult<String> {
+        let idp = IdpLogoutMetadata::parse(&self.idp_metadata_xml())?;
+        let destination = idp.slo_response_url
+            .or(idp.slo_url)
+            .ok_or_else(|| SSOError::SAMLError("IdP has no HTTP-Redirect logout endpoint".to_string()))?;
+
+        let xml = logout_response_xml(
+            &format!("_{}", uuid::Uuid::new_v4()),
+            &logout.message_id,
+            &destination,
+            &self.sp_entity_id,
+        );
+        encode_redirect(&destination, "SAMLResponse", &xml, logout.relay_state.as_deref(), &self.signing_key()?)
     }
 
-    async fn validate_token(&self, token: &str) -> SSOResult<SSOUser> {
+    async fn validate_token(&self, _token: &str) -> SSOResult<SSOUser> {
         // SAML doesn't use tokens in the same way as OAuth/OIDC
         // This would validate a session token created after SAML authentication
         Err(SSOError::SAMLError("Token validation not supported for SAML".to_string()))
@@ -170,22 +746,179 @@ impl SSOProvider for SAMLProvider {
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_saml_provider_creation() {
-        let config = ProviderConfig::SAML {
+    fn config() -> ProviderConfig {
+        ProviderConfig::SAML {
+            idp_metadata: None,
             entity_id: "https://idp.example.com".to_string(),
-            sso_url: "https://idp.example.com/sso".to_string(),
+            sso_url: "https://idp.example.com/sso?tenant=a&b".to_string(),
             slo_url: Some("https://idp.example.com/slo".to_string()),
-            idp_certificate: "cert".to_string(),
+            idp_certificate: "-----BEGIN CERTIFICATE-----\nMIIB\nAAAA\n-----END CERTIFICATE-----\n".to_string(),
             sp_entity_id: "https://sp.example.com".to_string(),
             acs_url: "https://sp.example.com/acs".to_string(),
+            sp_slo_url: Some("https://sp.example.com/slo".to_string()),
             sp_private_key: "key".to_string(),
             sp_certificate: "cert".to_string(),
             sign_authn_request: true,
             want_assertions_signed: true,
-        };
+            max_clock_skew: 180,
+            attribute_mapping: SAMLAttributeMapping::default(),
+        }
+    }
 
-        let provider = SAMLProvider::new("test-saml", config);
+    #[test]
+    fn test_saml_provider_creation() {
+        let provider = SAMLProvider::new("test-saml", config());
         assert!(provider.is_ok());
+
+        let mut missing_idp = config();
+        if let ProviderConfig::SAML { sso_url, .. } = &mut missing_idp {
+            sso_url.clear();
+        }
+        assert!(matches!(
+            SAMLProvider::new("test-saml", missing_idp),
+            Err(SSOError::ConfigError(_))
+        ));
+    }
+
+    #[test]
+    fn test_configured_idp_metadata() {
+        let provider = SAMLProvider::new("test-saml", config()).unwrap();
+        let xml = provider.configured_idp_metadata();
+
+        assert!(xml.contains(r#"entityID="https://idp.example.com""#));
+        assert!(xml.contains("<ds:X509Certificate>MIIBAAAA</ds:X509Certificate>"));
+        assert!(xml.contains(r#"Location="https://idp.example.com/sso?tenant=a&amp;b""#));
+        assert!(xml.contains("SingleLogoutService"));
+    }
+
+    #[test]
+    fn test_attribute_mapping() {
+        let provider = SAMLProvider::new("test-saml", config()).unwrap();
+        let mut attributes = BTreeMap::new();
+        attributes.insert("mail".to_string(), vec!["jane@example.com".to_string()]);
+        attributes.insert("givenName".to_string(), vec!["Jane".to_string()]);
+        attributes.insert("urn:oid:2.5.4.4".to_string(), vec!["Doe".to_string()]);
+
+        let user = provider
+            .map_attributes(Some("jdoe".to_string()), Some("_s1".to_string()), attributes)
+            .unwrap();
+        assert_eq!(user.id, "jdoe");
+        assert_eq!(user.email, "jane@example.com");
+        assert_eq!(user.given_name.as_deref(), Some("Jane"));
+        assert_eq!(user.family_name.as_deref(), Some("Doe"));
+        assert_eq!(user.provider, "test-saml");
+        assert_eq!(user.idp_subject().as_deref(), Some("jdoe"));
+        assert_eq!(user.idp_session_id().as_deref(), Some("_s1"));
+
+        // An email-shaped NameID stands in for a missing email attribute
+        let user = provider
+            .map_attributes(Some("jane@example.com".to_string()), None, BTreeMap::new())
+            .unwrap();
+        assert_eq!(user.email, "jane@example.com");
+
+        assert!(provider.map_attributes(Some("jdoe".to_string()), None, BTreeMap::new()).is_err());
+        assert!(provider.map_attributes(None, None, BTreeMap::new()).is_err());
+    }
+
+    /// Key pair standing in for both the IdP's and the SP's
+    fn key_pair() -> (Rsa<openssl::pkey::Private>, X509) {
+        let rsa = Rsa::generate(2048).unwrap();
+        let key = PKey::from_rsa(rsa.clone()).unwrap();
+
+        let mut name = openssl::x509::X509NameBuilder::new().unwrap();
+        name.append_entry_by_text("CN", "idp.example.com").unwrap();
+        let name = name.build();
+
+        let mut builder = X509::builder().unwrap();
+        builder.set_version(2).unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_issuer_name(&name).unwrap();
+        builder.set_pubkey(&key).unwrap();
+        builder.set_not_before(&openssl::asn1::Asn1Time::days_from_now(0).unwrap()).unwrap();
+        builder.set_not_after(&openssl::asn1::Asn1Time::days_from_now(1).unwrap()).unwrap();
+        builder.sign(&key, MessageDigest::sha256()).unwrap();
+
+        (rsa, builder.build())
+    }
+
+    fn slo_provider() -> (SAMLProvider, PKey<openssl::pkey::Private>) {
+        let (rsa, certificate) = key_pair();
+        let mut config = config();
+        if let ProviderConfig::SAML { idp_certificate, sp_private_key, .. } = &mut config {
+            *idp_certificate = String::from_utf8(certificate.to_pem().unwrap()).unwrap();
+            *sp_private_key = String::from_utf8(rsa.private_key_to_pem().unwrap()).unwrap();
+        }
+        (SAMLProvider::new("test-saml", config).unwrap(), PKey::from_rsa(rsa).unwrap())
+    }
+
+    fn idp_logout_request(key: &PKey<openssl::pkey::Private>, issuer: &str) -> String {
+        let xml = logout_request_xml("_idp1", "https://sp.example.com/slo", issuer, "jdoe", Some("_s1"));
+        let url = encode_redirect("https://sp.example.com/slo", "SAMLRequest", &xml, Some("relay"), key).unwrap();
+        url.split_once('?').unwrap().1.to_string()
+    }
+
+    #[test]
+    fn test_idp_logout_metadata() {
+        let provider = SAMLProvider::new("test-saml", config()).unwrap();
+        let idp = IdpLogoutMetadata::parse(&provider.configured_idp_metadata()).unwrap();
+
+        assert_eq!(idp.entity_id, "https://idp.example.com");
+        assert_eq!(idp.slo_url.as_deref(), Some("https://idp.example.com/slo"));
+        assert_eq!(idp.certificates, vec!["MIIBAAAA".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_idp_initiated_logout() {
+        let (provider, key) = slo_provider();
+
+        let logout = provider
+            .parse_logout_request(&idp_logout_request(&key, "https://idp.example.com"))
+            .await
+            .unwrap();
+        assert_eq!(logout.subject.as_deref(), Some("jdoe"));
+        assert_eq!(logout.session_ids, vec!["_s1".to_string()]);
+        assert_eq!(logout.message_id, "_idp1");
+        assert_eq!(logout.relay_state.as_deref(), Some("relay"));
+
+        let url = provider.logout_response_url(&logout).await.unwrap();
+        assert!(url.starts_with("https://idp.example.com/slo?SAMLResponse="));
+        let (xml, relay_state) =
+            decode_redirect(url.split_once('?').unwrap().1, "SAMLResponse", &[key_certificate(&provider)]).unwrap();
+        let response = LogoutMessage::parse(&xml).unwrap();
+        assert_eq!(response.kind, "LogoutResponse");
+        assert_eq!(response.in_response_to.as_deref(), Some("_idp1"));
+        assert_eq!(response.status.as_deref(), Some(STATUS_SUCCESS));
+        assert_eq!(relay_state.as_deref(), Some("relay"));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_logout_requests_rejected() {
+        let (provider, key) = slo_provider();
+
+        // Signed by someone other than the IdP
+        let (other, _) = key_pair();
+        let forged = idp_logout_request(&PKey::from_rsa(other).unwrap(), "https://idp.example.com");
+        assert!(provider.parse_logout_request(&forged).await.is_err());
+
+        // Unsigned
+        let query = idp_logout_request(&key, "https://idp.example.com");
+        let unsigned = query.split("&SigAlg=").next().unwrap();
+        assert!(provider.parse_logout_request(unsigned).await.is_err());
+
+        // Tampered after signing
+        let tampered = query.replace("RelayState=relay", "RelayState=other");
+        assert!(provider.parse_logout_request(&tampered).await.is_err());
+
+        // Signed, but claiming another issuer
+        let query = idp_logout_request(&key, "https://other-idp.example.com");
+        assert!(provider.parse_logout_request(&query).await.is_err());
+    }
+
+    fn key_certificate(provider: &SAMLProvider) -> X509 {
+        IdpLogoutMetadata::parse(&provider.configured_idp_metadata())
+            .unwrap()
+            .certificates()
+            .unwrap()
+            .remove(0)
     }
 }
@@ -0,0 +1,145 @@
+//! OAuth 2.0 Token Introspection (RFC 7662)
+//!
+//! Opaque access tokens cannot be verified locally; the authorization
+//! server is asked whether they are active instead.
+
+use crate::{SSOError, SSOResult};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+/// Introspection endpoint of an authorization server
+pub struct TokenIntrospector {
+    endpoint: String,
+    client_id: String,
+    client_secret: String,
+    client: reqwest::Client,
+}
+
+/// Introspection response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntrospectionResponse {
+    /// Whether the token is currently active
+    pub active: bool,
+
+    /// Space-separated scopes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+
+    /// Client the token was issued to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+
+    /// Resource owner username
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+
+    /// Subject
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+
+    /// Issuer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+
+    /// Expiration time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<i64>,
+
+    /// Not valid before
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<i64>,
+
+    /// Other members of the response
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl IntrospectionResponse {
+    /// Check that the token is active, within its validity window and, if
+    /// both sides name an issuer, issued by `issuer`
+    pub fn verify(&self, issuer: Option<&str>) -> SSOResult<()> {
+        if !self.active {
+            return Err(SSOError::InvalidToken);
+        }
+
+        let now = Utc::now().timestamp();
+        if self.exp.is_some_and(|exp| exp <= now) {
+            return Err(SSOError::TokenExpired);
+        }
+        if self.nbf.is_some_and(|nbf| nbf > now) {
+            return Err(SSOError::TokenValidationFailed("Token not yet valid".to_string()));
+        }
+        if let (Some(iss), Some(issuer)) = (self.iss.as_deref(), issuer) {
+            if iss != issuer {
+                return Err(SSOError::TokenValidationFailed("Token from another issuer".to_string()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl TokenIntrospector {
+    /// Create an introspector authenticating as the given client
+    pub fn new(endpoint: &str, client_id: &str, client_secret: &str) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            client_id: client_id.to_string(),
+            client_secret: client_secret.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Ask the server about an access token
+    pub async fn introspect(&self, token: &str) -> SSOResult<IntrospectionResponse> {
+        let response = self.client
+            .post(&self.endpoint)
+            .basic_auth(&self.client_id, Some(&self.client_secret))
+            .form(&[("token", token), ("token_type_hint", "access_token")])
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(SSOError::TokenValidationFailed(format!(
+                "Introspection failed with status {}",
+                response.status()
+            )));
+        }
+
+        Ok(response.json().await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(value: serde_json::Value) -> IntrospectionResponse {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_introspection_response_verified() {
+        let now = Utc::now().timestamp();
+        let active = response(serde_json::json!({
+            "active": true,
+            "sub": "alice",
+            "iss": "https://as.example.com",
+            "exp": now + 60,
+            "tenant": "acme",
+        }));
+        assert!(active.verify(Some("https://as.example.com")).is_ok());
+        assert_eq!(active.extra["tenant"], "acme");
+        assert!(active.verify(Some("https://other.example.com")).is_err());
+        assert!(active.verify(None).is_ok());
+
+        assert!(matches!(
+            response(serde_json::json!({ "active": false })).verify(Some("https://as.example.com")),
+            Err(SSOError::InvalidToken)
+        ));
+        assert!(matches!(
+            response(serde_json::json!({ "active": true, "exp": now - 1 })).verify(Some("https://as.example.com")),
+            Err(SSOError::TokenExpired)
+        ));
+    }
+}
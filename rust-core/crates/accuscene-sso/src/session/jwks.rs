@@ -0,0 +1,298 @@
+//! Provider Signing Key Cache
+//!
+//! Tokens issued by identity providers are verified against the key set
+//! (JWKS) the provider publishes. [`JwksCache`] keeps each key set for a
+//! TTL and fetches it again early when a token names a key ID it does not
+//! hold, which is how providers roll keys: the new key is published first,
+//! then used. Refetches for unknown key IDs happen at most once per
+//! `min_refresh_interval`, so tokens with made-up key IDs cannot flood the
+//! provider, and a failed fetch keeps serving the keys already held.
+//!
+//! JWKS URIs found through OIDC discovery are remembered per issuer so
+//! validating a token needs no discovery round trip.
+
+use crate::{SSOError, SSOResult};
+use async_trait::async_trait;
+use jsonwebtoken::{jwk::JwkSet, DecodingKey, Header};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock};
+
+/// Where key sets are fetched from
+#[async_trait]
+pub trait KeySource: Send + Sync {
+    /// Fetch the key set published at `jwks_uri`
+    async fn fetch(&self, jwks_uri: &str) -> SSOResult<JwkSet>;
+}
+
+/// Fetches key sets over HTTP
+#[derive(Default)]
+pub struct HttpKeySource {
+    client: reqwest::Client,
+}
+
+#[async_trait]
+impl KeySource for HttpKeySource {
+    async fn fetch(&self, jwks_uri: &str) -> SSOResult<JwkSet> {
+        Ok(self.client.get(jwks_uri).send().await?.error_for_status()?.json().await?)
+    }
+}
+
+/// Counters of the key cache
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct JwksCacheMetrics {
+    /// Lookups answered from the cache
+    pub hits: u64,
+
+    /// Lookups that needed a fetch
+    pub misses: u64,
+
+    /// Key sets fetched
+    pub refreshes: u64,
+
+    /// Fetches that failed
+    pub refresh_failures: u64,
+
+    /// Key sets held
+    pub key_sets: usize,
+}
+
+struct CachedKeys {
+    keys: Arc<JwkSet>,
+    fetched_at: Instant,
+}
+
+/// Cache of provider key sets, keyed by JWKS URI
+pub struct JwksCache {
+    source: Arc<dyn KeySource>,
+    ttl: Duration,
+    min_refresh_interval: Duration,
+    entries: RwLock<HashMap<String, CachedKeys>>,
+    discovered: RwLock<HashMap<String, String>>,
+    fetch_lock: Mutex<()>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    refreshes: AtomicU64,
+    refresh_failures: AtomicU64,
+}
+
+impl JwksCache {
+    /// Create a cache fetching over HTTP
+    pub fn new(ttl: Duration, min_refresh_interval: Duration) -> Self {
+        Self {
+            source: Arc::new(HttpKeySource::default()),
+            ttl,
+            min_refresh_interval,
+            entries: RwLock::new(HashMap::new()),
+            discovered: RwLock::new(HashMap::new()),
+            fetch_lock: Mutex::new(()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            refreshes: AtomicU64::new(0),
+            refresh_failures: AtomicU64::new(0),
+        }
+    }
+
+    /// Fetch key sets from another source
+    pub fn with_source(mut self, source: Arc<dyn KeySource>) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Get the key set at `jwks_uri`, making sure it holds `kid` if it can
+    ///
+    /// The returned set may still lack `kid` when the provider does not
+    /// publish it or was refetched too recently.
+    pub async fn key_set(&self, jwks_uri: &str, kid: Option<&str>) -> SSOResult<Arc<JwkSet>> {
+        if let Some(keys) = self.fresh(jwks_uri, kid).await {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(keys);
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        // One fetch at a time; callers that waited usually find it done
+        let _fetching = self.fetch_lock.lock().await;
+        if let Some(keys) = self.fresh(jwks_uri, kid).await {
+            return Ok(keys);
+        }
+
+        let held = self.entries.read().await.get(jwks_uri).map(|entry| (entry.keys.clone(), entry.fetched_at));
+        if let Some((keys, fetched_at)) = &held {
+            if fetched_at.elapsed() < self.min_refresh_interval {
+                return Ok(keys.clone());
+            }
+        }
+
+        match self.source.fetch(jwks_uri).await {
+            Ok(keys) => {
+                self.refreshes.fetch_add(1, Ordering::Relaxed);
+                let keys = Arc::new(keys);
+                self.entries.write().await.insert(
+                    jwks_uri.to_string(),
+                    CachedKeys { keys: keys.clone(), fetched_at: Instant::now() },
+                );
+                Ok(keys)
+            }
+            Err(err) => {
+                self.refresh_failures.fetch_add(1, Ordering::Relaxed);
+                match held {
+                    Some((keys, _)) => {
+                        tracing::warn!(jwks_uri, error = %err, "Key set refresh failed; serving cached keys");
+                        Ok(keys)
+                    }
+                    None => Err(err),
+                }
+            }
+        }
+    }
+
+    /// Get the key a token header names
+    pub async fn decoding_key(&self, jwks_uri: &str, header: &Header) -> SSOResult<DecodingKey> {
+        let keys = self.key_set(jwks_uri, header.kid.as_deref()).await?;
+        let jwk = match header.kid.as_deref() {
+            Some(kid) => keys.find(kid),
+            None if keys.keys.len() == 1 => keys.keys.first(),
+            None => None,
+        }
+        .ok_or_else(|| SSOError::TokenValidationFailed("Unknown token signing key".to_string()))?;
+
+        Ok(DecodingKey::from_jwk(jwk)?)
+    }
+
+    /// JWKS URI discovered earlier for an issuer
+    pub async fn discovered_uri(&self, issuer: &str) -> Option<String> {
+        self.discovered.read().await.get(issuer).cloned()
+    }
+
+    /// Remember the JWKS URI discovered for an issuer
+    pub async fn remember_uri(&self, issuer: &str, jwks_uri: &str) {
+        self.discovered.write().await.insert(issuer.to_string(), jwks_uri.to_string());
+    }
+
+    /// Drop the cached key set at `jwks_uri`
+    pub async fn invalidate(&self, jwks_uri: &str) {
+        self.entries.write().await.remove(jwks_uri);
+    }
+
+    /// Current counters
+    pub async fn metrics(&self) -> JwksCacheMetrics {
+        JwksCacheMetrics {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            refreshes: self.refreshes.load(Ordering::Relaxed),
+            refresh_failures: self.refresh_failures.load(Ordering::Relaxed),
+            key_sets: self.entries.read().await.len(),
+        }
+    }
+
+    /// The cached set if it is within its TTL and holds `kid`
+    async fn fresh(&self, jwks_uri: &str, kid: Option<&str>) -> Option<Arc<JwkSet>> {
+        let entries = self.entries.read().await;
+        let entry = entries.get(jwks_uri)?;
+
+        let current = entry.fetched_at.elapsed() < self.ttl;
+        let has_kid = match kid {
+            Some(kid) => entry.keys.find(kid).is_some(),
+            None => true,
+        };
+        (current && has_kid).then(|| entry.keys.clone())
+    }
+}
+
+impl Default for JwksCache {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(3600), Duration::from_secs(30))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+
+    /// Serves the keys it is given and counts fetches
+    #[derive(Default)]
+    struct StubSource {
+        kids: std::sync::Mutex<Vec<&'static str>>,
+        fetches: AtomicU64,
+        failing: AtomicBool,
+    }
+
+    impl StubSource {
+        fn publish(&self, kids: &[&'static str]) {
+            *self.kids.lock().unwrap() = kids.to_vec();
+        }
+    }
+
+    #[async_trait]
+    impl KeySource for StubSource {
+        async fn fetch(&self, _jwks_uri: &str) -> SSOResult<JwkSet> {
+            self.fetches.fetch_add(1, Ordering::Relaxed);
+            if self.failing.load(Ordering::Relaxed) {
+                return Err(SSOError::NetworkError("unreachable".to_string()));
+            }
+
+            let keys: Vec<_> = self.kids.lock().unwrap().iter()
+                .map(|kid| serde_json::json!({ "kty": "oct", "kid": kid, "k": "c2VjcmV0" }))
+                .collect();
+            Ok(serde_json::from_value(serde_json::json!({ "keys": keys })).unwrap())
+        }
+    }
+
+    const URI: &str = "https://idp.example.com/jwks";
+
+    fn cache(source: &Arc<StubSource>, min_refresh: Duration) -> JwksCache {
+        JwksCache::new(Duration::from_secs(3600), min_refresh).with_source(source.clone())
+    }
+
+    #[tokio::test]
+    async fn test_keys_cached_and_rotated() {
+        let source = Arc::new(StubSource::default());
+        source.publish(&["k1"]);
+        let cache = cache(&source, Duration::ZERO);
+
+        assert!(cache.key_set(URI, Some("k1")).await.unwrap().find("k1").is_some());
+        assert!(cache.key_set(URI, Some("k1")).await.unwrap().find("k1").is_some());
+        assert_eq!(source.fetches.load(Ordering::Relaxed), 1);
+
+        // The provider rolls to k2; the first token naming it triggers a refetch
+        source.publish(&["k1", "k2"]);
+        assert!(cache.key_set(URI, Some("k2")).await.unwrap().find("k2").is_some());
+        assert_eq!(source.fetches.load(Ordering::Relaxed), 2);
+
+        let metrics = cache.metrics().await;
+        assert_eq!(metrics.hits, 1);
+        assert_eq!(metrics.misses, 2);
+        assert_eq!(metrics.refreshes, 2);
+        assert_eq!(metrics.key_sets, 1);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_kid_refetch_limited() {
+        let source = Arc::new(StubSource::default());
+        source.publish(&["k1"]);
+        let cache = cache(&source, Duration::from_secs(60));
+
+        cache.key_set(URI, Some("k1")).await.unwrap();
+        for _ in 0..5 {
+            assert!(cache.key_set(URI, Some("bogus")).await.unwrap().find("bogus").is_none());
+        }
+        assert_eq!(source.fetches.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_refresh_serves_cached_keys() {
+        let source = Arc::new(StubSource::default());
+        source.publish(&["k1"]);
+        let cache = cache(&source, Duration::ZERO);
+        cache.key_set(URI, None).await.unwrap();
+
+        source.failing.store(true, Ordering::Relaxed);
+        assert!(cache.key_set(URI, Some("k2")).await.unwrap().find("k1").is_some());
+        assert!(cache.key_set("https://other.example.com/jwks", None).await.is_err());
+        assert_eq!(cache.metrics().await.refresh_failures, 2);
+    }
+}
@@ -2,8 +2,10 @@
 
 pub mod token;
 pub mod refresh;
+pub mod jwks;
+pub mod introspection;
 
-use crate::{SSOUser, SSOError, SSOResult, AuthenticationResult, config::SessionConfig};
+use crate::{SSOUser, SSOError, SSOResult, AuthenticationResult, config::SessionConfig, logout::RemoteLogout};
 use chrono::{Utc, Duration, DateTime};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -11,8 +13,10 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
-pub use token::TokenManager;
+pub use token::{ExternalTokenSource, TokenManager};
 pub use refresh::RefreshTokenManager;
+pub use jwks::{JwksCache, JwksCacheMetrics, KeySource};
+pub use introspection::{IntrospectionResponse, TokenIntrospector};
 
 /// Session information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +30,18 @@ pub struct Session {
     /// User email
     pub email: String,
 
+    /// Provider the user signed in with
+    #[serde(default)]
+    pub provider: String,
+
+    /// Subject at the identity provider (`sub` or NameID)
+    #[serde(default)]
+    pub idp_subject: Option<String>,
+
+    /// Session at the identity provider (`sid` or SessionIndex)
+    #[serde(default)]
+    pub idp_session_id: Option<String>,
+
     /// Created timestamp
     pub created_at: DateTime<Utc>,
 
@@ -64,7 +80,7 @@ impl Session {
 /// Session manager
 pub struct SessionManager {
     config: SessionConfig,
-    token_manager: TokenManager,
+    token_manager: Arc<TokenManager>,
     refresh_manager: RefreshTokenManager,
     sessions: Arc<RwLock<HashMap<Uuid, Session>>>,
     user_sessions: Arc<RwLock<HashMap<String, Vec<Uuid>>>>,
@@ -74,7 +90,7 @@ impl SessionManager {
     /// Create new session manager
     pub fn new(config: SessionConfig) -> Self {
         Self {
-            token_manager: TokenManager::new(config.clone()),
+            token_manager: Arc::new(TokenManager::new(config.clone())),
             refresh_manager: RefreshTokenManager::new(config.clone()),
             config,
             sessions: Arc::new(RwLock::new(HashMap::new())),
@@ -82,6 +98,11 @@ impl SessionManager {
         }
     }
 
+    /// Get the token manager, shared with the providers
+    pub fn token_manager(&self) -> Arc<TokenManager> {
+        self.token_manager.clone()
+    }
+
     /// Create new session
     pub async fn create_session(&self, user: &SSOUser) -> SSOResult<AuthenticationResult> {
         let session_id = Uuid::new_v4();
@@ -92,6 +113,9 @@ impl SessionManager {
             id: session_id,
             user_id: user.id.clone(),
             email: user.email.clone(),
+            provider: user.provider.clone(),
+            idp_subject: user.idp_subject(),
+            idp_session_id: user.idp_session_id(),
             created_at: now,
             last_accessed: now,
             expires_at: now + Duration::seconds(self.config.session_timeout),
@@ -139,8 +163,19 @@ impl SessionManager {
     }
 
     /// Validate access token and return user
+    ///
+    /// Tokens of sessions that were logged out stop validating at once.
     pub async fn validate_token(&self, token: &str) -> SSOResult<SSOUser> {
-        self.token_manager.validate_access_token(token).await
+        let user = self.token_manager.validate_access_token(token).await?;
+        let session_id = self.token_manager.extract_session_id(token)?;
+
+        let sessions = self.sessions.read().await;
+        let session = sessions.get(&session_id).ok_or(SSOError::SessionNotFound)?;
+        if session.is_expired() {
+            return Err(SSOError::SessionExpired);
+        }
+
+        Ok(user)
     }
 
     /// Refresh session using refresh token
@@ -228,6 +263,30 @@ impl SessionManager {
         Ok(())
     }
 
+    /// Invalidate the sessions an identity provider asked to end
+    pub async fn invalidate_remote_sessions(&self, logout: &RemoteLogout) -> Vec<Session> {
+        let mut sessions = self.sessions.write().await;
+        let mut user_sessions = self.user_sessions.write().await;
+
+        let ended: Vec<Uuid> = sessions
+            .values()
+            .filter(|session| logout.matches(session))
+            .map(|session| session.id)
+            .collect();
+
+        let mut removed = Vec::with_capacity(ended.len());
+        for session_id in ended {
+            if let Some(session) = sessions.remove(&session_id) {
+                if let Some(user_session_ids) = user_sessions.get_mut(&session.user_id) {
+                    user_session_ids.retain(|id| id != &session_id);
+                }
+                removed.push(session);
+            }
+        }
+
+        removed
+    }
+
     /// Clean up expired sessions
     pub async fn cleanup_expired_sessions(&self) -> usize {
         let now = Utc::now();
@@ -264,6 +323,9 @@ mod tests {
             id: Uuid::new_v4(),
             user_id: "user123".to_string(),
             email: "user@example.com".to_string(),
+            provider: "oidc".to_string(),
+            idp_subject: None,
+            idp_session_id: None,
             created_at: Utc::now() - Duration::hours(2),
             last_accessed: Utc::now() - Duration::hours(1),
             expires_at: Utc::now() - Duration::minutes(30),
@@ -194,6 +194,8 @@ mod tests {
             max_sessions_per_user: 5,
             issuer: "test-issuer".to_string(),
             audience: "test-audience".to_string(),
+            jwks_cache_ttl: 3600,
+            jwks_min_refresh_interval: 30,
         }
     }
 
@@ -1,9 +1,15 @@
 //! JWT Token Management
+//!
+//! Besides the session tokens it issues, the token manager validates tokens
+//! issued by identity providers: JWTs against the provider's cached signing
+//! keys, and opaque tokens through the provider's introspection endpoint.
 
+use super::{introspection::TokenIntrospector, jwks::{JwksCache, JwksCacheMetrics}};
 use crate::{SSOUser, SSOError, SSOResult, config::SessionConfig};
 use chrono::{Utc, Duration};
-use jsonwebtoken::{encode, decode, Header, Validation, EncodingKey, DecodingKey, Algorithm};
+use jsonwebtoken::{encode, decode, decode_header, Header, Validation, EncodingKey, DecodingKey, Algorithm};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use uuid::Uuid;
 
 /// JWT token claims
@@ -43,11 +49,27 @@ pub struct TokenClaims {
     pub mfa_verified: bool,
 }
 
+/// How to validate the tokens of an identity provider
+pub struct ExternalTokenSource {
+    /// Expected issuer, if the provider has one
+    pub issuer: Option<String>,
+
+    /// Expected audience of JWTs
+    pub audience: String,
+
+    /// Signing keys of JWTs
+    pub jwks_uri: Option<String>,
+
+    /// Introspection endpoint for opaque tokens
+    pub introspection: Option<TokenIntrospector>,
+}
+
 /// Token manager for creating and validating JWT tokens
 pub struct TokenManager {
     config: SessionConfig,
     encoding_key: EncodingKey,
     decoding_key: DecodingKey,
+    jwks: Arc<JwksCache>,
 }
 
 impl TokenManager {
@@ -57,10 +79,63 @@ impl TokenManager {
         Self {
             encoding_key: EncodingKey::from_secret(secret),
             decoding_key: DecodingKey::from_secret(secret),
+            jwks: Arc::new(JwksCache::new(
+                std::time::Duration::from_secs(config.jwks_cache_ttl),
+                std::time::Duration::from_secs(config.jwks_min_refresh_interval),
+            )),
             config,
         }
     }
 
+    /// Use another provider key cache
+    pub fn with_jwks_cache(mut self, jwks: Arc<JwksCache>) -> Self {
+        self.jwks = jwks;
+        self
+    }
+
+    /// Get the provider key cache
+    pub fn jwks_cache(&self) -> Arc<JwksCache> {
+        self.jwks.clone()
+    }
+
+    /// Get the provider key cache counters
+    pub async fn jwks_metrics(&self) -> JwksCacheMetrics {
+        self.jwks.metrics().await
+    }
+
+    /// Validate a token issued by an identity provider and return its claims
+    ///
+    /// JWTs are verified with the provider's signing keys; other tokens, and
+    /// JWTs when the provider publishes no keys, are introspected.
+    pub async fn validate_external_token(
+        &self,
+        token: &str,
+        source: &ExternalTokenSource,
+    ) -> SSOResult<serde_json::Value> {
+        let is_jwt = token.split('.').count() == 3;
+
+        if let Some(jwks_uri) = source.jwks_uri.as_deref().filter(|_| is_jwt) {
+            let header = decode_header(token)?;
+            let key = self.jwks.decoding_key(jwks_uri, &header).await?;
+
+            let mut validation = Validation::new(header.alg);
+            validation.set_audience(&[&source.audience]);
+            if let Some(issuer) = &source.issuer {
+                validation.set_issuer(&[issuer]);
+            }
+
+            return Ok(decode::<serde_json::Value>(token, &key, &validation)?.claims);
+        }
+
+        let introspection = source.introspection.as_ref().ok_or_else(|| {
+            SSOError::TokenValidationFailed("Token cannot be validated without introspection".to_string())
+        })?;
+        let response = introspection.introspect(token).await?;
+        response.verify(source.issuer.as_deref())?;
+
+        Ok(serde_json::to_value(response)?)
+    }
+
     /// Create access token
     pub fn create_access_token(&self, user: &SSOUser, session_id: &Uuid) -> SSOResult<String> {
         let now = Utc::now();
@@ -188,6 +263,8 @@ mod tests {
             max_sessions_per_user: 5,
             issuer: "test-issuer".to_string(),
             audience: "test-audience".to_string(),
+            jwks_cache_ttl: 3600,
+            jwks_min_refresh_interval: 30,
         }
     }
 
@@ -241,4 +318,48 @@ mod tests {
 
         assert_eq!(extracted_id, session_id);
     }
+
+    /// Publishes one symmetric key, so provider tokens can be signed here
+    struct ProviderKeys;
+
+    #[async_trait::async_trait]
+    impl crate::session::KeySource for ProviderKeys {
+        async fn fetch(&self, _jwks_uri: &str) -> SSOResult<jsonwebtoken::jwk::JwkSet> {
+            Ok(serde_json::from_value(serde_json::json!({
+                "keys": [{ "kty": "oct", "kid": "k1", "alg": "HS256", "k": "cHJvdmlkZXIta2V5" }]
+            }))?)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_external_token_validation() {
+        let jwks = JwksCache::default().with_source(Arc::new(ProviderKeys));
+        let manager = TokenManager::new(create_test_config()).with_jwks_cache(Arc::new(jwks));
+        let source = ExternalTokenSource {
+            issuer: Some("https://op.example.com".to_string()),
+            audience: "client".to_string(),
+            jwks_uri: Some("https://op.example.com/jwks".to_string()),
+            introspection: None,
+        };
+
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = Some("k1".to_string());
+        let claims = serde_json::json!({
+            "iss": "https://op.example.com",
+            "aud": "client",
+            "sub": "00u1",
+            "exp": Utc::now().timestamp() + 300,
+        });
+        let token = encode(&header, &claims, &EncodingKey::from_secret(b"provider-key")).unwrap();
+
+        let validated = manager.validate_external_token(&token, &source).await.unwrap();
+        assert_eq!(validated["sub"], "00u1");
+        manager.validate_external_token(&token, &source).await.unwrap();
+
+        let metrics = manager.jwks_metrics().await;
+        assert_eq!((metrics.hits, metrics.refreshes), (1, 1));
+
+        // Opaque tokens need an introspection endpoint
+        assert!(manager.validate_external_token("opaque", &source).await.is_err());
+    }
 }
@@ -0,0 +1,223 @@
+//! Authorization State Storage
+//!
+//! Every login started by [`SSOManager::initiate_login`](crate::SSOManager::initiate_login)
+//! stores its state parameter together with the redirect URI, nonce and PKCE
+//! verifier of the request. The callback takes the entry back out, so a state
+//! is accepted only once, only before it expires, and only for the provider
+//! that issued it.
+//!
+//! [`MemoryStateStore`] serves a single process. [`RedisStateStore`]
+//! (feature `redis`) shares states between instances behind a load balancer.
+
+use crate::{SSOError, SSOResult};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// A pending authorization request, keyed by its state parameter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthorizationState {
+    /// State parameter sent to the provider
+    pub state: String,
+
+    /// Provider the login was started with
+    pub provider: String,
+
+    /// Redirect URI the provider returns to
+    pub redirect_uri: String,
+
+    /// Nonce bound into the ID token
+    pub nonce: String,
+
+    /// PKCE code verifier, if the provider uses PKCE
+    pub pkce_verifier: Option<String>,
+
+    /// When the login was started
+    pub created_at: DateTime<Utc>,
+
+    /// When the state stops being accepted
+    pub expires_at: DateTime<Utc>,
+}
+
+impl AuthorizationState {
+    /// Check whether the state has expired
+    pub fn is_expired(&self) -> bool {
+        Utc::now() >= self.expires_at
+    }
+}
+
+/// Storage of pending authorization requests
+#[async_trait]
+pub trait StateStore: Send + Sync {
+    /// Store a request until it is taken or `ttl` passes
+    async fn insert(&self, request: &AuthorizationState, ttl: Duration) -> SSOResult<()>;
+
+    /// Remove and return the request for a state
+    ///
+    /// Must be atomic: of two concurrent callers, at most one gets the request.
+    async fn take(&self, state: &str) -> SSOResult<Option<AuthorizationState>>;
+}
+
+/// In-process state store
+#[derive(Default)]
+pub struct MemoryStateStore {
+    states: Mutex<HashMap<String, AuthorizationState>>,
+}
+
+impl MemoryStateStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of pending requests, including expired ones not yet pruned
+    pub async fn len(&self) -> usize {
+        self.states.lock().await.len()
+    }
+
+    /// Check whether no requests are pending
+    pub async fn is_empty(&self) -> bool {
+        self.states.lock().await.is_empty()
+    }
+}
+
+#[async_trait]
+impl StateStore for MemoryStateStore {
+    async fn insert(&self, request: &AuthorizationState, _ttl: Duration) -> SSOResult<()> {
+        let mut states = self.states.lock().await;
+
+        // Abandoned logins never reach the callback; drop them here
+        states.retain(|_, pending| !pending.is_expired());
+
+        if states.contains_key(&request.state) {
+            return Err(SSOError::InternalError("Duplicate state parameter".to_string()));
+        }
+        states.insert(request.state.clone(), request.clone());
+        Ok(())
+    }
+
+    async fn take(&self, state: &str) -> SSOResult<Option<AuthorizationState>> {
+        let request = self.states.lock().await.remove(state);
+        Ok(request.filter(|request| !request.is_expired()))
+    }
+}
+
+#[cfg(feature = "redis")]
+pub use self::redis_backend::RedisStateStore;
+
+#[cfg(feature = "redis")]
+mod redis_backend {
+    use super::{AuthorizationState, StateStore};
+    use crate::{SSOError, SSOResult};
+    use async_trait::async_trait;
+    use redis::aio::ConnectionManager;
+    use std::time::Duration;
+
+    /// State store shared between processes through Redis
+    ///
+    /// Each request is a JSON string under `<prefix>:<state>` with a Redis
+    /// expiry, and is taken with `GETDEL` (Redis 6.2 or later).
+    pub struct RedisStateStore {
+        connection: ConnectionManager,
+        key_prefix: String,
+    }
+
+    impl RedisStateStore {
+        /// Connect to Redis and use the keys under `key_prefix`
+        pub async fn connect(url: &str, key_prefix: &str) -> SSOResult<Self> {
+            let client = redis::Client::open(url)?;
+            let connection = ConnectionManager::new(client).await?;
+
+            Ok(Self {
+                connection,
+                key_prefix: key_prefix.to_string(),
+            })
+        }
+
+        fn key(&self, state: &str) -> String {
+            format!("{}:{}", self.key_prefix, state)
+        }
+    }
+
+    #[async_trait]
+    impl StateStore for RedisStateStore {
+        async fn insert(&self, request: &AuthorizationState, ttl: Duration) -> SSOResult<()> {
+            let payload = serde_json::to_string(request)?;
+
+            let stored: Option<String> = redis::cmd("SET")
+                .arg(self.key(&request.state))
+                .arg(payload)
+                .arg("NX")
+                .arg("EX")
+                .arg(ttl.as_secs().max(1))
+                .query_async(&mut self.connection.clone())
+                .await?;
+
+            if stored.is_none() {
+                return Err(SSOError::InternalError("Duplicate state parameter".to_string()));
+            }
+            Ok(())
+        }
+
+        async fn take(&self, state: &str) -> SSOResult<Option<AuthorizationState>> {
+            let payload: Option<String> = redis::cmd("GETDEL")
+                .arg(self.key(state))
+                .query_async(&mut self.connection.clone())
+                .await?;
+
+            match payload {
+                Some(payload) => {
+                    let request: AuthorizationState = serde_json::from_str(&payload)?;
+                    Ok(Some(request).filter(|request| !request.is_expired()))
+                }
+                None => Ok(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(state: &str, ttl_secs: i64) -> AuthorizationState {
+        let now = Utc::now();
+        AuthorizationState {
+            state: state.to_string(),
+            provider: "okta".to_string(),
+            redirect_uri: "https://app.example.com/auth/callback".to_string(),
+            nonce: "nonce".to_string(),
+            pkce_verifier: Some("verifier".to_string()),
+            created_at: now,
+            expires_at: now + chrono::Duration::seconds(ttl_secs),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_state_taken_once() {
+        let store = MemoryStateStore::new();
+        store.insert(&request("abc", 600), Duration::from_secs(600)).await.unwrap();
+
+        // The same state cannot be issued twice
+        assert!(store.insert(&request("abc", 600), Duration::from_secs(600)).await.is_err());
+
+        let taken = store.take("abc").await.unwrap().unwrap();
+        assert_eq!(taken.pkce_verifier.as_deref(), Some("verifier"));
+        assert!(store.take("abc").await.unwrap().is_none());
+        assert!(store.take("unknown").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_expired_state_rejected_and_pruned() {
+        let store = MemoryStateStore::new();
+        store.insert(&request("old", -1), Duration::from_secs(0)).await.unwrap();
+        store.insert(&request("stale", -1), Duration::from_secs(0)).await.unwrap();
+        assert!(store.take("old").await.unwrap().is_none());
+
+        store.insert(&request("new", 600), Duration::from_secs(600)).await.unwrap();
+        assert_eq!(store.len().await, 1);
+    }
+}
@@ -1,7 +1,9 @@
 //! WebSocket authentication and authorization.
 
 use crate::error::{Result, StreamingError};
-use crate::event::UserId;
+use crate::event::{RoomId, UserId};
+#[cfg(feature = "jwt-auth")]
+use accuscene_security::auth::{TokenBlacklist, TokenService, TokenType};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use parking_lot::RwLock;
@@ -189,6 +191,75 @@ impl Authenticator for InMemoryAuthenticator {
     }
 }
 
+/// Authenticator validating JWTs issued by `accuscene-security`
+///
+/// Only access tokens are accepted. The subject becomes the user ID, and the
+/// roles, permissions and organization of the token are exposed as claims.
+#[cfg(feature = "jwt-auth")]
+pub struct JwtAuthenticator {
+    /// Token service validating signatures and standard claims
+    service: TokenService,
+    /// IDs of revoked tokens
+    revoked: RwLock<TokenBlacklist>,
+}
+
+#[cfg(feature = "jwt-auth")]
+impl JwtAuthenticator {
+    /// Create a new JWT authenticator
+    pub fn new(service: TokenService) -> Self {
+        Self {
+            service,
+            revoked: RwLock::new(TokenBlacklist::new()),
+        }
+    }
+}
+
+#[cfg(feature = "jwt-auth")]
+#[async_trait]
+impl Authenticator for JwtAuthenticator {
+    async fn authenticate(&self, token: &str) -> Result<AuthResult> {
+        let claims = match self.service.validate_token(token) {
+            Ok(claims) => claims,
+            Err(e) => return Ok(AuthResult::failure(e.to_string())),
+        };
+
+        if claims.custom.token_type != TokenType::Access {
+            return Ok(AuthResult::failure("Not an access token"));
+        }
+        if self.revoked.read().is_revoked(&claims.jti) {
+            return Ok(AuthResult::failure("Token revoked"));
+        }
+
+        let mut auth_token = AuthToken::new(token, claims.sub.clone())
+            .with_claim("roles", serde_json::json!(claims.custom.roles))
+            .with_claim("permissions", serde_json::json!(claims.custom.permissions));
+        if let Some(org_id) = &claims.custom.org_id {
+            auth_token = auth_token.with_claim("org_id", serde_json::json!(org_id));
+        }
+        if let Some(expires_at) = DateTime::from_timestamp(claims.exp, 0) {
+            auth_token = auth_token.with_expiration(expires_at);
+        }
+
+        Ok(AuthResult::success(claims.sub, auth_token))
+    }
+
+    async fn validate(&self, token: &str) -> Result<bool> {
+        Ok(self.authenticate(token).await?.success)
+    }
+
+    async fn revoke(&self, token: &str) -> Result<()> {
+        let claims = self.service.validate_token(token).map_err(|e| {
+            StreamingError::Authentication(format!("Cannot revoke token: {}", e))
+        })?;
+        let expires_at = DateTime::from_timestamp(claims.exp, 0).unwrap_or_else(Utc::now);
+
+        let mut revoked = self.revoked.write();
+        revoked.cleanup_expired();
+        revoked.revoke(claims.jti, expires_at);
+        Ok(())
+    }
+}
+
 /// Permission-based authorization
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Permission {
@@ -297,6 +368,113 @@ impl Authorizer for RoleBasedAuthorizer {
     }
 }
 
+/// Room-scoped authorization
+#[async_trait]
+pub trait RoomAuthorizer: Send + Sync {
+    /// Check if the holder of a token has a permission in a room, or globally if there is no room
+    async fn authorize(
+        &self,
+        token: &AuthToken,
+        room_id: Option<&RoomId>,
+        permission: &Permission,
+    ) -> Result<bool>;
+}
+
+/// Permissions granted per room and user
+type RoomGrants = HashMap<RoomId, HashMap<UserId, Vec<Permission>>>;
+
+/// Room authorizer combining per-room grants with global permissions
+///
+/// A user has a permission in a room if it was granted in that room, if
+/// [`Permission::Admin`] was granted in that room, or if the global
+/// authorizer grants it. Actions outside rooms only consult the global
+/// authorizer.
+pub struct RoomAccessControl {
+    /// Global permissions
+    authorizer: Arc<dyn Authorizer>,
+    /// Permissions granted per room and user
+    grants: Arc<RwLock<RoomGrants>>,
+}
+
+impl RoomAccessControl {
+    /// Create a new room access control on top of global permissions
+    pub fn new(authorizer: Arc<dyn Authorizer>) -> Self {
+        Self {
+            authorizer,
+            grants: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Grant a permission to a user in a room
+    pub fn grant(
+        &self,
+        room_id: impl Into<RoomId>,
+        user_id: impl Into<UserId>,
+        permission: Permission,
+    ) {
+        let mut grants = self.grants.write();
+        let permissions = grants
+            .entry(room_id.into())
+            .or_default()
+            .entry(user_id.into())
+            .or_default();
+
+        if !permissions.contains(&permission) {
+            permissions.push(permission);
+        }
+    }
+
+    /// Revoke a permission from a user in a room
+    pub fn revoke(&self, room_id: &RoomId, user_id: &UserId, permission: &Permission) {
+        let mut grants = self.grants.write();
+        if let Some(users) = grants.get_mut(room_id) {
+            if let Some(permissions) = users.get_mut(user_id) {
+                permissions.retain(|p| p != permission);
+            }
+        }
+    }
+
+    /// Remove all grants of a room
+    pub fn remove_room(&self, room_id: &RoomId) {
+        self.grants.write().remove(room_id);
+    }
+
+    fn has_room_permission(
+        &self,
+        room_id: &RoomId,
+        user_id: &UserId,
+        permission: &Permission,
+    ) -> bool {
+        self.grants
+            .read()
+            .get(room_id)
+            .and_then(|users| users.get(user_id))
+            .is_some_and(|permissions| {
+                permissions.contains(&Permission::Admin) || permissions.contains(permission)
+            })
+    }
+}
+
+#[async_trait]
+impl RoomAuthorizer for RoomAccessControl {
+    async fn authorize(
+        &self,
+        token: &AuthToken,
+        room_id: Option<&RoomId>,
+        permission: &Permission,
+    ) -> Result<bool> {
+        if let Some(room_id) = room_id {
+            if self.has_room_permission(room_id, &token.user_id, permission) {
+                return Ok(true);
+            }
+        }
+
+        self.authorizer
+            .has_permission(&token.user_id, permission)
+            .await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -441,4 +619,47 @@ mod tests {
             .unwrap();
         assert!(!has_perm);
     }
+
+    #[tokio::test]
+    async fn test_room_access_control() {
+        let authz = Arc::new(RoleBasedAuthorizer::new());
+        authz.set_permissions("user1", vec![Permission::JoinRooms]);
+
+        let rooms = RoomAccessControl::new(authz);
+        rooms.grant("case-1", "user1", Permission::ReadEvents);
+        rooms.grant("case-2", "user1", Permission::Admin);
+
+        let token = AuthToken::new("token123", "user1");
+        let case_1 = "case-1".to_string();
+        let case_2 = "case-2".to_string();
+
+        assert!(rooms
+            .authorize(&token, Some(&case_1), &Permission::ReadEvents)
+            .await
+            .unwrap());
+        assert!(!rooms
+            .authorize(&token, Some(&case_1), &Permission::PublishEvents)
+            .await
+            .unwrap());
+        assert!(rooms
+            .authorize(&token, Some(&case_2), &Permission::PublishEvents)
+            .await
+            .unwrap());
+
+        // Room grants do not apply outside the room, global permissions apply everywhere
+        assert!(!rooms
+            .authorize(&token, None, &Permission::ReadEvents)
+            .await
+            .unwrap());
+        assert!(rooms
+            .authorize(&token, Some(&case_1), &Permission::JoinRooms)
+            .await
+            .unwrap());
+
+        rooms.revoke(&case_1, &"user1".to_string(), &Permission::ReadEvents);
+        assert!(!rooms
+            .authorize(&token, Some(&case_1), &Permission::ReadEvents)
+            .await
+            .unwrap());
+    }
 }
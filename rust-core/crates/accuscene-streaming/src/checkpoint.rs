@@ -1,6 +1,13 @@
 //! Checkpointing for fault tolerance and recovery.
+//!
+//! Sinks implementing [`TwoPhaseCommitSink`] can be registered with the
+//! [`CheckpointCoordinator`] for exactly-once output: completing a checkpoint
+//! pre-commits the sinks, stores the checkpoint and then commits the sinks,
+//! and [`CheckpointCoordinator::recover`] settles their transactions after a
+//! restart.
 
 use crate::error::{Result, StreamingError};
+use crate::sink::TwoPhaseCommitSink;
 use crate::watermark::Watermark;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -8,7 +15,7 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::fs;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 use tracing::{debug, error, info};
 
 /// Checkpoint ID
@@ -98,6 +105,9 @@ impl Checkpoint {
     }
 }
 
+/// Sink shared between its pipeline and the checkpoint coordinator
+pub type SharedCommitSink = Arc<Mutex<dyn TwoPhaseCommitSink>>;
+
 /// Checkpoint coordinator
 #[derive(Clone)]
 pub struct CheckpointCoordinator {
@@ -110,6 +120,7 @@ struct CheckpointCoordinatorInner {
     active_checkpoint: Option<CheckpointId>,
     completed_checkpoints: Vec<CheckpointMetadata>,
     max_retained: usize,
+    sinks: Vec<(String, SharedCommitSink)>,
 }
 
 impl CheckpointCoordinator {
@@ -121,11 +132,17 @@ impl CheckpointCoordinator {
                 active_checkpoint: None,
                 completed_checkpoints: Vec::new(),
                 max_retained,
+                sinks: Vec::new(),
             })),
             storage,
         }
     }
 
+    /// Register a sink committing its output with the checkpoints
+    pub async fn register_sink(&self, name: impl Into<String>, sink: SharedCommitSink) {
+        self.inner.write().await.sinks.push((name.into(), sink));
+    }
+
     /// Trigger a new checkpoint
     pub async fn trigger_checkpoint(&self) -> Result<CheckpointId> {
         let mut inner = self.inner.write().await;
@@ -145,6 +162,11 @@ impl CheckpointCoordinator {
     }
 
     /// Complete a checkpoint
+    ///
+    /// Call this once the checkpoint barrier has reached the registered
+    /// sinks. Their transactions are pre-committed before the checkpoint is
+    /// stored and committed after, so a checkpoint that fails to complete
+    /// leaves its output staged for the next one.
     pub async fn complete_checkpoint(
         &self,
         id: CheckpointId,
@@ -159,9 +181,26 @@ impl CheckpointCoordinator {
             )));
         }
 
+        // Stage sink output up to the barrier
+        for (name, sink) in &inner.sinks {
+            sink.lock().await.pre_commit(id).await.map_err(|e| {
+                StreamingError::Checkpoint(format!(
+                    "Sink {} failed to pre-commit checkpoint {}: {}",
+                    name, id, e
+                ))
+            })?;
+        }
+
         // Store checkpoint
         self.storage.store(&checkpoint).await?;
 
+        // The checkpoint is durable, so commit failures are retried on recovery
+        for (name, sink) in &inner.sinks {
+            if let Err(e) = sink.lock().await.commit(id).await {
+                error!("Sink {} failed to commit checkpoint {}: {}", name, id, e);
+            }
+        }
+
         // Update metadata
         inner.completed_checkpoints.push(checkpoint.metadata.clone());
         inner.active_checkpoint = None;
@@ -209,6 +248,40 @@ impl CheckpointCoordinator {
         }
     }
 
+    /// Recover after a restart
+    ///
+    /// Loads the checkpoints kept in storage, continues numbering after the
+    /// latest one and lets every registered sink commit the transactions it
+    /// covers and discard the rest. Returns the latest checkpoint to restore
+    /// state from.
+    pub async fn recover(&self) -> Result<Option<Checkpoint>> {
+        let mut inner = self.inner.write().await;
+
+        let mut stored = self.storage.list().await?;
+        let latest = match stored.last() {
+            Some(metadata) => Some(self.storage.load(metadata.id).await?),
+            None => None,
+        };
+        let last_completed = latest.as_ref().map(|checkpoint| checkpoint.metadata.id);
+
+        for (name, sink) in &inner.sinks {
+            sink.lock().await.recover(last_completed).await.map_err(|e| {
+                StreamingError::CheckpointRestore(format!("Sink {} failed to recover: {}", name, e))
+            })?;
+        }
+
+        if let Some(id) = last_completed {
+            inner.next_id = inner.next_id.max(id + 1);
+        }
+        let excess = stored.len().saturating_sub(inner.max_retained);
+        stored.drain(..excess);
+        inner.completed_checkpoints = stored;
+        inner.active_checkpoint = None;
+
+        info!("Recovered checkpoints, latest is {:?}", last_completed);
+        Ok(latest)
+    }
+
     /// Get all checkpoint metadata
     pub async fn list_checkpoints(&self) -> Vec<CheckpointMetadata> {
         self.inner.read().await.completed_checkpoints.clone()
@@ -408,6 +481,7 @@ impl CheckpointStorage for MemoryCheckpointStorage {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::sink::{Sink, TransactionalFileSink};
 
     #[tokio::test]
     async fn test_checkpoint_coordinator() {
@@ -447,4 +521,54 @@ mod tests {
         assert_eq!(checkpoints[0].id, 2);
         assert_eq!(checkpoints[1].id, 3);
     }
+
+    #[tokio::test]
+    async fn test_sink_exactly_once_recovery() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Arc::new(MemoryCheckpointStorage::new());
+
+        let sink = TransactionalFileSink::new(dir.path().to_path_buf(), "out")
+            .await
+            .unwrap();
+        let sink = Arc::new(Mutex::new(sink));
+        let coordinator = CheckpointCoordinator::new(storage.clone(), 3);
+        coordinator.register_sink("out", sink.clone()).await;
+
+        sink.lock().await.write("a".to_string()).await.unwrap();
+        let id = coordinator.trigger_checkpoint().await.unwrap();
+        coordinator
+            .complete_checkpoint(id, Checkpoint::new(id))
+            .await
+            .unwrap();
+
+        // Crash after checkpoint 2 is stored but before the sink commits it,
+        // with more output written after the barrier
+        {
+            let mut sink = sink.lock().await;
+            sink.write("b".to_string()).await.unwrap();
+            sink.pre_commit(2).await.unwrap();
+            storage.store(&Checkpoint::new(2)).await.unwrap();
+            sink.write("c".to_string()).await.unwrap();
+            sink.flush().await.unwrap();
+        }
+        drop(coordinator);
+        drop(sink);
+
+        let sink = TransactionalFileSink::new(dir.path().to_path_buf(), "out")
+            .await
+            .unwrap();
+        let sink = Arc::new(Mutex::new(sink));
+        let coordinator = CheckpointCoordinator::new(storage, 3);
+        coordinator.register_sink("out", sink.clone()).await;
+
+        let restored = coordinator.recover().await.unwrap().unwrap();
+        assert_eq!(restored.metadata.id, 2);
+        assert_eq!(coordinator.trigger_checkpoint().await.unwrap(), 3);
+
+        let mut output = String::new();
+        for file in sink.lock().await.committed_files().await.unwrap() {
+            output.push_str(&fs::read_to_string(file).await.unwrap());
+        }
+        assert_eq!(output, "a\nb\n");
+    }
 }
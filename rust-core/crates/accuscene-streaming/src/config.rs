@@ -126,6 +126,12 @@ pub struct WatermarkConfig {
     pub update_interval: Duration,
     /// Idle source timeout
     pub idle_timeout: Duration,
+    /// Maximum out-of-orderness of event timestamps within a partition
+    #[serde(default)]
+    pub max_out_of_orderness: Duration,
+    /// How far a partition may read ahead of the aligned watermark, `None` for strict alignment
+    #[serde(default)]
+    pub max_drift: Option<Duration>,
 }
 
 impl Default for WatermarkConfig {
@@ -135,10 +141,32 @@ impl Default for WatermarkConfig {
             max_lateness: Duration::from_secs(10),
             update_interval: Duration::from_millis(100),
             idle_timeout: Duration::from_secs(60),
+            max_out_of_orderness: Duration::from_secs(0),
+            max_drift: None,
         }
     }
 }
 
+impl WatermarkConfig {
+    /// Set the maximum out-of-orderness
+    pub fn with_max_out_of_orderness(mut self, max_out_of_orderness: Duration) -> Self {
+        self.max_out_of_orderness = max_out_of_orderness;
+        self
+    }
+
+    /// Set the idle source timeout
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    /// Set the maximum drift between partitions
+    pub fn with_max_drift(mut self, drift: Duration) -> Self {
+        self.max_drift = Some(drift);
+        self
+    }
+}
+
 /// Runtime configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RuntimeConfig {
@@ -1,11 +1,15 @@
 //! Domain-specific streaming for AccuScene Enterprise.
 
 pub mod event_stream;
+#[cfg(feature = "physics")]
+pub mod physics_stream;
 pub mod sensor_stream;
 pub mod simulation_stream;
 pub mod telemetry_stream;
 
 pub use event_stream::{EventStream, SystemEvent, SystemEventType};
+#[cfg(feature = "physics")]
+pub use physics_stream::{physics_frame, PhysicsPublisher, PhysicsSource};
 pub use sensor_stream::{SensorData, SensorStream, SensorType};
 pub use simulation_stream::{SimulationData, SimulationStream, SimulationState};
 pub use telemetry_stream::{TelemetryData, TelemetryStream, TelemetryType};
@@ -0,0 +1,354 @@
+//! Live simulation telemetry from the physics engine.
+//!
+//! Bridges `accuscene_physics_v3::PhysicsWorld` into the streaming pipeline
+//! as `SimulationData` frames. Each frame carries:
+//! - One `rigid_body` entity per body (position, velocity, orientation)
+//! - One `contact` entity per contact resolved in the step
+//! - Simulation time and energy totals in the metadata
+//!
+//! Use `PhysicsSource` when the stream drives the simulation, or
+//! `PhysicsPublisher` when the world is stepped elsewhere.
+
+use crate::domain::simulation_stream::{EntityData, SimulationData, SimulationState};
+use crate::error::{Result, StreamingError};
+use crate::source::{ChannelSource, Source};
+use crate::stream::DataStream;
+use accuscene_physics_v3::PhysicsWorld;
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
+/// Entity type of rigid bodies
+pub const RIGID_BODY_ENTITY: &str = "rigid_body";
+
+/// Entity type of contacts
+pub const CONTACT_ENTITY: &str = "contact";
+
+/// Builds a simulation frame from the current state of a world
+pub fn physics_frame(
+    simulation_id: &str,
+    frame: u64,
+    time_step: f64,
+    world: &PhysicsWorld,
+    include_contacts: bool,
+) -> SimulationData {
+    let mut data = SimulationData::new(simulation_id.to_string(), frame, time_step);
+
+    for body in world.bodies() {
+        let rotation = body.orientation.coords;
+        let mut properties = std::collections::HashMap::new();
+        properties.insert("mass".to_string(), body.mass_props.mass);
+        properties.insert("speed".to_string(), body.linear_velocity.norm());
+        properties.insert("angular_velocity_x".to_string(), body.angular_velocity.x);
+        properties.insert("angular_velocity_y".to_string(), body.angular_velocity.y);
+        properties.insert("angular_velocity_z".to_string(), body.angular_velocity.z);
+        properties.insert("is_static".to_string(), f64::from(u8::from(body.is_static)));
+
+        data.add_entity(EntityData {
+            entity_id: format!("body-{}", body.id),
+            entity_type: RIGID_BODY_ENTITY.to_string(),
+            position: body.position.into(),
+            velocity: body.linear_velocity.into(),
+            rotation: [rotation.x, rotation.y, rotation.z, rotation.w],
+            properties,
+        });
+    }
+
+    if include_contacts {
+        for contact in world.contacts() {
+            let mut properties = std::collections::HashMap::new();
+            properties.insert("body_a".to_string(), contact.body_a as f64);
+            properties.insert("body_b".to_string(), contact.body_b as f64);
+            properties.insert("penetration".to_string(), contact.penetration);
+            properties.insert("normal_impulse".to_string(), contact.accumulated_normal_impulse);
+            properties.insert("normal_x".to_string(), contact.normal.x);
+            properties.insert("normal_y".to_string(), contact.normal.y);
+            properties.insert("normal_z".to_string(), contact.normal.z);
+
+            data.add_entity(EntityData {
+                entity_id: format!("contact-{}-{}", contact.body_a, contact.body_b),
+                entity_type: CONTACT_ENTITY.to_string(),
+                position: contact.point_a.into(),
+                velocity: [0.0; 3],
+                rotation: [0.0, 0.0, 0.0, 1.0],
+                properties,
+            });
+        }
+    }
+
+    let energy = world.energy_analysis();
+    data.with_metadata("time".to_string(), world.time().to_string())
+        .with_metadata("energy.total_kinetic".to_string(), energy.total_kinetic.to_string())
+        .with_metadata(
+            "energy.translational_kinetic".to_string(),
+            energy.translational_kinetic.to_string(),
+        )
+        .with_metadata(
+            "energy.rotational_kinetic".to_string(),
+            energy.rotational_kinetic.to_string(),
+        )
+        .with_metadata(
+            "energy.deformation".to_string(),
+            energy.deformation_energy.to_string(),
+        )
+        .with_metadata(
+            "energy.conservation_error".to_string(),
+            energy.conservation_error.to_string(),
+        )
+}
+
+/// Source that steps a physics world and emits one frame per step
+pub struct PhysicsSource {
+    world: PhysicsWorld,
+    simulation_id: String,
+    time_step: f64,
+    max_frames: Option<u64>,
+    include_contacts: bool,
+    frame: u64,
+    running: bool,
+    finished: bool,
+}
+
+impl PhysicsSource {
+    /// Create a new physics source
+    pub fn new(simulation_id: String, world: PhysicsWorld, time_step: f64) -> Self {
+        Self {
+            world,
+            simulation_id,
+            time_step,
+            max_frames: None,
+            include_contacts: true,
+            frame: 0,
+            running: false,
+            finished: false,
+        }
+    }
+
+    /// Stop after a number of frames
+    pub fn with_max_frames(mut self, max_frames: u64) -> Self {
+        self.max_frames = Some(max_frames);
+        self
+    }
+
+    /// Stop after a simulated duration (seconds)
+    pub fn with_duration(mut self, duration: f64) -> Self {
+        self.max_frames = Some((duration / self.time_step).ceil() as u64);
+        self
+    }
+
+    /// Include contact entities in each frame
+    pub fn with_contacts(mut self, include_contacts: bool) -> Self {
+        self.include_contacts = include_contacts;
+        self
+    }
+
+    /// Get the physics world
+    pub fn world(&self) -> &PhysicsWorld {
+        &self.world
+    }
+
+    /// Get the physics world mutably, e.g. to apply forces between frames
+    pub fn world_mut(&mut self) -> &mut PhysicsWorld {
+        &mut self.world
+    }
+
+    /// Consume the source and return the physics world
+    pub fn into_world(self) -> PhysicsWorld {
+        self.world
+    }
+
+    /// Number of frames emitted
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+}
+
+#[async_trait]
+impl DataStream for PhysicsSource {
+    type Item = SimulationData;
+
+    async fn next(&mut self) -> Result<Option<Self::Item>> {
+        if !self.running || self.finished {
+            return Ok(None);
+        }
+
+        let step = self.world.step(self.time_step);
+        self.frame += 1;
+        let data = physics_frame(
+            &self.simulation_id,
+            self.frame,
+            self.time_step,
+            &self.world,
+            self.include_contacts,
+        );
+
+        // A failed step ends the stream with an error frame
+        let data = match step {
+            Err(e) => {
+                self.finished = true;
+                data.with_state(SimulationState::Error(e.to_string()))
+            }
+            Ok(()) if self.max_frames.is_some_and(|max| self.frame >= max) => {
+                self.finished = true;
+                data.with_state(SimulationState::Stopped)
+            }
+            Ok(()) => data,
+        };
+
+        Ok(Some(data))
+    }
+
+    fn is_complete(&self) -> bool {
+        !self.running || self.finished
+    }
+}
+
+#[async_trait]
+impl Source for PhysicsSource {
+    async fn start(&mut self) -> Result<()> {
+        if self.time_step <= 0.0 || !self.time_step.is_finite() {
+            return Err(StreamingError::Source(format!(
+                "invalid physics time step: {}",
+                self.time_step
+            )));
+        }
+
+        self.running = true;
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<()> {
+        self.running = false;
+        Ok(())
+    }
+
+    fn is_running(&self) -> bool {
+        self.running
+    }
+}
+
+/// Publisher for worlds stepped outside the streaming pipeline
+pub struct PhysicsPublisher {
+    sender: mpsc::UnboundedSender<SimulationData>,
+    simulation_id: String,
+    include_contacts: bool,
+    frame: u64,
+}
+
+impl PhysicsPublisher {
+    /// Create a new publisher sending to an existing channel
+    pub fn new(simulation_id: String, sender: mpsc::UnboundedSender<SimulationData>) -> Self {
+        Self {
+            sender,
+            simulation_id,
+            include_contacts: true,
+            frame: 0,
+        }
+    }
+
+    /// Create a publisher with a channel source receiving its frames
+    pub fn channel(simulation_id: String) -> (Self, ChannelSource<SimulationData>) {
+        let (sender, source) = ChannelSource::create();
+        (Self::new(simulation_id, sender), source)
+    }
+
+    /// Include contact entities in each frame
+    pub fn with_contacts(mut self, include_contacts: bool) -> Self {
+        self.include_contacts = include_contacts;
+        self
+    }
+
+    /// Publish the state of a world after a step of `time_step` seconds
+    pub fn publish(&mut self, world: &PhysicsWorld, time_step: f64) -> Result<()> {
+        self.frame += 1;
+        let data = physics_frame(
+            &self.simulation_id,
+            self.frame,
+            time_step,
+            world,
+            self.include_contacts,
+        );
+        self.send(data)
+    }
+
+    /// Publish a final frame marking the simulation as stopped
+    pub fn finish(&mut self, world: &PhysicsWorld) -> Result<()> {
+        let data = physics_frame(&self.simulation_id, self.frame, 0.0, world, self.include_contacts)
+            .with_state(SimulationState::Stopped);
+        self.send(data)
+    }
+
+    fn send(&self, data: SimulationData) -> Result<()> {
+        self.sender
+            .send(data)
+            .map_err(|e| StreamingError::ChannelSend(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use accuscene_physics_v3::prelude::{
+        CollisionShape, MassProperties, PhysicsConfig, RigidBody, Vector3,
+    };
+
+    fn world_with_falling_box() -> PhysicsWorld {
+        let mut world = PhysicsWorld::new(PhysicsConfig::default());
+        let mut body = RigidBody::new(0, MassProperties::from_box(1500.0, Vector3::new(4.0, 2.0, 1.5)));
+        body.position = Vector3::new(0.0, 0.0, 10.0);
+        body.linear_velocity = Vector3::new(5.0, 0.0, 0.0);
+        world.add_body(body, CollisionShape::Box { half_extents: Vector3::new(2.0, 1.0, 0.75) });
+        world
+    }
+
+    #[test]
+    fn test_physics_frame() {
+        let world = world_with_falling_box();
+        let data = physics_frame("sim1", 1, 0.01, &world, true);
+
+        assert_eq!(data.entities.len(), 1);
+        let entity = &data.entities[0];
+        assert_eq!(entity.entity_id, "body-0");
+        assert_eq!(entity.entity_type, RIGID_BODY_ENTITY);
+        assert_eq!(entity.position, [0.0, 0.0, 10.0]);
+        assert_eq!(entity.rotation, [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(entity.properties["mass"], 1500.0);
+        assert!(data.metadata.contains_key("energy.total_kinetic"));
+    }
+
+    #[tokio::test]
+    async fn test_physics_source() {
+        let mut source =
+            PhysicsSource::new("sim1".to_string(), world_with_falling_box(), 0.01).with_max_frames(3);
+
+        assert!(source.next().await.unwrap().is_none());
+        source.start().await.unwrap();
+
+        let first = source.next().await.unwrap().unwrap();
+        assert_eq!(first.frame, 1);
+        assert_eq!(first.state, SimulationState::Running);
+        assert!(first.entities[0].velocity[2] < 0.0);
+
+        source.next().await.unwrap().unwrap();
+        let last = source.next().await.unwrap().unwrap();
+        assert_eq!(last.state, SimulationState::Stopped);
+        assert!(source.next().await.unwrap().is_none());
+        assert!(source.is_complete());
+        assert!((source.world().time() - 0.03).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_physics_publisher() {
+        let mut world = world_with_falling_box();
+        let (mut publisher, mut source) = PhysicsPublisher::channel("sim1".to_string());
+        source.start().await.unwrap();
+
+        world.step(0.01).unwrap();
+        publisher.publish(&world, 0.01).unwrap();
+        publisher.finish(&world).unwrap();
+
+        let frame = source.next().await.unwrap().unwrap();
+        assert_eq!((frame.simulation_id.as_str(), frame.frame), ("sim1", 1));
+        let last = source.next().await.unwrap().unwrap();
+        assert_eq!(last.state, SimulationState::Stopped);
+    }
+}
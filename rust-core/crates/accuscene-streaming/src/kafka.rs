@@ -0,0 +1,157 @@
+//! Kafka connector configuration and serialization.
+//!
+//! [`KafkaSource`](crate::source::KafkaSource) and
+//! [`KafkaSink`](crate::sink::KafkaSink) share the client settings in
+//! [`KafkaConfig`] and convert between stream items and record payloads with
+//! a [`KafkaCodec`]: [`JsonCodec`] for serde types, or `ArrowCodec` for
+//! Arrow record batches in IPC stream format (`arrow-support` feature).
+
+use crate::error::Result;
+#[cfg(feature = "arrow-support")]
+use crate::error::StreamingError;
+#[cfg(feature = "arrow-support")]
+use arrow::ipc::reader::StreamReader;
+#[cfg(feature = "arrow-support")]
+use arrow::ipc::writer::StreamWriter;
+#[cfg(feature = "arrow-support")]
+use arrow::record_batch::RecordBatch;
+use rdkafka::ClientConfig;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Kafka client settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KafkaConfig {
+    /// Comma-separated list of bootstrap brokers
+    pub brokers: String,
+    /// Additional librdkafka client properties
+    pub properties: HashMap<String, String>,
+}
+
+impl KafkaConfig {
+    /// Create settings for a cluster
+    pub fn new(brokers: impl Into<String>) -> Self {
+        Self {
+            brokers: brokers.into(),
+            properties: HashMap::new(),
+        }
+    }
+
+    /// Set a librdkafka client property
+    pub fn with_property(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.properties.insert(key.into(), value.into());
+        self
+    }
+
+    /// Build the librdkafka client configuration
+    pub(crate) fn client_config(&self) -> ClientConfig {
+        let mut config = ClientConfig::new();
+        config.set("bootstrap.servers", &self.brokers);
+        for (key, value) in &self.properties {
+            config.set(key, value);
+        }
+        config
+    }
+}
+
+/// Conversion between stream items and Kafka record payloads
+pub trait KafkaCodec<T>: Send + Sync + 'static {
+    /// Encode an item as a record payload
+    fn encode(&self, item: &T) -> Result<Vec<u8>>;
+
+    /// Decode an item from a record payload
+    fn decode(&self, payload: &[u8]) -> Result<T>;
+}
+
+/// JSON codec for serde types
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonCodec;
+
+impl<T> KafkaCodec<T> for JsonCodec
+where
+    T: Serialize + DeserializeOwned,
+{
+    fn encode(&self, item: &T) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(item)?)
+    }
+
+    fn decode(&self, payload: &[u8]) -> Result<T> {
+        Ok(serde_json::from_slice(payload)?)
+    }
+}
+
+/// Arrow IPC codec carrying one record batch per record
+#[cfg(feature = "arrow-support")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ArrowCodec;
+
+#[cfg(feature = "arrow-support")]
+impl KafkaCodec<RecordBatch> for ArrowCodec {
+    fn encode(&self, batch: &RecordBatch) -> Result<Vec<u8>> {
+        let mut payload = Vec::new();
+        let mut writer = StreamWriter::try_new(&mut payload, &batch.schema())
+            .map_err(|e| StreamingError::Arrow(format!("Failed to create IPC writer: {}", e)))?;
+        writer
+            .write(batch)
+            .map_err(|e| StreamingError::Arrow(format!("Failed to write record batch: {}", e)))?;
+        writer
+            .finish()
+            .map_err(|e| StreamingError::Arrow(format!("Failed to finish IPC stream: {}", e)))?;
+        drop(writer);
+
+        Ok(payload)
+    }
+
+    fn decode(&self, payload: &[u8]) -> Result<RecordBatch> {
+        let mut reader = StreamReader::try_new(payload, None)
+            .map_err(|e| StreamingError::Arrow(format!("Failed to read IPC stream: {}", e)))?;
+
+        reader
+            .next()
+            .ok_or_else(|| StreamingError::Arrow("Record holds no record batch".to_string()))?
+            .map_err(|e| StreamingError::Arrow(format!("Failed to read record batch: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Reading {
+        sensor: String,
+        value: f64,
+    }
+
+    #[test]
+    fn test_json_codec() {
+        let reading = Reading {
+            sensor: "speed".to_string(),
+            value: 42.5,
+        };
+
+        let payload = JsonCodec.encode(&reading).unwrap();
+        let decoded: Reading = JsonCodec.decode(&payload).unwrap();
+        assert_eq!(decoded, reading);
+
+        let invalid: Result<Reading> = JsonCodec.decode(b"not json");
+        assert!(invalid.is_err());
+    }
+
+    #[cfg(feature = "arrow-support")]
+    #[test]
+    fn test_arrow_codec() {
+        use arrow::array::{ArrayRef, Float64Array};
+        use arrow::datatypes::{DataType, Field, Schema};
+        use std::sync::Arc;
+
+        let schema = Arc::new(Schema::new(vec![Field::new("value", DataType::Float64, false)]));
+        let values: ArrayRef = Arc::new(Float64Array::from(vec![1.0, 2.5, 4.0]));
+        let batch = RecordBatch::try_new(schema, vec![values]).unwrap();
+
+        let payload = ArrowCodec.encode(&batch).unwrap();
+        let decoded = ArrowCodec.decode(&payload).unwrap();
+        assert_eq!(decoded, batch);
+    }
+}
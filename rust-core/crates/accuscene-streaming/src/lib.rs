@@ -6,13 +6,15 @@
 //!
 //! ### Real-time Streaming Infrastructure
 //! - **Stream Processing**: High-performance streaming traits and operators
-//! - **Source Connectors**: Channel, file, WebSocket, and iterator sources
-//! - **Sink Connectors**: Channel, file, WebSocket, and Parquet sinks
+//! - **Source Connectors**: Channel, file, WebSocket, iterator, and Kafka sources
+//! - **Sink Connectors**: Channel, file, WebSocket, Parquet, and Kafka sinks
 //! - **Operators**: Map, filter, flatmap, window, join, aggregate, and keyby
+//! - **Event-Time Windows**: Session windows, early/late triggers, and allowed lateness
 //! - **Backpressure**: Adaptive flow control and backpressure handling
-//! - **Watermarks**: Event-time processing with watermark support
+//! - **Watermarks**: Event-time processing with per-partition alignment and idle detection
 //! - **Checkpointing**: Fault tolerance through state checkpointing
-//! - **State Management**: Stateful processing with multiple state backends
+//! - **Exactly-Once Sinks**: Two-phase commit of sink output with checkpoints
+//! - **State Management**: Stateful processing with memory and RocksDB state backends
 //! - **Partitioning**: Distributed processing with flexible partitioning strategies
 //! - **Apache Arrow**: Columnar data processing with Parquet support
 //!
@@ -20,12 +22,12 @@
 //! - **Event Bus**: Multiple event bus implementations (memory, channel, broadcast)
 //! - **Pub/Sub**: Topic-based publish/subscribe messaging
 //! - **WebSocket**: Server and client implementations with protocol support
-//! - **Event Replay**: Synchronization through event replay
+//! - **Event Replay**: Synchronization, time-range export, and re-publishing through event replay
 //! - **Presence Tracking**: User presence and activity monitoring
 //! - **Room Management**: Per-case collaboration rooms
 //! - **Heartbeat**: Connection health monitoring
 //! - **Compression**: Message compression for bandwidth optimization
-//! - **Authentication**: Token-based authentication and authorization
+//! - **Authentication**: Token-based authentication and per-room authorization
 //!
 //! ## Quick Start
 //!
@@ -70,6 +72,8 @@ pub mod partition;
 pub mod pipeline;
 pub mod runtime;
 pub mod domain;
+#[cfg(feature = "kafka")]
+pub mod kafka;
 
 // Legacy event distribution
 pub mod bus;
@@ -98,17 +102,30 @@ pub mod prelude {
 
     // Streaming infrastructure (v0.2.0)
     pub use crate::stream::{DataStream, StreamExt};
-    pub use crate::source::{Source, ChannelSource, FileSource, IteratorSource};
-    pub use crate::sink::{Sink, ChannelSink, FileSink};
+    pub use crate::source::{Source, AlignedSource, ChannelSource, FileSource, IteratorSource};
+    pub use crate::sink::{
+        Sink, ChannelSink, FileSink, TransactionalFileSink, TwoPhaseCommitSink,
+    };
     pub use crate::operators::{
         AggregateOperator, Aggregator, FilterOperator, FlatMapOperator,
         JoinOperator, JoinType, KeyByOperator, KeyExtractor, MapOperator,
-        WindowOperator, WindowAssigner, WindowType,
+        WindowOperator, WindowAssigner, WindowType, KeyedWindowOperator,
+        SessionWindowAssigner, WindowPane, PaneTiming, Trigger,
     };
+    #[cfg(feature = "kafka")]
+    pub use crate::kafka::{JsonCodec, KafkaCodec, KafkaConfig};
+    #[cfg(feature = "kafka")]
+    pub use crate::sink::KafkaSink;
+    #[cfg(feature = "kafka")]
+    pub use crate::source::KafkaSource;
     pub use crate::backpressure::{BackpressureController, AdaptiveBackpressure};
     pub use crate::checkpoint::{CheckpointCoordinator, Checkpoint};
-    pub use crate::watermark::{Watermark, Timestamp, WatermarkTracker};
-    pub use crate::state::{StateContext, ValueState, ListState, MapState};
+    pub use crate::watermark::{
+        PartitionedWatermarkGenerator, Timestamp, Watermark, WatermarkTracker,
+    };
+    pub use crate::state::{StateContext, StateDelta, ValueState, ListState, MapState};
+    #[cfg(feature = "rocksdb-state")]
+    pub use crate::state::{RocksDbStateBackend, RocksDbStateConfig};
     pub use crate::partition::{Partitioner, PartitionAssignment};
     pub use crate::pipeline::{Pipeline, PipelineBuilder};
     pub use crate::runtime::{StreamingRuntime, RuntimeBuilder};
@@ -122,7 +139,12 @@ pub mod prelude {
     };
 
     // Legacy event system
-    pub use crate::auth::{AuthResult, AuthToken, Authenticator, Authorizer, Permission};
+    pub use crate::auth::{
+        AuthResult, AuthToken, Authenticator, Authorizer, Permission, RoomAccessControl,
+        RoomAuthorizer,
+    };
+    #[cfg(feature = "jwt-auth")]
+    pub use crate::auth::JwtAuthenticator;
     pub use crate::bus::{EventBus, EventBusBuilder, EventBusType, EventStream as LegacyEventStream};
     pub use crate::compression::{
         CompressionConfig, CompressionLevel, Compressor, Decompressor,
@@ -134,10 +156,12 @@ pub mod prelude {
     pub use crate::heartbeat::{HeartbeatConfig, HeartbeatManager, HeartbeatMonitor};
     pub use crate::presence::{PresenceConfig, PresenceInfo, PresenceTracker};
     pub use crate::pubsub::{DefaultPubSub, PubSub, Publisher, Subscriber, SubscriberId};
-    pub use crate::replay::{ReplayBuffer, ReplayConfig, ReplayManager};
+    pub use crate::replay::{
+        ReplayBuffer, ReplayConfig, ReplayExportFormat, ReplayManager, ReplayQuery, ReplaySpeed,
+    };
     pub use crate::room::{Room, RoomInfo, RoomManager};
     pub use crate::websocket::{
-        ConnectionState, MessageHandler, ReconnectConfig, WsClient, WsMessage, WsServer,
+        ConnectionState, MessageHandler, ReconnectConfig, WsAuth, WsClient, WsMessage, WsServer,
     };
 }
 
@@ -6,6 +6,7 @@ pub mod flatmap;
 pub mod join;
 pub mod keyby;
 pub mod map;
+pub mod trigger;
 pub mod window;
 
 pub use self::aggregate::{AggregateOperator, Aggregator};
@@ -14,4 +15,10 @@ pub use self::flatmap::FlatMapOperator;
 pub use self::join::{JoinOperator, JoinType};
 pub use self::keyby::{KeyByOperator, KeyExtractor};
 pub use self::map::MapOperator;
-pub use self::window::{WindowAssigner, WindowOperator, WindowType};
+pub use self::trigger::{
+    CountTrigger, EventTimeTrigger, NoLateFirings, ProcessingTimeTrigger, Trigger, TriggerContext,
+};
+pub use self::window::{
+    KeyedWindowOperator, PaneTiming, SessionWindowAssigner, TimestampExtractor, WindowAssigner,
+    WindowOperator, WindowPane, WindowType,
+};
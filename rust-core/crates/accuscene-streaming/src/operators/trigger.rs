@@ -0,0 +1,136 @@
+//! Window triggers deciding when event-time windows emit early and late results.
+//!
+//! A [`KeyedWindowOperator`](crate::operators::window::KeyedWindowOperator)
+//! always fires a window once when the watermark passes its end. A [`Trigger`]
+//! adds early firings before that point and decides whether elements arriving
+//! within the allowed lateness fire the window again.
+
+use crate::operators::window::Window;
+use std::time::{Duration, Instant};
+
+/// State of a window pane presented to a trigger
+#[derive(Debug, Clone, Copy)]
+pub struct TriggerContext {
+    /// Window of the pane
+    pub window: Window,
+    /// Number of elements in the pane
+    pub elements: usize,
+    /// Number of elements added since the pane last fired
+    pub since_last_firing: usize,
+    /// When the pane last fired, or was created if it never fired
+    pub last_firing: Instant,
+}
+
+/// Trait for window triggers
+pub trait Trigger: Send + Sync {
+    /// Whether a window fires after an element arrives before the watermark reaches its end
+    fn fire_early(&self, context: &TriggerContext) -> bool;
+
+    /// Whether a window fires again after an element arrives within the allowed lateness
+    fn fire_late(&self, _context: &TriggerContext) -> bool {
+        true
+    }
+}
+
+/// Trigger firing only when the watermark passes the end of a window, and again for every late element
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EventTimeTrigger;
+
+impl Trigger for EventTimeTrigger {
+    fn fire_early(&self, _context: &TriggerContext) -> bool {
+        false
+    }
+}
+
+/// Trigger firing early every `count` elements
+#[derive(Debug, Clone, Copy)]
+pub struct CountTrigger {
+    count: usize,
+}
+
+impl CountTrigger {
+    /// Create a new count trigger
+    pub fn new(count: usize) -> Self {
+        Self {
+            count: count.max(1),
+        }
+    }
+}
+
+impl Trigger for CountTrigger {
+    fn fire_early(&self, context: &TriggerContext) -> bool {
+        context.since_last_firing >= self.count
+    }
+}
+
+/// Trigger firing early when an element arrives at least `interval` after the last firing
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessingTimeTrigger {
+    interval: Duration,
+}
+
+impl ProcessingTimeTrigger {
+    /// Create a new processing-time trigger
+    pub fn new(interval: Duration) -> Self {
+        Self { interval }
+    }
+}
+
+impl Trigger for ProcessingTimeTrigger {
+    fn fire_early(&self, context: &TriggerContext) -> bool {
+        context.last_firing.elapsed() >= self.interval
+    }
+}
+
+/// Trigger suppressing late firings of another trigger
+///
+/// Late elements are still added to their windows and included in any
+/// later firing.
+#[derive(Debug, Clone, Copy)]
+pub struct NoLateFirings<T> {
+    inner: T,
+}
+
+impl<T: Trigger> NoLateFirings<T> {
+    /// Wrap a trigger
+    pub fn new(inner: T) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T: Trigger> Trigger for NoLateFirings<T> {
+    fn fire_early(&self, context: &TriggerContext) -> bool {
+        self.inner.fire_early(context)
+    }
+
+    fn fire_late(&self, _context: &TriggerContext) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::watermark::Timestamp;
+
+    fn context(since_last_firing: usize) -> TriggerContext {
+        TriggerContext {
+            window: Window::new(Timestamp::from_millis(0), Timestamp::from_millis(1000)),
+            elements: since_last_firing,
+            since_last_firing,
+            last_firing: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn test_count_trigger() {
+        let trigger = CountTrigger::new(3);
+        assert!(!trigger.fire_early(&context(2)));
+        assert!(trigger.fire_early(&context(3)));
+        assert!(trigger.fire_late(&context(1)));
+
+        let trigger = NoLateFirings::new(trigger);
+        assert!(trigger.fire_early(&context(3)));
+        assert!(!trigger.fire_late(&context(1)));
+    }
+}
@@ -1,11 +1,22 @@
 //! Window operators for time-based and count-based windowing.
+//!
+//! [`KeyedWindowOperator`] windows a keyed stream by event time: windows fire
+//! when the watermark passes their end, [`Trigger`]s add early and late
+//! firings, session windows merge per key, and the state of a window is
+//! dropped once the watermark passes its end plus the allowed lateness.
+//! Elements arriving after that go to an optional late-data side output.
 
 use crate::error::Result;
+use crate::operators::keyby::KeyExtractor;
+use crate::operators::trigger::{EventTimeTrigger, Trigger, TriggerContext};
 use crate::stream::DataStream;
-use crate::watermark::{Timestamp, Watermark};
+use crate::watermark::{BoundedOutOfOrdernessStrategy, Timestamp, Watermark, WatermarkStrategy};
 use async_trait::async_trait;
-use std::collections::VecDeque;
-use std::time::Duration;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::trace;
 
 /// Window type
 #[derive(Debug, Clone)]
@@ -24,6 +35,11 @@ pub enum WindowType {
 pub trait WindowAssigner: Send + Sync {
     /// Assign an event to windows
     fn assign_windows(&self, timestamp: Timestamp) -> Vec<Window>;
+
+    /// Whether overlapping windows of a key merge into one
+    fn is_merging(&self) -> bool {
+        false
+    }
 }
 
 /// Window definition
@@ -45,6 +61,16 @@ impl Window {
     pub fn duration(&self) -> Duration {
         Duration::from_millis((self.end.as_millis() - self.start.as_millis()) as u64)
     }
+
+    /// Check if two windows overlap
+    pub fn intersects(&self, other: &Window) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+
+    /// Get the smallest window covering both windows
+    pub fn cover(&self, other: &Window) -> Window {
+        Window::new(self.start.min(other.start), self.end.max(other.end))
+    }
 }
 
 /// Tumbling window assigner
@@ -106,6 +132,30 @@ impl WindowAssigner for SlidingWindowAssigner {
     }
 }
 
+/// Session window assigner
+///
+/// Every event opens a window lasting `gap`; overlapping windows of a key
+/// merge, so a session closes once no event arrived for `gap`.
+pub struct SessionWindowAssigner {
+    gap: Duration,
+}
+
+impl SessionWindowAssigner {
+    pub fn new(gap: Duration) -> Self {
+        Self { gap }
+    }
+}
+
+impl WindowAssigner for SessionWindowAssigner {
+    fn assign_windows(&self, timestamp: Timestamp) -> Vec<Window> {
+        vec![Window::new(timestamp, timestamp.add(self.gap))]
+    }
+
+    fn is_merging(&self) -> bool {
+        true
+    }
+}
+
 /// Window operator
 pub struct WindowOperator<S, A>
 where
@@ -193,6 +243,330 @@ where
     }
 }
 
+/// Trait for extracting event timestamps from items
+pub trait TimestampExtractor<T>: Send + Sync {
+    fn extract_timestamp(&self, item: &T) -> Timestamp;
+}
+
+impl<T, F> TimestampExtractor<T> for F
+where
+    F: Fn(&T) -> Timestamp + Send + Sync,
+{
+    fn extract_timestamp(&self, item: &T) -> Timestamp {
+        self(item)
+    }
+}
+
+/// Timing of a window firing relative to the watermark
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaneTiming {
+    /// Fired by the trigger before the watermark passed the window end
+    Early,
+    /// Fired when the watermark passed the window end
+    OnTime,
+    /// Fired by the trigger for an element within the allowed lateness
+    Late,
+}
+
+/// Result of a keyed window firing
+///
+/// Firings accumulate: every pane holds all elements of the window so far.
+#[derive(Debug, Clone)]
+pub struct WindowPane<K, T> {
+    pub key: K,
+    pub window: Window,
+    pub items: Vec<T>,
+    pub timing: PaneTiming,
+}
+
+/// Buffered state of one window of one key
+struct Pane<T> {
+    window: Window,
+    items: Vec<T>,
+    since_last_firing: usize,
+    last_firing: Instant,
+}
+
+impl<T: Clone> Pane<T> {
+    fn new(window: Window) -> Self {
+        Self {
+            window,
+            items: Vec::new(),
+            since_last_firing: 0,
+            last_firing: Instant::now(),
+        }
+    }
+
+    fn merge(&mut self, other: Pane<T>) {
+        self.window = self.window.cover(&other.window);
+        self.items.extend(other.items);
+        self.since_last_firing += other.since_last_firing;
+        self.last_firing = self.last_firing.min(other.last_firing);
+    }
+
+    fn context(&self) -> TriggerContext {
+        TriggerContext {
+            window: self.window,
+            elements: self.items.len(),
+            since_last_firing: self.since_last_firing,
+            last_firing: self.last_firing,
+        }
+    }
+
+    fn fire<K>(&mut self, key: K, timing: PaneTiming) -> WindowPane<K, T> {
+        self.since_last_firing = 0;
+        self.last_firing = Instant::now();
+        WindowPane {
+            key,
+            window: self.window,
+            items: self.items.clone(),
+            timing,
+        }
+    }
+}
+
+/// Find the pane of a window, merging overlapping panes for merging assigners
+fn pane_for<T: Clone>(panes: &mut Vec<Pane<T>>, window: Window, merging: bool) -> &mut Pane<T> {
+    let index = match panes.iter().position(|pane| pane.window == window) {
+        Some(index) if !merging => index,
+        _ if !merging => {
+            panes.push(Pane::new(window));
+            panes.len() - 1
+        }
+        _ => {
+            let mut merged = Pane::new(window);
+            while let Some(index) =
+                panes.iter().position(|pane| pane.window.intersects(&merged.window))
+            {
+                merged.merge(panes.swap_remove(index));
+            }
+            panes.push(merged);
+            panes.len() - 1
+        }
+    };
+    &mut panes[index]
+}
+
+/// Keyed event-time window operator
+///
+/// Windows fire when the watermark passes their end, with early firings and
+/// firings for late elements decided by the [`Trigger`]. Elements may arrive
+/// until the watermark passes the window end plus the allowed lateness, at
+/// which point the state of the window is dropped. Later elements are sent
+/// to the late-data output if one is set, and dropped otherwise. When the
+/// input ends, all remaining windows fire.
+pub struct KeyedWindowOperator<S, K, A>
+where
+    S: DataStream,
+    K: Hash + Eq + Clone + Send + 'static,
+    A: WindowAssigner + 'static,
+{
+    stream: S,
+    assigner: A,
+    key_extractor: Box<dyn KeyExtractor<S::Item, K>>,
+    timestamp_extractor: Box<dyn TimestampExtractor<S::Item>>,
+    trigger: Box<dyn Trigger>,
+    watermark_strategy: Box<dyn WatermarkStrategy>,
+    allowed_lateness: Duration,
+    late_output: Option<mpsc::UnboundedSender<S::Item>>,
+    late_count: u64,
+    panes: HashMap<K, Vec<Pane<S::Item>>>,
+    current_watermark: Watermark,
+    output: VecDeque<WindowPane<K, S::Item>>,
+    finished: bool,
+}
+
+impl<S, K, A> KeyedWindowOperator<S, K, A>
+where
+    S: DataStream,
+    S::Item: Clone + Send + 'static,
+    K: Hash + Eq + Clone + Send + 'static,
+    A: WindowAssigner + 'static,
+{
+    /// Create a new keyed window operator
+    ///
+    /// Watermarks follow the largest event timestamp seen, and windows fire
+    /// only when the watermark passes their end.
+    pub fn new<E, T>(stream: S, assigner: A, key_extractor: E, timestamp_extractor: T) -> Self
+    where
+        E: KeyExtractor<S::Item, K> + 'static,
+        T: TimestampExtractor<S::Item> + 'static,
+    {
+        Self {
+            stream,
+            assigner,
+            key_extractor: Box::new(key_extractor),
+            timestamp_extractor: Box::new(timestamp_extractor),
+            trigger: Box::new(EventTimeTrigger),
+            watermark_strategy: Box::new(BoundedOutOfOrdernessStrategy::new(Duration::ZERO)),
+            allowed_lateness: Duration::ZERO,
+            late_output: None,
+            late_count: 0,
+            panes: HashMap::new(),
+            current_watermark: Watermark::min(),
+            output: VecDeque::new(),
+            finished: false,
+        }
+    }
+
+    /// Set the trigger deciding early and late firings
+    pub fn with_trigger(mut self, trigger: impl Trigger + 'static) -> Self {
+        self.trigger = Box::new(trigger);
+        self
+    }
+
+    /// Set the strategy generating watermarks from event timestamps
+    pub fn with_watermark_strategy(mut self, strategy: impl WatermarkStrategy + 'static) -> Self {
+        self.watermark_strategy = Box::new(strategy);
+        self
+    }
+
+    /// Keep windows open for late elements until the watermark passes their end plus `lateness`
+    pub fn with_allowed_lateness(mut self, lateness: Duration) -> Self {
+        self.allowed_lateness = lateness;
+        self
+    }
+
+    /// Send elements arriving after the allowed lateness to a side output
+    pub fn with_late_output(mut self, sender: mpsc::UnboundedSender<S::Item>) -> Self {
+        self.late_output = Some(sender);
+        self
+    }
+
+    /// Get the current watermark
+    pub fn current_watermark(&self) -> Watermark {
+        self.current_watermark
+    }
+
+    /// Get the number of elements that arrived after the allowed lateness
+    pub fn late_count(&self) -> u64 {
+        self.late_count
+    }
+
+    /// Get the number of keys with open windows
+    pub fn active_keys(&self) -> usize {
+        self.panes.len()
+    }
+
+    /// Get the number of open windows across all keys
+    pub fn active_windows(&self) -> usize {
+        self.panes.values().map(Vec::len).sum()
+    }
+
+    /// Check if the state of a window can be dropped at a watermark
+    fn is_expired(&self, window: &Window, watermark: Watermark) -> bool {
+        let lateness = self.allowed_lateness.as_millis() as i64;
+        window.end.as_millis().saturating_add(lateness) <= watermark.timestamp.as_millis()
+    }
+
+    fn process(&mut self, item: S::Item) {
+        let timestamp = self.timestamp_extractor.extract_timestamp(&item);
+        let watermark = self.current_watermark;
+        let windows: Vec<_> = self
+            .assigner
+            .assign_windows(timestamp)
+            .into_iter()
+            .filter(|window| !self.is_expired(window, watermark))
+            .collect();
+
+        if windows.is_empty() {
+            self.late_count += 1;
+            match &self.late_output {
+                Some(sender) => {
+                    if sender.send(item).is_err() {
+                        trace!("Late-data output closed, dropping late element");
+                    }
+                }
+                None => trace!("Dropping late element at {}", timestamp.as_millis()),
+            }
+        } else {
+            let key = self.key_extractor.extract_key(&item);
+            let merging = self.assigner.is_merging();
+            let panes = self.panes.entry(key.clone()).or_default();
+
+            for window in windows {
+                let pane = pane_for(panes, window, merging);
+                pane.items.push(item.clone());
+                pane.since_last_firing += 1;
+
+                let context = pane.context();
+                let timing = if pane.window.end <= watermark.timestamp {
+                    self.trigger.fire_late(&context).then_some(PaneTiming::Late)
+                } else {
+                    self.trigger.fire_early(&context).then_some(PaneTiming::Early)
+                };
+                if let Some(timing) = timing {
+                    self.output.push_back(pane.fire(key.clone(), timing));
+                }
+            }
+        }
+
+        if let Some(watermark) = self.watermark_strategy.generate(timestamp) {
+            self.advance_watermark(watermark);
+        }
+    }
+
+    /// Fire the windows the watermark passed and drop expired window state
+    fn advance_watermark(&mut self, watermark: Watermark) {
+        if watermark <= self.current_watermark {
+            return;
+        }
+        let previous = self.current_watermark;
+        self.current_watermark = watermark;
+
+        let mut fired = Vec::new();
+        let mut panes = std::mem::take(&mut self.panes);
+        for (key, key_panes) in panes.iter_mut() {
+            for pane in key_panes.iter_mut() {
+                if pane.window.end > previous.timestamp && pane.window.end <= watermark.timestamp {
+                    fired.push(pane.fire(key.clone(), PaneTiming::OnTime));
+                }
+            }
+            key_panes.retain(|pane| !self.is_expired(&pane.window, watermark));
+        }
+        panes.retain(|_, key_panes| !key_panes.is_empty());
+        self.panes = panes;
+
+        fired.sort_by_key(|pane| (pane.window.end, pane.window.start));
+        self.output.extend(fired);
+    }
+}
+
+#[async_trait]
+impl<S, K, A> DataStream for KeyedWindowOperator<S, K, A>
+where
+    S: DataStream,
+    S::Item: Clone + Send + 'static,
+    K: Hash + Eq + Clone + Send + 'static,
+    A: WindowAssigner + 'static,
+{
+    type Item = WindowPane<K, S::Item>;
+
+    async fn next(&mut self) -> Result<Option<Self::Item>> {
+        loop {
+            if let Some(pane) = self.output.pop_front() {
+                return Ok(Some(pane));
+            }
+            if self.finished {
+                return Ok(None);
+            }
+
+            match self.stream.next().await? {
+                Some(item) => self.process(item),
+                None => {
+                    // End of input, fire and drop all remaining windows
+                    self.finished = true;
+                    self.advance_watermark(Watermark::max());
+                }
+            }
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.finished && self.output.is_empty()
+    }
+}
+
 /// Count-based window operator
 pub struct CountWindowOperator<S>
 where
@@ -255,7 +629,8 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::source::iterator::RangeSource;
+    use crate::operators::trigger::CountTrigger;
+    use crate::source::iterator::{IteratorSource, RangeSource};
     use crate::source::Source;
 
     #[test]
@@ -286,4 +661,117 @@ mod tests {
         let window2 = windowed.next().await.unwrap().unwrap();
         assert_eq!(window2, vec![3, 4, 5]);
     }
+
+    fn event_time(item: &(&'static str, i64)) -> Timestamp {
+        Timestamp::from_millis(item.1)
+    }
+
+    fn millis(pane: &WindowPane<&'static str, (&'static str, i64)>) -> Vec<i64> {
+        pane.items.iter().map(|(_, ts)| *ts).collect()
+    }
+
+    #[tokio::test]
+    async fn test_session_windows_merge_per_key() {
+        let events = vec![("a", 0), ("a", 50), ("b", 60), ("a", 300), ("b", 400)];
+        let mut source = IteratorSource::new(events.into_iter());
+        source.start().await.unwrap();
+
+        let mut sessions = KeyedWindowOperator::new(
+            source,
+            SessionWindowAssigner::new(Duration::from_millis(100)),
+            |item: &(&'static str, i64)| item.0,
+            event_time,
+        );
+
+        let first = sessions.next().await.unwrap().unwrap();
+        assert_eq!(first.key, "a");
+        assert_eq!(first.window.start.as_millis(), 0);
+        assert_eq!(first.window.end.as_millis(), 150);
+        assert_eq!(first.timing, PaneTiming::OnTime);
+        assert_eq!(millis(&first), vec![0, 50]);
+
+        let second = sessions.next().await.unwrap().unwrap();
+        assert_eq!(second.key, "b");
+        assert_eq!(millis(&second), vec![60]);
+
+        // Closed sessions are dropped, only the new session of "a" is open
+        assert_eq!(sessions.active_keys(), 1);
+        assert_eq!(sessions.active_windows(), 1);
+
+        let third = sessions.next().await.unwrap().unwrap();
+        assert_eq!((third.key, millis(&third)), ("a", vec![300]));
+
+        let last = sessions.next().await.unwrap().unwrap();
+        assert_eq!((last.key, millis(&last)), ("b", vec![400]));
+        assert!(sessions.next().await.unwrap().is_none());
+        assert_eq!(sessions.active_windows(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_allowed_lateness_and_late_output() {
+        let events = vec![("a", 10), ("a", 120), ("a", 30), ("a", 200), ("a", 40)];
+        let mut source = IteratorSource::new(events.into_iter());
+        source.start().await.unwrap();
+
+        let (late_tx, mut late_rx) = mpsc::unbounded_channel();
+        let mut windows = KeyedWindowOperator::new(
+            source,
+            TumblingWindowAssigner::new(Duration::from_millis(100)),
+            |item: &(&'static str, i64)| item.0,
+            event_time,
+        )
+        .with_allowed_lateness(Duration::from_millis(50))
+        .with_late_output(late_tx);
+
+        let on_time = windows.next().await.unwrap().unwrap();
+        assert_eq!(on_time.timing, PaneTiming::OnTime);
+        assert_eq!(millis(&on_time), vec![10]);
+
+        // Within the allowed lateness, the window fires again
+        let late = windows.next().await.unwrap().unwrap();
+        assert_eq!(late.timing, PaneTiming::Late);
+        assert_eq!(late.window.end.as_millis(), 100);
+        assert_eq!(millis(&late), vec![10, 30]);
+
+        let next = windows.next().await.unwrap().unwrap();
+        assert_eq!(next.window.start.as_millis(), 100);
+        assert_eq!(millis(&next), vec![120]);
+        assert_eq!(windows.active_windows(), 2);
+
+        // Past the allowed lateness, the element goes to the side output
+        let last = windows.next().await.unwrap().unwrap();
+        assert_eq!(last.window.start.as_millis(), 200);
+        assert!(windows.next().await.unwrap().is_none());
+
+        assert_eq!(windows.late_count(), 1);
+        assert_eq!(late_rx.try_recv().unwrap(), ("a", 40));
+        assert_eq!(windows.active_windows(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_early_firing_trigger() {
+        let events = vec![("a", 1), ("a", 2), ("a", 3), ("a", 150)];
+        let mut source = IteratorSource::new(events.into_iter());
+        source.start().await.unwrap();
+
+        let mut windows = KeyedWindowOperator::new(
+            source,
+            TumblingWindowAssigner::new(Duration::from_millis(100)),
+            |item: &(&'static str, i64)| item.0,
+            event_time,
+        )
+        .with_trigger(CountTrigger::new(2));
+
+        let early = windows.next().await.unwrap().unwrap();
+        assert_eq!(early.timing, PaneTiming::Early);
+        assert_eq!(millis(&early), vec![1, 2]);
+
+        let on_time = windows.next().await.unwrap().unwrap();
+        assert_eq!(on_time.timing, PaneTiming::OnTime);
+        assert_eq!(millis(&on_time), vec![1, 2, 3]);
+
+        let last = windows.next().await.unwrap().unwrap();
+        assert_eq!(last.timing, PaneTiming::OnTime);
+        assert_eq!(millis(&last), vec![150]);
+    }
 }
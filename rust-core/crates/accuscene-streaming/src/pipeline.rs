@@ -2,11 +2,12 @@
 
 use crate::backpressure::BackpressureController;
 use crate::checkpoint::CheckpointCoordinator;
-use crate::config::StreamingConfig;
+use crate::config::{StreamingConfig, WatermarkConfig};
 use crate::error::{Result, StreamingError};
+use crate::operators::window::TimestampExtractor;
 use crate::runtime::StreamingRuntime;
 use crate::sink::Sink;
-use crate::source::Source;
+use crate::source::{AlignedSource, Source};
 use crate::state::StateContext;
 use crate::stream::DataStream;
 use crate::watermark::WatermarkTracker;
@@ -34,6 +35,12 @@ impl PipelineBuilder {
         self
     }
 
+    /// Set watermark generation for partitioned sources
+    pub fn with_watermarks(mut self, watermark: WatermarkConfig) -> Self {
+        self.config.watermark = watermark;
+        self
+    }
+
     /// Build the pipeline
     pub fn build(self) -> Pipeline {
         Pipeline {
@@ -71,6 +78,18 @@ impl Pipeline {
         &self.config
     }
 
+    /// Create a source merging partitions in event-time order with the pipeline watermark settings
+    pub fn aligned_source<S>(
+        &self,
+        timestamp_extractor: impl TimestampExtractor<S::Item> + 'static,
+    ) -> AlignedSource<S>
+    where
+        S: Source + 'static,
+        S::Item: Send + 'static,
+    {
+        AlignedSource::new(timestamp_extractor).with_config(&self.config.watermark)
+    }
+
     /// Initialize the pipeline
     pub async fn init(&mut self) -> Result<()> {
         let runtime = StreamingRuntime::new(self.config.clone()).await?;
@@ -1,10 +1,21 @@
 //! Event replay functionality for synchronization.
+//!
+//! Besides sequence-based replay for reconnecting clients, recorded events can
+//! be selected by time range and [`EventFilter`] with a [`ReplayQuery`], then
+//! exported to NDJSON or Parquet for investigation, or re-published into an
+//! [`EventBus`] at their original or an accelerated pace.
 
+use crate::bus::EventBus;
 use crate::error::{Result, StreamingError};
-use crate::event::Event;
+use crate::event::{Event, EventFilter};
+use chrono::{DateTime, Utc};
 use parking_lot::RwLock;
 use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Event replay buffer for storing and replaying events
 pub struct ReplayBuffer {
@@ -88,6 +99,21 @@ impl ReplayBuffer {
         events.iter().map(|(_, event)| event.clone()).collect()
     }
 
+    /// Get events matching a query, ordered by timestamp
+    pub fn replay_query(&self, query: &ReplayQuery) -> Vec<Event> {
+        let events = self.events.read();
+
+        let mut matched: Vec<Event> = events
+            .iter()
+            .filter(|(_, event)| query.matches(event))
+            .map(|(_, event)| event.clone())
+            .collect();
+
+        // Stable, so events with equal timestamps keep their recording order
+        matched.sort_by_key(|event| event.timestamp);
+        matched
+    }
+
     /// Get current sequence number
     pub fn current_sequence(&self) -> u64 {
         *self.sequence.read()
@@ -152,12 +178,276 @@ impl ReplayManager {
         }
     }
 
+    /// Handle a time-range query
+    pub fn handle_query(&self, query: &ReplayQuery) -> Result<Vec<Event>> {
+        if let (Some(from), Some(to)) = (query.from, query.to) {
+            if from > to {
+                return Err(StreamingError::Replay(
+                    "Invalid range: from > to".to_string(),
+                ));
+            }
+        }
+        Ok(self.buffer.replay_query(query))
+    }
+
+    /// Export events matching a query to a writer, returning the number of events written
+    pub fn export<W: Write + Send>(
+        &self,
+        query: &ReplayQuery,
+        format: ReplayExportFormat,
+        writer: W,
+    ) -> Result<usize> {
+        let events = self.handle_query(query)?;
+        match format {
+            ReplayExportFormat::Ndjson => write_ndjson(&events, writer)?,
+            #[cfg(feature = "arrow-support")]
+            ReplayExportFormat::Parquet => write_parquet(&events, writer)?,
+        }
+        Ok(events.len())
+    }
+
+    /// Export events matching a query to a file, returning the number of events written
+    pub fn export_to_file(
+        &self,
+        query: &ReplayQuery,
+        format: ReplayExportFormat,
+        path: impl AsRef<Path>,
+    ) -> Result<usize> {
+        let file = File::create(path.as_ref()).map_err(|e| {
+            StreamingError::Replay(format!("Failed to create export file: {}", e))
+        })?;
+        self.export(query, format, file)
+    }
+
+    /// Re-publish events matching a query into a bus, returning the number of events published
+    ///
+    /// Events are published unchanged, keeping their original ids and
+    /// timestamps, with the gaps between them scaled by `speed`.
+    pub async fn replay_into<B: EventBus + ?Sized>(
+        &self,
+        query: &ReplayQuery,
+        bus: &B,
+        speed: ReplaySpeed,
+    ) -> Result<usize> {
+        if let ReplaySpeed::Accelerated(factor) = speed {
+            if !(factor.is_finite() && factor > 0.0) {
+                return Err(StreamingError::Replay(format!(
+                    "Invalid acceleration factor: {}",
+                    factor
+                )));
+            }
+        }
+
+        let events = self.handle_query(query)?;
+        let count = events.len();
+        let mut previous: Option<DateTime<Utc>> = None;
+
+        for event in events {
+            if let Some(previous) = previous {
+                if let Some(delay) = speed.delay(event.timestamp - previous) {
+                    tokio::time::sleep(delay).await;
+                }
+            }
+            previous = Some(event.timestamp);
+            bus.publish(event).await?;
+        }
+
+        Ok(count)
+    }
+
     /// Get current sequence number
     pub fn current_sequence(&self) -> u64 {
         self.buffer.current_sequence()
     }
 }
 
+/// Query selecting recorded events by time range and filter
+///
+/// Both ends of the range are inclusive; an open end is unbounded.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayQuery {
+    /// Earliest event timestamp
+    pub from: Option<DateTime<Utc>>,
+    /// Latest event timestamp
+    pub to: Option<DateTime<Utc>>,
+    /// Filter events must pass
+    pub filter: EventFilter,
+}
+
+impl ReplayQuery {
+    /// Create a query matching all events
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restrict to events between two timestamps
+    pub fn between(from: DateTime<Utc>, to: DateTime<Utc>) -> Self {
+        Self::new().with_from(from).with_to(to)
+    }
+
+    /// Set the earliest event timestamp
+    pub fn with_from(mut self, from: DateTime<Utc>) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    /// Set the latest event timestamp, replaying up to that point in time
+    pub fn with_to(mut self, to: DateTime<Utc>) -> Self {
+        self.to = Some(to);
+        self
+    }
+
+    /// Set the event filter
+    pub fn with_filter(mut self, filter: EventFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Check if an event matches this query
+    pub fn matches(&self, event: &Event) -> bool {
+        if self.from.is_some_and(|from| event.timestamp < from) {
+            return false;
+        }
+        if self.to.is_some_and(|to| event.timestamp > to) {
+            return false;
+        }
+        self.filter.matches(event)
+    }
+}
+
+/// Pace at which events are re-published into a bus
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    /// Keep the original gaps between events
+    Original,
+    /// Divide the original gaps by a factor
+    Accelerated(f64),
+    /// Publish without waiting between events
+    Unthrottled,
+}
+
+impl ReplaySpeed {
+    /// Delay to wait for a gap between two events
+    fn delay(&self, gap: chrono::Duration) -> Option<Duration> {
+        let gap = gap.to_std().ok()?;
+        let delay = match self {
+            ReplaySpeed::Original => gap,
+            ReplaySpeed::Accelerated(factor) => gap.div_f64(*factor),
+            ReplaySpeed::Unthrottled => return None,
+        };
+        (!delay.is_zero()).then_some(delay)
+    }
+}
+
+/// Replay export file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayExportFormat {
+    /// One JSON-encoded event per line
+    Ndjson,
+    /// Columnar Parquet file
+    #[cfg(feature = "arrow-support")]
+    Parquet,
+}
+
+/// Write events as newline-delimited JSON
+pub fn write_ndjson<W: Write>(events: &[Event], writer: W) -> Result<()> {
+    let mut writer = BufWriter::new(writer);
+    for event in events {
+        serde_json::to_writer(&mut writer, event)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write events as a Parquet file
+///
+/// Metadata fields become their own columns; the event type and payload are
+/// stored as JSON strings.
+#[cfg(feature = "arrow-support")]
+pub fn write_parquet<W: Write + Send>(events: &[Event], writer: W) -> Result<()> {
+    use arrow::array::{ArrayRef, RecordBatch, StringArray, TimestampMillisecondArray, UInt8Array};
+    use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+    use parquet::arrow::ArrowWriter;
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Utf8, false),
+        Field::new(
+            "timestamp",
+            DataType::Timestamp(TimeUnit::Millisecond, Some("+00:00".into())),
+            false,
+        ),
+        Field::new("event_type", DataType::Utf8, false),
+        Field::new("room_id", DataType::Utf8, true),
+        Field::new("user_id", DataType::Utf8, true),
+        Field::new("correlation_id", DataType::Utf8, true),
+        Field::new("priority", DataType::UInt8, false),
+        Field::new("payload", DataType::Utf8, false),
+    ]));
+
+    let event_types = events
+        .iter()
+        .map(|event| serde_json::to_string(&event.event_type))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let payloads = events
+        .iter()
+        .map(|event| serde_json::to_string(&event.payload))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from(
+            events.iter().map(|e| e.id.to_string()).collect::<Vec<_>>(),
+        )),
+        Arc::new(
+            TimestampMillisecondArray::from(
+                events
+                    .iter()
+                    .map(|e| e.timestamp.timestamp_millis())
+                    .collect::<Vec<_>>(),
+            )
+            .with_timezone_utc(),
+        ),
+        Arc::new(StringArray::from(event_types)),
+        Arc::new(StringArray::from(
+            events
+                .iter()
+                .map(|e| e.metadata.room_id.clone())
+                .collect::<Vec<_>>(),
+        )),
+        Arc::new(StringArray::from(
+            events
+                .iter()
+                .map(|e| e.metadata.user_id.clone())
+                .collect::<Vec<_>>(),
+        )),
+        Arc::new(StringArray::from(
+            events
+                .iter()
+                .map(|e| e.metadata.correlation_id.clone())
+                .collect::<Vec<_>>(),
+        )),
+        Arc::new(UInt8Array::from(
+            events.iter().map(|e| e.metadata.priority).collect::<Vec<_>>(),
+        )),
+        Arc::new(StringArray::from(payloads)),
+    ];
+
+    let batch = RecordBatch::try_new(schema.clone(), columns)
+        .map_err(|e| StreamingError::Arrow(format!("Failed to create RecordBatch: {}", e)))?;
+
+    let mut writer = ArrowWriter::try_new(writer, schema, None).map_err(|e| {
+        StreamingError::Parquet(format!("Failed to create Parquet writer: {}", e))
+    })?;
+    writer.write(&batch).map_err(|e| {
+        StreamingError::Parquet(format!("Failed to write RecordBatch: {}", e))
+    })?;
+    writer.close().map_err(|e| {
+        StreamingError::Parquet(format!("Failed to close Parquet writer: {}", e))
+    })?;
+
+    Ok(())
+}
+
 /// Replay configuration
 #[derive(Debug, Clone)]
 pub struct ReplayConfig {
@@ -291,4 +581,103 @@ mod tests {
         let events = manager.handle_replay(Some(2), Some(2)).unwrap();
         assert_eq!(events.len(), 1);
     }
+
+    fn case_event(event_type: EventType, room: &str, at: DateTime<Utc>) -> Event {
+        let mut event = Event::new(event_type, EventPayload::Empty);
+        event.metadata.room_id = Some(room.to_string());
+        event.timestamp = at;
+        event
+    }
+
+    fn recorded_case() -> (ReplayManager, DateTime<Utc>) {
+        let manager = ReplayManager::new(100);
+        let start = Utc::now();
+        let at = |secs| start + chrono::Duration::seconds(secs);
+
+        // Recorded out of timestamp order
+        manager.record(case_event(EventType::CaseUpdated, "case-x", at(120)));
+        manager.record(case_event(EventType::CaseCreated, "case-x", at(0)));
+        manager.record(case_event(EventType::CaseUpdated, "case-y", at(60)));
+        manager.record(case_event(EventType::EditApplied, "case-x", at(60)));
+        manager.record(case_event(EventType::CaseDeleted, "case-x", at(600)));
+
+        (manager, start)
+    }
+
+    #[test]
+    fn test_replay_query() {
+        let (manager, start) = recorded_case();
+        let query = ReplayQuery::between(start, start + chrono::Duration::seconds(300))
+            .with_filter(EventFilter::new().with_room("case-x"));
+
+        let events = manager.handle_query(&query).unwrap();
+        let types: Vec<_> = events.iter().map(|e| e.event_type.clone()).collect();
+        assert_eq!(
+            types,
+            vec![
+                EventType::CaseCreated,
+                EventType::EditApplied,
+                EventType::CaseUpdated
+            ]
+        );
+
+        let point_in_time = ReplayQuery::new().with_to(start + chrono::Duration::seconds(60));
+        assert_eq!(manager.handle_query(&point_in_time).unwrap().len(), 3);
+
+        let inverted = ReplayQuery::between(start + chrono::Duration::seconds(1), start);
+        assert!(manager.handle_query(&inverted).is_err());
+    }
+
+    #[test]
+    fn test_export_ndjson() {
+        let (manager, start) = recorded_case();
+        let query = ReplayQuery::new()
+            .with_from(start + chrono::Duration::seconds(60))
+            .with_filter(EventFilter::new().with_room("case-x"));
+
+        let mut output = Vec::new();
+        let count = manager
+            .export(&query, ReplayExportFormat::Ndjson, &mut output)
+            .unwrap();
+        assert_eq!(count, 3);
+
+        let lines: Vec<Event> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].event_type, EventType::EditApplied);
+        assert_eq!(lines[2].event_type, EventType::CaseDeleted);
+    }
+
+    #[tokio::test]
+    async fn test_replay_into_bus() {
+        use crate::bus::memory::MemoryEventBus;
+        use futures::StreamExt;
+
+        let (manager, start) = recorded_case();
+        let bus = MemoryEventBus::new();
+        let mut stream = bus.subscribe_all().await.unwrap();
+        let query = ReplayQuery::between(start, start + chrono::Duration::seconds(120))
+            .with_filter(EventFilter::new().with_room("case-x"));
+
+        // 120 seconds of events at 1200x take about 100ms
+        let started = std::time::Instant::now();
+        let count = manager
+            .replay_into(&query, &bus, ReplaySpeed::Accelerated(1200.0))
+            .await
+            .unwrap();
+        assert_eq!(count, 3);
+        assert!(started.elapsed() >= Duration::from_millis(100));
+
+        let first = stream.next().await.unwrap();
+        assert_eq!(first.event_type, EventType::CaseCreated);
+        assert_eq!(first.timestamp, start);
+
+        let invalid = manager
+            .replay_into(&query, &bus, ReplaySpeed::Accelerated(0.0))
+            .await;
+        assert!(invalid.is_err());
+    }
 }
@@ -9,6 +9,7 @@ use crate::watermark::WatermarkTracker;
 use std::sync::Arc;
 use tokio::runtime::Runtime as TokioRuntime;
 use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, info};
 
 /// Streaming runtime
@@ -19,11 +20,20 @@ pub struct StreamingRuntime {
     watermark_tracker: Arc<WatermarkTracker>,
     backpressure_controller: Option<Arc<BackpressureController>>,
     checkpoint_task: Option<JoinHandle<()>>,
+    shutdown: CancellationToken,
 }
 
 impl StreamingRuntime {
     /// Create a new streaming runtime
     pub async fn new(config: StreamingConfig) -> Result<Self> {
+        Self::with_shutdown_token(config, CancellationToken::new()).await
+    }
+
+    /// Create a new streaming runtime whose background tasks stop when `shutdown` is cancelled
+    pub async fn with_shutdown_token(
+        config: StreamingConfig,
+        shutdown: CancellationToken,
+    ) -> Result<Self> {
         info!("Initializing streaming runtime");
 
         // Create state context
@@ -58,6 +68,7 @@ impl StreamingRuntime {
             watermark_tracker,
             backpressure_controller,
             checkpoint_task: None,
+            shutdown,
         };
 
         // Start checkpoint task if enabled
@@ -73,11 +84,18 @@ impl StreamingRuntime {
     async fn start_checkpoint_task(&mut self) -> Result<()> {
         let coordinator = self.checkpoint_coordinator.clone();
         let interval = self.config.checkpoint.interval;
+        let shutdown = self.shutdown.clone();
 
         let handle = tokio::spawn(async move {
             let mut interval_timer = tokio::time::interval(interval);
             loop {
-                interval_timer.tick().await;
+                tokio::select! {
+                    _ = interval_timer.tick() => {}
+                    _ = shutdown.cancelled() => {
+                        debug!("Checkpoint task stopping");
+                        break;
+                    }
+                }
 
                 match coordinator.trigger_checkpoint().await {
                     Ok(id) => {
@@ -115,13 +133,26 @@ impl StreamingRuntime {
         self.backpressure_controller.clone()
     }
 
+    /// Hand over the background task handles so a shutdown scope can await them
+    pub fn take_task_handles(&mut self) -> Vec<(String, JoinHandle<()>)> {
+        self.checkpoint_task
+            .take()
+            .map(|handle| ("checkpoint".to_string(), handle))
+            .into_iter()
+            .collect()
+    }
+
     /// Shutdown the runtime
+    ///
+    /// Lets an in-flight checkpoint finish before returning.
     pub async fn shutdown(self) -> Result<()> {
         info!("Shutting down streaming runtime");
 
-        // Stop checkpoint task
+        self.shutdown.cancel();
         if let Some(handle) = self.checkpoint_task {
-            handle.abort();
+            if let Err(e) = handle.await {
+                tracing::warn!("Checkpoint task did not stop cleanly: {}", e);
+            }
         }
 
         info!("Streaming runtime shutdown complete");
@@ -172,6 +203,7 @@ impl RuntimeMetrics {
 /// Runtime builder for custom runtime configuration
 pub struct RuntimeBuilder {
     config: StreamingConfig,
+    shutdown: Option<CancellationToken>,
 }
 
 impl RuntimeBuilder {
@@ -179,6 +211,7 @@ impl RuntimeBuilder {
     pub fn new() -> Self {
         Self {
             config: StreamingConfig::default(),
+            shutdown: None,
         }
     }
 
@@ -206,9 +239,16 @@ impl RuntimeBuilder {
         self
     }
 
+    /// Stop background tasks when the given token is cancelled
+    pub fn with_shutdown_token(mut self, token: CancellationToken) -> Self {
+        self.shutdown = Some(token);
+        self
+    }
+
     /// Build the runtime
     pub async fn build(self) -> Result<StreamingRuntime> {
-        StreamingRuntime::new(self.config).await
+        let shutdown = self.shutdown.unwrap_or_default();
+        StreamingRuntime::with_shutdown_token(self.config, shutdown).await
     }
 }
 
@@ -244,4 +284,27 @@ mod tests {
 
         runtime.shutdown().await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_checkpoint_task_stops_on_cancellation() {
+        let token = CancellationToken::new();
+        let mut runtime = RuntimeBuilder::new()
+            .with_checkpointing(true)
+            .with_shutdown_token(token.child_token())
+            .build()
+            .await
+            .unwrap();
+
+        let handles = runtime.take_task_handles();
+        assert_eq!(handles.len(), 1);
+        assert_eq!(handles[0].0, "checkpoint");
+
+        token.cancel();
+        for (_, handle) in handles {
+            tokio::time::timeout(std::time::Duration::from_secs(1), handle)
+                .await
+                .expect("checkpoint task should stop")
+                .unwrap();
+        }
+    }
 }
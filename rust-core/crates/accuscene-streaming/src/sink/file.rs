@@ -1,11 +1,13 @@
 //! File-based streaming sink with rotation support.
 
+use crate::checkpoint::CheckpointId;
 use crate::error::{Result, StreamingError};
-use crate::sink::Sink;
+use crate::sink::{Sink, TwoPhaseCommitSink};
 use async_trait::async_trait;
-use std::path::PathBuf;
-use tokio::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+use tokio::fs::{self, File, OpenOptions};
 use tokio::io::{AsyncWriteExt, BufWriter};
+use tracing::debug;
 
 /// File sink that writes data to a file
 pub struct FileSink {
@@ -224,6 +226,239 @@ impl Sink<String> for RotatingFileSink {
     }
 }
 
+/// Exactly-once file sink committing one part file per checkpoint
+///
+/// Lines are written to a hidden in-progress file. On a checkpoint barrier
+/// the file is synced and renamed to a pending file for the checkpoint, and
+/// once the checkpoint is complete the pending file is renamed to
+/// `{prefix}-{checkpoint_id}.part`. Part file names are zero-padded, so
+/// reading the committed parts in name order yields the output in order.
+pub struct TransactionalFileSink {
+    dir: PathBuf,
+    prefix: String,
+    writer: Option<BufWriter<File>>,
+}
+
+impl TransactionalFileSink {
+    /// Create a sink writing part files into a directory
+    pub async fn new(dir: PathBuf, prefix: impl Into<String>) -> Result<Self> {
+        fs::create_dir_all(&dir)
+            .await
+            .map_err(|e| StreamingError::Sink(format!("Failed to create sink directory: {}", e)))?;
+
+        Ok(Self {
+            dir,
+            prefix: prefix.into(),
+            writer: None,
+        })
+    }
+
+    /// Get the committed part files in output order
+    pub async fn committed_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files: Vec<_> = self
+            .list_files(".part")
+            .await?
+            .into_iter()
+            .map(|(_, path)| path)
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+
+    fn in_progress_path(&self) -> PathBuf {
+        self.dir.join(format!(".{}.inprogress", self.prefix))
+    }
+
+    fn pending_path(&self, checkpoint_id: CheckpointId) -> PathBuf {
+        self.dir.join(format!(".{}-{}.pending", self.prefix, checkpoint_id))
+    }
+
+    fn part_path(&self, checkpoint_id: CheckpointId) -> PathBuf {
+        self.dir.join(format!("{}-{:020}.part", self.prefix, checkpoint_id))
+    }
+
+    /// List this sink's files with a suffix, with the checkpoint ID of each
+    async fn list_files(&self, suffix: &str) -> Result<Vec<(CheckpointId, PathBuf)>> {
+        let start = if suffix == ".pending" {
+            format!(".{}-", self.prefix)
+        } else {
+            format!("{}-", self.prefix)
+        };
+
+        let mut entries = fs::read_dir(&self.dir)
+            .await
+            .map_err(|e| StreamingError::Sink(format!("Failed to read sink directory: {}", e)))?;
+
+        let mut files = Vec::new();
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| StreamingError::Sink(format!("Failed to read directory entry: {}", e)))?
+        {
+            let name = entry.file_name();
+            let id = name
+                .to_str()
+                .and_then(|name| name.strip_prefix(start.as_str()))
+                .and_then(|name| name.strip_suffix(suffix))
+                .and_then(|id| id.parse::<CheckpointId>().ok());
+
+            if let Some(id) = id {
+                files.push((id, entry.path()));
+            }
+        }
+
+        files.sort_by_key(|(id, _)| *id);
+        Ok(files)
+    }
+
+    async fn init_writer(&mut self) -> Result<()> {
+        if self.writer.is_some() {
+            return Ok(());
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.in_progress_path())
+            .await
+            .map_err(|e| StreamingError::Sink(format!("Failed to open file: {}", e)))?;
+
+        self.writer = Some(BufWriter::new(file));
+        Ok(())
+    }
+
+    /// Flush and sync the in-progress file and close it
+    async fn finish_writer(&mut self) -> Result<()> {
+        let Some(mut writer) = self.writer.take() else {
+            return Ok(());
+        };
+
+        writer
+            .flush()
+            .await
+            .map_err(|e| StreamingError::Sink(format!("File flush error: {}", e)))?;
+        writer
+            .get_mut()
+            .sync_all()
+            .await
+            .map_err(|e| StreamingError::Sink(format!("File sync error: {}", e)))
+    }
+
+    async fn rename(from: &Path, to: &Path) -> Result<()> {
+        fs::rename(from, to).await.map_err(|e| {
+            StreamingError::Sink(format!(
+                "Failed to move {} to {}: {}",
+                from.display(),
+                to.display(),
+                e
+            ))
+        })
+    }
+
+    async fn remove(path: &Path) -> Result<()> {
+        match fs::remove_file(path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(StreamingError::Sink(format!(
+                "Failed to remove {}: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+}
+
+#[async_trait]
+impl Sink<String> for TransactionalFileSink {
+    async fn write(&mut self, item: String) -> Result<()> {
+        self.init_writer().await?;
+
+        let writer = self
+            .writer
+            .as_mut()
+            .ok_or_else(|| StreamingError::Sink("No in-progress file is open".to_string()))?;
+        writer
+            .write_all(item.as_bytes())
+            .await
+            .map_err(|e| StreamingError::Sink(format!("File write error: {}", e)))?;
+        writer
+            .write_all(b"\n")
+            .await
+            .map_err(|e| StreamingError::Sink(format!("File write error: {}", e)))?;
+
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        if let Some(writer) = &mut self.writer {
+            writer
+                .flush()
+                .await
+                .map_err(|e| StreamingError::Sink(format!("File flush error: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    /// Close the sink
+    ///
+    /// Lines written since the last barrier stay uncommitted until a later
+    /// checkpoint covers them.
+    async fn close(&mut self) -> Result<()> {
+        self.finish_writer().await
+    }
+}
+
+#[async_trait]
+impl TwoPhaseCommitSink for TransactionalFileSink {
+    async fn pre_commit(&mut self, checkpoint_id: CheckpointId) -> Result<()> {
+        self.finish_writer().await?;
+
+        let in_progress = self.in_progress_path();
+        match fs::rename(&in_progress, self.pending_path(checkpoint_id)).await {
+            Ok(()) => {}
+            // Nothing written since the previous barrier
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => {
+                return Err(StreamingError::Sink(format!(
+                    "Failed to stage {}: {}",
+                    in_progress.display(),
+                    e
+                )))
+            }
+        }
+
+        debug!("Pre-committed {} for checkpoint {}", self.prefix, checkpoint_id);
+        Ok(())
+    }
+
+    async fn commit(&mut self, checkpoint_id: CheckpointId) -> Result<()> {
+        for (id, pending) in self.list_files(".pending").await? {
+            if id > checkpoint_id {
+                continue;
+            }
+            Self::rename(&pending, &self.part_path(id)).await?;
+            debug!("Committed {} for checkpoint {}", self.prefix, id);
+        }
+        Ok(())
+    }
+
+    async fn recover(&mut self, last_completed: Option<CheckpointId>) -> Result<()> {
+        self.writer = None;
+        Self::remove(&self.in_progress_path()).await?;
+
+        for (id, pending) in self.list_files(".pending").await? {
+            if last_completed.is_some_and(|last| id <= last) {
+                Self::rename(&pending, &self.part_path(id)).await?;
+                debug!("Committed {} for checkpoint {} on recovery", self.prefix, id);
+            } else {
+                Self::remove(&pending).await?;
+                debug!("Discarded {} for checkpoint {} on recovery", self.prefix, id);
+            }
+        }
+        Ok(())
+    }
+}
+
 /// JSON lines file sink
 pub struct JsonLinesSink<T> {
     inner: FileSink,
@@ -307,4 +542,33 @@ mod tests {
 
         sink.close().await.unwrap();
     }
+
+    #[tokio::test]
+    async fn test_transactional_file_sink() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut sink = TransactionalFileSink::new(dir.path().to_path_buf(), "out")
+            .await
+            .unwrap();
+
+        sink.write("line1".to_string()).await.unwrap();
+        sink.write("line2".to_string()).await.unwrap();
+        sink.pre_commit(1).await.unwrap();
+        sink.write("line3".to_string()).await.unwrap();
+
+        // Staged output is invisible until the checkpoint completes
+        assert!(sink.committed_files().await.unwrap().is_empty());
+
+        sink.commit(1).await.unwrap();
+        sink.pre_commit(2).await.unwrap();
+        sink.commit(2).await.unwrap();
+
+        // An empty transaction commits nothing
+        sink.pre_commit(3).await.unwrap();
+        sink.commit(3).await.unwrap();
+
+        let files = sink.committed_files().await.unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(fs::read_to_string(&files[0]).await.unwrap(), "line1\nline2\n");
+        assert_eq!(fs::read_to_string(&files[1]).await.unwrap(), "line3\n");
+    }
 }
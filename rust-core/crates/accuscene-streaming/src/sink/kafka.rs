@@ -0,0 +1,191 @@
+//! Kafka sink with bounded in-flight deliveries.
+
+use crate::backpressure::{BackpressureController, BackpressurePermit};
+use crate::error::{Result, StreamingError};
+use crate::kafka::{KafkaCodec, KafkaConfig};
+use crate::sink::Sink;
+use async_trait::async_trait;
+use futures::channel::oneshot::Canceled;
+use futures::FutureExt;
+use rdkafka::error::{KafkaError, RDKafkaErrorCode};
+use rdkafka::producer::{DeliveryFuture, FutureProducer, FutureRecord};
+use rdkafka::producer::future_producer::OwnedDeliveryResult;
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// Default limit of unconfirmed records
+const DEFAULT_MAX_IN_FLIGHT: usize = 10_000;
+
+/// Extracts the record key of an item
+pub type KeyFn<T> = Arc<dyn Fn(&T) -> Option<String> + Send + Sync>;
+
+type InFlight = VecDeque<(DeliveryFuture, Option<BackpressurePermit>)>;
+
+/// Kafka sink producing items to a topic
+///
+/// Deliveries are confirmed asynchronously, with at most `max_in_flight`
+/// records unconfirmed at a time. With a [`BackpressureController`], every
+/// unconfirmed record holds a permit and writes wait for confirmations while
+/// the controller is above its high watermark, so a slow cluster pushes back
+/// on the stream. Flushing waits for all deliveries; flushing before each
+/// checkpoint completes gives at-least-once output.
+pub struct KafkaSink<T, C> {
+    producer: FutureProducer,
+    topic: String,
+    codec: C,
+    key_fn: Option<KeyFn<T>>,
+    max_in_flight: usize,
+    in_flight: InFlight,
+    backpressure: Option<BackpressureController>,
+    _phantom: PhantomData<fn(T)>,
+}
+
+impl<T, C> KafkaSink<T, C>
+where
+    T: Send + 'static,
+    C: KafkaCodec<T>,
+{
+    /// Create a sink producing to a topic
+    pub fn new(config: &KafkaConfig, topic: impl Into<String>, codec: C) -> Result<Self> {
+        let producer: FutureProducer = config
+            .client_config()
+            .create()
+            .map_err(|e| sink_error("Failed to create Kafka producer", e))?;
+
+        Ok(Self {
+            producer,
+            topic: topic.into(),
+            codec,
+            key_fn: None,
+            max_in_flight: DEFAULT_MAX_IN_FLIGHT,
+            in_flight: VecDeque::new(),
+            backpressure: None,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Key records, selecting their partition
+    pub fn with_key_fn(mut self, key_fn: KeyFn<T>) -> Self {
+        self.key_fn = Some(key_fn);
+        self
+    }
+
+    /// Set the limit of unconfirmed records
+    pub fn with_max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = max_in_flight.max(1);
+        self
+    }
+
+    /// Hold a backpressure permit for every unconfirmed record
+    pub fn with_backpressure(mut self, controller: BackpressureController) -> Self {
+        self.backpressure = Some(controller);
+        self
+    }
+
+    /// Get the number of unconfirmed records
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Drop confirmed deliveries from the front of the queue
+    fn reap_delivered(in_flight: &mut InFlight) -> Result<()> {
+        while let Some((delivery, _)) = in_flight.front_mut() {
+            let Some(result) = delivery.now_or_never() else {
+                break;
+            };
+            in_flight.pop_front();
+            check_delivery(result)?;
+        }
+        Ok(())
+    }
+
+    /// Wait for the oldest delivery
+    async fn await_oldest(in_flight: &mut InFlight) -> Result<()> {
+        match in_flight.pop_front() {
+            Some((delivery, _permit)) => check_delivery(delivery.await),
+            None => Ok(()),
+        }
+    }
+
+    fn must_wait(&self) -> bool {
+        if self.in_flight.is_empty() {
+            return false;
+        }
+        self.in_flight.len() >= self.max_in_flight
+            || self
+                .backpressure
+                .as_ref()
+                .is_some_and(|backpressure| backpressure.should_apply_backpressure())
+    }
+}
+
+#[async_trait]
+impl<T, C> Sink<T> for KafkaSink<T, C>
+where
+    T: Send + 'static,
+    C: KafkaCodec<T>,
+{
+    async fn write(&mut self, item: T) -> Result<()> {
+        Self::reap_delivered(&mut self.in_flight)?;
+        while self.must_wait() {
+            Self::await_oldest(&mut self.in_flight).await?;
+        }
+
+        let permit = match &self.backpressure {
+            Some(backpressure) => Some(backpressure.acquire().await?),
+            None => None,
+        };
+
+        let payload = self.codec.encode(&item)?;
+        let key = self.key_fn.as_ref().and_then(|key_fn| key_fn(&item));
+
+        let mut record: FutureRecord<'_, str, [u8]> =
+            FutureRecord::to(&self.topic).payload(payload.as_slice());
+        if let Some(key) = &key {
+            record = record.key(key.as_str());
+        }
+
+        let delivery = loop {
+            match self.producer.send_result(record) {
+                Ok(delivery) => break delivery,
+                Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), returned))
+                    if !self.in_flight.is_empty() =>
+                {
+                    // The producer queue is full, wait for a confirmation
+                    record = returned;
+                    Self::await_oldest(&mut self.in_flight).await?;
+                }
+                Err((e, _)) => return Err(sink_error("Failed to produce Kafka record", e)),
+            }
+        };
+
+        self.in_flight.push_back((delivery, permit));
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        while !self.in_flight.is_empty() {
+            Self::await_oldest(&mut self.in_flight).await?;
+        }
+        Ok(())
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        self.flush().await
+    }
+}
+
+fn check_delivery(result: std::result::Result<OwnedDeliveryResult, Canceled>) -> Result<()> {
+    match result {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err((e, _))) => Err(sink_error("Kafka delivery failed", e)),
+        Err(Canceled) => Err(StreamingError::Sink(
+            "Kafka producer dropped a delivery".to_string(),
+        )),
+    }
+}
+
+fn sink_error(context: &str, error: KafkaError) -> StreamingError {
+    StreamingError::Sink(format!("{}: {}", context, error))
+}
@@ -2,9 +2,12 @@
 
 pub mod channel;
 pub mod file;
+#[cfg(feature = "kafka")]
+pub mod kafka;
 pub mod parquet;
 pub mod websocket;
 
+use crate::checkpoint::CheckpointId;
 use crate::error::Result;
 use async_trait::async_trait;
 
@@ -21,7 +24,34 @@ pub trait Sink<T>: Send + 'static {
     async fn close(&mut self) -> Result<()>;
 }
 
+/// Trait for sinks taking part in checkpoints through a two-phase commit
+///
+/// Output written between two checkpoint barriers forms one transaction.
+/// The transaction is staged when the barrier reaches the sink and only made
+/// visible once the checkpoint is stored, so output replayed after a failure
+/// is never emitted twice. Sinks are registered with a
+/// [`CheckpointCoordinator`](crate::checkpoint::CheckpointCoordinator), which
+/// drives these calls.
+#[async_trait]
+pub trait TwoPhaseCommitSink: Send + 'static {
+    /// Stage everything written since the previous barrier as the
+    /// transaction of a checkpoint
+    async fn pre_commit(&mut self, checkpoint_id: CheckpointId) -> Result<()>;
+
+    /// Make visible every staged transaction up to a completed checkpoint
+    async fn commit(&mut self, checkpoint_id: CheckpointId) -> Result<()>;
+
+    /// Restore the sink after a restart
+    ///
+    /// Staged transactions up to `last_completed` are committed; later
+    /// transactions and unstaged output are discarded, as the sources replay
+    /// them from the checkpoint.
+    async fn recover(&mut self, last_completed: Option<CheckpointId>) -> Result<()>;
+}
+
 pub use self::channel::ChannelSink;
-pub use self::file::FileSink;
+pub use self::file::{FileSink, TransactionalFileSink};
+#[cfg(feature = "kafka")]
+pub use self::kafka::KafkaSink;
 pub use self::parquet::ParquetSink;
 pub use self::websocket::WebSocketSink;
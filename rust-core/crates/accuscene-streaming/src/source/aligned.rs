@@ -0,0 +1,379 @@
+//! Event-time aligned merging of partitioned sources.
+//!
+//! Replaying historical files one after another, or reading them at different
+//! rates, lets event time jump to the end of the fastest file and closes
+//! windows before the slower files catch up. [`AlignedSource`] reads every
+//! partition in its own task and emits the buffered record with the smallest
+//! event timestamp, waiting for partitions that have not produced yet unless
+//! they are idle or the record lies within the configured drift of the
+//! combined watermark.
+
+use crate::config::WatermarkConfig;
+use crate::error::{Result, StreamingError};
+use crate::operators::window::TimestampExtractor;
+use crate::source::Source;
+use crate::stream::DataStream;
+use crate::watermark::{PartitionedWatermarkGenerator, Timestamp, Watermark};
+use async_trait::async_trait;
+use futures::future::select_all;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, error::TryRecvError};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+/// A partition of an aligned source
+struct Partition<S: Source> {
+    id: String,
+    source: Option<S>,
+    receiver: Option<mpsc::Receiver<Result<S::Item>>>,
+    head: Option<(Timestamp, S::Item)>,
+    finished: bool,
+}
+
+/// Source merging partitions in event-time order
+///
+/// Partitions that never finish and never produce, such as an empty tailed
+/// file, hold the source back until they are detected as idle, so idle
+/// detection should stay enabled when mixing live and historical partitions.
+pub struct AlignedSource<S: Source> {
+    partitions: Vec<Partition<S>>,
+    timestamp_extractor: Box<dyn TimestampExtractor<S::Item>>,
+    generator: PartitionedWatermarkGenerator,
+    buffer_size: usize,
+    shutdown: CancellationToken,
+    tasks: Vec<JoinHandle<()>>,
+    running: bool,
+}
+
+impl<S> AlignedSource<S>
+where
+    S: Source + 'static,
+    S::Item: Send + 'static,
+{
+    /// Create a new aligned source with strict alignment and no idle detection
+    pub fn new(timestamp_extractor: impl TimestampExtractor<S::Item> + 'static) -> Self {
+        Self {
+            partitions: Vec::new(),
+            timestamp_extractor: Box::new(timestamp_extractor),
+            generator: PartitionedWatermarkGenerator::new(Duration::ZERO),
+            buffer_size: 64,
+            shutdown: CancellationToken::new(),
+            tasks: Vec::new(),
+            running: false,
+        }
+    }
+
+    /// Configure out-of-orderness, idle detection and drift from a watermark configuration
+    pub fn with_config(mut self, config: &WatermarkConfig) -> Self {
+        self.generator = PartitionedWatermarkGenerator::from_config(config);
+        self
+    }
+
+    /// Set the watermark generator
+    pub fn with_generator(mut self, generator: PartitionedWatermarkGenerator) -> Self {
+        self.generator = generator;
+        self
+    }
+
+    /// Set how many records each partition reads ahead
+    pub fn with_buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = size.max(1);
+        self
+    }
+
+    /// Add a partition
+    pub fn with_partition(mut self, id: impl Into<String>, source: S) -> Self {
+        self.partitions.push(Partition {
+            id: id.into(),
+            source: Some(source),
+            receiver: None,
+            head: None,
+            finished: false,
+        });
+        self
+    }
+
+    /// Get the combined watermark of the emitted records
+    pub fn current_watermark(&self) -> Watermark {
+        self.generator.current_watermark()
+    }
+
+    /// Get the watermark generator
+    pub fn generator(&self) -> &PartitionedWatermarkGenerator {
+        &self.generator
+    }
+
+    /// Handle a message from a partition, `None` meaning the partition is exhausted
+    fn receive(&mut self, index: usize, message: Option<Result<S::Item>>) -> Result<()> {
+        let partition = &mut self.partitions[index];
+        match message {
+            Some(Ok(item)) => {
+                let timestamp = self.timestamp_extractor.extract_timestamp(&item);
+                self.generator.on_event(&partition.id, timestamp);
+                partition.head = Some((timestamp, item));
+            }
+            Some(Err(e)) => {
+                return Err(StreamingError::Source(format!(
+                    "Partition {} failed: {}",
+                    partition.id, e
+                )));
+            }
+            None => {
+                partition.finished = true;
+                partition.receiver = None;
+                self.generator.finish_partition(&partition.id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Buffer records that are already available without waiting
+    fn poll_ready(&mut self) -> Result<()> {
+        for index in 0..self.partitions.len() {
+            let partition = &mut self.partitions[index];
+            if partition.finished || partition.head.is_some() {
+                continue;
+            }
+            let Some(receiver) = partition.receiver.as_mut() else {
+                continue;
+            };
+            match receiver.try_recv() {
+                Ok(message) => self.receive(index, Some(message))?,
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => self.receive(index, None)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Find the partition whose buffered record can be emitted next
+    fn next_ready(&self) -> Option<usize> {
+        let (index, timestamp) = self
+            .partitions
+            .iter()
+            .enumerate()
+            .filter_map(|(index, partition)| {
+                partition.head.as_ref().map(|(timestamp, _)| (index, *timestamp))
+            })
+            .min_by_key(|(_, timestamp)| *timestamp)?;
+
+        let blocked = self.partitions.iter().any(|partition| {
+            !partition.finished
+                && partition.head.is_none()
+                && !self.generator.is_idle(&partition.id)
+        });
+
+        (!blocked || self.generator.within_drift(timestamp)).then_some(index)
+    }
+
+    /// Wait until a partition without a buffered record produces or an idle timeout passes
+    async fn wait(&mut self) -> Result<()> {
+        let (indices, receives): (Vec<_>, Vec<_>) = self
+            .partitions
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, partition)| !partition.finished && partition.head.is_none())
+            .filter_map(|(index, partition)| {
+                partition
+                    .receiver
+                    .as_mut()
+                    .map(|receiver| (index, Box::pin(receiver.recv())))
+            })
+            .unzip();
+
+        if receives.is_empty() {
+            return Ok(());
+        }
+
+        let received = match self.generator.next_idle_deadline() {
+            Some(deadline) => {
+                let deadline = tokio::time::Instant::from_std(deadline);
+                tokio::time::timeout_at(deadline, select_all(receives))
+                    .await
+                    .ok()
+                    .map(|(message, position, _)| (indices[position], message))
+            }
+            None => {
+                let (message, position, _) = select_all(receives).await;
+                Some((indices[position], message))
+            }
+        };
+
+        match received {
+            Some((index, message)) => self.receive(index, message),
+            None => {
+                self.generator.check_idle(Instant::now());
+                Ok(())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<S> DataStream for AlignedSource<S>
+where
+    S: Source + 'static,
+    S::Item: Send + 'static,
+{
+    type Item = S::Item;
+
+    async fn next(&mut self) -> Result<Option<Self::Item>> {
+        loop {
+            if !self.running {
+                return Ok(None);
+            }
+
+            self.poll_ready()?;
+            if let Some(index) = self.next_ready() {
+                let head = self.partitions[index].head.take();
+                return Ok(head.map(|(_, item)| item));
+            }
+            if self.partitions.iter().all(|partition| partition.finished) {
+                return Ok(None);
+            }
+
+            self.wait().await?;
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        !self.running
+            || self
+                .partitions
+                .iter()
+                .all(|partition| partition.finished && partition.head.is_none())
+    }
+}
+
+#[async_trait]
+impl<S> Source for AlignedSource<S>
+where
+    S: Source + 'static,
+    S::Item: Send + 'static,
+{
+    async fn start(&mut self) -> Result<()> {
+        if self.running {
+            return Ok(());
+        }
+
+        for partition in self.partitions.iter_mut() {
+            let Some(mut source) = partition.source.take() else {
+                continue;
+            };
+            source.start().await?;
+            self.generator.add_partition(partition.id.clone());
+
+            let (tx, rx) = mpsc::channel(self.buffer_size);
+            partition.receiver = Some(rx);
+
+            let id = partition.id.clone();
+            let shutdown = self.shutdown.clone();
+            self.tasks.push(tokio::spawn(async move {
+                loop {
+                    let item = tokio::select! {
+                        _ = shutdown.cancelled() => break,
+                        item = source.next() => item,
+                    };
+                    match item {
+                        Ok(Some(item)) => {
+                            if tx.send(Ok(item)).await.is_err() {
+                                break;
+                            }
+                        }
+                        Ok(None) => break,
+                        Err(e) => {
+                            let _ = tx.send(Err(e)).await;
+                            break;
+                        }
+                    }
+                }
+                if let Err(e) = source.stop().await {
+                    warn!("Failed to stop partition {}: {}", id, e);
+                }
+            }));
+        }
+
+        self.running = true;
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<()> {
+        self.running = false;
+        self.shutdown.cancel();
+        for partition in self.partitions.iter_mut() {
+            partition.receiver = None;
+        }
+        for task in self.tasks.drain(..) {
+            task.await.map_err(|e| {
+                StreamingError::Source(format!("Partition task failed: {}", e))
+            })?;
+        }
+        Ok(())
+    }
+
+    fn is_running(&self) -> bool {
+        self.running
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::{ChannelSource, IteratorSource};
+
+    fn millis(item: &i64) -> Timestamp {
+        Timestamp::from_millis(*item)
+    }
+
+    async fn drain<S: Source>(source: &mut S) -> Vec<S::Item> {
+        let mut items = Vec::new();
+        while let Some(item) = source.next().await.unwrap() {
+            items.push(item);
+        }
+        items
+    }
+
+    #[tokio::test]
+    async fn test_merges_partitions_in_event_time_order() {
+        let mut source = AlignedSource::new(millis)
+            .with_partition("a", IteratorSource::new(vec![1000, 3000, 5000].into_iter()))
+            .with_partition("b", IteratorSource::new(vec![2000, 4000, 6000, 7000].into_iter()));
+
+        source.start().await.unwrap();
+        let items = drain(&mut source).await;
+        source.stop().await.unwrap();
+
+        assert_eq!(items, vec![1000, 2000, 3000, 4000, 5000, 6000, 7000]);
+        assert_eq!(source.current_watermark(), Watermark::max());
+    }
+
+    #[tokio::test]
+    async fn test_idle_partition_stops_blocking() {
+        let (tx, quiet) = ChannelSource::create();
+        let (busy_tx, busy) = ChannelSource::create();
+        for ts in [1000, 2000, 3000] {
+            busy_tx.send(ts).unwrap();
+        }
+        drop(busy_tx);
+
+        let config = WatermarkConfig::default().with_idle_timeout(Duration::from_millis(50));
+        let mut source = AlignedSource::new(millis)
+            .with_config(&config)
+            .with_partition("quiet", quiet)
+            .with_partition("busy", busy);
+
+        source.start().await.unwrap();
+        let started = std::time::Instant::now();
+        assert_eq!(source.next().await.unwrap(), Some(1000));
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        assert!(source.generator().is_idle("quiet"));
+
+        // The quiet partition resumes once it produces again
+        tx.send(2500).unwrap();
+        drop(tx);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(drain(&mut source).await, vec![2000, 2500, 3000]);
+        source.stop().await.unwrap();
+    }
+}
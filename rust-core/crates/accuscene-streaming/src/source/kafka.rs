@@ -0,0 +1,326 @@
+//! Kafka source with consumer-group offsets tied to checkpoints.
+
+use crate::backpressure::BackpressureController;
+use crate::checkpoint::{Checkpoint, CheckpointId};
+use crate::error::{Result, StreamingError};
+use crate::kafka::{KafkaCodec, KafkaConfig};
+use crate::source::Source;
+use crate::stream::DataStream;
+use async_trait::async_trait;
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
+use rdkafka::error::KafkaError;
+use rdkafka::{Message, Offset, TopicPartitionList};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::marker::PhantomData;
+use std::time::Duration;
+use tracing::debug;
+
+/// How often a paused source checks whether backpressure has eased
+const BACKPRESSURE_POLL: Duration = Duration::from_millis(10);
+
+/// Position of a source in one topic partition
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartitionOffset {
+    /// Topic name
+    pub topic: String,
+    /// Partition number
+    pub partition: i32,
+    /// Offset of the next record to read
+    pub offset: i64,
+}
+
+/// Kafka source reading the topics of a consumer group
+///
+/// Offsets are never committed automatically. The positions read so far are
+/// recorded in a checkpoint by [`KafkaSource::snapshot_offsets`] and
+/// committed to the consumer group by [`KafkaSource::commit_offsets`] once the
+/// checkpoint completes. After a failure, [`KafkaSource::restore_offsets`]
+/// rewinds the group to the restored checkpoint, so the records after it are
+/// read again.
+///
+/// With a [`BackpressureController`], the source pauses its partitions while
+/// the controller is above its high watermark and resumes them once it drops
+/// below the low watermark.
+pub struct KafkaSource<T, C> {
+    consumer: StreamConsumer,
+    group_id: String,
+    topics: Vec<String>,
+    codec: C,
+    running: bool,
+    positions: HashMap<(String, i32), i64>,
+    snapshots: BTreeMap<CheckpointId, Vec<PartitionOffset>>,
+    backpressure: Option<BackpressureController>,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T, C> KafkaSource<T, C>
+where
+    T: Send + 'static,
+    C: KafkaCodec<T>,
+{
+    /// Create a source for a consumer group
+    ///
+    /// Must be called within a Tokio runtime.
+    pub fn new(
+        config: &KafkaConfig,
+        group_id: impl Into<String>,
+        topics: Vec<String>,
+        codec: C,
+    ) -> Result<Self> {
+        let group_id = group_id.into();
+
+        let mut client = config.client_config();
+        if client.get("auto.offset.reset").is_none() {
+            client.set("auto.offset.reset", "earliest");
+        }
+        let consumer: StreamConsumer = client
+            .set("group.id", &group_id)
+            .set("enable.auto.commit", "false")
+            .set("enable.auto.offset.store", "false")
+            .create()
+            .map_err(|e| source_error("Failed to create Kafka consumer", e))?;
+
+        Ok(Self {
+            consumer,
+            group_id,
+            topics,
+            codec,
+            running: false,
+            positions: HashMap::new(),
+            snapshots: BTreeMap::new(),
+            backpressure: None,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Pause consumption while a backpressure controller is under high load
+    pub fn with_backpressure(mut self, controller: BackpressureController) -> Self {
+        self.backpressure = Some(controller);
+        self
+    }
+
+    /// Get the positions read so far
+    pub fn positions(&self) -> Vec<PartitionOffset> {
+        let mut positions: Vec<_> = self
+            .positions
+            .iter()
+            .map(|((topic, partition), offset)| PartitionOffset {
+                topic: topic.clone(),
+                partition: *partition,
+                offset: *offset,
+            })
+            .collect();
+        positions.sort_by(|a, b| (&a.topic, a.partition).cmp(&(&b.topic, b.partition)));
+        positions
+    }
+
+    /// Record the positions read so far in a checkpoint
+    pub fn snapshot_offsets(&mut self, checkpoint: &mut Checkpoint) -> Result<()> {
+        let positions = self.positions();
+        checkpoint.add_state(self.state_key(), serde_json::to_vec(&positions)?);
+        self.snapshots.insert(checkpoint.metadata.id, positions);
+        Ok(())
+    }
+
+    /// Commit the positions of a completed checkpoint to the consumer group
+    ///
+    /// Snapshots of earlier checkpoints that never completed are superseded
+    /// and dropped.
+    pub fn commit_offsets(&mut self, checkpoint_id: CheckpointId) -> Result<()> {
+        let later = self.snapshots.split_off(&checkpoint_id.saturating_add(1));
+        let covered = std::mem::replace(&mut self.snapshots, later);
+
+        match covered.into_values().next_back() {
+            Some(positions) => {
+                self.commit(&positions, CommitMode::Async)?;
+                debug!(
+                    "Committed offsets of group {} for checkpoint {}",
+                    self.group_id, checkpoint_id
+                );
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Rewind the consumer group to the positions recorded in a checkpoint
+    ///
+    /// Call this before starting the source.
+    pub fn restore_offsets(&mut self, checkpoint: &Checkpoint) -> Result<()> {
+        let Some(data) = checkpoint.get_state(&self.state_key()) else {
+            return Ok(());
+        };
+        let positions: Vec<PartitionOffset> = serde_json::from_slice(data)?;
+
+        self.commit(&positions, CommitMode::Sync)?;
+        self.positions = positions
+            .into_iter()
+            .map(|position| ((position.topic, position.partition), position.offset))
+            .collect();
+        self.snapshots.clear();
+
+        debug!(
+            "Restored offsets of group {} from checkpoint {}",
+            self.group_id, checkpoint.metadata.id
+        );
+        Ok(())
+    }
+
+    fn state_key(&self) -> String {
+        format!("kafka-source/{}", self.group_id)
+    }
+
+    fn commit(&self, positions: &[PartitionOffset], mode: CommitMode) -> Result<()> {
+        if positions.is_empty() {
+            return Ok(());
+        }
+
+        let mut offsets = TopicPartitionList::new();
+        for position in positions {
+            offsets
+                .add_partition_offset(
+                    &position.topic,
+                    position.partition,
+                    Offset::Offset(position.offset),
+                )
+                .map_err(|e| source_error("Invalid Kafka offset", e))?;
+        }
+
+        self.consumer
+            .commit(&offsets, mode)
+            .map_err(|e| source_error("Failed to commit Kafka offsets", e))
+    }
+
+    /// Wait with paused partitions until backpressure has eased
+    async fn wait_for_capacity(&self) -> Result<()> {
+        let Some(backpressure) = &self.backpressure else {
+            return Ok(());
+        };
+        if !backpressure.should_apply_backpressure() {
+            return Ok(());
+        }
+
+        let assignment = self
+            .consumer
+            .assignment()
+            .map_err(|e| source_error("Failed to read Kafka assignment", e))?;
+        self.consumer
+            .pause(&assignment)
+            .map_err(|e| source_error("Failed to pause Kafka partitions", e))?;
+        debug!("Kafka source for group {} paused by backpressure", self.group_id);
+
+        while !backpressure.is_low_water() {
+            tokio::time::sleep(BACKPRESSURE_POLL).await;
+        }
+
+        self.consumer
+            .resume(&assignment)
+            .map_err(|e| source_error("Failed to resume Kafka partitions", e))?;
+        debug!("Kafka source for group {} resumed", self.group_id);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<T, C> DataStream for KafkaSource<T, C>
+where
+    T: Send + 'static,
+    C: KafkaCodec<T>,
+{
+    type Item = T;
+
+    /// Read the next item
+    ///
+    /// A record that cannot be decoded is reported as an error and skipped
+    /// by the next call. Records without a payload are skipped.
+    async fn next(&mut self) -> Result<Option<Self::Item>> {
+        if !self.running {
+            return Ok(None);
+        }
+
+        self.wait_for_capacity().await?;
+
+        loop {
+            let message = self
+                .consumer
+                .recv()
+                .await
+                .map_err(|e| source_error("Kafka receive error", e))?;
+
+            self.positions.insert(
+                (message.topic().to_string(), message.partition()),
+                message.offset() + 1,
+            );
+
+            if let Some(payload) = message.payload() {
+                return self.codec.decode(payload).map(Some);
+            }
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        !self.running
+    }
+}
+
+#[async_trait]
+impl<T, C> Source for KafkaSource<T, C>
+where
+    T: Send + 'static,
+    C: KafkaCodec<T>,
+{
+    async fn start(&mut self) -> Result<()> {
+        let topics: Vec<&str> = self.topics.iter().map(String::as_str).collect();
+        self.consumer
+            .subscribe(&topics)
+            .map_err(|e| source_error("Failed to subscribe to Kafka topics", e))?;
+
+        self.running = true;
+        Ok(())
+    }
+
+    async fn stop(&mut self) -> Result<()> {
+        self.consumer.unsubscribe();
+        self.running = false;
+        Ok(())
+    }
+
+    fn is_running(&self) -> bool {
+        self.running
+    }
+}
+
+fn source_error(context: &str, error: KafkaError) -> StreamingError {
+    StreamingError::Source(format!("{}: {}", context, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kafka::JsonCodec;
+
+    #[tokio::test]
+    async fn test_snapshot_offsets() {
+        let config = KafkaConfig::new("localhost:9092");
+        let mut source: KafkaSource<serde_json::Value, _> =
+            KafkaSource::new(&config, "scene-export", vec!["scenes".to_string()], JsonCodec)
+                .unwrap();
+
+        source.positions.insert(("scenes".to_string(), 1), 17);
+        source.positions.insert(("scenes".to_string(), 0), 42);
+
+        let mut checkpoint = Checkpoint::new(1);
+        source.snapshot_offsets(&mut checkpoint).unwrap();
+
+        let state = checkpoint.get_state("kafka-source/scene-export").unwrap();
+        let recorded: Vec<PartitionOffset> = serde_json::from_slice(state).unwrap();
+        assert_eq!(recorded, source.positions());
+        assert_eq!(recorded[0].partition, 0);
+        assert_eq!(recorded[0].offset, 42);
+
+        // Nothing to commit for checkpoints before the snapshot
+        source.commit_offsets(0).unwrap();
+        assert_eq!(source.snapshots.len(), 1);
+    }
+}
@@ -1,8 +1,11 @@
 //! Source connectors for streaming data.
 
+pub mod aligned;
 pub mod channel;
 pub mod file;
 pub mod iterator;
+#[cfg(feature = "kafka")]
+pub mod kafka;
 pub mod websocket;
 
 use crate::error::Result;
@@ -22,7 +25,10 @@ pub trait Source: DataStream {
     fn is_running(&self) -> bool;
 }
 
+pub use self::aligned::AlignedSource;
 pub use self::channel::ChannelSource;
 pub use self::file::FileSource;
 pub use self::iterator::IteratorSource;
+#[cfg(feature = "kafka")]
+pub use self::kafka::KafkaSource;
 pub use self::websocket::WebSocketSource;
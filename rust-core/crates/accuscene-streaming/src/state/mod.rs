@@ -1,4 +1,14 @@
 //! State management for stateful stream processing.
+//!
+//! State lives in a [`StateBackend`]: [`MemoryStateBackend`] keeps it in RAM,
+//! and `RocksDbStateBackend` (`rocksdb-state` feature) keeps it on disk for
+//! keyed state larger than memory, with incremental snapshots and state TTL.
+
+#[cfg(feature = "rocksdb-state")]
+pub mod rocksdb;
+
+#[cfg(feature = "rocksdb-state")]
+pub use self::rocksdb::{RocksDbStateBackend, RocksDbStateConfig};
 
 use crate::error::{Result, StreamingError};
 use dashmap::DashMap;
@@ -28,6 +38,57 @@ pub trait StateBackend: Send + Sync {
 
     /// Restore state from a snapshot
     async fn restore(&self, snapshot: HashMap<Vec<u8>, Vec<u8>>) -> Result<()>;
+
+    /// Create a snapshot of the changes since the previous incremental snapshot
+    ///
+    /// Backends that do not track changes return a full snapshot.
+    async fn incremental_snapshot(&self) -> Result<StateDelta> {
+        Ok(StateDelta::full(self.snapshot().await?))
+    }
+
+    /// Apply an incremental snapshot on top of the current state
+    async fn apply_delta(&self, delta: StateDelta) -> Result<()> {
+        if delta.full {
+            self.clear().await?;
+        }
+        for (key, value) in delta.puts {
+            self.put(key, value).await?;
+        }
+        for key in delta.deletes {
+            self.delete(&key).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Changes to a state backend between two incremental snapshots
+///
+/// A full delta replaces the whole state. Restoring a checkpoint applies the
+/// last full delta and every later delta in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateDelta {
+    /// Whether the delta holds the whole state
+    pub full: bool,
+    /// Entries written since the previous snapshot
+    pub puts: HashMap<Vec<u8>, Vec<u8>>,
+    /// Keys deleted since the previous snapshot
+    pub deletes: Vec<Vec<u8>>,
+}
+
+impl StateDelta {
+    /// Create a delta holding the whole state
+    pub fn full(state: HashMap<Vec<u8>, Vec<u8>>) -> Self {
+        Self {
+            full: true,
+            puts: state,
+            deletes: Vec::new(),
+        }
+    }
+
+    /// Check if the delta changes nothing
+    pub fn is_empty(&self) -> bool {
+        !self.full && self.puts.is_empty() && self.deletes.is_empty()
+    }
 }
 
 /// In-memory state backend using DashMap for concurrent access
@@ -243,7 +304,7 @@ impl<K, MK, MV> MapState<K, MK, MV>
 where
     K: Serialize + for<'de> Deserialize<'de>,
     MK: Serialize + for<'de> Deserialize<'de> + Hash + Eq,
-    MV: Serialize + for<'de> Deserialize<'de>,
+    MV: Serialize + for<'de> Deserialize<'de> + Clone,
 {
     /// Create a new map state
     pub fn new(backend: Arc<dyn StateBackend>, namespace: impl Into<String>) -> Self {
@@ -415,7 +476,7 @@ impl StateContext {
     where
         K: Serialize + for<'de> Deserialize<'de>,
         MK: Serialize + for<'de> Deserialize<'de> + Hash + Eq,
-        MV: Serialize + for<'de> Deserialize<'de>,
+        MV: Serialize + for<'de> Deserialize<'de> + Clone,
     {
         MapState::new(self.backend.clone(), &descriptor.name)
     }
@@ -0,0 +1,374 @@
+//! RocksDB state backend for keyed state larger than memory.
+
+use super::{StateBackend, StateDelta};
+use crate::error::{Result, StreamingError};
+use parking_lot::Mutex;
+use rocksdb::compaction_filter::Decision;
+use rocksdb::{IteratorMode, Options, WriteBatch, DB};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Length of the expiry header stored before every value
+const HEADER_LEN: usize = 8;
+
+/// Default memtable size
+const DEFAULT_WRITE_BUFFER_SIZE: usize = 64 * 1024 * 1024;
+
+/// RocksDB state backend configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RocksDbStateConfig {
+    /// Database directory
+    pub path: PathBuf,
+    /// Time after its last write at which an entry expires
+    pub ttl: Option<Duration>,
+    /// Memtable size in bytes
+    pub write_buffer_size: usize,
+}
+
+impl RocksDbStateConfig {
+    /// Create a configuration for a database directory
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            ttl: None,
+            write_buffer_size: DEFAULT_WRITE_BUFFER_SIZE,
+        }
+    }
+
+    /// Expire entries a fixed time after their last write
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Set the memtable size
+    pub fn with_write_buffer_size(mut self, size: usize) -> Self {
+        self.write_buffer_size = size;
+        self
+    }
+}
+
+/// Keys changed since the last incremental snapshot
+#[derive(Debug, Default)]
+struct Changes {
+    keys: HashSet<Vec<u8>>,
+    full: bool,
+}
+
+/// State backend storing keyed state in RocksDB
+///
+/// Every value is stored with its expiry time. Expired entries are hidden
+/// from reads and snapshots right away, and removed from disk by compaction
+/// or [`RocksDbStateBackend::purge_expired`].
+///
+/// Incremental snapshots only read the keys written or deleted since the
+/// previous one. The first snapshot after opening, clearing or restoring the
+/// backend is full. Restored entries start a new TTL.
+pub struct RocksDbStateBackend {
+    db: DB,
+    ttl: Option<Duration>,
+    changes: Mutex<Changes>,
+}
+
+impl RocksDbStateBackend {
+    /// Open or create a state database
+    pub fn open(config: RocksDbStateConfig) -> Result<Self> {
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        options.set_write_buffer_size(config.write_buffer_size);
+        options.set_compaction_filter("state-ttl", |_level: u32, _key: &[u8], value: &[u8]| {
+            if is_expired(value, now_millis()) {
+                Decision::Remove
+            } else {
+                Decision::Keep
+            }
+        });
+
+        let db = DB::open(&options, &config.path)
+            .map_err(|e| backend_error("Failed to open RocksDB state", e))?;
+
+        Ok(Self {
+            db,
+            ttl: config.ttl,
+            changes: Mutex::new(Changes {
+                keys: HashSet::new(),
+                full: true,
+            }),
+        })
+    }
+
+    /// Delete expired entries, returning how many were deleted
+    pub fn purge_expired(&self) -> Result<usize> {
+        let now = now_millis();
+        let mut batch = WriteBatch::default();
+        let mut expired = Vec::new();
+
+        for entry in self.db.iterator(IteratorMode::Start) {
+            let (key, value) = entry.map_err(|e| backend_error("Failed to scan state", e))?;
+            if is_expired(&value, now) {
+                batch.delete(&key);
+                expired.push(key.into_vec());
+            }
+        }
+
+        let count = expired.len();
+        if count > 0 {
+            self.db
+                .write(batch)
+                .map_err(|e| backend_error("Failed to purge expired state", e))?;
+            self.changes.lock().keys.extend(expired);
+        }
+        Ok(count)
+    }
+
+    fn encode(&self, value: &[u8]) -> Vec<u8> {
+        let expires_at = match self.ttl {
+            Some(ttl) => now_millis().saturating_add(ttl.as_millis() as u64),
+            None => 0,
+        };
+
+        let mut encoded = Vec::with_capacity(HEADER_LEN + value.len());
+        encoded.extend_from_slice(&expires_at.to_be_bytes());
+        encoded.extend_from_slice(value);
+        encoded
+    }
+
+    /// Read all live entries, as of a consistent point in time
+    fn live_entries(&self) -> Result<HashMap<Vec<u8>, Vec<u8>>> {
+        let now = now_millis();
+        let snapshot = self.db.snapshot();
+        let mut entries = HashMap::new();
+
+        for entry in snapshot.iterator(IteratorMode::Start) {
+            let (key, value) = entry.map_err(|e| backend_error("Failed to scan state", e))?;
+            if let Some(value) = live_value(&value, now) {
+                entries.insert(key.into_vec(), value.to_vec());
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Add deletes of all stored keys to a batch
+    fn delete_all(&self, batch: &mut WriteBatch) -> Result<()> {
+        for entry in self.db.iterator(IteratorMode::Start) {
+            let (key, _) = entry.map_err(|e| backend_error("Failed to scan state", e))?;
+            batch.delete(&key);
+        }
+        Ok(())
+    }
+
+    /// Start a new chain of incremental snapshots
+    fn reset_changes(&self) {
+        let mut changes = self.changes.lock();
+        changes.keys.clear();
+        changes.full = true;
+    }
+}
+
+#[async_trait::async_trait]
+impl StateBackend for RocksDbStateBackend {
+    async fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        let value = self.db.get(key).map_err(|e| backend_error("Failed to read state", e))?;
+        Ok(value.and_then(|value| live_value(&value, now_millis()).map(<[u8]>::to_vec)))
+    }
+
+    async fn put(&self, key: Vec<u8>, value: Vec<u8>) -> Result<()> {
+        self.db
+            .put(&key, self.encode(&value))
+            .map_err(|e| backend_error("Failed to write state", e))?;
+
+        // Mark after writing, so a concurrent snapshot never misses the change
+        self.changes.lock().keys.insert(key);
+        Ok(())
+    }
+
+    async fn delete(&self, key: &[u8]) -> Result<()> {
+        self.db.delete(key).map_err(|e| backend_error("Failed to delete state", e))?;
+
+        self.changes.lock().keys.insert(key.to_vec());
+        Ok(())
+    }
+
+    async fn clear(&self) -> Result<()> {
+        let mut batch = WriteBatch::default();
+        self.delete_all(&mut batch)?;
+        self.db.write(batch).map_err(|e| backend_error("Failed to clear state", e))?;
+
+        self.reset_changes();
+        Ok(())
+    }
+
+    async fn snapshot(&self) -> Result<HashMap<Vec<u8>, Vec<u8>>> {
+        self.live_entries()
+    }
+
+    async fn restore(&self, snapshot: HashMap<Vec<u8>, Vec<u8>>) -> Result<()> {
+        self.apply_delta(StateDelta::full(snapshot)).await
+    }
+
+    async fn incremental_snapshot(&self) -> Result<StateDelta> {
+        let changes = std::mem::take(&mut *self.changes.lock());
+        if changes.full {
+            return Ok(StateDelta::full(self.live_entries()?));
+        }
+
+        let now = now_millis();
+        let snapshot = self.db.snapshot();
+        let mut delta = StateDelta::default();
+
+        for key in changes.keys {
+            let value = snapshot.get(&key).map_err(|e| backend_error("Failed to read state", e))?;
+            match value.as_deref().and_then(|value| live_value(value, now)) {
+                Some(value) => {
+                    delta.puts.insert(key, value.to_vec());
+                }
+                None => delta.deletes.push(key),
+            }
+        }
+        Ok(delta)
+    }
+
+    async fn apply_delta(&self, delta: StateDelta) -> Result<()> {
+        let mut batch = WriteBatch::default();
+        if delta.full {
+            self.delete_all(&mut batch)?;
+        }
+        for (key, value) in &delta.puts {
+            batch.put(key, self.encode(value));
+        }
+        for key in &delta.deletes {
+            batch.delete(key);
+        }
+
+        self.db.write(batch).map_err(|e| backend_error("Failed to restore state", e))?;
+
+        self.reset_changes();
+        Ok(())
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Read the expiry header of a stored value, where 0 means never
+fn expires_at(stored: &[u8]) -> u64 {
+    stored
+        .get(..HEADER_LEN)
+        .and_then(|header| header.try_into().ok())
+        .map(u64::from_be_bytes)
+        .unwrap_or(0)
+}
+
+fn is_expired(stored: &[u8], now: u64) -> bool {
+    let expires_at = expires_at(stored);
+    expires_at != 0 && expires_at <= now
+}
+
+/// Strip the expiry header of a stored value that has not expired
+fn live_value(stored: &[u8], now: u64) -> Option<&[u8]> {
+    if is_expired(stored, now) {
+        return None;
+    }
+    stored.get(HEADER_LEN..)
+}
+
+fn backend_error(context: &str, error: rocksdb::Error) -> StreamingError {
+    StreamingError::StateBackend(format!("{}: {}", context, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{ListState, MapState, MemoryStateBackend, ValueState};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_keyed_state_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = RocksDbStateConfig::new(dir.path());
+
+        {
+            let backend = Arc::new(RocksDbStateBackend::open(config.clone()).unwrap());
+            let speeds: ValueState<String, f64> = ValueState::new(backend.clone(), "speed");
+            let impacts: ListState<String, u32> = ListState::new(backend.clone(), "impacts");
+            let parties: MapState<String, String, u32> = MapState::new(backend, "parties");
+
+            speeds.put(&"case-1".to_string(), &42.5).await.unwrap();
+            impacts.add(&"case-1".to_string(), 1).await.unwrap();
+            impacts.add(&"case-1".to_string(), 2).await.unwrap();
+            parties.put_entry(&"case-1".to_string(), "driver".to_string(), 2).await.unwrap();
+        }
+
+        let backend = Arc::new(RocksDbStateBackend::open(config).unwrap());
+        let speeds: ValueState<String, f64> = ValueState::new(backend.clone(), "speed");
+        let impacts: ListState<String, u32> = ListState::new(backend.clone(), "impacts");
+        let parties: MapState<String, String, u32> = MapState::new(backend, "parties");
+
+        assert_eq!(speeds.get(&"case-1".to_string()).await.unwrap(), Some(42.5));
+        assert_eq!(
+            impacts.get(&"case-1".to_string()).await.unwrap(),
+            vec![1, 2]
+        );
+        assert_eq!(
+            parties.get_entry(&"case-1".to_string(), &"driver".to_string()).await.unwrap(),
+            Some(2)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_incremental_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = RocksDbStateBackend::open(RocksDbStateConfig::new(dir.path())).unwrap();
+
+        backend.put(b"a".to_vec(), b"1".to_vec()).await.unwrap();
+        backend.put(b"b".to_vec(), b"2".to_vec()).await.unwrap();
+        let base = backend.incremental_snapshot().await.unwrap();
+        assert!(base.full);
+        assert_eq!(base.puts.len(), 2);
+
+        assert!(backend.incremental_snapshot().await.unwrap().is_empty());
+
+        backend.put(b"c".to_vec(), b"3".to_vec()).await.unwrap();
+        backend.delete(b"a").await.unwrap();
+        let delta = backend.incremental_snapshot().await.unwrap();
+        assert!(!delta.full);
+        assert_eq!(delta.puts, HashMap::from([(b"c".to_vec(), b"3".to_vec())]));
+        assert_eq!(delta.deletes, vec![b"a".to_vec()]);
+
+        let restored = MemoryStateBackend::new();
+        restored.apply_delta(base).await.unwrap();
+        restored.apply_delta(delta).await.unwrap();
+        assert_eq!(
+            restored.snapshot().await.unwrap(),
+            backend.snapshot().await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_state_ttl() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = RocksDbStateConfig::new(dir.path()).with_ttl(Duration::from_millis(50));
+        let backend = RocksDbStateBackend::open(config).unwrap();
+
+        backend.put(b"session".to_vec(), b"open".to_vec()).await.unwrap();
+        assert_eq!(
+            backend.get(b"session").await.unwrap(),
+            Some(b"open".to_vec())
+        );
+        backend.incremental_snapshot().await.unwrap();
+
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert_eq!(backend.get(b"session").await.unwrap(), None);
+        assert!(backend.snapshot().await.unwrap().is_empty());
+
+        assert_eq!(backend.purge_expired().unwrap(), 1);
+        let delta = backend.incremental_snapshot().await.unwrap();
+        assert_eq!(delta.deletes, vec![b"session".to_vec()]);
+    }
+}
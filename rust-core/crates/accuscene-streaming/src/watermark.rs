@@ -1,15 +1,16 @@
 //! Watermark handling for event-time processing.
 
+use crate::config::WatermarkConfig;
 use crate::error::{Result, StreamingError};
 use chrono::{DateTime, Utc};
 use std::collections::BTreeMap;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use tracing::{debug, trace};
 
 /// Represents an event timestamp
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Timestamp(pub i64);
 
 impl Timestamp {
@@ -205,6 +206,194 @@ impl WatermarkStrategy for PeriodicWatermarkStrategy {
     }
 }
 
+/// Watermark state of a single partition
+#[derive(Debug, Clone)]
+struct PartitionState {
+    max_timestamp: Option<Timestamp>,
+    last_activity: Instant,
+    idle: bool,
+    finished: bool,
+}
+
+/// Bounded out-of-orderness watermark generator over multiple partitions
+///
+/// Each partition's watermark trails the largest timestamp it has seen by the
+/// maximum out-of-orderness, and the combined watermark is the minimum over
+/// active partitions, so a partition read faster than the others (as when
+/// replaying historical files) cannot drag event time ahead of them.
+/// Partitions that see no events for the idle timeout stop holding the
+/// watermark back until they produce again, and the watermark only reaches
+/// [`Watermark::max`] once every partition has finished.
+#[derive(Debug, Clone)]
+pub struct PartitionedWatermarkGenerator {
+    max_out_of_orderness: Duration,
+    idle_timeout: Option<Duration>,
+    max_drift: Option<Duration>,
+    partitions: BTreeMap<String, PartitionState>,
+    current: Watermark,
+}
+
+impl PartitionedWatermarkGenerator {
+    /// Create a new generator without idle detection or drift allowance
+    pub fn new(max_out_of_orderness: Duration) -> Self {
+        Self {
+            max_out_of_orderness,
+            idle_timeout: None,
+            max_drift: None,
+            partitions: BTreeMap::new(),
+            current: Watermark::min(),
+        }
+    }
+
+    /// Create a generator from a watermark configuration
+    ///
+    /// A zero idle timeout disables idle detection.
+    pub fn from_config(config: &WatermarkConfig) -> Self {
+        let mut generator = Self::new(config.max_out_of_orderness);
+        if !config.idle_timeout.is_zero() {
+            generator = generator.with_idle_timeout(config.idle_timeout);
+        }
+        if let Some(drift) = config.max_drift {
+            generator = generator.with_max_drift(drift);
+        }
+        generator
+    }
+
+    /// Mark partitions idle after `timeout` without events
+    pub fn with_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Allow records up to `drift` past the watermark to be read ahead of lagging partitions
+    pub fn with_max_drift(mut self, drift: Duration) -> Self {
+        self.max_drift = Some(drift);
+        self
+    }
+
+    /// Register a partition, holding the watermark back until it produces
+    pub fn add_partition(&mut self, partition: impl Into<String>) {
+        self.partitions
+            .entry(partition.into())
+            .or_insert_with(|| PartitionState {
+                max_timestamp: None,
+                last_activity: Instant::now(),
+                idle: false,
+                finished: false,
+            });
+    }
+
+    /// Record an event, returning the combined watermark if it advanced
+    pub fn on_event(&mut self, partition: &str, timestamp: Timestamp) -> Option<Watermark> {
+        self.add_partition(partition);
+        let state = self.partitions.get_mut(partition)?;
+        state.max_timestamp = Some(state.max_timestamp.map_or(timestamp, |max| max.max(timestamp)));
+        state.last_activity = Instant::now();
+        if state.idle {
+            debug!("Partition {} is active again", partition);
+            state.idle = false;
+        }
+        self.advance()
+    }
+
+    /// Mark partitions without recent events as idle, returning the watermark if it advanced
+    pub fn check_idle(&mut self, now: Instant) -> Option<Watermark> {
+        let timeout = self.idle_timeout?;
+        for (partition, state) in self.partitions.iter_mut() {
+            let quiet = now.duration_since(state.last_activity) >= timeout;
+            if quiet && !state.idle && !state.finished {
+                debug!("Partition {} is idle", partition);
+                state.idle = true;
+            }
+        }
+        self.advance()
+    }
+
+    /// Mark a partition as exhausted, returning the combined watermark if it advanced
+    pub fn finish_partition(&mut self, partition: &str) -> Option<Watermark> {
+        if let Some(state) = self.partitions.get_mut(partition) {
+            state.finished = true;
+        }
+        self.advance()
+    }
+
+    /// When the next active partition becomes idle, if idle detection is enabled
+    pub fn next_idle_deadline(&self) -> Option<Instant> {
+        let timeout = self.idle_timeout?;
+        self.partitions
+            .values()
+            .filter(|state| !state.idle && !state.finished)
+            .map(|state| state.last_activity + timeout)
+            .min()
+    }
+
+    /// Check if a record may be read ahead of partitions that have not produced yet
+    pub fn within_drift(&self, timestamp: Timestamp) -> bool {
+        match self.max_drift {
+            Some(drift) => {
+                self.current != Watermark::min()
+                    && timestamp <= self.current.timestamp.add(drift)
+            }
+            None => false,
+        }
+    }
+
+    /// Get the combined watermark
+    pub fn current_watermark(&self) -> Watermark {
+        self.current
+    }
+
+    /// Get the watermark of a partition
+    pub fn partition_watermark(&self, partition: &str) -> Option<Watermark> {
+        self.partitions.get(partition).map(|state| self.watermark_of(state))
+    }
+
+    /// Check if a partition is idle
+    pub fn is_idle(&self, partition: &str) -> bool {
+        self.partitions.get(partition).is_some_and(|state| state.idle)
+    }
+
+    fn watermark_of(&self, state: &PartitionState) -> Watermark {
+        if state.finished {
+            return Watermark::max();
+        }
+        match state.max_timestamp {
+            Some(max) => Watermark::from_millis(
+                max.as_millis()
+                    .saturating_sub(self.max_out_of_orderness.as_millis() as i64),
+            ),
+            None => Watermark::min(),
+        }
+    }
+
+    /// Recompute the combined watermark, which never moves backwards
+    fn advance(&mut self) -> Option<Watermark> {
+        if self.partitions.is_empty() {
+            return None;
+        }
+
+        let all_finished = self.partitions.values().all(|state| state.finished);
+        let combined = if all_finished {
+            Watermark::max()
+        } else {
+            // With every remaining partition idle the watermark holds
+            self.partitions
+                .values()
+                .filter(|state| !state.idle && !state.finished)
+                .map(|state| self.watermark_of(state))
+                .min()?
+        };
+
+        if combined > self.current {
+            trace!("Partitioned watermark advanced: {:?} -> {:?}", self.current, combined);
+            self.current = combined;
+            Some(combined)
+        } else {
+            None
+        }
+    }
+}
+
 /// Watermark tracker that combines watermarks from multiple sources
 #[derive(Clone)]
 pub struct WatermarkTracker {
@@ -407,4 +596,35 @@ mod tests {
             .unwrap();
         assert_eq!(tracker.get().await.timestamp.as_millis(), 1000);
     }
+
+    #[test]
+    fn test_partitioned_watermark_generator() {
+        let mut generator = PartitionedWatermarkGenerator::new(Duration::from_secs(1))
+            .with_idle_timeout(Duration::from_millis(50));
+        generator.add_partition("a");
+        generator.add_partition("b");
+
+        // A partition racing ahead does not advance the watermark on its own
+        assert!(generator.on_event("b", Timestamp::from_millis(60_000)).is_none());
+        assert_eq!(generator.current_watermark(), Watermark::min());
+
+        let wm = generator.on_event("a", Timestamp::from_millis(5_000)).unwrap();
+        assert_eq!(wm.timestamp.as_millis(), 4_000);
+
+        // An idle partition stops holding the watermark back
+        std::thread::sleep(Duration::from_millis(60));
+        generator.on_event("b", Timestamp::from_millis(61_000));
+        let wm = generator.check_idle(Instant::now()).unwrap();
+        assert!(generator.is_idle("a"));
+        assert_eq!(wm.timestamp.as_millis(), 60_000);
+
+        // Resuming does not move the watermark backwards
+        assert!(generator.on_event("a", Timestamp::from_millis(6_000)).is_none());
+        assert!(!generator.is_idle("a"));
+        assert_eq!(generator.current_watermark().timestamp.as_millis(), 60_000);
+
+        generator.finish_partition("a");
+        let wm = generator.finish_partition("b").unwrap();
+        assert_eq!(wm, Watermark::max());
+    }
 }
@@ -8,4 +8,4 @@ pub mod server;
 pub use client::{ReconnectConfig, WsClient};
 pub use handler::{CompositeHandler, DefaultMessageHandler, HandlerBuilder, MessageHandler};
 pub use protocol::{ConnectionState, WsMessage};
-pub use server::{WsConnection, WsServer, WsServerBuilder};
+pub use server::{WsAuth, WsConnection, WsServer, WsServerBuilder};
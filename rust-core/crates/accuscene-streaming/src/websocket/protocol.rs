@@ -49,6 +49,14 @@ pub enum WsMessage {
         error: Option<String>,
     },
 
+    /// Notice that the connection token expires soon
+    ///
+    /// The client renews it by sending an `Auth` message with a new token
+    /// before `expires_at` (milliseconds since epoch), or is disconnected.
+    TokenExpiring {
+        expires_at: i64,
+    },
+
     /// Ping message for heartbeat
     Ping {
         timestamp: i64,
@@ -132,6 +140,25 @@ impl WsMessage {
         }
     }
 
+    /// Create an authentication request
+    pub fn auth(token: impl Into<String>) -> Self {
+        Self::Auth {
+            token: token.into(),
+        }
+    }
+
+    /// Create an authentication response
+    pub fn auth_response(success: bool, error: Option<String>) -> Self {
+        Self::AuthResponse { success, error }
+    }
+
+    /// Create a token expiry notice
+    pub fn token_expiring(expires_at: chrono::DateTime<chrono::Utc>) -> Self {
+        Self::TokenExpiring {
+            expires_at: expires_at.timestamp_millis(),
+        }
+    }
+
     /// Create a ping message
     pub fn ping() -> Self {
         Self::Ping {
@@ -1,14 +1,78 @@
 //! WebSocket server implementation.
 
+use crate::auth::{AuthToken, Authenticator, Permission, RoomAuthorizer};
 use crate::error::{Result, StreamingError};
+use crate::event::UserId;
 use crate::websocket::handler::MessageHandler;
 use crate::websocket::protocol::{ConnectionState, WsMessage};
+use chrono::Utc;
 use futures_util::{SinkExt, StreamExt};
 use parking_lot::RwLock;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::{TcpListener, TcpStream};
-use tokio_tungstenite::{accept_async, tungstenite::Message, WebSocketStream};
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::{accept_hdr_async, tungstenite::Message, WebSocketStream};
+
+/// Default time a client has to authenticate after connecting
+const DEFAULT_AUTH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Default time before token expiry at which clients are asked to renew
+const DEFAULT_RENEWAL_WINDOW: Duration = Duration::from_secs(60);
+
+/// Authentication and authorization enforced on server connections
+///
+/// Clients authenticate with a bearer token in the `Authorization` header of
+/// the handshake, or with an `Auth` message within the auth timeout. Until
+/// then, only `Auth`, `Ping` and `Pong` messages are accepted.
+///
+/// Subscribing to a room requires [`Permission::ReadEvents`] in it, joining
+/// it [`Permission::JoinRooms`], and publishing an event
+/// [`Permission::PublishEvents`] in the room of the event. Replaying events
+/// requires [`Permission::ReadEvents`] outside rooms.
+///
+/// Within the renewal window before its token expires, the client receives a
+/// `TokenExpiring` message and renews by sending an `Auth` message with a new
+/// token of the same user. The connection is closed when the token expires.
+#[derive(Clone)]
+pub struct WsAuth {
+    /// Token validation
+    authenticator: Arc<dyn Authenticator>,
+    /// Room permission checks
+    authorizer: Arc<dyn RoomAuthorizer>,
+    /// Time a client has to authenticate after connecting
+    auth_timeout: Duration,
+    /// Time before token expiry at which clients are asked to renew
+    renewal_window: Duration,
+}
+
+impl WsAuth {
+    /// Create a new authentication policy
+    pub fn new(authenticator: Arc<dyn Authenticator>, authorizer: Arc<dyn RoomAuthorizer>) -> Self {
+        Self {
+            authenticator,
+            authorizer,
+            auth_timeout: DEFAULT_AUTH_TIMEOUT,
+            renewal_window: DEFAULT_RENEWAL_WINDOW,
+        }
+    }
+
+    /// Set the time a client has to authenticate after connecting
+    pub fn with_auth_timeout(mut self, timeout: Duration) -> Self {
+        self.auth_timeout = timeout;
+        self
+    }
+
+    /// Set the time before token expiry at which clients are asked to renew
+    pub fn with_renewal_window(mut self, window: Duration) -> Self {
+        self.renewal_window = window;
+        self
+    }
+}
 
 /// WebSocket connection
 pub struct WsConnection {
@@ -22,6 +86,16 @@ pub struct WsConnection {
     state: Arc<RwLock<ConnectionState>>,
     /// Message handler
     handler: Arc<dyn MessageHandler>,
+    /// Authentication policy, if enforced
+    auth: Option<WsAuth>,
+    /// Token presented during the handshake
+    handshake_token: Option<String>,
+    /// Token the connection is authenticated with
+    session: Option<AuthToken>,
+    /// Deadline for authenticating
+    auth_deadline: Option<Instant>,
+    /// Whether the client was asked to renew its token
+    renewal_requested: bool,
 }
 
 impl WsConnection {
@@ -38,9 +112,28 @@ impl WsConnection {
             stream,
             state: Arc::new(RwLock::new(ConnectionState::Connected)),
             handler,
+            auth: None,
+            handshake_token: None,
+            session: None,
+            auth_deadline: None,
+            renewal_requested: false,
         }
     }
 
+    /// Require authentication, with the bearer token of the handshake if any
+    pub fn with_auth(mut self, auth: WsAuth, handshake_token: Option<String>) -> Self {
+        self.auth_deadline = Some(Instant::now() + auth.auth_timeout);
+        self.auth = Some(auth);
+        self.handshake_token = handshake_token;
+        *self.state.write() = ConnectionState::Connecting;
+        self
+    }
+
+    /// Get the authenticated user
+    pub fn user_id(&self) -> Option<&UserId> {
+        self.session.as_ref().map(|session| &session.user_id)
+    }
+
     /// Get connection state
     pub fn state(&self) -> ConnectionState {
         *self.state.read()
@@ -62,42 +155,40 @@ impl WsConnection {
 
     /// Receive and handle messages
     pub async fn handle_messages(&mut self) -> Result<()> {
+        if let Some(token) = self.handshake_token.take() {
+            if !self.authenticate(&token).await? {
+                self.disconnect("auth_failed", "Authentication failed").await;
+                return Ok(());
+            }
+        }
+
         // Notify handler of connection
         self.handler.on_connect().await?;
 
-        while let Some(message) = self.stream.next().await {
+        loop {
+            let message = match self.next_deadline() {
+                Some(deadline) => {
+                    let woken = tokio::select! {
+                        message = self.stream.next() => Some(message),
+                        _ = tokio::time::sleep_until(deadline) => None,
+                    };
+                    match woken {
+                        Some(message) => message,
+                        None if self.on_deadline().await => continue,
+                        None => break,
+                    }
+                }
+                None => self.stream.next().await,
+            };
+            let Some(message) = message else {
+                break;
+            };
+
             match message {
                 Ok(Message::Text(text)) => {
-                    match WsMessage::from_json(&text) {
-                        Ok(ws_msg) => {
-                            // Handle the message
-                            match self.handler.handle(ws_msg).await {
-                                Ok(Some(response)) => {
-                                    // Send response if provided
-                                    self.send(response).await?;
-                                }
-                                Ok(None) => {
-                                    // No response needed
-                                }
-                                Err(e) => {
-                                    self.handler.on_error(&e).await?;
-                                    // Send error message to client
-                                    let error_msg = WsMessage::error(
-                                        e.category(),
-                                        e.to_string(),
-                                    );
-                                    self.send(error_msg).await.ok();
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            tracing::error!("Failed to parse WebSocket message: {}", e);
-                            let error_msg = WsMessage::error(
-                                "parse_error",
-                                format!("Invalid message format: {}", e),
-                            );
-                            self.send(error_msg).await.ok();
-                        }
+                    let keep_open = self.handle_text(&text).await?;
+                    if !keep_open {
+                        break;
                     }
                 }
                 Ok(Message::Binary(_)) => {
@@ -138,6 +229,191 @@ impl WsConnection {
         Ok(())
     }
 
+    /// Handle a text message, returning whether the connection stays open
+    async fn handle_text(&mut self, text: &str) -> Result<bool> {
+        let ws_msg = match WsMessage::from_json(text) {
+            Ok(ws_msg) => ws_msg,
+            Err(e) => {
+                tracing::error!("Failed to parse WebSocket message: {}", e);
+                let error_msg = WsMessage::error(
+                    "parse_error",
+                    format!("Invalid message format: {}", e),
+                );
+                self.send(error_msg).await.ok();
+                return Ok(true);
+            }
+        };
+
+        if self.auth.is_some() {
+            if let WsMessage::Auth { token } = &ws_msg {
+                // A failed renewal keeps the current token until it expires
+                if !self.authenticate(token).await? && self.session.is_none() {
+                    self.disconnect("auth_failed", "Authentication failed").await;
+                    return Ok(false);
+                }
+                return Ok(true);
+            }
+
+            if let Some(rejection) = self.check_access(&ws_msg).await? {
+                self.send(rejection).await?;
+                return Ok(true);
+            }
+        }
+
+        // Handle the message
+        match self.handler.handle(ws_msg).await {
+            Ok(Some(response)) => {
+                // Send response if provided
+                self.send(response).await?;
+            }
+            Ok(None) => {
+                // No response needed
+            }
+            Err(e) => {
+                self.handler.on_error(&e).await?;
+                // Send error message to client
+                let error_msg = WsMessage::error(e.category(), e.to_string());
+                self.send(error_msg).await.ok();
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Authenticate or renew the connection token, answering the client
+    async fn authenticate(&mut self, token: &str) -> Result<bool> {
+        let Some(authenticator) = self.auth.as_ref().map(|auth| auth.authenticator.clone()) else {
+            return Ok(true);
+        };
+
+        let result = authenticator.authenticate(token).await?;
+        let error = match (result.success, result.token) {
+            (true, Some(token)) => match &self.session {
+                Some(session) if session.user_id != token.user_id => {
+                    Some("Token belongs to another user".to_string())
+                }
+                _ => {
+                    tracing::debug!(
+                        "Connection {} authenticated as {}",
+                        self.id,
+                        token.user_id
+                    );
+                    self.session = Some(token);
+                    self.auth_deadline = None;
+                    self.renewal_requested = false;
+                    *self.state.write() = ConnectionState::Connected;
+                    None
+                }
+            },
+            _ => Some(
+                result
+                    .error
+                    .unwrap_or_else(|| "Authentication failed".to_string()),
+            ),
+        };
+
+        let accepted = error.is_none();
+        self.send(WsMessage::auth_response(accepted, error)).await?;
+        Ok(accepted)
+    }
+
+    /// Check a message against the permissions of the connection
+    ///
+    /// Returns the error to answer with if the message is rejected.
+    async fn check_access(&self, message: &WsMessage) -> Result<Option<WsMessage>> {
+        let Some(auth) = &self.auth else {
+            return Ok(None);
+        };
+        let Some(session) = &self.session else {
+            return Ok(match message {
+                WsMessage::Ping { .. } | WsMessage::Pong { .. } => None,
+                _ => Some(WsMessage::error(
+                    "unauthenticated",
+                    "Authentication required",
+                )),
+            });
+        };
+
+        let (room_id, permission) = match message {
+            WsMessage::Subscribe { topic, .. } => (Some(topic), Permission::ReadEvents),
+            WsMessage::JoinRoom { room_id, user_id } => {
+                if user_id.as_ref().is_some_and(|user_id| user_id != &session.user_id) {
+                    return Ok(Some(WsMessage::error(
+                        "forbidden",
+                        "Cannot join a room as another user",
+                    )));
+                }
+                (Some(room_id), Permission::JoinRooms)
+            }
+            WsMessage::Event { event } => {
+                (event.metadata.room_id.as_ref(), Permission::PublishEvents)
+            }
+            WsMessage::ReplayRequest { .. } => (None, Permission::ReadEvents),
+            _ => return Ok(None),
+        };
+
+        if auth.authorizer.authorize(session, room_id, &permission).await? {
+            return Ok(None);
+        }
+
+        let message = match room_id {
+            Some(room_id) => format!("Permission {:?} denied in room {}", permission, room_id),
+            None => format!("Permission {:?} denied", permission),
+        };
+        Ok(Some(WsMessage::error("forbidden", message)))
+    }
+
+    /// Get the next authentication deadline
+    fn next_deadline(&self) -> Option<Instant> {
+        let auth = self.auth.as_ref()?;
+        let Some(session) = &self.session else {
+            return self.auth_deadline;
+        };
+
+        let remaining = (session.expires_at? - Utc::now())
+            .to_std()
+            .unwrap_or_default();
+        if self.renewal_requested {
+            Some(Instant::now() + remaining)
+        } else {
+            Some(Instant::now() + remaining.saturating_sub(auth.renewal_window))
+        }
+    }
+
+    /// Act on a passed deadline, returning whether the connection stays open
+    async fn on_deadline(&mut self) -> bool {
+        let Some(session) = &self.session else {
+            self.disconnect("auth_timeout", "Authentication timed out").await;
+            return false;
+        };
+
+        if session.is_expired() {
+            self.disconnect("token_expired", "Token expired").await;
+            return false;
+        }
+
+        if !self.renewal_requested {
+            if let Some(expires_at) = session.expires_at {
+                self.renewal_requested = true;
+                self.send(WsMessage::token_expiring(expires_at)).await.ok();
+            }
+        }
+        true
+    }
+
+    /// Tell the client why it is disconnected and close the connection
+    async fn disconnect(&mut self, code: &str, reason: &str) {
+        tracing::info!("Disconnecting {}: {}", self.id, reason);
+        self.send(WsMessage::error(code, reason)).await.ok();
+
+        let frame = CloseFrame {
+            code: CloseCode::Policy,
+            reason: reason.to_string().into(),
+        };
+        self.stream.close(Some(frame)).await.ok();
+        *self.state.write() = ConnectionState::Disconnected;
+    }
+
     /// Close the connection
     pub async fn close(&mut self) -> Result<()> {
         self.stream
@@ -157,6 +433,8 @@ pub struct WsServer {
     addr: SocketAddr,
     /// Message handler factory
     handler_factory: Arc<dyn Fn() -> Arc<dyn MessageHandler> + Send + Sync>,
+    /// Authentication policy, if enforced
+    auth: Option<WsAuth>,
 }
 
 impl WsServer {
@@ -168,9 +446,16 @@ impl WsServer {
         Self {
             addr,
             handler_factory: Arc::new(handler_factory),
+            auth: None,
         }
     }
 
+    /// Require authentication and room permissions on all connections
+    pub fn with_auth(mut self, auth: WsAuth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
     /// Start the server
     pub async fn start(self) -> Result<()> {
         let listener = TcpListener::bind(self.addr)
@@ -182,9 +467,19 @@ impl WsServer {
         while let Ok((stream, addr)) = listener.accept().await {
             let handler = (self.handler_factory)();
             let connection_id = uuid::Uuid::new_v4().to_string();
+            let auth = self.auth.clone();
 
             tokio::spawn(async move {
-                match accept_async(stream).await {
+                let mut bearer = None;
+                // The callback signature is fixed by tungstenite
+                #[allow(clippy::result_large_err)]
+                let capture_bearer = |request: &Request, response: Response| {
+                    bearer = bearer_token(request);
+                    Ok(response)
+                };
+                let accepted = accept_hdr_async(stream, capture_bearer).await;
+
+                match accepted {
                     Ok(ws_stream) => {
                         let mut connection = WsConnection::new(
                             connection_id.clone(),
@@ -192,6 +487,9 @@ impl WsServer {
                             ws_stream,
                             handler,
                         );
+                        if let Some(auth) = auth {
+                            connection = connection.with_auth(auth, bearer);
+                        }
 
                         tracing::info!(
                             "New WebSocket connection: {} from {}",
@@ -220,6 +518,16 @@ impl WsServer {
     }
 }
 
+/// Extract the bearer token of a handshake request
+fn bearer_token(request: &Request) -> Option<String> {
+    request
+        .headers()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string())
+}
+
 /// WebSocket server builder
 pub struct WsServerBuilder<F>
 where
@@ -227,6 +535,7 @@ where
 {
     addr: Option<SocketAddr>,
     handler_factory: Option<F>,
+    auth: Option<WsAuth>,
 }
 
 impl<F> WsServerBuilder<F>
@@ -238,6 +547,7 @@ where
         Self {
             addr: None,
             handler_factory: None,
+            auth: None,
         }
     }
 
@@ -253,6 +563,12 @@ where
         self
     }
 
+    /// Require authentication and room permissions
+    pub fn with_auth(mut self, auth: WsAuth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
     /// Build the server
     pub fn build(self) -> Result<WsServer> {
         let addr = self
@@ -263,6 +579,171 @@ where
             StreamingError::Configuration("Handler factory not set".to_string())
         })?;
 
-        Ok(WsServer::new(addr, handler_factory))
+        let server = WsServer::new(addr, handler_factory);
+        Ok(match self.auth {
+            Some(auth) => server.with_auth(auth),
+            None => server,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{InMemoryAuthenticator, RoleBasedAuthorizer, RoomAccessControl};
+    use crate::event::{Event, EventPayload, EventType};
+    use crate::websocket::handler::DefaultMessageHandler;
+    use tokio_tungstenite::MaybeTlsStream;
+
+    type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+    fn auth_policy(authenticator: Arc<InMemoryAuthenticator>) -> WsAuth {
+        let rooms = RoomAccessControl::new(Arc::new(RoleBasedAuthorizer::new()));
+        rooms.grant("case-1", "user1", Permission::ReadEvents);
+
+        WsAuth::new(authenticator, Arc::new(rooms))
+    }
+
+    async fn connect(auth: WsAuth) -> Client {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (stream, peer) = listener.accept().await.unwrap();
+            let ws_stream = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let handler = Arc::new(DefaultMessageHandler::new());
+            let mut connection = WsConnection::new("test".to_string(), peer, ws_stream, handler)
+                .with_auth(auth, None);
+            connection.handle_messages().await.unwrap();
+        });
+
+        let (client, _) = tokio_tungstenite::connect_async(format!("ws://{}", addr))
+            .await
+            .unwrap();
+        client
+    }
+
+    async fn send(client: &mut Client, message: WsMessage) {
+        client
+            .send(Message::Text(message.to_json().unwrap()))
+            .await
+            .unwrap();
+    }
+
+    /// Receive the next protocol message, or `None` once the connection closes
+    async fn recv(client: &mut Client) -> Option<WsMessage> {
+        while let Some(message) = client.next().await {
+            match message {
+                Ok(Message::Text(text)) => return Some(WsMessage::from_json(&text).unwrap()),
+                Ok(Message::Close(_)) | Err(_) => return None,
+                _ => {}
+            }
+        }
+        None
+    }
+
+    fn error_code(message: Option<WsMessage>) -> String {
+        match message {
+            Some(WsMessage::Error { code, .. }) => code,
+            other => panic!("Expected error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_room_permissions_enforced() {
+        let authenticator = Arc::new(InMemoryAuthenticator::new());
+        authenticator.add_token(AuthToken::new("token-1", "user1"));
+        let mut client = connect(auth_policy(authenticator)).await;
+
+        send(&mut client, WsMessage::subscribe("case-1")).await;
+        assert_eq!(error_code(recv(&mut client).await), "unauthenticated");
+
+        send(&mut client, WsMessage::auth("token-1")).await;
+        assert!(matches!(
+            recv(&mut client).await,
+            Some(WsMessage::AuthResponse { success: true, .. })
+        ));
+
+        send(&mut client, WsMessage::subscribe("case-2")).await;
+        assert_eq!(error_code(recv(&mut client).await), "forbidden");
+
+        let mut event = Event::new(EventType::UserJoined, EventPayload::Empty);
+        event.metadata.room_id = Some("case-1".to_string());
+        send(&mut client, WsMessage::event(event)).await;
+        assert_eq!(error_code(recv(&mut client).await), "forbidden");
+
+        // The permitted subscription passes through to the handler silently
+        send(&mut client, WsMessage::subscribe("case-1")).await;
+        send(&mut client, WsMessage::ping()).await;
+        assert!(matches!(recv(&mut client).await, Some(WsMessage::Pong { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_token_disconnects() {
+        let mut client = connect(auth_policy(Arc::new(InMemoryAuthenticator::new()))).await;
+
+        send(&mut client, WsMessage::auth("forged")).await;
+        assert!(matches!(
+            recv(&mut client).await,
+            Some(WsMessage::AuthResponse { success: false, .. })
+        ));
+        assert_eq!(error_code(recv(&mut client).await), "auth_failed");
+        assert!(recv(&mut client).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_token_expiry_and_renewal() {
+        let authenticator = Arc::new(InMemoryAuthenticator::new());
+        let expires_at = Utc::now() + chrono::Duration::milliseconds(300);
+        authenticator.add_token(AuthToken::new("short", "user1").with_expiration(expires_at));
+        authenticator.add_token(AuthToken::new("renewed", "user1"));
+        authenticator.add_token(AuthToken::new("other", "user2"));
+
+        let auth = auth_policy(authenticator.clone())
+            .with_renewal_window(Duration::from_millis(200));
+        let mut client = connect(auth.clone()).await;
+        send(&mut client, WsMessage::auth("short")).await;
+        assert!(matches!(
+            recv(&mut client).await,
+            Some(WsMessage::AuthResponse { success: true, .. })
+        ));
+
+        assert!(matches!(
+            recv(&mut client).await,
+            Some(WsMessage::TokenExpiring { .. })
+        ));
+
+        // Renewing as another user is refused
+        send(&mut client, WsMessage::auth("other")).await;
+        assert!(matches!(
+            recv(&mut client).await,
+            Some(WsMessage::AuthResponse { success: false, .. })
+        ));
+
+        send(&mut client, WsMessage::auth("renewed")).await;
+        assert!(matches!(
+            recv(&mut client).await,
+            Some(WsMessage::AuthResponse { success: true, .. })
+        ));
+
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        send(&mut client, WsMessage::ping()).await;
+        assert!(matches!(recv(&mut client).await, Some(WsMessage::Pong { .. })));
+
+        // Without renewal, the connection is closed at expiry
+        let expires_at = Utc::now() + chrono::Duration::milliseconds(300);
+        authenticator.add_token(AuthToken::new("brief", "user1").with_expiration(expires_at));
+        let mut client = connect(auth).await;
+        send(&mut client, WsMessage::auth("brief")).await;
+        assert!(matches!(
+            recv(&mut client).await,
+            Some(WsMessage::AuthResponse { success: true, .. })
+        ));
+        assert!(matches!(
+            recv(&mut client).await,
+            Some(WsMessage::TokenExpiring { .. })
+        ));
+        assert_eq!(error_code(recv(&mut client).await), "token_expired");
+        assert!(recv(&mut client).await.is_none());
     }
 }
@@ -1,4 +1,8 @@
+use crate::storage::StorageOptions;
+use crate::DataRecord;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 
 /// Transfer configuration
@@ -38,6 +42,35 @@ pub struct TransferConfig {
     pub continue_on_error: bool,
     /// Error threshold (max errors before stopping)
     pub error_threshold: usize,
+    /// Rows per record batch for columnar formats (Parquet row group size)
+    #[serde(default = "default_columnar_batch_size")]
+    pub columnar_batch_size: usize,
+    /// Predicates records must match to be imported
+    ///
+    /// Columnar formats push these down into the reader; all predicates
+    /// must match.
+    #[serde(default)]
+    pub import_predicates: Vec<ImportPredicate>,
+    /// Records per chunk file for resumable exports
+    #[serde(default = "default_export_chunk_records")]
+    pub export_chunk_records: usize,
+    /// Import source path or `s3://`, `az://`, `gs://` URI
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Export destination path or `s3://`, `az://`, `gs://` URI
+    #[serde(default)]
+    pub destination: Option<String>,
+    /// Object storage client settings
+    #[serde(default)]
+    pub storage: StorageOptions,
+}
+
+fn default_columnar_batch_size() -> usize {
+    8192
+}
+
+fn default_export_chunk_records() -> usize {
+    10_000
 }
 
 impl Default for TransferConfig {
@@ -60,6 +93,12 @@ impl Default for TransferConfig {
             skip_validation: false,
             continue_on_error: false,
             error_threshold: 100,
+            columnar_batch_size: default_columnar_batch_size(),
+            import_predicates: Vec::new(),
+            export_chunk_records: default_export_chunk_records(),
+            source: None,
+            destination: None,
+            storage: StorageOptions::default(),
         }
     }
 }
@@ -132,6 +171,42 @@ impl TransferConfig {
         self
     }
 
+    /// Builder: Set rows per columnar record batch
+    pub fn with_columnar_batch_size(mut self, size: usize) -> Self {
+        self.columnar_batch_size = size;
+        self
+    }
+
+    /// Builder: Set records per resumable export chunk
+    pub fn with_export_chunk_records(mut self, records: usize) -> Self {
+        self.export_chunk_records = records;
+        self
+    }
+
+    /// Builder: Add import predicate
+    pub fn with_import_predicate(mut self, predicate: ImportPredicate) -> Self {
+        self.import_predicates.push(predicate);
+        self
+    }
+
+    /// Builder: Set import source path or URI
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Builder: Set export destination path or URI
+    pub fn with_destination(mut self, destination: impl Into<String>) -> Self {
+        self.destination = Some(destination.into());
+        self
+    }
+
+    /// Builder: Set object storage settings
+    pub fn with_storage(mut self, storage: StorageOptions) -> Self {
+        self.storage = storage;
+        self
+    }
+
     /// Validate configuration
     pub fn validate(&self) -> Result<(), String> {
         if self.max_file_size == 0 {
@@ -140,12 +215,19 @@ impl TransferConfig {
         if self.chunk_size == 0 {
             return Err("chunk_size must be greater than 0".to_string());
         }
+        if self.columnar_batch_size == 0 {
+            return Err("columnar_batch_size must be greater than 0".to_string());
+        }
+        if self.export_chunk_records == 0 {
+            return Err("export_chunk_records must be greater than 0".to_string());
+        }
         if self.compression_level > 9 {
             return Err("compression_level must be between 0 and 9".to_string());
         }
         if self.xml_root_element.is_empty() {
             return Err("xml_root_element cannot be empty".to_string());
         }
+        self.storage.validate()?;
         Ok(())
     }
 }
@@ -160,6 +242,18 @@ pub enum TransferFormat {
     Xml,
     Pdf,
     Archive,
+    Parquet,
+    /// Arrow IPC file format
+    Arrow,
+    /// NHTSA-style crash report XML
+    #[serde(rename = "crash_report")]
+    CrashReport,
+    /// Bosch CDR crash data retrieval CSV export
+    Cdr,
+    /// GPX GPS track
+    Gpx,
+    /// NMEA 0183 GPS log
+    Nmea,
 }
 
 impl TransferFormat {
@@ -172,6 +266,12 @@ impl TransferFormat {
             Self::Xml => "xml",
             Self::Pdf => "pdf",
             Self::Archive => "zip",
+            Self::Parquet => "parquet",
+            Self::Arrow => "arrow",
+            Self::CrashReport => "xml",
+            Self::Cdr => "csv",
+            Self::Gpx => "gpx",
+            Self::Nmea => "nmea",
         }
     }
 
@@ -184,6 +284,12 @@ impl TransferFormat {
             Self::Xml => "application/xml",
             Self::Pdf => "application/pdf",
             Self::Archive => "application/zip",
+            Self::Parquet => "application/vnd.apache.parquet",
+            Self::Arrow => "application/vnd.apache.arrow.file",
+            Self::CrashReport => "application/xml",
+            Self::Cdr => "text/csv",
+            Self::Gpx => "application/gpx+xml",
+            Self::Nmea => "text/plain",
         }
     }
 
@@ -194,6 +300,122 @@ impl TransferFormat {
 
     /// Check if format supports export
     pub fn supports_export(&self) -> bool {
-        true
+        !matches!(self, Self::CrashReport | Self::Cdr | Self::Gpx | Self::Nmea)
+    }
+}
+
+/// Comparison applied by an import predicate
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PredicateOp {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    IsNull,
+    IsNotNull,
+}
+
+/// Filter on a single field, applied while importing
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ImportPredicate {
+    /// Field name
+    pub field: String,
+    /// Comparison
+    pub op: PredicateOp,
+    /// Value to compare against (ignored for null checks)
+    #[serde(default)]
+    pub value: Value,
+}
+
+impl ImportPredicate {
+    /// Create new predicate
+    pub fn new(field: impl Into<String>, op: PredicateOp, value: Value) -> Self {
+        Self {
+            field: field.into(),
+            op,
+            value,
+        }
+    }
+
+    /// Check a field value against the predicate
+    ///
+    /// Numbers compare numerically and strings lexicographically; values of
+    /// different types, including nulls, never match a comparison.
+    pub fn matches(&self, value: Option<&Value>) -> bool {
+        let value = value.unwrap_or(&Value::Null);
+        let ordering = compare_values(value, &self.value);
+        match self.op {
+            PredicateOp::IsNull => value.is_null(),
+            PredicateOp::IsNotNull => !value.is_null(),
+            PredicateOp::Eq => ordering == Some(Ordering::Equal),
+            PredicateOp::NotEq => matches!(ordering, Some(Ordering::Less | Ordering::Greater)),
+            PredicateOp::Lt => ordering == Some(Ordering::Less),
+            PredicateOp::LtEq => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+            PredicateOp::Gt => ordering == Some(Ordering::Greater),
+            PredicateOp::GtEq => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        }
+    }
+
+    /// Check a record against the predicate
+    pub fn matches_record(&self, record: &DataRecord) -> bool {
+        self.matches(record.get(&self.field))
+    }
+
+    /// Check whether any value in `[min, max]` can match the predicate
+    ///
+    /// Used to skip whole chunks of a file from their statistics. Answers
+    /// `true` whenever the bounds cannot be compared.
+    pub fn may_match_range(&self, min: &Value, max: &Value, null_count: Option<u64>) -> bool {
+        let below_min = compare_values(&self.value, min);
+        let above_max = compare_values(&self.value, max);
+        match self.op {
+            PredicateOp::IsNull => !matches!(null_count, Some(0)),
+            PredicateOp::IsNotNull => true,
+            PredicateOp::Eq => {
+                below_min != Some(Ordering::Less) && above_max != Some(Ordering::Greater)
+            }
+            PredicateOp::NotEq => {
+                !(below_min == Some(Ordering::Equal) && above_max == Some(Ordering::Equal))
+            }
+            PredicateOp::Lt => !matches!(below_min, Some(Ordering::Less | Ordering::Equal)),
+            PredicateOp::LtEq => below_min != Some(Ordering::Less),
+            PredicateOp::Gt => !matches!(above_max, Some(Ordering::Greater | Ordering::Equal)),
+            PredicateOp::GtEq => above_max != Some(Ordering::Greater),
+        }
+    }
+}
+
+/// Order two JSON values of the same kind
+fn compare_values(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_predicate() {
+        let predicate = ImportPredicate::new("speed", PredicateOp::Gt, Value::from(50));
+        assert!(predicate.matches(Some(&Value::from(65.5))));
+        assert!(!predicate.matches(Some(&Value::from(50))));
+        assert!(!predicate.matches(Some(&Value::from("fast"))));
+        assert!(!predicate.matches(None));
+
+        assert!(predicate.may_match_range(&Value::from(10), &Value::from(60), Some(0)));
+        assert!(!predicate.may_match_range(&Value::from(10), &Value::from(50), Some(0)));
+        assert!(predicate.may_match_range(&Value::from("a"), &Value::from("z"), None));
+
+        let predicate = ImportPredicate::new("notes", PredicateOp::IsNull, Value::Null);
+        assert!(predicate.matches(None));
+        assert!(!predicate.may_match_range(&Value::from("a"), &Value::from("z"), Some(0)));
     }
 }
@@ -24,9 +24,21 @@ pub enum TransferError {
     #[error("PDF error: {0}")]
     Pdf(String),
 
+    #[error("Arrow error: {0}")]
+    Arrow(String),
+
+    #[error("Parquet error: {0}")]
+    Parquet(String),
+
     #[error("Archive error: {0}")]
     Archive(#[from] zip::result::ZipError),
 
+    #[error("Storage error: {0}")]
+    Storage(String),
+
+    #[error("Compression error: {0}")]
+    Compression(#[from] accuscene_compression::CompressionError),
+
     #[error("Validation error: {0}")]
     Validation(String),
 
@@ -78,3 +90,24 @@ impl From<quick_xml::DeError> for TransferError {
         TransferError::Xml(err.to_string())
     }
 }
+
+#[cfg(any(feature = "s3", feature = "azure", feature = "gcs"))]
+impl From<object_store::Error> for TransferError {
+    fn from(err: object_store::Error) -> Self {
+        TransferError::Storage(err.to_string())
+    }
+}
+
+#[cfg(feature = "columnar")]
+impl From<arrow::error::ArrowError> for TransferError {
+    fn from(err: arrow::error::ArrowError) -> Self {
+        TransferError::Arrow(err.to_string())
+    }
+}
+
+#[cfg(feature = "columnar")]
+impl From<parquet::errors::ParquetError> for TransferError {
+    fn from(err: parquet::errors::ParquetError) -> Self {
+        TransferError::Parquet(err.to_string())
+    }
+}
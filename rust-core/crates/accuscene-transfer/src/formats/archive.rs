@@ -49,10 +49,11 @@ impl ImportHandler for ArchiveHandler {
             // Read file contents
             let mut contents = Vec::new();
             file.read_to_end(&mut contents)?;
-            let file_bytes = Bytes::from(contents);
+            let file_bytes = crate::formats::decompress_input(Bytes::from(contents), config)?;
 
-            // Determine format from extension
-            let extension = std::path::Path::new(&file_name)
+            // Determine format from extension, ignoring e.g. a trailing `.gz`
+            let inner_name = crate::formats::strip_compression_extension(&file_name);
+            let extension = std::path::Path::new(inner_name)
                 .extension()
                 .and_then(|e| e.to_str())
                 .unwrap_or("");
@@ -209,4 +210,27 @@ mod tests {
         let validation = handler.validate(exported.clone(), &config).await;
         assert!(validation.is_ok());
     }
+    #[tokio::test]
+    async fn test_archive_import_compressed_entry() {
+        let json = br#"[{"name": "Jane Roe"}]"#;
+        let gzipped = accuscene_compression::compress(
+            json,
+            accuscene_compression::Algorithm::Deflate,
+            accuscene_compression::CompressionLevel::Default,
+        )
+        .unwrap();
+
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file("records.json.gz", FileOptions::default()).unwrap();
+        zip.write_all(&gzipped).unwrap();
+        let bytes = Bytes::from(zip.finish().unwrap().into_inner());
+
+        let records = ArchiveHandler
+            .import(bytes, &TransferConfig::default(), None)
+            .await
+            .unwrap();
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].get("name"), Some(&Value::String("Jane Roe".to_string())));
+    }
 }
@@ -0,0 +1,170 @@
+use crate::{
+    config::TransferConfig,
+    error::{Result, TransferError},
+    formats::columnar::{filter_batch, infer_schema, record_stream, records_to_batch},
+    formats::{DataStream, ExportHandler, ImportHandler},
+    progress::ProgressTracker,
+    DataRecord,
+};
+use arrow::ipc::reader::{FileReader, StreamReader};
+use arrow::ipc::writer::FileWriter;
+use arrow::record_batch::RecordBatch;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::TryStreamExt;
+use std::io::Cursor;
+
+/// Leading magic bytes of the Arrow IPC file format
+const ARROW_FILE_MAGIC: &[u8] = b"ARROW1";
+
+type BatchIter = Box<dyn Iterator<Item = Result<RecordBatch>> + Send>;
+
+/// Arrow IPC handler
+///
+/// Exports the IPC file format; imports both the file and the streaming
+/// format. IPC files carry no statistics, so import predicates are applied
+/// to each batch before it is decoded into records.
+pub struct ArrowIpcHandler;
+
+impl ArrowIpcHandler {
+    fn batches(data: Bytes) -> Result<BatchIter> {
+        let batches: BatchIter = if data.starts_with(ARROW_FILE_MAGIC) {
+            let reader = FileReader::try_new(Cursor::new(data), None)?;
+            Box::new(reader.map(|batch| batch.map_err(TransferError::from)))
+        } else {
+            let reader = StreamReader::try_new(Cursor::new(data), None)?;
+            Box::new(reader.map(|batch| batch.map_err(TransferError::from)))
+        };
+
+        Ok(batches)
+    }
+}
+
+#[async_trait]
+impl ImportHandler for ArrowIpcHandler {
+    async fn import(
+        &self,
+        data: Bytes,
+        config: &TransferConfig,
+        tracker: Option<ProgressTracker>,
+    ) -> Result<Vec<DataRecord>> {
+        self.import_stream(data, config, tracker)
+            .await?
+            .try_collect()
+            .await
+    }
+
+    async fn import_stream(
+        &self,
+        data: Bytes,
+        config: &TransferConfig,
+        tracker: Option<ProgressTracker>,
+    ) -> Result<DataStream> {
+        if let Some(ref t) = tracker {
+            t.start().await;
+        }
+
+        let predicates = config.import_predicates.clone();
+        let batches = Self::batches(data)?
+            .map(move |batch| batch.and_then(|batch| filter_batch(batch, &predicates)));
+        Ok(record_stream(batches, tracker))
+    }
+
+    async fn validate(&self, data: Bytes, _config: &TransferConfig) -> Result<()> {
+        Self::batches(data)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ExportHandler for ArrowIpcHandler {
+    async fn export(
+        &self,
+        records: Vec<DataRecord>,
+        config: &TransferConfig,
+        tracker: Option<ProgressTracker>,
+    ) -> Result<Bytes> {
+        if let Some(ref t) = tracker {
+            t.start().await;
+        }
+
+        if records.is_empty() {
+            return Ok(Bytes::new());
+        }
+
+        let schema = infer_schema(&records);
+        let mut buffer = Vec::new();
+        let mut writer = FileWriter::try_new(&mut buffer, &schema)?;
+
+        let mut written = 0;
+        for chunk in records.chunks(config.columnar_batch_size) {
+            if let Some(ref t) = tracker {
+                if t.is_cancelled().await {
+                    return Err(TransferError::Cancelled);
+                }
+                t.update(written as u64, Some("Writing Arrow record batches".to_string())).await;
+            }
+
+            writer.write(&records_to_batch(&schema, chunk)?)?;
+            written += chunk.len();
+        }
+
+        writer.finish()?;
+        drop(writer);
+
+        if let Some(ref t) = tracker {
+            t.complete().await;
+        }
+
+        Ok(Bytes::from(buffer))
+    }
+
+    async fn export_stream(
+        &self,
+        records: Vec<DataRecord>,
+        config: &TransferConfig,
+        tracker: Option<ProgressTracker>,
+    ) -> Result<Bytes> {
+        // Records are already written one batch at a time
+        self.export(records, config, tracker).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ImportPredicate, PredicateOp};
+    use serde_json::Value;
+
+    #[tokio::test]
+    async fn test_arrow_export_import_with_predicate() {
+        let records: Vec<DataRecord> = (0..10)
+            .map(|i| {
+                let mut record = DataRecord::new();
+                record.set("id".to_string(), Value::from(i));
+                record.set("damaged".to_string(), Value::Bool(i % 3 == 0));
+                record
+            })
+            .collect();
+
+        let handler = ArrowIpcHandler;
+        let config = TransferConfig::default().with_columnar_batch_size(4);
+        let exported = handler.export(records, &config, None).await.unwrap();
+        assert!(exported.starts_with(ARROW_FILE_MAGIC));
+
+        let imported = handler.import(exported.clone(), &config, None).await.unwrap();
+        assert_eq!(imported.len(), 10);
+
+        let config = config.with_import_predicate(ImportPredicate::new(
+            "damaged",
+            PredicateOp::Eq,
+            Value::Bool(true),
+        ));
+        let imported = handler.import(exported, &config, None).await.unwrap();
+        let ids: Vec<i64> = imported
+            .iter()
+            .map(|record| record.get("id").and_then(Value::as_i64).unwrap())
+            .collect();
+        assert_eq!(ids, vec![0, 3, 6, 9]);
+    }
+}
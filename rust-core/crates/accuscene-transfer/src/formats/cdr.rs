@@ -0,0 +1,301 @@
+use crate::{
+    config::TransferConfig,
+    error::{Result, TransferError},
+    formats::{DataStream, ImportHandler},
+    mapping::{DataType, FieldMapping, MappingProfile, Transform},
+    progress::ProgressTracker,
+    DataRecord,
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream;
+use serde_json::Value;
+use std::io::Cursor;
+
+/// Miles per hour to meters per second
+const MPH_TO_MPS: f64 = 0.44704;
+
+/// Kilometers per hour to meters per second
+const KPH_TO_MPS: f64 = 1.0 / 3.6;
+
+/// Field naming the record type (`cdr_event` or `cdr_sample`)
+pub const RECORD_TYPE_FIELD: &str = "record_type";
+
+/// Field naming the table a sample was read from
+pub const SECTION_FIELD: &str = "section";
+
+/// Bosch CDR (Crash Data Retrieval) CSV export importer
+///
+/// CDR exports are a sequence of blank-line separated blocks: key/value
+/// rows describing the event (`Case Number`, `VIN`, ...) and titled tables
+/// of time series such as pre-crash speed or the crash pulse. The event
+/// data becomes one `cdr_event` record; every table row becomes a
+/// `cdr_sample` record carrying its table title in `section` plus the
+/// event's case number and VIN.
+pub struct CdrHandler {
+    profile: Option<MappingProfile>,
+}
+
+impl CdrHandler {
+    /// Create handler mapping records with the default [`profile`]
+    pub fn new() -> Self {
+        Self {
+            profile: Some(profile()),
+        }
+    }
+
+    /// Create handler returning the export fields unmapped
+    pub fn raw() -> Self {
+        Self { profile: None }
+    }
+
+    /// Use a custom mapping profile
+    pub fn with_profile(mut self, profile: MappingProfile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+}
+
+impl Default for CdrHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Default mapping of CDR columns onto AccuScene fields
+///
+/// Speeds and delta-V are converted to meters per second and times to
+/// seconds.
+pub fn profile() -> MappingProfile {
+    let scaled = |source: &str, target: &str, factor: f64| {
+        FieldMapping::typed(source, target, DataType::Float)
+            .with_transform(Transform::Scale { factor })
+    };
+
+    let mut profile = MappingProfile::new("bosch_cdr".to_string())
+        .add_mapping(FieldMapping::typed(RECORD_TYPE_FIELD, RECORD_TYPE_FIELD, DataType::String).required())
+        .add_mapping(FieldMapping::typed(SECTION_FIELD, SECTION_FIELD, DataType::String))
+        // Event
+        .add_mapping(FieldMapping::typed("Case Number", "case_number", DataType::String))
+        .add_mapping(FieldMapping::typed("VIN", "vin", DataType::String))
+        .add_mapping(FieldMapping::typed("Event Record", "event_record", DataType::String))
+        .add_mapping(FieldMapping::typed("Event Type", "event_type", DataType::String))
+        .add_mapping(FieldMapping::typed("Ignition Cycle, Crash", "ignition_cycle", DataType::Integer))
+        .add_mapping(FieldMapping::typed("Recording Status", "recording_status", DataType::String))
+        .add_mapping(FieldMapping::typed("Belt Switch Circuit Status, Driver", "driver_belted", DataType::String))
+        .add_mapping(scaled("Maximum Delta-V, Longitudinal (MPH)", "max_delta_v_longitudinal_mps", MPH_TO_MPS))
+        .add_mapping(scaled("Maximum Delta-V, Lateral (MPH)", "max_delta_v_lateral_mps", MPH_TO_MPS))
+        // Samples
+        .add_mapping(FieldMapping::typed("Time (sec)", "time_s", DataType::Float))
+        .add_mapping(FieldMapping::typed("Times (sec)", "time_s", DataType::Float))
+        .add_mapping(scaled("Time (msec)", "time_s", 0.001))
+        .add_mapping(scaled("Speed, Vehicle Indicated (MPH)", "speed_mps", MPH_TO_MPS))
+        .add_mapping(scaled("Speed, Vehicle Indicated (km/h)", "speed_mps", KPH_TO_MPS))
+        .add_mapping(FieldMapping::typed("Accelerator Pedal, % Full", "throttle_percent", DataType::Float))
+        .add_mapping(FieldMapping::typed("Service Brake", "brake_applied", DataType::Boolean))
+        .add_mapping(FieldMapping::typed("Engine RPM", "engine_rpm", DataType::Float))
+        .add_mapping(FieldMapping::typed("Steering Input (degrees)", "steering_angle_deg", DataType::Float))
+        .add_mapping(scaled("Longitudinal Cumulative Delta-V (MPH)", "delta_v_longitudinal_mps", MPH_TO_MPS))
+        .add_mapping(scaled("Lateral Cumulative Delta-V (MPH)", "delta_v_lateral_mps", MPH_TO_MPS))
+        .add_mapping(scaled("Longitudinal Cumulative Delta-V (km/h)", "delta_v_longitudinal_mps", KPH_TO_MPS))
+        .add_mapping(scaled("Lateral Cumulative Delta-V (km/h)", "delta_v_lateral_mps", KPH_TO_MPS));
+
+    profile.description = Some("Bosch CDR tool CSV export".to_string());
+    profile
+}
+
+/// Parse a CDR CSV export into raw records
+pub fn parse_cdr(data: &[u8]) -> Result<Vec<DataRecord>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(Cursor::new(data));
+
+    let mut event = DataRecord::new();
+    event.set(RECORD_TYPE_FIELD.to_string(), Value::String("cdr_event".to_string()));
+    let mut samples = Vec::new();
+
+    let mut title: Option<String> = None;
+    let mut headers: Option<Vec<String>> = None;
+
+    for row in reader.records() {
+        let row = row?;
+        let cells: Vec<&str> = row.iter().collect();
+        let filled: Vec<&str> = cells.iter().copied().filter(|c| !c.is_empty()).collect();
+
+        // A blank row ends the current block
+        if filled.is_empty() {
+            title = None;
+            headers = None;
+            continue;
+        }
+
+        if let Some(ref headers) = headers {
+            if is_numeric(cells[0]) {
+                let mut sample = DataRecord::new();
+                sample.set(RECORD_TYPE_FIELD.to_string(), Value::String("cdr_sample".to_string()));
+                if let Some(ref title) = title {
+                    sample.set(SECTION_FIELD.to_string(), Value::String(title.clone()));
+                }
+                for (header, cell) in headers.iter().zip(&cells) {
+                    if !header.is_empty() && !cell.is_empty() {
+                        sample.set(header.clone(), parse_cell(cell));
+                    }
+                }
+                samples.push(sample);
+                continue;
+            }
+        }
+
+        if filled.len() == 1 && headers.is_none() {
+            // Table title
+            title = Some(filled[0].to_string());
+        } else if filled.len() == 2
+            && title.is_none()
+            && headers.is_none()
+            && !filled[0].starts_with("Time")
+        {
+            // Event values stay text, identifiers may have leading zeros
+            event.set(filled[0].to_string(), Value::String(filled[1].to_string()));
+        } else {
+            // Table header row
+            headers = Some(cells.iter().map(|c| c.to_string()).collect());
+        }
+    }
+
+    // Samples carry the event keys so they can be joined after import
+    for key in ["Case Number", "VIN"] {
+        if let Some(value) = event.get(key).cloned() {
+            for sample in &mut samples {
+                sample.set(key.to_string(), value.clone());
+            }
+        }
+    }
+
+    let mut records = Vec::with_capacity(samples.len() + 1);
+    records.push(event);
+    records.extend(samples);
+    Ok(records)
+}
+
+fn is_numeric(cell: &str) -> bool {
+    cell.parse::<f64>().is_ok()
+}
+
+/// Parse numeric cells, keep everything else as text
+fn parse_cell(cell: &str) -> Value {
+    if let Ok(num) = cell.parse::<i64>() {
+        return Value::Number(num.into());
+    }
+    cell.parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+        .map(Value::Number)
+        .unwrap_or_else(|| Value::String(cell.to_string()))
+}
+
+#[async_trait]
+impl ImportHandler for CdrHandler {
+    async fn import(
+        &self,
+        data: Bytes,
+        config: &TransferConfig,
+        tracker: Option<ProgressTracker>,
+    ) -> Result<Vec<DataRecord>> {
+        if let Some(ref t) = tracker {
+            t.start().await;
+        }
+
+        let records = parse_cdr(&data)?;
+        if let Some(ref t) = tracker {
+            t.update(records.len() as u64, Some("Mapping CDR records".to_string()))
+                .await;
+        }
+
+        let records = match self.profile {
+            Some(ref profile) => profile.apply_with_config(records, config)?,
+            None => records,
+        };
+
+        if let Some(ref t) = tracker {
+            t.complete().await;
+        }
+
+        Ok(records)
+    }
+
+    async fn import_stream(
+        &self,
+        data: Bytes,
+        config: &TransferConfig,
+        tracker: Option<ProgressTracker>,
+    ) -> Result<DataStream> {
+        let records = self.import(data, config, tracker).await?;
+        Ok(Box::pin(stream::iter(records.into_iter().map(Ok))))
+    }
+
+    async fn validate(&self, data: Bytes, _config: &TransferConfig) -> Result<()> {
+        let records = parse_cdr(&data)?;
+        if records.len() < 2 {
+            return Err(TransferError::Validation(
+                "CDR export contains no data tables".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = "\
+Case Number,2024-000123
+VIN,1HGCM82633A004352
+Event Record,Most Recent Event
+
+Pre-Crash Data -5 to 0 sec (2 samples/sec)
+Times (sec),\"Speed, Vehicle Indicated (MPH)\",\"Accelerator Pedal, % Full\",Service Brake,Engine RPM
+-1.0,45,20,Off,2100
+-0.5,44,0,On,2000
+0.0,38,0,On,1800
+
+Longitudinal Crash Pulse
+Time (msec),Longitudinal Cumulative Delta-V (MPH)
+0,0
+10,-2.5
+";
+
+    #[test]
+    fn test_parse_cdr() {
+        let records = parse_cdr(EXPORT.as_bytes()).unwrap();
+        assert_eq!(records.len(), 6);
+        assert_eq!(records[0].get("VIN"), Some(&Value::from("1HGCM82633A004352")));
+
+        let sample = &records[1];
+        assert_eq!(sample.get(RECORD_TYPE_FIELD), Some(&Value::from("cdr_sample")));
+        assert_eq!(
+            sample.get(SECTION_FIELD),
+            Some(&Value::from("Pre-Crash Data -5 to 0 sec (2 samples/sec)"))
+        );
+        assert_eq!(sample.get("Speed, Vehicle Indicated (MPH)"), Some(&Value::from(45)));
+        assert_eq!(sample.get("Case Number"), Some(&Value::from("2024-000123")));
+        assert_eq!(records[5].get("Longitudinal Cumulative Delta-V (MPH)"), Some(&Value::from(-2.5)));
+    }
+
+    #[tokio::test]
+    async fn test_import_with_profile() {
+        let handler = CdrHandler::new();
+        let records = handler
+            .import(Bytes::from_static(EXPORT.as_bytes()), &TransferConfig::default(), None)
+            .await
+            .unwrap();
+
+        assert_eq!(records[0].get("case_number"), Some(&Value::from("2024-000123")));
+        assert_eq!(records[1].get("time_s"), Some(&Value::from(-1.0)));
+        assert_eq!(records[1].get("speed_mps"), Some(&Value::from(45.0 * MPH_TO_MPS)));
+        assert_eq!(records[2].get("brake_applied"), Some(&Value::Bool(true)));
+        assert_eq!(records[5].get("time_s"), Some(&Value::from(0.01)));
+    }
+}
@@ -0,0 +1,405 @@
+//! Shared record batch conversion for the columnar formats
+//!
+//! Parquet and Arrow IPC both move data as Arrow record batches. Schemas are
+//! inferred from the JSON types of the record fields; nested arrays and
+//! objects are stored as JSON strings.
+
+use crate::{
+    config::ImportPredicate,
+    error::{Result, TransferError},
+    formats::DataStream,
+    progress::ProgressTracker,
+    DataRecord,
+};
+use arrow::array::{
+    Array, ArrayRef, BooleanArray, BooleanBuilder, Float64Array, Float64Builder, Int64Array,
+    Int64Builder, LargeStringArray, StringArray, StringBuilder, TimestampMicrosecondArray,
+    TimestampMicrosecondBuilder,
+};
+use arrow::compute::{cast, filter_record_batch};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use arrow::util::display::{ArrayFormatter, FormatOptions};
+use chrono::{DateTime, SecondsFormat};
+use futures::stream::{self, StreamExt};
+use serde_json::{Number, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Timezone of exported timestamp columns
+const TIMESTAMP_TIMEZONE: &str = "UTC";
+
+/// Column type inferred from record values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    Boolean,
+    Int64,
+    Float64,
+    Timestamp,
+    Utf8,
+}
+
+impl ColumnType {
+    fn of(value: &Value) -> Option<Self> {
+        match value {
+            Value::Null => None,
+            Value::Bool(_) => Some(Self::Boolean),
+            Value::Number(n) if n.is_i64() => Some(Self::Int64),
+            Value::Number(_) => Some(Self::Float64),
+            Value::String(s) if DateTime::parse_from_rfc3339(s).is_ok() => Some(Self::Timestamp),
+            _ => Some(Self::Utf8),
+        }
+    }
+
+    /// Widen two column types to one holding both
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Self::Int64, Self::Float64) | (Self::Float64, Self::Int64) => Self::Float64,
+            _ => Self::Utf8,
+        }
+    }
+
+    fn data_type(self) -> DataType {
+        match self {
+            Self::Boolean => DataType::Boolean,
+            Self::Int64 => DataType::Int64,
+            Self::Float64 => DataType::Float64,
+            Self::Timestamp => {
+                DataType::Timestamp(TimeUnit::Microsecond, Some(TIMESTAMP_TIMEZONE.into()))
+            },
+            Self::Utf8 => DataType::Utf8,
+        }
+    }
+}
+
+/// Infer an Arrow schema from record field types
+///
+/// Fields are sorted by name and always nullable. Integers mixed with
+/// floats widen to `Float64`, RFC 3339 strings become UTC timestamps and
+/// any other mix of types falls back to `Utf8`.
+pub fn infer_schema(records: &[DataRecord]) -> SchemaRef {
+    let mut columns: BTreeMap<&str, Option<ColumnType>> = BTreeMap::new();
+
+    for record in records {
+        for (name, value) in &record.fields {
+            let column = columns.entry(name.as_str()).or_insert(None);
+            if let Some(value_type) = ColumnType::of(value) {
+                *column = Some(column.map_or(value_type, |t| t.merge(value_type)));
+            }
+        }
+    }
+
+    let fields: Vec<Field> = columns
+        .into_iter()
+        .map(|(name, column)| {
+            Field::new(name, column.unwrap_or(ColumnType::Utf8).data_type(), true)
+        })
+        .collect();
+
+    Arc::new(Schema::new(fields))
+}
+
+/// Build a record batch from records using an inferred schema
+pub fn records_to_batch(schema: &SchemaRef, records: &[DataRecord]) -> Result<RecordBatch> {
+    let columns = schema
+        .fields()
+        .iter()
+        .map(|field| build_column(field.name(), field.data_type(), records))
+        .collect::<Vec<_>>();
+
+    Ok(RecordBatch::try_new(schema.clone(), columns)?)
+}
+
+fn build_column(name: &str, data_type: &DataType, records: &[DataRecord]) -> ArrayRef {
+    let values = records.iter().map(|record| record.get(name));
+
+    match data_type {
+        DataType::Boolean => {
+            let mut builder = BooleanBuilder::with_capacity(records.len());
+            for value in values {
+                builder.append_option(value.and_then(Value::as_bool));
+            }
+            Arc::new(builder.finish())
+        },
+        DataType::Int64 => {
+            let mut builder = Int64Builder::with_capacity(records.len());
+            for value in values {
+                builder.append_option(value.and_then(Value::as_i64));
+            }
+            Arc::new(builder.finish())
+        },
+        DataType::Float64 => {
+            let mut builder = Float64Builder::with_capacity(records.len());
+            for value in values {
+                builder.append_option(value.and_then(Value::as_f64));
+            }
+            Arc::new(builder.finish())
+        },
+        DataType::Timestamp(_, _) => {
+            let mut builder = TimestampMicrosecondBuilder::with_capacity(records.len())
+                .with_timezone(TIMESTAMP_TIMEZONE);
+            for value in values {
+                builder.append_option(
+                    value
+                        .and_then(Value::as_str)
+                        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+                        .map(|timestamp| timestamp.timestamp_micros()),
+                );
+            }
+            Arc::new(builder.finish())
+        },
+        _ => {
+            let mut builder = StringBuilder::with_capacity(records.len(), records.len() * 16);
+            for value in values {
+                match value {
+                    None | Some(Value::Null) => builder.append_null(),
+                    Some(Value::String(s)) => builder.append_value(s),
+                    Some(other) => builder.append_value(other.to_string()),
+                }
+            }
+            Arc::new(builder.finish())
+        },
+    }
+}
+
+/// Convert a record batch back into records
+///
+/// Null cells are left out of the record.
+pub fn batch_to_records(batch: &RecordBatch) -> Result<Vec<DataRecord>> {
+    let schema = batch.schema();
+    let mut records = vec![DataRecord::new(); batch.num_rows()];
+
+    for (field, column) in schema.fields().iter().zip(batch.columns()) {
+        for (record, value) in records.iter_mut().zip(column_values(column)?) {
+            if !value.is_null() {
+                record.set(field.name().clone(), value);
+            }
+        }
+    }
+
+    Ok(records)
+}
+
+/// Decode an Arrow column into JSON values
+///
+/// Integer and float columns of any width decode to numbers, timestamps to
+/// RFC 3339 strings; other types use their display form.
+pub fn column_values(column: &ArrayRef) -> Result<Vec<Value>> {
+    let values = match column.data_type() {
+        DataType::Null => vec![Value::Null; column.len()],
+        DataType::Boolean => downcast::<BooleanArray>(column)
+            .iter()
+            .map(|v| v.map_or(Value::Null, Value::Bool))
+            .collect(),
+        DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::UInt64 => {
+            let column = cast(column, &DataType::Int64)?;
+            downcast::<Int64Array>(&column)
+                .iter()
+                .map(|v| v.map_or(Value::Null, |v| Value::Number(v.into())))
+                .collect()
+        },
+        DataType::Float16 | DataType::Float32 | DataType::Float64 => {
+            let column = cast(column, &DataType::Float64)?;
+            downcast::<Float64Array>(&column)
+                .iter()
+                .map(|v| v.and_then(Number::from_f64).map_or(Value::Null, Value::Number))
+                .collect()
+        },
+        DataType::Utf8 => downcast::<StringArray>(column)
+            .iter()
+            .map(|v| v.map_or(Value::Null, |s| Value::String(s.to_string())))
+            .collect(),
+        DataType::LargeUtf8 => downcast::<LargeStringArray>(column)
+            .iter()
+            .map(|v| v.map_or(Value::Null, |s| Value::String(s.to_string())))
+            .collect(),
+        DataType::Timestamp(_, timezone) => {
+            let column = cast(
+                column,
+                &DataType::Timestamp(TimeUnit::Microsecond, timezone.clone()),
+            )?;
+            downcast::<TimestampMicrosecondArray>(&column)
+                .iter()
+                .map(|v| {
+                    v.and_then(DateTime::from_timestamp_micros).map_or(Value::Null, |timestamp| {
+                        Value::String(timestamp.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+                    })
+                })
+                .collect()
+        },
+        _ => {
+            let options = FormatOptions::default();
+            let formatter = ArrayFormatter::try_new(column.as_ref(), &options)?;
+            (0..column.len())
+                .map(|i| {
+                    if column.is_null(i) {
+                        Value::Null
+                    } else {
+                        Value::String(formatter.value(i).to_string())
+                    }
+                })
+                .collect()
+        },
+    };
+
+    Ok(values)
+}
+
+/// Evaluate predicates against a batch, one flag per row
+///
+/// Predicates on columns missing from the batch only match null checks.
+pub fn predicate_mask(batch: &RecordBatch, predicates: &[ImportPredicate]) -> Result<BooleanArray> {
+    let mut mask = vec![true; batch.num_rows()];
+
+    for predicate in predicates {
+        match batch.column_by_name(&predicate.field) {
+            Some(column) => {
+                for (keep, value) in mask.iter_mut().zip(column_values(column)?) {
+                    *keep = *keep && predicate.matches(Some(&value));
+                }
+            },
+            None => {
+                if !predicate.matches(None) {
+                    mask.iter_mut().for_each(|keep| *keep = false);
+                }
+            },
+        }
+    }
+
+    Ok(BooleanArray::from(mask))
+}
+
+/// Drop the rows of a batch not matching the predicates
+pub fn filter_batch(batch: RecordBatch, predicates: &[ImportPredicate]) -> Result<RecordBatch> {
+    if predicates.is_empty() {
+        return Ok(batch);
+    }
+
+    let mask = predicate_mask(&batch, predicates)?;
+    Ok(filter_record_batch(&batch, &mask)?)
+}
+
+/// Stream the records of decoded batches, one batch at a time
+///
+/// The tracker counts imported rows and is completed once the batches are
+/// exhausted. Decoding stops after the first error or on cancellation.
+pub fn record_stream<I>(batches: I, tracker: Option<ProgressTracker>) -> DataStream
+where
+    I: Iterator<Item = Result<RecordBatch>> + Send + 'static,
+{
+    let records = stream::unfold(
+        (batches, tracker, 0u64, false),
+        |(mut batches, tracker, processed, done)| async move {
+            if done {
+                return None;
+            }
+
+            if let Some(ref t) = tracker {
+                if t.is_cancelled().await {
+                    return Some((
+                        vec![Err(TransferError::Cancelled)],
+                        (batches, tracker, processed, true),
+                    ));
+                }
+            }
+
+            let batch = match batches.next() {
+                Some(batch) => batch,
+                None => {
+                    if let Some(ref t) = tracker {
+                        t.complete().await;
+                    }
+                    return None;
+                },
+            };
+
+            match batch.and_then(|batch| batch_to_records(&batch)) {
+                Ok(records) => {
+                    let processed = processed + records.len() as u64;
+                    if let Some(ref t) = tracker {
+                        t.update(processed, Some("Reading record batches".to_string())).await;
+                    }
+                    let records = records.into_iter().map(Ok).collect::<Vec<_>>();
+                    Some((records, (batches, tracker, processed, false)))
+                },
+                Err(e) => Some((vec![Err(e)], (batches, tracker, processed, true))),
+            }
+        },
+    );
+
+    Box::pin(records.flat_map(stream::iter))
+}
+
+fn downcast<T: 'static>(column: &ArrayRef) -> &T {
+    column
+        .as_any()
+        .downcast_ref::<T>()
+        .expect("column type checked against its data type")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PredicateOp;
+
+    #[test]
+    fn test_infer_schema() {
+        let mut first = DataRecord::new();
+        first.set("speed".to_string(), Value::from(42));
+        first.set(
+            "reported_at".to_string(),
+            Value::from("2024-03-01T10:15:00Z"),
+        );
+        first.set("notes".to_string(), Value::Null);
+        let mut second = DataRecord::new();
+        second.set("speed".to_string(), Value::from(37.5));
+        second.set("reported_at".to_string(), Value::from("unknown"));
+
+        let schema = infer_schema(&[first, second]);
+        assert_eq!(
+            schema.field_with_name("speed").unwrap().data_type(),
+            &DataType::Float64
+        );
+        assert_eq!(
+            schema.field_with_name("reported_at").unwrap().data_type(),
+            &DataType::Utf8
+        );
+        assert_eq!(
+            schema.field_with_name("notes").unwrap().data_type(),
+            &DataType::Utf8
+        );
+    }
+
+    #[test]
+    fn test_batch_round_trip_and_mask() {
+        let records: Vec<DataRecord> = (0..4)
+            .map(|i| {
+                let mut record = DataRecord::new();
+                record.set("id".to_string(), Value::from(i));
+                record.set("at".to_string(), Value::from("2024-03-01T10:15:00Z"));
+                record
+            })
+            .collect();
+
+        let schema = infer_schema(&records);
+        let batch = records_to_batch(&schema, &records).unwrap();
+        let decoded = batch_to_records(&batch).unwrap();
+        assert_eq!(decoded[2].get("id"), Some(&Value::from(2)));
+        assert_eq!(
+            decoded[2].get("at"),
+            Some(&Value::from("2024-03-01T10:15:00Z"))
+        );
+
+        let predicate = ImportPredicate::new("id", PredicateOp::GtEq, Value::from(2));
+        let mask = predicate_mask(&batch, &[predicate]).unwrap();
+        assert_eq!(mask, BooleanArray::from(vec![false, false, true, true]));
+    }
+}
@@ -0,0 +1,438 @@
+use crate::{
+    config::TransferConfig,
+    error::{Result, TransferError},
+    formats::{DataStream, ImportHandler},
+    mapping::{DataType, FieldMapping, MappingProfile, Transform},
+    progress::ProgressTracker,
+    DataRecord,
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde_json::Value;
+
+/// Miles per hour to meters per second
+const MPH_TO_MPS: f64 = 0.44704;
+
+/// Field naming the record type (`crash`, `vehicle` or `person`)
+pub const RECORD_TYPE_FIELD: &str = "record_type";
+
+/// NHTSA-style crash report XML importer
+///
+/// Reads `Crash`, `Vehicle` and `Person` (or `NonMotorist`) elements into one
+/// record each, in document order. Leaf elements and attributes become
+/// fields holding their text. Vehicles and persons inherit the
+/// `CrashIdentifier` of their crash, and persons the `UnitNumber` of their
+/// vehicle, so the records can be joined after import.
+pub struct CrashReportHandler {
+    profile: Option<MappingProfile>,
+}
+
+impl CrashReportHandler {
+    /// Create handler mapping records with the default [`profile`]
+    pub fn new() -> Self {
+        Self {
+            profile: Some(profile()),
+        }
+    }
+
+    /// Create handler returning the report fields unmapped
+    pub fn raw() -> Self {
+        Self { profile: None }
+    }
+
+    /// Use a custom mapping profile
+    pub fn with_profile(mut self, profile: MappingProfile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+}
+
+impl Default for CrashReportHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Default mapping of crash report fields onto AccuScene fields
+///
+/// Speeds are reported in miles per hour and converted to meters per second.
+pub fn profile() -> MappingProfile {
+    let speed = |source: &str, target: &str| {
+        FieldMapping::typed(source, target, DataType::Float)
+            .with_transform(Transform::Scale { factor: MPH_TO_MPS })
+    };
+
+    let mut profile = MappingProfile::new("nhtsa_crash_report".to_string())
+        .add_mapping(
+            FieldMapping::typed(RECORD_TYPE_FIELD, RECORD_TYPE_FIELD, DataType::String).required(),
+        )
+        // Crash
+        .add_mapping(FieldMapping::typed(
+            "CrashIdentifier",
+            "case_number",
+            DataType::String,
+        ))
+        .add_mapping(FieldMapping::typed(
+            "CrashDate",
+            "crash_date",
+            DataType::Date,
+        ))
+        .add_mapping(FieldMapping::typed(
+            "CrashTime",
+            "crash_time",
+            DataType::Time,
+        ))
+        .add_mapping(FieldMapping::typed("County", "county", DataType::String))
+        .add_mapping(FieldMapping::typed("City", "city", DataType::String))
+        .add_mapping(FieldMapping::typed("Latitude", "latitude", DataType::Float))
+        .add_mapping(FieldMapping::typed(
+            "Longitude",
+            "longitude",
+            DataType::Float,
+        ))
+        .add_mapping(FieldMapping::typed(
+            "MannerOfCollision",
+            "collision_type",
+            DataType::String,
+        ))
+        .add_mapping(FieldMapping::typed(
+            "CrashSeverity",
+            "severity",
+            DataType::String,
+        ))
+        .add_mapping(FieldMapping::typed(
+            "WeatherCondition",
+            "weather",
+            DataType::String,
+        ))
+        .add_mapping(FieldMapping::typed(
+            "LightCondition",
+            "lighting",
+            DataType::String,
+        ))
+        .add_mapping(FieldMapping::typed(
+            "RoadSurfaceCondition",
+            "road_surface",
+            DataType::String,
+        ))
+        // Vehicle
+        .add_mapping(FieldMapping::typed(
+            "UnitNumber",
+            "unit_number",
+            DataType::Integer,
+        ))
+        .add_mapping(FieldMapping::typed("VIN", "vin", DataType::String))
+        .add_mapping(FieldMapping::typed("Make", "make", DataType::String))
+        .add_mapping(FieldMapping::typed("Model", "model", DataType::String))
+        .add_mapping(FieldMapping::typed("ModelYear", "year", DataType::Integer))
+        .add_mapping(FieldMapping::typed(
+            "BodyType",
+            "vehicle_type",
+            DataType::String,
+        ))
+        .add_mapping(FieldMapping::typed(
+            "DirectionOfTravel",
+            "direction",
+            DataType::String,
+        ))
+        .add_mapping(FieldMapping::typed(
+            "InitialPointOfContact",
+            "impact_point",
+            DataType::String,
+        ))
+        .add_mapping(FieldMapping::typed(
+            "DamageExtent",
+            "damage_severity",
+            DataType::String,
+        ))
+        .add_mapping(speed("TravelSpeed", "speed_mps"))
+        .add_mapping(speed("PostedSpeedLimit", "speed_limit_mps"))
+        // Person
+        .add_mapping(FieldMapping::typed(
+            "PersonNumber",
+            "person_number",
+            DataType::Integer,
+        ))
+        .add_mapping(FieldMapping::typed(
+            "PersonType",
+            "person_type",
+            DataType::String,
+        ))
+        .add_mapping(FieldMapping::typed(
+            "SeatingPosition",
+            "seating_position",
+            DataType::String,
+        ))
+        .add_mapping(FieldMapping::typed("Age", "age", DataType::Integer))
+        .add_mapping(FieldMapping::typed("Sex", "sex", DataType::String))
+        .add_mapping(FieldMapping::typed(
+            "InjuryStatus",
+            "injury_severity",
+            DataType::String,
+        ))
+        .add_mapping(FieldMapping::typed(
+            "SafetyEquipment",
+            "safety_equipment",
+            DataType::String,
+        ));
+
+    profile.description = Some("NHTSA-style crash report (MMUCC element names)".to_string());
+    profile
+}
+
+/// Kind of record an element starts
+fn record_kind(name: &str) -> Option<&'static str> {
+    match name.to_ascii_lowercase().as_str() {
+        "crash" => Some("crash"),
+        "vehicle" | "motorvehicle" => Some("vehicle"),
+        "person" | "nonmotorist" => Some("person"),
+        _ => None,
+    }
+}
+
+/// Record element being read
+struct OpenRecord {
+    kind: &'static str,
+    index: usize,
+    descendants: Vec<usize>,
+}
+
+/// Parse a crash report into raw records
+pub fn parse_crash_report(data: &[u8]) -> Result<Vec<DataRecord>> {
+    let mut reader = Reader::from_reader(data);
+    reader.trim_text(true);
+
+    let mut records: Vec<DataRecord> = Vec::new();
+    let mut open: Vec<OpenRecord> = Vec::new();
+    let mut field: Option<String> = None;
+    let mut text = String::new();
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) => {
+                let name = local_name(&e);
+                if let Some(kind) = record_kind(&name) {
+                    let mut record = DataRecord::new();
+                    record.set(
+                        RECORD_TYPE_FIELD.to_string(),
+                        Value::String(kind.to_string()),
+                    );
+                    set_attributes(&mut record, &e, &reader)?;
+                    records.push(record);
+                    open.push(OpenRecord {
+                        kind,
+                        index: records.len() - 1,
+                        descendants: Vec::new(),
+                    });
+                    field = None;
+                } else if let Some(current) = open.last() {
+                    // Attributes of grouping elements like <Location> belong to the record
+                    set_attributes(&mut records[current.index], &e, &reader)?;
+                    field = Some(name);
+                    text.clear();
+                }
+            },
+            Event::Empty(e) => {
+                if let Some(current) = open.last() {
+                    set_attributes(&mut records[current.index], &e, &reader)?;
+                }
+            },
+            Event::Text(e) if field.is_some() => {
+                text.push_str(&e.unescape()?);
+            },
+            Event::CData(e) if field.is_some() => {
+                text.push_str(&String::from_utf8_lossy(&e));
+            },
+            Event::End(e) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                if record_kind(&name).is_some() {
+                    if let Some(closed) = open.pop() {
+                        inherit_keys(&mut records, &closed);
+                        if let Some(parent) = open.last_mut() {
+                            parent.descendants.push(closed.index);
+                            parent.descendants.extend(closed.descendants);
+                        }
+                    }
+                    field = None;
+                } else if field.as_deref() == Some(name.as_str()) {
+                    if let (Some(current), Some(name)) = (open.last(), field.take()) {
+                        if !text.is_empty() {
+                            records[current.index].set(name, Value::String(text.clone()));
+                        }
+                    }
+                    text.clear();
+                }
+            },
+            Event::Eof => break,
+            _ => {},
+        }
+        buf.clear();
+    }
+
+    if !open.is_empty() {
+        return Err(TransferError::Xml(
+            "Unclosed crash report element".to_string(),
+        ));
+    }
+
+    Ok(records)
+}
+
+/// Copy the identifying key of a closed record into its descendants
+fn inherit_keys(records: &mut [DataRecord], closed: &OpenRecord) {
+    let key = match closed.kind {
+        "crash" => "CrashIdentifier",
+        "vehicle" => "UnitNumber",
+        _ => return,
+    };
+
+    if let Some(value) = records[closed.index].get(key).cloned() {
+        for &index in &closed.descendants {
+            if records[index].get(key).is_none() {
+                records[index].set(key.to_string(), value.clone());
+            }
+        }
+    }
+}
+
+fn local_name(e: &BytesStart) -> String {
+    String::from_utf8_lossy(e.local_name().as_ref()).to_string()
+}
+
+fn set_attributes(record: &mut DataRecord, e: &BytesStart, reader: &Reader<&[u8]>) -> Result<()> {
+    for attribute in e.attributes() {
+        let attribute = attribute.map_err(|e| TransferError::Xml(e.to_string()))?;
+        let key = String::from_utf8_lossy(attribute.key.local_name().as_ref()).to_string();
+        if key == "xmlns" || attribute.key.as_ref().starts_with(b"xmlns:") {
+            continue;
+        }
+        let value = attribute.decode_and_unescape_value(reader)?;
+        record.set(key, Value::String(value.to_string()));
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl ImportHandler for CrashReportHandler {
+    async fn import(
+        &self,
+        data: Bytes,
+        config: &TransferConfig,
+        tracker: Option<ProgressTracker>,
+    ) -> Result<Vec<DataRecord>> {
+        if let Some(ref t) = tracker {
+            t.start().await;
+        }
+
+        let records = parse_crash_report(&data)?;
+        if let Some(ref t) = tracker {
+            t.update(
+                records.len() as u64,
+                Some("Mapping crash report records".to_string()),
+            )
+            .await;
+        }
+
+        let records = match self.profile {
+            Some(ref profile) => profile.apply_with_config(records, config)?,
+            None => records,
+        };
+
+        if let Some(ref t) = tracker {
+            t.complete().await;
+        }
+
+        Ok(records)
+    }
+
+    async fn import_stream(
+        &self,
+        data: Bytes,
+        config: &TransferConfig,
+        tracker: Option<ProgressTracker>,
+    ) -> Result<DataStream> {
+        let records = self.import(data, config, tracker).await?;
+        Ok(Box::pin(stream::iter(records.into_iter().map(Ok))))
+    }
+
+    async fn validate(&self, data: Bytes, _config: &TransferConfig) -> Result<()> {
+        let records = parse_crash_report(&data)?;
+        if !records.iter().any(|r| r.get(RECORD_TYPE_FIELD) == Some(&Value::from("crash"))) {
+            return Err(TransferError::Validation(
+                "Crash report contains no Crash element".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPORT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<CrashReport xmlns="urn:example:crash">
+  <Crash>
+    <CrashIdentifier>2024-000123</CrashIdentifier>
+    <CrashDate>2024-03-01</CrashDate>
+    <CrashTime>14:35</CrashTime>
+    <Location><Latitude>41.8781</Latitude><Longitude>-87.6298</Longitude></Location>
+    <MannerOfCollision>Angle</MannerOfCollision>
+    <Vehicle UnitNumber="1">
+      <VIN>1HGCM82633A004352</VIN>
+      <ModelYear>2019</ModelYear>
+      <TravelSpeed>35</TravelSpeed>
+      <Person PersonNumber="1"><PersonType>Driver</PersonType><Age>42</Age></Person>
+    </Vehicle>
+    <Vehicle UnitNumber="2"><Make>Ford &amp; Co</Make></Vehicle>
+  </Crash>
+</CrashReport>"#;
+
+    #[test]
+    fn test_parse_crash_report() {
+        let records = parse_crash_report(REPORT.as_bytes()).unwrap();
+        assert_eq!(records.len(), 4);
+        assert_eq!(
+            records[0].get(RECORD_TYPE_FIELD),
+            Some(&Value::from("crash"))
+        );
+        assert_eq!(records[0].get("Latitude"), Some(&Value::from("41.8781")));
+
+        let person = &records[2];
+        assert_eq!(person.get(RECORD_TYPE_FIELD), Some(&Value::from("person")));
+        assert_eq!(
+            person.get("CrashIdentifier"),
+            Some(&Value::from("2024-000123"))
+        );
+        assert_eq!(person.get("UnitNumber"), Some(&Value::from("1")));
+        assert_eq!(records[3].get("Make"), Some(&Value::from("Ford & Co")));
+    }
+
+    #[tokio::test]
+    async fn test_import_with_profile() {
+        let handler = CrashReportHandler::new();
+        let config = TransferConfig::default();
+        let records = handler
+            .import(Bytes::from_static(REPORT.as_bytes()), &config, None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            records[0].get("case_number"),
+            Some(&Value::from("2024-000123"))
+        );
+        assert_eq!(records[0].get("crash_time"), Some(&Value::from("14:35:00")));
+        assert_eq!(records[0].get("latitude"), Some(&Value::from(41.8781)));
+        assert_eq!(records[1].get("year"), Some(&Value::from(2019)));
+        assert_eq!(
+            records[1].get("speed_mps"),
+            Some(&Value::from(35.0 * MPH_TO_MPS))
+        );
+        assert_eq!(records[2].get("unit_number"), Some(&Value::from(1)));
+        assert_eq!(records[2].get("age"), Some(&Value::from(42)));
+    }
+}
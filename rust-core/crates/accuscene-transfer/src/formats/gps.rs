@@ -0,0 +1,646 @@
+use crate::{
+    config::TransferConfig,
+    error::{Result, TransferError},
+    formats::{DataStream, ImportHandler},
+    mapping::{DataType, FieldMapping, MappingProfile, Transform},
+    progress::ProgressTracker,
+    DataRecord,
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{NaiveDate, NaiveTime, SecondsFormat};
+use futures::stream;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde_json::{Number, Value};
+
+/// Knots to meters per second
+const KNOTS_TO_MPS: f64 = 0.514444;
+
+/// Field naming the record type
+pub const RECORD_TYPE_FIELD: &str = "record_type";
+
+/// GPX track importer
+///
+/// Every track point, route point and waypoint becomes one record holding
+/// its `lat`/`lon` attributes and the text of its child elements (`ele`,
+/// `time`, and extension values such as `speed` or `course`). Points also
+/// carry the `track` name and the `segment` index they belong to.
+pub struct GpxHandler {
+    profile: Option<MappingProfile>,
+}
+
+/// NMEA 0183 log importer
+///
+/// Reads `RMC` and `GGA` sentences from any talker. Sentences reporting the
+/// same fix time are merged into one `fix` record with decimal degree
+/// coordinates. Sentences with a bad checksum are errors, subject to the
+/// configured error handling.
+pub struct NmeaHandler {
+    profile: Option<MappingProfile>,
+}
+
+impl GpxHandler {
+    /// Create handler mapping records with the default [`gpx_profile`]
+    pub fn new() -> Self {
+        Self {
+            profile: Some(gpx_profile()),
+        }
+    }
+
+    /// Create handler returning the GPX fields unmapped
+    pub fn raw() -> Self {
+        Self { profile: None }
+    }
+
+    /// Use a custom mapping profile
+    pub fn with_profile(mut self, profile: MappingProfile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+}
+
+impl Default for GpxHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NmeaHandler {
+    /// Create handler mapping records with the default [`nmea_profile`]
+    pub fn new() -> Self {
+        Self {
+            profile: Some(nmea_profile()),
+        }
+    }
+
+    /// Create handler returning the NMEA fields unmapped
+    pub fn raw() -> Self {
+        Self { profile: None }
+    }
+
+    /// Use a custom mapping profile
+    pub fn with_profile(mut self, profile: MappingProfile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+}
+
+impl Default for NmeaHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Default mapping of GPX fields onto AccuScene track fields
+pub fn gpx_profile() -> MappingProfile {
+    let mut profile = MappingProfile::new("gpx_track".to_string())
+        .add_mapping(
+            FieldMapping::typed(RECORD_TYPE_FIELD, RECORD_TYPE_FIELD, DataType::String).required(),
+        )
+        .add_mapping(FieldMapping::typed("lat", "latitude", DataType::Float).required())
+        .add_mapping(FieldMapping::typed("lon", "longitude", DataType::Float).required())
+        .add_mapping(FieldMapping::typed("ele", "elevation_m", DataType::Float))
+        .add_mapping(FieldMapping::typed("time", "timestamp", DataType::DateTime))
+        .add_mapping(FieldMapping::typed("speed", "speed_mps", DataType::Float))
+        .add_mapping(FieldMapping::typed(
+            "course",
+            "heading_deg",
+            DataType::Float,
+        ))
+        .add_mapping(FieldMapping::typed("sat", "satellites", DataType::Integer))
+        .add_mapping(FieldMapping::typed("hdop", "hdop", DataType::Float))
+        .add_mapping(FieldMapping::typed("name", "name", DataType::String))
+        .add_mapping(FieldMapping::typed("track", "track_name", DataType::String))
+        .add_mapping(FieldMapping::typed("segment", "segment", DataType::Integer));
+
+    profile.description = Some("GPX 1.1 tracks, routes and waypoints".to_string());
+    profile
+}
+
+/// Default mapping of NMEA fixes onto AccuScene track fields
+///
+/// Speeds are reported in knots and converted to meters per second.
+pub fn nmea_profile() -> MappingProfile {
+    let mut profile = MappingProfile::new("nmea_0183".to_string())
+        .add_mapping(
+            FieldMapping::typed(RECORD_TYPE_FIELD, RECORD_TYPE_FIELD, DataType::String).required(),
+        )
+        .add_mapping(FieldMapping::typed("latitude", "latitude", DataType::Float).required())
+        .add_mapping(FieldMapping::typed("longitude", "longitude", DataType::Float).required())
+        .add_mapping(FieldMapping::typed(
+            "altitude_m",
+            "elevation_m",
+            DataType::Float,
+        ))
+        .add_mapping(FieldMapping::typed(
+            "timestamp",
+            "timestamp",
+            DataType::DateTime,
+        ))
+        .add_mapping(FieldMapping::typed("time", "time_of_day", DataType::Time))
+        .add_mapping(
+            FieldMapping::typed("speed_knots", "speed_mps", DataType::Float).with_transform(
+                Transform::Scale {
+                    factor: KNOTS_TO_MPS,
+                },
+            ),
+        )
+        .add_mapping(FieldMapping::typed(
+            "course_deg",
+            "heading_deg",
+            DataType::Float,
+        ))
+        .add_mapping(FieldMapping::typed(
+            "satellites",
+            "satellites",
+            DataType::Integer,
+        ))
+        .add_mapping(FieldMapping::typed("hdop", "hdop", DataType::Float))
+        .add_mapping(FieldMapping::typed(
+            "fix_quality",
+            "fix_quality",
+            DataType::Integer,
+        ));
+
+    profile.description = Some("NMEA 0183 RMC/GGA sentences".to_string());
+    profile
+}
+
+/// Parse a GPX document into raw point records
+pub fn parse_gpx(data: &[u8]) -> Result<Vec<DataRecord>> {
+    let mut reader = Reader::from_reader(data);
+    reader.trim_text(true);
+
+    let mut records = Vec::new();
+    let mut point: Option<DataRecord> = None;
+    let mut track: Option<String> = None;
+    let mut segment: i64 = -1;
+    let mut field: Option<String> = None;
+    let mut text = String::new();
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) => {
+                let name = local_name(&e);
+                match name.as_str() {
+                    "trk" | "rte" => {
+                        track = None;
+                        segment = if name == "rte" { 0 } else { -1 };
+                    },
+                    "trkseg" => segment += 1,
+                    "trkpt" | "rtept" | "wpt" => {
+                        point = Some(start_point(&e, &reader, &name, track.as_deref(), segment)?);
+                    },
+                    _ => {
+                        field = Some(name);
+                        text.clear();
+                    },
+                }
+            },
+            Event::Empty(e) if matches!(local_name(&e).as_str(), "trkpt" | "rtept" | "wpt") => {
+                let name = local_name(&e);
+                records.push(start_point(&e, &reader, &name, track.as_deref(), segment)?);
+            },
+            Event::Text(e) if field.is_some() => {
+                text.push_str(&e.unescape()?);
+            },
+            Event::End(e) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                match name.as_str() {
+                    "trkpt" | "rtept" | "wpt" => {
+                        records.extend(point.take());
+                    },
+                    "trk" | "rte" => track = None,
+                    _ if field.as_deref() == Some(name.as_str()) => {
+                        field = None;
+                        if text.is_empty() {
+                            continue;
+                        }
+                        match point {
+                            Some(ref mut point) => {
+                                point.set(name, Value::String(text.clone()));
+                            },
+                            // The name of a track or route
+                            None if name == "name" && segment <= 0 => {
+                                track = Some(text.clone());
+                            },
+                            None => {},
+                        }
+                    },
+                    _ => {},
+                }
+            },
+            Event::Eof => break,
+            _ => {},
+        }
+        buf.clear();
+    }
+
+    Ok(records)
+}
+
+fn start_point(
+    e: &BytesStart,
+    reader: &Reader<&[u8]>,
+    name: &str,
+    track: Option<&str>,
+    segment: i64,
+) -> Result<DataRecord> {
+    let record_type = match name {
+        "trkpt" => "trackpoint",
+        "rtept" => "routepoint",
+        _ => "waypoint",
+    };
+
+    let mut record = DataRecord::new();
+    record.set(
+        RECORD_TYPE_FIELD.to_string(),
+        Value::String(record_type.to_string()),
+    );
+
+    for attribute in e.attributes() {
+        let attribute = attribute.map_err(|e| TransferError::Xml(e.to_string()))?;
+        let key = String::from_utf8_lossy(attribute.key.local_name().as_ref()).to_string();
+        record.set(
+            key,
+            Value::String(attribute.decode_and_unescape_value(reader)?.to_string()),
+        );
+    }
+
+    if record_type != "waypoint" {
+        if let Some(track) = track {
+            record.set("track".to_string(), Value::String(track.to_string()));
+        }
+        if segment >= 0 {
+            record.set("segment".to_string(), Value::from(segment));
+        }
+    }
+
+    Ok(record)
+}
+
+fn local_name(e: &BytesStart) -> String {
+    String::from_utf8_lossy(e.local_name().as_ref()).to_string()
+}
+
+/// Parse an NMEA 0183 log into raw fix records
+pub fn parse_nmea(data: &[u8], config: &TransferConfig) -> Result<Vec<DataRecord>> {
+    let text = String::from_utf8_lossy(data);
+
+    let mut records = Vec::new();
+    let mut current: Option<(String, DataRecord)> = None;
+    let mut last_date: Option<NaiveDate> = None;
+    let mut error_count = 0;
+
+    for (line_number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let fields = match sentence_fields(line) {
+            Ok(fields) => fields,
+            Err(e) => {
+                error_count += 1;
+                if !config.continue_on_error || error_count >= config.error_threshold {
+                    return Err(e);
+                }
+                tracing::warn!("NMEA error at line {}: {}", line_number + 1, e);
+                continue;
+            },
+        };
+
+        let kind = fields[0].get(2..).unwrap_or_default();
+        let fix = match kind {
+            "RMC" => parse_rmc(&fields),
+            "GGA" => parse_gga(&fields),
+            _ => None,
+        };
+        let Some((time, fix)) = fix else {
+            continue;
+        };
+
+        if let Some(date) = fix_date(&fields, kind) {
+            last_date = Some(date);
+        }
+
+        match current {
+            Some((ref current_time, ref mut record)) if *current_time == time => {
+                record.fields.extend(fix.fields);
+            },
+            _ => {
+                if let Some((time, record)) = current.take() {
+                    records.push(finish_fix(record, &time, last_date));
+                }
+                current = Some((time, fix));
+            },
+        }
+    }
+
+    if let Some((time, record)) = current.take() {
+        records.push(finish_fix(record, &time, last_date));
+    }
+
+    Ok(records)
+}
+
+/// Split a sentence into its fields after checking the optional checksum
+fn sentence_fields(line: &str) -> Result<Vec<&str>> {
+    let body = line
+        .strip_prefix('$')
+        .or_else(|| line.strip_prefix('!'))
+        .ok_or_else(|| TransferError::Validation(format!("Not an NMEA sentence: {}", line)))?;
+
+    let body = match body.split_once('*') {
+        Some((body, checksum)) => {
+            let expected = u8::from_str_radix(checksum.trim(), 16).map_err(|_| {
+                TransferError::Validation(format!("Invalid NMEA checksum: {}", line))
+            })?;
+            let actual = body.bytes().fold(0u8, |acc, b| acc ^ b);
+            if actual != expected {
+                return Err(TransferError::Validation(format!(
+                    "NMEA checksum mismatch (expected {:02X}, got {:02X}): {}",
+                    expected, actual, line
+                )));
+            }
+            body
+        },
+        None => body,
+    };
+
+    let fields: Vec<&str> = body.split(',').collect();
+    if fields[0].len() < 5 {
+        return Err(TransferError::Validation(format!(
+            "Invalid NMEA sentence: {}",
+            line
+        )));
+    }
+
+    Ok(fields)
+}
+
+/// Fix time and fields of a valid RMC sentence
+fn parse_rmc(fields: &[&str]) -> Option<(String, DataRecord)> {
+    if fields.get(2) != Some(&"A") {
+        return None;
+    }
+
+    let mut record = DataRecord::new();
+    set_coordinates(&mut record, fields, 3)?;
+    set_number(&mut record, "speed_knots", fields.get(7));
+    set_number(&mut record, "course_deg", fields.get(8));
+    Some((fields.get(1)?.to_string(), record))
+}
+
+/// Fix time and fields of a GGA sentence with a position fix
+fn parse_gga(fields: &[&str]) -> Option<(String, DataRecord)> {
+    let quality: i64 = fields.get(6)?.parse().ok()?;
+    if quality == 0 {
+        return None;
+    }
+
+    let mut record = DataRecord::new();
+    set_coordinates(&mut record, fields, 2)?;
+    record.set("fix_quality".to_string(), Value::from(quality));
+    set_number(&mut record, "satellites", fields.get(7));
+    set_number(&mut record, "hdop", fields.get(8));
+    set_number(&mut record, "altitude_m", fields.get(9));
+    Some((fields.get(1)?.to_string(), record))
+}
+
+fn fix_date(fields: &[&str], kind: &str) -> Option<NaiveDate> {
+    if kind != "RMC" {
+        return None;
+    }
+    NaiveDate::parse_from_str(fields.get(9)?, "%d%m%y").ok()
+}
+
+/// Set `latitude`/`longitude` from `ddmm.mmmm,N,dddmm.mmmm,E` fields
+fn set_coordinates(record: &mut DataRecord, fields: &[&str], start: usize) -> Option<()> {
+    let latitude = nmea_degrees(fields.get(start)?, fields.get(start + 1)?)?;
+    let longitude = nmea_degrees(fields.get(start + 2)?, fields.get(start + 3)?)?;
+    record.set("latitude".to_string(), number(latitude)?);
+    record.set("longitude".to_string(), number(longitude)?);
+    Some(())
+}
+
+fn nmea_degrees(value: &str, hemisphere: &str) -> Option<f64> {
+    let value: f64 = value.parse().ok()?;
+    let degrees = (value / 100.0).trunc();
+    let decimal = degrees + (value - degrees * 100.0) / 60.0;
+    match hemisphere {
+        "N" | "E" => Some(decimal),
+        "S" | "W" => Some(-decimal),
+        _ => None,
+    }
+}
+
+fn set_number(record: &mut DataRecord, key: &str, field: Option<&&str>) {
+    if let Some(value) = field.and_then(|f| f.parse::<f64>().ok()).and_then(number) {
+        record.set(key.to_string(), value);
+    }
+}
+
+fn number(value: f64) -> Option<Value> {
+    Number::from_f64(value).map(Value::Number)
+}
+
+/// Add the record type and fix time to a merged fix
+fn finish_fix(mut record: DataRecord, time: &str, date: Option<NaiveDate>) -> DataRecord {
+    record.set(
+        RECORD_TYPE_FIELD.to_string(),
+        Value::String("fix".to_string()),
+    );
+
+    if let Ok(time) = NaiveTime::parse_from_str(time, "%H%M%S%.f") {
+        record.set(
+            "time".to_string(),
+            Value::String(time.format("%H:%M:%S%.3f").to_string()),
+        );
+        if let Some(date) = date {
+            let timestamp = date.and_time(time).and_utc();
+            record.set(
+                "timestamp".to_string(),
+                Value::String(timestamp.to_rfc3339_opts(SecondsFormat::Millis, true)),
+            );
+        }
+    }
+
+    record
+}
+
+#[async_trait]
+impl ImportHandler for GpxHandler {
+    async fn import(
+        &self,
+        data: Bytes,
+        config: &TransferConfig,
+        tracker: Option<ProgressTracker>,
+    ) -> Result<Vec<DataRecord>> {
+        if let Some(ref t) = tracker {
+            t.start().await;
+        }
+
+        let records = parse_gpx(&data)?;
+        if let Some(ref t) = tracker {
+            t.update(records.len() as u64, Some("Mapping GPX points".to_string())).await;
+        }
+
+        let records = match self.profile {
+            Some(ref profile) => profile.apply_with_config(records, config)?,
+            None => records,
+        };
+
+        if let Some(ref t) = tracker {
+            t.complete().await;
+        }
+
+        Ok(records)
+    }
+
+    async fn import_stream(
+        &self,
+        data: Bytes,
+        config: &TransferConfig,
+        tracker: Option<ProgressTracker>,
+    ) -> Result<DataStream> {
+        let records = self.import(data, config, tracker).await?;
+        Ok(Box::pin(stream::iter(records.into_iter().map(Ok))))
+    }
+
+    async fn validate(&self, data: Bytes, _config: &TransferConfig) -> Result<()> {
+        if parse_gpx(&data)?.is_empty() {
+            return Err(TransferError::Validation(
+                "GPX file contains no points".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ImportHandler for NmeaHandler {
+    async fn import(
+        &self,
+        data: Bytes,
+        config: &TransferConfig,
+        tracker: Option<ProgressTracker>,
+    ) -> Result<Vec<DataRecord>> {
+        if let Some(ref t) = tracker {
+            t.start().await;
+        }
+
+        let records = parse_nmea(&data, config)?;
+        if let Some(ref t) = tracker {
+            t.update(records.len() as u64, Some("Mapping NMEA fixes".to_string())).await;
+        }
+
+        let records = match self.profile {
+            Some(ref profile) => profile.apply_with_config(records, config)?,
+            None => records,
+        };
+
+        if let Some(ref t) = tracker {
+            t.complete().await;
+        }
+
+        Ok(records)
+    }
+
+    async fn import_stream(
+        &self,
+        data: Bytes,
+        config: &TransferConfig,
+        tracker: Option<ProgressTracker>,
+    ) -> Result<DataStream> {
+        let records = self.import(data, config, tracker).await?;
+        Ok(Box::pin(stream::iter(records.into_iter().map(Ok))))
+    }
+
+    async fn validate(&self, data: Bytes, config: &TransferConfig) -> Result<()> {
+        if parse_nmea(&data, config)?.is_empty() {
+            return Err(TransferError::Validation(
+                "NMEA log contains no position fixes".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_gpx_import() {
+        let gpx = r#"<?xml version="1.0"?>
+<gpx version="1.1" xmlns="http://www.topografix.com/GPX/1/1"
+     xmlns:gpxtpx="http://www.garmin.com/xmlschemas/TrackPointExtension/v2">
+  <wpt lat="41.88" lon="-87.63"><name>Impact</name></wpt>
+  <trk>
+    <name>Unit 1</name>
+    <trkseg>
+      <trkpt lat="41.8781" lon="-87.6298">
+        <ele>181.2</ele>
+        <time>2024-03-01T14:35:00Z</time>
+        <extensions><gpxtpx:TrackPointExtension><gpxtpx:speed>15.6</gpxtpx:speed></gpxtpx:TrackPointExtension></extensions>
+      </trkpt>
+      <trkpt lat="41.8782" lon="-87.6297"/>
+    </trkseg>
+  </trk>
+</gpx>"#;
+
+        let raw = parse_gpx(gpx.as_bytes()).unwrap();
+        assert_eq!(raw.len(), 3);
+        assert_eq!(raw[1].get("track"), Some(&Value::from("Unit 1")));
+        assert_eq!(raw[1].get("segment"), Some(&Value::from(0)));
+
+        let records = GpxHandler::new()
+            .import(Bytes::from(gpx), &TransferConfig::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            records[0].get(RECORD_TYPE_FIELD),
+            Some(&Value::from("waypoint"))
+        );
+        assert_eq!(records[1].get("latitude"), Some(&Value::from(41.8781)));
+        assert_eq!(records[1].get("speed_mps"), Some(&Value::from(15.6)));
+        assert_eq!(
+            records[1].get("timestamp"),
+            Some(&Value::from("2024-03-01T14:35:00Z"))
+        );
+        assert_eq!(records[2].get("track_name"), Some(&Value::from("Unit 1")));
+    }
+
+    #[tokio::test]
+    async fn test_nmea_import() {
+        let log = "\
+$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47
+$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A
+$GPRMC,123520,V,,,,,,,230394,,*39
+$GPGSV,3,1,11,03,03,111,00,04,15,270,00,06,01,010,00,13,06,292,00*74
+";
+
+        let config = TransferConfig::default();
+        let raw = parse_nmea(log.as_bytes(), &config).unwrap();
+        assert_eq!(raw.len(), 1);
+        assert_eq!(raw[0].get("satellites"), Some(&Value::from(8.0)));
+        assert_eq!(
+            raw[0].get("timestamp"),
+            Some(&Value::from("1994-03-23T12:35:19.000Z"))
+        );
+
+        let records = NmeaHandler::new().import(Bytes::from(log), &config, None).await.unwrap();
+        let latitude = records[0].get("latitude").and_then(Value::as_f64).unwrap();
+        assert!((latitude - 48.1173).abs() < 1e-9);
+        assert_eq!(records[0].get("satellites"), Some(&Value::from(8)));
+        assert_eq!(records[0].get("elevation_m"), Some(&Value::from(545.4)));
+
+        let corrupted = log.replace("*6A", "*6B");
+        assert!(parse_nmea(corrupted.as_bytes(), &config).is_err());
+        let lenient = TransferConfig::default().with_error_handling(true, 10);
+        assert_eq!(parse_nmea(corrupted.as_bytes(), &lenient).unwrap().len(), 1);
+    }
+}
@@ -1,10 +1,21 @@
 /// Format handlers for import/export operations
 pub mod archive;
+#[cfg(feature = "columnar")]
+pub mod arrow_ipc;
+pub mod cdr;
+#[cfg(feature = "columnar")]
+pub mod columnar;
+pub mod crash_report;
 pub mod csv;
 pub mod excel;
+pub mod gps;
 pub mod json;
+#[cfg(feature = "columnar")]
+pub mod parquet;
 pub mod pdf;
+pub mod report;
 pub mod xml;
+pub mod xlsx;
 
 use crate::{error::Result, config::TransferConfig, DataRecord, progress::ProgressTracker};
 use async_trait::async_trait;
@@ -58,14 +69,55 @@ pub trait ExportHandler: Send + Sync {
     ) -> Result<Bytes>;
 }
 
+/// Decompress compressed input so format handlers see the raw payload
+///
+/// Compression is detected from the data itself; uncompressed input is
+/// returned unchanged. Decompressed output larger than
+/// `config.max_file_size` is rejected.
+pub fn decompress_input(data: Bytes, config: &TransferConfig) -> Result<Bytes> {
+    if !accuscene_compression::detect::is_compressed(&data) {
+        return Ok(data);
+    }
+
+    let decompressed = accuscene_compression::decompress_auto(&data)?;
+    if decompressed.len() as u64 > config.max_file_size {
+        return Err(crate::error::TransferError::FileTooLarge(
+            decompressed.len() as u64,
+            config.max_file_size,
+        ));
+    }
+
+    Ok(Bytes::from(decompressed))
+}
+
+/// Strip a trailing compression extension (`records.csv.gz` -> `records.csv`)
+pub fn strip_compression_extension(file_name: &str) -> &str {
+    match file_name.rsplit_once('.') {
+        Some((stem, extension))
+            if accuscene_compression::Algorithm::from_extension(extension).is_some() =>
+        {
+            stem
+        }
+        _ => file_name,
+    }
+}
+
 /// Get import handler for format
 pub fn get_import_handler(format: &str) -> Result<Box<dyn ImportHandler>> {
     match format.to_lowercase().as_str() {
         "csv" => Ok(Box::new(csv::CsvHandler)),
-        "excel" | "xlsx" => Ok(Box::new(excel::ExcelHandler)),
+        "excel" | "xlsx" => Ok(Box::new(xlsx::XlsxHandler::new())),
         "json" => Ok(Box::new(json::JsonHandler)),
         "xml" => Ok(Box::new(xml::XmlHandler)),
         "zip" | "archive" => Ok(Box::new(archive::ArchiveHandler)),
+        "crash_report" | "nhtsa" => Ok(Box::new(crash_report::CrashReportHandler::new())),
+        "cdr" => Ok(Box::new(cdr::CdrHandler::new())),
+        "gpx" => Ok(Box::new(gps::GpxHandler::new())),
+        "nmea" => Ok(Box::new(gps::NmeaHandler::new())),
+        #[cfg(feature = "columnar")]
+        "parquet" => Ok(Box::new(parquet::ParquetHandler)),
+        #[cfg(feature = "columnar")]
+        "arrow" | "arrows" | "ipc" | "feather" => Ok(Box::new(arrow_ipc::ArrowIpcHandler)),
         _ => Err(crate::error::TransferError::UnsupportedFormat(
             format.to_string(),
         )),
@@ -76,11 +128,16 @@ pub fn get_import_handler(format: &str) -> Result<Box<dyn ImportHandler>> {
 pub fn get_export_handler(format: &str) -> Result<Box<dyn ExportHandler>> {
     match format.to_lowercase().as_str() {
         "csv" => Ok(Box::new(csv::CsvHandler)),
-        "excel" | "xlsx" => Ok(Box::new(excel::ExcelHandler)),
+        "excel" | "xlsx" => Ok(Box::new(xlsx::XlsxHandler::new())),
         "json" => Ok(Box::new(json::JsonHandler)),
         "xml" => Ok(Box::new(xml::XmlHandler)),
         "pdf" => Ok(Box::new(pdf::PdfHandler)),
+        "report" => Ok(Box::new(report::ReportHandler::default())),
         "zip" | "archive" => Ok(Box::new(archive::ArchiveHandler)),
+        #[cfg(feature = "columnar")]
+        "parquet" => Ok(Box::new(parquet::ParquetHandler)),
+        #[cfg(feature = "columnar")]
+        "arrow" | "arrows" | "ipc" | "feather" => Ok(Box::new(arrow_ipc::ArrowIpcHandler)),
         _ => Err(crate::error::TransferError::UnsupportedFormat(
             format.to_string(),
         )),
@@ -0,0 +1,323 @@
+use crate::{
+    config::{ImportPredicate, TransferConfig},
+    error::{Result, TransferError},
+    formats::columnar::{infer_schema, predicate_mask, record_stream, records_to_batch},
+    formats::{DataStream, ExportHandler, ImportHandler},
+    progress::ProgressTracker,
+    DataRecord,
+};
+use arrow::datatypes::{DataType, Schema};
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatch;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::TryStreamExt;
+use parquet::arrow::arrow_reader::{
+    ArrowPredicateFn, ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder, RowFilter,
+};
+use parquet::arrow::{ArrowWriter, ProjectionMask};
+use parquet::basic::Compression;
+use parquet::file::metadata::RowGroupMetaData;
+use parquet::file::properties::WriterProperties;
+use parquet::file::statistics::Statistics;
+use serde_json::{Number, Value};
+
+pub struct ParquetHandler;
+
+impl ParquetHandler {
+    /// Open a reader with the import predicates pushed down
+    ///
+    /// Row groups whose column statistics rule out a match are skipped
+    /// entirely; the remaining rows are filtered while decoding, before the
+    /// other columns are materialized.
+    fn reader(data: Bytes, config: &TransferConfig) -> Result<ParquetRecordBatchReader> {
+        let builder = ParquetRecordBatchReaderBuilder::try_new(data)?
+            .with_batch_size(config.columnar_batch_size);
+
+        let predicates = &config.import_predicates;
+        if predicates.is_empty() {
+            return Ok(builder.build()?);
+        }
+
+        // Predicates on columns absent from the file see only nulls
+        let root_fields = builder.parquet_schema().root_schema().get_fields();
+        let mut present = Vec::new();
+        let mut columns = Vec::new();
+        let mut excluded = false;
+        for predicate in predicates {
+            match root_fields.iter().position(|f| f.name() == predicate.field) {
+                Some(index) => {
+                    present.push(predicate.clone());
+                    columns.push(index);
+                }
+                None => excluded |= !predicate.matches(None),
+            }
+        }
+
+        if excluded {
+            return Ok(builder.with_row_groups(Vec::new()).build()?);
+        }
+        if present.is_empty() {
+            return Ok(builder.build()?);
+        }
+        columns.sort_unstable();
+        columns.dedup();
+
+        let row_groups: Vec<usize> = builder
+            .metadata()
+            .row_groups()
+            .iter()
+            .enumerate()
+            .filter(|(_, row_group)| {
+                present
+                    .iter()
+                    .all(|predicate| row_group_may_match(row_group, builder.schema(), predicate))
+            })
+            .map(|(index, _)| index)
+            .collect();
+
+        let projection = ProjectionMask::roots(builder.parquet_schema(), columns);
+        let filter = ArrowPredicateFn::new(projection, move |batch: RecordBatch| {
+            predicate_mask(&batch, &present).map_err(|e| ArrowError::ComputeError(e.to_string()))
+        });
+
+        Ok(builder
+            .with_row_groups(row_groups)
+            .with_row_filter(RowFilter::new(vec![Box::new(filter)]))
+            .build()?)
+    }
+}
+
+#[async_trait]
+impl ImportHandler for ParquetHandler {
+    async fn import(
+        &self,
+        data: Bytes,
+        config: &TransferConfig,
+        tracker: Option<ProgressTracker>,
+    ) -> Result<Vec<DataRecord>> {
+        self.import_stream(data, config, tracker)
+            .await?
+            .try_collect()
+            .await
+    }
+
+    async fn import_stream(
+        &self,
+        data: Bytes,
+        config: &TransferConfig,
+        tracker: Option<ProgressTracker>,
+    ) -> Result<DataStream> {
+        if let Some(ref t) = tracker {
+            t.start().await;
+        }
+
+        let reader = Self::reader(data, config)?;
+        let batches = reader.map(|batch| batch.map_err(TransferError::from));
+        Ok(record_stream(batches, tracker))
+    }
+
+    async fn validate(&self, data: Bytes, _config: &TransferConfig) -> Result<()> {
+        let builder = ParquetRecordBatchReaderBuilder::try_new(data)?;
+
+        if builder.metadata().file_metadata().num_rows() == 0 {
+            return Err(TransferError::Validation("Parquet file is empty".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ExportHandler for ParquetHandler {
+    async fn export(
+        &self,
+        records: Vec<DataRecord>,
+        config: &TransferConfig,
+        tracker: Option<ProgressTracker>,
+    ) -> Result<Bytes> {
+        if let Some(ref t) = tracker {
+            t.start().await;
+        }
+
+        if records.is_empty() {
+            return Ok(Bytes::new());
+        }
+
+        let schema = infer_schema(&records);
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_max_row_group_size(config.columnar_batch_size)
+            .build();
+
+        let mut buffer = Vec::new();
+        let mut writer = ArrowWriter::try_new(&mut buffer, schema.clone(), Some(properties))?;
+
+        let mut written = 0;
+        for chunk in records.chunks(config.columnar_batch_size) {
+            if let Some(ref t) = tracker {
+                if t.is_cancelled().await {
+                    return Err(TransferError::Cancelled);
+                }
+                t.update(written as u64, Some("Writing Parquet row groups".to_string())).await;
+            }
+
+            writer.write(&records_to_batch(&schema, chunk)?)?;
+            written += chunk.len();
+        }
+
+        writer.close()?;
+
+        if let Some(ref t) = tracker {
+            t.complete().await;
+        }
+
+        Ok(Bytes::from(buffer))
+    }
+
+    async fn export_stream(
+        &self,
+        records: Vec<DataRecord>,
+        config: &TransferConfig,
+        tracker: Option<ProgressTracker>,
+    ) -> Result<Bytes> {
+        // Records are already written one row group at a time
+        self.export(records, config, tracker).await
+    }
+}
+
+/// Check a row group's statistics against a predicate
+///
+/// Statistics are only trusted for plain boolean, signed integer, float and
+/// string columns; anything else may match.
+fn row_group_may_match(
+    row_group: &RowGroupMetaData,
+    schema: &Schema,
+    predicate: &ImportPredicate,
+) -> bool {
+    let comparable = schema
+        .field_with_name(&predicate.field)
+        .map(|field| {
+            matches!(
+                field.data_type(),
+                DataType::Boolean
+                    | DataType::Int8
+                    | DataType::Int16
+                    | DataType::Int32
+                    | DataType::Int64
+                    | DataType::Float32
+                    | DataType::Float64
+                    | DataType::Utf8
+                    | DataType::LargeUtf8
+            )
+        })
+        .unwrap_or(false);
+    if !comparable {
+        return true;
+    }
+
+    let statistics = row_group
+        .columns()
+        .iter()
+        .find(|column| column.column_path().string() == predicate.field)
+        .and_then(|column| column.statistics());
+
+    match statistics.and_then(statistics_range) {
+        Some((min, max)) => {
+            predicate.may_match_range(&min, &max, statistics.and_then(Statistics::null_count_opt))
+        }
+        None => true,
+    }
+}
+
+/// Min and max of a column chunk as JSON values
+fn statistics_range(statistics: &Statistics) -> Option<(Value, Value)> {
+    let range = match statistics {
+        Statistics::Boolean(s) => (Value::Bool(*s.min_opt()?), Value::Bool(*s.max_opt()?)),
+        Statistics::Int32(s) => (Value::from(*s.min_opt()?), Value::from(*s.max_opt()?)),
+        Statistics::Int64(s) => (Value::from(*s.min_opt()?), Value::from(*s.max_opt()?)),
+        Statistics::Float(s) => (
+            Value::Number(Number::from_f64(f64::from(*s.min_opt()?))?),
+            Value::Number(Number::from_f64(f64::from(*s.max_opt()?))?),
+        ),
+        Statistics::Double(s) => (
+            Value::Number(Number::from_f64(*s.min_opt()?)?),
+            Value::Number(Number::from_f64(*s.max_opt()?)?),
+        ),
+        Statistics::ByteArray(s) => (
+            Value::String(s.min_opt()?.as_utf8().ok()?.to_string()),
+            Value::String(s.max_opt()?.as_utf8().ok()?.to_string()),
+        ),
+        _ => return None,
+    };
+
+    Some(range)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PredicateOp;
+
+    fn records() -> Vec<DataRecord> {
+        (0..100)
+            .map(|i| {
+                let mut record = DataRecord::new();
+                record.set("id".to_string(), Value::from(i));
+                record.set("speed".to_string(), Value::from(f64::from(i) / 2.0));
+                record.set("vehicle".to_string(), Value::String(format!("V-{:03}", i)));
+                record
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_parquet_export_import() {
+        let config = TransferConfig::default().with_columnar_batch_size(32);
+
+        let handler = ParquetHandler;
+        let exported = handler.export(records(), &config, None).await.unwrap();
+        handler.validate(exported.clone(), &config).await.unwrap();
+        let imported = handler.import(exported, &config, None).await.unwrap();
+
+        assert_eq!(imported.len(), 100);
+        assert_eq!(imported[7].get("id"), Some(&Value::from(7)));
+        assert_eq!(imported[7].get("speed"), Some(&Value::from(3.5)));
+        assert_eq!(imported[7].get("vehicle"), Some(&Value::from("V-007")));
+    }
+
+    #[tokio::test]
+    async fn test_parquet_predicate_pushdown() {
+        let handler = ParquetHandler;
+        let exported = handler
+            .export(records(), &TransferConfig::default().with_columnar_batch_size(32), None)
+            .await
+            .unwrap();
+
+        let config = TransferConfig::default()
+            .with_import_predicate(ImportPredicate::new("id", PredicateOp::GtEq, Value::from(90)))
+            .with_import_predicate(ImportPredicate::new(
+                "vehicle",
+                PredicateOp::NotEq,
+                Value::from("V-095"),
+            ));
+
+        // Statistics rule out the first two of the four row groups
+        let builder = ParquetRecordBatchReaderBuilder::try_new(exported.clone()).unwrap();
+        let row_groups = builder.metadata().row_groups();
+        let may_match: Vec<bool> = row_groups
+            .iter()
+            .map(|row_group| {
+                row_group_may_match(row_group, builder.schema(), &config.import_predicates[0])
+            })
+            .collect();
+        assert_eq!(may_match, vec![false, false, true, true]);
+
+        let imported = handler.import(exported, &config, None).await.unwrap();
+        let ids: Vec<i64> = imported
+            .iter()
+            .map(|record| record.get("id").and_then(Value::as_i64).unwrap())
+            .collect();
+        assert_eq!(ids, vec![90, 91, 92, 93, 94, 96, 97, 98, 99]);
+    }
+}
@@ -1,15 +1,19 @@
 use crate::{
     config::TransferConfig,
-    error::{Result, TransferError},
+    error::Result,
+    formats::report::{render_report, ReportSection, ReportTemplate, TableBlock},
     formats::ExportHandler,
     progress::ProgressTracker,
     DataRecord,
 };
 use async_trait::async_trait;
 use bytes::Bytes;
-use printpdf::*;
 use serde_json::Value;
 
+/// Flat PDF export: a paginated table of all records
+///
+/// Use [`ReportHandler`](crate::formats::report::ReportHandler) for
+/// templated reports.
 pub struct PdfHandler;
 
 #[async_trait]
@@ -17,7 +21,7 @@ impl ExportHandler for PdfHandler {
     async fn export(
         &self,
         records: Vec<DataRecord>,
-        config: &TransferConfig,
+        _config: &TransferConfig,
         tracker: Option<ProgressTracker>,
     ) -> Result<Bytes> {
         if let Some(ref t) = tracker {
@@ -28,124 +32,20 @@ impl ExportHandler for PdfHandler {
             return Ok(Bytes::new());
         }
 
-        // Create PDF document
-        let (doc, page1, layer1) = PdfDocument::new(
-            "AccuScene Export",
-            Mm(210.0), // A4 width
-            Mm(297.0), // A4 height
-            "Layer 1",
-        );
-
-        let current_layer = doc.get_page(page1).get_layer(layer1);
-
-        // Get headers from first record
-        let headers: Vec<String> = records[0].field_names();
-
-        // Define layout
-        let font_size = 10.0;
-        let line_height = 5.0;
-        let margin_left = 10.0;
-        let margin_top = 280.0;
-        let col_width = 40.0;
-
-        // Load font (built-in)
-        let font = doc.add_builtin_font(BuiltinFont::Helvetica)
-            .map_err(|e| TransferError::Pdf(format!("Font error: {:?}", e)))?;
-        let font_bold = doc.add_builtin_font(BuiltinFont::HelveticaBold)
-            .map_err(|e| TransferError::Pdf(format!("Font error: {:?}", e)))?;
-
-        let mut y_pos = margin_top;
-
-        // Write title
-        current_layer.use_text(
-            "AccuScene Data Export",
-            16.0,
-            Mm(margin_left),
-            Mm(y_pos),
-            &font_bold,
-        );
-        y_pos -= line_height * 2.0;
-
-        // Write headers
-        for (idx, header) in headers.iter().enumerate() {
-            let x_pos = margin_left + (idx as f32 * col_width);
-            current_layer.use_text(
-                header,
-                font_size,
-                Mm(x_pos),
-                Mm(y_pos),
-                &font_bold,
-            );
-        }
-        y_pos -= line_height;
-
-        // Write records
-        let mut current_page = page1;
-        let mut current_layer_ref = current_layer;
-
-        for (record_idx, record) in records.iter().enumerate() {
-            if let Some(ref t) = tracker {
-                if t.is_cancelled().await {
-                    return Err(TransferError::Cancelled);
-                }
-                t.update(record_idx as u64, Some("Generating PDF".to_string()))
-                    .await;
-            }
-
-            // Check if we need a new page
-            if y_pos < 20.0 {
-                let (new_page, new_layer) = doc.add_page(
-                    Mm(210.0),
-                    Mm(297.0),
-                    "Layer 1",
-                );
-                current_page = new_page;
-                current_layer_ref = doc.get_page(current_page).get_layer(new_layer);
-                y_pos = margin_top;
-            }
-
-            // Write record fields
-            for (idx, header) in headers.iter().enumerate() {
-                let x_pos = margin_left + (idx as f32 * col_width);
-                let value = record
-                    .get(header)
-                    .map(|v| format_pdf_value(v))
-                    .unwrap_or_default();
-
-                // Truncate long values
-                let display_value = if value.len() > 15 {
-                    format!("{}...", &value[..12])
-                } else {
-                    value
-                };
-
-                current_layer_ref.use_text(
-                    &display_value,
-                    font_size,
-                    Mm(x_pos),
-                    Mm(y_pos),
-                    &font,
-                );
-            }
-
-            y_pos -= line_height;
+        if let Some(ref t) = tracker {
+            t.update(0, Some("Generating PDF".to_string())).await;
         }
 
-        // Add metadata
-        doc.metadata.document_title = Some("AccuScene Export".to_string());
-        doc.metadata.creation_date = Some(chrono::Utc::now());
-        doc.metadata.creator = Some("AccuScene Enterprise".to_string());
-
-        // Save to bytes
-        let mut buffer = Vec::new();
-        doc.save(&mut buffer)
-            .map_err(|e| TransferError::Pdf(format!("PDF save error: {:?}", e)))?;
+        let template = ReportTemplate::new("AccuScene Data Export")
+            .with_section(ReportSection::untitled().with_table(TableBlock::default()));
+        let pdf = render_report(&template, &records)?;
 
         if let Some(ref t) = tracker {
+            t.update(records.len() as u64, None).await;
             t.complete().await;
         }
 
-        Ok(Bytes::from(buffer))
+        Ok(pdf)
     }
 
     async fn export_stream(
@@ -160,7 +60,7 @@ impl ExportHandler for PdfHandler {
 }
 
 /// Format JSON value for PDF output
-fn format_pdf_value(value: &Value) -> String {
+pub(crate) fn format_pdf_value(value: &Value) -> String {
     match value {
         Value::Null => String::new(),
         Value::Bool(b) => b.to_string(),
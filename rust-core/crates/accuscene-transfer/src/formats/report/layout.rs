@@ -0,0 +1,847 @@
+use super::{PageTemplate, ReportBlock, ReportTemplate, SignatureBlock, TableBlock, TableColumn};
+use crate::{
+    error::{Result, TransferError},
+    formats::pdf::format_pdf_value,
+    DataRecord,
+};
+use accuscene_visualization::{ChartData, ChartType};
+use printpdf::path::PaintMode;
+use printpdf::{BuiltinFont, Color, Line, Mm, PdfDocument, PdfLayerReference, Point, Rect, Rgb};
+
+/// Millimeters per typographic point
+const PT_TO_MM: f32 = 0.3528;
+
+/// Average Helvetica glyph width relative to the font size
+const GLYPH_WIDTH: f32 = 0.5;
+
+const BODY_SIZE: f32 = 10.0;
+const LINE_HEIGHT: f32 = 5.0;
+const ROW_HEIGHT: f32 = 6.0;
+const RUNNING_SIZE: f32 = 8.0;
+
+const BLACK: Rgb3 = (0.0, 0.0, 0.0);
+const GREY: Rgb3 = (0.55, 0.55, 0.55);
+const LIGHT_GREY: Rgb3 = (0.88, 0.88, 0.88);
+
+/// Series colors used when a series has none
+const PALETTE: [Rgb3; 6] = [
+    (0.12, 0.47, 0.71),
+    (0.84, 0.15, 0.16),
+    (0.17, 0.63, 0.17),
+    (1.0, 0.5, 0.05),
+    (0.58, 0.4, 0.74),
+    (0.55, 0.34, 0.29),
+];
+
+type Rgb3 = (f32, f32, f32);
+
+/// Drawing operation in page millimeters, origin at the bottom left
+#[derive(Debug, Clone)]
+pub(crate) enum DrawOp {
+    Text {
+        x: f32,
+        y: f32,
+        size: f32,
+        bold: bool,
+        text: String,
+    },
+    Line {
+        points: Vec<(f32, f32)>,
+        width: f32,
+        color: Rgb3,
+    },
+    Rect {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        fill: Option<Rgb3>,
+        stroke: Option<Rgb3>,
+    },
+}
+
+/// Report laid out onto pages
+pub(crate) struct LaidOutReport {
+    pub pages: Vec<Vec<DrawOp>>,
+    /// Section titles and the page they start on
+    pub bookmarks: Vec<(String, usize)>,
+}
+
+struct Layout<'a> {
+    template: &'a ReportTemplate,
+    height: f32,
+    left: f32,
+    right: f32,
+    top: f32,
+    bottom: f32,
+    y: f32,
+    pages: Vec<Vec<DrawOp>>,
+    bookmarks: Vec<(String, usize)>,
+}
+
+/// Lay out a template and the exported records
+///
+/// Headers and footers are added once the page count is known.
+pub(crate) fn layout(template: &ReportTemplate, records: &[DataRecord]) -> LaidOutReport {
+    let (width, height) = template.page_size.dimensions_mm();
+    let margin = template.margin_mm;
+    let top = height - margin - if template.header.is_some() { 10.0 } else { 0.0 };
+    let bottom = margin + if template.footer.is_some() { 10.0 } else { 0.0 };
+
+    let mut layout = Layout {
+        template,
+        height,
+        left: margin,
+        right: width - margin,
+        top,
+        bottom,
+        y: top,
+        pages: vec![Vec::new()],
+        bookmarks: Vec::new(),
+    };
+
+    layout.title_block();
+    for section in &template.sections {
+        if let Some(ref title) = section.title {
+            layout.heading(&template.expand(title));
+        }
+        for block in &section.blocks {
+            match block {
+                ReportBlock::Paragraph { text } => layout.paragraph(&template.expand(text)),
+                ReportBlock::Fields { fields } => layout.fields(fields),
+                ReportBlock::Table(table) => layout.table(table, records),
+                ReportBlock::Chart { chart, height_mm } => layout.chart(chart, *height_mm),
+                ReportBlock::PageBreak => layout.new_page(),
+            }
+        }
+    }
+    if let Some(ref signature) = template.signature {
+        layout.signature(signature);
+    }
+
+    layout.running_text()
+}
+
+impl<'a> Layout<'a> {
+    fn content_width(&self) -> f32 {
+        self.right - self.left
+    }
+
+    fn push(&mut self, op: DrawOp) {
+        if let Some(page) = self.pages.last_mut() {
+            page.push(op);
+        }
+    }
+
+    fn text(&mut self, x: f32, y: f32, size: f32, bold: bool, text: impl Into<String>) {
+        self.push(DrawOp::Text {
+            x,
+            y,
+            size,
+            bold,
+            text: text.into(),
+        });
+    }
+
+    fn new_page(&mut self) {
+        self.pages.push(Vec::new());
+        self.y = self.top;
+    }
+
+    /// Start a new page unless `height` fits below the cursor
+    fn ensure(&mut self, height: f32) -> bool {
+        if self.y - height < self.bottom && self.y < self.top {
+            self.new_page();
+            return true;
+        }
+        false
+    }
+
+    fn title_block(&mut self) {
+        let template = self.template;
+        self.y -= 8.0;
+        self.text(
+            self.left,
+            self.y,
+            18.0,
+            true,
+            template.expand(&template.title),
+        );
+        self.y -= 8.0;
+
+        if let Some(ref subtitle) = template.subtitle {
+            self.text(self.left, self.y, 12.0, false, template.expand(subtitle));
+            self.y -= 6.0;
+        }
+
+        let mut byline = chrono::Utc::now().format("Generated %Y-%m-%d").to_string();
+        if let Some(ref author) = template.author {
+            byline = format!("{} by {}", byline, template.expand(author));
+        }
+        self.text(self.left, self.y, RUNNING_SIZE, false, byline);
+        self.y -= 4.0;
+
+        let y = self.y;
+        self.rule(y, 0.8, BLACK);
+        self.y -= 6.0;
+    }
+
+    fn rule(&mut self, y: f32, width: f32, color: Rgb3) {
+        self.push(DrawOp::Line {
+            points: vec![(self.left, y), (self.right, y)],
+            width,
+            color,
+        });
+    }
+
+    fn heading(&mut self, title: &str) {
+        // Keep the heading with at least two lines of its content
+        self.ensure(10.0 + LINE_HEIGHT * 2.0);
+        self.y -= 4.0;
+        self.text(self.left, self.y, 14.0, true, title);
+        self.bookmarks.push((title.to_string(), self.pages.len() - 1));
+        self.y -= 6.0;
+    }
+
+    fn paragraph(&mut self, text: &str) {
+        for line in wrap(text, BODY_SIZE, self.content_width()) {
+            self.ensure(LINE_HEIGHT);
+            self.text(
+                self.left,
+                self.y - LINE_HEIGHT + 1.5,
+                BODY_SIZE,
+                false,
+                line,
+            );
+            self.y -= LINE_HEIGHT;
+        }
+        self.y -= LINE_HEIGHT / 2.0;
+    }
+
+    fn fields(&mut self, fields: &[(String, String)]) {
+        let key_width = 55.0;
+        let value_width = self.content_width() - key_width;
+
+        for (key, value) in fields {
+            let lines = wrap(&self.template.expand(value), BODY_SIZE, value_width);
+            let line_count = lines.len().max(1);
+            self.ensure(LINE_HEIGHT * line_count as f32);
+
+            let baseline = self.y - LINE_HEIGHT + 1.5;
+            self.text(
+                self.left,
+                baseline,
+                BODY_SIZE,
+                true,
+                self.template.expand(key),
+            );
+            for (i, line) in lines.into_iter().enumerate() {
+                let y = baseline - LINE_HEIGHT * i as f32;
+                self.text(self.left + key_width, y, BODY_SIZE, false, line);
+            }
+            self.y -= LINE_HEIGHT * line_count as f32;
+        }
+        self.y -= LINE_HEIGHT / 2.0;
+    }
+
+    fn table(&mut self, table: &TableBlock, records: &[DataRecord]) {
+        let rows = table.rows.as_deref().unwrap_or(records);
+        let columns = if table.columns.is_empty() {
+            columns_of(rows)
+        } else {
+            table.columns.clone()
+        };
+        if columns.is_empty() {
+            return;
+        }
+
+        let widths = column_widths(&columns, self.content_width());
+        self.ensure(ROW_HEIGHT * 2.0);
+        self.table_header(&columns, &widths);
+
+        for row in rows {
+            if self.ensure(ROW_HEIGHT) {
+                self.table_header(&columns, &widths);
+            }
+
+            let mut x = self.left;
+            for (column, width) in columns.iter().zip(&widths) {
+                let value = row.get(&column.field).map(format_pdf_value).unwrap_or_default();
+                let text = truncate(&value, 9.0, width - 2.0);
+                self.text(x + 1.0, self.y - ROW_HEIGHT + 1.8, 9.0, false, text);
+                x += width;
+            }
+            self.y -= ROW_HEIGHT;
+            let y = self.y;
+            self.rule(y, 0.2, LIGHT_GREY);
+        }
+        self.y -= LINE_HEIGHT;
+    }
+
+    fn table_header(&mut self, columns: &[TableColumn], widths: &[f32]) {
+        self.push(DrawOp::Rect {
+            x: self.left,
+            y: self.y - ROW_HEIGHT,
+            width: self.content_width(),
+            height: ROW_HEIGHT,
+            fill: Some(LIGHT_GREY),
+            stroke: None,
+        });
+
+        let mut x = self.left;
+        for (column, width) in columns.iter().zip(widths) {
+            let header = truncate(&column.header, 9.0, width - 2.0);
+            self.text(x + 1.0, self.y - ROW_HEIGHT + 1.8, 9.0, true, header);
+            x += width;
+        }
+        self.y -= ROW_HEIGHT;
+    }
+
+    fn chart(&mut self, chart: &ChartData, height: f32) {
+        self.ensure(height + 8.0);
+        self.text(self.left, self.y - 4.0, 11.0, true, chart.title.clone());
+        self.y -= 7.0;
+
+        // Plot area leaves room for tick labels and the legend
+        let plot_left = self.left + 15.0;
+        let plot_right = self.right - 2.0;
+        let plot_top = self.y - 4.0;
+        let plot_bottom = self.y - height + 14.0;
+
+        let points = chart.series.iter().flat_map(|s| s.data.iter());
+        let (x_min, x_max) = range(
+            points.clone().map(|p| p.x),
+            chart.x_axis.min,
+            chart.x_axis.max,
+        );
+        let (y_min, y_max) = range(points.map(|p| p.y), chart.y_axis.min, chart.y_axis.max);
+        let map_x =
+            |x: f64| plot_left + ((x - x_min) / (x_max - x_min)) as f32 * (plot_right - plot_left);
+        let map_y = |y: f64| {
+            plot_bottom + ((y - y_min) / (y_max - y_min)) as f32 * (plot_top - plot_bottom)
+        };
+
+        // Grid and tick labels
+        const TICKS: usize = 4;
+        for i in 0..=TICKS {
+            let fraction = i as f64 / TICKS as f64;
+            let y_value = y_min + (y_max - y_min) * fraction;
+            let x_value = x_min + (x_max - x_min) * fraction;
+            let (x, y) = (map_x(x_value), map_y(y_value));
+
+            if chart.y_axis.grid && i > 0 {
+                self.push(DrawOp::Line {
+                    points: vec![(plot_left, y), (plot_right, y)],
+                    width: 0.2,
+                    color: LIGHT_GREY,
+                });
+            }
+            if chart.x_axis.grid && i > 0 {
+                self.push(DrawOp::Line {
+                    points: vec![(x, plot_bottom), (x, plot_top)],
+                    width: 0.2,
+                    color: LIGHT_GREY,
+                });
+            }
+
+            let label = format_tick(y_value);
+            let label_x = plot_left - 1.5 - text_width(&label, 7.0);
+            self.text(label_x, y - 1.0, 7.0, false, label);
+            let label = format_tick(x_value);
+            let label_x = x - text_width(&label, 7.0) / 2.0;
+            self.text(label_x, plot_bottom - 4.0, 7.0, false, label);
+        }
+
+        self.push(DrawOp::Rect {
+            x: plot_left,
+            y: plot_bottom,
+            width: plot_right - plot_left,
+            height: plot_top - plot_bottom,
+            fill: None,
+            stroke: Some(GREY),
+        });
+
+        if !chart.y_axis.label.is_empty() {
+            self.text(
+                plot_left,
+                plot_top + 1.0,
+                7.0,
+                false,
+                chart.y_axis.label.clone(),
+            );
+        }
+        if !chart.x_axis.label.is_empty() {
+            let x = plot_right - text_width(&chart.x_axis.label, 7.0);
+            self.text(x, plot_bottom - 8.0, 7.0, false, chart.x_axis.label.clone());
+        }
+
+        // Series
+        let baseline = map_y(0.0_f64.clamp(y_min, y_max));
+        let bar_series = chart.series.iter().filter(|s| s.chart_type == ChartType::Bar).count();
+        let mut bar_index = 0;
+
+        for (index, series) in chart.series.iter().enumerate() {
+            let color = series
+                .color
+                .as_deref()
+                .and_then(parse_color)
+                .unwrap_or(PALETTE[index % PALETTE.len()]);
+
+            match series.chart_type {
+                ChartType::Scatter => {
+                    for point in &series.data {
+                        self.push(DrawOp::Rect {
+                            x: map_x(point.x) - 0.6,
+                            y: map_y(point.y) - 0.6,
+                            width: 1.2,
+                            height: 1.2,
+                            fill: Some(color),
+                            stroke: None,
+                        });
+                    }
+                },
+                ChartType::Bar => {
+                    let slot = (plot_right - plot_left) / series.data.len().max(1) as f32;
+                    let width = slot * 0.8 / bar_series as f32;
+                    for point in &series.data {
+                        let x = map_x(point.x) - slot * 0.4 + width * bar_index as f32;
+                        let y = map_y(point.y);
+                        self.push(DrawOp::Rect {
+                            x,
+                            y: y.min(baseline),
+                            width,
+                            height: (y - baseline).abs(),
+                            fill: Some(color),
+                            stroke: None,
+                        });
+                    }
+                    bar_index += 1;
+                },
+                _ => {
+                    let mut data: Vec<_> = series.data.iter().collect();
+                    data.sort_by(|a, b| a.x.total_cmp(&b.x));
+                    let points: Vec<(f32, f32)> =
+                        data.iter().map(|p| (map_x(p.x), map_y(p.y))).collect();
+                    if points.len() > 1 {
+                        self.push(DrawOp::Line {
+                            points,
+                            width: 1.0,
+                            color,
+                        });
+                    }
+                },
+            }
+
+            // Legend entry
+            let legend_x = self.left + 15.0 + 45.0 * index as f32;
+            let legend_y = plot_bottom - 11.0;
+            self.push(DrawOp::Rect {
+                x: legend_x,
+                y: legend_y,
+                width: 3.0,
+                height: 3.0,
+                fill: Some(color),
+                stroke: None,
+            });
+            let name = truncate(&series.name, 7.0, 38.0);
+            self.text(legend_x + 4.5, legend_y + 0.4, 7.0, false, name);
+        }
+
+        self.y -= height - 7.0 + LINE_HEIGHT;
+    }
+
+    fn signature(&mut self, signature: &SignatureBlock) {
+        let placeholder = if signature.digital_placeholder {
+            30.0
+        } else {
+            0.0
+        };
+        let needed = 20.0 * signature.signers.len() as f32 + placeholder + 20.0;
+        self.ensure(needed);
+
+        self.heading("Signatures");
+        if let Some(ref statement) = signature.statement {
+            self.paragraph(&self.template.expand(statement));
+        }
+
+        for signer in &signature.signers {
+            self.y -= 14.0;
+            let y = self.y;
+            self.push(DrawOp::Line {
+                points: vec![(self.left, y), (self.left + 90.0, y)],
+                width: 0.5,
+                color: BLACK,
+            });
+            self.push(DrawOp::Line {
+                points: vec![(self.left + 110.0, y), (self.right, y)],
+                width: 0.5,
+                color: BLACK,
+            });
+            self.text(self.left, y - 4.0, 9.0, false, self.template.expand(signer));
+            self.text(self.left + 110.0, y - 4.0, 9.0, false, "Date");
+            self.y -= 6.0;
+        }
+
+        if signature.digital_placeholder {
+            self.y -= 6.0;
+            self.push(DrawOp::Rect {
+                x: self.left,
+                y: self.y - 24.0,
+                width: 90.0,
+                height: 24.0,
+                fill: None,
+                stroke: Some(GREY),
+            });
+            let y = self.y - 5.0;
+            self.text(self.left + 2.0, y, 8.0, false, "Digital signature");
+            self.y -= 24.0;
+        }
+    }
+
+    /// Add headers and footers now that the page count is known
+    fn running_text(mut self) -> LaidOutReport {
+        let template = self.template;
+        let pages = self.pages.len();
+        let date = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        let margin = template.margin_mm;
+
+        for page in 0..pages {
+            let expand = |text: &str| {
+                template
+                    .expand(text)
+                    .replace("{page}", &(page + 1).to_string())
+                    .replace("{pages}", &pages.to_string())
+                    .replace("{date}", &date)
+            };
+
+            let mut ops = Vec::new();
+            if let Some(ref header) = template.header {
+                let y = self.height - margin - 4.0;
+                self.running_line(&mut ops, header, y, &expand);
+                ops.push(DrawOp::Line {
+                    points: vec![(self.left, y - 2.5), (self.right, y - 2.5)],
+                    width: 0.3,
+                    color: GREY,
+                });
+            }
+            if let Some(ref footer) = template.footer {
+                let y = margin + 2.0;
+                self.running_line(&mut ops, footer, y, &expand);
+                ops.push(DrawOp::Line {
+                    points: vec![(self.left, y + 4.0), (self.right, y + 4.0)],
+                    width: 0.3,
+                    color: GREY,
+                });
+            }
+            self.pages[page].extend(ops);
+        }
+
+        LaidOutReport {
+            pages: self.pages,
+            bookmarks: self.bookmarks,
+        }
+    }
+
+    fn running_line(
+        &self,
+        ops: &mut Vec<DrawOp>,
+        line: &PageTemplate,
+        y: f32,
+        expand: &dyn Fn(&str) -> String,
+    ) {
+        let parts = [
+            (line.left.as_deref(), 0.0),
+            (line.center.as_deref(), 0.5),
+            (line.right.as_deref(), 1.0),
+        ];
+
+        for (text, align) in parts {
+            let Some(text) = text.map(expand) else {
+                continue;
+            };
+            let x = self.left + (self.content_width() - text_width(&text, RUNNING_SIZE)) * align;
+            ops.push(DrawOp::Text {
+                x,
+                y,
+                size: RUNNING_SIZE,
+                bold: false,
+                text,
+            });
+        }
+    }
+}
+
+/// Write a laid out report as a PDF document
+pub(crate) fn write_pdf(template: &ReportTemplate, report: &LaidOutReport) -> Result<Vec<u8>> {
+    let (width, height) = template.page_size.dimensions_mm();
+    let title = template.expand(&template.title);
+    let (doc, first_page, first_layer) = PdfDocument::new(&title, Mm(width), Mm(height), "Content");
+
+    let mut doc = doc.with_creator("AccuScene Enterprise");
+    if let Some(ref author) = template.author {
+        doc = doc.with_author(template.expand(author));
+    }
+    if let Some(ref subtitle) = template.subtitle {
+        doc = doc.with_subject(template.expand(subtitle));
+    }
+
+    let font = doc
+        .add_builtin_font(BuiltinFont::Helvetica)
+        .map_err(|e| TransferError::Pdf(format!("Font error: {:?}", e)))?;
+    let font_bold = doc
+        .add_builtin_font(BuiltinFont::HelveticaBold)
+        .map_err(|e| TransferError::Pdf(format!("Font error: {:?}", e)))?;
+
+    let mut page_indices = Vec::with_capacity(report.pages.len());
+    for (index, ops) in report.pages.iter().enumerate() {
+        let (page, layer) = if index == 0 {
+            (first_page, first_layer)
+        } else {
+            doc.add_page(Mm(width), Mm(height), "Content")
+        };
+        page_indices.push(page);
+
+        let layer = doc.get_page(page).get_layer(layer);
+        for op in ops {
+            draw(&layer, op, &font, &font_bold);
+        }
+    }
+
+    for (title, page) in &report.bookmarks {
+        doc.add_bookmark(title.as_str(), page_indices[*page]);
+    }
+
+    doc.save_to_bytes()
+        .map_err(|e| TransferError::Pdf(format!("PDF save error: {:?}", e)))
+}
+
+fn draw(
+    layer: &PdfLayerReference,
+    op: &DrawOp,
+    font: &printpdf::IndirectFontRef,
+    font_bold: &printpdf::IndirectFontRef,
+) {
+    match op {
+        DrawOp::Text {
+            x,
+            y,
+            size,
+            bold,
+            text,
+        } => {
+            layer.set_fill_color(color(BLACK));
+            let font = if *bold { font_bold } else { font };
+            layer.use_text(text.as_str(), *size, Mm(*x), Mm(*y), font);
+        },
+        DrawOp::Line {
+            points,
+            width,
+            color: rgb,
+        } => {
+            layer.set_outline_color(color(*rgb));
+            layer.set_outline_thickness(*width);
+            layer.add_line(Line {
+                points: points.iter().map(|(x, y)| (Point::new(Mm(*x), Mm(*y)), false)).collect(),
+                is_closed: false,
+            });
+        },
+        DrawOp::Rect {
+            x,
+            y,
+            width,
+            height,
+            fill,
+            stroke,
+        } => {
+            let mode = match (fill, stroke) {
+                (Some(_), Some(_)) => PaintMode::FillStroke,
+                (Some(_), None) => PaintMode::Fill,
+                _ => PaintMode::Stroke,
+            };
+            if let Some(fill) = fill {
+                layer.set_fill_color(color(*fill));
+            }
+            if let Some(stroke) = stroke {
+                layer.set_outline_color(color(*stroke));
+                layer.set_outline_thickness(0.5);
+            }
+            layer
+                .add_rect(Rect::new(Mm(*x), Mm(*y), Mm(x + width), Mm(y + height)).with_mode(mode));
+        },
+    }
+}
+
+fn color((r, g, b): Rgb3) -> Color {
+    Color::Rgb(Rgb::new(r, g, b, None))
+}
+
+/// Parse `#rrggbb` colors
+fn parse_color(hex: &str) -> Option<Rgb3> {
+    let hex = hex.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel =
+        |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok().map(|c| c as f32 / 255.0);
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+/// Estimated width of text in millimeters
+fn text_width(text: &str, size: f32) -> f32 {
+    text.chars().count() as f32 * size * GLYPH_WIDTH * PT_TO_MM
+}
+
+fn max_chars(size: f32, width: f32) -> usize {
+    ((width / (size * GLYPH_WIDTH * PT_TO_MM)) as usize).max(1)
+}
+
+/// Greedy word wrap to the given width
+fn wrap(text: &str, size: f32, width: f32) -> Vec<String> {
+    let limit = max_chars(size, width);
+    let mut lines = Vec::new();
+
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let mut word: Vec<char> = word.chars().collect();
+            // Hard-break words longer than a line
+            while word.len() > limit {
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+                lines.push(word.drain(..limit).collect());
+            }
+            let word: String = word.into_iter().collect();
+
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > limit {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&word);
+        }
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Cut text to the given width
+fn truncate(text: &str, size: f32, width: f32) -> String {
+    let limit = max_chars(size, width);
+    if text.chars().count() <= limit {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(limit.saturating_sub(3)).collect();
+    format!("{}...", kept)
+}
+
+/// Every field of the rows in name order
+fn columns_of(rows: &[DataRecord]) -> Vec<TableColumn> {
+    let mut fields: Vec<&String> = rows.iter().flat_map(|row| row.fields.keys()).collect();
+    fields.sort();
+    fields.dedup();
+    fields
+        .into_iter()
+        .map(|field| TableColumn::new(field.as_str(), field.as_str()))
+        .collect()
+}
+
+/// Fixed widths first, the rest share what remains
+fn column_widths(columns: &[TableColumn], total: f32) -> Vec<f32> {
+    let fixed: f32 = columns.iter().filter_map(|c| c.width_mm).sum();
+    let flexible = columns.iter().filter(|c| c.width_mm.is_none()).count();
+    let share = if flexible > 0 {
+        ((total - fixed) / flexible as f32).max(10.0)
+    } else {
+        0.0
+    };
+    columns.iter().map(|c| c.width_mm.unwrap_or(share)).collect()
+}
+
+/// Axis range from explicit bounds or the data
+fn range(values: impl Iterator<Item = f64>, min: Option<f64>, max: Option<f64>) -> (f64, f64) {
+    let (data_min, data_max) = values
+        .filter(|v| v.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+            (lo.min(v), hi.max(v))
+        });
+    let (data_min, data_max) = if data_min > data_max {
+        (0.0, 1.0)
+    } else {
+        (data_min, data_max)
+    };
+
+    let lo = min.unwrap_or(data_min);
+    let hi = max.unwrap_or(data_max);
+    if hi - lo > f64::EPSILON {
+        (lo, hi)
+    } else {
+        (lo - 0.5, hi + 0.5)
+    }
+}
+
+fn format_tick(value: f64) -> String {
+    if value.abs() >= 100.0 || value.fract().abs() < 1e-9 {
+        format!("{:.0}", value)
+    } else {
+        format!("{:.2}", value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::report::{ReportSection, TableBlock};
+    use serde_json::Value;
+
+    fn texts(page: &[DrawOp]) -> Vec<&str> {
+        page.iter()
+            .filter_map(|op| match op {
+                DrawOp::Text { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_table_pagination() {
+        let template = ReportTemplate::new("Evidence Log")
+            .with_section(ReportSection::new("Items").with_table(TableBlock::default()));
+        let records: Vec<DataRecord> = (0..100)
+            .map(|i| {
+                let mut record = DataRecord::new();
+                record.set("item".to_string(), Value::from(i));
+                record
+            })
+            .collect();
+
+        let report = layout(&template, &records);
+        assert!(report.pages.len() >= 3);
+        assert_eq!(report.bookmarks, vec![("Items".to_string(), 0)]);
+
+        // Every page repeats the table header and carries its page number
+        let pages = report.pages.len();
+        for (index, page) in report.pages.iter().enumerate() {
+            let texts = texts(page);
+            assert!(texts.contains(&"item"));
+            assert!(texts.contains(&format!("Page {} of {}", index + 1, pages).as_str()));
+        }
+    }
+
+    #[test]
+    fn test_wrap_and_truncate() {
+        let lines = wrap(
+            "The vehicle left 24 m of skid marks before impact",
+            10.0,
+            30.0,
+        );
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|line| line.chars().count() <= max_chars(10.0, 30.0)));
+
+        assert_eq!(truncate("short", 9.0, 40.0), "short");
+        assert!(truncate(&"x".repeat(100), 9.0, 20.0).ends_with("..."));
+        assert_eq!(parse_color("#ff0000"), Some((1.0, 0.0, 0.0)));
+    }
+}
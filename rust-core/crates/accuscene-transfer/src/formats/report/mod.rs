@@ -0,0 +1,459 @@
+/// Template-driven PDF report generation
+///
+/// A [`ReportTemplate`] describes a complete report: page setup, running
+/// header and footer, titled sections of paragraphs, key/value blocks,
+/// tables and charts, and an optional signature block. Tables without
+/// their own rows are bound to the records being exported, so the same
+/// template renders any result set.
+mod layout;
+
+use crate::{
+    config::TransferConfig,
+    error::{Result, TransferError},
+    formats::ExportHandler,
+    progress::ProgressTracker,
+    DataRecord,
+};
+use accuscene_visualization::ChartData;
+use async_trait::async_trait;
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Page size of a report
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PageSize {
+    A4,
+    Letter,
+    Legal,
+}
+
+impl PageSize {
+    /// Width and height in millimeters
+    pub fn dimensions_mm(&self) -> (f32, f32) {
+        match self {
+            PageSize::A4 => (210.0, 297.0),
+            PageSize::Letter => (215.9, 279.4),
+            PageSize::Legal => (215.9, 355.6),
+        }
+    }
+}
+
+/// Running header or footer
+///
+/// Text may use the placeholders `{title}`, `{page}`, `{pages}`, `{date}`
+/// and any template variable.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PageTemplate {
+    pub left: Option<String>,
+    pub center: Option<String>,
+    pub right: Option<String>,
+}
+
+impl PageTemplate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_left(mut self, text: impl Into<String>) -> Self {
+        self.left = Some(text.into());
+        self
+    }
+
+    pub fn with_center(mut self, text: impl Into<String>) -> Self {
+        self.center = Some(text.into());
+        self
+    }
+
+    pub fn with_right(mut self, text: impl Into<String>) -> Self {
+        self.right = Some(text.into());
+        self
+    }
+}
+
+/// Table column bound to a record field
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableColumn {
+    pub field: String,
+    pub header: String,
+    /// Fixed width in millimeters; remaining space is shared otherwise
+    pub width_mm: Option<f32>,
+}
+
+impl TableColumn {
+    pub fn new(field: impl Into<String>, header: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            header: header.into(),
+            width_mm: None,
+        }
+    }
+
+    pub fn with_width(mut self, width_mm: f32) -> Self {
+        self.width_mm = Some(width_mm);
+        self
+    }
+}
+
+/// Table block
+///
+/// With no columns, every field of the rows becomes a column. With no rows,
+/// the table is bound to the exported records.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TableBlock {
+    #[serde(default)]
+    pub columns: Vec<TableColumn>,
+    #[serde(default)]
+    pub rows: Option<Vec<DataRecord>>,
+}
+
+impl TableBlock {
+    /// Table of the exported records
+    pub fn records(columns: Vec<TableColumn>) -> Self {
+        Self {
+            columns,
+            rows: None,
+        }
+    }
+
+    /// Table of fixed rows
+    pub fn rows(columns: Vec<TableColumn>, rows: Vec<DataRecord>) -> Self {
+        Self {
+            columns,
+            rows: Some(rows),
+        }
+    }
+}
+
+/// Content block of a section
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReportBlock {
+    Paragraph { text: String },
+    Fields { fields: Vec<(String, String)> },
+    Table(TableBlock),
+    Chart { chart: ChartData, height_mm: f32 },
+    PageBreak,
+}
+
+/// Titled report section
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportSection {
+    pub title: Option<String>,
+    pub blocks: Vec<ReportBlock>,
+}
+
+impl ReportSection {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: Some(title.into()),
+            blocks: Vec::new(),
+        }
+    }
+
+    /// Section without a heading
+    pub fn untitled() -> Self {
+        Self {
+            title: None,
+            blocks: Vec::new(),
+        }
+    }
+
+    pub fn with_block(mut self, block: ReportBlock) -> Self {
+        self.blocks.push(block);
+        self
+    }
+
+    pub fn with_paragraph(self, text: impl Into<String>) -> Self {
+        self.with_block(ReportBlock::Paragraph { text: text.into() })
+    }
+
+    pub fn with_fields(self, fields: Vec<(String, String)>) -> Self {
+        self.with_block(ReportBlock::Fields { fields })
+    }
+
+    pub fn with_table(self, table: TableBlock) -> Self {
+        self.with_block(ReportBlock::Table(table))
+    }
+
+    pub fn with_chart(self, chart: ChartData, height_mm: f32) -> Self {
+        self.with_block(ReportBlock::Chart { chart, height_mm })
+    }
+
+    pub fn with_page_break(self) -> Self {
+        self.with_block(ReportBlock::PageBreak)
+    }
+}
+
+/// Signature block closing the report
+///
+/// Renders a signature line per signer and a reserved box where a signing
+/// tool can later apply the digital signature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureBlock {
+    pub signers: Vec<String>,
+    pub statement: Option<String>,
+    #[serde(default = "default_true")]
+    pub digital_placeholder: bool,
+}
+
+impl SignatureBlock {
+    pub fn new(signers: Vec<String>) -> Self {
+        Self {
+            signers,
+            statement: None,
+            digital_placeholder: true,
+        }
+    }
+
+    pub fn with_statement(mut self, statement: impl Into<String>) -> Self {
+        self.statement = Some(statement.into());
+        self
+    }
+
+    pub fn with_digital_placeholder(mut self, enabled: bool) -> Self {
+        self.digital_placeholder = enabled;
+        self
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Complete report definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportTemplate {
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub author: Option<String>,
+    pub page_size: PageSize,
+    pub margin_mm: f32,
+    pub header: Option<PageTemplate>,
+    pub footer: Option<PageTemplate>,
+    pub sections: Vec<ReportSection>,
+    pub signature: Option<SignatureBlock>,
+    /// Values substituted for `{name}` placeholders in all text
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+}
+
+impl ReportTemplate {
+    /// Create template with an A4 page and a page-numbered footer
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            subtitle: None,
+            author: None,
+            page_size: PageSize::A4,
+            margin_mm: 15.0,
+            header: None,
+            footer: Some(
+                PageTemplate::new().with_left("{date}").with_right("Page {page} of {pages}"),
+            ),
+            sections: Vec::new(),
+            signature: None,
+            variables: HashMap::new(),
+        }
+    }
+
+    pub fn with_subtitle(mut self, subtitle: impl Into<String>) -> Self {
+        self.subtitle = Some(subtitle.into());
+        self
+    }
+
+    pub fn with_author(mut self, author: impl Into<String>) -> Self {
+        self.author = Some(author.into());
+        self
+    }
+
+    pub fn with_page_size(mut self, page_size: PageSize) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    pub fn with_margin(mut self, margin_mm: f32) -> Self {
+        self.margin_mm = margin_mm;
+        self
+    }
+
+    pub fn with_header(mut self, header: PageTemplate) -> Self {
+        self.header = Some(header);
+        self
+    }
+
+    pub fn with_footer(mut self, footer: Option<PageTemplate>) -> Self {
+        self.footer = footer;
+        self
+    }
+
+    pub fn with_section(mut self, section: ReportSection) -> Self {
+        self.sections.push(section);
+        self
+    }
+
+    pub fn with_signature(mut self, signature: SignatureBlock) -> Self {
+        self.signature = Some(signature);
+        self
+    }
+
+    pub fn with_variable(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.variables.insert(name.into(), value.into());
+        self
+    }
+
+    /// Check the template can be laid out
+    pub fn validate(&self) -> Result<()> {
+        let (width, height) = self.page_size.dimensions_mm();
+        if self.margin_mm < 0.0 || self.margin_mm * 2.0 >= width.min(height) - 40.0 {
+            return Err(TransferError::InvalidConfig(format!(
+                "Report margin {}mm does not fit the page",
+                self.margin_mm
+            )));
+        }
+
+        for section in &self.sections {
+            for block in &section.blocks {
+                if let ReportBlock::Chart { chart, height_mm } = block {
+                    if *height_mm < 30.0 || *height_mm > height - self.margin_mm * 2.0 - 40.0 {
+                        return Err(TransferError::InvalidConfig(format!(
+                            "Chart '{}' height {}mm does not fit the page",
+                            chart.title, height_mm
+                        )));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replace `{name}` placeholders with template variables
+    pub(crate) fn expand(&self, text: &str) -> String {
+        let mut text = text.replace("{title}", &self.title);
+        for (name, value) in &self.variables {
+            text = text.replace(&format!("{{{}}}", name), value);
+        }
+        text
+    }
+}
+
+/// Render a report to PDF bytes
+pub fn render_report(template: &ReportTemplate, records: &[DataRecord]) -> Result<Bytes> {
+    template.validate()?;
+    let report = layout::layout(template, records);
+    layout::write_pdf(template, &report).map(Bytes::from)
+}
+
+/// PDF export rendering records through a report template
+pub struct ReportHandler {
+    template: ReportTemplate,
+}
+
+impl ReportHandler {
+    pub fn new(template: ReportTemplate) -> Self {
+        Self { template }
+    }
+
+    pub fn template(&self) -> &ReportTemplate {
+        &self.template
+    }
+}
+
+impl Default for ReportHandler {
+    /// Title plus a table of all exported records
+    fn default() -> Self {
+        Self::new(
+            ReportTemplate::new("AccuScene Report")
+                .with_section(ReportSection::new("Records").with_table(TableBlock::default())),
+        )
+    }
+}
+
+#[async_trait]
+impl ExportHandler for ReportHandler {
+    async fn export(
+        &self,
+        records: Vec<DataRecord>,
+        _config: &TransferConfig,
+        tracker: Option<ProgressTracker>,
+    ) -> Result<Bytes> {
+        if let Some(ref t) = tracker {
+            t.start().await;
+            t.update(0, Some("Rendering report".to_string())).await;
+        }
+
+        let pdf = render_report(&self.template, &records)?;
+
+        if let Some(ref t) = tracker {
+            t.update(records.len() as u64, None).await;
+            t.complete().await;
+        }
+
+        Ok(pdf)
+    }
+
+    async fn export_stream(
+        &self,
+        records: Vec<DataRecord>,
+        config: &TransferConfig,
+        tracker: Option<ProgressTracker>,
+    ) -> Result<Bytes> {
+        // Page numbering needs the whole report laid out first
+        self.export(records, config, tracker).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use accuscene_visualization::{ChartType, SeriesData, SeriesPoint};
+    use serde_json::Value;
+
+    #[tokio::test]
+    async fn test_render_report() {
+        let mut chart = ChartData::new("Pre-crash speed".to_string());
+        chart.add_series(SeriesData::new(
+            "Unit 1".to_string(),
+            (0..10)
+                .map(|i| SeriesPoint::new(i as f64 * 0.5 - 5.0, 20.0 - i as f64))
+                .collect(),
+            ChartType::Line,
+        ));
+
+        let template = ReportTemplate::new("Reconstruction Report")
+            .with_subtitle("Case {case_number}")
+            .with_variable("case_number", "2024-000123")
+            .with_header(PageTemplate::new().with_left("{title}").with_right("Case {case_number}"))
+            .with_section(
+                ReportSection::new("Summary")
+                    .with_paragraph("Unit 1 struck Unit 2 at the intersection.")
+                    .with_fields(vec![("Weather".to_string(), "Clear".to_string())]),
+            )
+            .with_section(ReportSection::new("Speed").with_chart(chart, 80.0))
+            .with_section(
+                ReportSection::new("Evidence").with_table(TableBlock::records(vec![
+                    TableColumn::new("id", "ID").with_width(20.0),
+                    TableColumn::new("description", "Description"),
+                ])),
+            )
+            .with_signature(SignatureBlock::new(vec!["Lead Investigator".to_string()]));
+
+        let records: Vec<DataRecord> = (0..5)
+            .map(|i| {
+                let mut record = DataRecord::new();
+                record.set("id".to_string(), Value::from(i));
+                record.set("description".to_string(), Value::from("Skid mark"));
+                record
+            })
+            .collect();
+
+        let handler = ReportHandler::new(template);
+        let pdf = handler.export(records, &TransferConfig::default(), None).await.unwrap();
+        assert_eq!(&pdf[0..4], b"%PDF");
+
+        let invalid = ReportTemplate::new("Invalid").with_margin(200.0);
+        assert!(render_report(&invalid, &[]).is_err());
+    }
+}
@@ -0,0 +1,1081 @@
+use crate::{
+    config::TransferConfig,
+    error::{Result, TransferError},
+    formats::{DataStream, ExportHandler, ImportHandler},
+    progress::ProgressTracker,
+    DataRecord,
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{Duration, NaiveDate};
+use futures::{stream, StreamExt, TryStreamExt};
+use quick_xml::escape::{escape, unescape};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::io::{BufReader, Cursor, Read, Seek, Write};
+use tokio::sync::mpsc;
+use zip::{write::FileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+/// Field holding the sheet name when several sheets are imported
+pub const SHEET_FIELD: &str = "_sheet";
+
+/// Records buffered between the sheet parser and the stream consumer
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// Rows between progress updates and cancellation checks
+const PROGRESS_INTERVAL: u64 = 1000;
+
+/// Sheets to import
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum SheetSelection {
+    /// Sheet from `excel_sheet_name` or `excel_sheet_index`
+    #[default]
+    Config,
+    /// Sheets by name, in the given order
+    Named(Vec<String>),
+    /// Every sheet in workbook order
+    All,
+}
+
+/// Streaming XLSX handler
+///
+/// Imports parse sheet XML event by event, so memory does not grow with the
+/// number of rows; only the shared string table is held in memory. Exports
+/// write rows straight into the compressed sheet entry using inline
+/// strings. The first row of a sheet is its header row.
+#[derive(Debug, Clone, Default)]
+pub struct XlsxHandler {
+    sheets: SheetSelection,
+}
+
+impl XlsxHandler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Import the named sheets
+    pub fn with_sheets(mut self, sheets: Vec<String>) -> Self {
+        self.sheets = SheetSelection::Named(sheets);
+        self
+    }
+
+    /// Import every sheet
+    pub fn all_sheets(mut self) -> Self {
+        self.sheets = SheetSelection::All;
+        self
+    }
+
+    /// Resolve the selection against the workbook's sheets
+    fn selected_sheets<R: Read + Seek>(
+        &self,
+        reader: &XlsxReader<R>,
+        config: &TransferConfig,
+    ) -> Result<Vec<String>> {
+        let names = reader.sheet_names();
+        let find = |name: &str| {
+            names
+                .iter()
+                .find(|n| n.as_str() == name)
+                .cloned()
+                .ok_or_else(|| TransferError::Excel(format!("Sheet '{}' not found", name)))
+        };
+
+        match self.sheets {
+            SheetSelection::Config => match config.excel_sheet_name {
+                Some(ref name) => Ok(vec![find(name)?]),
+                None => names
+                    .get(config.excel_sheet_index)
+                    .map(|name| vec![name.clone()])
+                    .ok_or_else(|| {
+                        TransferError::Excel(format!(
+                            "Sheet index {} not found",
+                            config.excel_sheet_index
+                        ))
+                    }),
+            },
+            SheetSelection::Named(ref sheets) => sheets.iter().map(|name| find(name)).collect(),
+            SheetSelection::All => Ok(names),
+        }
+    }
+}
+
+#[async_trait]
+impl ImportHandler for XlsxHandler {
+    async fn import(
+        &self,
+        data: Bytes,
+        config: &TransferConfig,
+        tracker: Option<ProgressTracker>,
+    ) -> Result<Vec<DataRecord>> {
+        self.import_stream(data, config, tracker).await?.try_collect().await
+    }
+
+    async fn import_stream(
+        &self,
+        data: Bytes,
+        config: &TransferConfig,
+        tracker: Option<ProgressTracker>,
+    ) -> Result<DataStream> {
+        if let Some(ref t) = tracker {
+            t.start().await;
+        }
+
+        let mut reader = XlsxReader::new(Cursor::new(data))?;
+        let sheets = self.selected_sheets(&reader, config)?;
+        let tag_sheet = sheets.len() > 1;
+        let continue_on_error = config.continue_on_error;
+        let error_threshold = config.error_threshold;
+
+        let (tx, rx) = mpsc::channel::<Result<DataRecord>>(CHANNEL_CAPACITY);
+        tokio::task::spawn_blocking(move || {
+            let mut error_count = 0;
+            for sheet in &sheets {
+                let mut headers: Vec<String> = Vec::new();
+                let result = reader.read_sheet(sheet, |row_idx, row| {
+                    if headers.is_empty() {
+                        headers = row
+                            .iter()
+                            .enumerate()
+                            .map(|(i, value)| header_name(value, i))
+                            .collect();
+                        return Ok(true);
+                    }
+
+                    let mut record = row_to_record(row, &headers);
+                    if tag_sheet {
+                        record.set(SHEET_FIELD.to_string(), Value::String(sheet.clone()));
+                    }
+                    if record.fields.values().all(Value::is_null) {
+                        tracing::debug!("Skipping empty row {} in sheet '{}'", row_idx, sheet);
+                        return Ok(true);
+                    }
+
+                    // Stop parsing once the consumer has gone away
+                    Ok(tx.blocking_send(Ok(record)).is_ok())
+                });
+
+                if let Err(e) = result {
+                    error_count += 1;
+                    if !continue_on_error || error_count >= error_threshold {
+                        let _ = tx.blocking_send(Err(e));
+                        return;
+                    }
+                    tracing::warn!("XLSX error in sheet '{}': {}", sheet, e);
+                }
+            }
+        });
+
+        let records = stream::unfold(
+            (rx, tracker, 0u64),
+            |(mut rx, tracker, processed)| async move {
+                if processed % PROGRESS_INTERVAL == 0 {
+                    if let Some(ref t) = tracker {
+                        if t.is_cancelled().await {
+                            // Dropping the receiver stops the parser
+                            return Some((Err(TransferError::Cancelled), (rx, None, processed)));
+                        }
+                        t.update(processed, Some("Reading XLSX rows".to_string())).await;
+                    }
+                }
+
+                match rx.recv().await {
+                    Some(record) => Some((record, (rx, tracker, processed + 1))),
+                    None => {
+                        if let Some(ref t) = tracker {
+                            t.update(processed, None).await;
+                            t.complete().await;
+                        }
+                        None
+                    },
+                }
+            },
+        );
+
+        // Nothing follows an error or a cancellation
+        let records = records.scan(false, |failed, record| {
+            let item = if *failed { None } else { Some(record) };
+            if let Some(Err(_)) = item {
+                *failed = true;
+            }
+            futures::future::ready(item)
+        });
+
+        Ok(Box::pin(records))
+    }
+
+    async fn validate(&self, data: Bytes, config: &TransferConfig) -> Result<()> {
+        let reader = XlsxReader::new(Cursor::new(data))?;
+        self.selected_sheets(&reader, config)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ExportHandler for XlsxHandler {
+    async fn export(
+        &self,
+        records: Vec<DataRecord>,
+        config: &TransferConfig,
+        tracker: Option<ProgressTracker>,
+    ) -> Result<Bytes> {
+        let stream: DataStream = Box::pin(stream::iter(records.into_iter().map(Ok)));
+        let sheet = config.excel_sheet_name.as_deref().unwrap_or("Sheet1");
+        let buffer = write_stream(Cursor::new(Vec::new()), sheet, stream, tracker).await?;
+        Ok(Bytes::from(buffer.into_inner()))
+    }
+
+    async fn export_stream(
+        &self,
+        records: Vec<DataRecord>,
+        config: &TransferConfig,
+        tracker: Option<ProgressTracker>,
+    ) -> Result<Bytes> {
+        // Rows are already written one at a time
+        self.export(records, config, tracker).await
+    }
+}
+
+/// Write a record stream as a single-sheet workbook
+///
+/// Columns are taken from the first record. Memory use is independent of
+/// the number of records when `writer` is a file.
+pub async fn write_stream<W: Write + Seek>(
+    writer: W,
+    sheet_name: &str,
+    mut records: DataStream,
+    tracker: Option<ProgressTracker>,
+) -> Result<W> {
+    if let Some(ref t) = tracker {
+        t.start().await;
+    }
+
+    let mut xlsx = XlsxWriter::new(writer);
+    let mut headers: Option<Vec<String>> = None;
+    let mut written = 0u64;
+
+    while let Some(record) = records.next().await {
+        let record = record?;
+        let headers = match headers {
+            Some(ref headers) => headers,
+            None => {
+                let mut names = record.field_names();
+                names.sort();
+                xlsx.start_sheet(sheet_name, &names)?;
+                headers.insert(names)
+            },
+        };
+
+        xlsx.write_row(headers, &record)?;
+        written += 1;
+
+        if written % PROGRESS_INTERVAL == 0 {
+            if let Some(ref t) = tracker {
+                if t.is_cancelled().await {
+                    return Err(TransferError::Cancelled);
+                }
+                t.update(written, Some("Writing XLSX rows".to_string())).await;
+            }
+        }
+    }
+
+    if headers.is_none() {
+        xlsx.start_sheet(sheet_name, &[])?;
+    }
+    let writer = xlsx.finish()?;
+
+    if let Some(ref t) = tracker {
+        t.update(written, None).await;
+        t.complete().await;
+    }
+
+    Ok(writer)
+}
+
+/// Worksheet entry of a workbook
+#[derive(Debug, Clone)]
+struct SheetInfo {
+    name: String,
+    path: String,
+}
+
+/// Event-driven XLSX reader
+pub struct XlsxReader<R: Read + Seek> {
+    archive: ZipArchive<R>,
+    sheets: Vec<SheetInfo>,
+    shared_strings: Vec<String>,
+    date_styles: HashSet<usize>,
+    date1904: bool,
+}
+
+impl<R: Read + Seek> XlsxReader<R> {
+    /// Open a workbook and read its sheet list, shared strings and styles
+    pub fn new(reader: R) -> Result<Self> {
+        let mut archive = ZipArchive::new(reader)?;
+
+        let relationships =
+            parse_relationships(&read_entry(&mut archive, "xl/_rels/workbook.xml.rels")?)?;
+        let (sheets, date1904) = parse_workbook(
+            &read_entry(&mut archive, "xl/workbook.xml")?,
+            &relationships,
+        )?;
+
+        let shared_strings = match archive.by_name("xl/sharedStrings.xml") {
+            Ok(file) => parse_shared_strings(BufReader::new(file))?,
+            Err(zip::result::ZipError::FileNotFound) => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let date_styles = match archive.by_name("xl/styles.xml") {
+            Ok(mut file) => {
+                let mut data = Vec::new();
+                file.read_to_end(&mut data)?;
+                parse_date_styles(&data)?
+            },
+            Err(zip::result::ZipError::FileNotFound) => HashSet::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self {
+            archive,
+            sheets,
+            shared_strings,
+            date_styles,
+            date1904,
+        })
+    }
+
+    /// Sheet names in workbook order
+    pub fn sheet_names(&self) -> Vec<String> {
+        self.sheets.iter().map(|sheet| sheet.name.clone()).collect()
+    }
+
+    /// Parse a sheet, calling `on_row` with each row's zero-based index and
+    /// cell values
+    ///
+    /// Missing cells are null. Parsing stops early when `on_row` returns
+    /// `false`.
+    pub fn read_sheet<F>(&mut self, name: &str, mut on_row: F) -> Result<()>
+    where
+        F: FnMut(usize, Vec<Value>) -> Result<bool>,
+    {
+        let path = self
+            .sheets
+            .iter()
+            .find(|sheet| sheet.name == name)
+            .map(|sheet| sheet.path.clone())
+            .ok_or_else(|| TransferError::Excel(format!("Sheet '{}' not found", name)))?;
+
+        let file = self.archive.by_name(&path)?;
+        let mut reader = Reader::from_reader(BufReader::new(file));
+        let mut buf = Vec::new();
+
+        let mut row: Option<Vec<Value>> = None;
+        let mut row_idx = 0;
+        let mut next_col = 0;
+        let mut cell: Option<Cell> = None;
+        let mut capture = false;
+
+        loop {
+            match reader.read_event_into(&mut buf)? {
+                Event::Start(e) => match e.local_name().as_ref() {
+                    b"row" => {
+                        row_idx = match attribute(&e, b"r")? {
+                            Some(r) => {
+                                r.parse::<usize>().map(|r| r.saturating_sub(1)).unwrap_or(row_idx)
+                            },
+                            None => row_idx,
+                        };
+                        row = Some(Vec::new());
+                        next_col = 0;
+                    },
+                    b"c" => {
+                        let parsed = Cell::from_start(&e, next_col)?;
+                        next_col = parsed.col + 1;
+                        cell = Some(parsed);
+                    },
+                    b"v" | b"t" if cell.is_some() => capture = true,
+                    _ => {},
+                },
+                Event::Empty(e) if e.local_name().as_ref() == b"c" => {
+                    next_col = Cell::from_start(&e, next_col)?.col + 1;
+                },
+                Event::Empty(e) if e.local_name().as_ref() == b"row" => {
+                    if !on_row(row_idx, Vec::new())? {
+                        return Ok(());
+                    }
+                    row_idx += 1;
+                },
+                Event::Text(e) if capture => {
+                    if let Some(ref mut cell) = cell {
+                        cell.text.push_str(&e.unescape()?);
+                    }
+                },
+                Event::End(e) => match e.local_name().as_ref() {
+                    b"v" | b"t" => capture = false,
+                    b"c" => {
+                        if let (Some(parsed), Some(ref mut row)) = (cell.take(), row.as_mut()) {
+                            let col = parsed.col;
+                            let value = parsed.into_value(
+                                &self.shared_strings,
+                                &self.date_styles,
+                                self.date1904,
+                            )?;
+                            if row.len() <= col {
+                                row.resize(col + 1, Value::Null);
+                            }
+                            row[col] = value;
+                        }
+                    },
+                    b"row" => {
+                        if let Some(row) = row.take() {
+                            if !on_row(row_idx, row)? {
+                                return Ok(());
+                            }
+                        }
+                        row_idx += 1;
+                    },
+                    _ => {},
+                },
+                Event::Eof => break,
+                _ => {},
+            }
+            buf.clear();
+        }
+
+        Ok(())
+    }
+}
+
+/// Cell being parsed
+struct Cell {
+    col: usize,
+    kind: Option<String>,
+    style: Option<usize>,
+    text: String,
+}
+
+impl Cell {
+    fn from_start(e: &BytesStart, next_col: usize) -> Result<Self> {
+        let col = attribute(e, b"r")?.and_then(|r| column_index(&r)).unwrap_or(next_col);
+        Ok(Self {
+            col,
+            kind: attribute(e, b"t")?,
+            style: attribute(e, b"s")?.and_then(|s| s.parse().ok()),
+            text: String::new(),
+        })
+    }
+
+    /// Typed value of the parsed cell
+    fn into_value(
+        self,
+        shared_strings: &[String],
+        date_styles: &HashSet<usize>,
+        date1904: bool,
+    ) -> Result<Value> {
+        let text = self.text;
+        let value = match self.kind.as_deref() {
+            Some("s") => {
+                let index: usize = text.trim().parse().map_err(|_| {
+                    TransferError::Excel(format!("Invalid shared string index '{}'", text))
+                })?;
+                let shared = shared_strings.get(index).ok_or_else(|| {
+                    TransferError::Excel(format!("Shared string {} out of range", index))
+                })?;
+                Value::String(shared.clone())
+            },
+            Some("str") | Some("inlineStr") => Value::String(text),
+            Some("b") => Value::Bool(text.trim() == "1"),
+            Some("e") => Value::Null,
+            _ if text.is_empty() => Value::Null,
+            _ => {
+                let number: f64 = text
+                    .trim()
+                    .parse()
+                    .map_err(|_| TransferError::Excel(format!("Invalid number '{}'", text)))?;
+                if self.style.is_some_and(|s| date_styles.contains(&s)) {
+                    serial_to_date(number, date1904).map(Value::String).unwrap_or(Value::Null)
+                } else if number.fract() == 0.0 && number.abs() < 9_007_199_254_740_992.0 {
+                    Value::from(number as i64)
+                } else {
+                    serde_json::Number::from_f64(number).map(Value::Number).unwrap_or(Value::Null)
+                }
+            },
+        };
+        Ok(value)
+    }
+}
+
+/// Streaming XLSX writer
+///
+/// Each sheet is one zip entry written row by row; workbook parts are added
+/// by [`finish`](Self::finish).
+pub struct XlsxWriter<W: Write + Seek> {
+    zip: ZipWriter<W>,
+    sheets: Vec<String>,
+    row: usize,
+    open: bool,
+}
+
+impl<W: Write + Seek> XlsxWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            zip: ZipWriter::new(writer),
+            sheets: Vec::new(),
+            row: 0,
+            open: false,
+        }
+    }
+
+    fn options() -> FileOptions {
+        FileOptions::default().compression_method(CompressionMethod::Deflated)
+    }
+
+    /// Start a sheet and write its header row
+    pub fn start_sheet(&mut self, name: &str, headers: &[String]) -> Result<()> {
+        self.close_sheet()?;
+
+        let name = sheet_name(name, self.sheets.len());
+        self.sheets.push(name);
+        self.zip.start_file(
+            format!("xl/worksheets/sheet{}.xml", self.sheets.len()),
+            Self::options(),
+        )?;
+        self.zip.write_all(
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+                r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+                "<sheetData>"
+            )
+            .as_bytes(),
+        )?;
+        self.open = true;
+        self.row = 0;
+
+        if !headers.is_empty() {
+            let headers: Vec<Value> = headers.iter().map(|h| Value::String(h.clone())).collect();
+            self.write_values(&headers)?;
+        }
+        Ok(())
+    }
+
+    /// Write a record's values in header order
+    pub fn write_row(&mut self, headers: &[String], record: &DataRecord) -> Result<()> {
+        let values: Vec<Value> =
+            headers.iter().map(|h| record.get(h).cloned().unwrap_or(Value::Null)).collect();
+        self.write_values(&values)
+    }
+
+    /// Write one row of cell values
+    pub fn write_values(&mut self, values: &[Value]) -> Result<()> {
+        if !self.open {
+            return Err(TransferError::Excel("No sheet started".to_string()));
+        }
+
+        self.row += 1;
+        let mut xml = format!(r#"<row r="{}">"#, self.row);
+        for (col, value) in values.iter().enumerate() {
+            let reference = format!("{}{}", column_name(col), self.row);
+            match value {
+                Value::Null => continue,
+                Value::Bool(b) => {
+                    xml.push_str(&format!(
+                        r#"<c r="{}" t="b"><v>{}</v></c>"#,
+                        reference, *b as u8
+                    ));
+                },
+                Value::Number(n) => {
+                    xml.push_str(&format!(r#"<c r="{}"><v>{}</v></c>"#, reference, n));
+                },
+                Value::String(s) => push_inline_string(&mut xml, &reference, s),
+                other => push_inline_string(&mut xml, &reference, &other.to_string()),
+            }
+        }
+        xml.push_str("</row>");
+        self.zip.write_all(xml.as_bytes())?;
+        Ok(())
+    }
+
+    fn close_sheet(&mut self) -> Result<()> {
+        if self.open {
+            self.zip.write_all(b"</sheetData></worksheet>")?;
+            self.open = false;
+        }
+        Ok(())
+    }
+
+    /// Write the workbook parts and return the underlying writer
+    pub fn finish(mut self) -> Result<W> {
+        self.close_sheet()?;
+        if self.sheets.is_empty() {
+            self.start_sheet("Sheet1", &[])?;
+            self.close_sheet()?;
+        }
+
+        let mut sheets = String::new();
+        let mut relationships = String::new();
+        let mut overrides = String::new();
+        for (i, name) in self.sheets.iter().enumerate() {
+            let id = i + 1;
+            sheets.push_str(&format!(
+                r#"<sheet name="{}" sheetId="{}" r:id="rId{}"/>"#,
+                escape(name.as_str()),
+                id,
+                id
+            ));
+            relationships.push_str(&format!(
+                r#"<Relationship Id="rId{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet{}.xml"/>"#,
+                id, id
+            ));
+            overrides.push_str(&format!(
+                r#"<Override PartName="/xl/worksheets/sheet{}.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#,
+                id
+            ));
+        }
+        let styles_id = self.sheets.len() + 1;
+
+        let parts = [
+            (
+                "[Content_Types].xml",
+                format!(
+                    concat!(
+                        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+                        r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+                        r#"<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#,
+                        r#"<Default Extension="xml" ContentType="application/xml"/>"#,
+                        r#"<Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>"#,
+                        r#"<Override PartName="/xl/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml"/>"#,
+                        "{}</Types>"
+                    ),
+                    overrides
+                ),
+            ),
+            (
+                "_rels/.rels",
+                concat!(
+                    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+                    r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+                    r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>"#,
+                    "</Relationships>"
+                )
+                .to_string(),
+            ),
+            (
+                "xl/workbook.xml",
+                format!(
+                    concat!(
+                        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+                        r#"<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" "#,
+                        r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">"#,
+                        "<sheets>{}</sheets></workbook>"
+                    ),
+                    sheets
+                ),
+            ),
+            (
+                "xl/_rels/workbook.xml.rels",
+                format!(
+                    concat!(
+                        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+                        r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+                        "{}",
+                        r#"<Relationship Id="rId{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/>"#,
+                        "</Relationships>"
+                    ),
+                    relationships, styles_id
+                ),
+            ),
+            (
+                "xl/styles.xml",
+                concat!(
+                    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+                    r#"<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+                    r#"<fonts count="1"><font><sz val="11"/><name val="Calibri"/></font></fonts>"#,
+                    r#"<fills count="2"><fill><patternFill patternType="none"/></fill><fill><patternFill patternType="gray125"/></fill></fills>"#,
+                    r#"<borders count="1"><border><left/><right/><top/><bottom/><diagonal/></border></borders>"#,
+                    r#"<cellStyleXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/></cellStyleXfs>"#,
+                    r#"<cellXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/></cellXfs>"#,
+                    "</styleSheet>"
+                )
+                .to_string(),
+            ),
+        ];
+
+        for (path, content) in parts {
+            self.zip.start_file(path, Self::options())?;
+            self.zip.write_all(content.as_bytes())?;
+        }
+
+        Ok(self.zip.finish()?)
+    }
+}
+
+fn push_inline_string(xml: &mut String, reference: &str, text: &str) {
+    // Control characters other than tab and newlines are not valid XML
+    let text: String = text
+        .chars()
+        .filter(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+        .collect();
+    xml.push_str(&format!(
+        r#"<c r="{}" t="inlineStr"><is><t xml:space="preserve">{}</t></is></c>"#,
+        reference,
+        escape(text.as_str())
+    ));
+}
+
+/// Valid, unique-enough sheet name (at most 31 characters, no `[]:*?/\`)
+fn sheet_name(name: &str, index: usize) -> String {
+    let name: String = name
+        .chars()
+        .filter(|c| !matches!(c, '[' | ']' | ':' | '*' | '?' | '/' | '\\'))
+        .take(31)
+        .collect();
+    if name.trim().is_empty() {
+        format!("Sheet{}", index + 1)
+    } else {
+        name
+    }
+}
+
+fn read_entry<R: Read + Seek>(archive: &mut ZipArchive<R>, path: &str) -> Result<Vec<u8>> {
+    let mut file = archive
+        .by_name(path)
+        .map_err(|_| TransferError::Excel(format!("Workbook part '{}' missing", path)))?;
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+fn attribute(e: &BytesStart, name: &[u8]) -> Result<Option<String>> {
+    for attribute in e.attributes() {
+        let attribute = attribute.map_err(|e| TransferError::Xml(e.to_string()))?;
+        if attribute.key.local_name().as_ref() == name {
+            let value = String::from_utf8_lossy(&attribute.value);
+            let value = unescape(&value).map_err(|e| TransferError::Xml(e.to_string()))?;
+            return Ok(Some(value.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+/// Relationship ids to part paths
+fn parse_relationships(data: &[u8]) -> Result<HashMap<String, String>> {
+    let mut reader = Reader::from_reader(data);
+    let mut buf = Vec::new();
+    let mut relationships = HashMap::new();
+
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"Relationship" => {
+                if let (Some(id), Some(target)) = (attribute(&e, b"Id")?, attribute(&e, b"Target")?)
+                {
+                    let path = match target.strip_prefix('/') {
+                        Some(absolute) => absolute.to_string(),
+                        None => format!("xl/{}", target),
+                    };
+                    relationships.insert(id, path);
+                }
+            },
+            Event::Eof => break,
+            _ => {},
+        }
+        buf.clear();
+    }
+
+    Ok(relationships)
+}
+
+/// Sheets in workbook order and whether dates use the 1904 system
+fn parse_workbook(
+    data: &[u8],
+    relationships: &HashMap<String, String>,
+) -> Result<(Vec<SheetInfo>, bool)> {
+    let mut reader = Reader::from_reader(data);
+    let mut buf = Vec::new();
+    let mut sheets = Vec::new();
+    let mut date1904 = false;
+
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) | Event::Empty(e) => match e.local_name().as_ref() {
+                b"workbookPr" => {
+                    date1904 = matches!(
+                        attribute(&e, b"date1904")?.as_deref(),
+                        Some("1") | Some("true")
+                    );
+                },
+                b"sheet" => {
+                    let name = attribute(&e, b"name")?.unwrap_or_default();
+                    let path = attribute(&e, b"id")?
+                        .and_then(|id| relationships.get(&id).cloned())
+                        .ok_or_else(|| {
+                            TransferError::Excel(format!("Sheet '{}' has no worksheet part", name))
+                        })?;
+                    sheets.push(SheetInfo { name, path });
+                },
+                _ => {},
+            },
+            Event::Eof => break,
+            _ => {},
+        }
+        buf.clear();
+    }
+
+    Ok((sheets, date1904))
+}
+
+/// Shared string table; rich text runs are concatenated, phonetic runs skipped
+fn parse_shared_strings<R: std::io::BufRead>(data: R) -> Result<Vec<String>> {
+    let mut reader = Reader::from_reader(data);
+    let mut buf = Vec::new();
+    let mut strings = Vec::new();
+    let mut current = String::new();
+    let mut capture = false;
+    let mut phonetic = false;
+
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"si" => current.clear(),
+                b"rPh" => phonetic = true,
+                b"t" if !phonetic => capture = true,
+                _ => {},
+            },
+            Event::Empty(e) if e.local_name().as_ref() == b"si" => strings.push(String::new()),
+            Event::Text(e) if capture => current.push_str(&e.unescape()?),
+            Event::End(e) => match e.local_name().as_ref() {
+                b"t" => capture = false,
+                b"rPh" => phonetic = false,
+                b"si" => strings.push(std::mem::take(&mut current)),
+                _ => {},
+            },
+            Event::Eof => break,
+            _ => {},
+        }
+        buf.clear();
+    }
+
+    Ok(strings)
+}
+
+/// Indices of cell formats that display dates or times
+fn parse_date_styles(data: &[u8]) -> Result<HashSet<usize>> {
+    let mut reader = Reader::from_reader(data);
+    let mut buf = Vec::new();
+    let mut custom_formats: HashMap<u32, String> = HashMap::new();
+    let mut in_cell_xfs = false;
+    let mut xf_index = 0;
+    let mut date_styles = HashSet::new();
+
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(e) | Event::Empty(e) => match e.local_name().as_ref() {
+                b"numFmt" => {
+                    if let (Some(id), Some(code)) =
+                        (attribute(&e, b"numFmtId")?, attribute(&e, b"formatCode")?)
+                    {
+                        if let Ok(id) = id.parse() {
+                            custom_formats.insert(id, code);
+                        }
+                    }
+                },
+                b"cellXfs" => in_cell_xfs = true,
+                b"xf" if in_cell_xfs => {
+                    let id: u32 =
+                        attribute(&e, b"numFmtId")?.and_then(|id| id.parse().ok()).unwrap_or(0);
+                    if is_date_format(id, custom_formats.get(&id).map(String::as_str)) {
+                        date_styles.insert(xf_index);
+                    }
+                    xf_index += 1;
+                },
+                _ => {},
+            },
+            Event::End(e) if e.local_name().as_ref() == b"cellXfs" => in_cell_xfs = false,
+            Event::Eof => break,
+            _ => {},
+        }
+        buf.clear();
+    }
+
+    Ok(date_styles)
+}
+
+fn is_date_format(id: u32, code: Option<&str>) -> bool {
+    if matches!(id, 14..=22 | 45..=47) {
+        return true;
+    }
+    let Some(code) = code else {
+        return false;
+    };
+
+    // Ignore quoted literals and bracketed colors or conditions
+    let mut plain = String::new();
+    let mut quoted = false;
+    let mut bracketed = false;
+    for c in code.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            '[' if !quoted => bracketed = true,
+            ']' if !quoted => bracketed = false,
+            _ if !quoted && !bracketed => plain.push(c.to_ascii_lowercase()),
+            _ => {},
+        }
+    }
+    plain.contains(['y', 'd', 'h', 's'])
+}
+
+/// Excel serial date to an ISO 8601 date or date-time
+fn serial_to_date(serial: f64, date1904: bool) -> Option<String> {
+    let epoch = if date1904 {
+        NaiveDate::from_ymd_opt(1904, 1, 1)?
+    } else {
+        // Accounts for Excel treating 1900 as a leap year
+        NaiveDate::from_ymd_opt(1899, 12, 30)?
+    };
+
+    let millis = (serial * 86_400_000.0).round() as i64;
+    let datetime = epoch.and_hms_opt(0, 0, 0)? + Duration::milliseconds(millis);
+    if millis % 86_400_000 == 0 {
+        Some(datetime.date().to_string())
+    } else {
+        Some(datetime.format("%Y-%m-%dT%H:%M:%S").to_string())
+    }
+}
+
+/// Zero-based column index of a cell reference such as `AB12`
+fn column_index(reference: &str) -> Option<usize> {
+    let mut index = 0usize;
+    let mut letters = 0;
+    for c in reference.chars().take_while(|c| c.is_ascii_alphabetic()) {
+        index = index * 26 + (c.to_ascii_uppercase() as usize - 'A' as usize + 1);
+        letters += 1;
+    }
+    if letters == 0 {
+        None
+    } else {
+        Some(index - 1)
+    }
+}
+
+/// Column letters of a zero-based index
+fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+fn header_name(value: &Value, index: usize) -> String {
+    match value {
+        Value::Null => format!("column_{}", index + 1),
+        Value::String(s) if s.trim().is_empty() => format!("column_{}", index + 1),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn row_to_record(row: Vec<Value>, headers: &[String]) -> DataRecord {
+    let mut record = DataRecord::new();
+    for (header, value) in headers.iter().zip(row.into_iter().chain(std::iter::repeat(Value::Null)))
+    {
+        record.set(header.clone(), value);
+    }
+    record
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(count: usize) -> Vec<DataRecord> {
+        (0..count)
+            .map(|i| {
+                let mut record = DataRecord::new();
+                record.set("id".to_string(), Value::from(i as i64));
+                record.set("speed".to_string(), Value::from(12.5));
+                record.set("note".to_string(), Value::from(format!("a < b & {}", i)));
+                record.set("damaged".to_string(), Value::Bool(i % 2 == 0));
+                record
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_xlsx_roundtrip() {
+        let handler = XlsxHandler::new();
+        let config = TransferConfig::default();
+        let data = handler.export(records(2500), &config, None).await.unwrap();
+
+        let tracker = ProgressTracker::new(2500);
+        let imported = handler.import(data, &config, Some(tracker.clone())).await.unwrap();
+        assert_eq!(imported.len(), 2500);
+        assert_eq!(imported[3].get("id"), Some(&Value::from(3)));
+        assert_eq!(imported[3].get("speed"), Some(&Value::from(12.5)));
+        assert_eq!(imported[3].get("note"), Some(&Value::from("a < b & 3")));
+        assert_eq!(imported[3].get("damaged"), Some(&Value::Bool(false)));
+        assert_eq!(tracker.get().await.processed, 2500);
+    }
+
+    #[tokio::test]
+    async fn test_sheet_selection() {
+        let mut writer = XlsxWriter::new(Cursor::new(Vec::new()));
+        for (sheet, count) in [("Vehicles", 2), ("Witnesses", 3)] {
+            let headers = vec!["id".to_string()];
+            writer.start_sheet(sheet, &headers).unwrap();
+            for record in records(count) {
+                writer.write_row(&headers, &record).unwrap();
+            }
+        }
+        let data = Bytes::from(writer.finish().unwrap().into_inner());
+        let config = TransferConfig::default();
+
+        let imported = XlsxHandler::new().import(data.clone(), &config, None).await.unwrap();
+        assert_eq!(imported.len(), 2);
+        assert!(imported[0].get(SHEET_FIELD).is_none());
+
+        let handler = XlsxHandler::new().with_sheets(vec!["Witnesses".to_string()]);
+        assert_eq!(
+            handler.import(data.clone(), &config, None).await.unwrap().len(),
+            3
+        );
+
+        let imported = XlsxHandler::new()
+            .all_sheets()
+            .import(data.clone(), &config, None)
+            .await
+            .unwrap();
+        assert_eq!(imported.len(), 5);
+        assert_eq!(
+            imported[4].get(SHEET_FIELD),
+            Some(&Value::from("Witnesses"))
+        );
+
+        let missing = XlsxHandler::new().with_sheets(vec!["Evidence".to_string()]);
+        assert!(missing.validate(data, &config).await.is_err());
+    }
+
+    #[test]
+    fn test_cell_helpers() {
+        assert_eq!(column_index("A1"), Some(0));
+        assert_eq!(column_index("AB12"), Some(27));
+        assert_eq!(column_name(27), "AB");
+        assert_eq!(column_name(701), "ZZ");
+        assert_eq!(
+            serial_to_date(45352.0, false).as_deref(),
+            Some("2024-03-01")
+        );
+        assert_eq!(
+            serial_to_date(45352.5, false).as_deref(),
+            Some("2024-03-01T12:00:00")
+        );
+        assert!(is_date_format(164, Some("yyyy-mm-dd")));
+        assert!(!is_date_format(164, Some("0.00 \"days\"")));
+    }
+}
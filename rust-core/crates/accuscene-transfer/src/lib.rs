@@ -2,9 +2,14 @@
 ///
 /// Provides comprehensive import/export capabilities with support for:
 /// - CSV, Excel, JSON, XML, PDF, and Archive formats
-/// - Streaming for large files
+/// - Parquet and Arrow IPC columnar formats (`columnar` feature)
+/// - Crash report XML, Bosch CDR CSV and GPX/NMEA GPS track importers
+/// - Template-driven PDF reports with tables, charts and signature blocks
+/// - Streaming for large files, including constant-memory XLSX read/write
+/// - Resumable chunked exports with checkpoint manifests
+/// - S3, Azure Blob and GCS sources and destinations (`s3`, `azure`, `gcs` features)
 /// - Progress tracking
-/// - Field mapping and transformation
+/// - Reversible field mapping profiles with unit conversion and validation rules
 /// - Schema detection and validation
 /// - Error recovery
 
@@ -13,11 +18,15 @@ pub mod error;
 pub mod formats;
 pub mod mapping;
 pub mod progress;
+pub mod resumable;
+pub mod storage;
 pub mod validation;
 
-pub use config::{TransferConfig, TransferFormat};
+pub use config::{ImportPredicate, PredicateOp, TransferConfig, TransferFormat};
 pub use error::{Result, TransferError};
 pub use progress::{ProgressStatus, ProgressTracker, TransferProgress};
+pub use resumable::{resume_export, start_export, ExportJob, ExportManifest};
+pub use storage::{export_destination, import_source, StorageLocation, StorageOptions};
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -1,13 +1,21 @@
 /// Field mapping and transformation
 pub mod schema;
 pub mod transform;
-
-use crate::{error::Result, DataRecord};
+pub mod units;
+
+use crate::{
+    config::TransferConfig,
+    error::{Result, TransferError},
+    validation::RuleType,
+    DataRecord,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
 
-pub use schema::{FieldSchema, SchemaDetector};
+pub use schema::{DataType, FieldSchema, SchemaDetector};
 pub use transform::{Transform, TransformPipeline};
+pub use units::Unit;
 
 /// Field mapping configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +30,16 @@ pub struct FieldMapping {
     pub default: Option<serde_json::Value>,
     /// Is this field required?
     pub required: bool,
+    /// Type the value is coerced to after the transform
+    #[serde(default)]
+    pub data_type: Option<DataType>,
+    /// Transform used when mapping back from target to source, for
+    /// transforms without an exact inverse
+    #[serde(default)]
+    pub reverse_transform: Option<Transform>,
+    /// Rules the mapped value must satisfy
+    #[serde(default)]
+    pub rules: Vec<RuleType>,
 }
 
 impl FieldMapping {
@@ -33,9 +51,23 @@ impl FieldMapping {
             transform: None,
             default: None,
             required: false,
+            data_type: None,
+            reverse_transform: None,
+            rules: Vec::new(),
         }
     }
 
+    /// Create typed field mapping
+    pub fn typed(source: &str, target: &str, data_type: DataType) -> Self {
+        Self::new(source.to_string(), target.to_string()).with_type(data_type)
+    }
+
+    /// Set target type
+    pub fn with_type(mut self, data_type: DataType) -> Self {
+        self.data_type = Some(data_type);
+        self
+    }
+
     /// Set transformation
     pub fn with_transform(mut self, transform: Transform) -> Self {
         self.transform = Some(transform);
@@ -48,11 +80,77 @@ impl FieldMapping {
         self
     }
 
+    /// Convert between measurement units
+    pub fn with_unit_conversion(self, from: Unit, to: Unit) -> Self {
+        self.with_transform(Transform::ConvertUnit { from, to })
+    }
+
+    /// Set transform used when mapping in reverse
+    pub fn with_reverse_transform(mut self, transform: Transform) -> Self {
+        self.reverse_transform = Some(transform);
+        self
+    }
+
+    /// Add validation rule for the mapped value
+    pub fn with_rule(mut self, rule: RuleType) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
     /// Mark as required
     pub fn required(mut self) -> Self {
         self.required = true;
         self
     }
+
+    /// Mapping from target back to source
+    ///
+    /// Fails when the transform has no inverse and no reverse transform
+    /// was set. Types, defaults and rules only apply in the forward
+    /// direction.
+    pub fn reversed(&self) -> Result<FieldMapping> {
+        let transform = match (&self.reverse_transform, &self.transform) {
+            (Some(reverse), _) => Some(reverse.clone()),
+            (None, Some(transform)) => Some(transform.inverse().ok_or_else(|| {
+                TransferError::FieldMapping(format!(
+                    "Field '{}': transform {:?} cannot be reversed",
+                    self.source, transform
+                ))
+            })?),
+            (None, None) => None,
+        };
+
+        Ok(FieldMapping {
+            source: self.target.clone(),
+            target: self.source.clone(),
+            transform,
+            default: None,
+            required: self.required,
+            data_type: None,
+            reverse_transform: self.transform.clone(),
+            rules: Vec::new(),
+        })
+    }
+
+    /// Check the mapping's transforms and rules
+    pub fn validate(&self) -> Result<()> {
+        for transform in self.transform.iter().chain(&self.reverse_transform) {
+            transform.validate().map_err(|e| {
+                TransferError::FieldMapping(format!("Field '{}': {}", self.source, e))
+            })?;
+        }
+        for rule in &self.rules {
+            if let RuleType::Pattern { regex } = rule {
+                regex::Regex::new(regex).map_err(|e| {
+                    TransferError::FieldMapping(format!(
+                        "Field '{}': invalid regex: {}",
+                        self.source, e
+                    ))
+                })?;
+            }
+        }
+        Ok(())
+    }
 }
 
 /// Mapping profile for data transformation
@@ -91,6 +189,58 @@ impl MappingProfile {
         self
     }
 
+    /// Profile mapping target records back to the source layout
+    ///
+    /// Global transforms are not reversed.
+    pub fn reversed(&self) -> Result<MappingProfile> {
+        let mut profile = MappingProfile::new(format!("{}_reverse", self.name));
+        profile.description = self.description.clone();
+        for mapping in &self.mappings {
+            profile.mappings.push(mapping.reversed()?);
+        }
+        Ok(profile)
+    }
+
+    /// Map records from the target layout back to the source layout
+    pub fn apply_reverse(&self, records: Vec<DataRecord>) -> Result<Vec<DataRecord>> {
+        self.reversed()?.apply(records)
+    }
+
+    /// Check every mapping of the profile
+    pub fn validate(&self) -> Result<()> {
+        for mapping in &self.mappings {
+            mapping.validate()?;
+        }
+        for transform in &self.global_transforms {
+            transform.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Serialize profile to JSON
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Deserialize and validate a profile from JSON
+    pub fn from_json(json: &str) -> Result<Self> {
+        let profile: Self = serde_json::from_str(json)?;
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    /// Load profile saved with [`save`](Self::save)
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let data = tokio::fs::read_to_string(path.as_ref()).await?;
+        Self::from_json(&data)
+    }
+
+    /// Save profile for reuse across imports
+    pub async fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        self.validate()?;
+        crate::resumable::write_atomic(path.as_ref(), self.to_json()?.as_bytes()).await
+    }
+
     /// Apply profile to records
     pub fn apply(&self, records: Vec<DataRecord>) -> Result<Vec<DataRecord>> {
         let mut transformed = Vec::new();
@@ -103,6 +253,32 @@ impl MappingProfile {
         Ok(transformed)
     }
 
+    /// Apply profile to records, skipping failing records up to the
+    /// configured error threshold when `continue_on_error` is set
+    pub fn apply_with_config(
+        &self,
+        records: Vec<DataRecord>,
+        config: &TransferConfig,
+    ) -> Result<Vec<DataRecord>> {
+        let mut transformed = Vec::with_capacity(records.len());
+        let mut error_count = 0;
+
+        for (idx, record) in records.into_iter().enumerate() {
+            match self.apply_to_record(record) {
+                Ok(record) => transformed.push(record),
+                Err(e) => {
+                    error_count += 1;
+                    if !config.continue_on_error || error_count >= config.error_threshold {
+                        return Err(e);
+                    }
+                    tracing::warn!("Mapping error in record {}: {}", idx, e);
+                }
+            }
+        }
+
+        Ok(transformed)
+    }
+
     /// Apply profile to single record
     pub fn apply_to_record(&self, record: DataRecord) -> Result<DataRecord> {
         let mut new_record = DataRecord::new();
@@ -119,11 +295,28 @@ impl MappingProfile {
                     val = transform.apply(val)?;
                 }
 
+                if let Some(ref data_type) = mapping.data_type {
+                    val = data_type.coerce(val).map_err(|e| {
+                        crate::error::TransferError::FieldMapping(format!(
+                            "Field '{}': {}",
+                            mapping.source, e
+                        ))
+                    })?;
+                }
+
+                for rule in &mapping.rules {
+                    rule.check(&mapping.target, Some(&val))?;
+                }
+
                 new_record.set(mapping.target.clone(), val);
             } else if mapping.required {
                 return Err(crate::error::TransferError::FieldMapping(
                     format!("Required field '{}' not found", mapping.source),
                 ));
+            } else {
+                for rule in &mapping.rules {
+                    rule.check(&mapping.target, None)?;
+                }
             }
         }
 
@@ -227,6 +420,94 @@ mod tests {
         assert_eq!(result.get("new_name"), Some(&Value::String("test".to_string())));
     }
 
+    #[test]
+    fn test_typed_mapping() {
+        let profile = MappingProfile::new("typed".to_string())
+            .add_mapping(
+                FieldMapping::typed("Speed (MPH)", "speed_mps", DataType::Float)
+                    .with_transform(Transform::Scale { factor: 0.44704 }),
+            )
+            .add_mapping(FieldMapping::typed("Year", "year", DataType::Integer))
+            .add_mapping(FieldMapping::typed("When", "timestamp", DataType::DateTime));
+
+        let mut record = DataRecord::new();
+        record.set("Speed (MPH)".to_string(), Value::String("50".to_string()));
+        record.set("Year".to_string(), Value::String("2019".to_string()));
+        record.set(
+            "When".to_string(),
+            Value::String("2024-03-01T09:15:00-05:00".to_string()),
+        );
+
+        let result = profile.apply_to_record(record).unwrap();
+        assert_eq!(result.get("speed_mps"), Some(&Value::from(22.352)));
+        assert_eq!(result.get("year"), Some(&Value::from(2019)));
+        assert_eq!(
+            result.get("timestamp"),
+            Some(&Value::String("2024-03-01T14:15:00Z".to_string()))
+        );
+
+        let mut record = DataRecord::new();
+        record.set("Year".to_string(), Value::String("unknown".to_string()));
+        assert!(profile.apply_to_record(record.clone()).is_err());
+
+        let config = TransferConfig::default().with_error_handling(true, 10);
+        assert!(profile.apply_with_config(vec![record], &config).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_profile_roundtrip() {
+        let profile = MappingProfile::new("speed_log".to_string())
+            .add_mapping(
+                FieldMapping::typed("Speed", "speed_mps", DataType::Float)
+                    .with_unit_conversion(Unit::MilesPerHour, Unit::MetersPerSecond)
+                    .with_rule(RuleType::MinValue { min: 0.0 }),
+            )
+            .add_mapping(
+                FieldMapping::new("Unit".to_string(), "unit_number".to_string())
+                    .with_transform(Transform::Extract {
+                        pattern: r"^V(\d+)$".to_string(),
+                        group: 1,
+                    })
+                    .with_reverse_transform(Transform::Prefix {
+                        prefix: "V".to_string(),
+                    })
+                    .with_default(Value::from("V1")),
+            );
+
+        let mut record = DataRecord::new();
+        record.set("Speed".to_string(), Value::from(60));
+        record.set("Unit".to_string(), Value::from("V2"));
+
+        let mapped = profile.apply(vec![record]).unwrap();
+        let speed = mapped[0].get("speed_mps").and_then(Value::as_f64).unwrap();
+        assert!((speed - 26.8224).abs() < 1e-3);
+        assert_eq!(mapped[0].get("unit_number"), Some(&Value::from("2")));
+
+        let restored = profile.apply_reverse(mapped).unwrap();
+        let speed = restored[0].get("Speed").and_then(Value::as_f64).unwrap();
+        assert!((speed - 60.0).abs() < 1e-9);
+        assert_eq!(restored[0].get("Unit"), Some(&Value::from("V2")));
+
+        // Validation rules reject out of range values
+        let mut record = DataRecord::new();
+        record.set("Speed".to_string(), Value::from(-5));
+        assert!(profile.apply_to_record(record).is_err());
+
+        // Profiles survive a save/load cycle
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("speed_log.json");
+        profile.save(&path).await.unwrap();
+        let loaded = MappingProfile::load(&path).await.unwrap();
+        assert_eq!(loaded.mappings.len(), 2);
+        assert_eq!(loaded.mappings[0].rules.len(), 1);
+
+        // Transforms without an inverse need an explicit reverse transform
+        let lossy = MappingProfile::new("lossy".to_string()).add_mapping(
+            FieldMapping::new("a".to_string(), "b".to_string()).with_transform(Transform::Trim),
+        );
+        assert!(lossy.reversed().is_err());
+    }
+
     #[test]
     fn test_auto_map() {
         let source = vec!["firstName".to_string(), "lastName".to_string()];
@@ -1,5 +1,9 @@
-use crate::{error::Result, DataRecord};
+use crate::{
+    error::{Result, TransferError},
+    DataRecord,
+};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 
 /// Field schema information
@@ -29,7 +33,7 @@ pub struct FieldSchema {
     pub pattern: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum DataType {
     String,
@@ -63,6 +67,85 @@ impl FieldSchema {
     }
 }
 
+impl DataType {
+    /// Coerce a value to this type
+    ///
+    /// Nulls pass through unchanged. Dates must be `YYYY-MM-DD`, times
+    /// `HH:MM[:SS]` and date-times RFC 3339; date-times are normalized to
+    /// UTC.
+    pub fn coerce(&self, value: Value) -> Result<Value> {
+        if value.is_null() {
+            return Ok(value);
+        }
+
+        let text = match &value {
+            Value::String(s) => Some(s.trim()),
+            _ => None,
+        };
+        let invalid = || {
+            TransferError::Transform(format!("Cannot convert {} to {:?}", value, self))
+        };
+
+        match self {
+            DataType::String => match value {
+                Value::String(_) => Ok(value),
+                other => Ok(Value::String(other.to_string())),
+            },
+            DataType::Integer => match (&value, text) {
+                (Value::Number(n), _) if n.is_i64() => Ok(value),
+                (Value::Number(n), _) => match n.as_f64() {
+                    Some(f) if f.fract() == 0.0 && f.abs() < i64::MAX as f64 => {
+                        Ok(Value::from(f as i64))
+                    }
+                    _ => Err(invalid()),
+                },
+                (_, Some(s)) => s.parse::<i64>().map(Value::from).map_err(|_| invalid()),
+                _ => Err(invalid()),
+            },
+            DataType::Float => {
+                let number = match (&value, text) {
+                    (Value::Number(n), _) => n.as_f64(),
+                    (_, Some(s)) => s.parse::<f64>().ok(),
+                    _ => None,
+                };
+                number
+                    .and_then(serde_json::Number::from_f64)
+                    .map(Value::Number)
+                    .ok_or_else(invalid)
+            }
+            DataType::Boolean => match (&value, text.map(str::to_lowercase).as_deref()) {
+                (Value::Bool(_), _) => Ok(value),
+                (_, Some("true" | "yes" | "y" | "1" | "on")) => Ok(Value::Bool(true)),
+                (_, Some("false" | "no" | "n" | "0" | "off")) => Ok(Value::Bool(false)),
+                _ => Err(invalid()),
+            },
+            DataType::Date => text
+                .and_then(|s| chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d").ok())
+                .map(|date| Value::String(date.format("%Y-%m-%d").to_string()))
+                .ok_or_else(invalid),
+            DataType::Time => text
+                .and_then(|s| {
+                    chrono::NaiveTime::parse_from_str(s, "%H:%M:%S%.f")
+                        .or_else(|_| chrono::NaiveTime::parse_from_str(s, "%H:%M"))
+                        .ok()
+                })
+                .map(|time| Value::String(time.format("%H:%M:%S").to_string()))
+                .ok_or_else(invalid),
+            DataType::DateTime => text
+                .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+                .map(|datetime| {
+                    Value::String(
+                        datetime
+                            .with_timezone(&chrono::Utc)
+                            .to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true),
+                    )
+                })
+                .ok_or_else(invalid),
+            DataType::Array | DataType::Object | DataType::Unknown => Ok(value),
+        }
+    }
+}
+
 /// Schema detector
 pub struct SchemaDetector {
     /// Maximum samples to collect per field
@@ -1,3 +1,4 @@
+use super::units::{self, Unit};
 use crate::{error::{Result, TransferError}, DataRecord};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -18,6 +19,12 @@ pub enum Transform {
     FormatNumber { decimals: usize },
     /// Parse number
     ParseNumber,
+    /// Multiply a number, e.g. to convert units
+    Scale { factor: f64 },
+    /// Convert a number between measurement units
+    ConvertUnit { from: Unit, to: Unit },
+    /// Extract a regex capture group, null when the pattern does not match
+    Extract { pattern: String, group: usize },
     /// Format date
     FormatDate { from: String, to: String },
     /// Add prefix
@@ -87,6 +94,43 @@ impl Transform {
                 }
                 Ok(value)
             }
+            Transform::Scale { factor } => {
+                let number = match &value {
+                    Value::String(s) => s.trim().parse::<f64>().ok(),
+                    other => other.as_f64(),
+                };
+                match number.and_then(|n| serde_json::Number::from_f64(n * factor)) {
+                    Some(scaled) => Ok(Value::Number(scaled)),
+                    None => Ok(value),
+                }
+            }
+            Transform::ConvertUnit { from, to } => {
+                let number = match &value {
+                    Value::String(s) => s.trim().parse::<f64>().ok(),
+                    other => other.as_f64(),
+                };
+                match number {
+                    Some(n) => {
+                        let converted = units::convert(n, *from, *to)?;
+                        Ok(serde_json::Number::from_f64(converted)
+                            .map(Value::Number)
+                            .unwrap_or(value))
+                    }
+                    None => Ok(value),
+                }
+            }
+            Transform::Extract { pattern, group } => {
+                if let Some(s) = value.as_str() {
+                    let re = compile(pattern)?;
+                    Ok(re
+                        .captures(s)
+                        .and_then(|caps| caps.get(*group))
+                        .map(|m| Value::String(m.as_str().to_string()))
+                        .unwrap_or(Value::Null))
+                } else {
+                    Ok(value)
+                }
+            }
             Transform::FormatDate { from, to } => {
                 if let Some(s) = value.as_str() {
                     if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(s, from) {
@@ -162,6 +206,48 @@ impl Transform {
         }
     }
 
+    /// Transform undoing this one, if it is lossless
+    pub fn inverse(&self) -> Option<Transform> {
+        match self {
+            Transform::Scale { factor } if *factor != 0.0 => {
+                Some(Transform::Scale { factor: 1.0 / factor })
+            }
+            Transform::ConvertUnit { from, to } => Some(Transform::ConvertUnit {
+                from: *to,
+                to: *from,
+            }),
+            Transform::FormatDate { from, to } => Some(Transform::FormatDate {
+                from: to.clone(),
+                to: from.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Check the transform's parameters
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Transform::Scale { factor } if !factor.is_finite() => Err(TransferError::Transform(
+                format!("Invalid scale factor {}", factor),
+            )),
+            Transform::ConvertUnit { from, to } => units::check_compatible(*from, *to),
+            Transform::Extract { pattern, group } => {
+                let re = compile(pattern)?;
+                if *group >= re.captures_len() {
+                    return Err(TransferError::Transform(format!(
+                        "Pattern '{}' has no group {}",
+                        pattern, group
+                    )));
+                }
+                Ok(())
+            }
+            Transform::Substring { start, end: Some(end) } if end < start => Err(
+                TransferError::Transform(format!("Invalid substring range {}..{}", start, end)),
+            ),
+            _ => Ok(()),
+        }
+    }
+
     /// Apply transformation to entire record
     pub fn apply_to_record(&self, mut record: DataRecord) -> Result<DataRecord> {
         match self {
@@ -244,6 +330,11 @@ impl Default for TransformPipeline {
     }
 }
 
+fn compile(pattern: &str) -> Result<regex::Regex> {
+    regex::Regex::new(pattern)
+        .map_err(|e| TransferError::Transform(format!("Invalid regex '{}': {}", pattern, e)))
+}
+
 /// Convert value to target type
 fn convert_type(value: Value, target_type: &str) -> Result<Value> {
     match target_type.to_lowercase().as_str() {
@@ -333,6 +424,31 @@ mod tests {
         assert_eq!(result, Value::String("HELLO".to_string()));
     }
 
+    #[test]
+    fn test_unit_and_extract_transforms() {
+        let transform = Transform::ConvertUnit {
+            from: Unit::MilesPerHour,
+            to: Unit::MetersPerSecond,
+        };
+        let mps = transform.apply(Value::from("45")).unwrap();
+        let back = transform.inverse().unwrap().apply(mps).unwrap();
+        assert!((back.as_f64().unwrap() - 45.0).abs() < 1e-9);
+
+        let transform = Transform::Extract {
+            pattern: r"^(\d+)\s*mph$".to_string(),
+            group: 1,
+        };
+        assert_eq!(transform.apply(Value::from("38 mph")).unwrap(), Value::from("38"));
+        assert_eq!(transform.apply(Value::from("unknown")).unwrap(), Value::Null);
+        assert!(transform.inverse().is_none());
+
+        let invalid = Transform::Extract {
+            pattern: "(a)".to_string(),
+            group: 2,
+        };
+        assert!(invalid.validate().is_err());
+    }
+
     #[test]
     fn test_convert_type() {
         let result = convert_type(Value::String("42".to_string()), "integer").unwrap();
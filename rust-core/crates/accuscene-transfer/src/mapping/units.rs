@@ -0,0 +1,155 @@
+use crate::error::{Result, TransferError};
+use accuscene_core::utils::{deg_to_rad, kmh_to_ms, mph_to_ms, ms_to_kmh, ms_to_mph, rad_to_deg};
+use serde::{Deserialize, Serialize};
+
+/// Meters per foot
+const FOOT: f64 = 0.3048;
+
+/// Meters per statute mile
+const MILE: f64 = 1609.344;
+
+/// Meters per second per knot
+const KNOT: f64 = 1852.0 / 3600.0;
+
+/// Kilograms per pound
+const POUND: f64 = 0.453_592_37;
+
+/// Standard gravity in m/s²
+const STANDARD_GRAVITY: f64 = 9.806_65;
+
+/// Measurement unit for field conversion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Unit {
+    // Speed
+    MetersPerSecond,
+    KilometersPerHour,
+    MilesPerHour,
+    Knots,
+    // Length
+    Meters,
+    Kilometers,
+    Feet,
+    Miles,
+    // Mass
+    Kilograms,
+    Pounds,
+    // Acceleration
+    MetersPerSecondSquared,
+    StandardGravity,
+    // Angle
+    Degrees,
+    Radians,
+    // Time
+    Seconds,
+    Milliseconds,
+}
+
+/// Physical quantity measured by a unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quantity {
+    Speed,
+    Length,
+    Mass,
+    Acceleration,
+    Angle,
+    Time,
+}
+
+impl Unit {
+    /// Quantity the unit measures
+    pub fn quantity(&self) -> Quantity {
+        match self {
+            Unit::MetersPerSecond | Unit::KilometersPerHour | Unit::MilesPerHour | Unit::Knots => {
+                Quantity::Speed
+            },
+            Unit::Meters | Unit::Kilometers | Unit::Feet | Unit::Miles => Quantity::Length,
+            Unit::Kilograms | Unit::Pounds => Quantity::Mass,
+            Unit::MetersPerSecondSquared | Unit::StandardGravity => Quantity::Acceleration,
+            Unit::Degrees | Unit::Radians => Quantity::Angle,
+            Unit::Seconds | Unit::Milliseconds => Quantity::Time,
+        }
+    }
+
+    /// Convert a value in this unit to the SI unit of its quantity
+    fn to_si(self, value: f64) -> f64 {
+        match self {
+            Unit::KilometersPerHour => kmh_to_ms(value),
+            Unit::MilesPerHour => mph_to_ms(value),
+            Unit::Knots => value * KNOT,
+            Unit::Kilometers => value * 1000.0,
+            Unit::Feet => value * FOOT,
+            Unit::Miles => value * MILE,
+            Unit::Pounds => value * POUND,
+            Unit::StandardGravity => value * STANDARD_GRAVITY,
+            Unit::Degrees => deg_to_rad(value),
+            Unit::Milliseconds => value / 1000.0,
+            Unit::MetersPerSecond
+            | Unit::Meters
+            | Unit::Kilograms
+            | Unit::MetersPerSecondSquared
+            | Unit::Radians
+            | Unit::Seconds => value,
+        }
+    }
+
+    /// Convert an SI value to this unit
+    fn si_to_unit(self, value: f64) -> f64 {
+        match self {
+            Unit::KilometersPerHour => ms_to_kmh(value),
+            Unit::MilesPerHour => ms_to_mph(value),
+            Unit::Knots => value / KNOT,
+            Unit::Kilometers => value / 1000.0,
+            Unit::Feet => value / FOOT,
+            Unit::Miles => value / MILE,
+            Unit::Pounds => value / POUND,
+            Unit::StandardGravity => value / STANDARD_GRAVITY,
+            Unit::Degrees => rad_to_deg(value),
+            Unit::Milliseconds => value * 1000.0,
+            Unit::MetersPerSecond
+            | Unit::Meters
+            | Unit::Kilograms
+            | Unit::MetersPerSecondSquared
+            | Unit::Radians
+            | Unit::Seconds => value,
+        }
+    }
+}
+
+/// Check two units measure the same quantity
+pub fn check_compatible(from: Unit, to: Unit) -> Result<()> {
+    if from.quantity() != to.quantity() {
+        return Err(TransferError::Transform(format!(
+            "Cannot convert {:?} to {:?}",
+            from, to
+        )));
+    }
+    Ok(())
+}
+
+/// Convert a value between units of the same quantity
+pub fn convert(value: f64, from: Unit, to: Unit) -> Result<f64> {
+    check_compatible(from, to)?;
+    if from == to {
+        return Ok(value);
+    }
+    Ok(to.si_to_unit(from.to_si(value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_conversion() {
+        let mps = convert(60.0, Unit::MilesPerHour, Unit::MetersPerSecond).unwrap();
+        assert!((mps - 26.8224).abs() < 1e-3);
+        let mph = convert(mps, Unit::MetersPerSecond, Unit::MilesPerHour).unwrap();
+        assert!((mph - 60.0).abs() < 1e-9);
+
+        let feet = convert(100.0, Unit::Meters, Unit::Feet).unwrap();
+        assert!((feet - 328.084).abs() < 1e-3);
+
+        assert!(convert(1.0, Unit::Meters, Unit::Seconds).is_err());
+    }
+}
@@ -0,0 +1,572 @@
+//! Resumable chunked exports
+//!
+//! Large exports are split into chunk files holding a fixed number of
+//! records, each written by the regular format handler. A sidecar manifest
+//! is checkpointed after every chunk, so an export interrupted by e.g. a
+//! flaky network drive can be picked up again with [`resume_export`]:
+//! chunks already on disk are verified against their recorded size and
+//! SHA-256 digest, and only missing or damaged chunks are written again.
+
+use crate::{
+    config::{TransferConfig, TransferFormat},
+    error::{Result, TransferError},
+    formats::get_export_handler,
+    progress::ProgressTracker,
+    DataRecord,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+
+/// Suffix of the sidecar manifest file (`cases.manifest.json`)
+pub const MANIFEST_SUFFIX: &str = ".manifest.json";
+
+/// Manifest format version
+const MANIFEST_VERSION: u32 = 1;
+
+/// A chunk file that has been completely written
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChunkEntry {
+    /// Chunk index
+    pub index: usize,
+    /// File name, relative to the manifest
+    pub file_name: String,
+    /// Records in the chunk
+    pub record_count: usize,
+    /// File size in bytes
+    pub size: u64,
+    /// Hex encoded SHA-256 digest of the file
+    pub sha256: String,
+}
+
+/// Checkpoint of a chunked export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportManifest {
+    /// Manifest format version
+    pub version: u32,
+    /// Export job ID
+    pub job_id: String,
+    /// Format of the chunk files
+    pub format: TransferFormat,
+    /// Base name of the chunk files
+    pub base_name: String,
+    /// Records in the whole export
+    pub total_records: usize,
+    /// Records per chunk
+    pub chunk_records: usize,
+    /// Chunks written so far, ordered by index
+    pub chunks: Vec<ChunkEntry>,
+    /// Whether all chunks have been written
+    pub completed: bool,
+    /// Creation timestamp
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Last checkpoint timestamp
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl ExportManifest {
+    /// Create manifest for a new export
+    pub fn new(
+        base_name: impl Into<String>,
+        format: TransferFormat,
+        total_records: usize,
+        chunk_records: usize,
+    ) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            version: MANIFEST_VERSION,
+            job_id: uuid::Uuid::new_v4().to_string(),
+            format,
+            base_name: base_name.into(),
+            total_records,
+            chunk_records,
+            chunks: Vec::new(),
+            completed: false,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Manifest file name for an export base name
+    pub fn file_name(base_name: &str) -> String {
+        format!("{}{}", base_name, MANIFEST_SUFFIX)
+    }
+
+    /// Number of chunks in the whole export
+    pub fn chunk_count(&self) -> usize {
+        self.total_records.div_ceil(self.chunk_records)
+    }
+
+    /// Record range covered by a chunk
+    pub fn chunk_range(&self, index: usize) -> Range<usize> {
+        let start = index * self.chunk_records;
+        start..(start + self.chunk_records).min(self.total_records)
+    }
+
+    /// File name of a chunk (`cases.part-00003.csv`)
+    pub fn chunk_file_name(&self, index: usize) -> String {
+        format!(
+            "{}.part-{:05}.{}",
+            self.base_name,
+            index,
+            self.format.extension()
+        )
+    }
+
+    /// Get a written chunk
+    pub fn chunk(&self, index: usize) -> Option<&ChunkEntry> {
+        self.chunks.iter().find(|chunk| chunk.index == index)
+    }
+
+    /// Indices of chunks not written yet
+    pub fn pending_chunks(&self) -> Vec<usize> {
+        (0..self.chunk_count()).filter(|index| self.chunk(*index).is_none()).collect()
+    }
+
+    /// Records in the chunks written so far
+    pub fn written_records(&self) -> usize {
+        self.chunks.iter().map(|chunk| chunk.record_count).sum()
+    }
+
+    /// Load manifest from file
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let data = tokio::fs::read(path.as_ref()).await?;
+        let manifest: Self = serde_json::from_slice(&data)?;
+
+        if manifest.version > MANIFEST_VERSION {
+            return Err(TransferError::Validation(format!(
+                "Unsupported export manifest version {}",
+                manifest.version
+            )));
+        }
+        if manifest.chunk_records == 0 {
+            return Err(TransferError::Validation(
+                "Export manifest has no chunk size".to_string(),
+            ));
+        }
+
+        Ok(manifest)
+    }
+
+    /// Save manifest to file, replacing the previous checkpoint atomically
+    pub async fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let data = serde_json::to_vec_pretty(self)?;
+        write_atomic(path.as_ref(), &data).await
+    }
+
+    fn record_chunk(&mut self, entry: ChunkEntry) {
+        self.chunks.retain(|chunk| chunk.index != entry.index);
+        self.chunks.push(entry);
+        self.chunks.sort_by_key(|chunk| chunk.index);
+        self.updated_at = chrono::Utc::now();
+    }
+}
+
+/// Problem found with a written chunk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChunkIssue {
+    /// Chunk file is gone
+    Missing,
+    /// Chunk file was truncated or overwritten
+    SizeMismatch { expected: u64, actual: u64 },
+    /// Chunk file content changed
+    ChecksumMismatch,
+}
+
+/// Chunked export checkpointed to a sidecar manifest
+pub struct ExportJob {
+    manifest: ExportManifest,
+    manifest_path: PathBuf,
+    directory: PathBuf,
+}
+
+impl ExportJob {
+    /// Create a new export job writing chunks into `directory`
+    ///
+    /// Fails if a manifest for `base_name` already exists there; resume it
+    /// with [`ExportJob::open`] instead.
+    pub async fn create(
+        directory: impl AsRef<Path>,
+        base_name: &str,
+        format: TransferFormat,
+        total_records: usize,
+        chunk_records: usize,
+    ) -> Result<Self> {
+        if !format.supports_export() {
+            return Err(TransferError::UnsupportedFormat(
+                format.extension().to_string(),
+            ));
+        }
+        if chunk_records == 0 {
+            return Err(TransferError::InvalidConfig(
+                "export_chunk_records must be greater than 0".to_string(),
+            ));
+        }
+
+        let directory = directory.as_ref().to_path_buf();
+        tokio::fs::create_dir_all(&directory).await?;
+
+        let manifest_path = directory.join(ExportManifest::file_name(base_name));
+        if tokio::fs::try_exists(&manifest_path).await? {
+            return Err(TransferError::Validation(format!(
+                "Export manifest {} already exists",
+                manifest_path.display()
+            )));
+        }
+
+        let manifest = ExportManifest::new(base_name, format, total_records, chunk_records);
+        manifest.save(&manifest_path).await?;
+
+        Ok(Self {
+            manifest,
+            manifest_path,
+            directory,
+        })
+    }
+
+    /// Open an existing export job from its manifest
+    ///
+    /// Chunk files are expected next to the manifest.
+    pub async fn open(manifest_path: impl AsRef<Path>) -> Result<Self> {
+        let manifest_path = manifest_path.as_ref().to_path_buf();
+        let manifest = ExportManifest::load(&manifest_path).await?;
+        let directory = manifest_path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+        Ok(Self {
+            manifest,
+            manifest_path,
+            directory,
+        })
+    }
+
+    /// Get the manifest
+    pub fn manifest(&self) -> &ExportManifest {
+        &self.manifest
+    }
+
+    /// Get the manifest path
+    pub fn manifest_path(&self) -> &Path {
+        &self.manifest_path
+    }
+
+    /// Paths of the chunks written so far, in order
+    pub fn chunk_paths(&self) -> Vec<PathBuf> {
+        self.manifest
+            .chunks
+            .iter()
+            .map(|chunk| self.directory.join(&chunk.file_name))
+            .collect()
+    }
+
+    /// Check the written chunks against their recorded size and digest
+    pub async fn verify(&self) -> Result<Vec<(usize, ChunkIssue)>> {
+        let mut issues = Vec::new();
+
+        for chunk in &self.manifest.chunks {
+            let path = self.directory.join(&chunk.file_name);
+            let data = match tokio::fs::read(&path).await {
+                Ok(data) => data,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    issues.push((chunk.index, ChunkIssue::Missing));
+                    continue;
+                },
+                Err(e) => return Err(e.into()),
+            };
+
+            if data.len() as u64 != chunk.size {
+                issues.push((
+                    chunk.index,
+                    ChunkIssue::SizeMismatch {
+                        expected: chunk.size,
+                        actual: data.len() as u64,
+                    },
+                ));
+            } else if sha256_hex(&data) != chunk.sha256 {
+                issues.push((chunk.index, ChunkIssue::ChecksumMismatch));
+            }
+        }
+
+        Ok(issues)
+    }
+
+    /// Write all pending chunks
+    ///
+    /// `records` must be the complete export in the original order. Damaged
+    /// chunks are dropped from the manifest and written again; the manifest
+    /// is checkpointed after each chunk.
+    pub async fn run(
+        &mut self,
+        records: &[DataRecord],
+        config: &TransferConfig,
+        tracker: Option<ProgressTracker>,
+    ) -> Result<()> {
+        if records.len() != self.manifest.total_records {
+            return Err(TransferError::Validation(format!(
+                "Export job {} expects {} records, got {}",
+                self.manifest.job_id,
+                self.manifest.total_records,
+                records.len()
+            )));
+        }
+
+        if let Some(ref t) = tracker {
+            t.start().await;
+        }
+
+        let issues = self.verify().await?;
+        if !issues.is_empty() {
+            for (index, issue) in &issues {
+                tracing::warn!(
+                    "Export job {} chunk {} will be rewritten: {:?}",
+                    self.manifest.job_id,
+                    index,
+                    issue
+                );
+            }
+            self.manifest
+                .chunks
+                .retain(|chunk| !issues.iter().any(|(index, _)| *index == chunk.index));
+            self.manifest.completed = false;
+            self.manifest.save(&self.manifest_path).await?;
+        }
+
+        let handler = get_export_handler(self.manifest.format.extension())?;
+
+        for index in self.manifest.pending_chunks() {
+            if let Some(ref t) = tracker {
+                if t.is_cancelled().await {
+                    return Err(TransferError::Cancelled);
+                }
+                t.update(
+                    self.manifest.written_records() as u64,
+                    Some(format!("Writing chunk {}", index + 1)),
+                )
+                .await;
+            }
+
+            let range = self.manifest.chunk_range(index);
+            let record_count = range.len();
+            let data = handler.export(records[range].to_vec(), config, None).await?;
+
+            let file_name = self.manifest.chunk_file_name(index);
+            write_atomic(&self.directory.join(&file_name), &data).await?;
+
+            self.manifest.record_chunk(ChunkEntry {
+                index,
+                file_name,
+                record_count,
+                size: data.len() as u64,
+                sha256: sha256_hex(&data),
+            });
+            self.manifest.save(&self.manifest_path).await?;
+        }
+
+        self.manifest.completed = true;
+        self.manifest.updated_at = chrono::Utc::now();
+        self.manifest.save(&self.manifest_path).await?;
+
+        if let Some(ref t) = tracker {
+            t.complete().await;
+        }
+
+        Ok(())
+    }
+}
+
+/// Start a resumable export of `records` into `directory`
+///
+/// Chunks hold `config.export_chunk_records` records each.
+pub async fn start_export(
+    directory: impl AsRef<Path>,
+    base_name: &str,
+    format: TransferFormat,
+    records: &[DataRecord],
+    config: &TransferConfig,
+    tracker: Option<ProgressTracker>,
+) -> Result<ExportManifest> {
+    let mut job = ExportJob::create(
+        directory,
+        base_name,
+        format,
+        records.len(),
+        config.export_chunk_records,
+    )
+    .await?;
+    job.run(records, config, tracker).await?;
+    Ok(job.manifest)
+}
+
+/// Resume an interrupted export from its manifest
+///
+/// Already written chunks are verified and kept; missing and damaged
+/// chunks are written from `records`, which must be the same records the
+/// export was started with.
+pub async fn resume_export(
+    manifest_path: impl AsRef<Path>,
+    records: &[DataRecord],
+    config: &TransferConfig,
+    tracker: Option<ProgressTracker>,
+) -> Result<ExportManifest> {
+    let mut job = ExportJob::open(manifest_path).await?;
+    job.run(records, config, tracker).await?;
+    Ok(job.manifest)
+}
+
+/// Write a file through a temporary sibling so readers never see a partial file
+pub(crate) async fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    let mut temp_name = path.as_os_str().to_owned();
+    temp_name.push(".tmp");
+    let temp_path = PathBuf::from(temp_name);
+
+    let mut file = tokio::fs::File::create(&temp_path).await?;
+    file.write_all(data).await?;
+    file.sync_all().await?;
+    drop(file);
+
+    tokio::fs::rename(&temp_path, path).await?;
+    Ok(())
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::get_import_handler;
+    use serde_json::Value;
+
+    fn records(count: usize) -> Vec<DataRecord> {
+        (0..count)
+            .map(|i| {
+                let mut record = DataRecord::new();
+                record.set("id".to_string(), Value::from(i));
+                record
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_resume_export() {
+        let dir = tempfile::tempdir().unwrap();
+        let records = records(25);
+        let config = TransferConfig::default().with_export_chunk_records(10);
+
+        let manifest = start_export(
+            dir.path(),
+            "cases",
+            TransferFormat::Json,
+            &records,
+            &config,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(manifest.completed);
+        assert_eq!(manifest.chunks.len(), 3);
+        assert_eq!(manifest.chunks[2].record_count, 5);
+
+        // Lose one chunk and damage another
+        let manifest_path = dir.path().join("cases.manifest.json");
+        tokio::fs::remove_file(dir.path().join("cases.part-00000.json")).await.unwrap();
+        tokio::fs::write(dir.path().join("cases.part-00002.json"), b"[]").await.unwrap();
+
+        let job = ExportJob::open(&manifest_path).await.unwrap();
+        let issues = job.verify().await.unwrap();
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0], (0, ChunkIssue::Missing));
+
+        let err = resume_export(&manifest_path, &records[..20], &config, None).await.unwrap_err();
+        assert!(matches!(err, TransferError::Validation(_)));
+
+        let manifest = resume_export(&manifest_path, &records, &config, None).await.unwrap();
+        assert!(manifest.completed);
+        let job = ExportJob::open(&manifest_path).await.unwrap();
+        assert!(job.verify().await.unwrap().is_empty());
+
+        let handler = get_import_handler("json").unwrap();
+        let mut imported = 0;
+        for path in job.chunk_paths() {
+            let data = tokio::fs::read(path).await.unwrap();
+            imported += handler.import(data.into(), &config, None).await.unwrap().len();
+        }
+        assert_eq!(imported, 25);
+    }
+
+    #[tokio::test]
+    async fn test_create_rejects_invalid_exports() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let err = ExportJob::create(dir.path(), "cases", TransferFormat::Gpx, 10, 5)
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, TransferError::UnsupportedFormat(_)));
+
+        let err = ExportJob::create(dir.path(), "cases", TransferFormat::Json, 10, 0)
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, TransferError::InvalidConfig(_)));
+
+        ExportJob::create(dir.path(), "cases", TransferFormat::Json, 10, 5).await.unwrap();
+        let err = ExportJob::create(dir.path(), "cases", TransferFormat::Json, 10, 5)
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, TransferError::Validation(_)));
+    }
+
+    #[tokio::test]
+    async fn test_load_rejects_bad_manifests() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(ExportManifest::file_name("cases"));
+
+        let mut manifest = ExportManifest::new("cases", TransferFormat::Json, 10, 5);
+        manifest.version = MANIFEST_VERSION + 1;
+        manifest.save(&path).await.unwrap();
+        let err = ExportManifest::load(&path).await.unwrap_err();
+        assert!(matches!(err, TransferError::Validation(_)));
+
+        manifest.version = MANIFEST_VERSION;
+        manifest.chunk_records = 0;
+        manifest.save(&path).await.unwrap();
+        let err = ExportJob::open(&path).await.err().unwrap();
+        assert!(matches!(err, TransferError::Validation(_)));
+
+        tokio::fs::write(&path, b"{\"version\": 1").await.unwrap();
+        assert!(ExportManifest::load(&path).await.is_err());
+        assert!(ExportManifest::load(dir.path().join("missing.manifest.json")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_verify_detects_changed_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let records = records(20);
+        let config = TransferConfig::default().with_export_chunk_records(10);
+        start_export(dir.path(), "cases", TransferFormat::Json, &records, &config, None)
+            .await
+            .unwrap();
+
+        // Same size, different content
+        let first = dir.path().join("cases.part-00000.json");
+        let mut data = tokio::fs::read(&first).await.unwrap();
+        let digit = data.iter().position(u8::is_ascii_digit).unwrap();
+        data[digit] = if data[digit] == b'9' { b'8' } else { b'9' };
+        tokio::fs::write(&first, &data).await.unwrap();
+
+        let second = dir.path().join("cases.part-00001.json");
+        tokio::fs::write(&second, b"[]").await.unwrap();
+
+        let job = ExportJob::open(dir.path().join("cases.manifest.json")).await.unwrap();
+        let issues = job.verify().await.unwrap();
+        assert_eq!(issues[0], (0, ChunkIssue::ChecksumMismatch));
+        assert!(matches!(
+            issues[1],
+            (1, ChunkIssue::SizeMismatch { actual: 2, .. })
+        ));
+    }
+}
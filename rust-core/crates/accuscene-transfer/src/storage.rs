@@ -0,0 +1,486 @@
+//! Local and object-storage sources and destinations
+//!
+//! Transfers can read from and write to local paths or `s3://`, `az://`
+//! and `gs://` URIs. Remote clients are behind the `s3`, `azure` and `gcs`
+//! features and take credentials from the environment, with overrides from
+//! [`StorageOptions::client_options`].
+
+use crate::{
+    config::TransferConfig,
+    error::{Result, TransferError},
+    formats::{decompress_input, get_export_handler, get_import_handler},
+    progress::ProgressTracker,
+    resumable::write_atomic,
+    DataRecord,
+};
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Smallest part size accepted by S3-compatible multipart uploads
+pub const MIN_PART_SIZE: usize = 5 * 1024 * 1024;
+
+/// Where a transfer reads from or writes to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StorageLocation {
+    /// Local file path (plain path or `file://`)
+    Local(PathBuf),
+    /// Amazon S3 or S3-compatible object (`s3://bucket/key`)
+    S3 { bucket: String, key: String },
+    /// Azure Blob Storage blob (`az://container/blob`)
+    Azure { container: String, blob: String },
+    /// Google Cloud Storage object (`gs://bucket/object`)
+    Gcs { bucket: String, object: String },
+}
+
+impl StorageLocation {
+    /// Parse a path or storage URI
+    pub fn parse(uri: &str) -> Result<Self> {
+        let Some((scheme, rest)) = uri.split_once("://") else {
+            return Ok(Self::Local(PathBuf::from(uri)));
+        };
+
+        let split = |kind: &str| -> Result<(String, String)> {
+            match rest.split_once('/') {
+                Some((container, path)) if !container.is_empty() && !path.is_empty() => {
+                    Ok((container.to_string(), path.to_string()))
+                },
+                _ => Err(TransferError::InvalidConfig(format!(
+                    "Storage URI '{}' must name a {} and an object path",
+                    uri, kind
+                ))),
+            }
+        };
+
+        match scheme.to_lowercase().as_str() {
+            "file" => Ok(Self::Local(PathBuf::from(rest))),
+            "s3" | "s3a" => {
+                let (bucket, key) = split("bucket")?;
+                Ok(Self::S3 { bucket, key })
+            },
+            "az" | "azure" => {
+                let (container, blob) = split("container")?;
+                Ok(Self::Azure { container, blob })
+            },
+            "gs" | "gcs" => {
+                let (bucket, object) = split("bucket")?;
+                Ok(Self::Gcs { bucket, object })
+            },
+            other => Err(TransferError::InvalidConfig(format!(
+                "Unsupported storage scheme '{}'",
+                other
+            ))),
+        }
+    }
+
+    /// Check if the location is in object storage
+    pub fn is_remote(&self) -> bool {
+        !matches!(self, Self::Local(_))
+    }
+
+    /// Read the whole object, rejecting objects larger than `max_file_size`
+    pub async fn read(&self, config: &TransferConfig) -> Result<Bytes> {
+        match self {
+            Self::Local(path) => {
+                let size = tokio::fs::metadata(path).await?.len();
+                if size > config.max_file_size {
+                    return Err(TransferError::FileTooLarge(size, config.max_file_size));
+                }
+                Ok(Bytes::from(tokio::fs::read(path).await?))
+            },
+            _ => remote::read(self, config).await,
+        }
+    }
+
+    /// Write the object, replacing any existing one
+    ///
+    /// Local files are written atomically. Remote payloads of at least
+    /// `multipart_threshold` bytes are uploaded in parts.
+    pub async fn write(&self, data: Bytes, config: &TransferConfig) -> Result<()> {
+        match self {
+            Self::Local(path) => {
+                if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                write_atomic(path, &data).await
+            },
+            _ => remote::write(self, data, config).await,
+        }
+    }
+}
+
+impl FromStr for StorageLocation {
+    type Err = TransferError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for StorageLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local(path) => write!(f, "{}", path.display()),
+            Self::S3 { bucket, key } => write!(f, "s3://{}/{}", bucket, key),
+            Self::Azure { container, blob } => write!(f, "az://{}/{}", container, blob),
+            Self::Gcs { bucket, object } => write!(f, "gs://{}/{}", bucket, object),
+        }
+    }
+}
+
+/// Object storage client settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageOptions {
+    /// Payloads at or above this size use multipart upload (default: 16MB)
+    pub multipart_threshold: usize,
+    /// Multipart part size (default: 8MB, minimum 5MB)
+    pub part_size: usize,
+    /// Parts uploaded concurrently
+    pub max_concurrent_parts: usize,
+    /// Retries per request on transient failures
+    pub max_retries: usize,
+    /// First retry delay in milliseconds, doubled on each retry
+    pub retry_initial_backoff_ms: u64,
+    /// Longest retry delay in milliseconds
+    pub retry_max_backoff_ms: u64,
+    /// Give up retrying a request after this many seconds
+    pub retry_timeout_secs: u64,
+    /// Provider settings such as `aws_region`, `aws_endpoint` or
+    /// `azure_storage_account_name`; keys a provider does not recognise are
+    /// ignored
+    pub client_options: HashMap<String, String>,
+}
+
+impl Default for StorageOptions {
+    fn default() -> Self {
+        Self {
+            multipart_threshold: 16 * 1024 * 1024,
+            part_size: 8 * 1024 * 1024,
+            max_concurrent_parts: 4,
+            max_retries: 5,
+            retry_initial_backoff_ms: 100,
+            retry_max_backoff_ms: 15_000,
+            retry_timeout_secs: 180,
+            client_options: HashMap::new(),
+        }
+    }
+}
+
+impl StorageOptions {
+    /// Builder: Set multipart threshold and part size
+    pub fn with_multipart(mut self, threshold: usize, part_size: usize) -> Self {
+        self.multipart_threshold = threshold;
+        self.part_size = part_size;
+        self
+    }
+
+    /// Builder: Set retry count and backoff bounds
+    pub fn with_retry(
+        mut self,
+        max_retries: usize,
+        initial_backoff_ms: u64,
+        max_backoff_ms: u64,
+    ) -> Self {
+        self.max_retries = max_retries;
+        self.retry_initial_backoff_ms = initial_backoff_ms;
+        self.retry_max_backoff_ms = max_backoff_ms;
+        self
+    }
+
+    /// Builder: Add provider client option
+    pub fn with_client_option(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.client_options.insert(key.into(), value.into());
+        self
+    }
+
+    /// Validate settings
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if self.part_size < MIN_PART_SIZE {
+            return Err(format!(
+                "part_size must be at least {} bytes",
+                MIN_PART_SIZE
+            ));
+        }
+        if self.multipart_threshold < self.part_size {
+            return Err("multipart_threshold must be at least part_size".to_string());
+        }
+        if self.max_concurrent_parts == 0 {
+            return Err("max_concurrent_parts must be greater than 0".to_string());
+        }
+        if self.retry_initial_backoff_ms > self.retry_max_backoff_ms {
+            return Err("retry_initial_backoff_ms cannot exceed retry_max_backoff_ms".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Import records from `config.source`
+///
+/// Compressed sources are decompressed before `format`'s handler runs.
+pub async fn import_source(
+    format: &str,
+    config: &TransferConfig,
+    tracker: Option<ProgressTracker>,
+) -> Result<Vec<DataRecord>> {
+    let source = config
+        .source
+        .as_deref()
+        .ok_or_else(|| TransferError::InvalidConfig("No import source configured".to_string()))?;
+    let handler = get_import_handler(format)?;
+
+    let data = StorageLocation::parse(source)?.read(config).await?;
+    let data = decompress_input(data, config)?;
+    handler.import(data, config, tracker).await
+}
+
+/// Export records to `config.destination`
+pub async fn export_destination(
+    records: Vec<DataRecord>,
+    format: &str,
+    config: &TransferConfig,
+    tracker: Option<ProgressTracker>,
+) -> Result<()> {
+    let destination = config.destination.as_deref().ok_or_else(|| {
+        TransferError::InvalidConfig("No export destination configured".to_string())
+    })?;
+    let location = StorageLocation::parse(destination)?;
+    let handler = get_export_handler(format)?;
+
+    let data = handler.export(records, config, tracker).await?;
+    location.write(data, config).await
+}
+
+#[cfg(any(feature = "s3", feature = "azure", feature = "gcs"))]
+mod remote {
+    use super::*;
+    use futures::{stream, StreamExt, TryStreamExt};
+    use object_store::{
+        path::Path as ObjectPath, BackoffConfig, ObjectStore, PutPayload, RetryConfig,
+    };
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn retry_config(options: &StorageOptions) -> RetryConfig {
+        RetryConfig {
+            backoff: BackoffConfig {
+                init_backoff: Duration::from_millis(options.retry_initial_backoff_ms),
+                max_backoff: Duration::from_millis(options.retry_max_backoff_ms),
+                base: 2.0,
+            },
+            max_retries: options.max_retries,
+            retry_timeout: Duration::from_secs(options.retry_timeout_secs),
+        }
+    }
+
+    /// Build a client for the location's bucket or container
+    fn open(
+        location: &StorageLocation,
+        options: &StorageOptions,
+    ) -> Result<(Arc<dyn ObjectStore>, ObjectPath)> {
+        match location {
+            #[cfg(feature = "s3")]
+            StorageLocation::S3 { bucket, key } => {
+                use object_store::aws::{AmazonS3Builder, AmazonS3ConfigKey};
+
+                let mut builder = AmazonS3Builder::from_env()
+                    .with_bucket_name(bucket)
+                    .with_retry(retry_config(options));
+                for (key, value) in &options.client_options {
+                    if let Ok(key) = key.parse::<AmazonS3ConfigKey>() {
+                        builder = builder.with_config(key, value);
+                    }
+                }
+                Ok((Arc::new(builder.build()?), ObjectPath::from(key.as_str())))
+            },
+            #[cfg(feature = "azure")]
+            StorageLocation::Azure { container, blob } => {
+                use object_store::azure::{AzureConfigKey, MicrosoftAzureBuilder};
+
+                let mut builder = MicrosoftAzureBuilder::from_env()
+                    .with_container_name(container)
+                    .with_retry(retry_config(options));
+                for (key, value) in &options.client_options {
+                    if let Ok(key) = key.parse::<AzureConfigKey>() {
+                        builder = builder.with_config(key, value);
+                    }
+                }
+                Ok((Arc::new(builder.build()?), ObjectPath::from(blob.as_str())))
+            },
+            #[cfg(feature = "gcs")]
+            StorageLocation::Gcs { bucket, object } => {
+                use object_store::gcp::{GoogleCloudStorageBuilder, GoogleConfigKey};
+
+                let mut builder = GoogleCloudStorageBuilder::from_env()
+                    .with_bucket_name(bucket)
+                    .with_retry(retry_config(options));
+                for (key, value) in &options.client_options {
+                    if let Ok(key) = key.parse::<GoogleConfigKey>() {
+                        builder = builder.with_config(key, value);
+                    }
+                }
+                Ok((
+                    Arc::new(builder.build()?),
+                    ObjectPath::from(object.as_str()),
+                ))
+            },
+            other => Err(unsupported(other)),
+        }
+    }
+
+    pub(super) async fn read(location: &StorageLocation, config: &TransferConfig) -> Result<Bytes> {
+        let (store, path) = open(location, &config.storage)?;
+        let result = store.get(&path).await?;
+
+        let size = u64::try_from(result.meta.size).unwrap_or(u64::MAX);
+        if size > config.max_file_size {
+            return Err(TransferError::FileTooLarge(size, config.max_file_size));
+        }
+        Ok(result.bytes().await?)
+    }
+
+    pub(super) async fn write(
+        location: &StorageLocation,
+        data: Bytes,
+        config: &TransferConfig,
+    ) -> Result<()> {
+        let options = &config.storage;
+        let (store, path) = open(location, options)?;
+
+        if data.len() < options.multipart_threshold {
+            store.put(&path, PutPayload::from(data)).await?;
+            return Ok(());
+        }
+
+        let mut upload = store.put_multipart(&path).await?;
+        let parts: Vec<_> = (0..data.len())
+            .step_by(options.part_size)
+            .map(|start| {
+                let end = (start + options.part_size).min(data.len());
+                upload.put_part(PutPayload::from(data.slice(start..end)))
+            })
+            .collect();
+        tracing::debug!("Uploading {} in {} parts", location, parts.len());
+
+        let uploaded: std::result::Result<Vec<()>, _> = stream::iter(parts)
+            .buffer_unordered(options.max_concurrent_parts)
+            .try_collect()
+            .await;
+        if let Err(e) = uploaded {
+            if let Err(abort) = upload.abort().await {
+                tracing::warn!(
+                    "Failed to abort multipart upload to {}: {}",
+                    location,
+                    abort
+                );
+            }
+            return Err(e.into());
+        }
+
+        upload.complete().await?;
+        Ok(())
+    }
+}
+
+#[cfg(not(any(feature = "s3", feature = "azure", feature = "gcs")))]
+mod remote {
+    use super::*;
+
+    pub(super) async fn read(
+        location: &StorageLocation,
+        _config: &TransferConfig,
+    ) -> Result<Bytes> {
+        Err(unsupported(location))
+    }
+
+    pub(super) async fn write(
+        location: &StorageLocation,
+        _data: Bytes,
+        _config: &TransferConfig,
+    ) -> Result<()> {
+        Err(unsupported(location))
+    }
+}
+
+/// Error for a location whose client feature is disabled
+fn unsupported(location: &StorageLocation) -> TransferError {
+    let feature = match location {
+        StorageLocation::S3 { .. } => "s3",
+        StorageLocation::Azure { .. } => "azure",
+        StorageLocation::Gcs { .. } => "gcs",
+        StorageLocation::Local(_) => "local",
+    };
+    TransferError::Storage(format!(
+        "{} requires the `{}` feature of accuscene-transfer",
+        location, feature
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_parse_location() {
+        assert_eq!(
+            StorageLocation::parse("s3://evidence/cases/2024/scene.csv").unwrap(),
+            StorageLocation::S3 {
+                bucket: "evidence".to_string(),
+                key: "cases/2024/scene.csv".to_string(),
+            }
+        );
+        assert_eq!(
+            StorageLocation::parse("az://exports/report.pdf").unwrap(),
+            StorageLocation::Azure {
+                container: "exports".to_string(),
+                blob: "report.pdf".to_string(),
+            }
+        );
+        let gcs = StorageLocation::parse("gs://tracks/run.gpx").unwrap();
+        assert!(gcs.is_remote());
+        assert_eq!(gcs.to_string(), "gs://tracks/run.gpx");
+
+        assert_eq!(
+            StorageLocation::parse("file:///tmp/scene.csv").unwrap(),
+            StorageLocation::Local(PathBuf::from("/tmp/scene.csv"))
+        );
+        assert!(!StorageLocation::parse("scene.csv").unwrap().is_remote());
+
+        assert!(StorageLocation::parse("s3://evidence").is_err());
+        assert!(StorageLocation::parse("ftp://host/file.csv").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_local_transfer() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("exports/scene.json");
+        let config = TransferConfig::default()
+            .with_source(path.display().to_string())
+            .with_destination(path.display().to_string());
+
+        let mut record = DataRecord::new();
+        record.set("vehicle".to_string(), Value::from("V1"));
+        export_destination(vec![record], "json", &config, None).await.unwrap();
+
+        let imported = import_source("json", &config, None).await.unwrap();
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0].get("vehicle"), Some(&Value::from("V1")));
+
+        let small = config.clone().with_max_file_size(1);
+        assert!(matches!(
+            import_source("json", &small, None).await,
+            Err(TransferError::FileTooLarge(_, 1))
+        ));
+    }
+
+    #[test]
+    fn test_storage_options() {
+        assert!(StorageOptions::default().validate().is_ok());
+        assert!(StorageOptions::default().with_multipart(1024, 1024).validate().is_err());
+        assert!(StorageOptions::default().with_retry(3, 500, 100).validate().is_err());
+    }
+}
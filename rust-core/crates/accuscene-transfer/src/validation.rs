@@ -33,6 +33,120 @@ pub enum RuleType {
     Custom { validator: String },
 }
 
+impl RuleType {
+    /// Check a single field value against the rule
+    pub fn check(&self, field: &str, value: Option<&serde_json::Value>) -> Result<()> {
+        match self {
+            RuleType::Required => {
+                if value.is_none() || value == Some(&serde_json::Value::Null) {
+                    return Err(TransferError::Validation(format!(
+                        "Field '{}' is required",
+                        field
+                    )));
+                }
+            }
+            RuleType::MinLength { min } => {
+                if let Some(serde_json::Value::String(s)) = value {
+                    if s.len() < *min {
+                        return Err(TransferError::Validation(format!(
+                            "Field '{}' must be at least {} characters",
+                            field, min
+                        )));
+                    }
+                }
+            }
+            RuleType::MaxLength { max } => {
+                if let Some(serde_json::Value::String(s)) = value {
+                    if s.len() > *max {
+                        return Err(TransferError::Validation(format!(
+                            "Field '{}' must be at most {} characters",
+                            field, max
+                        )));
+                    }
+                }
+            }
+            RuleType::Pattern { regex } => {
+                if let Some(serde_json::Value::String(s)) = value {
+                    let re = regex::Regex::new(regex)
+                        .map_err(|e| TransferError::Validation(format!("Invalid regex: {}", e)))?;
+                    if !re.is_match(s) {
+                        return Err(TransferError::Validation(format!(
+                            "Field '{}' does not match pattern",
+                            field
+                        )));
+                    }
+                }
+            }
+            RuleType::MinValue { min } => {
+                if let Some(num) = value.and_then(|v| v.as_f64()) {
+                    if num < *min {
+                        return Err(TransferError::Validation(format!(
+                            "Field '{}' must be at least {}",
+                            field, min
+                        )));
+                    }
+                }
+            }
+            RuleType::MaxValue { max } => {
+                if let Some(num) = value.and_then(|v| v.as_f64()) {
+                    if num > *max {
+                        return Err(TransferError::Validation(format!(
+                            "Field '{}' must be at most {}",
+                            field, max
+                        )));
+                    }
+                }
+            }
+            RuleType::Email => {
+                if let Some(serde_json::Value::String(s)) = value {
+                    if !is_valid_email(s) {
+                        return Err(TransferError::Validation(format!(
+                            "Field '{}' is not a valid email",
+                            field
+                        )));
+                    }
+                }
+            }
+            RuleType::Url => {
+                if let Some(serde_json::Value::String(s)) = value {
+                    if !is_valid_url(s) {
+                        return Err(TransferError::Validation(format!(
+                            "Field '{}' is not a valid URL",
+                            field
+                        )));
+                    }
+                }
+            }
+            RuleType::Date { format } => {
+                if let Some(serde_json::Value::String(s)) = value {
+                    if chrono::NaiveDateTime::parse_from_str(s, format).is_err() {
+                        return Err(TransferError::Validation(format!(
+                            "Field '{}' is not a valid date (expected format: {})",
+                            field, format
+                        )));
+                    }
+                }
+            }
+            RuleType::Enum { values } => {
+                if let Some(serde_json::Value::String(s)) = value {
+                    if !values.contains(s) {
+                        return Err(TransferError::Validation(format!(
+                            "Field '{}' must be one of: {:?}",
+                            field, values
+                        )));
+                    }
+                }
+            }
+            RuleType::Custom { validator: _ } => {
+                // Custom validators would be implemented here
+                // For now, just pass
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// Validation result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationResult {
@@ -169,116 +283,14 @@ impl DataValidator {
         rule_type: &RuleType,
         record_idx: usize,
     ) -> Result<()> {
-        let value = record.get(field);
-
-        match rule_type {
-            RuleType::Required => {
-                if value.is_none() || value == Some(&serde_json::Value::Null) {
-                    return Err(TransferError::Validation(format!(
-                        "Record {}: Field '{}' is required",
-                        record_idx, field
-                    )));
-                }
-            }
-            RuleType::MinLength { min } => {
-                if let Some(serde_json::Value::String(s)) = value {
-                    if s.len() < *min {
-                        return Err(TransferError::Validation(format!(
-                            "Record {}: Field '{}' must be at least {} characters",
-                            record_idx, field, min
-                        )));
-                    }
-                }
-            }
-            RuleType::MaxLength { max } => {
-                if let Some(serde_json::Value::String(s)) = value {
-                    if s.len() > *max {
-                        return Err(TransferError::Validation(format!(
-                            "Record {}: Field '{}' must be at most {} characters",
-                            record_idx, field, max
-                        )));
-                    }
-                }
-            }
-            RuleType::Pattern { regex } => {
-                if let Some(serde_json::Value::String(s)) = value {
-                    let re = regex::Regex::new(regex)
-                        .map_err(|e| TransferError::Validation(format!("Invalid regex: {}", e)))?;
-                    if !re.is_match(s) {
-                        return Err(TransferError::Validation(format!(
-                            "Record {}: Field '{}' does not match pattern",
-                            record_idx, field
-                        )));
-                    }
-                }
-            }
-            RuleType::MinValue { min } => {
-                if let Some(num) = value.and_then(|v| v.as_f64()) {
-                    if num < *min {
-                        return Err(TransferError::Validation(format!(
-                            "Record {}: Field '{}' must be at least {}",
-                            record_idx, field, min
-                        )));
-                    }
-                }
-            }
-            RuleType::MaxValue { max } => {
-                if let Some(num) = value.and_then(|v| v.as_f64()) {
-                    if num > *max {
-                        return Err(TransferError::Validation(format!(
-                            "Record {}: Field '{}' must be at most {}",
-                            record_idx, field, max
-                        )));
-                    }
-                }
-            }
-            RuleType::Email => {
-                if let Some(serde_json::Value::String(s)) = value {
-                    if !is_valid_email(s) {
-                        return Err(TransferError::Validation(format!(
-                            "Record {}: Field '{}' is not a valid email",
-                            record_idx, field
-                        )));
-                    }
-                }
-            }
-            RuleType::Url => {
-                if let Some(serde_json::Value::String(s)) = value {
-                    if !is_valid_url(s) {
-                        return Err(TransferError::Validation(format!(
-                            "Record {}: Field '{}' is not a valid URL",
-                            record_idx, field
-                        )));
-                    }
-                }
-            }
-            RuleType::Date { format } => {
-                if let Some(serde_json::Value::String(s)) = value {
-                    if chrono::NaiveDateTime::parse_from_str(s, format).is_err() {
-                        return Err(TransferError::Validation(format!(
-                            "Record {}: Field '{}' is not a valid date (expected format: {})",
-                            record_idx, field, format
-                        )));
-                    }
-                }
-            }
-            RuleType::Enum { values } => {
-                if let Some(serde_json::Value::String(s)) = value {
-                    if !values.contains(s) {
-                        return Err(TransferError::Validation(format!(
-                            "Record {}: Field '{}' must be one of: {:?}",
-                            record_idx, field, values
-                        )));
-                    }
+        rule_type
+            .check(field, record.get(field))
+            .map_err(|e| match e {
+                TransferError::Validation(message) => {
+                    TransferError::Validation(format!("Record {}: {}", record_idx, message))
                 }
-            }
-            RuleType::Custom { validator: _ } => {
-                // Custom validators would be implemented here
-                // For now, just pass
-            }
-        }
-
-        Ok(())
+                e => e,
+            })
     }
 
     /// Quick validation (schema only)
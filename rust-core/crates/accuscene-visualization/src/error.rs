@@ -39,6 +39,9 @@ pub enum VisualizationError {
     #[error("Numerical error: {0}")]
     NumericalError(String),
 
+    #[error("Simulation error: {0}")]
+    SimulationError(String),
+
     #[error("IO error: {0}")]
     IoError(String),
 
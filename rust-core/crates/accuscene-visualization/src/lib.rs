@@ -10,12 +10,15 @@
 //! - Distribution and correlation analysis
 //! - Chart data generation for frontend rendering
 //! - Export capabilities (SVG, PNG, JSON, CSV)
+//! - Scene rendering with body poses interpolated between physics steps
 
 pub mod charts;
 pub mod config;
 pub mod data;
 pub mod error;
 pub mod export;
+#[cfg(feature = "scene")]
+pub mod scene;
 
 pub use charts::{
     ChartData, ChartType, SeriesData, SeriesPoint, AxisConfig,
@@ -53,6 +56,9 @@ pub use export::{
     export_chart, to_base64, to_data_url, ExportConfig, ExportFormat, ExportResult,
 };
 
+#[cfg(feature = "scene")]
+pub use scene::{BodyPose, SceneFrame, SceneRenderer};
+
 /// Library version
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
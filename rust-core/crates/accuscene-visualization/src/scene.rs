@@ -0,0 +1,239 @@
+//! Scene rendering from a fixed-step physics simulation
+//!
+//! The renderer is driven at display rate while the physics world steps at
+//! a fixed `dt`. Each call to [`SceneRenderer::advance`] adds the elapsed
+//! frame time to an accumulator, steps physics while a full step is
+//! available, and builds the frame from
+//! [`PhysicsWorld::interpolated_state`] using the leftover fraction of a step
+//! so motion stays smooth when the two rates do not line up.
+
+use crate::error::{Result, VisualizationError};
+use accuscene_physics_v3::interpolation::{BodyTransform, InterpolatedState};
+use accuscene_physics_v3::PhysicsWorld;
+use serde::{Deserialize, Serialize};
+
+/// Default fixed physics time step (s)
+pub const DEFAULT_PHYSICS_DT: f64 = 1.0 / 120.0;
+
+/// Default maximum physics steps per rendered frame
+pub const DEFAULT_MAX_STEPS_PER_FRAME: u32 = 8;
+
+/// Pose of a rigid body in a rendered frame
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BodyPose {
+    /// Body identifier in the physics world
+    pub body_id: usize,
+    /// Position in world space (m)
+    pub position: [f64; 3],
+    /// Orientation quaternion as `[x, y, z, w]`
+    pub rotation: [f64; 4],
+}
+
+impl From<&BodyTransform> for BodyPose {
+    fn from(transform: &BodyTransform) -> Self {
+        let position = transform.position;
+        let rotation = transform.orientation.coords;
+        Self {
+            body_id: transform.id,
+            position: [position.x, position.y, position.z],
+            rotation: [rotation.x, rotation.y, rotation.z, rotation.w],
+        }
+    }
+}
+
+/// Body poses to draw for one display frame
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SceneFrame {
+    /// Frame number, starting at 1
+    pub frame: u64,
+    /// Simulation time shown by the frame (s)
+    pub time: f64,
+    /// Fraction of a physics step the poses are blended by
+    pub alpha: f64,
+    /// Physics steps taken while producing this frame
+    pub steps: u32,
+    /// Blended body poses
+    pub bodies: Vec<BodyPose>,
+}
+
+impl From<InterpolatedState> for SceneFrame {
+    fn from(state: InterpolatedState) -> Self {
+        Self {
+            frame: 0,
+            time: state.time,
+            alpha: state.alpha,
+            steps: 0,
+            bodies: state.bodies.iter().map(BodyPose::from).collect(),
+        }
+    }
+}
+
+/// Renders a physics world at display rate
+pub struct SceneRenderer {
+    world: PhysicsWorld,
+    dt: f64,
+    max_steps_per_frame: u32,
+    accumulator: f64,
+    frame: u64,
+}
+
+impl SceneRenderer {
+    pub fn new(world: PhysicsWorld) -> Self {
+        Self {
+            world,
+            dt: DEFAULT_PHYSICS_DT,
+            max_steps_per_frame: DEFAULT_MAX_STEPS_PER_FRAME,
+            accumulator: 0.0,
+            frame: 0,
+        }
+    }
+
+    /// Set the fixed physics time step
+    pub fn with_physics_dt(mut self, dt: f64) -> Result<Self> {
+        if !(dt.is_finite() && dt > 0.0) {
+            return Err(VisualizationError::InvalidParameter {
+                parameter: "dt".to_string(),
+                value: dt.to_string(),
+            });
+        }
+        self.dt = dt;
+        Ok(self)
+    }
+
+    /// Limit the physics steps taken per frame so a slow frame cannot snowball
+    pub fn with_max_steps_per_frame(mut self, max_steps: u32) -> Self {
+        self.max_steps_per_frame = max_steps.max(1);
+        self
+    }
+
+    pub fn world(&self) -> &PhysicsWorld {
+        &self.world
+    }
+
+    pub fn world_mut(&mut self) -> &mut PhysicsWorld {
+        &mut self.world
+    }
+
+    pub fn physics_dt(&self) -> f64 {
+        self.dt
+    }
+
+    /// Advance by the time elapsed since the last frame and build the next frame
+    ///
+    /// If more than `max_steps_per_frame` steps are due, the excess time is
+    /// dropped and the simulation runs slower than real time until it
+    /// catches up.
+    pub fn advance(&mut self, frame_time: f64) -> Result<SceneFrame> {
+        if !(frame_time.is_finite() && frame_time >= 0.0) {
+            return Err(VisualizationError::InvalidParameter {
+                parameter: "frame_time".to_string(),
+                value: frame_time.to_string(),
+            });
+        }
+
+        self.accumulator += frame_time;
+        let mut steps = 0;
+        while self.accumulator >= self.dt && steps < self.max_steps_per_frame {
+            self.world
+                .step(self.dt)
+                .map_err(|e| VisualizationError::SimulationError(e.to_string()))?;
+            self.accumulator -= self.dt;
+            steps += 1;
+        }
+        if self.accumulator >= self.dt {
+            let dropped = self.accumulator - self.accumulator % self.dt;
+            tracing::debug!(
+                "Dropping {:.4}s of simulation time after {} physics steps",
+                dropped,
+                steps
+            );
+            self.accumulator -= dropped;
+        }
+
+        self.frame += 1;
+        Ok(SceneFrame {
+            steps,
+            ..self.render()
+        })
+    }
+
+    /// Build a frame at the current blend factor without stepping physics
+    pub fn render(&self) -> SceneFrame {
+        SceneFrame {
+            frame: self.frame,
+            ..self.world.interpolated_state(self.accumulator / self.dt).into()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use accuscene_physics_v3::collision::CollisionShape;
+    use accuscene_physics_v3::config::PhysicsConfig;
+    use accuscene_physics_v3::rigid_body::{MassProperties, RigidBody};
+    use approx::assert_relative_eq;
+
+    /// Renderer for a single body moving along x at 1 m/s without gravity
+    fn renderer(dt: f64) -> SceneRenderer {
+        let mut world = PhysicsWorld::new(PhysicsConfig::default());
+        world.set_gravity(world.gravity() * 0.0);
+
+        let mut body = RigidBody::new(0, MassProperties::from_sphere(1.0, 0.1));
+        body.linear_velocity.x = 1.0;
+        world.add_body(body, CollisionShape::Sphere { radius: 0.1 });
+
+        SceneRenderer::new(world).with_physics_dt(dt).unwrap()
+    }
+
+    #[test]
+    fn test_frames_blend_between_steps() {
+        let mut renderer = renderer(0.01);
+
+        let frame = renderer.advance(0.025).unwrap();
+        assert_eq!(frame.frame, 1);
+        assert_eq!(frame.steps, 2);
+        assert_relative_eq!(frame.alpha, 0.5, epsilon = 1e-9);
+
+        let x = frame.bodies[0].position[0];
+        let current = renderer.world().body(0).unwrap().position.x;
+        assert!(x > current - 0.01 && x < current);
+        assert_relative_eq!(frame.time, 0.015, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_motion_is_smooth_at_display_rate() {
+        // 60 Hz display over a 100 Hz simulation
+        let mut renderer = renderer(0.01);
+        let frames: Vec<SceneFrame> =
+            (0..30).map(|_| renderer.advance(1.0 / 60.0).unwrap()).collect();
+
+        for pair in frames.windows(2) {
+            assert_relative_eq!(pair[1].time - pair[0].time, 1.0 / 60.0, epsilon = 1e-9);
+            let moved = pair[1].bodies[0].position[0] - pair[0].bodies[0].position[0];
+            assert_relative_eq!(moved, 1.0 / 60.0, epsilon = 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_slow_frame_is_capped() {
+        let mut renderer = renderer(0.01).with_max_steps_per_frame(4);
+
+        let frame = renderer.advance(1.0).unwrap();
+        assert_eq!(frame.steps, 4);
+        assert!(frame.alpha < 1.0);
+        assert_relative_eq!(renderer.world().time(), 0.04, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_invalid_times_are_rejected() {
+        let mut renderer = renderer(0.01);
+        assert!(renderer.advance(-0.01).is_err());
+        assert!(renderer.advance(f64::NAN).is_err());
+        assert!(renderer.advance(0.0).is_ok());
+
+        assert!(SceneRenderer::new(PhysicsWorld::new(PhysicsConfig::default()))
+            .with_physics_dt(0.0)
+            .is_err());
+    }
+}
@@ -0,0 +1,53 @@
+/**
+ * Admin Controller
+ * AccuScene Enterprise Accident Recreation Platform
+ */
+
+import { Request, Response, NextFunction } from 'express';
+import { success } from '../responses';
+import { asyncHandler, AppError, ErrorCode } from '../middleware/errorHandler';
+import type { AdminRequest } from '../validators/admin.schemas';
+
+/**
+ * Backend executing administrative requests
+ *
+ * Implemented by the native `AdminService` binding, which authenticates the
+ * bearer token itself and enforces the `users:*` and `organizations:*`
+ * permissions.
+ */
+export interface AdminBackend {
+  handle(token: string, request: AdminRequest): Promise<unknown>;
+}
+
+let backend: AdminBackend | null = null;
+
+/**
+ * Register the backend serving admin requests
+ */
+export function registerAdminBackend(adminBackend: AdminBackend): void {
+  backend = adminBackend;
+}
+
+/**
+ * Dispatch an administrative request
+ * POST /api/admin
+ */
+export const handleAdminRequest = asyncHandler(
+  async (req: Request, res: Response, next: NextFunction) => {
+    if (!backend) {
+      throw new AppError(
+        'User administration is not available',
+        503,
+        ErrorCode.SERVICE_UNAVAILABLE
+      );
+    }
+
+    // authenticate() already checked the header format
+    const token = req.get('Authorization')!.substring(7);
+    const result = await backend.handle(token, req.body as AdminRequest);
+
+    res.status(200).json(
+      success(result ?? null, undefined, (req as any).id)
+    );
+  }
+);
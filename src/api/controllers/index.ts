@@ -23,3 +23,6 @@ export * as evidenceController from './evidence.controller';
 
 // Reports controller
 export * as reportsController from './reports.controller';
+
+// Admin controller
+export * as adminController from './admin.controller';
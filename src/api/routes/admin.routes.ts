@@ -0,0 +1,29 @@
+/**
+ * Admin Routes
+ * AccuScene Enterprise Accident Recreation Platform
+ */
+
+import { Router } from 'express';
+import * as adminController from '../controllers/admin.controller';
+import { validate } from '../middleware/validator';
+import { authenticate, requireAdmin } from '../middleware/auth';
+import { adminRequestSchema } from '../validators/admin.schemas';
+
+const router = Router();
+
+// All routes require an authenticated admin
+router.use(authenticate);
+router.use(requireAdmin);
+
+/**
+ * @route   POST /api/admin
+ * @desc    Dispatch a user or membership administration request
+ * @access  Private (Admin only)
+ */
+router.post(
+  '/',
+  validate(adminRequestSchema),
+  adminController.handleAdminRequest
+);
+
+export default router;
@@ -11,3 +11,4 @@ export { default as accidentsRoutes } from './accidents.routes';
 export { default as vehiclesRoutes } from './vehicles.routes';
 export { default as evidenceRoutes } from './evidence.routes';
 export { default as reportsRoutes } from './reports.routes';
+export { default as adminRoutes } from './admin.routes';
@@ -24,6 +24,7 @@ import accidentsRoutes from './routes/accidents.routes';
 import vehiclesRoutes from './routes/vehicles.routes';
 import evidenceRoutes from './routes/evidence.routes';
 import reportsRoutes from './routes/reports.routes';
+import adminRoutes from './routes/admin.routes';
 
 /**
  * Create and configure Express application
@@ -110,6 +111,7 @@ export function createApp(): Application {
         vehicles: '/api/vehicles',
         evidence: '/api/evidence',
         reports: '/api/reports',
+        admin: '/api/admin',
       },
     });
   });
@@ -125,6 +127,7 @@ export function createApp(): Application {
   app.use('/api/vehicles', vehiclesRoutes);
   app.use('/api/evidence', evidenceRoutes);
   app.use('/api/reports', reportsRoutes);
+  app.use('/api/admin', adminRoutes);
 
   // ==========================================
   // Static Files (for uploads and exports)
@@ -0,0 +1,59 @@
+/**
+ * Admin Validation Schemas
+ * AccuScene Enterprise Accident Recreation Platform
+ */
+
+import { z } from 'zod';
+import { emailSchema } from '../middleware/validator';
+
+const userIdSchema = z.string().min(1);
+
+/**
+ * Scope string, e.g. `org:<id>` or `team:<id>`
+ */
+const scopeSchema = z.string().regex(/^(org|team|workspace):.+$/, 'Invalid scope');
+
+/**
+ * Administrative request, mirroring `AdminRequest` in accuscene-integration
+ */
+export const adminRequestSchema = z.discriminatedUnion('action', [
+  z.object({
+    action: z.literal('list_users'),
+    active_only: z.boolean().default(false),
+  }),
+  z.object({
+    action: z.literal('create_user'),
+    email: emailSchema,
+    username: z.string().min(1).max(100),
+    full_name: z.string().min(1).max(200),
+    password: z.string().min(8, 'Password must be at least 8 characters'),
+    role: z.string().min(1),
+    organization: z.string().optional(),
+    phone: z.string().optional(),
+  }),
+  z.object({ action: z.literal('disable_user'), user_id: userIdSchema }),
+  z.object({ action: z.literal('enable_user'), user_id: userIdSchema }),
+  z.object({
+    action: z.literal('assign_role'),
+    user_id: userIdSchema,
+    role: z.string().min(1),
+  }),
+  z.object({ action: z.literal('reset_mfa'), user_id: userIdSchema }),
+  z.object({ action: z.literal('list_sessions'), user_id: userIdSchema }),
+  z.object({ action: z.literal('force_password_reset'), user_id: userIdSchema }),
+  z.object({
+    action: z.literal('add_member'),
+    user_id: userIdSchema,
+    scope: scopeSchema,
+    role: z.string().min(1),
+  }),
+  z.object({
+    action: z.literal('remove_member'),
+    user_id: userIdSchema,
+    scope: scopeSchema,
+  }),
+  z.object({ action: z.literal('list_members'), scope: scopeSchema }),
+  z.object({ action: z.literal('user_scopes'), user_id: userIdSchema }),
+]);
+
+export type AdminRequest = z.infer<typeof adminRequestSchema>;
@@ -72,3 +72,6 @@ export {
   ReportType,
   ReportFormat,
 } from './report.schemas';
+
+// Admin schemas
+export { adminRequestSchema } from './admin.schemas';